@@ -0,0 +1,182 @@
+//! # Deprecated: renamed to `llm-sentinel-alerting`
+//!
+//! `sentinel-alerting` only re-exports [`llm_sentinel_alerting`] under its old
+//! name, so builds importing `sentinel_alerting` keep compiling. Its modules
+//! are deprecated and warn where they are used; switch the dependency to
+//! `llm-sentinel-alerting`, or to the `llm-sentinel` facade, and replace
+//! `sentinel_alerting::` with `llm_sentinel_alerting::`. Items at the crate
+//! root, such as traits, are re-exported as they are and do not warn. The shim
+//! will be removed in 0.2.
+
+#![warn(missing_debug_implementations, rust_2018_idioms, unreachable_pub)]
+
+pub use llm_sentinel_alerting::*;
+
+#[deprecated(since = "0.1.0", note = "renamed to `llm_sentinel_alerting::aws`")]
+pub mod aws {
+    pub use llm_sentinel_alerting::aws::*;
+}
+
+#[deprecated(since = "0.1.0", note = "renamed to `llm_sentinel_alerting::bigquery`")]
+pub mod bigquery {
+    pub use llm_sentinel_alerting::bigquery::*;
+}
+
+#[deprecated(
+    since = "0.1.0",
+    note = "renamed to `llm_sentinel_alerting::cloudevents`"
+)]
+pub mod cloudevents {
+    pub use llm_sentinel_alerting::cloudevents::*;
+}
+
+#[deprecated(since = "0.1.0", note = "renamed to `llm_sentinel_alerting::datadog`")]
+pub mod datadog {
+    pub use llm_sentinel_alerting::datadog::*;
+}
+
+#[deprecated(
+    since = "0.1.0",
+    note = "renamed to `llm_sentinel_alerting::deduplication`"
+)]
+pub mod deduplication {
+    pub use llm_sentinel_alerting::deduplication::*;
+}
+
+#[deprecated(since = "0.1.0", note = "renamed to `llm_sentinel_alerting::digest`")]
+pub mod digest {
+    pub use llm_sentinel_alerting::digest::*;
+}
+
+#[deprecated(since = "0.1.0", note = "renamed to `llm_sentinel_alerting::discord`")]
+pub mod discord {
+    pub use llm_sentinel_alerting::discord::*;
+}
+
+#[deprecated(since = "0.1.0", note = "renamed to `llm_sentinel_alerting::dispatch`")]
+pub mod dispatch {
+    pub use llm_sentinel_alerting::dispatch::*;
+}
+
+#[deprecated(since = "0.1.0", note = "renamed to `llm_sentinel_alerting::encoding`")]
+pub mod encoding {
+    pub use llm_sentinel_alerting::encoding::*;
+}
+
+#[deprecated(since = "0.1.0", note = "renamed to `llm_sentinel_alerting::export`")]
+pub mod export {
+    pub use llm_sentinel_alerting::export::*;
+}
+
+#[cfg(feature = "rabbitmq")]
+#[deprecated(since = "0.1.0", note = "renamed to `llm_sentinel_alerting::fanout`")]
+pub mod fanout {
+    pub use llm_sentinel_alerting::fanout::*;
+}
+
+#[deprecated(
+    since = "0.1.0",
+    note = "renamed to `llm_sentinel_alerting::federation`"
+)]
+pub mod federation {
+    pub use llm_sentinel_alerting::federation::*;
+}
+
+#[deprecated(since = "0.1.0", note = "renamed to `llm_sentinel_alerting::holding`")]
+pub mod holding {
+    pub use llm_sentinel_alerting::holding::*;
+}
+
+#[deprecated(since = "0.1.0", note = "renamed to `llm_sentinel_alerting::http`")]
+pub mod http {
+    pub use llm_sentinel_alerting::http::*;
+}
+
+#[deprecated(since = "0.1.0", note = "renamed to `llm_sentinel_alerting::issues`")]
+pub mod issues {
+    pub use llm_sentinel_alerting::issues::*;
+}
+
+#[cfg(feature = "kafka")]
+#[deprecated(since = "0.1.0", note = "renamed to `llm_sentinel_alerting::kafka`")]
+pub mod kafka {
+    pub use llm_sentinel_alerting::kafka::*;
+}
+
+#[deprecated(since = "0.1.0", note = "renamed to `llm_sentinel_alerting::mqtt`")]
+pub mod mqtt {
+    pub use llm_sentinel_alerting::mqtt::*;
+}
+
+#[deprecated(since = "0.1.0", note = "renamed to `llm_sentinel_alerting::payload`")]
+pub mod payload {
+    pub use llm_sentinel_alerting::payload::*;
+}
+
+#[deprecated(since = "0.1.0", note = "renamed to `llm_sentinel_alerting::pubsub`")]
+pub mod pubsub {
+    pub use llm_sentinel_alerting::pubsub::*;
+}
+
+#[cfg(feature = "rabbitmq")]
+#[deprecated(since = "0.1.0", note = "renamed to `llm_sentinel_alerting::rabbitmq`")]
+pub mod rabbitmq {
+    pub use llm_sentinel_alerting::rabbitmq::*;
+}
+
+#[cfg(feature = "test-util")]
+#[deprecated(
+    since = "0.1.0",
+    note = "renamed to `llm_sentinel_alerting::recording`"
+)]
+pub mod recording {
+    pub use llm_sentinel_alerting::recording::*;
+}
+
+#[deprecated(since = "0.1.0", note = "renamed to `llm_sentinel_alerting::registry`")]
+pub mod registry {
+    pub use llm_sentinel_alerting::registry::*;
+}
+
+#[deprecated(
+    since = "0.1.0",
+    note = "renamed to `llm_sentinel_alerting::remediation`"
+)]
+pub mod remediation {
+    pub use llm_sentinel_alerting::remediation::*;
+}
+
+#[deprecated(since = "0.1.0", note = "renamed to `llm_sentinel_alerting::routing`")]
+pub mod routing {
+    pub use llm_sentinel_alerting::routing::*;
+}
+
+#[deprecated(since = "0.1.0", note = "renamed to `llm_sentinel_alerting::splunk`")]
+pub mod splunk {
+    pub use llm_sentinel_alerting::splunk::*;
+}
+
+#[deprecated(since = "0.1.0", note = "renamed to `llm_sentinel_alerting::telegram`")]
+pub mod telegram {
+    pub use llm_sentinel_alerting::telegram::*;
+}
+
+#[deprecated(since = "0.1.0", note = "renamed to `llm_sentinel_alerting::twilio`")]
+pub mod twilio {
+    pub use llm_sentinel_alerting::twilio::*;
+}
+
+#[deprecated(since = "0.1.0", note = "renamed to `llm_sentinel_alerting::vault`")]
+pub mod vault {
+    pub use llm_sentinel_alerting::vault::*;
+}
+
+#[deprecated(since = "0.1.0", note = "renamed to `llm_sentinel_alerting::webhook`")]
+pub mod webhook {
+    pub use llm_sentinel_alerting::webhook::*;
+}
+
+#[deprecated(since = "0.1.0", note = "renamed to `llm_sentinel_alerting::prelude`")]
+pub mod prelude {
+    pub use llm_sentinel_alerting::prelude::*;
+}
@@ -0,0 +1,57 @@
+//! # Deprecated: renamed to `llm-sentinel-api`
+//!
+//! `sentinel-api` only re-exports [`llm_sentinel_api`] under its old name, so
+//! builds importing `sentinel_api` keep compiling. Its modules are deprecated
+//! and warn where they are used; switch the dependency to `llm-sentinel-api`,
+//! or to the `llm-sentinel` facade, and replace `sentinel_api::` with
+//! `llm_sentinel_api::`. Items at the crate root, such as traits, are
+//! re-exported as they are and do not warn. The shim will be removed in 0.2.
+
+#![warn(missing_debug_implementations, rust_2018_idioms, unreachable_pub)]
+
+pub use llm_sentinel_api::*;
+
+#[deprecated(since = "0.1.0", note = "renamed to `llm_sentinel_api::access_log`")]
+pub mod access_log {
+    pub use llm_sentinel_api::access_log::*;
+}
+
+#[deprecated(since = "0.1.0", note = "renamed to `llm_sentinel_api::cache`")]
+pub mod cache {
+    pub use llm_sentinel_api::cache::*;
+}
+
+#[deprecated(since = "0.1.0", note = "renamed to `llm_sentinel_api::handlers`")]
+pub mod handlers {
+    pub use llm_sentinel_api::handlers::*;
+}
+
+#[deprecated(since = "0.1.0", note = "renamed to `llm_sentinel_api::idempotency`")]
+pub mod idempotency {
+    pub use llm_sentinel_api::idempotency::*;
+}
+
+#[deprecated(since = "0.1.0", note = "renamed to `llm_sentinel_api::middleware`")]
+pub mod middleware {
+    pub use llm_sentinel_api::middleware::*;
+}
+
+#[deprecated(since = "0.1.0", note = "renamed to `llm_sentinel_api::routes`")]
+pub mod routes {
+    pub use llm_sentinel_api::routes::*;
+}
+
+#[deprecated(since = "0.1.0", note = "renamed to `llm_sentinel_api::server`")]
+pub mod server {
+    pub use llm_sentinel_api::server::*;
+}
+
+#[deprecated(since = "0.1.0", note = "renamed to `llm_sentinel_api::ui`")]
+pub mod ui {
+    pub use llm_sentinel_api::ui::*;
+}
+
+#[deprecated(since = "0.1.0", note = "renamed to `llm_sentinel_api::prelude`")]
+pub mod prelude {
+    pub use llm_sentinel_api::prelude::*;
+}
@@ -0,0 +1,183 @@
+//! # Deprecated: renamed to `llm-sentinel-core`
+//!
+//! `sentinel-core` only re-exports [`llm_sentinel_core`] under its old name, so
+//! builds importing `sentinel_core` keep compiling. Its modules are deprecated
+//! and warn where they are used; switch the dependency to `llm-sentinel-core`,
+//! or to the `llm-sentinel` facade, and replace `sentinel_core::` with
+//! `llm_sentinel_core::`. Items at the crate root, such as traits, are
+//! re-exported as they are and do not warn. The shim will be removed in 0.2.
+
+#![warn(missing_debug_implementations, rust_2018_idioms, unreachable_pub)]
+
+pub use llm_sentinel_core::*;
+
+/// Renamed to `llm_sentinel_core::Error`
+#[deprecated(since = "0.1.0", note = "renamed to `llm_sentinel_core::Error`")]
+pub type Error = llm_sentinel_core::Error;
+
+/// Renamed to `llm_sentinel_core::Result`
+#[deprecated(since = "0.1.0", note = "renamed to `llm_sentinel_core::Result`")]
+pub type Result<T> = llm_sentinel_core::Result<T>;
+
+#[deprecated(since = "0.1.0", note = "renamed to `llm_sentinel_core::annotation`")]
+pub mod annotation {
+    pub use llm_sentinel_core::annotation::*;
+}
+
+#[deprecated(since = "0.1.0", note = "renamed to `llm_sentinel_core::audit`")]
+pub mod audit {
+    pub use llm_sentinel_core::audit::*;
+}
+
+#[deprecated(since = "0.1.0", note = "renamed to `llm_sentinel_core::blocking`")]
+pub mod blocking {
+    pub use llm_sentinel_core::blocking::*;
+}
+
+#[deprecated(since = "0.1.0", note = "renamed to `llm_sentinel_core::bus`")]
+pub mod bus {
+    pub use llm_sentinel_core::bus::*;
+}
+
+#[deprecated(since = "0.1.0", note = "renamed to `llm_sentinel_core::clock`")]
+pub mod clock {
+    pub use llm_sentinel_core::clock::*;
+}
+
+#[deprecated(since = "0.1.0", note = "renamed to `llm_sentinel_core::config`")]
+pub mod config {
+    pub use llm_sentinel_core::config::*;
+}
+
+#[deprecated(since = "0.1.0", note = "renamed to `llm_sentinel_core::correlation`")]
+pub mod correlation {
+    pub use llm_sentinel_core::correlation::*;
+}
+
+#[deprecated(since = "0.1.0", note = "renamed to `llm_sentinel_core::deadline`")]
+pub mod deadline {
+    pub use llm_sentinel_core::deadline::*;
+}
+
+#[deprecated(since = "0.1.0", note = "renamed to `llm_sentinel_core::error`")]
+pub mod error {
+    pub use llm_sentinel_core::error::*;
+}
+
+#[deprecated(since = "0.1.0", note = "renamed to `llm_sentinel_core::events`")]
+pub mod events {
+    pub use llm_sentinel_core::events::*;
+}
+
+#[deprecated(since = "0.1.0", note = "renamed to `llm_sentinel_core::federation`")]
+pub mod federation {
+    pub use llm_sentinel_core::federation::*;
+}
+
+#[deprecated(since = "0.1.0", note = "renamed to `llm_sentinel_core::identifiers`")]
+pub mod identifiers {
+    pub use llm_sentinel_core::identifiers::*;
+}
+
+#[deprecated(since = "0.1.0", note = "renamed to `llm_sentinel_core::labels`")]
+pub mod labels {
+    pub use llm_sentinel_core::labels::*;
+}
+
+#[deprecated(since = "0.1.0", note = "renamed to `llm_sentinel_core::leader`")]
+pub mod leader {
+    pub use llm_sentinel_core::leader::*;
+}
+
+#[deprecated(since = "0.1.0", note = "renamed to `llm_sentinel_core::lifecycle`")]
+pub mod lifecycle {
+    pub use llm_sentinel_core::lifecycle::*;
+}
+
+#[deprecated(since = "0.1.0", note = "renamed to `llm_sentinel_core::memory`")]
+pub mod memory {
+    pub use llm_sentinel_core::memory::*;
+}
+
+#[deprecated(since = "0.1.0", note = "renamed to `llm_sentinel_core::metrics`")]
+pub mod metrics {
+    pub use llm_sentinel_core::metrics::*;
+}
+
+#[deprecated(since = "0.1.0", note = "renamed to `llm_sentinel_core::pricing`")]
+pub mod pricing {
+    pub use llm_sentinel_core::pricing::*;
+}
+
+#[deprecated(since = "0.1.0", note = "renamed to `llm_sentinel_core::privacy`")]
+pub mod privacy {
+    pub use llm_sentinel_core::privacy::*;
+}
+
+#[deprecated(since = "0.1.0", note = "renamed to `llm_sentinel_core::retry`")]
+pub mod retry {
+    pub use llm_sentinel_core::retry::*;
+}
+
+#[deprecated(since = "0.1.0", note = "renamed to `llm_sentinel_core::secrets`")]
+pub mod secrets {
+    pub use llm_sentinel_core::secrets::*;
+}
+
+#[deprecated(since = "0.1.0", note = "renamed to `llm_sentinel_core::selfcheck`")]
+pub mod selfcheck {
+    pub use llm_sentinel_core::selfcheck::*;
+}
+
+#[deprecated(since = "0.1.0", note = "renamed to `llm_sentinel_core::simulation`")]
+pub mod simulation {
+    pub use llm_sentinel_core::simulation::*;
+}
+
+#[deprecated(since = "0.1.0", note = "renamed to `llm_sentinel_core::snapshot`")]
+pub mod snapshot {
+    pub use llm_sentinel_core::snapshot::*;
+}
+
+#[deprecated(since = "0.1.0", note = "renamed to `llm_sentinel_core::suppression`")]
+pub mod suppression {
+    pub use llm_sentinel_core::suppression::*;
+}
+
+#[deprecated(since = "0.1.0", note = "renamed to `llm_sentinel_core::timing`")]
+pub mod timing {
+    pub use llm_sentinel_core::timing::*;
+}
+
+#[deprecated(since = "0.1.0", note = "renamed to `llm_sentinel_core::types`")]
+pub mod types {
+    pub use llm_sentinel_core::types::*;
+}
+
+#[deprecated(since = "0.1.0", note = "renamed to `llm_sentinel_core::window`")]
+pub mod window {
+    pub use llm_sentinel_core::window::*;
+}
+
+#[deprecated(since = "0.1.0", note = "renamed to `llm_sentinel_core::workers`")]
+pub mod workers {
+    pub use llm_sentinel_core::workers::*;
+}
+
+#[deprecated(since = "0.1.0", note = "renamed to `llm_sentinel_core::prelude`")]
+pub mod prelude {
+    pub use llm_sentinel_core::prelude::*;
+}
+
+#[cfg(test)]
+mod tests {
+    #[test]
+    #[allow(deprecated)]
+    fn test_old_paths_name_renamed_items() {
+        let config: crate::config::Config = llm_sentinel_core::config::Config::default_test();
+        let result: crate::Result<crate::config::Config> = Ok(config);
+        let error: llm_sentinel_core::Error = crate::Error::config("invalid");
+        assert!(result.is_ok());
+        assert!(matches!(error, crate::Error::Config(_)));
+    }
+}
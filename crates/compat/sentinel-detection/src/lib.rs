@@ -0,0 +1,99 @@
+//! # Deprecated: renamed to `llm-sentinel-detection`
+//!
+//! `sentinel-detection` only re-exports [`llm_sentinel_detection`] under its
+//! old name, so builds importing `sentinel_detection` keep compiling. Its
+//! modules are deprecated and warn where they are used; switch the dependency
+//! to `llm-sentinel-detection`, or to the `llm-sentinel` facade, and replace
+//! `sentinel_detection::` with `llm_sentinel_detection::`. Items at the crate
+//! root, such as traits, are re-exported as they are and do not warn. The shim
+//! will be removed in 0.2.
+
+#![warn(missing_debug_implementations, rust_2018_idioms, unreachable_pub)]
+
+pub use llm_sentinel_detection::*;
+
+#[deprecated(
+    since = "0.1.0",
+    note = "renamed to `llm_sentinel_detection::availability`"
+)]
+pub mod availability {
+    pub use llm_sentinel_detection::availability::*;
+}
+
+#[deprecated(
+    since = "0.1.0",
+    note = "renamed to `llm_sentinel_detection::baseline`"
+)]
+pub mod baseline {
+    pub use llm_sentinel_detection::baseline::*;
+}
+
+#[deprecated(
+    since = "0.1.0",
+    note = "renamed to `llm_sentinel_detection::detectors`"
+)]
+pub mod detectors {
+    pub use llm_sentinel_detection::detectors::*;
+}
+
+#[deprecated(since = "0.1.0", note = "renamed to `llm_sentinel_detection::engine`")]
+pub mod engine {
+    pub use llm_sentinel_detection::engine::*;
+}
+
+#[deprecated(since = "0.1.0", note = "renamed to `llm_sentinel_detection::quality`")]
+pub mod quality {
+    pub use llm_sentinel_detection::quality::*;
+}
+
+#[deprecated(
+    since = "0.1.0",
+    note = "renamed to `llm_sentinel_detection::recovery`"
+)]
+pub mod recovery {
+    pub use llm_sentinel_detection::recovery::*;
+}
+
+#[deprecated(
+    since = "0.1.0",
+    note = "renamed to `llm_sentinel_detection::redetect`"
+)]
+pub mod redetect {
+    pub use llm_sentinel_detection::redetect::*;
+}
+
+#[deprecated(
+    since = "0.1.0",
+    note = "renamed to `llm_sentinel_detection::shedding`"
+)]
+pub mod shedding {
+    pub use llm_sentinel_detection::shedding::*;
+}
+
+#[deprecated(since = "0.1.0", note = "renamed to `llm_sentinel_detection::silence`")]
+pub mod silence {
+    pub use llm_sentinel_detection::silence::*;
+}
+
+#[deprecated(since = "0.1.0", note = "renamed to `llm_sentinel_detection::slo`")]
+pub mod slo {
+    pub use llm_sentinel_detection::slo::*;
+}
+
+#[deprecated(since = "0.1.0", note = "renamed to `llm_sentinel_detection::stats`")]
+pub mod stats {
+    pub use llm_sentinel_detection::stats::*;
+}
+
+#[deprecated(
+    since = "0.1.0",
+    note = "renamed to `llm_sentinel_detection::versions`"
+)]
+pub mod versions {
+    pub use llm_sentinel_detection::versions::*;
+}
+
+#[deprecated(since = "0.1.0", note = "renamed to `llm_sentinel_detection::prelude`")]
+pub mod prelude {
+    pub use llm_sentinel_detection::prelude::*;
+}
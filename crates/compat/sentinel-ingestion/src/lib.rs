@@ -0,0 +1,92 @@
+//! # Deprecated: renamed to `llm-sentinel-ingestion`
+//!
+//! `sentinel-ingestion` only re-exports [`llm_sentinel_ingestion`] under its
+//! old name, so builds importing `sentinel_ingestion` keep compiling. Its
+//! modules are deprecated and warn where they are used; switch the dependency
+//! to `llm-sentinel-ingestion`, or to the `llm-sentinel` facade, and replace
+//! `sentinel_ingestion::` with `llm_sentinel_ingestion::`. Items at the crate
+//! root, such as traits, are re-exported as they are and do not warn. The shim
+//! will be removed in 0.2.
+
+#![warn(missing_debug_implementations, rust_2018_idioms, unreachable_pub)]
+
+pub use llm_sentinel_ingestion::*;
+
+#[deprecated(
+    since = "0.1.0",
+    note = "renamed to `llm_sentinel_ingestion::backpressure`"
+)]
+pub mod backpressure {
+    pub use llm_sentinel_ingestion::backpressure::*;
+}
+
+#[deprecated(
+    since = "0.1.0",
+    note = "renamed to `llm_sentinel_ingestion::embedding`"
+)]
+pub mod embedding {
+    pub use llm_sentinel_ingestion::embedding::*;
+}
+
+#[cfg(feature = "grpc")]
+#[deprecated(since = "0.1.0", note = "renamed to `llm_sentinel_ingestion::grpc`")]
+pub mod grpc {
+    pub use llm_sentinel_ingestion::grpc::*;
+}
+
+#[cfg(feature = "kafka")]
+#[deprecated(since = "0.1.0", note = "renamed to `llm_sentinel_ingestion::kafka`")]
+pub mod kafka {
+    pub use llm_sentinel_ingestion::kafka::*;
+}
+
+#[deprecated(since = "0.1.0", note = "renamed to `llm_sentinel_ingestion::otlp`")]
+pub mod otlp {
+    pub use llm_sentinel_ingestion::otlp::*;
+}
+
+#[deprecated(
+    since = "0.1.0",
+    note = "renamed to `llm_sentinel_ingestion::pipeline`"
+)]
+pub mod pipeline {
+    pub use llm_sentinel_ingestion::pipeline::*;
+}
+
+#[deprecated(since = "0.1.0", note = "renamed to `llm_sentinel_ingestion::pubsub`")]
+pub mod pubsub {
+    pub use llm_sentinel_ingestion::pubsub::*;
+}
+
+#[deprecated(since = "0.1.0", note = "renamed to `llm_sentinel_ingestion::quality`")]
+pub mod quality {
+    pub use llm_sentinel_ingestion::quality::*;
+}
+
+#[deprecated(since = "0.1.0", note = "renamed to `llm_sentinel_ingestion::quota`")]
+pub mod quota {
+    pub use llm_sentinel_ingestion::quota::*;
+}
+
+#[deprecated(since = "0.1.0", note = "renamed to `llm_sentinel_ingestion::replay`")]
+pub mod replay {
+    pub use llm_sentinel_ingestion::replay::*;
+}
+
+#[deprecated(since = "0.1.0", note = "renamed to `llm_sentinel_ingestion::skew`")]
+pub mod skew {
+    pub use llm_sentinel_ingestion::skew::*;
+}
+
+#[deprecated(
+    since = "0.1.0",
+    note = "renamed to `llm_sentinel_ingestion::validation`"
+)]
+pub mod validation {
+    pub use llm_sentinel_ingestion::validation::*;
+}
+
+#[deprecated(since = "0.1.0", note = "renamed to `llm_sentinel_ingestion::prelude`")]
+pub mod prelude {
+    pub use llm_sentinel_ingestion::prelude::*;
+}
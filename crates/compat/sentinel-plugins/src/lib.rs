@@ -0,0 +1,28 @@
+//! # Deprecated: renamed to `llm-sentinel-plugins`
+//!
+//! `sentinel-plugins` only re-exports [`llm_sentinel_plugins`] under its old
+//! name, so builds importing `sentinel_plugins` keep compiling. Its modules are
+//! deprecated and warn where they are used; switch the dependency to
+//! `llm-sentinel-plugins`, or to the `llm-sentinel` facade, and replace
+//! `sentinel_plugins::` with `llm_sentinel_plugins::`. Items at the crate root,
+//! such as traits, are re-exported as they are and do not warn. The shim will
+//! be removed in 0.2.
+
+#![warn(missing_debug_implementations, rust_2018_idioms, unreachable_pub)]
+
+pub use llm_sentinel_plugins::*;
+
+#[deprecated(since = "0.1.0", note = "renamed to `llm_sentinel_plugins::script`")]
+pub mod script {
+    pub use llm_sentinel_plugins::script::*;
+}
+
+#[deprecated(since = "0.1.0", note = "renamed to `llm_sentinel_plugins::wasm`")]
+pub mod wasm {
+    pub use llm_sentinel_plugins::wasm::*;
+}
+
+#[deprecated(since = "0.1.0", note = "renamed to `llm_sentinel_plugins::prelude`")]
+pub mod prelude {
+    pub use llm_sentinel_plugins::prelude::*;
+}
@@ -0,0 +1,159 @@
+//! # Deprecated: renamed to `llm-sentinel-storage`
+//!
+//! `sentinel-storage` only re-exports [`llm_sentinel_storage`] under its old
+//! name, so builds importing `sentinel_storage` keep compiling. Its modules are
+//! deprecated and warn where they are used; switch the dependency to
+//! `llm-sentinel-storage`, or to the `llm-sentinel` facade, and replace
+//! `sentinel_storage::` with `llm_sentinel_storage::`. Items at the crate root,
+//! such as traits, are re-exported as they are and do not warn. The shim will
+//! be removed in 0.2.
+
+#![warn(missing_debug_implementations, rust_2018_idioms, unreachable_pub)]
+
+pub use llm_sentinel_storage::*;
+
+#[deprecated(
+    since = "0.1.0",
+    note = "renamed to `llm_sentinel_storage::annotation`"
+)]
+pub mod annotation {
+    pub use llm_sentinel_storage::annotation::*;
+}
+
+#[deprecated(since = "0.1.0", note = "renamed to `llm_sentinel_storage::budget`")]
+pub mod budget {
+    pub use llm_sentinel_storage::budget::*;
+}
+
+#[deprecated(since = "0.1.0", note = "renamed to `llm_sentinel_storage::cache`")]
+pub mod cache {
+    pub use llm_sentinel_storage::cache::*;
+}
+
+#[deprecated(
+    since = "0.1.0",
+    note = "renamed to `llm_sentinel_storage::cardinality`"
+)]
+pub mod cardinality {
+    pub use llm_sentinel_storage::cardinality::*;
+}
+
+#[deprecated(since = "0.1.0", note = "renamed to `llm_sentinel_storage::context`")]
+pub mod context {
+    pub use llm_sentinel_storage::context::*;
+}
+
+#[deprecated(since = "0.1.0", note = "renamed to `llm_sentinel_storage::cost`")]
+pub mod cost {
+    pub use llm_sentinel_storage::cost::*;
+}
+
+#[deprecated(since = "0.1.0", note = "renamed to `llm_sentinel_storage::delivery`")]
+pub mod delivery {
+    pub use llm_sentinel_storage::delivery::*;
+}
+
+#[deprecated(since = "0.1.0", note = "renamed to `llm_sentinel_storage::digest`")]
+pub mod digest {
+    pub use llm_sentinel_storage::digest::*;
+}
+
+#[deprecated(since = "0.1.0", note = "renamed to `llm_sentinel_storage::fleet`")]
+pub mod fleet {
+    pub use llm_sentinel_storage::fleet::*;
+}
+
+#[deprecated(
+    since = "0.1.0",
+    note = "renamed to `llm_sentinel_storage::idempotency`"
+)]
+pub mod idempotency {
+    pub use llm_sentinel_storage::idempotency::*;
+}
+
+#[cfg(feature = "influxdb")]
+#[deprecated(since = "0.1.0", note = "renamed to `llm_sentinel_storage::influxdb`")]
+pub mod influxdb {
+    pub use llm_sentinel_storage::influxdb::*;
+}
+
+#[deprecated(
+    since = "0.1.0",
+    note = "renamed to `llm_sentinel_storage::influxdb_v1`"
+)]
+pub mod influxdb_v1 {
+    pub use llm_sentinel_storage::influxdb_v1::*;
+}
+
+#[cfg(feature = "redis")]
+#[deprecated(since = "0.1.0", note = "renamed to `llm_sentinel_storage::leader`")]
+pub mod leader {
+    pub use llm_sentinel_storage::leader::*;
+}
+
+#[deprecated(since = "0.1.0", note = "renamed to `llm_sentinel_storage::lifecycle`")]
+pub mod lifecycle {
+    pub use llm_sentinel_storage::lifecycle::*;
+}
+
+#[cfg(feature = "test-util")]
+#[deprecated(since = "0.1.0", note = "renamed to `llm_sentinel_storage::memory`")]
+pub mod memory {
+    pub use llm_sentinel_storage::memory::*;
+}
+
+#[deprecated(since = "0.1.0", note = "renamed to `llm_sentinel_storage::noise`")]
+pub mod noise {
+    pub use llm_sentinel_storage::noise::*;
+}
+
+#[deprecated(since = "0.1.0", note = "renamed to `llm_sentinel_storage::outbox`")]
+pub mod outbox {
+    pub use llm_sentinel_storage::outbox::*;
+}
+
+#[deprecated(
+    since = "0.1.0",
+    note = "renamed to `llm_sentinel_storage::prometheus`"
+)]
+pub mod prometheus {
+    pub use llm_sentinel_storage::prometheus::*;
+}
+
+#[deprecated(since = "0.1.0", note = "renamed to `llm_sentinel_storage::query`")]
+pub mod query {
+    pub use llm_sentinel_storage::query::*;
+}
+
+#[deprecated(since = "0.1.0", note = "renamed to `llm_sentinel_storage::search`")]
+pub mod search {
+    pub use llm_sentinel_storage::search::*;
+}
+
+#[deprecated(
+    since = "0.1.0",
+    note = "renamed to `llm_sentinel_storage::similarity`"
+)]
+pub mod similarity {
+    pub use llm_sentinel_storage::similarity::*;
+}
+
+#[deprecated(since = "0.1.0", note = "renamed to `llm_sentinel_storage::snapshot`")]
+pub mod snapshot {
+    pub use llm_sentinel_storage::snapshot::*;
+}
+
+#[deprecated(since = "0.1.0", note = "renamed to `llm_sentinel_storage::standby`")]
+pub mod standby {
+    pub use llm_sentinel_storage::standby::*;
+}
+
+#[deprecated(since = "0.1.0", note = "renamed to `llm_sentinel_storage::stream`")]
+pub mod stream {
+    pub use llm_sentinel_storage::stream::*;
+}
+
+#[deprecated(since = "0.1.0", note = "renamed to `llm_sentinel_storage::prelude`")]
+pub mod prelude {
+    pub use llm_sentinel_storage::prelude::*;
+}
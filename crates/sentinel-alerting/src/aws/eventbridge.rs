@@ -0,0 +1,422 @@
+//! AWS EventBridge alert publisher.
+//!
+//! Anomalies are put on an event bus with a detail-type derived from the
+//! anomaly type and a structured detail carrying severity, service and model
+//! at the top level so EventBridge rules can match on them directly.
+
+use super::{resolve_endpoint, sigv4, AwsCredentials};
+use crate::{http::HttpClientConfig, Alerter};
+use async_trait::async_trait;
+use llm_sentinel_core::{events::AnomalyEvent, Error, Result};
+use reqwest::Client;
+use serde::{Deserialize, Serialize};
+use std::time::Duration;
+use tracing::{debug, error, info};
+
+/// Maximum number of entries accepted by a single PutEvents call
+const MAX_ENTRIES_PER_REQUEST: usize = 10;
+
+/// EventBridge alerter configuration
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct EventBridgeConfig {
+    /// AWS region
+    pub region: String,
+    /// Event bus name or ARN
+    pub event_bus_name: String,
+    /// Event source
+    pub source: String,
+    /// Detail-type template; supports `{severity}` and `{anomaly_type}`
+    pub detail_type_template: String,
+    /// Resource ARNs attached to every event
+    #[serde(default)]
+    pub resources: Vec<String>,
+    /// Endpoint override (e.g. LocalStack)
+    #[serde(default)]
+    pub endpoint: Option<String>,
+    /// Static credentials (falls back to `AWS_*` environment variables)
+    #[serde(default)]
+    pub credentials: Option<AwsCredentials>,
+    /// Request timeout (seconds)
+    pub timeout_secs: u64,
+    /// Proxy, CA and DNS settings
+    #[serde(default)]
+    pub http: HttpClientConfig,
+}
+
+impl Default for EventBridgeConfig {
+    fn default() -> Self {
+        Self {
+            region: "us-east-1".to_string(),
+            event_bus_name: "default".to_string(),
+            source: "llm.sentinel".to_string(),
+            detail_type_template: "LLM Anomaly {anomaly_type}".to_string(),
+            resources: Vec::new(),
+            endpoint: None,
+            credentials: None,
+            timeout_secs: 10,
+            http: HttpClientConfig::default(),
+        }
+    }
+}
+
+/// Structured event detail
+#[derive(Debug, Clone, Serialize)]
+struct EventDetail<'a> {
+    alert_id: String,
+    severity: String,
+    anomaly_type: String,
+    service: &'a str,
+    model: &'a str,
+    confidence: f64,
+    anomaly: &'a AnomalyEvent,
+}
+
+/// PutEvents request entry
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "PascalCase")]
+struct PutEventsEntry {
+    source: String,
+    detail_type: String,
+    detail: String,
+    event_bus_name: String,
+    resources: Vec<String>,
+    time: i64,
+}
+
+/// PutEvents response
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "PascalCase")]
+struct PutEventsResponse {
+    #[serde(default)]
+    failed_entry_count: usize,
+    #[serde(default)]
+    entries: Vec<PutEventsResultEntry>,
+}
+
+/// PutEvents per-entry result
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "PascalCase")]
+struct PutEventsResultEntry {
+    #[serde(default)]
+    error_code: Option<String>,
+    #[serde(default)]
+    error_message: Option<String>,
+}
+
+/// EventBridge alert publisher
+pub struct EventBridgeAlerter {
+    client: Client,
+    credentials: AwsCredentials,
+    url: String,
+    host: String,
+    config: EventBridgeConfig,
+}
+
+impl std::fmt::Debug for EventBridgeAlerter {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("EventBridgeAlerter")
+            .field("config", &self.config)
+            .field("url", &self.url)
+            .finish()
+    }
+}
+
+impl EventBridgeAlerter {
+    /// Create a new EventBridge alerter
+    pub fn new(config: EventBridgeConfig) -> Result<Self> {
+        if config.event_bus_name.is_empty() {
+            return Err(Error::config("EventBridge bus name cannot be empty"));
+        }
+        if config.source.is_empty() {
+            return Err(Error::config("EventBridge source cannot be empty"));
+        }
+
+        let credentials = AwsCredentials::resolve(config.credentials.as_ref())?;
+        let (url, host) = resolve_endpoint(config.endpoint.as_deref(), "events", &config.region)?;
+
+        info!(
+            "Creating EventBridge alerter for bus {}",
+            config.event_bus_name
+        );
+
+        let client = config
+            .http
+            .builder()?
+            .timeout(Duration::from_secs(config.timeout_secs))
+            .build()
+            .map_err(|e| Error::config(format!("Failed to create HTTP client: {}", e)))?;
+
+        Ok(Self {
+            client,
+            credentials,
+            url,
+            host,
+            config,
+        })
+    }
+
+    /// Render the detail-type for an alert
+    fn build_detail_type(&self, alert: &AnomalyEvent) -> String {
+        self.config
+            .detail_type_template
+            .replace("{severity}", &alert.severity.to_string())
+            .replace("{anomaly_type}", &alert.anomaly_type.to_string())
+    }
+
+    /// Build a PutEvents entry for an alert
+    fn build_entry(&self, alert: &AnomalyEvent) -> Result<PutEventsEntry> {
+        let detail = EventDetail {
+            alert_id: alert.alert_id.to_string(),
+            severity: alert.severity.to_string(),
+            anomaly_type: alert.anomaly_type.to_string(),
+            service: alert.service_name.as_str(),
+            model: alert.model.as_str(),
+            confidence: alert.confidence,
+            anomaly: alert,
+        };
+
+        Ok(PutEventsEntry {
+            source: self.config.source.clone(),
+            detail_type: self.build_detail_type(alert),
+            detail: serde_json::to_string(&detail)
+                .map_err(|e| Error::internal(format!("Failed to serialize alert: {}", e)))?,
+            event_bus_name: self.config.event_bus_name.clone(),
+            resources: self.config.resources.clone(),
+            time: alert.timestamp.timestamp(),
+        })
+    }
+
+    /// Put a chunk of alerts (at most 10) on the event bus
+    async fn put_events(&self, alerts: &[AnomalyEvent]) -> Result<()> {
+        let entries = alerts
+            .iter()
+            .map(|alert| self.build_entry(alert))
+            .collect::<Result<Vec<_>>>()?;
+        let body = serde_json::to_vec(&serde_json::json!({ "Entries": entries }))
+            .map_err(|e| Error::internal(format!("Failed to serialize PutEvents request: {}", e)))?;
+
+        let headers = sigv4::sign_post(
+            &self.credentials,
+            &self.config.region,
+            "events",
+            &self.host,
+            &[
+                ("Content-Type", "application/x-amz-json-1.1"),
+                ("X-Amz-Target", "AWSEvents.PutEvents"),
+            ],
+            &body,
+            chrono::Utc::now(),
+        );
+
+        let mut request = self.client.post(&self.url);
+        for (key, value) in &headers {
+            request = request.header(key, value);
+        }
+
+        let response = request.body(body).send().await.map_err(|e| {
+            metrics::counter!("sentinel_eventbridge_failures_total").increment(alerts.len() as u64);
+            Error::alerting(format!("EventBridge PutEvents request failed: {}", e))
+        })?;
+
+        let status = response.status();
+        if !status.is_success() {
+            let body = response.text().await.unwrap_or_default();
+            error!(status = %status, body = %body, "EventBridge PutEvents failed");
+            metrics::counter!("sentinel_eventbridge_failures_total").increment(alerts.len() as u64);
+            return Err(Error::alerting(format!(
+                "EventBridge PutEvents failed with status {}: {}",
+                status, body
+            )));
+        }
+
+        let result: PutEventsResponse = response.json().await.map_err(|e| {
+            Error::alerting(format!("Invalid EventBridge PutEvents response: {}", e))
+        })?;
+
+        if result.failed_entry_count > 0 {
+            let reasons: Vec<String> = result
+                .entries
+                .iter()
+                .filter_map(|entry| {
+                    entry.error_code.as_ref().map(|code| {
+                        format!("{}: {}", code, entry.error_message.as_deref().unwrap_or(""))
+                    })
+                })
+                .collect();
+
+            error!(
+                failed = result.failed_entry_count,
+                reasons = ?reasons,
+                "EventBridge rejected entries"
+            );
+            metrics::counter!("sentinel_eventbridge_failures_total")
+                .increment(result.failed_entry_count as u64);
+
+            return Err(Error::alerting(format!(
+                "EventBridge rejected {} out of {} entries: {}",
+                result.failed_entry_count,
+                alerts.len(),
+                reasons.join(", ")
+            )));
+        }
+
+        for alert in alerts {
+            debug!(
+                alert_id = %alert.alert_id,
+                event_bus = %self.config.event_bus_name,
+                "Alert put on EventBridge"
+            );
+            metrics::counter!(
+                "sentinel_eventbridge_publishes_total",
+                "severity" => alert.severity.to_string()
+            )
+            .increment(1);
+        }
+
+        Ok(())
+    }
+}
+
+#[async_trait]
+impl Alerter for EventBridgeAlerter {
+    async fn send(&self, alert: &AnomalyEvent) -> Result<()> {
+        self.put_events(std::slice::from_ref(alert)).await
+    }
+
+    async fn send_batch(&self, alerts: &[AnomalyEvent]) -> Result<()> {
+        for chunk in alerts.chunks(MAX_ENTRIES_PER_REQUEST) {
+            self.put_events(chunk).await?;
+        }
+        Ok(())
+    }
+
+    async fn health_check(&self) -> Result<()> {
+        // Credentials and endpoint are validated at construction; PutEvents has no cheap ping
+        Ok(())
+    }
+
+    fn name(&self) -> &str {
+        "EventBridge"
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use llm_sentinel_core::types::{AnomalyType, DetectionMethod, Severity};
+    use llm_sentinel_fixtures::anomaly;
+
+    use wiremock::{
+        matchers::{header, method},
+        Mock, MockServer, ResponseTemplate,
+    };
+
+    fn create_test_config(endpoint: Option<String>) -> EventBridgeConfig {
+        EventBridgeConfig {
+            endpoint,
+            credentials: Some(AwsCredentials {
+                access_key_id: "AKIDEXAMPLE".to_string(),
+                secret_access_key: "secret".to_string(),
+                session_token: None,
+            }),
+            ..Default::default()
+        }
+    }
+
+    fn create_test_anomaly(severity: Severity) -> AnomalyEvent {
+        anomaly()
+            .severity(severity)
+            .anomaly_type(AnomalyType::CostAnomaly)
+            .service("checkout")
+            .method(DetectionMethod::Cusum)
+            .metric("cost_usd")
+            .value(5.0)
+            .baseline(0.5)
+            .threshold(2.0)
+            .deviation_sigma(None)
+            .time_window("rolling_window")
+            .build()
+    }
+
+    #[test]
+    fn test_detail_type_template() {
+        let alerter = EventBridgeAlerter::new(EventBridgeConfig {
+            detail_type_template: "Sentinel {severity} {anomaly_type}".to_string(),
+            ..create_test_config(None)
+        })
+        .unwrap();
+
+        let alert = create_test_anomaly(Severity::Critical);
+        assert_eq!(
+            alerter.build_detail_type(&alert),
+            "Sentinel critical cost_anomaly"
+        );
+    }
+
+    #[test]
+    fn test_entry_detail_is_structured() {
+        let alerter = EventBridgeAlerter::new(create_test_config(None)).unwrap();
+        let entry = alerter.build_entry(&create_test_anomaly(Severity::High)).unwrap();
+
+        assert_eq!(entry.source, "llm.sentinel");
+        assert_eq!(entry.detail_type, "LLM Anomaly cost_anomaly");
+
+        let detail: serde_json::Value = serde_json::from_str(&entry.detail).unwrap();
+        assert_eq!(detail["severity"], "high");
+        assert_eq!(detail["service"], "checkout");
+        assert_eq!(detail["anomaly"]["details"]["metric"], "cost_usd");
+    }
+
+    #[tokio::test]
+    async fn test_put_events_success() {
+        let mock_server = MockServer::start().await;
+
+        Mock::given(method("POST"))
+            .and(header("X-Amz-Target", "AWSEvents.PutEvents"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({
+                "FailedEntryCount": 0,
+                "Entries": [{ "EventId": "1" }]
+            })))
+            .expect(1)
+            .mount(&mock_server)
+            .await;
+
+        let alerter = EventBridgeAlerter::new(create_test_config(Some(mock_server.uri()))).unwrap();
+        assert!(alerter.send(&create_test_anomaly(Severity::High)).await.is_ok());
+    }
+
+    #[tokio::test]
+    async fn test_put_events_partial_failure() {
+        let mock_server = MockServer::start().await;
+
+        Mock::given(method("POST"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({
+                "FailedEntryCount": 1,
+                "Entries": [{ "ErrorCode": "InternalFailure", "ErrorMessage": "boom" }]
+            })))
+            .mount(&mock_server)
+            .await;
+
+        let alerter = EventBridgeAlerter::new(create_test_config(Some(mock_server.uri()))).unwrap();
+        let result = alerter.send(&create_test_anomaly(Severity::High)).await;
+        assert!(result.is_err());
+    }
+
+    #[tokio::test]
+    async fn test_batch_is_chunked() {
+        let mock_server = MockServer::start().await;
+
+        Mock::given(method("POST"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({
+                "FailedEntryCount": 0,
+                "Entries": []
+            })))
+            .expect(2)
+            .mount(&mock_server)
+            .await;
+
+        let alerter = EventBridgeAlerter::new(create_test_config(Some(mock_server.uri()))).unwrap();
+        let alerts: Vec<_> = (0..15).map(|_| create_test_anomaly(Severity::Low)).collect();
+        assert!(alerter.send_batch(&alerts).await.is_ok());
+    }
+}
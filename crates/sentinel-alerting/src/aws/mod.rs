@@ -0,0 +1,116 @@
+//! AWS alert publishers for serverless fan-out, the Timestream export sink
+//! and the Secrets Manager secret backend.
+//!
+//! Requests are signed with SigV4 directly so the alerting crate does not
+//! have to pull in the full AWS SDK.
+
+pub mod eventbridge;
+pub mod secrets_manager;
+mod sigv4;
+pub mod sns;
+pub mod timestream;
+
+pub use eventbridge::{EventBridgeAlerter, EventBridgeConfig};
+pub use secrets_manager::SecretsManagerSecrets;
+pub use sns::{SnsAlerter, SnsConfig};
+pub use timestream::TimestreamSink;
+
+use llm_sentinel_core::{Error, Result};
+use serde::{Deserialize, Serialize};
+
+/// Static AWS credentials
+#[derive(Clone, Serialize, Deserialize)]
+pub struct AwsCredentials {
+    /// Access key ID
+    pub access_key_id: String,
+    /// Secret access key
+    pub secret_access_key: String,
+    /// Session token for temporary credentials
+    #[serde(default)]
+    pub session_token: Option<String>,
+}
+
+impl std::fmt::Debug for AwsCredentials {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("AwsCredentials")
+            .field("access_key_id", &self.access_key_id)
+            .field("secret_access_key", &"***")
+            .field("session_token", &self.session_token.as_ref().map(|_| "***"))
+            .finish()
+    }
+}
+
+impl AwsCredentials {
+    /// Load credentials from the standard `AWS_*` environment variables
+    pub fn from_env() -> Result<Self> {
+        let access_key_id = std::env::var("AWS_ACCESS_KEY_ID")
+            .map_err(|_| Error::config("AWS_ACCESS_KEY_ID is not set"))?;
+        let secret_access_key = std::env::var("AWS_SECRET_ACCESS_KEY")
+            .map_err(|_| Error::config("AWS_SECRET_ACCESS_KEY is not set"))?;
+
+        Ok(Self {
+            access_key_id,
+            secret_access_key,
+            session_token: std::env::var("AWS_SESSION_TOKEN").ok(),
+        })
+    }
+
+    /// Use configured credentials, falling back to the environment
+    fn resolve(configured: Option<&AwsCredentials>) -> Result<Self> {
+        match configured {
+            Some(credentials) => Ok(credentials.clone()),
+            None => Self::from_env(),
+        }
+    }
+}
+
+/// Resolve the endpoint URL and host for an AWS service
+fn resolve_endpoint(
+    endpoint: Option<&str>,
+    service: &str,
+    region: &str,
+) -> Result<(String, String)> {
+    let url = match endpoint {
+        Some(endpoint) => endpoint.trim_end_matches('/').to_string(),
+        None => format!("https://{}.{}.amazonaws.com", service, region),
+    };
+
+    let parsed = reqwest::Url::parse(&url)
+        .map_err(|e| Error::config(format!("Invalid AWS endpoint '{}': {}", url, e)))?;
+    let host = match (parsed.host_str(), parsed.port()) {
+        (Some(host), Some(port)) => format!("{}:{}", host, port),
+        (Some(host), None) => host.to_string(),
+        (None, _) => return Err(Error::config(format!("AWS endpoint '{}' has no host", url))),
+    };
+
+    Ok((format!("{}/", url), host))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_credentials_debug_masks_secret() {
+        let credentials = AwsCredentials {
+            access_key_id: "AKID".to_string(),
+            secret_access_key: "supersecret".to_string(),
+            session_token: Some("token".to_string()),
+        };
+        let debug = format!("{:?}", credentials);
+        assert!(debug.contains("AKID"));
+        assert!(!debug.contains("supersecret"));
+        assert!(!debug.contains("token\""));
+    }
+
+    #[test]
+    fn test_resolve_endpoint() {
+        let (url, host) = resolve_endpoint(None, "sns", "eu-west-1").unwrap();
+        assert_eq!(url, "https://sns.eu-west-1.amazonaws.com/");
+        assert_eq!(host, "sns.eu-west-1.amazonaws.com");
+
+        let (url, host) = resolve_endpoint(Some("http://localhost:4566/"), "sns", "us-east-1").unwrap();
+        assert_eq!(url, "http://localhost:4566/");
+        assert_eq!(host, "localhost:4566");
+    }
+}
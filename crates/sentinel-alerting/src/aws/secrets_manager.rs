@@ -0,0 +1,207 @@
+//! AWS Secrets Manager backend for `aws-sm:` secret references.
+//!
+//! Secrets are read with `GetSecretValue`. A reference with a `#key` takes
+//! that key from a secret stored as a JSON object, the way the console
+//! stores key/value secrets; without one the whole secret string is used.
+
+use super::{resolve_endpoint, sigv4, AwsCredentials};
+use crate::http::HttpClientConfig;
+use async_trait::async_trait;
+use llm_sentinel_core::{
+    config::AwsSecretsConfig,
+    secrets::{SecretBackend, SecretRef},
+    Error, Result,
+};
+use reqwest::Client;
+use serde::Deserialize;
+use std::time::Duration;
+use tracing::info;
+
+/// GetSecretValue response
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "PascalCase")]
+struct GetSecretValueResponse {
+    #[serde(default)]
+    secret_string: Option<String>,
+}
+
+/// Reads secrets from AWS Secrets Manager
+pub struct SecretsManagerSecrets {
+    client: Client,
+    credentials: AwsCredentials,
+    region: String,
+    url: String,
+    host: String,
+}
+
+impl std::fmt::Debug for SecretsManagerSecrets {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("SecretsManagerSecrets")
+            .field("region", &self.region)
+            .field("url", &self.url)
+            .finish_non_exhaustive()
+    }
+}
+
+impl SecretsManagerSecrets {
+    /// Create a backend, with credentials from the `AWS_*` environment
+    /// variables
+    pub fn new(config: &AwsSecretsConfig) -> Result<Self> {
+        Self::with_credentials(config, AwsCredentials::from_env()?)
+    }
+
+    /// Create a backend with the given credentials
+    pub fn with_credentials(
+        config: &AwsSecretsConfig,
+        credentials: AwsCredentials,
+    ) -> Result<Self> {
+        let (url, host) =
+            resolve_endpoint(config.endpoint.as_deref(), "secretsmanager", &config.region)?;
+        let client = HttpClientConfig::default()
+            .builder()?
+            .timeout(Duration::from_secs(config.timeout_secs))
+            .build()
+            .map_err(|e| Error::config(format!("Failed to create HTTP client: {}", e)))?;
+
+        info!(region = %config.region, "Creating AWS Secrets Manager backend");
+        Ok(Self {
+            client,
+            credentials,
+            region: config.region.clone(),
+            url,
+            host,
+        })
+    }
+}
+
+#[async_trait]
+impl SecretBackend for SecretsManagerSecrets {
+    async fn fetch(&self, reference: &SecretRef) -> Result<String> {
+        let SecretRef::AwsSecretsManager { secret_id, key } = reference else {
+            return Err(Error::config(format!(
+                "Cannot read {} from AWS Secrets Manager",
+                reference
+            )));
+        };
+
+        let body = serde_json::to_vec(&serde_json::json!({ "SecretId": secret_id }))?;
+        let headers = sigv4::sign_post(
+            &self.credentials,
+            &self.region,
+            "secretsmanager",
+            &self.host,
+            &[
+                ("Content-Type", "application/x-amz-json-1.1"),
+                ("X-Amz-Target", "secretsmanager.GetSecretValue"),
+            ],
+            &body,
+            chrono::Utc::now(),
+        );
+        let mut request = self.client.post(&self.url);
+        for (key, value) in &headers {
+            request = request.header(key, value);
+        }
+
+        let response = request.body(body).send().await.map_err(|e| {
+            Error::connection(format!(
+                "GetSecretValue request failed: {}",
+                e.without_url()
+            ))
+        })?;
+        let status = response.status();
+        if !status.is_success() {
+            let text = response.text().await.unwrap_or_default();
+            return Err(Error::connection(format!(
+                "GetSecretValue failed with status {}: {}",
+                status, text
+            )));
+        }
+        let secret = response
+            .json::<GetSecretValueResponse>()
+            .await
+            .map_err(|e| Error::connection(format!("Invalid GetSecretValue response: {}", e)))?
+            .secret_string
+            .ok_or_else(|| Error::not_found(format!("{} is not a string secret", secret_id)))?;
+
+        let Some(key) = key else {
+            return Ok(secret);
+        };
+        let fields: serde_json::Map<String, serde_json::Value> = serde_json::from_str(&secret)
+            .map_err(|_| {
+                Error::validation(format!("{} is not a JSON key/value secret", secret_id))
+            })?;
+        match fields.get(key) {
+            Some(serde_json::Value::String(value)) => Ok(value.clone()),
+            Some(serde_json::Value::Null) | None => Err(Error::not_found(format!(
+                "{} has no key {}",
+                secret_id, key
+            ))),
+            Some(value) => Ok(value.to_string()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use wiremock::{
+        matchers::{header, method},
+        Mock, MockServer, ResponseTemplate,
+    };
+
+    fn backend(endpoint: String) -> SecretsManagerSecrets {
+        SecretsManagerSecrets::with_credentials(
+            &AwsSecretsConfig {
+                region: "eu-west-1".to_string(),
+                endpoint: Some(endpoint),
+                timeout_secs: 5,
+            },
+            AwsCredentials {
+                access_key_id: "AKIDEXAMPLE".to_string(),
+                secret_access_key: "secret".to_string(),
+                session_token: None,
+            },
+        )
+        .unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_reads_json_key() {
+        let server = MockServer::start().await;
+        Mock::given(method("POST"))
+            .and(header("x-amz-target", "secretsmanager.GetSecretValue"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({
+                "Name": "prod/sentinel",
+                "SecretString": "{\"influx_token\":\"t0\",\"port\":8086}",
+            })))
+            .expect(3)
+            .mount(&server)
+            .await;
+
+        let secrets = backend(server.uri());
+        let reference = |raw: &str| SecretRef::parse(raw).unwrap().unwrap();
+        assert_eq!(
+            secrets
+                .fetch(&reference("aws-sm:prod/sentinel#influx_token"))
+                .await
+                .unwrap(),
+            "t0"
+        );
+        assert_eq!(
+            secrets
+                .fetch(&reference("aws-sm:prod/sentinel#port"))
+                .await
+                .unwrap(),
+            "8086"
+        );
+        assert!(secrets
+            .fetch(&reference("aws-sm:prod/sentinel#missing"))
+            .await
+            .is_err());
+
+        let requests = server.received_requests().await.unwrap();
+        let body: serde_json::Value = serde_json::from_slice(&requests[0].body).unwrap();
+        assert_eq!(body["SecretId"], "prod/sentinel");
+        assert!(requests[0].headers.contains_key("authorization"));
+    }
+}
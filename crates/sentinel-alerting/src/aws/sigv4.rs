@@ -0,0 +1,187 @@
+//! Minimal AWS Signature Version 4 request signing.
+//!
+//! Only covers what the alerters need: POST requests to the service root
+//! with an empty query string.
+
+use super::AwsCredentials;
+use chrono::{DateTime, Utc};
+use hmac::{Hmac, Mac};
+use sha2::{Digest, Sha256};
+
+type HmacSha256 = Hmac<Sha256>;
+
+const ALGORITHM: &str = "AWS4-HMAC-SHA256";
+
+/// Compute HMAC-SHA256
+fn hmac(key: &[u8], data: &[u8]) -> Vec<u8> {
+    let mut mac = HmacSha256::new_from_slice(key).expect("HMAC accepts keys of any length");
+    mac.update(data);
+    mac.finalize().into_bytes().to_vec()
+}
+
+/// Hex-encoded SHA-256 digest
+fn sha256_hex(data: &[u8]) -> String {
+    hex::encode(Sha256::digest(data))
+}
+
+/// Derive the signing key for a date, region and service
+pub(crate) fn signing_key(secret: &str, date: &str, region: &str, service: &str) -> Vec<u8> {
+    let k_date = hmac(format!("AWS4{}", secret).as_bytes(), date.as_bytes());
+    let k_region = hmac(&k_date, region.as_bytes());
+    let k_service = hmac(&k_region, service.as_bytes());
+    hmac(&k_service, b"aws4_request")
+}
+
+/// Sign a POST request to `/` and return the headers that must be sent with it.
+///
+/// `headers` are the request headers to include in the signature (besides
+/// `host` and `x-amz-date`, which are always signed). The returned list
+/// contains those headers plus `X-Amz-Date`, the optional session token and
+/// `Authorization`.
+pub(crate) fn sign_post(
+    credentials: &AwsCredentials,
+    region: &str,
+    service: &str,
+    host: &str,
+    headers: &[(&str, &str)],
+    body: &[u8],
+    now: DateTime<Utc>,
+) -> Vec<(String, String)> {
+    let amz_date = now.format("%Y%m%dT%H%M%SZ").to_string();
+    let date = now.format("%Y%m%d").to_string();
+
+    let mut signed: Vec<(String, String)> = headers
+        .iter()
+        .map(|(k, v)| (k.to_lowercase(), v.trim().to_string()))
+        .collect();
+    signed.push(("host".to_string(), host.to_string()));
+    signed.push(("x-amz-date".to_string(), amz_date.clone()));
+    if let Some(token) = &credentials.session_token {
+        signed.push(("x-amz-security-token".to_string(), token.clone()));
+    }
+    signed.sort();
+
+    let canonical_headers: String = signed
+        .iter()
+        .map(|(k, v)| format!("{}:{}\n", k, v))
+        .collect();
+    let signed_headers = signed
+        .iter()
+        .map(|(k, _)| k.as_str())
+        .collect::<Vec<_>>()
+        .join(";");
+
+    let canonical_request = format!(
+        "POST\n/\n\n{}\n{}\n{}",
+        canonical_headers,
+        signed_headers,
+        sha256_hex(body)
+    );
+
+    let scope = format!("{}/{}/{}/aws4_request", date, region, service);
+    let string_to_sign = format!(
+        "{}\n{}\n{}\n{}",
+        ALGORITHM,
+        amz_date,
+        scope,
+        sha256_hex(canonical_request.as_bytes())
+    );
+
+    let key = signing_key(&credentials.secret_access_key, &date, region, service);
+    let signature = hex::encode(hmac(&key, string_to_sign.as_bytes()));
+
+    let mut out: Vec<(String, String)> = headers
+        .iter()
+        .map(|(k, v)| (k.to_string(), v.to_string()))
+        .collect();
+    out.push(("X-Amz-Date".to_string(), amz_date));
+    if let Some(token) = &credentials.session_token {
+        out.push(("X-Amz-Security-Token".to_string(), token.clone()));
+    }
+    out.push((
+        "Authorization".to_string(),
+        format!(
+            "{} Credential={}/{}, SignedHeaders={}, Signature={}",
+            ALGORITHM, credentials.access_key_id, scope, signed_headers, signature
+        ),
+    ));
+    out
+}
+
+/// Percent-encode a value using the SigV4 unreserved character set
+pub(crate) fn uri_encode(value: &str) -> String {
+    let mut encoded = String::with_capacity(value.len());
+    for byte in value.bytes() {
+        match byte {
+            b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' | b'-' | b'_' | b'.' | b'~' => {
+                encoded.push(byte as char)
+            }
+            _ => encoded.push_str(&format!("%{:02X}", byte)),
+        }
+    }
+    encoded
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+
+    #[test]
+    fn test_signing_key_reference_vector() {
+        // Reference vector from the AWS SigV4 documentation
+        let key = signing_key(
+            "wJalrXUtnFEMI/K7MDENG+bPxRfiCYEXAMPLEKEY",
+            "20120215",
+            "us-east-1",
+            "iam",
+        );
+        assert_eq!(
+            hex::encode(key),
+            "f4780e2d9f65fa895f9c67b32ce1baf0b0d8a43505a000a1a9e090d414db404d"
+        );
+    }
+
+    #[test]
+    fn test_sign_post_headers() {
+        let credentials = AwsCredentials {
+            access_key_id: "AKIDEXAMPLE".to_string(),
+            secret_access_key: "secret".to_string(),
+            session_token: Some("token".to_string()),
+        };
+        let now = Utc.with_ymd_and_hms(2024, 1, 2, 3, 4, 5).unwrap();
+
+        let headers = sign_post(
+            &credentials,
+            "us-east-1",
+            "sns",
+            "sns.us-east-1.amazonaws.com",
+            &[("Content-Type", "application/x-www-form-urlencoded")],
+            b"Action=Publish",
+            now,
+        );
+
+        let get = |name: &str| {
+            headers
+                .iter()
+                .find(|(k, _)| k == name)
+                .map(|(_, v)| v.as_str())
+                .unwrap()
+        };
+
+        assert_eq!(get("X-Amz-Date"), "20240102T030405Z");
+        assert_eq!(get("X-Amz-Security-Token"), "token");
+        let auth = get("Authorization");
+        assert!(auth.starts_with(
+            "AWS4-HMAC-SHA256 Credential=AKIDEXAMPLE/20240102/us-east-1/sns/aws4_request"
+        ));
+        assert!(auth.contains("SignedHeaders=content-type;host;x-amz-date;x-amz-security-token"));
+    }
+
+    #[test]
+    fn test_uri_encode() {
+        assert_eq!(uri_encode("a-b_c.d~e"), "a-b_c.d~e");
+        assert_eq!(uri_encode("a b/c:d"), "a%20b%2Fc%3Ad");
+        assert_eq!(uri_encode("{\"k\":1}"), "%7B%22k%22%3A1%7D");
+    }
+}
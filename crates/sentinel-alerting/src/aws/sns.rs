@@ -0,0 +1,365 @@
+//! AWS SNS alert publisher.
+//!
+//! Each anomaly is published as a JSON message with message attributes for
+//! severity, anomaly type, service and model so subscriptions can use SNS
+//! filter policies instead of parsing the body.
+
+use super::{resolve_endpoint, sigv4, AwsCredentials};
+use crate::{
+    cloudevents::{encode_anomaly, CloudEventsConfig},
+    http::HttpClientConfig,
+    Alerter,
+};
+use async_trait::async_trait;
+use llm_sentinel_core::{events::AnomalyEvent, Error, Result};
+use reqwest::Client;
+use serde::{Deserialize, Serialize};
+use std::time::Duration;
+use tracing::{debug, error, info};
+
+const SNS_API_VERSION: &str = "2010-03-31";
+
+/// SNS alerter configuration
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SnsConfig {
+    /// AWS region
+    pub region: String,
+    /// Target topic ARN
+    pub topic_arn: String,
+    /// Endpoint override (e.g. LocalStack)
+    #[serde(default)]
+    pub endpoint: Option<String>,
+    /// Static credentials (falls back to `AWS_*` environment variables)
+    #[serde(default)]
+    pub credentials: Option<AwsCredentials>,
+    /// Prefix for the message subject
+    pub subject_prefix: String,
+    /// Request timeout (seconds)
+    pub timeout_secs: u64,
+    /// Wrap alerts in a CloudEvents envelope
+    #[serde(default)]
+    pub cloudevents: Option<CloudEventsConfig>,
+    /// Proxy, CA and DNS settings
+    #[serde(default)]
+    pub http: HttpClientConfig,
+}
+
+impl Default for SnsConfig {
+    fn default() -> Self {
+        Self {
+            region: "us-east-1".to_string(),
+            topic_arn: String::new(),
+            endpoint: None,
+            credentials: None,
+            subject_prefix: "LLM Sentinel".to_string(),
+            timeout_secs: 10,
+            cloudevents: None,
+            http: HttpClientConfig::default(),
+        }
+    }
+}
+
+/// SNS alert publisher
+pub struct SnsAlerter {
+    client: Client,
+    credentials: AwsCredentials,
+    url: String,
+    host: String,
+    config: SnsConfig,
+}
+
+impl std::fmt::Debug for SnsAlerter {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("SnsAlerter")
+            .field("config", &self.config)
+            .field("url", &self.url)
+            .finish()
+    }
+}
+
+impl SnsAlerter {
+    /// Create a new SNS alerter
+    pub fn new(config: SnsConfig) -> Result<Self> {
+        if config.topic_arn.is_empty() {
+            return Err(Error::config("SNS topic ARN cannot be empty"));
+        }
+
+        let credentials = AwsCredentials::resolve(config.credentials.as_ref())?;
+        let (url, host) = resolve_endpoint(config.endpoint.as_deref(), "sns", &config.region)?;
+
+        info!("Creating SNS alerter for topic {}", config.topic_arn);
+
+        let client = config
+            .http
+            .builder()?
+            .timeout(Duration::from_secs(config.timeout_secs))
+            .build()
+            .map_err(|e| Error::config(format!("Failed to create HTTP client: {}", e)))?;
+
+        Ok(Self {
+            client,
+            credentials,
+            url,
+            host,
+            config,
+        })
+    }
+
+    /// Whether the target topic is a FIFO topic
+    fn is_fifo(&self) -> bool {
+        self.config.topic_arn.ends_with(".fifo")
+    }
+
+    /// Build the message subject (SNS limits subjects to 100 characters)
+    fn build_subject(&self, alert: &AnomalyEvent) -> String {
+        let mut subject = format!(
+            "{}: [{}] {} on {}",
+            self.config.subject_prefix,
+            alert.severity.to_string().to_uppercase(),
+            alert.anomaly_type,
+            alert.service_name
+        );
+        subject.retain(|c| c.is_ascii() && !c.is_ascii_control());
+        subject.truncate(100);
+        subject
+    }
+
+    /// Build the form parameters for the Publish action
+    fn build_params(&self, alert: &AnomalyEvent) -> Result<Vec<(String, String)>> {
+        let (message, _) = encode_anomaly(alert, self.config.cloudevents.as_ref())?;
+
+        let mut params = vec![
+            ("Action".to_string(), "Publish".to_string()),
+            ("Version".to_string(), SNS_API_VERSION.to_string()),
+            ("TopicArn".to_string(), self.config.topic_arn.clone()),
+            ("Subject".to_string(), self.build_subject(alert)),
+            ("Message".to_string(), message),
+        ];
+
+        let attributes = [
+            ("severity", alert.severity.to_string()),
+            ("anomaly_type", alert.anomaly_type.to_string()),
+            ("service", alert.service_name.to_string()),
+            ("model", alert.model.to_string()),
+            ("signature_hash", alert.signature_hash.clone()),
+        ];
+        for (i, (name, value)) in attributes.into_iter().enumerate() {
+            let prefix = format!("MessageAttributes.entry.{}", i + 1);
+            params.push((format!("{}.Name", prefix), name.to_string()));
+            params.push((format!("{}.Value.DataType", prefix), "String".to_string()));
+            params.push((format!("{}.Value.StringValue", prefix), value));
+        }
+
+        if self.is_fifo() {
+            params.push(("MessageGroupId".to_string(), alert.service_name.to_string()));
+            params.push((
+                "MessageDeduplicationId".to_string(),
+                alert.alert_id.to_string(),
+            ));
+        }
+
+        Ok(params)
+    }
+
+    /// Publish an alert to the topic
+    async fn publish(&self, alert: &AnomalyEvent) -> Result<()> {
+        let body = self
+            .build_params(alert)?
+            .iter()
+            .map(|(k, v)| format!("{}={}", sigv4::uri_encode(k), sigv4::uri_encode(v)))
+            .collect::<Vec<_>>()
+            .join("&");
+
+        let headers = sigv4::sign_post(
+            &self.credentials,
+            &self.config.region,
+            "sns",
+            &self.host,
+            &[("Content-Type", "application/x-www-form-urlencoded; charset=utf-8")],
+            body.as_bytes(),
+            chrono::Utc::now(),
+        );
+
+        let mut request = self.client.post(&self.url);
+        for (key, value) in &headers {
+            request = request.header(key, value);
+        }
+
+        let response = request.body(body).send().await.map_err(|e| {
+            metrics::counter!("sentinel_sns_failures_total").increment(1);
+            Error::alerting(format!("SNS publish request failed: {}", e))
+        })?;
+
+        let status = response.status();
+        if !status.is_success() {
+            let body = response.text().await.unwrap_or_default();
+            error!(
+                alert_id = %alert.alert_id,
+                status = %status,
+                body = %body,
+                "SNS publish failed"
+            );
+            metrics::counter!("sentinel_sns_failures_total").increment(1);
+            return Err(Error::alerting(format!(
+                "SNS publish failed with status {}: {}",
+                status, body
+            )));
+        }
+
+        debug!(
+            alert_id = %alert.alert_id,
+            topic_arn = %self.config.topic_arn,
+            "Alert published to SNS"
+        );
+        metrics::counter!(
+            "sentinel_sns_publishes_total",
+            "severity" => alert.severity.to_string()
+        )
+        .increment(1);
+
+        Ok(())
+    }
+}
+
+#[async_trait]
+impl Alerter for SnsAlerter {
+    async fn send(&self, alert: &AnomalyEvent) -> Result<()> {
+        self.publish(alert).await
+    }
+
+    async fn health_check(&self) -> Result<()> {
+        // Credentials and endpoint are validated at construction; SNS has no cheap ping
+        Ok(())
+    }
+
+    fn name(&self) -> &str {
+        "SNS"
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use llm_sentinel_fixtures::anomaly;
+
+    use wiremock::{
+        matchers::{body_string_contains, header_exists, method},
+        Mock, MockServer, ResponseTemplate,
+    };
+
+    fn create_test_config(endpoint: Option<String>, topic_arn: &str) -> SnsConfig {
+        SnsConfig {
+            topic_arn: topic_arn.to_string(),
+            endpoint,
+            credentials: Some(AwsCredentials {
+                access_key_id: "AKIDEXAMPLE".to_string(),
+                secret_access_key: "secret".to_string(),
+                session_token: None,
+            }),
+            ..Default::default()
+        }
+    }
+
+    fn create_test_anomaly() -> AnomalyEvent {
+        anomaly()
+            .service("checkout")
+            .confidence(0.95)
+            .value(500.0)
+            .threshold(300.0)
+            .deviation_sigma(Some(4.0))
+            .time_window("rolling_window")
+            .build()
+    }
+
+    #[test]
+    fn test_empty_topic_error() {
+        let config = create_test_config(None, "");
+        assert!(SnsAlerter::new(config).is_err());
+    }
+
+    #[test]
+    fn test_params_include_attributes() {
+        let alerter = SnsAlerter::new(create_test_config(
+            None,
+            "arn:aws:sns:us-east-1:123456789012:alerts",
+        ))
+        .unwrap();
+        let params = alerter.build_params(&create_test_anomaly()).unwrap();
+
+        let get = |name: &str| {
+            params
+                .iter()
+                .find(|(k, _)| k == name)
+                .map(|(_, v)| v.as_str())
+        };
+
+        assert_eq!(get("Action"), Some("Publish"));
+        assert_eq!(get("MessageAttributes.entry.1.Name"), Some("severity"));
+        assert_eq!(get("MessageAttributes.entry.1.Value.StringValue"), Some("high"));
+        assert_eq!(get("MessageAttributes.entry.2.Value.StringValue"), Some("latency_spike"));
+        assert_eq!(get("MessageGroupId"), None);
+        assert_eq!(
+            get("Subject"),
+            Some("LLM Sentinel: [HIGH] latency_spike on checkout")
+        );
+    }
+
+    #[test]
+    fn test_fifo_topic_params() {
+        let alerter = SnsAlerter::new(create_test_config(
+            None,
+            "arn:aws:sns:us-east-1:123456789012:alerts.fifo",
+        ))
+        .unwrap();
+        let alert = create_test_anomaly();
+        let params = alerter.build_params(&alert).unwrap();
+
+        assert!(params.contains(&("MessageGroupId".to_string(), "checkout".to_string())));
+        assert!(params.contains(&(
+            "MessageDeduplicationId".to_string(),
+            alert.alert_id.to_string()
+        )));
+    }
+
+    #[tokio::test]
+    async fn test_publish_success() {
+        let mock_server = MockServer::start().await;
+
+        Mock::given(method("POST"))
+            .and(header_exists("Authorization"))
+            .and(header_exists("X-Amz-Date"))
+            .and(body_string_contains("Action=Publish"))
+            .respond_with(ResponseTemplate::new(200))
+            .expect(1)
+            .mount(&mock_server)
+            .await;
+
+        let alerter = SnsAlerter::new(create_test_config(
+            Some(mock_server.uri()),
+            "arn:aws:sns:us-east-1:123456789012:alerts",
+        ))
+        .unwrap();
+
+        assert!(alerter.send(&create_test_anomaly()).await.is_ok());
+    }
+
+    #[tokio::test]
+    async fn test_publish_failure() {
+        let mock_server = MockServer::start().await;
+
+        Mock::given(method("POST"))
+            .respond_with(ResponseTemplate::new(403).set_body_string("AccessDenied"))
+            .mount(&mock_server)
+            .await;
+
+        let alerter = SnsAlerter::new(create_test_config(
+            Some(mock_server.uri()),
+            "arn:aws:sns:us-east-1:123456789012:alerts",
+        ))
+        .unwrap();
+
+        let result = alerter.send(&create_test_anomaly()).await;
+        assert!(result.is_err());
+    }
+}
@@ -0,0 +1,437 @@
+//! AWS Timestream export sink.
+//!
+//! Each event becomes one multi-measure record: service, model, severity
+//! and labels as dimensions, numbers and IDs as measures, timestamped in
+//! milliseconds. The ingest endpoint is discovered through
+//! `DescribeEndpoints` when not configured, as Timestream requires.
+
+use super::{resolve_endpoint, sigv4, AwsCredentials};
+use crate::{export::ExportSink, http::HttpClientConfig};
+use async_trait::async_trait;
+use llm_sentinel_core::{
+    config::TimestreamExportConfig,
+    events::{AnomalyEvent, TelemetryEvent},
+    labels::Labels,
+    types::Environment,
+    Error, Result,
+};
+use reqwest::Client;
+use serde::{Deserialize, Serialize};
+use std::time::Duration;
+use tracing::{debug, error, info};
+
+const TARGET_PREFIX: &str = "Timestream_20181101";
+
+/// Maximum number of records per WriteRecords request
+const MAX_RECORDS_PER_REQUEST: usize = 100;
+
+/// Record dimension
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "PascalCase")]
+struct Dimension {
+    name: String,
+    value: String,
+}
+
+/// One measure of a multi-measure record
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "PascalCase")]
+struct MeasureValue {
+    name: &'static str,
+    value: String,
+    #[serde(rename = "Type")]
+    kind: &'static str,
+}
+
+/// Multi-measure record
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "PascalCase")]
+struct Record {
+    dimensions: Vec<Dimension>,
+    measure_name: &'static str,
+    measure_value_type: &'static str,
+    measure_values: Vec<MeasureValue>,
+    time: String,
+    time_unit: &'static str,
+}
+
+impl Record {
+    fn new(measure_name: &'static str, time: chrono::DateTime<chrono::Utc>) -> Self {
+        Self {
+            dimensions: Vec::new(),
+            measure_name,
+            measure_value_type: "MULTI",
+            measure_values: Vec::new(),
+            time: time.timestamp_millis().to_string(),
+            time_unit: "MILLISECONDS",
+        }
+    }
+
+    /// Add a dimension; Timestream rejects empty values, so they are left
+    /// out
+    fn dimension(mut self, name: impl Into<String>, value: &str) -> Self {
+        if !value.is_empty() {
+            self.dimensions.push(Dimension {
+                name: name.into(),
+                value: value.to_string(),
+            });
+        }
+        self
+    }
+
+    /// Add the environment and label dimensions
+    fn scope(mut self, environment: Option<&Environment>, labels: &Labels) -> Self {
+        if let Some(environment) = environment {
+            self = self.dimension("environment", environment.as_str());
+        }
+        for (key, value) in labels {
+            self = self.dimension(format!("label_{}", key), value);
+        }
+        self
+    }
+
+    fn double(mut self, name: &'static str, value: f64) -> Self {
+        // Timestream has no representation for NaN or infinity
+        if value.is_finite() {
+            self.measure_values.push(MeasureValue {
+                name,
+                value: value.to_string(),
+                kind: "DOUBLE",
+            });
+        }
+        self
+    }
+
+    fn bigint(mut self, name: &'static str, value: i64) -> Self {
+        self.measure_values.push(MeasureValue {
+            name,
+            value: value.to_string(),
+            kind: "BIGINT",
+        });
+        self
+    }
+
+    fn boolean(mut self, name: &'static str, value: bool) -> Self {
+        self.measure_values.push(MeasureValue {
+            name,
+            value: value.to_string(),
+            kind: "BOOLEAN",
+        });
+        self
+    }
+
+    fn varchar(mut self, name: &'static str, value: String) -> Self {
+        self.measure_values.push(MeasureValue {
+            name,
+            value,
+            kind: "VARCHAR",
+        });
+        self
+    }
+}
+
+/// DescribeEndpoints response
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "PascalCase")]
+struct DescribeEndpointsResponse {
+    endpoints: Vec<EndpointAddress>,
+}
+
+/// Discovered endpoint
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "PascalCase")]
+struct EndpointAddress {
+    address: String,
+}
+
+/// Timestream export sink
+pub struct TimestreamSink {
+    client: Client,
+    credentials: AwsCredentials,
+    url: String,
+    host: String,
+    config: TimestreamExportConfig,
+}
+
+impl std::fmt::Debug for TimestreamSink {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("TimestreamSink")
+            .field("config", &self.config)
+            .field("url", &self.url)
+            .finish()
+    }
+}
+
+impl TimestreamSink {
+    /// Create a sink, with credentials from the `AWS_*` environment
+    /// variables
+    pub async fn connect(config: TimestreamExportConfig) -> Result<Self> {
+        Self::with_credentials(config, AwsCredentials::from_env()?).await
+    }
+
+    /// Create a sink with the given credentials, discovering the ingest
+    /// endpoint unless one is configured
+    pub async fn with_credentials(
+        config: TimestreamExportConfig,
+        credentials: AwsCredentials,
+    ) -> Result<Self> {
+        let client = HttpClientConfig::default()
+            .builder()?
+            .timeout(Duration::from_secs(config.timeout_secs))
+            .build()
+            .map_err(|e| Error::config(format!("Failed to create HTTP client: {}", e)))?;
+
+        let endpoint = match &config.endpoint {
+            Some(endpoint) => endpoint.clone(),
+            None => {
+                let (url, host) = resolve_endpoint(None, "ingest.timestream", &config.region)?;
+                let response: DescribeEndpointsResponse = call(
+                    &client,
+                    &credentials,
+                    &config.region,
+                    (&url, &host),
+                    "DescribeEndpoints",
+                    b"{}".to_vec(),
+                )
+                .await?
+                .json()
+                .await
+                .map_err(|e| {
+                    Error::connection(format!(
+                        "Invalid Timestream DescribeEndpoints response: {}",
+                        e
+                    ))
+                })?;
+                let address = response.endpoints.into_iter().next().ok_or_else(|| {
+                    Error::connection("Timestream DescribeEndpoints returned no endpoint")
+                })?;
+                format!("https://{}", address.address)
+            }
+        };
+        let (url, host) = resolve_endpoint(Some(&endpoint), "ingest.timestream", &config.region)?;
+
+        info!(
+            "Creating Timestream export to {} at {}",
+            config.database, url
+        );
+
+        Ok(Self {
+            client,
+            credentials,
+            url,
+            host,
+            config,
+        })
+    }
+
+    /// Build the record for a telemetry event
+    fn telemetry_record(event: &TelemetryEvent) -> Record {
+        Record::new("telemetry", event.timestamp)
+            .dimension("service", event.service_name.as_str())
+            .dimension("model", event.model.as_str())
+            .scope(event.environment.as_ref(), &event.labels)
+            .varchar("event_id", event.event_id.to_string())
+            .double("latency_ms", event.latency_ms)
+            .bigint("prompt_tokens", event.prompt.tokens as i64)
+            .bigint("response_tokens", event.response.tokens as i64)
+            .bigint("total_tokens", event.total_tokens() as i64)
+            .double("cost_usd", event.cost_usd)
+            .boolean("has_errors", event.has_errors())
+    }
+
+    /// Build the record for an anomaly
+    fn anomaly_record(anomaly: &AnomalyEvent) -> Record {
+        Record::new("anomaly", anomaly.timestamp)
+            .dimension("service", anomaly.service_name.as_str())
+            .dimension("model", anomaly.model.as_str())
+            .dimension("severity", &anomaly.severity.to_string())
+            .dimension("anomaly_type", &anomaly.anomaly_type.to_string())
+            .dimension("detection_method", &anomaly.detection_method.to_string())
+            .scope(anomaly.environment.as_ref(), &anomaly.labels)
+            .varchar("alert_id", anomaly.alert_id.to_string())
+            .varchar("metric", anomaly.details.metric.clone())
+            .double("confidence", anomaly.confidence)
+            .double("value", anomaly.details.value)
+            .double("baseline", anomaly.details.baseline)
+            .double("threshold", anomaly.details.threshold)
+    }
+
+    /// Write records to a table, at most 100 per request
+    async fn write_records(&self, table: &str, records: Vec<Record>) -> Result<()> {
+        for chunk in records.chunks(MAX_RECORDS_PER_REQUEST) {
+            let body = serde_json::to_vec(&serde_json::json!({
+                "DatabaseName": self.config.database,
+                "TableName": table,
+                "Records": chunk,
+            }))
+            .map_err(|e| {
+                Error::internal(format!("Failed to serialize WriteRecords request: {}", e))
+            })?;
+
+            call(
+                &self.client,
+                &self.credentials,
+                &self.config.region,
+                (&self.url, &self.host),
+                "WriteRecords",
+                body,
+            )
+            .await?;
+            debug!(table, count = chunk.len(), "Wrote records to Timestream");
+        }
+        Ok(())
+    }
+}
+
+/// Send a signed Timestream API call, failing on non-success statuses
+///
+/// Rejected records are reported by WriteRecords as a 400 whose body lists
+/// them, which ends up in the error.
+async fn call(
+    client: &Client,
+    credentials: &AwsCredentials,
+    region: &str,
+    (url, host): (&str, &str),
+    action: &str,
+    body: Vec<u8>,
+) -> Result<reqwest::Response> {
+    let target = format!("{}.{}", TARGET_PREFIX, action);
+    let headers = sigv4::sign_post(
+        credentials,
+        region,
+        "timestream",
+        host,
+        &[
+            ("Content-Type", "application/x-amz-json-1.0"),
+            ("X-Amz-Target", target.as_str()),
+        ],
+        &body,
+        chrono::Utc::now(),
+    );
+
+    let mut request = client.post(url);
+    for (key, value) in &headers {
+        request = request.header(key, value);
+    }
+
+    let response = request
+        .body(body)
+        .send()
+        .await
+        .map_err(|e| Error::storage(format!("Timestream {} request failed: {}", action, e)))?;
+
+    let status = response.status();
+    if !status.is_success() {
+        let body = response.text().await.unwrap_or_default();
+        error!(status = %status, body = %body, "Timestream {} failed", action);
+        return Err(Error::storage(format!(
+            "Timestream {} failed with status {}: {}",
+            action, status, body
+        )));
+    }
+    Ok(response)
+}
+
+#[async_trait]
+impl ExportSink for TimestreamSink {
+    fn name(&self) -> &str {
+        "timestream"
+    }
+
+    async fn export_telemetry(&self, events: &[TelemetryEvent]) -> Result<()> {
+        let records = events.iter().map(Self::telemetry_record).collect();
+        self.write_records(&self.config.telemetry_table, records)
+            .await
+    }
+
+    async fn export_anomalies(&self, anomalies: &[AnomalyEvent]) -> Result<()> {
+        let records = anomalies.iter().map(Self::anomaly_record).collect();
+        self.write_records(&self.config.anomaly_table, records)
+            .await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use llm_sentinel_fixtures::event;
+    use wiremock::{
+        matchers::{header, header_exists, method},
+        Mock, MockServer, ResponseTemplate,
+    };
+
+    fn create_test_event() -> TelemetryEvent {
+        event().service("checkout").build()
+    }
+
+    #[test]
+    fn test_telemetry_record() {
+        let mut event = create_test_event();
+        event
+            .labels
+            .insert("team".to_string(), "payments".to_string());
+        event.labels.insert("empty".to_string(), String::new());
+        let record = serde_json::to_value(TimestreamSink::telemetry_record(&event)).unwrap();
+
+        assert_eq!(record["MeasureName"], "telemetry");
+        assert_eq!(record["MeasureValueType"], "MULTI");
+        assert_eq!(
+            record["Time"],
+            event.timestamp.timestamp_millis().to_string()
+        );
+        let dimensions: Vec<&str> = record["Dimensions"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|d| d["Name"].as_str().unwrap())
+            .collect();
+        assert_eq!(dimensions, vec!["service", "model", "label_team"]);
+        assert!(record["MeasureValues"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .any(|m| m["Name"] == "total_tokens" && m["Value"] == "30" && m["Type"] == "BIGINT"));
+    }
+
+    #[tokio::test]
+    async fn test_write_records() {
+        let server = MockServer::start().await;
+        Mock::given(method("POST"))
+            .and(header("X-Amz-Target", "Timestream_20181101.WriteRecords"))
+            .and(header_exists("Authorization"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({
+                "RecordsIngested": {"Total": 2}
+            })))
+            .expect(1)
+            .mount(&server)
+            .await;
+
+        let sink = TimestreamSink::with_credentials(
+            TimestreamExportConfig {
+                region: "us-east-1".to_string(),
+                database: "sentinel".to_string(),
+                telemetry_table: "telemetry".to_string(),
+                anomaly_table: "anomalies".to_string(),
+                endpoint: Some(server.uri()),
+                timeout_secs: 5,
+            },
+            AwsCredentials {
+                access_key_id: "AKIDEXAMPLE".to_string(),
+                secret_access_key: "secret".to_string(),
+                session_token: None,
+            },
+        )
+        .await
+        .unwrap();
+
+        sink.export_telemetry(&[create_test_event(), create_test_event()])
+            .await
+            .unwrap();
+
+        let requests = server.received_requests().await.unwrap();
+        let body: serde_json::Value = serde_json::from_slice(&requests[0].body).unwrap();
+        assert_eq!(body["DatabaseName"], "sentinel");
+        assert_eq!(body["TableName"], "telemetry");
+        assert_eq!(body["Records"].as_array().unwrap().len(), 2);
+    }
+}
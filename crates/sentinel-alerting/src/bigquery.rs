@@ -0,0 +1,308 @@
+//! Google BigQuery export sink.
+//!
+//! Rows are streamed with the `insertAll` API, one flat row per event with
+//! labels as a JSON string. Event and alert IDs are sent as insert IDs, so
+//! BigQuery drops rows repeated by a retried batch on a best-effort basis.
+//! Authentication works as for the Pub/Sub alerter: a configured access
+//! token, the GCE/GKE metadata server, or none for an emulator endpoint.
+
+use crate::export::ExportSink;
+use async_trait::async_trait;
+use llm_sentinel_core::{
+    config::BigQueryExportConfig,
+    events::{AnomalyEvent, TelemetryEvent},
+    gcp::{endpoint_auth, TokenSource},
+    Error, Result,
+};
+use reqwest::Client;
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+use std::time::Duration;
+use tracing::{debug, error, info};
+
+const DEFAULT_ENDPOINT: &str = "https://bigquery.googleapis.com";
+
+/// Maximum number of rows per insertAll request
+const MAX_ROWS_PER_REQUEST: usize = 500;
+
+/// Row of an insertAll request
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct InsertRow {
+    insert_id: String,
+    json: Value,
+}
+
+/// insertAll response
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct InsertAllResponse {
+    #[serde(default)]
+    insert_errors: Vec<InsertError>,
+}
+
+/// Errors of one rejected row
+#[derive(Debug, Deserialize)]
+struct InsertError {
+    index: usize,
+    #[serde(default)]
+    errors: Vec<ErrorProto>,
+}
+
+/// BigQuery error detail
+#[derive(Debug, Deserialize)]
+struct ErrorProto {
+    #[serde(default)]
+    message: String,
+}
+
+/// BigQuery export sink
+pub struct BigQuerySink {
+    client: Client,
+    token: TokenSource,
+    tables_url: String,
+    config: BigQueryExportConfig,
+}
+
+impl std::fmt::Debug for BigQuerySink {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("BigQuerySink")
+            .field("project_id", &self.config.project_id)
+            .field("dataset", &self.config.dataset)
+            .field("tables_url", &self.tables_url)
+            .finish()
+    }
+}
+
+impl BigQuerySink {
+    /// Create a new BigQuery sink
+    pub fn new(config: BigQueryExportConfig) -> Result<Self> {
+        let (endpoint, token) = endpoint_auth(
+            DEFAULT_ENDPOINT,
+            config.endpoint.as_deref(),
+            config.access_token.as_deref(),
+            None,
+        );
+
+        let tables_url = format!(
+            "{}/bigquery/v2/projects/{}/datasets/{}/tables",
+            endpoint.trim_end_matches('/'),
+            config.project_id,
+            config.dataset
+        );
+
+        info!("Creating BigQuery export to {}", tables_url);
+
+        let client = Client::builder()
+            .timeout(Duration::from_secs(config.timeout_secs))
+            .build()
+            .map_err(|e| Error::config(format!("Failed to create HTTP client: {}", e)))?;
+
+        Ok(Self {
+            client,
+            token,
+            tables_url,
+            config,
+        })
+    }
+
+    /// Build the row for a telemetry event
+    fn telemetry_row(event: &TelemetryEvent) -> InsertRow {
+        InsertRow {
+            insert_id: event.event_id.to_string(),
+            json: json!({
+                "event_id": event.event_id.to_string(),
+                "timestamp": event.timestamp.to_rfc3339(),
+                "service": event.service_name.as_str(),
+                "model": event.model.as_str(),
+                "environment": event.environment.as_ref().map(|e| e.as_str()),
+                "latency_ms": event.latency_ms,
+                "prompt_tokens": event.prompt.tokens,
+                "response_tokens": event.response.tokens,
+                "total_tokens": event.total_tokens(),
+                "cost_usd": event.cost_usd,
+                "has_errors": event.has_errors(),
+                "labels": serde_json::to_string(&event.labels).unwrap_or_default(),
+            }),
+        }
+    }
+
+    /// Build the row for an anomaly
+    fn anomaly_row(anomaly: &AnomalyEvent) -> InsertRow {
+        InsertRow {
+            insert_id: anomaly.alert_id.to_string(),
+            json: json!({
+                "alert_id": anomaly.alert_id.to_string(),
+                "timestamp": anomaly.timestamp.to_rfc3339(),
+                "service": anomaly.service_name.as_str(),
+                "model": anomaly.model.as_str(),
+                "environment": anomaly.environment.as_ref().map(|e| e.as_str()),
+                "severity": anomaly.severity.to_string(),
+                "anomaly_type": anomaly.anomaly_type.to_string(),
+                "detection_method": anomaly.detection_method.to_string(),
+                "confidence": anomaly.confidence,
+                "metric": anomaly.details.metric,
+                "value": anomaly.details.value,
+                "baseline": anomaly.details.baseline,
+                "threshold": anomaly.details.threshold,
+                "labels": serde_json::to_string(&anomaly.labels).unwrap_or_default(),
+            }),
+        }
+    }
+
+    /// Stream rows into a table, at most 500 per request
+    ///
+    /// Rows BigQuery rejects are reported in the error; the rest of their
+    /// batch is still inserted.
+    async fn insert_all(&self, table: &str, rows: Vec<InsertRow>) -> Result<()> {
+        let url = format!("{}/{}/insertAll", self.tables_url, table);
+        let mut rejected = Vec::new();
+
+        for chunk in rows.chunks(MAX_ROWS_PER_REQUEST) {
+            let mut request = self.client.post(&url);
+            if let Some(token) = self.token.token(&self.client).await? {
+                request = request.bearer_auth(token);
+            }
+
+            let response = request
+                .json(&json!({
+                    "rows": chunk,
+                    "skipInvalidRows": true,
+                    "ignoreUnknownValues": true,
+                }))
+                .send()
+                .await
+                .map_err(|e| Error::storage(format!("BigQuery insertAll request failed: {}", e)))?;
+
+            let status = response.status();
+            if !status.is_success() {
+                let body = response.text().await.unwrap_or_default();
+                error!(status = %status, body = %body, "BigQuery insertAll failed");
+                return Err(Error::storage(format!(
+                    "BigQuery insertAll failed with status {}: {}",
+                    status, body
+                )));
+            }
+
+            let result: InsertAllResponse = response.json().await.map_err(|e| {
+                Error::storage(format!("Invalid BigQuery insertAll response: {}", e))
+            })?;
+            for insert_error in result.insert_errors {
+                let message = insert_error
+                    .errors
+                    .first()
+                    .map(|e| e.message.clone())
+                    .unwrap_or_default();
+                let row = chunk
+                    .get(insert_error.index)
+                    .map(|row| row.insert_id.as_str());
+                rejected.push(format!("{}: {}", row.unwrap_or("?"), message));
+            }
+            debug!(table, count = chunk.len(), "Streamed rows to BigQuery");
+        }
+
+        if rejected.is_empty() {
+            Ok(())
+        } else {
+            Err(Error::storage(format!(
+                "BigQuery rejected {} of {} rows ({})",
+                rejected.len(),
+                rows.len(),
+                rejected.join("; ")
+            )))
+        }
+    }
+}
+
+#[async_trait]
+impl ExportSink for BigQuerySink {
+    fn name(&self) -> &str {
+        "bigquery"
+    }
+
+    async fn export_telemetry(&self, events: &[TelemetryEvent]) -> Result<()> {
+        let rows = events.iter().map(Self::telemetry_row).collect();
+        self.insert_all(&self.config.telemetry_table, rows).await
+    }
+
+    async fn export_anomalies(&self, anomalies: &[AnomalyEvent]) -> Result<()> {
+        let rows = anomalies.iter().map(Self::anomaly_row).collect();
+        self.insert_all(&self.config.anomaly_table, rows).await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use llm_sentinel_fixtures::event;
+    use wiremock::{
+        matchers::{header, method, path},
+        Mock, MockServer, ResponseTemplate,
+    };
+
+    fn create_test_event() -> TelemetryEvent {
+        event().service("checkout").build()
+    }
+
+    fn create_sink(server: &MockServer) -> BigQuerySink {
+        BigQuerySink::new(BigQueryExportConfig {
+            project_id: "analytics".to_string(),
+            dataset: "sentinel".to_string(),
+            telemetry_table: "telemetry".to_string(),
+            anomaly_table: "anomalies".to_string(),
+            endpoint: Some(server.uri()),
+            access_token: Some("token".to_string()),
+            timeout_secs: 5,
+        })
+        .unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_insert_telemetry() {
+        let server = MockServer::start().await;
+        Mock::given(method("POST"))
+            .and(path(
+                "/bigquery/v2/projects/analytics/datasets/sentinel/tables/telemetry/insertAll",
+            ))
+            .and(header("Authorization", "Bearer token"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(json!({
+                "kind": "bigquery#tableDataInsertAllResponse"
+            })))
+            .expect(1)
+            .mount(&server)
+            .await;
+
+        let sink = create_sink(&server);
+        let event = create_test_event();
+        sink.export_telemetry(std::slice::from_ref(&event))
+            .await
+            .unwrap();
+
+        let requests = server.received_requests().await.unwrap();
+        let body: Value = serde_json::from_slice(&requests[0].body).unwrap();
+        let row = &body["rows"][0];
+        assert_eq!(row["insertId"], event.event_id.to_string());
+        assert_eq!(row["json"]["service"], "checkout");
+        assert_eq!(row["json"]["total_tokens"], 30);
+    }
+
+    #[tokio::test]
+    async fn test_rejected_rows_are_reported() {
+        let server = MockServer::start().await;
+        Mock::given(method("POST"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(json!({
+                "insertErrors": [{
+                    "index": 1,
+                    "errors": [{"reason": "invalid", "message": "no such field: latency"}]
+                }]
+            })))
+            .mount(&server)
+            .await;
+
+        let sink = create_sink(&server);
+        let events = [create_test_event(), create_test_event()];
+        let err = sink.export_telemetry(&events).await.unwrap_err();
+        assert!(err.to_string().contains("rejected 1 of 2 rows"));
+        assert!(err.to_string().contains(&events[1].event_id.to_string()));
+    }
+}
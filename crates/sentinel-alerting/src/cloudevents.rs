@@ -0,0 +1,167 @@
+//! CloudEvents 1.0 envelope for published anomalies.
+//!
+//! Destinations that carry the anomaly as a message body can wrap it in a
+//! CloudEvents structured-mode JSON envelope, so event routers such as
+//! Knative brokers or EventBridge rules can filter on standard attributes
+//! without knowing the anomaly schema. Severity, anomaly type, service and
+//! model are also set as extension attributes for the same reason.
+
+use chrono::{DateTime, Utc};
+use llm_sentinel_core::{events::AnomalyEvent, Error, Result};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+
+/// Media type of a structured-mode JSON CloudEvent
+pub const CLOUDEVENTS_CONTENT_TYPE: &str = "application/cloudevents+json";
+
+/// CloudEvents specification version
+pub const SPEC_VERSION: &str = "1.0";
+
+/// Event type of a detected anomaly
+pub const ANOMALY_EVENT_TYPE: &str = "io.llm-sentinel.anomaly.detected";
+
+/// CloudEvents settings for a destination
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct CloudEventsConfig {
+    /// `source` attribute identifying this deployment
+    pub source: String,
+    /// `type` attribute; defaults to `io.llm-sentinel.anomaly.detected`
+    #[serde(rename = "type")]
+    pub event_type: String,
+    /// `dataschema` attribute
+    pub dataschema: Option<String>,
+}
+
+impl Default for CloudEventsConfig {
+    fn default() -> Self {
+        Self {
+            source: "/llm-sentinel".to_string(),
+            event_type: ANOMALY_EVENT_TYPE.to_string(),
+            dataschema: None,
+        }
+    }
+}
+
+/// Structured-mode CloudEvent
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CloudEvent<T> {
+    /// Specification version
+    pub specversion: String,
+    /// Event ID, unique per source
+    pub id: String,
+    /// Event producer
+    pub source: String,
+    /// Event type
+    #[serde(rename = "type")]
+    pub event_type: String,
+    /// Subject within the source, `<service>/<model>` for anomalies
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub subject: Option<String>,
+    /// Time of the occurrence
+    pub time: DateTime<Utc>,
+    /// Media type of `data`
+    pub datacontenttype: String,
+    /// Schema `data` adheres to
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub dataschema: Option<String>,
+    /// Extension attributes
+    #[serde(flatten)]
+    pub extensions: BTreeMap<String, String>,
+    /// Event payload
+    pub data: T,
+}
+
+impl<'a> CloudEvent<&'a AnomalyEvent> {
+    /// Wrap an anomaly
+    pub fn anomaly(alert: &'a AnomalyEvent, config: &CloudEventsConfig) -> Self {
+        // Extension names must be lowercase alphanumeric
+        let mut extensions = BTreeMap::from([
+            ("severity".to_string(), alert.severity.to_string()),
+            ("anomalytype".to_string(), alert.anomaly_type.to_string()),
+            ("service".to_string(), alert.service_name.to_string()),
+            ("model".to_string(), alert.model.to_string()),
+        ]);
+        if let Some(environment) = &alert.environment {
+            extensions.insert("environment".to_string(), environment.to_string());
+        }
+
+        Self {
+            specversion: SPEC_VERSION.to_string(),
+            id: alert.alert_id.to_string(),
+            source: config.source.clone(),
+            event_type: config.event_type.clone(),
+            subject: Some(format!("{}/{}", alert.service_name, alert.model)),
+            time: alert.timestamp,
+            datacontenttype: "application/json".to_string(),
+            dataschema: config.dataschema.clone(),
+            extensions,
+            data: alert,
+        }
+    }
+}
+
+/// Serialize an anomaly as a published message body
+///
+/// Returns the body and its content type: the plain anomaly as
+/// `application/json`, or a CloudEvent when `cloudevents` is set.
+pub fn encode_anomaly(
+    alert: &AnomalyEvent,
+    cloudevents: Option<&CloudEventsConfig>,
+) -> Result<(String, &'static str)> {
+    let encoded = match cloudevents {
+        Some(config) => serde_json::to_string(&CloudEvent::anomaly(alert, config))
+            .map(|body| (body, CLOUDEVENTS_CONTENT_TYPE)),
+        None => serde_json::to_string(alert).map(|body| (body, "application/json")),
+    };
+    encoded.map_err(|e| Error::internal(format!("Failed to serialize alert: {}", e)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use llm_sentinel_core::types::Environment;
+
+    fn create_test_anomaly() -> AnomalyEvent {
+        let mut alert = llm_sentinel_fixtures::anomaly().service("checkout").build();
+        alert.environment = Some(Environment::new("prod"));
+        alert
+    }
+
+    #[test]
+    fn test_structured_envelope() {
+        let alert = create_test_anomaly();
+        let config = CloudEventsConfig {
+            source: "//sentinel.example.com/prod".to_string(),
+            ..Default::default()
+        };
+        let (body, content_type) = encode_anomaly(&alert, Some(&config)).unwrap();
+        assert_eq!(content_type, CLOUDEVENTS_CONTENT_TYPE);
+
+        let event: serde_json::Value = serde_json::from_str(&body).unwrap();
+        assert_eq!(event["specversion"], "1.0");
+        assert_eq!(event["id"], alert.alert_id.to_string());
+        assert_eq!(event["source"], "//sentinel.example.com/prod");
+        assert_eq!(event["type"], ANOMALY_EVENT_TYPE);
+        assert_eq!(event["subject"], "checkout/gpt-4");
+        assert_eq!(event["datacontenttype"], "application/json");
+        assert!(event.get("dataschema").is_none());
+        assert_eq!(event["severity"], "high");
+        assert_eq!(event["anomalytype"], "latency_spike");
+        assert_eq!(event["environment"], "prod");
+        assert_eq!(event["data"]["alert_id"], alert.alert_id.to_string());
+
+        let parsed: CloudEvent<AnomalyEvent> = serde_json::from_str(&body).unwrap();
+        assert_eq!(parsed.data.alert_id, alert.alert_id);
+        assert_eq!(parsed.extensions["service"], "checkout");
+    }
+
+    #[test]
+    fn test_native_encoding() {
+        let alert = create_test_anomaly();
+        let (body, content_type) = encode_anomaly(&alert, None).unwrap();
+        assert_eq!(content_type, "application/json");
+        let parsed: AnomalyEvent = serde_json::from_str(&body).unwrap();
+        assert_eq!(parsed.alert_id, alert.alert_id);
+    }
+}
@@ -0,0 +1,443 @@
+//! Datadog Events API alert delivery.
+//!
+//! Posts each alert as a Datadog event. Service, model, severity, anomaly
+//! type, lifecycle state and labels become tags, the environment becomes
+//! Datadog's reserved `env` tag, and the aggregation key groups repeated
+//! anomalies of the same type on the same service and model into one event
+//! stream entry.
+
+use crate::{http::HttpClientConfig, Alerter};
+use async_trait::async_trait;
+use llm_sentinel_core::{
+    events::AnomalyEvent,
+    retry::{retry, RetryError, RetryPolicy},
+    types::Severity,
+    Error, Result,
+};
+use reqwest::{Client, StatusCode};
+use serde::{Deserialize, Serialize};
+use std::time::Duration;
+use tracing::{debug, error, info, warn};
+
+/// Maximum Datadog event title length
+const MAX_TITLE_LEN: usize = 100;
+
+/// Datadog Events alerter configuration
+#[derive(Clone, Serialize, Deserialize)]
+pub struct DatadogConfig {
+    /// API key
+    pub api_key: String,
+    /// Datadog site, e.g. `datadoghq.com` or `datadoghq.eu`
+    pub site: String,
+    /// API base URL override (defaults to `https://api.{site}`)
+    pub endpoint: Option<String>,
+    /// Tags added to every event, e.g. `team:ml-platform`
+    pub tags: Vec<String>,
+    /// Source type name shown on the event
+    pub source_type_name: Option<String>,
+    /// Host the events are attributed to
+    pub host: Option<String>,
+    /// Minimum severity to deliver
+    pub min_severity: Option<Severity>,
+    /// Request timeout (seconds)
+    pub timeout_secs: u64,
+    /// Maximum attempts per event
+    pub max_retries: u32,
+    /// Initial retry delay in milliseconds
+    pub retry_delay_ms: u64,
+    /// Proxy, CA and DNS settings
+    #[serde(default)]
+    pub http: HttpClientConfig,
+}
+
+impl Default for DatadogConfig {
+    fn default() -> Self {
+        Self {
+            api_key: String::new(),
+            site: "datadoghq.com".to_string(),
+            endpoint: None,
+            tags: Vec::new(),
+            source_type_name: None,
+            host: None,
+            min_severity: None,
+            timeout_secs: 10,
+            max_retries: 3,
+            retry_delay_ms: 1000,
+            http: HttpClientConfig::default(),
+        }
+    }
+}
+
+impl std::fmt::Debug for DatadogConfig {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("DatadogConfig")
+            .field("site", &self.site)
+            .field("endpoint", &self.endpoint)
+            .field("tags", &self.tags)
+            .field("source_type_name", &self.source_type_name)
+            .field("host", &self.host)
+            .field("min_severity", &self.min_severity)
+            .field("timeout_secs", &self.timeout_secs)
+            .field("http", &self.http)
+            .finish_non_exhaustive()
+    }
+}
+
+/// Datadog Events alerter
+pub struct DatadogAlerter {
+    client: Client,
+    base_url: String,
+    config: DatadogConfig,
+}
+
+impl std::fmt::Debug for DatadogAlerter {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("DatadogAlerter")
+            .field("base_url", &self.base_url)
+            .field("config", &self.config)
+            .finish_non_exhaustive()
+    }
+}
+
+impl DatadogAlerter {
+    /// Create a new Datadog alerter
+    pub fn new(config: DatadogConfig) -> Result<Self> {
+        if config.api_key.is_empty() {
+            return Err(Error::config("Datadog API key cannot be empty"));
+        }
+
+        let base_url = config
+            .endpoint
+            .clone()
+            .unwrap_or_else(|| format!("https://api.{}", config.site))
+            .trim_end_matches('/')
+            .to_string();
+        info!(url = %base_url, "Creating Datadog alerter");
+
+        let client = config
+            .http
+            .builder()?
+            .timeout(Duration::from_secs(config.timeout_secs))
+            .build()
+            .map_err(|e| Error::config(format!("Failed to create HTTP client: {}", e)))?;
+
+        Ok(Self {
+            client,
+            base_url,
+            config,
+        })
+    }
+
+    /// Build the event body for an alert
+    fn build_event(&self, alert: &AnomalyEvent) -> serde_json::Value {
+        let mut tags = self.config.tags.clone();
+        tags.push(tag("service", alert.service_name.as_str()));
+        tags.push(tag("model", alert.model.as_str()));
+        tags.push(tag("severity", &alert.severity.to_string()));
+        tags.push(tag("anomaly_type", &alert.anomaly_type.to_string()));
+        tags.push(tag("state", &alert.state.to_string()));
+        tags.push(tag("source", "llm-sentinel"));
+        if let Some(environment) = &alert.environment {
+            tags.push(tag("env", environment.as_str()));
+        }
+        for (key, value) in &alert.labels {
+            tags.push(tag(key, value));
+        }
+
+        let title = format!(
+            "[{}] {} on {}/{}",
+            alert.severity.to_string().to_uppercase(),
+            alert.anomaly_type,
+            alert.service_name,
+            alert.model
+        );
+
+        let mut lines = vec![
+            format!(
+                "**{}** {:.2} (baseline {:.2}, threshold {:.2})",
+                alert.details.metric,
+                alert.details.value,
+                alert.details.baseline,
+                alert.details.threshold
+            ),
+            format!(
+                "Detected by {} with {:.0}% confidence",
+                alert.detection_method,
+                alert.confidence * 100.0
+            ),
+        ];
+        if let Some(root_cause) = &alert.root_cause {
+            lines.push(format!("Root cause: {}", root_cause));
+        }
+        if let Some(runbook) = &alert.runbook_url {
+            lines.push(format!("[Runbook]({})", runbook));
+        }
+        for (name, url) in &alert.trace_links {
+            lines.push(format!("[Trace in {}]({})", name, url));
+        }
+        lines.push(format!("Alert ID: {}", alert.alert_id));
+
+        let mut event = serde_json::json!({
+            "title": title.chars().take(MAX_TITLE_LEN).collect::<String>(),
+            "text": format!("%%%\n{}\n%%%", lines.join("\n\n")),
+            "date_happened": alert.timestamp.timestamp(),
+            "alert_type": alert_type(alert.severity),
+            "priority": if alert.severity == Severity::Low { "low" } else { "normal" },
+            "aggregation_key": alert.signature_hash,
+            "tags": tags,
+        });
+        if let Some(source_type_name) = &self.config.source_type_name {
+            event["source_type_name"] = source_type_name.as_str().into();
+        }
+        if let Some(host) = &self.config.host {
+            event["host"] = host.as_str().into();
+        }
+        event
+    }
+}
+
+/// `key:value` tag with whitespace replaced, as Datadog expects
+fn tag(key: &str, value: &str) -> String {
+    format!("{}:{}", key, value)
+        .chars()
+        .map(|c| if c.is_whitespace() { '_' } else { c })
+        .collect()
+}
+
+/// Datadog alert type for a severity
+fn alert_type(severity: Severity) -> &'static str {
+    match severity {
+        Severity::Critical | Severity::High => "error",
+        Severity::Medium => "warning",
+        Severity::Low => "info",
+    }
+}
+
+#[async_trait]
+impl Alerter for DatadogAlerter {
+    async fn send(&self, alert: &AnomalyEvent) -> Result<()> {
+        if self
+            .config
+            .min_severity
+            .is_some_and(|min| alert.severity < min)
+        {
+            debug!(alert_id = %alert.alert_id, "Alert below Datadog minimum severity");
+            return Ok(());
+        }
+
+        let url = format!("{}/api/v1/events", self.base_url);
+        let event = self.build_event(alert);
+        let policy = RetryPolicy::new(self.config.max_retries.max(1))
+            .with_initial_delay(Duration::from_millis(self.config.retry_delay_ms));
+        retry(&policy, |attempt| {
+            let request = self
+                .client
+                .post(&url)
+                .header("DD-API-KEY", &self.config.api_key)
+                .json(&event);
+
+            async move {
+                let response = match request.send().await {
+                    Ok(response) => response,
+                    Err(e) => {
+                        let e = e.without_url();
+                        warn!(attempt, error = %e, "Datadog request failed");
+                        return Err(RetryError::Transient(e.to_string()));
+                    }
+                };
+
+                let status = response.status();
+                if status.is_success() {
+                    return Ok(());
+                }
+                let text = response.text().await.unwrap_or_default();
+                let message = format!("status {}: {}", status, text);
+                if status == StatusCode::REQUEST_TIMEOUT
+                    || status == StatusCode::TOO_MANY_REQUESTS
+                    || status.is_server_error()
+                {
+                    warn!(attempt, status = %status, "Datadog rate limited or failing");
+                    Err(RetryError::Transient(message))
+                } else {
+                    Err(RetryError::Permanent(message))
+                }
+            }
+        })
+        .await
+        .map_err(|failure| {
+            error!(
+                alert_id = %alert.alert_id,
+                attempts = failure.attempts,
+                error = %failure.error,
+                "Datadog delivery failed"
+            );
+            metrics::counter!("sentinel_datadog_failures_total").increment(1);
+            Error::alerting(format!("Datadog delivery failed: {}", failure.error))
+        })?;
+
+        debug!(alert_id = %alert.alert_id, "Alert sent to Datadog");
+        metrics::counter!(
+            "sentinel_datadog_events_total",
+            "severity" => alert.severity.to_string()
+        )
+        .increment(1);
+
+        Ok(())
+    }
+
+    async fn health_check(&self) -> Result<()> {
+        // Validates the API key
+        self.client
+            .get(format!("{}/api/v1/validate", self.base_url))
+            .header("DD-API-KEY", &self.config.api_key)
+            .send()
+            .await
+            .and_then(|r| r.error_for_status())
+            .map_err(|e| {
+                Error::connection(format!("Datadog health check failed: {}", e.without_url()))
+            })?;
+
+        Ok(())
+    }
+
+    fn name(&self) -> &str {
+        "Datadog"
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use llm_sentinel_core::types::{AnomalyType, Environment};
+    use llm_sentinel_fixtures::anomaly;
+
+    use wiremock::{
+        matchers::{body_partial_json, header, method, path},
+        Mock, MockServer, ResponseTemplate,
+    };
+
+    fn create_test_anomaly(severity: Severity) -> AnomalyEvent {
+        let mut alert = anomaly()
+            .severity(severity)
+            .anomaly_type(AnomalyType::CostAnomaly)
+            .service("checkout")
+            .confidence(0.95)
+            .metric("cost_usd")
+            .value(5.0)
+            .baseline(1.0)
+            .deviation_sigma(Some(4.0))
+            .time_window("rolling_window")
+            .build();
+        alert.environment = Some(Environment::new("prod"));
+        alert
+            .labels
+            .insert("team".to_string(), "search ranking".to_string());
+        alert
+    }
+
+    #[test]
+    fn test_build_event() {
+        let alerter = DatadogAlerter::new(DatadogConfig {
+            api_key: "dd-key".to_string(),
+            tags: vec!["owner:ml".to_string()],
+            ..Default::default()
+        })
+        .unwrap();
+        assert_eq!(alerter.base_url, "https://api.datadoghq.com");
+
+        let anomaly = create_test_anomaly(Severity::Medium);
+        let event = alerter.build_event(&anomaly);
+        assert_eq!(event["title"], "[MEDIUM] cost_anomaly on checkout/gpt-4");
+        assert_eq!(event["alert_type"], "warning");
+        assert_eq!(event["priority"], "normal");
+        assert_eq!(event["aggregation_key"], anomaly.signature_hash);
+        let tags: Vec<&str> = event["tags"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|t| t.as_str().unwrap())
+            .collect();
+        assert_eq!(tags[0], "owner:ml");
+        assert!(tags.contains(&"service:checkout"));
+        assert!(tags.contains(&"env:prod"));
+        assert!(tags.contains(&"state:open"));
+        assert!(tags.contains(&"team:search_ranking"));
+        assert!(event["text"]
+            .as_str()
+            .unwrap()
+            .starts_with("%%%\n**cost_usd** 5.00"));
+
+        assert_eq!(alert_type(Severity::Critical), "error");
+        assert_eq!(alert_type(Severity::Low), "info");
+    }
+
+    #[test]
+    fn test_config_validation() {
+        assert!(DatadogAlerter::new(DatadogConfig::default()).is_err());
+        let config = DatadogConfig {
+            api_key: "dd-key".to_string(),
+            ..Default::default()
+        };
+        assert!(!format!("{:?}", config).contains("dd-key"));
+    }
+
+    #[tokio::test]
+    async fn test_send() {
+        let server = MockServer::start().await;
+        Mock::given(method("POST"))
+            .and(path("/api/v1/events"))
+            .and(header("DD-API-KEY", "dd-key"))
+            .and(body_partial_json(
+                serde_json::json!({ "alert_type": "error" }),
+            ))
+            .respond_with(
+                ResponseTemplate::new(202).set_body_json(serde_json::json!({ "status": "ok" })),
+            )
+            .expect(1)
+            .mount(&server)
+            .await;
+
+        let alerter = DatadogAlerter::new(DatadogConfig {
+            api_key: "dd-key".to_string(),
+            endpoint: Some(server.uri()),
+            min_severity: Some(Severity::Medium),
+            ..Default::default()
+        })
+        .unwrap();
+        alerter
+            .send(&create_test_anomaly(Severity::Critical))
+            .await
+            .unwrap();
+        // Below the minimum severity: not sent
+        alerter
+            .send(&create_test_anomaly(Severity::Low))
+            .await
+            .unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_send_forbidden() {
+        let server = MockServer::start().await;
+        Mock::given(method("POST"))
+            .respond_with(
+                ResponseTemplate::new(403)
+                    .set_body_json(serde_json::json!({ "errors": ["Forbidden"] })),
+            )
+            .expect(1)
+            .mount(&server)
+            .await;
+
+        let alerter = DatadogAlerter::new(DatadogConfig {
+            api_key: "bad-key".to_string(),
+            endpoint: Some(server.uri()),
+            retry_delay_ms: 1,
+            ..Default::default()
+        })
+        .unwrap();
+        let err = alerter
+            .send(&create_test_anomaly(Severity::High))
+            .await
+            .unwrap_err();
+        assert!(err.to_string().contains("Forbidden"));
+    }
+}
@@ -3,13 +3,16 @@
 use chrono::{DateTime, Utc};
 use dashmap::DashMap;
 use llm_sentinel_core::{
-    events::AnomalyEvent,
-    types::{ModelId, ServiceId},
+    clock::Clock,
+    config::DeduplicationKeyConfig,
+    events::{signature_hash, AnomalyEvent},
+    memory::{MemoryConsumer, ShedCost},
+    types::{Environment, Severity},
 };
 use serde::{Deserialize, Serialize};
-use std::sync::Arc;
+use std::sync::{Arc, Mutex};
 use std::time::Duration;
-use tracing::{debug, info};
+use tracing::{debug, info, warn};
 
 /// Configuration for alert deduplication
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -20,6 +23,15 @@ pub struct DeduplicationConfig {
     pub enabled: bool,
     /// Cleanup interval (seconds)
     pub cleanup_interval_secs: u64,
+    /// Optional fields included in the key
+    #[serde(default)]
+    pub key_fields: DeduplicationKeyConfig,
+    /// Produce a summary when a window that suppressed alerts expires
+    #[serde(default)]
+    pub summarize_on_expiry: bool,
+    /// Clock windows are measured against
+    #[serde(skip)]
+    pub clock: Clock,
 }
 
 impl Default for DeduplicationConfig {
@@ -28,34 +40,72 @@ impl Default for DeduplicationConfig {
             window_secs: 300,        // 5 minutes
             enabled: true,
             cleanup_interval_secs: 60, // 1 minute
+            key_fields: DeduplicationKeyConfig::default(),
+            summarize_on_expiry: false,
+            clock: Clock::system(),
         }
     }
 }
 
 /// Key for deduplication - represents a unique alert signature
-#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
 pub struct DeduplicationKey {
-    pub service: ServiceId,
-    pub model: ModelId,
-    pub anomaly_type: String,
+    /// Signature hash of the anomaly, covering its service, model, anomaly
+    /// type and, unless left out of the key, its metric
+    #[serde(default)]
+    pub signature: String,
+    /// Environment, so staging duplicates never suppress production alerts
+    pub environment: Option<Environment>,
     pub severity: String,
+    /// Detection method, when configured as a key field
+    pub detector: Option<String>,
+    /// User ID, when configured as a key field
+    pub user: Option<String>,
 }
 
 impl DeduplicationKey {
-    /// Create key from anomaly event
+    /// Create key from anomaly event with the default key fields
     pub fn from_event(event: &AnomalyEvent) -> Self {
+        Self::with_fields(event, &DeduplicationKeyConfig::default())
+    }
+
+    /// Create key from anomaly event, including the configured fields
+    pub fn with_fields(event: &AnomalyEvent, fields: &DeduplicationKeyConfig) -> Self {
+        let signature = if fields.metric && !event.signature_hash.is_empty() {
+            event.signature_hash.clone()
+        } else if fields.metric {
+            signature_hash(
+                &event.service_name,
+                &event.model,
+                &event.anomaly_type,
+                &event.details.metric,
+            )
+        } else {
+            signature_hash(&event.service_name, &event.model, &event.anomaly_type, "")
+        };
         Self {
-            service: event.service_name.clone(),
-            model: event.model.clone(),
-            anomaly_type: event.anomaly_type.to_string(),
+            signature,
+            environment: event.environment.clone(),
             severity: event.severity.to_string(),
+            detector: fields
+                .detector
+                .then(|| event.detection_method.to_string()),
+            user: if fields.user {
+                event.context.user_id.clone()
+            } else {
+                None
+            },
         }
     }
 }
 
 /// Deduplication entry tracking when an alert was last seen
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 struct DeduplicationEntry {
+    /// Alert that opened the window
+    first_alert: AnomalyEvent,
+    /// First occurrence timestamp
+    first_seen: DateTime<Utc>,
     /// Last occurrence timestamp
     last_seen: DateTime<Utc>,
     /// Number of occurrences in current window
@@ -65,24 +115,134 @@ struct DeduplicationEntry {
 }
 
 impl DeduplicationEntry {
-    fn new(alert_id: String) -> Self {
+    fn new(alert: &AnomalyEvent, now: DateTime<Utc>) -> Self {
         Self {
-            last_seen: Utc::now(),
+            first_alert: alert.clone(),
+            first_seen: now,
+            last_seen: now,
             count: 1,
-            alert_ids: vec![alert_id],
+            alert_ids: vec![alert.alert_id.to_string()],
         }
     }
 
-    fn increment(&mut self, alert_id: String) {
-        self.last_seen = Utc::now();
+    fn increment(&mut self, alert_id: String, now: DateTime<Utc>) {
+        self.last_seen = now;
         self.count += 1;
         self.alert_ids.push(alert_id);
     }
 
-    fn is_expired(&self, window: Duration) -> bool {
-        let elapsed = Utc::now().signed_duration_since(self.last_seen);
+    /// Approximate bytes held by the entry and its key
+    fn memory_usage(&self, key: &DeduplicationKey) -> usize {
+        let optional = |field: &Option<String>| field.as_ref().map_or(0, |f| f.len());
+        std::mem::size_of::<DeduplicationKey>()
+            + std::mem::size_of::<Self>()
+            + key.signature.len()
+            + key.severity.len()
+            + optional(&key.detector)
+            + optional(&key.user)
+            + self.alert_ids.capacity() * std::mem::size_of::<String>()
+            + self.alert_ids.iter().map(|id| id.len()).sum::<usize>()
+    }
+
+    fn is_expired(&self, window: Duration, now: DateTime<Utc>) -> bool {
+        let elapsed = now.signed_duration_since(self.last_seen);
         elapsed.num_seconds() > window.as_secs() as i64
     }
+
+    /// Summary of the window, if any alerts were suppressed
+    fn summary(&self, key: &DeduplicationKey) -> Option<DeduplicationSummary> {
+        (self.count > 1).then(|| DeduplicationSummary {
+            key: key.clone(),
+            first_alert: self.first_alert.clone(),
+            occurrences: self.count,
+            first_seen: self.first_seen,
+            last_seen: self.last_seen,
+            suppressed_alert_ids: self.alert_ids[1..].to_vec(),
+        })
+    }
+}
+
+/// Context key marking a deduplication summary alert
+pub const SUMMARY_KEY: &str = "dedup_summary";
+
+/// Whether an alert summarizes an expired deduplication window
+pub fn is_summary(anomaly: &AnomalyEvent) -> bool {
+    anomaly
+        .context
+        .additional
+        .get(SUMMARY_KEY)
+        .is_some_and(|value| value == "true")
+}
+
+/// Alerts suppressed during an expired deduplication window
+#[derive(Debug, Clone)]
+pub struct DeduplicationSummary {
+    /// Signature of the suppressed alerts
+    pub key: DeduplicationKey,
+    /// Alert that was sent when the window opened
+    pub first_alert: AnomalyEvent,
+    /// Occurrences in the window, including the first alert
+    pub occurrences: u64,
+    /// First occurrence timestamp
+    pub first_seen: DateTime<Utc>,
+    /// Last occurrence timestamp
+    pub last_seen: DateTime<Utc>,
+    /// IDs of the suppressed alerts
+    pub suppressed_alert_ids: Vec<String>,
+}
+
+impl DeduplicationSummary {
+    /// Build the summary alert sent at `now`: a copy of the first alert
+    /// with a new ID, the suppressed alerts as related alerts and the counts
+    /// in context
+    pub fn to_alert(&self, now: DateTime<Utc>) -> AnomalyEvent {
+        let mut alert = self.first_alert.clone();
+        alert.alert_id = uuid::Uuid::new_v4();
+        alert.timestamp = now;
+        alert.related_alerts = self
+            .suppressed_alert_ids
+            .iter()
+            .filter_map(|id| id.parse().ok())
+            .collect();
+        alert.root_cause = Some(format!(
+            "{} occurrences between {} and {}; {} duplicate alerts were suppressed",
+            self.occurrences,
+            self.first_seen.to_rfc3339(),
+            self.last_seen.to_rfc3339(),
+            self.suppressed_alert_ids.len()
+        ));
+
+        let additional = &mut alert.context.additional;
+        additional.insert(SUMMARY_KEY.to_string(), "true".to_string());
+        additional.insert(
+            "dedup_occurrences".to_string(),
+            self.occurrences.to_string(),
+        );
+        additional.insert(
+            "dedup_first_seen".to_string(),
+            self.first_seen.to_rfc3339(),
+        );
+        additional.insert("dedup_last_seen".to_string(), self.last_seen.to_rfc3339());
+        alert
+    }
+}
+
+/// Open deduplication windows, for carrying them over to another process
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct DeduplicationSnapshot {
+    entries: Vec<(DeduplicationKey, DeduplicationEntry)>,
+}
+
+impl DeduplicationSnapshot {
+    /// Number of open windows
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    /// Check if no windows are open
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
 }
 
 /// Alert deduplicator to prevent duplicate alerts
@@ -91,6 +251,8 @@ pub struct AlertDeduplicator {
     entries: Arc<DashMap<DeduplicationKey, DeduplicationEntry>>,
     /// Configuration
     config: DeduplicationConfig,
+    /// Summaries of expired windows awaiting delivery
+    summaries: Mutex<Vec<DeduplicationSummary>>,
 }
 
 impl std::fmt::Debug for AlertDeduplicator {
@@ -113,6 +275,7 @@ impl AlertDeduplicator {
         Self {
             entries: Arc::new(DashMap::new()),
             config,
+            summaries: Mutex::new(Vec::new()),
         }
     }
 
@@ -126,25 +289,26 @@ impl AlertDeduplicator {
             return true;
         }
 
-        let key = DeduplicationKey::from_event(event);
-        let alert_id = event.alert_id.to_string();
+        let key = DeduplicationKey::with_fields(event, &self.config.key_fields);
+        let now = self.config.clock.now();
 
         // Check if we've seen this alert signature recently
         if let Some(mut entry) = self.entries.get_mut(&key) {
             let window = Duration::from_secs(self.config.window_secs);
 
-            if entry.is_expired(window) {
+            if entry.is_expired(window, now) {
                 // Window expired, reset and send
                 debug!(
                     "Deduplication window expired for {:?}, sending alert",
                     key
                 );
-                *entry = DeduplicationEntry::new(alert_id);
+                self.record_summary(&key, &entry);
+                *entry = DeduplicationEntry::new(event, now);
                 metrics::counter!("sentinel_alerts_sent_total").increment(1);
                 true
             } else {
                 // Still in window, deduplicate
-                entry.increment(alert_id);
+                entry.increment(event.alert_id.to_string(), now);
                 metrics::counter!("sentinel_alerts_deduplicated_total").increment(1);
                 debug!(
                     "Alert deduplicated: {:?}, count: {}",
@@ -155,7 +319,7 @@ impl AlertDeduplicator {
         } else {
             // First time seeing this alert signature
             self.entries
-                .insert(key.clone(), DeduplicationEntry::new(alert_id));
+                .insert(key.clone(), DeduplicationEntry::new(event, now));
             metrics::counter!("sentinel_alerts_sent_total").increment(1);
             debug!("New alert signature: {:?}, sending", key);
             true
@@ -168,16 +332,21 @@ impl AlertDeduplicator {
             total_signatures: self.entries.len(),
             total_deduplicated: 0,
             by_severity: std::collections::HashMap::new(),
+            by_detector: std::collections::HashMap::new(),
         };
 
         for entry in self.entries.iter() {
-            stats.total_deduplicated += entry.value().count.saturating_sub(1);
+            let suppressed = entry.value().count.saturating_sub(1);
+            stats.total_deduplicated += suppressed;
 
             let severity_count = stats
                 .by_severity
                 .entry(entry.key().severity.clone())
                 .or_insert(0);
-            *severity_count += entry.value().count.saturating_sub(1);
+            *severity_count += suppressed;
+
+            let detector = entry.value().first_alert.detection_method.detector();
+            *stats.by_detector.entry(detector).or_insert(0) += suppressed;
         }
 
         stats
@@ -186,11 +355,13 @@ impl AlertDeduplicator {
     /// Clean up expired entries
     pub fn cleanup_expired(&self) {
         let window = Duration::from_secs(self.config.window_secs);
+        let now = self.config.clock.now();
         let mut removed = 0;
 
-        self.entries.retain(|_, entry| {
-            let keep = !entry.is_expired(window);
+        self.entries.retain(|key, entry| {
+            let keep = !entry.is_expired(window, now);
             if !keep {
+                self.record_summary(key, entry);
                 removed += 1;
             }
             keep
@@ -200,7 +371,30 @@ impl AlertDeduplicator {
             info!("Cleaned up {} expired deduplication entries", removed);
         }
 
-        metrics::gauge!("sentinel_deduplication_entries").set(self.entries.len() as f64);
+        self.get_stats().record_metrics();
+    }
+
+    /// Keep a summary of an expiring window, if enabled and alerts were
+    /// suppressed
+    fn record_summary(&self, key: &DeduplicationKey, entry: &DeduplicationEntry) {
+        if !self.config.summarize_on_expiry {
+            return;
+        }
+        if let Some(summary) = entry.summary(key) {
+            debug!(
+                "Deduplication window closed for {:?} after {} occurrences",
+                key, summary.occurrences
+            );
+            self.summaries.lock().unwrap().push(summary);
+        }
+    }
+
+    /// Take summaries of windows that expired since the last call
+    ///
+    /// Expired entries are collected by [`cleanup_expired`](Self::cleanup_expired)
+    /// or when a new alert with the same signature arrives.
+    pub fn take_summaries(&self) -> Vec<DeduplicationSummary> {
+        std::mem::take(&mut *self.summaries.lock().unwrap())
     }
 
     /// Start background cleanup task
@@ -217,6 +411,30 @@ impl AlertDeduplicator {
         });
     }
 
+    /// Open deduplication windows
+    pub fn snapshot(&self) -> DeduplicationSnapshot {
+        DeduplicationSnapshot {
+            entries: self
+                .entries
+                .iter()
+                .map(|entry| (entry.key().clone(), entry.value().clone()))
+                .collect(),
+        }
+    }
+
+    /// Replace all open windows with snapshotted ones
+    ///
+    /// Windows that expired since the snapshot was taken are collected by
+    /// the next cleanup as usual.
+    pub fn restore(&self, snapshot: DeduplicationSnapshot) {
+        self.entries.clear();
+        let count = snapshot.len();
+        for (key, entry) in snapshot.entries {
+            self.entries.insert(key, entry);
+        }
+        info!("Restored {} deduplication entries", count);
+    }
+
     /// Clear all entries (for testing)
     pub fn clear(&self) {
         self.entries.clear();
@@ -229,8 +447,56 @@ impl AlertDeduplicator {
     }
 }
 
+/// Windows are closed least recently seen first, as if they had expired;
+/// the next alert of an evicted signature is sent and opens a new window.
+impl MemoryConsumer for AlertDeduplicator {
+    fn name(&self) -> &str {
+        "deduplication"
+    }
+
+    fn memory_usage(&self) -> usize {
+        self.entries
+            .iter()
+            .map(|entry| entry.value().memory_usage(entry.key()))
+            .sum()
+    }
+
+    fn shed_cost(&self) -> ShedCost {
+        ShedCost::Expensive
+    }
+
+    fn shed(&self, bytes: usize) -> usize {
+        let mut idle: Vec<(DateTime<Utc>, DeduplicationKey)> = self
+            .entries
+            .iter()
+            .map(|entry| (entry.value().last_seen, entry.key().clone()))
+            .collect();
+        idle.sort_by_key(|(last_seen, _)| *last_seen);
+
+        let mut freed = 0;
+        let mut evicted = 0;
+        for (_, key) in idle {
+            if freed >= bytes {
+                break;
+            }
+            if let Some((key, entry)) = self.entries.remove(&key) {
+                freed += entry.memory_usage(&key);
+                self.record_summary(&key, &entry);
+                evicted += 1;
+            }
+        }
+        if evicted > 0 {
+            warn!(
+                "Closed {} deduplication windows early to free memory",
+                evicted
+            );
+        }
+        freed
+    }
+}
+
 /// Statistics about deduplicated alerts
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize)]
 pub struct DeduplicationStats {
     /// Total unique alert signatures
     pub total_signatures: usize,
@@ -238,6 +504,8 @@ pub struct DeduplicationStats {
     pub total_deduplicated: u64,
     /// Deduplicated count by severity
     pub by_severity: std::collections::HashMap<String, u64>,
+    /// Deduplicated count by the detector of the alert opening the window
+    pub by_detector: std::collections::HashMap<String, u64>,
 }
 
 impl DeduplicationStats {
@@ -250,49 +518,43 @@ impl DeduplicationStats {
         self.total_deduplicated as f64
             / (self.total_deduplicated as f64 + self.total_signatures as f64)
     }
+
+    /// Publish the statistics as gauges
+    pub fn record_metrics(&self) {
+        metrics::gauge!("sentinel_deduplication_entries").set(self.total_signatures as f64);
+        metrics::gauge!("sentinel_deduplication_rate").set(self.deduplication_rate());
+        // Every severity is reported so counts drop to zero as windows close
+        for severity in [
+            Severity::Low,
+            Severity::Medium,
+            Severity::High,
+            Severity::Critical,
+        ] {
+            let severity = severity.to_string();
+            let count = self.by_severity.get(&severity).copied().unwrap_or(0);
+            metrics::gauge!("sentinel_deduplication_suppressed", "severity" => severity)
+                .set(count as f64);
+        }
+    }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
-    use llm_sentinel_core::{
-        events::{AnomalyDetails, PromptInfo, ResponseInfo, TelemetryEvent},
-        types::{DetectionMethod, ModelId, ServiceId},
-    };
+    use llm_sentinel_core::types::{AnomalyType, DetectionMethod, Severity};
+    use llm_sentinel_fixtures::anomaly;
 
     fn create_test_anomaly(severity: Severity, anomaly_type: AnomalyType) -> AnomalyEvent {
-        let telemetry = TelemetryEvent::new(
-            ServiceId::new("test-service"),
-            ModelId::new("gpt-4"),
-            PromptInfo {
-                text: "test".to_string(),
-                tokens: 10,
-                embedding: None,
-            },
-            ResponseInfo {
-                text: "response".to_string(),
-                tokens: 20,
-                finish_reason: "stop".to_string(),
-                embedding: None,
-            },
-            100.0,
-            0.01,
-        );
-
-        AnomalyEvent::new(
-            severity,
-            anomaly_type,
-            DetectionMethod::ZScore,
-            0.95,
-            AnomalyDetails {
-                metric: "latency_ms".to_string(),
-                value: 500.0,
-                baseline: 100.0,
-                threshold: 300.0,
-                deviation_percent: 400.0,
-            },
-            &telemetry,
-        )
+        anomaly()
+            .severity(severity)
+            .anomaly_type(anomaly_type)
+            .service("test-service")
+            .confidence(0.95)
+            .value(500.0)
+            .threshold(300.0)
+            .deviation_sigma(Some(4.0))
+            .time_window("rolling_window")
+            .build()
     }
 
     #[test]
@@ -300,10 +562,17 @@ mod tests {
         let event = create_test_anomaly(Severity::High, AnomalyType::LatencySpike);
         let key = DeduplicationKey::from_event(&event);
 
-        assert_eq!(key.service.as_str(), "test-service");
-        assert_eq!(key.model.as_str(), "gpt-4");
-        assert_eq!(key.severity, Severity::High);
-        assert_eq!(key.anomaly_type, AnomalyType::LatencySpike);
+        assert_eq!(key.signature, event.signature_hash);
+        assert_eq!(key.severity, Severity::High.to_string());
+        assert_eq!(key.environment, None);
+
+        // An empty signature is recomputed rather than shared by all events
+        let mut unsigned = event.clone();
+        unsigned.signature_hash.clear();
+        assert_eq!(
+            DeduplicationKey::from_event(&unsigned).signature,
+            event.signature_hash
+        );
     }
 
     #[test]
@@ -312,6 +581,9 @@ mod tests {
             enabled: true,
             window_secs: 300,
             cleanup_interval_secs: 60,
+            key_fields: DeduplicationKeyConfig::default(),
+            summarize_on_expiry: false,
+            clock: Clock::system(),
         };
 
         let deduplicator = AlertDeduplicator::new(config);
@@ -327,6 +599,9 @@ mod tests {
             enabled: true,
             window_secs: 300,
             cleanup_interval_secs: 60,
+            key_fields: DeduplicationKeyConfig::default(),
+            summarize_on_expiry: false,
+            clock: Clock::system(),
         };
 
         let deduplicator = AlertDeduplicator::new(config);
@@ -366,6 +641,56 @@ mod tests {
         assert!(deduplicator.should_send(&event2));
     }
 
+    #[test]
+    fn test_deduplication_different_environment_not_deduplicated() {
+        let deduplicator = AlertDeduplicator::new(DeduplicationConfig::default());
+
+        let mut staging = create_test_anomaly(Severity::High, AnomalyType::LatencySpike);
+        staging.environment = Some(Environment::new("staging"));
+        let mut prod = create_test_anomaly(Severity::High, AnomalyType::LatencySpike);
+        prod.environment = Some(Environment::new("prod"));
+
+        assert!(deduplicator.should_send(&staging));
+        assert!(deduplicator.should_send(&prod));
+        assert!(!deduplicator.should_send(&prod));
+    }
+
+    #[test]
+    fn test_deduplication_key_fields() {
+        let latency = create_test_anomaly(Severity::High, AnomalyType::LatencySpike);
+        let mut tokens = create_test_anomaly(Severity::High, AnomalyType::LatencySpike);
+        tokens.details.metric = "total_tokens".to_string();
+        tokens.refresh_signature_hash();
+
+        // Metric is part of the key by default
+        let deduplicator = AlertDeduplicator::new(DeduplicationConfig::default());
+        assert!(deduplicator.should_send(&latency));
+        assert!(deduplicator.should_send(&tokens));
+
+        let deduplicator = AlertDeduplicator::new(DeduplicationConfig {
+            key_fields: DeduplicationKeyConfig {
+                metric: false,
+                detector: true,
+                user: true,
+            },
+            ..Default::default()
+        });
+        assert!(deduplicator.should_send(&latency));
+        assert!(!deduplicator.should_send(&tokens));
+
+        let mut other_user = create_test_anomaly(Severity::High, AnomalyType::LatencySpike);
+        other_user.context.user_id = Some("user-1".to_string());
+        assert!(deduplicator.should_send(&other_user));
+
+        let mut other_detector = create_test_anomaly(Severity::High, AnomalyType::LatencySpike);
+        other_detector.detection_method = DetectionMethod::Iqr;
+        assert!(deduplicator.should_send(&other_detector));
+
+        let key = DeduplicationKey::from_event(&latency);
+        assert_eq!(key.signature, latency.signature_hash);
+        assert_eq!(key.detector, None);
+    }
+
     #[test]
     fn test_deduplication_stats() {
         let config = DeduplicationConfig::default();
@@ -382,6 +707,7 @@ mod tests {
         let stats = deduplicator.get_stats();
         assert_eq!(stats.total_signatures, 1);
         assert_eq!(stats.total_deduplicated, 2);
+        assert_eq!(stats.by_detector["zscore"], 2);
     }
 
     #[test]
@@ -390,6 +716,9 @@ mod tests {
             enabled: false,
             window_secs: 300,
             cleanup_interval_secs: 60,
+            key_fields: DeduplicationKeyConfig::default(),
+            summarize_on_expiry: false,
+            clock: Clock::system(),
         };
 
         let deduplicator = AlertDeduplicator::new(config);
@@ -401,12 +730,62 @@ mod tests {
         assert!(deduplicator.should_send(&event2));
     }
 
+    #[test]
+    fn test_summary_on_expiry() {
+        let clock = Clock::manual(Utc::now());
+        let config = DeduplicationConfig {
+            window_secs: 1,
+            summarize_on_expiry: true,
+            clock: clock.clone(),
+            ..Default::default()
+        };
+        let deduplicator = AlertDeduplicator::new(config);
+
+        let first = create_test_anomaly(Severity::High, AnomalyType::LatencySpike);
+        let duplicates: Vec<_> = (0..3)
+            .map(|_| create_test_anomaly(Severity::High, AnomalyType::LatencySpike))
+            .collect();
+        assert!(deduplicator.should_send(&first));
+        for duplicate in &duplicates {
+            assert!(!deduplicator.should_send(duplicate));
+        }
+        // A window without duplicates produces no summary
+        let single = create_test_anomaly(Severity::Low, AnomalyType::CostAnomaly);
+        assert!(deduplicator.should_send(&single));
+
+        assert!(deduplicator.take_summaries().is_empty());
+        clock.advance(chrono::Duration::seconds(2));
+        deduplicator.cleanup_expired();
+
+        let summaries = deduplicator.take_summaries();
+        assert_eq!(summaries.len(), 1);
+        let summary = &summaries[0];
+        assert_eq!(summary.occurrences, 4);
+        assert_eq!(summary.first_alert.alert_id, first.alert_id);
+        assert!(summary.first_seen <= summary.last_seen);
+        assert_eq!(summary.suppressed_alert_ids.len(), 3);
+
+        let alert = summary.to_alert(clock.now());
+        assert_ne!(alert.alert_id, first.alert_id);
+        assert_eq!(alert.timestamp, clock.now());
+        assert_eq!(
+            alert.related_alerts,
+            duplicates.iter().map(|d| d.alert_id).collect::<Vec<_>>()
+        );
+        assert_eq!(alert.context.additional["dedup_occurrences"], "4");
+        assert!(deduplicator.take_summaries().is_empty());
+    }
+
     #[test]
     fn test_cleanup_expired() {
+        let clock = Clock::manual(Utc::now());
         let config = DeduplicationConfig {
             enabled: true,
             window_secs: 1, // 1 second window
             cleanup_interval_secs: 60,
+            key_fields: DeduplicationKeyConfig::default(),
+            summarize_on_expiry: false,
+            clock: clock.clone(),
         };
 
         let deduplicator = AlertDeduplicator::new(config);
@@ -415,10 +794,59 @@ mod tests {
         deduplicator.should_send(&event);
         assert_eq!(deduplicator.entry_count(), 1);
 
-        // Wait for expiration
-        std::thread::sleep(std::time::Duration::from_secs(2));
+        // Still inside the window
+        clock.advance(chrono::Duration::seconds(1));
+        deduplicator.cleanup_expired();
+        assert_eq!(deduplicator.entry_count(), 1);
 
+        clock.advance(chrono::Duration::seconds(1));
         deduplicator.cleanup_expired();
         assert_eq!(deduplicator.entry_count(), 0);
     }
+
+    #[test]
+    fn test_shed_closes_idle_windows() {
+        let clock = Clock::manual(Utc::now());
+        let deduplicator = AlertDeduplicator::new(DeduplicationConfig {
+            summarize_on_expiry: true,
+            clock: clock.clone(),
+            ..Default::default()
+        });
+        let idle = create_test_anomaly(Severity::High, AnomalyType::LatencySpike);
+        let active = create_test_anomaly(Severity::Low, AnomalyType::CostAnomaly);
+        assert!(deduplicator.should_send(&idle));
+        assert!(!deduplicator.should_send(&idle));
+        clock.advance(chrono::Duration::seconds(10));
+        assert!(deduplicator.should_send(&active));
+
+        let usage = deduplicator.memory_usage();
+        let freed = deduplicator.shed(1);
+        assert!(freed > 0);
+        assert_eq!(deduplicator.memory_usage(), usage - freed);
+        assert_eq!(deduplicator.entry_count(), 1);
+
+        // The closed window is summarized and its signature alerts again
+        assert_eq!(deduplicator.take_summaries().len(), 1);
+        assert!(deduplicator.should_send(&idle));
+        assert!(!deduplicator.should_send(&active));
+    }
+
+    #[test]
+    fn test_snapshot_restore() {
+        let deduplicator = AlertDeduplicator::new(DeduplicationConfig::default());
+        let event = create_test_anomaly(Severity::High, AnomalyType::LatencySpike);
+        assert!(deduplicator.should_send(&event));
+        assert!(!deduplicator.should_send(&event));
+
+        let snapshot: DeduplicationSnapshot =
+            serde_json::from_value(serde_json::to_value(deduplicator.snapshot()).unwrap())
+                .unwrap();
+        assert_eq!(snapshot.len(), 1);
+
+        // The restored window keeps suppressing and counting duplicates
+        let restored = AlertDeduplicator::new(DeduplicationConfig::default());
+        restored.restore(snapshot);
+        assert!(!restored.should_send(&event));
+        assert_eq!(restored.get_stats().total_deduplicated, 2);
+    }
 }
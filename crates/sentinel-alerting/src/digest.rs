@@ -0,0 +1,353 @@
+//! Scheduled digest delivery.
+//!
+//! Digests configured under `alerting.digests` are sent daily or weekly at
+//! a fixed hour (UTC) by email (`email` feature) and/or to a Slack incoming
+//! webhook. [`DigestScheduler`] tracks when each digest is next due; the
+//! caller builds the report for the period a run covers and hands the
+//! rendered subject and body to [`DigestSender::send`].
+
+use crate::http::HttpClientConfig;
+use chrono::{DateTime, Datelike, Duration, NaiveDate, Utc};
+use llm_sentinel_core::{
+    config::{DigestConfig, DigestSchedule},
+    Error, Result,
+};
+use reqwest::Client;
+use std::sync::Mutex;
+use tracing::{error, info};
+
+/// Sends one configured digest
+pub struct DigestSender {
+    config: DigestConfig,
+    client: Client,
+    #[cfg(feature = "email")]
+    email: Option<EmailChannel>,
+}
+
+#[cfg(feature = "email")]
+struct EmailChannel {
+    transport: lettre::AsyncSmtpTransport<lettre::Tokio1Executor>,
+    from: lettre::message::Mailbox,
+    to: Vec<lettre::message::Mailbox>,
+}
+
+impl DigestSender {
+    /// Create a sender for a configured digest
+    pub fn new(config: DigestConfig) -> Result<Self> {
+        if config.email.is_none() && config.slack_webhook_url.is_none() {
+            return Err(Error::config(format!(
+                "Digest {} has neither email nor slack_webhook_url",
+                config.name
+            )));
+        }
+
+        let client = HttpClientConfig::default()
+            .builder()?
+            .timeout(std::time::Duration::from_secs(30))
+            .build()
+            .map_err(|e| Error::config(format!("Failed to create HTTP client: {}", e)))?;
+
+        #[cfg(feature = "email")]
+        let email = config.email.as_ref().map(EmailChannel::new).transpose()?;
+        #[cfg(not(feature = "email"))]
+        if config.email.is_some() {
+            return Err(Error::config("Digest email requires building with the `email` feature"));
+        }
+
+        Ok(Self {
+            config,
+            client,
+            #[cfg(feature = "email")]
+            email,
+        })
+    }
+
+    /// Digest configuration
+    pub fn config(&self) -> &DigestConfig {
+        &self.config
+    }
+
+    /// First run strictly after `after`
+    pub fn next_run(&self, after: DateTime<Utc>) -> DateTime<Utc> {
+        let at_hour = |date: NaiveDate| {
+            date.and_hms_opt(self.config.hour.min(23), 0, 0)
+                .unwrap_or_default()
+                .and_utc()
+        };
+        match self.config.schedule {
+            DigestSchedule::Daily => {
+                let run = at_hour(after.date_naive());
+                if run > after {
+                    run
+                } else {
+                    run + Duration::days(1)
+                }
+            }
+            DigestSchedule::Weekly => {
+                let days_ahead = (self.config.weekday.num_days_from_monday() + 7
+                    - after.weekday().num_days_from_monday())
+                    % 7;
+                let run = at_hour(after.date_naive() + Duration::days(days_ahead as i64));
+                if run > after {
+                    run
+                } else {
+                    run + Duration::weeks(1)
+                }
+            }
+        }
+    }
+
+    /// Period covered by the run at `at`, as (start, end)
+    pub fn period(&self, at: DateTime<Utc>) -> (DateTime<Utc>, DateTime<Utc>) {
+        let length = match self.config.schedule {
+            DigestSchedule::Daily => Duration::days(1),
+            DigestSchedule::Weekly => Duration::weeks(1),
+        };
+        (at - length, at)
+    }
+
+    /// Send a rendered digest to every configured channel
+    ///
+    /// Every channel is attempted; the first failure is returned.
+    pub async fn send(&self, subject: &str, body: &str) -> Result<()> {
+        let mut result = Ok(());
+
+        #[cfg(feature = "email")]
+        if let Some(email) = &self.email {
+            if let Err(e) = email.send(subject, body).await {
+                error!(digest = %self.config.name, "Failed to email digest: {}", e);
+                result = result.and(Err(e));
+            }
+        }
+
+        if let Some(url) = &self.config.slack_webhook_url {
+            if let Err(e) = self.post_slack(url, subject, body).await {
+                error!(digest = %self.config.name, "Failed to post digest to Slack: {}", e);
+                result = result.and(Err(e));
+            }
+        }
+
+        let status = if result.is_ok() { "sent" } else { "failed" };
+        metrics::counter!(
+            "sentinel_digests_total",
+            "digest" => self.config.name.clone(),
+            "status" => status
+        )
+        .increment(1);
+        if result.is_ok() {
+            info!(digest = %self.config.name, "Digest sent");
+        }
+        result
+    }
+
+    async fn post_slack(&self, url: &str, subject: &str, body: &str) -> Result<()> {
+        let payload = serde_json::json!({
+            "text": format!("*{}*\n```{}```", subject, body),
+        });
+        let response = self
+            .client
+            .post(url)
+            .json(&payload)
+            .send()
+            .await
+            .map_err(|e| Error::connection(format!("Slack request failed: {}", e.without_url())))?;
+        if !response.status().is_success() {
+            return Err(Error::connection(format!("Slack returned {}", response.status())));
+        }
+        Ok(())
+    }
+}
+
+impl std::fmt::Debug for DigestSender {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("DigestSender")
+            .field("config", &self.config)
+            .finish_non_exhaustive()
+    }
+}
+
+#[cfg(feature = "email")]
+impl EmailChannel {
+    fn new(config: &llm_sentinel_core::config::DigestEmailConfig) -> Result<Self> {
+        let mailbox = |address: &str| {
+            address
+                .parse::<lettre::message::Mailbox>()
+                .map_err(|e| Error::config(format!("Invalid email address {}: {}", address, e)))
+        };
+        let transport =
+            lettre::AsyncSmtpTransport::<lettre::Tokio1Executor>::from_url(&config.smtp_url)
+                .map_err(|e| Error::config(format!("Invalid SMTP URL: {}", e)))?
+                .build();
+        Ok(Self {
+            transport,
+            from: mailbox(&config.from)?,
+            to: config
+                .to
+                .iter()
+                .map(|to| mailbox(to))
+                .collect::<Result<_>>()?,
+        })
+    }
+
+    async fn send(&self, subject: &str, body: &str) -> Result<()> {
+        use lettre::AsyncTransport;
+
+        let mut builder = lettre::Message::builder()
+            .from(self.from.clone())
+            .subject(subject)
+            .header(lettre::message::header::ContentType::TEXT_PLAIN);
+        for to in &self.to {
+            builder = builder.to(to.clone());
+        }
+        let message = builder
+            .body(body.to_string())
+            .map_err(|e| Error::internal(format!("Failed to build digest email: {}", e)))?;
+        self.transport
+            .send(message)
+            .await
+            .map_err(|e| Error::connection(format!("SMTP delivery failed: {}", e)))?;
+        Ok(())
+    }
+}
+
+/// When each digest is next due
+#[derive(Debug)]
+pub struct DigestScheduler {
+    digests: Vec<(DigestSender, Mutex<DateTime<Utc>>)>,
+}
+
+impl DigestScheduler {
+    /// Schedule digests, each first due at its next run after `now`
+    pub fn new(configs: Vec<DigestConfig>, now: DateTime<Utc>) -> Result<Self> {
+        let digests = configs
+            .into_iter()
+            .map(|config| {
+                let sender = DigestSender::new(config)?;
+                let next = sender.next_run(now);
+                info!(digest = %sender.config.name, next = %next, "Digest scheduled");
+                Ok((sender, Mutex::new(next)))
+            })
+            .collect::<Result<_>>()?;
+        Ok(Self { digests })
+    }
+
+    /// Digests due at `now` with the run time they are due for
+    ///
+    /// Each is rescheduled to its next run after `now`, so runs missed
+    /// while not checking are sent once, not once per missed run.
+    pub fn take_due(&self, now: DateTime<Utc>) -> Vec<(&DigestSender, DateTime<Utc>)> {
+        self.digests
+            .iter()
+            .filter_map(|(sender, next)| {
+                let mut next = next.lock().unwrap();
+                if *next > now {
+                    return None;
+                }
+                let due = *next;
+                *next = sender.next_run(now);
+                Some((sender, due))
+            })
+            .collect()
+    }
+
+    /// Whether any digest is configured
+    pub fn is_empty(&self) -> bool {
+        self.digests.is_empty()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::{TimeZone, Weekday};
+    use wiremock::{matchers::*, Mock, MockServer, ResponseTemplate};
+
+    fn config(schedule: DigestSchedule) -> DigestConfig {
+        slack_config(schedule, "https://hooks.slack.test/services/T/B/X")
+    }
+
+    fn slack_config(schedule: DigestSchedule, slack: &str) -> DigestConfig {
+        DigestConfig {
+            name: "ops".to_string(),
+            schedule,
+            hour: 8,
+            weekday: Weekday::Mon,
+            top: 5,
+            subject: "{name}".to_string(),
+            template: None,
+            email: None,
+            slack_webhook_url: Some(slack.to_string()),
+        }
+    }
+
+    #[test]
+    fn test_next_run() {
+        // Wednesday 2024-01-03 10:00 UTC
+        let now = Utc.with_ymd_and_hms(2024, 1, 3, 10, 0, 0).unwrap();
+
+        let daily = DigestSender::new(config(DigestSchedule::Daily)).unwrap();
+        assert_eq!(
+            daily.next_run(now),
+            Utc.with_ymd_and_hms(2024, 1, 4, 8, 0, 0).unwrap()
+        );
+        let at_seven = Utc.with_ymd_and_hms(2024, 1, 3, 7, 0, 0).unwrap();
+        assert_eq!(
+            daily.next_run(at_seven),
+            Utc.with_ymd_and_hms(2024, 1, 3, 8, 0, 0).unwrap()
+        );
+
+        let weekly = DigestSender::new(config(DigestSchedule::Weekly)).unwrap();
+        let monday = Utc.with_ymd_and_hms(2024, 1, 8, 8, 0, 0).unwrap();
+        assert_eq!(weekly.next_run(now), monday);
+        assert_eq!(
+            weekly.next_run(monday),
+            Utc.with_ymd_and_hms(2024, 1, 15, 8, 0, 0).unwrap()
+        );
+        assert_eq!(
+            weekly.period(monday).0,
+            Utc.with_ymd_and_hms(2024, 1, 1, 8, 0, 0).unwrap()
+        );
+    }
+
+    #[test]
+    fn test_scheduler_sends_missed_runs_once() {
+        let now = Utc.with_ymd_and_hms(2024, 1, 3, 10, 0, 0).unwrap();
+        let scheduler = DigestScheduler::new(vec![config(DigestSchedule::Daily)], now).unwrap();
+        assert!(scheduler.take_due(now).is_empty());
+
+        // Three days later only one run is due
+        let later = now + Duration::days(3);
+        let due = scheduler.take_due(later);
+        assert_eq!(due.len(), 1);
+        assert_eq!(due[0].1, Utc.with_ymd_and_hms(2024, 1, 4, 8, 0, 0).unwrap());
+        assert!(scheduler.take_due(later).is_empty());
+    }
+
+    #[test]
+    fn test_digest_requires_channel() {
+        let mut config = config(DigestSchedule::Daily);
+        config.slack_webhook_url = None;
+        assert!(DigestSender::new(config).is_err());
+    }
+
+    #[tokio::test]
+    async fn test_slack_digest() {
+        let server = MockServer::start().await;
+        Mock::given(method("POST"))
+            .and(path("/hook"))
+            .and(body_partial_json(serde_json::json!({
+                "text": "*Daily digest*\n```4 anomalies```"
+            })))
+            .respond_with(ResponseTemplate::new(200))
+            .expect(1)
+            .mount(&server)
+            .await;
+
+        let sender = DigestSender::new(slack_config(
+            DigestSchedule::Daily,
+            &format!("{}/hook", server.uri()),
+        ))
+        .unwrap();
+        sender.send("Daily digest", "4 anomalies").await.unwrap();
+    }
+}
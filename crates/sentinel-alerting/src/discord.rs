@@ -0,0 +1,417 @@
+//! Discord webhook alert delivery.
+//!
+//! Posts alerts as rich embeds colored by severity. Each severity can be
+//! routed to its own webhook, posted into an existing thread, or opened as
+//! a new post in a forum channel, and can mention a role or user.
+
+use crate::{http::HttpClientConfig, Alerter};
+use async_trait::async_trait;
+use llm_sentinel_core::{events::AnomalyEvent, types::Severity, Error, Result};
+use reqwest::Client;
+use serde::{Deserialize, Serialize};
+use std::{collections::BTreeMap, time::Duration};
+use tracing::{debug, error, info};
+
+/// Webhook, thread and mention for a severity
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct DiscordTarget {
+    /// Webhook URL (defaults to the alerter's webhook)
+    #[serde(default)]
+    pub webhook_url: Option<String>,
+    /// Existing thread to post into
+    #[serde(default)]
+    pub thread_id: Option<String>,
+    /// Mention prepended to the message (e.g. `<@&role_id>`)
+    #[serde(default)]
+    pub mention: Option<String>,
+}
+
+/// Discord alerter configuration
+#[derive(Clone, Serialize, Deserialize)]
+pub struct DiscordConfig {
+    /// Default webhook URL
+    pub webhook_url: String,
+    /// Default thread to post into
+    pub thread_id: Option<String>,
+    /// Open a forum post per alert instead (forum channel webhooks only)
+    pub forum_posts: bool,
+    /// Per-severity webhook/thread/mention overrides
+    pub routes: BTreeMap<Severity, DiscordTarget>,
+    /// Minimum severity to deliver
+    pub min_severity: Option<Severity>,
+    /// Display name of the webhook
+    pub username: String,
+    /// Request timeout (seconds)
+    pub timeout_secs: u64,
+    /// Proxy, CA and DNS settings
+    #[serde(default)]
+    pub http: HttpClientConfig,
+}
+
+impl Default for DiscordConfig {
+    fn default() -> Self {
+        Self {
+            webhook_url: String::new(),
+            thread_id: None,
+            forum_posts: false,
+            routes: BTreeMap::new(),
+            min_severity: None,
+            username: "LLM-Sentinel".to_string(),
+            timeout_secs: 10,
+            http: HttpClientConfig::default(),
+        }
+    }
+}
+
+impl std::fmt::Debug for DiscordConfig {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        // Webhook URLs embed their token
+        f.debug_struct("DiscordConfig")
+            .field("thread_id", &self.thread_id)
+            .field("forum_posts", &self.forum_posts)
+            .field("routes", &self.routes.keys().collect::<Vec<_>>())
+            .field("min_severity", &self.min_severity)
+            .field("username", &self.username)
+            .field("timeout_secs", &self.timeout_secs)
+            .field("http", &self.http)
+            .finish_non_exhaustive()
+    }
+}
+
+/// Discord webhook alerter
+pub struct DiscordAlerter {
+    client: Client,
+    config: DiscordConfig,
+}
+
+impl std::fmt::Debug for DiscordAlerter {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("DiscordAlerter")
+            .field("config", &self.config)
+            .finish_non_exhaustive()
+    }
+}
+
+impl DiscordAlerter {
+    /// Create a new Discord alerter
+    pub fn new(config: DiscordConfig) -> Result<Self> {
+        if config.webhook_url.is_empty() {
+            return Err(Error::config("Discord webhook URL cannot be empty"));
+        }
+        if config.forum_posts && config.thread_id.is_some() {
+            return Err(Error::config(
+                "Discord forum_posts and thread_id are mutually exclusive",
+            ));
+        }
+
+        info!("Creating Discord alerter");
+
+        let client = config
+            .http
+            .builder()?
+            .timeout(Duration::from_secs(config.timeout_secs))
+            .build()
+            .map_err(|e| Error::config(format!("Failed to create HTTP client: {}", e)))?;
+
+        Ok(Self { client, config })
+    }
+
+    /// Webhook URL, thread and mention for a severity
+    fn target(&self, severity: Severity) -> (String, Option<String>, Option<String>) {
+        let route = self.config.routes.get(&severity);
+        (
+            route
+                .and_then(|r| r.webhook_url.clone())
+                .unwrap_or_else(|| self.config.webhook_url.clone()),
+            route
+                .and_then(|r| r.thread_id.clone())
+                .or_else(|| self.config.thread_id.clone()),
+            route.and_then(|r| r.mention.clone()),
+        )
+    }
+
+    /// Build the webhook execution body
+    fn build_message(&self, alert: &AnomalyEvent, mention: Option<&str>) -> serde_json::Value {
+        let title = format!(
+            "{} {} on {}/{}",
+            alert.severity.to_string().to_uppercase(),
+            alert.anomaly_type,
+            alert.service_name,
+            alert.model
+        );
+
+        let mut fields = vec![
+            serde_json::json!({ "name": "Service", "value": alert.service_name.as_str(), "inline": true }),
+            serde_json::json!({ "name": "Model", "value": alert.model.as_str(), "inline": true }),
+            serde_json::json!({ "name": "Confidence", "value": format!("{:.0}%", alert.confidence * 100.0), "inline": true }),
+            serde_json::json!({
+                "name": alert.details.metric,
+                "value": format!("{:.2} (baseline {:.2})", alert.details.value, alert.details.baseline),
+                "inline": false
+            }),
+        ];
+        if !alert.trace_links.is_empty() {
+            let links: Vec<String> = alert
+                .trace_links
+                .iter()
+                .map(|(name, url)| format!("[{}]({})", name, url))
+                .collect();
+            fields.push(
+                serde_json::json!({ "name": "Trace", "value": links.join(" · "), "inline": false }),
+            );
+        }
+        if let Some(root_cause) = &alert.root_cause {
+            fields.push(
+                serde_json::json!({ "name": "Root cause", "value": root_cause, "inline": false }),
+            );
+        }
+
+        let mut message = serde_json::json!({
+            "username": self.config.username,
+            "allowed_mentions": { "parse": ["roles", "users"] },
+            "embeds": [{
+                "title": title,
+                "color": severity_color(alert.severity),
+                "fields": fields,
+                "timestamp": alert.timestamp.to_rfc3339(),
+                "footer": { "text": format!("Alert {}", alert.alert_id) },
+            }],
+        });
+        if let Some(url) = alert.trace_links.values().next() {
+            message["embeds"][0]["url"] = url.as_str().into();
+        }
+        if let Some(mention) = mention {
+            message["content"] = mention.into();
+        }
+        if self.config.forum_posts {
+            // Forum post titles are limited to 100 characters
+            message["thread_name"] = title.chars().take(100).collect::<String>().into();
+        }
+        message
+    }
+}
+
+#[async_trait]
+impl Alerter for DiscordAlerter {
+    async fn send(&self, alert: &AnomalyEvent) -> Result<()> {
+        if self
+            .config
+            .min_severity
+            .is_some_and(|min| alert.severity < min)
+        {
+            debug!(alert_id = %alert.alert_id, "Alert below Discord minimum severity");
+            return Ok(());
+        }
+
+        let (url, thread_id, mention) = self.target(alert.severity);
+        let mut request = self
+            .client
+            .post(&url)
+            .query(&[("wait", "true")])
+            .json(&self.build_message(alert, mention.as_deref()));
+        if let Some(thread_id) = &thread_id {
+            request = request.query(&[("thread_id", thread_id)]);
+        }
+
+        let response = request.send().await.map_err(|e| {
+            metrics::counter!("sentinel_discord_failures_total").increment(1);
+            Error::alerting(format!("Discord request failed: {}", e.without_url()))
+        })?;
+
+        let status = response.status();
+        if !status.is_success() {
+            let body = response.text().await.unwrap_or_default();
+            error!(status = %status, body = %body, "Discord delivery failed");
+            metrics::counter!("sentinel_discord_failures_total").increment(1);
+            return Err(Error::alerting(format!(
+                "Discord delivery failed with status {}: {}",
+                status, body
+            )));
+        }
+
+        debug!(alert_id = %alert.alert_id, "Alert sent to Discord");
+        metrics::counter!(
+            "sentinel_discord_messages_total",
+            "severity" => alert.severity.to_string()
+        )
+        .increment(1);
+
+        Ok(())
+    }
+
+    async fn health_check(&self) -> Result<()> {
+        // GET on a webhook URL returns the webhook object
+        self.client
+            .get(&self.config.webhook_url)
+            .send()
+            .await
+            .and_then(|r| r.error_for_status())
+            .map_err(|e| {
+                Error::connection(format!("Discord health check failed: {}", e.without_url()))
+            })?;
+
+        Ok(())
+    }
+
+    fn name(&self) -> &str {
+        "Discord"
+    }
+}
+
+/// Embed color for a severity
+fn severity_color(severity: Severity) -> u32 {
+    match severity {
+        Severity::Critical => 0xE0_1E_5A,
+        Severity::High => 0xF2_99_4A,
+        Severity::Medium => 0xF2_C9_4C,
+        Severity::Low => 0x2D_9C_DB,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use llm_sentinel_core::types::AnomalyType;
+    use llm_sentinel_fixtures::anomaly;
+
+    use wiremock::{
+        matchers::{body_partial_json, method, path, query_param},
+        Mock, MockServer, ResponseTemplate,
+    };
+
+    fn create_test_config(server: &str) -> DiscordConfig {
+        DiscordConfig {
+            webhook_url: format!("{}/api/webhooks/1/default", server),
+            routes: BTreeMap::from([(
+                Severity::Critical,
+                DiscordTarget {
+                    webhook_url: Some(format!("{}/api/webhooks/2/oncall", server)),
+                    thread_id: Some("99".to_string()),
+                    mention: Some("<@&1234>".to_string()),
+                },
+            )]),
+            min_severity: Some(Severity::Medium),
+            ..Default::default()
+        }
+    }
+
+    fn create_test_anomaly(severity: Severity) -> AnomalyEvent {
+        anomaly()
+            .severity(severity)
+            .anomaly_type(AnomalyType::CostAnomaly)
+            .service("checkout")
+            .confidence(0.95)
+            .metric("cost_usd")
+            .value(5.0)
+            .baseline(1.0)
+            .deviation_sigma(Some(4.0))
+            .time_window("rolling_window")
+            .build()
+    }
+
+    #[test]
+    fn test_config_validation() {
+        assert!(DiscordAlerter::new(DiscordConfig::default()).is_err());
+
+        let config = DiscordConfig {
+            webhook_url: "https://discord.com/api/webhooks/1/secret".to_string(),
+            thread_id: Some("1".to_string()),
+            forum_posts: true,
+            ..Default::default()
+        };
+        assert!(!format!("{:?}", config).contains("secret"));
+        assert!(DiscordAlerter::new(config).is_err());
+    }
+
+    #[test]
+    fn test_build_message() {
+        let alerter = DiscordAlerter::new(DiscordConfig {
+            forum_posts: true,
+            ..create_test_config("https://discord.com")
+        })
+        .unwrap();
+        let message = alerter.build_message(&create_test_anomaly(Severity::High), Some("<@1>"));
+
+        assert_eq!(message["content"], "<@1>");
+        assert_eq!(
+            message["thread_name"],
+            "HIGH cost_anomaly on checkout/gpt-4"
+        );
+        assert_eq!(message["embeds"][0]["color"], 0xF2994A);
+        assert_eq!(message["embeds"][0]["fields"][3]["name"], "cost_usd");
+        assert!(message["embeds"][0].get("url").is_none());
+
+        let mut alert = create_test_anomaly(Severity::High);
+        alert.trace_links.insert(
+            "jaeger".to_string(),
+            "https://jaeger.local/trace/abc".to_string(),
+        );
+        let message = alerter.build_message(&alert, None);
+        assert_eq!(
+            message["embeds"][0]["url"],
+            "https://jaeger.local/trace/abc"
+        );
+        assert_eq!(
+            message["embeds"][0]["fields"][4]["value"],
+            "[jaeger](https://jaeger.local/trace/abc)"
+        );
+    }
+
+    #[tokio::test]
+    async fn test_severity_routing() {
+        let mock_server = MockServer::start().await;
+
+        Mock::given(method("POST"))
+            .and(path("/api/webhooks/2/oncall"))
+            .and(query_param("thread_id", "99"))
+            .and(body_partial_json(
+                serde_json::json!({ "content": "<@&1234>" }),
+            ))
+            .respond_with(
+                ResponseTemplate::new(200).set_body_json(serde_json::json!({ "id": "1" })),
+            )
+            .expect(1)
+            .mount(&mock_server)
+            .await;
+        Mock::given(method("POST"))
+            .and(path("/api/webhooks/1/default"))
+            .respond_with(
+                ResponseTemplate::new(200).set_body_json(serde_json::json!({ "id": "2" })),
+            )
+            .expect(1)
+            .mount(&mock_server)
+            .await;
+
+        let alerter = DiscordAlerter::new(create_test_config(&mock_server.uri())).unwrap();
+        alerter
+            .send(&create_test_anomaly(Severity::Critical))
+            .await
+            .unwrap();
+        alerter
+            .send(&create_test_anomaly(Severity::High))
+            .await
+            .unwrap();
+        // Below the minimum severity: not sent
+        alerter
+            .send(&create_test_anomaly(Severity::Low))
+            .await
+            .unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_send_failure() {
+        let mock_server = MockServer::start().await;
+
+        Mock::given(method("POST"))
+            .respond_with(ResponseTemplate::new(404).set_body_string("Unknown Webhook"))
+            .mount(&mock_server)
+            .await;
+
+        let alerter = DiscordAlerter::new(create_test_config(&mock_server.uri())).unwrap();
+        let err = alerter
+            .send(&create_test_anomaly(Severity::High))
+            .await
+            .unwrap_err();
+        assert!(err.to_string().contains("Unknown Webhook"));
+    }
+}
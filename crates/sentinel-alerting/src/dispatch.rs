@@ -0,0 +1,493 @@
+//! Queued alert dispatch.
+//!
+//! Sending alerts inline means a slow webhook stalls the detection loop
+//! behind it. [`AlertDispatcher`] instead gives every alerter a bounded
+//! queue drained by its own task, which sends queued alerts in batches
+//! through [`Alerter::send_batch`]. Once every routed alerter has handled an
+//! alert, its deliveries are handed to a completion handler as one
+//! [`AlertDispatched`].
+//!
+//! Enqueueing lower-severity alerts never waits: an alert arriving at a full
+//! queue is recorded as a failed delivery and counted in
+//! `sentinel_alert_dispatch_overflow_total`. High and Critical alerts take a
+//! separate priority queue that each task drains first; when it is full they
+//! wait for room rather than being dropped, counted in
+//! `sentinel_alert_dispatch_priority_waits_total`. Queue depth is exported
+//! as the `sentinel_alert_queue_depth` gauge.
+
+use crate::{deliver_batch, AlertMetadata, Alerter};
+use futures::{future::BoxFuture, FutureExt};
+use llm_sentinel_core::{bus::AlertDispatched, config::AlertDispatchConfig, events::AnomalyEvent};
+use std::collections::HashMap;
+use std::future::Future;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+use tokio::sync::{mpsc, watch};
+use tokio::task::JoinHandle;
+use tracing::{debug, warn};
+
+type CompletionHandler = Arc<dyn Fn(AlertDispatched) -> BoxFuture<'static, ()> + Send + Sync>;
+
+/// Alert waiting for its routed alerters
+#[derive(Debug)]
+struct PendingAlert {
+    alert: AnomalyEvent,
+    remaining: AtomicUsize,
+    deliveries: Mutex<Vec<AlertMetadata>>,
+}
+
+/// Queues of one alerter
+struct Lanes {
+    priority: mpsc::Sender<Arc<PendingAlert>>,
+    normal: mpsc::Sender<Arc<PendingAlert>>,
+}
+
+impl Lanes {
+    fn depth(&self) -> usize {
+        let depth = |queue: &mpsc::Sender<_>| queue.max_capacity() - queue.capacity();
+        depth(&self.priority) + depth(&self.normal)
+    }
+}
+
+/// Sends alerts from per-alerter queues
+pub struct AlertDispatcher {
+    queues: HashMap<String, Lanes>,
+    complete: CompletionHandler,
+    outstanding: Arc<watch::Sender<usize>>,
+    handles: Vec<JoinHandle<()>>,
+}
+
+impl AlertDispatcher {
+    /// Spawn one pair of queues and sending task per alerter
+    ///
+    /// Each task sends up to `batch_size` alerts at a time, waiting at most
+    /// `batch_timeout` after the first for more to arrive. `on_dispatched`
+    /// runs on the sending task once all of an alert's deliveries are known.
+    pub fn spawn<F, Fut>(
+        alerters: &[Arc<dyn Alerter>],
+        config: &AlertDispatchConfig,
+        batch_size: usize,
+        batch_timeout: Duration,
+        on_dispatched: F,
+    ) -> Self
+    where
+        F: Fn(AlertDispatched) -> Fut + Send + Sync + 'static,
+        Fut: Future<Output = ()> + Send + 'static,
+    {
+        let on_dispatched = Arc::new(on_dispatched);
+        let complete: CompletionHandler = Arc::new(move |dispatched| {
+            let on_dispatched = Arc::clone(&on_dispatched);
+            async move { on_dispatched(dispatched).await }.boxed()
+        });
+        let outstanding = Arc::new(watch::Sender::new(0));
+        let batch_size = batch_size.max(1);
+
+        let mut queues = HashMap::new();
+        let mut handles = Vec::new();
+        for alerter in alerters {
+            let (priority, priority_rx) = mpsc::channel(config.priority_queue_capacity.max(1));
+            let (normal, normal_rx) = mpsc::channel(config.queue_capacity.max(1));
+            queues.insert(alerter.name().to_string(), Lanes { priority, normal });
+            handles.push(tokio::spawn(Self::run(
+                Arc::clone(alerter),
+                priority_rx,
+                normal_rx,
+                batch_size,
+                batch_timeout,
+                Arc::clone(&complete),
+                Arc::clone(&outstanding),
+            )));
+        }
+
+        Self {
+            queues,
+            complete,
+            outstanding,
+            handles,
+        }
+    }
+
+    async fn run(
+        alerter: Arc<dyn Alerter>,
+        mut priority: mpsc::Receiver<Arc<PendingAlert>>,
+        mut normal: mpsc::Receiver<Arc<PendingAlert>>,
+        batch_size: usize,
+        batch_timeout: Duration,
+        complete: CompletionHandler,
+        outstanding: Arc<watch::Sender<usize>>,
+    ) {
+        while let Some(first) = Self::next(&mut priority, &mut normal).await {
+            let mut batch = vec![first];
+            let deadline = tokio::time::Instant::now() + batch_timeout;
+            while batch.len() < batch_size {
+                let next = Self::next(&mut priority, &mut normal);
+                match tokio::time::timeout_at(deadline, next).await {
+                    Ok(Some(pending)) => batch.push(pending),
+                    _ => break,
+                }
+            }
+            queue_depth(alerter.name(), priority.len() + normal.len());
+
+            let alerts: Vec<AnomalyEvent> = batch.iter().map(|p| p.alert.clone()).collect();
+            metrics::histogram!(
+                "sentinel_alert_batch_size",
+                "alerter" => alerter.name().to_string()
+            )
+            .record(alerts.len() as f64);
+            let deliveries = deliver_batch(alerter.as_ref(), &alerts).await;
+            for (pending, delivery) in batch.into_iter().zip(deliveries) {
+                Self::record(&pending, delivery, &complete, &outstanding).await;
+            }
+        }
+        debug!(alerter = alerter.name(), "Alert dispatch task stopped");
+    }
+
+    /// Next queued alert, taking priority alerts first
+    async fn next(
+        priority: &mut mpsc::Receiver<Arc<PendingAlert>>,
+        normal: &mut mpsc::Receiver<Arc<PendingAlert>>,
+    ) -> Option<Arc<PendingAlert>> {
+        tokio::select! {
+            biased;
+            Some(pending) = priority.recv() => Some(pending),
+            Some(pending) = normal.recv() => Some(pending),
+            else => None,
+        }
+    }
+
+    /// Record one delivery, completing the alert after its last
+    async fn record(
+        pending: &PendingAlert,
+        delivery: AlertMetadata,
+        complete: &CompletionHandler,
+        outstanding: &watch::Sender<usize>,
+    ) {
+        pending.deliveries.lock().unwrap().push(delivery);
+        Self::release(pending, complete, outstanding).await;
+    }
+
+    /// Release one count of an alert, completing it after the last
+    async fn release(
+        pending: &PendingAlert,
+        complete: &CompletionHandler,
+        outstanding: &watch::Sender<usize>,
+    ) {
+        if pending.remaining.fetch_sub(1, Ordering::AcqRel) != 1 {
+            return;
+        }
+        let deliveries = std::mem::take(&mut *pending.deliveries.lock().unwrap());
+        complete(AlertDispatched {
+            alert: pending.alert.clone(),
+            deliveries,
+        })
+        .await;
+        outstanding.send_modify(|count| *count -= 1);
+    }
+
+    /// Queue an alert for the named alerters
+    ///
+    /// Returns without waiting for delivery. Priority alerts wait for room
+    /// in a full priority queue; other alerts get a failed delivery from
+    /// alerters whose queue is full.
+    pub async fn dispatch(&self, alert: AnomalyEvent, alerters: &[String]) {
+        let queues: Vec<_> = self
+            .queues
+            .iter()
+            .filter(|(name, _)| alerters.contains(name))
+            .collect();
+        let pending = Arc::new(PendingAlert {
+            alert,
+            // One extra count is released below, so the alert cannot
+            // complete while it is still being queued
+            remaining: AtomicUsize::new(queues.len() + 1),
+            deliveries: Mutex::new(Vec::with_capacity(queues.len())),
+        });
+        self.outstanding.send_modify(|count| *count += 1);
+
+        let priority = pending.alert.severity.is_priority();
+        for (name, lanes) in queues {
+            let queue = if priority {
+                &lanes.priority
+            } else {
+                &lanes.normal
+            };
+            let sent = match queue.try_send(Arc::clone(&pending)) {
+                Err(mpsc::error::TrySendError::Full(waiting)) if priority => {
+                    debug!(
+                        alerter = %name,
+                        alert_id = %pending.alert.alert_id,
+                        "Priority dispatch queue full, waiting"
+                    );
+                    metrics::counter!(
+                        "sentinel_alert_dispatch_priority_waits_total",
+                        "alerter" => name.clone()
+                    )
+                    .increment(1);
+                    queue
+                        .send(waiting)
+                        .await
+                        .map_err(|e| mpsc::error::TrySendError::Closed(e.0))
+                }
+                sent => sent,
+            };
+            match sent {
+                Ok(()) => queue_depth(name, lanes.depth()),
+                Err(e) => {
+                    let reason = match e {
+                        mpsc::error::TrySendError::Full(_) => "dispatch queue full",
+                        mpsc::error::TrySendError::Closed(_) => "dispatch queue closed",
+                    };
+                    warn!(
+                        alerter = %name,
+                        alert_id = %pending.alert.alert_id,
+                        "Dropping alert: {}", reason
+                    );
+                    metrics::counter!(
+                        "sentinel_alert_dispatch_overflow_total",
+                        "alerter" => name.clone()
+                    )
+                    .increment(1);
+                    let mut delivery = AlertMetadata::pending(&pending.alert, name.as_str());
+                    delivery.mark_failed(chrono::Utc::now(), reason);
+                    Self::record(&pending, delivery, &self.complete, &self.outstanding).await;
+                }
+            }
+        }
+
+        // Release the extra count, completing the alert if every alerter
+        // has already handled it (or none was routed)
+        Self::release(&pending, &self.complete, &self.outstanding).await;
+    }
+
+    /// Alerts queued or being sent
+    pub fn outstanding(&self) -> usize {
+        *self.outstanding.borrow()
+    }
+
+    /// Wait until every queued alert has been handled, for at most
+    /// `timeout`
+    ///
+    /// Returns whether the queues drained in time.
+    pub async fn flush(&self, timeout: Duration) -> bool {
+        let mut outstanding = self.outstanding.subscribe();
+        tokio::time::timeout(timeout, outstanding.wait_for(|count| *count == 0))
+            .await
+            .is_ok_and(|result| result.is_ok())
+    }
+}
+
+impl std::fmt::Debug for AlertDispatcher {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("AlertDispatcher")
+            .field("alerters", &self.queues.keys().collect::<Vec<_>>())
+            .field("outstanding", &self.outstanding())
+            .finish_non_exhaustive()
+    }
+}
+
+impl Drop for AlertDispatcher {
+    fn drop(&mut self) {
+        for handle in &self.handles {
+            handle.abort();
+        }
+    }
+}
+
+fn queue_depth(alerter: &str, depth: usize) {
+    metrics::gauge!("sentinel_alert_queue_depth", "alerter" => alerter.to_string())
+        .set(depth as f64);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::recording::RecordingAlerter;
+    use async_trait::async_trait;
+    use llm_sentinel_core::{types::Severity, Result};
+    use llm_sentinel_fixtures::anomaly;
+
+    fn create_test_anomaly() -> AnomalyEvent {
+        anomaly()
+            .severity(Severity::Medium)
+            .service("test-service")
+            .confidence(0.95)
+            .value(500.0)
+            .threshold(300.0)
+            .deviation_sigma(Some(4.0))
+            .time_window("5m")
+            .build()
+    }
+
+    /// Alerter that blocks until released, recording batch sizes
+    struct GatedAlerter {
+        gate: watch::Receiver<bool>,
+        batches: Mutex<Vec<usize>>,
+    }
+
+    #[async_trait]
+    impl Alerter for GatedAlerter {
+        async fn send(&self, alert: &AnomalyEvent) -> Result<()> {
+            self.send_batch(std::slice::from_ref(alert)).await
+        }
+
+        async fn send_batch(&self, alerts: &[AnomalyEvent]) -> Result<()> {
+            let _ = self.gate.clone().wait_for(|open| *open).await;
+            self.batches.lock().unwrap().push(alerts.len());
+            Ok(())
+        }
+
+        async fn health_check(&self) -> Result<()> {
+            Ok(())
+        }
+
+        fn name(&self) -> &str {
+            "gated"
+        }
+    }
+
+    fn collector() -> (
+        Arc<Mutex<Vec<AlertDispatched>>>,
+        impl Fn(AlertDispatched) -> futures::future::Ready<()> + Send + Sync + 'static,
+    ) {
+        let dispatched = Arc::new(Mutex::new(Vec::new()));
+        let sink = Arc::clone(&dispatched);
+        (dispatched, move |d| {
+            sink.lock().unwrap().push(d);
+            futures::future::ready(())
+        })
+    }
+
+    #[tokio::test]
+    async fn test_dispatch_per_alerter() {
+        let first = Arc::new(RecordingAlerter::new("first"));
+        let second = Arc::new(RecordingAlerter::new("second"));
+        let alerters: Vec<Arc<dyn Alerter>> = vec![first.clone(), second.clone()];
+        let (dispatched, on_dispatched) = collector();
+        let dispatcher = AlertDispatcher::spawn(
+            &alerters,
+            &AlertDispatchConfig {
+                queue_capacity: 10,
+                priority_queue_capacity: 10,
+            },
+            5,
+            Duration::from_millis(10),
+            on_dispatched,
+        );
+
+        let both = ["first".to_string(), "second".to_string()];
+        for _ in 0..4 {
+            dispatcher.dispatch(create_test_anomaly(), &both).await;
+        }
+        dispatcher
+            .dispatch(create_test_anomaly(), &["first".to_string()])
+            .await;
+        // Nothing routed completes at once
+        dispatcher.dispatch(create_test_anomaly(), &[]).await;
+        assert!(dispatcher.flush(Duration::from_secs(5)).await);
+
+        assert_eq!(first.count(), 5);
+        assert_eq!(second.count(), 4);
+        let dispatched = dispatched.lock().unwrap();
+        assert_eq!(dispatched.len(), 6);
+        let deliveries: Vec<usize> = dispatched.iter().map(|d| d.deliveries.len()).collect();
+        assert_eq!(deliveries.iter().filter(|n| **n == 2).count(), 4);
+        assert_eq!(deliveries.iter().filter(|n| **n == 1).count(), 1);
+        assert_eq!(deliveries.iter().filter(|n| **n == 0).count(), 1);
+    }
+
+    #[tokio::test]
+    async fn test_slow_alerter_batches_and_overflows() {
+        let (release, gate) = watch::channel(false);
+        let gated = Arc::new(GatedAlerter {
+            gate,
+            batches: Mutex::new(Vec::new()),
+        });
+        let alerters: Vec<Arc<dyn Alerter>> = vec![gated.clone()];
+        let (dispatched, on_dispatched) = collector();
+        let dispatcher = AlertDispatcher::spawn(
+            &alerters,
+            &AlertDispatchConfig {
+                queue_capacity: 3,
+                priority_queue_capacity: 3,
+            },
+            10,
+            Duration::from_millis(1),
+            on_dispatched,
+        );
+        let route = ["gated".to_string()];
+
+        // The first alert is taken by the blocked sender
+        dispatcher.dispatch(create_test_anomaly(), &route).await;
+        tokio::time::sleep(Duration::from_millis(20)).await;
+
+        // Three more fill the queue; the fifth overflows without waiting
+        for _ in 0..4 {
+            dispatcher.dispatch(create_test_anomaly(), &route).await;
+        }
+        {
+            let dispatched = dispatched.lock().unwrap();
+            assert_eq!(dispatched.len(), 1);
+            assert_eq!(
+                dispatched[0].deliveries[0].error.as_deref(),
+                Some("dispatch queue full")
+            );
+        }
+        assert_eq!(dispatcher.outstanding(), 4);
+
+        release.send(true).unwrap();
+        assert!(dispatcher.flush(Duration::from_secs(5)).await);
+        assert_eq!(dispatched.lock().unwrap().len(), 5);
+        // Queued alerts went out as one batch
+        assert_eq!(*gated.batches.lock().unwrap(), vec![1, 3]);
+    }
+
+    #[tokio::test]
+    async fn test_priority_alerts_jump_queue_and_wait() {
+        let (release, gate) = watch::channel(false);
+        let gated = Arc::new(GatedAlerter {
+            gate,
+            batches: Mutex::new(Vec::new()),
+        });
+        let alerters: Vec<Arc<dyn Alerter>> = vec![gated.clone()];
+        let (dispatched, on_dispatched) = collector();
+        let dispatcher = Arc::new(AlertDispatcher::spawn(
+            &alerters,
+            &AlertDispatchConfig {
+                queue_capacity: 1,
+                priority_queue_capacity: 1,
+            },
+            10,
+            Duration::from_millis(1),
+            on_dispatched,
+        ));
+        let route = ["gated".to_string()];
+        let mut critical = create_test_anomaly();
+        critical.severity = Severity::Critical;
+
+        // Block the sender and fill both queues
+        dispatcher.dispatch(create_test_anomaly(), &route).await;
+        tokio::time::sleep(Duration::from_millis(20)).await;
+        dispatcher.dispatch(create_test_anomaly(), &route).await;
+        dispatcher.dispatch(critical.clone(), &route).await;
+
+        // A second critical alert waits for room instead of overflowing
+        let waiting = tokio::spawn({
+            let dispatcher = Arc::clone(&dispatcher);
+            let route = route.clone();
+            async move { dispatcher.dispatch(critical, &route).await }
+        });
+        tokio::time::sleep(Duration::from_millis(20)).await;
+        assert!(!waiting.is_finished());
+        assert!(dispatched.lock().unwrap().is_empty());
+
+        release.send(true).unwrap();
+        waiting.await.unwrap();
+        assert!(dispatcher.flush(Duration::from_secs(5)).await);
+        let dispatched = dispatched.lock().unwrap();
+        assert_eq!(dispatched.len(), 4);
+        assert!(dispatched.iter().all(|d| d.deliveries[0].error.is_none()));
+        // The queued critical alert was sent ahead of the earlier medium one
+        assert_eq!(dispatched[1].alert.severity, Severity::Critical);
+    }
+}
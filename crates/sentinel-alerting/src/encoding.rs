@@ -0,0 +1,400 @@
+//! Binary alert payload encodings.
+//!
+//! Broker publishers (RabbitMQ, Kafka, MQTT and Pub/Sub) send anomalies as
+//! JSON by default. High-volume consumers can instead choose, per
+//! destination, MessagePack (the JSON document with field names, so
+//! generic decoders see the same structure) or Protobuf following the
+//! published [`ANOMALY_PROTO`] schema. CloudEvents envelopes are JSON only.
+
+use crate::cloudevents::{encode_anomaly, CloudEventsConfig};
+use llm_sentinel_core::{events::AnomalyEvent, Error, Result};
+use prost::Message;
+use serde::{Deserialize, Serialize};
+
+/// Protobuf schema of [`PayloadEncoding::Protobuf`] payloads
+pub const ANOMALY_PROTO: &str = include_str!("../proto/anomaly.proto");
+
+/// Content type of MessagePack payloads
+pub const MSGPACK_CONTENT_TYPE: &str = "application/msgpack";
+
+/// Content type of Protobuf payloads
+pub const PROTOBUF_CONTENT_TYPE: &str = "application/x-protobuf";
+
+/// Alert payload encoding
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum PayloadEncoding {
+    /// JSON, or a CloudEvent when CloudEvents are enabled
+    #[default]
+    Json,
+    /// MessagePack, with field names
+    #[serde(rename = "msgpack")]
+    MessagePack,
+    /// Protobuf `llm_sentinel.alert.v1.Anomaly`
+    Protobuf,
+}
+
+impl PayloadEncoding {
+    /// Check that the encoding can be combined with CloudEvents envelopes
+    pub fn check(self, cloudevents: Option<&CloudEventsConfig>) -> Result<()> {
+        if cloudevents.is_some() && self != Self::Json {
+            return Err(Error::config(
+                "CloudEvents envelopes require the json payload encoding",
+            ));
+        }
+        Ok(())
+    }
+}
+
+/// Serialize an anomaly as a published message body
+///
+/// Returns the body and its content type.
+pub fn encode_payload(
+    alert: &AnomalyEvent,
+    encoding: PayloadEncoding,
+    cloudevents: Option<&CloudEventsConfig>,
+) -> Result<(Vec<u8>, &'static str)> {
+    encoding.check(cloudevents)?;
+    match encoding {
+        PayloadEncoding::Json => encode_anomaly(alert, cloudevents)
+            .map(|(body, content_type)| (body.into_bytes(), content_type)),
+        PayloadEncoding::MessagePack => rmp_serde::to_vec_named(alert)
+            .map(|body| (body, MSGPACK_CONTENT_TYPE))
+            .map_err(|e| Error::internal(format!("Failed to serialize alert: {}", e))),
+        PayloadEncoding::Protobuf => Ok((
+            proto::Anomaly::from(alert).encode_to_vec(),
+            PROTOBUF_CONTENT_TYPE,
+        )),
+    }
+}
+
+/// Messages of the [`ANOMALY_PROTO`] schema
+pub mod proto {
+    use llm_sentinel_core::events::AnomalyEvent;
+    use std::collections::HashMap;
+
+    /// `llm_sentinel.alert.v1.Anomaly`
+    #[derive(Clone, PartialEq, prost::Message)]
+    pub struct Anomaly {
+        /// Unique alert identifier
+        #[prost(string, tag = "1")]
+        pub alert_id: String,
+        /// Detection time (Unix milliseconds)
+        #[prost(int64, tag = "2")]
+        pub timestamp_ms: i64,
+        /// Severity name
+        #[prost(string, tag = "3")]
+        pub severity: String,
+        /// Anomaly type name
+        #[prost(string, tag = "4")]
+        pub anomaly_type: String,
+        /// Service name
+        #[prost(string, tag = "5")]
+        pub service_name: String,
+        /// Model identifier
+        #[prost(string, tag = "6")]
+        pub model: String,
+        /// Deployment environment
+        #[prost(string, optional, tag = "7")]
+        pub environment: Option<String>,
+        /// Detection method name
+        #[prost(string, tag = "8")]
+        pub detection_method: String,
+        /// Confidence score (0.0 - 1.0)
+        #[prost(double, tag = "9")]
+        pub confidence: f64,
+        /// Detailed anomaly information
+        #[prost(message, optional, tag = "10")]
+        pub details: Option<Details>,
+        /// Context information
+        #[prost(message, optional, tag = "11")]
+        pub context: Option<Context>,
+        /// Root cause analysis
+        #[prost(string, optional, tag = "12")]
+        pub root_cause: Option<String>,
+        /// Remediation suggestions
+        #[prost(string, repeated, tag = "13")]
+        pub remediation: Vec<String>,
+        /// Related alert IDs
+        #[prost(string, repeated, tag = "14")]
+        pub related_alerts: Vec<String>,
+        /// Runbook URL
+        #[prost(string, optional, tag = "15")]
+        pub runbook_url: Option<String>,
+        /// Trace deep links by tracing backend
+        #[prost(map = "string, string", tag = "16")]
+        pub trace_links: HashMap<String, String>,
+        /// Labels of the triggering telemetry
+        #[prost(map = "string, string", tag = "17")]
+        pub labels: HashMap<String, String>,
+        /// Lifecycle state name
+        #[prost(string, tag = "18")]
+        pub state: String,
+        /// When the state last changed (Unix milliseconds)
+        #[prost(int64, optional, tag = "19")]
+        pub state_changed_at_ms: Option<i64>,
+        /// What was dropped to fit the payload size limit
+        #[prost(message, optional, tag = "20")]
+        pub truncation: Option<Truncation>,
+        /// Correlation ID of the triggering telemetry or request
+        #[prost(string, optional, tag = "21")]
+        pub correlation_id: Option<String>,
+        /// Hash of the service, model, anomaly type and metric
+        #[prost(string, tag = "22")]
+        pub signature_hash: String,
+    }
+
+    /// `llm_sentinel.alert.v1.Details`
+    #[derive(Clone, PartialEq, prost::Message)]
+    pub struct Details {
+        /// Metric name
+        #[prost(string, tag = "1")]
+        pub metric: String,
+        /// Observed value
+        #[prost(double, tag = "2")]
+        pub value: f64,
+        /// Baseline value
+        #[prost(double, tag = "3")]
+        pub baseline: f64,
+        /// Threshold that was exceeded
+        #[prost(double, tag = "4")]
+        pub threshold: f64,
+        /// Standard deviations from baseline
+        #[prost(double, optional, tag = "5")]
+        pub deviation_sigma: Option<f64>,
+        /// Additional details, JSON-encoded
+        #[prost(map = "string, string", tag = "6")]
+        pub additional: HashMap<String, String>,
+        /// Contributing metrics
+        #[prost(message, repeated, tag = "7")]
+        pub observations: Vec<Observation>,
+    }
+
+    /// `llm_sentinel.alert.v1.Observation`
+    #[derive(Clone, PartialEq, prost::Message)]
+    pub struct Observation {
+        /// Metric name
+        #[prost(string, tag = "1")]
+        pub metric: String,
+        /// Observed value
+        #[prost(double, tag = "2")]
+        pub value: f64,
+        /// Baseline value
+        #[prost(double, tag = "3")]
+        pub baseline: f64,
+        /// Threshold for this metric
+        #[prost(double, optional, tag = "4")]
+        pub threshold: Option<f64>,
+        /// Standard deviations from baseline
+        #[prost(double, optional, tag = "5")]
+        pub deviation_sigma: Option<f64>,
+        /// Share of the anomaly score
+        #[prost(double, optional, tag = "6")]
+        pub contribution: Option<f64>,
+    }
+
+    /// `llm_sentinel.alert.v1.Context`
+    #[derive(Clone, PartialEq, prost::Message)]
+    pub struct Context {
+        /// Trace ID
+        #[prost(string, optional, tag = "1")]
+        pub trace_id: Option<String>,
+        /// User ID
+        #[prost(string, optional, tag = "2")]
+        pub user_id: Option<String>,
+        /// Region/datacenter
+        #[prost(string, optional, tag = "3")]
+        pub region: Option<String>,
+        /// Time window analyzed
+        #[prost(string, tag = "4")]
+        pub time_window: String,
+        /// Number of samples in window
+        #[prost(uint64, tag = "5")]
+        pub sample_count: u64,
+        /// Span of the samples analyzed
+        #[prost(message, optional, tag = "6")]
+        pub window: Option<Window>,
+        /// Additional context
+        #[prost(map = "string, string", tag = "7")]
+        pub additional: HashMap<String, String>,
+    }
+
+    /// `llm_sentinel.alert.v1.Window`
+    #[derive(Clone, PartialEq, prost::Message)]
+    pub struct Window {
+        /// Oldest sample (Unix milliseconds)
+        #[prost(int64, tag = "1")]
+        pub start_ms: i64,
+        /// Newest sample (Unix milliseconds)
+        #[prost(int64, tag = "2")]
+        pub end_ms: i64,
+        /// Time between the oldest and newest sample (seconds)
+        #[prost(double, tag = "3")]
+        pub span_secs: f64,
+        /// Number of samples
+        #[prost(uint64, tag = "4")]
+        pub sample_count: u64,
+    }
+
+    /// `llm_sentinel.alert.v1.Truncation`
+    #[derive(Clone, PartialEq, prost::Message)]
+    pub struct Truncation {
+        /// Size of the full JSON payload (bytes)
+        #[prost(uint64, tag = "1")]
+        pub original_bytes: u64,
+        /// Prompt/response text was dropped
+        #[prost(bool, tag = "2")]
+        pub text_dropped: bool,
+        /// Additional entries dropped, text aside
+        #[prost(uint64, tag = "3")]
+        pub additional_dropped: u64,
+    }
+
+    impl From<&AnomalyEvent> for Anomaly {
+        fn from(alert: &AnomalyEvent) -> Self {
+            let details = &alert.details;
+            let context = &alert.context;
+            Self {
+                alert_id: alert.alert_id.to_string(),
+                timestamp_ms: alert.timestamp.timestamp_millis(),
+                severity: alert.severity.to_string(),
+                anomaly_type: alert.anomaly_type.to_string(),
+                service_name: alert.service_name.to_string(),
+                model: alert.model.to_string(),
+                environment: alert.environment.as_ref().map(ToString::to_string),
+                detection_method: alert.detection_method.to_string(),
+                confidence: alert.confidence,
+                details: Some(Details {
+                    metric: details.metric.clone(),
+                    value: details.value,
+                    baseline: details.baseline,
+                    threshold: details.threshold,
+                    deviation_sigma: details.deviation_sigma,
+                    additional: details
+                        .additional
+                        .iter()
+                        .map(|(key, value)| (key.clone(), value.to_string()))
+                        .collect(),
+                    observations: details
+                        .observations
+                        .iter()
+                        .map(|observation| Observation {
+                            metric: observation.metric.clone(),
+                            value: observation.value,
+                            baseline: observation.baseline,
+                            threshold: observation.threshold,
+                            deviation_sigma: observation.deviation_sigma,
+                            contribution: observation.contribution,
+                        })
+                        .collect(),
+                }),
+                context: Some(Context {
+                    trace_id: context.trace_id.clone(),
+                    user_id: context.user_id.clone(),
+                    region: context.region.clone(),
+                    time_window: context.time_window.clone(),
+                    sample_count: context.sample_count as u64,
+                    window: context.window.as_ref().map(|window| Window {
+                        start_ms: window.start.timestamp_millis(),
+                        end_ms: window.end.timestamp_millis(),
+                        span_secs: window.span_secs,
+                        sample_count: window.sample_count as u64,
+                    }),
+                    additional: context.additional.clone(),
+                }),
+                root_cause: alert.root_cause.clone(),
+                remediation: alert.remediation.clone(),
+                related_alerts: alert
+                    .related_alerts
+                    .iter()
+                    .map(ToString::to_string)
+                    .collect(),
+                runbook_url: alert.runbook_url.clone(),
+                trace_links: alert.trace_links.clone().into_iter().collect(),
+                labels: alert.labels.clone().into_iter().collect(),
+                state: alert.state.to_string(),
+                state_changed_at_ms: alert.state_changed_at.map(|at| at.timestamp_millis()),
+                truncation: alert.truncation.as_ref().map(|truncation| Truncation {
+                    original_bytes: truncation.original_bytes as u64,
+                    text_dropped: truncation.text_dropped,
+                    additional_dropped: truncation.additional_dropped as u64,
+                }),
+                correlation_id: alert.correlation_id.clone(),
+                signature_hash: alert.signature_hash.clone(),
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use llm_sentinel_fixtures::anomaly;
+
+    fn create_test_anomaly() -> AnomalyEvent {
+        let mut alert = anomaly()
+            .service("checkout")
+            .trace_id("trace-1")
+            .label("team", "payments")
+            .build();
+        alert
+            .details
+            .additional
+            .insert("window".to_string(), serde_json::json!(100));
+        alert
+    }
+
+    #[test]
+    fn test_protobuf_payload() {
+        let alert = create_test_anomaly();
+        let (body, content_type) = encode_payload(&alert, PayloadEncoding::Protobuf, None).unwrap();
+        assert_eq!(content_type, PROTOBUF_CONTENT_TYPE);
+
+        let decoded = proto::Anomaly::decode(body.as_slice()).unwrap();
+        assert_eq!(decoded.alert_id, alert.alert_id.to_string());
+        assert_eq!(decoded.severity, "high");
+        assert_eq!(decoded.detection_method, "z_score");
+        assert_eq!(decoded.labels["team"], "payments");
+        assert_eq!(decoded.signature_hash, alert.signature_hash);
+        let details = decoded.details.unwrap();
+        assert_eq!(details.value, 5000.0);
+        assert_eq!(details.additional["window"], "100");
+        assert_eq!(
+            decoded.context.unwrap().trace_id.as_deref(),
+            Some("trace-1")
+        );
+        assert!(decoded.truncation.is_none());
+
+        let json = serde_json::to_vec(&alert).unwrap();
+        assert!(body.len() < json.len());
+    }
+
+    #[test]
+    fn test_msgpack_payload() {
+        let alert = create_test_anomaly();
+        let (body, content_type) =
+            encode_payload(&alert, PayloadEncoding::MessagePack, None).unwrap();
+        assert_eq!(content_type, MSGPACK_CONTENT_TYPE);
+
+        let decoded: AnomalyEvent = rmp_serde::from_slice(&body).unwrap();
+        assert_eq!(decoded.alert_id, alert.alert_id);
+        assert_eq!(decoded.labels, alert.labels);
+    }
+
+    #[test]
+    fn test_cloudevents_require_json() {
+        let alert = create_test_anomaly();
+        let cloudevents = CloudEventsConfig::default();
+        let (_, content_type) =
+            encode_payload(&alert, PayloadEncoding::Json, Some(&cloudevents)).unwrap();
+        assert_eq!(content_type, crate::cloudevents::CLOUDEVENTS_CONTENT_TYPE);
+        assert!(encode_payload(&alert, PayloadEncoding::Protobuf, Some(&cloudevents)).is_err());
+    }
+
+    #[test]
+    fn test_schema_published() {
+        assert!(ANOMALY_PROTO.contains("package llm_sentinel.alert.v1;"));
+        assert!(ANOMALY_PROTO.contains("message Anomaly {"));
+    }
+}
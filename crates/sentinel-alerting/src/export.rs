@@ -0,0 +1,220 @@
+//! Write-only export of telemetry and anomalies to analytics stores.
+//!
+//! Cloud analytics teams want to run SQL over LLM telemetry in the store
+//! they already use, without sentinel having to query it back. An
+//! [`ExportSink`] only accepts batches of events, which keeps an integration
+//! much smaller than a full `Storage` backend. The [`Exporter`] copies
+//! events off the event bus, buffers them per kind and hands every batch to
+//! each sink; a failing sink is logged and counted without holding back the
+//! others or the detection path.
+
+use async_trait::async_trait;
+use futures::future::join_all;
+use llm_sentinel_core::{
+    bus::Subscription,
+    config::ExportConfig,
+    events::{AnomalyEvent, TelemetryEvent},
+    Result,
+};
+use std::{sync::Arc, time::Duration};
+use tokio::task::JoinHandle;
+use tracing::{debug, warn};
+
+/// Write-only destination for telemetry and anomalies
+#[async_trait]
+pub trait ExportSink: Send + Sync {
+    /// Sink name used in logs and metrics
+    fn name(&self) -> &str;
+
+    /// Write a batch of telemetry events
+    async fn export_telemetry(&self, events: &[TelemetryEvent]) -> Result<()>;
+
+    /// Write a batch of anomalies
+    async fn export_anomalies(&self, anomalies: &[AnomalyEvent]) -> Result<()>;
+}
+
+/// Count the outcome of one batch written to one sink
+fn record_outcome(sink: &dyn ExportSink, kind: &'static str, count: usize, result: Result<()>) {
+    let name = sink.name().to_string();
+    match result {
+        Ok(()) => {
+            debug!(sink = %name, kind, count, "Exported batch");
+            metrics::counter!("sentinel_export_records_total", "sink" => name, "kind" => kind)
+                .increment(count as u64);
+        }
+        Err(e) => {
+            warn!(sink = %name, kind, count, error = %e, "Export failed");
+            metrics::counter!("sentinel_export_failures_total", "sink" => name, "kind" => kind)
+                .increment(count as u64);
+        }
+    }
+}
+
+/// Batches events off the bus into export sinks
+pub struct Exporter {
+    sinks: Vec<Arc<dyn ExportSink>>,
+    batch_size: usize,
+    flush_interval: Duration,
+}
+
+impl std::fmt::Debug for Exporter {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let sinks: Vec<&str> = self.sinks.iter().map(|sink| sink.name()).collect();
+        f.debug_struct("Exporter")
+            .field("sinks", &sinks)
+            .field("batch_size", &self.batch_size)
+            .field("flush_interval", &self.flush_interval)
+            .finish()
+    }
+}
+
+impl Exporter {
+    /// Create an exporter writing to `sinks`
+    pub fn new(sinks: Vec<Arc<dyn ExportSink>>, config: &ExportConfig) -> Self {
+        Self {
+            sinks,
+            batch_size: config.batch_size.max(1),
+            flush_interval: Duration::from_secs(config.flush_interval_secs.max(1)),
+        }
+    }
+
+    /// Write telemetry to every sink concurrently
+    pub async fn export_telemetry(&self, events: &[TelemetryEvent]) {
+        if events.is_empty() {
+            return;
+        }
+        join_all(self.sinks.iter().map(|sink| async move {
+            let result = sink.export_telemetry(events).await;
+            record_outcome(sink.as_ref(), "telemetry", events.len(), result);
+        }))
+        .await;
+    }
+
+    /// Write anomalies to every sink concurrently
+    pub async fn export_anomalies(&self, anomalies: &[AnomalyEvent]) {
+        if anomalies.is_empty() {
+            return;
+        }
+        join_all(self.sinks.iter().map(|sink| async move {
+            let result = sink.export_anomalies(anomalies).await;
+            record_outcome(sink.as_ref(), "anomaly", anomalies.len(), result);
+        }))
+        .await;
+    }
+
+    /// Export everything received on `telemetry` and `anomalies` until the
+    /// bus is dropped
+    ///
+    /// A batch is written once it is full or the flush interval passes;
+    /// whatever is buffered when the bus closes is written before the task
+    /// ends.
+    pub fn spawn(
+        self,
+        mut telemetry: Subscription<TelemetryEvent>,
+        mut anomalies: Subscription<AnomalyEvent>,
+    ) -> JoinHandle<()> {
+        tokio::spawn(async move {
+            let mut events: Vec<TelemetryEvent> = Vec::with_capacity(self.batch_size);
+            let mut detected: Vec<AnomalyEvent> = Vec::new();
+            let mut interval = tokio::time::interval(self.flush_interval);
+            interval.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Delay);
+            // The first tick completes immediately
+            interval.tick().await;
+
+            let (mut telemetry_open, mut anomalies_open) = (true, true);
+            while telemetry_open || anomalies_open {
+                tokio::select! {
+                    event = telemetry.recv(), if telemetry_open => match event {
+                        Some(event) => {
+                            events.push((*event).clone());
+                            if events.len() >= self.batch_size {
+                                self.export_telemetry(&std::mem::take(&mut events)).await;
+                            }
+                        }
+                        None => telemetry_open = false,
+                    },
+                    anomaly = anomalies.recv(), if anomalies_open => match anomaly {
+                        Some(anomaly) => {
+                            detected.push((*anomaly).clone());
+                            if detected.len() >= self.batch_size {
+                                self.export_anomalies(&std::mem::take(&mut detected)).await;
+                            }
+                        }
+                        None => anomalies_open = false,
+                    },
+                    _ = interval.tick() => {
+                        self.export_telemetry(&std::mem::take(&mut events)).await;
+                        self.export_anomalies(&std::mem::take(&mut detected)).await;
+                    }
+                }
+            }
+
+            self.export_telemetry(&events).await;
+            self.export_anomalies(&detected).await;
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use llm_sentinel_core::{bus::EventBus, Error};
+    use llm_sentinel_fixtures::event;
+    use std::sync::Mutex;
+
+    #[derive(Default)]
+    struct RecordingSink {
+        batches: Mutex<Vec<usize>>,
+        fail: bool,
+    }
+
+    #[async_trait]
+    impl ExportSink for RecordingSink {
+        fn name(&self) -> &str {
+            "recording"
+        }
+
+        async fn export_telemetry(&self, events: &[TelemetryEvent]) -> Result<()> {
+            if self.fail {
+                return Err(Error::storage("unavailable"));
+            }
+            self.batches.lock().unwrap().push(events.len());
+            Ok(())
+        }
+
+        async fn export_anomalies(&self, _anomalies: &[AnomalyEvent]) -> Result<()> {
+            Ok(())
+        }
+    }
+
+    fn create_test_event() -> TelemetryEvent {
+        event().service("checkout").build()
+    }
+
+    #[tokio::test]
+    async fn test_batches_and_flushes_on_close() {
+        let sink = Arc::new(RecordingSink::default());
+        let failing = Arc::new(RecordingSink {
+            fail: true,
+            ..Default::default()
+        });
+        let config = ExportConfig {
+            batch_size: 2,
+            flush_interval_secs: 3600,
+            timestream: None,
+            bigquery: None,
+        };
+        let exporter = Exporter::new(vec![failing, sink.clone()], &config);
+
+        let bus = EventBus::new(16);
+        let handle = exporter.spawn(bus.telemetry().subscribe(), bus.anomalies().subscribe());
+        for _ in 0..5 {
+            bus.telemetry().publish(create_test_event());
+        }
+        drop(bus);
+        handle.await.unwrap();
+
+        // A failing sink does not hold back the others
+        assert_eq!(*sink.batches.lock().unwrap(), vec![2, 2, 1]);
+    }
+}
@@ -0,0 +1,288 @@
+//! RabbitMQ fan-out of normalized telemetry.
+//!
+//! Analytics consumers that prefer AMQP over Kafka receive every telemetry
+//! event after normalization and enrichment. Each destination exchange
+//! chooses its encoding: JSON or MessagePack (with field names, so both
+//! decode to the same document), optionally zstd-compressed. Messages have
+//! type [`TELEMETRY_SCHEMA`], a content type naming the encoding and, when
+//! compressed, content encoding `zstd`.
+
+use crate::rabbitmq::exchange_kind;
+use lapin::{
+    options::{BasicPublishOptions, ExchangeDeclareOptions},
+    types::FieldTable,
+    BasicProperties, Channel, Connection, ConnectionProperties,
+};
+use llm_sentinel_core::{
+    blocking::{self, BlockingPool},
+    bus::Subscription,
+    config::{FanoutDestinationConfig, TelemetryEncoding, TelemetryFanoutConfig},
+    events::TelemetryEvent,
+    Error, Result,
+};
+use serde::Serialize;
+use tokio::task::JoinHandle;
+use tracing::{debug, info, warn};
+
+/// Message type of published telemetry events
+pub const TELEMETRY_SCHEMA: &str = "llm_sentinel.telemetry.v1";
+
+/// Encoded telemetry message body
+#[derive(Debug, Clone, PartialEq)]
+pub struct EncodedTelemetry {
+    /// Message body
+    pub body: Vec<u8>,
+    /// Content type of the uncompressed body
+    pub content_type: &'static str,
+    /// `zstd` when the body is compressed
+    pub content_encoding: Option<&'static str>,
+}
+
+/// Encode as MessagePack with field names and textual IDs and timestamps,
+/// matching the JSON document
+fn encode_msgpack(event: &TelemetryEvent) -> Result<Vec<u8>> {
+    let mut body = Vec::new();
+    event
+        .serialize(
+            &mut rmp_serde::Serializer::new(&mut body)
+                .with_struct_map()
+                .with_human_readable(),
+        )
+        .map_err(|e| Error::internal(format!("Failed to encode MessagePack: {}", e)))?;
+    Ok(body)
+}
+
+/// Encode a telemetry event for a destination
+pub fn encode_telemetry(
+    event: &TelemetryEvent,
+    destination: &FanoutDestinationConfig,
+) -> Result<EncodedTelemetry> {
+    let (body, content_type) = match destination.encoding {
+        TelemetryEncoding::Json => (serde_json::to_vec(event)?, "application/json"),
+        TelemetryEncoding::MessagePack => (encode_msgpack(event)?, "application/msgpack"),
+    };
+
+    match destination.zstd_level {
+        Some(level) => Ok(EncodedTelemetry {
+            body: zstd::encode_all(body.as_slice(), level)
+                .map_err(|e| Error::internal(format!("Failed to compress telemetry: {}", e)))?,
+            content_type,
+            content_encoding: Some("zstd"),
+        }),
+        None => Ok(EncodedTelemetry {
+            body,
+            content_type,
+            content_encoding: None,
+        }),
+    }
+}
+
+/// Publishes telemetry events to RabbitMQ exchanges
+pub struct TelemetryFanout {
+    channel: Channel,
+    destinations: Vec<FanoutDestinationConfig>,
+}
+
+impl std::fmt::Debug for TelemetryFanout {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("TelemetryFanout")
+            .field("destinations", &self.destinations)
+            .finish_non_exhaustive()
+    }
+}
+
+impl TelemetryFanout {
+    /// Connect and declare the destination exchanges
+    pub async fn connect(config: &TelemetryFanoutConfig) -> Result<Self> {
+        info!("Connecting telemetry fan-out to RabbitMQ at {}", config.url);
+
+        let connection = Connection::connect(
+            &config.url,
+            ConnectionProperties::default().with_connection_name("sentinel-fanout".into()),
+        )
+        .await
+        .map_err(|e| Error::connection(format!("Failed to connect to RabbitMQ: {}", e)))?;
+        let channel = connection
+            .create_channel()
+            .await
+            .map_err(|e| Error::connection(format!("Failed to create channel: {}", e)))?;
+
+        for destination in &config.destinations {
+            channel
+                .exchange_declare(
+                    &destination.exchange,
+                    exchange_kind(&destination.exchange_type),
+                    ExchangeDeclareOptions {
+                        durable: true,
+                        ..Default::default()
+                    },
+                    FieldTable::default(),
+                )
+                .await
+                .map_err(|e| {
+                    Error::connection(format!(
+                        "Failed to declare exchange {}: {}",
+                        destination.exchange, e
+                    ))
+                })?;
+        }
+
+        info!(
+            destinations = config.destinations.len(),
+            "Telemetry fan-out exchanges declared"
+        );
+        Ok(Self {
+            channel,
+            destinations: config.destinations.clone(),
+        })
+    }
+
+    /// Publish an event to every destination
+    ///
+    /// Failures are logged and counted per exchange; a failing destination
+    /// does not hold back the others.
+    pub async fn publish(&self, event: &TelemetryEvent) {
+        for destination in &self.destinations {
+            let exchange = destination.exchange.clone();
+            match self.publish_to(destination, event).await {
+                Ok(()) => {
+                    metrics::counter!("sentinel_fanout_published_total", "exchange" => exchange)
+                        .increment(1);
+                }
+                Err(e) => {
+                    warn!(
+                        exchange = %destination.exchange,
+                        event_id = %event.event_id,
+                        error = %e,
+                        "Failed to publish telemetry"
+                    );
+                    metrics::counter!("sentinel_fanout_failures_total", "exchange" => exchange)
+                        .increment(1);
+                }
+            }
+        }
+    }
+
+    async fn publish_to(
+        &self,
+        destination: &FanoutDestinationConfig,
+        event: &TelemetryEvent,
+    ) -> Result<()> {
+        let encoded = match destination.zstd_level {
+            // Compression blocks, so it runs off the async workers
+            Some(_) => {
+                let (event, destination) = (event.clone(), destination.clone());
+                blocking::run(BlockingPool::Compression, move || {
+                    encode_telemetry(&event, &destination)
+                })
+                .await??
+            }
+            None => encode_telemetry(event, destination)?,
+        };
+        let routing_key = format!("{}.{}", destination.routing_key_prefix, event.service_name);
+
+        let mut properties = BasicProperties::default()
+            .with_delivery_mode(if destination.persistent { 2 } else { 1 })
+            .with_content_type(encoded.content_type.into())
+            .with_type(TELEMETRY_SCHEMA.into())
+            .with_timestamp(event.timestamp.timestamp() as u64)
+            .with_message_id(event.event_id.to_string().into());
+        if let Some(encoding) = encoded.content_encoding {
+            properties = properties.with_content_encoding(encoding.into());
+        }
+
+        self.channel
+            .basic_publish(
+                &destination.exchange,
+                &routing_key,
+                BasicPublishOptions::default(),
+                &encoded.body,
+                properties,
+            )
+            .await
+            .map_err(|e| Error::connection(format!("Failed to publish telemetry: {}", e)))?;
+        debug!(
+            exchange = %destination.exchange,
+            routing_key = %routing_key,
+            bytes = encoded.body.len(),
+            "Telemetry published"
+        );
+        Ok(())
+    }
+
+    /// Publish every event received on `telemetry` until the bus is dropped
+    pub fn spawn(self, mut telemetry: Subscription<TelemetryEvent>) -> JoinHandle<()> {
+        tokio::spawn(async move {
+            while let Some(event) = telemetry.recv().await {
+                self.publish(&event).await;
+            }
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use llm_sentinel_fixtures::event;
+    use serde::Deserialize;
+
+    fn create_event() -> TelemetryEvent {
+        event()
+            .service("chat")
+            .prompt("Summarize the refund policy".repeat(20))
+            .response("Refunds are issued within 14 days")
+            .tokens(400, 12)
+            .latency_ms(250.0)
+            .cost_usd(0.004)
+            .build()
+    }
+
+    fn destination(
+        encoding: TelemetryEncoding,
+        zstd_level: Option<i32>,
+    ) -> FanoutDestinationConfig {
+        FanoutDestinationConfig {
+            exchange: "analytics".to_string(),
+            exchange_type: "topic".to_string(),
+            routing_key_prefix: "telemetry".to_string(),
+            encoding,
+            zstd_level,
+            persistent: true,
+        }
+    }
+
+    #[test]
+    fn test_encode_zstd_json() {
+        let event = create_event();
+        let encoded =
+            encode_telemetry(&event, &destination(TelemetryEncoding::Json, Some(3))).unwrap();
+        assert_eq!(encoded.content_type, "application/json");
+        assert_eq!(encoded.content_encoding, Some("zstd"));
+
+        let plain = serde_json::to_vec(&event).unwrap();
+        assert!(encoded.body.len() < plain.len());
+        let decoded: TelemetryEvent =
+            serde_json::from_slice(&zstd::decode_all(encoded.body.as_slice()).unwrap()).unwrap();
+        assert_eq!(decoded.event_id, event.event_id);
+        assert_eq!(decoded.prompt.text, event.prompt.text);
+    }
+
+    #[test]
+    fn test_encode_msgpack() {
+        let event = create_event();
+        let encoded =
+            encode_telemetry(&event, &destination(TelemetryEncoding::MessagePack, None)).unwrap();
+        assert_eq!(encoded.content_type, "application/msgpack");
+        assert_eq!(encoded.content_encoding, None);
+
+        let mut deserializer =
+            rmp_serde::Deserializer::new(encoded.body.as_slice()).with_human_readable();
+        let decoded = TelemetryEvent::deserialize(&mut deserializer).unwrap();
+        assert_eq!(decoded.event_id, event.event_id);
+        assert_eq!(decoded.response.tokens, 12);
+        // Same document as JSON, so generic decoders see names and text IDs
+        let value: serde_json::Value = rmp_serde::from_slice(&encoded.body).unwrap();
+        assert_eq!(value["service_name"], "chat");
+        assert_eq!(value["event_id"], event.event_id.to_string());
+    }
+}
@@ -0,0 +1,243 @@
+//! Anomaly forwarding from an edge to a central sentinel.
+//!
+//! An edge instance forwards the anomalies it detects, not its telemetry, to
+//! the central instance configured under `federation.upstream`. Anomalies
+//! are stamped with the edge's region and posted in batches with the
+//! region's bearer token; the central instance deduplicates them across
+//! regions. As an [`Alerter`], forwarding follows the edge's own
+//! deduplication, routing and queued dispatch.
+
+use crate::{http::HttpClientConfig, Alerter};
+use async_trait::async_trait;
+use llm_sentinel_core::{
+    config::FederationUpstreamConfig,
+    events::AnomalyEvent,
+    federation::{stamp_region, FederationBatch, FEDERATION_PATH},
+    retry::{retry, RetryError, RetryPolicy},
+    Error, Result,
+};
+use reqwest::{Client, StatusCode};
+use std::time::Duration;
+use tracing::{debug, error, info, warn};
+
+/// Forwards anomalies to the central instance
+pub struct FederationForwarder {
+    client: Client,
+    region: String,
+    config: FederationUpstreamConfig,
+}
+
+impl std::fmt::Debug for FederationForwarder {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("FederationForwarder")
+            .field("region", &self.region)
+            .field("url", &self.config.url)
+            .finish_non_exhaustive()
+    }
+}
+
+impl FederationForwarder {
+    /// Create a forwarder for anomalies detected in `region`
+    pub fn new(region: impl Into<String>, config: &FederationUpstreamConfig) -> Result<Self> {
+        let region = region.into();
+        if config.url.is_empty() {
+            return Err(Error::config("Federation upstream URL cannot be empty"));
+        }
+        if config.token.is_empty() {
+            return Err(Error::config("Federation upstream token cannot be empty"));
+        }
+
+        info!(region = %region, url = %config.url, "Creating federation forwarder");
+
+        let client = HttpClientConfig::default()
+            .builder()?
+            .timeout(Duration::from_secs(config.timeout_secs))
+            .build()
+            .map_err(|e| Error::config(format!("Failed to create HTTP client: {}", e)))?;
+
+        Ok(Self {
+            client,
+            region,
+            config: config.clone(),
+        })
+    }
+
+    fn endpoint(&self, path: &str) -> String {
+        format!("{}{}", self.config.url.trim_end_matches('/'), path)
+    }
+
+    /// Post a batch of anomalies, stamped with this region
+    async fn forward(&self, alerts: &[AnomalyEvent]) -> Result<()> {
+        let anomalies = alerts
+            .iter()
+            .cloned()
+            .map(|mut anomaly| {
+                stamp_region(&mut anomaly, &self.region);
+                anomaly
+            })
+            .collect();
+        let batch = FederationBatch {
+            region: self.region.clone(),
+            anomalies,
+        };
+        let body = serde_json::to_string(&batch)?;
+
+        let url = self.endpoint(FEDERATION_PATH);
+        let policy = RetryPolicy::new(self.config.max_attempts.max(1));
+        retry(&policy, |attempt| {
+            let request = self
+                .client
+                .post(&url)
+                .bearer_auth(&self.config.token)
+                .header("Content-Type", "application/json")
+                .body(body.clone());
+
+            async move {
+                let response = match request.send().await {
+                    Ok(response) => response,
+                    Err(e) => {
+                        let e = e.without_url();
+                        warn!(attempt, error = %e, "Federation request failed");
+                        return Err(RetryError::Transient(e.to_string()));
+                    }
+                };
+
+                let status = response.status();
+                if status.is_success() {
+                    return Ok(());
+                }
+                let text = response.text().await.unwrap_or_default();
+                let message = format!("status {}: {}", status, text);
+                if status == StatusCode::TOO_MANY_REQUESTS || status.is_server_error() {
+                    warn!(attempt, status = %status, "Central sentinel busy or failing");
+                    Err(RetryError::Transient(message))
+                } else {
+                    Err(RetryError::Permanent(message))
+                }
+            }
+        })
+        .await
+        .map_err(|failure| {
+            error!(
+                attempts = failure.attempts,
+                error = %failure.error,
+                "Failed to forward anomalies to the central sentinel"
+            );
+            metrics::counter!("sentinel_federation_forward_failures_total").increment(1);
+            Error::alerting(format!("Federation forwarding failed: {}", failure.error))
+        })?;
+
+        metrics::counter!("sentinel_federation_forwarded_total").increment(alerts.len() as u64);
+        Ok(())
+    }
+}
+
+#[async_trait]
+impl Alerter for FederationForwarder {
+    async fn send(&self, alert: &AnomalyEvent) -> Result<()> {
+        self.forward(std::slice::from_ref(alert)).await?;
+        debug!(alert_id = %alert.alert_id, "Anomaly forwarded to the central sentinel");
+        Ok(())
+    }
+
+    async fn send_batch(&self, alerts: &[AnomalyEvent]) -> Result<()> {
+        if alerts.is_empty() {
+            return Ok(());
+        }
+
+        self.forward(alerts).await?;
+        debug!(
+            "Forwarded {} anomalies to the central sentinel",
+            alerts.len()
+        );
+        Ok(())
+    }
+
+    async fn health_check(&self) -> Result<()> {
+        self.client
+            .get(self.endpoint("/health/live"))
+            .send()
+            .await
+            .and_then(|r| r.error_for_status())
+            .map_err(|e| {
+                Error::connection(format!(
+                    "Central sentinel health check failed: {}",
+                    e.without_url()
+                ))
+            })?;
+
+        Ok(())
+    }
+
+    fn name(&self) -> &str {
+        "federation"
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use llm_sentinel_core::federation::REGION_LABEL;
+    use llm_sentinel_fixtures::anomaly;
+
+    use wiremock::{
+        matchers::{header, method, path},
+        Mock, MockServer, ResponseTemplate,
+    };
+
+    fn config(url: &str) -> FederationUpstreamConfig {
+        FederationUpstreamConfig {
+            url: url.to_string(),
+            token: "edge-secret".to_string(),
+            timeout_secs: 5,
+            max_attempts: 1,
+        }
+    }
+
+    fn create_test_anomaly() -> AnomalyEvent {
+        anomaly().service("checkout").build()
+    }
+
+    #[tokio::test]
+    async fn test_forwards_stamped_batch() {
+        let server = MockServer::start().await;
+        Mock::given(method("POST"))
+            .and(path(FEDERATION_PATH))
+            .and(header("authorization", "Bearer edge-secret"))
+            .respond_with(ResponseTemplate::new(200))
+            .expect(1)
+            .mount(&server)
+            .await;
+
+        let forwarder = FederationForwarder::new("eu-west-1", &config(&server.uri())).unwrap();
+        forwarder
+            .send_batch(&[create_test_anomaly(), create_test_anomaly()])
+            .await
+            .unwrap();
+
+        let requests = server.received_requests().await.unwrap();
+        let batch: FederationBatch = serde_json::from_slice(&requests[0].body).unwrap();
+        assert_eq!(batch.region, "eu-west-1");
+        assert_eq!(batch.anomalies.len(), 2);
+        assert_eq!(batch.anomalies[0].labels[REGION_LABEL], "eu-west-1");
+        assert_eq!(
+            batch.anomalies[0].context.region.as_deref(),
+            Some("eu-west-1")
+        );
+    }
+
+    #[tokio::test]
+    async fn test_rejected_token_is_permanent() {
+        let server = MockServer::start().await;
+        Mock::given(method("POST"))
+            .respond_with(ResponseTemplate::new(401))
+            .expect(1)
+            .mount(&server)
+            .await;
+
+        let mut config = config(&server.uri());
+        config.max_attempts = 3;
+        let forwarder = FederationForwarder::new("eu-west-1", &config).unwrap();
+        assert!(forwarder.send(&create_test_anomaly()).await.is_err());
+    }
+}
@@ -0,0 +1,191 @@
+//! Persistent queue of alerts held for their delivery window.
+//!
+//! Alerts a delivery schedule keeps from an alerter until its window opens
+//! (see [`crate::routing`]) can wait for hours, so they are kept in a JSON
+//! file rather than in memory only. The whole queue is rewritten to a
+//! temporary file and renamed on every change, which keeps the file
+//! consistent across crashes; held alerts are few (lower severities outside
+//! working hours), so the rewrite stays cheap.
+//!
+//! Alerts are removed once sent, so one sent just before a crash may be sent
+//! again after the restart, but none is lost.
+
+use chrono::{DateTime, Utc};
+use llm_sentinel_core::{events::AnomalyEvent, Error, Result};
+use serde::{Deserialize, Serialize};
+use std::path::{Path, PathBuf};
+use tokio::sync::Mutex;
+use tracing::info;
+
+/// Alert waiting for an alerter's delivery window
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct HeldAlert {
+    /// The alert, as it will be sent
+    pub alert: AnomalyEvent,
+    /// Alerter it is held for
+    pub alerter: String,
+    /// Schedule holding it
+    pub schedule: String,
+    /// When it was held
+    pub held_at: DateTime<Utc>,
+    /// When the delivery window opens
+    pub release_at: DateTime<Utc>,
+}
+
+impl HeldAlert {
+    fn is(&self, other: &HeldAlert) -> bool {
+        self.alert.alert_id == other.alert.alert_id && self.alerter == other.alerter
+    }
+}
+
+/// Held alerts, persisted to a file
+#[derive(Debug)]
+pub struct HoldingQueue {
+    path: PathBuf,
+    held: Mutex<Vec<HeldAlert>>,
+}
+
+impl HoldingQueue {
+    /// Open the queue, loading the alerts held before a restart
+    pub async fn open(path: impl Into<PathBuf>) -> Result<Self> {
+        let path = path.into();
+        let held: Vec<HeldAlert> = match tokio::fs::read(&path).await {
+            Ok(bytes) => serde_json::from_slice(&bytes).map_err(|e| {
+                Error::storage(format!("Invalid holding queue {}: {}", path.display(), e))
+            })?,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => {
+                if let Some(dir) = path.parent().filter(|dir| !dir.as_os_str().is_empty()) {
+                    tokio::fs::create_dir_all(dir).await.map_err(|e| {
+                        Error::storage(format!(
+                            "Failed to create holding queue directory {}: {}",
+                            dir.display(),
+                            e
+                        ))
+                    })?;
+                }
+                Vec::new()
+            }
+            Err(e) => {
+                return Err(Error::storage(format!(
+                    "Failed to read holding queue {}: {}",
+                    path.display(),
+                    e
+                )))
+            }
+        };
+        if !held.is_empty() {
+            info!(held = held.len(), "Loaded held alerts");
+        }
+        metrics::gauge!("sentinel_alerts_held").set(held.len() as f64);
+
+        Ok(Self {
+            path,
+            held: Mutex::new(held),
+        })
+    }
+
+    /// Hold an alert, returning once it is persisted
+    pub async fn hold(&self, alert: HeldAlert) -> Result<()> {
+        let mut held = self.held.lock().await;
+        let mut updated: Vec<HeldAlert> = held.iter().filter(|h| !h.is(&alert)).cloned().collect();
+        updated.push(alert);
+        persist(&self.path, &updated).await?;
+        *held = updated;
+        metrics::gauge!("sentinel_alerts_held").set(held.len() as f64);
+        Ok(())
+    }
+
+    /// Held alerts whose window has opened by `now`, in the order they were
+    /// held
+    pub async fn due(&self, now: DateTime<Utc>) -> Vec<HeldAlert> {
+        let held = self.held.lock().await;
+        held.iter()
+            .filter(|h| h.release_at <= now)
+            .cloned()
+            .collect()
+    }
+
+    /// Remove sent alerts
+    pub async fn release(&self, sent: &[HeldAlert]) -> Result<()> {
+        let mut held = self.held.lock().await;
+        let remaining: Vec<HeldAlert> = held
+            .iter()
+            .filter(|h| !sent.iter().any(|s| s.is(h)))
+            .cloned()
+            .collect();
+        persist(&self.path, &remaining).await?;
+        *held = remaining;
+        metrics::gauge!("sentinel_alerts_held").set(held.len() as f64);
+        Ok(())
+    }
+
+    /// Number of held alerts
+    pub async fn len(&self) -> usize {
+        self.held.lock().await.len()
+    }
+
+    /// Whether no alerts are held
+    pub async fn is_empty(&self) -> bool {
+        self.len().await == 0
+    }
+}
+
+async fn persist(path: &Path, held: &[HeldAlert]) -> Result<()> {
+    let bytes = serde_json::to_vec(held)
+        .map_err(|e| Error::storage(format!("Failed to serialize held alerts: {}", e)))?;
+    let tmp = path.with_extension("tmp");
+    tokio::fs::write(&tmp, &bytes)
+        .await
+        .map_err(|e| Error::storage(format!("Failed to write holding queue: {}", e)))?;
+    tokio::fs::rename(&tmp, path)
+        .await
+        .map_err(|e| Error::storage(format!("Failed to write holding queue: {}", e)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use llm_sentinel_core::types::Severity;
+    use llm_sentinel_fixtures::anomaly;
+
+    fn held(alerter: &str, release_in_secs: i64) -> HeldAlert {
+        let alert = anomaly()
+            .severity(Severity::Low)
+            .service("chat")
+            .value(900.0)
+            .deviation_sigma(Some(8.0))
+            .build();
+        HeldAlert {
+            alert,
+            alerter: alerter.to_string(),
+            schedule: "daytime".to_string(),
+            held_at: Utc::now(),
+            release_at: Utc::now() + chrono::Duration::seconds(release_in_secs),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_held_alerts_survive_restart() {
+        let dir = std::env::temp_dir().join(format!("sentinel-holding-{}", uuid::Uuid::new_v4()));
+        let path = dir.join("held").join("alerts.json");
+
+        let queue = HoldingQueue::open(&path).await.unwrap();
+        let (morning, later) = (held("slack", -1), held("slack", 3600));
+        queue.hold(morning.clone()).await.unwrap();
+        queue.hold(later.clone()).await.unwrap();
+        drop(queue);
+
+        let queue = HoldingQueue::open(&path).await.unwrap();
+        assert_eq!(queue.len().await, 2);
+        let due = queue.due(Utc::now()).await;
+        assert_eq!(due.len(), 1);
+        assert_eq!(due[0].alert.alert_id, morning.alert.alert_id);
+
+        queue.release(&due).await.unwrap();
+        let queue = HoldingQueue::open(&path).await.unwrap();
+        assert_eq!(queue.len().await, 1);
+        assert!(queue.due(Utc::now()).await.is_empty());
+
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+}
@@ -0,0 +1,728 @@
+//! GitHub and GitLab issue alerter.
+//!
+//! Files an issue for anomalies that developers should act on (for example
+//! quality degradation on a specific model). Each issue carries the
+//! anomaly's signature hash, derived from its service, model, type and
+//! metric. Before opening an issue the alerter searches open issues for the
+//! signature and, if one exists, comments the new occurrence count on it
+//! instead of opening a duplicate. Issues filed under the older marker,
+//! derived from the type, service and model only, are still found.
+
+use crate::{http::HttpClientConfig, Alerter};
+use async_trait::async_trait;
+use llm_sentinel_core::{events::AnomalyEvent, types::Severity, Error, Result};
+use reqwest::{Client, Method, RequestBuilder};
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::time::Duration;
+use tracing::{debug, error, info};
+
+const GITHUB_API: &str = "https://api.github.com";
+const GITLAB_API: &str = "https://gitlab.com/api/v4";
+
+/// Issue tracker provider
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum IssueProvider {
+    /// GitHub (or GitHub Enterprise) issues
+    Github,
+    /// GitLab issues
+    Gitlab,
+}
+
+/// Issue alerter configuration
+#[derive(Clone, Serialize, Deserialize)]
+pub struct IssueConfig {
+    /// Issue tracker provider
+    pub provider: IssueProvider,
+    /// API endpoint override (GitHub Enterprise, self-hosted GitLab)
+    pub endpoint: Option<String>,
+    /// Repository (`owner/repo`) or GitLab project path or ID
+    pub repository: String,
+    /// API token
+    pub token: String,
+    /// Anomaly types to file issues for (e.g. `quality_degradation`; all
+    /// when empty)
+    pub anomaly_types: Vec<String>,
+    /// Models to file issues for (all when empty)
+    pub models: Vec<String>,
+    /// Minimum severity to file issues for
+    pub min_severity: Option<Severity>,
+    /// Labels added to new issues
+    pub labels: Vec<String>,
+    /// Request timeout (seconds)
+    pub timeout_secs: u64,
+    /// Proxy, CA and DNS settings
+    #[serde(default)]
+    pub http: HttpClientConfig,
+}
+
+impl Default for IssueConfig {
+    fn default() -> Self {
+        Self {
+            provider: IssueProvider::Github,
+            endpoint: None,
+            repository: String::new(),
+            token: String::new(),
+            anomaly_types: Vec::new(),
+            models: Vec::new(),
+            min_severity: None,
+            labels: vec!["llm-sentinel".to_string()],
+            timeout_secs: 10,
+            http: HttpClientConfig::default(),
+        }
+    }
+}
+
+impl std::fmt::Debug for IssueConfig {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("IssueConfig")
+            .field("provider", &self.provider)
+            .field("endpoint", &self.endpoint)
+            .field("repository", &self.repository)
+            .field("anomaly_types", &self.anomaly_types)
+            .field("models", &self.models)
+            .field("min_severity", &self.min_severity)
+            .field("labels", &self.labels)
+            .field("timeout_secs", &self.timeout_secs)
+            .field("http", &self.http)
+            .finish_non_exhaustive()
+    }
+}
+
+/// Open issue matching a signature
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct OpenIssue {
+    /// Issue number (GitHub) or project-scoped IID (GitLab)
+    number: u64,
+    /// Comments already on the issue
+    comments: u64,
+}
+
+/// Alerter filing GitHub or GitLab issues
+pub struct IssueAlerter {
+    client: Client,
+    api_url: String,
+    base_url: String,
+    config: IssueConfig,
+}
+
+impl std::fmt::Debug for IssueAlerter {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("IssueAlerter")
+            .field("config", &self.config)
+            .field("base_url", &self.base_url)
+            .finish()
+    }
+}
+
+impl IssueAlerter {
+    /// Create a new issue alerter
+    pub fn new(config: IssueConfig) -> Result<Self> {
+        if config.repository.is_empty() {
+            return Err(Error::config("Issue alerter repository cannot be empty"));
+        }
+        if config.token.is_empty() {
+            return Err(Error::config("Issue alerter token cannot be empty"));
+        }
+
+        let api_url = config
+            .endpoint
+            .as_deref()
+            .unwrap_or(match config.provider {
+                IssueProvider::Github => GITHUB_API,
+                IssueProvider::Gitlab => GITLAB_API,
+            })
+            .trim_end_matches('/')
+            .to_string();
+        let base_url = match config.provider {
+            IssueProvider::Github => format!("{}/repos/{}", api_url, config.repository),
+            IssueProvider::Gitlab => format!(
+                "{}/projects/{}",
+                api_url,
+                config.repository.replace('/', "%2F")
+            ),
+        };
+
+        info!("Creating issue alerter for {}", base_url);
+
+        let client = config
+            .http
+            .builder()?
+            .timeout(Duration::from_secs(config.timeout_secs))
+            .user_agent(concat!("llm-sentinel/", env!("CARGO_PKG_VERSION")))
+            .build()
+            .map_err(|e| Error::config(format!("Failed to create HTTP client: {}", e)))?;
+
+        Ok(Self {
+            client,
+            api_url,
+            base_url,
+            config,
+        })
+    }
+
+    /// Whether the alert should be filed as an issue
+    fn matches(&self, alert: &AnomalyEvent) -> bool {
+        let anomaly_type = alert.anomaly_type.to_string();
+        (self.config.anomaly_types.is_empty() || self.config.anomaly_types.contains(&anomaly_type))
+            && (self.config.models.is_empty()
+                || self.config.models.iter().any(|m| m == alert.model.as_str()))
+            && self
+                .config
+                .min_severity
+                .map_or(true, |min| alert.severity >= min)
+    }
+
+    /// Issue marker for the anomaly's signature hash
+    fn signature(alert: &AnomalyEvent) -> String {
+        format!("sentinel-{}", alert.signature_hash)
+    }
+
+    /// Issue marker of releases before signature hashes, derived from the
+    /// anomaly type, service and model
+    fn legacy_signature(alert: &AnomalyEvent) -> String {
+        let digest = Sha256::digest(
+            format!(
+                "{}\n{}\n{}",
+                alert.anomaly_type, alert.service_name, alert.model
+            )
+            .as_bytes(),
+        );
+        format!("sentinel-{}", &hex::encode(digest)[..16])
+    }
+
+    /// Build an authenticated request
+    fn request(&self, method: Method, url: &str) -> RequestBuilder {
+        let request = self.client.request(method, url);
+        match self.config.provider {
+            IssueProvider::Github => request
+                .bearer_auth(&self.config.token)
+                .header("Accept", "application/vnd.github+json"),
+            IssueProvider::Gitlab => request.header("PRIVATE-TOKEN", &self.config.token),
+        }
+    }
+
+    /// Send a request and decode the JSON response
+    async fn send_json<T: serde::de::DeserializeOwned>(
+        &self,
+        request: RequestBuilder,
+        action: &str,
+    ) -> Result<T> {
+        let response = request
+            .send()
+            .await
+            .map_err(|e| Error::alerting(format!("Failed to {}: {}", action, e)))?;
+
+        let status = response.status();
+        if !status.is_success() {
+            let body = response.text().await.unwrap_or_default();
+            error!(status = %status, body = %body, "Failed to {}", action);
+            return Err(Error::alerting(format!(
+                "Failed to {}: status {}: {}",
+                action, status, body
+            )));
+        }
+
+        response
+            .json()
+            .await
+            .map_err(|e| Error::alerting(format!("Invalid response to {}: {}", action, e)))
+    }
+
+    /// Find an open issue carrying the signature
+    async fn find_open_issue(&self, signature: &str) -> Result<Option<OpenIssue>> {
+        match self.config.provider {
+            IssueProvider::Github => {
+                #[derive(Deserialize)]
+                struct SearchResponse {
+                    items: Vec<Item>,
+                }
+                #[derive(Deserialize)]
+                struct Item {
+                    number: u64,
+                    comments: u64,
+                }
+
+                let query = format!(
+                    "\"{}\" repo:{} is:issue is:open in:body",
+                    signature, self.config.repository
+                );
+                let response: SearchResponse = self
+                    .send_json(
+                        self.request(Method::GET, &format!("{}/search/issues", self.api_url))
+                            .query(&[("q", query.as_str())]),
+                        "search issues",
+                    )
+                    .await?;
+                Ok(response.items.into_iter().next().map(|item| OpenIssue {
+                    number: item.number,
+                    comments: item.comments,
+                }))
+            }
+            IssueProvider::Gitlab => {
+                #[derive(Deserialize)]
+                struct Item {
+                    iid: u64,
+                    user_notes_count: u64,
+                }
+
+                let items: Vec<Item> = self
+                    .send_json(
+                        self.request(Method::GET, &format!("{}/issues", self.base_url))
+                            .query(&[
+                                ("state", "opened"),
+                                ("in", "description"),
+                                ("search", signature),
+                            ]),
+                        "search issues",
+                    )
+                    .await?;
+                Ok(items.into_iter().next().map(|item| OpenIssue {
+                    number: item.iid,
+                    comments: item.user_notes_count,
+                }))
+            }
+        }
+    }
+
+    /// Open a new issue for the alert
+    async fn open_issue(&self, alert: &AnomalyEvent, signature: &str) -> Result<u64> {
+        let title = format!(
+            "[LLM-Sentinel] {} on {}/{}",
+            alert.anomaly_type, alert.service_name, alert.model
+        );
+        let body = format!(
+            "{}\n\n<!-- {} -->\n_Filed by LLM-Sentinel. Further occurrences are added as comments._",
+            describe(alert),
+            signature
+        );
+
+        match self.config.provider {
+            IssueProvider::Github => {
+                #[derive(Deserialize)]
+                struct Created {
+                    number: u64,
+                }
+                let created: Created = self
+                    .send_json(
+                        self.request(Method::POST, &format!("{}/issues", self.base_url))
+                            .json(&serde_json::json!({
+                                "title": title,
+                                "body": body,
+                                "labels": self.config.labels,
+                            })),
+                        "open issue",
+                    )
+                    .await?;
+                Ok(created.number)
+            }
+            IssueProvider::Gitlab => {
+                #[derive(Deserialize)]
+                struct Created {
+                    iid: u64,
+                }
+                let created: Created = self
+                    .send_json(
+                        self.request(Method::POST, &format!("{}/issues", self.base_url))
+                            .json(&serde_json::json!({
+                                "title": title,
+                                "description": body,
+                                "labels": self.config.labels.join(","),
+                            })),
+                        "open issue",
+                    )
+                    .await?;
+                Ok(created.iid)
+            }
+        }
+    }
+
+    /// Comment a new occurrence on an existing issue
+    async fn comment(&self, issue: &OpenIssue, alert: &AnomalyEvent) -> Result<()> {
+        // The issue itself is the first occurrence, each comment one more
+        let occurrence = issue.comments + 2;
+        let body = format!("**Occurrence #{}**\n\n{}", occurrence, describe(alert));
+        let url = match self.config.provider {
+            IssueProvider::Github => format!("{}/issues/{}/comments", self.base_url, issue.number),
+            IssueProvider::Gitlab => format!("{}/issues/{}/notes", self.base_url, issue.number),
+        };
+
+        self.send_json::<serde_json::Value>(
+            self.request(Method::POST, &url)
+                .json(&serde_json::json!({ "body": body })),
+            "comment on issue",
+        )
+        .await?;
+        Ok(())
+    }
+}
+
+#[async_trait]
+impl Alerter for IssueAlerter {
+    async fn send(&self, alert: &AnomalyEvent) -> Result<()> {
+        if !self.matches(alert) {
+            debug!(alert_id = %alert.alert_id, "Alert not selected for issue filing");
+            return Ok(());
+        }
+
+        let signature = Self::signature(alert);
+        let existing = match self.find_open_issue(&signature).await? {
+            Some(issue) => Some(issue),
+            None => self.find_open_issue(&Self::legacy_signature(alert)).await?,
+        };
+        let outcome = match existing {
+            Some(issue) => {
+                self.comment(&issue, alert).await?;
+                debug!(alert_id = %alert.alert_id, issue = issue.number, "Occurrence commented");
+                "commented"
+            }
+            None => {
+                let number = self.open_issue(alert, &signature).await?;
+                info!(alert_id = %alert.alert_id, issue = number, "Issue opened");
+                "opened"
+            }
+        };
+
+        metrics::counter!(
+            "sentinel_issue_alerts_total",
+            "provider" => self.name().to_string(),
+            "outcome" => outcome
+        )
+        .increment(1);
+
+        Ok(())
+    }
+
+    async fn health_check(&self) -> Result<()> {
+        self.request(Method::GET, &self.base_url)
+            .send()
+            .await
+            .and_then(|r| r.error_for_status())
+            .map_err(|e| Error::connection(format!("Issue tracker health check failed: {}", e)))?;
+
+        Ok(())
+    }
+
+    fn name(&self) -> &str {
+        match self.config.provider {
+            IssueProvider::Github => "GitHub",
+            IssueProvider::Gitlab => "GitLab",
+        }
+    }
+}
+
+/// Markdown description of an anomaly occurrence
+fn describe(alert: &AnomalyEvent) -> String {
+    let mut description = format!(
+        "| | |\n|---|---|\n\
+         | Severity | {} |\n\
+         | Service | {} |\n\
+         | Model | {} |\n\
+         | Metric | {} = {} (baseline {}) |\n\
+         | Detected | {} |\n\
+         | Alert ID | `{}` |",
+        alert.severity,
+        alert.service_name,
+        alert.model,
+        alert.details.metric,
+        alert.details.value,
+        alert.details.baseline,
+        alert.timestamp.to_rfc3339(),
+        alert.alert_id
+    );
+    for (name, url) in &alert.trace_links {
+        description.push_str(&format!("\n| Trace ({}) | [{}]({}) |", name, name, url));
+    }
+    if let Some(root_cause) = &alert.root_cause {
+        description.push_str(&format!("\n\n**Root cause:** {}", root_cause));
+    }
+    description
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use llm_sentinel_core::types::AnomalyType;
+    use llm_sentinel_fixtures::anomaly;
+
+    use wiremock::{
+        matchers::{body_partial_json, header, method, path, query_param},
+        Mock, MockServer, ResponseTemplate,
+    };
+
+    fn create_test_config(provider: IssueProvider, endpoint: &str) -> IssueConfig {
+        IssueConfig {
+            provider,
+            endpoint: Some(endpoint.to_string()),
+            repository: "acme/assistant".to_string(),
+            token: "test-token".to_string(),
+            anomaly_types: vec!["quality_degradation".to_string()],
+            models: vec!["gpt-4".to_string()],
+            ..Default::default()
+        }
+    }
+
+    fn create_test_anomaly(anomaly_type: AnomalyType) -> AnomalyEvent {
+        anomaly()
+            .severity(Severity::Medium)
+            .anomaly_type(anomaly_type)
+            .service("assistant")
+            .metric("quality_score")
+            .value(0.4)
+            .baseline(0.9)
+            .deviation_sigma(Some(5.0))
+            .time_window("rolling_window")
+            .build()
+    }
+
+    #[test]
+    fn test_config_validation() {
+        let config = IssueConfig {
+            repository: "acme/assistant".to_string(),
+            ..Default::default()
+        };
+        assert!(IssueAlerter::new(config).is_err());
+
+        let config = create_test_config(IssueProvider::Github, GITHUB_API);
+        assert!(!format!("{:?}", config).contains("test-token"));
+    }
+
+    #[test]
+    fn test_signature_is_stable() {
+        let first = create_test_anomaly(AnomalyType::QualityDegradation);
+        let second = create_test_anomaly(AnomalyType::QualityDegradation);
+        assert_eq!(
+            IssueAlerter::signature(&first),
+            IssueAlerter::signature(&second)
+        );
+        assert_ne!(
+            IssueAlerter::signature(&first),
+            IssueAlerter::signature(&create_test_anomaly(AnomalyType::Hallucination))
+        );
+    }
+
+    #[tokio::test]
+    async fn test_github_opens_issue() {
+        let mock_server = MockServer::start().await;
+        let alert = create_test_anomaly(AnomalyType::QualityDegradation);
+        let signature = IssueAlerter::signature(&alert);
+
+        Mock::given(method("GET"))
+            .and(path("/search/issues"))
+            .and(header("Authorization", "Bearer test-token"))
+            .and(query_param(
+                "q",
+                format!(
+                    "\"{}\" repo:acme/assistant is:issue is:open in:body",
+                    signature
+                )
+                .as_str(),
+            ))
+            .respond_with(
+                ResponseTemplate::new(200)
+                    .set_body_json(serde_json::json!({ "total_count": 0, "items": [] })),
+            )
+            .expect(1)
+            .mount(&mock_server)
+            .await;
+        Mock::given(method("GET"))
+            .and(path("/search/issues"))
+            .and(query_param(
+                "q",
+                format!(
+                    "\"{}\" repo:acme/assistant is:issue is:open in:body",
+                    IssueAlerter::legacy_signature(&alert)
+                )
+                .as_str(),
+            ))
+            .respond_with(
+                ResponseTemplate::new(200)
+                    .set_body_json(serde_json::json!({ "total_count": 0, "items": [] })),
+            )
+            .expect(1)
+            .mount(&mock_server)
+            .await;
+        Mock::given(method("POST"))
+            .and(path("/repos/acme/assistant/issues"))
+            .and(body_partial_json(
+                serde_json::json!({ "labels": ["llm-sentinel"] }),
+            ))
+            .respond_with(
+                ResponseTemplate::new(201).set_body_json(serde_json::json!({ "number": 7 })),
+            )
+            .expect(1)
+            .mount(&mock_server)
+            .await;
+
+        let alerter = IssueAlerter::new(create_test_config(
+            IssueProvider::Github,
+            &mock_server.uri(),
+        ))
+        .unwrap();
+        assert_eq!(alerter.name(), "GitHub");
+        alerter.send(&alert).await.unwrap();
+
+        let requests = mock_server.received_requests().await.unwrap();
+        let body: serde_json::Value = serde_json::from_slice(&requests[2].body).unwrap();
+        assert!(body["body"].as_str().unwrap().contains(&signature));
+    }
+
+    #[tokio::test]
+    async fn test_github_comments_on_open_issue() {
+        let mock_server = MockServer::start().await;
+
+        Mock::given(method("GET"))
+            .and(path("/search/issues"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({
+                "total_count": 1,
+                "items": [{ "number": 7, "comments": 2 }]
+            })))
+            .mount(&mock_server)
+            .await;
+        Mock::given(method("POST"))
+            .and(path("/repos/acme/assistant/issues/7/comments"))
+            .respond_with(ResponseTemplate::new(201).set_body_json(serde_json::json!({ "id": 1 })))
+            .expect(1)
+            .mount(&mock_server)
+            .await;
+        Mock::given(method("POST"))
+            .and(path("/repos/acme/assistant/issues"))
+            .respond_with(ResponseTemplate::new(201))
+            .expect(0)
+            .mount(&mock_server)
+            .await;
+
+        let alerter = IssueAlerter::new(create_test_config(
+            IssueProvider::Github,
+            &mock_server.uri(),
+        ))
+        .unwrap();
+        alerter
+            .send(&create_test_anomaly(AnomalyType::QualityDegradation))
+            .await
+            .unwrap();
+
+        let requests = mock_server.received_requests().await.unwrap();
+        let body: serde_json::Value = serde_json::from_slice(&requests[1].body).unwrap();
+        assert!(body["body"].as_str().unwrap().contains("Occurrence #4"));
+    }
+
+    #[tokio::test]
+    async fn test_github_finds_issue_under_legacy_signature() {
+        let mock_server = MockServer::start().await;
+        let alert = create_test_anomaly(AnomalyType::QualityDegradation);
+
+        Mock::given(method("GET"))
+            .and(path("/search/issues"))
+            .and(query_param(
+                "q",
+                format!(
+                    "\"{}\" repo:acme/assistant is:issue is:open in:body",
+                    IssueAlerter::legacy_signature(&alert)
+                )
+                .as_str(),
+            ))
+            .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({
+                "total_count": 1,
+                "items": [{ "number": 5, "comments": 0 }]
+            })))
+            .mount(&mock_server)
+            .await;
+        Mock::given(method("GET"))
+            .and(path("/search/issues"))
+            .respond_with(
+                ResponseTemplate::new(200)
+                    .set_body_json(serde_json::json!({ "total_count": 0, "items": [] })),
+            )
+            .mount(&mock_server)
+            .await;
+        Mock::given(method("POST"))
+            .and(path("/repos/acme/assistant/issues/5/comments"))
+            .respond_with(ResponseTemplate::new(201).set_body_json(serde_json::json!({ "id": 1 })))
+            .expect(1)
+            .mount(&mock_server)
+            .await;
+
+        let alerter = IssueAlerter::new(create_test_config(
+            IssueProvider::Github,
+            &mock_server.uri(),
+        ))
+        .unwrap();
+        alerter.send(&alert).await.unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_gitlab_opens_issue() {
+        let mock_server = MockServer::start().await;
+
+        Mock::given(method("GET"))
+            .and(path("/projects/acme%2Fassistant/issues"))
+            .and(header("PRIVATE-TOKEN", "test-token"))
+            .and(query_param("state", "opened"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!([])))
+            .expect(2)
+            .mount(&mock_server)
+            .await;
+        Mock::given(method("POST"))
+            .and(path("/projects/acme%2Fassistant/issues"))
+            .and(body_partial_json(
+                serde_json::json!({ "labels": "llm-sentinel" }),
+            ))
+            .respond_with(ResponseTemplate::new(201).set_body_json(serde_json::json!({ "iid": 3 })))
+            .expect(1)
+            .mount(&mock_server)
+            .await;
+
+        let alerter = IssueAlerter::new(create_test_config(
+            IssueProvider::Gitlab,
+            &mock_server.uri(),
+        ))
+        .unwrap();
+        assert_eq!(alerter.name(), "GitLab");
+        alerter
+            .send(&create_test_anomaly(AnomalyType::QualityDegradation))
+            .await
+            .unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_unselected_anomaly_skipped() {
+        let mock_server = MockServer::start().await;
+        Mock::given(method("GET"))
+            .respond_with(ResponseTemplate::new(200))
+            .expect(0)
+            .mount(&mock_server)
+            .await;
+
+        let alerter = IssueAlerter::new(create_test_config(
+            IssueProvider::Github,
+            &mock_server.uri(),
+        ))
+        .unwrap();
+        alerter
+            .send(&create_test_anomaly(AnomalyType::LatencySpike))
+            .await
+            .unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_search_failure() {
+        let mock_server = MockServer::start().await;
+        Mock::given(method("GET"))
+            .respond_with(ResponseTemplate::new(403).set_body_string("rate limited"))
+            .mount(&mock_server)
+            .await;
+
+        let alerter = IssueAlerter::new(create_test_config(
+            IssueProvider::Github,
+            &mock_server.uri(),
+        ))
+        .unwrap();
+        let err = alerter
+            .send(&create_test_anomaly(AnomalyType::QualityDegradation))
+            .await
+            .unwrap_err();
+        assert!(err.to_string().contains("rate limited"));
+    }
+}
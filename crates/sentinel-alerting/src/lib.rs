@@ -3,16 +3,72 @@
 //! Alert delivery and notification system for LLM-Sentinel.
 //!
 //! This crate provides:
-//! - Alert delivery via RabbitMQ
+//! - Alert delivery via RabbitMQ (`rabbitmq` feature)
+//! - RabbitMQ fan-out of normalized telemetry as JSON or MessagePack,
+//!   optionally zstd-compressed (`rabbitmq` feature)
+//! - Anomaly publication to Kafka (`kafka` feature)
+//! - MQTT publication for edge deployments
+//! - AWS SNS and EventBridge publication
+//! - Google Cloud Pub/Sub publication
+//! - Write-only export of telemetry and anomalies to AWS Timestream and
+//!   Google BigQuery
 //! - Webhook notifications
+//! - Anomaly forwarding from edge to central sentinels
+//! - CloudEvents envelopes for published anomalies
+//! - JSON, MessagePack or Protobuf payloads per broker destination
+//! - Splunk HEC and Datadog Events integrations
+//! - GitHub and GitLab issues for developer-facing anomalies
+//! - Telegram bot and Discord webhook notifications routed by severity
+//! - Twilio SMS and voice paging with rate limits and quiet hours
+//! - Scheduled digests by email (`email` feature) and Slack
 //! - Alert deduplication
-//! - Retry logic with exponential backoff
-//! - Alert routing by severity
+//! - Queued, batched alert dispatch off the detection path
+//! - Payload size limits that trim oversized anomalies before they are sent
+//! - Automated remediation actions with guardrails and audit logging
+//! - Proxy, custom CA and DNS override settings for HTTP-based alerters
+//! - Vault and AWS Secrets Manager backends for secret references
+//! - Alerter registry for config-driven and third-party alerters
+//! - Recording alerter for tests (`test-util` feature)
+//! - Delivery latency and outcome tracking
+//! - Retry logic with exponential backoff and jitter
+//! - Alert routing by severity and by labels
+//! - Time-aware severity rules (business hours, sales events)
+//! - Per-alerter delivery windows with a persistent holding queue
 
 #![warn(missing_debug_implementations, rust_2018_idioms, unreachable_pub)]
 
+pub mod aws;
+pub mod bigquery;
+pub mod cloudevents;
+pub mod datadog;
 pub mod deduplication;
+pub mod digest;
+pub mod discord;
+pub mod dispatch;
+pub mod encoding;
+pub mod export;
+#[cfg(feature = "rabbitmq")]
+pub mod fanout;
+pub mod federation;
+pub mod holding;
+pub mod http;
+pub mod issues;
+#[cfg(feature = "kafka")]
+pub mod kafka;
+pub mod mqtt;
+pub mod payload;
+pub mod pubsub;
+#[cfg(feature = "rabbitmq")]
 pub mod rabbitmq;
+#[cfg(any(test, feature = "test-util"))]
+pub mod recording;
+pub mod registry;
+pub mod remediation;
+pub mod routing;
+pub mod splunk;
+pub mod telegram;
+pub mod twilio;
+pub mod vault;
 pub mod webhook;
 
 use async_trait::async_trait;
@@ -33,37 +89,116 @@ pub trait Alerter: Send + Sync {
         Ok(())
     }
 
+    /// Send a single alert, returning the receiver's response for
+    /// alerters that capture it
+    async fn send_with_receipt(&self, alert: &AnomalyEvent) -> Result<Option<String>> {
+        self.send(alert).await?;
+        Ok(None)
+    }
+
+    /// Send multiple alerts in batch, returning the receiver's response to
+    /// each for alerters that capture it
+    async fn send_batch_with_receipts(
+        &self,
+        alerts: &[AnomalyEvent],
+    ) -> Result<Vec<Option<String>>> {
+        self.send_batch(alerts).await?;
+        Ok(vec![None; alerts.len()])
+    }
+
     /// Health check
     async fn health_check(&self) -> Result<()>;
 
+    /// Apply credentials from the alerter's settings, re-resolved after a
+    /// secret rotation, returning whether any changed
+    ///
+    /// Alerters that cannot change credentials at runtime keep the ones
+    /// they were created with.
+    async fn rotate_credentials(&self, _settings: &serde_json::Value) -> Result<bool> {
+        Ok(false)
+    }
+
+    /// Summary of the requests the alerter sends, e.g. method and URL,
+    /// recorded with each delivery
+    fn request_summary(&self) -> Option<String> {
+        None
+    }
+
     /// Get alerter name for logging
     fn name(&self) -> &str;
 }
 
-/// Alert metadata for tracking delivery
-#[derive(Debug, Clone, Serialize, Deserialize)]
-pub struct AlertMetadata {
-    /// Unique alert ID
-    pub alert_id: String,
-    /// Number of delivery attempts
-    pub attempts: u32,
-    /// Last delivery attempt timestamp
-    pub last_attempt: chrono::DateTime<chrono::Utc>,
-    /// Delivery status
-    pub status: AlertStatus,
+pub use llm_sentinel_core::events::{AlertMetadata, AlertStatus};
+
+/// Send an alert and record the delivery outcome
+///
+/// Latency is measured from the anomaly timestamp to delivery confirmation
+/// and exported as `sentinel_alert_delivery_latency_seconds`.
+pub async fn deliver(alerter: &dyn Alerter, alert: &AnomalyEvent) -> AlertMetadata {
+    let result = alerter.send_with_receipt(alert).await;
+    record_delivery(alerter, alert, result.map_err(|e| e.to_string()))
 }
 
-/// Alert delivery status
-#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
-pub enum AlertStatus {
-    /// Pending delivery
-    Pending,
-    /// Successfully delivered
-    Delivered,
-    /// Failed after retries
-    Failed,
-    /// Deduplicated (not sent)
-    Deduplicated,
+/// Send alerts to one alerter as a batch and record each delivery outcome
+///
+/// A batch succeeds or fails as a whole, so every alert in it gets the same
+/// outcome.
+pub async fn deliver_batch(
+    alerter: &dyn Alerter,
+    alerts: &[AnomalyEvent],
+) -> Vec<AlertMetadata> {
+    match alerter.send_batch_with_receipts(alerts).await {
+        Ok(receipts) => alerts
+            .iter()
+            .zip(receipts.into_iter().chain(std::iter::repeat(None)))
+            .map(|(alert, receipt)| record_delivery(alerter, alert, Ok(receipt)))
+            .collect(),
+        Err(e) => {
+            let error = e.to_string();
+            alerts
+                .iter()
+                .map(|alert| record_delivery(alerter, alert, Err(error.clone())))
+                .collect()
+        }
+    }
+}
+
+fn record_delivery(
+    alerter: &dyn Alerter,
+    alert: &AnomalyEvent,
+    result: std::result::Result<Option<String>, String>,
+) -> AlertMetadata {
+    let mut delivery = AlertMetadata::pending(alert, alerter.name());
+    delivery.request = alerter.request_summary();
+    let now = chrono::Utc::now();
+
+    match result {
+        Ok(response) => {
+            delivery.mark_delivered(now);
+            delivery.response = response;
+        }
+        Err(e) => delivery.mark_failed(now, e),
+    }
+
+    let alerter_name = alerter.name().to_string();
+    let severity = alert.severity.to_string();
+    if let Some(latency_ms) = delivery.latency_ms {
+        metrics::histogram!(
+            "sentinel_alert_delivery_latency_seconds",
+            "alerter" => alerter_name.clone(),
+            "severity" => severity.clone()
+        )
+        .record(latency_ms / 1000.0);
+    }
+    metrics::counter!(
+        "sentinel_alert_deliveries_total",
+        "alerter" => alerter_name,
+        "severity" => severity,
+        "status" => delivery.status.to_string()
+    )
+    .increment(1);
+
+    delivery
 }
 
 /// Alert delivery configuration
@@ -95,8 +230,41 @@ impl Default for AlertConfig {
 
 /// Re-export commonly used types
 pub mod prelude {
-    pub use crate::deduplication::{AlertDeduplicator, DeduplicationConfig};
+    pub use crate::aws::{
+        EventBridgeAlerter, EventBridgeConfig, SecretsManagerSecrets, SnsAlerter, SnsConfig,
+        TimestreamSink,
+    };
+    pub use crate::bigquery::BigQuerySink;
+    pub use crate::cloudevents::{CloudEvent, CloudEventsConfig};
+    pub use crate::datadog::{DatadogAlerter, DatadogConfig};
+    pub use crate::deduplication::{AlertDeduplicator, DeduplicationConfig, DeduplicationSnapshot};
+    pub use crate::digest::{DigestScheduler, DigestSender};
+    pub use crate::discord::{DiscordAlerter, DiscordConfig, DiscordTarget};
+    pub use crate::dispatch::AlertDispatcher;
+    pub use crate::encoding::PayloadEncoding;
+    pub use crate::export::{ExportSink, Exporter};
+    #[cfg(feature = "rabbitmq")]
+    pub use crate::fanout::TelemetryFanout;
+    pub use crate::federation::FederationForwarder;
+    pub use crate::holding::{HeldAlert, HoldingQueue};
+    pub use crate::http::HttpClientConfig;
+    pub use crate::issues::{IssueAlerter, IssueConfig, IssueProvider};
+    #[cfg(feature = "kafka")]
+    pub use crate::kafka::{KafkaAlerter, KafkaAlerterConfig};
+    pub use crate::mqtt::{MqttAlerter, MqttConfig, MqttQos};
+    pub use crate::payload::fit_payload;
+    pub use crate::pubsub::{PubSubAlerter, PubSubConfig};
+    #[cfg(feature = "rabbitmq")]
     pub use crate::rabbitmq::{RabbitMqAlerter, RabbitMqConfig};
+    #[cfg(any(test, feature = "test-util"))]
+    pub use crate::recording::RecordingAlerter;
+    pub use crate::registry::{AlerterFactory, AlerterRegistry};
+    pub use crate::remediation::{RemediationEngine, RemediationOutcome, RemediationStatus};
+    pub use crate::routing::AlertRouter;
+    pub use crate::splunk::{SplunkAlerter, SplunkConfig};
+    pub use crate::telegram::{TelegramAlerter, TelegramConfig, TelegramTarget};
+    pub use crate::twilio::{QuietHours, TwilioAlerter, TwilioConfig};
+    pub use crate::vault::VaultSecrets;
     pub use crate::webhook::{WebhookAlerter, WebhookConfig};
-    pub use crate::{AlertConfig, AlertStatus, Alerter};
+    pub use crate::{deliver, deliver_batch, AlertConfig, AlertMetadata, AlertStatus, Alerter};
 }
@@ -0,0 +1,379 @@
+//! MQTT alert publisher for edge deployments.
+//!
+//! Topics are rendered from a template so subscribers can filter by severity
+//! or service with standard MQTT wildcards (e.g. `sentinel/alerts/critical/+`).
+
+use crate::{
+    cloudevents::CloudEventsConfig,
+    encoding::{encode_payload, PayloadEncoding},
+    Alerter,
+};
+use async_trait::async_trait;
+use llm_sentinel_core::{events::AnomalyEvent, retry::RetryPolicy, Error, Result};
+use rumqttc::{AsyncClient, Event, MqttOptions, Packet, QoS};
+use serde::{Deserialize, Serialize};
+use std::sync::{
+    atomic::{AtomicBool, Ordering},
+    Arc,
+};
+use std::time::Duration;
+use tokio::task::JoinHandle;
+use tracing::{debug, error, info, warn};
+
+/// Upper bound on the reconnect backoff
+const MAX_RECONNECT_DELAY: Duration = Duration::from_secs(30);
+
+/// MQTT quality of service level
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum MqttQos {
+    /// QoS 0: fire and forget
+    AtMostOnce,
+    /// QoS 1: acknowledged delivery, may duplicate
+    AtLeastOnce,
+    /// QoS 2: exactly-once handshake
+    ExactlyOnce,
+}
+
+impl From<MqttQos> for QoS {
+    fn from(qos: MqttQos) -> Self {
+        match qos {
+            MqttQos::AtMostOnce => QoS::AtMostOnce,
+            MqttQos::AtLeastOnce => QoS::AtLeastOnce,
+            MqttQos::ExactlyOnce => QoS::ExactlyOnce,
+        }
+    }
+}
+
+/// MQTT alerter configuration
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MqttConfig {
+    /// Broker host
+    pub host: String,
+    /// Broker port
+    pub port: u16,
+    /// Client identifier
+    pub client_id: String,
+    /// Optional username
+    pub username: Option<String>,
+    /// Optional password
+    pub password: Option<String>,
+    /// Topic template; supports `{severity}`, `{service}`, `{model}` and `{anomaly_type}`
+    pub topic_template: String,
+    /// Quality of service for published alerts
+    pub qos: MqttQos,
+    /// Publish alerts as retained messages
+    pub retain: bool,
+    /// Keep-alive interval (seconds)
+    pub keep_alive_secs: u64,
+    /// Timeout for enqueueing a publish (seconds)
+    pub timeout_secs: u64,
+    /// Capacity of the outgoing request channel
+    pub channel_capacity: usize,
+    /// Initial delay between reconnect attempts, backing off with jitter (milliseconds)
+    pub reconnect_delay_ms: u64,
+    /// Wrap alerts in a CloudEvents envelope
+    #[serde(default)]
+    pub cloudevents: Option<CloudEventsConfig>,
+    /// Payload encoding (CloudEvents require json)
+    #[serde(default)]
+    pub encoding: PayloadEncoding,
+}
+
+impl Default for MqttConfig {
+    fn default() -> Self {
+        Self {
+            host: "localhost".to_string(),
+            port: 1883,
+            client_id: "sentinel-alerter".to_string(),
+            username: None,
+            password: None,
+            topic_template: "sentinel/alerts/{severity}/{service}".to_string(),
+            qos: MqttQos::AtLeastOnce,
+            retain: false,
+            keep_alive_secs: 30,
+            timeout_secs: 10,
+            channel_capacity: 100,
+            reconnect_delay_ms: 1000,
+            cloudevents: None,
+            encoding: PayloadEncoding::Json,
+        }
+    }
+}
+
+/// MQTT alert publisher
+pub struct MqttAlerter {
+    client: AsyncClient,
+    connected: Arc<AtomicBool>,
+    event_loop: JoinHandle<()>,
+    config: MqttConfig,
+}
+
+impl std::fmt::Debug for MqttAlerter {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("MqttAlerter")
+            .field("config", &self.config)
+            .field("connected", &self.connected.load(Ordering::Relaxed))
+            .finish()
+    }
+}
+
+impl MqttAlerter {
+    /// Create a new MQTT alerter.
+    ///
+    /// Spawns the MQTT event loop on the current Tokio runtime; the connection
+    /// is established (and re-established) in the background.
+    pub fn new(config: MqttConfig) -> Result<Self> {
+        if config.host.is_empty() {
+            return Err(Error::config("MQTT host cannot be empty"));
+        }
+        if config.topic_template.is_empty() {
+            return Err(Error::config("MQTT topic template cannot be empty"));
+        }
+        config.encoding.check(config.cloudevents.as_ref())?;
+
+        info!(
+            "Creating MQTT alerter for {}:{}, topic template '{}'",
+            config.host, config.port, config.topic_template
+        );
+
+        let mut options = MqttOptions::new(&config.client_id, &config.host, config.port);
+        options.set_keep_alive(Duration::from_secs(config.keep_alive_secs));
+        if let (Some(username), Some(password)) = (&config.username, &config.password) {
+            options.set_credentials(username, password);
+        }
+
+        let (client, mut event_loop) = AsyncClient::new(options, config.channel_capacity);
+        let connected = Arc::new(AtomicBool::new(false));
+
+        let state = Arc::clone(&connected);
+        let reconnect = RetryPolicy::default()
+            .with_initial_delay(Duration::from_millis(config.reconnect_delay_ms))
+            .with_max_delay(
+                MAX_RECONNECT_DELAY.max(Duration::from_millis(config.reconnect_delay_ms)),
+            );
+        let event_loop = tokio::spawn(async move {
+            let mut failures = 0;
+            loop {
+                match event_loop.poll().await {
+                    Ok(Event::Incoming(Packet::ConnAck(_))) => {
+                        info!("Connected to MQTT broker");
+                        state.store(true, Ordering::Relaxed);
+                        failures = 0;
+                    }
+                    Ok(Event::Incoming(Packet::PubAck(ack))) => {
+                        debug!(pkid = ack.pkid, "MQTT publish acknowledged");
+                    }
+                    Ok(_) => {}
+                    Err(e) => {
+                        if state.swap(false, Ordering::Relaxed) {
+                            warn!(error = %e, "MQTT connection lost, reconnecting...");
+                        }
+                        failures += 1;
+                        tokio::time::sleep(reconnect.delay(failures)).await;
+                    }
+                }
+            }
+        });
+
+        Ok(Self {
+            client,
+            connected,
+            event_loop,
+            config,
+        })
+    }
+
+    /// Render the topic template for an alert
+    fn build_topic(&self, alert: &AnomalyEvent) -> String {
+        self.config
+            .topic_template
+            .replace("{severity}", &Self::topic_segment(&alert.severity.to_string()))
+            .replace("{service}", &Self::topic_segment(alert.service_name.as_str()))
+            .replace("{model}", &Self::topic_segment(alert.model.as_str()))
+            .replace(
+                "{anomaly_type}",
+                &Self::topic_segment(&alert.anomaly_type.to_string()),
+            )
+    }
+
+    /// Make a value safe to embed as a single topic level
+    fn topic_segment(value: &str) -> String {
+        value
+            .chars()
+            .map(|c| match c {
+                '/' | '+' | '#' => '_',
+                c => c,
+            })
+            .collect()
+    }
+
+    /// Publish an alert to its rendered topic
+    async fn publish(&self, alert: &AnomalyEvent) -> Result<()> {
+        let topic = self.build_topic(alert);
+        let (payload, _) = encode_payload(
+            alert,
+            self.config.encoding,
+            self.config.cloudevents.as_ref(),
+        )?;
+
+        let publish = self
+            .client
+            .publish(&topic, self.config.qos.into(), self.config.retain, payload);
+
+        match tokio::time::timeout(Duration::from_secs(self.config.timeout_secs), publish).await {
+            Ok(Ok(())) => {
+                debug!(
+                    alert_id = %alert.alert_id,
+                    topic = %topic,
+                    "Alert published to MQTT"
+                );
+
+                metrics::counter!(
+                    "sentinel_mqtt_publishes_total",
+                    "severity" => alert.severity.to_string()
+                )
+                .increment(1);
+
+                Ok(())
+            }
+            Ok(Err(e)) => {
+                error!(
+                    alert_id = %alert.alert_id,
+                    topic = %topic,
+                    error = %e,
+                    "Failed to publish alert to MQTT"
+                );
+
+                metrics::counter!("sentinel_mqtt_failures_total").increment(1);
+
+                Err(Error::alerting(format!(
+                    "Failed to publish alert to MQTT topic {}: {}",
+                    topic, e
+                )))
+            }
+            Err(_) => {
+                metrics::counter!("sentinel_mqtt_failures_total").increment(1);
+
+                Err(Error::timeout(format!(
+                    "Timed out publishing alert to MQTT topic {}",
+                    topic
+                )))
+            }
+        }
+    }
+}
+
+impl Drop for MqttAlerter {
+    fn drop(&mut self) {
+        self.event_loop.abort();
+    }
+}
+
+#[async_trait]
+impl Alerter for MqttAlerter {
+    async fn send(&self, alert: &AnomalyEvent) -> Result<()> {
+        self.publish(alert).await
+    }
+
+    async fn health_check(&self) -> Result<()> {
+        if !self.connected.load(Ordering::Relaxed) {
+            return Err(Error::connection("MQTT client is not connected"));
+        }
+
+        Ok(())
+    }
+
+    fn name(&self) -> &str {
+        "MQTT"
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use llm_sentinel_core::types::Severity;
+    use llm_sentinel_fixtures::anomaly;
+
+    fn create_test_anomaly(service: &str, severity: Severity) -> AnomalyEvent {
+        anomaly()
+            .severity(severity)
+            .service(service)
+            .confidence(0.95)
+            .value(500.0)
+            .threshold(300.0)
+            .deviation_sigma(Some(4.0))
+            .time_window("rolling_window")
+            .build()
+    }
+
+    #[test]
+    fn test_config_defaults() {
+        let config = MqttConfig::default();
+        assert_eq!(config.port, 1883);
+        assert_eq!(config.qos, MqttQos::AtLeastOnce);
+        assert_eq!(config.topic_template, "sentinel/alerts/{severity}/{service}");
+    }
+
+    #[test]
+    fn test_qos_serialization() {
+        let json = serde_json::to_string(&MqttQos::ExactlyOnce).unwrap();
+        assert_eq!(json, "\"exactly_once\"");
+        assert_eq!(QoS::from(MqttQos::AtMostOnce), QoS::AtMostOnce);
+    }
+
+    #[tokio::test]
+    async fn test_empty_host_error() {
+        let config = MqttConfig {
+            host: String::new(),
+            ..Default::default()
+        };
+        assert!(MqttAlerter::new(config).is_err());
+    }
+
+    #[tokio::test]
+    async fn test_topic_template() {
+        let alerter = MqttAlerter::new(MqttConfig {
+            topic_template: "edge/{severity}/{service}/{model}/{anomaly_type}".to_string(),
+            ..Default::default()
+        })
+        .unwrap();
+
+        let alert = create_test_anomaly("checkout", Severity::Critical);
+        assert_eq!(
+            alerter.build_topic(&alert),
+            "edge/critical/checkout/gpt-4/latency_spike"
+        );
+    }
+
+    #[tokio::test]
+    async fn test_topic_segment_sanitized() {
+        let alerter = MqttAlerter::new(MqttConfig::default()).unwrap();
+        let alert = create_test_anomaly("team/a+b#", Severity::Low);
+        assert_eq!(alerter.build_topic(&alert), "sentinel/alerts/low/team_a_b_");
+    }
+
+    #[tokio::test]
+    async fn test_health_check_disconnected() {
+        let alerter = MqttAlerter::new(MqttConfig {
+            port: 1,
+            ..Default::default()
+        })
+        .unwrap();
+        assert!(alerter.health_check().await.is_err());
+        assert_eq!(alerter.name(), "MQTT");
+    }
+
+    // Integration tests require a running MQTT broker
+    #[tokio::test]
+    #[ignore = "Requires MQTT broker"]
+    async fn test_send_alert() {
+        let alerter = MqttAlerter::new(MqttConfig::default()).unwrap();
+        let alert = create_test_anomaly("test-service", Severity::High);
+
+        match alerter.send(&alert).await {
+            Ok(_) => println!("Alert sent successfully"),
+            Err(e) => println!("Send failed: {}", e),
+        }
+    }
+}
@@ -0,0 +1,199 @@
+//! Alert payload size limits.
+//!
+//! Anomalies detected from events with huge prompts can encode to payloads
+//! of several megabytes, which Kafka, RabbitMQ, SNS and EventBridge reject.
+//! [`fit_payload`] trims anomalies whose JSON encoding exceeds the limit
+//! before they are sent: prompt and response text in the additional maps
+//! (entries whose key starts with `prompt` or `response`) is dropped first,
+//! then the remaining additional entries, largest first. The anomaly's
+//! `truncation` field tells receivers what was dropped.
+
+use llm_sentinel_core::{
+    config::PayloadLimitConfig,
+    events::{AnomalyEvent, PayloadTruncation},
+    privacy::is_text_key,
+};
+use std::borrow::Cow;
+use tracing::{debug, warn};
+
+/// Additional map entry that can be dropped
+enum Entry {
+    Detail(String),
+    Context(String),
+}
+
+/// Trim an anomaly to the configured payload size limit
+///
+/// Returns the anomaly unchanged when it already fits.
+pub fn fit_payload<'a>(
+    anomaly: &'a AnomalyEvent,
+    config: &PayloadLimitConfig,
+) -> Cow<'a, AnomalyEvent> {
+    let original_bytes = payload_size(anomaly);
+    if original_bytes <= config.max_bytes {
+        return Cow::Borrowed(anomaly);
+    }
+
+    let mut trimmed = anomaly.clone();
+    let mut truncation = PayloadTruncation {
+        original_bytes,
+        ..PayloadTruncation::default()
+    };
+
+    let entries = trimmed.details.additional.len() + trimmed.context.additional.len();
+    trimmed
+        .details
+        .additional
+        .retain(|key, value| !(is_text_key(key) && value.is_string()));
+    trimmed
+        .context
+        .additional
+        .retain(|key, _| !is_text_key(key));
+    truncation.text_dropped =
+        trimmed.details.additional.len() + trimmed.context.additional.len() < entries;
+    trimmed.truncation = Some(truncation.clone());
+
+    let mut remaining: Vec<(usize, Entry)> = trimmed
+        .details
+        .additional
+        .iter()
+        .map(|(key, value)| {
+            (
+                key.len() + value.to_string().len(),
+                Entry::Detail(key.clone()),
+            )
+        })
+        .chain(
+            trimmed
+                .context
+                .additional
+                .iter()
+                .map(|(key, value)| (key.len() + value.len(), Entry::Context(key.clone()))),
+        )
+        .collect();
+    remaining.sort_by_key(|r| std::cmp::Reverse(r.0));
+
+    for (_, entry) in remaining {
+        if payload_size(&trimmed) <= config.max_bytes {
+            break;
+        }
+        match entry {
+            Entry::Detail(key) => {
+                trimmed.details.additional.remove(&key);
+            }
+            Entry::Context(key) => {
+                trimmed.context.additional.remove(&key);
+            }
+        }
+        truncation.additional_dropped += 1;
+        trimmed.truncation = Some(truncation.clone());
+    }
+
+    let bytes = payload_size(&trimmed);
+    metrics::counter!("sentinel_alert_payloads_truncated_total").increment(1);
+    if bytes > config.max_bytes {
+        warn!(
+            alert_id = %anomaly.alert_id,
+            bytes,
+            max_bytes = config.max_bytes,
+            "Alert payload exceeds the size limit after truncation"
+        );
+    } else {
+        debug!(
+            alert_id = %anomaly.alert_id,
+            original_bytes,
+            bytes,
+            "Truncated alert payload"
+        );
+    }
+
+    Cow::Owned(trimmed)
+}
+
+/// Size of the anomaly's JSON encoding (bytes)
+fn payload_size(anomaly: &AnomalyEvent) -> usize {
+    serde_json::to_vec(anomaly).map_or(0, |payload| payload.len())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use llm_sentinel_core::types::AnomalyType;
+    use llm_sentinel_fixtures::anomaly;
+
+    fn create_anomaly() -> AnomalyEvent {
+        anomaly()
+            .anomaly_type(AnomalyType::TokenUsageSpike)
+            .service("chat")
+            .metric("prompt_tokens")
+            .value(120_000.0)
+            .baseline(800.0)
+            .deviation_sigma(Some(12.0))
+            .build()
+    }
+
+    fn limit(max_bytes: usize) -> PayloadLimitConfig {
+        PayloadLimitConfig { max_bytes }
+    }
+
+    #[test]
+    fn test_small_payload_unchanged() {
+        let anomaly = create_anomaly();
+        assert!(matches!(
+            fit_payload(&anomaly, &limit(64 * 1024)),
+            Cow::Borrowed(_)
+        ));
+    }
+
+    #[test]
+    fn test_text_dropped_first() {
+        let mut anomaly = create_anomaly();
+        anomaly
+            .context
+            .additional
+            .insert("prompt_text".to_string(), "x".repeat(100_000));
+        anomaly
+            .details
+            .additional
+            .insert("response_growth".to_string(), serde_json::json!(4.2));
+        anomaly
+            .context
+            .additional
+            .insert("recent_trace_ids".to_string(), "t1,t2".to_string());
+
+        let trimmed = fit_payload(&anomaly, &limit(4096));
+        let truncation = trimmed.truncation.as_ref().unwrap();
+        assert!(truncation.text_dropped);
+        assert_eq!(truncation.additional_dropped, 0);
+        assert!(truncation.original_bytes > 100_000);
+        assert!(!trimmed.context.additional.contains_key("prompt_text"));
+        // Numeric entries are kept
+        assert!(trimmed.details.additional.contains_key("response_growth"));
+        assert!(trimmed.context.additional.contains_key("recent_trace_ids"));
+        assert!(payload_size(&trimmed) <= 4096);
+    }
+
+    #[test]
+    fn test_largest_additional_dropped_next() {
+        let mut anomaly = create_anomaly();
+        anomaly
+            .details
+            .additional
+            .insert("samples".to_string(), serde_json::json!(vec![1.5; 2000]));
+        anomaly
+            .details
+            .additional
+            .insert("window".to_string(), serde_json::json!(100));
+
+        let trimmed = fit_payload(&anomaly, &limit(4096));
+        let truncation = trimmed.truncation.as_ref().unwrap();
+        assert!(!truncation.text_dropped);
+        assert_eq!(truncation.additional_dropped, 1);
+        assert!(!trimmed.details.additional.contains_key("samples"));
+        assert!(trimmed.details.additional.contains_key("window"));
+        assert!(payload_size(&trimmed) <= 4096);
+
+        let json = serde_json::to_value(&*trimmed).unwrap();
+        assert_eq!(json["truncation"]["additional_dropped"], 1);
+    }
+}
@@ -0,0 +1,345 @@
+//! Google Cloud Pub/Sub alert publisher.
+//!
+//! Uses the Pub/Sub REST API. Authentication uses a configured access token,
+//! the GCE/GKE metadata server, or no auth at all when talking to the
+//! emulator (`PUBSUB_EMULATOR_HOST`).
+
+use crate::{
+    cloudevents::CloudEventsConfig,
+    encoding::{encode_payload, PayloadEncoding},
+    http::HttpClientConfig,
+    Alerter,
+};
+use async_trait::async_trait;
+use base64::{engine::general_purpose::STANDARD as BASE64, Engine as _};
+use llm_sentinel_core::{
+    events::AnomalyEvent,
+    gcp::{endpoint_auth, TokenSource},
+    Error, Result,
+};
+use reqwest::Client;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::time::Duration;
+use tracing::{debug, error, info};
+
+const DEFAULT_ENDPOINT: &str = "https://pubsub.googleapis.com";
+
+/// Maximum number of messages per publish request
+const MAX_MESSAGES_PER_REQUEST: usize = 1000;
+
+/// Pub/Sub alerter configuration
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PubSubConfig {
+    /// GCP project ID
+    pub project_id: String,
+    /// Topic to publish to
+    pub topic: String,
+    /// Endpoint override (emulator or private endpoint)
+    pub endpoint: Option<String>,
+    /// Static OAuth2 access token (defaults to the metadata server)
+    pub access_token: Option<String>,
+    /// Use the service name as ordering key
+    pub ordered_by_service: bool,
+    /// Request timeout (seconds)
+    pub timeout_secs: u64,
+    /// Wrap alerts in a CloudEvents envelope
+    #[serde(default)]
+    pub cloudevents: Option<CloudEventsConfig>,
+    /// Payload encoding (CloudEvents require json)
+    #[serde(default)]
+    pub encoding: PayloadEncoding,
+    /// Proxy, CA and DNS settings
+    #[serde(default)]
+    pub http: HttpClientConfig,
+}
+
+impl Default for PubSubConfig {
+    fn default() -> Self {
+        Self {
+            project_id: String::new(),
+            topic: "sentinel-anomalies".to_string(),
+            endpoint: None,
+            access_token: None,
+            ordered_by_service: false,
+            timeout_secs: 10,
+            cloudevents: None,
+            encoding: PayloadEncoding::Json,
+            http: HttpClientConfig::default(),
+        }
+    }
+}
+
+/// Outgoing Pub/Sub message
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct PubsubMessage {
+    data: String,
+    attributes: HashMap<String, String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    ordering_key: Option<String>,
+}
+
+/// Pub/Sub alert publisher
+pub struct PubSubAlerter {
+    client: Client,
+    token: TokenSource,
+    topic_url: String,
+    config: PubSubConfig,
+}
+
+impl std::fmt::Debug for PubSubAlerter {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("PubSubAlerter")
+            .field("config", &self.config)
+            .field("topic_url", &self.topic_url)
+            .finish()
+    }
+}
+
+impl PubSubAlerter {
+    /// Create a new Pub/Sub alerter
+    pub fn new(config: PubSubConfig) -> Result<Self> {
+        if config.project_id.is_empty() {
+            return Err(Error::config("Pub/Sub project ID cannot be empty"));
+        }
+        if config.topic.is_empty() {
+            return Err(Error::config("Pub/Sub topic cannot be empty"));
+        }
+        config.encoding.check(config.cloudevents.as_ref())?;
+
+        let emulator_host = std::env::var("PUBSUB_EMULATOR_HOST").ok();
+        let (endpoint, token) = endpoint_auth(
+            DEFAULT_ENDPOINT,
+            config.endpoint.as_deref(),
+            config.access_token.as_deref(),
+            emulator_host.as_deref(),
+        );
+
+        let topic_url = format!(
+            "{}/v1/projects/{}/topics/{}",
+            endpoint.trim_end_matches('/'),
+            config.project_id,
+            config.topic
+        );
+
+        info!("Creating Pub/Sub alerter for {}", topic_url);
+
+        let client = config
+            .http
+            .builder()?
+            .timeout(Duration::from_secs(config.timeout_secs))
+            .build()
+            .map_err(|e| Error::config(format!("Failed to create HTTP client: {}", e)))?;
+
+        Ok(Self {
+            client,
+            token,
+            topic_url,
+            config,
+        })
+    }
+
+    /// Build an authenticated request
+    async fn request(&self, method: reqwest::Method, url: &str) -> Result<reqwest::RequestBuilder> {
+        let mut request = self.client.request(method, url);
+        if let Some(token) = self.token.token(&self.client).await? {
+            request = request.bearer_auth(token);
+        }
+        Ok(request)
+    }
+
+    /// Build the Pub/Sub message for an alert
+    fn build_message(&self, alert: &AnomalyEvent) -> Result<PubsubMessage> {
+        let (payload, content_type) = encode_payload(
+            alert,
+            self.config.encoding,
+            self.config.cloudevents.as_ref(),
+        )?;
+
+        let mut attributes = HashMap::new();
+        attributes.insert("alert_id".to_string(), alert.alert_id.to_string());
+        attributes.insert("severity".to_string(), alert.severity.to_string());
+        attributes.insert("anomaly_type".to_string(), alert.anomaly_type.to_string());
+        attributes.insert("service".to_string(), alert.service_name.to_string());
+        attributes.insert("model".to_string(), alert.model.to_string());
+        attributes.insert("signature_hash".to_string(), alert.signature_hash.clone());
+        attributes.insert("content_type".to_string(), content_type.to_string());
+
+        Ok(PubsubMessage {
+            data: BASE64.encode(payload),
+            attributes,
+            ordering_key: self
+                .config
+                .ordered_by_service
+                .then(|| alert.service_name.to_string()),
+        })
+    }
+
+    /// Publish a chunk of alerts
+    async fn publish(&self, alerts: &[AnomalyEvent]) -> Result<()> {
+        let messages = alerts
+            .iter()
+            .map(|alert| self.build_message(alert))
+            .collect::<Result<Vec<_>>>()?;
+
+        let url = format!("{}:publish", self.topic_url);
+        let response = self
+            .request(reqwest::Method::POST, &url)
+            .await?
+            .json(&serde_json::json!({ "messages": messages }))
+            .send()
+            .await
+            .map_err(|e| {
+                metrics::counter!("sentinel_pubsub_failures_total").increment(alerts.len() as u64);
+                Error::alerting(format!("Pub/Sub publish request failed: {}", e))
+            })?;
+
+        let status = response.status();
+        if !status.is_success() {
+            let body = response.text().await.unwrap_or_default();
+            error!(status = %status, body = %body, "Pub/Sub publish failed");
+            metrics::counter!("sentinel_pubsub_failures_total").increment(alerts.len() as u64);
+            return Err(Error::alerting(format!(
+                "Pub/Sub publish failed with status {}: {}",
+                status, body
+            )));
+        }
+
+        for alert in alerts {
+            debug!(
+                alert_id = %alert.alert_id,
+                topic = %self.config.topic,
+                "Alert published to Pub/Sub"
+            );
+            metrics::counter!(
+                "sentinel_pubsub_publishes_total",
+                "severity" => alert.severity.to_string()
+            )
+            .increment(1);
+        }
+
+        Ok(())
+    }
+}
+
+#[async_trait]
+impl Alerter for PubSubAlerter {
+    async fn send(&self, alert: &AnomalyEvent) -> Result<()> {
+        self.publish(std::slice::from_ref(alert)).await
+    }
+
+    async fn send_batch(&self, alerts: &[AnomalyEvent]) -> Result<()> {
+        for chunk in alerts.chunks(MAX_MESSAGES_PER_REQUEST) {
+            self.publish(chunk).await?;
+        }
+        Ok(())
+    }
+
+    async fn health_check(&self) -> Result<()> {
+        self.request(reqwest::Method::GET, &self.topic_url)
+            .await?
+            .send()
+            .await
+            .and_then(|r| r.error_for_status())
+            .map_err(|e| Error::connection(format!("Pub/Sub health check failed: {}", e)))?;
+
+        Ok(())
+    }
+
+    fn name(&self) -> &str {
+        "PubSub"
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use llm_sentinel_fixtures::anomaly;
+    use wiremock::{
+        matchers::{header, method, path},
+        Mock, MockServer, ResponseTemplate,
+    };
+
+    fn create_test_config(endpoint: &str) -> PubSubConfig {
+        PubSubConfig {
+            project_id: "test-project".to_string(),
+            topic: "anomalies".to_string(),
+            endpoint: Some(endpoint.to_string()),
+            access_token: Some("test-token".to_string()),
+            ordered_by_service: true,
+            timeout_secs: 5,
+            cloudevents: None,
+            encoding: PayloadEncoding::Json,
+            http: HttpClientConfig::default(),
+        }
+    }
+
+    fn create_test_anomaly() -> AnomalyEvent {
+        anomaly()
+            .service("checkout")
+            .confidence(0.95)
+            .value(500.0)
+            .threshold(300.0)
+            .deviation_sigma(Some(4.0))
+            .time_window("rolling_window")
+            .build()
+    }
+
+    #[test]
+    fn test_empty_project_error() {
+        let config = PubSubConfig {
+            project_id: String::new(),
+            ..Default::default()
+        };
+        assert!(PubSubAlerter::new(config).is_err());
+    }
+
+    #[test]
+    fn test_build_message() {
+        let alerter = PubSubAlerter::new(create_test_config("http://localhost:8085")).unwrap();
+        let alert = create_test_anomaly();
+        let message = alerter.build_message(&alert).unwrap();
+
+        assert_eq!(message.ordering_key.as_deref(), Some("checkout"));
+        assert_eq!(message.attributes.get("severity").unwrap(), "high");
+        assert_eq!(message.attributes.get("anomaly_type").unwrap(), "latency_spike");
+        assert_eq!(message.attributes["signature_hash"], alert.signature_hash);
+
+        let decoded: AnomalyEvent =
+            serde_json::from_slice(&BASE64.decode(message.data).unwrap()).unwrap();
+        assert_eq!(decoded.alert_id, alert.alert_id);
+    }
+
+    #[tokio::test]
+    async fn test_publish_success() {
+        let mock_server = MockServer::start().await;
+
+        Mock::given(method("POST"))
+            .and(path("/v1/projects/test-project/topics/anomalies:publish"))
+            .and(header("Authorization", "Bearer test-token"))
+            .respond_with(
+                ResponseTemplate::new(200).set_body_json(serde_json::json!({ "messageIds": ["1"] })),
+            )
+            .expect(1)
+            .mount(&mock_server)
+            .await;
+
+        let alerter = PubSubAlerter::new(create_test_config(&mock_server.uri())).unwrap();
+        assert!(alerter.send(&create_test_anomaly()).await.is_ok());
+    }
+
+    #[tokio::test]
+    async fn test_publish_failure() {
+        let mock_server = MockServer::start().await;
+
+        Mock::given(method("POST"))
+            .respond_with(ResponseTemplate::new(404).set_body_string("topic not found"))
+            .mount(&mock_server)
+            .await;
+
+        let alerter = PubSubAlerter::new(create_test_config(&mock_server.uri())).unwrap();
+        assert!(alerter.send(&create_test_anomaly()).await.is_err());
+    }
+}
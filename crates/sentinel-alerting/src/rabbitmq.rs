@@ -1,15 +1,27 @@
 //! RabbitMQ alert publisher with severity-based routing.
 
-use crate::Alerter;
+use crate::{
+    cloudevents::CloudEventsConfig,
+    encoding::{encode_payload, PayloadEncoding},
+    Alerter,
+};
 use async_trait::async_trait;
 use lapin::{
-    options::*, types::FieldTable, BasicProperties, Channel, Connection, ConnectionProperties,
-    ExchangeKind,
+    options::*,
+    protocol::{AMQPErrorKind, AMQPSoftError},
+    types::FieldTable,
+    BasicProperties, Channel, Connection, ConnectionProperties, ExchangeKind,
+};
+use llm_sentinel_core::{
+    events::AnomalyEvent,
+    retry::{retry, RetryError, RetryPolicy},
+    selfcheck::{CheckKind, CheckResult, SelfCheck},
+    types::Severity,
+    Error, Result,
 };
-use llm_sentinel_core::{events::AnomalyEvent, types::Severity, Error, Result};
 use serde::{Deserialize, Serialize};
 use std::sync::Arc;
-use std::time::Duration;
+use tokio::time::Instant;
 use tracing::{debug, error, info, warn};
 
 /// RabbitMQ configuration
@@ -29,6 +41,12 @@ pub struct RabbitMqConfig {
     pub timeout_secs: u64,
     /// Retry configuration
     pub retry_config: RetryConfig,
+    /// Wrap alerts in a CloudEvents envelope
+    #[serde(default)]
+    pub cloudevents: Option<CloudEventsConfig>,
+    /// Payload encoding (CloudEvents require json)
+    #[serde(default)]
+    pub encoding: PayloadEncoding,
 }
 
 impl Default for RabbitMqConfig {
@@ -41,6 +59,8 @@ impl Default for RabbitMqConfig {
             persistent: true,
             timeout_secs: 10,
             retry_config: RetryConfig::default(),
+            cloudevents: None,
+            encoding: PayloadEncoding::Json,
         }
     }
 }
@@ -69,6 +89,35 @@ impl Default for RetryConfig {
     }
 }
 
+impl RetryConfig {
+    /// Retry policy for publishes
+    pub fn policy(&self) -> RetryPolicy {
+        RetryPolicy {
+            max_attempts: self.max_attempts.max(1),
+            initial_delay_ms: self.initial_delay_ms,
+            max_delay_ms: self.max_delay_ms,
+            multiplier: self.backoff_multiplier,
+            ..Default::default()
+        }
+    }
+}
+
+/// Exchange kind for a configured exchange type, defaulting to topic
+pub(crate) fn exchange_kind(exchange_type: &str) -> ExchangeKind {
+    match exchange_type {
+        "topic" => ExchangeKind::Topic,
+        "direct" => ExchangeKind::Direct,
+        "fanout" => ExchangeKind::Fanout,
+        _ => {
+            warn!(
+                "Unknown exchange type '{}', defaulting to topic",
+                exchange_type
+            );
+            ExchangeKind::Topic
+        }
+    }
+}
+
 /// RabbitMQ alert publisher
 pub struct RabbitMqAlerter {
     channel: Arc<Channel>,
@@ -86,6 +135,7 @@ impl std::fmt::Debug for RabbitMqAlerter {
 impl RabbitMqAlerter {
     /// Create a new RabbitMQ alerter
     pub async fn new(config: RabbitMqConfig) -> Result<Self> {
+        config.encoding.check(config.cloudevents.as_ref())?;
         info!("Connecting to RabbitMQ at {}", config.url);
 
         let connection = Connection::connect(
@@ -104,18 +154,7 @@ impl RabbitMqAlerter {
             .map_err(|e| Error::connection(format!("Failed to create channel: {}", e)))?;
 
         // Declare exchange
-        let exchange_kind = match config.exchange_type.as_str() {
-            "topic" => ExchangeKind::Topic,
-            "direct" => ExchangeKind::Direct,
-            "fanout" => ExchangeKind::Fanout,
-            _ => {
-                warn!(
-                    "Unknown exchange type '{}', defaulting to topic",
-                    config.exchange_type
-                );
-                ExchangeKind::Topic
-            }
-        };
+        let exchange_kind = exchange_kind(&config.exchange_type);
 
         channel
             .exchange_declare(
@@ -156,83 +195,81 @@ impl RabbitMqAlerter {
     /// Publish alert with retry logic
     async fn publish_with_retry(&self, alert: &AnomalyEvent) -> Result<()> {
         let routing_key = self.build_routing_key(alert.severity);
-        let payload = serde_json::to_vec(alert)
-            .map_err(|e| Error::internal(format!("Failed to serialize alert: {}", e)))?;
+        let (payload, content_type) = encode_payload(
+            alert,
+            self.config.encoding,
+            self.config.cloudevents.as_ref(),
+        )?;
 
         let properties = BasicProperties::default()
             .with_delivery_mode(if self.config.persistent { 2 } else { 1 })
-            .with_content_type("application/json".into())
+            .with_content_type(content_type.into())
             .with_timestamp(chrono::Utc::now().timestamp() as u64)
             .with_message_id(alert.alert_id.to_string().into());
 
-        let mut attempt = 0;
-        let mut delay = self.config.retry_config.initial_delay_ms;
-
-        loop {
-            attempt += 1;
-
-            match self
-                .channel
-                .basic_publish(
-                    &self.config.exchange,
-                    &routing_key,
-                    BasicPublishOptions::default(),
-                    &payload,
-                    properties.clone(),
-                )
-                .await
-            {
-                Ok(_) => {
-                    debug!(
-                        alert_id = %alert.alert_id,
-                        routing_key = %routing_key,
-                        attempt = attempt,
-                        "Alert published to RabbitMQ"
-                    );
-
-                    metrics::counter!(
-                        "sentinel_rabbitmq_publishes_total",
-                        "severity" => routing_key.clone()
+        let result = retry(&self.config.retry_config.policy(), |attempt| {
+            let routing_key = &routing_key;
+            let payload = &payload;
+            let properties = properties.clone();
+            async move {
+                self.channel
+                    .basic_publish(
+                        &self.config.exchange,
+                        routing_key,
+                        BasicPublishOptions::default(),
+                        payload,
+                        properties,
                     )
-                    .increment(1);
-
-                    if attempt > 1 {
-                        metrics::counter!("sentinel_rabbitmq_retries_total").increment(1);
-                    }
-
-                    return Ok(());
-                }
-                Err(e) => {
-                    if attempt >= self.config.retry_config.max_attempts {
-                        error!(
+                    .await
+                    .map_err(|e| {
+                        warn!(
                             alert_id = %alert.alert_id,
-                            attempts = attempt,
+                            attempt = attempt,
                             error = %e,
-                            "Failed to publish alert after max retries"
+                            "Failed to publish alert"
                         );
+                        RetryError::Transient(e)
+                    })?;
+                Ok(attempt)
+            }
+        })
+        .await;
 
-                        metrics::counter!("sentinel_rabbitmq_failures_total").increment(1);
+        match result {
+            Ok(attempts) => {
+                debug!(
+                    alert_id = %alert.alert_id,
+                    routing_key = %routing_key,
+                    attempt = attempts,
+                    "Alert published to RabbitMQ"
+                );
 
-                        return Err(Error::alerting(format!(
-                            "Failed to publish alert after {} attempts: {}",
-                            attempt, e
-                        )));
-                    }
+                metrics::counter!(
+                    "sentinel_rabbitmq_publishes_total",
+                    "severity" => routing_key.clone()
+                )
+                .increment(1);
 
-                    warn!(
-                        alert_id = %alert.alert_id,
-                        attempt = attempt,
-                        delay_ms = delay,
-                        error = %e,
-                        "Failed to publish alert, retrying..."
-                    );
+                if attempts > 1 {
+                    metrics::counter!("sentinel_rabbitmq_retries_total").increment(1);
+                }
 
-                    tokio::time::sleep(Duration::from_millis(delay)).await;
+                Ok(())
+            }
+            Err(failure) => {
+                error!(
+                    alert_id = %alert.alert_id,
+                    attempts = failure.attempts,
+                    error = %failure.error,
+                    "Failed to publish alert after max retries"
+                );
 
-                    // Exponential backoff
-                    delay = (delay as f64 * self.config.retry_config.backoff_multiplier) as u64;
-                    delay = delay.min(self.config.retry_config.max_delay_ms);
-                }
+                metrics::counter!("sentinel_rabbitmq_failures_total").increment(1);
+
+                Err(Error::alerting(format!(
+                    "Failed to publish alert after {} attempts: {}",
+                    failure.attempts, failure.error
+                )))
             }
         }
     }
@@ -288,13 +325,163 @@ impl Alerter for RabbitMqAlerter {
     }
 }
 
+/// Self-check of the RabbitMQ exchange alerts are published to: broker
+/// reachable, vhost and exchange permissions granted, and the exchange
+/// compatible with the configured type
+///
+/// The exchange is declared exactly as the alerter declares it at startup,
+/// so a missing exchange is created rather than reported.
+#[derive(Debug, Clone)]
+pub struct RabbitMqCheck {
+    dependency: String,
+    url: String,
+    exchange: String,
+    exchange_type: String,
+}
+
+impl RabbitMqCheck {
+    /// Check `exchange` of type `exchange_type` on the broker at `url`,
+    /// reported as `dependency`
+    pub fn new(
+        dependency: impl Into<String>,
+        url: impl Into<String>,
+        exchange: impl Into<String>,
+        exchange_type: impl Into<String>,
+    ) -> Self {
+        Self {
+            dependency: dependency.into(),
+            url: url.into(),
+            exchange: exchange.into(),
+            exchange_type: exchange_type.into(),
+        }
+    }
+}
+
+/// AMQP reply code of a broker error, if the broker sent one
+fn amqp_error(e: &lapin::Error) -> Option<&AMQPErrorKind> {
+    match e {
+        lapin::Error::ProtocolError(e) => Some(e.kind()),
+        _ => None,
+    }
+}
+
+#[async_trait]
+impl SelfCheck for RabbitMqCheck {
+    fn dependency(&self) -> String {
+        self.dependency.clone()
+    }
+
+    async fn run(&self) -> Vec<CheckResult> {
+        let name = self.dependency.as_str();
+        let refused = AMQPErrorKind::Soft(AMQPSoftError::ACCESSREFUSED);
+        let started = Instant::now();
+        let connected = match Connection::connect(
+            &self.url,
+            ConnectionProperties::default().with_connection_name("sentinel-selfcheck".into()),
+        )
+        .await
+        {
+            Ok(connection) => connection
+                .create_channel()
+                .await
+                .map(|channel| (connection, channel)),
+            Err(e) => Err(e),
+        };
+        let (connection, channel) = match connected {
+            Ok(connected) => connected,
+            Err(e) if amqp_error(&e) == Some(&refused) => {
+                return vec![
+                    CheckResult::pass(name, CheckKind::Connectivity, "broker answered")
+                        .timed(started),
+                    CheckResult::fail(
+                        name,
+                        CheckKind::Permission,
+                        format!("access to the virtual host refused: {}", e),
+                    ),
+                    CheckResult::skip(name, CheckKind::Schema),
+                ]
+            }
+            Err(e) => {
+                return vec![
+                    CheckResult::fail(name, CheckKind::Connectivity, e.to_string()).timed(started),
+                    CheckResult::skip(name, CheckKind::Permission),
+                    CheckResult::skip(name, CheckKind::Schema),
+                ]
+            }
+        };
+        let mut results = vec![CheckResult::pass(
+            name,
+            CheckKind::Connectivity,
+            "connected and opened a channel",
+        )
+        .timed(started)];
+
+        let started = Instant::now();
+        let declared = channel
+            .exchange_declare(
+                &self.exchange,
+                exchange_kind(&self.exchange_type),
+                ExchangeDeclareOptions {
+                    durable: true,
+                    ..Default::default()
+                },
+                FieldTable::default(),
+            )
+            .await;
+        match declared {
+            Ok(()) => {
+                results.push(
+                    CheckResult::pass(
+                        name,
+                        CheckKind::Permission,
+                        format!("declare on exchange {} allowed", self.exchange),
+                    )
+                    .timed(started),
+                );
+                results.push(CheckResult::pass(
+                    name,
+                    CheckKind::Schema,
+                    format!(
+                        "exchange {} is a durable {} exchange",
+                        self.exchange, self.exchange_type
+                    ),
+                ));
+            }
+            Err(e)
+                if amqp_error(&e)
+                    == Some(&AMQPErrorKind::Soft(AMQPSoftError::PRECONDITIONFAILED)) =>
+            {
+                results.push(
+                    CheckResult::pass(name, CheckKind::Permission, "declare allowed")
+                        .timed(started),
+                );
+                results.push(CheckResult::fail(
+                    name,
+                    CheckKind::Schema,
+                    format!(
+                        "exchange {} exists with a different type or durability",
+                        self.exchange
+                    ),
+                ));
+            }
+            Err(e) => {
+                results.push(
+                    CheckResult::fail(name, CheckKind::Permission, e.to_string()).timed(started),
+                );
+                results.push(CheckResult::skip(name, CheckKind::Schema));
+            }
+        }
+
+        let _ = connection.close(200, "self-check done").await;
+        results
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
-    use llm_sentinel_core::{
-        events::{AnomalyDetails, PromptInfo, ResponseInfo, TelemetryEvent},
-        types::{AnomalyType, DetectionMethod, ModelId, ServiceId},
-    };
+
+    use llm_sentinel_fixtures::anomaly;
 
     fn create_test_config() -> RabbitMqConfig {
         RabbitMqConfig {
@@ -310,42 +497,21 @@ mod tests {
                 backoff_multiplier: 2.0,
                 max_delay_ms: 5000,
             },
+            cloudevents: None,
+            encoding: PayloadEncoding::Json,
         }
     }
 
     fn create_test_anomaly(severity: Severity) -> AnomalyEvent {
-        let telemetry = TelemetryEvent::new(
-            ServiceId::new("test-service"),
-            ModelId::new("gpt-4"),
-            PromptInfo {
-                text: "test".to_string(),
-                tokens: 10,
-                embedding: None,
-            },
-            ResponseInfo {
-                text: "response".to_string(),
-                tokens: 20,
-                finish_reason: "stop".to_string(),
-                embedding: None,
-            },
-            100.0,
-            0.01,
-        );
-
-        AnomalyEvent::new(
-            severity,
-            AnomalyType::LatencySpike,
-            DetectionMethod::ZScore,
-            0.95,
-            AnomalyDetails {
-                metric: "latency_ms".to_string(),
-                value: 500.0,
-                baseline: 100.0,
-                threshold: 300.0,
-                deviation_percent: 400.0,
-            },
-            &telemetry,
-        )
+        anomaly()
+            .severity(severity)
+            .service("test-service")
+            .confidence(0.95)
+            .value(500.0)
+            .threshold(300.0)
+            .deviation_sigma(Some(4.0))
+            .time_window("rolling_window")
+            .build()
     }
 
     #[test]
@@ -376,6 +542,15 @@ mod tests {
         assert_eq!(config.backoff_multiplier, 2.0);
     }
 
+    #[test]
+    fn test_retry_config_policy() {
+        let policy = create_test_config().retry_config.policy();
+        assert_eq!(policy.max_attempts, 3);
+        assert_eq!(policy.backoff(1), std::time::Duration::from_millis(100));
+        assert_eq!(policy.backoff(10), std::time::Duration::from_millis(5000));
+        assert!(policy.jitter);
+    }
+
     // Integration tests require a running RabbitMQ instance
     #[tokio::test]
     #[ignore = "Requires RabbitMQ"]
@@ -0,0 +1,119 @@
+//! Recording alerter for tests.
+//!
+//! [`RecordingAlerter`] keeps every alert it is asked to send so tests can
+//! assert on what would have been delivered, and can be switched into a
+//! failing mode to exercise error paths. Available with the `test-util`
+//! feature.
+
+use async_trait::async_trait;
+use llm_sentinel_core::{events::AnomalyEvent, Error, Result};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Mutex;
+
+use crate::Alerter;
+
+/// Alerter that records sent alerts instead of delivering them
+#[derive(Debug)]
+pub struct RecordingAlerter {
+    name: String,
+    sent: Mutex<Vec<AnomalyEvent>>,
+    failing: AtomicBool,
+}
+
+impl RecordingAlerter {
+    /// Create a recording alerter with the given name
+    pub fn new(name: impl Into<String>) -> Self {
+        Self {
+            name: name.into(),
+            sent: Mutex::new(Vec::new()),
+            failing: AtomicBool::new(false),
+        }
+    }
+
+    /// Alerts sent so far, in order
+    pub fn sent(&self) -> Vec<AnomalyEvent> {
+        self.sent.lock().unwrap().clone()
+    }
+
+    /// Number of alerts sent so far
+    pub fn count(&self) -> usize {
+        self.sent.lock().unwrap().len()
+    }
+
+    /// Make sends and health checks fail (or succeed again)
+    ///
+    /// Failed sends are not recorded.
+    pub fn set_failing(&self, failing: bool) {
+        self.failing.store(failing, Ordering::SeqCst);
+    }
+
+    /// Forget all recorded alerts
+    pub fn clear(&self) {
+        self.sent.lock().unwrap().clear();
+    }
+
+    fn check(&self) -> Result<()> {
+        if self.failing.load(Ordering::SeqCst) {
+            Err(Error::alerting(format!(
+                "{} alerter set to fail",
+                self.name
+            )))
+        } else {
+            Ok(())
+        }
+    }
+}
+
+impl Default for RecordingAlerter {
+    fn default() -> Self {
+        Self::new("recording")
+    }
+}
+
+#[async_trait]
+impl Alerter for RecordingAlerter {
+    async fn send(&self, alert: &AnomalyEvent) -> Result<()> {
+        self.check()?;
+        self.sent.lock().unwrap().push(alert.clone());
+        Ok(())
+    }
+
+    async fn health_check(&self) -> Result<()> {
+        self.check()
+    }
+
+    fn name(&self) -> &str {
+        &self.name
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{deliver, AlertStatus};
+
+    use llm_sentinel_fixtures::anomaly;
+
+    fn create_test_anomaly() -> AnomalyEvent {
+        anomaly().build()
+    }
+
+    #[tokio::test]
+    async fn test_deliver_records_outcome() {
+        let alerter = RecordingAlerter::new("pager");
+        let anomaly = create_test_anomaly();
+
+        let delivery = deliver(&alerter, &anomaly).await;
+        assert_eq!(delivery.status, AlertStatus::Delivered);
+        assert_eq!(delivery.alerter, "pager");
+        assert!(delivery.latency_ms.is_some());
+        assert_eq!(alerter.sent()[0].alert_id, anomaly.alert_id);
+
+        alerter.set_failing(true);
+        let delivery = deliver(&alerter, &anomaly).await;
+        assert_eq!(delivery.status, AlertStatus::Failed);
+        assert!(delivery.error.is_some());
+        assert_eq!(alerter.count(), 1);
+        assert!(alerter.health_check().await.is_err());
+    }
+}
@@ -0,0 +1,278 @@
+//! Alerter registry for config-driven construction.
+//!
+//! Maps alerter type strings (`rabbitmq`, `webhook`, `kafka`, `github`, ...) to
+//! factories so the set of alerters can be built from configuration.
+//! Third-party crates register their own factories under new type names,
+//! or replace a built-in one, before building.
+
+use async_trait::async_trait;
+use llm_sentinel_core::{config::AlerterSpec, Error, Result};
+use serde::{de::DeserializeOwned, Serialize};
+use serde_json::Value;
+use std::{collections::BTreeMap, future::Future, sync::Arc};
+
+use crate::{
+    aws::{EventBridgeAlerter, SnsAlerter},
+    datadog::DatadogAlerter,
+    discord::DiscordAlerter,
+    issues::{IssueAlerter, IssueConfig, IssueProvider},
+    mqtt::MqttAlerter,
+    pubsub::PubSubAlerter,
+    splunk::SplunkAlerter,
+    telegram::TelegramAlerter,
+    twilio::TwilioAlerter,
+    webhook::WebhookAlerter,
+    Alerter,
+};
+
+#[cfg(feature = "kafka")]
+use crate::kafka::KafkaAlerter;
+#[cfg(feature = "rabbitmq")]
+use crate::rabbitmq::RabbitMqAlerter;
+
+/// Factory constructing an alerter from its settings
+#[async_trait]
+pub trait AlerterFactory: Send + Sync {
+    /// Create an alerter from the settings object of an [`AlerterSpec`]
+    async fn create(&self, settings: Value) -> Result<Arc<dyn Alerter>>;
+}
+
+#[async_trait]
+impl<F, Fut> AlerterFactory for F
+where
+    F: Fn(Value) -> Fut + Send + Sync,
+    Fut: Future<Output = Result<Arc<dyn Alerter>>> + Send,
+{
+    async fn create(&self, settings: Value) -> Result<Arc<dyn Alerter>> {
+        self(settings).await
+    }
+}
+
+/// Registry of alerter factories keyed by type
+#[derive(Default)]
+pub struct AlerterRegistry {
+    factories: BTreeMap<String, Arc<dyn AlerterFactory>>,
+}
+
+impl AlerterRegistry {
+    /// Create an empty registry
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Create a registry with all built-in alerters registered
+    pub fn with_builtin() -> Self {
+        let mut registry = Self::new();
+        #[cfg(feature = "rabbitmq")]
+        registry.register("rabbitmq", |settings| async move {
+            let alerter = RabbitMqAlerter::new(with_defaults(settings)?).await?;
+            Ok(Arc::new(alerter) as Arc<dyn Alerter>)
+        });
+        #[cfg(feature = "kafka")]
+        registry.register("kafka", |settings| async move {
+            let alerter = KafkaAlerter::new(with_defaults(settings)?)?;
+            Ok(Arc::new(alerter) as Arc<dyn Alerter>)
+        });
+        registry
+            .register("webhook", |settings| async move {
+                let alerter = WebhookAlerter::new(with_defaults(settings)?)?;
+                Ok(Arc::new(alerter) as Arc<dyn Alerter>)
+            })
+            .register("mqtt", |settings| async move {
+                let alerter = MqttAlerter::new(with_defaults(settings)?)?;
+                Ok(Arc::new(alerter) as Arc<dyn Alerter>)
+            })
+            .register("pubsub", |settings| async move {
+                let alerter = PubSubAlerter::new(with_defaults(settings)?)?;
+                Ok(Arc::new(alerter) as Arc<dyn Alerter>)
+            })
+            .register("sns", |settings| async move {
+                let alerter = SnsAlerter::new(with_defaults(settings)?)?;
+                Ok(Arc::new(alerter) as Arc<dyn Alerter>)
+            })
+            .register("eventbridge", |settings| async move {
+                let alerter = EventBridgeAlerter::new(with_defaults(settings)?)?;
+                Ok(Arc::new(alerter) as Arc<dyn Alerter>)
+            })
+            .register("github", |settings| async move {
+                let config = IssueConfig {
+                    provider: IssueProvider::Github,
+                    ..with_defaults(settings)?
+                };
+                Ok(Arc::new(IssueAlerter::new(config)?) as Arc<dyn Alerter>)
+            })
+            .register("gitlab", |settings| async move {
+                let config = IssueConfig {
+                    provider: IssueProvider::Gitlab,
+                    ..with_defaults(settings)?
+                };
+                Ok(Arc::new(IssueAlerter::new(config)?) as Arc<dyn Alerter>)
+            })
+            .register("telegram", |settings| async move {
+                let alerter = TelegramAlerter::new(with_defaults(settings)?)?;
+                Ok(Arc::new(alerter) as Arc<dyn Alerter>)
+            })
+            .register("discord", |settings| async move {
+                let alerter = DiscordAlerter::new(with_defaults(settings)?)?;
+                Ok(Arc::new(alerter) as Arc<dyn Alerter>)
+            })
+            .register("twilio", |settings| async move {
+                let alerter = TwilioAlerter::new(with_defaults(settings)?)?;
+                Ok(Arc::new(alerter) as Arc<dyn Alerter>)
+            })
+            .register("splunk", |settings| async move {
+                let alerter = SplunkAlerter::new(with_defaults(settings)?)?;
+                Ok(Arc::new(alerter) as Arc<dyn Alerter>)
+            })
+            .register("datadog", |settings| async move {
+                let alerter = DatadogAlerter::new(with_defaults(settings)?)?;
+                Ok(Arc::new(alerter) as Arc<dyn Alerter>)
+            });
+        registry
+    }
+
+    /// Register a factory, replacing any existing factory for the type
+    pub fn register(
+        &mut self,
+        kind: impl Into<String>,
+        factory: impl AlerterFactory + 'static,
+    ) -> &mut Self {
+        self.factories
+            .insert(kind.into().to_lowercase(), Arc::new(factory));
+        self
+    }
+
+    /// Whether a factory is registered for the type
+    pub fn contains(&self, kind: &str) -> bool {
+        self.factories.contains_key(&kind.to_lowercase())
+    }
+
+    /// Registered alerter types
+    pub fn kinds(&self) -> Vec<&str> {
+        self.factories.keys().map(String::as_str).collect()
+    }
+
+    /// Construct the alerter described by a spec
+    pub async fn build(&self, spec: &AlerterSpec) -> Result<Arc<dyn Alerter>> {
+        let factory = self
+            .factories
+            .get(&spec.kind.to_lowercase())
+            .ok_or_else(|| {
+                Error::config(format!(
+                    "Unknown alerter type: {} (registered: {})",
+                    spec.kind,
+                    self.kinds().join(", ")
+                ))
+            })?;
+
+        factory
+            .create(Value::Object(spec.settings.clone()))
+            .await
+            .map_err(|e| e.context(format!("Failed to create {} alerter", spec.kind)))
+    }
+
+    /// Construct all alerters described by the specs, in order
+    pub async fn build_all(&self, specs: &[AlerterSpec]) -> Result<Vec<Arc<dyn Alerter>>> {
+        let mut alerters = Vec::with_capacity(specs.len());
+        for spec in specs {
+            alerters.push(self.build(spec).await?);
+        }
+        Ok(alerters)
+    }
+}
+
+impl std::fmt::Debug for AlerterRegistry {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("AlerterRegistry")
+            .field("kinds", &self.kinds())
+            .finish()
+    }
+}
+
+/// Deserialize alerter settings on top of the config type's defaults
+///
+/// Keys missing from `settings` keep their default value, so a spec only
+/// needs the fields it changes.
+pub fn with_defaults<T>(settings: Value) -> Result<T>
+where
+    T: DeserializeOwned + Serialize + Default,
+{
+    let mut config = serde_json::to_value(T::default())?;
+    match (&mut config, settings) {
+        (Value::Object(defaults), Value::Object(overrides)) => defaults.extend(overrides),
+        (_, Value::Null) => {}
+        (_, other) => config = other,
+    }
+
+    serde_json::from_value(config)
+        .map_err(|e| Error::config(format!("Invalid alerter settings: {}", e)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::recording::RecordingAlerter;
+    use crate::webhook::WebhookConfig;
+
+    fn spec(value: Value) -> AlerterSpec {
+        serde_json::from_value(value).unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_custom_alerter() {
+        let mut registry = AlerterRegistry::with_builtin();
+        registry.register("Custom", |settings: Value| async move {
+            let name = settings["name"].as_str().unwrap_or("custom").to_string();
+            Ok(Arc::new(RecordingAlerter::new(name)) as Arc<dyn Alerter>)
+        });
+        assert!(registry.contains("custom"));
+        assert!(registry.kinds().contains(&"webhook"));
+
+        let alerters = registry
+            .build_all(&[spec(
+                serde_json::json!({ "type": "custom", "name": "pager" }),
+            )])
+            .await
+            .unwrap();
+        assert_eq!(alerters.len(), 1);
+        assert_eq!(alerters[0].name(), "pager");
+
+        let err = registry
+            .build(&spec(serde_json::json!({ "type": "carrier-pigeon" })))
+            .await;
+        assert!(err.err().unwrap().to_string().contains("carrier-pigeon"));
+    }
+
+    #[tokio::test]
+    async fn test_builtin_webhook() {
+        let registry = AlerterRegistry::with_builtin();
+
+        let alerter = registry
+            .build(&spec(
+                serde_json::json!({ "type": "webhook", "url": "https://example.com/hook" }),
+            ))
+            .await
+            .unwrap();
+        assert_eq!(alerter.name(), "Webhook");
+
+        // Validation errors from the alerter are surfaced
+        assert!(registry
+            .build(&spec(serde_json::json!({ "type": "webhook" })))
+            .await
+            .is_err());
+    }
+
+    #[test]
+    fn test_with_defaults() {
+        let config: WebhookConfig =
+            with_defaults(serde_json::json!({ "url": "https://example.com", "max_retries": 5 }))
+                .unwrap();
+        assert_eq!(config.url, "https://example.com");
+        assert_eq!(config.max_retries, 5);
+        assert_eq!(config.timeout_secs, 10);
+
+        assert!(
+            with_defaults::<WebhookConfig>(serde_json::json!({ "max_retries": "many" })).is_err()
+        );
+    }
+}
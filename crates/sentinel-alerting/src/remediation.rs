@@ -0,0 +1,662 @@
+//! Automated remediation actions.
+//!
+//! Rules match anomaly signatures (type, minimum severity, service, model)
+//! and trigger an action: a webhook, a generic HTTP request (e.g. scaling a
+//! deployment) or a ticket. Guardrails keep automation from running away:
+//! a cooldown per rule and service/model, an hourly cap per rule and a global
+//! hourly cap. Every decision, including skipped ones, is recorded in the
+//! audit log.
+
+use chrono::{DateTime, Duration as ChronoDuration, Utc};
+use llm_sentinel_core::{
+    audit::{AuditEntry, AuditLog, AuditOutcome},
+    config::{RemediationActionConfig, RemediationConfig, RemediationRuleConfig},
+    events::AnomalyEvent,
+    types::{ModelId, ServiceId},
+    Error, Result,
+};
+use reqwest::{header::CONTENT_TYPE, Client, Method};
+use serde::{Deserialize, Serialize};
+use std::{
+    collections::{BTreeMap, HashMap, VecDeque},
+    fmt,
+    sync::{Arc, Mutex},
+    time::Duration,
+};
+use tracing::{info, warn};
+
+/// Result of a triggered remediation rule
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "status", rename_all = "snake_case")]
+pub enum RemediationStatus {
+    /// The action ran and the target accepted it
+    Executed {
+        /// HTTP status code
+        status_code: u16,
+    },
+    /// The action ran and failed
+    Failed {
+        /// Failure reason
+        error: String,
+    },
+    /// Skipped: the rule fired for this service/model too recently
+    Cooldown,
+    /// Skipped: the rule or global hourly cap is reached
+    RateLimited,
+    /// Skipped: dry run
+    DryRun,
+}
+
+impl RemediationStatus {
+    fn outcome(&self) -> AuditOutcome {
+        match self {
+            RemediationStatus::Executed { .. } => AuditOutcome::Success,
+            RemediationStatus::Failed { .. } => AuditOutcome::Failure,
+            _ => AuditOutcome::Skipped,
+        }
+    }
+}
+
+impl fmt::Display for RemediationStatus {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            RemediationStatus::Executed { .. } => write!(f, "executed"),
+            RemediationStatus::Failed { .. } => write!(f, "failed"),
+            RemediationStatus::Cooldown => write!(f, "cooldown"),
+            RemediationStatus::RateLimited => write!(f, "rate_limited"),
+            RemediationStatus::DryRun => write!(f, "dry_run"),
+        }
+    }
+}
+
+/// Outcome of one triggered rule
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct RemediationOutcome {
+    /// Rule name
+    pub rule: String,
+    /// Action type (`webhook`, `http`, `ticket`)
+    pub action: String,
+    /// What happened
+    #[serde(flatten)]
+    pub status: RemediationStatus,
+}
+
+/// Sliding-window guardrail state
+#[derive(Debug, Default)]
+struct Guardrails {
+    last_action: HashMap<(String, ServiceId, ModelId), DateTime<Utc>>,
+    rule_actions: HashMap<String, VecDeque<DateTime<Utc>>>,
+    actions: VecDeque<DateTime<Utc>>,
+}
+
+/// Executes remediation rules for anomalies
+pub struct RemediationEngine {
+    config: RemediationConfig,
+    client: Client,
+    audit: Arc<dyn AuditLog>,
+    guardrails: Mutex<Guardrails>,
+}
+
+impl RemediationEngine {
+    /// Create a remediation engine recording decisions in `audit`
+    pub fn new(config: RemediationConfig, audit: Arc<dyn AuditLog>) -> Result<Self> {
+        for rule in &config.rules {
+            let (url, method) = match &rule.action {
+                RemediationActionConfig::Webhook { url, .. }
+                | RemediationActionConfig::Ticket { url, .. } => (url, None),
+                RemediationActionConfig::Http { url, method, .. } => (url, Some(method)),
+            };
+            if url.is_empty() {
+                return Err(Error::config(format!(
+                    "Remediation rule {} has an empty URL",
+                    rule.name
+                )));
+            }
+            if let Some(method) = method {
+                Method::from_bytes(method.to_uppercase().as_bytes()).map_err(|_| {
+                    Error::config(format!(
+                        "Remediation rule {} has an invalid HTTP method: {}",
+                        rule.name, method
+                    ))
+                })?;
+            }
+        }
+
+        let client = Client::builder()
+            .timeout(Duration::from_secs(10))
+            .build()
+            .map_err(|e| Error::config(format!("Failed to create HTTP client: {}", e)))?;
+
+        info!(
+            rules = config.rules.len(),
+            dry_run = config.dry_run,
+            "Remediation enabled"
+        );
+
+        Ok(Self {
+            config,
+            client,
+            audit,
+            guardrails: Mutex::new(Guardrails::default()),
+        })
+    }
+
+    /// Run every rule matching the anomaly
+    pub async fn handle(&self, anomaly: &AnomalyEvent) -> Vec<RemediationOutcome> {
+        self.handle_at(anomaly, Utc::now()).await
+    }
+
+    async fn handle_at(
+        &self,
+        anomaly: &AnomalyEvent,
+        now: DateTime<Utc>,
+    ) -> Vec<RemediationOutcome> {
+        let mut outcomes = Vec::new();
+
+        for rule in self
+            .config
+            .rules
+            .iter()
+            .filter(|r| Self::matches(r, anomaly))
+        {
+            let status = match self.reserve(rule, anomaly, now) {
+                Err(skipped) => skipped,
+                Ok(()) if self.config.dry_run => RemediationStatus::DryRun,
+                Ok(()) => self.execute(&rule.action, anomaly).await,
+            };
+            let action = action_kind(&rule.action);
+
+            match &status {
+                RemediationStatus::Failed { error } => {
+                    warn!(rule = %rule.name, alert_id = %anomaly.alert_id, "Remediation failed: {}", error)
+                }
+                status => {
+                    info!(rule = %rule.name, alert_id = %anomaly.alert_id, %status, "Remediation")
+                }
+            }
+            metrics::counter!(
+                "sentinel_remediation_actions_total",
+                "rule" => rule.name.clone(),
+                "status" => status.to_string()
+            )
+            .increment(1);
+
+            let mut entry = AuditEntry::new(
+                "remediation",
+                format!("remediation.{}", action),
+                format!("{}/{}", anomaly.service_name, anomaly.model),
+                status.outcome(),
+            )
+            .with_detail("rule", rule.name.clone())
+            .with_detail("alert_id", anomaly.alert_id.to_string())
+            .with_detail("anomaly_type", anomaly.anomaly_type.to_string())
+            .with_detail("status", status.to_string());
+            match &status {
+                RemediationStatus::Executed { status_code } => {
+                    entry = entry.with_detail("status_code", status_code.to_string())
+                }
+                RemediationStatus::Failed { error } => {
+                    entry = entry.with_detail("error", error.clone())
+                }
+                _ => {}
+            }
+            self.audit.record(entry);
+
+            outcomes.push(RemediationOutcome {
+                rule: rule.name.clone(),
+                action: action.to_string(),
+                status,
+            });
+        }
+
+        outcomes
+    }
+
+    /// Whether the anomaly matches the rule's signature
+    fn matches(rule: &RemediationRuleConfig, anomaly: &AnomalyEvent) -> bool {
+        let anomaly_type = anomaly.anomaly_type.to_string();
+        (rule.anomaly_types.is_empty() || rule.anomaly_types.contains(&anomaly_type))
+            && rule
+                .min_severity
+                .map_or(true, |min| anomaly.severity >= min)
+            && (rule.services.is_empty()
+                || rule
+                    .services
+                    .iter()
+                    .any(|s| s == anomaly.service_name.as_str()))
+            && (rule.models.is_empty() || rule.models.iter().any(|m| m == anomaly.model.as_str()))
+    }
+
+    /// Check the guardrails and, if they allow it, count the action
+    fn reserve(
+        &self,
+        rule: &RemediationRuleConfig,
+        anomaly: &AnomalyEvent,
+        now: DateTime<Utc>,
+    ) -> std::result::Result<(), RemediationStatus> {
+        let mut guardrails = self.guardrails.lock().unwrap();
+        let hour_ago = now - ChronoDuration::hours(1);
+
+        let key = (
+            rule.name.clone(),
+            anomaly.service_name.clone(),
+            anomaly.model.clone(),
+        );
+        if let Some(last) = guardrails.last_action.get(&key) {
+            if now - *last < ChronoDuration::seconds(rule.cooldown_secs as i64) {
+                return Err(RemediationStatus::Cooldown);
+            }
+        }
+
+        while guardrails.actions.front().is_some_and(|t| *t <= hour_ago) {
+            guardrails.actions.pop_front();
+        }
+        if guardrails.actions.len() >= self.config.max_actions_per_hour as usize {
+            return Err(RemediationStatus::RateLimited);
+        }
+
+        let rule_actions = guardrails
+            .rule_actions
+            .entry(rule.name.clone())
+            .or_default();
+        while rule_actions.front().is_some_and(|t| *t <= hour_ago) {
+            rule_actions.pop_front();
+        }
+        if rule_actions.len() >= rule.max_actions_per_hour as usize {
+            return Err(RemediationStatus::RateLimited);
+        }
+
+        rule_actions.push_back(now);
+        guardrails.actions.push_back(now);
+        guardrails.last_action.insert(key, now);
+        Ok(())
+    }
+
+    async fn execute(
+        &self,
+        action: &RemediationActionConfig,
+        anomaly: &AnomalyEvent,
+    ) -> RemediationStatus {
+        let request = match action {
+            RemediationActionConfig::Webhook { url, headers } => self
+                .request(Method::POST, url, headers, anomaly)
+                .json(&serde_json::json!({
+                    "event_type": "anomaly.remediation",
+                    "timestamp": Utc::now(),
+                    "data": anomaly,
+                })),
+            RemediationActionConfig::Http {
+                url,
+                method,
+                headers,
+                body,
+            } => {
+                let method =
+                    Method::from_bytes(method.to_uppercase().as_bytes()).unwrap_or(Method::POST);
+                let mut request = self.request(method, url, headers, anomaly);
+                if let Some(body) = body {
+                    if !headers
+                        .keys()
+                        .any(|k| k.eq_ignore_ascii_case("content-type"))
+                    {
+                        request = request.header(CONTENT_TYPE, "application/json");
+                    }
+                    request = request.body(render(body, anomaly));
+                }
+                request
+            }
+            RemediationActionConfig::Ticket {
+                url,
+                headers,
+                project,
+                labels,
+            } => self
+                .request(Method::POST, url, headers, anomaly)
+                .json(&ticket(anomaly, project.as_deref(), labels)),
+        };
+
+        match request.send().await {
+            Ok(response) if response.status().is_success() => RemediationStatus::Executed {
+                status_code: response.status().as_u16(),
+            },
+            Ok(response) => RemediationStatus::Failed {
+                error: format!("HTTP {}", response.status()),
+            },
+            Err(e) => RemediationStatus::Failed {
+                error: e.to_string(),
+            },
+        }
+    }
+
+    fn request(
+        &self,
+        method: Method,
+        url: &str,
+        headers: &BTreeMap<String, String>,
+        anomaly: &AnomalyEvent,
+    ) -> reqwest::RequestBuilder {
+        headers.iter().fold(
+            self.client.request(method, render(url, anomaly)),
+            |request, (name, value)| request.header(name, render(value, anomaly)),
+        )
+    }
+}
+
+impl fmt::Debug for RemediationEngine {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("RemediationEngine")
+            .field("rules", &self.config.rules.len())
+            .field("dry_run", &self.config.dry_run)
+            .field("audit", &self.audit)
+            .finish()
+    }
+}
+
+fn action_kind(action: &RemediationActionConfig) -> &'static str {
+    match action {
+        RemediationActionConfig::Webhook { .. } => "webhook",
+        RemediationActionConfig::Http { .. } => "http",
+        RemediationActionConfig::Ticket { .. } => "ticket",
+    }
+}
+
+/// Substitute anomaly fields into a template
+fn render(template: &str, anomaly: &AnomalyEvent) -> String {
+    template
+        .replace("{alert_id}", &anomaly.alert_id.to_string())
+        .replace("{service}", anomaly.service_name.as_str())
+        .replace("{model}", anomaly.model.as_str())
+        .replace("{severity}", &anomaly.severity.to_string())
+        .replace("{anomaly_type}", &anomaly.anomaly_type.to_string())
+        .replace("{metric}", &anomaly.details.metric)
+        .replace("{value}", &anomaly.details.value.to_string())
+}
+
+/// Ticket body for a JSON ticketing API
+fn ticket(anomaly: &AnomalyEvent, project: Option<&str>, labels: &[String]) -> serde_json::Value {
+    let mut description = format!(
+        "{} {} anomaly on {}/{}: {} = {} (baseline {}, threshold {})",
+        anomaly.severity,
+        anomaly.anomaly_type,
+        anomaly.service_name,
+        anomaly.model,
+        anomaly.details.metric,
+        anomaly.details.value,
+        anomaly.details.baseline,
+        anomaly.details.threshold
+    );
+    if let Some(root_cause) = &anomaly.root_cause {
+        description.push_str(&format!("\n\nRoot cause: {}", root_cause));
+    }
+    for step in &anomaly.remediation {
+        description.push_str(&format!("\n- {}", step));
+    }
+
+    serde_json::json!({
+        "title": format!(
+            "[{}] {} on {}/{}",
+            anomaly.severity, anomaly.anomaly_type, anomaly.service_name, anomaly.model
+        ),
+        "description": description,
+        "severity": anomaly.severity,
+        "project": project,
+        "labels": labels,
+        "alert_id": anomaly.alert_id,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use llm_sentinel_core::{
+        audit::MemoryAuditLog,
+        types::{AnomalyType, Severity},
+    };
+    use llm_sentinel_fixtures::anomaly;
+
+    use wiremock::{matchers::*, Mock, MockServer, ResponseTemplate};
+
+    fn create_anomaly(severity: Severity, service: &str) -> AnomalyEvent {
+        anomaly().severity(severity).service(service).build()
+    }
+
+    fn rule(name: &str, action: RemediationActionConfig) -> RemediationRuleConfig {
+        RemediationRuleConfig {
+            name: name.to_string(),
+            anomaly_types: vec!["latency_spike".to_string()],
+            min_severity: Some(Severity::High),
+            services: Vec::new(),
+            models: Vec::new(),
+            action,
+            cooldown_secs: 600,
+            max_actions_per_hour: 2,
+        }
+    }
+
+    fn scale_action(server: &MockServer) -> RemediationActionConfig {
+        RemediationActionConfig::Http {
+            url: format!("{}/deployments/{{service}}/scale", server.uri()),
+            method: "patch".to_string(),
+            headers: BTreeMap::from([("X-Alert".to_string(), "{alert_id}".to_string())]),
+            body: Some(r#"{"replicas":6,"reason":"{anomaly_type}"}"#.to_string()),
+        }
+    }
+
+    fn engine(
+        rules: Vec<RemediationRuleConfig>,
+        dry_run: bool,
+        max_actions_per_hour: u32,
+    ) -> (RemediationEngine, Arc<MemoryAuditLog>) {
+        let audit = Arc::new(MemoryAuditLog::new(100));
+        let engine = RemediationEngine::new(
+            RemediationConfig {
+                dry_run,
+                max_actions_per_hour,
+                rules,
+            },
+            audit.clone(),
+        )
+        .unwrap();
+        (engine, audit)
+    }
+
+    #[tokio::test]
+    async fn test_http_action() {
+        let server = MockServer::start().await;
+        let anomaly = create_anomaly(Severity::Critical, "checkout");
+        Mock::given(method("PATCH"))
+            .and(path("/deployments/checkout/scale"))
+            .and(header("X-Alert", anomaly.alert_id.to_string().as_str()))
+            .and(body_json(
+                serde_json::json!({ "replicas": 6, "reason": "latency_spike" }),
+            ))
+            .respond_with(ResponseTemplate::new(200))
+            .expect(1)
+            .mount(&server)
+            .await;
+
+        let (engine, audit) = engine(vec![rule("scale-out", scale_action(&server))], false, 10);
+        let outcomes = engine.handle(&anomaly).await;
+        assert_eq!(
+            outcomes,
+            vec![RemediationOutcome {
+                rule: "scale-out".to_string(),
+                action: "http".to_string(),
+                status: RemediationStatus::Executed { status_code: 200 },
+            }]
+        );
+
+        let entries = audit.entries();
+        assert_eq!(entries.len(), 1);
+        assert_eq!(entries[0].action, "remediation.http");
+        assert_eq!(entries[0].target, "checkout/gpt-4");
+        assert_eq!(entries[0].outcome, AuditOutcome::Success);
+        assert_eq!(entries[0].details["rule"], "scale-out");
+    }
+
+    #[tokio::test]
+    async fn test_signature_matching() {
+        let server = MockServer::start().await;
+        let (engine, audit) = engine(vec![rule("scale-out", scale_action(&server))], false, 10);
+
+        // Below the minimum severity
+        assert!(engine
+            .handle(&create_anomaly(Severity::Medium, "checkout"))
+            .await
+            .is_empty());
+
+        // Different anomaly type
+        let mut anomaly = create_anomaly(Severity::Critical, "checkout");
+        anomaly.anomaly_type = AnomalyType::CostAnomaly;
+        assert!(engine.handle(&anomaly).await.is_empty());
+        assert!(audit.entries().is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_guardrails() {
+        let server = MockServer::start().await;
+        Mock::given(method("PATCH"))
+            .respond_with(ResponseTemplate::new(202))
+            .expect(3)
+            .mount(&server)
+            .await;
+
+        let (engine, audit) = engine(vec![rule("scale-out", scale_action(&server))], false, 10);
+        let start = Utc::now();
+        let status = |outcomes: Vec<RemediationOutcome>| outcomes[0].status.clone();
+
+        let anomaly = create_anomaly(Severity::High, "checkout");
+        assert_eq!(
+            status(engine.handle_at(&anomaly, start).await),
+            RemediationStatus::Executed { status_code: 202 }
+        );
+
+        // Same service within the cooldown
+        let later = start + ChronoDuration::minutes(5);
+        assert_eq!(
+            status(engine.handle_at(&anomaly, later).await),
+            RemediationStatus::Cooldown
+        );
+
+        // Other services have their own cooldown, but share the hourly cap
+        let search = create_anomaly(Severity::High, "search");
+        assert!(matches!(
+            status(engine.handle_at(&search, later).await),
+            RemediationStatus::Executed { .. }
+        ));
+        let billing = create_anomaly(Severity::High, "billing");
+        assert_eq!(
+            status(engine.handle_at(&billing, later).await),
+            RemediationStatus::RateLimited
+        );
+
+        // The hourly window slides
+        let next_hour = start + ChronoDuration::minutes(61);
+        assert!(matches!(
+            status(engine.handle_at(&billing, next_hour).await),
+            RemediationStatus::Executed { .. }
+        ));
+
+        let skipped = audit
+            .entries()
+            .iter()
+            .filter(|e| e.outcome == AuditOutcome::Skipped)
+            .count();
+        assert_eq!(skipped, 2);
+    }
+
+    #[tokio::test]
+    async fn test_global_cap() {
+        let server = MockServer::start().await;
+        Mock::given(method("PATCH"))
+            .respond_with(ResponseTemplate::new(200))
+            .mount(&server)
+            .await;
+
+        let (engine, _) = engine(
+            vec![
+                rule("first", scale_action(&server)),
+                rule("second", scale_action(&server)),
+            ],
+            false,
+            1,
+        );
+        let outcomes = engine
+            .handle(&create_anomaly(Severity::High, "checkout"))
+            .await;
+        assert!(matches!(
+            outcomes[0].status,
+            RemediationStatus::Executed { .. }
+        ));
+        assert_eq!(outcomes[1].status, RemediationStatus::RateLimited);
+    }
+
+    #[tokio::test]
+    async fn test_dry_run() {
+        let server = MockServer::start().await;
+        Mock::given(any())
+            .respond_with(ResponseTemplate::new(200))
+            .expect(0)
+            .mount(&server)
+            .await;
+
+        let (engine, audit) = engine(vec![rule("scale-out", scale_action(&server))], true, 10);
+        let outcomes = engine
+            .handle(&create_anomaly(Severity::High, "checkout"))
+            .await;
+        assert_eq!(outcomes[0].status, RemediationStatus::DryRun);
+        assert_eq!(audit.entries()[0].details["status"], "dry_run");
+    }
+
+    #[tokio::test]
+    async fn test_ticket_failure_audited() {
+        let server = MockServer::start().await;
+        Mock::given(method("POST"))
+            .and(path("/tickets"))
+            .and(body_partial_json(serde_json::json!({
+                "project": "OPS",
+                "labels": ["llm"],
+                "severity": "critical",
+            })))
+            .respond_with(ResponseTemplate::new(503))
+            .expect(1)
+            .mount(&server)
+            .await;
+
+        let action = RemediationActionConfig::Ticket {
+            url: format!("{}/tickets", server.uri()),
+            headers: BTreeMap::new(),
+            project: Some("OPS".to_string()),
+            labels: vec!["llm".to_string()],
+        };
+        let (engine, audit) = engine(vec![rule("ticket", action)], false, 10);
+        let outcomes = engine
+            .handle(&create_anomaly(Severity::Critical, "checkout"))
+            .await;
+
+        assert!(matches!(
+            &outcomes[0].status,
+            RemediationStatus::Failed { error } if error.contains("503")
+        ));
+        let entry = &audit.entries()[0];
+        assert_eq!(entry.action, "remediation.ticket");
+        assert_eq!(entry.outcome, AuditOutcome::Failure);
+        assert!(entry.details["error"].contains("503"));
+    }
+
+    #[test]
+    fn test_invalid_config() {
+        let action = RemediationActionConfig::Http {
+            url: "http://localhost/scale".to_string(),
+            method: "NOT A METHOD".to_string(),
+            headers: BTreeMap::new(),
+            body: None,
+        };
+        let config = RemediationConfig {
+            dry_run: false,
+            max_actions_per_hour: 10,
+            rules: vec![rule("bad", action)],
+        };
+        assert!(RemediationEngine::new(config, Arc::new(MemoryAuditLog::new(10))).is_err());
+    }
+}
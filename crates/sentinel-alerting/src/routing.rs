@@ -0,0 +1,520 @@
+//! Label-based alert routing and time-aware severity rules.
+//!
+//! An [`AlertRouter`] selects the alerters for an alert from its environment
+//! and labels, so e.g. `team=search` alerts reach only the search team's
+//! channels and staging alerts never page production on-call. Routes are
+//! checked in order and the first that matches wins; alerts matching no
+//! route go to every alerter.
+//!
+//! Before routing, severity rules raise or lower an alert's severity by its
+//! detection time, e.g. medium latency anomalies outside business hours
+//! become low while everything on the checkout service escalates during a
+//! sales event. The first matching rule applies and names itself in the
+//! [`SEVERITY_RULE_LABEL`] label of the alert.
+//!
+//! After routing, delivery schedules restrict when some alerters receive
+//! alerts, e.g. Low severity Slack messages only between 09:00 and 18:00.
+//! An alert routed to such an alerter outside its window is held until the
+//! window next opens; the first schedule matching the alerter and severity
+//! applies and names itself in the [`DELIVERY_SCHEDULE_LABEL`] label.
+
+use chrono::{DateTime, Datelike, Duration, FixedOffset, NaiveTime, Utc};
+use llm_sentinel_core::{
+    config::{AlertRouteConfig, BusinessHoursConfig, DeliveryScheduleConfig, SeverityRuleConfig},
+    events::AnomalyEvent,
+    labels,
+    types::Severity,
+    Error, Result,
+};
+
+/// Label naming the severity rule that adjusted an alert
+pub const SEVERITY_RULE_LABEL: &str = "severity_rule";
+
+/// Label naming the delivery schedule that held an alert
+pub const DELIVERY_SCHEDULE_LABEL: &str = "delivery_schedule";
+
+const SEVERITIES: [Severity; 4] = [
+    Severity::Low,
+    Severity::Medium,
+    Severity::High,
+    Severity::Critical,
+];
+
+/// Business hours with parsed times
+#[derive(Debug, Clone)]
+struct BusinessHours {
+    days: Vec<u32>,
+    start: NaiveTime,
+    end: NaiveTime,
+    offset: FixedOffset,
+}
+
+impl BusinessHours {
+    fn new(config: &BusinessHoursConfig) -> Result<Self> {
+        let parse = |time: &str| {
+            NaiveTime::parse_from_str(time, "%H:%M")
+                .map_err(|_| Error::config(format!("Invalid business hours time: {}", time)))
+        };
+        if let Some(day) = config.days.iter().find(|&&day| day > 6) {
+            return Err(Error::config(format!("Invalid business day: {}", day)));
+        }
+        Ok(Self {
+            days: config.days.clone(),
+            start: parse(&config.start)?,
+            end: parse(&config.end)?,
+            offset: FixedOffset::east_opt(config.utc_offset_minutes * 60)
+                .ok_or_else(|| Error::config("Invalid business hours UTC offset"))?,
+        })
+    }
+
+    /// Whether `time` falls inside business hours
+    fn contains(&self, time: DateTime<Utc>) -> bool {
+        let local = time.with_timezone(&self.offset);
+        let (now, today) = (local.time(), local.weekday().num_days_from_sunday());
+        let open_on = |day: u32| self.days.contains(&day);
+        if self.start <= self.end {
+            open_on(today) && self.start <= now && now < self.end
+        } else {
+            // Past midnight the hours belong to the day they opened on
+            let yesterday = (local - Duration::days(1)).weekday().num_days_from_sunday();
+            (open_on(today) && now >= self.start) || (open_on(yesterday) && now < self.end)
+        }
+    }
+
+    /// First opening after `time`
+    fn next_open(&self, time: DateTime<Utc>) -> Option<DateTime<Utc>> {
+        let today = time.with_timezone(&self.offset).date_naive();
+        (0..=7)
+            .map(|days| today + Duration::days(days))
+            .filter(|date| self.days.contains(&date.weekday().num_days_from_sunday()))
+            .filter_map(|date| {
+                date.and_time(self.start)
+                    .and_local_timezone(self.offset)
+                    .single()
+            })
+            .map(|open| open.with_timezone(&Utc))
+            .find(|&open| open > time)
+    }
+}
+
+/// Severity rule with parsed business hours
+#[derive(Debug, Clone)]
+struct SeverityRule {
+    config: SeverityRuleConfig,
+    business_hours: Option<BusinessHours>,
+}
+
+impl SeverityRule {
+    fn matches(&self, alert: &AnomalyEvent) -> bool {
+        let config = &self.config;
+        let time = alert.timestamp;
+        (config.services.is_empty()
+            || config
+                .services
+                .iter()
+                .any(|s| s == alert.service_name.as_str()))
+            && (config.anomaly_types.is_empty()
+                || config
+                    .anomaly_types
+                    .contains(&alert.anomaly_type.to_string()))
+            && (config.severities.is_empty() || config.severities.contains(&alert.severity))
+            && config.start.map_or(true, |start| time >= start)
+            && config.end.map_or(true, |end| time < end)
+            && self.business_hours.as_ref().map_or(true, |hours| {
+                hours.contains(time) != config.outside_business_hours
+            })
+    }
+}
+
+/// Severity change decided by a severity rule
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SeverityAdjustment {
+    /// Name of the rule
+    pub rule: String,
+    /// Severity before the adjustment
+    pub from: Severity,
+    /// Severity after the adjustment
+    pub to: Severity,
+}
+
+impl SeverityAdjustment {
+    /// Set the alert's severity and label it with the rule
+    pub fn apply(&self, alert: &mut AnomalyEvent) {
+        alert.severity = self.to;
+        alert
+            .labels
+            .insert(SEVERITY_RULE_LABEL.to_string(), self.rule.clone());
+    }
+}
+
+/// Delivery schedule with parsed window
+#[derive(Debug, Clone)]
+struct DeliverySchedule {
+    config: DeliveryScheduleConfig,
+    window: BusinessHours,
+}
+
+impl DeliverySchedule {
+    fn matches(&self, alert: &AnomalyEvent, alerter: &str) -> bool {
+        self.config.alerters.iter().any(|a| a == alerter)
+            && (self.config.severities.is_empty()
+                || self.config.severities.contains(&alert.severity))
+    }
+}
+
+/// Delivery of an alert to an alerter postponed by a delivery schedule
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DeliveryHold {
+    /// Name of the schedule
+    pub schedule: String,
+    /// When the schedule's window next opens
+    pub until: DateTime<Utc>,
+}
+
+/// Routes alerts to alerters by label
+#[derive(Debug, Clone, Default)]
+pub struct AlertRouter {
+    routes: Vec<AlertRouteConfig>,
+    severity_rules: Vec<SeverityRule>,
+    delivery_schedules: Vec<DeliverySchedule>,
+}
+
+impl AlertRouter {
+    /// Create a router from the configured routes
+    pub fn new(routes: Vec<AlertRouteConfig>) -> Self {
+        Self {
+            routes,
+            severity_rules: Vec::new(),
+            delivery_schedules: Vec::new(),
+        }
+    }
+
+    /// Adjust severities with the configured rules, failing on invalid
+    /// business hours
+    pub fn with_severity_rules(mut self, rules: &[SeverityRuleConfig]) -> Result<Self> {
+        self.severity_rules = rules
+            .iter()
+            .map(|config| {
+                let business_hours = config
+                    .business_hours
+                    .as_ref()
+                    .map(BusinessHours::new)
+                    .transpose()
+                    .map_err(|e| {
+                        Error::config(format!("Severity rule '{}': {}", config.name, e))
+                    })?;
+                Ok(SeverityRule {
+                    config: config.clone(),
+                    business_hours,
+                })
+            })
+            .collect::<Result<_>>()?;
+        Ok(self)
+    }
+
+    /// Hold alerts outside the configured delivery windows, failing on
+    /// invalid windows
+    pub fn with_delivery_schedules(mut self, schedules: &[DeliveryScheduleConfig]) -> Result<Self> {
+        self.delivery_schedules = schedules
+            .iter()
+            .map(|config| {
+                let window = BusinessHours::new(&config.window).map_err(|e| {
+                    Error::config(format!("Delivery schedule '{}': {}", config.name, e))
+                })?;
+                Ok(DeliverySchedule {
+                    config: config.clone(),
+                    window,
+                })
+            })
+            .collect::<Result<_>>()?;
+        Ok(self)
+    }
+
+    /// Whether any routes are configured
+    pub fn is_empty(&self) -> bool {
+        self.routes.is_empty()
+    }
+
+    /// Severity change of the first severity rule matching the alert, if
+    /// it changes the severity
+    pub fn severity_adjustment(&self, alert: &AnomalyEvent) -> Option<SeverityAdjustment> {
+        let rule = self
+            .severity_rules
+            .iter()
+            .find(|rule| rule.matches(alert))?;
+        let level = SEVERITIES.iter().position(|&s| s == alert.severity)? as i64;
+        let adjusted = (level + rule.config.adjust as i64).clamp(0, SEVERITIES.len() as i64 - 1);
+        let to = SEVERITIES[adjusted as usize];
+        (to != alert.severity).then(|| SeverityAdjustment {
+            rule: rule.config.name.clone(),
+            from: alert.severity,
+            to,
+        })
+    }
+
+    /// Narrow `alerters` to those the alert routes to
+    pub fn route(&self, alert: &AnomalyEvent, alerters: Vec<String>) -> Vec<String> {
+        match self.routes.iter().find(|route| Self::matches(route, alert)) {
+            Some(route) => alerters
+                .into_iter()
+                .filter(|name| route.alerters.contains(name))
+                .collect(),
+            None => alerters,
+        }
+    }
+
+    /// Hold of the alert for `alerter` at `now`, when the first delivery
+    /// schedule matching them is outside its window
+    pub fn delivery_hold(
+        &self,
+        alert: &AnomalyEvent,
+        alerter: &str,
+        now: DateTime<Utc>,
+    ) -> Option<DeliveryHold> {
+        let schedule = self
+            .delivery_schedules
+            .iter()
+            .find(|schedule| schedule.matches(alert, alerter))?;
+        if schedule.window.contains(now) {
+            return None;
+        }
+        Some(DeliveryHold {
+            schedule: schedule.config.name.clone(),
+            until: schedule.window.next_open(now)?,
+        })
+    }
+
+    fn matches(route: &AlertRouteConfig, alert: &AnomalyEvent) -> bool {
+        route
+            .environment
+            .as_ref()
+            .map_or(true, |env| alert.environment.as_ref() == Some(env))
+            && labels::matches(&alert.labels, &route.labels)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use llm_sentinel_core::{labels::Labels, types::Environment};
+    use llm_sentinel_fixtures::anomaly;
+
+    fn create_alert(labels: &[(&str, &str)]) -> AnomalyEvent {
+        let mut alert = anomaly().service("checkout").build();
+        alert.labels = labels
+            .iter()
+            .map(|(k, v)| (k.to_string(), v.to_string()))
+            .collect();
+        alert
+    }
+
+    fn route(labels: &[(&str, &str)], alerters: &[&str]) -> AlertRouteConfig {
+        AlertRouteConfig {
+            environment: None,
+            labels: labels
+                .iter()
+                .map(|(k, v)| (k.to_string(), v.to_string()))
+                .collect::<Labels>(),
+            alerters: alerters.iter().map(|a| a.to_string()).collect(),
+        }
+    }
+
+    #[test]
+    fn test_route_by_labels() {
+        let router = AlertRouter::new(vec![
+            route(&[("team", "search"), ("env", "prod")], &["pager", "slack"]),
+            route(&[("team", "search")], &["slack"]),
+        ]);
+        let all = || {
+            vec![
+                "pager".to_string(),
+                "slack".to_string(),
+                "email".to_string(),
+            ]
+        };
+
+        let alert = create_alert(&[("team", "search"), ("env", "prod")]);
+        assert_eq!(router.route(&alert, all()), vec!["pager", "slack"]);
+
+        let alert = create_alert(&[("team", "search"), ("env", "staging")]);
+        assert_eq!(router.route(&alert, all()), vec!["slack"]);
+
+        // Unmatched alerts go everywhere
+        let alert = create_alert(&[("team", "ads")]);
+        assert_eq!(router.route(&alert, all()), all());
+        assert_eq!(router.route(&create_alert(&[]), all()), all());
+    }
+
+    #[test]
+    fn test_route_by_environment() {
+        let router = AlertRouter::new(vec![AlertRouteConfig {
+            environment: Some(Environment::new("staging")),
+            ..route(&[], &["slack"])
+        }]);
+        let all = || vec!["pager".to_string(), "slack".to_string()];
+
+        let mut alert = create_alert(&[("team", "search")]);
+        alert.environment = Some(Environment::new("staging"));
+        assert_eq!(router.route(&alert, all()), vec!["slack"]);
+
+        alert.environment = Some(Environment::new("prod"));
+        assert_eq!(router.route(&alert, all()), all());
+        alert.environment = None;
+        assert_eq!(router.route(&alert, all()), all());
+    }
+
+    fn severity_rule(name: &str, adjust: i8) -> SeverityRuleConfig {
+        SeverityRuleConfig {
+            name: name.to_string(),
+            services: Vec::new(),
+            anomaly_types: Vec::new(),
+            severities: Vec::new(),
+            business_hours: None,
+            outside_business_hours: false,
+            start: None,
+            end: None,
+            adjust,
+        }
+    }
+
+    fn at(alert: &AnomalyEvent, time: &str) -> AnomalyEvent {
+        let mut alert = alert.clone();
+        alert.timestamp = time.parse().unwrap();
+        alert
+    }
+
+    #[test]
+    fn test_severity_rules() {
+        let business_hours = BusinessHoursConfig {
+            days: vec![1, 2, 3, 4, 5],
+            start: "09:00".to_string(),
+            end: "17:00".to_string(),
+            // UTC-5
+            utc_offset_minutes: -300,
+        };
+        let router = AlertRouter::default()
+            .with_severity_rules(&[
+                SeverityRuleConfig {
+                    services: vec!["checkout".to_string()],
+                    start: Some("2024-11-29T00:00:00Z".parse().unwrap()),
+                    end: Some("2024-11-30T00:00:00Z".parse().unwrap()),
+                    ..severity_rule("black-friday", 1)
+                },
+                SeverityRuleConfig {
+                    anomaly_types: vec!["latency_spike".to_string()],
+                    severities: vec![Severity::Medium],
+                    business_hours: Some(business_hours.clone()),
+                    outside_business_hours: true,
+                    ..severity_rule("after-hours", -1)
+                },
+            ])
+            .unwrap();
+
+        let mut alert = create_alert(&[]);
+        alert.severity = Severity::Medium;
+
+        // Monday 10:00 local
+        assert_eq!(
+            router.severity_adjustment(&at(&alert, "2024-06-03T15:00:00Z")),
+            None
+        );
+        // Monday 20:00 local
+        let mut late = at(&alert, "2024-06-04T01:00:00Z");
+        let adjustment = router.severity_adjustment(&late).unwrap();
+        assert_eq!(
+            (adjustment.from, adjustment.to),
+            (Severity::Medium, Severity::Low)
+        );
+        adjustment.apply(&mut late);
+        assert_eq!(late.severity, Severity::Low);
+        assert_eq!(late.labels[SEVERITY_RULE_LABEL], "after-hours");
+        // Saturday afternoon local
+        assert!(router
+            .severity_adjustment(&at(&alert, "2024-06-08T19:00:00Z"))
+            .is_some());
+
+        // Black Friday wins over after-hours, and critical stays critical
+        let friday = at(&alert, "2024-11-29T23:00:00Z");
+        let adjustment = router.severity_adjustment(&friday).unwrap();
+        assert_eq!(
+            (adjustment.rule.as_str(), adjustment.to),
+            ("black-friday", Severity::High)
+        );
+        let mut critical = friday.clone();
+        critical.severity = Severity::Critical;
+        assert_eq!(router.severity_adjustment(&critical), None);
+
+        // Invalid business hours are rejected
+        let invalid = SeverityRuleConfig {
+            business_hours: Some(BusinessHoursConfig {
+                end: "25:00".to_string(),
+                ..business_hours
+            }),
+            ..severity_rule("broken", -1)
+        };
+        let err = AlertRouter::default()
+            .with_severity_rules(&[invalid])
+            .unwrap_err();
+        assert!(err.to_string().contains("Severity rule 'broken'"));
+    }
+
+    #[test]
+    fn test_delivery_schedules() {
+        let router = AlertRouter::default()
+            .with_delivery_schedules(&[DeliveryScheduleConfig {
+                name: "daytime".to_string(),
+                alerters: vec!["slack".to_string()],
+                severities: vec![Severity::Low],
+                window: BusinessHoursConfig {
+                    days: vec![1, 2, 3, 4, 5],
+                    start: "09:00".to_string(),
+                    end: "18:00".to_string(),
+                    utc_offset_minutes: 60,
+                },
+            }])
+            .unwrap();
+        let mut alert = create_alert(&[]);
+        alert.severity = Severity::Low;
+
+        // Monday 11:00 local
+        let monday = "2024-06-03T10:00:00Z".parse().unwrap();
+        assert_eq!(router.delivery_hold(&alert, "slack", monday), None);
+        // Monday 20:00 local waits for Tuesday morning
+        let evening = "2024-06-03T19:00:00Z".parse().unwrap();
+        let hold = router.delivery_hold(&alert, "slack", evening).unwrap();
+        assert_eq!(hold.schedule, "daytime");
+        assert_eq!(
+            hold.until,
+            "2024-06-04T08:00:00Z".parse::<DateTime<Utc>>().unwrap()
+        );
+        // Friday night waits for Monday
+        let friday = "2024-06-07T22:00:00Z".parse().unwrap();
+        let hold = router.delivery_hold(&alert, "slack", friday).unwrap();
+        assert_eq!(
+            hold.until,
+            "2024-06-10T08:00:00Z".parse::<DateTime<Utc>>().unwrap()
+        );
+
+        // Other alerters and severities are not held
+        assert_eq!(router.delivery_hold(&alert, "pager", evening), None);
+        alert.severity = Severity::High;
+        assert_eq!(router.delivery_hold(&alert, "slack", evening), None);
+    }
+
+    #[test]
+    fn test_business_hours_past_midnight() {
+        let hours = BusinessHours::new(&BusinessHoursConfig {
+            days: vec![5],
+            start: "22:00".to_string(),
+            end: "06:00".to_string(),
+            utc_offset_minutes: 0,
+        })
+        .unwrap();
+
+        // Friday night shift runs into Saturday morning
+        assert!(hours.contains("2024-06-07T23:00:00Z".parse().unwrap()));
+        assert!(hours.contains("2024-06-08T05:00:00Z".parse().unwrap()));
+        assert!(!hours.contains("2024-06-08T23:00:00Z".parse().unwrap()));
+        assert!(!hours.contains("2024-06-07T05:00:00Z".parse().unwrap()));
+    }
+}
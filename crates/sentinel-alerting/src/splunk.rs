@@ -0,0 +1,378 @@
+//! Splunk HTTP Event Collector alert delivery.
+//!
+//! Sends each alert as a HEC event whose body is the full anomaly. Service,
+//! model, severity, anomaly type, environment and labels are also sent as
+//! indexed fields so searches can filter on them without parsing the event.
+//! Batches go out in a single request.
+
+use crate::{http::HttpClientConfig, Alerter};
+use async_trait::async_trait;
+use llm_sentinel_core::{
+    events::AnomalyEvent,
+    retry::{retry, RetryError, RetryPolicy},
+    types::Severity,
+    Error, Result,
+};
+use reqwest::{header::AUTHORIZATION, Client, StatusCode};
+use serde::{Deserialize, Serialize};
+use std::time::Duration;
+use tracing::{debug, error, info, warn};
+
+/// Splunk HEC alerter configuration
+#[derive(Clone, Serialize, Deserialize)]
+pub struct SplunkConfig {
+    /// HEC base URL, e.g. `https://splunk.example.com:8088`
+    pub url: String,
+    /// HEC token
+    pub token: String,
+    /// Target index (defaults to the token's index)
+    pub index: Option<String>,
+    /// Event source
+    pub source: String,
+    /// Event sourcetype
+    pub sourcetype: String,
+    /// Event host
+    pub host: Option<String>,
+    /// Minimum severity to deliver
+    pub min_severity: Option<Severity>,
+    /// Request timeout (seconds)
+    pub timeout_secs: u64,
+    /// Maximum attempts per request
+    pub max_retries: u32,
+    /// Initial retry delay in milliseconds
+    pub retry_delay_ms: u64,
+    /// Proxy, CA and DNS settings
+    #[serde(default)]
+    pub http: HttpClientConfig,
+}
+
+impl Default for SplunkConfig {
+    fn default() -> Self {
+        Self {
+            url: String::new(),
+            token: String::new(),
+            index: None,
+            source: "llm-sentinel".to_string(),
+            sourcetype: "llm_sentinel:anomaly".to_string(),
+            host: None,
+            min_severity: None,
+            timeout_secs: 10,
+            max_retries: 3,
+            retry_delay_ms: 1000,
+            http: HttpClientConfig::default(),
+        }
+    }
+}
+
+impl std::fmt::Debug for SplunkConfig {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("SplunkConfig")
+            .field("url", &self.url)
+            .field("index", &self.index)
+            .field("source", &self.source)
+            .field("sourcetype", &self.sourcetype)
+            .field("host", &self.host)
+            .field("min_severity", &self.min_severity)
+            .field("timeout_secs", &self.timeout_secs)
+            .field("http", &self.http)
+            .finish_non_exhaustive()
+    }
+}
+
+/// Splunk HEC alerter
+pub struct SplunkAlerter {
+    client: Client,
+    config: SplunkConfig,
+}
+
+impl std::fmt::Debug for SplunkAlerter {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("SplunkAlerter")
+            .field("config", &self.config)
+            .finish_non_exhaustive()
+    }
+}
+
+impl SplunkAlerter {
+    /// Create a new Splunk HEC alerter
+    pub fn new(config: SplunkConfig) -> Result<Self> {
+        if config.url.is_empty() {
+            return Err(Error::config("Splunk HEC URL cannot be empty"));
+        }
+        if config.token.is_empty() {
+            return Err(Error::config("Splunk HEC token cannot be empty"));
+        }
+
+        info!(url = %config.url, "Creating Splunk HEC alerter");
+
+        let client = config
+            .http
+            .builder()?
+            .timeout(Duration::from_secs(config.timeout_secs))
+            .build()
+            .map_err(|e| Error::config(format!("Failed to create HTTP client: {}", e)))?;
+
+        Ok(Self { client, config })
+    }
+
+    fn endpoint(&self, path: &str) -> String {
+        format!("{}{}", self.config.url.trim_end_matches('/'), path)
+    }
+
+    /// Build the HEC event for an alert
+    fn build_event(&self, alert: &AnomalyEvent) -> Result<serde_json::Value> {
+        let mut fields = serde_json::json!({
+            "service": alert.service_name.as_str(),
+            "model": alert.model.as_str(),
+            "severity": alert.severity.to_string(),
+            "anomaly_type": alert.anomaly_type.to_string(),
+            "state": alert.state.to_string(),
+        });
+        if let Some(environment) = &alert.environment {
+            fields["environment"] = environment.as_str().into();
+        }
+        for (key, value) in &alert.labels {
+            fields[format!("label_{}", key)] = value.as_str().into();
+        }
+
+        let mut event = serde_json::json!({
+            "time": alert.timestamp.timestamp_millis() as f64 / 1000.0,
+            "source": self.config.source,
+            "sourcetype": self.config.sourcetype,
+            "event": serde_json::to_value(alert)?,
+            "fields": fields,
+        });
+        if let Some(index) = &self.config.index {
+            event["index"] = index.as_str().into();
+        }
+        if let Some(host) = &self.config.host {
+            event["host"] = host.as_str().into();
+        }
+        Ok(event)
+    }
+
+    /// Post events, concatenated as HEC expects for batches
+    async fn post(&self, alerts: &[&AnomalyEvent]) -> Result<()> {
+        let mut body = String::new();
+        for alert in alerts {
+            body.push_str(&serde_json::to_string(&self.build_event(alert)?)?);
+            body.push('\n');
+        }
+
+        let url = self.endpoint("/services/collector/event");
+        let policy = RetryPolicy::new(self.config.max_retries.max(1))
+            .with_initial_delay(Duration::from_millis(self.config.retry_delay_ms));
+        retry(&policy, |attempt| {
+            let request = self
+                .client
+                .post(&url)
+                .header(AUTHORIZATION, format!("Splunk {}", self.config.token))
+                .body(body.clone());
+
+            async move {
+                let response = match request.send().await {
+                    Ok(response) => response,
+                    Err(e) => {
+                        let e = e.without_url();
+                        warn!(attempt, error = %e, "Splunk HEC request failed");
+                        return Err(RetryError::Transient(e.to_string()));
+                    }
+                };
+
+                let status = response.status();
+                if status.is_success() {
+                    return Ok(());
+                }
+                let text = response.text().await.unwrap_or_default();
+                let message = format!("status {}: {}", status, text);
+                if is_retryable_status(status) {
+                    warn!(attempt, status = %status, "Splunk HEC busy or failing");
+                    Err(RetryError::Transient(message))
+                } else {
+                    Err(RetryError::Permanent(message))
+                }
+            }
+        })
+        .await
+        .map_err(|failure| {
+            error!(attempts = failure.attempts, error = %failure.error, "Splunk HEC delivery failed");
+            metrics::counter!("sentinel_splunk_failures_total").increment(1);
+            Error::alerting(format!("Splunk HEC delivery failed: {}", failure.error))
+        })?;
+
+        metrics::counter!("sentinel_splunk_events_total").increment(alerts.len() as u64);
+        Ok(())
+    }
+
+    fn wanted(&self, alert: &AnomalyEvent) -> bool {
+        self.config
+            .min_severity
+            .map_or(true, |min| alert.severity >= min)
+    }
+}
+
+/// Statuses HEC returns when it is overloaded or failing
+fn is_retryable_status(status: StatusCode) -> bool {
+    status == StatusCode::REQUEST_TIMEOUT
+        || status == StatusCode::TOO_MANY_REQUESTS
+        || status.is_server_error()
+}
+
+#[async_trait]
+impl Alerter for SplunkAlerter {
+    async fn send(&self, alert: &AnomalyEvent) -> Result<()> {
+        if !self.wanted(alert) {
+            debug!(alert_id = %alert.alert_id, "Alert below Splunk minimum severity");
+            return Ok(());
+        }
+
+        self.post(&[alert]).await?;
+        debug!(alert_id = %alert.alert_id, "Alert sent to Splunk HEC");
+        Ok(())
+    }
+
+    async fn send_batch(&self, alerts: &[AnomalyEvent]) -> Result<()> {
+        let alerts: Vec<_> = alerts.iter().filter(|a| self.wanted(a)).collect();
+        if alerts.is_empty() {
+            return Ok(());
+        }
+
+        self.post(&alerts).await?;
+        debug!("Sent {} alerts to Splunk HEC", alerts.len());
+        Ok(())
+    }
+
+    async fn health_check(&self) -> Result<()> {
+        self.client
+            .get(self.endpoint("/services/collector/health"))
+            .send()
+            .await
+            .and_then(|r| r.error_for_status())
+            .map_err(|e| {
+                Error::connection(format!(
+                    "Splunk HEC health check failed: {}",
+                    e.without_url()
+                ))
+            })?;
+
+        Ok(())
+    }
+
+    fn name(&self) -> &str {
+        "Splunk"
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use llm_sentinel_core::types::Environment;
+    use llm_sentinel_fixtures::anomaly;
+
+    use wiremock::{
+        matchers::{header, method, path},
+        Mock, MockServer, ResponseTemplate,
+    };
+
+    fn create_test_config(url: &str) -> SplunkConfig {
+        SplunkConfig {
+            url: url.to_string(),
+            token: "hec-token".to_string(),
+            index: Some("llm".to_string()),
+            retry_delay_ms: 1,
+            ..Default::default()
+        }
+    }
+
+    fn create_test_anomaly(severity: Severity) -> AnomalyEvent {
+        let mut alert = anomaly().severity(severity).service("checkout").build();
+        alert.environment = Some(Environment::new("prod"));
+        alert
+            .labels
+            .insert("team".to_string(), "search".to_string());
+        alert
+    }
+
+    #[test]
+    fn test_build_event() {
+        let alerter = SplunkAlerter::new(create_test_config("https://splunk:8088")).unwrap();
+        let alert = create_test_anomaly(Severity::High);
+        let event = alerter.build_event(&alert).unwrap();
+
+        assert_eq!(event["sourcetype"], "llm_sentinel:anomaly");
+        assert_eq!(event["source"], "llm-sentinel");
+        assert_eq!(event["index"], "llm");
+        assert!(event.get("host").is_none());
+        assert_eq!(event["fields"]["service"], "checkout");
+        assert_eq!(event["fields"]["severity"], "high");
+        assert_eq!(event["fields"]["state"], "open");
+        assert_eq!(event["fields"]["environment"], "prod");
+        assert_eq!(event["fields"]["label_team"], "search");
+        assert_eq!(event["event"]["alert_id"], alert.alert_id.to_string());
+    }
+
+    #[test]
+    fn test_config_validation() {
+        assert!(SplunkAlerter::new(SplunkConfig::default()).is_err());
+        let config = create_test_config("https://splunk:8088");
+        assert!(!format!("{:?}", config).contains("hec-token"));
+    }
+
+    #[tokio::test]
+    async fn test_send_batch() {
+        let server = MockServer::start().await;
+        Mock::given(method("POST"))
+            .and(path("/services/collector/event"))
+            .and(header("Authorization", "Splunk hec-token"))
+            .respond_with(
+                ResponseTemplate::new(200)
+                    .set_body_json(serde_json::json!({ "text": "Success", "code": 0 })),
+            )
+            .expect(1)
+            .mount(&server)
+            .await;
+
+        let alerter = SplunkAlerter::new(SplunkConfig {
+            min_severity: Some(Severity::Medium),
+            ..create_test_config(&server.uri())
+        })
+        .unwrap();
+        alerter
+            .send_batch(&[
+                create_test_anomaly(Severity::High),
+                create_test_anomaly(Severity::Critical),
+                create_test_anomaly(Severity::Low),
+            ])
+            .await
+            .unwrap();
+
+        let requests = server.received_requests().await.unwrap();
+        let body = String::from_utf8(requests[0].body.clone()).unwrap();
+        assert_eq!(body.lines().count(), 2);
+    }
+
+    #[tokio::test]
+    async fn test_retry_then_reject() {
+        let server = MockServer::start().await;
+        Mock::given(method("POST"))
+            .respond_with(ResponseTemplate::new(503))
+            .up_to_n_times(1)
+            .mount(&server)
+            .await;
+        Mock::given(method("POST"))
+            .respond_with(
+                ResponseTemplate::new(403)
+                    .set_body_json(serde_json::json!({ "text": "Invalid token", "code": 4 })),
+            )
+            .expect(1)
+            .mount(&server)
+            .await;
+
+        let alerter = SplunkAlerter::new(create_test_config(&server.uri())).unwrap();
+        let err = alerter
+            .send(&create_test_anomaly(Severity::High))
+            .await
+            .unwrap_err();
+        assert!(err.to_string().contains("Invalid token"));
+    }
+}
@@ -0,0 +1,394 @@
+//! Telegram bot alert delivery.
+//!
+//! Sends HTML-formatted messages through the Bot API `sendMessage` method.
+//! Each severity can be routed to its own chat or forum topic, and lower
+//! severities can be delivered silently.
+
+use crate::{http::HttpClientConfig, Alerter};
+use async_trait::async_trait;
+use llm_sentinel_core::{events::AnomalyEvent, types::Severity, Error, Result};
+use reqwest::Client;
+use serde::{Deserialize, Serialize};
+use std::{collections::BTreeMap, time::Duration};
+use tracing::{debug, error, info};
+
+const DEFAULT_ENDPOINT: &str = "https://api.telegram.org";
+
+/// Chat (and optional forum topic) to deliver to
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct TelegramTarget {
+    /// Chat ID or `@channelusername`
+    pub chat_id: String,
+    /// Forum topic (thread) ID
+    #[serde(default)]
+    pub message_thread_id: Option<i64>,
+    /// Deliver without a notification sound
+    #[serde(default)]
+    pub silent: bool,
+}
+
+/// Telegram alerter configuration
+#[derive(Clone, Serialize, Deserialize)]
+pub struct TelegramConfig {
+    /// Bot API token
+    pub bot_token: String,
+    /// Default chat for alerts
+    pub chat_id: String,
+    /// Default forum topic (thread) ID
+    pub message_thread_id: Option<i64>,
+    /// Per-severity chat/topic overrides
+    pub routes: BTreeMap<Severity, TelegramTarget>,
+    /// Minimum severity to deliver
+    pub min_severity: Option<Severity>,
+    /// Bot API endpoint override
+    pub endpoint: Option<String>,
+    /// Request timeout (seconds)
+    pub timeout_secs: u64,
+    /// Proxy, CA and DNS settings
+    #[serde(default)]
+    pub http: HttpClientConfig,
+}
+
+impl Default for TelegramConfig {
+    fn default() -> Self {
+        Self {
+            bot_token: String::new(),
+            chat_id: String::new(),
+            message_thread_id: None,
+            routes: BTreeMap::new(),
+            min_severity: None,
+            endpoint: None,
+            timeout_secs: 10,
+            http: HttpClientConfig::default(),
+        }
+    }
+}
+
+impl std::fmt::Debug for TelegramConfig {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("TelegramConfig")
+            .field("chat_id", &self.chat_id)
+            .field("message_thread_id", &self.message_thread_id)
+            .field("routes", &self.routes)
+            .field("min_severity", &self.min_severity)
+            .field("endpoint", &self.endpoint)
+            .field("timeout_secs", &self.timeout_secs)
+            .field("http", &self.http)
+            .finish_non_exhaustive()
+    }
+}
+
+/// Telegram bot alerter
+pub struct TelegramAlerter {
+    client: Client,
+    send_url: String,
+    config: TelegramConfig,
+}
+
+impl std::fmt::Debug for TelegramAlerter {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("TelegramAlerter")
+            .field("config", &self.config)
+            .finish_non_exhaustive()
+    }
+}
+
+impl TelegramAlerter {
+    /// Create a new Telegram alerter
+    pub fn new(config: TelegramConfig) -> Result<Self> {
+        if config.bot_token.is_empty() {
+            return Err(Error::config("Telegram bot token cannot be empty"));
+        }
+        if config.chat_id.is_empty() {
+            return Err(Error::config("Telegram chat ID cannot be empty"));
+        }
+        if config.routes.values().any(|route| route.chat_id.is_empty()) {
+            return Err(Error::config("Telegram route chat ID cannot be empty"));
+        }
+
+        let send_url = format!(
+            "{}/bot{}/sendMessage",
+            config
+                .endpoint
+                .as_deref()
+                .unwrap_or(DEFAULT_ENDPOINT)
+                .trim_end_matches('/'),
+            config.bot_token
+        );
+
+        info!("Creating Telegram alerter for chat {}", config.chat_id);
+
+        let client = config
+            .http
+            .builder()?
+            .timeout(Duration::from_secs(config.timeout_secs))
+            .build()
+            .map_err(|e| Error::config(format!("Failed to create HTTP client: {}", e)))?;
+
+        Ok(Self {
+            client,
+            send_url,
+            config,
+        })
+    }
+
+    /// Chat and topic for a severity
+    fn target(&self, severity: Severity) -> TelegramTarget {
+        self.config
+            .routes
+            .get(&severity)
+            .cloned()
+            .unwrap_or_else(|| TelegramTarget {
+                chat_id: self.config.chat_id.clone(),
+                message_thread_id: self.config.message_thread_id,
+                silent: false,
+            })
+    }
+
+    /// Build the `sendMessage` request body
+    fn build_message(&self, alert: &AnomalyEvent) -> serde_json::Value {
+        let target = self.target(alert.severity);
+        let mut message = serde_json::json!({
+            "chat_id": target.chat_id,
+            "text": format_message(alert),
+            "parse_mode": "HTML",
+            "disable_notification": target.silent,
+            "disable_web_page_preview": true,
+        });
+        if let Some(thread_id) = target.message_thread_id {
+            message["message_thread_id"] = thread_id.into();
+        }
+        message
+    }
+}
+
+#[async_trait]
+impl Alerter for TelegramAlerter {
+    async fn send(&self, alert: &AnomalyEvent) -> Result<()> {
+        if self
+            .config
+            .min_severity
+            .is_some_and(|min| alert.severity < min)
+        {
+            debug!(alert_id = %alert.alert_id, "Alert below Telegram minimum severity");
+            return Ok(());
+        }
+
+        #[derive(Deserialize)]
+        struct ApiResponse {
+            ok: bool,
+            description: Option<String>,
+        }
+
+        let response = self
+            .client
+            .post(&self.send_url)
+            .json(&self.build_message(alert))
+            .send()
+            .await
+            .map_err(|e| {
+                metrics::counter!("sentinel_telegram_failures_total").increment(1);
+                // The request URL contains the bot token
+                Error::alerting(format!("Telegram request failed: {}", e.without_url()))
+            })?;
+
+        let status = response.status();
+        let body: Option<ApiResponse> = response.json().await.ok();
+        match body {
+            Some(ApiResponse { ok: true, .. }) if status.is_success() => {}
+            body => {
+                let description = body
+                    .and_then(|b| b.description)
+                    .unwrap_or_else(|| status.to_string());
+                error!(status = %status, "Telegram delivery failed: {}", description);
+                metrics::counter!("sentinel_telegram_failures_total").increment(1);
+                return Err(Error::alerting(format!(
+                    "Telegram delivery failed: {}",
+                    description
+                )));
+            }
+        }
+
+        debug!(alert_id = %alert.alert_id, "Alert sent to Telegram");
+        metrics::counter!(
+            "sentinel_telegram_messages_total",
+            "severity" => alert.severity.to_string()
+        )
+        .increment(1);
+
+        Ok(())
+    }
+
+    async fn health_check(&self) -> Result<()> {
+        let url = self.send_url.replace("/sendMessage", "/getMe");
+        self.client
+            .get(&url)
+            .send()
+            .await
+            .and_then(|r| r.error_for_status())
+            .map_err(|e| {
+                Error::connection(format!("Telegram health check failed: {}", e.without_url()))
+            })?;
+
+        Ok(())
+    }
+
+    fn name(&self) -> &str {
+        "Telegram"
+    }
+}
+
+/// Escape text for Telegram HTML parse mode
+fn escape_html(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+}
+
+/// HTML message body for an alert
+fn format_message(alert: &AnomalyEvent) -> String {
+    let icon = match alert.severity {
+        Severity::Critical => "🔴",
+        Severity::High => "🟠",
+        Severity::Medium => "🟡",
+        Severity::Low => "🔵",
+    };
+
+    let mut text = format!(
+        "{} <b>{}</b> {}\n\
+         <b>Service:</b> <code>{}</code>\n\
+         <b>Model:</b> <code>{}</code>\n\
+         <b>{}:</b> {:.2} (baseline {:.2})\n\
+         <b>Confidence:</b> {:.0}%",
+        icon,
+        alert.severity.to_string().to_uppercase(),
+        escape_html(&alert.anomaly_type.to_string()),
+        escape_html(alert.service_name.as_str()),
+        escape_html(alert.model.as_str()),
+        escape_html(&alert.details.metric),
+        alert.details.value,
+        alert.details.baseline,
+        alert.confidence * 100.0
+    );
+    if let Some(root_cause) = &alert.root_cause {
+        text.push_str(&format!("\n<b>Root cause:</b> {}", escape_html(root_cause)));
+    }
+    for (name, url) in &alert.trace_links {
+        text.push_str(&format!(
+            "\n<a href=\"{}\">Trace ({})</a>",
+            escape_html(url).replace('"', "&quot;"),
+            escape_html(name)
+        ));
+    }
+    text.push_str(&format!("\n<i>Alert {}</i>", alert.alert_id));
+    text
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use llm_sentinel_fixtures::anomaly;
+
+    use wiremock::{
+        matchers::{body_partial_json, method, path},
+        Mock, MockServer, ResponseTemplate,
+    };
+
+    fn create_test_config(endpoint: &str) -> TelegramConfig {
+        TelegramConfig {
+            bot_token: "123:abc".to_string(),
+            chat_id: "-100200".to_string(),
+            routes: BTreeMap::from([(
+                Severity::Critical,
+                TelegramTarget {
+                    chat_id: "-100300".to_string(),
+                    message_thread_id: Some(42),
+                    silent: false,
+                },
+            )]),
+            endpoint: Some(endpoint.to_string()),
+            ..Default::default()
+        }
+    }
+
+    fn create_test_anomaly(severity: Severity) -> AnomalyEvent {
+        anomaly()
+            .severity(severity)
+            .service("checkout<prod>")
+            .confidence(0.95)
+            .value(500.0)
+            .threshold(300.0)
+            .deviation_sigma(Some(4.0))
+            .time_window("rolling_window")
+            .build()
+    }
+
+    #[test]
+    fn test_config_validation() {
+        assert!(TelegramAlerter::new(TelegramConfig::default()).is_err());
+
+        let config = create_test_config(DEFAULT_ENDPOINT);
+        assert!(!format!("{:?}", config).contains("123:abc"));
+    }
+
+    #[test]
+    fn test_severity_routing() {
+        let alerter = TelegramAlerter::new(create_test_config(DEFAULT_ENDPOINT)).unwrap();
+
+        let message = alerter.build_message(&create_test_anomaly(Severity::Critical));
+        assert_eq!(message["chat_id"], "-100300");
+        assert_eq!(message["message_thread_id"], 42);
+
+        let message = alerter.build_message(&create_test_anomaly(Severity::High));
+        assert_eq!(message["chat_id"], "-100200");
+        assert!(message.get("message_thread_id").is_none());
+        let text = message["text"].as_str().unwrap();
+        assert!(text.contains("<b>HIGH</b> latency_spike"));
+        assert!(text.contains("checkout&lt;prod&gt;"));
+    }
+
+    #[tokio::test]
+    async fn test_send_success() {
+        let mock_server = MockServer::start().await;
+
+        Mock::given(method("POST"))
+            .and(path("/bot123:abc/sendMessage"))
+            .and(body_partial_json(
+                serde_json::json!({ "chat_id": "-100300", "parse_mode": "HTML" }),
+            ))
+            .respond_with(
+                ResponseTemplate::new(200)
+                    .set_body_json(serde_json::json!({ "ok": true, "result": {} })),
+            )
+            .expect(1)
+            .mount(&mock_server)
+            .await;
+
+        let alerter = TelegramAlerter::new(create_test_config(&mock_server.uri())).unwrap();
+        alerter
+            .send(&create_test_anomaly(Severity::Critical))
+            .await
+            .unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_send_failure() {
+        let mock_server = MockServer::start().await;
+
+        Mock::given(method("POST"))
+            .respond_with(ResponseTemplate::new(400).set_body_json(
+                serde_json::json!({ "ok": false, "description": "Bad Request: chat not found" }),
+            ))
+            .mount(&mock_server)
+            .await;
+
+        let alerter = TelegramAlerter::new(create_test_config(&mock_server.uri())).unwrap();
+        let err = alerter
+            .send(&create_test_anomaly(Severity::High))
+            .await
+            .unwrap_err();
+        assert!(err.to_string().contains("chat not found"));
+        assert!(!err.to_string().contains("123:abc"));
+    }
+}
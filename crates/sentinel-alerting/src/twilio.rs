@@ -0,0 +1,543 @@
+//! Twilio SMS and voice paging.
+//!
+//! Pages on-call numbers by SMS and, for critical alerts, escalates with a
+//! voice call. Each number has an hourly page cap, and quiet hours hold
+//! back everything except critical alerts so a non-critical item never
+//! wakes anyone up.
+
+use crate::{http::HttpClientConfig, Alerter};
+use async_trait::async_trait;
+use chrono::{DateTime, Duration as ChronoDuration, FixedOffset, NaiveTime, Utc};
+use llm_sentinel_core::{events::AnomalyEvent, types::Severity, Error, Result};
+use reqwest::Client;
+use serde::{Deserialize, Serialize};
+use std::{
+    collections::{HashMap, VecDeque},
+    sync::Mutex,
+    time::Duration,
+};
+use tracing::{debug, error, info, warn};
+
+const DEFAULT_ENDPOINT: &str = "https://api.twilio.com";
+
+/// Daily window during which only critical alerts page
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct QuietHours {
+    /// Start time (`HH:MM`)
+    pub start: String,
+    /// End time (`HH:MM`); may be earlier than `start` to span midnight
+    pub end: String,
+    /// Offset of the on-call timezone from UTC, in minutes
+    #[serde(default)]
+    pub utc_offset_minutes: i32,
+}
+
+impl QuietHours {
+    /// Whether `now` falls inside the window
+    fn contains(&self, now: DateTime<Utc>) -> Result<bool> {
+        let parse = |time: &str| {
+            NaiveTime::parse_from_str(time, "%H:%M")
+                .map_err(|_| Error::config(format!("Invalid quiet hours time: {}", time)))
+        };
+        let (start, end) = (parse(&self.start)?, parse(&self.end)?);
+        let offset = FixedOffset::east_opt(self.utc_offset_minutes * 60)
+            .ok_or_else(|| Error::config("Invalid quiet hours UTC offset"))?;
+        let local = now.with_timezone(&offset).time();
+
+        Ok(if start <= end {
+            start <= local && local < end
+        } else {
+            local >= start || local < end
+        })
+    }
+}
+
+/// Twilio alerter configuration
+#[derive(Clone, Serialize, Deserialize)]
+pub struct TwilioConfig {
+    /// Account SID
+    pub account_sid: String,
+    /// Auth token
+    pub auth_token: String,
+    /// Sending phone number (E.164)
+    pub from_number: String,
+    /// Numbers to page (E.164)
+    pub to_numbers: Vec<String>,
+    /// Minimum severity to page
+    pub min_severity: Severity,
+    /// Escalate critical alerts with a voice call after the SMS
+    pub voice_escalation: bool,
+    /// Pages allowed per number per hour (an SMS and its escalation call
+    /// count as one page)
+    pub max_pages_per_hour: u32,
+    /// Quiet hours, during which only critical alerts page
+    pub quiet_hours: Option<QuietHours>,
+    /// API endpoint override
+    pub endpoint: Option<String>,
+    /// Request timeout (seconds)
+    pub timeout_secs: u64,
+    /// Proxy, CA and DNS settings
+    #[serde(default)]
+    pub http: HttpClientConfig,
+}
+
+impl Default for TwilioConfig {
+    fn default() -> Self {
+        Self {
+            account_sid: String::new(),
+            auth_token: String::new(),
+            from_number: String::new(),
+            to_numbers: Vec::new(),
+            min_severity: Severity::Critical,
+            voice_escalation: false,
+            max_pages_per_hour: 5,
+            quiet_hours: None,
+            endpoint: None,
+            timeout_secs: 10,
+            http: HttpClientConfig::default(),
+        }
+    }
+}
+
+impl std::fmt::Debug for TwilioConfig {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("TwilioConfig")
+            .field("account_sid", &self.account_sid)
+            .field("from_number", &self.from_number)
+            .field("to_numbers", &self.to_numbers)
+            .field("min_severity", &self.min_severity)
+            .field("voice_escalation", &self.voice_escalation)
+            .field("max_pages_per_hour", &self.max_pages_per_hour)
+            .field("quiet_hours", &self.quiet_hours)
+            .field("endpoint", &self.endpoint)
+            .field("timeout_secs", &self.timeout_secs)
+            .field("http", &self.http)
+            .finish_non_exhaustive()
+    }
+}
+
+/// Twilio SMS/voice alerter
+pub struct TwilioAlerter {
+    client: Client,
+    account_url: String,
+    config: TwilioConfig,
+    /// Recent page times per number
+    pages: Mutex<HashMap<String, VecDeque<DateTime<Utc>>>>,
+}
+
+impl std::fmt::Debug for TwilioAlerter {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("TwilioAlerter")
+            .field("config", &self.config)
+            .finish_non_exhaustive()
+    }
+}
+
+impl TwilioAlerter {
+    /// Create a new Twilio alerter
+    pub fn new(config: TwilioConfig) -> Result<Self> {
+        if config.account_sid.is_empty() || config.auth_token.is_empty() {
+            return Err(Error::config(
+                "Twilio account SID and auth token are required",
+            ));
+        }
+        if config.from_number.is_empty() {
+            return Err(Error::config("Twilio from number cannot be empty"));
+        }
+        if config.to_numbers.is_empty() {
+            return Err(Error::config("Twilio requires at least one number to page"));
+        }
+        if config.max_pages_per_hour == 0 {
+            return Err(Error::config("Twilio max_pages_per_hour must be positive"));
+        }
+        if let Some(quiet_hours) = &config.quiet_hours {
+            quiet_hours.contains(Utc::now())?;
+        }
+
+        let account_url = format!(
+            "{}/2010-04-01/Accounts/{}",
+            config
+                .endpoint
+                .as_deref()
+                .unwrap_or(DEFAULT_ENDPOINT)
+                .trim_end_matches('/'),
+            config.account_sid
+        );
+
+        info!(
+            "Creating Twilio alerter paging {} numbers",
+            config.to_numbers.len()
+        );
+
+        let client = config
+            .http
+            .builder()?
+            .timeout(Duration::from_secs(config.timeout_secs))
+            .build()
+            .map_err(|e| Error::config(format!("Failed to create HTTP client: {}", e)))?;
+
+        Ok(Self {
+            client,
+            account_url,
+            config,
+            pages: Mutex::new(HashMap::new()),
+        })
+    }
+
+    /// Whether the alert should page at `now`
+    fn should_page(&self, alert: &AnomalyEvent, now: DateTime<Utc>) -> bool {
+        if alert.severity < self.config.min_severity {
+            return false;
+        }
+        match &self.config.quiet_hours {
+            Some(quiet_hours) if alert.severity < Severity::Critical => {
+                !quiet_hours.contains(now).unwrap_or(false)
+            }
+            _ => true,
+        }
+    }
+
+    /// Count a page to `number` if it is under its hourly cap
+    fn reserve(&self, number: &str, now: DateTime<Utc>) -> bool {
+        let mut pages = self.pages.lock().unwrap();
+        let recent = pages.entry(number.to_string()).or_default();
+        while recent
+            .front()
+            .is_some_and(|t| *t <= now - ChronoDuration::hours(1))
+        {
+            recent.pop_front();
+        }
+        if recent.len() >= self.config.max_pages_per_hour as usize {
+            return false;
+        }
+        recent.push_back(now);
+        true
+    }
+
+    /// Create a Twilio resource (`Messages` or `Calls`)
+    async fn create(&self, resource: &str, params: &[(&str, &str)]) -> Result<()> {
+        let response = self
+            .client
+            .post(format!("{}/{}.json", self.account_url, resource))
+            .basic_auth(&self.config.account_sid, Some(&self.config.auth_token))
+            .form(params)
+            .send()
+            .await
+            .map_err(|e| Error::alerting(format!("Twilio request failed: {}", e)))?;
+
+        let status = response.status();
+        if !status.is_success() {
+            let body = response.text().await.unwrap_or_default();
+            error!(status = %status, body = %body, "Twilio {} request failed", resource);
+            return Err(Error::alerting(format!(
+                "Twilio {} request failed with status {}: {}",
+                resource, status, body
+            )));
+        }
+
+        Ok(())
+    }
+
+    /// Page every number, subject to quiet hours and rate limits
+    async fn page_at(&self, alert: &AnomalyEvent, now: DateTime<Utc>) -> Result<()> {
+        if !self.should_page(alert, now) {
+            debug!(alert_id = %alert.alert_id, "Alert not paged (severity or quiet hours)");
+            metrics::counter!("sentinel_twilio_suppressed_total").increment(1);
+            return Ok(());
+        }
+
+        let sms = format_sms(alert);
+        let voice = (self.config.voice_escalation && alert.severity == Severity::Critical)
+            .then(|| format_twiml(alert));
+        let mut first_error = None;
+
+        for number in &self.config.to_numbers {
+            if !self.reserve(number, now) {
+                warn!(alert_id = %alert.alert_id, number = %number, "Twilio page rate limited");
+                metrics::counter!("sentinel_twilio_rate_limited_total").increment(1);
+                continue;
+            }
+
+            let mut result = self
+                .create(
+                    "Messages",
+                    &[
+                        ("To", number),
+                        ("From", &self.config.from_number),
+                        ("Body", &sms),
+                    ],
+                )
+                .await;
+            if let (Ok(()), Some(twiml)) = (&result, &voice) {
+                result = self
+                    .create(
+                        "Calls",
+                        &[
+                            ("To", number),
+                            ("From", &self.config.from_number),
+                            ("Twiml", twiml),
+                        ],
+                    )
+                    .await;
+            }
+
+            match result {
+                Ok(()) => {
+                    metrics::counter!(
+                        "sentinel_twilio_pages_total",
+                        "channel" => if voice.is_some() { "voice" } else { "sms" }
+                    )
+                    .increment(1);
+                }
+                Err(e) => {
+                    metrics::counter!("sentinel_twilio_failures_total").increment(1);
+                    first_error.get_or_insert(e);
+                }
+            }
+        }
+
+        first_error.map_or(Ok(()), Err)
+    }
+}
+
+#[async_trait]
+impl Alerter for TwilioAlerter {
+    async fn send(&self, alert: &AnomalyEvent) -> Result<()> {
+        self.page_at(alert, Utc::now()).await
+    }
+
+    async fn health_check(&self) -> Result<()> {
+        self.client
+            .get(format!("{}.json", self.account_url))
+            .basic_auth(&self.config.account_sid, Some(&self.config.auth_token))
+            .send()
+            .await
+            .and_then(|r| r.error_for_status())
+            .map_err(|e| Error::connection(format!("Twilio health check failed: {}", e)))?;
+
+        Ok(())
+    }
+
+    fn name(&self) -> &str {
+        "Twilio"
+    }
+}
+
+/// SMS body, kept within a single segment where possible
+fn format_sms(alert: &AnomalyEvent) -> String {
+    format!(
+        "[{}] {} on {}/{}: {} {:.2} (baseline {:.2}). Alert {}",
+        alert.severity.to_string().to_uppercase(),
+        alert.anomaly_type,
+        alert.service_name,
+        alert.model,
+        alert.details.metric,
+        alert.details.value,
+        alert.details.baseline,
+        &alert.alert_id.to_string()[..8]
+    )
+}
+
+/// TwiML spoken on the escalation call
+fn format_twiml(alert: &AnomalyEvent) -> String {
+    let escape = |text: &str| {
+        text.replace('&', "&amp;")
+            .replace('<', "&lt;")
+            .replace('>', "&gt;")
+    };
+    let message = format!(
+        "LLM Sentinel critical alert. {} on service {}, model {}.",
+        alert.anomaly_type.to_string().replace('_', " "),
+        alert.service_name,
+        alert.model
+    );
+    format!(
+        "<Response><Say>{0}</Say><Pause length=\"1\"/><Say>{0}</Say></Response>",
+        escape(&message)
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+    use llm_sentinel_core::types::AnomalyType;
+    use llm_sentinel_fixtures::anomaly;
+    use wiremock::{
+        matchers::{body_string_contains, header_exists, method, path},
+        Mock, MockServer, ResponseTemplate,
+    };
+
+    fn create_test_config(endpoint: &str) -> TwilioConfig {
+        TwilioConfig {
+            account_sid: "AC123".to_string(),
+            auth_token: "secret".to_string(),
+            from_number: "+15550000000".to_string(),
+            to_numbers: vec!["+15551111111".to_string()],
+            min_severity: Severity::High,
+            voice_escalation: true,
+            max_pages_per_hour: 2,
+            quiet_hours: Some(QuietHours {
+                start: "22:00".to_string(),
+                end: "07:00".to_string(),
+                utc_offset_minutes: -300,
+            }),
+            endpoint: Some(endpoint.to_string()),
+            timeout_secs: 5,
+            http: HttpClientConfig::default(),
+        }
+    }
+
+    fn create_test_anomaly(severity: Severity) -> AnomalyEvent {
+        anomaly()
+            .severity(severity)
+            .anomaly_type(AnomalyType::ErrorRateIncrease)
+            .service("checkout")
+            .confidence(0.95)
+            .metric("error_rate")
+            .value(0.3)
+            .baseline(0.01)
+            .deviation_sigma(Some(8.0))
+            .time_window("rolling_window")
+            .build()
+    }
+
+    // 15:00 in UTC-5
+    fn daytime() -> DateTime<Utc> {
+        Utc.with_ymd_and_hms(2024, 3, 1, 20, 0, 0).unwrap()
+    }
+
+    // 02:00 in UTC-5
+    fn night() -> DateTime<Utc> {
+        Utc.with_ymd_and_hms(2024, 3, 2, 7, 0, 0).unwrap()
+    }
+
+    #[test]
+    fn test_quiet_hours() {
+        let quiet_hours = QuietHours {
+            start: "22:00".to_string(),
+            end: "07:00".to_string(),
+            utc_offset_minutes: -300,
+        };
+        assert!(!quiet_hours.contains(daytime()).unwrap());
+        assert!(quiet_hours.contains(night()).unwrap());
+
+        let invalid = QuietHours {
+            start: "25:00".to_string(),
+            ..quiet_hours
+        };
+        assert!(invalid.contains(daytime()).is_err());
+    }
+
+    #[test]
+    fn test_config_validation() {
+        assert!(TwilioAlerter::new(TwilioConfig::default()).is_err());
+        let config = create_test_config(DEFAULT_ENDPOINT);
+        assert!(!format!("{:?}", config).contains("secret"));
+    }
+
+    #[tokio::test]
+    async fn test_critical_sms_and_voice() {
+        let mock_server = MockServer::start().await;
+
+        Mock::given(method("POST"))
+            .and(path("/2010-04-01/Accounts/AC123/Messages.json"))
+            .and(header_exists("Authorization"))
+            .and(body_string_contains("To=%2B15551111111"))
+            .respond_with(ResponseTemplate::new(201).set_body_json(serde_json::json!({})))
+            .expect(1)
+            .mount(&mock_server)
+            .await;
+        Mock::given(method("POST"))
+            .and(path("/2010-04-01/Accounts/AC123/Calls.json"))
+            .and(body_string_contains("Twiml="))
+            .respond_with(ResponseTemplate::new(201).set_body_json(serde_json::json!({})))
+            .expect(1)
+            .mount(&mock_server)
+            .await;
+
+        let alerter = TwilioAlerter::new(create_test_config(&mock_server.uri())).unwrap();
+        // Critical alerts page even during quiet hours
+        alerter
+            .page_at(&create_test_anomaly(Severity::Critical), night())
+            .await
+            .unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_non_critical_respects_quiet_hours() {
+        let mock_server = MockServer::start().await;
+
+        Mock::given(method("POST"))
+            .and(path("/2010-04-01/Accounts/AC123/Messages.json"))
+            .respond_with(ResponseTemplate::new(201).set_body_json(serde_json::json!({})))
+            .expect(1)
+            .mount(&mock_server)
+            .await;
+        Mock::given(method("POST"))
+            .and(path("/2010-04-01/Accounts/AC123/Calls.json"))
+            .respond_with(ResponseTemplate::new(201))
+            .expect(0)
+            .mount(&mock_server)
+            .await;
+
+        let alerter = TwilioAlerter::new(create_test_config(&mock_server.uri())).unwrap();
+        let alert = create_test_anomaly(Severity::High);
+        alerter.page_at(&alert, night()).await.unwrap();
+        // Daytime: SMS only, no voice escalation for non-critical alerts
+        alerter.page_at(&alert, daytime()).await.unwrap();
+        // Below the minimum severity
+        alerter
+            .page_at(&create_test_anomaly(Severity::Medium), daytime())
+            .await
+            .unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_per_number_rate_limit() {
+        let mock_server = MockServer::start().await;
+
+        Mock::given(method("POST"))
+            .and(path("/2010-04-01/Accounts/AC123/Messages.json"))
+            .respond_with(ResponseTemplate::new(201).set_body_json(serde_json::json!({})))
+            .expect(3)
+            .mount(&mock_server)
+            .await;
+
+        let config = TwilioConfig {
+            voice_escalation: false,
+            ..create_test_config(&mock_server.uri())
+        };
+        let alerter = TwilioAlerter::new(config).unwrap();
+        let alert = create_test_anomaly(Severity::Critical);
+
+        for minutes in [0, 10, 20] {
+            alerter
+                .page_at(&alert, daytime() + ChronoDuration::minutes(minutes))
+                .await
+                .unwrap();
+        }
+        // The first page has left the hourly window
+        alerter
+            .page_at(&alert, daytime() + ChronoDuration::minutes(61))
+            .await
+            .unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_send_failure() {
+        let mock_server = MockServer::start().await;
+
+        Mock::given(method("POST"))
+            .respond_with(ResponseTemplate::new(400).set_body_string("invalid To number"))
+            .mount(&mock_server)
+            .await;
+
+        let alerter = TwilioAlerter::new(create_test_config(&mock_server.uri())).unwrap();
+        let err = alerter
+            .page_at(&create_test_anomaly(Severity::Critical), daytime())
+            .await
+            .unwrap_err();
+        assert!(err.to_string().contains("invalid To number"));
+    }
+}
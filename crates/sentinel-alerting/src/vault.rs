@@ -0,0 +1,175 @@
+//! HashiCorp Vault backend for `vault:` secret references.
+//!
+//! References name a key of a KV version 2 secret, mount first:
+//! `vault:kv/sentinel#influx_token` reads `influx_token` from the secret
+//! `sentinel` of the `kv` mount, at `/v1/kv/data/sentinel`.
+
+use crate::http::HttpClientConfig;
+use async_trait::async_trait;
+use llm_sentinel_core::{
+    config::VaultSecretsConfig,
+    secrets::{SecretBackend, SecretRef},
+    Error, Result,
+};
+use reqwest::{Client, StatusCode};
+use serde::Deserialize;
+use std::collections::HashMap;
+use std::time::Duration;
+use tracing::info;
+
+/// KV version 2 read response
+#[derive(Debug, Deserialize)]
+struct KvResponse {
+    data: KvData,
+}
+
+#[derive(Debug, Deserialize)]
+struct KvData {
+    #[serde(default)]
+    data: HashMap<String, serde_json::Value>,
+}
+
+/// Reads secrets from a Vault KV version 2 engine
+pub struct VaultSecrets {
+    client: Client,
+    addr: String,
+    token: String,
+    namespace: Option<String>,
+}
+
+impl std::fmt::Debug for VaultSecrets {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("VaultSecrets")
+            .field("addr", &self.addr)
+            .field("namespace", &self.namespace)
+            .finish_non_exhaustive()
+    }
+}
+
+impl VaultSecrets {
+    /// Create a backend, reading the token from `VAULT_TOKEN` when the
+    /// configuration has none
+    pub fn new(config: &VaultSecretsConfig) -> Result<Self> {
+        let token = match &config.token {
+            Some(token) => token.clone(),
+            None => std::env::var("VAULT_TOKEN").map_err(|_| {
+                Error::config("Vault token is not configured and VAULT_TOKEN is not set")
+            })?,
+        };
+        let client = HttpClientConfig::default()
+            .builder()?
+            .timeout(Duration::from_secs(config.timeout_secs))
+            .build()
+            .map_err(|e| Error::config(format!("Failed to create HTTP client: {}", e)))?;
+
+        info!(addr = %config.addr, "Creating Vault secret backend");
+        Ok(Self {
+            client,
+            addr: config.addr.trim_end_matches('/').to_string(),
+            token,
+            namespace: config.namespace.clone(),
+        })
+    }
+}
+
+#[async_trait]
+impl SecretBackend for VaultSecrets {
+    async fn fetch(&self, reference: &SecretRef) -> Result<String> {
+        let SecretRef::Vault { path, key } = reference else {
+            return Err(Error::config(format!(
+                "Cannot read {} from Vault",
+                reference
+            )));
+        };
+        let (mount, secret) = path
+            .split_once('/')
+            .ok_or_else(|| Error::config(format!("{} names no KV mount", reference)))?;
+
+        let mut request = self
+            .client
+            .get(format!("{}/v1/{}/data/{}", self.addr, mount, secret))
+            .header("X-Vault-Token", &self.token);
+        if let Some(namespace) = &self.namespace {
+            request = request.header("X-Vault-Namespace", namespace);
+        }
+
+        let response = request
+            .send()
+            .await
+            .map_err(|e| Error::connection(format!("Vault request failed: {}", e.without_url())))?;
+        match response.status() {
+            status if status.is_success() => {}
+            StatusCode::NOT_FOUND => {
+                return Err(Error::not_found(format!("Vault secret {}", path)));
+            }
+            status => {
+                return Err(Error::connection(format!(
+                    "Vault read of {} failed with status {}",
+                    path, status
+                )));
+            }
+        }
+        let mut response: KvResponse = response
+            .json()
+            .await
+            .map_err(|e| Error::connection(format!("Invalid Vault response: {}", e)))?;
+
+        match response.data.data.remove(key) {
+            Some(serde_json::Value::String(value)) => Ok(value),
+            Some(serde_json::Value::Null) | None => Err(Error::not_found(format!(
+                "Vault secret {} has no key {}",
+                path, key
+            ))),
+            Some(value) => Ok(value.to_string()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use wiremock::{
+        matchers::{header, method, path},
+        Mock, MockServer, ResponseTemplate,
+    };
+
+    #[tokio::test]
+    async fn test_reads_kv_key() {
+        let server = MockServer::start().await;
+        Mock::given(method("GET"))
+            .and(path("/v1/kv/data/sentinel"))
+            .and(header("x-vault-token", "root"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({
+                "data": {
+                    "data": { "influx_token": "t0" },
+                    "metadata": { "version": 3 },
+                },
+            })))
+            .mount(&server)
+            .await;
+
+        let secrets = VaultSecrets::new(&VaultSecretsConfig {
+            addr: server.uri(),
+            token: Some("root".to_string()),
+            namespace: None,
+            timeout_secs: 5,
+        })
+        .unwrap();
+        let reference = |raw: &str| SecretRef::parse(raw).unwrap().unwrap();
+        assert_eq!(
+            secrets
+                .fetch(&reference("vault:kv/sentinel#influx_token"))
+                .await
+                .unwrap(),
+            "t0"
+        );
+        assert!(secrets
+            .fetch(&reference("vault:kv/sentinel#redis_password"))
+            .await
+            .is_err());
+        assert!(secrets
+            .fetch(&reference("vault:kv/other#influx_token"))
+            .await
+            .is_err());
+    }
+}
@@ -0,0 +1,261 @@
+//! Structured API access log.
+//!
+//! Unlike the debug-level request logging in [`crate::middleware`], access
+//! log entries are emitted on their own `access_log` tracing target so they
+//! can be routed to a separate sink, and are sampled per route so that probe
+//! and scrape endpoints do not drown out real traffic.
+
+use axum::{
+    body::{Body, HttpBody},
+    extract::{MatchedPath, State},
+    http::{header, HeaderMap, Request},
+    middleware::Next,
+    response::Response,
+};
+use llm_sentinel_core::{config::AccessLogConfig, correlation};
+use serde::Serialize;
+use sha2::{Digest, Sha256};
+use std::{sync::Arc, time::Instant};
+
+/// One logged request
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct AccessLogEntry {
+    /// Request method
+    pub method: String,
+    /// Request path
+    pub path: String,
+    /// Matched route template, e.g. `/api/v1/pricing/:model`
+    pub route: Option<String>,
+    /// Response status code
+    pub status: u16,
+    /// Time to produce the response headers
+    pub latency_ms: f64,
+    /// Declared request body size
+    pub request_bytes: Option<u64>,
+    /// Response body size, when known up front
+    pub response_bytes: Option<u64>,
+    /// Fingerprint of the presented API key
+    pub api_key_id: Option<String>,
+    /// Whether the request exceeded the slow-request threshold
+    pub slow: bool,
+    /// Correlation ID returned with the response
+    pub correlation_id: Option<String>,
+}
+
+impl AccessLogEntry {
+    fn emit(&self) {
+        macro_rules! emit {
+            ($level:ident) => {
+                tracing::$level!(
+                    target: "access_log",
+                    method = %self.method,
+                    path = %self.path,
+                    route = self.route.as_deref(),
+                    status = self.status,
+                    latency_ms = self.latency_ms,
+                    request_bytes = self.request_bytes,
+                    response_bytes = self.response_bytes,
+                    api_key_id = self.api_key_id.as_deref(),
+                    slow = self.slow,
+                    correlation_id = self.correlation_id.as_deref(),
+                    "Access"
+                )
+            };
+        }
+        if self.slow {
+            emit!(warn);
+        } else {
+            emit!(info);
+        }
+    }
+}
+
+/// Decides which requests are logged
+#[derive(Debug)]
+pub struct AccessLogger {
+    config: AccessLogConfig,
+}
+
+impl AccessLogger {
+    /// Create a logger
+    pub fn new(config: AccessLogConfig) -> Self {
+        Self { config }
+    }
+
+    /// Sample rate for a route template or, for unmatched requests, a path
+    pub fn sample_rate(&self, route: &str) -> f64 {
+        self.config
+            .routes
+            .get(route)
+            .copied()
+            .unwrap_or(self.config.sample_rate)
+    }
+
+    /// Whether an entry is logged
+    ///
+    /// Slow requests and server errors are always logged.
+    pub fn should_log(&self, entry: &AccessLogEntry) -> bool {
+        if entry.slow || entry.status >= 500 {
+            return true;
+        }
+        let rate = self.sample_rate(entry.route.as_deref().unwrap_or(&entry.path));
+        rate >= 1.0 || (rate > 0.0 && rand::random::<f64>() < rate)
+    }
+
+    fn is_slow(&self, latency_ms: f64) -> bool {
+        self.config
+            .slow_request_ms
+            .is_some_and(|threshold| latency_ms >= threshold as f64)
+    }
+}
+
+/// Non-reversible identifier of the API key presented in `X-API-Key` or as
+/// a bearer token
+pub fn api_key_id(headers: &HeaderMap) -> Option<String> {
+    let key = headers
+        .get("x-api-key")
+        .and_then(|value| value.to_str().ok())
+        .or_else(|| {
+            headers
+                .get(header::AUTHORIZATION)
+                .and_then(|value| value.to_str().ok())
+                .and_then(|value| value.strip_prefix("Bearer "))
+        })?
+        .trim();
+    if key.is_empty() {
+        return None;
+    }
+    let digest = Sha256::digest(key.as_bytes());
+    Some(hex::encode(&digest[..6]))
+}
+
+/// Access log middleware
+pub async fn access_log_middleware(
+    State(logger): State<Arc<AccessLogger>>,
+    req: Request<Body>,
+    next: Next,
+) -> Response {
+    let start = Instant::now();
+    let method = req.method().clone();
+    let path = req.uri().path().to_string();
+    let route = req
+        .extensions()
+        .get::<MatchedPath>()
+        .map(|matched| matched.as_str().to_string());
+    let request_bytes = req
+        .headers()
+        .get(header::CONTENT_LENGTH)
+        .and_then(|value| value.to_str().ok())
+        .and_then(|value| value.parse().ok());
+    let api_key_id = api_key_id(req.headers());
+
+    let response = next.run(req).await;
+
+    let latency_ms = start.elapsed().as_secs_f64() * 1000.0;
+    let entry = AccessLogEntry {
+        method: method.to_string(),
+        path,
+        route,
+        status: response.status().as_u16(),
+        latency_ms,
+        request_bytes,
+        response_bytes: response.body().size_hint().exact(),
+        api_key_id,
+        slow: logger.is_slow(latency_ms),
+        correlation_id: response
+            .headers()
+            .get(correlation::HEADER)
+            .and_then(|value| value.to_str().ok())
+            .map(str::to_string),
+    };
+    if logger.should_log(&entry) {
+        entry.emit();
+    }
+
+    response
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use axum::{http::HeaderValue, middleware, routing::get, Router};
+    use std::collections::BTreeMap;
+    use tower::ServiceExt;
+
+    fn entry(route: &str, status: u16) -> AccessLogEntry {
+        AccessLogEntry {
+            method: "GET".to_string(),
+            path: route.to_string(),
+            route: Some(route.to_string()),
+            status,
+            latency_ms: 1.0,
+            request_bytes: None,
+            response_bytes: Some(2),
+            api_key_id: None,
+            slow: false,
+            correlation_id: None,
+        }
+    }
+
+    #[test]
+    fn test_sampling() {
+        let logger = AccessLogger::new(AccessLogConfig {
+            enabled: true,
+            sample_rate: 1.0,
+            routes: BTreeMap::from([("/health/live".to_string(), 0.0)]),
+            slow_request_ms: Some(500),
+        });
+
+        assert!(logger.should_log(&entry("/api/v1/telemetry", 200)));
+        assert!(!logger.should_log(&entry("/health/live", 200)));
+        assert!(logger.should_log(&entry("/health/live", 503)));
+        assert!(logger.is_slow(750.0));
+        assert!(!logger.is_slow(20.0));
+        assert!(logger.should_log(&AccessLogEntry {
+            slow: true,
+            ..entry("/health/live", 200)
+        }));
+    }
+
+    #[test]
+    fn test_api_key_id() {
+        let mut headers = HeaderMap::new();
+        assert_eq!(api_key_id(&headers), None);
+
+        headers.insert(
+            header::AUTHORIZATION,
+            HeaderValue::from_static("Bearer s3cret"),
+        );
+        let bearer = api_key_id(&headers).unwrap();
+        assert_eq!(bearer.len(), 12);
+        assert!(!bearer.contains("s3cret"));
+
+        headers.insert("x-api-key", HeaderValue::from_static("s3cret"));
+        assert_eq!(api_key_id(&headers).as_ref(), Some(&bearer));
+
+        headers.insert("x-api-key", HeaderValue::from_static("other"));
+        assert_ne!(api_key_id(&headers).unwrap(), bearer);
+    }
+
+    #[tokio::test]
+    async fn test_middleware_passes_response_through() {
+        let logger = Arc::new(AccessLogger::new(AccessLogConfig {
+            enabled: true,
+            ..Default::default()
+        }));
+        let app = Router::new()
+            .route("/items/:id", get(|| async { "ok" }))
+            .layer(middleware::from_fn_with_state(
+                logger,
+                access_log_middleware,
+            ));
+
+        let request = Request::get("/items/7").body(Body::empty()).unwrap();
+        let response = app.oneshot(request).await.unwrap();
+        assert_eq!(response.status(), 200);
+        let body = axum::body::to_bytes(response.into_body(), usize::MAX)
+            .await
+            .unwrap();
+        assert_eq!(&body[..], b"ok");
+    }
+}
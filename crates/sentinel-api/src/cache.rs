@@ -0,0 +1,338 @@
+//! Response cache for aggregate queries.
+//!
+//! Heatmaps, cost reports, SLO status and the other summary endpoints each
+//! run one or more heavy storage queries, and dashboards poll them every few
+//! seconds. Successful `GET` responses are kept for a short TTL, keyed by
+//! path, query parameters and `Accept` header (the cost report also renders
+//! CSV).
+//!
+//! Clients force a fresh response with `Cache-Control: no-cache` (or
+//! `Pragma: no-cache`), which also refreshes the cached entry, or skip the
+//! cache entirely with `Cache-Control: no-store`. Every response carries
+//! `X-Cache: hit`, `miss` or `bypass`, and hits carry an `Age` header.
+
+use axum::{
+    body::{Body, Bytes},
+    extract::State,
+    http::{header, HeaderMap, HeaderValue, Method, Request, StatusCode},
+    middleware::Next,
+    response::{IntoResponse, Response},
+};
+use llm_sentinel_core::{
+    config::ResponseCacheConfig,
+    memory::{MemoryConsumer, ShedCost},
+};
+use moka::future::Cache;
+use std::{
+    sync::Arc,
+    time::{Duration, Instant},
+};
+use tracing::warn;
+
+/// Response header reporting how the cache handled a request
+pub const X_CACHE: &str = "x-cache";
+
+/// A cached response
+#[derive(Debug)]
+struct CachedResponse {
+    status: StatusCode,
+    headers: HeaderMap,
+    body: Bytes,
+    stored_at: Instant,
+}
+
+/// How a request uses the cache, from its `Cache-Control` and `Pragma`
+/// headers
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CacheDirective {
+    /// Serve from the cache when possible
+    Use,
+    /// Skip the cached entry but store the fresh response
+    Refresh,
+    /// Skip the cache entirely
+    Bypass,
+}
+
+impl CacheDirective {
+    /// Read the directive from request headers
+    pub fn from_headers(headers: &HeaderMap) -> Self {
+        let directives = headers
+            .get_all(header::CACHE_CONTROL)
+            .iter()
+            .filter_map(|value| value.to_str().ok())
+            .flat_map(|value| value.split(','))
+            .map(|directive| directive.trim().to_ascii_lowercase())
+            .collect::<Vec<_>>();
+        if directives.iter().any(|d| d == "no-store") {
+            return Self::Bypass;
+        }
+        let pragma_no_cache = headers
+            .get(header::PRAGMA)
+            .and_then(|value| value.to_str().ok())
+            .is_some_and(|value| value.trim().eq_ignore_ascii_case("no-cache"));
+        if pragma_no_cache
+            || directives
+                .iter()
+                .any(|d| d == "no-cache" || d == "max-age=0")
+        {
+            Self::Refresh
+        } else {
+            Self::Use
+        }
+    }
+}
+
+/// Cache key for a request: path, sorted query parameters and `Accept`
+/// header
+pub fn cache_key<B>(req: &Request<B>) -> String {
+    let mut params: Vec<&str> = req
+        .uri()
+        .query()
+        .unwrap_or_default()
+        .split('&')
+        .filter(|param| !param.is_empty())
+        .collect();
+    params.sort_unstable();
+    let accept = req
+        .headers()
+        .get(header::ACCEPT)
+        .and_then(|value| value.to_str().ok())
+        .unwrap_or_default();
+    format!("{}?{}|{}", req.uri().path(), params.join("&"), accept)
+}
+
+/// Cache of successful aggregate responses
+pub struct ResponseCache {
+    cache: Cache<String, Arc<CachedResponse>>,
+    config: ResponseCacheConfig,
+}
+
+impl std::fmt::Debug for ResponseCache {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("ResponseCache")
+            .field("config", &self.config)
+            .field("entry_count", &self.cache.entry_count())
+            .finish()
+    }
+}
+
+impl ResponseCache {
+    /// Create a cache
+    pub fn new(config: ResponseCacheConfig) -> Self {
+        let cache = Cache::builder()
+            .max_capacity(config.max_entries)
+            .time_to_live(Duration::from_secs(config.ttl_secs))
+            .build();
+        Self { cache, config }
+    }
+}
+
+/// Responses are cheap to recompute, so the whole cache is dropped
+impl MemoryConsumer for ResponseCache {
+    fn name(&self) -> &str {
+        "response_cache"
+    }
+
+    fn memory_usage(&self) -> usize {
+        self.cache
+            .iter()
+            .map(|(key, cached)| {
+                let headers: usize = cached
+                    .headers
+                    .iter()
+                    .map(|(name, value)| name.as_str().len() + value.len())
+                    .sum();
+                std::mem::size_of::<CachedResponse>() + key.len() + headers + cached.body.len()
+            })
+            .sum()
+    }
+
+    fn shed_cost(&self) -> ShedCost {
+        ShedCost::Cheap
+    }
+
+    fn shed(&self, _bytes: usize) -> usize {
+        let usage = self.memory_usage();
+        self.cache.invalidate_all();
+        usage
+    }
+}
+
+fn with_cache_header(mut response: Response, result: &'static str) -> Response {
+    response
+        .headers_mut()
+        .insert(X_CACHE, HeaderValue::from_static(result));
+    metrics::counter!("sentinel_api_cache_requests_total", "result" => result).increment(1);
+    response
+}
+
+/// Response cache middleware, applied to aggregate routes
+pub async fn response_cache_middleware(
+    State(cache): State<Arc<ResponseCache>>,
+    req: Request<Body>,
+    next: Next,
+) -> Response {
+    if req.method() != Method::GET {
+        return next.run(req).await;
+    }
+    let directive = CacheDirective::from_headers(req.headers());
+    if directive == CacheDirective::Bypass {
+        return with_cache_header(next.run(req).await, "bypass");
+    }
+
+    let key = cache_key(&req);
+    if directive == CacheDirective::Use {
+        if let Some(cached) = cache.cache.get(&key).await {
+            let mut response = Response::new(Body::from(cached.body.clone()));
+            *response.status_mut() = cached.status;
+            *response.headers_mut() = cached.headers.clone();
+            response.headers_mut().insert(
+                header::AGE,
+                HeaderValue::from(cached.stored_at.elapsed().as_secs()),
+            );
+            return with_cache_header(response, "hit");
+        }
+    }
+
+    let response = next.run(req).await;
+    if response.status() != StatusCode::OK {
+        return with_cache_header(response, "miss");
+    }
+
+    let (parts, body) = response.into_parts();
+    let body = match axum::body::to_bytes(body, usize::MAX).await {
+        Ok(body) => body,
+        Err(e) => {
+            warn!(error = %e, "Failed to buffer response for caching");
+            return StatusCode::INTERNAL_SERVER_ERROR.into_response();
+        }
+    };
+    cache
+        .cache
+        .insert(
+            key,
+            Arc::new(CachedResponse {
+                status: parts.status,
+                headers: parts.headers.clone(),
+                body: body.clone(),
+                stored_at: Instant::now(),
+            }),
+        )
+        .await;
+    with_cache_header(Response::from_parts(parts, Body::from(body)), "miss")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use axum::{middleware, routing::get, Router};
+    use std::sync::atomic::{AtomicU64, Ordering};
+    use tower::ServiceExt;
+
+    fn app() -> Router {
+        let cache = Arc::new(ResponseCache::new(ResponseCacheConfig {
+            enabled: true,
+            ..Default::default()
+        }));
+        let calls = Arc::new(AtomicU64::new(0));
+        Router::new()
+            .route(
+                "/report",
+                get(move || {
+                    let calls = calls.clone();
+                    async move { calls.fetch_add(1, Ordering::SeqCst).to_string() }
+                }),
+            )
+            .route_layer(middleware::from_fn_with_state(
+                cache,
+                response_cache_middleware,
+            ))
+    }
+
+    async fn get_report(app: &Router, uri: &str, cache_control: Option<&str>) -> (String, String) {
+        let mut request = Request::get(uri);
+        if let Some(value) = cache_control {
+            request = request.header(header::CACHE_CONTROL, value);
+        }
+        let response = app
+            .clone()
+            .oneshot(request.body(Body::empty()).unwrap())
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+        let result = response.headers()[X_CACHE].to_str().unwrap().to_string();
+        let body = axum::body::to_bytes(response.into_body(), usize::MAX)
+            .await
+            .unwrap();
+        (String::from_utf8(body.to_vec()).unwrap(), result)
+    }
+
+    #[test]
+    fn test_cache_key() {
+        let a = Request::get("/api/v1/costs?group_by=model&hours=24")
+            .body(())
+            .unwrap();
+        let b = Request::get("/api/v1/costs?hours=24&group_by=model")
+            .body(())
+            .unwrap();
+        let csv = Request::get("/api/v1/costs?hours=24&group_by=model")
+            .header(header::ACCEPT, "text/csv")
+            .body(())
+            .unwrap();
+        assert_eq!(cache_key(&a), cache_key(&b));
+        assert_ne!(cache_key(&a), cache_key(&csv));
+    }
+
+    #[test]
+    fn test_cache_directive() {
+        let mut headers = HeaderMap::new();
+        assert_eq!(CacheDirective::from_headers(&headers), CacheDirective::Use);
+
+        headers.insert(header::PRAGMA, HeaderValue::from_static("no-cache"));
+        assert_eq!(
+            CacheDirective::from_headers(&headers),
+            CacheDirective::Refresh
+        );
+
+        headers.insert(
+            header::CACHE_CONTROL,
+            HeaderValue::from_static("max-age=0, no-store"),
+        );
+        assert_eq!(
+            CacheDirective::from_headers(&headers),
+            CacheDirective::Bypass
+        );
+    }
+
+    #[tokio::test]
+    async fn test_middleware() {
+        let app = app();
+
+        assert_eq!(
+            get_report(&app, "/report", None).await,
+            ("0".into(), "miss".into())
+        );
+        assert_eq!(
+            get_report(&app, "/report", None).await,
+            ("0".into(), "hit".into())
+        );
+        assert_eq!(
+            get_report(&app, "/report?hours=1", None).await,
+            ("1".into(), "miss".into())
+        );
+
+        // no-cache refreshes the entry, no-store leaves it alone
+        assert_eq!(
+            get_report(&app, "/report", Some("no-cache")).await,
+            ("2".into(), "miss".into())
+        );
+        assert_eq!(
+            get_report(&app, "/report", Some("no-store")).await,
+            ("3".into(), "bypass".into())
+        );
+        assert_eq!(
+            get_report(&app, "/report", None).await,
+            ("2".into(), "hit".into())
+        );
+    }
+}
@@ -1,9 +1,75 @@
 //! API request handlers.
 
+use axum::{http::StatusCode, Json};
+use llm_sentinel_core::Error;
+
+use crate::ErrorResponse;
+
+pub mod availability;
+pub mod compare;
+pub mod cost;
+pub mod credentials;
+pub mod dedup;
+pub mod delivery;
+pub mod export;
+pub mod federation;
+pub mod fleet;
 pub mod health;
+pub mod incidents;
+pub mod lifecycle;
 pub mod metrics;
+pub mod noise;
+pub mod pricing;
+pub mod quality;
 pub mod query;
+pub mod redetect;
+pub mod search;
+pub mod selfcheck;
+pub mod similarity;
+pub mod simulation;
+pub mod slo;
+pub mod snapshot;
+pub mod stream;
+pub mod tuning;
 
+pub use availability::*;
+pub use compare::*;
+pub use cost::*;
+pub use credentials::*;
+pub use dedup::*;
+pub use delivery::*;
+pub use export::*;
+pub use federation::*;
+pub use fleet::*;
 pub use health::*;
+pub use lifecycle::*;
 pub use metrics::*;
+pub use noise::*;
+pub use pricing::*;
+pub use quality::*;
 pub use query::*;
+pub use redetect::*;
+pub use search::*;
+pub use selfcheck::*;
+pub use similarity::*;
+pub use simulation::*;
+pub use slo::*;
+pub use snapshot::*;
+pub use stream::*;
+pub use tuning::*;
+
+/// Error response for a failed storage query
+///
+/// Queries cut off by the request deadline are reported as
+/// `504 Gateway Timeout` rather than as server errors.
+pub(crate) fn query_failed(e: &Error) -> (StatusCode, Json<ErrorResponse>) {
+    let mut source = e;
+    while let Error::WithContext { source: inner, .. } = source {
+        source = inner;
+    }
+    let (status, code) = match source {
+        Error::Timeout(_) => (StatusCode::GATEWAY_TIMEOUT, "query_timeout"),
+        _ => (StatusCode::INTERNAL_SERVER_ERROR, "query_failed"),
+    };
+    (status, Json(ErrorResponse::new(code, e.to_string())))
+}
@@ -0,0 +1,73 @@
+//! Provider/model availability endpoint.
+
+use axum::{
+    extract::{Query, State},
+    http::StatusCode,
+    Json,
+};
+use llm_sentinel_core::types::ModelId;
+use llm_sentinel_detection::availability::ModelAvailability;
+use serde::Deserialize;
+use std::sync::Arc;
+use tracing::debug;
+
+use super::query::QueryState;
+use crate::{ErrorResponse, ResponseMetadata, SuccessResponse};
+
+/// Default availability range (1 hour)
+const DEFAULT_RANGE_MINUTES: u64 = 60;
+
+/// Query parameters for availability
+#[derive(Debug, Deserialize)]
+pub struct AvailabilityQueryParams {
+    /// Provider filter (e.g. `openai`)
+    pub provider: Option<String>,
+    /// Model ID filter
+    pub model: Option<String>,
+    /// Range in minutes (bounded by the tracker's retention)
+    pub minutes: Option<u64>,
+}
+
+/// Availability endpoint
+pub async fn availability(
+    State(state): State<Arc<QueryState>>,
+    Query(params): Query<AvailabilityQueryParams>,
+) -> Result<Json<SuccessResponse<Vec<ModelAvailability>>>, (StatusCode, Json<ErrorResponse>)> {
+    let tracker = state.availability_tracker.as_ref().ok_or_else(|| {
+        (
+            StatusCode::NOT_FOUND,
+            Json(ErrorResponse::new(
+                "availability_disabled",
+                "Availability tracking is not enabled",
+            )),
+        )
+    })?;
+
+    let range_secs = params.minutes.unwrap_or(DEFAULT_RANGE_MINUTES) * 60;
+    if range_secs == 0 || range_secs > tracker.config().retention_secs {
+        return Err((
+            StatusCode::BAD_REQUEST,
+            Json(ErrorResponse::new(
+                "invalid_range",
+                format!(
+                    "minutes must be between 1 and {}",
+                    tracker.config().retention_secs / 60
+                ),
+            )),
+        ));
+    }
+
+    let model = params.model.map(ModelId::new);
+    let series = tracker.availability(params.provider.as_deref(), model.as_ref(), range_secs);
+    debug!("Reporting availability for {} models", series.len());
+
+    let total_count = series.len();
+    Ok(Json(SuccessResponse::new(series).with_metadata(
+        ResponseMetadata {
+            total_count: Some(total_count),
+            page: None,
+            page_size: None,
+            next_cursor: None,
+        },
+    )))
+}
@@ -0,0 +1,275 @@
+//! Anomaly comparison endpoint for detector tuning.
+//!
+//! Compares anomaly counts between a baseline and a candidate slice, each a
+//! time range plus an optional label selector. Comparing two ranges shows
+//! the effect of a threshold change deployed between them; comparing two
+//! label values over the same range (e.g. `config_version=v1` against
+//! `config_version=v2`) compares configurations running side by side.
+
+use axum::{
+    extract::{Query, State},
+    http::StatusCode,
+    Json,
+};
+use chrono::{DateTime, Utc};
+use llm_sentinel_core::{
+    events::AnomalyEvent,
+    types::{Environment, ModelId, ServiceId},
+};
+use llm_sentinel_storage::query::{AnomalyQuery, TimeRange};
+use serde::{Deserialize, Serialize};
+use std::{collections::BTreeMap, sync::Arc};
+use tracing::{debug, error};
+
+use super::{
+    query::{parse_label_selector, parse_time_range, QueryState},
+    query_failed,
+};
+use crate::{ErrorResponse, SuccessResponse};
+
+/// Maximum number of anomalies counted per side
+const MAX_COMPARED_ANOMALIES: usize = 100_000;
+
+/// Query parameters for the anomaly comparison
+///
+/// Without explicit baseline bounds, the baseline is the period of the same
+/// length immediately before the candidate, or the candidate range itself
+/// when `baseline_labels` is given.
+#[derive(Debug, Default, Deserialize)]
+pub struct CompareQueryParams {
+    /// Candidate start time (ISO 8601)
+    pub start: Option<String>,
+    /// Candidate end time (ISO 8601)
+    pub end: Option<String>,
+    /// Candidate range in hours (default: 24)
+    pub hours: Option<i64>,
+    /// Candidate label filter, e.g. `config_version=v2`
+    pub labels: Option<String>,
+    /// Baseline start time (ISO 8601)
+    pub baseline_start: Option<String>,
+    /// Baseline end time (ISO 8601)
+    pub baseline_end: Option<String>,
+    /// Baseline label filter, e.g. `config_version=v1`
+    pub baseline_labels: Option<String>,
+    /// Service ID filter
+    pub service: Option<String>,
+    /// Model ID filter
+    pub model: Option<String>,
+    /// Environment filter
+    pub environment: Option<String>,
+}
+
+/// One side of a comparison
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CompareSlice {
+    /// Range start
+    pub start: DateTime<Utc>,
+    /// Range end
+    pub end: DateTime<Utc>,
+    /// Label filter
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub labels: Option<String>,
+    /// Anomalies counted
+    pub total: u64,
+    /// Whether counting stopped at the per-side maximum
+    pub truncated: bool,
+}
+
+/// Count difference for one dimension value
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct CountDiff {
+    /// Dimension value, e.g. a service ID
+    pub key: String,
+    /// Baseline count
+    pub baseline: u64,
+    /// Candidate count
+    pub candidate: u64,
+    /// Candidate minus baseline
+    pub change: i64,
+    /// Relative change in percent; absent when the baseline is zero
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub change_pct: Option<f64>,
+}
+
+impl CountDiff {
+    fn new(key: impl Into<String>, baseline: u64, candidate: u64) -> Self {
+        Self {
+            key: key.into(),
+            baseline,
+            candidate,
+            change: candidate as i64 - baseline as i64,
+            change_pct: (baseline > 0)
+                .then(|| (candidate as f64 - baseline as f64) / baseline as f64 * 100.0),
+        }
+    }
+}
+
+/// Anomaly comparison
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CompareResponse {
+    /// Baseline slice
+    pub baseline: CompareSlice,
+    /// Candidate slice
+    pub candidate: CompareSlice,
+    /// Overall difference
+    pub total: CountDiff,
+    /// Differences by severity
+    pub by_severity: Vec<CountDiff>,
+    /// Differences by anomaly type
+    pub by_type: Vec<CountDiff>,
+    /// Differences by service
+    pub by_service: Vec<CountDiff>,
+}
+
+/// Anomaly counts by dimension
+#[derive(Debug, Default)]
+struct Counts {
+    total: u64,
+    by_severity: BTreeMap<String, u64>,
+    by_type: BTreeMap<String, u64>,
+    by_service: BTreeMap<String, u64>,
+}
+
+impl Counts {
+    fn of(anomalies: &[AnomalyEvent]) -> Self {
+        let mut counts = Self::default();
+        for anomaly in anomalies {
+            counts.total += 1;
+            *counts
+                .by_severity
+                .entry(anomaly.severity.to_string())
+                .or_default() += 1;
+            *counts
+                .by_type
+                .entry(anomaly.anomaly_type.to_string())
+                .or_default() += 1;
+            *counts
+                .by_service
+                .entry(anomaly.service_name.to_string())
+                .or_default() += 1;
+        }
+        counts
+    }
+}
+
+/// Differences for every key on either side, largest change first
+fn diff(baseline: &BTreeMap<String, u64>, candidate: &BTreeMap<String, u64>) -> Vec<CountDiff> {
+    let mut keys: Vec<&String> = baseline.keys().chain(candidate.keys()).collect();
+    keys.sort();
+    keys.dedup();
+
+    let mut diffs: Vec<CountDiff> = keys
+        .into_iter()
+        .map(|key| {
+            let count = |counts: &BTreeMap<String, u64>| counts.get(key).copied().unwrap_or(0);
+            CountDiff::new(key.as_str(), count(baseline), count(candidate))
+        })
+        .collect();
+    diffs.sort_by(|a, b| {
+        b.change
+            .unsigned_abs()
+            .cmp(&a.change.unsigned_abs())
+            .then_with(|| a.key.cmp(&b.key))
+    });
+    diffs
+}
+
+/// Count the anomalies of one slice
+async fn count_slice(
+    state: &QueryState,
+    base_query: &AnomalyQuery,
+    range: TimeRange,
+    labels: Option<String>,
+) -> Result<(CompareSlice, Counts), (StatusCode, Json<ErrorResponse>)> {
+    let mut query = base_query.clone();
+    query.time_range = range.clone();
+    if let Some(selector) = labels.as_deref() {
+        query = query.with_labels(parse_label_selector(selector)?);
+    }
+
+    let mut anomalies = state.storage.query_anomalies(query).await.map_err(|e| {
+        error!("Anomaly comparison query failed: {}", e);
+        query_failed(&e)
+    })?;
+    let truncated = anomalies.len() > MAX_COMPARED_ANOMALIES;
+    anomalies.truncate(MAX_COMPARED_ANOMALIES);
+
+    let counts = Counts::of(&anomalies);
+    let slice = CompareSlice {
+        start: range.start,
+        end: range.end,
+        labels,
+        total: counts.total,
+        truncated,
+    };
+    Ok((slice, counts))
+}
+
+/// Compare anomaly counts between a baseline and a candidate
+pub async fn compare_anomalies(
+    State(state): State<Arc<QueryState>>,
+    Query(params): Query<CompareQueryParams>,
+) -> Result<Json<SuccessResponse<CompareResponse>>, (StatusCode, Json<ErrorResponse>)> {
+    debug!("Anomaly comparison: {:?}", params);
+
+    let candidate_range = parse_time_range(params.start, params.end, params.hours)?;
+    let baseline_range = match (params.baseline_start, params.baseline_end) {
+        (Some(start), Some(end)) => parse_time_range(Some(start), Some(end), None)?,
+        (None, None) if params.baseline_labels.is_some() => candidate_range.clone(),
+        (None, None) => {
+            let length = candidate_range.end - candidate_range.start;
+            TimeRange::new(candidate_range.start - length, candidate_range.start)
+        }
+        _ => {
+            return Err((
+                StatusCode::BAD_REQUEST,
+                Json(ErrorResponse::new(
+                    "invalid_time",
+                    "baseline_start and baseline_end must be given together",
+                )),
+            ))
+        }
+    };
+    if params.labels.is_none()
+        && params.baseline_labels.is_none()
+        && baseline_range.start == candidate_range.start
+        && baseline_range.end == candidate_range.end
+    {
+        return Err((
+            StatusCode::BAD_REQUEST,
+            Json(ErrorResponse::new(
+                "identical_slices",
+                "Baseline and candidate select the same anomalies",
+            )),
+        ));
+    }
+
+    // Expired anomalies were still detected in the compared periods
+    let mut base_query = AnomalyQuery::new(candidate_range.clone()).with_expired();
+    base_query.limit = Some(MAX_COMPARED_ANOMALIES + 1);
+    if let Some(service) = params.service {
+        base_query = base_query.with_service(ServiceId::new(service));
+    }
+    if let Some(model) = params.model {
+        base_query = base_query.with_model(ModelId::new(model));
+    }
+    if let Some(environment) = params.environment {
+        base_query = base_query.with_environment(Environment::new(environment));
+    }
+
+    let (baseline, baseline_counts) =
+        count_slice(&state, &base_query, baseline_range, params.baseline_labels).await?;
+    let (candidate, candidate_counts) =
+        count_slice(&state, &base_query, candidate_range, params.labels).await?;
+
+    let response = CompareResponse {
+        total: CountDiff::new("total", baseline.total, candidate.total),
+        by_severity: diff(&baseline_counts.by_severity, &candidate_counts.by_severity),
+        by_type: diff(&baseline_counts.by_type, &candidate_counts.by_type),
+        by_service: diff(&baseline_counts.by_service, &candidate_counts.by_service),
+        baseline,
+        candidate,
+    };
+
+    Ok(Json(SuccessResponse::new(response)))
+}
@@ -0,0 +1,303 @@
+//! Cost report endpoints.
+
+use axum::{
+    extract::{Query, State},
+    http::{header, StatusCode},
+    response::{IntoResponse, Response},
+    Json,
+};
+use llm_sentinel_core::types::{ModelId, ServiceId};
+use llm_sentinel_storage::{
+    budget::BudgetProjection,
+    cost::{CostDimension, CostReportQuery, CostReportRow},
+};
+use serde::{Deserialize, Serialize};
+use std::sync::Arc;
+use tracing::{debug, error};
+
+use super::{
+    query::{parse_interval, parse_time_range, QueryState},
+    query_failed,
+};
+use crate::{ErrorResponse, ResponseMetadata, SuccessResponse};
+
+/// Query parameters for cost reports
+#[derive(Debug, Deserialize)]
+pub struct CostQueryParams {
+    /// Comma-separated dimensions: `service`, `model`, `user`, `tag:<key>`
+    pub group_by: Option<String>,
+    /// Optional bucket width for a time series breakdown, e.g. `1d`
+    pub interval: Option<String>,
+    /// Service ID filter
+    pub service: Option<String>,
+    /// Model ID filter
+    pub model: Option<String>,
+    /// Start time (ISO 8601)
+    pub start: Option<String>,
+    /// End time (ISO 8601)
+    pub end: Option<String>,
+    /// Time range in hours
+    pub hours: Option<i64>,
+    /// Output format: `json` (default) or `csv`
+    pub format: Option<String>,
+}
+
+/// Cost report response
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CostReportResponse {
+    /// Start of the reported range
+    pub start: chrono::DateTime<chrono::Utc>,
+    /// End of the reported range
+    pub end: chrono::DateTime<chrono::Utc>,
+    /// Dimensions the rows are grouped by
+    pub group_by: Vec<String>,
+    /// Total cost across all rows (USD)
+    pub total_cost_usd: f64,
+    /// Report rows
+    pub rows: Vec<CostReportRow>,
+}
+
+/// Cost report endpoint
+pub async fn cost_report(
+    State(state): State<Arc<QueryState>>,
+    Query(params): Query<CostQueryParams>,
+) -> Result<Response, (StatusCode, Json<ErrorResponse>)> {
+    debug!("Cost report query: {:?}", params);
+
+    let csv = match params.format.as_deref().map(str::to_lowercase).as_deref() {
+        None | Some("json") => false,
+        Some("csv") => true,
+        Some(other) => {
+            return Err((
+                StatusCode::BAD_REQUEST,
+                Json(ErrorResponse::new(
+                    "invalid_format",
+                    format!("Invalid format: {} (expected json or csv)", other),
+                )),
+            ))
+        }
+    };
+
+    let time_range = parse_time_range(params.start, params.end, params.hours)?;
+    let mut query = CostReportQuery::new(time_range.clone());
+
+    if let Some(group_by) = params.group_by {
+        for dimension in group_by.split(',').filter(|d| !d.trim().is_empty()) {
+            let dimension: CostDimension = dimension.parse().map_err(|e| {
+                (
+                    StatusCode::BAD_REQUEST,
+                    Json(ErrorResponse::new("invalid_group_by", format!("{}", e))),
+                )
+            })?;
+            query = query.group_by(dimension);
+        }
+    }
+
+    if let Some(interval) = params.interval {
+        let interval_secs = parse_interval(&interval).map_err(|e| {
+            (
+                StatusCode::BAD_REQUEST,
+                Json(ErrorResponse::new("invalid_interval", e)),
+            )
+        })?;
+        query = query.with_interval(interval_secs);
+    }
+
+    if let Some(service) = params.service {
+        query = query.with_service(ServiceId::new(service));
+    }
+
+    if let Some(model) = params.model {
+        query = query.with_model(ModelId::new(model));
+    }
+
+    let group_by: Vec<String> = query.group_by.iter().map(|d| d.to_string()).collect();
+
+    let rows = state
+        .storage
+        .query_cost_report(query)
+        .await
+        .map_err(|e| {
+            error!("Cost report query failed: {}", e);
+            query_failed(&e)
+        })?;
+
+    debug!("Retrieved {} cost report rows", rows.len());
+
+    if csv {
+        return Ok((
+            [
+                (header::CONTENT_TYPE, "text/csv; charset=utf-8"),
+                (
+                    header::CONTENT_DISPOSITION,
+                    "attachment; filename=\"cost-report.csv\"",
+                ),
+            ],
+            to_csv(&group_by, &rows),
+        )
+            .into_response());
+    }
+
+    let total_count = rows.len();
+    let response = SuccessResponse::new(CostReportResponse {
+        start: time_range.start,
+        end: time_range.end,
+        group_by,
+        total_cost_usd: rows.iter().map(|r| r.cost_usd).sum(),
+        rows,
+    })
+    .with_metadata(ResponseMetadata {
+        total_count: Some(total_count),
+        page: None,
+        page_size: None,
+        next_cursor: None,
+    });
+
+    Ok(Json(response).into_response())
+}
+
+/// Query parameters for budget projections
+#[derive(Debug, Default, Deserialize)]
+pub struct CostForecastParams {
+    /// Service ID filter
+    pub service: Option<String>,
+}
+
+/// Month-end spend projections against the configured budgets
+pub async fn cost_forecast(
+    State(state): State<Arc<QueryState>>,
+    Query(params): Query<CostForecastParams>,
+) -> Result<Json<SuccessResponse<Vec<BudgetProjection>>>, (StatusCode, Json<ErrorResponse>)> {
+    let Some(budgets) = &state.budgets else {
+        return Err((
+            StatusCode::NOT_FOUND,
+            Json(ErrorResponse::new(
+                "budgets_disabled",
+                "Cost budgets are not configured",
+            )),
+        ));
+    };
+
+    let mut projections = budgets
+        .project(state.storage.as_ref(), chrono::Utc::now())
+        .await
+        .map_err(|e| {
+            error!("Cost forecast failed: {}", e);
+            query_failed(&e)
+        })?;
+    if let Some(service) = params.service {
+        let service = ServiceId::new(service);
+        projections.retain(|projection| projection.service == service);
+    }
+
+    let total_count = projections.len();
+    Ok(Json(SuccessResponse::new(projections).with_metadata(
+        ResponseMetadata {
+            total_count: Some(total_count),
+            page: None,
+            page_size: None,
+            next_cursor: None,
+        },
+    )))
+}
+
+/// Render report rows as CSV
+fn to_csv(group_by: &[String], rows: &[CostReportRow]) -> String {
+    let with_bucket = rows.iter().any(|r| r.bucket_start.is_some());
+
+    let mut header: Vec<String> = Vec::new();
+    if with_bucket {
+        header.push("bucket_start".to_string());
+    }
+    header.extend(group_by.iter().cloned());
+    header.extend(
+        ["cost_usd", "requests", "prompt_tokens", "completion_tokens"]
+            .iter()
+            .map(|c| c.to_string()),
+    );
+
+    let mut out = csv_line(&header);
+
+    for row in rows {
+        let mut fields: Vec<String> = Vec::with_capacity(header.len());
+        if with_bucket {
+            fields.push(
+                row.bucket_start
+                    .map(|t| t.to_rfc3339())
+                    .unwrap_or_default(),
+            );
+        }
+        for dimension in group_by {
+            fields.push(row.group.get(dimension).cloned().unwrap_or_default());
+        }
+        fields.push(format!("{:.6}", row.cost_usd));
+        fields.push(row.requests.to_string());
+        fields.push(row.prompt_tokens.to_string());
+        fields.push(row.completion_tokens.to_string());
+        out.push_str(&csv_line(&fields));
+    }
+
+    out
+}
+
+/// Format one CSV record, quoting fields as needed (RFC 4180)
+fn csv_line(fields: &[String]) -> String {
+    let escaped: Vec<String> = fields
+        .iter()
+        .map(|field| {
+            if field.contains([',', '"', '\n', '\r']) {
+                format!("\"{}\"", field.replace('"', "\"\""))
+            } else {
+                field.clone()
+            }
+        })
+        .collect();
+
+    format!("{}\r\n", escaped.join(","))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::BTreeMap;
+
+    fn create_test_row(service: &str, cost: f64) -> CostReportRow {
+        let mut group = BTreeMap::new();
+        group.insert("service".to_string(), service.to_string());
+        CostReportRow {
+            bucket_start: None,
+            group,
+            cost_usd: cost,
+            requests: 3,
+            prompt_tokens: 30,
+            completion_tokens: 60,
+        }
+    }
+
+    #[test]
+    fn test_csv_export() {
+        let rows = vec![
+            create_test_row("checkout", 1.5),
+            create_test_row("search, \"beta\"", 0.25),
+        ];
+        let csv = to_csv(&["service".to_string()], &rows);
+        let lines: Vec<&str> = csv.split("\r\n").collect();
+
+        assert_eq!(
+            lines[0],
+            "service,cost_usd,requests,prompt_tokens,completion_tokens"
+        );
+        assert_eq!(lines[1], "checkout,1.500000,3,30,60");
+        assert_eq!(lines[2], "\"search, \"\"beta\"\"\",0.250000,3,30,60");
+    }
+
+    #[test]
+    fn test_csv_export_with_buckets() {
+        let mut row = create_test_row("checkout", 1.0);
+        row.bucket_start = Some(chrono::TimeZone::timestamp_opt(&chrono::Utc, 0, 0).unwrap());
+        let csv = to_csv(&["service".to_string()], &[row]);
+
+        assert!(csv.starts_with("bucket_start,service,"));
+        assert!(csv.contains("1970-01-01T00:00:00+00:00,checkout,"));
+    }
+}
@@ -0,0 +1,54 @@
+//! Credential rotation endpoint.
+
+use async_trait::async_trait;
+use axum::{extract::State, http::StatusCode, Json};
+use llm_sentinel_core::Result;
+use serde::Serialize;
+use std::sync::Arc;
+use tracing::{error, info};
+
+use super::query::QueryState;
+use crate::{ErrorResponse, SuccessResponse};
+
+type CredentialError = (StatusCode, Json<ErrorResponse>);
+
+/// Fetches secrets again and applies rotated credentials
+#[async_trait]
+pub trait CredentialControl: Send + Sync {
+    /// Rotate credentials, returning the components whose credentials
+    /// changed
+    async fn rotate(&self) -> Result<Vec<String>>;
+}
+
+/// Outcome of a credential rotation
+#[derive(Debug, Clone, Serialize)]
+pub struct RotationResponse {
+    /// Components that switched to new credentials
+    pub rotated: Vec<String>,
+}
+
+/// Fetch secrets again and re-establish clients whose credentials changed
+pub async fn rotate_credentials(
+    State(state): State<Arc<QueryState>>,
+) -> std::result::Result<Json<SuccessResponse<RotationResponse>>, CredentialError> {
+    let credentials = state.credentials.as_ref().ok_or_else(|| {
+        (
+            StatusCode::NOT_FOUND,
+            Json(ErrorResponse::new(
+                "rotation_disabled",
+                "Credential rotation is not enabled",
+            )),
+        )
+    })?;
+
+    let rotated = credentials.rotate().await.map_err(|e| {
+        error!("Failed to rotate credentials: {}", e);
+        (
+            StatusCode::INTERNAL_SERVER_ERROR,
+            Json(ErrorResponse::new("rotation_failed", e.to_string())),
+        )
+    })?;
+
+    info!(rotated = ?rotated, "Credentials rotated through the API");
+    Ok(Json(SuccessResponse::new(RotationResponse { rotated })))
+}
@@ -0,0 +1,41 @@
+//! Alert deduplication statistics endpoint.
+
+use axum::{extract::State, http::StatusCode, Json};
+use serde::{Deserialize, Serialize};
+use std::{collections::BTreeMap, sync::Arc};
+
+use super::query::QueryState;
+use crate::{ErrorResponse, SuccessResponse};
+
+/// Snapshot of alert deduplication within the current windows
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct DedupStatsReport {
+    /// Alert signatures with an open window
+    pub active_signatures: usize,
+    /// Alerts suppressed in the open windows
+    pub total_deduplicated: u64,
+    /// Share of alerts suppressed (0.0 to 1.0)
+    pub deduplication_rate: f64,
+    /// Suppressed alerts by severity
+    pub by_severity: BTreeMap<String, u64>,
+}
+
+/// Source of deduplication statistics
+pub type DedupStatsSource = Arc<dyn Fn() -> DedupStatsReport + Send + Sync>;
+
+/// Deduplication statistics endpoint
+pub async fn dedup_stats(
+    State(state): State<Arc<QueryState>>,
+) -> Result<Json<SuccessResponse<DedupStatsReport>>, (StatusCode, Json<ErrorResponse>)> {
+    let source = state.dedup_stats.as_ref().ok_or_else(|| {
+        (
+            StatusCode::NOT_FOUND,
+            Json(ErrorResponse::new(
+                "dedup_disabled",
+                "Alert deduplication is not enabled",
+            )),
+        )
+    })?;
+
+    Ok(Json(SuccessResponse::new(source())))
+}
@@ -0,0 +1,281 @@
+//! Alert delivery SLA, history and redelivery endpoints.
+//!
+//! When a receiver reports a missing alert, the deliveries of that alert
+//! show what was sent where and what came back, and the alert can be sent
+//! again to one alerter. Redeliveries are recorded like any other delivery.
+
+use async_trait::async_trait;
+use axum::{
+    extract::{Path, Query, State},
+    http::StatusCode,
+    Json,
+};
+use llm_sentinel_core::{events::AlertMetadata, types::Severity, Error, Result as CoreResult};
+use llm_sentinel_storage::{
+    delivery::{sla_report, DeliveryQuery, DeliverySlaRow},
+    query::TimeRange,
+};
+use serde::{Deserialize, Serialize};
+use std::sync::Arc;
+use tracing::{debug, error, info};
+use uuid::Uuid;
+
+use super::{
+    lifecycle::{not_found, parse_alert_id},
+    query::{parse_time_range, QueryState},
+    query_failed,
+};
+use crate::{ErrorResponse, ResponseMetadata, SuccessResponse};
+
+type DeliveryError = (StatusCode, Json<ErrorResponse>);
+
+/// Sends stored alerts again
+#[async_trait]
+pub trait AlertRedelivery: Send + Sync {
+    /// Send a stored alert to the named alerter now, returning the recorded
+    /// delivery
+    async fn redeliver(&self, alert_id: Uuid, alerter: &str) -> CoreResult<AlertMetadata>;
+}
+
+/// Requested redelivery
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RedeliveryRequest {
+    /// Alerter to send the alert to
+    pub alerter: String,
+}
+
+/// Query parameters for the delivery SLA report
+#[derive(Debug, Deserialize)]
+pub struct DeliverySlaParams {
+    /// Alerter filter
+    pub alerter: Option<String>,
+    /// Severity filter
+    pub severity: Option<String>,
+    /// Start time (ISO 8601)
+    pub start: Option<String>,
+    /// End time (ISO 8601)
+    pub end: Option<String>,
+    /// Time range in hours
+    pub hours: Option<i64>,
+}
+
+/// Query parameters for the delivery history
+#[derive(Debug, Deserialize)]
+pub struct DeliveryHistoryParams {
+    /// Alerter filter
+    pub alerter: Option<String>,
+    /// Severity filter
+    pub severity: Option<String>,
+    /// Alert ID filter
+    pub alert_id: Option<String>,
+    /// Start time (ISO 8601)
+    pub start: Option<String>,
+    /// End time (ISO 8601)
+    pub end: Option<String>,
+    /// Time range in hours
+    pub hours: Option<i64>,
+}
+
+/// Delivery SLA report response
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DeliverySlaResponse {
+    /// Start of the reported range
+    pub start: chrono::DateTime<chrono::Utc>,
+    /// End of the reported range
+    pub end: chrono::DateTime<chrono::Utc>,
+    /// Whether every row meets its objective
+    pub compliant: bool,
+    /// Per alerter/severity compliance
+    pub rows: Vec<DeliverySlaRow>,
+}
+
+/// Apply the alerter and severity filters shared by the delivery endpoints
+fn delivery_query(
+    mut query: DeliveryQuery,
+    alerter: Option<String>,
+    severity: Option<String>,
+) -> Result<DeliveryQuery, DeliveryError> {
+    if let Some(alerter) = alerter {
+        query = query.with_alerter(alerter);
+    }
+
+    if let Some(severity) = severity {
+        let severity: Severity = severity.parse().map_err(|e| {
+            (
+                StatusCode::BAD_REQUEST,
+                Json(ErrorResponse::new("invalid_severity", e)),
+            )
+        })?;
+        query = query.with_severity(severity);
+    }
+
+    Ok(query)
+}
+
+/// Alert delivery SLA report endpoint
+pub async fn delivery_sla(
+    State(state): State<Arc<QueryState>>,
+    Query(params): Query<DeliverySlaParams>,
+) -> Result<Json<SuccessResponse<DeliverySlaResponse>>, DeliveryError> {
+    debug!("Delivery SLA query: {:?}", params);
+
+    let time_range = parse_time_range(params.start, params.end, params.hours)?;
+    let query = delivery_query(
+        DeliveryQuery::new(time_range.clone()),
+        params.alerter,
+        params.severity,
+    )?;
+
+    let records = state
+        .storage
+        .query_alert_deliveries(query)
+        .await
+        .map_err(|e| {
+            error!("Alert delivery query failed: {}", e);
+            query_failed(&e)
+        })?;
+
+    debug!("Retrieved {} alert delivery records", records.len());
+
+    let rows = sla_report(&records, &state.delivery_sla);
+    let total_count = rows.len();
+    let response = SuccessResponse::new(DeliverySlaResponse {
+        start: time_range.start,
+        end: time_range.end,
+        compliant: rows.iter().all(|r| r.met),
+        rows,
+    })
+    .with_metadata(ResponseMetadata {
+        total_count: Some(total_count),
+        page: None,
+        page_size: None,
+        next_cursor: None,
+    });
+
+    Ok(Json(response))
+}
+
+/// Alert delivery history endpoint
+///
+/// Returns each delivery with its outcome and, for alerters that capture
+/// it, the receiver's response body, so alerts can be matched to tickets.
+pub async fn alert_deliveries(
+    State(state): State<Arc<QueryState>>,
+    Query(params): Query<DeliveryHistoryParams>,
+) -> Result<Json<SuccessResponse<Vec<AlertMetadata>>>, DeliveryError> {
+    debug!("Delivery history query: {:?}", params);
+
+    let time_range = parse_time_range(params.start, params.end, params.hours)?;
+    let mut query = delivery_query(
+        DeliveryQuery::new(time_range),
+        params.alerter,
+        params.severity,
+    )?;
+    if let Some(alert_id) = params.alert_id {
+        query = query.with_alert_id(alert_id);
+    }
+
+    let records = state
+        .storage
+        .query_alert_deliveries(query)
+        .await
+        .map_err(|e| {
+            error!("Alert delivery query failed: {}", e);
+            query_failed(&e)
+        })?;
+
+    debug!("Retrieved {} alert delivery records", records.len());
+
+    let total_count = records.len();
+    let response = SuccessResponse::new(records).with_metadata(ResponseMetadata {
+        total_count: Some(total_count),
+        page: None,
+        page_size: None,
+        next_cursor: None,
+    });
+
+    Ok(Json(response))
+}
+
+/// Deliveries of one alert, oldest first
+///
+/// Covers every delivery since the anomaly was detected, each with the
+/// request sent and the receiver's response or error where the alerter
+/// reports them, redeliveries included.
+pub async fn alert_delivery_attempts(
+    State(state): State<Arc<QueryState>>,
+    Path(id): Path<String>,
+) -> Result<Json<SuccessResponse<Vec<AlertMetadata>>>, DeliveryError> {
+    let alert_id = parse_alert_id(&id)?;
+    let anomaly = state
+        .storage
+        .get_anomaly(alert_id)
+        .await
+        .map_err(|e| {
+            error!("Anomaly lookup failed: {}", e);
+            query_failed(&e)
+        })?
+        .ok_or_else(|| not_found(alert_id))?;
+
+    let time_range = TimeRange::new(anomaly.timestamp, chrono::Utc::now());
+    let query = DeliveryQuery::new(time_range).with_alert_id(alert_id.to_string());
+    let records = state
+        .storage
+        .query_alert_deliveries(query)
+        .await
+        .map_err(|e| {
+            error!("Alert delivery query failed: {}", e);
+            query_failed(&e)
+        })?;
+
+    let total_count = records.len();
+    let response = SuccessResponse::new(records).with_metadata(ResponseMetadata {
+        total_count: Some(total_count),
+        page: None,
+        page_size: None,
+        next_cursor: None,
+    });
+
+    Ok(Json(response))
+}
+
+/// Send a stored alert again to one alerter
+///
+/// Responds with the recorded delivery, failed or not, once the alerter is
+/// done with it.
+pub async fn redeliver_alert(
+    State(state): State<Arc<QueryState>>,
+    Path(id): Path<String>,
+    Json(request): Json<RedeliveryRequest>,
+) -> Result<Json<SuccessResponse<AlertMetadata>>, DeliveryError> {
+    let redelivery = state.redelivery.as_ref().ok_or_else(|| {
+        (
+            StatusCode::NOT_FOUND,
+            Json(ErrorResponse::new(
+                "redelivery_disabled",
+                "Alert redelivery is not enabled",
+            )),
+        )
+    })?;
+    let alert_id = parse_alert_id(&id)?;
+
+    let delivery = redelivery
+        .redeliver(alert_id, &request.alerter)
+        .await
+        .map_err(|e| {
+            error!(alert_id = %alert_id, "Failed to redeliver alert: {}", e);
+            let (status, code) = match &e {
+                Error::NotFound(_) => (StatusCode::NOT_FOUND, "not_found"),
+                _ => (StatusCode::INTERNAL_SERVER_ERROR, "redelivery_failed"),
+            };
+            (status, Json(ErrorResponse::new(code, e.to_string())))
+        })?;
+
+    info!(
+        alert_id = %alert_id,
+        alerter = %delivery.alerter,
+        status = %delivery.status,
+        "Alert redelivered through the API"
+    );
+    Ok(Json(SuccessResponse::new(delivery)))
+}
@@ -0,0 +1,265 @@
+//! Telemetry export stream.
+//!
+//! Returns every telemetry event matching a query as newline-delimited JSON.
+//! Events are read from storage one keyset page at a time and the next page
+//! is only fetched once the client has taken the previous one, so exporting
+//! a day of telemetry holds a single page in memory rather than the whole
+//! result set, and a slow client slows the reads down instead of piling up
+//! events.
+//!
+//! The first page is read before the response starts, so query errors are
+//! reported with a status code. A storage failure later on aborts the
+//! response, which clients see as a truncated body.
+
+use axum::{
+    body::{Body, Bytes},
+    extract::{Query, State},
+    http::{header, StatusCode},
+    response::{IntoResponse, Response},
+    Json,
+};
+use futures::stream;
+use llm_sentinel_core::{
+    events::TelemetryEvent,
+    types::{Environment, ModelId, ServiceId},
+    Result,
+};
+use llm_sentinel_storage::{
+    query::{Cursor, TelemetryQuery, TelemetrySort},
+    Storage,
+};
+use serde::Deserialize;
+use std::sync::Arc;
+use tracing::{debug, error};
+
+use super::{
+    query::{parse_label_selector, parse_time_range, QueryState},
+    query_failed,
+};
+use crate::ErrorResponse;
+
+/// NDJSON content type
+pub const NDJSON_CONTENT_TYPE: &str = "application/x-ndjson";
+
+/// Events read from storage per page
+const STREAM_PAGE_SIZE: usize = 1000;
+
+/// Query parameters for the telemetry stream
+#[derive(Debug, Default, Deserialize)]
+pub struct TelemetryStreamParams {
+    /// Service ID filter
+    pub service: Option<String>,
+    /// Model ID filter
+    pub model: Option<String>,
+    /// Environment filter
+    pub environment: Option<String>,
+    /// Label filter, e.g. `team=search,env=prod`
+    pub labels: Option<String>,
+    /// Start time (ISO 8601)
+    pub start: Option<String>,
+    /// End time (ISO 8601)
+    pub end: Option<String>,
+    /// Time range in hours (default: 24)
+    pub hours: Option<i64>,
+    /// Oldest first (default: true)
+    pub ascending: Option<bool>,
+    /// Stop after this many events (default: all)
+    pub limit: Option<usize>,
+    /// Fields to return, e.g. `latency_ms,cost_usd` (default: all)
+    pub fields: Option<String>,
+}
+
+/// Position of a stream in the result set
+struct StreamState {
+    storage: Arc<dyn Storage>,
+    query: TelemetryQuery,
+    /// Fields to keep, or `None` for all
+    fields: Option<Vec<String>>,
+    /// Page read ahead of the stream, or `None` once it is exhausted
+    page: Option<Vec<TelemetryEvent>>,
+    /// Events still allowed by the limit
+    remaining: usize,
+}
+
+impl StreamState {
+    /// Read the page after `cursor`
+    async fn fetch(&self, cursor: Option<Cursor>) -> Result<Vec<TelemetryEvent>> {
+        let mut query = self
+            .query
+            .clone()
+            .with_limit(STREAM_PAGE_SIZE.min(self.remaining));
+        if let Some(cursor) = cursor {
+            query = query.with_cursor(cursor);
+        }
+        self.storage.query_telemetry(query).await
+    }
+
+    /// Serialize the read-ahead page and read the next one
+    async fn next_chunk(mut self) -> Result<Option<(Bytes, Self)>> {
+        let Some(page) = self.page.take().filter(|page| !page.is_empty()) else {
+            return Ok(None);
+        };
+        let chunk = ndjson(&page, self.fields.as_deref())?;
+        ::metrics::counter!("sentinel_api_telemetry_streamed_total").increment(page.len() as u64);
+
+        self.remaining -= page.len();
+        if page.len() == STREAM_PAGE_SIZE && self.remaining > 0 {
+            let cursor = page
+                .last()
+                .map(|last| TelemetrySort::Timestamp.cursor(last));
+            self.page = Some(self.fetch(cursor).await?);
+        }
+        Ok(Some((chunk, self)))
+    }
+}
+
+/// One JSON line per event, keeping only `fields` and the event ID if given
+fn ndjson(events: &[TelemetryEvent], fields: Option<&[String]>) -> Result<Bytes> {
+    let mut chunk = Vec::with_capacity(events.len() * 256);
+    for event in events {
+        let mut value = serde_json::to_value(event)?;
+        if let (Some(fields), serde_json::Value::Object(map)) = (fields, &mut value) {
+            map.retain(|key, _| key == "event_id" || fields.iter().any(|f| f == key));
+        }
+        serde_json::to_writer(&mut chunk, &value)?;
+        chunk.push(b'\n');
+    }
+    Ok(Bytes::from(chunk))
+}
+
+/// Stream telemetry as NDJSON
+pub async fn stream_telemetry(
+    State(state): State<Arc<QueryState>>,
+    Query(params): Query<TelemetryStreamParams>,
+) -> std::result::Result<Response, (StatusCode, Json<ErrorResponse>)> {
+    debug!("Telemetry stream: {:?}", params);
+
+    let time_range = parse_time_range(params.start, params.end, params.hours)?;
+    let mut query = TelemetryQuery::new(time_range).sort_by(TelemetrySort::Timestamp);
+    if let Some(service) = params.service {
+        query = query.with_service(ServiceId::new(service));
+    }
+    if let Some(model) = params.model {
+        query = query.with_model(ModelId::new(model));
+    }
+    if let Some(environment) = params.environment {
+        query = query.with_environment(Environment::new(environment));
+    }
+    if let Some(selector) = params.labels {
+        query = query.with_labels(parse_label_selector(&selector)?);
+    }
+    query = if params.ascending.unwrap_or(true) {
+        query.ascending()
+    } else {
+        query.descending()
+    };
+
+    let mut state = StreamState {
+        storage: state.storage.clone(),
+        query,
+        fields: params.fields.map(|fields| {
+            fields
+                .split(',')
+                .map(str::trim)
+                .filter(|f| !f.is_empty())
+                .map(String::from)
+                .collect()
+        }),
+        page: None,
+        remaining: params.limit.unwrap_or(usize::MAX),
+    };
+    if state.remaining > 0 {
+        let first = state.fetch(None).await.map_err(|e| {
+            error!("Telemetry stream query failed: {}", e);
+            query_failed(&e)
+        })?;
+        state.page = Some(first);
+    }
+
+    let body = stream::try_unfold(state, |state| async move {
+        state.next_chunk().await.map_err(|e| {
+            error!("Telemetry stream aborted: {}", e);
+            e
+        })
+    });
+    Ok((
+        [(header::CONTENT_TYPE, NDJSON_CONTENT_TYPE)],
+        Body::from_stream(body),
+    )
+        .into_response())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::{Duration, Utc};
+    use llm_sentinel_storage::memory::InMemoryStorage;
+    use uuid::Uuid;
+
+    fn event(seconds_ago: i64) -> TelemetryEvent {
+        llm_sentinel_fixtures::event()
+            .service("chat")
+            .timestamp(Utc::now() - Duration::seconds(seconds_ago))
+            .build()
+    }
+
+    async fn lines(
+        state: Arc<QueryState>,
+        params: TelemetryStreamParams,
+    ) -> Vec<serde_json::Value> {
+        let response = stream_telemetry(State(state), Query(params)).await.unwrap();
+        assert_eq!(
+            response.headers()[header::CONTENT_TYPE],
+            NDJSON_CONTENT_TYPE
+        );
+        let body = axum::body::to_bytes(response.into_body(), usize::MAX)
+            .await
+            .unwrap();
+        body.split(|b| *b == b'\n')
+            .filter(|line| !line.is_empty())
+            .map(|line| serde_json::from_slice(line).unwrap())
+            .collect()
+    }
+
+    #[tokio::test]
+    async fn test_stream_spans_pages() {
+        let storage = Arc::new(InMemoryStorage::new());
+        let events: Vec<_> = (0..2500).rev().map(|i| event(10 + i)).collect();
+        storage.write_telemetry_batch(&events).await.unwrap();
+        let state = Arc::new(QueryState::new(storage));
+
+        let streamed = lines(state.clone(), TelemetryStreamParams::default()).await;
+        let ids: Vec<Uuid> = streamed
+            .iter()
+            .map(|line| line["event_id"].as_str().unwrap().parse().unwrap())
+            .collect();
+        let expected: Vec<Uuid> = events.iter().map(|e| e.event_id).collect();
+        assert_eq!(ids, expected);
+
+        let params = TelemetryStreamParams {
+            limit: Some(1500),
+            ascending: Some(false),
+            fields: Some("latency_ms".to_string()),
+            ..Default::default()
+        };
+        let streamed = lines(state, params).await;
+        assert_eq!(streamed.len(), 1500);
+        assert_eq!(streamed[0]["event_id"], events[2499].event_id.to_string());
+        assert_eq!(streamed[0].as_object().unwrap().len(), 2);
+    }
+
+    #[tokio::test]
+    async fn test_stream_empty() {
+        let state = Arc::new(QueryState::new(Arc::new(InMemoryStorage::new())));
+        assert!(lines(state.clone(), TelemetryStreamParams::default())
+            .await
+            .is_empty());
+
+        let params = TelemetryStreamParams {
+            labels: Some("team".to_string()),
+            ..Default::default()
+        };
+        let result = stream_telemetry(State(state), Query(params)).await;
+        assert_eq!(result.unwrap_err().0, StatusCode::BAD_REQUEST);
+    }
+}
@@ -0,0 +1,120 @@
+//! Federation endpoint receiving anomalies from edge instances.
+
+use async_trait::async_trait;
+use axum::{
+    extract::State,
+    http::{header, HeaderMap, StatusCode},
+    Json,
+};
+use llm_sentinel_core::{
+    events::AnomalyEvent,
+    federation::{FederationBatch, FederationReceipt},
+    Error, Result,
+};
+use sha2::{Digest, Sha256};
+use std::{collections::BTreeMap, sync::Arc};
+use tracing::{error, warn};
+
+use super::query::QueryState;
+use crate::{ErrorResponse, SuccessResponse};
+
+type FederationError = (StatusCode, Json<ErrorResponse>);
+
+/// Accepts anomalies forwarded by edge instances
+#[async_trait]
+pub trait FederationSink: Send + Sync {
+    /// Process anomalies forwarded from an authenticated `region`,
+    /// returning how many were accepted
+    async fn receive(&self, region: &str, anomalies: Vec<AnomalyEvent>) -> Result<usize>;
+}
+
+/// Edge regions allowed to forward anomalies, and where they go
+#[derive(Clone)]
+pub struct FederationReceiver {
+    /// SHA-256 of each region's bearer token
+    tokens: BTreeMap<String, [u8; 32]>,
+    sink: Arc<dyn FederationSink>,
+}
+
+impl FederationReceiver {
+    /// Accept anomalies from the given regions, keyed by bearer token
+    pub fn new(edges: &BTreeMap<String, String>, sink: Arc<dyn FederationSink>) -> Self {
+        let tokens = edges
+            .iter()
+            .map(|(region, token)| (region.clone(), Sha256::digest(token.as_bytes()).into()))
+            .collect();
+        Self { tokens, sink }
+    }
+
+    /// Whether `token` is the bearer token of `region`
+    ///
+    /// Digests are compared rather than the tokens, so the comparison time
+    /// says nothing about the expected token.
+    fn authorize(&self, region: &str, token: &str) -> bool {
+        let digest: [u8; 32] = Sha256::digest(token.as_bytes()).into();
+        self.tokens
+            .get(region)
+            .is_some_and(|expected| *expected == digest)
+    }
+}
+
+impl std::fmt::Debug for FederationReceiver {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("FederationReceiver")
+            .field("regions", &self.tokens.keys().collect::<Vec<_>>())
+            .finish_non_exhaustive()
+    }
+}
+
+fn unauthorized() -> FederationError {
+    (
+        StatusCode::UNAUTHORIZED,
+        Json(ErrorResponse::new(
+            "unauthorized",
+            "Missing or invalid federation token for region",
+        )),
+    )
+}
+
+/// Receive a batch of anomalies from an edge instance
+pub async fn receive_federated_anomalies(
+    State(state): State<Arc<QueryState>>,
+    headers: HeaderMap,
+    Json(batch): Json<FederationBatch>,
+) -> std::result::Result<Json<SuccessResponse<FederationReceipt>>, FederationError> {
+    let receiver = state.federation.as_ref().ok_or_else(|| {
+        (
+            StatusCode::NOT_FOUND,
+            Json(ErrorResponse::new(
+                "federation_disabled",
+                "This instance does not accept federated anomalies",
+            )),
+        )
+    })?;
+
+    let token = headers
+        .get(header::AUTHORIZATION)
+        .and_then(|value| value.to_str().ok())
+        .and_then(|value| value.strip_prefix("Bearer "))
+        .ok_or_else(unauthorized)?;
+    if !receiver.authorize(&batch.region, token.trim()) {
+        warn!(region = %batch.region, "Rejected federated anomalies with an invalid token");
+        metrics::counter!("sentinel_federation_rejected_total").increment(1);
+        return Err(unauthorized());
+    }
+
+    let accepted = receiver
+        .sink
+        .receive(&batch.region, batch.anomalies)
+        .await
+        .map_err(|e| {
+            error!(region = %batch.region, "Failed to process federated anomalies: {}", e);
+            let (status, code) = match e {
+                Error::Validation(_) => (StatusCode::BAD_REQUEST, "invalid_batch"),
+                _ => (StatusCode::INTERNAL_SERVER_ERROR, "federation_failed"),
+            };
+            (status, Json(ErrorResponse::new(code, e.to_string())))
+        })?;
+
+    Ok(Json(SuccessResponse::new(FederationReceipt { accepted })))
+}
@@ -0,0 +1,91 @@
+//! Fleet comparison endpoint.
+//!
+//! Shows how an anomaly's metric compares against every service using the
+//! same model, so responders can tell a provider-wide issue from one
+//! specific to the affected service.
+
+use axum::{
+    extract::{Path, Query, State},
+    http::StatusCode,
+    Json,
+};
+use chrono::Duration;
+use llm_sentinel_core::Error;
+use llm_sentinel_storage::fleet::{compare_to_fleet, FleetComparison};
+use serde::Deserialize;
+use std::sync::Arc;
+use tracing::{debug, error};
+
+use super::{
+    lifecycle::{not_found, parse_alert_id, LifecycleError},
+    query::QueryState,
+    query_failed,
+};
+use crate::{ErrorResponse, SuccessResponse};
+
+/// Default window before the anomaly (minutes)
+const DEFAULT_FLEET_MINUTES: i64 = 60;
+
+/// Maximum window before the anomaly (minutes)
+const MAX_FLEET_MINUTES: i64 = 7 * 24 * 60;
+
+/// Maximum fleet events sampled
+const MAX_FLEET_EVENTS: usize = 10_000;
+
+/// Query parameters for the fleet comparison
+#[derive(Debug, Default, Deserialize)]
+pub struct FleetQueryParams {
+    /// Window before the anomaly in minutes (default: 60)
+    pub minutes: Option<i64>,
+}
+
+/// Compare an anomaly's metric against all services using its model
+pub async fn anomaly_fleet_comparison(
+    State(state): State<Arc<QueryState>>,
+    Path(id): Path<String>,
+    Query(params): Query<FleetQueryParams>,
+) -> Result<Json<SuccessResponse<FleetComparison>>, LifecycleError> {
+    debug!("Fleet comparison for {}: {:?}", id, params);
+    let alert_id = parse_alert_id(&id)?;
+
+    let minutes = params.minutes.unwrap_or(DEFAULT_FLEET_MINUTES);
+    if !(1..=MAX_FLEET_MINUTES).contains(&minutes) {
+        return Err((
+            StatusCode::BAD_REQUEST,
+            Json(ErrorResponse::new(
+                "invalid_window",
+                format!("minutes must be between 1 and {}", MAX_FLEET_MINUTES),
+            )),
+        ));
+    }
+
+    let anomaly = state
+        .storage
+        .get_anomaly(alert_id)
+        .await
+        .map_err(|e| {
+            error!("Anomaly lookup failed: {}", e);
+            query_failed(&e)
+        })?
+        .ok_or_else(|| not_found(alert_id))?;
+
+    let comparison = compare_to_fleet(
+        state.storage.as_ref(),
+        &anomaly,
+        Duration::minutes(minutes),
+        MAX_FLEET_EVENTS,
+    )
+    .await
+    .map_err(|e| match e {
+        Error::Validation(message) => (
+            StatusCode::BAD_REQUEST,
+            Json(ErrorResponse::new("unsupported_metric", message)),
+        ),
+        e => {
+            error!("Fleet comparison query failed: {}", e);
+            query_failed(&e)
+        }
+    })?;
+
+    Ok(Json(SuccessResponse::new(comparison)))
+}
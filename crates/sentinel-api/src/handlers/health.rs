@@ -55,13 +55,25 @@ impl ComponentHealth {
             error: Some(error.into()),
         }
     }
+
+    pub fn degraded(name: impl Into<String>, reason: impl Into<String>) -> Self {
+        Self {
+            name: name.into(),
+            status: ServiceStatus::Degraded,
+            error: Some(reason.into()),
+        }
+    }
 }
 
+/// Reports the health of one component
+pub type ComponentCheck = Arc<dyn Fn() -> ComponentHealth + Send + Sync>;
+
 /// Application state for health checks
 #[derive(Clone)]
 pub struct HealthState {
     pub version: String,
     pub storage_health: Arc<dyn Fn() -> Result<(), String> + Send + Sync>,
+    pub components: Vec<ComponentCheck>,
 }
 
 impl HealthState {
@@ -72,10 +84,44 @@ impl HealthState {
         Self {
             version,
             storage_health,
+            components: Vec::new(),
+        }
+    }
+
+    /// Report another component
+    ///
+    /// A degraded component degrades the reported status without failing
+    /// readiness; an unhealthy one fails readiness like storage does.
+    pub fn with_component(mut self, check: ComponentCheck) -> Self {
+        self.components.push(check);
+        self
+    }
+
+    /// Run the extra component checks, folding them into `status`
+    fn check_components(&self, components: &mut Vec<ComponentHealth>, status: &mut ServiceStatus) {
+        for check in &self.components {
+            let component = check();
+            match component.status {
+                ServiceStatus::Unhealthy => *status = ServiceStatus::Unhealthy,
+                ServiceStatus::Degraded if *status == ServiceStatus::Healthy => {
+                    *status = ServiceStatus::Degraded
+                }
+                _ => {}
+            }
+            components.push(component);
         }
     }
 }
 
+impl std::fmt::Debug for HealthState {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("HealthState")
+            .field("version", &self.version)
+            .field("components", &self.components.len())
+            .finish_non_exhaustive()
+    }
+}
+
 /// Liveness probe - returns 200 if service is running
 pub async fn liveness() -> StatusCode {
     debug!("Liveness probe called");
@@ -102,6 +148,7 @@ pub async fn readiness(
             overall_status = ServiceStatus::Unhealthy;
         }
     }
+    state.check_components(&mut components, &mut overall_status);
 
     let response = HealthResponse {
         status: overall_status,
@@ -139,6 +186,7 @@ pub async fn health(
             overall_status = ServiceStatus::Degraded;
         }
     }
+    state.check_components(&mut components, &mut overall_status);
 
     let response = HealthResponse {
         status: overall_status,
@@ -176,4 +224,20 @@ mod tests {
         assert!(json.contains("\"status\":\"healthy\""));
         assert!(json.contains("\"version\":\"0.1.0\""));
     }
+
+    #[tokio::test]
+    async fn test_degraded_component() {
+        let state = HealthState::new("0.1.0".to_string(), Arc::new(|| Ok(()))).with_component(
+            Arc::new(|| ComponentHealth::degraded("detection", "sampling 40% of events")),
+        );
+        let state = Arc::new(state);
+
+        let response = health(State(state.clone())).await;
+        assert_eq!(response.0.data.status, ServiceStatus::Degraded);
+        assert_eq!(response.0.data.components[1].name, "detection");
+
+        // Still ready to serve
+        let response = readiness(State(state)).await.unwrap();
+        assert_eq!(response.0.data.status, ServiceStatus::Degraded);
+    }
 }
@@ -0,0 +1,255 @@
+//! Incident endpoints.
+//!
+//! Responders list incidents, read one with its timeline, change its state
+//! and attach links. Changes go through the incident tracker, when one is
+//! attached, so they never race with anomalies joining the incident. State
+//! changes are published on the event bus, when one is attached, so alerters
+//! notified of incidents hear of them.
+
+use axum::{
+    extract::{Path, Query, State},
+    http::StatusCode,
+    Json,
+};
+use llm_sentinel_core::{
+    bus::IncidentEvent,
+    incident::{Incident, IncidentState, IncidentUpdate},
+    types::ServiceId,
+    Error,
+};
+use llm_sentinel_storage::incident::{
+    get_incident as load_incident, link_incident, transition_incident, IncidentQuery,
+};
+use serde::{Deserialize, Serialize};
+use std::sync::Arc;
+use tracing::{debug, error};
+use uuid::Uuid;
+
+use super::{
+    query::{parse_time_range, QueryState},
+    query_failed,
+};
+use crate::{ErrorResponse, ResponseMetadata, SuccessResponse};
+
+type IncidentError = (StatusCode, Json<ErrorResponse>);
+
+/// Query parameters for listing incidents
+#[derive(Debug, Deserialize)]
+pub struct IncidentParams {
+    /// State filter
+    pub state: Option<String>,
+    /// Service filter
+    pub service: Option<String>,
+    /// Start time (ISO 8601)
+    pub start: Option<String>,
+    /// End time (ISO 8601)
+    pub end: Option<String>,
+    /// Time range in hours
+    pub hours: Option<i64>,
+    /// Maximum number of incidents
+    pub limit: Option<usize>,
+}
+
+/// Requested incident state change
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct IncidentStateRequest {
+    /// New state
+    pub state: IncidentState,
+    /// Who is making the change
+    #[serde(default)]
+    pub actor: Option<String>,
+    /// Why the change is made
+    #[serde(default)]
+    pub reason: Option<String>,
+}
+
+/// Link to attach to an incident
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct IncidentLinkRequest {
+    /// Link title
+    pub title: String,
+    /// Linked URL
+    pub url: String,
+    /// Who is adding the link
+    #[serde(default)]
+    pub added_by: Option<String>,
+}
+
+fn parse_incident_id(id: &str) -> Result<Uuid, IncidentError> {
+    id.parse().map_err(|_| {
+        (
+            StatusCode::BAD_REQUEST,
+            Json(ErrorResponse::new(
+                "invalid_id",
+                format!("Invalid incident ID: {}", id),
+            )),
+        )
+    })
+}
+
+fn not_found(incident_id: Uuid) -> IncidentError {
+    (
+        StatusCode::NOT_FOUND,
+        Json(ErrorResponse::new(
+            "not_found",
+            format!("Incident not found: {}", incident_id),
+        )),
+    )
+}
+
+/// List incidents updated in a time range, most recently updated first
+pub async fn list_incidents(
+    State(state): State<Arc<QueryState>>,
+    Query(params): Query<IncidentParams>,
+) -> Result<Json<SuccessResponse<Vec<Incident>>>, IncidentError> {
+    debug!("Incident query: {:?}", params);
+
+    let time_range = parse_time_range(params.start, params.end, params.hours)?;
+    let mut query = IncidentQuery::new(time_range).with_limit(params.limit.unwrap_or(100));
+    if let Some(incident_state) = params.state {
+        let incident_state: IncidentState = incident_state.parse().map_err(|e| {
+            (
+                StatusCode::BAD_REQUEST,
+                Json(ErrorResponse::new("invalid_state", e)),
+            )
+        })?;
+        query = query.with_state(incident_state);
+    }
+    if let Some(service) = params.service {
+        query = query.with_service(ServiceId::new(service));
+    }
+
+    let incidents = state.storage.query_incidents(query).await.map_err(|e| {
+        error!("Incident query failed: {}", e);
+        query_failed(&e)
+    })?;
+
+    debug!("Retrieved {} incidents", incidents.len());
+
+    let total_count = incidents.len();
+    let response = SuccessResponse::new(incidents).with_metadata(ResponseMetadata {
+        total_count: Some(total_count),
+        page: None,
+        page_size: None,
+        next_cursor: None,
+    });
+
+    Ok(Json(response))
+}
+
+/// Get a single incident with its timeline and links
+pub async fn get_incident(
+    State(state): State<Arc<QueryState>>,
+    Path(id): Path<String>,
+) -> Result<Json<SuccessResponse<Incident>>, IncidentError> {
+    let incident_id = parse_incident_id(&id)?;
+    let incident = load_incident(state.storage.as_ref(), incident_id)
+        .await
+        .map_err(|e| {
+            error!("Incident lookup failed: {}", e);
+            query_failed(&e)
+        })?
+        .ok_or_else(|| not_found(incident_id))?;
+
+    Ok(Json(SuccessResponse::new(incident)))
+}
+
+/// Change the state of an incident
+pub async fn update_incident_state(
+    State(state): State<Arc<QueryState>>,
+    Path(id): Path<String>,
+    Json(request): Json<IncidentStateRequest>,
+) -> Result<Json<SuccessResponse<Incident>>, IncidentError> {
+    let incident_id = parse_incident_id(&id)?;
+    let storage = state.storage.as_ref();
+    let result = match &state.incidents {
+        Some(tracker) => {
+            tracker
+                .transition(
+                    storage,
+                    incident_id,
+                    request.state,
+                    request.actor,
+                    request.reason,
+                )
+                .await
+        }
+        None => {
+            transition_incident(
+                storage,
+                incident_id,
+                request.state,
+                request.actor,
+                request.reason,
+            )
+            .await
+        }
+    };
+    let incident = result.map_err(|e| match e {
+        Error::NotFound(_) => not_found(incident_id),
+        Error::Validation(message) => (
+            StatusCode::CONFLICT,
+            Json(ErrorResponse::new("invalid_transition", message)),
+        ),
+        e => {
+            error!("Incident state change failed: {}", e);
+            query_failed(&e)
+        }
+    })?;
+
+    if let Some(bus) = &state.bus {
+        bus.incidents().publish(IncidentEvent {
+            incident: incident.clone(),
+            update: IncidentUpdate::StateChanged,
+            anomaly: None,
+        });
+    }
+
+    Ok(Json(SuccessResponse::new(incident)))
+}
+
+/// Attach a link, such as a ticket or postmortem, to an incident
+pub async fn add_incident_link(
+    State(state): State<Arc<QueryState>>,
+    Path(id): Path<String>,
+    Json(request): Json<IncidentLinkRequest>,
+) -> Result<(StatusCode, Json<SuccessResponse<Incident>>), IncidentError> {
+    let incident_id = parse_incident_id(&id)?;
+    let storage = state.storage.as_ref();
+    let result = match &state.incidents {
+        Some(tracker) => {
+            tracker
+                .link(
+                    storage,
+                    incident_id,
+                    request.title,
+                    request.url,
+                    request.added_by,
+                )
+                .await
+        }
+        None => {
+            link_incident(
+                storage,
+                incident_id,
+                request.title,
+                request.url,
+                request.added_by,
+            )
+            .await
+        }
+    };
+    let incident = result.map_err(|e| match e {
+        Error::NotFound(_) => not_found(incident_id),
+        Error::Validation(message) => (
+            StatusCode::BAD_REQUEST,
+            Json(ErrorResponse::new("invalid_link", message)),
+        ),
+        e => {
+            error!("Incident link failed: {}", e);
+            query_failed(&e)
+        }
+    })?;
+
+    Ok((StatusCode::CREATED, Json(SuccessResponse::new(incident))))
+}
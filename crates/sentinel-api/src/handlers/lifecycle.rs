@@ -0,0 +1,195 @@
+//! Anomaly lifecycle endpoints.
+//!
+//! Responders read a single anomaly, change its lifecycle state, list its
+//! state history and annotate it with notes, links and resolution
+//! summaries. State changes are published on the event bus, when one is
+//! attached, so the updated anomaly can be re-sent to alerters.
+
+use axum::{
+    extract::{Path, State},
+    http::StatusCode,
+    Json,
+};
+use llm_sentinel_core::{
+    annotation::{AnnotationKind, AnomalyAnnotation},
+    bus::LifecycleEvent,
+    events::AnomalyEvent,
+    lifecycle::{AnomalyState, AnomalyStateChange},
+    Error,
+};
+use llm_sentinel_storage::{
+    annotation::{annotate_anomaly, attach_annotations},
+    lifecycle::transition_anomaly,
+};
+use serde::{Deserialize, Serialize};
+use std::sync::Arc;
+use tracing::error;
+use uuid::Uuid;
+
+use super::{query::QueryState, query_failed};
+use crate::{ErrorResponse, SuccessResponse};
+
+pub(super) type LifecycleError = (StatusCode, Json<ErrorResponse>);
+
+/// Requested state change
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct StateChangeRequest {
+    /// New state
+    pub state: AnomalyState,
+    /// Who is making the change
+    #[serde(default)]
+    pub actor: Option<String>,
+    /// Why the change is made
+    #[serde(default)]
+    pub reason: Option<String>,
+}
+
+/// Annotation to attach
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AnnotationRequest {
+    /// What the annotation records (defaults to `note`)
+    #[serde(default)]
+    pub kind: AnnotationKind,
+    /// Note, link title or resolution summary
+    pub text: String,
+    /// Linked URL (required for links)
+    #[serde(default)]
+    pub url: Option<String>,
+    /// Who is writing the annotation
+    #[serde(default)]
+    pub author: Option<String>,
+}
+
+pub(super) fn parse_alert_id(id: &str) -> Result<Uuid, LifecycleError> {
+    id.parse().map_err(|_| {
+        (
+            StatusCode::BAD_REQUEST,
+            Json(ErrorResponse::new(
+                "invalid_id",
+                format!("Invalid anomaly ID: {}", id),
+            )),
+        )
+    })
+}
+
+pub(super) fn not_found(alert_id: Uuid) -> LifecycleError {
+    (
+        StatusCode::NOT_FOUND,
+        Json(ErrorResponse::new(
+            "not_found",
+            format!("Anomaly not found: {}", alert_id),
+        )),
+    )
+}
+
+/// Get a single anomaly with its annotations
+pub async fn get_anomaly(
+    State(state): State<Arc<QueryState>>,
+    Path(id): Path<String>,
+) -> Result<Json<SuccessResponse<AnomalyEvent>>, LifecycleError> {
+    let alert_id = parse_alert_id(&id)?;
+    let mut anomaly = state
+        .storage
+        .get_anomaly(alert_id)
+        .await
+        .map_err(|e| {
+            error!("Anomaly lookup failed: {}", e);
+            query_failed(&e)
+        })?
+        .ok_or_else(|| not_found(alert_id))?;
+
+    attach_annotations(state.storage.as_ref(), &mut anomaly)
+        .await
+        .map_err(|e| {
+            error!("Anomaly annotation query failed: {}", e);
+            query_failed(&e)
+        })?;
+
+    Ok(Json(SuccessResponse::new(anomaly)))
+}
+
+/// Change the lifecycle state of an anomaly
+pub async fn update_anomaly_state(
+    State(state): State<Arc<QueryState>>,
+    Path(id): Path<String>,
+    Json(request): Json<StateChangeRequest>,
+) -> Result<Json<SuccessResponse<AnomalyEvent>>, LifecycleError> {
+    let alert_id = parse_alert_id(&id)?;
+    let (anomaly, change) = transition_anomaly(
+        state.storage.as_ref(),
+        alert_id,
+        request.state,
+        request.actor,
+        request.reason,
+    )
+    .await
+    .map_err(|e| match e {
+        Error::NotFound(_) => not_found(alert_id),
+        Error::Validation(message) => (
+            StatusCode::CONFLICT,
+            Json(ErrorResponse::new("invalid_transition", message)),
+        ),
+        e => {
+            error!("Anomaly state change failed: {}", e);
+            query_failed(&e)
+        }
+    })?;
+
+    if let Some(bus) = &state.bus {
+        bus.lifecycle().publish(LifecycleEvent {
+            anomaly: anomaly.clone(),
+            change,
+        });
+    }
+
+    Ok(Json(SuccessResponse::new(anomaly)))
+}
+
+/// List the state changes of an anomaly, oldest first
+pub async fn anomaly_state_history(
+    State(state): State<Arc<QueryState>>,
+    Path(id): Path<String>,
+) -> Result<Json<SuccessResponse<Vec<AnomalyStateChange>>>, LifecycleError> {
+    let alert_id = parse_alert_id(&id)?;
+    let changes = state
+        .storage
+        .query_anomaly_state_changes(alert_id)
+        .await
+        .map_err(|e| {
+            error!("Anomaly state history query failed: {}", e);
+            query_failed(&e)
+        })?;
+
+    Ok(Json(SuccessResponse::new(changes)))
+}
+
+/// Attach a note, link or resolution summary to an anomaly
+pub async fn add_anomaly_annotation(
+    State(state): State<Arc<QueryState>>,
+    Path(id): Path<String>,
+    Json(request): Json<AnnotationRequest>,
+) -> Result<(StatusCode, Json<SuccessResponse<AnomalyAnnotation>>), LifecycleError> {
+    let alert_id = parse_alert_id(&id)?;
+    let annotation = annotate_anomaly(
+        state.storage.as_ref(),
+        alert_id,
+        request.kind,
+        request.text,
+        request.url,
+        request.author,
+    )
+    .await
+    .map_err(|e| match e {
+        Error::NotFound(_) => not_found(alert_id),
+        Error::Validation(message) => (
+            StatusCode::BAD_REQUEST,
+            Json(ErrorResponse::new("invalid_annotation", message)),
+        ),
+        e => {
+            error!("Anomaly annotation failed: {}", e);
+            query_failed(&e)
+        }
+    })?;
+
+    Ok((StatusCode::CREATED, Json(SuccessResponse::new(annotation))))
+}
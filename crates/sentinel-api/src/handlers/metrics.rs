@@ -1,10 +1,274 @@
 //! Prometheus metrics endpoint.
+//!
+//! The Prometheus recorder is installed once per process, configured by
+//! [`MetricsExportConfig`]: metric names are re-prefixed, label sets beyond
+//! the per-metric limit are folded into an `other` series, and histograms
+//! get the configured bucket boundaries.
+//!
+//! Metrics declared in the core [`catalog`](llm_sentinel_core::metrics::CATALOG)
+//! are exported with their help text and declared labels only, and are not
+//! recorded while their family is disabled. The catalog itself is served
+//! next to the metrics endpoint.
 
-use axum::http::StatusCode;
+use axum::{http::StatusCode, Json};
+use llm_sentinel_core::{
+    config::MetricsExportConfig,
+    metrics::{self as catalog, MetricKind, CATALOG},
+    Error, Result,
+};
+use metrics::{Counter, Gauge, Histogram, Key, KeyName, Label, Metadata, Recorder, SharedString, Unit};
 use metrics_exporter_prometheus::{Matcher, PrometheusBuilder, PrometheusHandle};
-use std::sync::Arc;
+use serde::Serialize;
+use std::{
+    collections::{hash_map::DefaultHasher, HashMap, HashSet},
+    hash::{Hash, Hasher},
+    sync::{Arc, Mutex, OnceLock},
+    time::Duration,
+};
 use tracing::{debug, warn};
 
+use crate::SuccessResponse;
+
+/// Process-wide Prometheus handle (the global recorder can only be installed once)
+static PROMETHEUS_HANDLE: OnceLock<Arc<PrometheusHandle>> = OnceLock::new();
+
+/// Prefix of exported metric names, set when the recorder is installed
+static EXPORT_PREFIX: OnceLock<String> = OnceLock::new();
+
+/// Serializes recorder installation
+static INSTALL_LOCK: Mutex<()> = Mutex::new(());
+
+/// Prefix metric names are emitted with
+const SOURCE_PREFIX: &str = "sentinel_";
+
+/// Interval of histogram/summary maintenance
+const UPKEEP_INTERVAL: Duration = Duration::from_secs(5);
+
+/// Install the Prometheus recorder with the given configuration
+///
+/// Only the first call installs a recorder; later calls return its handle
+/// and ignore `config`.
+pub fn install_recorder(config: &MetricsExportConfig) -> Result<Arc<PrometheusHandle>> {
+    let _guard = INSTALL_LOCK.lock().unwrap();
+    if let Some(handle) = PROMETHEUS_HANDLE.get() {
+        return Ok(handle.clone());
+    }
+
+    catalog::set_disabled_families(&config.disabled_families)?;
+    let recorder = NamingRecorder::new(prometheus_builder(config)?.build_recorder(), config)?;
+    let handle = recorder.inner.handle();
+    for def in CATALOG {
+        let name = KeyName::from_const_str(def.name);
+        let help = SharedString::const_str(def.help);
+        match def.kind {
+            MetricKind::Counter => recorder.describe_counter(name, None, help),
+            MetricKind::Gauge => recorder.describe_gauge(name, None, help),
+            MetricKind::Histogram => recorder.describe_histogram(name, None, help),
+        }
+    }
+    metrics::set_global_recorder(recorder)
+        .map_err(|e| Error::config(format!("Failed to install metrics recorder: {}", e)))?;
+
+    let upkeep = handle.clone();
+    std::thread::Builder::new()
+        .name("metrics-upkeep".to_string())
+        .spawn(move || loop {
+            std::thread::sleep(UPKEEP_INTERVAL);
+            upkeep.run_upkeep();
+        })
+        .map_err(|e| Error::config(format!("Failed to start metrics upkeep: {}", e)))?;
+
+    let handle = Arc::new(handle);
+    EXPORT_PREFIX.get_or_init(|| config.prefix.clone());
+    PROMETHEUS_HANDLE
+        .set(handle.clone())
+        .unwrap_or_else(|_| unreachable!("recorder installed twice"));
+    Ok(handle)
+}
+
+/// Exporter with the configured histogram buckets
+fn prometheus_builder(config: &MetricsExportConfig) -> Result<PrometheusBuilder> {
+    let mut builder = PrometheusBuilder::new();
+    if let Some(buckets) = &config.default_buckets {
+        validate_buckets("default_buckets", buckets)?;
+        builder = builder
+            .set_buckets(buckets)
+            .map_err(|e| Error::config(format!("Invalid default_buckets: {}", e)))?;
+    }
+    for (name, buckets) in &config.buckets {
+        validate_buckets(name, buckets)?;
+        builder = builder
+            .set_buckets_for_metric(
+                Matcher::Full(format!("{}_{}", config.prefix, name)),
+                buckets,
+            )
+            .map_err(|e| Error::config(format!("Invalid buckets for {}: {}", name, e)))?;
+    }
+    Ok(builder)
+}
+
+/// Buckets must be finite and strictly increasing
+fn validate_buckets(name: &str, buckets: &[f64]) -> Result<()> {
+    if buckets.is_empty()
+        || buckets.iter().any(|b| !b.is_finite())
+        || buckets.windows(2).any(|w| w[0] >= w[1])
+    {
+        return Err(Error::config(format!(
+            "Histogram buckets for {} must be finite and strictly increasing",
+            name
+        )));
+    }
+    Ok(())
+}
+
+/// Check a prefix is a valid Prometheus metric name
+fn validate_prefix(prefix: &str) -> Result<()> {
+    let mut chars = prefix.chars();
+    let valid = chars
+        .next()
+        .is_some_and(|c| c.is_ascii_alphabetic() || c == '_' || c == ':')
+        && chars.all(|c| c.is_ascii_alphanumeric() || c == '_' || c == ':');
+    if !valid {
+        return Err(Error::config(format!(
+            "Invalid metric prefix: {:?}",
+            prefix
+        )));
+    }
+    Ok(())
+}
+
+/// Recorder renaming metrics, enforcing declared labels, capping label sets
+/// and skipping disabled families before delegating
+#[derive(Debug)]
+struct NamingRecorder<R> {
+    inner: R,
+    prefix: String,
+    max_label_sets: Option<usize>,
+    /// Hashes of the label sets seen per metric
+    label_sets: Mutex<HashMap<String, HashSet<u64>>>,
+    /// Metrics already warned about for undeclared labels
+    undeclared: Mutex<HashSet<String>>,
+}
+
+impl<R> NamingRecorder<R> {
+    fn new(inner: R, config: &MetricsExportConfig) -> Result<Self> {
+        validate_prefix(&config.prefix)?;
+        Ok(Self {
+            inner,
+            prefix: config.prefix.clone(),
+            max_label_sets: config.max_label_sets,
+            label_sets: Mutex::new(HashMap::new()),
+            undeclared: Mutex::new(HashSet::new()),
+        })
+    }
+
+    /// Metric name with the configured prefix
+    fn name(&self, name: &str) -> String {
+        match name.strip_prefix(SOURCE_PREFIX) {
+            Some(rest) => format!("{}_{}", self.prefix, rest),
+            None => name.to_string(),
+        }
+    }
+
+    fn key_name(&self, name: KeyName) -> KeyName {
+        KeyName::from(self.name(name.as_str()))
+    }
+
+    /// Labels of a key, without labels its catalog entry does not declare
+    fn labels(&self, key: &Key) -> Vec<Label> {
+        let Some(def) = catalog::lookup(key.name()) else {
+            return key.labels().cloned().collect();
+        };
+        let (labels, undeclared): (Vec<Label>, Vec<Label>) = key
+            .labels()
+            .cloned()
+            .partition(|label| def.labels.contains(&label.key()));
+        if !undeclared.is_empty() && self.undeclared.lock().unwrap().insert(def.name.to_string()) {
+            let keys: Vec<&str> = undeclared.iter().map(Label::key).collect();
+            warn!(
+                metric = def.name,
+                ?keys,
+                "Dropping labels not declared in the metrics catalog"
+            );
+        }
+        labels
+    }
+
+    /// Renamed key with declared labels, with label values replaced by
+    /// `other` once the metric has reached its label set limit
+    fn key(&self, key: &Key) -> Key {
+        let name = self.name(key.name());
+        let labels = self.labels(key);
+        let Some(max) = self.max_label_sets else {
+            return Key::from_parts(name, labels);
+        };
+        if labels.is_empty() {
+            return Key::from_parts(name, labels);
+        }
+
+        let mut hasher = DefaultHasher::new();
+        for label in &labels {
+            label.key().hash(&mut hasher);
+            label.value().hash(&mut hasher);
+        }
+        let hash = hasher.finish();
+
+        let mut label_sets = self.label_sets.lock().unwrap();
+        let seen = label_sets.entry(name.clone()).or_default();
+        if seen.contains(&hash) || seen.len() < max {
+            seen.insert(hash);
+            return Key::from_parts(name, labels);
+        }
+        if seen.len() == max {
+            // Sentinel entry so the warning is logged once per metric
+            seen.insert(0);
+            warn!(metric = %name, max, "Metric reached its label set limit");
+        }
+        let labels: Vec<Label> = labels
+            .iter()
+            .map(|label| Label::new(label.key().to_string(), "other"))
+            .collect();
+        Key::from_parts(name, labels)
+    }
+}
+
+impl<R: Recorder> Recorder for NamingRecorder<R> {
+    fn describe_counter(&self, key: KeyName, unit: Option<Unit>, description: SharedString) {
+        self.inner
+            .describe_counter(self.key_name(key), unit, description)
+    }
+
+    fn describe_gauge(&self, key: KeyName, unit: Option<Unit>, description: SharedString) {
+        self.inner.describe_gauge(self.key_name(key), unit, description)
+    }
+
+    fn describe_histogram(&self, key: KeyName, unit: Option<Unit>, description: SharedString) {
+        self.inner
+            .describe_histogram(self.key_name(key), unit, description)
+    }
+
+    fn register_counter(&self, key: &Key, metadata: &Metadata<'_>) -> Counter {
+        if !catalog::is_enabled(key.name()) {
+            return Counter::noop();
+        }
+        self.inner.register_counter(&self.key(key), metadata)
+    }
+
+    fn register_gauge(&self, key: &Key, metadata: &Metadata<'_>) -> Gauge {
+        if !catalog::is_enabled(key.name()) {
+            return Gauge::noop();
+        }
+        self.inner.register_gauge(&self.key(key), metadata)
+    }
+
+    fn register_histogram(&self, key: &Key, metadata: &Metadata<'_>) -> Histogram {
+        if !catalog::is_enabled(key.name()) {
+            return Histogram::noop();
+        }
+        self.inner.register_histogram(&self.key(key), metadata)
+    }
+}
+
 /// Metrics exporter handle
 #[derive(Clone)]
 pub struct MetricsState {
@@ -12,37 +276,36 @@ pub struct MetricsState {
 }
 
 impl MetricsState {
-    /// Create a new metrics state with Prometheus exporter
+    /// Create a new metrics state, installing the Prometheus recorder with
+    /// the default configuration if none was installed yet
     pub fn new() -> Self {
-        let handle = PrometheusBuilder::new()
-            .set_buckets_for_metric(
-                Matcher::Full("sentinel_detection_latency_seconds".to_string()),
-                &[0.001, 0.005, 0.01, 0.025, 0.05, 0.1, 0.25, 0.5, 1.0, 2.5, 5.0],
-            )
-            .unwrap()
-            .set_buckets_for_metric(
-                Matcher::Full("sentinel_ingestion_latency_seconds".to_string()),
-                &[0.001, 0.005, 0.01, 0.025, 0.05, 0.1, 0.25, 0.5, 1.0],
-            )
-            .unwrap()
-            .install_recorder()
-            .unwrap();
-
-        Self {
-            handle: Arc::new(handle),
-        }
+        let handle = install_recorder(&MetricsExportConfig::default())
+            .expect("default metrics configuration is valid");
+        Self { handle }
     }
 
-    /// Get the Prometheus handle
+/// Get the Prometheus handle
     pub fn handle(&self) -> Arc<PrometheusHandle> {
         self.handle.clone()
     }
 }
 
+impl Default for MetricsState {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl std::fmt::Debug for MetricsState {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("MetricsState").finish_non_exhaustive()
+    }
+}
+
 /// Prometheus metrics endpoint handler
 pub async fn metrics_handler(
     axum::extract::State(state): axum::extract::State<Arc<MetricsState>>,
-) -> Result<String, StatusCode> {
+) -> std::result::Result<String, StatusCode> {
     debug!("Metrics endpoint called");
 
     let metrics = state.handle.render();
@@ -50,6 +313,43 @@ pub async fn metrics_handler(
     Ok(metrics)
 }
 
+/// Catalog entry as served by the catalog endpoint
+#[derive(Debug, Serialize)]
+pub struct MetricCatalogEntry {
+    /// Exported name, with the configured prefix
+    pub name: String,
+    /// Metric kind
+    pub kind: MetricKind,
+    /// Family the metric belongs to
+    pub family: &'static str,
+    /// Label keys
+    pub labels: &'static [&'static str],
+    /// Help text
+    pub help: &'static str,
+    /// Whether the metric's family is recorded
+    pub enabled: bool,
+}
+
+/// Metrics catalog endpoint handler
+pub async fn metrics_catalog() -> Json<SuccessResponse<Vec<MetricCatalogEntry>>> {
+    let prefix = EXPORT_PREFIX.get().map_or("sentinel", String::as_str);
+    let entries = CATALOG
+        .iter()
+        .map(|def| MetricCatalogEntry {
+            name: match def.name.strip_prefix(SOURCE_PREFIX) {
+                Some(rest) => format!("{}_{}", prefix, rest),
+                None => def.name.to_string(),
+            },
+            kind: def.kind,
+            family: def.family,
+            labels: def.labels,
+            help: def.help,
+            enabled: catalog::is_family_enabled(def.family),
+        })
+        .collect();
+    Json(SuccessResponse::new(entries))
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -74,4 +374,81 @@ mod tests {
         let metrics_text = result.unwrap();
         assert!(metrics_text.contains("test_counter"));
     }
+
+    #[test]
+    fn test_naming_recorder_key() {
+        let config = MetricsExportConfig {
+            prefix: "llm".to_string(),
+            max_label_sets: Some(2),
+            ..Default::default()
+        };
+        let recorder = NamingRecorder::new((), &config).unwrap();
+        let key = |service: &str| {
+            Key::from_parts(
+                "sentinel_events_total",
+                vec![Label::new("service", service.to_string())],
+            )
+        };
+
+        let first = recorder.key(&key("a"));
+        assert_eq!(first.name(), "llm_events_total");
+        assert_eq!(first.labels().next().unwrap().value(), "a");
+        assert_eq!(recorder.key(&key("b")).labels().next().unwrap().value(), "b");
+        // Label sets beyond the limit are folded into `other`
+        assert_eq!(recorder.key(&key("c")).labels().next().unwrap().value(), "other");
+        assert_eq!(recorder.key(&key("d")).labels().next().unwrap().value(), "other");
+        // Known label sets keep their values
+        assert_eq!(recorder.key(&key("a")).labels().next().unwrap().value(), "a");
+
+        // Metrics not emitted under the `sentinel_` prefix keep their name
+        assert_eq!(recorder.key(&Key::from_name("process_cpu")).name(), "process_cpu");
+    }
+
+    #[test]
+    fn test_naming_recorder_declared_labels() {
+        let recorder = NamingRecorder::new((), &MetricsExportConfig::default()).unwrap();
+        let key = Key::from_parts(
+            "sentinel_storage_writes_total",
+            vec![
+                Label::new("type", "anomaly"),
+                Label::new("service", "chat"),
+            ],
+        );
+        // Labels the catalog does not declare are dropped
+        let key = recorder.key(&key);
+        let labels: Vec<_> = key.labels().map(Label::key).collect();
+        assert_eq!(labels, ["type"]);
+    }
+
+    #[tokio::test]
+    async fn test_metrics_catalog() {
+        let Json(response) = metrics_catalog().await;
+        assert_eq!(response.data.len(), CATALOG.len());
+        let entry = response
+            .data
+            .iter()
+            .find(|entry| entry.name == "sentinel_alert_deliveries_total")
+            .unwrap();
+        assert_eq!(entry.kind, MetricKind::Counter);
+        assert_eq!(entry.family, "alerting");
+        assert_eq!(entry.labels, ["alerter", "severity", "status"]);
+    }
+
+    #[test]
+    fn test_metrics_config_validation() {
+        assert!(validate_prefix("llm_sentinel").is_ok());
+        assert!(validate_prefix("9lives").is_err());
+        assert!(validate_prefix("has-dash").is_err());
+
+        assert!(validate_buckets("ok", &[0.0001, 0.001, 0.01]).is_ok());
+        assert!(validate_buckets("empty", &[]).is_err());
+        assert!(validate_buckets("unsorted", &[0.1, 0.01]).is_err());
+        assert!(validate_buckets("nan", &[f64::NAN]).is_err());
+
+        let config = MetricsExportConfig {
+            default_buckets: Some(vec![1.0, 1.0]),
+            ..Default::default()
+        };
+        assert!(prometheus_builder(&config).is_err());
+    }
 }
@@ -0,0 +1,96 @@
+//! Alert noise endpoint.
+//!
+//! Lists the anomaly signatures that fire most often without a responder
+//! acting on them, with their acknowledgement rates and response times, so
+//! tuning effort goes where it reduces pager fatigue.
+
+use axum::{
+    extract::{Query, State},
+    http::StatusCode,
+    Json,
+};
+use llm_sentinel_core::types::{Environment, ModelId, ServiceId};
+use llm_sentinel_storage::{
+    noise::{noise_report, NoiseReport},
+    query::AnomalyQuery,
+};
+use serde::Deserialize;
+use std::sync::Arc;
+use tracing::{debug, error};
+
+use super::{
+    query::{parse_time_range, QueryState},
+    query_failed,
+};
+use crate::{ErrorResponse, SuccessResponse};
+
+/// Default range (hours)
+const DEFAULT_NOISE_HOURS: i64 = 7 * 24;
+
+/// Default number of signatures returned
+const DEFAULT_NOISE_LIMIT: usize = 20;
+
+/// Maximum number of signatures returned
+const MAX_NOISE_LIMIT: usize = 500;
+
+/// Maximum number of anomalies analyzed
+const MAX_NOISE_ANOMALIES: usize = 100_000;
+
+/// Query parameters for the noise report
+#[derive(Debug, Default, Deserialize)]
+pub struct NoiseQueryParams {
+    /// Start time (ISO 8601)
+    pub start: Option<String>,
+    /// End time (ISO 8601)
+    pub end: Option<String>,
+    /// Range in hours (default: 168)
+    pub hours: Option<i64>,
+    /// Service ID filter
+    pub service: Option<String>,
+    /// Model ID filter
+    pub model: Option<String>,
+    /// Environment filter
+    pub environment: Option<String>,
+    /// Signatures returned (default: 20)
+    pub limit: Option<usize>,
+}
+
+/// Report the noisiest anomaly signatures
+pub async fn alert_noise(
+    State(state): State<Arc<QueryState>>,
+    Query(params): Query<NoiseQueryParams>,
+) -> Result<Json<SuccessResponse<NoiseReport>>, (StatusCode, Json<ErrorResponse>)> {
+    debug!("Alert noise query: {:?}", params);
+
+    let limit = params.limit.unwrap_or(DEFAULT_NOISE_LIMIT);
+    if !(1..=MAX_NOISE_LIMIT).contains(&limit) {
+        return Err((
+            StatusCode::BAD_REQUEST,
+            Json(ErrorResponse::new(
+                "invalid_limit",
+                format!("limit must be between 1 and {}", MAX_NOISE_LIMIT),
+            )),
+        ));
+    }
+
+    let hours = params.hours.unwrap_or(DEFAULT_NOISE_HOURS);
+    let mut query = AnomalyQuery::new(parse_time_range(params.start, params.end, Some(hours))?);
+    if let Some(service) = params.service {
+        query = query.with_service(ServiceId::new(service));
+    }
+    if let Some(model) = params.model {
+        query = query.with_model(ModelId::new(model));
+    }
+    if let Some(environment) = params.environment {
+        query = query.with_environment(Environment::new(environment));
+    }
+
+    let report = noise_report(state.storage.as_ref(), query, MAX_NOISE_ANOMALIES, limit)
+        .await
+        .map_err(|e| {
+            error!("Alert noise query failed: {}", e);
+            query_failed(&e)
+        })?;
+
+    Ok(Json(SuccessResponse::new(report)))
+}
@@ -0,0 +1,85 @@
+//! Model pricing endpoints.
+
+use axum::{
+    extract::{Path, State},
+    http::StatusCode,
+    Json,
+};
+use llm_sentinel_core::pricing::{ModelPrice, PriceTable};
+use std::{collections::BTreeMap, sync::Arc};
+use tracing::info;
+
+use super::query::QueryState;
+use crate::{ErrorResponse, ResponseMetadata, SuccessResponse};
+
+type PricingError = (StatusCode, Json<ErrorResponse>);
+
+fn price_table(state: &QueryState) -> Result<&Arc<PriceTable>, PricingError> {
+    state.pricing.as_ref().ok_or_else(|| {
+        (
+            StatusCode::NOT_FOUND,
+            Json(ErrorResponse::new(
+                "pricing_disabled",
+                "Model pricing is not enabled",
+            )),
+        )
+    })
+}
+
+/// List model prices
+pub async fn list_pricing(
+    State(state): State<Arc<QueryState>>,
+) -> Result<Json<SuccessResponse<BTreeMap<String, ModelPrice>>>, PricingError> {
+    let prices = price_table(&state)?.prices();
+
+    let total_count = prices.len();
+    Ok(Json(SuccessResponse::new(prices).with_metadata(
+        ResponseMetadata {
+            total_count: Some(total_count),
+            page: None,
+            page_size: None,
+            next_cursor: None,
+        },
+    )))
+}
+
+/// Set the price for a model (or model prefix)
+pub async fn update_pricing(
+    State(state): State<Arc<QueryState>>,
+    Path(model): Path<String>,
+    Json(price): Json<ModelPrice>,
+) -> Result<Json<SuccessResponse<ModelPrice>>, PricingError> {
+    price_table(&state)?.set(&model, price).map_err(|e| {
+        (
+            StatusCode::BAD_REQUEST,
+            Json(ErrorResponse::new("invalid_price", e.to_string())),
+        )
+    })?;
+
+    info!(
+        model = %model,
+        input_per_1k = price.input_per_1k,
+        output_per_1k = price.output_per_1k,
+        "Model price updated"
+    );
+    Ok(Json(SuccessResponse::new(price)))
+}
+
+/// Remove the price for a model
+pub async fn delete_pricing(
+    State(state): State<Arc<QueryState>>,
+    Path(model): Path<String>,
+) -> Result<Json<SuccessResponse<ModelPrice>>, PricingError> {
+    let price = price_table(&state)?.remove(&model).ok_or_else(|| {
+        (
+            StatusCode::NOT_FOUND,
+            Json(ErrorResponse::new(
+                "not_found",
+                format!("No price configured for model: {}", model),
+            )),
+        )
+    })?;
+
+    info!(model = %model, "Model price removed");
+    Ok(Json(SuccessResponse::new(price)))
+}
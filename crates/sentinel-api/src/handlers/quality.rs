@@ -0,0 +1,132 @@
+//! Detector quality report and live detection statistics endpoints.
+
+use async_trait::async_trait;
+use axum::{
+    extract::{Query, State},
+    http::StatusCode,
+    Json,
+};
+use llm_sentinel_detection::{
+    engine::EngineStats,
+    load::LoadReport,
+    quality::{quality_report, DetectorQuality},
+    DetectorStats,
+};
+use llm_sentinel_storage::query::AnomalyQuery;
+use serde::{Deserialize, Serialize};
+use std::{collections::HashMap, sync::Arc};
+use tracing::{debug, error};
+
+use super::{
+    query::{parse_time_range, QueryState},
+    query_failed,
+};
+use crate::{ErrorResponse, ResponseMetadata, SuccessResponse};
+
+/// Live detection statistics the quality report draws on
+#[async_trait]
+pub trait DetectionStatsSource: Send + Sync {
+    /// Statistics of the running detectors, by detector name
+    async fn detector_stats(&self) -> Vec<(String, DetectorStats)>;
+
+    /// Alerts suppressed in open deduplication windows, by detector name
+    fn deduplicated_by_detector(&self) -> HashMap<String, u64>;
+
+    /// Statistics of the detection engine, with its load since startup and
+    /// over the last five minutes
+    async fn engine_stats(&self) -> EngineStats;
+}
+
+/// Live detection statistics
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct DetectionStatsReport {
+    /// Events run through detection since startup
+    pub events_processed: u64,
+    /// Events with an anomaly since startup
+    pub anomalies_detected: u64,
+    /// Share of events with an anomaly
+    pub detection_rate: f64,
+    /// Load since startup
+    pub lifetime: LoadReport,
+    /// Load over the last five minutes
+    pub recent: LoadReport,
+}
+
+impl From<EngineStats> for DetectionStatsReport {
+    fn from(stats: EngineStats) -> Self {
+        Self {
+            events_processed: stats.events_processed,
+            anomalies_detected: stats.anomalies_detected,
+            detection_rate: stats.detection_rate,
+            lifetime: stats.load,
+            recent: stats.recent,
+        }
+    }
+}
+
+/// Live detection statistics endpoint: events skipped by load shedding or
+/// memory pressure, events over their deadline, detector error rates and
+/// detection queue waits
+pub async fn detection_stats(
+    State(state): State<Arc<QueryState>>,
+) -> Result<Json<SuccessResponse<DetectionStatsReport>>, (StatusCode, Json<ErrorResponse>)> {
+    let source = state.detection_stats.as_ref().ok_or_else(|| {
+        (
+            StatusCode::NOT_FOUND,
+            Json(ErrorResponse::new(
+                "detection_stats_unavailable",
+                "Live detection statistics are not available",
+            )),
+        )
+    })?;
+
+    let stats = source.engine_stats().await;
+    Ok(Json(SuccessResponse::new(stats.into())))
+}
+
+/// Query parameters for the detector quality report
+#[derive(Debug, Default, Deserialize)]
+pub struct QualityQueryParams {
+    /// Start time (ISO 8601)
+    pub start: Option<String>,
+    /// End time (ISO 8601)
+    pub end: Option<String>,
+    /// Time range in hours
+    pub hours: Option<i64>,
+}
+
+/// Per-detector quality report over stored anomalies
+pub async fn detection_quality(
+    State(state): State<Arc<QueryState>>,
+    Query(params): Query<QualityQueryParams>,
+) -> Result<Json<SuccessResponse<Vec<DetectorQuality>>>, (StatusCode, Json<ErrorResponse>)> {
+    debug!("Detector quality query: {:?}", params);
+
+    let time_range = parse_time_range(params.start, params.end, params.hours)?;
+    // Expired anomalies count as unlabeled rather than disappearing
+    let mut query = AnomalyQuery::new(time_range).with_expired();
+    query.limit = None;
+    let anomalies = state.storage.query_anomalies(query).await.map_err(|e| {
+        error!("Failed to query anomalies for detector quality: {}", e);
+        query_failed(&e)
+    })?;
+
+    let (detector_stats, deduplicated) = match &state.detection_stats {
+        Some(source) => (
+            source.detector_stats().await,
+            source.deduplicated_by_detector(),
+        ),
+        None => (Vec::new(), HashMap::new()),
+    };
+    let report = quality_report(&anomalies, &detector_stats, &deduplicated);
+
+    let total_count = report.len();
+    Ok(Json(SuccessResponse::new(report).with_metadata(
+        ResponseMetadata {
+            total_count: Some(total_count),
+            page: None,
+            page_size: None,
+            next_cursor: None,
+        },
+    )))
+}
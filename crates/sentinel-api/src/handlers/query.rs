@@ -1,39 +1,256 @@
 //! Query endpoints for telemetry and anomalies.
 
 use axum::{extract::{Query, State}, http::StatusCode, Json};
-use serde::Deserialize;
+use serde::{Deserialize, Serialize};
 use llm_sentinel_core::{
-    events::{AnomalyEvent, TelemetryEvent},
-    types::{AnomalyType, ModelId, ServiceId, Severity},
+    bus::EventBus,
+    labels::{self, Labels},
+    memory::MemoryWatchdog,
+    pricing::PriceTable,
+    types::{AnomalyType, Environment, ModelId, ServiceId, Severity},
 };
+use llm_sentinel_detection::{availability::AvailabilityTracker, slo::SloTracker};
 use llm_sentinel_storage::{
-    query::{AnomalyQuery, TelemetryQuery, TimeRange},
+    budget::BudgetForecaster,
+    delivery::DeliverySlaPolicy,
+    incident::IncidentTracker,
+    query::{
+        AnomalyQuery, AnomalySort, Cursor, HeatmapBucket, HeatmapGroupBy, HeatmapQuery,
+        TelemetryQuery, TelemetrySort, TimeRange,
+    },
+    search::TextSearch,
+    similarity::VectorIndex,
     Storage,
 };
 use std::sync::Arc;
 use tracing::{debug, error};
 
+use super::{
+    credentials::CredentialControl, dedup::DedupStatsSource, delivery::AlertRedelivery,
+    federation::FederationReceiver, quality::DetectionStatsSource, query_failed,
+    redetect::Redetector, selfcheck::SelfChecker, simulation::AnomalySimulator,
+    snapshot::SnapshotControl, tuning::DetectionConfigControl,
+};
 use crate::{ErrorResponse, ResponseMetadata, SuccessResponse};
 
 /// Application state for queries
 #[derive(Clone)]
 pub struct QueryState {
     pub storage: Arc<dyn Storage>,
+    pub slo_tracker: Option<Arc<SloTracker>>,
+    pub availability_tracker: Option<Arc<AvailabilityTracker>>,
+    pub pricing: Option<Arc<PriceTable>>,
+    pub delivery_sla: Vec<DeliverySlaPolicy>,
+    pub dedup_stats: Option<DedupStatsSource>,
+    pub search: Option<Arc<dyn TextSearch>>,
+    pub similarity: Option<Arc<dyn VectorIndex>>,
+    pub bus: Option<EventBus>,
+    pub snapshots: Option<Arc<dyn SnapshotControl>>,
+    pub credentials: Option<Arc<dyn CredentialControl>>,
+    pub federation: Option<FederationReceiver>,
+    pub simulator: Option<Arc<dyn AnomalySimulator>>,
+    pub redetector: Option<Arc<dyn Redetector>>,
+    pub redelivery: Option<Arc<dyn AlertRedelivery>>,
+    pub self_checker: Option<Arc<dyn SelfChecker>>,
+    pub detection_stats: Option<Arc<dyn DetectionStatsSource>>,
+    pub budgets: Option<Arc<BudgetForecaster>>,
+    pub memory: Option<Arc<MemoryWatchdog>>,
+    pub tuning: Option<Arc<dyn DetectionConfigControl>>,
+    pub incidents: Option<Arc<IncidentTracker>>,
 }
 
 impl QueryState {
     pub fn new(storage: Arc<dyn Storage>) -> Self {
-        Self { storage }
+        Self {
+            storage,
+            slo_tracker: None,
+            availability_tracker: None,
+            pricing: None,
+            delivery_sla: DeliverySlaPolicy::defaults(),
+            dedup_stats: None,
+            search: None,
+            similarity: None,
+            bus: None,
+            snapshots: None,
+            credentials: None,
+            federation: None,
+            simulator: None,
+            redetector: None,
+            redelivery: None,
+            self_checker: None,
+            detection_stats: None,
+            budgets: None,
+            memory: None,
+            tuning: None,
+            incidents: None,
+        }
+    }
+
+    /// Expose SLO status from the given tracker
+    pub fn with_slo_tracker(mut self, tracker: Arc<SloTracker>) -> Self {
+        self.slo_tracker = Some(tracker);
+        self
+    }
+
+    /// Expose availability series from the given tracker
+    pub fn with_availability_tracker(mut self, tracker: Arc<AvailabilityTracker>) -> Self {
+        self.availability_tracker = Some(tracker);
+        self
+    }
+
+    /// Expose and update model prices in the given table
+    pub fn with_pricing(mut self, pricing: Arc<PriceTable>) -> Self {
+        self.pricing = Some(pricing);
+        self
+    }
+
+    /// Report alert delivery against the given objectives
+    pub fn with_delivery_sla(mut self, policies: Vec<DeliverySlaPolicy>) -> Self {
+        self.delivery_sla = policies;
+        self
+    }
+
+    /// Expose alert deduplication statistics from the given source
+    pub fn with_dedup_stats(mut self, source: DedupStatsSource) -> Self {
+        self.dedup_stats = Some(source);
+        self
+    }
+
+    /// Serve full-text search from the given index
+    pub fn with_search(mut self, search: Arc<dyn TextSearch>) -> Self {
+        self.search = Some(search);
+        self
+    }
+
+    /// Serve embedding similarity search from the given index
+    pub fn with_similarity(mut self, similarity: Arc<dyn VectorIndex>) -> Self {
+        self.similarity = Some(similarity);
+        self
+    }
+
+    /// Publish anomaly state changes to, and stream anomalies from, the
+    /// given bus
+    pub fn with_event_bus(mut self, bus: EventBus) -> Self {
+        self.bus = Some(bus);
+        self
+    }
+
+    /// Take and restore runtime state snapshots through the given control
+    pub fn with_snapshots(mut self, snapshots: Arc<dyn SnapshotControl>) -> Self {
+        self.snapshots = Some(snapshots);
+        self
+    }
+
+    /// Rotate credentials through the given control
+    pub fn with_credentials(mut self, credentials: Arc<dyn CredentialControl>) -> Self {
+        self.credentials = Some(credentials);
+        self
+    }
+
+    /// Accept anomalies forwarded by edge instances through the given
+    /// receiver
+    pub fn with_federation(mut self, federation: FederationReceiver) -> Self {
+        self.federation = Some(federation);
+        self
+    }
+
+    /// Send test anomalies through the alert pipeline with the given
+    /// simulator
+    pub fn with_simulator(mut self, simulator: Arc<dyn AnomalySimulator>) -> Self {
+        self.simulator = Some(simulator);
+        self
+    }
+
+    /// Run re-detection jobs with the given redetector
+    pub fn with_redetector(mut self, redetector: Arc<dyn Redetector>) -> Self {
+        self.redetector = Some(redetector);
+        self
+    }
+
+    /// Send stored alerts again through the given redelivery
+    pub fn with_redelivery(mut self, redelivery: Arc<dyn AlertRedelivery>) -> Self {
+        self.redelivery = Some(redelivery);
+        self
+    }
+
+    /// Check configured dependencies with the given checker
+    pub fn with_self_checker(mut self, checker: Arc<dyn SelfChecker>) -> Self {
+        self.self_checker = Some(checker);
+        self
+    }
+
+    /// Include live detector and deduplication statistics from the given
+    /// source in the detector quality report
+    pub fn with_detection_stats(mut self, source: Arc<dyn DetectionStatsSource>) -> Self {
+        self.detection_stats = Some(source);
+        self
+    }
+
+    /// Project spend against budgets with the given forecaster
+    pub fn with_budgets(mut self, budgets: Arc<BudgetForecaster>) -> Self {
+        self.budgets = Some(budgets);
+        self
+    }
+
+    /// Track the response cache against the given memory budget
+    pub fn with_memory_watchdog(mut self, watchdog: Arc<MemoryWatchdog>) -> Self {
+        self.memory = Some(watchdog);
+        self
+    }
+
+    /// Export and import detection configuration documents through the
+    /// given control
+    pub fn with_tuning(mut self, tuning: Arc<dyn DetectionConfigControl>) -> Self {
+        self.tuning = Some(tuning);
+        self
+    }
+
+    /// Apply incident state changes and links through the given tracker, so
+    /// they are serialized with its anomaly attaches
+    pub fn with_incident_tracker(mut self, tracker: Arc<IncidentTracker>) -> Self {
+        self.incidents = Some(tracker);
+        self
+    }
+}
+
+impl std::fmt::Debug for QueryState {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("QueryState")
+            .field("slo_tracker", &self.slo_tracker.is_some())
+            .field("availability_tracker", &self.availability_tracker.is_some())
+            .field("pricing", &self.pricing.is_some())
+            .field("delivery_sla", &self.delivery_sla.len())
+            .field("dedup_stats", &self.dedup_stats.is_some())
+            .field("search", &self.search.is_some())
+            .field("similarity", &self.similarity.is_some())
+            .field("bus", &self.bus.is_some())
+            .field("snapshots", &self.snapshots.is_some())
+            .field("credentials", &self.credentials.is_some())
+            .field("federation", &self.federation)
+            .field("simulator", &self.simulator.is_some())
+            .field("redetector", &self.redetector.is_some())
+            .field("redelivery", &self.redelivery.is_some())
+            .field("self_checker", &self.self_checker.is_some())
+            .field("detection_stats", &self.detection_stats.is_some())
+            .field("budgets", &self.budgets.is_some())
+            .field("memory", &self.memory.is_some())
+            .field("tuning", &self.tuning.is_some())
+            .field("incidents", &self.incidents.is_some())
+            .finish_non_exhaustive()
     }
 }
 
 /// Query parameters for telemetry
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Default, Deserialize)]
 pub struct TelemetryQueryParams {
     /// Service ID filter
     pub service: Option<String>,
     /// Model ID filter
     pub model: Option<String>,
+    /// Environment filter
+    pub environment: Option<String>,
+    /// Label filter, e.g. `team=search,env=prod`
+    pub labels: Option<String>,
     /// Start time (ISO 8601)
     pub start: Option<String>,
     /// End time (ISO 8601)
@@ -46,10 +263,16 @@ pub struct TelemetryQueryParams {
     pub offset: Option<usize>,
     /// Sort ascending
     pub ascending: Option<bool>,
+    /// Sort field: `timestamp` (default), `latency`, `cost` or `tokens`
+    pub sort: Option<String>,
+    /// Continuation token from a previous page's `next_cursor`
+    pub cursor: Option<String>,
+    /// Fields to return, e.g. `latency_ms,cost_usd` (default: all)
+    pub fields: Option<String>,
 }
 
 /// Query parameters for anomalies
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Default, Deserialize)]
 pub struct AnomalyQueryParams {
     /// Service ID filter
     pub service: Option<String>,
@@ -61,6 +284,10 @@ pub struct AnomalyQueryParams {
     pub anomaly_type: Option<String>,
     /// Minimum confidence
     pub min_confidence: Option<f64>,
+    /// Environment filter
+    pub environment: Option<String>,
+    /// Label filter, e.g. `team=search,env=prod`
+    pub labels: Option<String>,
     /// Start time (ISO 8601)
     pub start: Option<String>,
     /// End time (ISO 8601)
@@ -71,47 +298,64 @@ pub struct AnomalyQueryParams {
     pub limit: Option<usize>,
     /// Offset for pagination
     pub offset: Option<usize>,
+    /// Sort ascending
+    pub ascending: Option<bool>,
+    /// Sort field: `timestamp` (default), `confidence` or `severity`
+    pub sort: Option<String>,
+    /// Continuation token from a previous page's `next_cursor`
+    pub cursor: Option<String>,
+    /// Fields to return, e.g. `severity,confidence` (default: all)
+    pub fields: Option<String>,
+    /// Include expired anomalies
+    pub include_expired: Option<bool>,
 }
 
+/// Query parameters for the anomaly heatmap
+#[derive(Debug, Deserialize)]
+pub struct HeatmapQueryParams {
+    /// Bucket width, e.g. `15m`, `1h`, `1d` (default: `1h`)
+    pub interval: Option<String>,
+    /// Grouping dimension: `service` or `severity` (default: `service`)
+    pub group_by: Option<String>,
+    /// Service ID filter
+    pub service: Option<String>,
+    /// Severity filter
+    pub severity: Option<String>,
+    /// Start time (ISO 8601)
+    pub start: Option<String>,
+    /// End time (ISO 8601)
+    pub end: Option<String>,
+    /// Time range in hours
+    pub hours: Option<i64>,
+}
+
+/// Anomaly heatmap response
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct HeatmapResponse {
+    /// Start of the queried range
+    pub start: chrono::DateTime<chrono::Utc>,
+    /// End of the queried range
+    pub end: chrono::DateTime<chrono::Utc>,
+    /// Bucket width in seconds
+    pub interval_secs: u64,
+    /// Grouping dimension
+    pub group_by: HeatmapGroupBy,
+    /// Non-empty buckets ordered by start time
+    pub buckets: Vec<HeatmapBucket>,
+}
+
+/// Maximum number of time buckets a heatmap query may span
+const MAX_HEATMAP_BUCKETS: u64 = 10_000;
+
 /// Telemetry query endpoint
 pub async fn query_telemetry(
     State(state): State<Arc<QueryState>>,
     Query(params): Query<TelemetryQueryParams>,
-) -> Result<Json<SuccessResponse<Vec<TelemetryEvent>>>, (StatusCode, Json<ErrorResponse>)> {
+) -> Result<Json<SuccessResponse<Vec<serde_json::Value>>>, (StatusCode, Json<ErrorResponse>)> {
     debug!("Telemetry query: {:?}", params);
 
     // Build time range
-    let time_range = match (params.start, params.end, params.hours) {
-        (Some(start), Some(end), _) => {
-            let start_dt = chrono::DateTime::parse_from_rfc3339(&start)
-                .map_err(|e| {
-                    (
-                        StatusCode::BAD_REQUEST,
-                        Json(ErrorResponse::new(
-                            "invalid_time",
-                            format!("Invalid start time: {}", e),
-                        )),
-                    )
-                })?
-                .with_timezone(&chrono::Utc);
-
-            let end_dt = chrono::DateTime::parse_from_rfc3339(&end)
-                .map_err(|e| {
-                    (
-                        StatusCode::BAD_REQUEST,
-                        Json(ErrorResponse::new(
-                            "invalid_time",
-                            format!("Invalid end time: {}", e),
-                        )),
-                    )
-                })?
-                .with_timezone(&chrono::Utc);
-
-            TimeRange::new(start_dt, end_dt)
-        }
-        (_, _, Some(hours)) => TimeRange::last_hours(hours),
-        _ => TimeRange::last_hours(24), // Default: last 24 hours
-    };
+    let time_range = parse_time_range(params.start, params.end, params.hours)?;
 
     // Build query
     let mut query = TelemetryQuery::new(time_range);
@@ -124,6 +368,14 @@ pub async fn query_telemetry(
         query = query.with_model(ModelId::new(model));
     }
 
+    if let Some(environment) = params.environment {
+        query = query.with_environment(Environment::new(environment));
+    }
+
+    if let Some(selector) = params.labels {
+        query = query.with_labels(parse_label_selector(&selector)?);
+    }
+
     if let Some(limit) = params.limit {
         query = query.with_limit(limit);
     }
@@ -138,6 +390,17 @@ pub async fn query_telemetry(
         query = query.descending();
     }
 
+    if let Some(sort) = params.sort.as_deref() {
+        let sort = TelemetrySort::parse(sort).ok_or_else(|| invalid_sort(sort))?;
+        query = query.sort_by(sort);
+    }
+
+    if let Some(token) = params.cursor.as_deref() {
+        query = query.with_cursor(parse_cursor(token)?);
+    }
+
+    let (sort, limit) = (query.sort_by, query.limit);
+
     // Execute query
     let events = state
         .storage
@@ -145,18 +408,18 @@ pub async fn query_telemetry(
         .await
         .map_err(|e| {
             error!("Telemetry query failed: {}", e);
-            (
-                StatusCode::INTERNAL_SERVER_ERROR,
-                Json(ErrorResponse::new("query_failed", e.to_string())),
-            )
+            query_failed(&e)
         })?;
 
     debug!("Retrieved {} telemetry events", events.len());
 
-    let response = SuccessResponse::new(events.clone()).with_metadata(ResponseMetadata {
+    let next_cursor = next_cursor(&events, limit, |e| sort.cursor(e));
+    let data = select_fields(&events, params.fields.as_deref(), "event_id")?;
+    let response = SuccessResponse::new(data).with_metadata(ResponseMetadata {
         total_count: Some(events.len()),
         page: params.offset.map(|o| o / params.limit.unwrap_or(100)),
         page_size: params.limit,
+        next_cursor,
     });
 
     Ok(Json(response))
@@ -166,41 +429,11 @@ pub async fn query_telemetry(
 pub async fn query_anomalies(
     State(state): State<Arc<QueryState>>,
     Query(params): Query<AnomalyQueryParams>,
-) -> Result<Json<SuccessResponse<Vec<AnomalyEvent>>>, (StatusCode, Json<ErrorResponse>)> {
+) -> Result<Json<SuccessResponse<Vec<serde_json::Value>>>, (StatusCode, Json<ErrorResponse>)> {
     debug!("Anomaly query: {:?}", params);
 
     // Build time range
-    let time_range = match (params.start, params.end, params.hours) {
-        (Some(start), Some(end), _) => {
-            let start_dt = chrono::DateTime::parse_from_rfc3339(&start)
-                .map_err(|e| {
-                    (
-                        StatusCode::BAD_REQUEST,
-                        Json(ErrorResponse::new(
-                            "invalid_time",
-                            format!("Invalid start time: {}", e),
-                        )),
-                    )
-                })?
-                .with_timezone(&chrono::Utc);
-
-            let end_dt = chrono::DateTime::parse_from_rfc3339(&end)
-                .map_err(|e| {
-                    (
-                        StatusCode::BAD_REQUEST,
-                        Json(ErrorResponse::new(
-                            "invalid_time",
-                            format!("Invalid end time: {}", e),
-                        )),
-                    )
-                })?
-                .with_timezone(&chrono::Utc);
-
-            TimeRange::new(start_dt, end_dt)
-        }
-        (_, _, Some(hours)) => TimeRange::last_hours(hours),
-        _ => TimeRange::last_hours(24), // Default: last 24 hours
-    };
+    let time_range = parse_time_range(params.start, params.end, params.hours)?;
 
     // Build query
     let mut query = AnomalyQuery::new(time_range);
@@ -237,10 +470,41 @@ pub async fn query_anomalies(
         query = query.with_min_confidence(confidence);
     }
 
+    if let Some(environment) = params.environment {
+        query = query.with_environment(Environment::new(environment));
+    }
+
+    if let Some(selector) = params.labels {
+        query = query.with_labels(parse_label_selector(&selector)?);
+    }
+
     if let Some(limit) = params.limit {
         query = query.with_limit(limit);
     }
 
+    if let Some(offset) = params.offset {
+        query = query.with_offset(offset);
+    }
+
+    if params.include_expired.unwrap_or(false) {
+        query = query.with_expired();
+    }
+
+    if params.ascending.unwrap_or(false) {
+        query = query.ascending();
+    }
+
+    if let Some(sort) = params.sort.as_deref() {
+        let sort = AnomalySort::parse(sort).ok_or_else(|| invalid_sort(sort))?;
+        query = query.sort_by(sort);
+    }
+
+    if let Some(token) = params.cursor.as_deref() {
+        query = query.with_cursor(parse_cursor(token)?);
+    }
+
+    let (sort, limit) = (query.sort_by, query.limit);
+
     // Execute query
     let anomalies = state
         .storage
@@ -248,23 +512,259 @@ pub async fn query_anomalies(
         .await
         .map_err(|e| {
             error!("Anomaly query failed: {}", e);
-            (
-                StatusCode::INTERNAL_SERVER_ERROR,
-                Json(ErrorResponse::new("query_failed", e.to_string())),
-            )
+            query_failed(&e)
         })?;
 
     debug!("Retrieved {} anomalies", anomalies.len());
 
-    let response = SuccessResponse::new(anomalies.clone()).with_metadata(ResponseMetadata {
+    let next_cursor = next_cursor(&anomalies, limit, |a| sort.cursor(a));
+    let data = select_fields(&anomalies, params.fields.as_deref(), "alert_id")?;
+    let response = SuccessResponse::new(data).with_metadata(ResponseMetadata {
         total_count: Some(anomalies.len()),
         page: params.offset.map(|o| o / params.limit.unwrap_or(100)),
         page_size: params.limit,
+        next_cursor,
+    });
+
+    Ok(Json(response))
+}
+
+/// Anomaly heatmap endpoint
+pub async fn anomaly_heatmap(
+    State(state): State<Arc<QueryState>>,
+    Query(params): Query<HeatmapQueryParams>,
+) -> Result<Json<SuccessResponse<HeatmapResponse>>, (StatusCode, Json<ErrorResponse>)> {
+    debug!("Anomaly heatmap query: {:?}", params);
+
+    let time_range = parse_time_range(params.start, params.end, params.hours)?;
+
+    let interval_secs = match params.interval {
+        Some(interval) => parse_interval(&interval).map_err(|e| {
+            (
+                StatusCode::BAD_REQUEST,
+                Json(ErrorResponse::new("invalid_interval", e)),
+            )
+        })?,
+        None => 3600,
+    };
+
+    let group_by = match params.group_by.as_deref().map(str::to_lowercase).as_deref() {
+        None | Some("service") => HeatmapGroupBy::Service,
+        Some("severity") => HeatmapGroupBy::Severity,
+        Some(other) => {
+            return Err((
+                StatusCode::BAD_REQUEST,
+                Json(ErrorResponse::new(
+                    "invalid_group_by",
+                    format!("Invalid group_by: {} (expected service or severity)", other),
+                )),
+            ))
+        }
+    };
+
+    let mut query = HeatmapQuery::new(time_range.clone(), interval_secs, group_by);
+
+    if query.bucket_count() > MAX_HEATMAP_BUCKETS {
+        return Err((
+            StatusCode::BAD_REQUEST,
+            Json(ErrorResponse::new(
+                "too_many_buckets",
+                format!(
+                    "Query spans {} buckets (max {}); use a larger interval",
+                    query.bucket_count(),
+                    MAX_HEATMAP_BUCKETS
+                ),
+            )),
+        ));
+    }
+
+    if let Some(service) = params.service {
+        query = query.with_service(ServiceId::new(service));
+    }
+
+    if let Some(severity_str) = params.severity {
+        let severity = parse_severity(&severity_str).map_err(|e| {
+            (
+                StatusCode::BAD_REQUEST,
+                Json(ErrorResponse::new("invalid_severity", e)),
+            )
+        })?;
+        query = query.with_severity(severity);
+    }
+
+    let buckets = state
+        .storage
+        .anomaly_heatmap(query)
+        .await
+        .map_err(|e| {
+            error!("Anomaly heatmap query failed: {}", e);
+            query_failed(&e)
+        })?;
+
+    debug!("Retrieved {} heatmap buckets", buckets.len());
+
+    let total_count = buckets.len();
+    let response = SuccessResponse::new(HeatmapResponse {
+        start: time_range.start,
+        end: time_range.end,
+        interval_secs,
+        group_by,
+        buckets,
+    })
+    .with_metadata(ResponseMetadata {
+        total_count: Some(total_count),
+        page: None,
+        page_size: None,
+        next_cursor: None,
     });
 
     Ok(Json(response))
 }
 
+/// Parse a bucket interval such as `300`, `30s`, `15m`, `1h` or `1d` into seconds
+pub(crate) fn parse_interval(s: &str) -> Result<u64, String> {
+    let s = s.trim();
+    let (digits, multiplier) = match s.char_indices().last() {
+        Some((i, 's')) => (&s[..i], 1),
+        Some((i, 'm')) => (&s[..i], 60),
+        Some((i, 'h')) => (&s[..i], 3600),
+        Some((i, 'd')) => (&s[..i], 86400),
+        Some((i, 'w')) => (&s[..i], 604800),
+        _ => (s, 1),
+    };
+
+    digits
+        .parse::<u64>()
+        .ok()
+        .filter(|value| *value > 0)
+        .and_then(|value| value.checked_mul(multiplier))
+        .ok_or_else(|| format!("Invalid interval: {}", s))
+}
+
+/// Build a time range from explicit bounds or a trailing window in hours
+pub(crate) fn parse_time_range(
+    start: Option<String>,
+    end: Option<String>,
+    hours: Option<i64>,
+) -> Result<TimeRange, (StatusCode, Json<ErrorResponse>)> {
+    let time_range = match (start, end, hours) {
+        (Some(start), Some(end), _) => {
+            let start_dt = chrono::DateTime::parse_from_rfc3339(&start)
+                .map_err(|e| {
+                    (
+                        StatusCode::BAD_REQUEST,
+                        Json(ErrorResponse::new(
+                            "invalid_time",
+                            format!("Invalid start time: {}", e),
+                        )),
+                    )
+                })?
+                .with_timezone(&chrono::Utc);
+
+            let end_dt = chrono::DateTime::parse_from_rfc3339(&end)
+                .map_err(|e| {
+                    (
+                        StatusCode::BAD_REQUEST,
+                        Json(ErrorResponse::new(
+                            "invalid_time",
+                            format!("Invalid end time: {}", e),
+                        )),
+                    )
+                })?
+                .with_timezone(&chrono::Utc);
+
+            TimeRange::new(start_dt, end_dt)
+        }
+        (_, _, Some(hours)) => TimeRange::last_hours(hours),
+        _ => TimeRange::last_hours(24), // Default: last 24 hours
+    };
+
+    Ok(time_range)
+}
+
+/// Parse a `key=value,...` label filter
+pub(crate) fn parse_label_selector(
+    selector: &str,
+) -> Result<Labels, (StatusCode, Json<ErrorResponse>)> {
+    labels::parse_selector(selector).map_err(|e| {
+        (
+            StatusCode::BAD_REQUEST,
+            Json(ErrorResponse::new("invalid_labels", e.to_string())),
+        )
+    })
+}
+
+/// Error for an unknown sort field
+fn invalid_sort(sort: &str) -> (StatusCode, Json<ErrorResponse>) {
+    (
+        StatusCode::BAD_REQUEST,
+        Json(ErrorResponse::new(
+            "invalid_sort",
+            format!("Unknown sort field: {}", sort),
+        )),
+    )
+}
+
+/// Decode a continuation token
+fn parse_cursor(token: &str) -> Result<Cursor, (StatusCode, Json<ErrorResponse>)> {
+    Cursor::decode(token).map_err(|e| {
+        (
+            StatusCode::BAD_REQUEST,
+            Json(ErrorResponse::new("invalid_cursor", e.to_string())),
+        )
+    })
+}
+
+/// Continuation token for the page after `items`
+///
+/// Only a full page can be followed by more results.
+fn next_cursor<T>(
+    items: &[T],
+    limit: Option<usize>,
+    cursor: impl Fn(&T) -> Cursor,
+) -> Option<String> {
+    match (items.last(), limit) {
+        (Some(last), Some(limit)) if items.len() >= limit => Some(cursor(last).encode()),
+        _ => None,
+    }
+}
+
+/// Serialize items, keeping only the requested top-level fields
+///
+/// `id_field` is always kept so sparse results can still be told apart.
+/// Unknown field names are ignored.
+fn select_fields<T: Serialize>(
+    items: &[T],
+    fields: Option<&str>,
+    id_field: &str,
+) -> Result<Vec<serde_json::Value>, (StatusCode, Json<ErrorResponse>)> {
+    let fields: Option<Vec<&str>> = fields.map(|f| {
+        f.split(',')
+            .map(str::trim)
+            .filter(|f| !f.is_empty())
+            .chain([id_field])
+            .collect()
+    });
+
+    items
+        .iter()
+        .map(|item| {
+            let mut value = serde_json::to_value(item)?;
+            if let (Some(fields), serde_json::Value::Object(map)) = (&fields, &mut value) {
+                map.retain(|key, _| fields.contains(&key.as_str()));
+            }
+            Ok(value)
+        })
+        .collect::<serde_json::Result<_>>()
+        .map_err(|e| {
+            error!("Failed to serialize query results: {}", e);
+            (
+                StatusCode::INTERNAL_SERVER_ERROR,
+                Json(ErrorResponse::new("serialization_failed", e.to_string())),
+            )
+        })
+}
+
 /// Parse severity string
 fn parse_severity(s: &str) -> Result<Severity, String> {
     match s.to_lowercase().as_str() {
@@ -293,6 +793,7 @@ fn parse_anomaly_type(s: &str) -> Result<AnomalyType, String> {
         "security_threat" | "prompt_injection" | "data_exfiltration" | "jailbreak_attempt" => {
             Ok(AnomalyType::SecurityThreat)
         }
+        "context_overflow" => Ok(AnomalyType::ContextOverflow),
         _ => Ok(AnomalyType::Custom(s.to_string())),
     }
 }
@@ -333,4 +834,29 @@ mod tests {
             Ok(AnomalyType::Custom("invalid".to_string()))
         );
     }
+
+    #[test]
+    fn test_select_fields() {
+        let items = vec![serde_json::json!({"event_id": "a", "latency_ms": 1.0, "cost_usd": 0.1})];
+
+        let all = select_fields(&items, None, "event_id").unwrap();
+        assert_eq!(all, items);
+
+        let sparse = select_fields(&items, Some("latency_ms, unknown"), "event_id").unwrap();
+        assert_eq!(
+            sparse[0],
+            serde_json::json!({"event_id": "a", "latency_ms": 1.0})
+        );
+    }
+
+    #[test]
+    fn test_parse_interval() {
+        assert_eq!(parse_interval("300"), Ok(300));
+        assert_eq!(parse_interval("15m"), Ok(900));
+        assert_eq!(parse_interval("1h"), Ok(3600));
+        assert_eq!(parse_interval("1d"), Ok(86400));
+        assert!(parse_interval("0h").is_err());
+        assert!(parse_interval("abc").is_err());
+        assert!(parse_interval("").is_err());
+    }
 }
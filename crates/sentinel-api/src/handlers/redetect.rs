@@ -0,0 +1,83 @@
+//! Re-detection job endpoints.
+
+use async_trait::async_trait;
+use axum::{
+    extract::{Path, State},
+    http::StatusCode,
+    Json,
+};
+use llm_sentinel_core::{Error, Result};
+use llm_sentinel_detection::redetect::{RedetectJob, RedetectRequest};
+use std::sync::Arc;
+use tracing::{error, info};
+use uuid::Uuid;
+
+use super::query::QueryState;
+use crate::{ErrorResponse, SuccessResponse};
+
+type RedetectError = (StatusCode, Json<ErrorResponse>);
+
+/// Runs re-detection jobs over stored telemetry
+#[async_trait]
+pub trait Redetector: Send + Sync {
+    /// Start a job in the background, returning it as submitted
+    async fn submit(&self, request: RedetectRequest) -> Result<RedetectJob>;
+
+    /// Current state of a submitted job
+    async fn job(&self, job_id: Uuid) -> Result<RedetectJob>;
+}
+
+fn redetector(state: &QueryState) -> std::result::Result<&Arc<dyn Redetector>, RedetectError> {
+    state.redetector.as_ref().ok_or_else(|| {
+        (
+            StatusCode::NOT_FOUND,
+            Json(ErrorResponse::new(
+                "redetection_disabled",
+                "Re-detection jobs are not enabled",
+            )),
+        )
+    })
+}
+
+fn redetection_failed(e: &Error) -> RedetectError {
+    let (status, code) = match e {
+        Error::Validation(_) => (StatusCode::BAD_REQUEST, "invalid_request"),
+        Error::NotFound(_) => (StatusCode::NOT_FOUND, "not_found"),
+        _ => (StatusCode::INTERNAL_SERVER_ERROR, "redetection_failed"),
+    };
+    (status, Json(ErrorResponse::new(code, e.to_string())))
+}
+
+/// Replay a stored time range through a freshly configured detection engine
+pub async fn submit_redetection(
+    State(state): State<Arc<QueryState>>,
+    Json(request): Json<RedetectRequest>,
+) -> std::result::Result<(StatusCode, Json<SuccessResponse<RedetectJob>>), RedetectError> {
+    let redetector = redetector(&state)?;
+    request.validate().map_err(|e| redetection_failed(&e))?;
+
+    let job = redetector.submit(request).await.map_err(|e| {
+        error!("Failed to submit re-detection job: {}", e);
+        redetection_failed(&e)
+    })?;
+
+    info!(
+        job_id = %job.job_id,
+        start = %job.start,
+        end = %job.end,
+        "Re-detection job submitted through the API"
+    );
+    Ok((StatusCode::ACCEPTED, Json(SuccessResponse::new(job))))
+}
+
+/// Status of a re-detection job
+pub async fn redetection_job(
+    State(state): State<Arc<QueryState>>,
+    Path(job_id): Path<Uuid>,
+) -> std::result::Result<Json<SuccessResponse<RedetectJob>>, RedetectError> {
+    let job = redetector(&state)?
+        .job(job_id)
+        .await
+        .map_err(|e| redetection_failed(&e))?;
+    Ok(Json(SuccessResponse::new(job)))
+}
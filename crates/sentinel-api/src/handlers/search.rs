@@ -0,0 +1,122 @@
+//! Full-text search endpoint.
+
+use axum::{
+    extract::{Query, State},
+    http::StatusCode,
+    Json,
+};
+use llm_sentinel_core::{
+    types::{Environment, ModelId, ServiceId},
+    Error,
+};
+use llm_sentinel_storage::search::{SearchHit, SearchQuery};
+use serde::Deserialize;
+use std::sync::Arc;
+use tracing::{debug, error};
+
+use super::query::{parse_time_range, QueryState};
+use crate::{ErrorResponse, ResponseMetadata, SuccessResponse};
+
+/// Default number of search hits
+const DEFAULT_SEARCH_LIMIT: usize = 100;
+
+/// Maximum number of search hits
+const MAX_SEARCH_LIMIT: usize = 1000;
+
+/// Query parameters for search
+#[derive(Debug, Default, Deserialize)]
+pub struct SearchParams {
+    /// Keywords or `"quoted phrase"` to find in prompts and responses
+    pub q: Option<String>,
+    /// Service ID filter
+    pub service: Option<String>,
+    /// Model ID filter
+    pub model: Option<String>,
+    /// Environment filter
+    pub environment: Option<String>,
+    /// Start time (ISO 8601)
+    pub start: Option<String>,
+    /// End time (ISO 8601)
+    pub end: Option<String>,
+    /// Time range in hours
+    pub hours: Option<i64>,
+    /// Limit results (default 100, at most 1000)
+    pub limit: Option<usize>,
+}
+
+/// Search prompt and response text
+pub async fn search_telemetry(
+    State(state): State<Arc<QueryState>>,
+    Query(params): Query<SearchParams>,
+) -> Result<Json<SuccessResponse<Vec<SearchHit>>>, (StatusCode, Json<ErrorResponse>)> {
+    debug!("Search query: {:?}", params);
+
+    let search = state.search.clone().ok_or_else(|| {
+        (
+            StatusCode::NOT_FOUND,
+            Json(ErrorResponse::new(
+                "search_disabled",
+                "Full-text search is not enabled",
+            )),
+        )
+    })?;
+
+    let text = params.q.filter(|q| !q.trim().is_empty()).ok_or_else(|| {
+        (
+            StatusCode::BAD_REQUEST,
+            Json(ErrorResponse::new(
+                "invalid_query",
+                "Missing query parameter q",
+            )),
+        )
+    })?;
+
+    let time_range = parse_time_range(params.start, params.end, params.hours)?;
+    let limit = params
+        .limit
+        .unwrap_or(DEFAULT_SEARCH_LIMIT)
+        .clamp(1, MAX_SEARCH_LIMIT);
+    let mut query = SearchQuery::new(text, time_range).with_limit(limit);
+
+    if let Some(service) = params.service {
+        query = query.with_service(ServiceId::new(service));
+    }
+
+    if let Some(model) = params.model {
+        query = query.with_model(ModelId::new(model));
+    }
+
+    if let Some(environment) = params.environment {
+        query = query.with_environment(Environment::new(environment));
+    }
+
+    let hits = tokio::task::spawn_blocking(move || search.search(&query))
+        .await
+        .map_err(|e| Error::internal(e.to_string()))
+        .and_then(|result| result)
+        .map_err(|e| match e {
+            Error::Validation(_) => (
+                StatusCode::BAD_REQUEST,
+                Json(ErrorResponse::new("invalid_query", e.to_string())),
+            ),
+            e => {
+                error!("Search failed: {}", e);
+                (
+                    StatusCode::INTERNAL_SERVER_ERROR,
+                    Json(ErrorResponse::new("search_failed", e.to_string())),
+                )
+            }
+        })?;
+
+    debug!("Found {} matching events", hits.len());
+
+    let total_count = hits.len();
+    Ok(Json(SuccessResponse::new(hits).with_metadata(
+        ResponseMetadata {
+            total_count: Some(total_count),
+            page: None,
+            page_size: Some(limit),
+            next_cursor: None,
+        },
+    )))
+}
@@ -0,0 +1,54 @@
+//! Dependency self-check endpoint.
+
+use async_trait::async_trait;
+use axum::{extract::State, http::StatusCode, Json};
+use llm_sentinel_core::{selfcheck::SelfCheckReport, Result};
+use std::sync::Arc;
+use tracing::{error, warn};
+
+use super::query::QueryState;
+use crate::{ErrorResponse, SuccessResponse};
+
+type SelfCheckError = (StatusCode, Json<ErrorResponse>);
+
+/// Checks the dependencies the running instance is configured with
+#[async_trait]
+pub trait SelfChecker: Send + Sync {
+    /// Run connectivity, permission and schema checks against every
+    /// configured dependency
+    async fn self_check(&self) -> Result<SelfCheckReport>;
+}
+
+/// Check every configured dependency and report each check as passed,
+/// failed or skipped
+pub async fn self_check(
+    State(state): State<Arc<QueryState>>,
+) -> std::result::Result<Json<SuccessResponse<SelfCheckReport>>, SelfCheckError> {
+    let checker = state.self_checker.as_ref().ok_or_else(|| {
+        (
+            StatusCode::NOT_FOUND,
+            Json(ErrorResponse::new(
+                "selfcheck_disabled",
+                "Self-checks are not enabled",
+            )),
+        )
+    })?;
+
+    let report = checker.self_check().await.map_err(|e| {
+        error!("Failed to run self-checks: {}", e);
+        (
+            StatusCode::INTERNAL_SERVER_ERROR,
+            Json(ErrorResponse::new("selfcheck_failed", e.to_string())),
+        )
+    })?;
+
+    for failure in report.failures() {
+        warn!(
+            dependency = %failure.dependency,
+            check = %failure.check,
+            "Self-check failed: {}",
+            failure.detail
+        );
+    }
+    Ok(Json(SuccessResponse::new(report)))
+}
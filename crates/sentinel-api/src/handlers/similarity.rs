@@ -0,0 +1,145 @@
+//! Embedding similarity endpoint.
+
+use axum::{extract::State, http::StatusCode, Json};
+use llm_sentinel_core::{
+    types::{Environment, ModelId, ServiceId},
+    Error,
+};
+use llm_sentinel_storage::{
+    query::TimeRange,
+    similarity::{EmbeddingSource, SimilarHit, SimilarityQuery},
+};
+use serde::Deserialize;
+use std::sync::Arc;
+use tracing::debug;
+use uuid::Uuid;
+
+use super::query::QueryState;
+use crate::{ErrorResponse, ResponseMetadata, SuccessResponse};
+
+/// Default number of similar events
+const DEFAULT_SIMILAR_LIMIT: usize = 20;
+
+/// Maximum number of similar events
+const MAX_SIMILAR_LIMIT: usize = 500;
+
+/// Similarity search request
+///
+/// Exactly one of `event_id` and `embedding` must be given.
+#[derive(Debug, Default, Deserialize)]
+pub struct SimilarRequest {
+    /// Find events similar to this indexed event
+    pub event_id: Option<Uuid>,
+    /// Find events similar to this embedding
+    pub embedding: Option<Vec<f32>>,
+    /// Compare `prompt` (default) or `response` embeddings
+    #[serde(default)]
+    pub source: EmbeddingSource,
+    /// Service ID filter
+    pub service: Option<String>,
+    /// Model ID filter
+    pub model: Option<String>,
+    /// Environment filter
+    pub environment: Option<String>,
+    /// Only consider events from the last N hours
+    pub hours: Option<i64>,
+    /// Minimum cosine similarity
+    pub min_score: Option<f32>,
+    /// Limit results (default 20, at most 500)
+    pub limit: Option<usize>,
+}
+
+fn bad_request(code: &str, message: impl Into<String>) -> (StatusCode, Json<ErrorResponse>) {
+    (
+        StatusCode::BAD_REQUEST,
+        Json(ErrorResponse::new(code, message)),
+    )
+}
+
+/// Find events with similar prompt or response embeddings
+pub async fn similar_events(
+    State(state): State<Arc<QueryState>>,
+    Json(request): Json<SimilarRequest>,
+) -> Result<Json<SuccessResponse<Vec<SimilarHit>>>, (StatusCode, Json<ErrorResponse>)> {
+    let index = state.similarity.as_ref().ok_or_else(|| {
+        (
+            StatusCode::NOT_FOUND,
+            Json(ErrorResponse::new(
+                "similarity_disabled",
+                "Embedding similarity search is not enabled",
+            )),
+        )
+    })?;
+
+    let embedding = match (request.event_id, request.embedding) {
+        (Some(event_id), None) => index.embedding(event_id, request.source).ok_or_else(|| {
+            (
+                StatusCode::NOT_FOUND,
+                Json(ErrorResponse::new(
+                    "event_not_found",
+                    format!(
+                        "Event {} has no indexed {:?} embedding",
+                        event_id, request.source
+                    ),
+                )),
+            )
+        })?,
+        (None, Some(embedding)) => embedding,
+        _ => {
+            return Err(bad_request(
+                "invalid_request",
+                "Exactly one of event_id and embedding is required",
+            ))
+        }
+    };
+
+    let limit = request
+        .limit
+        .unwrap_or(DEFAULT_SIMILAR_LIMIT)
+        .clamp(1, MAX_SIMILAR_LIMIT);
+    let mut query = SimilarityQuery::new(embedding, request.source).with_limit(limit);
+
+    if let Some(event_id) = request.event_id {
+        query = query.excluding(event_id);
+    }
+
+    if let Some(hours) = request.hours {
+        query = query.with_time_range(TimeRange::last_hours(hours));
+    }
+
+    if let Some(service) = request.service {
+        query = query.with_service(ServiceId::new(service));
+    }
+
+    if let Some(model) = request.model {
+        query = query.with_model(ModelId::new(model));
+    }
+
+    if let Some(environment) = request.environment {
+        query = query.with_environment(Environment::new(environment));
+    }
+
+    if let Some(min_score) = request.min_score {
+        query = query.with_min_score(min_score);
+    }
+
+    let hits = index.search(&query).map_err(|e| match e {
+        Error::Validation(message) => bad_request("invalid_embedding", message),
+        e => (
+            StatusCode::INTERNAL_SERVER_ERROR,
+            Json(ErrorResponse::new("similarity_failed", e.to_string())),
+        ),
+    })?;
+
+    debug!("Found {} similar events", hits.len());
+
+    let total_count = hits.len();
+    Ok(Json(SuccessResponse::new(hits).with_metadata(
+        ResponseMetadata {
+            total_count: Some(total_count),
+            page: None,
+            page_size: Some(limit),
+            next_cursor: None,
+        },
+    )))
+}
@@ -0,0 +1,59 @@
+//! Anomaly simulation endpoint for testing the alert pipeline.
+
+use async_trait::async_trait;
+use axum::{extract::State, http::StatusCode, Json};
+use llm_sentinel_core::{
+    events::AnomalyEvent,
+    simulation::{SimulationReport, TestAnomalyRequest},
+    Result,
+};
+use std::sync::Arc;
+use tracing::{error, info};
+
+use super::query::QueryState;
+use crate::{ErrorResponse, SuccessResponse};
+
+type SimulationError = (StatusCode, Json<ErrorResponse>);
+
+/// Sends synthesized anomalies through deduplication, routing and alerting
+#[async_trait]
+pub trait AnomalySimulator: Send + Sync {
+    /// Handle a test anomaly like a detected one, reporting where it went
+    async fn simulate(&self, anomaly: AnomalyEvent) -> Result<SimulationReport>;
+}
+
+/// Synthesize a test anomaly and send it through the alert pipeline
+pub async fn simulate_anomaly(
+    State(state): State<Arc<QueryState>>,
+    Json(request): Json<TestAnomalyRequest>,
+) -> std::result::Result<Json<SuccessResponse<SimulationReport>>, SimulationError> {
+    let simulator = state.simulator.as_ref().ok_or_else(|| {
+        (
+            StatusCode::NOT_FOUND,
+            Json(ErrorResponse::new(
+                "simulation_disabled",
+                "Anomaly simulation is not enabled",
+            )),
+        )
+    })?;
+
+    let report = simulator
+        .simulate(request.synthesize())
+        .await
+        .map_err(|e| {
+            error!("Failed to simulate anomaly: {}", e);
+            (
+                StatusCode::INTERNAL_SERVER_ERROR,
+                Json(ErrorResponse::new("simulation_failed", e.to_string())),
+            )
+        })?;
+
+    info!(
+        alert_id = %report.alert_id,
+        service = %request.service,
+        deduplicated = report.deduplicated,
+        routed = ?report.routed,
+        "Test anomaly sent through the API"
+    );
+    Ok(Json(SuccessResponse::new(report)))
+}
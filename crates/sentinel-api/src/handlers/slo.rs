@@ -0,0 +1,37 @@
+//! SLO status endpoint.
+
+use axum::{extract::State, http::StatusCode, Json};
+use llm_sentinel_detection::slo::SloStatus;
+use std::sync::Arc;
+use tracing::debug;
+
+use super::query::QueryState;
+use crate::{ErrorResponse, ResponseMetadata, SuccessResponse};
+
+/// SLO status endpoint
+pub async fn slo_status(
+    State(state): State<Arc<QueryState>>,
+) -> Result<Json<SuccessResponse<Vec<SloStatus>>>, (StatusCode, Json<ErrorResponse>)> {
+    let tracker = state.slo_tracker.as_ref().ok_or_else(|| {
+        (
+            StatusCode::NOT_FOUND,
+            Json(ErrorResponse::new(
+                "slo_disabled",
+                "No SLOs are configured",
+            )),
+        )
+    })?;
+
+    let statuses = tracker.status();
+    debug!("Reporting status for {} SLOs", statuses.len());
+
+    let total_count = statuses.len();
+    Ok(Json(SuccessResponse::new(statuses).with_metadata(
+        ResponseMetadata {
+            total_count: Some(total_count),
+            page: None,
+            page_size: None,
+            next_cursor: None,
+        },
+    )))
+}
@@ -0,0 +1,96 @@
+//! Runtime state snapshot endpoints.
+
+use async_trait::async_trait;
+use axum::{
+    extract::{Path, State},
+    http::StatusCode,
+    Json,
+};
+use llm_sentinel_core::{snapshot::SnapshotInfo, Error, Result};
+use std::sync::Arc;
+use tracing::{error, info};
+
+use super::query::QueryState;
+use crate::{ErrorResponse, ResponseMetadata, SuccessResponse};
+
+type SnapshotError = (StatusCode, Json<ErrorResponse>);
+
+/// Takes, lists and restores runtime state snapshots
+#[async_trait]
+pub trait SnapshotControl: Send + Sync {
+    /// Snapshot the current runtime state
+    async fn snapshot(&self) -> Result<SnapshotInfo>;
+
+    /// Restore the snapshot with the given ID, or the newest for `latest`
+    async fn restore(&self, id: &str) -> Result<SnapshotInfo>;
+
+    /// Stored snapshots, newest first
+    async fn list(&self) -> Result<Vec<SnapshotInfo>>;
+}
+
+fn snapshot_control(state: &QueryState) -> std::result::Result<&Arc<dyn SnapshotControl>, SnapshotError> {
+    state.snapshots.as_ref().ok_or_else(|| {
+        (
+            StatusCode::NOT_FOUND,
+            Json(ErrorResponse::new(
+                "snapshots_disabled",
+                "Runtime state snapshots are not enabled",
+            )),
+        )
+    })
+}
+
+fn snapshot_failed(e: &Error) -> SnapshotError {
+    let (status, code) = match e {
+        Error::Validation(_) => (StatusCode::BAD_REQUEST, "invalid_snapshot"),
+        Error::NotFound(_) => (StatusCode::NOT_FOUND, "not_found"),
+        _ => (StatusCode::INTERNAL_SERVER_ERROR, "snapshot_failed"),
+    };
+    (status, Json(ErrorResponse::new(code, e.to_string())))
+}
+
+/// List stored snapshots
+pub async fn list_snapshots(
+    State(state): State<Arc<QueryState>>,
+) -> std::result::Result<Json<SuccessResponse<Vec<SnapshotInfo>>>, SnapshotError> {
+    let snapshots = snapshot_control(&state)?.list().await.map_err(|e| {
+        error!("Failed to list snapshots: {}", e);
+        snapshot_failed(&e)
+    })?;
+
+    let total_count = snapshots.len();
+    Ok(Json(SuccessResponse::new(snapshots).with_metadata(
+        ResponseMetadata {
+            total_count: Some(total_count),
+            page: None,
+            page_size: None,
+            next_cursor: None,
+        },
+    )))
+}
+
+/// Snapshot the current runtime state
+pub async fn create_snapshot(
+    State(state): State<Arc<QueryState>>,
+) -> std::result::Result<(StatusCode, Json<SuccessResponse<SnapshotInfo>>), SnapshotError> {
+    let snapshot = snapshot_control(&state)?.snapshot().await.map_err(|e| {
+        error!("Failed to snapshot runtime state: {}", e);
+        snapshot_failed(&e)
+    })?;
+
+    Ok((StatusCode::CREATED, Json(SuccessResponse::new(snapshot))))
+}
+
+/// Restore runtime state from a snapshot
+pub async fn restore_snapshot(
+    State(state): State<Arc<QueryState>>,
+    Path(id): Path<String>,
+) -> std::result::Result<Json<SuccessResponse<SnapshotInfo>>, SnapshotError> {
+    let snapshot = snapshot_control(&state)?.restore(&id).await.map_err(|e| {
+        error!(id = %id, "Failed to restore snapshot: {}", e);
+        snapshot_failed(&e)
+    })?;
+
+    info!(id = %snapshot.id, "Runtime state restored through the API");
+    Ok(Json(SuccessResponse::new(snapshot)))
+}
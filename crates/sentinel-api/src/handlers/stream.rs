@@ -0,0 +1,361 @@
+//! Real-time anomaly stream.
+//!
+//! Clients open a WebSocket and receive each anomaly published on the event
+//! bus as a JSON text message, optionally filtered by service and minimum
+//! severity. Slow anonymous clients skip anomalies rather than holding up
+//! the bus.
+//!
+//! A downstream processor that must see every anomaly exactly once connects
+//! with a `consumer` ID. Each message then carries the anomaly's stream
+//! `cursor`, which the consumer sends back as `{"ack": "<cursor>"}` once it
+//! has processed the anomaly. On reconnect, the anomalies stored after its
+//! last acknowledged cursor are replayed before live ones. A consumer that
+//! falls behind the bus does not skip: the anomalies stored after the last
+//! one sent to it are replayed from storage, and the stream is closed when
+//! there is no position to replay from, so the consumer resumes from its
+//! acknowledged cursor on reconnect.
+
+use axum::{
+    extract::{
+        ws::{Message, WebSocket, WebSocketUpgrade},
+        Query, State,
+    },
+    http::StatusCode,
+    response::Response,
+    Json,
+};
+use llm_sentinel_core::{
+    bus::{Received, Subscription},
+    events::AnomalyEvent,
+    types::Severity,
+};
+use llm_sentinel_storage::{
+    query::Cursor,
+    stream::{acknowledge, replay_page, stream_cursor},
+    Storage,
+};
+use serde::{Deserialize, Serialize};
+use std::{collections::HashSet, sync::Arc};
+use tracing::{debug, error, info, warn};
+use uuid::Uuid;
+
+use super::{query::QueryState, query_failed};
+use crate::ErrorResponse;
+
+/// Stored anomalies fetched per replay query
+const REPLAY_PAGE_SIZE: usize = 500;
+
+/// Longest accepted consumer ID
+const MAX_CONSUMER_ID_LEN: usize = 128;
+
+/// Stream filters
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct StreamQueryParams {
+    /// Only anomalies of this service
+    pub service: Option<String>,
+    /// Only anomalies at or above this severity
+    pub min_severity: Option<Severity>,
+    /// Consumer ID to resume from and acknowledge under
+    pub consumer: Option<String>,
+}
+
+impl StreamQueryParams {
+    fn matches(&self, anomaly: &AnomalyEvent) -> bool {
+        self.service
+            .as_deref()
+            .map_or(true, |service| anomaly.service_name.as_str() == service)
+            && self
+                .min_severity
+                .map_or(true, |min| anomaly.severity >= min)
+    }
+}
+
+/// Anomaly sent to a named consumer
+#[derive(Debug, Serialize)]
+pub struct ConsumerMessage<'a> {
+    /// Stream position to acknowledge once processed
+    pub cursor: String,
+    /// The anomaly
+    pub anomaly: &'a AnomalyEvent,
+}
+
+/// Acknowledgement sent by a named consumer
+#[derive(Debug, Deserialize)]
+pub struct StreamAck {
+    /// Cursor of the last processed anomaly
+    pub ack: String,
+}
+
+/// Stream anomalies over WebSocket
+pub async fn stream_anomalies(
+    State(state): State<Arc<QueryState>>,
+    Query(params): Query<StreamQueryParams>,
+    ws: WebSocketUpgrade,
+) -> Result<Response, (StatusCode, Json<ErrorResponse>)> {
+    let bus = state.bus.as_ref().ok_or_else(|| {
+        (
+            StatusCode::NOT_FOUND,
+            Json(ErrorResponse::new(
+                "stream_disabled",
+                "No event bus is attached",
+            )),
+        )
+    })?;
+
+    // Subscribe before upgrading so nothing published meanwhile is missed
+    let subscription = bus.anomalies().subscribe();
+
+    let resume = match params.consumer.as_deref() {
+        Some(consumer) => {
+            if consumer.is_empty() || consumer.len() > MAX_CONSUMER_ID_LEN {
+                return Err((
+                    StatusCode::BAD_REQUEST,
+                    Json(ErrorResponse::new(
+                        "invalid_consumer",
+                        format!(
+                            "Consumer ID must be 1 to {} characters",
+                            MAX_CONSUMER_ID_LEN
+                        ),
+                    )),
+                ));
+            }
+            state
+                .storage
+                .get_stream_offset(consumer)
+                .await
+                .map_err(|e| {
+                    error!("Stream offset lookup failed: {}", e);
+                    query_failed(&e)
+                })?
+                .map(|offset| offset.cursor)
+        }
+        None => None,
+    };
+
+    let storage = state.storage.clone();
+    Ok(ws
+        .on_upgrade(move |socket| forward_anomalies(socket, subscription, params, storage, resume)))
+}
+
+async fn forward_anomalies(
+    mut socket: WebSocket,
+    mut subscription: Subscription<AnomalyEvent>,
+    params: StreamQueryParams,
+    storage: Arc<dyn Storage>,
+    resume: Option<Cursor>,
+) {
+    debug!(?params, "Anomaly stream opened");
+    ::metrics::gauge!("sentinel_api_streams_active").increment(1.0);
+
+    // Replayed anomalies published again while replaying are not resent
+    let mut replayed = HashSet::new();
+    // Stream position of the last anomaly sent to a named consumer
+    let mut position = resume;
+    let resumed = match resume {
+        Some(cursor) => {
+            replay(
+                &mut socket,
+                storage.as_ref(),
+                cursor,
+                &params,
+                &mut replayed,
+                &mut position,
+            )
+            .await
+        }
+        None => true,
+    };
+
+    if resumed {
+        loop {
+            tokio::select! {
+                received = subscription.next() => match received {
+                    None => break,
+                    Some(Received::Message(anomaly)) => {
+                        if replayed.remove(&anomaly.alert_id) || !params.matches(&anomaly) {
+                            continue;
+                        }
+                        if !send_anomaly(&mut socket, &anomaly, &params).await {
+                            break;
+                        }
+                        if params.consumer.is_some() {
+                            position = Some(stream_cursor(&anomaly));
+                        }
+                    }
+                    // Anonymous clients skip what they missed
+                    Some(Received::Lagged(_)) if params.consumer.is_none() => {}
+                    Some(Received::Lagged(skipped)) => {
+                        warn!(
+                            consumer = params.consumer.as_deref(),
+                            skipped, "Anomaly stream consumer lagged, replaying from storage"
+                        );
+                        let Some(cursor) = position else {
+                            // Nothing sent yet: the consumer resumes from its
+                            // acknowledged cursor on reconnect
+                            let _ = socket.send(Message::Close(None)).await;
+                            break;
+                        };
+                        if !replay(
+                            &mut socket,
+                            storage.as_ref(),
+                            cursor,
+                            &params,
+                            &mut replayed,
+                            &mut position,
+                        )
+                        .await
+                        {
+                            break;
+                        }
+                    }
+                },
+                message = socket.recv() => match message {
+                    Some(Ok(Message::Close(_))) | Some(Err(_)) | None => break,
+                    Some(Ok(Message::Text(text))) => {
+                        if let Some(consumer) = params.consumer.as_deref() {
+                            handle_ack(storage.as_ref(), consumer, &text).await;
+                        }
+                    }
+                    // Pings are answered by axum; other client messages are ignored
+                    Some(Ok(_)) => {}
+                },
+            }
+        }
+    }
+
+    ::metrics::gauge!("sentinel_api_streams_active").decrement(1.0);
+    debug!("Anomaly stream closed");
+}
+
+/// Send the stored anomalies after `cursor`, returning whether the stream
+/// is still open
+///
+/// `position` is moved to the last anomaly sent.
+async fn replay(
+    socket: &mut WebSocket,
+    storage: &dyn Storage,
+    mut cursor: Cursor,
+    params: &StreamQueryParams,
+    replayed: &mut HashSet<Uuid>,
+    position: &mut Option<Cursor>,
+) -> bool {
+    let mut count = 0;
+    loop {
+        let page = match replay_page(storage, cursor, REPLAY_PAGE_SIZE).await {
+            Ok(page) => page,
+            Err(e) => {
+                error!("Anomaly stream replay failed: {}", e);
+                let _ = socket.send(Message::Close(None)).await;
+                return false;
+            }
+        };
+        for anomaly in page.iter().filter(|anomaly| params.matches(anomaly)) {
+            if !send_anomaly(socket, anomaly, params).await {
+                return false;
+            }
+            replayed.insert(anomaly.alert_id);
+            *position = Some(stream_cursor(anomaly));
+            count += 1;
+        }
+        match page.last() {
+            Some(last) if page.len() == REPLAY_PAGE_SIZE => cursor = stream_cursor(last),
+            _ => break,
+        }
+    }
+
+    info!(
+        consumer = params.consumer.as_deref(),
+        count, "Anomaly stream resumed"
+    );
+    ::metrics::counter!("sentinel_api_stream_replayed_total").increment(count as u64);
+    true
+}
+
+/// Send one anomaly, wrapped with its cursor for named consumers
+async fn send_anomaly(
+    socket: &mut WebSocket,
+    anomaly: &AnomalyEvent,
+    params: &StreamQueryParams,
+) -> bool {
+    let text = match params.consumer {
+        Some(_) => serde_json::to_string(&ConsumerMessage {
+            cursor: stream_cursor(anomaly).encode(),
+            anomaly,
+        }),
+        None => serde_json::to_string(anomaly),
+    };
+    match text {
+        Ok(text) => socket.send(Message::Text(text)).await.is_ok(),
+        Err(e) => {
+            error!("Failed to serialize streamed anomaly: {}", e);
+            true
+        }
+    }
+}
+
+/// Store a consumer's acknowledgement
+async fn handle_ack(storage: &dyn Storage, consumer: &str, text: &str) {
+    let cursor = serde_json::from_str::<StreamAck>(text)
+        .map_err(|e| e.to_string())
+        .and_then(|ack| Cursor::decode(&ack.ack).map_err(|e| e.to_string()));
+    match cursor {
+        Ok(cursor) => {
+            if let Err(e) = acknowledge(storage, consumer, cursor).await {
+                error!(consumer, "Failed to store stream offset: {}", e);
+            }
+        }
+        Err(e) => warn!(consumer, "Ignoring invalid stream acknowledgement: {}", e),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use llm_sentinel_fixtures::anomaly;
+
+    fn create_test_anomaly(service: &str, severity: Severity) -> AnomalyEvent {
+        anomaly().severity(severity).service(service).build()
+    }
+
+    #[test]
+    fn test_stream_filters() {
+        let params = StreamQueryParams {
+            service: Some("checkout".to_string()),
+            min_severity: Some(Severity::High),
+            consumer: None,
+        };
+        assert!(params.matches(&create_test_anomaly("checkout", Severity::High)));
+        assert!(params.matches(&create_test_anomaly("checkout", Severity::Critical)));
+        assert!(!params.matches(&create_test_anomaly("checkout", Severity::Medium)));
+        assert!(!params.matches(&create_test_anomaly("search", Severity::Critical)));
+        assert!(StreamQueryParams::default().matches(&create_test_anomaly("search", Severity::Low)));
+    }
+
+    #[tokio::test]
+    async fn test_handle_ack() {
+        use llm_sentinel_storage::memory::InMemoryStorage;
+
+        let storage = InMemoryStorage::new();
+        let anomaly = create_test_anomaly("checkout", Severity::High);
+        let cursor = stream_cursor(&anomaly);
+
+        let message = serde_json::to_value(ConsumerMessage {
+            cursor: cursor.encode(),
+            anomaly: &anomaly,
+        })
+        .unwrap();
+        let ack = serde_json::json!({ "ack": message["cursor"] }).to_string();
+
+        handle_ack(&storage, "billing", "not json").await;
+        assert!(storage
+            .get_stream_offset("billing")
+            .await
+            .unwrap()
+            .is_none());
+
+        handle_ack(&storage, "billing", &ack).await;
+        let offset = storage.get_stream_offset("billing").await.unwrap().unwrap();
+        assert_eq!(offset.cursor, cursor);
+    }
+}
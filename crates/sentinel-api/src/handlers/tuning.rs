@@ -0,0 +1,129 @@
+//! Detection configuration document endpoints.
+//!
+//! `GET /admin/detection-config` returns the running detection
+//! configuration as a YAML document. `POST /admin/detection-config/stage`
+//! validates a document against it, reports the sections it changes and,
+//! unless `dry_run` is set, stages it in the detection file. Staging never
+//! touches the running detectors, routes, severity rules or suppression
+//! schedules; the staged document takes effect on the next start.
+
+use async_trait::async_trait;
+use axum::{
+    extract::{Query, State},
+    http::{header, StatusCode},
+    response::{IntoResponse, Response},
+    Json,
+};
+use llm_sentinel_core::{
+    tuning::{DetectionDocument, SectionChange},
+    Error, Result,
+};
+use serde::{Deserialize, Serialize};
+use std::sync::Arc;
+use tracing::{error, info};
+
+use super::query::QueryState;
+use crate::{ErrorResponse, SuccessResponse};
+
+/// Content type of detection documents
+pub const YAML_CONTENT_TYPE: &str = "application/yaml";
+
+type TuningError = (StatusCode, Json<ErrorResponse>);
+
+/// Exports and imports the detection configuration document
+#[async_trait]
+pub trait DetectionConfigControl: Send + Sync {
+    /// Detection document of the running configuration
+    fn export(&self) -> Result<DetectionDocument>;
+
+    /// Validate `document` against the running configuration and, unless
+    /// `dry_run`, write it to the detection file for the next start; returns
+    /// the sections it changes
+    ///
+    /// The running configuration is left as it is.
+    async fn stage(&self, document: DetectionDocument, dry_run: bool)
+        -> Result<Vec<SectionChange>>;
+}
+
+/// Query parameters for staging a detection document
+#[derive(Debug, Deserialize)]
+pub struct DetectionStageParams {
+    /// Validate and diff without writing the document
+    #[serde(default)]
+    pub dry_run: bool,
+}
+
+/// Outcome of staging a detection document
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DetectionStageReport {
+    /// Whether the document was written to the detection file, to take
+    /// effect on the next start
+    pub staged: bool,
+    /// Sections the document changes
+    pub changes: Vec<SectionChange>,
+}
+
+fn tuning_control(
+    state: &QueryState,
+) -> std::result::Result<&Arc<dyn DetectionConfigControl>, TuningError> {
+    state.tuning.as_ref().ok_or_else(|| {
+        (
+            StatusCode::NOT_FOUND,
+            Json(ErrorResponse::new(
+                "detection_config_disabled",
+                "Detection configuration documents are not enabled",
+            )),
+        )
+    })
+}
+
+fn tuning_failed(e: &Error) -> TuningError {
+    let (status, code) = match e {
+        Error::Validation(_) => (StatusCode::BAD_REQUEST, "invalid_document"),
+        Error::Config(_) => (StatusCode::CONFLICT, "detection_file_unset"),
+        _ => (StatusCode::INTERNAL_SERVER_ERROR, "detection_config_failed"),
+    };
+    (status, Json(ErrorResponse::new(code, e.to_string())))
+}
+
+/// Export the running detection configuration as YAML
+pub async fn export_detection_config(
+    State(state): State<Arc<QueryState>>,
+) -> std::result::Result<Response, TuningError> {
+    let yaml = tuning_control(&state)?
+        .export()
+        .and_then(|document| document.to_yaml())
+        .map_err(|e| {
+            error!("Failed to export detection configuration: {}", e);
+            tuning_failed(&e)
+        })?;
+
+    Ok(([(header::CONTENT_TYPE, YAML_CONTENT_TYPE)], yaml).into_response())
+}
+
+/// Validate a YAML detection document, diff it against the running
+/// configuration and, unless `dry_run`, stage it for the next start
+pub async fn stage_detection_config(
+    State(state): State<Arc<QueryState>>,
+    Query(params): Query<DetectionStageParams>,
+    body: String,
+) -> std::result::Result<Json<SuccessResponse<DetectionStageReport>>, TuningError> {
+    let control = tuning_control(&state)?;
+    let document = DetectionDocument::from_yaml(&body).map_err(|e| tuning_failed(&e))?;
+    let changes = control.stage(document, params.dry_run).await.map_err(|e| {
+        error!("Failed to stage detection configuration: {}", e);
+        tuning_failed(&e)
+    })?;
+
+    let staged = !params.dry_run;
+    if staged {
+        info!(
+            changes = changes.len(),
+            "Detection configuration staged through the API; it takes effect on restart"
+        );
+    }
+    Ok(Json(SuccessResponse::new(DetectionStageReport {
+        staged,
+        changes,
+    })))
+}
@@ -0,0 +1,353 @@
+//! Idempotent API writes.
+//!
+//! A client retrying a `POST`, `PUT`, `PATCH` or `DELETE` after a network
+//! failure cannot tell whether the first attempt was applied. Requests
+//! carrying an `Idempotency-Key` header have their response kept for a short
+//! TTL; a retry with the same key and the same request gets that response
+//! back, marked with `Idempotent-Replayed: true`, instead of ingesting or
+//! applying the action again.
+//!
+//! Keys are scoped to the caller's `Authorization` header. Reusing a key for
+//! a different request is rejected with `422`, and a retry arriving while
+//! the first attempt is still running with `409`. Server errors are not
+//! kept, so a request that failed can be retried with its key.
+
+use axum::{
+    body::{Body, Bytes},
+    extract::State,
+    http::{header, HeaderMap, HeaderValue, Method, Request, StatusCode},
+    middleware::Next,
+    response::{IntoResponse, Response},
+    Json,
+};
+use llm_sentinel_core::{
+    config::IdempotencyConfig,
+    memory::{MemoryConsumer, ShedCost},
+};
+use moka::future::Cache;
+use sha2::{Digest, Sha256};
+use std::{
+    collections::HashSet,
+    sync::{Arc, Mutex},
+    time::Duration,
+};
+use tracing::{debug, warn};
+
+use crate::ErrorResponse;
+
+/// Request header carrying the client's idempotency key
+pub const IDEMPOTENCY_KEY: &str = "idempotency-key";
+
+/// Response header marking a replayed response
+pub const IDEMPOTENT_REPLAYED: &str = "idempotent-replayed";
+
+/// Maximum idempotency key length
+const MAX_KEY_LEN: usize = 255;
+
+/// A kept response
+#[derive(Debug)]
+struct StoredResponse {
+    /// Hash of the request the response answered
+    fingerprint: String,
+    status: StatusCode,
+    headers: HeaderMap,
+    body: Bytes,
+}
+
+/// Responses to requests carrying an idempotency key
+pub struct IdempotencyStore {
+    responses: Cache<String, Arc<StoredResponse>>,
+    /// Keys whose first request is still running
+    in_flight: Mutex<HashSet<String>>,
+    max_body_size: usize,
+}
+
+impl std::fmt::Debug for IdempotencyStore {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("IdempotencyStore")
+            .field("entry_count", &self.responses.entry_count())
+            .field("in_flight", &self.in_flight.lock().unwrap().len())
+            .finish()
+    }
+}
+
+impl IdempotencyStore {
+    /// Create a store; request bodies up to `max_body_size` are hashed
+    pub fn new(config: &IdempotencyConfig, max_body_size: usize) -> Self {
+        let responses = Cache::builder()
+            .max_capacity(config.max_entries)
+            .time_to_live(Duration::from_secs(config.ttl_secs))
+            .build();
+        Self {
+            responses,
+            in_flight: Mutex::new(HashSet::new()),
+            max_body_size,
+        }
+    }
+}
+
+/// Kept responses only save retries from repeating a write, so the whole
+/// store can be dropped
+impl MemoryConsumer for IdempotencyStore {
+    fn name(&self) -> &str {
+        "idempotency"
+    }
+
+    fn memory_usage(&self) -> usize {
+        self.responses
+            .iter()
+            .map(|(key, stored)| {
+                let headers: usize = stored
+                    .headers
+                    .iter()
+                    .map(|(name, value)| name.as_str().len() + value.len())
+                    .sum();
+                std::mem::size_of::<StoredResponse>()
+                    + key.len()
+                    + stored.fingerprint.len()
+                    + headers
+                    + stored.body.len()
+            })
+            .sum()
+    }
+
+    fn shed_cost(&self) -> ShedCost {
+        ShedCost::Cheap
+    }
+
+    fn shed(&self, _bytes: usize) -> usize {
+        let usage = self.memory_usage();
+        self.responses.invalidate_all();
+        usage
+    }
+}
+
+/// Releases an in-flight key, also when the request is cancelled
+struct InFlight<'a> {
+    store: &'a IdempotencyStore,
+    key: String,
+}
+
+impl Drop for InFlight<'_> {
+    fn drop(&mut self) {
+        self.store.in_flight.lock().unwrap().remove(&self.key);
+    }
+}
+
+/// Key of a request, scoped to the caller's credentials
+fn scoped_key(key: &str, headers: &HeaderMap) -> String {
+    let caller = headers
+        .get(header::AUTHORIZATION)
+        .map(|value| hex::encode(Sha256::digest(value.as_bytes())))
+        .unwrap_or_default();
+    format!("{}|{}", caller, key)
+}
+
+/// Hash of a request's method, path, query and body
+fn fingerprint(method: &Method, uri: &str, body: &[u8]) -> String {
+    let mut hasher = Sha256::new();
+    hasher.update(method.as_str());
+    hasher.update(b" ");
+    hasher.update(uri);
+    hasher.update(b"\n");
+    hasher.update(body);
+    hex::encode(hasher.finalize())
+}
+
+fn valid_key(key: &str) -> bool {
+    !key.is_empty() && key.len() <= MAX_KEY_LEN && key.bytes().all(|b| b.is_ascii_graphic())
+}
+
+fn rejected(status: StatusCode, code: &str, message: &str, result: &'static str) -> Response {
+    metrics::counter!("sentinel_api_idempotency_requests_total", "result" => result).increment(1);
+    (status, Json(ErrorResponse::new(code, message))).into_response()
+}
+
+/// Idempotency middleware, applied to API routes
+pub async fn idempotency_middleware(
+    State(store): State<Arc<IdempotencyStore>>,
+    req: Request<Body>,
+    next: Next,
+) -> Response {
+    if matches!(*req.method(), Method::GET | Method::HEAD | Method::OPTIONS) {
+        return next.run(req).await;
+    }
+    let Some(key) = req.headers().get(IDEMPOTENCY_KEY) else {
+        return next.run(req).await;
+    };
+    let Some(key) = key.to_str().ok().filter(|key| valid_key(key)) else {
+        return rejected(
+            StatusCode::BAD_REQUEST,
+            "invalid_idempotency_key",
+            "Idempotency-Key must be 1 to 255 visible ASCII characters",
+            "invalid",
+        );
+    };
+    let key = scoped_key(key, req.headers());
+
+    let (parts, body) = req.into_parts();
+    let body = match axum::body::to_bytes(body, store.max_body_size).await {
+        Ok(body) => body,
+        // Rendered as JSON by the body limit middleware
+        Err(_) => return StatusCode::PAYLOAD_TOO_LARGE.into_response(),
+    };
+    let fingerprint = fingerprint(&parts.method, &parts.uri.to_string(), &body);
+
+    // Held until the response is kept, so a retry is either rejected while
+    // the first attempt runs or sees its response
+    if !store.in_flight.lock().unwrap().insert(key.clone()) {
+        return rejected(
+            StatusCode::CONFLICT,
+            "idempotency_key_in_progress",
+            "A request with this Idempotency-Key is still being processed",
+            "in_progress",
+        );
+    }
+    let _in_flight = InFlight {
+        store: &store,
+        key: key.clone(),
+    };
+
+    if let Some(stored) = store.responses.get(&key).await {
+        if stored.fingerprint != fingerprint {
+            return rejected(
+                StatusCode::UNPROCESSABLE_ENTITY,
+                "idempotency_key_reused",
+                "Idempotency-Key was already used for a different request",
+                "reused",
+            );
+        }
+        debug!(path = %parts.uri.path(), "Replaying idempotent response");
+        metrics::counter!("sentinel_api_idempotency_requests_total", "result" => "replayed")
+            .increment(1);
+        let mut response = Response::new(Body::from(stored.body.clone()));
+        *response.status_mut() = stored.status;
+        *response.headers_mut() = stored.headers.clone();
+        response
+            .headers_mut()
+            .insert(IDEMPOTENT_REPLAYED, HeaderValue::from_static("true"));
+        return response;
+    }
+
+    let response = next.run(Request::from_parts(parts, Body::from(body))).await;
+    metrics::counter!("sentinel_api_idempotency_requests_total", "result" => "executed")
+        .increment(1);
+    // Failed requests may be retried with the same key
+    if response.status().is_server_error() {
+        return response;
+    }
+
+    let (parts, body) = response.into_parts();
+    let body = match axum::body::to_bytes(body, usize::MAX).await {
+        Ok(body) => body,
+        Err(e) => {
+            warn!(error = %e, "Failed to buffer response for idempotent replay");
+            return StatusCode::INTERNAL_SERVER_ERROR.into_response();
+        }
+    };
+    store
+        .responses
+        .insert(
+            key,
+            Arc::new(StoredResponse {
+                fingerprint,
+                status: parts.status,
+                headers: parts.headers.clone(),
+                body: body.clone(),
+            }),
+        )
+        .await;
+    Response::from_parts(parts, Body::from(body))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use axum::{middleware, routing::post, Router};
+    use std::sync::atomic::{AtomicU64, Ordering};
+    use tower::ServiceExt;
+
+    fn app(calls: Arc<AtomicU64>) -> Router {
+        let store = Arc::new(IdempotencyStore::new(&IdempotencyConfig::default(), 1024));
+        Router::new()
+            .route(
+                "/ingest",
+                post(move |body: String| {
+                    let calls = calls.clone();
+                    async move {
+                        let call = calls.fetch_add(1, Ordering::SeqCst);
+                        if body == "fail" {
+                            return (StatusCode::SERVICE_UNAVAILABLE, call.to_string());
+                        }
+                        (StatusCode::CREATED, call.to_string())
+                    }
+                }),
+            )
+            .route_layer(middleware::from_fn_with_state(
+                store,
+                idempotency_middleware,
+            ))
+    }
+
+    async fn send(app: &Router, key: Option<&str>, body: &str) -> (StatusCode, String, bool) {
+        let mut request = Request::post("/ingest");
+        if let Some(key) = key {
+            request = request.header(IDEMPOTENCY_KEY, key);
+        }
+        let response = app
+            .clone()
+            .oneshot(request.body(Body::from(body.to_string())).unwrap())
+            .await
+            .unwrap();
+        let status = response.status();
+        let replayed = response.headers().contains_key(IDEMPOTENT_REPLAYED);
+        let body = axum::body::to_bytes(response.into_body(), usize::MAX)
+            .await
+            .unwrap();
+        (status, String::from_utf8(body.to_vec()).unwrap(), replayed)
+    }
+
+    #[tokio::test]
+    async fn test_retry_replayed() {
+        let calls = Arc::new(AtomicU64::new(0));
+        let app = app(calls.clone());
+
+        assert_eq!(
+            send(&app, Some("a"), "event").await,
+            (StatusCode::CREATED, "0".into(), false)
+        );
+        assert_eq!(
+            send(&app, Some("a"), "event").await,
+            (StatusCode::CREATED, "0".into(), true)
+        );
+        assert_eq!(calls.load(Ordering::SeqCst), 1);
+
+        // Without a key every request runs
+        send(&app, None, "event").await;
+        send(&app, None, "event").await;
+        assert_eq!(calls.load(Ordering::SeqCst), 3);
+    }
+
+    #[tokio::test]
+    async fn test_key_reused_for_other_request() {
+        let app = app(Arc::new(AtomicU64::new(0)));
+        send(&app, Some("a"), "event").await;
+        let (status, body, _) = send(&app, Some("a"), "other event").await;
+        assert_eq!(status, StatusCode::UNPROCESSABLE_ENTITY);
+        assert!(body.contains("idempotency_key_reused"));
+
+        let (status, _, _) = send(&app, Some(""), "event").await;
+        assert_eq!(status, StatusCode::BAD_REQUEST);
+    }
+
+    #[tokio::test]
+    async fn test_server_errors_not_kept() {
+        let calls = Arc::new(AtomicU64::new(0));
+        let app = app(calls.clone());
+        let (status, _, _) = send(&app, Some("a"), "fail").await;
+        assert_eq!(status, StatusCode::SERVICE_UNAVAILABLE);
+        let (_, _, replayed) = send(&app, Some("a"), "fail").await;
+        assert!(!replayed);
+        assert_eq!(calls.load(Ordering::SeqCst), 2);
+    }
+}
@@ -7,15 +7,41 @@
 //! - Metrics export (Prometheus)
 //! - Telemetry query API
 //! - Anomaly query API
+//! - Anomaly comparison between time ranges or label values
+//! - Anomaly lifecycle state changes
+//! - Incidents grouping related anomalies, with state changes and links
+//! - Cost reports (JSON and CSV)
+//! - SLO status and error budgets
+//! - Provider/model availability
+//! - Model pricing management
+//! - Alert delivery SLA reporting
+//! - Alert deduplication statistics
+//! - Runtime state snapshots and credential rotation
+//! - Detection configuration export and import as YAML
 //! - Real-time anomaly stream (WebSocket)
+//! - Dual-stack TCP, Unix socket and per-listener TLS serving
+//! - gzip/zstd request and response compression with enforced body limits
+//! - Sampled structured access log
+//! - Response cache for aggregate queries
+//! - Idempotent writes with the `Idempotency-Key` header
+//! - Built-in dashboard of live anomalies, snapshots and health
 
 #![warn(missing_debug_implementations, rust_2018_idioms, unreachable_pub)]
 
+pub mod access_log;
+pub mod cache;
 pub mod handlers;
+pub mod idempotency;
+mod listener;
 pub mod middleware;
 pub mod routes;
 pub mod server;
+pub mod ui;
 
+use llm_sentinel_core::config::{
+    AccessLogConfig, IdempotencyConfig, ListenerConfig, ResponseCacheConfig, RouteTimeoutsConfig,
+    ServerTlsConfig,
+};
 use serde::{Deserialize, Serialize};
 use std::net::SocketAddr;
 
@@ -24,29 +50,69 @@ use std::net::SocketAddr;
 pub struct ApiConfig {
     /// Server bind address
     pub bind_addr: SocketAddr,
+    /// Serve `bind_addr` over HTTPS
+    #[serde(default)]
+    pub tls: Option<ServerTlsConfig>,
+    /// Additional listeners
+    #[serde(default)]
+    pub listeners: Vec<ListenerConfig>,
     /// Enable CORS
     pub enable_cors: bool,
     /// CORS allowed origins
     pub cors_origins: Vec<String>,
     /// Request timeout (seconds)
     pub timeout_secs: u64,
-    /// Maximum request body size (bytes)
+    /// Timeouts by route class, overriding `timeout_secs`
+    #[serde(default)]
+    pub route_timeouts: RouteTimeoutsConfig,
+    /// Maximum request body size (bytes), enforced after decompression
     pub max_body_size: usize,
+    /// Accept gzip/zstd request bodies and compress responses
+    #[serde(default = "default_compression")]
+    pub compression: bool,
     /// Enable request logging
     pub enable_logging: bool,
+    /// Structured access log
+    #[serde(default)]
+    pub access_log: AccessLogConfig,
+    /// Cache for aggregate query responses
+    #[serde(default)]
+    pub response_cache: ResponseCacheConfig,
+    /// Replay of writes retried with the same `Idempotency-Key`
+    #[serde(default)]
+    pub idempotency: IdempotencyConfig,
+    /// Serve the built-in dashboard at `/ui`
+    #[serde(default = "default_dashboard")]
+    pub dashboard: bool,
     /// Metrics endpoint path
     pub metrics_path: String,
 }
 
+fn default_compression() -> bool {
+    true
+}
+
+fn default_dashboard() -> bool {
+    true
+}
+
 impl Default for ApiConfig {
     fn default() -> Self {
         Self {
             bind_addr: "0.0.0.0:8080".parse().unwrap(),
+            tls: None,
+            listeners: Vec::new(),
             enable_cors: true,
             cors_origins: vec!["*".to_string()],
             timeout_secs: 30,
+            route_timeouts: RouteTimeoutsConfig::default(),
             max_body_size: 10 * 1024 * 1024, // 10MB
+            compression: true,
             enable_logging: true,
+            access_log: AccessLogConfig::default(),
+            response_cache: ResponseCacheConfig::default(),
+            idempotency: IdempotencyConfig::default(),
+            dashboard: true,
             metrics_path: "/metrics".to_string(),
         }
     }
@@ -115,6 +181,9 @@ pub struct ResponseMetadata {
     /// Page size
     #[serde(skip_serializing_if = "Option::is_none")]
     pub page_size: Option<usize>,
+    /// Opaque token for the next page, present when more results may follow
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub next_cursor: Option<String>,
 }
 
 /// Re-export commonly used types
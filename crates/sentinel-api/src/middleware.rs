@@ -2,12 +2,20 @@
 
 use axum::{
     body::Body,
-    http::{header, Method, Request, StatusCode},
+    extract::State,
+    http::{header, HeaderMap, HeaderName, HeaderValue, Method, Request, StatusCode},
     middleware::Next,
     response::{IntoResponse, Response},
+    Json,
 };
+use llm_sentinel_core::{correlation, deadline};
+use sha2::{Digest, Sha256};
+use std::time::Duration;
+use tokio::time::Instant;
 use tower_http::cors::{Any, CorsLayer};
-use tracing::{debug, warn};
+use tracing::{debug, warn, Instrument};
+
+use crate::ErrorResponse;
 
 /// Create CORS middleware
 pub fn cors_middleware(origins: Vec<String>) -> CorsLayer {
@@ -15,7 +23,13 @@ pub fn cors_middleware(origins: Vec<String>) -> CorsLayer {
         CorsLayer::new()
             .allow_origin(Any)
             .allow_methods([Method::GET, Method::POST, Method::PUT, Method::DELETE])
-            .allow_headers([header::CONTENT_TYPE, header::AUTHORIZATION])
+            .allow_headers([
+                header::CONTENT_TYPE,
+                header::AUTHORIZATION,
+                header::IF_NONE_MATCH,
+                HeaderName::from_static(correlation::HEADER),
+            ])
+            .expose_headers([header::ETAG, HeaderName::from_static(correlation::HEADER)])
     } else {
         let allowed_origins: Vec<_> = origins
             .iter()
@@ -25,7 +39,13 @@ pub fn cors_middleware(origins: Vec<String>) -> CorsLayer {
         CorsLayer::new()
             .allow_origin(allowed_origins)
             .allow_methods([Method::GET, Method::POST, Method::PUT, Method::DELETE])
-            .allow_headers([header::CONTENT_TYPE, header::AUTHORIZATION])
+            .allow_headers([
+                header::CONTENT_TYPE,
+                header::AUTHORIZATION,
+                header::IF_NONE_MATCH,
+                HeaderName::from_static(correlation::HEADER),
+            ])
+            .expose_headers([header::ETAG, HeaderName::from_static(correlation::HEADER)])
     }
 }
 
@@ -46,6 +66,135 @@ pub async fn logging_middleware(
     Ok(response)
 }
 
+/// Request deadline middleware
+///
+/// Makes the route's timeout budget visible to storage backends through
+/// [`deadline::remaining`].
+pub async fn deadline_middleware(
+    State(timeout): State<Duration>,
+    req: Request<Body>,
+    next: Next,
+) -> Response {
+    deadline::scope(Instant::now() + timeout, next.run(req)).await
+}
+
+/// Correlation ID middleware
+///
+/// Takes the request's correlation ID from the `X-Correlation-ID` header, or
+/// generates one if it is missing or invalid, runs the request in its
+/// [`correlation::scope`] and echoes it in the response header.
+pub async fn correlation_middleware(req: Request<Body>, next: Next) -> Response {
+    let id = req
+        .headers()
+        .get(correlation::HEADER)
+        .and_then(|value| value.to_str().ok())
+        .filter(|id| correlation::is_valid(id))
+        .map_or_else(correlation::generate, str::to_string);
+    let span = tracing::debug_span!("request", correlation_id = %id);
+    let value = HeaderValue::from_str(&id).ok();
+
+    let mut response = correlation::scope(id, next.run(req)).instrument(span).await;
+    if let Some(value) = value {
+        response.headers_mut().insert(correlation::HEADER, value);
+    }
+    response
+}
+
+/// Request body size limit middleware
+///
+/// Rejects bodies whose declared length exceeds `limit` before they are read,
+/// and turns the rejection of streamed or decompressed bodies that outgrow it
+/// into the same JSON `413 Payload Too Large` response.
+pub async fn body_limit_middleware(
+    State(limit): State<usize>,
+    req: Request<Body>,
+    next: Next,
+) -> Response {
+    let declared = req
+        .headers()
+        .get(header::CONTENT_LENGTH)
+        .and_then(|value| value.to_str().ok())
+        .and_then(|value| value.parse::<u64>().ok());
+    if declared.is_some_and(|length| length > limit as u64) {
+        return payload_too_large(limit);
+    }
+
+    // Extractors reject oversized streamed bodies with a plain-text 413
+    let response = next.run(req).await;
+    let is_json = response
+        .headers()
+        .get(header::CONTENT_TYPE)
+        .is_some_and(|value| value.as_bytes().starts_with(b"application/json"));
+    if response.status() == StatusCode::PAYLOAD_TOO_LARGE && !is_json {
+        return payload_too_large(limit);
+    }
+    response
+}
+
+fn payload_too_large(limit: usize) -> Response {
+    (
+        StatusCode::PAYLOAD_TOO_LARGE,
+        Json(ErrorResponse::new(
+            "payload_too_large",
+            format!("Request body exceeds the limit of {} bytes", limit),
+        )),
+    )
+        .into_response()
+}
+
+/// Weak entity tag over a response body
+///
+/// Weak, since compression is applied after the tag is computed.
+pub fn etag(body: &[u8]) -> String {
+    let digest = Sha256::digest(body);
+    format!("W/\"{}\"", hex::encode(&digest[..16]))
+}
+
+/// Whether an `If-None-Match` header matches the entity tag, using weak
+/// comparison
+pub fn if_none_match(headers: &HeaderMap, etag: &str) -> bool {
+    let opaque = |tag: &str| tag.trim().trim_start_matches("W/").to_string();
+    let etag = opaque(etag);
+    headers
+        .get_all(header::IF_NONE_MATCH)
+        .iter()
+        .filter_map(|value| value.to_str().ok())
+        .flat_map(|value| value.split(','))
+        .any(|tag| tag.trim() == "*" || opaque(tag) == etag)
+}
+
+/// Entity tag middleware for query endpoints
+///
+/// Tags successful `GET` responses with an `ETag` over their body and
+/// answers a matching `If-None-Match` with `304 Not Modified`, so polling
+/// clients do not re-transfer identical result sets.
+pub async fn etag_middleware(req: Request<Body>, next: Next) -> Response {
+    if req.method() != Method::GET {
+        return next.run(req).await;
+    }
+    let headers = req.headers().clone();
+    let response = next.run(req).await;
+    if response.status() != StatusCode::OK {
+        return response;
+    }
+
+    let (mut parts, body) = response.into_parts();
+    let body = match axum::body::to_bytes(body, usize::MAX).await {
+        Ok(body) => body,
+        Err(e) => {
+            warn!(error = %e, "Failed to buffer response for ETag");
+            return StatusCode::INTERNAL_SERVER_ERROR.into_response();
+        }
+    };
+    let etag = etag(&body);
+    let value = HeaderValue::from_str(&etag).expect("hex entity tag is a valid header value");
+    if if_none_match(&headers, &etag) {
+        return (StatusCode::NOT_MODIFIED, [(header::ETAG, value)]).into_response();
+    }
+    parts.headers.insert(header::ETAG, value);
+    Response::from_parts(parts, Body::from(body))
+}
+
 /// Error handling middleware
 pub async fn error_handling_middleware(
     req: Request<Body>,
@@ -71,6 +220,84 @@ mod tests {
         drop(cors);
     }
 
+    #[tokio::test]
+    async fn test_deadline_middleware() {
+        use axum::{middleware, routing::get, Router};
+        use tower::ServiceExt;
+
+        let app = Router::new()
+            .route(
+                "/",
+                get(|| async { format!("{:?}", deadline::remaining().is_some()) }),
+            )
+            .layer(middleware::from_fn_with_state(
+                Duration::from_secs(5),
+                deadline_middleware,
+            ));
+
+        let request = Request::get("/").body(Body::empty()).unwrap();
+        let response = app.oneshot(request).await.unwrap();
+        let body = axum::body::to_bytes(response.into_body(), usize::MAX)
+            .await
+            .unwrap();
+        assert_eq!(&body[..], b"true");
+        assert!(deadline::remaining().is_none());
+    }
+
+    #[test]
+    fn test_if_none_match() {
+        let tag = etag(b"[]");
+        assert!(tag.starts_with("W/\""));
+        assert_ne!(tag, etag(b"[1]"));
+
+        let mut headers = HeaderMap::new();
+        assert!(!if_none_match(&headers, &tag));
+
+        let strong = tag.trim_start_matches("W/").to_string();
+        headers.insert(
+            header::IF_NONE_MATCH,
+            HeaderValue::from_str(&format!("\"other\", {}", strong)).unwrap(),
+        );
+        assert!(if_none_match(&headers, &tag));
+
+        headers.insert(header::IF_NONE_MATCH, HeaderValue::from_static("*"));
+        assert!(if_none_match(&headers, &tag));
+    }
+
+    #[tokio::test]
+    async fn test_etag_middleware() {
+        use axum::{middleware, routing::get, Router};
+        use tower::ServiceExt;
+
+        let app = Router::new()
+            .route("/", get(|| async { "[1, 2, 3]" }))
+            .layer(middleware::from_fn(etag_middleware));
+
+        let request = Request::get("/").body(Body::empty()).unwrap();
+        let response = app.clone().oneshot(request).await.unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+        let tag = response.headers()[header::ETAG].clone();
+
+        let request = Request::get("/")
+            .header(header::IF_NONE_MATCH, tag.clone())
+            .body(Body::empty())
+            .unwrap();
+        let response = app.clone().oneshot(request).await.unwrap();
+        assert_eq!(response.status(), StatusCode::NOT_MODIFIED);
+        assert_eq!(response.headers()[header::ETAG], tag);
+        let body = axum::body::to_bytes(response.into_body(), usize::MAX)
+            .await
+            .unwrap();
+        assert!(body.is_empty());
+
+        let request = Request::get("/")
+            .header(header::IF_NONE_MATCH, "W/\"stale\"")
+            .body(Body::empty())
+            .unwrap();
+        let response = app.oneshot(request).await.unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+    }
+
     #[test]
     fn test_cors_specific_origins() {
         let cors = cors_middleware(vec![
@@ -1,20 +1,42 @@
 //! API route definitions.
 
 use axum::{
+    extract::DefaultBodyLimit,
+    http::StatusCode,
     middleware,
-    routing::get,
+    routing::{get, post, put},
     Router,
 };
 use std::sync::Arc;
-use tower_http::timeout::TimeoutLayer;
+use tower_http::{
+    compression::CompressionLayer, decompression::RequestDecompressionLayer, timeout::TimeoutLayer,
+};
 use std::time::Duration;
 
 use crate::{
-    handlers::{health::*, metrics::*, query::*},
-    middleware::{cors_middleware, logging_middleware},
-    ApiConfig,
+    access_log::{access_log_middleware, AccessLogger},
+    cache::{response_cache_middleware, ResponseCache},
+    handlers::{
+        availability::*, compare::*, cost::*, credentials::*, dedup::*, delivery::*, export::*, federation::*, fleet::*, health::*, incidents::*, lifecycle::*, metrics::*, noise::*, pricing::*, quality::*, query::*, redetect::*, search::*, selfcheck::*, similarity::*, simulation::*, slo::*, snapshot::*, stream::*, tuning::*,
+    },
+    idempotency::{idempotency_middleware, IdempotencyStore},
+    middleware::{
+        body_limit_middleware, correlation_middleware, cors_middleware, deadline_middleware,
+        etag_middleware, logging_middleware,
+    },
+    ui, ApiConfig,
 };
 
+/// Enforce a route class timeout and expose its deadline to storage queries
+fn with_timeout(router: Router, timeout: Duration) -> Router {
+    router
+        .layer(middleware::from_fn_with_state(timeout, deadline_middleware))
+        .layer(TimeoutLayer::with_status_code(
+            StatusCode::REQUEST_TIMEOUT,
+            timeout,
+        ))
+}
+
 /// Create the main API router
 pub fn create_router(
     config: ApiConfig,
@@ -22,11 +44,88 @@ pub fn create_router(
     metrics_state: Arc<MetricsState>,
     query_state: Arc<QueryState>,
 ) -> Router {
+    // Aggregate queries, optionally served from the response cache
+    let aggregates = Router::new()
+        .route("/anomalies/heatmap", get(anomaly_heatmap))
+        .route("/anomalies/compare", get(compare_anomalies))
+        .route("/costs", get(cost_report))
+        .route("/costs/forecast", get(cost_forecast))
+        .route("/alerts/sla", get(delivery_sla))
+        .route("/alerts/noise", get(alert_noise))
+        .route("/alerts/dedup/stats", get(dedup_stats))
+        .route("/detection/quality", get(detection_quality))
+        .route("/slos", get(slo_status))
+        .route("/availability", get(availability));
+    let aggregates = if config.response_cache.enabled {
+        let cache = Arc::new(ResponseCache::new(config.response_cache.clone()));
+        if let Some(watchdog) = &query_state.memory {
+            watchdog.register(cache.clone());
+        }
+        aggregates.route_layer(middleware::from_fn_with_state(
+            cache,
+            response_cache_middleware,
+        ))
+    } else {
+        aggregates
+    };
+
     // API v1 routes
     let api_v1 = Router::new()
-        .route("/telemetry", get(query_telemetry))
-        .route("/anomalies", get(query_anomalies))
-        .with_state(query_state);
+        .route(
+            "/telemetry",
+            get(query_telemetry).route_layer(middleware::from_fn(etag_middleware)),
+        )
+        .route("/telemetry/stream", get(stream_telemetry))
+        .route("/search", get(search_telemetry))
+        .route("/similar", post(similar_events))
+        .route(
+            "/anomalies",
+            get(query_anomalies).route_layer(middleware::from_fn(etag_middleware)),
+        )
+        .route("/anomalies/:id", get(get_anomaly))
+        .route("/anomalies/:id/state", post(update_anomaly_state))
+        .route("/anomalies/:id/history", get(anomaly_state_history))
+        .route("/anomalies/:id/fleet", get(anomaly_fleet_comparison))
+        .route("/anomalies/:id/annotations", post(add_anomaly_annotation))
+        .route("/incidents", get(list_incidents))
+        .route("/incidents/:id", get(get_incident))
+        .route("/incidents/:id/state", post(update_incident_state))
+        .route("/incidents/:id/links", post(add_incident_link))
+        .route("/alerts/deliveries", get(alert_deliveries))
+        .route("/alerts/:id/deliveries", get(alert_delivery_attempts))
+        .route("/alerts/:id/redeliver", post(redeliver_alert))
+        .route("/detection/stats", get(detection_stats))
+        .route("/pricing", get(list_pricing))
+        .route("/pricing/:model", put(update_pricing).delete(delete_pricing))
+        .route("/stream/anomalies", get(stream_anomalies))
+        .route("/admin/snapshots", get(list_snapshots).post(create_snapshot))
+        .route("/admin/snapshots/:id/restore", post(restore_snapshot))
+        .route("/admin/credentials/rotate", post(rotate_credentials))
+        .route("/admin/test-anomaly", post(simulate_anomaly))
+        .route("/admin/redetect", post(submit_redetection))
+        .route("/admin/redetect/:id", get(redetection_job))
+        .route("/admin/selfcheck", get(self_check))
+        .route("/admin/detection-config", get(export_detection_config))
+        .route("/admin/detection-config/stage", post(stage_detection_config))
+        .route("/federation/anomalies", post(receive_federated_anomalies))
+        .merge(aggregates)
+        .with_state(query_state.clone());
+    // Writes retried with the same Idempotency-Key are replayed
+    let api_v1 = if config.idempotency.enabled {
+        let store = Arc::new(IdempotencyStore::new(
+            &config.idempotency,
+            config.max_body_size,
+        ));
+        if let Some(watchdog) = &query_state.memory {
+            watchdog.register(store.clone());
+        }
+        api_v1.route_layer(middleware::from_fn_with_state(
+            store,
+            idempotency_middleware,
+        ))
+    } else {
+        api_v1
+    };
 
     // Health routes
     let health_routes = Router::new()
@@ -38,13 +137,59 @@ pub fn create_router(
     // Metrics route
     let metrics_route = Router::new()
         .route(&config.metrics_path, get(metrics_handler))
+        .route(
+            &format!("{}/catalog", config.metrics_path.trim_end_matches('/')),
+            get(metrics_catalog),
+        )
         .with_state(metrics_state);
 
+    // Per-class timeouts
+    let timeouts = &config.route_timeouts;
+    let timeout = |secs: Option<u64>| Duration::from_secs(secs.unwrap_or(config.timeout_secs));
+    let api_v1 = with_timeout(api_v1, timeout(timeouts.query_secs));
+    let health_routes = with_timeout(health_routes, timeout(timeouts.health_secs));
+    let metrics_route = with_timeout(metrics_route, timeout(timeouts.metrics_secs));
+
     // Combine all routes
     let app = Router::new()
         .nest("/api/v1", api_v1)
         .merge(health_routes)
         .merge(metrics_route);
+    let app = if config.dashboard {
+        app.merge(ui::router())
+    } else {
+        app
+    };
+
+    // Body limits apply to the decompressed body; the declared length of a
+    // compressed body is checked before it is inflated
+    let app = app.layer(DefaultBodyLimit::max(config.max_body_size));
+    let app = if config.compression {
+        app.layer(
+            RequestDecompressionLayer::new()
+                .gzip(true)
+                .zstd(true)
+                .br(false)
+                .deflate(false),
+        )
+    } else {
+        app
+    };
+    let app = app.layer(middleware::from_fn_with_state(
+        config.max_body_size,
+        body_limit_middleware,
+    ));
+    let app = if config.compression {
+        app.layer(
+            CompressionLayer::new()
+                .gzip(true)
+                .zstd(true)
+                .br(false)
+                .deflate(false),
+        )
+    } else {
+        app
+    };
 
     // Add middleware
     let app = if config.enable_logging {
@@ -54,10 +199,18 @@ pub fn create_router(
     };
 
     let app = app.layer(cors_middleware(config.cors_origins));
+    let app = app.layer(middleware::from_fn(correlation_middleware));
 
-    let app = app.layer(TimeoutLayer::new(Duration::from_secs(config.timeout_secs)));
-
-    app
+    // Outermost, so timed-out requests are logged too
+    if config.access_log.enabled {
+        let logger = Arc::new(AccessLogger::new(config.access_log));
+        app.layer(middleware::from_fn_with_state(
+            logger,
+            access_log_middleware,
+        ))
+    } else {
+        app
+    }
 }
 
 #[cfg(test)]
@@ -73,49 +226,161 @@ mod tests {
     impl Storage for MockStorage {
         async fn write_telemetry(
             &self,
-            _event: &sentinel_core::events::TelemetryEvent,
-        ) -> sentinel_core::Result<()> {
+            _event: &llm_sentinel_core::events::TelemetryEvent,
+        ) -> llm_sentinel_core::Result<()> {
             Ok(())
         }
 
         async fn write_anomaly(
             &self,
-            _anomaly: &sentinel_core::events::AnomalyEvent,
-        ) -> sentinel_core::Result<()> {
+            _anomaly: &llm_sentinel_core::events::AnomalyEvent,
+        ) -> llm_sentinel_core::Result<()> {
             Ok(())
         }
 
         async fn write_telemetry_batch(
             &self,
-            _events: &[sentinel_core::events::TelemetryEvent],
-        ) -> sentinel_core::Result<()> {
+            _events: &[llm_sentinel_core::events::TelemetryEvent],
+        ) -> llm_sentinel_core::Result<()> {
             Ok(())
         }
 
         async fn write_anomaly_batch(
             &self,
-            _anomalies: &[sentinel_core::events::AnomalyEvent],
-        ) -> sentinel_core::Result<()> {
+            _anomalies: &[llm_sentinel_core::events::AnomalyEvent],
+        ) -> llm_sentinel_core::Result<()> {
             Ok(())
         }
 
         async fn query_telemetry(
             &self,
-            _query: sentinel_storage::query::TelemetryQuery,
-        ) -> sentinel_core::Result<Vec<sentinel_core::events::TelemetryEvent>> {
+            _query: llm_sentinel_storage::query::TelemetryQuery,
+        ) -> llm_sentinel_core::Result<Vec<llm_sentinel_core::events::TelemetryEvent>> {
             Ok(Vec::new())
         }
 
         async fn query_anomalies(
             &self,
-            _query: sentinel_storage::query::AnomalyQuery,
-        ) -> sentinel_core::Result<Vec<sentinel_core::events::AnomalyEvent>> {
+            _query: llm_sentinel_storage::query::AnomalyQuery,
+        ) -> llm_sentinel_core::Result<Vec<llm_sentinel_core::events::AnomalyEvent>> {
+            Ok(Vec::new())
+        }
+
+        async fn get_anomaly(
+            &self,
+            _alert_id: uuid::Uuid,
+        ) -> llm_sentinel_core::Result<Option<llm_sentinel_core::events::AnomalyEvent>> {
+            Ok(None)
+        }
+
+        async fn write_anomaly_state_changes(
+            &self,
+            _changes: &[llm_sentinel_core::lifecycle::AnomalyStateChange],
+        ) -> llm_sentinel_core::Result<()> {
+            Ok(())
+        }
+
+        async fn query_anomaly_state_changes(
+            &self,
+            _alert_id: uuid::Uuid,
+        ) -> llm_sentinel_core::Result<Vec<llm_sentinel_core::lifecycle::AnomalyStateChange>>
+        {
+            Ok(Vec::new())
+        }
+
+        async fn write_anomaly_annotations(
+            &self,
+            _annotations: &[llm_sentinel_core::annotation::AnomalyAnnotation],
+        ) -> llm_sentinel_core::Result<()> {
+            Ok(())
+        }
+
+        async fn query_anomaly_annotations(
+            &self,
+            _query: llm_sentinel_storage::annotation::AnnotationQuery,
+        ) -> llm_sentinel_core::Result<Vec<llm_sentinel_core::annotation::AnomalyAnnotation>>
+        {
+            Ok(Vec::new())
+        }
+
+        async fn write_stream_offset(
+            &self,
+            _offset: &llm_sentinel_storage::stream::StreamOffset,
+        ) -> llm_sentinel_core::Result<()> {
+            Ok(())
+        }
+
+        async fn get_stream_offset(
+            &self,
+            _consumer_id: &str,
+        ) -> llm_sentinel_core::Result<Option<llm_sentinel_storage::stream::StreamOffset>> {
+            Ok(None)
+        }
+
+        async fn anomaly_heatmap(
+            &self,
+            _query: llm_sentinel_storage::query::HeatmapQuery,
+        ) -> llm_sentinel_core::Result<Vec<llm_sentinel_storage::query::HeatmapBucket>> {
+            Ok(Vec::new())
+        }
+
+        async fn write_cost_rollups(
+            &self,
+            _rollups: &[llm_sentinel_storage::cost::CostRollup],
+        ) -> llm_sentinel_core::Result<()> {
+            Ok(())
+        }
+
+        async fn query_cost_report(
+            &self,
+            _query: llm_sentinel_storage::cost::CostReportQuery,
+        ) -> llm_sentinel_core::Result<Vec<llm_sentinel_storage::cost::CostReportRow>> {
             Ok(Vec::new())
         }
 
-        async fn health_check(&self) -> sentinel_core::Result<()> {
+        async fn write_alert_deliveries(
+            &self,
+            _deliveries: &[llm_sentinel_core::events::AlertMetadata],
+        ) -> llm_sentinel_core::Result<()> {
+            Ok(())
+        }
+
+        async fn query_alert_deliveries(
+            &self,
+            _query: llm_sentinel_storage::delivery::DeliveryQuery,
+        ) -> llm_sentinel_core::Result<Vec<llm_sentinel_core::events::AlertMetadata>> {
+            Ok(Vec::new())
+        }
+
+        async fn write_incident(
+            &self,
+            _incident: &llm_sentinel_core::incident::Incident,
+        ) -> llm_sentinel_core::Result<()> {
+            Ok(())
+        }
+
+        async fn query_incidents(
+            &self,
+            _query: llm_sentinel_storage::incident::IncidentQuery,
+        ) -> llm_sentinel_core::Result<Vec<llm_sentinel_core::incident::Incident>> {
+            Ok(Vec::new())
+        }
+
+        async fn write_redetected_anomalies(
+            &self,
+            _job_id: uuid::Uuid,
+            _anomalies: &[llm_sentinel_core::events::AnomalyEvent],
+        ) -> llm_sentinel_core::Result<()> {
+            Ok(())
+        }
+
+        async fn health_check(&self) -> llm_sentinel_core::Result<()> {
             Ok(())
         }
+
+        fn upserts_by_id(&self) -> bool {
+            true
+        }
     }
 
     #[test]
@@ -137,4 +402,1725 @@ mod tests {
         // Just test that it creates without panicking
         drop(router);
     }
+
+    fn test_router(config: ApiConfig) -> Router {
+        let health_state = Arc::new(HealthState::new("0.1.0".to_string(), Arc::new(|| Ok(()))));
+        let query_state = Arc::new(QueryState::new(Arc::new(MockStorage)));
+        create_router(
+            config,
+            health_state,
+            Arc::new(MetricsState::new()),
+            query_state,
+        )
+    }
+
+    fn limited(max_body_size: usize) -> Router {
+        test_router(ApiConfig {
+            max_body_size,
+            ..Default::default()
+        })
+    }
+
+    fn similar_request() -> axum::http::request::Builder {
+        axum::http::Request::post("/api/v1/similar")
+            .header(axum::http::header::CONTENT_TYPE, "application/json")
+    }
+
+    fn gzip(body: &[u8]) -> Vec<u8> {
+        use std::io::Write;
+        let mut encoder = flate2::write::GzEncoder::new(Vec::new(), flate2::Compression::default());
+        encoder.write_all(body).unwrap();
+        encoder.finish().unwrap()
+    }
+
+    async fn error_code(response: axum::response::Response) -> String {
+        let body = axum::body::to_bytes(response.into_body(), usize::MAX)
+            .await
+            .unwrap();
+        let error: crate::ErrorResponse = serde_json::from_slice(&body).unwrap();
+        error.code
+    }
+
+    #[tokio::test]
+    async fn test_body_limit() {
+        use axum::{body::Body, http::header};
+        use tower::ServiceExt;
+
+        let oversized = format!(r#"{{"event_id": null, "service": "{}"}}"#, "x".repeat(100));
+
+        // Declared length
+        let request = similar_request()
+            .header(header::CONTENT_LENGTH, oversized.len())
+            .body(Body::from(oversized.clone()))
+            .unwrap();
+        let response = limited(64).oneshot(request).await.unwrap();
+        assert_eq!(response.status(), StatusCode::PAYLOAD_TOO_LARGE);
+        assert_eq!(error_code(response).await, "payload_too_large");
+
+        // Streamed without a length
+        let request = similar_request().body(Body::from(oversized)).unwrap();
+        let response = limited(64).oneshot(request).await.unwrap();
+        assert_eq!(response.status(), StatusCode::PAYLOAD_TOO_LARGE);
+        assert_eq!(error_code(response).await, "payload_too_large");
+
+        // Within the limit
+        let request = similar_request().body(Body::from("{}")).unwrap();
+        let response = limited(64).oneshot(request).await.unwrap();
+        assert_eq!(error_code(response).await, "similarity_disabled");
+    }
+
+    #[tokio::test]
+    async fn test_request_decompression() {
+        use axum::{body::Body, http::header};
+        use tower::ServiceExt;
+
+        let request = similar_request()
+            .header(header::CONTENT_ENCODING, "gzip")
+            .body(Body::from(gzip(b"{}")))
+            .unwrap();
+        let response = limited(64).oneshot(request).await.unwrap();
+        assert_eq!(error_code(response).await, "similarity_disabled");
+
+        // The limit applies to the inflated body
+        let bomb = gzip(format!("{{}}{}", " ".repeat(10_000)).as_bytes());
+        assert!(bomb.len() < 64);
+        let request = similar_request()
+            .header(header::CONTENT_ENCODING, "gzip")
+            .header(header::CONTENT_LENGTH, bomb.len())
+            .body(Body::from(bomb))
+            .unwrap();
+        let response = limited(64).oneshot(request).await.unwrap();
+        assert_eq!(response.status(), StatusCode::PAYLOAD_TOO_LARGE);
+    }
+
+    #[tokio::test]
+    async fn test_response_compression() {
+        use axum::{
+            body::Body,
+            http::{header, Request},
+        };
+        use tower::ServiceExt;
+
+        let request = Request::get("/health")
+            .header(header::ACCEPT_ENCODING, "zstd")
+            .body(Body::empty())
+            .unwrap();
+        let response = limited(64).oneshot(request).await.unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+        assert_eq!(response.headers()[header::CONTENT_ENCODING], "zstd");
+
+        let router = test_router(ApiConfig {
+            compression: false,
+            ..Default::default()
+        });
+        let request = Request::get("/health")
+            .header(header::ACCEPT_ENCODING, "zstd")
+            .body(Body::empty())
+            .unwrap();
+        let response = router.oneshot(request).await.unwrap();
+        assert!(!response.headers().contains_key(header::CONTENT_ENCODING));
+    }
+
+    #[tokio::test]
+    async fn test_dashboard() {
+        use axum::{
+            body::Body,
+            http::{header, Request},
+        };
+        use tower::ServiceExt;
+
+        let get = |uri: &str| Request::get(uri).body(Body::empty()).unwrap();
+        let router = test_router(ApiConfig::default());
+
+        let response = router.clone().oneshot(get("/ui")).await.unwrap();
+        assert_eq!(response.status(), StatusCode::PERMANENT_REDIRECT);
+
+        let response = router.clone().oneshot(get("/ui/")).await.unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+        assert!(response.headers()[header::CONTENT_TYPE]
+            .to_str()
+            .unwrap()
+            .starts_with("text/html"));
+
+        // Revalidated by ETag
+        let response = router.clone().oneshot(get("/ui/app.js")).await.unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+        let etag = response.headers()[header::ETAG].clone();
+        let request = Request::get("/ui/app.js")
+            .header(header::IF_NONE_MATCH, etag)
+            .body(Body::empty())
+            .unwrap();
+        let response = router.clone().oneshot(request).await.unwrap();
+        assert_eq!(response.status(), StatusCode::NOT_MODIFIED);
+
+        let response = router.oneshot(get("/ui/missing.js")).await.unwrap();
+        assert_eq!(response.status(), StatusCode::NOT_FOUND);
+
+        let router = test_router(ApiConfig {
+            dashboard: false,
+            ..Default::default()
+        });
+        let response = router.oneshot(get("/ui/")).await.unwrap();
+        assert_eq!(response.status(), StatusCode::NOT_FOUND);
+    }
+
+    #[tokio::test]
+    async fn test_correlation_id() {
+        use axum::{body::Body, http::Request};
+        use llm_sentinel_core::correlation;
+        use tower::ServiceExt;
+
+        let router = test_router(ApiConfig::default());
+
+        // Echoed when provided
+        let request = Request::get("/health/live")
+            .header(correlation::HEADER, "req-42")
+            .body(Body::empty())
+            .unwrap();
+        let response = router.clone().oneshot(request).await.unwrap();
+        assert_eq!(response.headers()[correlation::HEADER], "req-42");
+
+        // Generated when missing or invalid
+        let request = Request::get("/health/live")
+            .header(correlation::HEADER, "x".repeat(correlation::MAX_LEN + 1))
+            .body(Body::empty())
+            .unwrap();
+        let response = router.oneshot(request).await.unwrap();
+        let id = response.headers()[correlation::HEADER].to_str().unwrap();
+        assert!(correlation::is_valid(id));
+        assert_ne!(id.len(), correlation::MAX_LEN + 1);
+    }
+
+    #[test]
+    fn test_query_timeout_status() {
+        use crate::handlers::query_failed;
+        use llm_sentinel_core::Error;
+
+        let (status, body) = query_failed(&Error::timeout("deadline").context("Telemetry query"));
+        assert_eq!(status, StatusCode::GATEWAY_TIMEOUT);
+        assert_eq!(body.0.code, "query_timeout");
+
+        let (status, _) = query_failed(&Error::storage("unreachable"));
+        assert_eq!(status, StatusCode::INTERNAL_SERVER_ERROR);
+    }
+
+    #[tokio::test]
+    async fn test_slo_status_disabled() {
+        let state = Arc::new(QueryState::new(Arc::new(MockStorage)));
+        let result = slo_status(axum::extract::State(state)).await;
+        assert_eq!(result.unwrap_err().0, axum::http::StatusCode::NOT_FOUND);
+    }
+
+    #[tokio::test]
+    async fn test_slo_status() {
+        use llm_sentinel_detection::slo::{SloDefinition, SloObjective, SloTracker};
+
+        let tracker =
+            SloTracker::new(vec![SloDefinition::new("errors", SloObjective::ErrorRate, 0.99)])
+                .unwrap();
+        let state = Arc::new(
+            QueryState::new(Arc::new(MockStorage)).with_slo_tracker(Arc::new(tracker)),
+        );
+
+        let response = slo_status(axum::extract::State(state)).await.unwrap();
+        assert_eq!(response.0.data.len(), 1);
+        assert_eq!(response.0.data[0].name, "errors");
+        assert!(response.0.data[0].met);
+    }
+
+    #[tokio::test]
+    async fn test_availability() {
+        use axum::extract::{Query, State};
+        use llm_sentinel_detection::availability::{
+            AvailabilityTracker, AvailabilityTrackerConfig,
+        };
+
+        let params = || AvailabilityQueryParams {
+            provider: None,
+            model: None,
+            minutes: None,
+        };
+
+        let state = Arc::new(QueryState::new(Arc::new(MockStorage)));
+        let result = availability(State(state), Query(params())).await;
+        assert_eq!(result.unwrap_err().0, axum::http::StatusCode::NOT_FOUND);
+
+        let tracker = AvailabilityTracker::new(AvailabilityTrackerConfig::default()).unwrap();
+        let state = Arc::new(
+            QueryState::new(Arc::new(MockStorage)).with_availability_tracker(Arc::new(tracker)),
+        );
+
+        let response = availability(State(state.clone()), Query(params())).await.unwrap();
+        assert!(response.0.data.is_empty());
+
+        let result = availability(
+            State(state),
+            Query(AvailabilityQueryParams {
+                minutes: Some(0),
+                ..params()
+            }),
+        )
+        .await;
+        assert_eq!(result.unwrap_err().0, axum::http::StatusCode::BAD_REQUEST);
+    }
+
+    #[tokio::test]
+    async fn test_pricing() {
+        use axum::{
+            extract::{Path, State},
+            http::StatusCode,
+            Json,
+        };
+        use llm_sentinel_core::pricing::{ModelPrice, PriceTable};
+
+        let state = Arc::new(QueryState::new(Arc::new(MockStorage)));
+        let result = list_pricing(State(state)).await;
+        assert_eq!(result.unwrap_err().0, StatusCode::NOT_FOUND);
+
+        let state = Arc::new(
+            QueryState::new(Arc::new(MockStorage)).with_pricing(Arc::new(PriceTable::new())),
+        );
+
+        let price = ModelPrice::new(0.001, 0.002);
+        let response =
+            update_pricing(State(state.clone()), Path("My-Model".to_string()), Json(price))
+                .await
+                .unwrap();
+        assert_eq!(response.0.data, price);
+        let response = list_pricing(State(state.clone())).await.unwrap();
+        assert_eq!(response.0.data["my-model"], price);
+
+        let result = update_pricing(
+            State(state.clone()),
+            Path("my-model".to_string()),
+            Json(ModelPrice::new(-1.0, 0.0)),
+        )
+        .await;
+        assert_eq!(result.unwrap_err().0, StatusCode::BAD_REQUEST);
+
+        let response = delete_pricing(State(state.clone()), Path("my-model".to_string()))
+            .await
+            .unwrap();
+        assert_eq!(response.0.data, price);
+        let result = delete_pricing(State(state), Path("my-model".to_string())).await;
+        assert_eq!(result.unwrap_err().0, StatusCode::NOT_FOUND);
+    }
+
+    #[tokio::test]
+    async fn test_delivery_sla() {
+        use axum::extract::{Query, State};
+
+        let params = |severity: Option<&str>| DeliverySlaParams {
+            alerter: None,
+            severity: severity.map(str::to_string),
+            start: None,
+            end: None,
+            hours: Some(24),
+        };
+
+        let state = Arc::new(QueryState::new(Arc::new(MockStorage)));
+        let response = delivery_sla(State(state.clone()), Query(params(Some("critical"))))
+            .await
+            .unwrap();
+        assert!(response.0.data.compliant);
+        assert!(response.0.data.rows.is_empty());
+
+        let result = delivery_sla(State(state), Query(params(Some("urgent")))).await;
+        assert_eq!(result.unwrap_err().0, axum::http::StatusCode::BAD_REQUEST);
+    }
+
+    #[tokio::test]
+    async fn test_alert_deliveries() {
+        use axum::extract::{Query, State};
+        use llm_sentinel_core::{
+            events::{AlertMetadata, AlertStatus},
+            types::Severity,
+        };
+        use llm_sentinel_storage::memory::InMemoryStorage;
+
+        let now = chrono::Utc::now();
+        let delivery = |alert_id: &str, response: Option<&str>| AlertMetadata {
+            alert_id: alert_id.to_string(),
+            alerter: "webhook".to_string(),
+            severity: Severity::High,
+            anomaly_timestamp: now,
+            attempts: 1,
+            last_attempt: now,
+            status: AlertStatus::Delivered,
+            latency_ms: Some(120.0),
+            error: None,
+            request: Some("POST https://hooks.example.com/alerts".to_string()),
+            response: response.map(str::to_string),
+        };
+        let storage = Arc::new(InMemoryStorage::new());
+        storage
+            .write_alert_deliveries(&[
+                delivery("a1", Some(r#"{"ticket":"INC-1042"}"#)),
+                delivery("a2", None),
+            ])
+            .await
+            .unwrap();
+        let params = |alert_id: Option<&str>, severity: Option<&str>| DeliveryHistoryParams {
+            alerter: None,
+            severity: severity.map(str::to_string),
+            alert_id: alert_id.map(str::to_string),
+            start: None,
+            end: None,
+            hours: Some(1),
+        };
+
+        let state = Arc::new(QueryState::new(storage));
+        let response = alert_deliveries(State(state.clone()), Query(params(None, None)))
+            .await
+            .unwrap();
+        assert_eq!(response.0.data.len(), 2);
+
+        let response = alert_deliveries(State(state.clone()), Query(params(Some("a1"), None)))
+            .await
+            .unwrap();
+        assert_eq!(response.0.data.len(), 1);
+        assert_eq!(
+            response.0.data[0].response.as_deref(),
+            Some(r#"{"ticket":"INC-1042"}"#)
+        );
+
+        let result = alert_deliveries(State(state), Query(params(None, Some("urgent")))).await;
+        assert_eq!(result.unwrap_err().0, axum::http::StatusCode::BAD_REQUEST);
+    }
+
+    #[tokio::test]
+    async fn test_alert_delivery_attempts_and_redelivery() {
+        use axum::{
+            extract::{Path, State},
+            http::StatusCode,
+            Json,
+        };
+        use llm_sentinel_core::events::{AlertMetadata, AlertStatus};
+        use llm_sentinel_storage::memory::InMemoryStorage;
+
+        struct MockRedelivery(Arc<InMemoryStorage>);
+
+        #[async_trait::async_trait]
+        impl AlertRedelivery for MockRedelivery {
+            async fn redeliver(
+                &self,
+                alert_id: uuid::Uuid,
+                alerter: &str,
+            ) -> llm_sentinel_core::Result<AlertMetadata> {
+                if alerter != "Webhook" {
+                    return Err(llm_sentinel_core::Error::not_found(alerter));
+                }
+                let anomaly = self
+                    .0
+                    .get_anomaly(alert_id)
+                    .await?
+                    .ok_or_else(|| llm_sentinel_core::Error::not_found(alert_id.to_string()))?;
+                let mut delivery = AlertMetadata::pending(&anomaly, alerter);
+                delivery.mark_delivered(chrono::Utc::now());
+                self.0.write_alert_deliveries(&[delivery.clone()]).await?;
+                Ok(delivery)
+            }
+        }
+
+        let anomaly = llm_sentinel_fixtures::anomaly().service("chat").build();
+        let alert_id = anomaly.alert_id;
+        let mut failed = AlertMetadata::pending(&anomaly, "Webhook");
+        failed.mark_failed(chrono::Utc::now(), "status 503 Service Unavailable: ");
+        let storage = Arc::new(InMemoryStorage::new());
+        storage.write_anomaly(&anomaly).await.unwrap();
+        storage.write_alert_deliveries(&[failed]).await.unwrap();
+
+        let request = |alerter: &str| {
+            Json(RedeliveryRequest {
+                alerter: alerter.to_string(),
+            })
+        };
+        let state = Arc::new(QueryState::new(storage.clone()));
+        let result =
+            redeliver_alert(State(state), Path(alert_id.to_string()), request("Webhook")).await;
+        assert_eq!(result.unwrap_err().0, StatusCode::NOT_FOUND);
+
+        let state = Arc::new(
+            QueryState::new(storage.clone())
+                .with_redelivery(Arc::new(MockRedelivery(storage.clone()))),
+        );
+        let delivery = redeliver_alert(
+            State(state.clone()),
+            Path(alert_id.to_string()),
+            request("Webhook"),
+        )
+        .await
+        .unwrap();
+        assert_eq!(delivery.0.data.status, AlertStatus::Delivered);
+        let result = redeliver_alert(
+            State(state.clone()),
+            Path(alert_id.to_string()),
+            request("Slack"),
+        )
+        .await;
+        assert_eq!(result.unwrap_err().0, StatusCode::NOT_FOUND);
+
+        let attempts = alert_delivery_attempts(State(state.clone()), Path(alert_id.to_string()))
+            .await
+            .unwrap();
+        let statuses: Vec<AlertStatus> = attempts.0.data.iter().map(|d| d.status).collect();
+        assert_eq!(statuses, [AlertStatus::Failed, AlertStatus::Delivered]);
+        assert!(attempts.0.data[0].error.as_deref().unwrap().contains("503"));
+
+        let missing =
+            alert_delivery_attempts(State(state.clone()), Path(uuid::Uuid::new_v4().to_string()))
+                .await;
+        assert_eq!(missing.unwrap_err().0, StatusCode::NOT_FOUND);
+        let invalid = alert_delivery_attempts(State(state), Path("nope".to_string())).await;
+        assert_eq!(invalid.unwrap_err().0, StatusCode::BAD_REQUEST);
+    }
+
+    #[tokio::test]
+    async fn test_dedup_stats() {
+        use axum::{extract::State, http::StatusCode};
+
+        let state = Arc::new(QueryState::new(Arc::new(MockStorage)));
+        let result = dedup_stats(State(state)).await;
+        assert_eq!(result.unwrap_err().0, StatusCode::NOT_FOUND);
+
+        let state = Arc::new(QueryState::new(Arc::new(MockStorage)).with_dedup_stats(Arc::new(
+            || DedupStatsReport {
+                active_signatures: 2,
+                total_deduplicated: 6,
+                deduplication_rate: 0.75,
+                by_severity: [("high".to_string(), 6)].into_iter().collect(),
+            },
+        )));
+        let response = dedup_stats(State(state)).await.unwrap();
+        assert_eq!(response.0.data.active_signatures, 2);
+        assert_eq!(response.0.data.by_severity["high"], 6);
+    }
+
+    #[tokio::test]
+    async fn test_detection_quality() {
+        use axum::extract::{Query, State};
+        use llm_sentinel_detection::{
+            engine::{DetectionEngine, EngineConfig, EngineStats},
+            DetectorStats,
+        };
+        use std::collections::HashMap;
+
+        struct MockStats;
+
+        #[async_trait::async_trait]
+        impl DetectionStatsSource for MockStats {
+            async fn detector_stats(&self) -> Vec<(String, DetectorStats)> {
+                let mut stats = DetectorStats::empty();
+                stats.update(false, None);
+                vec![("zscore".to_string(), stats)]
+            }
+
+            fn deduplicated_by_detector(&self) -> HashMap<String, u64> {
+                HashMap::from([("iqr".to_string(), 4)])
+            }
+
+            async fn engine_stats(&self) -> EngineStats {
+                let engine = DetectionEngine::new(EngineConfig::default()).unwrap();
+                let load = engine.load();
+                load.record_sampled_out();
+                load.record_detector("zscore", true);
+                engine.stats().await
+            }
+        }
+
+        // Without live statistics only stored anomalies are reported
+        let state = Arc::new(QueryState::new(Arc::new(MockStorage)));
+        let response = detection_quality(State(state), Query(QualityQueryParams::default()))
+            .await
+            .unwrap();
+        assert!(response.0.data.is_empty());
+
+        let state = Arc::new(
+            QueryState::new(Arc::new(MockStorage)).with_detection_stats(Arc::new(MockStats)),
+        );
+        let response =
+            detection_quality(State(state.clone()), Query(QualityQueryParams::default()))
+                .await
+                .unwrap();
+        let report = response.0.data;
+        assert_eq!(report.len(), 2);
+        assert_eq!(report[0].detector, "iqr");
+        assert_eq!(report[0].deduplicated, 4);
+        assert_eq!(report[1].events_processed, Some(1));
+        assert_eq!(report[1].precision, None);
+
+        let response = detection_stats(State(state)).await.unwrap();
+        let stats = response.0.data;
+        assert_eq!(stats.recent.sampled_out, 1);
+        assert_eq!(stats.recent.skipped_rate, 1.0);
+        assert_eq!(stats.lifetime.detectors["zscore"].error_rate, 1.0);
+        let state = Arc::new(QueryState::new(Arc::new(MockStorage)));
+        let result = detection_stats(State(state)).await;
+        assert_eq!(result.unwrap_err().0, StatusCode::NOT_FOUND);
+    }
+
+    #[tokio::test]
+    async fn test_cost_forecast() {
+        use axum::extract::{Query, State};
+        use llm_sentinel_core::config::{CostBudgetConfig, ForecastMethod};
+        use llm_sentinel_storage::budget::BudgetForecaster;
+
+        let state = Arc::new(QueryState::new(Arc::new(MockStorage)));
+        let result = cost_forecast(State(state), Query(CostForecastParams::default())).await;
+        assert_eq!(result.unwrap_err().0, StatusCode::NOT_FOUND);
+
+        let forecaster = BudgetForecaster::new(CostBudgetConfig {
+            monthly_usd: [("chat", 100.0), ("search", 50.0)]
+                .into_iter()
+                .map(|(service, budget)| (service.to_string(), budget))
+                .collect(),
+            method: ForecastMethod::Linear,
+            lookback_days: 14,
+            margin: 0.1,
+            alpha: 0.5,
+            beta: 0.3,
+            check_interval_secs: 3600,
+            severity: llm_sentinel_core::types::Severity::High,
+        })
+        .unwrap();
+        let state =
+            Arc::new(QueryState::new(Arc::new(MockStorage)).with_budgets(Arc::new(forecaster)));
+        let params = CostForecastParams {
+            service: Some("search".to_string()),
+        };
+        let response = cost_forecast(State(state), Query(params)).await.unwrap();
+        let projections = response.0.data;
+        assert_eq!(projections.len(), 1);
+        assert_eq!(projections[0].service.as_str(), "search");
+        assert_eq!(projections[0].projected_usd, 0.0);
+        assert!(!projections[0].over_budget);
+    }
+
+    #[tokio::test]
+    async fn test_snapshot_endpoints() {
+        use axum::{
+            extract::{Path, State},
+            http::StatusCode,
+        };
+        use llm_sentinel_core::snapshot::{RuntimeSnapshot, SnapshotInfo};
+
+        struct MockSnapshots;
+
+        #[async_trait::async_trait]
+        impl SnapshotControl for MockSnapshots {
+            async fn snapshot(&self) -> llm_sentinel_core::Result<SnapshotInfo> {
+                Ok(RuntimeSnapshot::new("0.1.0", chrono::Utc::now()).info())
+            }
+
+            async fn restore(&self, id: &str) -> llm_sentinel_core::Result<SnapshotInfo> {
+                Err(llm_sentinel_core::Error::not_found(format!("Snapshot {}", id)))
+            }
+
+            async fn list(&self) -> llm_sentinel_core::Result<Vec<SnapshotInfo>> {
+                Ok(Vec::new())
+            }
+        }
+
+        let state = Arc::new(QueryState::new(Arc::new(MockStorage)));
+        let result = list_snapshots(State(state)).await;
+        assert_eq!(result.unwrap_err().0, StatusCode::NOT_FOUND);
+
+        let state = Arc::new(
+            QueryState::new(Arc::new(MockStorage)).with_snapshots(Arc::new(MockSnapshots)),
+        );
+        let (status, _) = create_snapshot(State(state.clone())).await.unwrap();
+        assert_eq!(status, StatusCode::CREATED);
+        let response = list_snapshots(State(state.clone())).await.unwrap();
+        assert!(response.0.data.is_empty());
+        let result = restore_snapshot(State(state), Path("missing".to_string())).await;
+        assert_eq!(result.unwrap_err().0, StatusCode::NOT_FOUND);
+    }
+
+    #[tokio::test]
+    async fn test_detection_config_endpoints() {
+        use axum::{
+            extract::{Query, State},
+            http::StatusCode,
+        };
+        use llm_sentinel_core::{
+            config::Config,
+            tuning::{DetectionDocument, SectionChange},
+        };
+
+        struct MockTuning;
+
+        #[async_trait::async_trait]
+        impl DetectionConfigControl for MockTuning {
+            fn export(&self) -> llm_sentinel_core::Result<DetectionDocument> {
+                Ok(DetectionDocument::from_config(&Config::default_test()))
+            }
+
+            async fn stage(
+                &self,
+                document: DetectionDocument,
+                dry_run: bool,
+            ) -> llm_sentinel_core::Result<Vec<SectionChange>> {
+                if !dry_run {
+                    return Err(llm_sentinel_core::Error::config("No detection file"));
+                }
+                Ok(document.plan(&Config::default_test())?.changes)
+            }
+        }
+
+        let state = Arc::new(QueryState::new(Arc::new(MockStorage)));
+        let result = export_detection_config(State(state)).await;
+        assert_eq!(result.unwrap_err().0, StatusCode::NOT_FOUND);
+
+        let state =
+            Arc::new(QueryState::new(Arc::new(MockStorage)).with_tuning(Arc::new(MockTuning)));
+        let response = export_detection_config(State(state.clone())).await.unwrap();
+        assert_eq!(
+            response.headers()[axum::http::header::CONTENT_TYPE],
+            YAML_CONTENT_TYPE
+        );
+
+        let report = stage_detection_config(
+            State(state.clone()),
+            Query(DetectionStageParams { dry_run: true }),
+            "version: 1\n".to_string(),
+        )
+        .await
+        .unwrap()
+        .0
+        .data;
+        assert!(!report.staged);
+        assert!(report
+            .changes
+            .iter()
+            .any(|change| change.section == "detection.engines"));
+
+        let result = stage_detection_config(
+            State(state.clone()),
+            Query(DetectionStageParams { dry_run: true }),
+            "version: 9\n".to_string(),
+        )
+        .await;
+        assert_eq!(result.unwrap_err().0, StatusCode::BAD_REQUEST);
+
+        let result = stage_detection_config(
+            State(state),
+            Query(DetectionStageParams { dry_run: false }),
+            "version: 1\n".to_string(),
+        )
+        .await;
+        assert_eq!(result.unwrap_err().0, StatusCode::CONFLICT);
+    }
+
+    #[tokio::test]
+    async fn test_rotate_credentials_endpoint() {
+        use axum::{extract::State, http::StatusCode};
+
+        struct MockCredentials;
+
+        #[async_trait::async_trait]
+        impl CredentialControl for MockCredentials {
+            async fn rotate(&self) -> llm_sentinel_core::Result<Vec<String>> {
+                Ok(vec!["influxdb".to_string()])
+            }
+        }
+
+        let state = Arc::new(QueryState::new(Arc::new(MockStorage)));
+        let result = rotate_credentials(State(state)).await;
+        assert_eq!(result.unwrap_err().0, StatusCode::NOT_FOUND);
+
+        let state = Arc::new(
+            QueryState::new(Arc::new(MockStorage)).with_credentials(Arc::new(MockCredentials)),
+        );
+        let response = rotate_credentials(State(state)).await.unwrap();
+        assert_eq!(response.0.data.rotated, vec!["influxdb"]);
+    }
+
+    #[tokio::test]
+    async fn test_simulate_anomaly_endpoint() {
+        use axum::{extract::State, http::StatusCode, Json};
+        use llm_sentinel_core::{
+            events::AnomalyEvent,
+            simulation::{is_test, SimulationReport, TestAnomalyRequest},
+            types::{AnomalyType, ServiceId, Severity},
+        };
+        use std::sync::Mutex;
+
+        #[derive(Default)]
+        struct MockSimulator(Mutex<Vec<AnomalyEvent>>);
+
+        #[async_trait::async_trait]
+        impl AnomalySimulator for MockSimulator {
+            async fn simulate(
+                &self,
+                anomaly: AnomalyEvent,
+            ) -> llm_sentinel_core::Result<SimulationReport> {
+                let report = SimulationReport {
+                    alert_id: anomaly.alert_id,
+                    deduplicated: false,
+                    expected: None,
+                    routed: vec!["pagerduty".to_string()],
+                };
+                self.0.lock().unwrap().push(anomaly);
+                Ok(report)
+            }
+        }
+
+        let request = || TestAnomalyRequest {
+            severity: Severity::Critical,
+            anomaly_type: AnomalyType::LatencySpike,
+            service: ServiceId::new("checkout"),
+            model: None,
+            environment: Some("production".to_string()),
+            labels: [("team".to_string(), "payments".to_string())].into(),
+        };
+
+        let state = Arc::new(QueryState::new(Arc::new(MockStorage)));
+        let result = simulate_anomaly(State(state), Json(request())).await;
+        assert_eq!(result.unwrap_err().0, StatusCode::NOT_FOUND);
+
+        let simulator = Arc::new(MockSimulator::default());
+        let state =
+            Arc::new(QueryState::new(Arc::new(MockStorage)).with_simulator(simulator.clone()));
+        let response = simulate_anomaly(State(state), Json(request()))
+            .await
+            .unwrap();
+        assert_eq!(response.0.data.routed, vec!["pagerduty"]);
+
+        let sent = simulator.0.lock().unwrap();
+        assert_eq!(sent.len(), 1);
+        assert_eq!(sent[0].alert_id, response.0.data.alert_id);
+        assert_eq!(sent[0].severity, Severity::Critical);
+        assert_eq!(sent[0].service_name.as_str(), "checkout");
+        assert_eq!(sent[0].labels["team"], "payments");
+        assert!(is_test(&sent[0]));
+    }
+
+    #[tokio::test]
+    async fn test_redetection_endpoints() {
+        use axum::{
+            extract::{Path, State},
+            http::StatusCode,
+            Json,
+        };
+        use llm_sentinel_detection::redetect::{
+            DetectorSettings, RedetectJob, RedetectRequest, RedetectStatus,
+        };
+        use std::sync::Mutex;
+
+        #[derive(Default)]
+        struct MockRedetector(Mutex<Vec<RedetectJob>>);
+
+        #[async_trait::async_trait]
+        impl Redetector for MockRedetector {
+            async fn submit(
+                &self,
+                request: RedetectRequest,
+            ) -> llm_sentinel_core::Result<RedetectJob> {
+                let job = RedetectJob::new(&request);
+                self.0.lock().unwrap().push(job.clone());
+                Ok(job)
+            }
+
+            async fn job(&self, job_id: uuid::Uuid) -> llm_sentinel_core::Result<RedetectJob> {
+                self.0
+                    .lock()
+                    .unwrap()
+                    .iter()
+                    .find(|job| job.job_id == job_id)
+                    .cloned()
+                    .ok_or_else(|| llm_sentinel_core::Error::not_found(job_id.to_string()))
+            }
+        }
+
+        let end = chrono::Utc::now();
+        let request = |detectors: Vec<&str>| RedetectRequest {
+            start: end - chrono::Duration::days(1),
+            end,
+            max_events: 1000,
+            settings: DetectorSettings {
+                detectors: detectors.into_iter().map(String::from).collect(),
+                zscore_threshold: Some(4.0),
+                ..Default::default()
+            },
+        };
+
+        let state = Arc::new(QueryState::new(Arc::new(MockStorage)));
+        let result = submit_redetection(State(state), Json(request(vec!["zscore"]))).await;
+        assert_eq!(result.unwrap_err().0, StatusCode::NOT_FOUND);
+
+        let redetector = Arc::new(MockRedetector::default());
+        let state = Arc::new(QueryState::new(Arc::new(MockStorage)).with_redetector(redetector));
+
+        let result = submit_redetection(State(state.clone()), Json(request(vec!["nope"]))).await;
+        assert_eq!(result.unwrap_err().0, StatusCode::BAD_REQUEST);
+
+        let (status, response) =
+            submit_redetection(State(state.clone()), Json(request(vec!["zscore"])))
+                .await
+                .unwrap();
+        assert_eq!(status, StatusCode::ACCEPTED);
+        let job_id = response.0.data.job_id;
+        assert_eq!(response.0.data.status, RedetectStatus::Running);
+
+        let job = redetection_job(State(state.clone()), Path(job_id))
+            .await
+            .unwrap();
+        assert_eq!(job.0.data.settings.zscore_threshold, Some(4.0));
+        let missing = redetection_job(State(state), Path(uuid::Uuid::new_v4())).await;
+        assert_eq!(missing.unwrap_err().0, StatusCode::NOT_FOUND);
+    }
+
+    #[tokio::test]
+    async fn test_self_check() {
+        use axum::{extract::State, http::StatusCode};
+        use llm_sentinel_core::selfcheck::{CheckKind, CheckResult, SelfCheckReport};
+
+        struct MockChecker;
+
+        #[async_trait::async_trait]
+        impl SelfChecker for MockChecker {
+            async fn self_check(&self) -> llm_sentinel_core::Result<SelfCheckReport> {
+                Ok(SelfCheckReport::new(vec![
+                    CheckResult::pass("kafka", CheckKind::Connectivity, "1 broker(s) answered"),
+                    CheckResult::fail("kafka", CheckKind::Permission, "not authorized"),
+                    CheckResult::skip("kafka", CheckKind::Schema),
+                ]))
+            }
+        }
+
+        let state = Arc::new(QueryState::new(Arc::new(MockStorage)));
+        let result = self_check(State(state)).await;
+        assert_eq!(result.unwrap_err().0, StatusCode::NOT_FOUND);
+
+        let state = Arc::new(
+            QueryState::new(Arc::new(MockStorage)).with_self_checker(Arc::new(MockChecker)),
+        );
+        let report = self_check(State(state)).await.unwrap().0.data;
+        assert!(!report.passed);
+        assert_eq!(report.failures().count(), 1);
+
+        let json = serde_json::to_value(&report).unwrap();
+        assert_eq!(json["results"][1]["check"], "permission");
+        assert_eq!(json["results"][1]["status"], "fail");
+        assert_eq!(json["results"][2]["status"], "skip");
+    }
+
+    #[tokio::test]
+    async fn test_query_by_labels() {
+        use axum::{
+            extract::{Query, State},
+            http::StatusCode,
+        };
+        use llm_sentinel_storage::memory::InMemoryStorage;
+
+        let event = |team: &str| {
+            llm_sentinel_fixtures::event()
+                .service("checkout")
+                .label("team", team)
+                .build()
+        };
+        let storage = Arc::new(InMemoryStorage::new());
+        storage
+            .write_telemetry_batch(&[event("payments"), event("search")])
+            .await
+            .unwrap();
+
+        let params = |labels: &str| TelemetryQueryParams {
+            service: None,
+            model: None,
+            environment: None,
+            labels: Some(labels.to_string()),
+            start: None,
+            end: None,
+            hours: Some(1),
+            limit: None,
+            offset: None,
+            ascending: None,
+            sort: None,
+            cursor: None,
+            fields: None,
+        };
+        let state = Arc::new(QueryState::new(storage));
+        let response = query_telemetry(State(state.clone()), Query(params("team=search")))
+            .await
+            .unwrap();
+        assert_eq!(response.0.data.len(), 1);
+        assert_eq!(response.0.data[0]["labels"]["team"], "search");
+
+        let result = query_telemetry(State(state), Query(params("team"))).await;
+        assert_eq!(result.unwrap_err().0, StatusCode::BAD_REQUEST);
+    }
+
+    #[tokio::test]
+    async fn test_query_sort_fields_and_cursor() {
+        use crate::handlers::query::{query_telemetry, QueryState, TelemetryQueryParams};
+        use axum::extract::{Query, State};
+        use llm_sentinel_storage::memory::InMemoryStorage;
+
+        let event = |latency_ms: f64| {
+            llm_sentinel_fixtures::event()
+                .service("checkout")
+                .latency_ms(latency_ms)
+                .build()
+        };
+        let storage = Arc::new(InMemoryStorage::new());
+        storage
+            .write_telemetry_batch(&[event(200.0), event(900.0), event(500.0)])
+            .await
+            .unwrap();
+        let state = Arc::new(QueryState::new(storage));
+
+        let params = |cursor: Option<String>| TelemetryQueryParams {
+            hours: Some(1),
+            limit: Some(2),
+            sort: Some("latency".to_string()),
+            fields: Some("latency_ms".to_string()),
+            cursor,
+            ..Default::default()
+        };
+        let first = query_telemetry(State(state.clone()), Query(params(None)))
+            .await
+            .unwrap()
+            .0;
+        let latencies: Vec<_> = first.data.iter().map(|e| e["latency_ms"].clone()).collect();
+        assert_eq!(latencies, vec![900.0, 500.0]);
+        let keys: Vec<_> = first.data[0].as_object().unwrap().keys().cloned().collect();
+        assert_eq!(keys, vec!["event_id", "latency_ms"]);
+
+        let cursor = first.metadata.unwrap().next_cursor;
+        assert!(cursor.is_some());
+        let second = query_telemetry(State(state.clone()), Query(params(cursor)))
+            .await
+            .unwrap()
+            .0;
+        assert_eq!(second.data.len(), 1);
+        assert_eq!(second.data[0]["latency_ms"], 200.0);
+        assert!(second.metadata.unwrap().next_cursor.is_none());
+
+        let bad = TelemetryQueryParams {
+            cursor: Some("garbage".to_string()),
+            ..params(None)
+        };
+        let result = query_telemetry(State(state.clone()), Query(bad)).await;
+        assert_eq!(result.unwrap_err().0, StatusCode::BAD_REQUEST);
+        let bad = TelemetryQueryParams {
+            sort: Some("prompt".to_string()),
+            ..params(None)
+        };
+        let result = query_telemetry(State(state), Query(bad)).await;
+        assert_eq!(result.unwrap_err().0, StatusCode::BAD_REQUEST);
+    }
+
+    #[tokio::test]
+    async fn test_search_endpoint() {
+        use crate::handlers::{
+            query::QueryState,
+            search::{search_telemetry, SearchParams},
+        };
+        use axum::extract::{Query, State};
+        use llm_sentinel_core::{events::TelemetryEvent, types::ServiceId, Error, Result};
+        use llm_sentinel_storage::{
+            memory::InMemoryStorage,
+            search::{SearchHit, SearchQuery, TextSearch},
+        };
+
+        /// Substring search over a fixed set of prompts
+        struct FakeSearch(Vec<(&'static str, &'static str)>);
+
+        impl TextSearch for FakeSearch {
+            fn index(&self, _events: &[TelemetryEvent]) -> Result<()> {
+                Ok(())
+            }
+
+            fn commit(&self) -> Result<()> {
+                Ok(())
+            }
+
+            fn search(&self, query: &SearchQuery) -> Result<Vec<SearchHit>> {
+                if query.text.contains('(') {
+                    return Err(Error::validation("Invalid search query"));
+                }
+                Ok(self
+                    .0
+                    .iter()
+                    .filter(|(service, prompt)| {
+                        prompt.contains(&query.text)
+                            && query
+                                .service
+                                .as_ref()
+                                .map_or(true, |s| s.as_str() == *service)
+                    })
+                    .take(query.limit)
+                    .map(|(service, prompt)| SearchHit {
+                        event_id: uuid::Uuid::new_v4(),
+                        timestamp: chrono::Utc::now(),
+                        service_name: ServiceId::new(*service),
+                        model: llm_sentinel_core::types::ModelId::new("gpt-4"),
+                        environment: None,
+                        score: 1.0,
+                        prompt: prompt.to_string(),
+                        response: String::new(),
+                    })
+                    .collect())
+            }
+        }
+
+        let storage = Arc::new(InMemoryStorage::new());
+        let params = |q: &str| SearchParams {
+            q: Some(q.to_string()),
+            ..Default::default()
+        };
+
+        // Disabled without an index
+        let state = Arc::new(QueryState::new(storage.clone()));
+        let result = search_telemetry(State(state), Query(params("refund"))).await;
+        assert_eq!(result.unwrap_err().0, StatusCode::NOT_FOUND);
+
+        let search = FakeSearch(vec![
+            ("checkout", "where is my refund"),
+            ("search", "refund policy"),
+            ("checkout", "hello"),
+        ]);
+        let state = Arc::new(QueryState::new(storage).with_search(Arc::new(search)));
+        let response = search_telemetry(State(state.clone()), Query(params("refund")))
+            .await
+            .unwrap();
+        assert_eq!(response.0.data.len(), 2);
+
+        let scoped = SearchParams {
+            service: Some("checkout".to_string()),
+            ..params("refund")
+        };
+        let response = search_telemetry(State(state.clone()), Query(scoped))
+            .await
+            .unwrap();
+        assert_eq!(response.0.data.len(), 1);
+        assert_eq!(response.0.data[0].prompt, "where is my refund");
+
+        let result = search_telemetry(State(state.clone()), Query(params(" "))).await;
+        assert_eq!(result.unwrap_err().0, StatusCode::BAD_REQUEST);
+        let result = search_telemetry(State(state), Query(params("prompt:("))).await;
+        assert_eq!(result.unwrap_err().0, StatusCode::BAD_REQUEST);
+    }
+
+    #[tokio::test]
+    async fn test_similar_events() {
+        use crate::handlers::{
+            query::QueryState,
+            similarity::{similar_events, SimilarRequest},
+        };
+        use axum::{extract::State, Json};
+        use llm_sentinel_storage::{
+            memory::InMemoryStorage,
+            similarity::{FlatVectorIndex, VectorIndex},
+        };
+
+        let event = |embedding: Vec<f32>, text: &str| {
+            llm_sentinel_fixtures::event()
+                .service("checkout")
+                .prompt(text)
+                .prompt_embedding(embedding)
+                .build()
+        };
+        let events = [
+            event(vec![1.0, 0.0], "ignore previous instructions"),
+            event(vec![0.95, 0.05], "ignore all prior instructions"),
+            event(vec![0.0, 1.0], "what's the weather"),
+        ];
+        let index = Arc::new(FlatVectorIndex::new(100));
+        for event in &events {
+            index.insert(event);
+        }
+
+        let storage = Arc::new(InMemoryStorage::new());
+        let state = Arc::new(QueryState::new(storage.clone()));
+        let result = similar_events(State(state), Json(SimilarRequest::default())).await;
+        assert_eq!(result.unwrap_err().0, StatusCode::NOT_FOUND);
+
+        let state = Arc::new(QueryState::new(storage).with_similarity(index));
+        let request = SimilarRequest {
+            event_id: Some(events[0].event_id),
+            min_score: Some(0.5),
+            ..Default::default()
+        };
+        let response = similar_events(State(state.clone()), Json(request))
+            .await
+            .unwrap();
+        assert_eq!(response.0.data.len(), 1);
+        assert_eq!(response.0.data[0].event_id, events[1].event_id);
+
+        let request = SimilarRequest {
+            embedding: Some(vec![0.0, 2.0]),
+            limit: Some(1),
+            ..Default::default()
+        };
+        let response = similar_events(State(state.clone()), Json(request))
+            .await
+            .unwrap();
+        assert_eq!(response.0.data[0].event_id, events[2].event_id);
+
+        let unknown = SimilarRequest {
+            event_id: Some(uuid::Uuid::new_v4()),
+            ..Default::default()
+        };
+        let result = similar_events(State(state.clone()), Json(unknown)).await;
+        assert_eq!(result.unwrap_err().0, StatusCode::NOT_FOUND);
+        let result = similar_events(State(state.clone()), Json(SimilarRequest::default())).await;
+        assert_eq!(result.unwrap_err().0, StatusCode::BAD_REQUEST);
+        let zero = SimilarRequest {
+            embedding: Some(vec![0.0, 0.0]),
+            ..Default::default()
+        };
+        let result = similar_events(State(state), Json(zero)).await;
+        assert_eq!(result.unwrap_err().0, StatusCode::BAD_REQUEST);
+    }
+
+    #[tokio::test]
+    async fn test_compare_anomalies() {
+        use axum::extract::{Query, State};
+        use chrono::{Duration as ChronoDuration, Utc};
+
+        use llm_sentinel_storage::memory::InMemoryStorage;
+
+        let now = Utc::now();
+        let anomaly = |service: &str, minutes_ago: i64, version: &str| {
+            let mut anomaly = llm_sentinel_fixtures::anomaly().service(service).build();
+            anomaly.timestamp = now - ChronoDuration::minutes(minutes_ago);
+            anomaly
+                .labels
+                .insert("config_version".to_string(), version.to_string());
+            anomaly
+        };
+        let storage = Arc::new(InMemoryStorage::new());
+        storage
+            .write_anomaly_batch(&[
+                // Previous hour
+                anomaly("checkout", 90, "v1"),
+                anomaly("checkout", 80, "v1"),
+                anomaly("search", 70, "v1"),
+                // Last hour
+                anomaly("checkout", 30, "v1"),
+                anomaly("search", 20, "v2"),
+                anomaly("search", 10, "v2"),
+                anomaly("billing", 5, "v2"),
+            ])
+            .await
+            .unwrap();
+        let state = Arc::new(QueryState::new(storage));
+
+        // Last hour against the hour before
+        let params = CompareQueryParams {
+            hours: Some(1),
+            ..Default::default()
+        };
+        let response = compare_anomalies(State(state.clone()), Query(params))
+            .await
+            .unwrap()
+            .0
+            .data;
+        assert_eq!((response.baseline.total, response.candidate.total), (3, 4));
+        assert_eq!(response.total.change, 1);
+        assert_eq!(response.baseline.end, response.candidate.start);
+        let change = |key: &str| {
+            let diff = response.by_service.iter().find(|d| d.key == key).unwrap();
+            (diff.baseline, diff.candidate, diff.change_pct)
+        };
+        assert_eq!(change("checkout"), (2, 1, Some(-50.0)));
+        assert_eq!(change("search"), (1, 2, Some(100.0)));
+        assert_eq!(change("billing"), (0, 1, None));
+        assert_eq!(response.by_type[0].key, "latency_spike");
+
+        // Two configurations over the same range
+        let params = CompareQueryParams {
+            hours: Some(1),
+            labels: Some("config_version=v2".to_string()),
+            baseline_labels: Some("config_version=v1".to_string()),
+            ..Default::default()
+        };
+        let response = compare_anomalies(State(state.clone()), Query(params))
+            .await
+            .unwrap()
+            .0
+            .data;
+        assert_eq!((response.baseline.total, response.candidate.total), (1, 3));
+        assert_eq!(response.baseline.start, response.candidate.start);
+        assert_eq!(response.by_service[0].key, "search");
+
+        let params = CompareQueryParams {
+            baseline_start: Some(now.to_rfc3339()),
+            ..Default::default()
+        };
+        let result = compare_anomalies(State(state), Query(params)).await;
+        assert_eq!(result.unwrap_err().0, StatusCode::BAD_REQUEST);
+    }
+
+    #[tokio::test]
+    async fn test_anomaly_lifecycle() {
+        use axum::{body::Body, http::header};
+        use llm_sentinel_core::{
+            annotation::AnnotationKind,
+            bus::EventBus,
+            events::AnomalyEvent,
+            lifecycle::{AnomalyState, AnomalyStateChange},
+        };
+        use llm_sentinel_storage::memory::InMemoryStorage;
+
+        use tower::ServiceExt;
+
+        let anomaly = llm_sentinel_fixtures::anomaly().service("checkout").build();
+        let storage = Arc::new(InMemoryStorage::new());
+        storage.write_anomaly(&anomaly).await.unwrap();
+
+        let bus = EventBus::default();
+        let mut lifecycle = bus.lifecycle().subscribe();
+        let query_state = QueryState::new(storage).with_event_bus(bus);
+        let router = create_router(
+            ApiConfig::default(),
+            Arc::new(HealthState::new("0.1.0".to_string(), Arc::new(|| Ok(())))),
+            Arc::new(MetricsState::new()),
+            Arc::new(query_state),
+        );
+        let change_state = |id: String, body: &'static str| {
+            axum::http::Request::post(format!("/api/v1/anomalies/{}/state", id))
+                .header(header::CONTENT_TYPE, "application/json")
+                .body(Body::from(body))
+                .unwrap()
+        };
+        let id = anomaly.alert_id.to_string();
+
+        let request = change_state(id.clone(), r#"{"state": "acknowledged", "actor": "alice"}"#);
+        let response = router.clone().oneshot(request).await.unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+
+        let request = axum::http::Request::get(format!("/api/v1/anomalies/{}", id))
+            .body(Body::empty())
+            .unwrap();
+        let response = router.clone().oneshot(request).await.unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+        let body = axum::body::to_bytes(response.into_body(), usize::MAX)
+            .await
+            .unwrap();
+        let stored: crate::SuccessResponse<AnomalyEvent> = serde_json::from_slice(&body).unwrap();
+        assert_eq!(stored.data.state, AnomalyState::Acknowledged);
+
+        // The updated anomaly is published for alerting
+        let event = lifecycle.recv().await.unwrap();
+        assert_eq!(event.anomaly.state, AnomalyState::Acknowledged);
+        assert_eq!(event.change.actor.as_deref(), Some("alice"));
+
+        let request = change_state(id.clone(), r#"{"state": "resolved"}"#);
+        let response = router.clone().oneshot(request).await.unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+
+        let request = change_state(id.clone(), r#"{"state": "acknowledged"}"#);
+        let response = router.clone().oneshot(request).await.unwrap();
+        assert_eq!(response.status(), StatusCode::CONFLICT);
+        assert_eq!(error_code(response).await, "invalid_transition");
+
+        let request = axum::http::Request::get(format!("/api/v1/anomalies/{}/history", id))
+            .body(Body::empty())
+            .unwrap();
+        let response = router.clone().oneshot(request).await.unwrap();
+        let body = axum::body::to_bytes(response.into_body(), usize::MAX)
+            .await
+            .unwrap();
+        let history: crate::SuccessResponse<Vec<AnomalyStateChange>> =
+            serde_json::from_slice(&body).unwrap();
+        let states: Vec<_> = history.data.iter().map(|c| c.to).collect();
+        assert_eq!(
+            states,
+            vec![AnomalyState::Acknowledged, AnomalyState::Resolved]
+        );
+        assert_eq!(history.data[0].actor.as_deref(), Some("alice"));
+
+        let annotate = |id: String, body: &'static str| {
+            axum::http::Request::post(format!("/api/v1/anomalies/{}/annotations", id))
+                .header(header::CONTENT_TYPE, "application/json")
+                .body(Body::from(body))
+                .unwrap()
+        };
+        let request = annotate(
+            id.clone(),
+            r#"{"kind": "resolution", "text": "Rolled back deploy 1234", "author": "alice"}"#,
+        );
+        let response = router.clone().oneshot(request).await.unwrap();
+        assert_eq!(response.status(), StatusCode::CREATED);
+
+        let request = annotate(id.clone(), r#"{"kind": "link", "text": "Dashboard"}"#);
+        let response = router.clone().oneshot(request).await.unwrap();
+        assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+        assert_eq!(error_code(response).await, "invalid_annotation");
+
+        let request = annotate(uuid::Uuid::new_v4().to_string(), r#"{"text": "note"}"#);
+        let response = router.clone().oneshot(request).await.unwrap();
+        assert_eq!(response.status(), StatusCode::NOT_FOUND);
+
+        // The detail endpoint returns the annotations
+        let request = axum::http::Request::get(format!("/api/v1/anomalies/{}", id))
+            .body(Body::empty())
+            .unwrap();
+        let response = router.clone().oneshot(request).await.unwrap();
+        let body = axum::body::to_bytes(response.into_body(), usize::MAX)
+            .await
+            .unwrap();
+        let stored: crate::SuccessResponse<AnomalyEvent> = serde_json::from_slice(&body).unwrap();
+        assert_eq!(stored.data.annotations.len(), 1);
+        assert_eq!(stored.data.annotations[0].kind, AnnotationKind::Resolution);
+        assert_eq!(stored.data.annotations[0].author.as_deref(), Some("alice"));
+
+        let request = change_state(uuid::Uuid::new_v4().to_string(), r#"{"state": "resolved"}"#);
+        let response = router.clone().oneshot(request).await.unwrap();
+        assert_eq!(response.status(), StatusCode::NOT_FOUND);
+
+        let request = change_state("not-a-uuid".to_string(), r#"{"state": "resolved"}"#);
+        let response = router.oneshot(request).await.unwrap();
+        assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+        assert_eq!(error_code(response).await, "invalid_id");
+    }
+
+    #[tokio::test]
+    async fn test_incident_endpoints() {
+        use axum::{body::Body, http::header};
+        use llm_sentinel_core::{
+            bus::EventBus,
+            config::{IncidentConfig, IncidentGroupField},
+            incident::{Incident, IncidentState, IncidentUpdate},
+        };
+        use llm_sentinel_storage::{incident::IncidentTracker, memory::InMemoryStorage};
+
+        use tower::ServiceExt;
+
+        let anomaly = llm_sentinel_fixtures::anomaly().service("checkout").build();
+        let storage = Arc::new(InMemoryStorage::new());
+        let tracker = Arc::new(IncidentTracker::new(&IncidentConfig {
+            group_by: vec![IncidentGroupField::Service],
+            group_window_secs: 1800,
+            notify_alerters: Vec::new(),
+        }));
+        let (incident, _) = tracker.record(storage.as_ref(), &anomaly).await.unwrap();
+
+        let bus = EventBus::default();
+        let mut incidents = bus.incidents().subscribe();
+        let query_state = QueryState::new(storage)
+            .with_event_bus(bus)
+            .with_incident_tracker(tracker);
+        let router = create_router(
+            ApiConfig::default(),
+            Arc::new(HealthState::new("0.1.0".to_string(), Arc::new(|| Ok(())))),
+            Arc::new(MetricsState::new()),
+            Arc::new(query_state),
+        );
+        let post = |path: String, body: &'static str| {
+            axum::http::Request::post(path)
+                .header(header::CONTENT_TYPE, "application/json")
+                .body(Body::from(body))
+                .unwrap()
+        };
+        let read = |response: axum::response::Response| async move {
+            let body = axum::body::to_bytes(response.into_body(), usize::MAX)
+                .await
+                .unwrap();
+            body.to_vec()
+        };
+        let id = incident.incident_id;
+
+        let request = axum::http::Request::get("/api/v1/incidents?state=open&service=checkout")
+            .body(Body::empty())
+            .unwrap();
+        let response = router.clone().oneshot(request).await.unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+        let listed: crate::SuccessResponse<Vec<Incident>> =
+            serde_json::from_slice(&read(response).await).unwrap();
+        assert_eq!(listed.data.len(), 1);
+        assert_eq!(listed.data[0].alert_ids, vec![anomaly.alert_id]);
+
+        let request = post(
+            format!("/api/v1/incidents/{}/state", id),
+            r#"{"state": "acknowledged", "actor": "alice"}"#,
+        );
+        let response = router.clone().oneshot(request).await.unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+
+        // The change is published for alerting
+        let event = incidents.recv().await.unwrap();
+        assert_eq!(event.update, IncidentUpdate::StateChanged);
+        assert_eq!(event.incident.state, IncidentState::Acknowledged);
+
+        let request = post(
+            format!("/api/v1/incidents/{}/links", id),
+            r#"{"title": "Postmortem", "url": "https://docs.example.com/pm/1"}"#,
+        );
+        let response = router.clone().oneshot(request).await.unwrap();
+        assert_eq!(response.status(), StatusCode::CREATED);
+
+        let request = post(
+            format!("/api/v1/incidents/{}/links", id),
+            r#"{"title": "", "url": ""}"#,
+        );
+        let response = router.clone().oneshot(request).await.unwrap();
+        assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+        assert_eq!(error_code(response).await, "invalid_link");
+
+        let request = post(
+            format!("/api/v1/incidents/{}/state", id),
+            r#"{"state": "resolved"}"#,
+        );
+        let response = router.clone().oneshot(request).await.unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+        let request = post(
+            format!("/api/v1/incidents/{}/state", id),
+            r#"{"state": "acknowledged"}"#,
+        );
+        let response = router.clone().oneshot(request).await.unwrap();
+        assert_eq!(response.status(), StatusCode::CONFLICT);
+        assert_eq!(error_code(response).await, "invalid_transition");
+
+        let request = axum::http::Request::get(format!("/api/v1/incidents/{}", id))
+            .body(Body::empty())
+            .unwrap();
+        let response = router.clone().oneshot(request).await.unwrap();
+        let stored: crate::SuccessResponse<Incident> =
+            serde_json::from_slice(&read(response).await).unwrap();
+        assert_eq!(stored.data.state, IncidentState::Resolved);
+        assert_eq!(stored.data.links[0].title, "Postmortem");
+        let updates: Vec<_> = stored.data.timeline.iter().map(|e| e.update).collect();
+        assert_eq!(
+            updates,
+            vec![
+                IncidentUpdate::Opened,
+                IncidentUpdate::StateChanged,
+                IncidentUpdate::Linked,
+                IncidentUpdate::StateChanged
+            ]
+        );
+
+        let request =
+            axum::http::Request::get(format!("/api/v1/incidents/{}", uuid::Uuid::new_v4()))
+                .body(Body::empty())
+                .unwrap();
+        let response = router.clone().oneshot(request).await.unwrap();
+        assert_eq!(response.status(), StatusCode::NOT_FOUND);
+    }
+
+    #[tokio::test]
+    async fn test_anomaly_fleet_comparison() {
+        use axum::extract::{Path, Query, State};
+        use chrono::{Duration as ChronoDuration, Utc};
+
+        use llm_sentinel_storage::{fleet::FleetScope, memory::InMemoryStorage};
+
+        let event = |service: &str, latency_ms: f64, minutes_ago: i64| {
+            llm_sentinel_fixtures::event()
+                .service(service)
+                .latency_ms(latency_ms)
+                .timestamp(Utc::now() - ChronoDuration::minutes(minutes_ago))
+                .build()
+        };
+        let anomaly = |metric: &str| {
+            llm_sentinel_fixtures::anomaly()
+                .service("checkout")
+                .metric(metric)
+                .value(4000.0)
+                .build()
+        };
+
+        let storage = Arc::new(InMemoryStorage::new());
+        let events: Vec<_> = (1..=10)
+            .flat_map(|i| [event("checkout", 3000.0, i), event("search", 1000.0, i)])
+            .collect();
+        storage.write_telemetry_batch(&events).await.unwrap();
+        let spike = anomaly("latency_ms");
+        let burn = anomaly("slo_burn_rate");
+        storage
+            .write_anomaly_batch(&[spike.clone(), burn.clone()])
+            .await
+            .unwrap();
+        let state = Arc::new(QueryState::new(storage));
+
+        let response = anomaly_fleet_comparison(
+            State(state.clone()),
+            Path(spike.alert_id.to_string()),
+            Query(FleetQueryParams::default()),
+        )
+        .await
+        .unwrap()
+        .0
+        .data;
+        assert_eq!(response.fleet.unwrap().services, 2);
+        assert_eq!(response.value_to_fleet, Some(4000.0 / 3000.0));
+        assert_eq!(response.affected_to_others, Some(3.0));
+        assert_eq!(response.scope, FleetScope::ServiceSpecific);
+
+        let compare = |id: String, minutes: Option<i64>| {
+            anomaly_fleet_comparison(
+                State(state.clone()),
+                Path(id),
+                Query(FleetQueryParams { minutes }),
+            )
+        };
+        let error = compare(burn.alert_id.to_string(), None).await.unwrap_err();
+        assert_eq!(error.0, StatusCode::BAD_REQUEST);
+        assert_eq!(error.1 .0.code, "unsupported_metric");
+        let error = compare(spike.alert_id.to_string(), Some(0))
+            .await
+            .unwrap_err();
+        assert_eq!(error.0, StatusCode::BAD_REQUEST);
+        let error = compare(uuid::Uuid::new_v4().to_string(), None)
+            .await
+            .unwrap_err();
+        assert_eq!(error.0, StatusCode::NOT_FOUND);
+    }
+
+    #[tokio::test]
+    async fn test_alert_noise() {
+        use axum::extract::{Query, State};
+        use llm_sentinel_core::{
+            lifecycle::{AnomalyState, AUTO_RESOLVER},
+            types::Severity,
+        };
+        use llm_sentinel_storage::{lifecycle::transition_anomaly, memory::InMemoryStorage};
+
+        let anomaly = |service: &str| {
+            llm_sentinel_fixtures::anomaly()
+                .severity(Severity::Medium)
+                .service(service)
+                .value(500.0)
+                .deviation_sigma(Some(4.0))
+                .build()
+        };
+
+        let storage = Arc::new(InMemoryStorage::new());
+        let flapping: Vec<_> = (0..3).map(|_| anomaly("search")).collect();
+        let handled = anomaly("checkout");
+        storage
+            .write_anomaly_batch(&[flapping.clone(), vec![handled.clone()]].concat())
+            .await
+            .unwrap();
+        for anomaly in &flapping {
+            let actor = Some(AUTO_RESOLVER.to_string());
+            transition_anomaly(
+                storage.as_ref(),
+                anomaly.alert_id,
+                AnomalyState::Resolved,
+                actor,
+                None,
+            )
+            .await
+            .unwrap();
+        }
+        let actor = Some("alice".to_string());
+        transition_anomaly(
+            storage.as_ref(),
+            handled.alert_id,
+            AnomalyState::Acknowledged,
+            actor,
+            None,
+        )
+        .await
+        .unwrap();
+        let state = Arc::new(QueryState::new(storage));
+
+        let report = alert_noise(State(state.clone()), Query(NoiseQueryParams::default()))
+            .await
+            .unwrap()
+            .0
+            .data;
+        assert_eq!(report.anomalies, 4);
+        assert_eq!(report.noisiest.len(), 2);
+        assert_eq!(report.noisiest[0].service.as_str(), "search");
+        assert_eq!(report.noisiest[0].auto_resolved, 3);
+        assert_eq!(report.noisiest[0].ack_rate, 0.0);
+        assert_eq!(report.noisiest[1].ack_rate, 1.0);
+        assert_eq!(report.noisiest[1].noise_score, 0.0);
+
+        let params = NoiseQueryParams {
+            limit: Some(0),
+            ..Default::default()
+        };
+        let error = alert_noise(State(state), Query(params)).await.unwrap_err();
+        assert_eq!(error.0, StatusCode::BAD_REQUEST);
+        assert_eq!(error.1 .0.code, "invalid_limit");
+    }
+    #[tokio::test]
+    async fn test_federation_endpoint() {
+        use axum::{body::Body, http::header};
+        use llm_sentinel_core::{events::AnomalyEvent, federation::FederationBatch};
+        use std::sync::Mutex;
+        use tower::ServiceExt;
+
+        #[derive(Default)]
+        struct MockSink(Mutex<Vec<(String, usize)>>);
+
+        #[async_trait::async_trait]
+        impl FederationSink for MockSink {
+            async fn receive(
+                &self,
+                region: &str,
+                anomalies: Vec<AnomalyEvent>,
+            ) -> llm_sentinel_core::Result<usize> {
+                self.0
+                    .lock()
+                    .unwrap()
+                    .push((region.to_string(), anomalies.len()));
+                Ok(anomalies.len())
+            }
+        }
+
+        let anomaly = llm_sentinel_fixtures::anomaly().service("checkout").build();
+        let body = serde_json::to_string(&FederationBatch {
+            region: "eu-west-1".to_string(),
+            anomalies: vec![anomaly],
+        })
+        .unwrap();
+        let forward = |token: Option<&str>| {
+            let mut request = axum::http::Request::post("/api/v1/federation/anomalies")
+                .header(header::CONTENT_TYPE, "application/json");
+            if let Some(token) = token {
+                request = request.header(header::AUTHORIZATION, format!("Bearer {}", token));
+            }
+            request.body(Body::from(body.clone())).unwrap()
+        };
+
+        // Not accepted unless configured
+        let response = limited(1024 * 1024)
+            .oneshot(forward(Some("eu-secret")))
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::NOT_FOUND);
+
+        let sink = Arc::new(MockSink::default());
+        let edges = [
+            ("eu-west-1".to_string(), "eu-secret".to_string()),
+            ("us-east-1".to_string(), "us-secret".to_string()),
+        ]
+        .into_iter()
+        .collect();
+        let query_state = QueryState::new(Arc::new(MockStorage))
+            .with_federation(FederationReceiver::new(&edges, sink.clone()));
+        let router = create_router(
+            ApiConfig::default(),
+            Arc::new(HealthState::new("0.1.0".to_string(), Arc::new(|| Ok(())))),
+            Arc::new(MetricsState::new()),
+            Arc::new(query_state),
+        );
+
+        let response = router.clone().oneshot(forward(None)).await.unwrap();
+        assert_eq!(response.status(), StatusCode::UNAUTHORIZED);
+        // Another region's token does not authenticate this region
+        let response = router
+            .clone()
+            .oneshot(forward(Some("us-secret")))
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::UNAUTHORIZED);
+        assert_eq!(error_code(response).await, "unauthorized");
+        assert!(sink.0.lock().unwrap().is_empty());
+
+        let response = router.oneshot(forward(Some("eu-secret"))).await.unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+        assert_eq!(
+            *sink.0.lock().unwrap(),
+            vec![("eu-west-1".to_string(), 1)]
+        );
+    }
 }
@@ -1,16 +1,37 @@
 //! API server implementation.
 
 use crate::{
-    handlers::{health::HealthState, metrics::MetricsState, query::QueryState},
+    handlers::{
+        credentials::CredentialControl,
+        dedup::DedupStatsSource,
+        delivery::AlertRedelivery,
+        federation::FederationReceiver,
+        health::{ComponentCheck, HealthState},
+        metrics::MetricsState,
+        quality::DetectionStatsSource,
+        query::QueryState,
+        redetect::Redetector,
+        selfcheck::SelfChecker,
+        simulation::AnomalySimulator,
+        snapshot::SnapshotControl,
+        tuning::DetectionConfigControl,
+    },
+    listener,
     routes::create_router,
     ApiConfig,
 };
-use llm_sentinel_storage::Storage;
+use futures::future;
+use llm_sentinel_core::{bus::EventBus, memory::MemoryWatchdog, pricing::PriceTable};
+use llm_sentinel_detection::{availability::AvailabilityTracker, slo::SloTracker};
+use llm_sentinel_storage::{
+    budget::BudgetForecaster, delivery::DeliverySlaPolicy, incident::IncidentTracker,
+    search::TextSearch, similarity::VectorIndex, Storage,
+};
 use std::sync::Arc;
-use tokio::net::TcpListener;
 use tracing::{info, error};
 
 /// API server
+#[derive(Debug)]
 pub struct ApiServer {
     config: ApiConfig,
     health_state: Arc<HealthState>,
@@ -47,7 +68,166 @@ impl ApiServer {
         }
     }
 
+    /// Expose SLO status from the given tracker
+    pub fn with_slo_tracker(mut self, tracker: Arc<SloTracker>) -> Self {
+        let query_state = (*self.query_state).clone().with_slo_tracker(tracker);
+        self.query_state = Arc::new(query_state);
+        self
+    }
+
+    /// Expose availability series from the given tracker
+    pub fn with_availability_tracker(mut self, tracker: Arc<AvailabilityTracker>) -> Self {
+        let query_state = (*self.query_state)
+            .clone()
+            .with_availability_tracker(tracker);
+        self.query_state = Arc::new(query_state);
+        self
+    }
+
+    /// Expose and update model prices in the given table
+    pub fn with_pricing(mut self, pricing: Arc<PriceTable>) -> Self {
+        let query_state = (*self.query_state).clone().with_pricing(pricing);
+        self.query_state = Arc::new(query_state);
+        self
+    }
+
+    /// Report alert delivery against the given objectives
+    pub fn with_delivery_sla(mut self, policies: Vec<DeliverySlaPolicy>) -> Self {
+        let query_state = (*self.query_state).clone().with_delivery_sla(policies);
+        self.query_state = Arc::new(query_state);
+        self
+    }
+
+    /// Expose alert deduplication statistics from the given source
+    pub fn with_dedup_stats(mut self, source: DedupStatsSource) -> Self {
+        let query_state = (*self.query_state).clone().with_dedup_stats(source);
+        self.query_state = Arc::new(query_state);
+        self
+    }
+
+    /// Serve full-text search from the given index
+    pub fn with_search(mut self, search: Arc<dyn TextSearch>) -> Self {
+        let query_state = (*self.query_state).clone().with_search(search);
+        self.query_state = Arc::new(query_state);
+        self
+    }
+
+    /// Serve embedding similarity search from the given index
+    pub fn with_similarity(mut self, similarity: Arc<dyn VectorIndex>) -> Self {
+        let query_state = (*self.query_state).clone().with_similarity(similarity);
+        self.query_state = Arc::new(query_state);
+        self
+    }
+
+    /// Publish anomaly state changes made through the API to the given bus,
+    /// and stream its anomalies over WebSocket
+    pub fn with_event_bus(mut self, bus: EventBus) -> Self {
+        let query_state = (*self.query_state).clone().with_event_bus(bus);
+        self.query_state = Arc::new(query_state);
+        self
+    }
+
+    /// Serve the admin snapshot endpoints through the given control
+    pub fn with_snapshots(mut self, snapshots: Arc<dyn SnapshotControl>) -> Self {
+        let query_state = (*self.query_state).clone().with_snapshots(snapshots);
+        self.query_state = Arc::new(query_state);
+        self
+    }
+
+    /// Serve the admin credential rotation endpoint through the given
+    /// control
+    pub fn with_credentials(mut self, credentials: Arc<dyn CredentialControl>) -> Self {
+        let query_state = (*self.query_state).clone().with_credentials(credentials);
+        self.query_state = Arc::new(query_state);
+        self
+    }
+
+    /// Accept anomalies forwarded by edge instances through the given
+    /// receiver
+    pub fn with_federation(mut self, federation: FederationReceiver) -> Self {
+        let query_state = (*self.query_state).clone().with_federation(federation);
+        self.query_state = Arc::new(query_state);
+        self
+    }
+
+    /// Serve the admin anomaly simulation endpoint through the given
+    /// simulator
+    pub fn with_simulator(mut self, simulator: Arc<dyn AnomalySimulator>) -> Self {
+        let query_state = (*self.query_state).clone().with_simulator(simulator);
+        self.query_state = Arc::new(query_state);
+        self
+    }
+
+    /// Serve the admin re-detection job endpoints through the given
+    /// redetector
+    pub fn with_redetector(mut self, redetector: Arc<dyn Redetector>) -> Self {
+        let query_state = (*self.query_state).clone().with_redetector(redetector);
+        self.query_state = Arc::new(query_state);
+        self
+    }
+
+    /// Serve the alert redelivery endpoint through the given redelivery
+    pub fn with_redelivery(mut self, redelivery: Arc<dyn AlertRedelivery>) -> Self {
+        let query_state = (*self.query_state).clone().with_redelivery(redelivery);
+        self.query_state = Arc::new(query_state);
+        self
+    }
+
+    /// Serve the admin self-check endpoint through the given checker
+    pub fn with_self_checker(mut self, checker: Arc<dyn SelfChecker>) -> Self {
+        let query_state = (*self.query_state).clone().with_self_checker(checker);
+        self.query_state = Arc::new(query_state);
+        self
+    }
+
+    /// Include live detector and deduplication statistics from the given
+    /// source in the detector quality report
+    pub fn with_detection_stats(mut self, source: Arc<dyn DetectionStatsSource>) -> Self {
+        let query_state = (*self.query_state).clone().with_detection_stats(source);
+        self.query_state = Arc::new(query_state);
+        self
+    }
+
+    /// Serve month-end cost projections from the given forecaster
+    pub fn with_budgets(mut self, budgets: Arc<BudgetForecaster>) -> Self {
+        let query_state = (*self.query_state).clone().with_budgets(budgets);
+        self.query_state = Arc::new(query_state);
+        self
+    }
+
+    /// Track the response cache against the given memory budget
+    pub fn with_memory_watchdog(mut self, watchdog: Arc<MemoryWatchdog>) -> Self {
+        let query_state = (*self.query_state).clone().with_memory_watchdog(watchdog);
+        self.query_state = Arc::new(query_state);
+        self
+    }
+
+    /// Serve the admin detection configuration endpoints through the given
+    /// control
+    pub fn with_tuning(mut self, tuning: Arc<dyn DetectionConfigControl>) -> Self {
+        let query_state = (*self.query_state).clone().with_tuning(tuning);
+        self.query_state = Arc::new(query_state);
+        self
+    }
+
+    /// Apply incident state changes and links through the given tracker
+    pub fn with_incident_tracker(mut self, tracker: Arc<IncidentTracker>) -> Self {
+        let query_state = (*self.query_state).clone().with_incident_tracker(tracker);
+        self.query_state = Arc::new(query_state);
+        self
+    }
+
+    /// Report the given component in the health endpoints
+    pub fn with_health_check(mut self, check: ComponentCheck) -> Self {
+        let health_state = (*self.health_state).clone().with_component(check);
+        self.health_state = Arc::new(health_state);
+        self
+    }
+
     /// Start the API server
+    ///
+    /// Binds every configured listener before serving, so a bad address or
+    /// certificate fails startup instead of leaving the server half up.
     pub async fn serve(self) -> Result<(), Box<dyn std::error::Error>> {
         info!("Starting API server on {}", self.config.bind_addr);
 
@@ -58,9 +238,10 @@ impl ApiServer {
             self.query_state,
         );
 
-        let listener = TcpListener::bind(self.config.bind_addr).await?;
-
-        info!("API server listening on {}", self.config.bind_addr);
+        let listeners = listener::bind_all(&self.config)?;
+        for listener in &listeners {
+            info!("API server listening on {}", listener);
+        }
         info!("Health check: http://{}/health", self.config.bind_addr);
         info!(
             "Metrics: http://{}{}",
@@ -68,12 +249,13 @@ impl ApiServer {
         );
         info!("API docs: http://{}/api/v1", self.config.bind_addr);
 
-        axum::serve(listener, router)
+        future::try_join_all(listeners.into_iter().map(|l| l.serve(router.clone())))
             .await
             .map_err(|e| {
                 error!("Server error: {}", e);
                 Box::new(e) as Box<dyn std::error::Error>
-            })
+            })?;
+        Ok(())
     }
 
     /// Get server bind address
@@ -93,49 +275,161 @@ mod tests {
     impl Storage for MockStorage {
         async fn write_telemetry(
             &self,
-            _event: &sentinel_core::events::TelemetryEvent,
-        ) -> sentinel_core::Result<()> {
+            _event: &llm_sentinel_core::events::TelemetryEvent,
+        ) -> llm_sentinel_core::Result<()> {
             Ok(())
         }
 
         async fn write_anomaly(
             &self,
-            _anomaly: &sentinel_core::events::AnomalyEvent,
-        ) -> sentinel_core::Result<()> {
+            _anomaly: &llm_sentinel_core::events::AnomalyEvent,
+        ) -> llm_sentinel_core::Result<()> {
             Ok(())
         }
 
         async fn write_telemetry_batch(
             &self,
-            _events: &[sentinel_core::events::TelemetryEvent],
-        ) -> sentinel_core::Result<()> {
+            _events: &[llm_sentinel_core::events::TelemetryEvent],
+        ) -> llm_sentinel_core::Result<()> {
             Ok(())
         }
 
         async fn write_anomaly_batch(
             &self,
-            _anomalies: &[sentinel_core::events::AnomalyEvent],
-        ) -> sentinel_core::Result<()> {
+            _anomalies: &[llm_sentinel_core::events::AnomalyEvent],
+        ) -> llm_sentinel_core::Result<()> {
             Ok(())
         }
 
         async fn query_telemetry(
             &self,
-            _query: sentinel_storage::query::TelemetryQuery,
-        ) -> sentinel_core::Result<Vec<sentinel_core::events::TelemetryEvent>> {
+            _query: llm_sentinel_storage::query::TelemetryQuery,
+        ) -> llm_sentinel_core::Result<Vec<llm_sentinel_core::events::TelemetryEvent>> {
             Ok(Vec::new())
         }
 
         async fn query_anomalies(
             &self,
-            _query: sentinel_storage::query::AnomalyQuery,
-        ) -> sentinel_core::Result<Vec<sentinel_core::events::AnomalyEvent>> {
+            _query: llm_sentinel_storage::query::AnomalyQuery,
+        ) -> llm_sentinel_core::Result<Vec<llm_sentinel_core::events::AnomalyEvent>> {
             Ok(Vec::new())
         }
 
-        async fn health_check(&self) -> sentinel_core::Result<()> {
+        async fn get_anomaly(
+            &self,
+            _alert_id: uuid::Uuid,
+        ) -> llm_sentinel_core::Result<Option<llm_sentinel_core::events::AnomalyEvent>> {
+            Ok(None)
+        }
+
+        async fn write_anomaly_state_changes(
+            &self,
+            _changes: &[llm_sentinel_core::lifecycle::AnomalyStateChange],
+        ) -> llm_sentinel_core::Result<()> {
             Ok(())
         }
+
+        async fn query_anomaly_state_changes(
+            &self,
+            _alert_id: uuid::Uuid,
+        ) -> llm_sentinel_core::Result<Vec<llm_sentinel_core::lifecycle::AnomalyStateChange>>
+        {
+            Ok(Vec::new())
+        }
+
+        async fn write_anomaly_annotations(
+            &self,
+            _annotations: &[llm_sentinel_core::annotation::AnomalyAnnotation],
+        ) -> llm_sentinel_core::Result<()> {
+            Ok(())
+        }
+
+        async fn query_anomaly_annotations(
+            &self,
+            _query: llm_sentinel_storage::annotation::AnnotationQuery,
+        ) -> llm_sentinel_core::Result<Vec<llm_sentinel_core::annotation::AnomalyAnnotation>>
+        {
+            Ok(Vec::new())
+        }
+
+        async fn write_stream_offset(
+            &self,
+            _offset: &llm_sentinel_storage::stream::StreamOffset,
+        ) -> llm_sentinel_core::Result<()> {
+            Ok(())
+        }
+
+        async fn get_stream_offset(
+            &self,
+            _consumer_id: &str,
+        ) -> llm_sentinel_core::Result<Option<llm_sentinel_storage::stream::StreamOffset>> {
+            Ok(None)
+        }
+
+        async fn anomaly_heatmap(
+            &self,
+            _query: llm_sentinel_storage::query::HeatmapQuery,
+        ) -> llm_sentinel_core::Result<Vec<llm_sentinel_storage::query::HeatmapBucket>> {
+            Ok(Vec::new())
+        }
+
+        async fn write_cost_rollups(
+            &self,
+            _rollups: &[llm_sentinel_storage::cost::CostRollup],
+        ) -> llm_sentinel_core::Result<()> {
+            Ok(())
+        }
+
+        async fn query_cost_report(
+            &self,
+            _query: llm_sentinel_storage::cost::CostReportQuery,
+        ) -> llm_sentinel_core::Result<Vec<llm_sentinel_storage::cost::CostReportRow>> {
+            Ok(Vec::new())
+        }
+
+        async fn write_alert_deliveries(
+            &self,
+            _deliveries: &[llm_sentinel_core::events::AlertMetadata],
+        ) -> llm_sentinel_core::Result<()> {
+            Ok(())
+        }
+
+        async fn query_alert_deliveries(
+            &self,
+            _query: llm_sentinel_storage::delivery::DeliveryQuery,
+        ) -> llm_sentinel_core::Result<Vec<llm_sentinel_core::events::AlertMetadata>> {
+            Ok(Vec::new())
+        }
+
+        async fn write_incident(
+            &self,
+            _incident: &llm_sentinel_core::incident::Incident,
+        ) -> llm_sentinel_core::Result<()> {
+            Ok(())
+        }
+
+        async fn query_incidents(
+            &self,
+            _query: llm_sentinel_storage::incident::IncidentQuery,
+        ) -> llm_sentinel_core::Result<Vec<llm_sentinel_core::incident::Incident>> {
+            Ok(Vec::new())
+        }
+
+        async fn write_redetected_anomalies(
+            &self,
+            _job_id: uuid::Uuid,
+            _anomalies: &[llm_sentinel_core::events::AnomalyEvent],
+        ) -> llm_sentinel_core::Result<()> {
+            Ok(())
+        }
+
+        async fn health_check(&self) -> llm_sentinel_core::Result<()> {
+            Ok(())
+        }
+
+        fn upserts_by_id(&self) -> bool {
+            true
+        }
     }
 
     #[test]
@@ -0,0 +1,61 @@
+//! Built-in dashboard.
+//!
+//! A small web UI for teams without Grafana, served at `/ui` from assets
+//! embedded in the binary. It lists recent anomalies and appends live ones
+//! from the WebSocket stream, filters them by severity and service, and shows
+//! runtime state snapshots and component health, all through the public API.
+//! Assets are served with an ETag so browsers revalidate them after upgrades.
+
+use axum::{
+    extract::Path,
+    http::{header, HeaderMap, StatusCode},
+    response::{IntoResponse, Redirect, Response},
+    routing::get,
+    Router,
+};
+use rust_embed::RustEmbed;
+
+/// Dashboard assets, embedded at compile time
+#[derive(RustEmbed)]
+#[folder = "ui/"]
+struct Assets;
+
+/// Dashboard routes
+pub fn router() -> Router {
+    Router::new()
+        .route("/ui", get(|| async { Redirect::permanent("/ui/") }))
+        .route("/ui/", get(index))
+        .route("/ui/*path", get(asset))
+}
+
+async fn index(headers: HeaderMap) -> Response {
+    serve("index.html", &headers)
+}
+
+async fn asset(Path(path): Path<String>, headers: HeaderMap) -> Response {
+    serve(&path, &headers)
+}
+
+fn serve(path: &str, headers: &HeaderMap) -> Response {
+    let Some(file) = Assets::get(path) else {
+        return StatusCode::NOT_FOUND.into_response();
+    };
+
+    let etag = format!("\"{}\"", hex::encode(file.metadata.sha256_hash()));
+    let cached = headers
+        .get(header::IF_NONE_MATCH)
+        .is_some_and(|value| value.as_bytes() == etag.as_bytes());
+    if cached {
+        return (StatusCode::NOT_MODIFIED, [(header::ETAG, etag)]).into_response();
+    }
+
+    (
+        [
+            (header::CONTENT_TYPE, file.metadata.mimetype().to_string()),
+            (header::ETAG, etag),
+            (header::CACHE_CONTROL, "no-cache".to_string()),
+        ],
+        file.data,
+    )
+        .into_response()
+}
@@ -0,0 +1,177 @@
+// LLM-Sentinel dashboard: recent and live anomalies, health and snapshots.
+// API paths are relative so the dashboard also works behind a path prefix.
+
+"use strict";
+
+const SEVERITIES = ["low", "medium", "high", "critical"];
+const MAX_ROWS = 200;
+const REFRESH_MS = 15000;
+
+const api = (path) => new URL("../" + path, window.location.href);
+
+const rows = document.getElementById("anomaly-rows");
+const severityFilter = document.getElementById("severity");
+const serviceFilter = document.getElementById("service");
+const streamBadge = document.getElementById("stream");
+
+let anomalies = [];
+let socket = null;
+
+function badge(element, text, kind) {
+  element.textContent = text;
+  element.className = "badge " + kind;
+}
+
+function cell(text) {
+  const td = document.createElement("td");
+  td.textContent = text;
+  return td;
+}
+
+function anomalyType(type) {
+  // Custom types serialize as {"custom": "..."}
+  return typeof type === "string" ? type : Object.values(type)[0];
+}
+
+function number(value) {
+  return typeof value === "number" ? value.toFixed(2) : "";
+}
+
+function matches(anomaly) {
+  const minSeverity = SEVERITIES.indexOf(severityFilter.value);
+  const service = serviceFilter.value.trim();
+  return (
+    SEVERITIES.indexOf(anomaly.severity) >= minSeverity &&
+    (!service || anomaly.service_name === service)
+  );
+}
+
+function render(highlight) {
+  rows.replaceChildren();
+  for (const anomaly of anomalies.filter(matches)) {
+    const tr = document.createElement("tr");
+    if (anomaly.alert_id === highlight) {
+      tr.className = "new";
+    }
+    const severity = document.createElement("span");
+    badge(severity, anomaly.severity, anomaly.severity);
+    const severityCell = document.createElement("td");
+    severityCell.append(severity);
+    tr.append(
+      cell(new Date(anomaly.timestamp).toLocaleString()),
+      severityCell,
+      cell(anomaly.service_name),
+      cell(anomaly.model),
+      cell(anomalyType(anomaly.anomaly_type)),
+      cell(anomaly.details.metric),
+      cell(number(anomaly.details.value)),
+      cell(number(anomaly.details.baseline)),
+      cell(number(anomaly.confidence)),
+    );
+    rows.append(tr);
+  }
+}
+
+function add(anomaly) {
+  if (anomalies.some((existing) => existing.alert_id === anomaly.alert_id)) {
+    return;
+  }
+  anomalies.unshift(anomaly);
+  anomalies.length = Math.min(anomalies.length, MAX_ROWS);
+  render(anomaly.alert_id);
+}
+
+async function loadAnomalies() {
+  const url = api("api/v1/anomalies");
+  url.searchParams.set("limit", MAX_ROWS);
+  const response = await fetch(url);
+  if (!response.ok) {
+    return;
+  }
+  const body = await response.json();
+  anomalies = body.data;
+  render();
+}
+
+function connect() {
+  const url = api("api/v1/stream/anomalies");
+  url.protocol = url.protocol === "https:" ? "wss:" : "ws:";
+  socket = new WebSocket(url);
+  socket.onopen = () => badge(streamBadge, "live", "healthy");
+  socket.onmessage = (message) => add(JSON.parse(message.data));
+  socket.onclose = () => {
+    badge(streamBadge, "offline", "unhealthy");
+    setTimeout(connect, 5000);
+  };
+}
+
+async function loadHealth() {
+  const status = document.getElementById("status");
+  const list = document.getElementById("components");
+  try {
+    const response = await fetch(api("health"));
+    const health = await response.json();
+    badge(status, health.status + " · v" + health.version, health.status);
+    list.replaceChildren(
+      ...health.components.map((component) => {
+        const li = document.createElement("li");
+        const state = document.createElement("span");
+        badge(state, component.status, component.status);
+        li.append(component.name + " ", state);
+        if (component.error) {
+          const error = document.createElement("div");
+          error.className = "muted";
+          error.textContent = component.error;
+          li.append(error);
+        }
+        return li;
+      }),
+    );
+  } catch (e) {
+    badge(status, "unreachable", "unhealthy");
+  }
+}
+
+async function loadSnapshots() {
+  const list = document.getElementById("snapshots");
+  const response = await fetch(api("api/v1/admin/snapshots"));
+  if (!response.ok) {
+    const li = document.createElement("li");
+    li.className = "muted";
+    li.textContent = response.status === 404 ? "Snapshots are not enabled" : "Unavailable";
+    list.replaceChildren(li);
+    return;
+  }
+  const body = await response.json();
+  if (body.data.length === 0) {
+    const li = document.createElement("li");
+    li.className = "muted";
+    li.textContent = "No snapshots yet";
+    list.replaceChildren(li);
+    return;
+  }
+  list.replaceChildren(
+    ...body.data.map((snapshot) => {
+      const li = document.createElement("li");
+      li.textContent = new Date(snapshot.created_at).toLocaleString();
+      const sections = document.createElement("div");
+      sections.className = "muted";
+      sections.textContent = snapshot.sections.join(", ") + " · v" + snapshot.version;
+      li.append(sections);
+      return li;
+    }),
+  );
+}
+
+function refresh() {
+  loadHealth();
+  loadSnapshots().catch(() => {});
+}
+
+severityFilter.value = "low";
+severityFilter.onchange = () => render();
+serviceFilter.oninput = () => render();
+
+loadAnomalies().catch(() => {}).finally(connect);
+refresh();
+setInterval(refresh, REFRESH_MS);
@@ -0,0 +1,136 @@
+//! Responder annotations on anomalies.
+//!
+//! Responders attach investigation notes, links (dashboards, tickets,
+//! postmortems) and resolution summaries to an anomaly while they work on
+//! it. Annotations are recorded apart from the anomaly, like its state
+//! changes, and attached to it when it is read back.
+
+use crate::{Error, Result};
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+/// What an annotation records
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum AnnotationKind {
+    /// Free-form investigation note
+    #[default]
+    Note,
+    /// Link to a dashboard, ticket or document
+    Link,
+    /// Summary of how the anomaly was resolved
+    Resolution,
+}
+
+impl std::fmt::Display for AnnotationKind {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Note => write!(f, "note"),
+            Self::Link => write!(f, "link"),
+            Self::Resolution => write!(f, "resolution"),
+        }
+    }
+}
+
+impl std::str::FromStr for AnnotationKind {
+    type Err = String;
+
+    fn from_str(s: &str) -> std::result::Result<Self, Self::Err> {
+        match s {
+            "note" => Ok(Self::Note),
+            "link" => Ok(Self::Link),
+            "resolution" => Ok(Self::Resolution),
+            other => Err(format!("unknown annotation kind: {}", other)),
+        }
+    }
+}
+
+/// Annotation attached to an anomaly
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct AnomalyAnnotation {
+    /// Annotation identifier
+    pub annotation_id: Uuid,
+    /// Anomaly the annotation is attached to
+    pub alert_id: Uuid,
+    /// What the annotation records
+    pub kind: AnnotationKind,
+    /// Note, link title or resolution summary
+    pub text: String,
+    /// Linked URL
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub url: Option<String>,
+    /// Who wrote the annotation
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub author: Option<String>,
+    /// When the annotation was written
+    pub created_at: DateTime<Utc>,
+}
+
+impl AnomalyAnnotation {
+    /// Create an annotation written now
+    ///
+    /// Fails with a validation error if the text is blank or a link has no
+    /// URL.
+    pub fn new(
+        alert_id: Uuid,
+        kind: AnnotationKind,
+        text: impl Into<String>,
+        url: Option<String>,
+        author: Option<String>,
+    ) -> Result<Self> {
+        let text = text.into();
+        if text.trim().is_empty() {
+            return Err(Error::validation("annotation text must not be empty"));
+        }
+        if kind == AnnotationKind::Link && url.is_none() {
+            return Err(Error::validation("link annotations need a url"));
+        }
+        Ok(Self {
+            annotation_id: Uuid::new_v4(),
+            alert_id,
+            kind,
+            text,
+            url,
+            author,
+            created_at: Utc::now(),
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_new_annotation() {
+        let alert_id = Uuid::new_v4();
+        let note = AnomalyAnnotation::new(
+            alert_id,
+            AnnotationKind::Note,
+            "Traffic from a load test",
+            None,
+            Some("alice".to_string()),
+        )
+        .unwrap();
+        assert_eq!(note.alert_id, alert_id);
+        assert_eq!(note.kind, AnnotationKind::Note);
+
+        assert!(matches!(
+            AnomalyAnnotation::new(alert_id, AnnotationKind::Note, "  ", None, None),
+            Err(Error::Validation(_))
+        ));
+        assert!(matches!(
+            AnomalyAnnotation::new(alert_id, AnnotationKind::Link, "Dashboard", None, None),
+            Err(Error::Validation(_))
+        ));
+
+        for kind in [
+            AnnotationKind::Note,
+            AnnotationKind::Link,
+            AnnotationKind::Resolution,
+        ] {
+            assert_eq!(kind.to_string().parse::<AnnotationKind>().unwrap(), kind);
+        }
+    }
+}
@@ -0,0 +1,163 @@
+//! Audit log for automated and operator actions.
+//!
+//! Subsystems that change the outside world (remediation actions, for
+//! example) record what they did, or chose not to do, as [`AuditEntry`]
+//! values. The default [`TracingAuditLog`] emits them as structured log
+//! events on the `audit` target so they can be routed to a separate sink.
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::{
+    collections::{BTreeMap, VecDeque},
+    fmt,
+    sync::Mutex,
+};
+
+/// Outcome of an audited action
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum AuditOutcome {
+    /// The action was carried out
+    Success,
+    /// The action was attempted and failed
+    Failure,
+    /// The action was not attempted (guardrail, dry run)
+    Skipped,
+}
+
+impl fmt::Display for AuditOutcome {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            AuditOutcome::Success => write!(f, "success"),
+            AuditOutcome::Failure => write!(f, "failure"),
+            AuditOutcome::Skipped => write!(f, "skipped"),
+        }
+    }
+}
+
+/// One audited action
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct AuditEntry {
+    /// When the action happened
+    pub timestamp: DateTime<Utc>,
+    /// Subsystem or user performing the action
+    pub actor: String,
+    /// Action name (e.g. `remediation.http`)
+    pub action: String,
+    /// What the action applied to
+    pub target: String,
+    /// Outcome
+    pub outcome: AuditOutcome,
+    /// Additional details
+    pub details: BTreeMap<String, String>,
+}
+
+impl AuditEntry {
+    /// Create an entry timestamped now
+    pub fn new(
+        actor: impl Into<String>,
+        action: impl Into<String>,
+        target: impl Into<String>,
+        outcome: AuditOutcome,
+    ) -> Self {
+        Self {
+            timestamp: Utc::now(),
+            actor: actor.into(),
+            action: action.into(),
+            target: target.into(),
+            outcome,
+            details: BTreeMap::new(),
+        }
+    }
+
+    /// Add a detail
+    pub fn with_detail(mut self, key: impl Into<String>, value: impl Into<String>) -> Self {
+        self.details.insert(key.into(), value.into());
+        self
+    }
+}
+
+/// Destination for audit entries
+pub trait AuditLog: Send + Sync + fmt::Debug {
+    /// Record an entry
+    fn record(&self, entry: AuditEntry);
+}
+
+/// Audit log writing entries as structured log events on the `audit` target
+#[derive(Debug, Default, Clone, Copy)]
+pub struct TracingAuditLog;
+
+impl AuditLog for TracingAuditLog {
+    fn record(&self, entry: AuditEntry) {
+        let details = serde_json::to_string(&entry.details).unwrap_or_default();
+        tracing::info!(
+            target: "audit",
+            actor = %entry.actor,
+            action = %entry.action,
+            target_name = %entry.target,
+            outcome = %entry.outcome,
+            details = %details,
+            "Audit"
+        );
+    }
+}
+
+/// Audit log keeping the most recent entries in memory
+#[derive(Debug)]
+pub struct MemoryAuditLog {
+    capacity: usize,
+    entries: Mutex<VecDeque<AuditEntry>>,
+}
+
+impl MemoryAuditLog {
+    /// Create a log keeping at most `capacity` entries
+    pub fn new(capacity: usize) -> Self {
+        Self {
+            capacity,
+            entries: Mutex::new(VecDeque::with_capacity(capacity.min(1024))),
+        }
+    }
+
+    /// Recorded entries, oldest first
+    pub fn entries(&self) -> Vec<AuditEntry> {
+        self.entries.lock().unwrap().iter().cloned().collect()
+    }
+}
+
+impl AuditLog for MemoryAuditLog {
+    fn record(&self, entry: AuditEntry) {
+        let mut entries = self.entries.lock().unwrap();
+        if entries.len() == self.capacity {
+            entries.pop_front();
+        }
+        if self.capacity > 0 {
+            entries.push_back(entry);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_memory_audit_log() {
+        let log = MemoryAuditLog::new(2);
+        for i in 0..3 {
+            log.record(
+                AuditEntry::new(
+                    "test",
+                    "action",
+                    format!("target-{}", i),
+                    AuditOutcome::Success,
+                )
+                .with_detail("attempt", i.to_string()),
+            );
+        }
+
+        let entries = log.entries();
+        assert_eq!(entries.len(), 2);
+        assert_eq!(entries[0].target, "target-1");
+        assert_eq!(entries[1].details["attempt"], "2");
+    }
+}
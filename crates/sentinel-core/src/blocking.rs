@@ -0,0 +1,131 @@
+//! Dedicated pools for blocking work.
+//!
+//! librdkafka metadata calls and zstd compression block the thread they run
+//! on. On tokio's shared blocking pool a burst of either can hold threads
+//! needed elsewhere, so each kind runs on its own bounded pool, sized under
+//! `server.runtime` and created by [`init`] at startup. Until then (in tests
+//! or when the crates are embedded) work falls back to
+//! [`tokio::task::spawn_blocking`].
+//!
+//! Pools are small helper runtimes whose blocking threads are named after
+//! them (`sentinel-kafka`, `sentinel-compression`). Tasks run are counted in
+//! `sentinel_blocking_tasks_total` and waiting tasks are exported as the
+//! `sentinel_blocking_queued` gauge, both labelled by pool.
+
+use crate::{config::RuntimeConfig, Error, Result};
+use std::sync::OnceLock;
+use tokio::runtime::{Builder, Runtime};
+use tracing::{info, warn};
+
+/// Dedicated blocking pool
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BlockingPool {
+    /// Blocking librdkafka calls
+    Kafka,
+    /// Compression and decompression
+    Compression,
+}
+
+impl BlockingPool {
+    /// Pool name used for threads and metrics
+    pub fn name(&self) -> &'static str {
+        match self {
+            Self::Kafka => "kafka",
+            Self::Compression => "compression",
+        }
+    }
+}
+
+/// Runtimes backing the pools; never dropped, as dropping a runtime from
+/// async code panics
+#[derive(Debug)]
+struct Pools {
+    kafka: Runtime,
+    compression: Runtime,
+}
+
+static POOLS: OnceLock<Pools> = OnceLock::new();
+
+fn pool_runtime(pool: BlockingPool, threads: usize) -> Result<Runtime> {
+    Builder::new_multi_thread()
+        .worker_threads(1)
+        .max_blocking_threads(threads.max(1))
+        .thread_name(format!("sentinel-{}", pool.name()))
+        .build()
+        .map_err(|e| Error::internal(format!("Failed to build {} pool: {}", pool.name(), e)))
+}
+
+/// Create the dedicated pools
+///
+/// Only the first call has an effect.
+pub fn init(config: &RuntimeConfig) -> Result<()> {
+    if POOLS.get().is_some() {
+        warn!("Blocking pools already initialized");
+        return Ok(());
+    }
+    let pools = Pools {
+        kafka: pool_runtime(BlockingPool::Kafka, config.kafka_threads)?,
+        compression: pool_runtime(BlockingPool::Compression, config.compression_threads)?,
+    };
+    if POOLS.set(pools).is_ok() {
+        info!(
+            kafka_threads = config.kafka_threads,
+            compression_threads = config.compression_threads,
+            "Blocking pools initialized"
+        );
+    }
+    Ok(())
+}
+
+/// Run `f` on a dedicated pool and wait for its result
+pub async fn run<F, R>(pool: BlockingPool, f: F) -> Result<R>
+where
+    F: FnOnce() -> R + Send + 'static,
+    R: Send + 'static,
+{
+    let name = pool.name();
+    let queued = metrics::gauge!("sentinel_blocking_queued", "pool" => name);
+    queued.increment(1.0);
+    let task = {
+        let queued = queued.clone();
+        move || {
+            queued.decrement(1.0);
+            f()
+        }
+    };
+
+    let handle = match POOLS.get() {
+        Some(pools) => match pool {
+            BlockingPool::Kafka => pools.kafka.spawn_blocking(task),
+            BlockingPool::Compression => pools.compression.spawn_blocking(task),
+        },
+        None => tokio::task::spawn_blocking(task),
+    };
+    metrics::counter!("sentinel_blocking_tasks_total", "pool" => name).increment(1);
+
+    handle
+        .await
+        .map_err(|e| Error::internal(format!("Blocking {} task failed: {}", name, e)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_run_on_pools() {
+        init(&RuntimeConfig::default()).unwrap();
+
+        let thread = run(BlockingPool::Compression, || {
+            std::thread::current().name().map(str::to_string)
+        })
+        .await
+        .unwrap();
+        assert_eq!(thread.as_deref(), Some("sentinel-compression"));
+
+        let err = run(BlockingPool::Kafka, || -> u32 { panic!("librdkafka") })
+            .await
+            .unwrap_err();
+        assert!(err.to_string().contains("Blocking kafka task failed"));
+    }
+}
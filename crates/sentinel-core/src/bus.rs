@@ -0,0 +1,292 @@
+//! Internal event bus between subsystems.
+//!
+//! The processing pipeline publishes what happens to an [`EventBus`]: each
+//! prepared telemetry event, each stored anomaly, each dispatched alert,
+//! each anomaly state change and each incident update. Subsystems (the API
+//! stream, the audit log, alert re-sending) subscribe to the topics they
+//! need, so new consumers can be added without touching the processing
+//! loop.
+//!
+//! Topics are bounded broadcast channels. Publishing never blocks; a
+//! subscriber that falls more than the channel capacity behind skips the
+//! oldest messages and the skip is counted in
+//! `sentinel_bus_messages_lagged_total`. Subscribers that must not miss
+//! messages read with [`Subscription::next`], which reports the skip so they
+//! can catch up from storage.
+
+use crate::{
+    events::{AlertMetadata, AnomalyEvent, TelemetryEvent},
+    incident::{Incident, IncidentUpdate},
+    lifecycle::AnomalyStateChange,
+};
+use serde::{Deserialize, Serialize};
+use std::sync::Arc;
+use tokio::sync::broadcast;
+
+/// Default number of messages buffered per topic
+pub const DEFAULT_CAPACITY: usize = 1024;
+
+/// Alert sent to the routed alerters
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AlertDispatched {
+    /// Dispatched alert
+    pub alert: AnomalyEvent,
+    /// Delivery outcome per alerter
+    pub deliveries: Vec<AlertMetadata>,
+}
+
+/// Anomaly whose lifecycle state changed
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct LifecycleEvent {
+    /// Anomaly after the change
+    pub anomaly: AnomalyEvent,
+    /// The change
+    pub change: AnomalyStateChange,
+}
+
+/// Incident that opened or changed
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct IncidentEvent {
+    /// Incident after the update
+    pub incident: Incident,
+    /// What changed
+    pub update: IncidentUpdate,
+    /// Anomaly that opened the incident or attached to it
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub anomaly: Option<AnomalyEvent>,
+}
+
+/// One broadcast topic
+#[derive(Debug)]
+pub struct Topic<T> {
+    name: &'static str,
+    sender: broadcast::Sender<Arc<T>>,
+}
+
+impl<T> Clone for Topic<T> {
+    fn clone(&self) -> Self {
+        Self {
+            name: self.name,
+            sender: self.sender.clone(),
+        }
+    }
+}
+
+impl<T> Topic<T> {
+    fn new(name: &'static str, capacity: usize) -> Self {
+        let (sender, _) = broadcast::channel(capacity.max(1));
+        Self { name, sender }
+    }
+
+    /// Topic name
+    pub fn name(&self) -> &'static str {
+        self.name
+    }
+
+    /// Publish a message to current subscribers
+    ///
+    /// Messages published while nobody is subscribed are dropped.
+    pub fn publish(&self, message: impl Into<Arc<T>>) {
+        // Err only means there are no subscribers
+        let _ = self.sender.send(message.into());
+    }
+
+    /// Subscribe to messages published from now on
+    pub fn subscribe(&self) -> Subscription<T> {
+        Subscription {
+            name: self.name,
+            receiver: self.sender.subscribe(),
+        }
+    }
+
+    /// Whether anyone is subscribed
+    ///
+    /// Publishers can skip building expensive messages when nobody listens.
+    pub fn has_subscribers(&self) -> bool {
+        self.sender.receiver_count() > 0
+    }
+}
+
+/// Receiver of one topic
+#[derive(Debug)]
+pub struct Subscription<T> {
+    name: &'static str,
+    receiver: broadcast::Receiver<Arc<T>>,
+}
+
+/// Message or skip read from a [`Subscription`]
+#[derive(Debug)]
+pub enum Received<T> {
+    /// The next message
+    Message(Arc<T>),
+    /// This many messages were skipped because the subscriber fell behind
+    Lagged(u64),
+}
+
+impl<T> Subscription<T> {
+    /// Next message, or `None` once the bus is dropped
+    ///
+    /// Messages missed because this subscriber fell behind are skipped.
+    pub async fn recv(&mut self) -> Option<Arc<T>> {
+        loop {
+            match self.next().await? {
+                Received::Message(message) => return Some(message),
+                Received::Lagged(_) => {}
+            }
+        }
+    }
+
+    /// Next message or the number of messages skipped since the last read,
+    /// or `None` once the bus is dropped
+    pub async fn next(&mut self) -> Option<Received<T>> {
+        match self.receiver.recv().await {
+            Ok(message) => Some(Received::Message(message)),
+            Err(broadcast::error::RecvError::Lagged(skipped)) => {
+                tracing::warn!(topic = self.name, skipped, "Bus subscriber lagged");
+                metrics::counter!("sentinel_bus_messages_lagged_total", "topic" => self.name)
+                    .increment(skipped);
+                Some(Received::Lagged(skipped))
+            }
+            Err(broadcast::error::RecvError::Closed) => None,
+        }
+    }
+}
+
+/// Typed topics shared by the subsystems of one sentinel
+///
+/// Cloning the bus shares its topics.
+#[derive(Debug, Clone)]
+pub struct EventBus {
+    telemetry: Topic<TelemetryEvent>,
+    anomalies: Topic<AnomalyEvent>,
+    alerts: Topic<AlertDispatched>,
+    lifecycle: Topic<LifecycleEvent>,
+    incidents: Topic<IncidentEvent>,
+}
+
+impl EventBus {
+    /// Create a bus buffering up to `capacity` messages per topic
+    pub fn new(capacity: usize) -> Self {
+        Self {
+            telemetry: Topic::new("telemetry", capacity),
+            anomalies: Topic::new("anomalies", capacity),
+            alerts: Topic::new("alerts", capacity),
+            lifecycle: Topic::new("lifecycle", capacity),
+            incidents: Topic::new("incidents", capacity),
+        }
+    }
+
+    /// Prepared telemetry events, after they were stored
+    pub fn telemetry(&self) -> &Topic<TelemetryEvent> {
+        &self.telemetry
+    }
+
+    /// Anomalies, after they were stored
+    pub fn anomalies(&self) -> &Topic<AnomalyEvent> {
+        &self.anomalies
+    }
+
+    /// Alerts sent to alerters, with their delivery outcomes
+    pub fn alerts(&self) -> &Topic<AlertDispatched> {
+        &self.alerts
+    }
+
+    /// Anomaly state changes
+    pub fn lifecycle(&self) -> &Topic<LifecycleEvent> {
+        &self.lifecycle
+    }
+
+    /// Incidents opened, updated by anomalies or changed by responders
+    pub fn incidents(&self) -> &Topic<IncidentEvent> {
+        &self.incidents
+    }
+}
+
+impl Default for EventBus {
+    fn default() -> Self {
+        Self::new(DEFAULT_CAPACITY)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        events::{PromptInfo, ResponseInfo},
+        types::{ModelId, ServiceId},
+    };
+
+    fn create_test_event() -> TelemetryEvent {
+        TelemetryEvent::new(
+            ServiceId::new("test"),
+            ModelId::new("gpt-4"),
+            PromptInfo {
+                text: "test".to_string(),
+                tokens: 10,
+                embedding: None,
+            },
+            ResponseInfo {
+                text: "response".to_string(),
+                tokens: 20,
+                finish_reason: "stop".to_string(),
+                embedding: None,
+            },
+            100.0,
+            0.01,
+        )
+    }
+
+    #[tokio::test]
+    async fn test_subscribers_receive_independently() {
+        let bus = EventBus::default();
+        assert!(!bus.telemetry().has_subscribers());
+        // Dropped, nobody is subscribed yet
+        bus.telemetry().publish(create_test_event());
+
+        let mut first = bus.telemetry().subscribe();
+        let mut second = bus.clone().telemetry().subscribe();
+        assert!(bus.telemetry().has_subscribers());
+
+        let event = create_test_event();
+        bus.telemetry().publish(event.clone());
+        assert_eq!(first.recv().await.unwrap().event_id, event.event_id);
+        assert_eq!(second.recv().await.unwrap().event_id, event.event_id);
+    }
+
+    #[tokio::test]
+    async fn test_lagging_subscriber_skips_oldest() {
+        let bus = EventBus::new(2);
+        let mut subscription = bus.telemetry().subscribe();
+
+        let events: Vec<_> = (0..3).map(|_| create_test_event()).collect();
+        for event in &events {
+            bus.telemetry().publish(event.clone());
+        }
+
+        assert_eq!(subscription.recv().await.unwrap().event_id, events[1].event_id);
+        assert_eq!(subscription.recv().await.unwrap().event_id, events[2].event_id);
+
+        drop(bus);
+        assert!(subscription.recv().await.is_none());
+    }
+
+    #[tokio::test]
+    async fn test_next_reports_lag() {
+        let bus = EventBus::new(2);
+        let mut subscription = bus.telemetry().subscribe();
+
+        let events: Vec<_> = (0..3).map(|_| create_test_event()).collect();
+        for event in &events {
+            bus.telemetry().publish(event.clone());
+        }
+
+        assert!(matches!(
+            subscription.next().await,
+            Some(Received::Lagged(1))
+        ));
+        match subscription.next().await {
+            Some(Received::Message(event)) => assert_eq!(event.event_id, events[1].event_id),
+            other => panic!("expected a message, got {:?}", other),
+        }
+    }
+}
@@ -0,0 +1,184 @@
+//! Per-model capabilities.
+//!
+//! A [`CapabilityTable`] maps model names to what the model can take, such
+//! as its context window, so detectors can judge a request against the
+//! model's limits rather than its history. Model names match exactly or by
+//! longest prefix, as in [`crate::pricing::PriceTable`], so
+//! `gpt-4o-mini-2024-07-18` uses the `gpt-4o-mini` entry.
+
+use crate::{
+    config::ContextLimitConfig,
+    error::{Error, Result},
+    types::ModelId,
+};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+
+/// Built-in context windows (prompt and response tokens together)
+const DEFAULT_CONTEXT_TOKENS: &[(&str, u32)] = &[
+    ("gpt-4", 8_192),
+    ("gpt-4-32k", 32_768),
+    ("gpt-4-turbo", 128_000),
+    ("gpt-4o", 128_000),
+    ("gpt-4o-mini", 128_000),
+    ("gpt-3.5-turbo", 16_385),
+    ("o1", 200_000),
+    ("o1-mini", 128_000),
+    ("claude-3-opus", 200_000),
+    ("claude-3-sonnet", 200_000),
+    ("claude-3-5-sonnet", 200_000),
+    ("claude-3-haiku", 200_000),
+    ("claude-3-5-haiku", 200_000),
+    ("gemini-1.5-pro", 2_097_152),
+    ("gemini-1.5-flash", 1_048_576),
+    ("mistral-large", 128_000),
+    ("mistral-small", 32_000),
+];
+
+/// Capabilities of one model
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ModelCapabilities {
+    /// Context window: prompt and response tokens the model can handle
+    /// together
+    pub max_context_tokens: u32,
+}
+
+/// Model capability table
+#[derive(Debug, Clone, Default)]
+pub struct CapabilityTable {
+    /// Capabilities keyed by lowercase model name (or prefix)
+    models: BTreeMap<String, ModelCapabilities>,
+}
+
+impl CapabilityTable {
+    /// Create an empty capability table
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Create a capability table with the built-in context windows of
+    /// common models
+    pub fn with_defaults() -> Self {
+        let mut table = Self::new();
+        for (model, max_context_tokens) in DEFAULT_CONTEXT_TOKENS {
+            table.models.insert(
+                model.to_string(),
+                ModelCapabilities {
+                    max_context_tokens: *max_context_tokens,
+                },
+            );
+        }
+        table
+    }
+
+    /// Build a capability table from configuration
+    ///
+    /// Configured context windows override the built-in defaults.
+    pub fn from_config(config: &ContextLimitConfig) -> Result<Self> {
+        let mut table = if config.include_defaults {
+            Self::with_defaults()
+        } else {
+            Self::new()
+        };
+        for (model, max_context_tokens) in &config.models {
+            table.set(
+                model,
+                ModelCapabilities {
+                    max_context_tokens: *max_context_tokens,
+                },
+            )?;
+        }
+        Ok(table)
+    }
+
+    /// Set the capabilities of a model (or model prefix)
+    pub fn set(&mut self, model: &str, capabilities: ModelCapabilities) -> Result<()> {
+        let model = model.trim().to_lowercase();
+        if model.is_empty() {
+            return Err(Error::validation("Model name cannot be empty"));
+        }
+        if capabilities.max_context_tokens == 0 {
+            return Err(Error::validation(format!(
+                "max_context_tokens for {} must be positive",
+                model
+            )));
+        }
+        self.models.insert(model, capabilities);
+        Ok(())
+    }
+
+    /// All configured capabilities
+    pub fn models(&self) -> &BTreeMap<String, ModelCapabilities> {
+        &self.models
+    }
+
+    /// Capabilities of a model: exact match, otherwise the longest matching
+    /// prefix
+    pub fn capabilities_for(&self, model: &ModelId) -> Option<ModelCapabilities> {
+        let model = model.as_str().to_lowercase();
+        if let Some(capabilities) = self.models.get(&model) {
+            return Some(*capabilities);
+        }
+
+        self.models
+            .iter()
+            .filter(|(name, _)| model.starts_with(name.as_str()))
+            .max_by_key(|(name, _)| name.len())
+            .map(|(_, capabilities)| *capabilities)
+    }
+
+    /// Context window of a model, if known
+    pub fn max_context_tokens(&self, model: &ModelId) -> Option<u32> {
+        self.capabilities_for(model)
+            .map(|capabilities| capabilities.max_context_tokens)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_longest_prefix_match() {
+        let table = CapabilityTable::with_defaults();
+        assert_eq!(
+            table.max_context_tokens(&ModelId::new("gpt-4o-mini-2024-07-18")),
+            Some(128_000)
+        );
+        assert_eq!(
+            table.max_context_tokens(&ModelId::new("GPT-4-0613")),
+            Some(8_192)
+        );
+        assert_eq!(table.max_context_tokens(&ModelId::new("llama-3")), None);
+    }
+
+    #[test]
+    fn test_config_overrides_defaults() {
+        let config = ContextLimitConfig {
+            include_defaults: true,
+            models: [
+                ("gpt-4".to_string(), 16_000),
+                ("llama-3".to_string(), 8_000),
+            ]
+            .into_iter()
+            .collect(),
+            warn_ratio: 0.9,
+        };
+        let table = CapabilityTable::from_config(&config).unwrap();
+        assert_eq!(
+            table.max_context_tokens(&ModelId::new("gpt-4")),
+            Some(16_000)
+        );
+        assert_eq!(
+            table.max_context_tokens(&ModelId::new("llama-3-70b")),
+            Some(8_000)
+        );
+
+        let config = ContextLimitConfig {
+            include_defaults: false,
+            models: [("llama-3".to_string(), 0)].into_iter().collect(),
+            warn_ratio: 0.9,
+        };
+        assert!(CapabilityTable::from_config(&config).is_err());
+    }
+}
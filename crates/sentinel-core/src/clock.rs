@@ -0,0 +1,96 @@
+//! Injectable wall clock.
+//!
+//! Time-dependent components (deduplication windows, baselines, retention)
+//! read the current time from a [`Clock`] carried in their configuration
+//! instead of calling `Utc::now()` directly. The default is the system
+//! clock; tests and replays use a [`Clock::manual`] clock and move it
+//! forward explicitly, so time-dependent behavior is deterministic and does
+//! not need real sleeps.
+
+use chrono::{DateTime, Duration, Utc};
+use std::sync::{Arc, Mutex};
+
+/// Source of the current time
+///
+/// Cloning a manual clock shares it: advancing one clone advances all.
+#[derive(Debug, Clone, Default)]
+pub struct Clock {
+    manual: Option<Arc<Mutex<DateTime<Utc>>>>,
+}
+
+impl Clock {
+    /// The system clock
+    pub fn system() -> Self {
+        Self::default()
+    }
+
+    /// A clock that stands still at `start` until moved
+    pub fn manual(start: DateTime<Utc>) -> Self {
+        Self {
+            manual: Some(Arc::new(Mutex::new(start))),
+        }
+    }
+
+    /// Whether this is a manual clock
+    pub fn is_manual(&self) -> bool {
+        self.manual.is_some()
+    }
+
+    /// Current time
+    pub fn now(&self) -> DateTime<Utc> {
+        match &self.manual {
+            Some(now) => *now.lock().unwrap(),
+            None => Utc::now(),
+        }
+    }
+
+    /// Move a manual clock forward by `by` (no-op on the system clock)
+    pub fn advance(&self, by: Duration) {
+        if let Some(now) = &self.manual {
+            let mut now = now.lock().unwrap();
+            *now += by;
+        }
+    }
+
+    /// Move a manual clock forward to `at`, never backwards (no-op on the
+    /// system clock)
+    pub fn advance_to(&self, at: DateTime<Utc>) {
+        if let Some(now) = &self.manual {
+            let mut now = now.lock().unwrap();
+            *now = (*now).max(at);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_manual_clock() {
+        let start = Utc::now() - Duration::days(1);
+        let clock = Clock::manual(start);
+        let shared = clock.clone();
+        assert!(clock.is_manual());
+        assert_eq!(clock.now(), start);
+
+        shared.advance(Duration::seconds(30));
+        assert_eq!(clock.now(), start + Duration::seconds(30));
+
+        // Never moves backwards
+        clock.advance_to(start);
+        assert_eq!(clock.now(), start + Duration::seconds(30));
+        clock.advance_to(start + Duration::minutes(5));
+        assert_eq!(shared.now(), start + Duration::minutes(5));
+    }
+
+    #[test]
+    fn test_system_clock() {
+        let clock = Clock::system();
+        assert!(!clock.is_manual());
+        let before = Utc::now();
+        clock.advance(Duration::days(1));
+        let now = clock.now();
+        assert!(now >= before && now <= Utc::now());
+    }
+}
@@ -0,0 +1,122 @@
+//! Correlation IDs tying telemetry, anomalies, alerts and API calls together.
+//!
+//! Every telemetry event gets a correlation ID at ingestion, kept in its
+//! metadata under [`METADATA_KEY`]; producers may set their own. Processing
+//! of the event runs inside a [`scope`] with that ID, so the anomalies it
+//! produces, the alerts sent for them and the log lines written along the
+//! way all carry it. API requests take theirs from the [`HEADER`] request
+//! header, or get a new one, and echo it in the response.
+
+use crate::events::TelemetryEvent;
+use std::future::Future;
+use uuid::Uuid;
+
+/// Telemetry metadata key holding the correlation ID
+pub const METADATA_KEY: &str = "correlation_id";
+
+/// HTTP header carrying the correlation ID
+pub const HEADER: &str = "x-correlation-id";
+
+/// Maximum length of a correlation ID
+pub const MAX_LEN: usize = 128;
+
+tokio::task_local! {
+    static CURRENT: String;
+}
+
+/// Generate a new correlation ID
+pub fn generate() -> String {
+    Uuid::new_v4().to_string()
+}
+
+/// Whether `id` can be used as a correlation ID: 1 to [`MAX_LEN`] visible
+/// ASCII characters, so it is safe to echo in headers and logs
+pub fn is_valid(id: &str) -> bool {
+    !id.is_empty() && id.len() <= MAX_LEN && id.bytes().all(|b| b.is_ascii_graphic())
+}
+
+/// Correlation ID of an event, if assigned
+pub fn of(event: &TelemetryEvent) -> Option<&str> {
+    event.metadata.get(METADATA_KEY).map(String::as_str)
+}
+
+/// Assign a correlation ID to an event, keeping a valid one it already has
+pub fn assign(event: &mut TelemetryEvent) -> &str {
+    let id = event.metadata.entry(METADATA_KEY.to_string()).or_default();
+    if !is_valid(id) {
+        *id = generate();
+    }
+    id
+}
+
+/// Run `future` with a correlation ID
+pub async fn scope<F: Future>(id: String, future: F) -> F::Output {
+    CURRENT.scope(id, future).await
+}
+
+/// Correlation ID of the current task, if any
+pub fn current() -> Option<String> {
+    CURRENT.try_with(Clone::clone).ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        events::{PromptInfo, ResponseInfo},
+        types::{ModelId, ServiceId},
+    };
+
+    fn event() -> TelemetryEvent {
+        TelemetryEvent::new(
+            ServiceId::new("chat"),
+            ModelId::new("gpt-4"),
+            PromptInfo {
+                text: "Hello".to_string(),
+                tokens: 10,
+                embedding: None,
+            },
+            ResponseInfo {
+                text: "Hi".to_string(),
+                tokens: 5,
+                finish_reason: "stop".to_string(),
+                embedding: None,
+            },
+            100.0,
+            0.001,
+        )
+    }
+
+    #[test]
+    fn test_assign() {
+        let mut generated = event();
+        let id = assign(&mut generated).to_string();
+        assert!(is_valid(&id));
+        assert_eq!(of(&generated), Some(id.as_str()));
+        // Stable once assigned
+        assert_eq!(assign(&mut generated), id);
+
+        let mut provided = event();
+        provided
+            .metadata
+            .insert(METADATA_KEY.to_string(), "req-42".to_string());
+        assert_eq!(assign(&mut provided), "req-42");
+
+        let mut invalid = event();
+        invalid
+            .metadata
+            .insert(METADATA_KEY.to_string(), "bad id\n".to_string());
+        assert_ne!(assign(&mut invalid), "bad id\n");
+        assert!(!is_valid(&"x".repeat(MAX_LEN + 1)));
+    }
+
+    #[tokio::test]
+    async fn test_scope() {
+        assert!(current().is_none());
+        scope("req-42".to_string(), async {
+            assert_eq!(current().as_deref(), Some("req-42"));
+        })
+        .await;
+        assert!(current().is_none());
+    }
+}
@@ -0,0 +1,96 @@
+//! Request deadlines propagated to downstream calls.
+//!
+//! The API sets a deadline for each request with [`scope`]; storage backends
+//! read the time left with [`remaining`] to bound their own queries, so a
+//! slow database cannot hold a request past its budget. Outside a scope
+//! there is no deadline.
+
+use crate::{Error, Result};
+use std::{future::Future, time::Duration};
+use tokio::time::Instant;
+
+tokio::task_local! {
+    static DEADLINE: Instant;
+}
+
+/// Run `future` with a deadline
+///
+/// A deadline already in effect is kept if it is earlier.
+pub async fn scope<F: Future>(deadline: Instant, future: F) -> F::Output {
+    let deadline = current().map_or(deadline, |outer| outer.min(deadline));
+    DEADLINE.scope(deadline, future).await
+}
+
+/// Deadline of the current task, if any
+pub fn current() -> Option<Instant> {
+    DEADLINE.try_with(|deadline| *deadline).ok()
+}
+
+/// Time left until the current deadline, if any
+pub fn remaining() -> Option<Duration> {
+    current().map(|deadline| deadline.saturating_duration_since(Instant::now()))
+}
+
+/// Fail `future` with a timeout error once the current deadline passes
+pub async fn enforce<T, F>(operation: &str, future: F) -> Result<T>
+where
+    F: Future<Output = Result<T>>,
+{
+    match current() {
+        Some(deadline) => tokio::time::timeout_at(deadline, future)
+            .await
+            .unwrap_or_else(|_| {
+                Err(Error::timeout(format!(
+                    "{} exceeded the request deadline",
+                    operation
+                )))
+            }),
+        None => future.await,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test(start_paused = true)]
+    async fn test_scope() {
+        assert!(current().is_none());
+        assert!(remaining().is_none());
+
+        let deadline = Instant::now() + Duration::from_secs(5);
+        scope(deadline, async move {
+            assert_eq!(current(), Some(deadline));
+            assert_eq!(remaining(), Some(Duration::from_secs(5)));
+
+            // A later inner deadline does not extend the outer one
+            scope(deadline + Duration::from_secs(10), async move {
+                assert_eq!(current(), Some(deadline));
+            })
+            .await;
+            scope(deadline - Duration::from_secs(4), async move {
+                assert_eq!(remaining(), Some(Duration::from_secs(1)));
+            })
+            .await;
+        })
+        .await;
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn test_enforce() {
+        let slow = async {
+            tokio::time::sleep(Duration::from_secs(10)).await;
+            Ok(())
+        };
+        let deadline = Instant::now() + Duration::from_secs(1);
+        let result = scope(deadline, enforce("query", slow)).await;
+        assert!(matches!(result, Err(Error::Timeout(_))));
+
+        // No deadline, no limit
+        let slow = async {
+            tokio::time::sleep(Duration::from_secs(10)).await;
+            Ok(7)
+        };
+        assert_eq!(enforce("query", slow).await.unwrap(), 7);
+    }
+}
@@ -5,10 +5,18 @@
 //! - AnomalyEvent: Detected anomalies
 //! - AlertEvent: Alerts sent to incident manager
 
-use crate::types::{AnomalyType, DetectionMethod, ModelId, ServiceId, Severity};
+use crate::annotation::AnomalyAnnotation;
+use crate::labels::Labels;
+use crate::lifecycle::{AnomalyState, AnomalyStateChange};
+use crate::types::{
+    AnomalyType, DetectionMethod, Environment, ModelId, ModelVersion, ServiceId, Severity,
+};
+use crate::window::WindowMetadata;
 use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
-use std::collections::HashMap;
+use sha2::{Digest, Sha256};
+use std::collections::{BTreeMap, HashMap};
+use std::sync::Arc;
 use uuid::Uuid;
 use validator::Validate;
 
@@ -33,11 +41,22 @@ pub struct TelemetryEvent {
     /// Model identifier (e.g., "gpt-4", "claude-3")
     pub model: ModelId,
 
-    /// Prompt information
-    pub prompt: PromptInfo,
+    /// Model version, when the serving model identifies one
+    #[serde(default)]
+    pub model_version: Option<ModelVersion>,
 
-    /// Response information
-    pub response: ResponseInfo,
+    /// Deployment environment; events from different environments keep
+    /// separate baselines
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub environment: Option<Environment>,
+
+    /// Prompt information, shared by clones of the event (see
+    /// [`prompt_mut`](Self::prompt_mut))
+    pub prompt: Arc<PromptInfo>,
+
+    /// Response information, shared by clones of the event (see
+    /// [`response_mut`](Self::response_mut))
+    pub response: Arc<ResponseInfo>,
 
     /// Request latency in milliseconds
     #[validate(range(min = 0.0))]
@@ -47,9 +66,18 @@ pub struct TelemetryEvent {
     #[validate(range(min = 0.0))]
     pub cost_usd: f64,
 
+    /// Latency in milliseconds by request component (e.g. `retrieval`,
+    /// `tool`, `provider`), from the child spans of the request span
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub latency_breakdown: BTreeMap<String, f64>,
+
     /// Additional metadata
     pub metadata: HashMap<String, String>,
 
+    /// Labels for slicing queries and routing alerts (e.g. `team=search`)
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub labels: Labels,
+
     /// Errors if any
     pub errors: Vec<String>,
 }
@@ -89,6 +117,7 @@ pub struct ResponseInfo {
 
 /// Anomaly event detected by Sentinel
 #[derive(Debug, Clone, Serialize, Deserialize, Validate)]
+#[serde(remote = "Self")]
 pub struct AnomalyEvent {
     /// Unique alert identifier
     pub alert_id: Uuid,
@@ -108,6 +137,21 @@ pub struct AnomalyEvent {
     /// Model identifier
     pub model: ModelId,
 
+    /// Stable hash of the service, model, anomaly type and metric, shared
+    /// by every occurrence of the same anomaly (see [`signature_hash`]);
+    /// recomputed on deserialization when missing or empty
+    #[serde(default)]
+    pub signature_hash: String,
+
+    /// Deployment environment of the triggering telemetry
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub environment: Option<Environment>,
+
+    /// Correlation ID of the telemetry event or request the anomaly was
+    /// detected from
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub correlation_id: Option<String>,
+
     /// Detection method used
     pub detection_method: DetectionMethod,
 
@@ -132,6 +176,31 @@ pub struct AnomalyEvent {
 
     /// Runbook URL
     pub runbook_url: Option<String>,
+
+    /// Deep links to the offending trace, keyed by tracing backend
+    #[serde(default)]
+    pub trace_links: BTreeMap<String, String>,
+
+    /// Labels of the telemetry event the anomaly was detected from
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub labels: Labels,
+
+    /// Lifecycle state
+    #[serde(default)]
+    pub state: AnomalyState,
+
+    /// When the state last changed
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub state_changed_at: Option<DateTime<Utc>>,
+
+    /// What was dropped to fit the alert payload size limit, if anything
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub truncation: Option<PayloadTruncation>,
+
+    /// Responder annotations, oldest first (attached when the anomaly is
+    /// read back)
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub annotations: Vec<AnomalyAnnotation>,
 }
 
 /// Detailed anomaly information
@@ -154,6 +223,76 @@ pub struct AnomalyDetails {
 
     /// Additional details
     pub additional: HashMap<String, serde_json::Value>,
+
+    /// Every contributing metric, for ensemble and multivariate detectors;
+    /// the top-level fields describe the primary one
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub observations: Vec<MetricObservation>,
+}
+
+/// Observation of one metric contributing to an anomaly
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct MetricObservation {
+    /// Metric name
+    pub metric: String,
+
+    /// Observed value
+    pub value: f64,
+
+    /// Baseline/expected value
+    pub baseline: f64,
+
+    /// Threshold for this metric (if it has its own)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub threshold: Option<f64>,
+
+    /// Standard deviations from baseline (if applicable)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub deviation_sigma: Option<f64>,
+
+    /// Share of the anomaly score attributed to this metric (0.0 - 1.0)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub contribution: Option<f64>,
+}
+
+impl MetricObservation {
+    /// Create an observation of a metric against its baseline
+    pub fn new(metric: impl Into<String>, value: f64, baseline: f64) -> Self {
+        Self {
+            metric: metric.into(),
+            value,
+            baseline,
+            threshold: None,
+            deviation_sigma: None,
+            contribution: None,
+        }
+    }
+
+    /// Set the metric's threshold
+    pub fn with_threshold(mut self, threshold: f64) -> Self {
+        self.threshold = Some(threshold);
+        self
+    }
+
+    /// Set the deviation from baseline in standard deviations
+    pub fn with_deviation_sigma(mut self, sigma: f64) -> Self {
+        self.deviation_sigma = Some(sigma);
+        self
+    }
+
+    /// Set the share of the anomaly score attributed to the metric
+    pub fn with_contribution(mut self, contribution: f64) -> Self {
+        self.contribution = Some(contribution);
+        self
+    }
+
+    /// Ranking weight: contribution, then absolute deviation
+    fn weight(&self) -> (f64, f64) {
+        (
+            self.contribution.unwrap_or(0.0),
+            self.deviation_sigma.map(f64::abs).unwrap_or(0.0),
+        )
+    }
 }
 
 /// Context information for anomaly
@@ -174,10 +313,139 @@ pub struct AnomalyContext {
     /// Number of samples in window
     pub sample_count: usize,
 
+    /// Timestamps and span of the samples analyzed, when known
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub window: Option<WindowMetadata>,
+
     /// Additional context
     pub additional: HashMap<String, String>,
 }
 
+/// Parts of an anomaly dropped to fit an alert payload size limit
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PayloadTruncation {
+    /// Size of the full payload (bytes)
+    pub original_bytes: usize,
+    /// Prompt/response text was dropped from the additional maps
+    pub text_dropped: bool,
+    /// Additional detail/context entries dropped, text aside
+    pub additional_dropped: usize,
+}
+
+/// Alert metadata for tracking delivery
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AlertMetadata {
+    /// Unique alert ID
+    pub alert_id: String,
+    /// Alerter the delivery went through
+    #[serde(default)]
+    pub alerter: String,
+    /// Alert severity
+    #[serde(default)]
+    pub severity: Severity,
+    /// When the anomaly was detected
+    pub anomaly_timestamp: DateTime<Utc>,
+    /// Number of delivery attempts
+    pub attempts: u32,
+    /// Last delivery attempt timestamp
+    pub last_attempt: DateTime<Utc>,
+    /// Delivery status
+    pub status: AlertStatus,
+    /// End-to-end latency from detection to delivery confirmation
+    #[serde(default)]
+    pub latency_ms: Option<f64>,
+    /// Last delivery error
+    #[serde(default)]
+    pub error: Option<String>,
+    /// Summary of the request sent, e.g. method and URL, for alerters that
+    /// report it
+    #[serde(default)]
+    pub request: Option<String>,
+    /// Response body returned by the receiver, e.g. a ticket ID, for
+    /// alerters that capture it
+    #[serde(default)]
+    pub response: Option<String>,
+}
+
+impl AlertMetadata {
+    /// Start tracking delivery of an anomaly through an alerter
+    pub fn pending(anomaly: &AnomalyEvent, alerter: impl Into<String>) -> Self {
+        Self {
+            alert_id: anomaly.alert_id.to_string(),
+            alerter: alerter.into(),
+            severity: anomaly.severity,
+            anomaly_timestamp: anomaly.timestamp,
+            attempts: 0,
+            last_attempt: anomaly.timestamp,
+            status: AlertStatus::Pending,
+            latency_ms: None,
+            error: None,
+            request: None,
+            response: None,
+        }
+    }
+
+    /// Record a confirmed delivery
+    pub fn mark_delivered(&mut self, at: DateTime<Utc>) {
+        self.attempts += 1;
+        self.last_attempt = at;
+        self.status = AlertStatus::Delivered;
+        self.latency_ms = Some(
+            (at - self.anomaly_timestamp)
+                .num_microseconds()
+                .map_or(0.0, |us| us.max(0) as f64 / 1000.0),
+        );
+        self.error = None;
+    }
+
+    /// Record a failed delivery
+    pub fn mark_failed(&mut self, at: DateTime<Utc>, error: impl Into<String>) {
+        self.attempts += 1;
+        self.last_attempt = at;
+        self.status = AlertStatus::Failed;
+        self.error = Some(error.into());
+    }
+}
+
+/// Alert delivery status
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum AlertStatus {
+    /// Pending delivery
+    Pending,
+    /// Successfully delivered
+    Delivered,
+    /// Failed after retries
+    Failed,
+    /// Deduplicated (not sent)
+    Deduplicated,
+}
+
+impl std::fmt::Display for AlertStatus {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Pending => write!(f, "pending"),
+            Self::Delivered => write!(f, "delivered"),
+            Self::Failed => write!(f, "failed"),
+            Self::Deduplicated => write!(f, "deduplicated"),
+        }
+    }
+}
+
+impl std::str::FromStr for AlertStatus {
+    type Err = String;
+
+    fn from_str(s: &str) -> std::result::Result<Self, Self::Err> {
+        match s {
+            "pending" => Ok(Self::Pending),
+            "delivered" => Ok(Self::Delivered),
+            "failed" => Ok(Self::Failed),
+            "deduplicated" => Ok(Self::Deduplicated),
+            other => Err(format!("unknown alert status: {}", other)),
+        }
+    }
+}
+
 /// Alert event sent to incident manager
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct AlertEvent {
@@ -202,6 +470,9 @@ pub struct AlertEvent {
     /// Model affected
     pub model: ModelId,
 
+    /// Signature hash of the source anomaly
+    pub signature_hash: String,
+
     /// Alert tags
     pub tags: Vec<String>,
 
@@ -225,24 +496,85 @@ impl TelemetryEvent {
             service_name,
             trace_id: None,
             span_id: None,
+            model_version: ModelVersion::parse(model.as_str()),
             model,
-            prompt,
-            response,
+            environment: None,
+            prompt: Arc::new(prompt),
+            response: Arc::new(response),
             latency_ms,
             cost_usd,
+            latency_breakdown: BTreeMap::new(),
             metadata: HashMap::new(),
+            labels: Labels::new(),
             errors: Vec::new(),
         }
     }
 
+    /// Set the deployment environment
+    pub fn with_environment(mut self, environment: impl Into<Environment>) -> Self {
+        self.environment = Some(environment.into());
+        self
+    }
+
+    /// Add a label
+    pub fn with_label(mut self, key: impl Into<String>, value: impl Into<String>) -> Self {
+        self.labels.insert(key.into(), value.into());
+        self
+    }
+
+    /// Prompt information for modification
+    ///
+    /// Texts and embeddings are shared between clones of an event, so
+    /// passing events between pipeline stages does not copy them; they are
+    /// copied here only if another clone still holds them.
+    pub fn prompt_mut(&mut self) -> &mut PromptInfo {
+        Arc::make_mut(&mut self.prompt)
+    }
+
+    /// Response information for modification, copied only if another clone
+    /// of the event still holds it
+    pub fn response_mut(&mut self) -> &mut ResponseInfo {
+        Arc::make_mut(&mut self.response)
+    }
+
     /// Check if event has errors
     pub fn has_errors(&self) -> bool {
         !self.errors.is_empty()
     }
 
+    /// Model version, falling back to parsing the model identifier
+    ///
+    /// Covers events deserialized without a `model_version` field.
+    pub fn resolved_model_version(&self) -> Option<ModelVersion> {
+        self.model_version
+            .clone()
+            .or_else(|| ModelVersion::parse(self.model.as_str()))
+    }
+
     /// Calculate total tokens
     pub fn total_tokens(&self) -> u32 {
-        self.prompt.tokens + self.response.tokens
+        self.prompt.tokens.saturating_add(self.response.tokens)
+    }
+
+    /// Share of the request latency spent in each component of the latency
+    /// breakdown
+    ///
+    /// Components can overlap (e.g. parallel tool calls), so shares are
+    /// taken of the larger of the request latency and the components' sum
+    /// and never add up to more than 1.
+    pub fn latency_shares(&self) -> BTreeMap<String, f64> {
+        let total = self
+            .latency_breakdown
+            .values()
+            .sum::<f64>()
+            .max(self.latency_ms);
+        if total <= 0.0 {
+            return BTreeMap::new();
+        }
+        self.latency_breakdown
+            .iter()
+            .map(|(component, ms)| (component.clone(), ms / total))
+            .collect()
     }
 
     /// Get error rate (0 or 1 for single event)
@@ -253,6 +585,73 @@ impl TelemetryEvent {
             0.0
         }
     }
+
+    /// Approximate bytes held by the event, including texts, embeddings,
+    /// metadata and labels
+    pub fn approximate_size(&self) -> usize {
+        let embedding = |e: &Option<Vec<f32>>| e.as_ref().map_or(0, |e| e.len() * 4);
+        let pairs = |len: usize, bytes: usize| len * 2 * std::mem::size_of::<String>() + bytes;
+        std::mem::size_of::<Self>()
+            + std::mem::size_of::<PromptInfo>()
+            + std::mem::size_of::<ResponseInfo>()
+            + self.prompt.text.len()
+            + self.response.text.len()
+            + self.response.finish_reason.len()
+            + embedding(&self.prompt.embedding)
+            + embedding(&self.response.embedding)
+            + pairs(
+                self.metadata.len(),
+                self.metadata.iter().map(|(k, v)| k.len() + v.len()).sum(),
+            )
+            + pairs(
+                self.labels.len(),
+                self.labels.iter().map(|(k, v)| k.len() + v.len()).sum(),
+            )
+            + pairs(
+                self.latency_breakdown.len(),
+                self.latency_breakdown.keys().map(|k| k.len()).sum(),
+            )
+            + self.errors.iter().map(|e| e.len()).sum::<usize>()
+    }
+}
+
+/// Stable hash of an anomaly signature
+///
+/// Every occurrence of an anomaly with the same service, model, anomaly type
+/// and metric hashes to the same 16 hex digits, across processes and
+/// releases. Deduplication, external ticket lookups and alerter grouping
+/// keys all use it, so they agree on which anomalies are the same.
+pub fn signature_hash(
+    service: &ServiceId,
+    model: &ModelId,
+    anomaly_type: &AnomalyType,
+    metric: &str,
+) -> String {
+    let digest =
+        Sha256::digest(format!("{}\n{}\n{}\n{}", service, model, anomaly_type, metric).as_bytes());
+    hex::encode(&digest[..8])
+}
+
+impl Serialize for AnomalyEvent {
+    fn serialize<S: serde::Serializer>(
+        &self,
+        serializer: S,
+    ) -> std::result::Result<S::Ok, S::Error> {
+        AnomalyEvent::serialize(self, serializer)
+    }
+}
+
+impl<'de> Deserialize<'de> for AnomalyEvent {
+    fn deserialize<D: serde::Deserializer<'de>>(
+        deserializer: D,
+    ) -> std::result::Result<Self, D::Error> {
+        // Anomalies stored or federated before signatures existed carry none
+        let mut anomaly = AnomalyEvent::deserialize(deserializer)?;
+        if anomaly.signature_hash.is_empty() {
+            anomaly.refresh_signature_hash();
+        }
+        Ok(anomaly)
+    }
 }
 
 impl AnomalyEvent {
@@ -268,6 +667,7 @@ impl AnomalyEvent {
         details: AnomalyDetails,
         context: AnomalyContext,
     ) -> Self {
+        let signature_hash = signature_hash(&service_name, &model, &anomaly_type, &details.metric);
         Self {
             alert_id: Uuid::new_v4(),
             timestamp: Utc::now(),
@@ -275,6 +675,9 @@ impl AnomalyEvent {
             anomaly_type,
             service_name,
             model,
+            signature_hash,
+            environment: None,
+            correlation_id: None,
             detection_method,
             confidence,
             details,
@@ -283,9 +686,55 @@ impl AnomalyEvent {
             remediation: Vec::new(),
             related_alerts: Vec::new(),
             runbook_url: None,
+            trace_links: BTreeMap::new(),
+            labels: Labels::new(),
+            state: AnomalyState::Open,
+            state_changed_at: None,
+            truncation: None,
+            annotations: Vec::new(),
         }
     }
 
+    /// Recompute `signature_hash` after changing the service, model,
+    /// anomaly type or metric
+    pub fn refresh_signature_hash(&mut self) {
+        self.signature_hash = signature_hash(
+            &self.service_name,
+            &self.model,
+            &self.anomaly_type,
+            &self.details.metric,
+        );
+    }
+
+    /// Move to a new lifecycle state
+    ///
+    /// Fails with a validation error if the transition is not allowed.
+    pub fn transition(
+        &mut self,
+        to: AnomalyState,
+        actor: Option<String>,
+        reason: Option<String>,
+    ) -> crate::Result<AnomalyStateChange> {
+        let from = self.state;
+        self.state = from.transition(to)?;
+        let at = Utc::now();
+        self.state_changed_at = Some(at);
+        Ok(AnomalyStateChange {
+            alert_id: self.alert_id,
+            from,
+            to,
+            at,
+            actor,
+            reason,
+        })
+    }
+
+    /// Apply a recorded state change
+    pub fn apply_state_change(&mut self, change: &AnomalyStateChange) {
+        self.state = change.to;
+        self.state_changed_at = Some(change.at);
+    }
+
     /// Set root cause
     pub fn with_root_cause(mut self, root_cause: impl Into<String>) -> Self {
         self.root_cause = Some(root_cause.into());
@@ -309,6 +758,92 @@ impl AnomalyEvent {
         self.runbook_url = Some(url.into());
         self
     }
+
+    /// Set the runbook URL from the first matching runbook mapping
+    ///
+    /// Mappings for the anomaly's service take precedence over mappings for
+    /// any service. A runbook URL already set by a detector is kept.
+    pub fn apply_runbook(&mut self, runbooks: &[crate::config::RunbookConfig]) {
+        if self.runbook_url.is_some() {
+            return;
+        }
+        let anomaly_type = self.anomaly_type.to_string();
+        let matching = runbooks.iter().filter(|r| r.anomaly_type == anomaly_type);
+        let runbook = matching
+            .clone()
+            .find(|r| r.service.as_deref() == Some(self.service_name.as_str()))
+            .or_else(|| matching.clone().find(|r| r.service.is_none()));
+
+        if let Some(runbook) = runbook {
+            self.runbook_url = Some(
+                runbook
+                    .url
+                    .replace("{service}", self.service_name.as_str())
+                    .replace("{model}", self.model.as_str()),
+            );
+        }
+    }
+
+    /// Render trace deep links from URL templates
+    ///
+    /// Templates may reference `{trace_id}`, `{service}` and `{model}`.
+    /// Nothing is added when the anomaly has no trace ID.
+    pub fn attach_trace_links(&mut self, templates: &[crate::config::TraceLinkConfig]) {
+        let Some(trace_id) = &self.context.trace_id else {
+            return;
+        };
+        for template in templates {
+            let url = template
+                .url_template
+                .replace("{trace_id}", trace_id)
+                .replace("{service}", self.service_name.as_str())
+                .replace("{model}", self.model.as_str());
+            self.trace_links.insert(template.name.clone(), url);
+        }
+    }
+}
+
+impl AnomalyDetails {
+    /// Details for an anomaly spanning several metrics
+    ///
+    /// The observation with the largest contribution (then deviation)
+    /// becomes the primary metric; `threshold` is the anomaly score
+    /// threshold of the detector. Returns `None` without observations.
+    pub fn from_observations(observations: Vec<MetricObservation>, threshold: f64) -> Option<Self> {
+        let primary = observations
+            .iter()
+            .max_by(|a, b| {
+                let (a, b) = (a.weight(), b.weight());
+                a.0.total_cmp(&b.0).then(a.1.total_cmp(&b.1))
+            })?
+            .clone();
+
+        Some(Self {
+            metric: primary.metric,
+            value: primary.value,
+            baseline: primary.baseline,
+            threshold,
+            deviation_sigma: primary.deviation_sigma,
+            additional: HashMap::new(),
+            observations,
+        })
+    }
+
+    /// All contributing metrics; single-metric details yield one observation
+    pub fn metric_observations(&self) -> Vec<MetricObservation> {
+        if !self.observations.is_empty() {
+            return self.observations.clone();
+        }
+
+        vec![MetricObservation {
+            metric: self.metric.clone(),
+            value: self.value,
+            baseline: self.baseline,
+            threshold: Some(self.threshold),
+            deviation_sigma: self.deviation_sigma,
+            contribution: None,
+        }]
+    }
 }
 
 impl AlertEvent {
@@ -319,7 +854,7 @@ impl AlertEvent {
             anomaly.anomaly_type, anomaly.service_name, anomaly.model
         );
 
-        let description = format!(
+        let mut description = format!(
             "Detected {} anomaly using {} method. Confidence: {:.2}%. Metric: {} = {:.2} (baseline: {:.2}, threshold: {:.2})",
             anomaly.anomaly_type,
             anomaly.detection_method,
@@ -329,6 +864,18 @@ impl AlertEvent {
             anomaly.details.baseline,
             anomaly.details.threshold
         );
+        if anomaly.details.observations.len() > 1 {
+            description.push_str("\nContributing metrics:");
+            for observation in &anomaly.details.observations {
+                description.push_str(&format!(
+                    "\n- {} = {:.2} (baseline: {:.2})",
+                    observation.metric, observation.value, observation.baseline
+                ));
+            }
+        }
+        for (name, url) in &anomaly.trace_links {
+            description.push_str(&format!("\nTrace ({}): {}", name, url));
+        }
 
         let tags = vec![
             format!("severity:{}", anomaly.severity),
@@ -346,6 +893,7 @@ impl AlertEvent {
             description,
             service_name: anomaly.service_name.clone(),
             model: anomaly.model.clone(),
+            signature_hash: anomaly.signature_hash.clone(),
             tags,
             anomaly,
         }
@@ -391,6 +939,41 @@ mod tests {
         assert!(event.validate().is_ok());
     }
 
+    #[test]
+    fn test_telemetry_event_clones_share_bodies() {
+        let event = create_test_telemetry_event();
+        let mut clone = event.clone();
+        assert!(Arc::ptr_eq(&event.prompt, &clone.prompt));
+
+        clone.prompt_mut().text = "Changed".to_string();
+        assert!(!Arc::ptr_eq(&event.prompt, &clone.prompt));
+        assert_ne!(event.prompt.text, clone.prompt.text);
+        assert!(Arc::ptr_eq(&event.response, &clone.response));
+    }
+
+    #[test]
+    fn test_latency_shares() {
+        let mut event = create_test_telemetry_event();
+        assert!(event.latency_shares().is_empty());
+
+        event.latency_ms = 200.0;
+        event
+            .latency_breakdown
+            .insert("retrieval".to_string(), 50.0);
+        event
+            .latency_breakdown
+            .insert("provider".to_string(), 100.0);
+        let shares = event.latency_shares();
+        assert_eq!(shares["retrieval"], 0.25);
+        assert_eq!(shares["provider"], 0.5);
+
+        // Overlapping components exceed the request latency
+        event.latency_breakdown.insert("tool".to_string(), 250.0);
+        let shares = event.latency_shares();
+        assert_eq!(shares["tool"], 0.625);
+        assert!(shares.values().sum::<f64>() <= 1.0);
+    }
+
     #[test]
     fn test_anomaly_event_creation() {
         let anomaly = AnomalyEvent::new(
@@ -407,6 +990,7 @@ mod tests {
                 threshold: 500.0,
                 deviation_sigma: Some(5.2),
                 additional: HashMap::new(),
+                observations: Vec::new(),
             },
             AnomalyContext {
                 trace_id: None,
@@ -414,6 +998,7 @@ mod tests {
                 region: Some("us-east-1".to_string()),
                 time_window: "last_5_minutes".to_string(),
                 sample_count: 1000,
+                window: None,
                 additional: HashMap::new(),
             },
         )
@@ -428,6 +1013,60 @@ mod tests {
         assert!(anomaly.runbook_url.is_some());
     }
 
+    #[test]
+    fn test_anomaly_state_transition() {
+        let mut anomaly = AnomalyEvent::new(
+            Severity::High,
+            AnomalyType::LatencySpike,
+            ServiceId::new("test-service"),
+            ModelId::new("gpt-4"),
+            DetectionMethod::ZScore,
+            0.95,
+            AnomalyDetails {
+                metric: "latency_ms".to_string(),
+                value: 5000.0,
+                baseline: 150.0,
+                threshold: 500.0,
+                deviation_sigma: None,
+                additional: HashMap::new(),
+                observations: Vec::new(),
+            },
+            AnomalyContext {
+                trace_id: None,
+                user_id: None,
+                region: None,
+                time_window: "last_5_minutes".to_string(),
+                sample_count: 1000,
+                window: None,
+                additional: HashMap::new(),
+            },
+        );
+        assert_eq!(anomaly.state, AnomalyState::Open);
+        assert!(anomaly.state_changed_at.is_none());
+
+        let change = anomaly
+            .transition(AnomalyState::Acknowledged, Some("alice".to_string()), None)
+            .unwrap();
+        assert_eq!(change.alert_id, anomaly.alert_id);
+        assert_eq!(change.from, AnomalyState::Open);
+        assert_eq!(change.to, AnomalyState::Acknowledged);
+        assert_eq!(anomaly.state, AnomalyState::Acknowledged);
+        assert_eq!(anomaly.state_changed_at, Some(change.at));
+
+        // Invalid transitions leave the anomaly unchanged
+        anomaly
+            .transition(AnomalyState::Expired, None, None)
+            .unwrap();
+        assert!(anomaly.transition(AnomalyState::Open, None, None).is_err());
+        assert_eq!(anomaly.state, AnomalyState::Expired);
+
+        // Anomalies stored before lifecycle states existed are open
+        let mut json = serde_json::to_value(&anomaly).unwrap();
+        json.as_object_mut().unwrap().remove("state");
+        let anomaly: AnomalyEvent = serde_json::from_value(json).unwrap();
+        assert_eq!(anomaly.state, AnomalyState::Open);
+    }
+
     #[test]
     fn test_alert_event_from_anomaly() {
         let anomaly = AnomalyEvent::new(
@@ -444,6 +1083,7 @@ mod tests {
                 threshold: 500.0,
                 deviation_sigma: Some(8.5),
                 additional: HashMap::new(),
+                observations: Vec::new(),
             },
             AnomalyContext {
                 trace_id: None,
@@ -451,6 +1091,7 @@ mod tests {
                 region: Some("us-east-1".to_string()),
                 time_window: "last_5_minutes".to_string(),
                 sample_count: 1000,
+                window: None,
                 additional: HashMap::new(),
             },
         );
@@ -461,6 +1102,265 @@ mod tests {
         assert!(alert.title.contains("latency_spike"));
         assert!(alert.description.contains("98.00%"));
         assert_eq!(alert.tags.len(), 5);
+        assert_eq!(alert.signature_hash, anomaly.signature_hash);
+    }
+
+    #[test]
+    fn test_signature_hash() {
+        let service = ServiceId::new("checkout");
+        let model = ModelId::new("gpt-4");
+        let hash = signature_hash(&service, &model, &AnomalyType::LatencySpike, "latency_ms");
+        assert_eq!(hash.len(), 16);
+        assert_eq!(
+            hash,
+            signature_hash(&service, &model, &AnomalyType::LatencySpike, "latency_ms")
+        );
+        assert_ne!(
+            hash,
+            signature_hash(&service, &model, &AnomalyType::LatencySpike, "total_tokens")
+        );
+        assert_ne!(
+            hash,
+            signature_hash(&service, &model, &AnomalyType::CostAnomaly, "latency_ms")
+        );
+
+        let mut anomaly = AnomalyEvent::new(
+            Severity::High,
+            AnomalyType::LatencySpike,
+            service.clone(),
+            model.clone(),
+            DetectionMethod::ZScore,
+            0.9,
+            AnomalyDetails {
+                metric: "latency_ms".to_string(),
+                value: 900.0,
+                baseline: 150.0,
+                threshold: 500.0,
+                deviation_sigma: None,
+                additional: HashMap::new(),
+                observations: Vec::new(),
+            },
+            AnomalyContext {
+                trace_id: None,
+                user_id: None,
+                region: None,
+                time_window: "last_5_minutes".to_string(),
+                sample_count: 100,
+                window: None,
+                additional: HashMap::new(),
+            },
+        );
+        assert_eq!(anomaly.signature_hash, hash);
+
+        anomaly.details.metric = "total_tokens".to_string();
+        anomaly.refresh_signature_hash();
+        assert_ne!(anomaly.signature_hash, hash);
+
+        // Anomalies serialized without a signature get one on read
+        let mut json = serde_json::to_value(&anomaly).unwrap();
+        json.as_object_mut().unwrap().remove("signature_hash");
+        let decoded: AnomalyEvent = serde_json::from_value(json).unwrap();
+        assert_eq!(decoded.signature_hash, anomaly.signature_hash);
+    }
+
+    #[test]
+    fn test_apply_runbook() {
+        let runbooks = vec![
+            crate::config::RunbookConfig {
+                anomaly_type: "latency_spike".to_string(),
+                service: None,
+                url: "https://wiki.local/runbooks/latency?model={model}".to_string(),
+            },
+            crate::config::RunbookConfig {
+                anomaly_type: "latency_spike".to_string(),
+                service: Some("checkout".to_string()),
+                url: "https://wiki.local/runbooks/checkout-latency".to_string(),
+            },
+        ];
+        let anomaly = |service: &str, anomaly_type: AnomalyType| {
+            AnomalyEvent::new(
+                Severity::High,
+                anomaly_type,
+                ServiceId::new(service),
+                ModelId::new("gpt-4"),
+                DetectionMethod::ZScore,
+                0.9,
+                AnomalyDetails {
+                    metric: "latency_ms".to_string(),
+                    value: 5000.0,
+                    baseline: 150.0,
+                    threshold: 500.0,
+                    deviation_sigma: None,
+                    additional: HashMap::new(),
+                    observations: Vec::new(),
+                },
+                AnomalyContext {
+                    trace_id: None,
+                    user_id: None,
+                    region: None,
+                    time_window: "last_5_minutes".to_string(),
+                    sample_count: 1000,
+                    window: None,
+                    additional: HashMap::new(),
+                },
+            )
+        };
+
+        // Service-specific mapping wins regardless of order
+        let mut checkout = anomaly("checkout", AnomalyType::LatencySpike);
+        checkout.apply_runbook(&runbooks);
+        assert_eq!(
+            checkout.runbook_url.as_deref(),
+            Some("https://wiki.local/runbooks/checkout-latency")
+        );
+
+        let mut search = anomaly("search", AnomalyType::LatencySpike);
+        search.apply_runbook(&runbooks);
+        assert_eq!(
+            search.runbook_url.as_deref(),
+            Some("https://wiki.local/runbooks/latency?model=gpt-4")
+        );
+
+        let mut cost = anomaly("search", AnomalyType::CostAnomaly);
+        cost.apply_runbook(&runbooks);
+        assert!(cost.runbook_url.is_none());
+
+        // Detector-provided runbooks are kept
+        let mut preset = anomaly("checkout", AnomalyType::LatencySpike)
+            .with_runbook("https://wiki.local/custom");
+        preset.apply_runbook(&runbooks);
+        assert_eq!(
+            preset.runbook_url.as_deref(),
+            Some("https://wiki.local/custom")
+        );
+    }
+
+    #[test]
+    fn test_attach_trace_links() {
+        let templates = vec![
+            crate::config::TraceLinkConfig {
+                name: "jaeger".to_string(),
+                url_template: "https://jaeger.local/trace/{trace_id}".to_string(),
+            },
+            crate::config::TraceLinkConfig {
+                name: "tempo".to_string(),
+                url_template: "https://grafana.local/explore?traceId={trace_id}&service={service}"
+                    .to_string(),
+            },
+        ];
+        let mut anomaly = AnomalyEvent::new(
+            Severity::High,
+            AnomalyType::LatencySpike,
+            ServiceId::new("checkout"),
+            ModelId::new("gpt-4"),
+            DetectionMethod::ZScore,
+            0.9,
+            AnomalyDetails {
+                metric: "latency_ms".to_string(),
+                value: 5000.0,
+                baseline: 150.0,
+                threshold: 500.0,
+                deviation_sigma: None,
+                additional: HashMap::new(),
+                observations: Vec::new(),
+            },
+            AnomalyContext {
+                trace_id: None,
+                user_id: None,
+                region: None,
+                time_window: "last_5_minutes".to_string(),
+                sample_count: 1000,
+                window: None,
+                additional: HashMap::new(),
+            },
+        );
+
+        anomaly.attach_trace_links(&templates);
+        assert!(anomaly.trace_links.is_empty());
+
+        anomaly.context.trace_id = Some("4bf92f3577b34da6".to_string());
+        anomaly.attach_trace_links(&templates);
+        assert_eq!(
+            anomaly.trace_links["jaeger"],
+            "https://jaeger.local/trace/4bf92f3577b34da6"
+        );
+        assert_eq!(
+            anomaly.trace_links["tempo"],
+            "https://grafana.local/explore?traceId=4bf92f3577b34da6&service=checkout"
+        );
+
+        let alert = AlertEvent::from_anomaly(anomaly);
+        assert!(alert
+            .description
+            .contains("Trace (jaeger): https://jaeger.local/trace/4bf92f3577b34da6"));
+    }
+
+    #[test]
+    fn test_multi_metric_details() {
+        let details = AnomalyDetails::from_observations(
+            vec![
+                MetricObservation::new("latency_ms", 900.0, 200.0)
+                    .with_deviation_sigma(4.0)
+                    .with_contribution(0.3),
+                MetricObservation::new("total_tokens", 9000.0, 800.0)
+                    .with_deviation_sigma(6.0)
+                    .with_contribution(0.6),
+                MetricObservation::new("cost_usd", 0.2, 0.1).with_contribution(0.1),
+            ],
+            0.7,
+        )
+        .unwrap();
+        assert_eq!(details.metric, "total_tokens");
+        assert_eq!(details.value, 9000.0);
+        assert_eq!(details.deviation_sigma, Some(6.0));
+        assert_eq!(details.threshold, 0.7);
+        assert_eq!(details.metric_observations().len(), 3);
+        assert!(AnomalyDetails::from_observations(Vec::new(), 0.7).is_none());
+
+        // Observations round-trip and are omitted for single-metric details
+        let json = serde_json::to_value(&details).unwrap();
+        assert_eq!(json["observations"].as_array().unwrap().len(), 3);
+        let parsed: AnomalyDetails = serde_json::from_value(json).unwrap();
+        assert_eq!(parsed.observations, details.observations);
+
+        let single = AnomalyDetails {
+            metric: "latency_ms".to_string(),
+            value: 500.0,
+            baseline: 100.0,
+            threshold: 3.0,
+            deviation_sigma: None,
+            additional: HashMap::new(),
+            observations: Vec::new(),
+        };
+        let json = serde_json::to_value(&single).unwrap();
+        assert!(json.get("observations").is_none());
+        let observations = single.metric_observations();
+        assert_eq!(observations.len(), 1);
+        assert_eq!(observations[0].threshold, Some(3.0));
+
+        // Alerts list every contributing metric
+        let anomaly = AnomalyEvent::new(
+            Severity::High,
+            AnomalyType::Custom("multivariate".to_string()),
+            ServiceId::new("checkout"),
+            ModelId::new("gpt-4"),
+            DetectionMethod::IsolationForest,
+            0.9,
+            details,
+            AnomalyContext {
+                trace_id: None,
+                user_id: None,
+                region: None,
+                time_window: "last_100_samples".to_string(),
+                sample_count: 100,
+                window: None,
+                additional: HashMap::new(),
+            },
+        );
+        let alert = AlertEvent::from_anomaly(anomaly);
+        assert!(alert
+            .description
+            .contains("- cost_usd = 0.20 (baseline: 0.10)"));
     }
 
     #[test]
@@ -471,4 +1371,17 @@ mod tests {
         assert_eq!(event.event_id, deserialized.event_id);
         assert_eq!(event.service_name, deserialized.service_name);
     }
+
+    #[test]
+    fn test_labels_serialization() {
+        let event = create_test_telemetry_event();
+        let json = serde_json::to_value(&event).unwrap();
+        assert!(json.get("labels").is_none());
+
+        let event = event.with_label("team", "search");
+        let json = serde_json::to_value(&event).unwrap();
+        assert_eq!(json["labels"]["team"], "search");
+        let deserialized: TelemetryEvent = serde_json::from_value(json).unwrap();
+        assert_eq!(deserialized.labels, event.labels);
+    }
 }
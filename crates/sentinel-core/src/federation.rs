@@ -0,0 +1,45 @@
+//! Anomaly federation between sentinel instances.
+//!
+//! Edge instances post batches of the anomalies they detected, never their
+//! raw telemetry, to [`FEDERATION_PATH`] on a central instance,
+//! authenticated with a per-region bearer token. Federated anomalies carry
+//! their region as the [`REGION_LABEL`] label, so the central API can slice
+//! the global view by region with the usual label filters.
+
+use crate::events::AnomalyEvent;
+use serde::{Deserialize, Serialize};
+
+/// Path of the central instance's federation endpoint
+pub const FEDERATION_PATH: &str = "/api/v1/federation/anomalies";
+
+/// Label holding the region an anomaly was detected in
+pub const REGION_LABEL: &str = "region";
+
+/// Anomalies forwarded by one edge instance
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct FederationBatch {
+    /// Region of the forwarding instance
+    pub region: String,
+    /// Anomalies detected there
+    pub anomalies: Vec<AnomalyEvent>,
+}
+
+/// Central instance's answer to a forwarded batch
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct FederationReceipt {
+    /// Anomalies accepted
+    pub accepted: usize,
+}
+
+/// Record the region an anomaly was detected in
+///
+/// The region label is always set; `context.region` is only filled in when
+/// the telemetry did not carry one.
+pub fn stamp_region(anomaly: &mut AnomalyEvent, region: &str) {
+    anomaly
+        .labels
+        .insert(REGION_LABEL.to_string(), region.to_string());
+    if anomaly.context.region.is_none() {
+        anomaly.context.region = Some(region.to_string());
+    }
+}
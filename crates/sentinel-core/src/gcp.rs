@@ -0,0 +1,124 @@
+//! Google Cloud authentication shared by the Pub/Sub and BigQuery clients.
+//!
+//! Requests to Google APIs authenticate with a configured access token, a
+//! token from the GCE/GKE metadata server, or not at all when talking to an
+//! emulator or a private endpoint. [`endpoint_auth`] picks the endpoint and
+//! [`TokenSource`] for a client's configuration.
+
+use crate::{Error, Result};
+use reqwest::Client;
+use serde::Deserialize;
+use std::time::{Duration, Instant};
+use tokio::sync::Mutex;
+
+/// Metadata server URL returning the default service account's token
+pub const METADATA_TOKEN_URL: &str =
+    "http://metadata.google.internal/computeMetadata/v1/instance/service-accounts/default/token";
+
+/// Source of OAuth2 access tokens for Google API requests
+#[derive(Debug)]
+pub enum TokenSource {
+    /// No authentication (emulator or private endpoint)
+    None,
+    /// Static token from configuration
+    Static(String),
+    /// Token fetched from the metadata server and cached until expiry
+    Metadata(Mutex<Option<(String, Instant)>>),
+}
+
+impl TokenSource {
+    /// Tokens from the metadata server, fetched on first use
+    pub fn metadata() -> Self {
+        TokenSource::Metadata(Mutex::new(None))
+    }
+
+    /// Get a bearer token, refreshing from the metadata server if needed
+    pub async fn token(&self, client: &Client) -> Result<Option<String>> {
+        match self {
+            TokenSource::None => Ok(None),
+            TokenSource::Static(token) => Ok(Some(token.clone())),
+            TokenSource::Metadata(cache) => {
+                let mut cache = cache.lock().await;
+                if let Some((token, expires_at)) = cache.as_ref() {
+                    if Instant::now() < *expires_at {
+                        return Ok(Some(token.clone()));
+                    }
+                }
+
+                #[derive(Deserialize)]
+                struct TokenResponse {
+                    access_token: String,
+                    expires_in: u64,
+                }
+
+                let response: TokenResponse = client
+                    .get(METADATA_TOKEN_URL)
+                    .header("Metadata-Flavor", "Google")
+                    .send()
+                    .await
+                    .and_then(|r| r.error_for_status())
+                    .map_err(|e| {
+                        Error::connection(format!("Failed to fetch GCP access token: {}", e))
+                    })?
+                    .json()
+                    .await
+                    .map_err(|e| Error::connection(format!("Invalid GCP token response: {}", e)))?;
+
+                // Refresh a minute early to avoid using a token right at expiry
+                let ttl = Duration::from_secs(response.expires_in.saturating_sub(60));
+                *cache = Some((response.access_token.clone(), Instant::now() + ttl));
+                Ok(Some(response.access_token))
+            }
+        }
+    }
+}
+
+/// Endpoint and token source for a Google API client
+///
+/// A configured access token is sent to the configured endpoint, or to
+/// `default_endpoint`. Without one, a configured endpoint or the emulator
+/// host is called unauthenticated, and `default_endpoint` with tokens from
+/// the metadata server.
+pub fn endpoint_auth(
+    default_endpoint: &str,
+    endpoint: Option<&str>,
+    access_token: Option<&str>,
+    emulator_host: Option<&str>,
+) -> (String, TokenSource) {
+    match (endpoint, emulator_host, access_token) {
+        (_, _, Some(token)) => (
+            endpoint.unwrap_or(default_endpoint).to_string(),
+            TokenSource::Static(token.to_string()),
+        ),
+        (Some(endpoint), _, None) => (endpoint.to_string(), TokenSource::None),
+        (None, Some(host), None) => (format!("http://{}", host), TokenSource::None),
+        (None, None, None) => (default_endpoint.to_string(), TokenSource::metadata()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const DEFAULT: &str = "https://pubsub.googleapis.com";
+
+    #[test]
+    fn test_endpoint_auth() {
+        let (endpoint, token) =
+            endpoint_auth(DEFAULT, None, Some("secret"), Some("localhost:8085"));
+        assert_eq!(endpoint, DEFAULT);
+        assert!(matches!(token, TokenSource::Static(t) if t == "secret"));
+
+        let (endpoint, token) = endpoint_auth(DEFAULT, None, None, Some("localhost:8085"));
+        assert_eq!(endpoint, "http://localhost:8085");
+        assert!(matches!(token, TokenSource::None));
+
+        let (endpoint, token) = endpoint_auth(DEFAULT, Some("http://private"), None, None);
+        assert_eq!(endpoint, "http://private");
+        assert!(matches!(token, TokenSource::None));
+
+        let (endpoint, token) = endpoint_auth(DEFAULT, None, None, None);
+        assert_eq!(endpoint, DEFAULT);
+        assert!(matches!(token, TokenSource::Metadata(_)));
+    }
+}
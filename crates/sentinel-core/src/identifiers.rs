@@ -0,0 +1,124 @@
+//! Service and model identifier normalization.
+//!
+//! Detection keys baselines by service and model, so `Checkout`,
+//! `checkout ` and `checkout` would otherwise learn three separate
+//! baselines. An [`IdentifierNormalizer`] normalizes IDs with
+//! [`ServiceId::try_new`] / [`ModelId::try_new`] and then maps aliases such
+//! as `gpt-4-turbo-preview` to a canonical name like `gpt-4-turbo`.
+
+use crate::{
+    config::IdentifierConfig,
+    error::{Error, Result},
+    events::TelemetryEvent,
+    types::{ModelId, ServiceId},
+};
+use std::collections::HashMap;
+
+/// Normalizes service and model IDs and resolves aliases
+#[derive(Debug, Clone, Default)]
+pub struct IdentifierNormalizer {
+    service_aliases: HashMap<ServiceId, ServiceId>,
+    model_aliases: HashMap<ModelId, ModelId>,
+}
+
+impl IdentifierNormalizer {
+    /// Create a normalizer without aliases
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Create a normalizer from configuration
+    ///
+    /// Alias names and targets are normalized too, so configuration may use
+    /// any casing.
+    pub fn from_config(config: &IdentifierConfig) -> Result<Self> {
+        let mut normalizer = Self::new();
+        for (alias, canonical) in &config.service_aliases {
+            normalizer = normalizer.with_service_alias(alias, canonical)?;
+        }
+        for (alias, canonical) in &config.model_aliases {
+            normalizer = normalizer.with_model_alias(alias, canonical)?;
+        }
+        Ok(normalizer)
+    }
+
+    /// Map a service alias to its canonical ID
+    pub fn with_service_alias(mut self, alias: &str, canonical: &str) -> Result<Self> {
+        let alias = ServiceId::try_new(alias)
+            .map_err(|e| Error::config(format!("Invalid service alias: {}", e)))?;
+        let canonical = ServiceId::try_new(canonical)
+            .map_err(|e| Error::config(format!("Invalid service alias target: {}", e)))?;
+        self.service_aliases.insert(alias, canonical);
+        Ok(self)
+    }
+
+    /// Map a model alias to its canonical ID
+    pub fn with_model_alias(mut self, alias: &str, canonical: &str) -> Result<Self> {
+        let alias = ModelId::try_new(alias)
+            .map_err(|e| Error::config(format!("Invalid model alias: {}", e)))?;
+        let canonical = ModelId::try_new(canonical)
+            .map_err(|e| Error::config(format!("Invalid model alias target: {}", e)))?;
+        self.model_aliases.insert(alias, canonical);
+        Ok(self)
+    }
+
+    /// Normalize a service ID and resolve its alias
+    pub fn service(&self, id: &ServiceId) -> Result<ServiceId> {
+        let id = ServiceId::try_new(id.as_str())?;
+        Ok(self.service_aliases.get(&id).cloned().unwrap_or(id))
+    }
+
+    /// Normalize a model ID and resolve its alias
+    pub fn model(&self, id: &ModelId) -> Result<ModelId> {
+        let id = ModelId::try_new(id.as_str())?;
+        Ok(self.model_aliases.get(&id).cloned().unwrap_or(id))
+    }
+
+    /// Normalize the event's service and model IDs in place
+    ///
+    /// The event is left unchanged when either ID is invalid.
+    pub fn apply(&self, event: &mut TelemetryEvent) -> Result<()> {
+        let service = self.service(&event.service_name)?;
+        let model = self.model(&event.model)?;
+        event.service_name = service;
+        event.model = model;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_aliases() {
+        let config = IdentifierConfig {
+            service_aliases: HashMap::from([("Checkout-V2".to_string(), "checkout".to_string())]),
+            model_aliases: HashMap::from([(
+                "gpt-4-turbo-preview".to_string(),
+                "GPT-4-Turbo".to_string(),
+            )]),
+        };
+        let normalizer = IdentifierNormalizer::from_config(&config).unwrap();
+
+        let service = normalizer.service(&ServiceId::new(" checkout-v2")).unwrap();
+        assert_eq!(service.as_str(), "checkout");
+        let model = normalizer
+            .model(&ModelId::new("GPT-4-Turbo-Preview"))
+            .unwrap();
+        assert_eq!(model.as_str(), "gpt-4-turbo");
+        let model = normalizer.model(&ModelId::new("Claude-3-Opus")).unwrap();
+        assert_eq!(model.as_str(), "claude-3-opus");
+
+        assert!(normalizer.service(&ServiceId::new("bad service")).is_err());
+    }
+
+    #[test]
+    fn test_invalid_alias_config() {
+        let config = IdentifierConfig {
+            service_aliases: HashMap::from([("ok".to_string(), "not ok".to_string())]),
+            model_aliases: HashMap::new(),
+        };
+        assert!(IdentifierNormalizer::from_config(&config).is_err());
+    }
+}
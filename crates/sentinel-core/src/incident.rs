@@ -0,0 +1,533 @@
+//! Incidents grouping related anomalies.
+//!
+//! An incident collects the anomalies of one group (by default a service in
+//! an environment, see [`IncidentConfig`](crate::config::IncidentConfig))
+//! while the group keeps producing them. It carries its own state, a
+//! timeline of what happened to it and links responders attach, so a
+//! responder works one incident rather than every anomaly in it.
+//!
+//! | From         | To                     |
+//! |--------------|------------------------|
+//! | open         | acknowledged, resolved |
+//! | acknowledged | open, resolved         |
+//! | resolved     | (none)                 |
+//!
+//! Resolved incidents are closed: later anomalies of the group open a new
+//! incident.
+
+use crate::{
+    config::IncidentGroupField,
+    events::AnomalyEvent,
+    lifecycle::AnomalyState,
+    types::{Environment, ServiceId, Severity},
+    Error, Result,
+};
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+/// Anomalies listed on an incident; later ones are counted only
+pub const MAX_LISTED_ANOMALIES: usize = 1000;
+
+/// Lifecycle state of an incident
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum IncidentState {
+    /// Receiving anomalies and awaiting a response
+    #[default]
+    Open,
+    /// A responder is working on it
+    Acknowledged,
+    /// Closed; the group's next anomaly opens a new incident
+    Resolved,
+}
+
+impl IncidentState {
+    /// All states
+    pub const ALL: [IncidentState; 3] = [Self::Open, Self::Acknowledged, Self::Resolved];
+
+    /// Whether an incident in this state may move to `to`
+    pub fn can_transition_to(self, to: IncidentState) -> bool {
+        use IncidentState::*;
+        matches!(
+            (self, to),
+            (Open, Acknowledged | Resolved) | (Acknowledged, Open | Resolved)
+        )
+    }
+
+    /// Whether the incident still receives anomalies
+    pub fn is_active(self) -> bool {
+        !matches!(self, Self::Resolved)
+    }
+
+    /// Anomaly state matching this incident state, for notifications
+    pub fn anomaly_state(self) -> AnomalyState {
+        match self {
+            Self::Open => AnomalyState::Open,
+            Self::Acknowledged => AnomalyState::Acknowledged,
+            Self::Resolved => AnomalyState::Resolved,
+        }
+    }
+}
+
+impl std::fmt::Display for IncidentState {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Open => write!(f, "open"),
+            Self::Acknowledged => write!(f, "acknowledged"),
+            Self::Resolved => write!(f, "resolved"),
+        }
+    }
+}
+
+impl std::str::FromStr for IncidentState {
+    type Err = String;
+
+    fn from_str(s: &str) -> std::result::Result<Self, Self::Err> {
+        match s {
+            "open" => Ok(Self::Open),
+            "acknowledged" => Ok(Self::Acknowledged),
+            "resolved" => Ok(Self::Resolved),
+            other => Err(format!("unknown incident state: {}", other)),
+        }
+    }
+}
+
+/// What happened to an incident
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum IncidentUpdate {
+    /// Opened by its first anomaly
+    Opened,
+    /// An anomaly attached without raising the severity
+    AnomalyAttached,
+    /// An anomaly attached and raised the severity
+    Escalated,
+    /// A responder changed the state
+    StateChanged,
+    /// A responder attached a link
+    Linked,
+}
+
+impl IncidentUpdate {
+    /// Whether alerters configured for incidents are notified
+    ///
+    /// Anomalies attaching at the incident's severity are only recorded.
+    pub fn is_notified(self) -> bool {
+        matches!(self, Self::Opened | Self::Escalated | Self::StateChanged)
+    }
+}
+
+impl std::fmt::Display for IncidentUpdate {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Opened => write!(f, "opened"),
+            Self::AnomalyAttached => write!(f, "anomaly_attached"),
+            Self::Escalated => write!(f, "escalated"),
+            Self::StateChanged => write!(f, "state_changed"),
+            Self::Linked => write!(f, "linked"),
+        }
+    }
+}
+
+/// Entry of an incident's timeline
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct IncidentTimelineEntry {
+    /// When it happened
+    pub at: DateTime<Utc>,
+    /// What happened
+    pub update: IncidentUpdate,
+    /// Anomaly that attached
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub alert_id: Option<Uuid>,
+    /// Severity of the attached anomaly
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub severity: Option<Severity>,
+    /// State after a state change
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub state: Option<IncidentState>,
+    /// Who made the change
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub actor: Option<String>,
+    /// Reason for a state change or title of a link
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub text: Option<String>,
+}
+
+impl IncidentTimelineEntry {
+    fn new(at: DateTime<Utc>, update: IncidentUpdate) -> Self {
+        Self {
+            at,
+            update,
+            alert_id: None,
+            severity: None,
+            state: None,
+            actor: None,
+            text: None,
+        }
+    }
+
+    fn anomaly(at: DateTime<Utc>, update: IncidentUpdate, anomaly: &AnomalyEvent) -> Self {
+        Self {
+            alert_id: Some(anomaly.alert_id),
+            severity: Some(anomaly.severity),
+            ..Self::new(at, update)
+        }
+    }
+}
+
+/// Link attached to an incident, e.g. a ticket, dashboard or postmortem
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct IncidentLink {
+    /// Link title
+    pub title: String,
+    /// Linked URL
+    pub url: String,
+    /// Who attached the link
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub added_by: Option<String>,
+    /// When the link was attached
+    pub added_at: DateTime<Utc>,
+}
+
+/// Related anomalies handled as one
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Incident {
+    /// Incident identifier
+    pub incident_id: Uuid,
+    /// Group the incident collects anomalies of (see [`group_key`])
+    pub group_key: String,
+    /// Summary, from the anomaly that opened it
+    pub title: String,
+    /// Lifecycle state
+    pub state: IncidentState,
+    /// Highest severity of its anomalies
+    pub severity: Severity,
+    /// Services its anomalies came from
+    pub services: Vec<ServiceId>,
+    /// Environment of the anomaly that opened it
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub environment: Option<Environment>,
+    /// Attached anomalies, oldest first, up to [`MAX_LISTED_ANOMALIES`]
+    pub alert_ids: Vec<Uuid>,
+    /// Attached anomalies, listed or not
+    pub anomaly_count: u64,
+    /// When the incident opened
+    pub opened_at: DateTime<Utc>,
+    /// When the last anomaly attached
+    pub last_anomaly_at: DateTime<Utc>,
+    /// When anything last changed
+    pub updated_at: DateTime<Utc>,
+    /// When the incident was resolved
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub resolved_at: Option<DateTime<Utc>>,
+    /// What happened to the incident, oldest first
+    pub timeline: Vec<IncidentTimelineEntry>,
+    /// Links attached by responders
+    #[serde(default)]
+    pub links: Vec<IncidentLink>,
+}
+
+/// Group of an anomaly: the values of `fields`, in order
+pub fn group_key(anomaly: &AnomalyEvent, fields: &[IncidentGroupField]) -> String {
+    fields
+        .iter()
+        .map(|field| match field {
+            IncidentGroupField::Service => format!("service={}", anomaly.service_name),
+            IncidentGroupField::Model => format!("model={}", anomaly.model),
+            IncidentGroupField::Environment => format!(
+                "environment={}",
+                anomaly
+                    .environment
+                    .as_ref()
+                    .map_or_else(String::new, ToString::to_string)
+            ),
+            IncidentGroupField::AnomalyType => format!("anomaly_type={}", anomaly.anomaly_type),
+            IncidentGroupField::Signature => format!("signature={}", anomaly.signature_hash),
+        })
+        .collect::<Vec<_>>()
+        .join(",")
+}
+
+impl Incident {
+    /// Open an incident with its first anomaly
+    pub fn open(group_key: String, anomaly: &AnomalyEvent, at: DateTime<Utc>) -> Self {
+        Self {
+            incident_id: Uuid::new_v4(),
+            group_key,
+            title: format!("{} on {}", anomaly.anomaly_type, anomaly.service_name),
+            state: IncidentState::Open,
+            severity: anomaly.severity,
+            services: vec![anomaly.service_name.clone()],
+            environment: anomaly.environment.clone(),
+            alert_ids: vec![anomaly.alert_id],
+            anomaly_count: 1,
+            opened_at: at,
+            last_anomaly_at: at,
+            updated_at: at,
+            resolved_at: None,
+            timeline: vec![IncidentTimelineEntry::anomaly(
+                at,
+                IncidentUpdate::Opened,
+                anomaly,
+            )],
+            links: Vec::new(),
+        }
+    }
+
+    /// Whether an anomaly arriving at `at` attaches to this incident
+    pub fn accepts(&self, at: DateTime<Utc>, window: chrono::Duration) -> bool {
+        self.state.is_active() && at - self.last_anomaly_at <= window
+    }
+
+    /// Attach an anomaly, raising the severity to the anomaly's
+    ///
+    /// Only the first [`MAX_LISTED_ANOMALIES`] anomalies are listed and put
+    /// on the timeline; escalations always are.
+    pub fn attach(&mut self, anomaly: &AnomalyEvent, at: DateTime<Utc>) -> IncidentUpdate {
+        let update = if anomaly.severity > self.severity {
+            self.severity = anomaly.severity;
+            IncidentUpdate::Escalated
+        } else {
+            IncidentUpdate::AnomalyAttached
+        };
+        if !self.services.contains(&anomaly.service_name) {
+            self.services.push(anomaly.service_name.clone());
+        }
+
+        let listed = self.alert_ids.len() < MAX_LISTED_ANOMALIES;
+        if listed {
+            self.alert_ids.push(anomaly.alert_id);
+        }
+        if listed || update == IncidentUpdate::Escalated {
+            self.timeline
+                .push(IncidentTimelineEntry::anomaly(at, update, anomaly));
+        }
+        self.anomaly_count += 1;
+        self.last_anomaly_at = at;
+        self.updated_at = at;
+        update
+    }
+
+    /// Move the incident to a new state
+    ///
+    /// Fails with a validation error for transitions the current state does
+    /// not allow.
+    pub fn transition(
+        &mut self,
+        to: IncidentState,
+        actor: Option<String>,
+        reason: Option<String>,
+        at: DateTime<Utc>,
+    ) -> Result<()> {
+        if !self.state.can_transition_to(to) {
+            return Err(Error::validation(format!(
+                "cannot change incident state from {} to {}",
+                self.state, to
+            )));
+        }
+        self.state = to;
+        self.updated_at = at;
+        if to == IncidentState::Resolved {
+            self.resolved_at = Some(at);
+        }
+        self.timeline.push(IncidentTimelineEntry {
+            state: Some(to),
+            actor,
+            text: reason,
+            ..IncidentTimelineEntry::new(at, IncidentUpdate::StateChanged)
+        });
+        Ok(())
+    }
+
+    /// Attach a link
+    ///
+    /// Fails with a validation error if the title or URL is blank.
+    pub fn link(
+        &mut self,
+        title: String,
+        url: String,
+        added_by: Option<String>,
+        at: DateTime<Utc>,
+    ) -> Result<()> {
+        if title.trim().is_empty() || url.trim().is_empty() {
+            return Err(Error::validation("incident links need a title and a URL"));
+        }
+        self.timeline.push(IncidentTimelineEntry {
+            actor: added_by.clone(),
+            text: Some(title.clone()),
+            ..IncidentTimelineEntry::new(at, IncidentUpdate::Linked)
+        });
+        self.links.push(IncidentLink {
+            title,
+            url,
+            added_by,
+            added_at: at,
+        });
+        self.updated_at = at;
+        Ok(())
+    }
+
+    /// Alert notifying alerters of an update: a copy of `anomaly` (the one
+    /// that attached, or the latest) with a new ID, the incident's severity
+    /// and state, its anomalies as related alerts and the incident in
+    /// context
+    pub fn notification(&self, anomaly: &AnomalyEvent, update: IncidentUpdate) -> AnomalyEvent {
+        let mut alert = anomaly.clone();
+        alert.alert_id = Uuid::new_v4();
+        alert.timestamp = self.updated_at;
+        alert.severity = self.severity;
+        alert.state = self.state.anomaly_state();
+        alert.related_alerts = self.alert_ids.clone();
+        alert.root_cause = Some(format!(
+            "Incident {} ({}): {}; {} anomalies since {}",
+            self.title,
+            self.state,
+            update,
+            self.anomaly_count,
+            self.opened_at.to_rfc3339()
+        ));
+
+        let additional = &mut alert.context.additional;
+        additional.insert("incident_id".to_string(), self.incident_id.to_string());
+        additional.insert("incident_state".to_string(), self.state.to_string());
+        additional.insert("incident_update".to_string(), update.to_string());
+        additional.insert(
+            "incident_anomalies".to_string(),
+            self.anomaly_count.to_string(),
+        );
+        alert
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        events::{AnomalyContext, AnomalyDetails},
+        types::{AnomalyType, DetectionMethod, ModelId},
+    };
+    use std::collections::HashMap;
+
+    fn anomaly(service: &str, severity: Severity) -> AnomalyEvent {
+        AnomalyEvent::new(
+            severity,
+            AnomalyType::LatencySpike,
+            ServiceId::new(service),
+            ModelId::new("gpt-4"),
+            DetectionMethod::ZScore,
+            0.9,
+            AnomalyDetails {
+                metric: "latency_ms".to_string(),
+                value: 5000.0,
+                baseline: 100.0,
+                threshold: 3.0,
+                deviation_sigma: Some(10.0),
+                additional: HashMap::new(),
+                observations: Vec::new(),
+            },
+            AnomalyContext {
+                trace_id: None,
+                user_id: None,
+                region: None,
+                time_window: "last_100_samples".to_string(),
+                sample_count: 100,
+                window: None,
+                additional: HashMap::new(),
+            },
+        )
+    }
+
+    #[test]
+    fn test_incident_collects_anomalies() {
+        let first = anomaly("checkout", Severity::Medium);
+        let key = group_key(
+            &first,
+            &[IncidentGroupField::Service, IncidentGroupField::Environment],
+        );
+        assert_eq!(key, "service=checkout,environment=");
+
+        let opened_at = Utc::now();
+        let mut incident = Incident::open(key, &first, opened_at);
+        assert_eq!(incident.timeline[0].update, IncidentUpdate::Opened);
+
+        let window = chrono::Duration::minutes(30);
+        let at = opened_at + chrono::Duration::minutes(5);
+        assert!(incident.accepts(at, window));
+        assert!(!incident.accepts(at + window, window));
+
+        let second = anomaly("checkout", Severity::Medium);
+        assert_eq!(
+            incident.attach(&second, at),
+            IncidentUpdate::AnomalyAttached
+        );
+        let third = anomaly("checkout", Severity::Critical);
+        assert_eq!(incident.attach(&third, at), IncidentUpdate::Escalated);
+        assert_eq!(incident.severity, Severity::Critical);
+        assert_eq!(incident.anomaly_count, 3);
+        assert_eq!(incident.alert_ids[2], third.alert_id);
+
+        let notification = incident.notification(&third, IncidentUpdate::Escalated);
+        assert_ne!(notification.alert_id, third.alert_id);
+        assert_eq!(notification.related_alerts.len(), 3);
+        assert_eq!(
+            notification.context.additional["incident_id"],
+            incident.incident_id.to_string()
+        );
+        assert_eq!(
+            notification.context.additional["incident_update"],
+            "escalated"
+        );
+    }
+
+    #[test]
+    fn test_incident_transitions() {
+        let mut incident = Incident::open(
+            "service=checkout".to_string(),
+            &anomaly("checkout", Severity::High),
+            Utc::now(),
+        );
+        incident
+            .transition(
+                IncidentState::Acknowledged,
+                Some("alice".to_string()),
+                None,
+                Utc::now(),
+            )
+            .unwrap();
+        incident
+            .link(
+                "OPS-42".to_string(),
+                "https://tickets.example.com/OPS-42".to_string(),
+                Some("alice".to_string()),
+                Utc::now(),
+            )
+            .unwrap();
+        assert!(incident
+            .link("Blank".to_string(), " ".to_string(), None, Utc::now())
+            .is_err());
+        incident
+            .transition(IncidentState::Resolved, None, None, Utc::now())
+            .unwrap();
+
+        assert!(incident.resolved_at.is_some());
+        assert!(!incident.accepts(Utc::now(), chrono::Duration::minutes(30)));
+        assert!(matches!(
+            incident.transition(IncidentState::Open, None, None, Utc::now()),
+            Err(Error::Validation(_))
+        ));
+        let updates: Vec<_> = incident.timeline.iter().map(|e| e.update).collect();
+        assert_eq!(
+            updates,
+            vec![
+                IncidentUpdate::Opened,
+                IncidentUpdate::StateChanged,
+                IncidentUpdate::Linked,
+                IncidentUpdate::StateChanged,
+            ]
+        );
+        for state in IncidentState::ALL {
+            assert_eq!(state.to_string().parse::<IncidentState>().unwrap(), state);
+        }
+    }
+}
@@ -0,0 +1,130 @@
+//! Telemetry labels.
+//!
+//! Labels are validated key/value pairs such as `team=search` or
+//! `env=prod` attached to telemetry events and carried over to the anomalies
+//! detected from them. Unlike free-form metadata they are meant for slicing:
+//! queries filter on them and alert routes match on them, so keys and values
+//! are restricted to a small, index-friendly shape.
+
+use crate::error::{Error, Result};
+use std::collections::BTreeMap;
+
+/// Label set, ordered by key
+pub type Labels = BTreeMap<String, String>;
+
+/// Maximum number of labels on one event
+pub const MAX_LABELS: usize = 32;
+
+/// Maximum label key length
+pub const MAX_LABEL_KEY_LEN: usize = 63;
+
+/// Maximum label value length
+pub const MAX_LABEL_VALUE_LEN: usize = 255;
+
+/// Validate a label
+///
+/// Keys start with a lowercase letter followed by lowercase letters, digits,
+/// `_`, `-` or `.`. Values are non-empty and contain no control characters.
+pub fn validate_label(key: &str, value: &str) -> Result<()> {
+    let valid_key = key.len() <= MAX_LABEL_KEY_LEN
+        && key.starts_with(|c: char| c.is_ascii_lowercase())
+        && key
+            .chars()
+            .all(|c| c.is_ascii_lowercase() || c.is_ascii_digit() || matches!(c, '_' | '-' | '.'));
+    if !valid_key {
+        return Err(Error::validation(format!("Invalid label key '{}'", key)));
+    }
+
+    if value.is_empty() || value.len() > MAX_LABEL_VALUE_LEN {
+        return Err(Error::validation(format!(
+            "Label '{}' value must be 1-{} characters",
+            key, MAX_LABEL_VALUE_LEN
+        )));
+    }
+    if value.chars().any(char::is_control) {
+        return Err(Error::validation(format!(
+            "Label '{}' value contains control characters",
+            key
+        )));
+    }
+    Ok(())
+}
+
+/// Validate a label set
+pub fn validate_labels(labels: &Labels) -> Result<()> {
+    if labels.len() > MAX_LABELS {
+        return Err(Error::validation(format!(
+            "At most {} labels are allowed, got {}",
+            MAX_LABELS,
+            labels.len()
+        )));
+    }
+    labels
+        .iter()
+        .try_for_each(|(key, value)| validate_label(key, value))
+}
+
+/// Parse a label selector of the form `key=value,key=value`
+pub fn parse_selector(selector: &str) -> Result<Labels> {
+    let mut labels = Labels::new();
+    for pair in selector.split(',').map(str::trim).filter(|p| !p.is_empty()) {
+        let (key, value) = pair
+            .split_once('=')
+            .ok_or_else(|| Error::validation(format!("Expected key=value, got '{}'", pair)))?;
+        let (key, value) = (key.trim(), value.trim());
+        validate_label(key, value)?;
+        labels.insert(key.to_string(), value.to_string());
+    }
+    Ok(labels)
+}
+
+/// Whether `labels` contains every pair in `selector`
+///
+/// An empty selector matches everything.
+pub fn matches(labels: &Labels, selector: &Labels) -> bool {
+    selector
+        .iter()
+        .all(|(key, value)| labels.get(key) == Some(value))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_validate_label() {
+        assert!(validate_label("team", "search").is_ok());
+        assert!(validate_label("feature.new-ranker", "on").is_ok());
+        assert!(validate_label("env_2", "prod eu").is_ok());
+
+        assert!(validate_label("Team", "search").is_err());
+        assert!(validate_label("2fa", "on").is_err());
+        assert!(validate_label("team name", "search").is_err());
+        assert!(validate_label("team", "").is_err());
+        assert!(validate_label("team", "a\nb").is_err());
+        assert!(validate_label(&"k".repeat(MAX_LABEL_KEY_LEN + 1), "v").is_err());
+
+        let too_many: Labels = (0..=MAX_LABELS)
+            .map(|i| (format!("k{}", i), "v".to_string()))
+            .collect();
+        assert!(validate_labels(&too_many).is_err());
+    }
+
+    #[test]
+    fn test_selector() {
+        let selector = parse_selector("team=search, env=prod").unwrap();
+        assert_eq!(selector.len(), 2);
+        assert!(parse_selector("team").is_err());
+        assert!(parse_selector("").unwrap().is_empty());
+
+        let labels = Labels::from([
+            ("team".to_string(), "search".to_string()),
+            ("env".to_string(), "prod".to_string()),
+            ("flag".to_string(), "on".to_string()),
+        ]);
+        assert!(matches(&labels, &selector));
+        assert!(matches(&labels, &Labels::new()));
+        assert!(!matches(&labels, &parse_selector("team=ads").unwrap()));
+        assert!(!matches(&Labels::new(), &selector));
+    }
+}
@@ -0,0 +1,213 @@
+//! Leader election for singleton background tasks.
+//!
+//! In an HA deployment several replicas run the same background tasks. Tasks
+//! acting on shared state (storage retention, shared configuration, probes
+//! against providers) must only run on one of them. A [`LeaderElector`]
+//! holds a lease in a shared store; [`Leadership`] renews it in the
+//! background and answers whether this replica currently leads, so
+//! [`TaskScope::LeaderOnly`] tasks can skip their work elsewhere.
+//!
+//! Tasks acting on replica-local state (in-memory deduplication windows,
+//! local search indexes) run on every replica.
+
+use crate::Result;
+use async_trait::async_trait;
+use std::{
+    fmt,
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Arc,
+    },
+    time::Duration,
+};
+use tracing::{info, warn};
+
+/// Lease shared between replicas
+#[async_trait]
+pub trait LeaderElector: Send + Sync + fmt::Debug {
+    /// Acquire the lease, or extend it when already held
+    ///
+    /// Returns whether this replica holds the lease afterwards.
+    async fn try_acquire(&self) -> Result<bool>;
+
+    /// Give up the lease if held
+    async fn release(&self) -> Result<()>;
+
+    /// Identity this replica holds the lease under
+    fn identity(&self) -> &str;
+}
+
+/// Where a background task runs
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TaskScope {
+    /// On every replica, for replica-local state
+    EveryReplica,
+    /// Only on the current leader, for shared state
+    LeaderOnly,
+}
+
+/// Whether this replica currently leads
+///
+/// Cloning shares the state. Without an elector (a single replica) this
+/// replica always leads.
+#[derive(Clone)]
+pub struct Leadership {
+    elector: Option<Arc<dyn LeaderElector>>,
+    is_leader: Arc<AtomicBool>,
+    resigned: Arc<AtomicBool>,
+}
+
+impl Leadership {
+    /// Leadership of a single-replica deployment
+    pub fn single() -> Self {
+        Self {
+            elector: None,
+            is_leader: Arc::new(AtomicBool::new(true)),
+            resigned: Arc::new(AtomicBool::new(false)),
+        }
+    }
+
+    /// Campaign for the lease, renewing it every `renew_every`
+    ///
+    /// Leadership is given up as soon as a renewal fails, so a replica cut
+    /// off from the store stops leader-only work before its lease expires
+    /// and another replica takes over. Renew well within the lease duration.
+    pub fn campaign(elector: Arc<dyn LeaderElector>, renew_every: Duration) -> Self {
+        let leadership = Self {
+            elector: Some(elector.clone()),
+            is_leader: Arc::new(AtomicBool::new(false)),
+            resigned: Arc::new(AtomicBool::new(false)),
+        };
+
+        let is_leader = leadership.is_leader.clone();
+        let resigned = leadership.resigned.clone();
+        tokio::spawn(async move {
+            let mut ticker = tokio::time::interval(renew_every);
+            loop {
+                ticker.tick().await;
+                if resigned.load(Ordering::SeqCst) {
+                    break;
+                }
+                let leading = match elector.try_acquire().await {
+                    // Released while the renewal was in flight
+                    Ok(leading) => leading && !resigned.load(Ordering::SeqCst),
+                    Err(e) => {
+                        warn!("Leader lease renewal failed: {}", e);
+                        false
+                    }
+                };
+                if is_leader.swap(leading, Ordering::SeqCst) != leading {
+                    info!(identity = elector.identity(), leading, "Leadership changed");
+                }
+                metrics::gauge!("sentinel_leader").set(if leading { 1.0 } else { 0.0 });
+            }
+        });
+
+        leadership
+    }
+
+    /// Whether this replica currently leads
+    pub fn is_leader(&self) -> bool {
+        self.is_leader.load(Ordering::SeqCst)
+    }
+
+    /// Whether a task of the given scope should run now
+    pub fn should_run(&self, scope: TaskScope) -> bool {
+        match scope {
+            TaskScope::EveryReplica => true,
+            TaskScope::LeaderOnly => self.is_leader(),
+        }
+    }
+
+    /// Stop campaigning and give up the lease, e.g. on shutdown, so another
+    /// replica takes over without waiting for it to expire
+    pub async fn release(&self) -> Result<()> {
+        self.resigned.store(true, Ordering::SeqCst);
+        self.is_leader.store(false, Ordering::SeqCst);
+        match &self.elector {
+            Some(elector) => elector.release().await,
+            None => Ok(()),
+        }
+    }
+}
+
+impl Default for Leadership {
+    fn default() -> Self {
+        Self::single()
+    }
+}
+
+impl fmt::Debug for Leadership {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Leadership")
+            .field("elector", &self.elector)
+            .field("is_leader", &self.is_leader())
+            .finish()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::Mutex;
+
+    // Elector whose outcome the test controls
+    #[derive(Debug)]
+    struct ScriptedElector {
+        outcome: Mutex<Result<bool>>,
+    }
+
+    #[async_trait]
+    impl LeaderElector for ScriptedElector {
+        async fn try_acquire(&self) -> Result<bool> {
+            match &*self.outcome.lock().unwrap() {
+                Ok(leading) => Ok(*leading),
+                Err(e) => Err(crate::Error::connection(e.to_string())),
+            }
+        }
+
+        async fn release(&self) -> Result<()> {
+            Ok(())
+        }
+
+        fn identity(&self) -> &str {
+            "test"
+        }
+    }
+
+    #[test]
+    fn test_single_replica_leads() {
+        let leadership = Leadership::single();
+        assert!(leadership.is_leader());
+        assert!(leadership.should_run(TaskScope::LeaderOnly));
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn test_campaign_follows_lease() {
+        let elector = Arc::new(ScriptedElector {
+            outcome: Mutex::new(Ok(false)),
+        });
+        let leadership = Leadership::campaign(elector.clone(), Duration::from_secs(5));
+        tokio::time::sleep(Duration::from_secs(1)).await;
+        assert!(!leadership.is_leader());
+        assert!(leadership.should_run(TaskScope::EveryReplica));
+        assert!(!leadership.should_run(TaskScope::LeaderOnly));
+
+        *elector.outcome.lock().unwrap() = Ok(true);
+        tokio::time::sleep(Duration::from_secs(5)).await;
+        assert!(leadership.is_leader());
+
+        // A failed renewal gives up leadership
+        *elector.outcome.lock().unwrap() = Err(crate::Error::connection("down"));
+        tokio::time::sleep(Duration::from_secs(5)).await;
+        assert!(!leadership.is_leader());
+
+        *elector.outcome.lock().unwrap() = Ok(true);
+        tokio::time::sleep(Duration::from_secs(5)).await;
+        assert!(leadership.is_leader());
+        leadership.release().await.unwrap();
+        assert!(!leadership.is_leader());
+        tokio::time::sleep(Duration::from_secs(5)).await;
+        assert!(!leadership.is_leader());
+    }
+}
@@ -5,9 +5,36 @@
 //! This crate provides the foundational building blocks used across all Sentinel components:
 //! - Common error types and result handling
 //! - Telemetry event models (OTLP-compatible)
-//! - Anomaly event models
+//! - Anomaly event models and lifecycle states
+//! - Incidents grouping related anomalies
+//! - Responder annotations on anomalies
+//! - Labels for slicing telemetry and routing alerts
 //! - Alert definitions
 //! - Configuration structures
+//! - Per-model token pricing
+//! - Per-model capabilities such as context windows
+//! - Aggregation-only data handling for sensitive services
+//! - Service/model identifier normalization and aliases
+//! - Audit logging for automated actions
+//! - Retry with exponential backoff and jitter
+//! - Request deadlines for downstream calls
+//! - Per-event stage timings for slow-event tracing
+//! - Correlation IDs propagated from ingestion to alerts and API responses
+//! - Bounded worker pools
+//! - Memory budget for large in-memory structures
+//! - Dedicated pools for blocking work
+//! - Sample window specifications and metadata
+//! - Injectable clock for deterministic tests and replays
+//! - Internal event bus between subsystems
+//! - Leader election for singleton background tasks
+//! - Snapshots of learned runtime state
+//! - Anomaly federation between regions
+//! - Secret references in configuration
+//! - Synthetic anomalies for alert pipeline tests
+//! - Self-checks against configured dependencies
+//! - Recurring suppression schedules for expected anomalies
+//! - Detection configuration as a versioned document
+//! - Google Cloud authentication (`gcp` feature)
 //! - Shared utilities
 
 #![warn(
@@ -18,11 +45,40 @@
 )]
 #![forbid(unsafe_code)]
 
+pub mod annotation;
+pub mod audit;
+pub mod blocking;
+pub mod bus;
+pub mod capabilities;
+pub mod clock;
 pub mod config;
+pub mod correlation;
+pub mod deadline;
 pub mod error;
 pub mod events;
+pub mod federation;
+#[cfg(feature = "gcp")]
+pub mod gcp;
+pub mod identifiers;
+pub mod incident;
+pub mod labels;
+pub mod leader;
+pub mod lifecycle;
+pub mod memory;
 pub mod metrics;
+pub mod pricing;
+pub mod privacy;
+pub mod retry;
+pub mod secrets;
+pub mod selfcheck;
+pub mod simulation;
+pub mod snapshot;
+pub mod suppression;
+pub mod timing;
+pub mod tuning;
 pub mod types;
+pub mod window;
+pub mod workers;
 
 pub use error::{Error, Result};
 
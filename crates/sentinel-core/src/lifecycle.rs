@@ -0,0 +1,175 @@
+//! Anomaly lifecycle states.
+//!
+//! A detected anomaly starts [`Open`](AnomalyState::Open). Responders
+//! acknowledge, suppress or resolve it; anomalies nobody acts on can expire.
+//! Every change is recorded as an [`AnomalyStateChange`] and validated
+//! against the allowed transitions:
+//!
+//! | From         | To                                          |
+//! |--------------|---------------------------------------------|
+//! | open         | acknowledged, suppressed, resolved, expired |
+//! | acknowledged | open, suppressed, resolved, expired         |
+//! | suppressed   | open, resolved, expired                     |
+//! | resolved     | open                                        |
+//! | expired      | (none)                                      |
+
+use crate::{Error, Result};
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+/// Actor recorded on state changes made by the recovery monitor
+pub const AUTO_RESOLVER: &str = "auto-resolver";
+
+/// Actor recorded on expiries of anomalies nobody acted on
+pub const EXPIRER: &str = "expirer";
+
+/// Lifecycle state of an anomaly
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum AnomalyState {
+    /// Detected and awaiting a response
+    #[default]
+    Open,
+    /// A responder is looking into it
+    Acknowledged,
+    /// The underlying problem is gone
+    Resolved,
+    /// Deliberately ignored
+    Suppressed,
+    /// Left unresolved past its lifetime
+    Expired,
+}
+
+impl AnomalyState {
+    /// All states
+    pub const ALL: [AnomalyState; 5] = [
+        Self::Open,
+        Self::Acknowledged,
+        Self::Resolved,
+        Self::Suppressed,
+        Self::Expired,
+    ];
+
+    /// Whether an anomaly in this state may move to `to`
+    pub fn can_transition_to(self, to: AnomalyState) -> bool {
+        use AnomalyState::*;
+        matches!(
+            (self, to),
+            (Open, Acknowledged | Suppressed | Resolved | Expired)
+                | (Acknowledged, Open | Suppressed | Resolved | Expired)
+                | (Suppressed, Open | Resolved | Expired)
+                | (Resolved, Open)
+        )
+    }
+
+    /// Validate a transition to `to`
+    pub fn transition(self, to: AnomalyState) -> Result<AnomalyState> {
+        if self.can_transition_to(to) {
+            Ok(to)
+        } else {
+            Err(Error::validation(format!(
+                "cannot change anomaly state from {} to {}",
+                self, to
+            )))
+        }
+    }
+
+    /// Whether the anomaly still needs attention
+    pub fn is_active(self) -> bool {
+        matches!(self, Self::Open | Self::Acknowledged)
+    }
+}
+
+impl std::fmt::Display for AnomalyState {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Open => write!(f, "open"),
+            Self::Acknowledged => write!(f, "acknowledged"),
+            Self::Resolved => write!(f, "resolved"),
+            Self::Suppressed => write!(f, "suppressed"),
+            Self::Expired => write!(f, "expired"),
+        }
+    }
+}
+
+impl std::str::FromStr for AnomalyState {
+    type Err = String;
+
+    fn from_str(s: &str) -> std::result::Result<Self, Self::Err> {
+        match s {
+            "open" => Ok(Self::Open),
+            "acknowledged" => Ok(Self::Acknowledged),
+            "resolved" => Ok(Self::Resolved),
+            "suppressed" => Ok(Self::Suppressed),
+            "expired" => Ok(Self::Expired),
+            other => Err(format!("unknown anomaly state: {}", other)),
+        }
+    }
+}
+
+/// Recorded change of an anomaly's state
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct AnomalyStateChange {
+    /// Anomaly the change applies to
+    pub alert_id: Uuid,
+    /// State before the change
+    pub from: AnomalyState,
+    /// State after the change
+    pub to: AnomalyState,
+    /// When the change happened
+    pub at: DateTime<Utc>,
+    /// Who made the change, e.g. a user or `auto-resolver`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub actor: Option<String>,
+    /// Why the change was made
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub reason: Option<String>,
+}
+
+impl AnomalyStateChange {
+    /// Whether Sentinel made the change rather than a responder
+    pub fn is_automatic(&self) -> bool {
+        matches!(self.actor.as_deref(), Some(AUTO_RESOLVER | EXPIRER))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_transitions() {
+        use AnomalyState::*;
+
+        assert!(Open.can_transition_to(Acknowledged));
+        assert!(Acknowledged.can_transition_to(Resolved));
+        assert!(Suppressed.can_transition_to(Open));
+        assert!(Resolved.can_transition_to(Open));
+
+        assert!(!Resolved.can_transition_to(Acknowledged));
+        assert!(!Suppressed.can_transition_to(Acknowledged));
+        for state in AnomalyState::ALL {
+            assert!(!state.can_transition_to(state));
+            assert!(!Expired.can_transition_to(state));
+        }
+
+        assert_eq!(Open.transition(Resolved).unwrap(), Resolved);
+        assert!(matches!(
+            Expired.transition(Open),
+            Err(Error::Validation(_))
+        ));
+    }
+
+    #[test]
+    fn test_state_round_trip() {
+        for state in AnomalyState::ALL {
+            assert_eq!(state.to_string().parse::<AnomalyState>().unwrap(), state);
+            let json = serde_json::to_string(&state).unwrap();
+            assert_eq!(json, format!("\"{}\"", state));
+        }
+        assert!("closed".parse::<AnomalyState>().is_err());
+        assert!(AnomalyState::Acknowledged.is_active());
+        assert!(!AnomalyState::Suppressed.is_active());
+    }
+}
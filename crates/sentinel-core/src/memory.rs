@@ -0,0 +1,302 @@
+//! Memory budget for large in-memory structures.
+//!
+//! Baseline windows, deduplication windows, queued events and caches grow
+//! with the number of services, models and alert signatures seen, which
+//! sentinel does not control. Each implements [`MemoryConsumer`] and
+//! registers with the [`MemoryWatchdog`], which periodically adds up their
+//! approximate usage against `memory.budget_mb`. Past the high watermark it
+//! asks consumers to evict down to the target share of the budget, cheapest
+//! to rebuild first and largest first within a [`ShedCost`]. Usage still over
+//! the budget after eviction puts the watchdog in shedding mode, in which new
+//! events skip detection.
+//!
+//! Usage is exported as the `sentinel_memory_bytes` gauge labelled by
+//! component, next to `sentinel_memory_budget_bytes` and
+//! `sentinel_memory_shedding`; evicted bytes are counted in
+//! `sentinel_memory_evicted_bytes_total`.
+
+use crate::config::MemoryBudgetConfig;
+use std::sync::{
+    atomic::{AtomicBool, Ordering},
+    Arc, RwLock,
+};
+use tracing::{info, warn};
+
+/// How costly evicting a consumer's memory is
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum ShedCost {
+    /// Rebuilt on demand, e.g. caches
+    Cheap,
+    /// Learned state that takes time to rebuild, e.g. baselines
+    Expensive,
+}
+
+/// Component holding a large in-memory structure
+pub trait MemoryConsumer: Send + Sync {
+    /// Component name used in logs and metrics
+    fn name(&self) -> &str;
+
+    /// Approximate bytes held
+    fn memory_usage(&self) -> usize;
+
+    /// How costly evicting this component's memory is
+    fn shed_cost(&self) -> ShedCost;
+
+    /// Evict roughly `bytes`, returning the approximate bytes freed
+    ///
+    /// Components that cannot evict (e.g. queues) return 0.
+    fn shed(&self, bytes: usize) -> usize;
+}
+
+/// Usage of one consumer
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ComponentUsage {
+    /// Component name
+    pub name: String,
+    /// Approximate bytes held
+    pub bytes: usize,
+}
+
+/// Result of a usage check
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MemoryReport {
+    /// Budget in bytes
+    pub budget: usize,
+    /// Usage after eviction, by component
+    pub components: Vec<ComponentUsage>,
+    /// Bytes evicted by this check
+    pub evicted: usize,
+    /// Whether usage is still over the budget
+    pub shedding: bool,
+}
+
+impl MemoryReport {
+    /// Total bytes held by all components
+    pub fn total(&self) -> usize {
+        self.components.iter().map(|c| c.bytes).sum()
+    }
+}
+
+/// Tracks registered consumers against a memory budget
+pub struct MemoryWatchdog {
+    config: MemoryBudgetConfig,
+    consumers: RwLock<Vec<Arc<dyn MemoryConsumer>>>,
+    shedding: AtomicBool,
+}
+
+impl std::fmt::Debug for MemoryWatchdog {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let consumers: Vec<String> = self
+            .consumers
+            .read()
+            .unwrap()
+            .iter()
+            .map(|c| c.name().to_string())
+            .collect();
+        f.debug_struct("MemoryWatchdog")
+            .field("config", &self.config)
+            .field("consumers", &consumers)
+            .field("shedding", &self.is_shedding())
+            .finish()
+    }
+}
+
+impl MemoryWatchdog {
+    /// Create a watchdog for the given budget
+    pub fn new(config: MemoryBudgetConfig) -> Self {
+        info!(
+            "Creating memory watchdog with {} MB budget",
+            config.budget_mb
+        );
+        metrics::gauge!("sentinel_memory_budget_bytes").set(Self::budget(&config) as f64);
+        metrics::gauge!("sentinel_memory_shedding").set(0.0);
+        Self {
+            config,
+            consumers: RwLock::new(Vec::new()),
+            shedding: AtomicBool::new(false),
+        }
+    }
+
+    fn budget(config: &MemoryBudgetConfig) -> usize {
+        (config.budget_mb as usize).saturating_mul(1024 * 1024)
+    }
+
+    /// Track a consumer
+    pub fn register(&self, consumer: Arc<dyn MemoryConsumer>) {
+        info!(component = consumer.name(), "Tracking memory usage");
+        self.consumers.write().unwrap().push(consumer);
+    }
+
+    /// Whether usage was over the budget at the last check
+    pub fn is_shedding(&self) -> bool {
+        self.shedding.load(Ordering::Relaxed)
+    }
+
+    /// Measure all consumers, evicting if usage is past the high watermark
+    pub fn check(&self) -> MemoryReport {
+        let budget = Self::budget(&self.config);
+        let consumers = self.consumers.read().unwrap().clone();
+        let mut usage: Vec<usize> = consumers.iter().map(|c| c.memory_usage()).collect();
+        let total: usize = usage.iter().sum();
+
+        let mut evicted = 0;
+        if total as f64 >= budget as f64 * self.config.high_watermark {
+            let target = (budget as f64 * self.config.target) as usize;
+            let mut excess = total.saturating_sub(target);
+            warn!(
+                usage = total,
+                budget, "Memory usage past high watermark, evicting"
+            );
+
+            let mut order: Vec<usize> = (0..consumers.len()).collect();
+            order.sort_by_key(|&i| (consumers[i].shed_cost(), std::cmp::Reverse(usage[i])));
+            for i in order {
+                if excess == 0 {
+                    break;
+                }
+                let freed = consumers[i].shed(excess).min(usage[i]);
+                if freed > 0 {
+                    metrics::counter!("sentinel_memory_evicted_bytes_total",
+                        "component" => consumers[i].name().to_string()
+                    )
+                    .increment(freed as u64);
+                    usage[i] -= freed;
+                    excess = excess.saturating_sub(freed);
+                    evicted += freed;
+                }
+            }
+        }
+
+        let components: Vec<ComponentUsage> = consumers
+            .iter()
+            .zip(&usage)
+            .map(|(consumer, &bytes)| {
+                metrics::gauge!("sentinel_memory_bytes", "component" => consumer.name().to_string())
+                    .set(bytes as f64);
+                ComponentUsage {
+                    name: consumer.name().to_string(),
+                    bytes,
+                }
+            })
+            .collect();
+
+        let remaining: usize = usage.iter().sum();
+        let shedding = remaining > budget;
+        if shedding != self.shedding.swap(shedding, Ordering::Relaxed) {
+            if shedding {
+                warn!(
+                    usage = remaining,
+                    budget, "Memory still over budget, skipping detection of new events"
+                );
+            } else {
+                info!(usage = remaining, budget, "Memory back within budget");
+            }
+            metrics::gauge!("sentinel_memory_shedding").set(if shedding { 1.0 } else { 0.0 });
+        }
+
+        MemoryReport {
+            budget,
+            components,
+            evicted,
+            shedding,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::Mutex;
+
+    const MB: usize = 1024 * 1024;
+
+    struct FixedConsumer {
+        name: &'static str,
+        cost: ShedCost,
+        evictable: bool,
+        bytes: Mutex<usize>,
+    }
+
+    impl FixedConsumer {
+        fn new(name: &'static str, cost: ShedCost, bytes: usize) -> Arc<Self> {
+            Arc::new(Self {
+                name,
+                cost,
+                evictable: true,
+                bytes: Mutex::new(bytes),
+            })
+        }
+    }
+
+    impl MemoryConsumer for FixedConsumer {
+        fn name(&self) -> &str {
+            self.name
+        }
+
+        fn memory_usage(&self) -> usize {
+            *self.bytes.lock().unwrap()
+        }
+
+        fn shed_cost(&self) -> ShedCost {
+            self.cost
+        }
+
+        fn shed(&self, bytes: usize) -> usize {
+            if !self.evictable {
+                return 0;
+            }
+            let mut held = self.bytes.lock().unwrap();
+            let freed = bytes.min(*held);
+            *held -= freed;
+            freed
+        }
+    }
+
+    fn config() -> MemoryBudgetConfig {
+        MemoryBudgetConfig {
+            budget_mb: 10,
+            high_watermark: 0.9,
+            target: 0.5,
+            check_interval_secs: 5,
+        }
+    }
+
+    #[test]
+    fn test_evicts_cheapest_first() {
+        let watchdog = MemoryWatchdog::new(config());
+        let baselines = FixedConsumer::new("baselines", ShedCost::Expensive, 6 * MB);
+        let cache = FixedConsumer::new("cache", ShedCost::Cheap, 3 * MB);
+        watchdog.register(baselines.clone());
+        watchdog.register(cache.clone());
+
+        // 9 MB of a 10 MB budget reaches the watermark; evict down to 5 MB
+        let report = watchdog.check();
+        assert_eq!(report.evicted, 4 * MB);
+        assert_eq!(cache.memory_usage(), 0);
+        assert_eq!(baselines.memory_usage(), 5 * MB);
+        assert_eq!(report.total(), 5 * MB);
+        assert!(!report.shedding);
+
+        // Below the watermark nothing is evicted
+        assert_eq!(watchdog.check().evicted, 0);
+    }
+
+    #[test]
+    fn test_sheds_while_over_budget() {
+        let watchdog = MemoryWatchdog::new(config());
+        let queue = Arc::new(FixedConsumer {
+            name: "queue",
+            cost: ShedCost::Expensive,
+            evictable: false,
+            bytes: Mutex::new(12 * MB),
+        });
+        watchdog.register(queue.clone());
+
+        assert!(watchdog.check().shedding);
+        assert!(watchdog.is_shedding());
+
+        *queue.bytes.lock().unwrap() = MB;
+        assert!(!watchdog.check().shedding);
+        assert!(!watchdog.is_shedding());
+    }
+}
@@ -1,6 +1,15 @@
 //! Metrics definitions and constants for Sentinel.
 //!
 //! This module provides metric names and labels used throughout the system.
+//! [`CATALOG`] declares every metric recorded across the crates with its
+//! kind, label keys and help text; a test keeps it in sync with the code.
+//! Metrics are grouped into families that can be disabled at runtime with
+//! [`set_disabled_families`], which the exporter consults before recording.
+
+use crate::{Error, Result};
+use serde::Serialize;
+use std::collections::BTreeSet;
+use std::sync::RwLock;
 
 /// Metrics namespace
 pub const METRICS_NAMESPACE: &str = "sentinel";
@@ -121,6 +130,322 @@ pub const COST_BUCKETS: &[f64] = &[
     0.0001, 0.0005, 0.001, 0.005, 0.01, 0.05, 0.1, 0.5, 1.0, 5.0, 10.0, 50.0,
 ];
 
+/// Kind of a metric
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum MetricKind {
+    /// Monotonic counter
+    Counter,
+    /// Value that goes up and down
+    Gauge,
+    /// Distribution of observed values
+    Histogram,
+}
+
+/// Declaration of a metric in the [`CATALOG`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+pub struct MetricDef {
+    /// Full name as recorded, e.g. `sentinel_events_ingested_total`
+    pub name: &'static str,
+    /// Metric kind
+    pub kind: MetricKind,
+    /// Family the metric can be disabled with
+    pub family: &'static str,
+    /// Label keys every series carries
+    pub labels: &'static [&'static str],
+    /// Help text
+    pub help: &'static str,
+}
+
+impl MetricDef {
+    /// Whether `labels` are exactly the declared label keys, in any order
+    pub fn has_labels<'a>(&self, labels: impl IntoIterator<Item = &'a str>) -> bool {
+        let mut labels: Vec<&str> = labels.into_iter().collect();
+        labels.sort_unstable();
+        let mut declared = self.labels.to_vec();
+        declared.sort_unstable();
+        labels == declared
+    }
+}
+
+const fn counter(
+    family: &'static str,
+    name: &'static str,
+    labels: &'static [&'static str],
+    help: &'static str,
+) -> MetricDef {
+    MetricDef {
+        name,
+        kind: MetricKind::Counter,
+        family,
+        labels,
+        help,
+    }
+}
+
+const fn gauge(
+    family: &'static str,
+    name: &'static str,
+    labels: &'static [&'static str],
+    help: &'static str,
+) -> MetricDef {
+    MetricDef {
+        name,
+        kind: MetricKind::Gauge,
+        family,
+        labels,
+        help,
+    }
+}
+
+const fn histogram(
+    family: &'static str,
+    name: &'static str,
+    labels: &'static [&'static str],
+    help: &'static str,
+) -> MetricDef {
+    MetricDef {
+        name,
+        kind: MetricKind::Histogram,
+        family,
+        labels,
+        help,
+    }
+}
+
+/// Every metric Sentinel records, by family
+#[rustfmt::skip]
+pub const CATALOG: &[MetricDef] = &[
+    // Ingestion
+    counter("ingestion", "sentinel_events_ingested_total", &[], "Telemetry events consumed from the broker"),
+    counter("ingestion", "sentinel_events_dropped_total", &["reason"], "Telemetry events dropped before detection"),
+    counter("ingestion", "sentinel_events_excluded_total", &["reason"], "Telemetry events stored but kept out of detection"),
+    counter("ingestion", "sentinel_events_processed_total", &[], "Telemetry events processed"),
+    counter("ingestion", "sentinel_events_over_quota_total", &["service", "sampled"], "Telemetry events over their service's ingestion quota"),
+    counter("ingestion", "sentinel_events_skewed_total", &["direction", "action"], "Telemetry events with timestamps outside the clock skew tolerance"),
+    histogram("ingestion", "sentinel_event_clock_skew_seconds", &["direction"], "Distance of skewed event timestamps from the local clock"),
+    counter("ingestion", "sentinel_errors_total", &["error_type"], "Broker consumer errors"),
+    counter("ingestion", "sentinel_ingestion_errors_total", &[], "Failed ingester batch fetches"),
+    counter("ingestion", "sentinel_enrichment_errors_total", &[], "Failed event enrichments"),
+    counter("ingestion", "sentinel_raw_events_withheld_total", &[], "Raw events not kept under aggregation-only mode"),
+    gauge("ingestion", "sentinel_ingestion_paused", &[], "Whether ingestion is paused by back-pressure (0 or 1)"),
+    counter("ingestion", "sentinel_ingestion_paused_total", &[], "Ingestion pauses for a backed-up detection queue"),
+    counter("ingestion", "sentinel_ingestion_resumed_total", &[], "Ingestion resumes after the detection queue drained"),
+    histogram("ingestion", "sentinel_ingestion_paused_seconds", &[], "Time ingestion spent paused"),
+    // Data quality
+    counter("quality", "sentinel_data_quality_issues_total", &["service", "issue"], "Data quality issues found in telemetry"),
+    gauge("quality", "sentinel_data_quality_score", &["service"], "Rolling data quality score per service (0-1)"),
+    // Embeddings
+    counter("embedding", "sentinel_embedding_texts_total", &[], "Texts embedded"),
+    counter("embedding", "sentinel_embedding_tokens_total", &[], "Tokens sent to the embedding provider"),
+    counter("embedding", "sentinel_embedding_cache_hits_total", &[], "Embeddings served from cache"),
+    counter("embedding", "sentinel_embedding_errors_total", &[], "Failed embedding requests"),
+    gauge("embedding", "sentinel_embedding_cost_usd", &[], "Accumulated embedding cost (USD)"),
+    histogram("embedding", "sentinel_embedding_request_duration_seconds", &[], "Embedding request latency"),
+    // Detection
+    counter("detection", "sentinel_anomalies_detected_total", &["detector", "severity", "type"], "Anomalies detected"),
+    histogram("detection", "sentinel_detection_duration_seconds", &["detector"], "Time a detector spent on an event"),
+    counter("detection", "sentinel_detection_errors_total", &["detector"], "Detector failures"),
+    counter("detection", "sentinel_detection_guard_total", &["outcome"], "Tiered detection guard decisions"),
+    counter("detection", "sentinel_detection_stage_events_total", &["stage"], "Events run through a detection stage"),
+    counter("detection", "sentinel_detection_stage_anomalies_total", &["stage"], "Anomalies detected per detection stage"),
+    histogram("detection", "sentinel_detection_stage_duration_seconds", &["stage"], "Time spent in a detection stage"),
+    gauge("detection", "sentinel_detection_degraded", &[], "Whether detection load shedding is active (0 or 1)"),
+    gauge("detection", "sentinel_detection_sample_rate", &[], "Share of events sent to detection while shedding load"),
+    counter("detection", "sentinel_events_sampled_out_total", &[], "Events skipped by detection load shedding"),
+    counter("detection", "sentinel_events_normal_total", &[], "Events no detector flagged"),
+    counter("detection", "sentinel_events_over_deadline_total", &["stage"], "Events that exceeded their processing deadline"),
+    histogram("detection", "sentinel_event_processing_seconds", &[], "End-to-end processing time of an event"),
+    counter("detection", "sentinel_baseline_bootstrap_events_total", &[], "Stored events replayed to bootstrap baselines"),
+    gauge("detection", "sentinel_baseline_mean", &["service", "model", "metric"], "Baseline mean per service, model and metric"),
+    gauge("detection", "sentinel_recovery_watched_anomalies", &[], "Anomalies watched for recovery"),
+    counter("detection", "sentinel_telemetry_silence_total", &[], "Producers that went silent"),
+    counter("detection", "sentinel_telemetry_silence_resumed_total", &[], "Silent producers that resumed sending"),
+    gauge("detection", "sentinel_traces_open", &[], "Traces collecting spans for chain evaluation"),
+    counter("detection", "sentinel_traces_evaluated_total", &[], "Completed traces evaluated as chains"),
+    counter("detection", "sentinel_trace_spans_dropped_total", &["reason"], "Spans left out of chain evaluation"),
+    counter("detection", "sentinel_span_anomalies_suppressed_total", &[], "Span anomalies left to chain evaluation"),
+    counter("detection", "sentinel_model_version_changes_total", &["family"], "Model version changes seen in telemetry"),
+    counter("detection", "sentinel_availability_alerts_total", &["provider", "scope"], "Provider and model availability alerts"),
+    counter("detection", "sentinel_redetected_anomalies_total", &[], "Anomalies found by re-detection jobs"),
+    counter("detection", "sentinel_test_anomalies_total", &[], "Synthetic test anomalies injected"),
+    // SLOs
+    counter("slo", "sentinel_slo_burn_alerts_total", &["slo", "severity"], "SLO burn rate alerts"),
+    gauge("slo", "sentinel_slo_error_budget_remaining", &["slo"], "Share of the SLO error budget left"),
+    // Anomaly lifecycle
+    counter("lifecycle", "sentinel_anomaly_state_changes_total", &["from", "to"], "Anomaly lifecycle state changes"),
+    counter("lifecycle", "sentinel_anomaly_annotations_total", &["kind"], "Annotations added to anomalies"),
+    counter("lifecycle", "sentinel_anomalies_auto_resolved_total", &[], "Anomalies resolved after their metric recovered"),
+    counter("lifecycle", "sentinel_anomalies_expired_total", &["severity"], "Anomalies expired without a response"),
+    counter("lifecycle", "sentinel_anomalies_expected_total", &["schedule"], "Anomalies expected by a suppression schedule"),
+    counter("lifecycle", "sentinel_anomalies_suppressed_total", &[], "Anomalies suppressed by scripts"),
+    // Incidents
+    counter("incidents", "sentinel_incidents_opened_total", &[], "Incidents opened"),
+    counter("incidents", "sentinel_incident_anomalies_total", &["update"], "Anomalies attached to incidents"),
+    counter("incidents", "sentinel_incident_state_changes_total", &["to"], "Incident state changes"),
+    counter("incidents", "sentinel_incident_notifications_total", &["update"], "Incident updates sent to alerters"),
+    // Alerting
+    counter("alerting", "sentinel_alerts_sent_total", &[], "Alerts passed deduplication"),
+    counter("alerting", "sentinel_alerts_deduplicated_total", &[], "Alerts suppressed as duplicates"),
+    gauge("alerting", "sentinel_deduplication_entries", &[], "Alert signatures tracked for deduplication"),
+    gauge("alerting", "sentinel_deduplication_rate", &[], "Share of alerts suppressed as duplicates"),
+    gauge("alerting", "sentinel_deduplication_suppressed", &["severity"], "Alerts suppressed as duplicates per severity"),
+    counter("alerting", "sentinel_alert_deliveries_total", &["alerter", "severity", "status"], "Alert deliveries"),
+    histogram("alerting", "sentinel_alert_delivery_latency_seconds", &["alerter", "severity"], "Time from detection to alert delivery"),
+    counter("alerting", "sentinel_alert_failures_total", &[], "Failed alert deliveries"),
+    gauge("alerting", "sentinel_alert_queue_depth", &["alerter"], "Alerts queued for an alerter"),
+    histogram("alerting", "sentinel_alert_batch_size", &["alerter"], "Alerts sent per batch"),
+    counter("alerting", "sentinel_alert_dispatch_overflow_total", &["alerter"], "Alerts dropped from a full dispatch queue"),
+    counter("alerting", "sentinel_alert_dispatch_priority_waits_total", &["alerter"], "Priority alerts that waited for queue space"),
+    counter("alerting", "sentinel_alert_payloads_truncated_total", &[], "Alert payloads trimmed to the size limit"),
+    counter("alerting", "sentinel_alert_severity_adjusted_total", &["rule"], "Alerts whose severity a rule adjusted"),
+    gauge("alerting", "sentinel_alerts_held", &[], "Alerts held for their delivery window"),
+    counter("alerting", "sentinel_alerts_held_total", &["alerter", "schedule"], "Alerts held outside a delivery window"),
+    counter("alerting", "sentinel_alerts_released_total", &["alerter"], "Held alerts sent once their window opened"),
+    counter("alerting", "sentinel_alert_redeliveries_total", &["alerter", "status"], "Alerts sent again through the API"),
+    counter("alerting", "sentinel_digests_total", &["digest", "status"], "Digest reports sent"),
+    counter("alerting", "sentinel_remediation_actions_total", &["rule", "status"], "Remediation actions run"),
+    counter("alerting", "sentinel_issue_alerts_total", &["provider", "outcome"], "Issue tracker alert operations"),
+    // Alerters
+    counter("alerters", "sentinel_datadog_events_total", &["severity"], "Events posted to Datadog"),
+    counter("alerters", "sentinel_datadog_failures_total", &[], "Failed Datadog posts"),
+    counter("alerters", "sentinel_discord_messages_total", &["severity"], "Messages posted to Discord"),
+    counter("alerters", "sentinel_discord_failures_total", &[], "Failed Discord posts"),
+    counter("alerters", "sentinel_eventbridge_publishes_total", &["severity"], "Events published to EventBridge"),
+    counter("alerters", "sentinel_eventbridge_failures_total", &[], "Failed EventBridge publishes"),
+    counter("alerters", "sentinel_kafka_alert_publishes_total", &["severity"], "Alerts published to Kafka"),
+    counter("alerters", "sentinel_kafka_alert_failures_total", &[], "Failed Kafka alert publishes"),
+    counter("alerters", "sentinel_mqtt_publishes_total", &["severity"], "Alerts published to MQTT"),
+    counter("alerters", "sentinel_mqtt_failures_total", &[], "Failed MQTT publishes"),
+    counter("alerters", "sentinel_pubsub_publishes_total", &["severity"], "Alerts published to Pub/Sub"),
+    counter("alerters", "sentinel_pubsub_failures_total", &[], "Failed Pub/Sub publishes"),
+    counter("alerters", "sentinel_rabbitmq_publishes_total", &["severity"], "Alerts published to RabbitMQ"),
+    counter("alerters", "sentinel_rabbitmq_failures_total", &[], "Failed RabbitMQ publishes"),
+    counter("alerters", "sentinel_rabbitmq_retries_total", &[], "Retried RabbitMQ publishes"),
+    counter("alerters", "sentinel_sns_publishes_total", &["severity"], "Alerts published to SNS"),
+    counter("alerters", "sentinel_sns_failures_total", &[], "Failed SNS publishes"),
+    counter("alerters", "sentinel_splunk_events_total", &[], "Events sent to Splunk"),
+    counter("alerters", "sentinel_splunk_failures_total", &[], "Failed Splunk sends"),
+    counter("alerters", "sentinel_telegram_messages_total", &["severity"], "Messages sent to Telegram"),
+    counter("alerters", "sentinel_telegram_failures_total", &[], "Failed Telegram sends"),
+    counter("alerters", "sentinel_twilio_pages_total", &["channel"], "Pages sent through Twilio"),
+    counter("alerters", "sentinel_twilio_failures_total", &[], "Failed Twilio pages"),
+    counter("alerters", "sentinel_twilio_rate_limited_total", &[], "Twilio pages held back by the rate limit"),
+    counter("alerters", "sentinel_twilio_suppressed_total", &[], "Alerts not paged for their severity or quiet hours"),
+    counter("alerters", "sentinel_webhook_success_total", &[], "Successful webhook deliveries"),
+    counter("alerters", "sentinel_webhook_failures_total", &[], "Failed webhook deliveries"),
+    counter("alerters", "sentinel_webhook_retries_total", &[], "Retried webhook deliveries"),
+    // Export and federation
+    counter("export", "sentinel_export_records_total", &["sink", "kind"], "Records exported to analytics stores"),
+    counter("export", "sentinel_export_failures_total", &["sink", "kind"], "Failed exports to analytics stores"),
+    counter("export", "sentinel_fanout_published_total", &["exchange"], "Telemetry events fanned out"),
+    counter("export", "sentinel_fanout_failures_total", &["exchange"], "Failed telemetry fan-out publishes"),
+    counter("federation", "sentinel_federation_forwarded_total", &[], "Anomalies forwarded to the upstream region"),
+    counter("federation", "sentinel_federation_forward_failures_total", &[], "Failed anomaly forwards"),
+    counter("federation", "sentinel_federated_anomalies_total", &["region"], "Anomalies received from other regions"),
+    counter("federation", "sentinel_federation_rejected_total", &[], "Rejected federation batches"),
+    // Storage
+    counter("storage", "sentinel_storage_writes_total", &["type"], "Storage writes"),
+    counter("storage", "sentinel_storage_queries_total", &["type"], "Storage queries"),
+    counter("storage", "sentinel_storage_errors_total", &["type"], "Failed storage operations"),
+    counter("storage", "sentinel_storage_write_retries_total", &[], "Retried storage writes"),
+    counter("storage", "sentinel_storage_rejected_points_total", &["type"], "Points the storage backend rejected"),
+    counter("storage", "sentinel_storage_duplicate_writes_total", &["kind"], "Duplicate writes skipped"),
+    gauge("storage", "sentinel_storage_tag_cardinality", &["tag"], "Estimated distinct values per storage tag"),
+    counter("storage", "sentinel_priority_write_failures_total", &["type"], "Priority writes lost after every retry"),
+    counter("storage", "sentinel_stream_acks_total", &[], "Anomaly stream offsets acknowledged"),
+    counter("storage", "sentinel_cost_rollups_flushed_total", &[], "Cost rollups written"),
+    histogram("storage", "sentinel_anomaly_write_batch_size", &[], "Anomalies per batch write"),
+    gauge("storage", "sentinel_outbox_entries", &["status"], "Anomalies in the outbox awaiting write or dispatch"),
+    counter("storage", "sentinel_outbox_deferred_total", &[], "Anomalies whose write failed, kept for retry"),
+    counter("storage", "sentinel_outbox_relayed_total", &[], "Deferred anomalies written and dispatched"),
+    counter("storage", "sentinel_outbox_dropped_total", &[], "Unwritten anomalies dropped from a full outbox"),
+    // Caches
+    counter("cache", "sentinel_cache_hits_total", &["cache"], "Cache hits"),
+    counter("cache", "sentinel_cache_misses_total", &["cache"], "Cache misses"),
+    counter("cache", "sentinel_cache_inserts_total", &["cache"], "Cache inserts"),
+    counter("cache", "sentinel_cache_removals_total", &["cache"], "Cache removals"),
+    // Cost
+    counter("cost", "sentinel_cost_computed_total", &[], "Event costs computed from the price table"),
+    gauge("cost", "sentinel_cost_projected_usd", &["service"], "Projected month-end spend (USD)"),
+    // API
+    counter("api", "sentinel_api_cache_requests_total", &["result"], "API response cache lookups"),
+    counter("api", "sentinel_api_idempotency_requests_total", &["result"], "API writes carrying an Idempotency-Key"),
+    gauge("api", "sentinel_api_streams_active", &[], "Open anomaly streams"),
+    counter("api", "sentinel_api_stream_replayed_total", &[], "Anomalies replayed to stream consumers"),
+    counter("api", "sentinel_api_telemetry_streamed_total", &[], "Telemetry events sent by the NDJSON export stream"),
+    // Runtime
+    gauge("runtime", "sentinel_active_workers", &["pool"], "Busy workers per pool"),
+    gauge("runtime", "sentinel_queue_depth", &["pool"], "Jobs queued per worker pool"),
+    counter("runtime", "sentinel_worker_jobs_total", &["pool"], "Jobs run per worker pool"),
+    gauge("runtime", "sentinel_blocking_queued", &["pool"], "Tasks queued per blocking pool"),
+    counter("runtime", "sentinel_blocking_tasks_total", &["pool"], "Tasks run per blocking pool"),
+    counter("runtime", "sentinel_bus_messages_lagged_total", &["topic"], "Event bus messages missed by slow subscribers"),
+    gauge("runtime", "sentinel_runtime_workers", &[], "Async runtime worker threads"),
+    gauge("runtime", "sentinel_runtime_alive_tasks", &[], "Live async tasks"),
+    gauge("runtime", "sentinel_runtime_global_queue_depth", &[], "Tasks in the runtime's global queue"),
+    gauge("runtime", "sentinel_runtime_busy_ratio", &[], "Share of time runtime workers were busy"),
+    histogram("runtime", "sentinel_runtime_schedule_delay_seconds", &[], "Delay before a probe task was scheduled"),
+    gauge("runtime", "sentinel_leader", &[], "Whether this replica leads (0 or 1)"),
+    counter("runtime", "sentinel_snapshots_total", &["operation"], "Runtime state snapshots taken or restored"),
+    gauge("runtime", "sentinel_standby", &[], "Whether this replica is a warm standby (0 or 1)"),
+    counter("runtime", "sentinel_state_syncs_total", &["operation"], "Standby state published or restored"),
+    counter("runtime", "sentinel_state_sync_failures_total", &["operation"], "Failed standby state syncs"),
+    gauge("runtime", "sentinel_standby_state_age_seconds", &[], "Age of the newest state published by the primary"),
+    counter("runtime", "sentinel_standby_promotions_total", &[], "Standbys promoted to primary"),
+    counter("runtime", "sentinel_chaos_faults_total", &["target", "fault"], "Faults injected by chaos testing"),
+    counter("runtime", "sentinel_log_records_shipped_total", &["sink"], "Log records shipped to the log sink"),
+    counter("runtime", "sentinel_log_records_dropped_total", &["reason"], "Log records dropped instead of shipped"),
+    // Memory
+    gauge("memory", "sentinel_memory_budget_bytes", &[], "Configured memory budget"),
+    gauge("memory", "sentinel_memory_bytes", &["component"], "Approximate memory use per component"),
+    counter("memory", "sentinel_memory_evicted_bytes_total", &["component"], "Memory evicted to stay within budget"),
+    gauge("memory", "sentinel_memory_shedding", &[], "Whether detection is shed for memory (0 or 1)"),
+    // Secrets
+    counter("secrets", "sentinel_secret_rotations_total", &["scheme"], "Secrets fetched again after a change"),
+    counter("secrets", "sentinel_secret_fetch_failures_total", &["scheme"], "Failed secret fetches"),
+    counter("secrets", "sentinel_credential_rotations_total", &["component"], "Client credentials rotated"),
+    counter("secrets", "sentinel_credential_rotation_failures_total", &["component"], "Failed credential rotations"),
+    // Plugins
+    histogram("plugins", "sentinel_plugin_call_duration_seconds", &["plugin"], "WASM plugin call latency"),
+    counter("plugins", "sentinel_script_errors_total", &["hook", "script"], "Failed script hook runs"),
+];
+
+/// Families disabled at runtime
+static DISABLED_FAMILIES: RwLock<BTreeSet<String>> = RwLock::new(BTreeSet::new());
+
+/// Declaration of a metric, if it is in the catalog
+pub fn lookup(name: &str) -> Option<&'static MetricDef> {
+    CATALOG.iter().find(|def| def.name == name)
+}
+
+/// Names of all metric families
+pub fn families() -> BTreeSet<&'static str> {
+    CATALOG.iter().map(|def| def.family).collect()
+}
+
+/// Disable the given metric families, enabling every other one
+///
+/// Fails on families not in the catalog.
+pub fn set_disabled_families(disabled: &[String]) -> Result<()> {
+    let known = families();
+    if let Some(unknown) = disabled.iter().find(|f| !known.contains(f.as_str())) {
+        return Err(Error::config(format!("Unknown metric family: {}", unknown)));
+    }
+    *DISABLED_FAMILIES.write().unwrap() = disabled.iter().cloned().collect();
+    Ok(())
+}
+
+/// Whether a metric family is enabled
+pub fn is_family_enabled(family: &str) -> bool {
+    !DISABLED_FAMILIES.read().unwrap().contains(family)
+}
+
+/// Whether a metric is recorded; metrics outside the catalog always are
+pub fn is_enabled(name: &str) -> bool {
+    lookup(name).map_or(true, |def| is_family_enabled(def.family))
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -146,4 +471,142 @@ mod tests {
             assert!(window[0] < window[1]);
         }
     }
+
+    #[test]
+    fn test_catalog_well_formed() {
+        let mut names = BTreeSet::new();
+        for def in CATALOG {
+            assert!(names.insert(def.name), "{} declared twice", def.name);
+            assert!(def.name.starts_with("sentinel_"), "{}", def.name);
+            assert!(!def.help.is_empty(), "{} has no help", def.name);
+            if def.kind == MetricKind::Counter {
+                assert!(def.name.ends_with("_total"), "{}", def.name);
+            }
+            let labels: BTreeSet<_> = def.labels.iter().collect();
+            assert_eq!(labels.len(), def.labels.len(), "{}", def.name);
+        }
+        assert_eq!(
+            lookup("sentinel_storage_writes_total").map(|def| def.family),
+            Some("storage")
+        );
+        assert!(lookup("sentinel_unknown_total").is_none());
+    }
+
+    #[test]
+    fn test_disabled_families() {
+        assert!(set_disabled_families(&["nonexistent".to_string()]).is_err());
+
+        set_disabled_families(&["cache".to_string()]).unwrap();
+        assert!(!is_enabled("sentinel_cache_hits_total"));
+        assert!(is_enabled("sentinel_storage_writes_total"));
+        // Metrics outside the catalog are never disabled
+        assert!(is_enabled("custom_total"));
+
+        set_disabled_families(&[]).unwrap();
+        assert!(is_enabled("sentinel_cache_hits_total"));
+    }
+
+    /// Metric name and label keys of each `counter!`, `gauge!` and
+    /// `histogram!` invocation with a literal name
+    fn macro_calls(source: &str) -> Vec<(String, BTreeSet<String>)> {
+        let mut calls = Vec::new();
+        let starts = ["counter!(", "gauge!(", "histogram!("]
+            .iter()
+            .flat_map(|m| source.match_indices(m))
+            // Skip e.g. `describe_counter!(`
+            .filter(|(i, _)| {
+                !source[..*i]
+                    .chars()
+                    .next_back()
+                    .is_some_and(|c| c.is_alphanumeric() || c == '_')
+            })
+            .map(|(i, m)| i + m.len());
+        for start in starts {
+            let mut depth = 1;
+            let mut end = start;
+            for (i, c) in source[start..].char_indices() {
+                match c {
+                    '(' => depth += 1,
+                    ')' => depth -= 1,
+                    _ => {}
+                }
+                if depth == 0 {
+                    end = start + i;
+                    break;
+                }
+            }
+            let args = source[start..end].trim_start();
+            let Some(rest) = args.strip_prefix('"') else {
+                continue;
+            };
+            let Some(name) = rest.split('"').next() else {
+                continue;
+            };
+            let labels = args
+                .split("=>")
+                .collect::<Vec<_>>()
+                .split_last()
+                .map(|(_, keys)| {
+                    keys.iter()
+                        .filter_map(|piece| {
+                            let piece = piece.trim_end().strip_suffix('"')?;
+                            Some(piece[piece.rfind('"')? + 1..].to_string())
+                        })
+                        .collect()
+                })
+                .unwrap_or_default();
+            calls.push((name.to_string(), labels));
+        }
+        calls
+    }
+
+    fn rust_files(dir: &std::path::Path, files: &mut Vec<std::path::PathBuf>) {
+        let Ok(entries) = std::fs::read_dir(dir) else {
+            return;
+        };
+        for path in entries.flatten().map(|entry| entry.path()) {
+            if path.is_dir() && !path.ends_with("target") {
+                rust_files(&path, files);
+            } else if path.extension().is_some_and(|ext| ext == "rs") {
+                files.push(path);
+            }
+        }
+    }
+
+    #[test]
+    fn test_catalog_matches_source() {
+        let root = std::path::Path::new(env!("CARGO_MANIFEST_DIR")).join("../..");
+        let mut files = Vec::new();
+        rust_files(&root.join("crates"), &mut files);
+        rust_files(&root.join("sentinel/src"), &mut files);
+
+        let mut used = BTreeSet::new();
+        for file in &files {
+            let source = std::fs::read_to_string(file).unwrap();
+            for (name, labels) in macro_calls(&source) {
+                if !name.starts_with("sentinel_") {
+                    continue;
+                }
+                let def = lookup(&name).unwrap_or_else(|| {
+                    panic!("{} in {} is not in the catalog", name, file.display())
+                });
+                assert!(
+                    def.has_labels(labels.iter().map(String::as_str)),
+                    "{} in {} has labels {:?}, declared {:?}",
+                    name,
+                    file.display(),
+                    labels,
+                    def.labels
+                );
+                used.insert(name);
+            }
+        }
+
+        // Skip the reverse check outside the workspace
+        if !files.is_empty() {
+            for def in CATALOG {
+                assert!(used.contains(def.name), "{} is never recorded", def.name);
+            }
+        }
+    }
 }
@@ -0,0 +1,270 @@
+//! Per-model token pricing.
+//!
+//! A [`PriceTable`] maps model names to input/output token prices and fills
+//! in `cost_usd` for telemetry that arrives without one. Model names match
+//! exactly or by longest prefix, so `gpt-4o-mini-2024-07-18` uses the
+//! `gpt-4o-mini` price rather than `gpt-4o` or `gpt-4`. The table can be
+//! updated at runtime (e.g. from the API) without a redeploy.
+
+use crate::{
+    config::PricingConfig,
+    error::{Error, Result},
+    events::TelemetryEvent,
+    types::ModelId,
+};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::sync::RwLock;
+
+/// Built-in prices (USD per 1K input tokens, USD per 1K output tokens)
+const DEFAULT_PRICES: &[(&str, f64, f64)] = &[
+    ("gpt-4", 0.03, 0.06),
+    ("gpt-4-32k", 0.06, 0.12),
+    ("gpt-4-turbo", 0.01, 0.03),
+    ("gpt-4o", 0.0025, 0.01),
+    ("gpt-4o-mini", 0.00015, 0.0006),
+    ("gpt-3.5-turbo", 0.0005, 0.0015),
+    ("o1", 0.015, 0.06),
+    ("o1-mini", 0.003, 0.012),
+    ("claude-3-opus", 0.015, 0.075),
+    ("claude-3-sonnet", 0.003, 0.015),
+    ("claude-3-5-sonnet", 0.003, 0.015),
+    ("claude-3-haiku", 0.00025, 0.00125),
+    ("claude-3-5-haiku", 0.0008, 0.004),
+    ("gemini-1.5-pro", 0.00125, 0.005),
+    ("gemini-1.5-flash", 0.000075, 0.0003),
+    ("mistral-large", 0.002, 0.006),
+    ("mistral-small", 0.0002, 0.0006),
+];
+
+/// Token prices for one model
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct ModelPrice {
+    /// USD per 1K prompt tokens
+    pub input_per_1k: f64,
+    /// USD per 1K response tokens
+    pub output_per_1k: f64,
+}
+
+impl ModelPrice {
+    /// Create a new model price
+    pub fn new(input_per_1k: f64, output_per_1k: f64) -> Self {
+        Self {
+            input_per_1k,
+            output_per_1k,
+        }
+    }
+
+    /// Validate that both prices are finite and non-negative
+    pub fn validate(&self) -> Result<()> {
+        for (name, value) in [
+            ("input_per_1k", self.input_per_1k),
+            ("output_per_1k", self.output_per_1k),
+        ] {
+            if !value.is_finite() || value < 0.0 {
+                return Err(Error::validation(format!(
+                    "{} must be a non-negative number, got {}",
+                    name, value
+                )));
+            }
+        }
+        Ok(())
+    }
+
+    /// Cost in USD for the given token counts
+    pub fn cost(&self, prompt_tokens: u32, response_tokens: u32) -> f64 {
+        (prompt_tokens as f64 * self.input_per_1k + response_tokens as f64 * self.output_per_1k)
+            / 1000.0
+    }
+}
+
+/// Runtime-updatable model price table
+#[derive(Debug, Default)]
+pub struct PriceTable {
+    /// Prices keyed by lowercase model name (or prefix)
+    prices: RwLock<BTreeMap<String, ModelPrice>>,
+}
+
+impl PriceTable {
+    /// Create an empty price table
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Create a price table with the built-in defaults for common models
+    pub fn with_defaults() -> Self {
+        let table = Self::new();
+        {
+            let mut prices = table.write();
+            for (model, input, output) in DEFAULT_PRICES {
+                prices.insert(model.to_string(), ModelPrice::new(*input, *output));
+            }
+        }
+        table
+    }
+
+    /// Build a price table from configuration
+    ///
+    /// Configured prices override the built-in defaults.
+    pub fn from_config(config: &PricingConfig) -> Result<Self> {
+        let table = if config.include_defaults {
+            Self::with_defaults()
+        } else {
+            Self::new()
+        };
+        for (model, price) in &config.models {
+            table.set(model, *price)?;
+        }
+        Ok(table)
+    }
+
+    /// Set the price for a model (or model prefix)
+    pub fn set(&self, model: &str, price: ModelPrice) -> Result<()> {
+        let model = model.trim().to_lowercase();
+        if model.is_empty() {
+            return Err(Error::validation("Model name cannot be empty"));
+        }
+        price.validate()?;
+        self.write().insert(model, price);
+        Ok(())
+    }
+
+    /// Remove the price for a model, returning it if present
+    pub fn remove(&self, model: &str) -> Option<ModelPrice> {
+        self.write().remove(&model.trim().to_lowercase())
+    }
+
+    /// Snapshot of all configured prices
+    pub fn prices(&self) -> BTreeMap<String, ModelPrice> {
+        self.read().clone()
+    }
+
+    /// Price for a model: exact match, otherwise the longest matching prefix
+    pub fn price_for(&self, model: &ModelId) -> Option<ModelPrice> {
+        let model = model.as_str().to_lowercase();
+        let prices = self.read();
+
+        if let Some(price) = prices.get(&model) {
+            return Some(*price);
+        }
+
+        prices
+            .iter()
+            .filter(|(name, _)| model.starts_with(name.as_str()))
+            .max_by_key(|(name, _)| name.len())
+            .map(|(_, price)| *price)
+    }
+
+    /// Cost in USD for the given model and token counts
+    pub fn cost(&self, model: &ModelId, prompt_tokens: u32, response_tokens: u32) -> Option<f64> {
+        self.price_for(model)
+            .map(|price| price.cost(prompt_tokens, response_tokens))
+    }
+
+    /// Fill in `cost_usd` when the event doesn't carry one
+    ///
+    /// Returns `true` if the cost was computed.
+    pub fn apply(&self, event: &mut TelemetryEvent) -> bool {
+        if event.cost_usd > 0.0 {
+            return false;
+        }
+
+        match self.cost(&event.model, event.prompt.tokens, event.response.tokens) {
+            Some(cost) => {
+                event.cost_usd = cost;
+                metrics::counter!("sentinel_cost_computed_total").increment(1);
+                true
+            }
+            None => false,
+        }
+    }
+
+    fn read(&self) -> std::sync::RwLockReadGuard<'_, BTreeMap<String, ModelPrice>> {
+        self.prices.read().unwrap_or_else(|e| e.into_inner())
+    }
+
+    fn write(&self) -> std::sync::RwLockWriteGuard<'_, BTreeMap<String, ModelPrice>> {
+        self.prices.write().unwrap_or_else(|e| e.into_inner())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        events::{PromptInfo, ResponseInfo},
+        types::ServiceId,
+    };
+
+    fn create_test_event(model: &str, cost_usd: f64) -> TelemetryEvent {
+        TelemetryEvent::new(
+            ServiceId::new("test"),
+            ModelId::new(model),
+            PromptInfo {
+                text: "Hello".to_string(),
+                tokens: 1000,
+                embedding: None,
+            },
+            ResponseInfo {
+                text: "Hi".to_string(),
+                tokens: 500,
+                finish_reason: "stop".to_string(),
+                embedding: None,
+            },
+            100.0,
+            cost_usd,
+        )
+    }
+
+    #[test]
+    fn test_longest_prefix_match() {
+        let table = PriceTable::with_defaults();
+
+        let mini = table
+            .price_for(&ModelId::new("gpt-4o-mini-2024-07-18"))
+            .unwrap();
+        assert_eq!(mini, ModelPrice::new(0.00015, 0.0006));
+
+        let gpt4 = table.price_for(&ModelId::new("GPT-4-0613")).unwrap();
+        assert_eq!(gpt4, ModelPrice::new(0.03, 0.06));
+
+        assert!(table.price_for(&ModelId::new("llama-3-70b")).is_none());
+    }
+
+    #[test]
+    fn test_apply_fills_missing_cost() {
+        let table = PriceTable::with_defaults();
+
+        let mut event = create_test_event("gpt-4", 0.0);
+        assert!(table.apply(&mut event));
+        assert!((event.cost_usd - 0.06).abs() < 1e-12);
+
+        // Reported costs are kept
+        let mut event = create_test_event("gpt-4", 0.5);
+        assert!(!table.apply(&mut event));
+        assert_eq!(event.cost_usd, 0.5);
+
+        // Unknown models stay at zero
+        let mut event = create_test_event("custom-model", 0.0);
+        assert!(!table.apply(&mut event));
+        assert_eq!(event.cost_usd, 0.0);
+    }
+
+    #[test]
+    fn test_runtime_updates() {
+        let table = PriceTable::new();
+        assert!(table
+            .set("custom-model", ModelPrice::new(0.001, 0.002))
+            .is_ok());
+        assert!(table.set("bad", ModelPrice::new(-1.0, 0.0)).is_err());
+        assert!(table.set(" ", ModelPrice::new(0.0, 0.0)).is_err());
+
+        let cost = table
+            .cost(&ModelId::new("Custom-Model"), 1000, 1000)
+            .unwrap();
+        assert!((cost - 0.003).abs() < 1e-12);
+
+        assert!(table.remove("custom-model").is_some());
+        assert!(table.prices().is_empty());
+    }
+}
@@ -0,0 +1,208 @@
+//! Aggregation-only data handling for sensitive services.
+//!
+//! Some data-handling agreements rule out keeping raw events. For the
+//! services and tenants covered by [`AggregationOnly`], the pipeline keeps
+//! only the aggregated statistics detection needs: detector baselines, SLO
+//! windows and cost rollups are still updated, but the event itself is never
+//! written to storage, indexed for search or similarity, or published on the
+//! event bus, so it is not exported or fanned out either. Anomalies detected
+//! from covered events are [`scrub`]bed of per-event identifiers and text
+//! before they are stored or sent, leaving aggregated evidence only.
+//!
+//! Tenants are selected by label, e.g. `tenant: acme`.
+
+use crate::{
+    config::AggregationOnlyConfig,
+    events::{AnomalyEvent, TelemetryEvent},
+    labels::{self, Labels},
+    types::ServiceId,
+    Error, Result,
+};
+use std::collections::HashSet;
+
+/// Services and tenants whose raw events are never kept
+#[derive(Debug, Clone)]
+pub struct AggregationOnly {
+    services: HashSet<ServiceId>,
+    selectors: Vec<Labels>,
+}
+
+impl AggregationOnly {
+    /// Build the policy from configuration
+    pub fn from_config(config: &AggregationOnlyConfig) -> Result<Self> {
+        if config.services.is_empty() && config.selectors.is_empty() {
+            return Err(Error::config(
+                "Aggregation-only mode needs at least one service or selector",
+            ));
+        }
+        // An empty selector would cover every event
+        if config.selectors.iter().any(Labels::is_empty) {
+            return Err(Error::config(
+                "Aggregation-only selectors must not be empty",
+            ));
+        }
+        for selector in &config.selectors {
+            labels::validate_labels(selector)?;
+        }
+
+        Ok(Self {
+            services: config.services.iter().map(ServiceId::new).collect(),
+            selectors: config.selectors.clone(),
+        })
+    }
+
+    /// Whether a service's events with the given labels are covered
+    pub fn covers(&self, service: &ServiceId, labels: &Labels) -> bool {
+        self.services.contains(service)
+            || self
+                .selectors
+                .iter()
+                .any(|selector| labels::matches(labels, selector))
+    }
+
+    /// Whether an event is covered
+    pub fn covers_event(&self, event: &TelemetryEvent) -> bool {
+        self.covers(&event.service_name, &event.labels)
+    }
+
+    /// Whether an anomaly is covered
+    pub fn covers_anomaly(&self, anomaly: &AnomalyEvent) -> bool {
+        self.covers(&anomaly.service_name, &anomaly.labels)
+    }
+}
+
+/// Whether an additional map entry holds prompt or response text
+pub fn is_text_key(key: &str) -> bool {
+    key.starts_with("prompt") || key.starts_with("response")
+}
+
+/// Drop what an anomaly carries from the individual event it was detected
+/// from: trace and user IDs, trace links and prompt/response text
+pub fn scrub(anomaly: &mut AnomalyEvent) {
+    anomaly.context.trace_id = None;
+    anomaly.context.user_id = None;
+    anomaly.trace_links.clear();
+    anomaly
+        .details
+        .additional
+        .retain(|key, value| !(is_text_key(key) && value.is_string()));
+    anomaly
+        .context
+        .additional
+        .retain(|key, _| !is_text_key(key));
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        events::{AnomalyContext, AnomalyDetails, PromptInfo, ResponseInfo},
+        types::{AnomalyType, DetectionMethod, ModelId, Severity},
+    };
+    use std::collections::HashMap;
+
+    fn config() -> AggregationOnlyConfig {
+        AggregationOnlyConfig {
+            services: vec!["payments".to_string()],
+            selectors: vec![Labels::from([("tenant".to_string(), "acme".to_string())])],
+        }
+    }
+
+    fn event(service: &str) -> TelemetryEvent {
+        TelemetryEvent::new(
+            ServiceId::new(service),
+            ModelId::new("gpt-4"),
+            PromptInfo {
+                text: "Hello".to_string(),
+                tokens: 10,
+                embedding: None,
+            },
+            ResponseInfo {
+                text: "Hi".to_string(),
+                tokens: 5,
+                finish_reason: "stop".to_string(),
+                embedding: None,
+            },
+            100.0,
+            0.001,
+        )
+    }
+
+    #[test]
+    fn test_covers() {
+        let policy = AggregationOnly::from_config(&config()).unwrap();
+        assert!(policy.covers_event(&event("payments")));
+        assert!(!policy.covers_event(&event("search")));
+        assert!(policy.covers_event(&event("search").with_label("tenant", "acme")));
+        assert!(!policy.covers_event(&event("search").with_label("tenant", "globex")));
+    }
+
+    #[test]
+    fn test_invalid_config() {
+        let empty = AggregationOnlyConfig {
+            services: Vec::new(),
+            selectors: Vec::new(),
+        };
+        assert!(AggregationOnly::from_config(&empty).is_err());
+        let match_all = AggregationOnlyConfig {
+            selectors: vec![Labels::new()],
+            ..config()
+        };
+        assert!(AggregationOnly::from_config(&match_all).is_err());
+    }
+
+    #[test]
+    fn test_scrub() {
+        let mut details = HashMap::new();
+        details.insert("prompt_text".to_string(), serde_json::json!("card 4111"));
+        details.insert("prompt_growth".to_string(), serde_json::json!(1.5));
+        let mut context = HashMap::new();
+        context.insert("response_text".to_string(), "ok".to_string());
+        context.insert("p95_latency_ms".to_string(), "250".to_string());
+        let mut anomaly = AnomalyEvent::new(
+            Severity::High,
+            AnomalyType::LatencySpike,
+            ServiceId::new("payments"),
+            ModelId::new("gpt-4"),
+            DetectionMethod::ZScore,
+            0.9,
+            AnomalyDetails {
+                metric: "latency_ms".to_string(),
+                value: 900.0,
+                baseline: 100.0,
+                threshold: 3.0,
+                deviation_sigma: Some(8.0),
+                additional: details,
+                observations: Vec::new(),
+            },
+            AnomalyContext {
+                trace_id: Some("trace-1".to_string()),
+                user_id: Some("user-1".to_string()),
+                region: Some("eu-west-1".to_string()),
+                time_window: "last_100_samples".to_string(),
+                sample_count: 100,
+                window: None,
+                additional: context,
+            },
+        );
+        anomaly
+            .trace_links
+            .insert("jaeger".to_string(), "https://jaeger/trace-1".to_string());
+
+        scrub(&mut anomaly);
+        assert!(anomaly.context.trace_id.is_none());
+        assert!(anomaly.context.user_id.is_none());
+        assert!(anomaly.trace_links.is_empty());
+        assert_eq!(
+            anomaly.details.additional.keys().collect::<Vec<_>>(),
+            ["prompt_growth"]
+        );
+        assert_eq!(
+            anomaly.context.additional.keys().collect::<Vec<_>>(),
+            ["p95_latency_ms"]
+        );
+        // Aggregated evidence stays
+        assert_eq!(anomaly.details.value, 900.0);
+        assert_eq!(anomaly.context.region.as_deref(), Some("eu-west-1"));
+    }
+}
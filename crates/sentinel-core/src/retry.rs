@@ -0,0 +1,317 @@
+//! Retry with exponential backoff.
+//!
+//! [`RetryPolicy`] describes how often and how long to retry; [`retry`]
+//! runs an async operation under a policy. Delays grow exponentially up to
+//! a cap and use full jitter (a random delay between zero and the current
+//! backoff) so that many clients retrying at once spread out. An optional
+//! elapsed-time budget bounds the total time spent, and operations can ask
+//! for a specific delay, e.g. from an HTTP `Retry-After` header.
+
+use chrono::{DateTime, Utc};
+use rand::Rng;
+use serde::{Deserialize, Serialize};
+use std::{
+    future::Future,
+    time::{Duration, Instant},
+};
+use tracing::debug;
+
+/// Retry policy
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct RetryPolicy {
+    /// Maximum attempts, including the first
+    pub max_attempts: u32,
+    /// Backoff before the first retry (milliseconds)
+    pub initial_delay_ms: u64,
+    /// Backoff cap (milliseconds)
+    pub max_delay_ms: u64,
+    /// Backoff multiplier per retry
+    pub multiplier: f64,
+    /// Randomize each delay between zero and the backoff (full jitter)
+    pub jitter: bool,
+    /// Total time budget across attempts and delays (milliseconds)
+    pub max_elapsed_ms: Option<u64>,
+}
+
+impl Default for RetryPolicy {
+    fn default() -> Self {
+        Self {
+            max_attempts: 3,
+            initial_delay_ms: 1000,
+            max_delay_ms: 30_000,
+            multiplier: 2.0,
+            jitter: true,
+            max_elapsed_ms: None,
+        }
+    }
+}
+
+impl RetryPolicy {
+    /// Create a policy with the given maximum attempts and default backoff
+    pub fn new(max_attempts: u32) -> Self {
+        Self {
+            max_attempts,
+            ..Default::default()
+        }
+    }
+
+    /// Policy making a single attempt
+    pub fn no_retry() -> Self {
+        Self::new(1)
+    }
+
+    /// Set the backoff before the first retry
+    pub fn with_initial_delay(mut self, delay: Duration) -> Self {
+        self.initial_delay_ms = delay.as_millis() as u64;
+        self
+    }
+
+    /// Set the backoff cap
+    pub fn with_max_delay(mut self, delay: Duration) -> Self {
+        self.max_delay_ms = delay.as_millis() as u64;
+        self
+    }
+
+    /// Set the backoff multiplier
+    pub fn with_multiplier(mut self, multiplier: f64) -> Self {
+        self.multiplier = multiplier;
+        self
+    }
+
+    /// Enable or disable full jitter
+    pub fn with_jitter(mut self, jitter: bool) -> Self {
+        self.jitter = jitter;
+        self
+    }
+
+    /// Set the total time budget
+    pub fn with_max_elapsed(mut self, budget: Duration) -> Self {
+        self.max_elapsed_ms = Some(budget.as_millis() as u64);
+        self
+    }
+
+    /// Backoff cap for the given retry (1 for the first retry)
+    pub fn backoff(&self, retry: u32) -> Duration {
+        let exponent = retry.saturating_sub(1).min(i32::MAX as u32) as i32;
+        let delay_ms = self.initial_delay_ms as f64 * self.multiplier.max(1.0).powi(exponent);
+        Duration::from_millis(delay_ms.min(self.max_delay_ms as f64) as u64)
+    }
+
+    /// Delay before the given retry, with jitter applied
+    pub fn delay(&self, retry: u32) -> Duration {
+        let backoff = self.backoff(retry);
+        if self.jitter && !backoff.is_zero() {
+            Duration::from_millis(rand::thread_rng().gen_range(0..=backoff.as_millis() as u64))
+        } else {
+            backoff
+        }
+    }
+}
+
+/// Error returned by a retried operation
+#[derive(Debug)]
+pub enum RetryError<E> {
+    /// Retry according to the policy
+    Transient(E),
+    /// Retry after the given delay (e.g. from `Retry-After`)
+    RetryAfter(E, Duration),
+    /// Do not retry
+    Permanent(E),
+}
+
+impl<E> RetryError<E> {
+    /// The underlying error
+    pub fn into_inner(self) -> E {
+        match self {
+            Self::Transient(e) | Self::RetryAfter(e, _) | Self::Permanent(e) => e,
+        }
+    }
+}
+
+/// Final error of a retried operation
+#[derive(Debug)]
+pub struct RetryFailure<E> {
+    /// Error from the last attempt
+    pub error: E,
+    /// Attempts made
+    pub attempts: u32,
+    /// Whether the attempts or time budget ran out (as opposed to a
+    /// permanent error)
+    pub exhausted: bool,
+}
+
+/// Run `operation` until it succeeds, fails permanently, or the policy's
+/// attempts or time budget run out
+///
+/// The operation receives the attempt number, starting at 1.
+pub async fn retry<T, E, F, Fut>(
+    policy: &RetryPolicy,
+    mut operation: F,
+) -> std::result::Result<T, RetryFailure<E>>
+where
+    F: FnMut(u32) -> Fut,
+    Fut: Future<Output = std::result::Result<T, RetryError<E>>>,
+{
+    let started = Instant::now();
+    let budget = policy.max_elapsed_ms.map(Duration::from_millis);
+    let mut attempt = 0;
+
+    loop {
+        attempt += 1;
+        let (error, requested) = match operation(attempt).await {
+            Ok(value) => return Ok(value),
+            Err(RetryError::Permanent(error)) => {
+                return Err(RetryFailure {
+                    error,
+                    attempts: attempt,
+                    exhausted: false,
+                })
+            }
+            Err(RetryError::Transient(error)) => (error, None),
+            Err(RetryError::RetryAfter(error, delay)) => (error, Some(delay)),
+        };
+
+        let delay = requested.unwrap_or_else(|| policy.delay(attempt));
+        let over_budget = budget.is_some_and(|budget| started.elapsed() + delay > budget);
+        if attempt >= policy.max_attempts || over_budget {
+            return Err(RetryFailure {
+                error,
+                attempts: attempt,
+                exhausted: true,
+            });
+        }
+
+        debug!(
+            attempt = attempt,
+            delay_ms = delay.as_millis() as u64,
+            "Retrying"
+        );
+        tokio::time::sleep(delay).await;
+    }
+}
+
+/// Parse an HTTP `Retry-After` value (delay in seconds or an HTTP date)
+pub fn parse_retry_after(value: &str) -> Option<Duration> {
+    let value = value.trim();
+    if let Ok(secs) = value.parse::<u64>() {
+        return Some(Duration::from_secs(secs));
+    }
+
+    let at = DateTime::parse_from_rfc2822(value).ok()?;
+    Some(
+        (at.with_timezone(&Utc) - Utc::now())
+            .to_std()
+            .unwrap_or(Duration::ZERO),
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::atomic::{AtomicU32, Ordering};
+
+    fn fast_policy(max_attempts: u32) -> RetryPolicy {
+        RetryPolicy::new(max_attempts)
+            .with_initial_delay(Duration::from_millis(1))
+            .with_max_delay(Duration::from_millis(5))
+    }
+
+    #[test]
+    fn test_backoff() {
+        let policy = RetryPolicy::new(10)
+            .with_initial_delay(Duration::from_millis(100))
+            .with_max_delay(Duration::from_millis(1000))
+            .with_jitter(false);
+
+        assert_eq!(policy.backoff(1), Duration::from_millis(100));
+        assert_eq!(policy.backoff(2), Duration::from_millis(200));
+        assert_eq!(policy.backoff(4), Duration::from_millis(800));
+        assert_eq!(policy.backoff(5), Duration::from_millis(1000));
+        assert_eq!(policy.backoff(u32::MAX), Duration::from_millis(1000));
+        assert_eq!(policy.delay(3), Duration::from_millis(400));
+
+        let jittered = policy.with_jitter(true);
+        for retry in 1..10 {
+            assert!(jittered.delay(retry) <= jittered.backoff(retry));
+        }
+    }
+
+    #[tokio::test]
+    async fn test_retry_until_success() {
+        let calls = AtomicU32::new(0);
+        let result = retry(&fast_policy(5), |attempt| {
+            calls.fetch_add(1, Ordering::SeqCst);
+            async move {
+                if attempt < 3 {
+                    Err(RetryError::Transient("unavailable"))
+                } else {
+                    Ok(attempt)
+                }
+            }
+        })
+        .await;
+
+        assert_eq!(result.unwrap(), 3);
+        assert_eq!(calls.load(Ordering::SeqCst), 3);
+    }
+
+    #[tokio::test]
+    async fn test_retry_exhausted_and_permanent() {
+        let failure = retry(&fast_policy(3), |_| async {
+            Err::<(), _>(RetryError::Transient("unavailable"))
+        })
+        .await
+        .unwrap_err();
+        assert_eq!(failure.attempts, 3);
+        assert!(failure.exhausted);
+
+        let failure = retry(&fast_policy(3), |_| async {
+            Err::<(), _>(RetryError::Permanent("bad request"))
+        })
+        .await
+        .unwrap_err();
+        assert_eq!(failure.attempts, 1);
+        assert!(!failure.exhausted);
+        assert_eq!(failure.error, "bad request");
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn test_retry_after_and_budget() {
+        let policy = fast_policy(10).with_max_elapsed(Duration::from_secs(5));
+
+        // Retry-After delays are honored while they fit in the budget
+        let started = tokio::time::Instant::now();
+        let result = retry(&policy, |attempt| async move {
+            if attempt == 1 {
+                Err(RetryError::RetryAfter("throttled", Duration::from_secs(2)))
+            } else {
+                Ok(())
+            }
+        })
+        .await;
+        assert!(result.is_ok());
+        assert!(started.elapsed() >= Duration::from_secs(2));
+
+        // A delay past the budget stops retrying
+        let failure = retry(&policy, |_| async {
+            Err::<(), _>(RetryError::RetryAfter("throttled", Duration::from_secs(60)))
+        })
+        .await
+        .unwrap_err();
+        assert_eq!(failure.attempts, 1);
+        assert!(failure.exhausted);
+    }
+
+    #[test]
+    fn test_parse_retry_after() {
+        assert_eq!(parse_retry_after("120"), Some(Duration::from_secs(120)));
+        assert_eq!(
+            parse_retry_after("Wed, 21 Oct 2015 07:28:00 GMT"),
+            Some(Duration::ZERO)
+        );
+        let future = (Utc::now() + chrono::Duration::seconds(30)).to_rfc2822();
+        let delay = parse_retry_after(&future).unwrap();
+        assert!(delay > Duration::from_secs(25) && delay <= Duration::from_secs(30));
+        assert_eq!(parse_retry_after("soon"), None);
+    }
+}
@@ -0,0 +1,294 @@
+//! Startup self-checks against configured dependencies.
+//!
+//! Each dependency (a broker, a database, a cache) is checked by a
+//! [`SelfCheck`] built from configuration alone, without starting the
+//! component that uses it. A check reports separate results for
+//! connectivity, permissions and schema, so an operator can tell a
+//! firewall rule from a missing ACL or bucket. [`run_checks`] runs the
+//! checks concurrently and collects them into a [`SelfCheckReport`].
+
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::{fmt, sync::Arc, time::Duration};
+use tokio::{task::JoinSet, time::Instant};
+
+/// Outcome of one check
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum CheckStatus {
+    /// The dependency behaves as configured
+    Pass,
+    /// The dependency is unreachable or misconfigured
+    Fail,
+    /// Not checked, because an earlier check of the dependency failed
+    Skip,
+}
+
+/// What a check verifies
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum CheckKind {
+    /// The dependency answers with the configured credentials
+    Connectivity,
+    /// The credentials may perform the operations Sentinel needs
+    Permission,
+    /// Topics, buckets and exchanges exist as configured
+    Schema,
+}
+
+impl fmt::Display for CheckKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.pad(match self {
+            Self::Connectivity => "connectivity",
+            Self::Permission => "permission",
+            Self::Schema => "schema",
+        })
+    }
+}
+
+/// Result of one check against one dependency
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct CheckResult {
+    /// Dependency checked, e.g. `kafka` or `influxdb`
+    pub dependency: String,
+    /// What was checked
+    pub check: CheckKind,
+    /// Outcome
+    pub status: CheckStatus,
+    /// What was found, or why the check failed
+    pub detail: String,
+    /// Time the check took, in milliseconds
+    pub duration_ms: u64,
+}
+
+impl CheckResult {
+    /// Passing check
+    pub fn pass(
+        dependency: impl Into<String>,
+        check: CheckKind,
+        detail: impl Into<String>,
+    ) -> Self {
+        Self::new(dependency, check, CheckStatus::Pass, detail)
+    }
+
+    /// Failing check
+    pub fn fail(
+        dependency: impl Into<String>,
+        check: CheckKind,
+        detail: impl Into<String>,
+    ) -> Self {
+        Self::new(dependency, check, CheckStatus::Fail, detail)
+    }
+
+    /// Check not run because an earlier one failed
+    pub fn skip(dependency: impl Into<String>, check: CheckKind) -> Self {
+        Self::new(dependency, check, CheckStatus::Skip, "skipped")
+    }
+
+    fn new(
+        dependency: impl Into<String>,
+        check: CheckKind,
+        status: CheckStatus,
+        detail: impl Into<String>,
+    ) -> Self {
+        Self {
+            dependency: dependency.into(),
+            check,
+            status,
+            detail: detail.into(),
+            duration_ms: 0,
+        }
+    }
+
+    /// Record how long the check took since `started`
+    pub fn timed(mut self, started: Instant) -> Self {
+        self.duration_ms = started.elapsed().as_millis() as u64;
+        self
+    }
+}
+
+/// Checks one configured dependency
+#[async_trait]
+pub trait SelfCheck: Send + Sync {
+    /// Dependency name used in results
+    fn dependency(&self) -> String;
+
+    /// Run the checks, stopping at the first failure and skipping the rest
+    async fn run(&self) -> Vec<CheckResult>;
+}
+
+/// Results of checking every configured dependency
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SelfCheckReport {
+    /// Whether no check failed
+    pub passed: bool,
+    /// When the checks ran
+    pub checked_at: DateTime<Utc>,
+    /// Results, grouped by dependency in the order checked
+    pub results: Vec<CheckResult>,
+}
+
+impl SelfCheckReport {
+    /// Build a report from results
+    pub fn new(results: Vec<CheckResult>) -> Self {
+        Self {
+            passed: results.iter().all(|r| r.status != CheckStatus::Fail),
+            checked_at: Utc::now(),
+            results,
+        }
+    }
+
+    /// Failed checks
+    pub fn failures(&self) -> impl Iterator<Item = &CheckResult> {
+        self.results
+            .iter()
+            .filter(|r| r.status == CheckStatus::Fail)
+    }
+
+    /// Plain-text table for terminals
+    pub fn render(&self) -> String {
+        let mut out = String::new();
+        for result in &self.results {
+            let status = match result.status {
+                CheckStatus::Pass => "PASS",
+                CheckStatus::Fail => "FAIL",
+                CheckStatus::Skip => "SKIP",
+            };
+            out.push_str(&format!(
+                "{:<4}  {:<16} {:<12} {} ({} ms)\n",
+                status, result.dependency, result.check, result.detail, result.duration_ms
+            ));
+        }
+        if self.results.is_empty() {
+            out.push_str("No dependencies configured\n");
+        }
+        let failed = self.failures().count();
+        if failed == 0 {
+            out.push_str("All checks passed\n");
+        } else {
+            out.push_str(&format!("{} check(s) failed\n", failed));
+        }
+        out
+    }
+}
+
+/// Run `checks` concurrently, failing any that outlasts `timeout` as a
+/// connectivity failure
+pub async fn run_checks(checks: Vec<Arc<dyn SelfCheck>>, timeout: Duration) -> SelfCheckReport {
+    let mut tasks = JoinSet::new();
+    for (index, check) in checks.iter().cloned().enumerate() {
+        tasks.spawn(async move {
+            let started = Instant::now();
+            let results = match tokio::time::timeout(timeout, check.run()).await {
+                Ok(results) => results,
+                Err(_) => vec![CheckResult::fail(
+                    check.dependency(),
+                    CheckKind::Connectivity,
+                    format!("no answer within {} s", timeout.as_secs()),
+                )
+                .timed(started)],
+            };
+            (index, results)
+        });
+    }
+
+    let mut results: Vec<Option<Vec<CheckResult>>> = vec![None; checks.len()];
+    while let Some(joined) = tasks.join_next().await {
+        if let Ok((index, check_results)) = joined {
+            results[index] = Some(check_results);
+        }
+    }
+
+    let results = results
+        .into_iter()
+        .zip(&checks)
+        .flat_map(|(check_results, check)| {
+            check_results.unwrap_or_else(|| {
+                vec![CheckResult::fail(
+                    check.dependency(),
+                    CheckKind::Connectivity,
+                    "check panicked",
+                )]
+            })
+        })
+        .collect();
+    SelfCheckReport::new(results)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    struct FixedCheck {
+        name: &'static str,
+        results: Vec<(CheckKind, CheckStatus)>,
+        delay: Duration,
+    }
+
+    #[async_trait]
+    impl SelfCheck for FixedCheck {
+        fn dependency(&self) -> String {
+            self.name.to_string()
+        }
+
+        async fn run(&self) -> Vec<CheckResult> {
+            tokio::time::sleep(self.delay).await;
+            self.results
+                .iter()
+                .map(|(check, status)| CheckResult::new(self.name, *check, *status, "checked"))
+                .collect()
+        }
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn test_run_checks() {
+        let checks: Vec<Arc<dyn SelfCheck>> = vec![
+            Arc::new(FixedCheck {
+                name: "influxdb",
+                results: vec![
+                    (CheckKind::Connectivity, CheckStatus::Pass),
+                    (CheckKind::Permission, CheckStatus::Pass),
+                    (CheckKind::Schema, CheckStatus::Fail),
+                ],
+                delay: Duration::from_secs(2),
+            }),
+            Arc::new(FixedCheck {
+                name: "kafka",
+                results: vec![(CheckKind::Connectivity, CheckStatus::Pass)],
+                delay: Duration::ZERO,
+            }),
+            Arc::new(FixedCheck {
+                name: "rabbitmq",
+                results: vec![(CheckKind::Connectivity, CheckStatus::Pass)],
+                delay: Duration::from_secs(60),
+            }),
+        ];
+
+        let report = run_checks(checks, Duration::from_secs(10)).await;
+        assert!(!report.passed);
+
+        // Results keep the order of the checks, not of completion
+        let dependencies: Vec<&str> = report
+            .results
+            .iter()
+            .map(|r| r.dependency.as_str())
+            .collect();
+        assert_eq!(
+            dependencies,
+            ["influxdb", "influxdb", "influxdb", "kafka", "rabbitmq"]
+        );
+
+        // A check that hangs counts as unreachable
+        let rabbitmq = &report.results[4];
+        assert_eq!(rabbitmq.check, CheckKind::Connectivity);
+        assert_eq!(rabbitmq.status, CheckStatus::Fail);
+
+        let failed: Vec<CheckKind> = report.failures().map(|r| r.check).collect();
+        assert_eq!(failed, [CheckKind::Schema, CheckKind::Connectivity]);
+        assert!(report.render().ends_with("2 check(s) failed\n"));
+
+        let empty = run_checks(Vec::new(), Duration::from_secs(10)).await;
+        assert!(empty.passed);
+    }
+}
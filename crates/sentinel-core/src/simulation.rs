@@ -0,0 +1,108 @@
+//! Synthetic anomalies for testing the alert pipeline.
+//!
+//! Operators verify routing rules and alerter integrations by posting a
+//! [`TestAnomalyRequest`] to [`SIMULATION_PATH`]. The synthesized anomaly
+//! carries the [`TEST_LABEL`] label, so receivers and label filters can tell
+//! it apart from real detections, and goes through deduplication, routing
+//! and alerting like a detected one.
+
+use crate::{
+    events::{AnomalyContext, AnomalyDetails, AnomalyEvent},
+    labels::Labels,
+    types::{AnomalyType, DetectionMethod, ModelId, ServiceId, Severity},
+};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use uuid::Uuid;
+
+/// Path of the anomaly simulation endpoint
+pub const SIMULATION_PATH: &str = "/api/v1/admin/test-anomaly";
+
+/// Label marking synthesized anomalies, set to `"true"`
+pub const TEST_LABEL: &str = "test";
+
+/// Detection method reported by synthesized anomalies
+pub const SIMULATION_METHOD: &str = "simulation";
+
+/// Anomaly to synthesize
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TestAnomalyRequest {
+    /// Severity
+    pub severity: Severity,
+    /// Anomaly type
+    pub anomaly_type: AnomalyType,
+    /// Service the anomaly is reported for
+    pub service: ServiceId,
+    /// Model (defaults to `test-model`)
+    #[serde(default)]
+    pub model: Option<ModelId>,
+    /// Deployment environment
+    #[serde(default)]
+    pub environment: Option<String>,
+    /// Additional labels, e.g. to exercise label-based routes
+    #[serde(default)]
+    pub labels: Labels,
+}
+
+impl TestAnomalyRequest {
+    /// Build the anomaly, labelled as a test
+    pub fn synthesize(&self) -> AnomalyEvent {
+        let mut anomaly = AnomalyEvent::new(
+            self.severity,
+            self.anomaly_type.clone(),
+            self.service.clone(),
+            self.model
+                .clone()
+                .unwrap_or_else(|| ModelId::new("test-model")),
+            DetectionMethod::Custom(SIMULATION_METHOD.to_string()),
+            1.0,
+            AnomalyDetails {
+                metric: "test".to_string(),
+                value: 0.0,
+                baseline: 0.0,
+                threshold: 0.0,
+                deviation_sigma: None,
+                additional: HashMap::new(),
+                observations: Vec::new(),
+            },
+            AnomalyContext {
+                trace_id: None,
+                user_id: None,
+                region: None,
+                time_window: "simulation".to_string(),
+                sample_count: 0,
+                window: None,
+                additional: HashMap::new(),
+            },
+        );
+        anomaly.environment = self.environment.clone().map(Into::into);
+        anomaly.labels = self.labels.clone();
+        anomaly
+            .labels
+            .insert(TEST_LABEL.to_string(), "true".to_string());
+        anomaly
+    }
+}
+
+/// Whether an anomaly was synthesized for testing
+pub fn is_test(anomaly: &AnomalyEvent) -> bool {
+    anomaly
+        .labels
+        .get(TEST_LABEL)
+        .is_some_and(|value| value == "true")
+}
+
+/// What happened to a synthesized anomaly
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SimulationReport {
+    /// ID of the synthesized anomaly
+    pub alert_id: Uuid,
+    /// Suppressed by an open deduplication window
+    pub deduplicated: bool,
+    /// Suppression schedule the anomaly fell in, which keeps it from
+    /// alerting
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub expected: Option<String>,
+    /// Alerters the anomaly was routed to
+    pub routed: Vec<String>,
+}
@@ -0,0 +1,104 @@
+//! Snapshots of learned runtime state.
+//!
+//! A [`RuntimeSnapshot`] carries the mutable state sentinel learns while
+//! running (baseline windows, detector state, open deduplication windows)
+//! so a new deployment can pick up where the old one stopped. Each
+//! subsystem stores its state as a named JSON section; sections a restoring
+//! build does not know are ignored, and missing sections leave that
+//! subsystem cold.
+
+use crate::{Error, Result};
+use chrono::{DateTime, Utc};
+use serde::{de::DeserializeOwned, Deserialize, Serialize};
+use std::collections::BTreeMap;
+
+/// Learned runtime state of one sentinel
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct RuntimeSnapshot {
+    /// Snapshot ID, sortable by creation time
+    pub id: String,
+    /// When the snapshot was taken
+    pub created_at: DateTime<Utc>,
+    /// Version of the sentinel that took it
+    pub version: String,
+    /// State by subsystem
+    pub sections: BTreeMap<String, serde_json::Value>,
+}
+
+impl RuntimeSnapshot {
+    /// Create an empty snapshot taken at `created_at`
+    pub fn new(version: impl Into<String>, created_at: DateTime<Utc>) -> Self {
+        Self {
+            id: created_at.format("%Y%m%dT%H%M%S%.3fZ").to_string(),
+            created_at,
+            version: version.into(),
+            sections: BTreeMap::new(),
+        }
+    }
+
+    /// Add a subsystem's state
+    pub fn insert<T: Serialize>(&mut self, section: &str, state: &T) -> Result<()> {
+        let value = serde_json::to_value(state).map_err(|e| {
+            Error::internal(format!("Failed to serialize snapshot section {}: {}", section, e))
+        })?;
+        self.sections.insert(section.to_string(), value);
+        Ok(())
+    }
+
+    /// A subsystem's state, if the snapshot has it
+    pub fn get<T: DeserializeOwned>(&self, section: &str) -> Result<Option<T>> {
+        self.sections
+            .get(section)
+            .map(|value| {
+                T::deserialize(value).map_err(|e| {
+                    Error::validation(format!("Invalid snapshot section {}: {}", section, e))
+                })
+            })
+            .transpose()
+    }
+
+    /// Summary without the state itself
+    pub fn info(&self) -> SnapshotInfo {
+        SnapshotInfo {
+            id: self.id.clone(),
+            created_at: self.created_at,
+            version: self.version.clone(),
+            sections: self.sections.keys().cloned().collect(),
+        }
+    }
+}
+
+/// Summary of a stored snapshot
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SnapshotInfo {
+    /// Snapshot ID
+    pub id: String,
+    /// When the snapshot was taken
+    pub created_at: DateTime<Utc>,
+    /// Version of the sentinel that took it
+    pub version: String,
+    /// Subsystems with state in the snapshot
+    pub sections: Vec<String>,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+
+    #[test]
+    fn test_snapshot_sections() {
+        let at = Utc.with_ymd_and_hms(2024, 1, 1, 12, 0, 0).unwrap();
+        let mut snapshot = RuntimeSnapshot::new("0.1.0", at);
+        assert_eq!(snapshot.id, "20240101T120000.000Z");
+
+        snapshot.insert("counts", &vec![1u64, 2, 3]).unwrap();
+        assert_eq!(
+            snapshot.get::<Vec<u64>>("counts").unwrap(),
+            Some(vec![1, 2, 3])
+        );
+        assert_eq!(snapshot.get::<Vec<u64>>("missing").unwrap(), None);
+        assert!(snapshot.get::<String>("counts").is_err());
+        assert_eq!(snapshot.info().sections, vec!["counts".to_string()]);
+    }
+}
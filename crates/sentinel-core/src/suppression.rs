@@ -0,0 +1,343 @@
+//! Recurring suppression schedules for expected spikes.
+//!
+//! Batch jobs that legitimately spike token usage or cost at the same time
+//! every night are described by a [`SuppressionScheduleConfig`]: a
+//! five-field cron expression (evaluated in UTC) for when the job starts and
+//! how long its window lasts. Anomalies from a covered service and anomaly
+//! type detected inside a window carry the [`EXPECTED_LABEL`] label naming
+//! the schedule; they are stored and published like any other anomaly but
+//! not alerted on.
+
+use crate::{config::SuppressionScheduleConfig, events::AnomalyEvent, Error, Result};
+use chrono::{DateTime, Datelike, DurationRound, Timelike, Utc};
+
+/// Label marking anomalies detected inside a suppression window, set to the
+/// schedule's name
+pub const EXPECTED_LABEL: &str = "expected";
+
+/// Name of the suppression schedule an anomaly was expected by, if any
+pub fn expected_by(anomaly: &AnomalyEvent) -> Option<&str> {
+    anomaly.labels.get(EXPECTED_LABEL).map(String::as_str)
+}
+
+/// Parsed five-field cron expression: minute, hour, day of month, month and
+/// day of week
+///
+/// Fields accept `*`, numbers, ranges (`1-5`), lists (`0,30`) and steps
+/// (`*/15`, `0-30/10`). Day of week runs from 0 (Sunday) to 7 (also
+/// Sunday). As in cron, when both day fields are restricted a time matches
+/// if either does.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CronSchedule {
+    minutes: u64,
+    hours: u64,
+    days_of_month: u64,
+    months: u64,
+    days_of_week: u64,
+    day_of_month_any: bool,
+    day_of_week_any: bool,
+}
+
+impl CronSchedule {
+    /// Parse a five-field cron expression
+    pub fn parse(expr: &str) -> Result<Self> {
+        let fields: Vec<&str> = expr.split_whitespace().collect();
+        let [minute, hour, day_of_month, month, day_of_week] = fields[..] else {
+            return Err(Error::config(format!(
+                "Cron expression '{}' must have 5 fields, found {}",
+                expr,
+                fields.len()
+            )));
+        };
+
+        let mut days_of_week = parse_field(day_of_week, 0, 7)?;
+        // 7 is Sunday too
+        if days_of_week & (1 << 7) != 0 {
+            days_of_week |= 1;
+        }
+        Ok(Self {
+            minutes: parse_field(minute, 0, 59)?,
+            hours: parse_field(hour, 0, 23)?,
+            days_of_month: parse_field(day_of_month, 1, 31)?,
+            months: parse_field(month, 1, 12)?,
+            days_of_week,
+            day_of_month_any: day_of_month == "*",
+            day_of_week_any: day_of_week == "*",
+        })
+    }
+
+    /// Whether the minute containing `time` matches
+    pub fn matches(&self, time: DateTime<Utc>) -> bool {
+        let day_of_month = bit(self.days_of_month, time.day());
+        let day_of_week = bit(self.days_of_week, time.weekday().num_days_from_sunday());
+        let day = match (self.day_of_month_any, self.day_of_week_any) {
+            (false, false) => day_of_month || day_of_week,
+            _ => day_of_month && day_of_week,
+        };
+        day && bit(self.minutes, time.minute())
+            && bit(self.hours, time.hour())
+            && bit(self.months, time.month())
+    }
+
+    /// Latest matching minute within the `window_mins` minutes up to and
+    /// including `time`
+    pub fn last_match_within(
+        &self,
+        time: DateTime<Utc>,
+        window_mins: u32,
+    ) -> Option<DateTime<Utc>> {
+        let minute = time.duration_trunc(chrono::Duration::minutes(1)).ok()?;
+        (0..window_mins as i64)
+            .map(|offset| minute - chrono::Duration::minutes(offset))
+            .find(|&candidate| self.matches(candidate))
+    }
+}
+
+fn bit(set: u64, value: u32) -> bool {
+    set & (1 << value) != 0
+}
+
+/// Parse one cron field into a bit set of the values it matches
+fn parse_field(field: &str, min: u32, max: u32) -> Result<u64> {
+    let invalid = || {
+        Error::config(format!(
+            "Invalid cron field '{}' (values {}-{})",
+            field, min, max
+        ))
+    };
+    let value = |s: &str| {
+        s.parse::<u32>()
+            .ok()
+            .filter(|v| (min..=max).contains(v))
+            .ok_or_else(invalid)
+    };
+
+    let mut set = 0u64;
+    for part in field.split(',') {
+        let (range, step) = match part.split_once('/') {
+            Some((range, step)) => (
+                range,
+                step.parse::<u32>()
+                    .ok()
+                    .filter(|&s| s > 0)
+                    .ok_or_else(invalid)?,
+            ),
+            None => (part, 1),
+        };
+        let (start, end) = match range {
+            "*" => (min, max),
+            _ => match range.split_once('-') {
+                Some((start, end)) => (value(start)?, value(end)?),
+                // `5/15` runs from 5 to the end of the range
+                None if step > 1 => (value(range)?, max),
+                None => (value(range)?, value(range)?),
+            },
+        };
+        if start > end {
+            return Err(invalid());
+        }
+        for v in (start..=end).step_by(step as usize) {
+            set |= 1 << v;
+        }
+    }
+    Ok(set)
+}
+
+/// Compiled suppression schedules
+#[derive(Debug, Clone, Default)]
+pub struct SuppressionSchedules {
+    schedules: Vec<(SuppressionScheduleConfig, CronSchedule)>,
+}
+
+impl SuppressionSchedules {
+    /// Compile the configured schedules, failing on an invalid cron
+    /// expression
+    pub fn new(configs: &[SuppressionScheduleConfig]) -> Result<Self> {
+        let schedules = configs
+            .iter()
+            .map(|config| {
+                CronSchedule::parse(&config.cron)
+                    .map(|cron| (config.clone(), cron))
+                    .map_err(|e| {
+                        Error::config(format!("Suppression schedule '{}': {}", config.name, e))
+                    })
+            })
+            .collect::<Result<_>>()?;
+        Ok(Self { schedules })
+    }
+
+    /// Whether any schedules are configured
+    pub fn is_empty(&self) -> bool {
+        self.schedules.is_empty()
+    }
+
+    /// The first schedule whose window covers the anomaly's service, type
+    /// and detection time
+    pub fn matching(&self, anomaly: &AnomalyEvent) -> Option<&SuppressionScheduleConfig> {
+        let anomaly_type = anomaly.anomaly_type.to_string();
+        self.schedules
+            .iter()
+            .find(|(config, cron)| {
+                (config.services.is_empty()
+                    || config
+                        .services
+                        .iter()
+                        .any(|s| s == anomaly.service_name.as_str()))
+                    && (config.anomaly_types.is_empty()
+                        || config.anomaly_types.contains(&anomaly_type))
+                    && cron
+                        .last_match_within(anomaly.timestamp, config.duration_mins)
+                        .is_some()
+            })
+            .map(|(config, _)| config)
+    }
+
+    /// Label the anomaly as expected when a schedule covers it, returning
+    /// the schedule's name
+    pub fn annotate(&self, anomaly: &mut AnomalyEvent) -> Option<String> {
+        let name = self.matching(anomaly)?.name.clone();
+        anomaly
+            .labels
+            .insert(EXPECTED_LABEL.to_string(), name.clone());
+        Some(name)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        events::{AnomalyContext, AnomalyDetails},
+        types::{AnomalyType, DetectionMethod, ModelId, ServiceId, Severity},
+    };
+    use chrono::TimeZone;
+    use std::collections::HashMap;
+
+    fn at(day: u32, hour: u32, minute: u32) -> DateTime<Utc> {
+        // 2024-06-03 is a Monday
+        Utc.with_ymd_and_hms(2024, 6, day, hour, minute, 30)
+            .unwrap()
+    }
+
+    fn anomaly(service: &str, anomaly_type: AnomalyType, timestamp: DateTime<Utc>) -> AnomalyEvent {
+        let mut anomaly = AnomalyEvent::new(
+            Severity::High,
+            anomaly_type,
+            ServiceId::new(service),
+            ModelId::new("gpt-4"),
+            DetectionMethod::ZScore,
+            0.9,
+            AnomalyDetails {
+                metric: "total_tokens".to_string(),
+                value: 50000.0,
+                baseline: 1000.0,
+                threshold: 3.0,
+                deviation_sigma: Some(8.0),
+                additional: HashMap::new(),
+                observations: Vec::new(),
+            },
+            AnomalyContext {
+                trace_id: None,
+                user_id: None,
+                region: None,
+                time_window: "last_5_minutes".to_string(),
+                sample_count: 10,
+                window: None,
+                additional: HashMap::new(),
+            },
+        );
+        anomaly.timestamp = timestamp;
+        anomaly
+    }
+
+    #[test]
+    fn test_cron_parse() {
+        let cron = CronSchedule::parse("*/15 2 * * 1-5").unwrap();
+        assert!(cron.matches(at(3, 2, 45)));
+        assert!(!cron.matches(at(3, 2, 50)));
+        assert!(!cron.matches(at(3, 3, 0)));
+        // Sunday
+        assert!(!cron.matches(at(2, 2, 0)));
+
+        let sunday = CronSchedule::parse("0 0 * * 7").unwrap();
+        assert!(sunday.matches(at(2, 0, 0)));
+
+        // Either restricted day field matches
+        let either = CronSchedule::parse("0 0 1 * 1").unwrap();
+        assert!(either.matches(at(1, 0, 0)));
+        assert!(either.matches(at(3, 0, 0)));
+        assert!(!either.matches(at(4, 0, 0)));
+
+        for invalid in [
+            "* * * *",
+            "60 * * * *",
+            "5-1 * * * *",
+            "*/0 * * * *",
+            "a * * * *",
+        ] {
+            assert!(CronSchedule::parse(invalid).is_err(), "{}", invalid);
+        }
+    }
+
+    #[test]
+    fn test_last_match_within() {
+        let cron = CronSchedule::parse("0 2 * * *").unwrap();
+        assert_eq!(
+            cron.last_match_within(at(3, 2, 0), 60),
+            Some(at(3, 2, 0) - chrono::Duration::seconds(30))
+        );
+        assert!(cron.last_match_within(at(3, 2, 59), 60).is_some());
+        assert!(cron.last_match_within(at(3, 3, 0), 60).is_none());
+        assert!(cron.last_match_within(at(3, 1, 59), 60).is_none());
+    }
+
+    #[test]
+    fn test_annotate() {
+        let schedules = SuppressionSchedules::new(&[SuppressionScheduleConfig {
+            name: "nightly-batch".to_string(),
+            cron: "0 2 * * *".to_string(),
+            duration_mins: 90,
+            services: vec!["batch-summarizer".to_string()],
+            anomaly_types: vec!["token_usage_spike".to_string(), "cost_anomaly".to_string()],
+        }])
+        .unwrap();
+
+        let mut expected = anomaly(
+            "batch-summarizer",
+            AnomalyType::TokenUsageSpike,
+            at(3, 3, 10),
+        );
+        assert_eq!(
+            schedules.annotate(&mut expected).as_deref(),
+            Some("nightly-batch")
+        );
+        assert_eq!(expected_by(&expected), Some("nightly-batch"));
+
+        let mut after = anomaly(
+            "batch-summarizer",
+            AnomalyType::TokenUsageSpike,
+            at(3, 3, 31),
+        );
+        assert!(schedules.annotate(&mut after).is_none());
+        assert!(expected_by(&after).is_none());
+
+        let other_service = anomaly("chat", AnomalyType::TokenUsageSpike, at(3, 2, 10));
+        assert!(schedules.matching(&other_service).is_none());
+
+        let other_type = anomaly("batch-summarizer", AnomalyType::LatencySpike, at(3, 2, 10));
+        assert!(schedules.matching(&other_type).is_none());
+    }
+
+    #[test]
+    fn test_invalid_schedule() {
+        let err = SuppressionSchedules::new(&[SuppressionScheduleConfig {
+            name: "broken".to_string(),
+            cron: "0 25 * * *".to_string(),
+            duration_mins: 30,
+            services: Vec::new(),
+            anomaly_types: Vec::new(),
+        }])
+        .unwrap_err();
+        assert!(err.to_string().contains("broken"));
+    }
+}
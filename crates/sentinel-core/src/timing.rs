@@ -0,0 +1,115 @@
+//! Per-event stage timings.
+//!
+//! Processing an event crosses enrichment, every detector, storage and the
+//! alerters. Inside a [`collect`] scope, each stage reports how long it took
+//! with [`record`] or [`time`], so an event that blows its deadline can be
+//! logged with a breakdown showing which detector or backend was slow.
+//! Outside a scope recording is a no-op, and [`active`] lets callers skip
+//! building stage names nobody will read.
+
+use std::{borrow::Cow, cell::RefCell, fmt, future::Future, time::Duration};
+use tokio::time::Instant;
+
+tokio::task_local! {
+    static STAGES: RefCell<Vec<StageTiming>>;
+}
+
+/// Time spent in one processing stage
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct StageTiming {
+    /// Stage name, e.g. `store` or `detect:zscore`
+    pub stage: Cow<'static, str>,
+    /// Time spent in the stage
+    pub elapsed: Duration,
+}
+
+/// Stage timings of one event, in the order the stages ran
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Breakdown(pub Vec<StageTiming>);
+
+impl Breakdown {
+    /// Slowest stage, if any ran
+    pub fn slowest(&self) -> Option<&StageTiming> {
+        self.0.iter().max_by_key(|timing| timing.elapsed)
+    }
+}
+
+/// Renders as `stage=12.3ms stage=0.4ms ...`
+impl fmt::Display for Breakdown {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for (i, timing) in self.0.iter().enumerate() {
+            if i > 0 {
+                f.write_str(" ")?;
+            }
+            write!(
+                f,
+                "{}={:.1}ms",
+                timing.stage,
+                timing.elapsed.as_secs_f64() * 1000.0
+            )?;
+        }
+        Ok(())
+    }
+}
+
+/// Run `future`, collecting the stage timings recorded while it runs
+pub async fn collect<F: Future>(future: F) -> (F::Output, Breakdown) {
+    STAGES
+        .scope(RefCell::new(Vec::new()), async move {
+            let output = future.await;
+            let stages = STAGES.with(|stages| stages.take());
+            (output, Breakdown(stages))
+        })
+        .await
+}
+
+/// Whether stage timings are being collected for the current task
+pub fn active() -> bool {
+    STAGES.try_with(|_| ()).is_ok()
+}
+
+/// Record time spent in a stage
+pub fn record(stage: impl Into<Cow<'static, str>>, elapsed: Duration) {
+    let _ = STAGES.try_with(|stages| {
+        stages.borrow_mut().push(StageTiming {
+            stage: stage.into(),
+            elapsed,
+        })
+    });
+}
+
+/// Run `future` as a stage, recording its duration
+pub async fn time<F: Future>(stage: &'static str, future: F) -> F::Output {
+    if !active() {
+        return future.await;
+    }
+    let start = Instant::now();
+    let output = future.await;
+    record(stage, start.elapsed());
+    output
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test(start_paused = true)]
+    async fn test_collect() {
+        assert!(!active());
+        // Ignored outside a scope
+        record("lost", Duration::from_millis(1));
+
+        let (value, breakdown) = collect(async {
+            assert!(active());
+            time("store", tokio::time::sleep(Duration::from_millis(30))).await;
+            record(format!("detect:{}", "zscore"), Duration::from_millis(5));
+            7
+        })
+        .await;
+
+        assert_eq!(value, 7);
+        assert_eq!(breakdown.0.len(), 2);
+        assert_eq!(breakdown.slowest().unwrap().stage, "store");
+        assert_eq!(breakdown.to_string(), "store=30.0ms detect:zscore=5.0ms");
+    }
+}
@@ -0,0 +1,488 @@
+//! Detection configuration as code.
+//!
+//! A [`DetectionDocument`] gathers everything that decides what is detected
+//! and who hears about it — detection engines and their thresholds, tiering,
+//! SLOs, recovery and silence detection, model context windows, cost
+//! regression, change points, distribution drift, output drift, content-
+//! filter surges, prompt bloat, regime shifts, alert routes, severity rules
+//! and suppression schedules — into one versioned YAML document that can
+//! live in version control next to the services it watches.
+//!
+//! The document is authoritative for its sections: a section left out of it
+//! is cleared, not inherited. [`DetectionDocument::plan`] applies a document
+//! to a copy of the current configuration, validates the result and lists
+//! the sections that change, so a change can be reviewed before
+//! [`DetectionDocument::write`] replaces the file named by
+//! `detection_file`, which [`Config`] loading applies over the base
+//! configuration.
+
+use crate::{
+    config::{
+        AlertRouteConfig, ChangePointConfig, Config, ContentFilterSurgeConfig, ContextLimitConfig,
+        CostRegressionConfig, DetectionEngineConfig, DetectionTieringConfig,
+        DistributionDriftConfig, OutputDriftConfig, PromptBloatConfig, RecoveryConfig,
+        RegimeShiftConfig, SeverityRuleConfig, SilenceConfig, SloConfig, SuppressionScheduleConfig,
+    },
+    Error, Result,
+};
+use serde::{Deserialize, Serialize};
+use std::path::Path;
+use validator::Validate;
+
+/// Version of the document format written by this build
+pub const DOCUMENT_VERSION: u32 = 1;
+
+/// Detection configuration document
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct DetectionDocument {
+    /// Document format version
+    pub version: u32,
+
+    /// Detection engines, methods and thresholds (`detection.engines`)
+    #[serde(default)]
+    pub engines: Vec<DetectionEngineConfig>,
+
+    /// Tiered detection (`detection.tiering`)
+    #[serde(default)]
+    pub tiering: Option<DetectionTieringConfig>,
+
+    /// Service level objectives (`detection.slos`)
+    #[serde(default)]
+    pub slos: Vec<SloConfig>,
+
+    /// Anomaly auto-resolution (`detection.recovery`)
+    #[serde(default)]
+    pub recovery: Option<RecoveryConfig>,
+
+    /// Telemetry silence detection (`detection.silence`)
+    #[serde(default)]
+    pub silence: Option<SilenceConfig>,
+
+    /// Model context windows (`detection.context_limits`)
+    #[serde(default)]
+    pub context_limits: Option<ContextLimitConfig>,
+
+    /// Cost regression on tokens (`detection.cost_regression`)
+    #[serde(default)]
+    pub cost_regression: Option<CostRegressionConfig>,
+
+    /// Change point detection (`detection.change_points`)
+    #[serde(default)]
+    pub change_points: Option<ChangePointConfig>,
+
+    /// Token and latency distribution drift (`detection.distribution_drift`)
+    #[serde(default)]
+    pub distribution_drift: Option<DistributionDriftConfig>,
+
+    /// Response length and finish reason drift (`detection.output_drift`)
+    #[serde(default)]
+    pub output_drift: Option<OutputDriftConfig>,
+
+    /// Content-filter surges (`detection.content_filter_surge`)
+    #[serde(default)]
+    pub content_filter_surge: Option<ContentFilterSurgeConfig>,
+
+    /// Token-efficiency regressions (`detection.prompt_bloat`)
+    #[serde(default)]
+    pub prompt_bloat: Option<PromptBloatConfig>,
+
+    /// Regime shifts between short and long baselines (`detection.regime_shift`)
+    #[serde(default)]
+    pub regime_shift: Option<RegimeShiftConfig>,
+
+    /// Alert routes (`alerting.routes`)
+    #[serde(default)]
+    pub routes: Vec<AlertRouteConfig>,
+
+    /// Severity rules (`alerting.severity_rules`)
+    #[serde(default)]
+    pub severity_rules: Vec<SeverityRuleConfig>,
+
+    /// Suppression schedules (`alerting.suppression_schedules`)
+    #[serde(default)]
+    pub suppression_schedules: Vec<SuppressionScheduleConfig>,
+}
+
+impl DetectionDocument {
+    /// The detection sections of `config`
+    pub fn from_config(config: &Config) -> Self {
+        Self {
+            version: DOCUMENT_VERSION,
+            engines: config.detection.engines.clone(),
+            tiering: config.detection.tiering.clone(),
+            slos: config.detection.slos.clone(),
+            recovery: config.detection.recovery.clone(),
+            silence: config.detection.silence.clone(),
+            context_limits: config.detection.context_limits.clone(),
+            cost_regression: config.detection.cost_regression.clone(),
+            change_points: config.detection.change_points.clone(),
+            distribution_drift: config.detection.distribution_drift.clone(),
+            output_drift: config.detection.output_drift.clone(),
+            content_filter_surge: config.detection.content_filter_surge.clone(),
+            prompt_bloat: config.detection.prompt_bloat.clone(),
+            regime_shift: config.detection.regime_shift.clone(),
+            routes: config.alerting.routes.clone(),
+            severity_rules: config.alerting.severity_rules.clone(),
+            suppression_schedules: config.alerting.suppression_schedules.clone(),
+        }
+    }
+
+    /// Parse a YAML document, rejecting versions this build cannot read
+    pub fn from_yaml(yaml: &str) -> Result<Self> {
+        let document: Self = serde_yaml::from_str(yaml)
+            .map_err(|e| Error::validation(format!("Invalid detection document: {}", e)))?;
+        if document.version != DOCUMENT_VERSION {
+            return Err(Error::validation(format!(
+                "Unsupported detection document version {} (expected {})",
+                document.version, DOCUMENT_VERSION
+            )));
+        }
+        Ok(document)
+    }
+
+    /// Read a YAML document from `path`
+    pub fn from_file<P: AsRef<Path>>(path: P) -> Result<Self> {
+        let path = path.as_ref();
+        let yaml = std::fs::read_to_string(path)?;
+        Self::from_yaml(&yaml).map_err(|e| e.context(path.display()))
+    }
+
+    /// The document as YAML
+    pub fn to_yaml(&self) -> Result<String> {
+        serde_yaml::to_string(self)
+            .map_err(|e| Error::Internal(format!("Failed to serialize detection document: {}", e)))
+    }
+
+    /// Replace the detection sections of `config` with this document's
+    pub fn apply(&self, config: &mut Config) {
+        config.detection.engines = self.engines.clone();
+        config.detection.tiering = self.tiering.clone();
+        config.detection.slos = self.slos.clone();
+        config.detection.recovery = self.recovery.clone();
+        config.detection.silence = self.silence.clone();
+        config.detection.context_limits = self.context_limits.clone();
+        config.detection.cost_regression = self.cost_regression.clone();
+        config.detection.change_points = self.change_points.clone();
+        config.detection.distribution_drift = self.distribution_drift.clone();
+        config.detection.output_drift = self.output_drift.clone();
+        config.detection.content_filter_surge = self.content_filter_surge.clone();
+        config.detection.prompt_bloat = self.prompt_bloat.clone();
+        config.detection.regime_shift = self.regime_shift.clone();
+        config.alerting.routes = self.routes.clone();
+        config.alerting.severity_rules = self.severity_rules.clone();
+        config.alerting.suppression_schedules = self.suppression_schedules.clone();
+    }
+
+    /// Apply this document to a copy of `current`, validate the result and
+    /// list the sections that change
+    pub fn plan(&self, current: &Config) -> Result<DetectionPlan> {
+        let mut config = current.clone();
+        self.apply(&mut config);
+        self.validate_sections()?;
+        config.validate_config()?;
+
+        let changes = DetectionDocument::from_config(current).diff(self)?;
+        Ok(DetectionPlan { config, changes })
+    }
+
+    /// Check the settings of every section
+    fn validate_sections(&self) -> Result<()> {
+        validate_each(&self.engines)?;
+        validate_each(&self.tiering)?;
+        validate_each(&self.slos)?;
+        validate_each(&self.recovery)?;
+        validate_each(&self.silence)?;
+        validate_each(&self.context_limits)?;
+        validate_each(&self.cost_regression)?;
+        validate_each(&self.change_points)?;
+        validate_each(&self.distribution_drift)?;
+        validate_each(&self.output_drift)?;
+        validate_each(&self.content_filter_surge)?;
+        validate_each(&self.prompt_bloat)?;
+        validate_each(&self.regime_shift)?;
+        validate_each(&self.routes)?;
+        validate_each(&self.severity_rules)?;
+        validate_each(&self.suppression_schedules)
+    }
+
+    /// Sections that differ between this document and `proposed`, in
+    /// document order
+    pub fn diff(&self, proposed: &DetectionDocument) -> Result<Vec<SectionChange>> {
+        let before = self.sections()?;
+        let after = proposed.sections()?;
+        let mut changes = Vec::new();
+        for ((section, before), (_, after)) in before.into_iter().zip(after) {
+            if before != after {
+                changes.push(SectionChange::new(section, before, after)?);
+            }
+        }
+        Ok(changes)
+    }
+
+    /// Write the document to `path`, replacing any previous one atomically
+    ///
+    /// The document is written to a temporary file next to `path` and
+    /// renamed over it, so readers never see a partial document.
+    pub fn write<P: AsRef<Path>>(&self, path: P) -> Result<()> {
+        let path = path.as_ref();
+        let file_name = path
+            .file_name()
+            .and_then(|name| name.to_str())
+            .ok_or_else(|| Error::config(format!("Invalid document path: {}", path.display())))?;
+        let tmp = path.with_file_name(format!(".{}.tmp", file_name));
+        std::fs::write(&tmp, self.to_yaml()?)?;
+        if let Err(e) = std::fs::rename(&tmp, path) {
+            let _ = std::fs::remove_file(&tmp);
+            return Err(e.into());
+        }
+        Ok(())
+    }
+
+    fn sections(&self) -> Result<Vec<(&'static str, serde_json::Value)>> {
+        Ok(vec![
+            ("detection.engines", serde_json::to_value(&self.engines)?),
+            ("detection.tiering", serde_json::to_value(&self.tiering)?),
+            ("detection.slos", serde_json::to_value(&self.slos)?),
+            ("detection.recovery", serde_json::to_value(&self.recovery)?),
+            ("detection.silence", serde_json::to_value(&self.silence)?),
+            (
+                "detection.context_limits",
+                serde_json::to_value(&self.context_limits)?,
+            ),
+            (
+                "detection.cost_regression",
+                serde_json::to_value(&self.cost_regression)?,
+            ),
+            (
+                "detection.change_points",
+                serde_json::to_value(&self.change_points)?,
+            ),
+            (
+                "detection.distribution_drift",
+                serde_json::to_value(&self.distribution_drift)?,
+            ),
+            (
+                "detection.output_drift",
+                serde_json::to_value(&self.output_drift)?,
+            ),
+            (
+                "detection.content_filter_surge",
+                serde_json::to_value(&self.content_filter_surge)?,
+            ),
+            (
+                "detection.prompt_bloat",
+                serde_json::to_value(&self.prompt_bloat)?,
+            ),
+            (
+                "detection.regime_shift",
+                serde_json::to_value(&self.regime_shift)?,
+            ),
+            ("alerting.routes", serde_json::to_value(&self.routes)?),
+            (
+                "alerting.severity_rules",
+                serde_json::to_value(&self.severity_rules)?,
+            ),
+            (
+                "alerting.suppression_schedules",
+                serde_json::to_value(&self.suppression_schedules)?,
+            ),
+        ])
+    }
+}
+
+fn validate_each<'a, T: Validate + 'a>(sections: impl IntoIterator<Item = &'a T>) -> Result<()> {
+    sections.into_iter().try_for_each(|section| {
+        section
+            .validate()
+            .map_err(|e| Error::validation(format!("Invalid detection document: {}", e)))
+    })
+}
+
+/// A validated detection document applied to the current configuration
+#[derive(Debug, Clone)]
+pub struct DetectionPlan {
+    /// The configuration with the document applied
+    pub config: Config,
+
+    /// Sections the document changes
+    pub changes: Vec<SectionChange>,
+}
+
+/// One changed section of a detection document
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SectionChange {
+    /// Configuration path of the section, e.g. `alerting.routes`
+    pub section: String,
+
+    /// Current value
+    pub before: serde_json::Value,
+
+    /// Proposed value
+    pub after: serde_json::Value,
+
+    /// Line diff of the section's YAML, with `-` and `+` marking removed
+    /// and added lines
+    pub diff: String,
+}
+
+impl SectionChange {
+    fn new(section: &str, before: serde_json::Value, after: serde_json::Value) -> Result<Self> {
+        let diff = line_diff(&section_yaml(&before)?, &section_yaml(&after)?);
+        Ok(Self {
+            section: section.to_string(),
+            before,
+            after,
+            diff,
+        })
+    }
+}
+
+/// Render changes for a terminal, one `--- section` header per change
+pub fn render_changes(changes: &[SectionChange]) -> String {
+    if changes.is_empty() {
+        return "No changes\n".to_string();
+    }
+    let mut out = String::new();
+    for change in changes {
+        out.push_str(&format!("--- {}\n", change.section));
+        out.push_str(&change.diff);
+    }
+    out
+}
+
+fn section_yaml(value: &serde_json::Value) -> Result<String> {
+    if value.is_null() {
+        return Ok(String::new());
+    }
+    serde_yaml::to_string(value)
+        .map_err(|e| Error::Internal(format!("Failed to serialize section: {}", e)))
+}
+
+/// Line diff of `before` and `after` from their longest common subsequence
+fn line_diff(before: &str, after: &str) -> String {
+    let before: Vec<&str> = before.lines().collect();
+    let after: Vec<&str> = after.lines().collect();
+
+    // common[i][j]: length of the longest common subsequence of the suffixes
+    let mut common = vec![vec![0usize; after.len() + 1]; before.len() + 1];
+    for i in (0..before.len()).rev() {
+        for j in (0..after.len()).rev() {
+            common[i][j] = if before[i] == after[j] {
+                common[i + 1][j + 1] + 1
+            } else {
+                common[i + 1][j].max(common[i][j + 1])
+            };
+        }
+    }
+
+    let mut out = String::new();
+    let (mut i, mut j) = (0, 0);
+    while i < before.len() || j < after.len() {
+        if i < before.len() && j < after.len() && before[i] == after[j] {
+            out.push_str(&format!("  {}\n", before[i]));
+            i += 1;
+            j += 1;
+        } else if j < after.len() && (i == before.len() || common[i][j + 1] >= common[i + 1][j]) {
+            out.push_str(&format!("+ {}\n", after[j]));
+            j += 1;
+        } else {
+            out.push_str(&format!("- {}\n", before[i]));
+            i += 1;
+        }
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_document_round_trip_and_diff() {
+        let config = Config::default_test();
+        let document = DetectionDocument::from_config(&config);
+        let parsed = DetectionDocument::from_yaml(&document.to_yaml().unwrap()).unwrap();
+        assert!(document.diff(&parsed).unwrap().is_empty());
+
+        let mut proposed = parsed.clone();
+        proposed.recovery = Some(RecoveryConfig {
+            recovery_secs: 600,
+            min_samples: 5,
+            notify: true,
+        });
+        let plan = proposed.plan(&config).unwrap();
+        assert_eq!(plan.changes.len(), 1);
+        assert_eq!(plan.changes[0].section, "detection.recovery");
+        assert!(plan.changes[0].diff.contains("+ recovery_secs: 600"));
+        assert_eq!(
+            plan.config
+                .detection
+                .recovery
+                .as_ref()
+                .unwrap()
+                .recovery_secs,
+            600
+        );
+
+        // Sections missing from a document are cleared
+        let cleared = DetectionDocument::from_yaml("version: 1\n").unwrap();
+        let plan = cleared.plan(&plan.config).unwrap();
+        assert!(plan.config.detection.recovery.is_none());
+        assert!(plan.config.detection.engines.is_empty());
+    }
+
+    #[test]
+    fn test_document_rejected() {
+        assert!(DetectionDocument::from_yaml("version: 2\n").is_err());
+        assert!(DetectionDocument::from_yaml("version: 1\nthresholds: {}\n").is_err());
+
+        let mut document = DetectionDocument::from_config(&Config::default_test());
+        document.silence = Some(SilenceConfig {
+            silence_secs: 0,
+            min_events: 10,
+            forget_secs: 86400,
+            check_interval_secs: 30,
+            severity: crate::types::Severity::High,
+        });
+        assert!(document.plan(&Config::default_test()).is_err());
+    }
+
+    #[test]
+    fn test_detection_file_applied_on_load() {
+        let dir = std::env::temp_dir().join(format!("sentinel-tuning-{}", uuid::Uuid::new_v4()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let detection_file = dir.join("detection.yaml");
+        let mut config = Config::default_test();
+        config.detection_file = Some(detection_file.display().to_string());
+        let base = dir.join("sentinel.yaml");
+        std::fs::write(&base, serde_yaml::to_string(&config).unwrap()).unwrap();
+
+        // A missing document leaves the base configuration alone
+        let loaded = Config::from_file(&base).unwrap();
+        assert_eq!(
+            loaded.detection.engines.len(),
+            config.detection.engines.len()
+        );
+
+        let mut document = DetectionDocument::from_config(&config);
+        document.engines.clear();
+        document.recovery = Some(RecoveryConfig {
+            recovery_secs: 120,
+            min_samples: 3,
+            notify: false,
+        });
+        document.write(&detection_file).unwrap();
+        assert!(!dir.join(".detection.yaml.tmp").exists());
+
+        let loaded = Config::from_file(&base).unwrap();
+        assert!(loaded.detection.engines.is_empty());
+        assert_eq!(loaded.detection.recovery.unwrap().recovery_secs, 120);
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn test_line_diff() {
+        assert_eq!(line_diff("a\nb\nc\n", "a\nc\nd\n"), "  a\n- b\n  c\n+ d\n");
+    }
+}
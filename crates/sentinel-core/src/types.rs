@@ -2,16 +2,18 @@
 //!
 //! This module provides fundamental types used throughout the Sentinel system.
 
+use crate::error::Error;
 use serde::{Deserialize, Serialize};
 use std::fmt;
 
 /// Severity level for anomalies and alerts
-#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize, Default)]
 #[serde(rename_all = "lowercase")]
 pub enum Severity {
     /// Low severity - informational
     Low,
     /// Medium severity - warning
+    #[default]
     Medium,
     /// High severity - requires attention
     High,
@@ -19,6 +21,15 @@ pub enum Severity {
     Critical,
 }
 
+impl Severity {
+    /// Whether anomalies of this severity take the priority lane: their
+    /// storage writes are retried and their alerts are never dropped from a
+    /// full dispatch queue
+    pub fn is_priority(&self) -> bool {
+        *self >= Severity::High
+    }
+}
+
 impl fmt::Display for Severity {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         match self {
@@ -30,9 +41,17 @@ impl fmt::Display for Severity {
     }
 }
 
-impl Default for Severity {
-    fn default() -> Self {
-        Severity::Medium
+impl std::str::FromStr for Severity {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "low" => Ok(Severity::Low),
+            "medium" => Ok(Severity::Medium),
+            "high" => Ok(Severity::High),
+            "critical" => Ok(Severity::Critical),
+            _ => Err(format!("Invalid severity: {}", s)),
+        }
     }
 }
 
@@ -64,10 +83,22 @@ pub enum AnomalyType {
     QualityDegradation,
     /// Security threat
     SecurityThreat,
+    /// Request approaching or exceeding the model's context window
+    ContextOverflow,
     /// Custom anomaly type
     Custom(String),
 }
 
+impl AnomalyType {
+    /// Whether this is a distribution drift anomaly
+    pub fn is_drift(&self) -> bool {
+        matches!(
+            self,
+            Self::InputDrift | Self::OutputDrift | Self::ConceptDrift | Self::EmbeddingDrift
+        )
+    }
+}
+
 impl fmt::Display for AnomalyType {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         match self {
@@ -83,6 +114,7 @@ impl fmt::Display for AnomalyType {
             AnomalyType::Hallucination => write!(f, "hallucination"),
             AnomalyType::QualityDegradation => write!(f, "quality_degradation"),
             AnomalyType::SecurityThreat => write!(f, "security_threat"),
+            AnomalyType::ContextOverflow => write!(f, "context_overflow"),
             AnomalyType::Custom(s) => write!(f, "{}", s),
         }
     }
@@ -100,6 +132,10 @@ pub enum DetectionMethod {
     Mad,
     /// Cumulative Sum (CUSUM)
     Cusum,
+    /// Bayesian online change point detection
+    Bocpd,
+    /// Divergence between short- and long-window baselines
+    MultiWindow,
     /// Isolation Forest ML algorithm
     IsolationForest,
     /// LSTM Autoencoder
@@ -125,6 +161,8 @@ impl fmt::Display for DetectionMethod {
             DetectionMethod::Iqr => write!(f, "iqr"),
             DetectionMethod::Mad => write!(f, "mad"),
             DetectionMethod::Cusum => write!(f, "cusum"),
+            DetectionMethod::Bocpd => write!(f, "bocpd"),
+            DetectionMethod::MultiWindow => write!(f, "multi_window"),
             DetectionMethod::IsolationForest => write!(f, "isolation_forest"),
             DetectionMethod::LstmAutoencoder => write!(f, "lstm_autoencoder"),
             DetectionMethod::OneClassSvm => write!(f, "one_class_svm"),
@@ -137,8 +175,57 @@ impl fmt::Display for DetectionMethod {
     }
 }
 
+impl DetectionMethod {
+    /// Name of the detector reporting this method, as detectors are enabled
+    /// and listed by (`zscore` for [`DetectionMethod::ZScore`])
+    ///
+    /// Methods without a built-in detector keep their own name.
+    pub fn detector(&self) -> String {
+        match self {
+            DetectionMethod::ZScore => "zscore".to_string(),
+            DetectionMethod::Custom(s) if s == "content_filter_surge" => {
+                "content_filter".to_string()
+            }
+            other => other.to_string(),
+        }
+    }
+}
+
+/// Maximum length of a service identifier
+pub const MAX_SERVICE_ID_LEN: usize = 128;
+
+/// Maximum length of a model identifier
+pub const MAX_MODEL_ID_LEN: usize = 256;
+
+/// Trim and lowercase an identifier and check its length and charset
+///
+/// Letters, digits, `-`, `_` and `.` are always allowed; `extra` lists
+/// further allowed characters.
+fn normalize_id(kind: &str, id: &str, max_len: usize, extra: &[char]) -> crate::Result<String> {
+    let id = id.trim().to_lowercase();
+    if id.is_empty() {
+        return Err(Error::validation(format!("{} ID must not be empty", kind)));
+    }
+    if id.len() > max_len {
+        return Err(Error::validation(format!(
+            "{} ID exceeds {} characters",
+            kind, max_len
+        )));
+    }
+    if let Some(c) = id
+        .chars()
+        .find(|c| !(c.is_ascii_alphanumeric() || matches!(c, '-' | '_' | '.') || extra.contains(c)))
+    {
+        return Err(Error::validation(format!(
+            "{} ID '{}' contains invalid character '{}'",
+            kind, id, c
+        )));
+    }
+    Ok(id)
+}
+
 /// Service identifier
-#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Eq, Hash, PartialOrd, Ord, Serialize, Deserialize)]
 pub struct ServiceId(String);
 
 impl ServiceId {
@@ -147,6 +234,14 @@ impl ServiceId {
         Self(id.into())
     }
 
+    /// Create a normalized service ID
+    ///
+    /// Trims and lowercases `id`, which must be at most
+    /// [`MAX_SERVICE_ID_LEN`] characters of letters, digits, `-`, `_` and `.`.
+    pub fn try_new(id: impl AsRef<str>) -> crate::Result<Self> {
+        normalize_id("Service", id.as_ref(), MAX_SERVICE_ID_LEN, &[]).map(Self)
+    }
+
     /// Get the service ID as a string
     pub fn as_str(&self) -> &str {
         &self.0
@@ -172,7 +267,7 @@ impl From<&str> for ServiceId {
 }
 
 /// Model identifier
-#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Eq, Hash, PartialOrd, Ord, Serialize, Deserialize)]
 pub struct ModelId(String);
 
 impl ModelId {
@@ -181,6 +276,16 @@ impl ModelId {
         Self(id.into())
     }
 
+    /// Create a normalized model ID
+    ///
+    /// Trims and lowercases `id`, which must be at most [`MAX_MODEL_ID_LEN`]
+    /// characters. Besides the service ID charset, `:`, `/` and `@` are
+    /// allowed for provider-qualified names such as
+    /// `anthropic.claude-3-sonnet-20240229-v1:0`.
+    pub fn try_new(id: impl AsRef<str>) -> crate::Result<Self> {
+        normalize_id("Model", id.as_ref(), MAX_MODEL_ID_LEN, &[':', '/', '@']).map(Self)
+    }
+
     /// Get the model ID as a string
     pub fn as_str(&self) -> &str {
         &self.0
@@ -205,6 +310,123 @@ impl From<&str> for ModelId {
     }
 }
 
+/// Deployment environment (e.g. `prod`, `staging`, `dev`)
+///
+/// Names are trimmed and lowercased so `Prod` and `prod` are the same
+/// environment.
+#[derive(Debug, Clone, PartialEq, Eq, Hash, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(from = "String")]
+pub struct Environment(String);
+
+impl Environment {
+    /// Create a new environment
+    pub fn new(name: impl AsRef<str>) -> Self {
+        Self(name.as_ref().trim().to_lowercase())
+    }
+
+    /// Get the environment name as a string
+    pub fn as_str(&self) -> &str {
+        &self.0
+    }
+}
+
+impl fmt::Display for Environment {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.0)
+    }
+}
+
+impl From<String> for Environment {
+    fn from(s: String) -> Self {
+        Self::new(s)
+    }
+}
+
+impl From<&str> for Environment {
+    fn from(s: &str) -> Self {
+        Self::new(s)
+    }
+}
+
+/// Model version parsed from a model identifier
+///
+/// Providers pin snapshots with a suffix on the model family:
+/// `gpt-4-0613`, `gpt-4o-2024-08-06`, `claude-3-5-sonnet-20241022`,
+/// `claude-3-opus@20240229`, `anthropic.claude-3-sonnet-20240229-v1:0`.
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub struct ModelVersion {
+    /// Model family without the version suffix (e.g. `gpt-4`)
+    pub family: String,
+    /// Version suffix (e.g. `0613`, `2024-08-06`)
+    pub version: String,
+}
+
+impl ModelVersion {
+    /// Create a new model version
+    pub fn new(family: impl Into<String>, version: impl Into<String>) -> Self {
+        Self {
+            family: family.into(),
+            version: version.into(),
+        }
+    }
+
+    /// Parse the version suffix from a model identifier
+    ///
+    /// Returns `None` for unversioned aliases such as `gpt-4` or
+    /// `gpt-4-turbo-preview`.
+    pub fn parse(model: &str) -> Option<Self> {
+        if let Some((family, version)) = model.split_once('@') {
+            return (!family.is_empty() && !version.is_empty())
+                .then(|| Self::new(family, version));
+        }
+
+        let segments: Vec<&str> = model.split('-').collect();
+        let is_digits = |s: &str| !s.is_empty() && s.bytes().all(|b| b.is_ascii_digit());
+        let is_revision = |s: &str| {
+            s.strip_prefix('v').is_some_and(|rest| {
+                let (major, minor) = rest.split_once(':').unwrap_or((rest, "0"));
+                is_digits(major) && is_digits(minor)
+            })
+        };
+
+        // Walk back over version-like segments
+        let mut start = segments.len();
+        while start > 1 {
+            let segment = segments[start - 1];
+            let trailing = start == segments.len();
+            if (is_digits(segment) && segment.len() >= 2)
+                || (trailing && (segment == "preview" || is_revision(segment)))
+            {
+                start -= 1;
+            } else {
+                break;
+            }
+        }
+
+        // The version starts at a snapshot of at least 3 digits; shorter
+        // leading numbers belong to the family (e.g. `qwen-72`)
+        while start < segments.len()
+            && !(is_digits(segments[start]) && segments[start].len() >= 3)
+        {
+            start += 1;
+        }
+        if start == segments.len() {
+            return None;
+        }
+
+        Some(Self::new(
+            segments[..start].join("-"),
+            segments[start..].join("-"),
+        ))
+    }
+}
+
+impl fmt::Display for ModelVersion {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}@{}", self.family, self.version)
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -214,6 +436,8 @@ mod tests {
         assert!(Severity::Critical > Severity::High);
         assert!(Severity::High > Severity::Medium);
         assert!(Severity::Medium > Severity::Low);
+        assert!(Severity::Critical.is_priority() && Severity::High.is_priority());
+        assert!(!Severity::Medium.is_priority());
     }
 
     #[test]
@@ -232,6 +456,17 @@ mod tests {
         assert_eq!(AnomalyType::Custom("test".to_string()).to_string(), "test");
     }
 
+    #[test]
+    fn test_detection_method_detector() {
+        assert_eq!(DetectionMethod::ZScore.detector(), "zscore");
+        assert_eq!(DetectionMethod::KlDivergence.detector(), "kl_divergence");
+        assert_eq!(DetectionMethod::MultiWindow.detector(), "multi_window");
+        let surge = DetectionMethod::Custom("content_filter_surge".to_string());
+        assert_eq!(surge.detector(), "content_filter");
+        let slo = DetectionMethod::Custom("slo_burn_rate".to_string());
+        assert_eq!(slo.detector(), "slo_burn_rate");
+    }
+
     #[test]
     fn test_service_id_creation() {
         let id = ServiceId::new("test-service");
@@ -244,4 +479,69 @@ mod tests {
         let id: ModelId = "gpt-4".into();
         assert_eq!(id.as_str(), "gpt-4");
     }
+
+    #[test]
+    fn test_environment() {
+        let env = Environment::new(" Staging ");
+        assert_eq!(env.as_str(), "staging");
+        assert_eq!(env, Environment::from("staging"));
+
+        let parsed: Environment = serde_json::from_str(r#""PROD""#).unwrap();
+        assert_eq!(parsed.to_string(), "prod");
+    }
+
+    #[test]
+    fn test_id_normalization() {
+        assert_eq!(
+            ServiceId::try_new("  Checkout-API ").unwrap().as_str(),
+            "checkout-api"
+        );
+        assert_eq!(
+            ModelId::try_new("Anthropic.Claude-3-Sonnet-20240229-v1:0")
+                .unwrap()
+                .as_str(),
+            "anthropic.claude-3-sonnet-20240229-v1:0"
+        );
+        assert_eq!(
+            ModelId::try_new("models/gemini-1.5-pro").unwrap().as_str(),
+            "models/gemini-1.5-pro"
+        );
+
+        assert!(ServiceId::try_new("   ").is_err());
+        assert!(ServiceId::try_new("checkout api").is_err());
+        assert!(ServiceId::try_new("models/x").is_err());
+        assert!(ServiceId::try_new("a".repeat(MAX_SERVICE_ID_LEN + 1)).is_err());
+        assert!(ModelId::try_new("a".repeat(MAX_MODEL_ID_LEN)).is_ok());
+        assert!(ModelId::try_new("gpt-4\tturbo").is_err());
+    }
+
+    #[test]
+    fn test_model_version_parse() {
+        let cases = [
+            ("gpt-4-0613", "gpt-4", "0613"),
+            ("gpt-4-1106-preview", "gpt-4", "1106-preview"),
+            ("gpt-4o-2024-08-06", "gpt-4o", "2024-08-06"),
+            ("gpt-3.5-turbo-0125", "gpt-3.5-turbo", "0125"),
+            ("claude-3-5-sonnet-20241022", "claude-3-5-sonnet", "20241022"),
+            ("claude-3-opus@20240229", "claude-3-opus", "20240229"),
+            (
+                "anthropic.claude-3-sonnet-20240229-v1:0",
+                "anthropic.claude-3-sonnet",
+                "20240229-v1:0",
+            ),
+            ("gemini-1.5-pro-002", "gemini-1.5-pro", "002"),
+        ];
+        for (model, family, version) in cases {
+            assert_eq!(
+                ModelVersion::parse(model),
+                Some(ModelVersion::new(family, version)),
+                "{}",
+                model
+            );
+        }
+
+        for model in ["gpt-4", "gpt-4-turbo-preview", "claude-2", "qwen-72", "llama-3-70b"] {
+            assert_eq!(ModelVersion::parse(model), None, "{}", model);
+        }
+    }
 }
@@ -0,0 +1,125 @@
+//! Sample windows shared by detectors and windowed evaluation.
+//!
+//! A [`WindowSpec`] says which samples a window keeps; [`WindowMetadata`]
+//! records the samples it actually held when an anomaly was detected, so
+//! alerts can say "compared against 1000 samples from 09:12 to 09:47"
+//! instead of a fixed label.
+
+use chrono::{DateTime, Duration, Utc};
+use serde::{Deserialize, Serialize};
+use std::fmt;
+
+/// Bounds of a sample window
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub struct WindowSpec {
+    /// Maximum number of samples kept
+    pub max_samples: usize,
+    /// Maximum sample age relative to the newest sample (seconds)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub max_age_secs: Option<u64>,
+}
+
+impl WindowSpec {
+    /// Window of the most recent `max_samples` samples
+    pub fn samples(max_samples: usize) -> Self {
+        Self {
+            max_samples,
+            max_age_secs: None,
+        }
+    }
+
+    /// Also drop samples older than `secs` before the newest one
+    pub fn with_max_age_secs(mut self, secs: u64) -> Self {
+        self.max_age_secs = Some(secs);
+        self
+    }
+
+    /// Maximum sample age
+    pub fn max_age(&self) -> Option<Duration> {
+        self.max_age_secs
+            .map(|secs| Duration::seconds(i64::try_from(secs).unwrap_or(i64::MAX)))
+    }
+}
+
+impl Default for WindowSpec {
+    fn default() -> Self {
+        Self::samples(1000)
+    }
+}
+
+impl fmt::Display for WindowSpec {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "last_{}_samples", self.max_samples)?;
+        if let Some(secs) = self.max_age_secs {
+            write!(f, "_within_{}s", secs)?;
+        }
+        Ok(())
+    }
+}
+
+/// Samples a window actually held
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct WindowMetadata {
+    /// Window bounds
+    pub spec: WindowSpec,
+    /// Timestamp of the oldest sample
+    pub start: DateTime<Utc>,
+    /// Timestamp of the newest sample
+    pub end: DateTime<Utc>,
+    /// Time between the oldest and newest sample (seconds)
+    pub span_secs: f64,
+    /// Number of samples
+    pub sample_count: usize,
+}
+
+impl WindowMetadata {
+    /// Describe `sample_count` samples taken between `start` and `end`
+    pub fn new(
+        spec: WindowSpec,
+        start: DateTime<Utc>,
+        end: DateTime<Utc>,
+        sample_count: usize,
+    ) -> Self {
+        Self {
+            spec,
+            start,
+            end,
+            span_secs: (end - start).num_milliseconds() as f64 / 1000.0,
+            sample_count,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_window_spec_display() {
+        assert_eq!(WindowSpec::samples(100).to_string(), "last_100_samples");
+        assert_eq!(
+            WindowSpec::samples(100).with_max_age_secs(300).to_string(),
+            "last_100_samples_within_300s"
+        );
+    }
+
+    #[test]
+    fn test_window_spec_serde() {
+        let spec: WindowSpec = serde_json::from_str(r#"{"max_samples": 50}"#).unwrap();
+        assert_eq!(spec, WindowSpec::samples(50));
+        assert_eq!(spec.max_age(), None);
+
+        let spec = WindowSpec::samples(50).with_max_age_secs(60);
+        let json = serde_json::to_string(&spec).unwrap();
+        assert_eq!(serde_json::from_str::<WindowSpec>(&json).unwrap(), spec);
+        assert_eq!(spec.max_age(), Some(Duration::seconds(60)));
+    }
+
+    #[test]
+    fn test_window_metadata_span() {
+        let end = Utc::now();
+        let metadata =
+            WindowMetadata::new(WindowSpec::default(), end - Duration::seconds(90), end, 10);
+        assert_eq!(metadata.span_secs, 90.0);
+    }
+}
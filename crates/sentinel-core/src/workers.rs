@@ -0,0 +1,333 @@
+//! Bounded worker pools.
+//!
+//! [`WorkerPool`] runs a fixed number of tasks that pull jobs from a bounded
+//! queue, so submitters wait (back-pressure) instead of buffering without
+//! limit. Pools created with [`WorkerPool::spawn_ordered`] route every job to
+//! a worker chosen by hashing a key, so jobs with the same key run one at a
+//! time in submission order while other keys proceed in parallel.
+//!
+//! Queue depth and busy workers are exported as the `sentinel_queue_depth`
+//! and `sentinel_active_workers` gauges, labelled by pool name.
+
+use crate::{Error, Result};
+use std::{
+    collections::hash_map::DefaultHasher,
+    future::Future,
+    hash::{Hash, Hasher},
+    sync::{
+        atomic::{AtomicUsize, Ordering},
+        Arc,
+    },
+};
+use tokio::{
+    sync::{mpsc, Mutex},
+    task::JoinHandle,
+};
+use tracing::{debug, error};
+
+/// Worker pool configuration
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct WorkerPoolConfig {
+    /// Number of worker tasks
+    pub workers: usize,
+    /// Jobs that may wait in the queue before `submit` blocks
+    pub queue_capacity: usize,
+}
+
+impl Default for WorkerPoolConfig {
+    fn default() -> Self {
+        Self {
+            workers: 4,
+            queue_capacity: 10_000,
+        }
+    }
+}
+
+type KeyFn<T> = Box<dyn Fn(&T) -> u64 + Send + Sync>;
+
+/// Pool of worker tasks consuming a bounded job queue
+pub struct WorkerPool<T> {
+    name: &'static str,
+    senders: Vec<mpsc::Sender<T>>,
+    key: Option<KeyFn<T>>,
+    next: AtomicUsize,
+    depth: Arc<AtomicUsize>,
+    handles: Vec<JoinHandle<()>>,
+}
+
+impl<T> std::fmt::Debug for WorkerPool<T> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("WorkerPool")
+            .field("name", &self.name)
+            .field("workers", &self.handles.len())
+            .field("ordered", &self.key.is_some())
+            .field("queue_depth", &self.depth.load(Ordering::Relaxed))
+            .finish()
+    }
+}
+
+impl<T: Send + 'static> WorkerPool<T> {
+    /// Spawn a pool whose workers share one queue
+    ///
+    /// Jobs start in submission order but may complete in any order.
+    pub fn spawn<F, Fut>(name: &'static str, config: &WorkerPoolConfig, handler: F) -> Self
+    where
+        F: Fn(T) -> Fut + Send + Sync + 'static,
+        Fut: Future<Output = ()> + Send + 'static,
+    {
+        let workers = config.workers.max(1);
+        let (tx, rx) = mpsc::channel(config.queue_capacity.max(1));
+        let rx = Arc::new(Mutex::new(rx));
+        let handler = Arc::new(handler);
+        let depth = Arc::new(AtomicUsize::new(0));
+        let active = Arc::new(AtomicUsize::new(0));
+
+        let handles = (0..workers)
+            .map(|worker_id| {
+                let rx = Arc::clone(&rx);
+                let handler = Arc::clone(&handler);
+                let depth = Arc::clone(&depth);
+                let active = Arc::clone(&active);
+                tokio::spawn(async move {
+                    loop {
+                        let job = rx.lock().await.recv().await;
+                        let Some(job) = job else { break };
+                        Self::run(name, job, &*handler, &depth, &active).await;
+                    }
+                    debug!(pool = name, worker_id, "Worker stopped");
+                })
+            })
+            .collect();
+
+        Self {
+            name,
+            senders: vec![tx],
+            key: None,
+            next: AtomicUsize::new(0),
+            depth,
+            handles,
+        }
+    }
+
+    /// Spawn a pool that runs jobs with the same key in submission order
+    ///
+    /// Each worker owns a queue of `queue_capacity / workers` jobs; a job
+    /// goes to the worker selected by hashing `key(&job)`.
+    pub fn spawn_ordered<F, Fut, K, KF>(
+        name: &'static str,
+        config: &WorkerPoolConfig,
+        key: KF,
+        handler: F,
+    ) -> Self
+    where
+        F: Fn(T) -> Fut + Send + Sync + 'static,
+        Fut: Future<Output = ()> + Send + 'static,
+        K: Hash,
+        KF: Fn(&T) -> K + Send + Sync + 'static,
+    {
+        let workers = config.workers.max(1);
+        let capacity = (config.queue_capacity / workers).max(1);
+        let handler = Arc::new(handler);
+        let depth = Arc::new(AtomicUsize::new(0));
+        let active = Arc::new(AtomicUsize::new(0));
+
+        let (senders, handles) = (0..workers)
+            .map(|worker_id| {
+                let (tx, mut rx) = mpsc::channel(capacity);
+                let handler = Arc::clone(&handler);
+                let depth = Arc::clone(&depth);
+                let active = Arc::clone(&active);
+                let handle = tokio::spawn(async move {
+                    while let Some(job) = rx.recv().await {
+                        Self::run(name, job, &*handler, &depth, &active).await;
+                    }
+                    debug!(pool = name, worker_id, "Worker stopped");
+                });
+                (tx, handle)
+            })
+            .unzip();
+
+        Self {
+            name,
+            senders,
+            key: Some(Box::new(move |job| {
+                let mut hasher = DefaultHasher::new();
+                key(job).hash(&mut hasher);
+                hasher.finish()
+            })),
+            next: AtomicUsize::new(0),
+            depth,
+            handles,
+        }
+    }
+
+    async fn run<F, Fut>(
+        name: &'static str,
+        job: T,
+        handler: &F,
+        depth: &AtomicUsize,
+        active: &AtomicUsize,
+    ) where
+        F: Fn(T) -> Fut,
+        Fut: Future<Output = ()>,
+    {
+        let queued = depth.fetch_sub(1, Ordering::Relaxed) - 1;
+        metrics::gauge!("sentinel_queue_depth", "pool" => name).set(queued as f64);
+
+        let busy = active.fetch_add(1, Ordering::Relaxed) + 1;
+        metrics::gauge!("sentinel_active_workers", "pool" => name).set(busy as f64);
+
+        handler(job).await;
+
+        let busy = active.fetch_sub(1, Ordering::Relaxed) - 1;
+        metrics::gauge!("sentinel_active_workers", "pool" => name).set(busy as f64);
+        metrics::counter!("sentinel_worker_jobs_total", "pool" => name).increment(1);
+    }
+
+    /// Queue a job, waiting while the queue is full
+    pub async fn submit(&self, job: T) -> Result<()> {
+        let index = match &self.key {
+            Some(key) => (key(&job) % self.senders.len() as u64) as usize,
+            None => self.next.fetch_add(1, Ordering::Relaxed) % self.senders.len(),
+        };
+
+        let queued = self.depth.fetch_add(1, Ordering::Relaxed) + 1;
+        metrics::gauge!("sentinel_queue_depth", "pool" => self.name).set(queued as f64);
+
+        if self.senders[index].send(job).await.is_err() {
+            self.depth.fetch_sub(1, Ordering::Relaxed);
+            return Err(Error::internal(format!(
+                "Worker pool '{}' is shut down",
+                self.name
+            )));
+        }
+        Ok(())
+    }
+
+    /// Jobs waiting to be picked up by a worker
+    pub fn queue_depth(&self) -> usize {
+        self.depth.load(Ordering::Relaxed)
+    }
+
+    /// Number of worker tasks
+    pub fn workers(&self) -> usize {
+        self.handles.len()
+    }
+
+    /// Stop accepting jobs and wait for queued jobs to finish
+    pub async fn shutdown(mut self) {
+        self.senders.clear();
+        for handle in self.handles.drain(..) {
+            if let Err(e) = handle.await {
+                error!(pool = self.name, "Worker join error: {}", e);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::Duration;
+
+    #[tokio::test]
+    async fn test_jobs_run_concurrently() {
+        let config = WorkerPoolConfig {
+            workers: 4,
+            queue_capacity: 16,
+        };
+        let running = Arc::new(AtomicUsize::new(0));
+        let peak = Arc::new(AtomicUsize::new(0));
+
+        let pool = {
+            let running = Arc::clone(&running);
+            let peak = Arc::clone(&peak);
+            WorkerPool::spawn("test", &config, move |_: usize| {
+                let running = Arc::clone(&running);
+                let peak = Arc::clone(&peak);
+                async move {
+                    let now = running.fetch_add(1, Ordering::SeqCst) + 1;
+                    peak.fetch_max(now, Ordering::SeqCst);
+                    tokio::time::sleep(Duration::from_millis(20)).await;
+                    running.fetch_sub(1, Ordering::SeqCst);
+                }
+            })
+        };
+
+        for job in 0..8 {
+            pool.submit(job).await.unwrap();
+        }
+        pool.shutdown().await;
+
+        assert!(peak.load(Ordering::SeqCst) > 1);
+        assert!(peak.load(Ordering::SeqCst) <= 4);
+        assert_eq!(running.load(Ordering::SeqCst), 0);
+    }
+
+    #[tokio::test]
+    async fn test_ordered_per_key() {
+        let config = WorkerPoolConfig {
+            workers: 3,
+            queue_capacity: 30,
+        };
+        let seen = Arc::new(std::sync::Mutex::new(Vec::new()));
+
+        let pool = {
+            let seen = Arc::clone(&seen);
+            WorkerPool::spawn_ordered(
+                "test",
+                &config,
+                |job: &(String, usize)| job.0.clone(),
+                move |job: (String, usize)| {
+                    let seen = Arc::clone(&seen);
+                    async move {
+                        // Later jobs finish faster, so only the queue
+                        // keeps them in order
+                        tokio::time::sleep(Duration::from_millis(10 - job.1 as u64)).await;
+                        seen.lock().unwrap().push(job);
+                    }
+                },
+            )
+        };
+
+        for i in 0..10 {
+            for key in ["a", "b", "c", "d"] {
+                pool.submit((key.to_string(), i)).await.unwrap();
+            }
+        }
+        assert_eq!(pool.workers(), 3);
+        pool.shutdown().await;
+
+        let seen = seen.lock().unwrap();
+        assert_eq!(seen.len(), 40);
+        for key in ["a", "b", "c", "d"] {
+            let order: Vec<usize> = seen.iter().filter(|j| j.0 == key).map(|j| j.1).collect();
+            assert_eq!(order, (0..10).collect::<Vec<_>>());
+        }
+    }
+
+    #[tokio::test]
+    async fn test_queue_depth() {
+        let config = WorkerPoolConfig {
+            workers: 1,
+            queue_capacity: 8,
+        };
+        let (release, gate) = tokio::sync::watch::channel(false);
+        let pool = WorkerPool::spawn("test", &config, move |_: usize| {
+            let mut gate = gate.clone();
+            async move {
+                let _ = gate.wait_for(|open| *open).await;
+            }
+        });
+
+        for job in 0..4 {
+            pool.submit(job).await.unwrap();
+        }
+        // One job is held by the worker; the rest are queued
+        tokio::time::sleep(Duration::from_millis(20)).await;
+        assert_eq!(pool.queue_depth(), 3);
+
+        release.send(true).unwrap();
+        pool.shutdown().await;
+    }
+}
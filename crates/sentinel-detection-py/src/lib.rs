@@ -0,0 +1,480 @@
+//! # Sentinel Detection (Python)
+//!
+//! PyO3 bindings for LLM-Sentinel's baselines and statistical detectors.
+//!
+//! This module exposes:
+//! - `BaselineManager`, the rolling per-service/model/metric baselines
+//! - `ZScoreDetector`, `IqrDetector`, `MadDetector` and `CusumDetector`
+//! - `baseline_from_values` for one-off baseline statistics
+//!
+//! The classes wrap the production Rust types directly, so replaying
+//! historical telemetry (e.g. rows of a pandas DataFrame) through them gives
+//! exactly the anomalies the service would have raised. Anomalies and
+//! baselines are returned as plain dicts in their JSON shape.
+
+#![warn(missing_debug_implementations, rust_2018_idioms, unreachable_pub)]
+
+use chrono::{DateTime, TimeZone, Utc};
+use futures::executor::block_on;
+use llm_sentinel_core::{
+    events::{PromptInfo, ResponseInfo, TelemetryEvent},
+    types::{ModelId, ServiceId},
+    window::WindowSpec,
+};
+use llm_sentinel_detection::{
+    baseline::{self, BaselineKey},
+    detectors::{
+        cusum::{CusumConfig, CusumDetector as RustCusumDetector},
+        iqr::{IqrConfig, IqrDetector as RustIqrDetector},
+        mad::{MadConfig, MadDetector as RustMadDetector},
+        zscore::{ZScoreConfig, ZScoreDetector as RustZScoreDetector},
+        DetectionConfig,
+    },
+    Detector as RustDetector,
+};
+use pyo3::{
+    exceptions::{PyRuntimeError, PyValueError},
+    prelude::*,
+    types::PyDict,
+};
+use serde::Serialize;
+use std::sync::Arc;
+
+/// Convert a Rust error into a Python `RuntimeError`
+fn runtime_error(err: impl std::fmt::Display) -> PyErr {
+    PyRuntimeError::new_err(err.to_string())
+}
+
+/// Convert a serializable value into the equivalent Python object
+fn to_python<T: Serialize>(py: Python<'_>, value: &T) -> PyResult<PyObject> {
+    let json = serde_json::to_string(value).map_err(runtime_error)?;
+    Ok(py.import_bound("json")?.call_method1("loads", (json,))?.unbind())
+}
+
+/// Timestamp from seconds since the Unix epoch (now when absent)
+fn timestamp(secs: Option<f64>) -> PyResult<DateTime<Utc>> {
+    match secs {
+        None => Ok(Utc::now()),
+        Some(secs) => Utc
+            .timestamp_millis_opt((secs * 1000.0).round() as i64)
+            .single()
+            .ok_or_else(|| PyValueError::new_err(format!("invalid timestamp: {secs}"))),
+    }
+}
+
+/// Rolling baselines keyed by service, model and metric
+#[pyclass(module = "sentinel_detection")]
+#[derive(Debug, Clone)]
+struct BaselineManager {
+    inner: Arc<baseline::BaselineManager>,
+}
+
+#[pymethods]
+impl BaselineManager {
+    /// Keep the last `window_size` samples, optionally no older than
+    /// `max_age_secs` relative to the newest sample
+    #[new]
+    #[pyo3(signature = (window_size = 1000, max_age_secs = None))]
+    fn new(window_size: usize, max_age_secs: Option<u64>) -> Self {
+        let mut spec = WindowSpec::samples(window_size);
+        if let Some(secs) = max_age_secs {
+            spec = spec.with_max_age_secs(secs);
+        }
+        Self {
+            inner: Arc::new(baseline::BaselineManager::with_spec(spec)),
+        }
+    }
+
+    /// Add a sample to a baseline
+    #[pyo3(signature = (service, model, metric, value, timestamp = None))]
+    fn update(
+        &self,
+        service: &str,
+        model: &str,
+        metric: &str,
+        value: f64,
+        timestamp: Option<f64>,
+    ) -> PyResult<()> {
+        let key = BaselineKey::new(ServiceId::new(service), ModelId::new(model), metric);
+        self.inner
+            .update_at(key, value, self::timestamp(timestamp)?)
+            .map_err(runtime_error)
+    }
+
+    /// Baseline statistics as a dict, or `None` before enough samples
+    fn get(&self, py: Python<'_>, service: &str, model: &str, metric: &str) -> PyResult<PyObject> {
+        let key = BaselineKey::new(ServiceId::new(service), ModelId::new(model), metric);
+        match self.inner.get(&key) {
+            Some(baseline) => to_python(py, &baseline),
+            None => Ok(py.None()),
+        }
+    }
+
+    /// Check whether a baseline has enough samples to detect against
+    fn has_valid_baseline(&self, service: &str, model: &str, metric: &str) -> bool {
+        let key = BaselineKey::new(ServiceId::new(service), ModelId::new(model), metric);
+        self.inner.has_valid_baseline(&key)
+    }
+
+    /// `(service, model, metric)` tuples with a baseline
+    fn keys(&self) -> Vec<(String, String, String)> {
+        self.inner
+            .keys()
+            .into_iter()
+            .map(|key| (key.service.to_string(), key.model.to_string(), key.metric))
+            .collect()
+    }
+
+    /// Drop all baselines
+    fn clear_all(&self) -> PyResult<()> {
+        self.inner.clear_all().map_err(runtime_error)
+    }
+
+    fn __repr__(&self) -> String {
+        format!(
+            "BaselineManager(window={}, baselines={})",
+            self.inner.spec(),
+            self.inner.keys().len()
+        )
+    }
+}
+
+/// Base class of the detectors
+///
+/// Events are passed as keyword arguments; `detect` checks an event against
+/// the current baselines, `update` feeds it into them, and `process` does
+/// both in the detection engine's order.
+#[pyclass(module = "sentinel_detection", subclass)]
+struct Detector {
+    inner: Box<dyn RustDetector>,
+}
+
+impl std::fmt::Debug for Detector {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("Detector")
+            .field("name", &self.inner.name())
+            .finish()
+    }
+}
+
+/// Build a telemetry event from the keyword arguments shared by detectors
+#[allow(clippy::too_many_arguments)]
+fn event(
+    latency_ms: f64,
+    cost_usd: f64,
+    prompt_tokens: u32,
+    completion_tokens: u32,
+    service: &str,
+    model: &str,
+    timestamp: Option<f64>,
+) -> PyResult<TelemetryEvent> {
+    let mut event = TelemetryEvent::new(
+        ServiceId::new(service),
+        ModelId::new(model),
+        PromptInfo {
+            text: String::new(),
+            tokens: prompt_tokens,
+            embedding: None,
+        },
+        ResponseInfo {
+            text: String::new(),
+            tokens: completion_tokens,
+            finish_reason: "stop".to_string(),
+            embedding: None,
+        },
+        latency_ms,
+        cost_usd,
+    );
+    event.timestamp = self::timestamp(timestamp)?;
+    Ok(event)
+}
+
+#[pymethods]
+impl Detector {
+    /// Anomaly dict if the event is anomalous, otherwise `None`
+    #[pyo3(signature = (
+        latency_ms,
+        cost_usd = 0.0,
+        prompt_tokens = 0,
+        completion_tokens = 0,
+        service = "default",
+        model = "default",
+        timestamp = None,
+    ))]
+    #[allow(clippy::too_many_arguments)]
+    fn detect(
+        &self,
+        py: Python<'_>,
+        latency_ms: f64,
+        cost_usd: f64,
+        prompt_tokens: u32,
+        completion_tokens: u32,
+        service: &str,
+        model: &str,
+        timestamp: Option<f64>,
+    ) -> PyResult<PyObject> {
+        let event = event(
+            latency_ms,
+            cost_usd,
+            prompt_tokens,
+            completion_tokens,
+            service,
+            model,
+            timestamp,
+        )?;
+        match block_on(self.inner.detect(&event)).map_err(runtime_error)? {
+            Some(anomaly) => to_python(py, &anomaly),
+            None => Ok(py.None()),
+        }
+    }
+
+    /// Feed the event into the detector's baselines
+    #[pyo3(signature = (
+        latency_ms,
+        cost_usd = 0.0,
+        prompt_tokens = 0,
+        completion_tokens = 0,
+        service = "default",
+        model = "default",
+        timestamp = None,
+    ))]
+    #[allow(clippy::too_many_arguments)]
+    fn update(
+        &mut self,
+        latency_ms: f64,
+        cost_usd: f64,
+        prompt_tokens: u32,
+        completion_tokens: u32,
+        service: &str,
+        model: &str,
+        timestamp: Option<f64>,
+    ) -> PyResult<()> {
+        let event = event(
+            latency_ms,
+            cost_usd,
+            prompt_tokens,
+            completion_tokens,
+            service,
+            model,
+            timestamp,
+        )?;
+        block_on(self.inner.update(&event)).map_err(runtime_error)
+    }
+
+    /// Detect, then update, as the detection engine does per event
+    #[pyo3(signature = (
+        latency_ms,
+        cost_usd = 0.0,
+        prompt_tokens = 0,
+        completion_tokens = 0,
+        service = "default",
+        model = "default",
+        timestamp = None,
+    ))]
+    #[allow(clippy::too_many_arguments)]
+    fn process(
+        &mut self,
+        py: Python<'_>,
+        latency_ms: f64,
+        cost_usd: f64,
+        prompt_tokens: u32,
+        completion_tokens: u32,
+        service: &str,
+        model: &str,
+        timestamp: Option<f64>,
+    ) -> PyResult<PyObject> {
+        let event = event(
+            latency_ms,
+            cost_usd,
+            prompt_tokens,
+            completion_tokens,
+            service,
+            model,
+            timestamp,
+        )?;
+        let anomaly = block_on(self.inner.detect(&event)).map_err(runtime_error)?;
+        block_on(self.inner.update(&event)).map_err(runtime_error)?;
+        match anomaly {
+            Some(anomaly) => to_python(py, &anomaly),
+            None => Ok(py.None()),
+        }
+    }
+
+    /// Clear detector state and its baselines
+    fn reset(&mut self) -> PyResult<()> {
+        block_on(self.inner.reset()).map_err(runtime_error)
+    }
+
+    /// Detector counters as a dict
+    fn stats<'py>(&self, py: Python<'py>) -> PyResult<Bound<'py, PyDict>> {
+        let stats = self.inner.stats();
+        let dict = PyDict::new_bound(py);
+        dict.set_item("events_processed", stats.events_processed)?;
+        dict.set_item("anomalies_detected", stats.anomalies_detected)?;
+        dict.set_item("detection_rate", stats.detection_rate)?;
+        dict.set_item("avg_confidence", stats.avg_confidence)?;
+        Ok(dict)
+    }
+
+    /// Detector name
+    #[getter]
+    fn name(&self) -> String {
+        self.inner.name().to_string()
+    }
+
+    fn __repr__(&self) -> String {
+        format!("Detector(name={:?})", self.inner.name())
+    }
+}
+
+/// Detection settings shared by all detectors
+fn detection_config(min_samples: usize, update_baseline: bool) -> DetectionConfig {
+    DetectionConfig {
+        min_samples,
+        update_baseline,
+    }
+}
+
+/// Shared baselines, or fresh ones sized `window_size`
+fn baselines(manager: Option<&BaselineManager>) -> Arc<baseline::BaselineManager> {
+    manager
+        .map(|manager| Arc::clone(&manager.inner))
+        .unwrap_or_else(|| Arc::new(baseline::BaselineManager::new(1000)))
+}
+
+/// Latency, token and cost outliers by standard deviations from the mean
+#[pyclass(module = "sentinel_detection", extends = Detector)]
+#[derive(Debug)]
+struct ZScoreDetector;
+
+#[pymethods]
+impl ZScoreDetector {
+    #[new]
+    #[pyo3(signature = (
+        baselines = None,
+        threshold = 3.0,
+        min_samples = 10,
+        update_baseline = true,
+    ))]
+    fn new(
+        baselines: Option<&BaselineManager>,
+        threshold: f64,
+        min_samples: usize,
+        update_baseline: bool,
+    ) -> (Self, Detector) {
+        let config = ZScoreConfig {
+            threshold,
+            detection: detection_config(min_samples, update_baseline),
+        };
+        let inner = RustZScoreDetector::new(config, self::baselines(baselines));
+        (Self, Detector { inner: Box::new(inner) })
+    }
+}
+
+/// Latency outliers beyond `multiplier` interquartile ranges
+#[pyclass(module = "sentinel_detection", extends = Detector)]
+#[derive(Debug)]
+struct IqrDetector;
+
+#[pymethods]
+impl IqrDetector {
+    #[new]
+    #[pyo3(signature = (
+        baselines = None,
+        multiplier = 1.5,
+        min_samples = 10,
+        update_baseline = true,
+    ))]
+    fn new(
+        baselines: Option<&BaselineManager>,
+        multiplier: f64,
+        min_samples: usize,
+        update_baseline: bool,
+    ) -> (Self, Detector) {
+        let config = IqrConfig {
+            multiplier,
+            detection: detection_config(min_samples, update_baseline),
+        };
+        let inner = RustIqrDetector::new(config, self::baselines(baselines));
+        (Self, Detector { inner: Box::new(inner) })
+    }
+}
+
+/// Latency outliers by modified Z-score over the median absolute deviation
+#[pyclass(module = "sentinel_detection", extends = Detector)]
+#[derive(Debug)]
+struct MadDetector;
+
+#[pymethods]
+impl MadDetector {
+    #[new]
+    #[pyo3(signature = (
+        baselines = None,
+        threshold = 3.5,
+        min_samples = 10,
+        update_baseline = true,
+    ))]
+    fn new(
+        baselines: Option<&BaselineManager>,
+        threshold: f64,
+        min_samples: usize,
+        update_baseline: bool,
+    ) -> (Self, Detector) {
+        let config = MadConfig {
+            threshold,
+            detection: detection_config(min_samples, update_baseline),
+        };
+        let inner = RustMadDetector::new(config, self::baselines(baselines));
+        (Self, Detector { inner: Box::new(inner) })
+    }
+}
+
+/// Sustained cost shifts by cumulative sum
+#[pyclass(module = "sentinel_detection", extends = Detector)]
+#[derive(Debug)]
+struct CusumDetector;
+
+#[pymethods]
+impl CusumDetector {
+    #[new]
+    #[pyo3(signature = (
+        baselines = None,
+        threshold = 5.0,
+        slack = 0.5,
+        min_samples = 10,
+        update_baseline = true,
+    ))]
+    fn new(
+        baselines: Option<&BaselineManager>,
+        threshold: f64,
+        slack: f64,
+        min_samples: usize,
+        update_baseline: bool,
+    ) -> (Self, Detector) {
+        let config = CusumConfig {
+            threshold,
+            slack,
+            detection: detection_config(min_samples, update_baseline),
+        };
+        let inner = RustCusumDetector::new(config, self::baselines(baselines));
+        (Self, Detector { inner: Box::new(inner) })
+    }
+}
+
+/// Baseline statistics of a sequence of values, as a dict
+#[pyfunction]
+fn baseline_from_values(py: Python<'_>, values: Vec<f64>) -> PyResult<PyObject> {
+    to_python(py, &baseline::Baseline::from_data(&values))
+}
+
+#[pymodule]
+fn sentinel_detection(m: &Bound<'_, PyModule>) -> PyResult<()> {
+    m.add_class::<BaselineManager>()?;
+    m.add_class::<Detector>()?;
+    m.add_class::<ZScoreDetector>()?;
+    m.add_class::<IqrDetector>()?;
+    m.add_class::<MadDetector>()?;
+    m.add_class::<CusumDetector>()?;
+    m.add_function(wrap_pyfunction!(baseline_from_values, m)?)?;
+    Ok(())
+}
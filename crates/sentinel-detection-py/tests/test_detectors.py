@@ -0,0 +1,37 @@
+"""Smoke tests for the sentinel_detection extension module.
+
+Build with `maturin develop` before running `pytest`.
+"""
+
+import sentinel_detection as sd
+
+
+def test_zscore_flags_latency_spike():
+    baselines = sd.BaselineManager(window_size=100)
+    detector = sd.ZScoreDetector(baselines, threshold=3.0)
+
+    for i in range(50):
+        assert detector.process(latency_ms=100.0 + i % 5, service="chat", model="gpt-4") is None
+
+    anomaly = detector.detect(latency_ms=1000.0, service="chat", model="gpt-4")
+    assert anomaly is not None
+    assert anomaly["detection_method"] == "z_score"
+    assert anomaly["details"]["metric"] == "latency_ms"
+    assert baselines.has_valid_baseline("chat", "gpt-4", "latency_ms")
+
+
+def test_baseline_from_values():
+    baseline = sd.baseline_from_values([1.0, 2.0, 3.0, 4.0, 5.0])
+    assert baseline["mean"] == 3.0
+    assert baseline["median"] == 3.0
+    assert baseline["sample_count"] == 5
+
+
+def test_detectors_share_baselines():
+    baselines = sd.BaselineManager()
+    sd.MadDetector(baselines).update(latency_ms=120.0, timestamp=1_700_000_000.0)
+    assert baselines.keys() == []
+
+    for _ in range(9):
+        sd.IqrDetector(baselines).update(latency_ms=120.0)
+    assert baselines.keys() == [("default", "default", "latency_ms")]
@@ -0,0 +1,106 @@
+//! Detection hot path benchmarks.
+//!
+//! Run with `cargo bench -p llm-sentinel-detection --bench hot_path`.
+//! Besides criterion's timings for 10k events, prints the heap allocations
+//! per event of handing a telemetry event to another pipeline stage (a
+//! clone) and of running it through the default detection engine.
+
+use criterion::{criterion_group, criterion_main, BatchSize, Criterion, Throughput};
+use llm_sentinel_core::events::TelemetryEvent;
+use llm_sentinel_detection::prelude::{DetectionEngine, EngineConfig};
+use llm_sentinel_fixtures::EventBuilder;
+use std::alloc::{GlobalAlloc, Layout, System};
+use std::hint::black_box;
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+/// System allocator counting allocations
+struct CountingAllocator;
+
+static ALLOCATIONS: AtomicUsize = AtomicUsize::new(0);
+
+unsafe impl GlobalAlloc for CountingAllocator {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        ALLOCATIONS.fetch_add(1, Ordering::Relaxed);
+        System.alloc(layout)
+    }
+
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        System.dealloc(ptr, layout)
+    }
+
+    unsafe fn realloc(&self, ptr: *mut u8, layout: Layout, new_size: usize) -> *mut u8 {
+        ALLOCATIONS.fetch_add(1, Ordering::Relaxed);
+        System.realloc(ptr, layout, new_size)
+    }
+}
+
+#[global_allocator]
+static GLOBAL: CountingAllocator = CountingAllocator;
+
+/// One second of traffic at 10k events/sec
+const EVENTS: usize = 10_000;
+
+fn event(i: usize) -> TelemetryEvent {
+    EventBuilder::new()
+        .service("chat")
+        .prompt("How do I reset my password? ".repeat(64))
+        .prompt_tokens(400)
+        .prompt_embedding(vec![0.1; 1536])
+        .response("Open settings and choose reset. ".repeat(64))
+        .response_tokens(600)
+        .latency_ms(100.0 + (i % 50) as f64)
+        // Keyed by environment, so detectors see a rekeyed copy of each event
+        .environment("production")
+        .build()
+}
+
+/// Heap allocations per event made by `f`
+fn allocations_per_event(f: impl FnOnce()) -> f64 {
+    let before = ALLOCATIONS.load(Ordering::Relaxed);
+    f();
+    (ALLOCATIONS.load(Ordering::Relaxed) - before) as f64 / EVENTS as f64
+}
+
+fn bench_hot_path(c: &mut Criterion) {
+    let events: Vec<TelemetryEvent> = (0..EVENTS).map(event).collect();
+    let runtime = tokio::runtime::Builder::new_current_thread()
+        .build()
+        .unwrap();
+    let detect = |engine: &mut DetectionEngine| {
+        runtime.block_on(async {
+            for event in &events {
+                black_box(engine.process(event).await.unwrap());
+            }
+        })
+    };
+
+    let clone = allocations_per_event(|| {
+        for event in &events {
+            black_box(event.clone());
+        }
+    });
+    let mut engine = DetectionEngine::new(EngineConfig::default()).unwrap();
+    let detection = allocations_per_event(|| detect(&mut engine));
+    println!("allocations per event: clone {clone:.1}, detection {detection:.1}");
+
+    let mut group = c.benchmark_group("hot_path");
+    group.throughput(Throughput::Elements(EVENTS as u64));
+    group.bench_function("clone_10k_events", |b| {
+        b.iter(|| {
+            for event in &events {
+                black_box(event.clone());
+            }
+        })
+    });
+    group.bench_function("detect_10k_events", |b| {
+        b.iter_batched(
+            || DetectionEngine::new(EngineConfig::default()).unwrap(),
+            |mut engine| detect(&mut engine),
+            BatchSize::LargeInput,
+        )
+    });
+    group.finish();
+}
+
+criterion_group!(benches, bench_hot_path);
+criterion_main!(benches);
@@ -0,0 +1,706 @@
+//! Provider and model availability tracking.
+//!
+//! Telemetry is counted per provider, model and service in fixed buckets,
+//! giving availability and error-rate time series for every model. When a
+//! service's error rate over the evaluation window crosses the threshold the
+//! tracker raises an [`AnomalyType::ErrorRateIncrease`] alert. If several
+//! services on the same provider degrade together the alert is scoped to a
+//! provider-wide outage instead of the individual services.
+
+use chrono::{DateTime, TimeZone, Utc};
+use llm_sentinel_core::{
+    clock::Clock,
+    events::{AnomalyContext, AnomalyDetails, AnomalyEvent, TelemetryEvent},
+    types::{AnomalyType, DetectionMethod, ModelId, ServiceId, Severity},
+    Error, Result,
+};
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, BTreeSet, HashMap, HashSet, VecDeque};
+use std::sync::Mutex;
+use tracing::{info, warn};
+
+/// Availability tracker configuration
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AvailabilityTrackerConfig {
+    /// Bucket width (seconds)
+    pub bucket_secs: u64,
+    /// How long buckets are kept for the time series (seconds)
+    pub retention_secs: u64,
+    /// Window the error rate is evaluated over (seconds)
+    pub window_secs: u64,
+    /// Error rate at which a service counts as degraded
+    pub error_rate_threshold: f64,
+    /// Minimum requests in the window before a service is evaluated
+    pub min_requests: u64,
+    /// Degraded services on one provider that make a provider-wide outage
+    pub provider_outage_min_services: usize,
+    /// Metadata key holding the provider name
+    pub provider_key: String,
+    /// Clock retention and evaluation windows are measured against
+    #[serde(skip)]
+    pub clock: Clock,
+}
+
+impl Default for AvailabilityTrackerConfig {
+    fn default() -> Self {
+        Self {
+            bucket_secs: 60,
+            retention_secs: 86400,
+            window_secs: 300,
+            error_rate_threshold: 0.2,
+            min_requests: 20,
+            provider_outage_min_services: 2,
+            provider_key: "provider".to_string(),
+            clock: Clock::system(),
+        }
+    }
+}
+
+impl AvailabilityTrackerConfig {
+    /// Validate the configuration
+    pub fn validate(&self) -> Result<()> {
+        if self.bucket_secs == 0 {
+            return Err(Error::config("Availability bucket_secs must be positive"));
+        }
+        if self.window_secs < self.bucket_secs || self.retention_secs < self.window_secs {
+            return Err(Error::config(
+                "Availability windows must satisfy bucket_secs <= window_secs <= retention_secs",
+            ));
+        }
+        if !(self.error_rate_threshold > 0.0 && self.error_rate_threshold <= 1.0) {
+            return Err(Error::config(
+                "Availability error_rate_threshold must be in (0, 1]",
+            ));
+        }
+        if self.provider_outage_min_services < 2 {
+            return Err(Error::config(
+                "Availability provider_outage_min_services must be at least 2",
+            ));
+        }
+        Ok(())
+    }
+}
+
+/// Infer the provider from well-known model name prefixes
+pub fn infer_provider(model: &ModelId) -> &'static str {
+    let model = model.as_str().to_ascii_lowercase();
+    let model = model.rsplit('/').next().unwrap_or_default();
+
+    const PREFIXES: &[(&str, &str)] = &[
+        ("gpt-", "openai"),
+        ("o1", "openai"),
+        ("o3", "openai"),
+        ("text-embedding-", "openai"),
+        ("claude", "anthropic"),
+        ("gemini", "google"),
+        ("palm", "google"),
+        ("command", "cohere"),
+        ("mistral", "mistral"),
+        ("mixtral", "mistral"),
+        ("llama", "meta"),
+    ];
+
+    PREFIXES
+        .iter()
+        .find(|(prefix, _)| model.starts_with(prefix))
+        .map_or("unknown", |(_, provider)| provider)
+}
+
+/// One point of an availability time series
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AvailabilityPoint {
+    /// Bucket start
+    pub start: DateTime<Utc>,
+    /// Requests in the bucket
+    pub total: u64,
+    /// Failed requests in the bucket
+    pub errors: u64,
+    /// Ratio of successful requests
+    pub availability: f64,
+}
+
+/// Availability of one model over the requested range
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ModelAvailability {
+    /// Provider name
+    pub provider: String,
+    /// Model identifier
+    pub model: ModelId,
+    /// Requests in the range
+    pub total: u64,
+    /// Failed requests in the range
+    pub errors: u64,
+    /// Ratio of successful requests (1.0 with no traffic)
+    pub availability: f64,
+    /// Ratio of failed requests
+    pub error_rate: f64,
+    /// Whether a provider-wide outage is currently firing
+    pub provider_outage: bool,
+    /// Services currently degraded on this model
+    pub degraded_services: Vec<ServiceId>,
+    /// Per-bucket series
+    pub points: Vec<AvailabilityPoint>,
+}
+
+/// Series key: provider, model, service
+#[derive(Debug, Clone, PartialEq, Eq, Hash, PartialOrd, Ord)]
+struct SeriesKey {
+    provider: String,
+    model: ModelId,
+    service: ServiceId,
+}
+
+/// Request counts for one bucket
+#[derive(Debug, Clone, Copy)]
+struct Bucket {
+    start: i64,
+    total: u64,
+    errors: u64,
+}
+
+/// Mutable tracker state
+#[derive(Debug, Default)]
+struct State {
+    series: HashMap<SeriesKey, VecDeque<Bucket>>,
+    /// Service-scoped alerts currently firing
+    degraded: HashSet<SeriesKey>,
+    /// Providers with an outage currently firing
+    outages: HashSet<String>,
+}
+
+/// Tracks availability and error rates per provider and model
+#[derive(Debug)]
+pub struct AvailabilityTracker {
+    config: AvailabilityTrackerConfig,
+    state: Mutex<State>,
+}
+
+impl AvailabilityTracker {
+    /// Create a new tracker
+    pub fn new(config: AvailabilityTrackerConfig) -> Result<Self> {
+        config.validate()?;
+        Ok(Self {
+            config,
+            state: Mutex::new(State::default()),
+        })
+    }
+
+    /// Get the configuration
+    pub fn config(&self) -> &AvailabilityTrackerConfig {
+        &self.config
+    }
+
+    /// Provider an event belongs to
+    pub fn provider_for(&self, event: &TelemetryEvent) -> String {
+        event
+            .metadata
+            .get(&self.config.provider_key)
+            .filter(|p| !p.is_empty())
+            .map(|p| p.to_ascii_lowercase())
+            .unwrap_or_else(|| infer_provider(&event.model).to_string())
+    }
+
+    /// Record an event and return alerts that started firing
+    pub fn record(&self, event: &TelemetryEvent) -> Vec<AnomalyEvent> {
+        self.record_at(event, self.config.clock.now())
+    }
+
+    /// Record an event evaluated at the given time
+    pub fn record_at(&self, event: &TelemetryEvent, now: DateTime<Utc>) -> Vec<AnomalyEvent> {
+        let bucket_secs = self.config.bucket_secs as i64;
+        let retention_start = now.timestamp() - self.config.retention_secs as i64;
+        let timestamp = event.timestamp.timestamp();
+        if timestamp <= retention_start || event.timestamp > now {
+            return Vec::new();
+        }
+
+        let key = SeriesKey {
+            provider: self.provider_for(event),
+            model: event.model.clone(),
+            service: event.service_name.clone(),
+        };
+
+        let mut state = self.state.lock().unwrap();
+        let buckets = state.series.entry(key.clone()).or_default();
+
+        let start = timestamp.div_euclid(bucket_secs) * bucket_secs;
+        let bucket = match buckets.iter().rposition(|b| b.start <= start) {
+            Some(i) if buckets[i].start == start => &mut buckets[i],
+            Some(i) => {
+                buckets.insert(
+                    i + 1,
+                    Bucket {
+                        start,
+                        total: 0,
+                        errors: 0,
+                    },
+                );
+                &mut buckets[i + 1]
+            }
+            None => {
+                buckets.push_front(Bucket {
+                    start,
+                    total: 0,
+                    errors: 0,
+                });
+                &mut buckets[0]
+            }
+        };
+        bucket.total += 1;
+        if event.has_errors() {
+            bucket.errors += 1;
+        }
+
+        self.prune(&mut state, now);
+        self.evaluate(&mut state, &key.provider, event, now)
+    }
+
+    /// Drop buckets past retention and series left empty
+    fn prune(&self, state: &mut State, now: DateTime<Utc>) {
+        let cutoff = now.timestamp() - self.config.retention_secs as i64;
+        let bucket_secs = self.config.bucket_secs as i64;
+        state.series.retain(|_, buckets| {
+            while buckets
+                .front()
+                .is_some_and(|b| b.start + bucket_secs <= cutoff)
+            {
+                buckets.pop_front();
+            }
+            !buckets.is_empty()
+        });
+    }
+
+    /// Request and error counts for a series over the trailing window
+    fn window_counts(&self, buckets: &VecDeque<Bucket>, now: DateTime<Utc>) -> (u64, u64) {
+        let cutoff = now.timestamp() - self.config.window_secs as i64;
+        let bucket_secs = self.config.bucket_secs as i64;
+        buckets
+            .iter()
+            .rev()
+            .take_while(|b| b.start + bucket_secs > cutoff)
+            .fold((0, 0), |(total, errors), b| {
+                (total + b.total, errors + b.errors)
+            })
+    }
+
+    /// Re-evaluate a provider and return alerts that started firing
+    fn evaluate(
+        &self,
+        state: &mut State,
+        provider: &str,
+        event: &TelemetryEvent,
+        now: DateTime<Utc>,
+    ) -> Vec<AnomalyEvent> {
+        // Degraded series on this provider, with their window counts
+        let mut degraded: BTreeMap<SeriesKey, (u64, u64)> = BTreeMap::new();
+        for (key, buckets) in state.series.iter().filter(|(k, _)| k.provider == provider) {
+            let (total, errors) = self.window_counts(buckets, now);
+            if total >= self.config.min_requests
+                && errors as f64 / total as f64 >= self.config.error_rate_threshold
+            {
+                degraded.insert(key.clone(), (total, errors));
+            }
+        }
+
+        let services: BTreeSet<&ServiceId> = degraded.keys().map(|k| &k.service).collect();
+        let outage = services.len() >= self.config.provider_outage_min_services;
+        let mut alerts = Vec::new();
+
+        if outage && state.outages.insert(provider.to_string()) {
+            alerts.push(self.outage_alert(provider, &degraded));
+        } else if !outage && state.outages.remove(provider) {
+            info!(provider = %provider, "Provider outage resolved");
+        }
+
+        // Service-scoped alerts are folded into a firing provider outage
+        let was_degraded: Vec<SeriesKey> = state
+            .degraded
+            .iter()
+            .filter(|k| k.provider == provider && !degraded.contains_key(*k))
+            .cloned()
+            .collect();
+        for key in was_degraded {
+            info!(
+                provider = %key.provider,
+                model = %key.model,
+                service = %key.service,
+                "Service availability recovered"
+            );
+            state.degraded.remove(&key);
+        }
+
+        for (key, (total, errors)) in &degraded {
+            if state.degraded.insert(key.clone()) && !outage {
+                alerts.push(self.service_alert(key, *total, *errors, event));
+            }
+        }
+
+        alerts
+    }
+
+    /// Alert for a single degraded service
+    fn service_alert(
+        &self,
+        key: &SeriesKey,
+        total: u64,
+        errors: u64,
+        event: &TelemetryEvent,
+    ) -> AnomalyEvent {
+        let error_rate = errors as f64 / total as f64;
+        warn!(
+            provider = %key.provider,
+            model = %key.model,
+            service = %key.service,
+            error_rate,
+            "Service error rate above availability threshold"
+        );
+
+        let mut alert = self.build_alert(
+            "service",
+            &key.provider,
+            key.service.clone(),
+            key.model.clone(),
+            error_rate,
+            total,
+        );
+        alert.context.trace_id = event.trace_id.clone();
+        alert.root_cause = Some(format!(
+            "{} of {} requests from {} to {} ({}) failed in the last {}s",
+            errors, total, key.service, key.model, key.provider, self.config.window_secs
+        ));
+        alert
+    }
+
+    /// Alert for a provider-wide outage
+    fn outage_alert(
+        &self,
+        provider: &str,
+        degraded: &BTreeMap<SeriesKey, (u64, u64)>,
+    ) -> AnomalyEvent {
+        let (total, errors) = degraded
+            .values()
+            .fold((0, 0), |(total, errors), (t, e)| (total + t, errors + e));
+        let error_rate = errors as f64 / total as f64;
+
+        let services: BTreeSet<&str> = degraded.keys().map(|k| k.service.as_str()).collect();
+        let models: BTreeSet<&str> = degraded.keys().map(|k| k.model.as_str()).collect();
+        let model = match models.iter().next() {
+            Some(model) if models.len() == 1 => ModelId::new(*model),
+            _ => ModelId::new("all"),
+        };
+
+        warn!(
+            provider = %provider,
+            services = services.len(),
+            error_rate,
+            "Provider-wide outage detected"
+        );
+
+        let mut alert = self.build_alert(
+            "provider",
+            provider,
+            ServiceId::new("all"),
+            model,
+            error_rate,
+            total,
+        );
+        alert
+            .details
+            .additional
+            .insert("affected_services".to_string(), serde_json::json!(services));
+        alert
+            .details
+            .additional
+            .insert("affected_models".to_string(), serde_json::json!(models));
+        alert.root_cause = Some(format!(
+            "Provider {} is failing for {} services ({:.0}% errors in the last {}s)",
+            provider,
+            services.len(),
+            error_rate * 100.0,
+            self.config.window_secs
+        ));
+        alert
+    }
+
+    /// Common alert construction
+    fn build_alert(
+        &self,
+        scope: &str,
+        provider: &str,
+        service: ServiceId,
+        model: ModelId,
+        error_rate: f64,
+        total: u64,
+    ) -> AnomalyEvent {
+        metrics::counter!(
+            "sentinel_availability_alerts_total",
+            "provider" => provider.to_string(),
+            "scope" => scope.to_string()
+        )
+        .increment(1);
+
+        let threshold = self.config.error_rate_threshold;
+        let severity = if scope == "provider" {
+            Severity::Critical
+        } else {
+            Severity::High
+        };
+
+        let mut additional = HashMap::new();
+        additional.insert("scope".to_string(), serde_json::json!(scope));
+        additional.insert("provider".to_string(), serde_json::json!(provider));
+
+        let mut context_additional = HashMap::new();
+        context_additional.insert("scope".to_string(), scope.to_string());
+        context_additional.insert("provider".to_string(), provider.to_string());
+
+        AnomalyEvent::new(
+            severity,
+            AnomalyType::ErrorRateIncrease,
+            service,
+            model,
+            DetectionMethod::Custom("availability".to_string()),
+            (0.5 + (error_rate - threshold) / (1.0 - threshold).max(f64::EPSILON) / 2.0)
+                .clamp(0.5, 1.0),
+            AnomalyDetails {
+                metric: "error_rate".to_string(),
+                value: error_rate,
+                baseline: 0.0,
+                threshold,
+                deviation_sigma: None,
+                additional,
+                observations: Vec::new(),
+            },
+            AnomalyContext {
+                trace_id: None,
+                user_id: None,
+                region: None,
+                time_window: format!("{}s", self.config.window_secs),
+                sample_count: total as usize,
+                window: None,
+                additional: context_additional,
+            },
+        )
+    }
+
+    /// Availability series per model over the trailing range
+    pub fn availability(
+        &self,
+        provider: Option<&str>,
+        model: Option<&ModelId>,
+        range_secs: u64,
+    ) -> Vec<ModelAvailability> {
+        self.availability_at(provider, model, range_secs, self.config.clock.now())
+    }
+
+    /// Availability series per model at the given time
+    pub fn availability_at(
+        &self,
+        provider: Option<&str>,
+        model: Option<&ModelId>,
+        range_secs: u64,
+        now: DateTime<Utc>,
+    ) -> Vec<ModelAvailability> {
+        let state = self.state.lock().unwrap();
+        let bucket_secs = self.config.bucket_secs as i64;
+        let cutoff = now.timestamp() - range_secs as i64;
+
+        // Merge services into per-model buckets
+        let mut models: BTreeMap<(String, ModelId), BTreeMap<i64, (u64, u64)>> = BTreeMap::new();
+        for (key, buckets) in &state.series {
+            if provider.is_some_and(|p| !p.eq_ignore_ascii_case(&key.provider))
+                || model.is_some_and(|m| *m != key.model)
+            {
+                continue;
+            }
+            let merged = models
+                .entry((key.provider.clone(), key.model.clone()))
+                .or_default();
+            for bucket in buckets.iter().filter(|b| b.start + bucket_secs > cutoff) {
+                let entry = merged.entry(bucket.start).or_default();
+                entry.0 += bucket.total;
+                entry.1 += bucket.errors;
+            }
+        }
+
+        models
+            .into_iter()
+            .filter(|(_, buckets)| !buckets.is_empty())
+            .map(|((provider, model), buckets)| {
+                let (total, errors) = buckets
+                    .values()
+                    .fold((0, 0), |(total, errors), (t, e)| (total + t, errors + e));
+                let error_rate = if total == 0 {
+                    0.0
+                } else {
+                    errors as f64 / total as f64
+                };
+                let mut degraded_services: Vec<ServiceId> = state
+                    .degraded
+                    .iter()
+                    .filter(|k| k.provider == provider && k.model == model)
+                    .map(|k| k.service.clone())
+                    .collect();
+                degraded_services.sort();
+
+                ModelAvailability {
+                    provider_outage: state.outages.contains(&provider),
+                    provider,
+                    model,
+                    total,
+                    errors,
+                    availability: 1.0 - error_rate,
+                    error_rate,
+                    degraded_services,
+                    points: buckets
+                        .into_iter()
+                        .map(|(start, (total, errors))| AvailabilityPoint {
+                            start: Utc.timestamp_opt(start, 0).single().unwrap_or(now),
+                            total,
+                            errors,
+                            availability: 1.0 - errors as f64 / total.max(1) as f64,
+                        })
+                        .collect(),
+                }
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use llm_sentinel_fixtures::event;
+
+    fn create_test_event(
+        service: &str,
+        model: &str,
+        failed: bool,
+        at: DateTime<Utc>,
+    ) -> TelemetryEvent {
+        let builder = event().service(service).model(model).timestamp(at);
+        if failed {
+            builder.error("503 service unavailable").build()
+        } else {
+            builder.build()
+        }
+    }
+
+    fn start() -> DateTime<Utc> {
+        Utc.with_ymd_and_hms(2024, 1, 1, 0, 0, 0).unwrap()
+    }
+
+    fn create_tracker() -> AvailabilityTracker {
+        AvailabilityTracker::new(AvailabilityTrackerConfig {
+            min_requests: 10,
+            ..Default::default()
+        })
+        .unwrap()
+    }
+
+    #[test]
+    fn test_infer_provider() {
+        assert_eq!(infer_provider(&ModelId::new("gpt-4o")), "openai");
+        assert_eq!(infer_provider(&ModelId::new("claude-3-opus")), "anthropic");
+        assert_eq!(
+            infer_provider(&ModelId::new("models/gemini-1.5-pro")),
+            "google"
+        );
+        assert_eq!(infer_provider(&ModelId::new("my-finetune")), "unknown");
+    }
+
+    #[test]
+    fn test_availability_series() {
+        let tracker = create_tracker();
+        let now = start() + chrono::Duration::minutes(5);
+
+        for i in 0..20 {
+            let at = start() + chrono::Duration::seconds(i * 6);
+            tracker.record_at(
+                &create_test_event("checkout", "gpt-4", i % 10 == 0, at),
+                now,
+            );
+        }
+        let mut event = create_test_event("search", "gpt-4", false, now);
+        event
+            .metadata
+            .insert("provider".to_string(), "Azure".to_string());
+        tracker.record_at(&event, now);
+
+        let all = tracker.availability_at(None, None, 3600, now);
+        assert_eq!(all.len(), 2);
+
+        let openai = tracker.availability_at(Some("openai"), None, 3600, now);
+        assert_eq!(openai.len(), 1);
+        assert_eq!(openai[0].total, 20);
+        assert_eq!(openai[0].errors, 2);
+        assert!((openai[0].availability - 0.9).abs() < 1e-9);
+        assert_eq!(openai[0].points.len(), 2);
+        assert!(openai[0].degraded_services.is_empty());
+
+        let azure = tracker.availability_at(Some("azure"), None, 3600, now);
+        assert_eq!(azure[0].model, ModelId::new("gpt-4"));
+    }
+
+    #[test]
+    fn test_single_service_alert() {
+        let tracker = create_tracker();
+        let mut alerts = Vec::new();
+
+        for i in 0..20 {
+            let at = start() + chrono::Duration::seconds(i);
+            alerts.extend(
+                tracker.record_at(&create_test_event("checkout", "gpt-4", i % 2 == 0, at), at),
+            );
+            alerts.extend(tracker.record_at(&create_test_event("search", "gpt-4", false, at), at));
+        }
+
+        assert_eq!(alerts.len(), 1);
+        assert_eq!(alerts[0].anomaly_type, AnomalyType::ErrorRateIncrease);
+        assert_eq!(alerts[0].severity, Severity::High);
+        assert_eq!(alerts[0].service_name, ServiceId::new("checkout"));
+        assert_eq!(alerts[0].context.additional["scope"], "service");
+    }
+
+    #[test]
+    fn test_provider_outage_alert() {
+        let tracker = create_tracker();
+        let mut alerts = Vec::new();
+
+        for i in 0..20 {
+            let at = start() + chrono::Duration::seconds(i);
+            for service in ["checkout", "search", "support"] {
+                alerts.extend(
+                    tracker.record_at(&create_test_event(service, "claude-3", true, at), at),
+                );
+            }
+            // Other providers are unaffected
+            alerts
+                .extend(tracker.record_at(&create_test_event("checkout", "gpt-4", false, at), at));
+        }
+
+        let outages: Vec<_> = alerts
+            .iter()
+            .filter(|a| a.context.additional["scope"] == "provider")
+            .collect();
+        assert_eq!(outages.len(), 1);
+        assert_eq!(outages[0].severity, Severity::Critical);
+        assert_eq!(outages[0].service_name, ServiceId::new("all"));
+        assert_eq!(outages[0].model, ModelId::new("claude-3"));
+        assert_eq!(outages[0].context.additional["provider"], "anthropic");
+
+        let now = start() + chrono::Duration::seconds(20);
+        let status = tracker.availability_at(Some("anthropic"), None, 3600, now);
+        assert!(status[0].provider_outage);
+        assert_eq!(status[0].degraded_services.len(), 3);
+
+        // Recovery clears the outage
+        let later = now + chrono::Duration::minutes(10);
+        for i in 0..10 {
+            let at = later + chrono::Duration::seconds(i);
+            tracker.record_at(&create_test_event("checkout", "claude-3", false, at), at);
+        }
+        let status = tracker.availability_at(Some("anthropic"), None, 3600, later);
+        assert!(!status[0].provider_outage);
+    }
+}
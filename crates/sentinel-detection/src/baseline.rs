@@ -1,14 +1,19 @@
 //! Baseline calculation and management for anomaly detection.
 
-use crate::stats::RollingWindow;
+use crate::stats::SampleWindow;
+use chrono::{DateTime, Utc};
 use dashmap::DashMap;
 use llm_sentinel_core::{
+    clock::Clock,
+    memory::{MemoryConsumer, ShedCost},
     types::{ModelId, ServiceId},
+    window::{WindowMetadata, WindowSpec},
     Result,
 };
+use llm_sentinel_stats::Summary;
 use serde::{Deserialize, Serialize};
 use std::sync::Arc;
-use tracing::{debug, info};
+use tracing::{debug, info, warn};
 
 /// Baseline statistics for a metric
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -37,47 +42,29 @@ pub struct Baseline {
     pub max: f64,
     /// Number of samples
     pub sample_count: usize,
+    /// Samples the baseline was computed from, when known
+    #[serde(default)]
+    pub window: Option<WindowMetadata>,
 }
 
 impl Baseline {
     /// Calculate baseline from data
     pub fn from_data(data: &[f64]) -> Self {
-        if data.is_empty() {
-            return Self::empty();
-        }
-
-        let mean = crate::stats::mean(data);
-        let std_dev = crate::stats::std_dev(data);
-        let median = crate::stats::median(data);
-        let mad = crate::stats::mad(data);
-        let (q1, q3, iqr) = crate::stats::iqr(data);
-        let p95 = crate::stats::percentile(data, 95.0);
-        let p99 = crate::stats::percentile(data, 99.0);
-
-        let min = data
-            .iter()
-            .min_by(|a, b| a.partial_cmp(b).unwrap_or(std::cmp::Ordering::Equal))
-            .copied()
-            .unwrap_or(0.0);
-        let max = data
-            .iter()
-            .max_by(|a, b| a.partial_cmp(b).unwrap_or(std::cmp::Ordering::Equal))
-            .copied()
-            .unwrap_or(0.0);
-
+        let summary = Summary::from_data(data);
         Self {
-            mean,
-            std_dev,
-            median,
-            mad,
-            q1,
-            q3,
-            iqr,
-            p95,
-            p99,
-            min,
-            max,
-            sample_count: data.len(),
+            mean: summary.mean,
+            std_dev: summary.std_dev,
+            median: summary.median,
+            mad: summary.mad,
+            q1: summary.q1,
+            q3: summary.q3,
+            iqr: summary.iqr,
+            p95: summary.p95,
+            p99: summary.p99,
+            min: summary.min,
+            max: summary.max,
+            sample_count: summary.sample_count,
+            window: None,
         }
     }
 
@@ -96,6 +83,7 @@ impl Baseline {
             min: 0.0,
             max: 0.0,
             sample_count: 0,
+            window: None,
         }
     }
 
@@ -106,7 +94,7 @@ impl Baseline {
 }
 
 /// Baseline key for multi-dimensional baselines
-#[derive(Debug, Clone, Hash, Eq, PartialEq)]
+#[derive(Debug, Clone, Hash, Eq, PartialEq, Serialize, Deserialize)]
 pub struct BaselineKey {
     /// Service identifier
     pub service: ServiceId,
@@ -147,20 +135,31 @@ impl BaselineKey {
     }
 }
 
+/// Samples of one baseline window, for carrying it over to another process
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct BaselineSnapshot {
+    /// Baseline the samples belong to
+    pub key: BaselineKey,
+    /// Samples with their timestamps, oldest first
+    pub samples: Vec<(DateTime<Utc>, f64)>,
+}
+
 /// Baseline manager for storing and updating baselines
 pub struct BaselineManager {
-    /// Window size for rolling baselines
-    window_size: usize,
+    /// Bounds of the rolling baseline windows
+    spec: WindowSpec,
     /// Rolling windows for each key
-    windows: Arc<DashMap<BaselineKey, RollingWindow>>,
+    windows: Arc<DashMap<BaselineKey, SampleWindow>>,
     /// Cached baselines
     baselines: Arc<DashMap<BaselineKey, Baseline>>,
+    /// Clock samples without a timestamp are stamped with
+    clock: Clock,
 }
 
 impl std::fmt::Debug for BaselineManager {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         f.debug_struct("BaselineManager")
-            .field("window_size", &self.window_size)
+            .field("spec", &self.spec)
             .field("windows_count", &self.windows.len())
             .field("baselines_count", &self.baselines.len())
             .finish()
@@ -168,29 +167,52 @@ impl std::fmt::Debug for BaselineManager {
 }
 
 impl BaselineManager {
-    /// Create a new baseline manager
+    /// Create a new baseline manager keeping the last `window_size` samples
     pub fn new(window_size: usize) -> Self {
-        info!("Creating baseline manager with window size {}", window_size);
+        Self::with_spec(WindowSpec::samples(window_size))
+    }
+
+    /// Create a new baseline manager with the given window bounds
+    pub fn with_spec(spec: WindowSpec) -> Self {
+        info!("Creating baseline manager with window {}", spec);
         Self {
-            window_size,
+            spec,
             windows: Arc::new(DashMap::new()),
             baselines: Arc::new(DashMap::new()),
+            clock: Clock::system(),
         }
     }
 
-    /// Update baseline with a new value
+    /// Stamp samples passed to [`update`](Self::update) with the given clock
+    pub fn with_clock(mut self, clock: Clock) -> Self {
+        self.clock = clock;
+        self
+    }
+
+    /// Bounds of the baseline windows
+    pub fn spec(&self) -> WindowSpec {
+        self.spec
+    }
+
+    /// Update baseline with a new value observed now
     pub fn update(&self, key: BaselineKey, value: f64) -> Result<()> {
+        self.update_at(key, value, self.clock.now())
+    }
+
+    /// Update baseline with a new value observed at `at`
+    pub fn update_at(&self, key: BaselineKey, value: f64, at: DateTime<Utc>) -> Result<()> {
         // Get or create rolling window
         let mut window = self
             .windows
             .entry(key.clone())
-            .or_insert_with(|| RollingWindow::new(self.window_size));
+            .or_insert_with(|| SampleWindow::new(self.spec));
 
-        window.push(value);
+        window.push(value, at);
 
         // Recalculate baseline if window is full
         if window.is_full() {
-            let baseline = Baseline::from_data(window.data());
+            let mut baseline = Baseline::from_data(window.values());
+            baseline.window = window.metadata();
             self.baselines.insert(key.clone(), baseline);
 
             debug!(
@@ -254,6 +276,34 @@ impl BaselineManager {
         Ok(())
     }
 
+    /// Samples of every baseline window
+    pub fn snapshot(&self) -> Vec<BaselineSnapshot> {
+        self.windows
+            .iter()
+            .map(|entry| BaselineSnapshot {
+                key: entry.key().clone(),
+                samples: entry.value().samples().collect(),
+            })
+            .collect()
+    }
+
+    /// Replace all baselines with ones rebuilt from snapshotted samples
+    ///
+    /// Samples are replayed through the current window bounds, so a snapshot
+    /// taken with wider bounds is trimmed to fit.
+    pub fn restore(&self, snapshots: Vec<BaselineSnapshot>) -> Result<()> {
+        self.windows.clear();
+        self.baselines.clear();
+        let count = snapshots.len();
+        for snapshot in snapshots {
+            for (at, value) in snapshot.samples {
+                self.update_at(snapshot.key.clone(), value, at)?;
+            }
+        }
+        info!("Restored {} baselines", count);
+        Ok(())
+    }
+
     /// Get statistics about baseline manager
     pub fn stats(&self) -> BaselineManagerStats {
         let total_baselines = self.baselines.len();
@@ -266,11 +316,70 @@ impl BaselineManager {
         BaselineManagerStats {
             total_baselines,
             valid_baselines,
-            window_size: self.window_size,
+            window_size: self.spec.max_samples,
         }
     }
 }
 
+impl BaselineManager {
+    /// Approximate bytes held by one baseline entry besides its window
+    fn entry_size(key: &BaselineKey) -> usize {
+        std::mem::size_of::<BaselineKey>()
+            + std::mem::size_of::<SampleWindow>()
+            + std::mem::size_of::<Baseline>()
+            + key.service.as_str().len()
+            + key.model.as_str().len()
+            + key.metric.len()
+    }
+}
+
+/// Baselines are evicted least recently updated first; they are relearned
+/// if their service and model send telemetry again.
+impl MemoryConsumer for BaselineManager {
+    fn name(&self) -> &str {
+        "baselines"
+    }
+
+    fn memory_usage(&self) -> usize {
+        self.windows
+            .iter()
+            .map(|entry| Self::entry_size(entry.key()) + entry.value().memory_usage())
+            .sum()
+    }
+
+    fn shed_cost(&self) -> ShedCost {
+        ShedCost::Expensive
+    }
+
+    fn shed(&self, bytes: usize) -> usize {
+        let mut idle: Vec<(Option<DateTime<Utc>>, BaselineKey, usize)> = self
+            .windows
+            .iter()
+            .map(|entry| {
+                let size = Self::entry_size(entry.key()) + entry.value().memory_usage();
+                (entry.value().newest(), entry.key().clone(), size)
+            })
+            .collect();
+        idle.sort_by_key(|(newest, _, _)| *newest);
+
+        let mut freed = 0;
+        let mut evicted = 0;
+        for (_, key, size) in idle {
+            if freed >= bytes {
+                break;
+            }
+            self.windows.remove(&key);
+            self.baselines.remove(&key);
+            freed += size;
+            evicted += 1;
+        }
+        if evicted > 0 {
+            warn!("Evicted {} idle baselines to free memory", evicted);
+        }
+        freed
+    }
+}
+
 /// Baseline manager statistics
 #[derive(Debug, Clone)]
 pub struct BaselineManagerStats {
@@ -286,6 +395,26 @@ pub struct BaselineManagerStats {
 mod tests {
     use super::*;
 
+    #[test]
+    fn test_baseline_snapshot_restore() {
+        let manager = BaselineManager::new(20);
+        let key = BaselineKey::latency(ServiceId::new("svc"), ModelId::new("gpt-4"));
+        for i in 0..15 {
+            manager.update(key.clone(), 100.0 + i as f64).unwrap();
+        }
+        let snapshot = manager.snapshot();
+        assert_eq!(snapshot.len(), 1);
+        assert_eq!(snapshot[0].samples.len(), 15);
+
+        // Restored into narrower windows, only the newest samples are kept
+        let restored = BaselineManager::new(12);
+        restored.restore(snapshot).unwrap();
+        let baseline = restored.get(&key).unwrap();
+        assert_eq!(baseline.sample_count, 12);
+        assert_eq!(baseline.min, 103.0);
+        assert_eq!(baseline.max, 114.0);
+    }
+
     #[test]
     fn test_baseline_from_data() {
         let data = vec![1.0, 2.0, 3.0, 4.0, 5.0, 6.0, 7.0, 8.0, 9.0, 10.0];
@@ -367,4 +496,63 @@ mod tests {
         assert_eq!(stats.valid_baselines, 2);
         assert_eq!(stats.window_size, 10);
     }
+
+    #[test]
+    fn test_shed_evicts_idle_baselines() {
+        let manager = BaselineManager::new(10);
+        let idle = BaselineKey::latency(ServiceId::new("idle"), ModelId::new("gpt-4"));
+        let active = BaselineKey::latency(ServiceId::new("active"), ModelId::new("gpt-4"));
+        let start = Utc::now();
+
+        for i in 0..10 {
+            let at = start + chrono::Duration::seconds(i);
+            manager.update_at(idle.clone(), i as f64, at).unwrap();
+            manager
+                .update_at(active.clone(), i as f64, at + chrono::Duration::hours(1))
+                .unwrap();
+        }
+
+        let usage = manager.memory_usage();
+        let freed = manager.shed(1);
+        assert!(freed > 0);
+        assert_eq!(manager.memory_usage(), usage - freed);
+        assert!(manager.get(&idle).is_none());
+        assert!(manager.has_valid_baseline(&active));
+    }
+
+    #[test]
+    fn test_baseline_window_metadata() {
+        let manager = BaselineManager::with_spec(WindowSpec::samples(10).with_max_age_secs(3600));
+        let key = BaselineKey::latency(ServiceId::new("test"), ModelId::new("gpt-4"));
+        let start = Utc::now();
+
+        for i in 0..12 {
+            let at = start + chrono::Duration::seconds(i * 60);
+            manager.update_at(key.clone(), i as f64, at).unwrap();
+        }
+
+        let window = manager.get(&key).unwrap().window.unwrap();
+        assert_eq!(window.spec, manager.spec());
+        assert_eq!(window.start, start + chrono::Duration::seconds(120));
+        assert_eq!(window.end, start + chrono::Duration::seconds(660));
+        assert_eq!(window.span_secs, 540.0);
+        assert_eq!(window.sample_count, 10);
+    }
+
+    #[test]
+    fn test_baseline_manager_clock() {
+        let start = Utc::now() - chrono::Duration::days(1);
+        let clock = Clock::manual(start);
+        let manager = BaselineManager::new(10).with_clock(clock.clone());
+        let key = BaselineKey::latency(ServiceId::new("test"), ModelId::new("gpt-4"));
+
+        for i in 0..10 {
+            manager.update(key.clone(), i as f64).unwrap();
+            clock.advance(chrono::Duration::seconds(30));
+        }
+
+        let window = manager.get(&key).unwrap().window.unwrap();
+        assert_eq!(window.start, start);
+        assert_eq!(window.end, start + chrono::Duration::seconds(270));
+    }
 }
@@ -0,0 +1,534 @@
+//! Bayesian online change point detection (BOCPD).
+//!
+//! Tracks a posterior over the "run length" (observations since the last
+//! change) per metric, following Adams & MacKay (2007). Each run length keeps
+//! Normal-Gamma sufficient statistics, so both mean and variance shifts move
+//! probability mass to short run lengths. A change point is reported when
+//! most of the mass sits on run lengths shorter than `min_run_length`.
+//! Observations are clipped to `clip_sigma` around the dominant run before
+//! updating, so a single outlier cannot reset the posterior on its own.
+
+use crate::{
+    baseline::BaselineKey, detectors::DetectionConfig, Detector, DetectorStats, DetectorType,
+};
+use async_trait::async_trait;
+use dashmap::DashMap;
+use llm_sentinel_core::{
+    config::ChangePointConfig,
+    events::{AnomalyContext, AnomalyDetails, AnomalyEvent, TelemetryEvent},
+    types::{AnomalyType, DetectionMethod, Severity},
+    window::WindowSpec,
+    Result,
+};
+use statrs::function::gamma::ln_gamma;
+use std::{
+    collections::{HashMap, VecDeque},
+    sync::Arc,
+};
+use tracing::debug;
+
+/// BOCPD detector configuration
+#[derive(Debug, Clone)]
+pub struct BocpdConfig {
+    /// Expected run length between change points (hazard = 1 / lambda)
+    pub hazard_lambda: f64,
+    /// Maximum run length kept in the posterior
+    pub max_run_length: usize,
+    /// Run lengths shorter than this count as "recently changed"
+    pub min_run_length: usize,
+    /// Posterior probability of a recent change required to alert
+    pub threshold: f64,
+    /// Clip observations to this many predictive std devs of the dominant run
+    pub clip_sigma: f64,
+    /// Shift (in warm-up standard deviations) considered severe
+    pub high_severity_shift: f64,
+    /// Common detection config (`min_samples` is the warm-up length)
+    pub detection: DetectionConfig,
+}
+
+impl Default for BocpdConfig {
+    fn default() -> Self {
+        Self {
+            hazard_lambda: 250.0,
+            max_run_length: 300,
+            min_run_length: 5,
+            threshold: 0.7,
+            clip_sigma: 4.0,
+            high_severity_shift: 5.0,
+            detection: DetectionConfig {
+                min_samples: 30,
+                update_baseline: true,
+            },
+        }
+    }
+}
+
+impl BocpdConfig {
+    /// Detector configuration for the given change point settings
+    pub fn from_config(config: &ChangePointConfig) -> Self {
+        Self {
+            hazard_lambda: config.hazard_lambda,
+            max_run_length: config.max_run_length,
+            min_run_length: config.min_run_length,
+            threshold: config.threshold,
+            clip_sigma: config.clip_sigma,
+            high_severity_shift: config.high_severity_shift,
+            detection: DetectionConfig {
+                min_samples: config.warmup_samples,
+                update_baseline: true,
+            },
+        }
+    }
+}
+
+/// Normal-Gamma prior hyperparameters (on standardized values)
+const PRIOR_MU: f64 = 0.0;
+const PRIOR_KAPPA: f64 = 1.0;
+const PRIOR_ALPHA: f64 = 1.0;
+const PRIOR_BETA: f64 = 1.0;
+
+/// Sufficient statistics for one run length
+#[derive(Debug, Clone, Copy)]
+struct RunStats {
+    mu: f64,
+    kappa: f64,
+    alpha: f64,
+    beta: f64,
+}
+
+impl RunStats {
+    fn prior() -> Self {
+        Self {
+            mu: PRIOR_MU,
+            kappa: PRIOR_KAPPA,
+            alpha: PRIOR_ALPHA,
+            beta: PRIOR_BETA,
+        }
+    }
+
+    /// Squared scale of the Student-t posterior predictive
+    fn predictive_scale2(&self) -> f64 {
+        self.beta * (self.kappa + 1.0) / (self.alpha * self.kappa)
+    }
+
+    /// Log density of the Student-t posterior predictive
+    fn log_predictive(&self, x: f64) -> f64 {
+        let nu = 2.0 * self.alpha;
+        let scale2 = self.predictive_scale2();
+        let d = x - self.mu;
+        ln_gamma((nu + 1.0) / 2.0)
+            - ln_gamma(nu / 2.0)
+            - 0.5 * (nu * std::f64::consts::PI * scale2).ln()
+            - (nu + 1.0) / 2.0 * (1.0 + d * d / (nu * scale2)).ln()
+    }
+
+    /// Posterior after observing `x`
+    fn observe(&self, x: f64) -> Self {
+        let d = x - self.mu;
+        Self {
+            mu: (self.kappa * self.mu + x) / (self.kappa + 1.0),
+            kappa: self.kappa + 1.0,
+            alpha: self.alpha + 0.5,
+            beta: self.beta + self.kappa * d * d / (2.0 * (self.kappa + 1.0)),
+        }
+    }
+}
+
+/// Run-length posterior for one metric
+#[derive(Debug, Clone)]
+struct BocpdState {
+    /// Warm-up values used to standardize observations
+    warmup: Vec<f64>,
+    /// Standardization (mean, std dev) once warmed up
+    scale: Option<(f64, f64)>,
+    /// Run-length probabilities
+    probs: Vec<f64>,
+    /// Sufficient statistics per run length
+    runs: Vec<RunStats>,
+    /// Most recent raw values (up to `min_run_length`)
+    recent: VecDeque<f64>,
+    /// Mean of the last stable run
+    stable_mean: f64,
+    /// Observations since warm-up
+    observations: u64,
+    /// Whether a change is currently being reported
+    in_change: bool,
+}
+
+/// Outcome of one BOCPD step
+#[derive(Debug, Clone, Copy)]
+struct Step {
+    /// Probability that a change happened within `min_run_length`
+    change_prob: f64,
+    /// Mean of the last stable run
+    previous_mean: f64,
+    /// Median of the most recent values
+    current_mean: f64,
+}
+
+impl BocpdState {
+    fn new() -> Self {
+        Self {
+            warmup: Vec::new(),
+            scale: None,
+            probs: vec![1.0],
+            runs: vec![RunStats::prior()],
+            recent: VecDeque::new(),
+            stable_mean: 0.0,
+            observations: 0,
+            in_change: false,
+        }
+    }
+
+    /// Advance the posterior with a new value
+    ///
+    /// Returns `None` while warming up.
+    fn step(&mut self, value: f64, config: &BocpdConfig) -> Option<Step> {
+        let Some((mean, std_dev)) = self.scale else {
+            self.warmup.push(value);
+            if self.warmup.len() >= config.detection.min_samples.max(2) {
+                let mean = crate::stats::mean(&self.warmup);
+                let std_dev = crate::stats::std_dev(&self.warmup);
+                // Constant warm-up data: fall back to a small relative scale
+                let std_dev = if std_dev > f64::EPSILON {
+                    std_dev
+                } else {
+                    (mean.abs() * 0.01).max(1e-9)
+                };
+                self.scale = Some((mean, std_dev));
+                self.stable_mean = mean;
+                self.warmup = Vec::new();
+            }
+            return None;
+        };
+
+        let hazard = 1.0 / config.hazard_lambda;
+
+        // Clip around the dominant run to limit the influence of outliers
+        let dominant = &self.runs[argmax(&self.probs)];
+        let limit = config.clip_sigma * dominant.predictive_scale2().sqrt();
+        let x = ((value - mean) / std_dev).clamp(dominant.mu - limit, dominant.mu + limit);
+
+        self.recent.push_back(value);
+        if self.recent.len() > config.min_run_length.max(1) {
+            self.recent.pop_front();
+        }
+
+        // Growth and change-point probabilities in log space
+        let log_pred: Vec<f64> = self.runs.iter().map(|r| r.log_predictive(x)).collect();
+        let max_log = log_pred.iter().cloned().fold(f64::NEG_INFINITY, f64::max);
+
+        let mut probs = Vec::with_capacity(self.probs.len() + 1);
+        probs.push(0.0);
+        let mut change = 0.0;
+        for (p, lp) in self.probs.iter().zip(&log_pred) {
+            let joint = p * (lp - max_log).exp();
+            probs.push(joint * (1.0 - hazard));
+            change += joint * hazard;
+        }
+        probs[0] = change;
+
+        let mut runs = Vec::with_capacity(self.runs.len() + 1);
+        runs.push(RunStats::prior());
+        runs.extend(self.runs.iter().map(|r| r.observe(x)));
+
+        // Truncate the longest run lengths
+        probs.truncate(config.max_run_length);
+        runs.truncate(config.max_run_length);
+
+        let total: f64 = probs.iter().sum();
+        if total > 0.0 && total.is_finite() {
+            probs.iter_mut().for_each(|p| *p /= total);
+        } else {
+            // Numerical collapse (e.g. extreme outlier): restart the run
+            probs = vec![1.0];
+            runs = vec![RunStats::prior().observe(x)];
+        }
+
+        self.probs = probs;
+        self.runs = runs;
+        self.observations += 1;
+
+        let short = config.min_run_length.min(self.probs.len());
+        let change_prob: f64 = self.probs[..short].iter().sum();
+
+        let step = Step {
+            change_prob,
+            previous_mean: self.stable_mean,
+            current_mean: crate::stats::median(self.recent.make_contiguous()),
+        };
+
+        // Track the level of the dominant run once it is established
+        let dominant = argmax(&self.probs);
+        if dominant >= 2 * config.min_run_length {
+            self.stable_mean = self.runs[dominant].mu * std_dev + mean;
+        }
+
+        Some(step)
+    }
+
+    /// Warm-up standard deviation
+    fn std_dev(&self) -> f64 {
+        self.scale.map_or(1.0, |(_, s)| s)
+    }
+}
+
+/// Index of the largest element
+fn argmax(values: &[f64]) -> usize {
+    values
+        .iter()
+        .enumerate()
+        .max_by(|a, b| a.1.total_cmp(b.1))
+        .map_or(0, |(i, _)| i)
+}
+
+/// Bayesian online change point detector
+///
+/// Detects sustained mean and variance shifts in latency, token usage and
+/// cost per service/model, emitting [`AnomalyType::ConceptDrift`].
+pub struct BocpdDetector {
+    config: BocpdConfig,
+    states: Arc<DashMap<BaselineKey, BocpdState>>,
+    stats: DetectorStats,
+}
+
+impl std::fmt::Debug for BocpdDetector {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("BocpdDetector")
+            .field("config", &self.config)
+            .field("states_count", &self.states.len())
+            .field("stats", &self.stats)
+            .finish()
+    }
+}
+
+impl BocpdDetector {
+    /// Create a new BOCPD detector
+    pub fn new(config: BocpdConfig) -> Self {
+        Self {
+            config,
+            states: Arc::new(DashMap::new()),
+            stats: DetectorStats::empty(),
+        }
+    }
+
+    /// Metric values tracked for an event
+    fn metrics(event: &TelemetryEvent) -> [(BaselineKey, f64); 3] {
+        let service = &event.service_name;
+        let model = &event.model;
+        [
+            (
+                BaselineKey::latency(service.clone(), model.clone()),
+                event.latency_ms,
+            ),
+            (
+                BaselineKey::tokens(service.clone(), model.clone()),
+                event.total_tokens() as f64,
+            ),
+            (
+                BaselineKey::cost(service.clone(), model.clone()),
+                event.cost_usd,
+            ),
+        ]
+    }
+
+    /// Whether a step signals a change point
+    fn is_change(&self, state: &BocpdState, step: &Step) -> bool {
+        state.observations >= 2 * self.config.min_run_length as u64
+            && step.change_prob >= self.config.threshold
+    }
+
+    fn build_anomaly(
+        &self,
+        event: &TelemetryEvent,
+        key: &BaselineKey,
+        value: f64,
+        state: &BocpdState,
+        step: &Step,
+    ) -> AnomalyEvent {
+        let shift = (step.current_mean - step.previous_mean) / state.std_dev();
+        let severity = if shift.abs() >= self.config.high_severity_shift {
+            Severity::High
+        } else {
+            Severity::Medium
+        };
+        let direction = if shift >= 0.0 { "increase" } else { "decrease" };
+
+        AnomalyEvent::new(
+            severity,
+            AnomalyType::ConceptDrift,
+            event.service_name.clone(),
+            event.model.clone(),
+            DetectionMethod::Bocpd,
+            step.change_prob.clamp(0.5, 0.99),
+            AnomalyDetails {
+                metric: key.metric.clone(),
+                value,
+                baseline: step.previous_mean,
+                threshold: self.config.threshold,
+                deviation_sigma: Some(shift),
+                additional: {
+                    let mut map = HashMap::new();
+                    map.insert(
+                        "change_probability".to_string(),
+                        serde_json::json!(step.change_prob),
+                    );
+                    map.insert(
+                        "previous_mean".to_string(),
+                        serde_json::json!(step.previous_mean),
+                    );
+                    map.insert(
+                        "current_mean".to_string(),
+                        serde_json::json!(step.current_mean),
+                    );
+                    map
+                },
+                observations: Vec::new(),
+            },
+            AnomalyContext {
+                trace_id: event.trace_id.clone(),
+                user_id: event.metadata.get("user_id").cloned(),
+                region: event.metadata.get("region").cloned(),
+                time_window: WindowSpec::samples(self.config.min_run_length).to_string(),
+                sample_count: state.observations as usize,
+                window: None,
+                additional: HashMap::new(),
+            },
+        )
+        .with_root_cause(format!(
+            "Change point in {}: mean {} from {:.4} to {:.4} ({:+.1}σ)",
+            key.metric, direction, step.previous_mean, step.current_mean, shift
+        ))
+        .with_remediation("Check for model version, prompt or traffic mix changes")
+        .with_remediation("Confirm whether the new level is expected before rebaselining")
+    }
+}
+
+#[async_trait]
+impl Detector for BocpdDetector {
+    async fn detect(&self, event: &TelemetryEvent) -> Result<Option<AnomalyEvent>> {
+        // Evaluate the step on a copy; `update` commits it
+        for (key, value) in Self::metrics(event) {
+            let Some(state) = self.states.get(&key) else {
+                continue;
+            };
+            if state.in_change {
+                continue;
+            }
+
+            let mut next = state.clone();
+            drop(state);
+
+            if let Some(step) = next.step(value, &self.config) {
+                if self.is_change(&next, &step) {
+                    debug!(
+                        service = %event.service_name,
+                        model = %event.model,
+                        metric = %key.metric,
+                        change_prob = step.change_prob,
+                        "Change point detected"
+                    );
+                    return Ok(Some(self.build_anomaly(event, &key, value, &next, &step)));
+                }
+            }
+        }
+
+        Ok(None)
+    }
+
+    fn name(&self) -> &str {
+        "bocpd"
+    }
+
+    fn detector_type(&self) -> DetectorType {
+        DetectorType::Statistical
+    }
+
+    async fn update(&mut self, event: &TelemetryEvent) -> Result<()> {
+        if !self.config.detection.update_baseline {
+            return Ok(());
+        }
+
+        for (key, value) in Self::metrics(event) {
+            let mut state = self.states.entry(key).or_insert_with(BocpdState::new);
+            if let Some(step) = state.step(value, &self.config) {
+                let change = self.is_change(&state, &step);
+                state.in_change = change;
+            }
+        }
+
+        self.stats.events_processed += 1;
+        Ok(())
+    }
+
+    async fn reset(&mut self) -> Result<()> {
+        self.states.clear();
+        self.stats = DetectorStats::empty();
+        Ok(())
+    }
+
+    fn stats(&self) -> DetectorStats {
+        self.stats.clone()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use llm_sentinel_fixtures::event;
+
+    fn create_test_event(latency: f64) -> TelemetryEvent {
+        event().latency_ms(latency).build()
+    }
+
+    /// Deterministic noise in [-1, 1)
+    fn noise(i: u64) -> f64 {
+        ((i.wrapping_mul(2654435761) % 1000) as f64 / 500.0) - 1.0
+    }
+
+    #[tokio::test]
+    async fn test_detects_mean_shift() {
+        let mut detector = BocpdDetector::new(BocpdConfig::default());
+
+        // Stable around 100ms: no change points
+        for i in 0..200 {
+            let event = create_test_event(100.0 + 10.0 * noise(i));
+            assert!(detector.detect(&event).await.unwrap().is_none());
+            detector.update(&event).await.unwrap();
+        }
+
+        // Sustained shift to 200ms
+        let mut detected = Vec::new();
+        for i in 200..260 {
+            let event = create_test_event(200.0 + 10.0 * noise(i));
+            if let Some(anomaly) = detector.detect(&event).await.unwrap() {
+                detected.push(anomaly);
+            }
+            detector.update(&event).await.unwrap();
+        }
+
+        assert_eq!(detected.len(), 1);
+        let anomaly = &detected[0];
+        assert_eq!(anomaly.anomaly_type, AnomalyType::ConceptDrift);
+        assert_eq!(anomaly.detection_method, DetectionMethod::Bocpd);
+        assert_eq!(anomaly.details.metric, "latency_ms");
+        assert!((anomaly.details.baseline - 100.0).abs() < 10.0);
+        assert!(anomaly.details.deviation_sigma.unwrap() > 0.0);
+    }
+
+    #[tokio::test]
+    async fn test_single_outlier_ignored() {
+        let mut detector = BocpdDetector::new(BocpdConfig::default());
+
+        for i in 0..200 {
+            let latency = if i == 150 {
+                1000.0
+            } else {
+                100.0 + 10.0 * noise(i)
+            };
+            let event = create_test_event(latency);
+            assert!(detector.detect(&event).await.unwrap().is_none());
+            detector.update(&event).await.unwrap();
+        }
+    }
+}
@@ -0,0 +1,444 @@
+//! Content-filter surge detector.
+//!
+//! Tracks the share of responses stopped by provider safety filters
+//! (`content_filter`, `safety`, `refusal` finish reasons) per service/model
+//! and alerts when the recent rate jumps well above its long-run level.
+//! Surges usually follow a prompt change or an abuse attempt; when most of
+//! the filtered requests come from a single user the anomaly is reported as
+//! a security threat, otherwise as quality degradation.
+
+use crate::{
+    baseline::BaselineKey, detectors::DetectionConfig, Detector, DetectorStats, DetectorType,
+};
+use async_trait::async_trait;
+use dashmap::DashMap;
+use llm_sentinel_core::{
+    config::ContentFilterSurgeConfig,
+    events::{AnomalyContext, AnomalyDetails, AnomalyEvent, TelemetryEvent},
+    types::{AnomalyType, DetectionMethod, Severity},
+    window::WindowSpec,
+    Result,
+};
+use std::{
+    collections::{HashMap, VecDeque},
+    sync::Arc,
+};
+use tracing::debug;
+
+/// Content-filter surge detector configuration
+#[derive(Debug, Clone)]
+pub struct ContentFilterConfig {
+    /// Finish reasons counted as filtered (case-insensitive)
+    pub finish_reasons: Vec<String>,
+    /// Recent window size (events)
+    pub window_size: usize,
+    /// Events over which the long-run rate adapts
+    pub reference_size: usize,
+    /// Recent rate must reach this multiple of the long-run rate
+    pub multiplier: f64,
+    /// Minimum recent rate to alert on
+    pub min_rate: f64,
+    /// Minimum filtered events in the window to alert on
+    pub min_count: usize,
+    /// Share of filtered events from one user that indicates abuse
+    pub abuse_user_share: f64,
+    /// Metadata key holding the user identifier
+    pub user_key: String,
+    /// Common detection config (`min_samples` gates the long-run rate)
+    pub detection: DetectionConfig,
+}
+
+impl Default for ContentFilterConfig {
+    fn default() -> Self {
+        Self {
+            finish_reasons: vec![
+                "content_filter".to_string(),
+                "safety".to_string(),
+                "refusal".to_string(),
+            ],
+            window_size: 100,
+            reference_size: 1000,
+            multiplier: 3.0,
+            min_rate: 0.05,
+            min_count: 5,
+            abuse_user_share: 0.5,
+            user_key: "user_id".to_string(),
+            detection: DetectionConfig {
+                min_samples: 100,
+                update_baseline: true,
+            },
+        }
+    }
+}
+
+impl ContentFilterConfig {
+    /// Detector configuration for the given content-filter surge settings
+    pub fn from_config(config: &ContentFilterSurgeConfig) -> Self {
+        Self {
+            finish_reasons: config.finish_reasons.clone(),
+            window_size: config.window_size,
+            reference_size: config.reference_size,
+            multiplier: config.multiplier,
+            min_rate: config.min_rate,
+            min_count: config.min_count,
+            abuse_user_share: config.abuse_user_share,
+            user_key: config.user_key.clone(),
+            detection: DetectionConfig {
+                min_samples: config.min_samples,
+                update_baseline: true,
+            },
+        }
+    }
+}
+
+/// Filter state for one service/model
+#[derive(Debug, Clone, Default)]
+struct FilterState {
+    /// Recent events: whether filtered, and the requesting user
+    window: VecDeque<(bool, Option<String>)>,
+    /// Filtered events in the window
+    filtered: usize,
+    /// Long-run filtered rate (EWMA over events leaving the window)
+    baseline_rate: f64,
+    /// Events folded into the long-run rate
+    baseline_samples: u64,
+    /// Whether a surge is currently being reported
+    in_surge: bool,
+}
+
+/// Snapshot of the window used for evaluation
+#[derive(Debug, Clone)]
+struct Surge {
+    rate: f64,
+    baseline_rate: f64,
+    filtered: usize,
+    window: usize,
+    top_user: Option<(String, usize)>,
+}
+
+impl FilterState {
+    /// Add an event to the window, folding evicted events into the baseline
+    fn push(&mut self, filtered: bool, user: Option<String>, config: &ContentFilterConfig) {
+        self.window.push_back((filtered, user));
+        if filtered {
+            self.filtered += 1;
+        }
+
+        if self.window.len() > config.window_size {
+            if let Some((evicted, _)) = self.window.pop_front() {
+                if evicted {
+                    self.filtered -= 1;
+                }
+                let x = if evicted { 1.0 } else { 0.0 };
+                self.baseline_samples += 1;
+                // Plain average until the reference fills, then EWMA
+                let alpha = 1.0 / (self.baseline_samples as f64).min(config.reference_size as f64);
+                self.baseline_rate += alpha * (x - self.baseline_rate);
+            }
+        }
+    }
+
+    /// Evaluate the window, returning a surge if thresholds are met
+    fn evaluate(&self, config: &ContentFilterConfig) -> Option<Surge> {
+        if self.window.len() < config.window_size
+            || self.baseline_samples < config.detection.min_samples as u64
+            || self.filtered < config.min_count
+        {
+            return None;
+        }
+
+        let rate = self.filtered as f64 / self.window.len() as f64;
+        if rate < config.min_rate || rate < config.multiplier * self.baseline_rate {
+            return None;
+        }
+
+        let mut users: HashMap<&str, usize> = HashMap::new();
+        for (_, user) in self.window.iter().filter(|(filtered, _)| *filtered) {
+            if let Some(user) = user {
+                *users.entry(user.as_str()).or_default() += 1;
+            }
+        }
+        let top_user = users
+            .into_iter()
+            .max_by(|a, b| a.1.cmp(&b.1).then_with(|| b.0.cmp(a.0)))
+            .map(|(user, count)| (user.to_string(), count));
+
+        Some(Surge {
+            rate,
+            baseline_rate: self.baseline_rate,
+            filtered: self.filtered,
+            window: self.window.len(),
+            top_user,
+        })
+    }
+}
+
+/// Content-filter surge detector
+pub struct ContentFilterDetector {
+    config: ContentFilterConfig,
+    states: Arc<DashMap<BaselineKey, FilterState>>,
+    stats: DetectorStats,
+}
+
+impl std::fmt::Debug for ContentFilterDetector {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("ContentFilterDetector")
+            .field("config", &self.config)
+            .field("states_count", &self.states.len())
+            .field("stats", &self.stats)
+            .finish()
+    }
+}
+
+impl ContentFilterDetector {
+    /// Create a new content-filter surge detector
+    pub fn new(config: ContentFilterConfig) -> Self {
+        Self {
+            config,
+            states: Arc::new(DashMap::new()),
+            stats: DetectorStats::empty(),
+        }
+    }
+
+    fn key(event: &TelemetryEvent) -> BaselineKey {
+        BaselineKey::new(
+            event.service_name.clone(),
+            event.model.clone(),
+            "content_filter_rate",
+        )
+    }
+
+    /// Whether the event was stopped by a content filter
+    fn is_filtered(&self, event: &TelemetryEvent) -> bool {
+        self.config
+            .finish_reasons
+            .iter()
+            .any(|reason| reason.eq_ignore_ascii_case(&event.response.finish_reason))
+    }
+
+    fn user(&self, event: &TelemetryEvent) -> Option<String> {
+        event.metadata.get(&self.config.user_key).cloned()
+    }
+
+    fn build_anomaly(&self, event: &TelemetryEvent, surge: &Surge) -> AnomalyEvent {
+        let abuser = surge.top_user.as_ref().filter(|(_, count)| {
+            *count as f64 / surge.filtered as f64 >= self.config.abuse_user_share
+        });
+
+        let (anomaly_type, severity) = if abuser.is_some() {
+            (AnomalyType::SecurityThreat, Severity::High)
+        } else if surge.rate >= 2.0 * self.config.multiplier * surge.baseline_rate.max(0.01) {
+            (AnomalyType::QualityDegradation, Severity::High)
+        } else {
+            (AnomalyType::QualityDegradation, Severity::Medium)
+        };
+
+        let mut additional = HashMap::new();
+        additional.insert("filtered".to_string(), serde_json::json!(surge.filtered));
+        additional.insert("window".to_string(), serde_json::json!(surge.window));
+        if let Some((user, count)) = &surge.top_user {
+            additional.insert("top_user".to_string(), serde_json::json!(user));
+            additional.insert("top_user_filtered".to_string(), serde_json::json!(count));
+        }
+
+        let root_cause = match abuser {
+            Some((user, count)) => format!(
+                "Content filter rate at {:.1}% (baseline {:.1}%), {} of {} filtered requests from user {}",
+                surge.rate * 100.0,
+                surge.baseline_rate * 100.0,
+                count,
+                surge.filtered,
+                user
+            ),
+            None => format!(
+                "Content filter rate at {:.1}% (baseline {:.1}%) across {} filtered requests",
+                surge.rate * 100.0,
+                surge.baseline_rate * 100.0,
+                surge.filtered
+            ),
+        };
+
+        let anomaly = AnomalyEvent::new(
+            severity,
+            anomaly_type,
+            event.service_name.clone(),
+            event.model.clone(),
+            DetectionMethod::Custom("content_filter_surge".to_string()),
+            (surge.rate / (surge.rate + surge.baseline_rate)).clamp(0.5, 0.99),
+            AnomalyDetails {
+                metric: "content_filter_rate".to_string(),
+                value: surge.rate,
+                baseline: surge.baseline_rate,
+                threshold: (self.config.multiplier * surge.baseline_rate).max(self.config.min_rate),
+                deviation_sigma: None,
+                additional,
+                observations: Vec::new(),
+            },
+            AnomalyContext {
+                trace_id: event.trace_id.clone(),
+                user_id: abuser.map(|(user, _)| user.clone()),
+                region: event.metadata.get("region").cloned(),
+                time_window: WindowSpec::samples(surge.window).to_string(),
+                sample_count: surge.window,
+                window: None,
+                additional: HashMap::new(),
+            },
+        )
+        .with_root_cause(root_cause);
+
+        if abuser.is_some() {
+            anomaly
+                .with_remediation("Review recent requests from the top user for abuse")
+                .with_remediation("Consider rate limiting or blocking the user")
+        } else {
+            anomaly
+                .with_remediation("Check for recent prompt template or system prompt changes")
+                .with_remediation("Review a sample of filtered requests")
+        }
+    }
+}
+
+#[async_trait]
+impl Detector for ContentFilterDetector {
+    async fn detect(&self, event: &TelemetryEvent) -> Result<Option<AnomalyEvent>> {
+        let key = Self::key(event);
+        let Some(state) = self.states.get(&key) else {
+            return Ok(None);
+        };
+        if state.in_surge {
+            return Ok(None);
+        }
+
+        // Evaluate on a copy; `update` commits the event
+        let mut next = state.clone();
+        drop(state);
+        next.push(self.is_filtered(event), self.user(event), &self.config);
+
+        Ok(next.evaluate(&self.config).map(|surge| {
+            debug!(
+                service = %event.service_name,
+                model = %event.model,
+                rate = surge.rate,
+                baseline = surge.baseline_rate,
+                "Content filter surge detected"
+            );
+            self.build_anomaly(event, &surge)
+        }))
+    }
+
+    fn name(&self) -> &str {
+        "content_filter"
+    }
+
+    fn detector_type(&self) -> DetectorType {
+        DetectorType::Statistical
+    }
+
+    async fn update(&mut self, event: &TelemetryEvent) -> Result<()> {
+        if !self.config.detection.update_baseline {
+            return Ok(());
+        }
+
+        let filtered = self.is_filtered(event);
+        let user = self.user(event);
+        let mut state = self.states.entry(Self::key(event)).or_default();
+        state.push(filtered, user, &self.config);
+        state.in_surge = state.evaluate(&self.config).is_some();
+
+        self.stats.events_processed += 1;
+        Ok(())
+    }
+
+    async fn reset(&mut self) -> Result<()> {
+        self.states.clear();
+        self.stats = DetectorStats::empty();
+        Ok(())
+    }
+
+    fn stats(&self) -> DetectorStats {
+        self.stats.clone()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use llm_sentinel_fixtures::event;
+
+    fn create_test_event(finish_reason: &str, user: &str) -> TelemetryEvent {
+        event()
+            .service("chat")
+            .finish_reason(finish_reason)
+            .metadata("user_id", user)
+            .build()
+    }
+
+    /// Warm up with a 1% filter rate
+    async fn warm_up(detector: &mut ContentFilterDetector) {
+        for i in 0..400 {
+            let reason = if i % 100 == 0 {
+                "content_filter"
+            } else {
+                "stop"
+            };
+            let event = create_test_event(reason, &format!("user-{}", i % 50));
+            assert!(detector.detect(&event).await.unwrap().is_none());
+            detector.update(&event).await.unwrap();
+        }
+    }
+
+    async fn feed(
+        detector: &mut ContentFilterDetector,
+        events: Vec<TelemetryEvent>,
+    ) -> Vec<AnomalyEvent> {
+        let mut detected = Vec::new();
+        for event in events {
+            if let Some(anomaly) = detector.detect(&event).await.unwrap() {
+                detected.push(anomaly);
+            }
+            detector.update(&event).await.unwrap();
+        }
+        detected
+    }
+
+    #[tokio::test]
+    async fn test_surge_from_prompt_change() {
+        let mut detector = ContentFilterDetector::new(ContentFilterConfig::default());
+        warm_up(&mut detector).await;
+
+        let events = (0..100)
+            .map(|i| {
+                let reason = if i % 5 == 0 { "content_filter" } else { "stop" };
+                create_test_event(reason, &format!("user-{}", i % 50))
+            })
+            .collect();
+        let detected = feed(&mut detector, events).await;
+
+        assert_eq!(detected.len(), 1);
+        assert_eq!(detected[0].anomaly_type, AnomalyType::QualityDegradation);
+        assert_eq!(detected[0].details.metric, "content_filter_rate");
+        assert!(detected[0].context.user_id.is_none());
+    }
+
+    #[tokio::test]
+    async fn test_surge_from_single_user() {
+        let mut detector = ContentFilterDetector::new(ContentFilterConfig::default());
+        warm_up(&mut detector).await;
+
+        let events = (0..100)
+            .map(|i| {
+                if i % 4 == 0 {
+                    create_test_event("CONTENT_FILTER", "mallory")
+                } else {
+                    create_test_event("stop", &format!("user-{}", i % 50))
+                }
+            })
+            .collect();
+        let detected = feed(&mut detector, events).await;
+
+        assert_eq!(detected.len(), 1);
+        assert_eq!(detected[0].anomaly_type, AnomalyType::SecurityThreat);
+        assert_eq!(detected[0].severity, Severity::High);
+        assert_eq!(detected[0].context.user_id.as_deref(), Some("mallory"));
+    }
+}
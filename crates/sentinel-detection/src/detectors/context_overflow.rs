@@ -0,0 +1,290 @@
+//! Context window overflow detector.
+//!
+//! Compares each request's prompt and response tokens with its model's
+//! context window from a [`CapabilityTable`]. Requests close to the window
+//! risk silent truncation: providers drop the oldest context or cut the
+//! response short without an error. Reported per request:
+//! - utilization: (prompt + response tokens) / context window
+//! - headroom: tokens and percentage of the window left unused (negative
+//!   past the window)
+
+use crate::{Detector, DetectorStats, DetectorType};
+use async_trait::async_trait;
+use llm_sentinel_core::{
+    capabilities::CapabilityTable,
+    config::ContextLimitConfig,
+    events::{AnomalyContext, AnomalyDetails, AnomalyEvent, TelemetryEvent},
+    types::{AnomalyType, DetectionMethod, Severity},
+    Result,
+};
+use std::collections::HashMap;
+use tracing::debug;
+
+/// Finish reason of responses cut off at the token limit
+const LENGTH_FINISH_REASON: &str = "length";
+
+/// Context overflow detector configuration
+#[derive(Debug, Clone)]
+pub struct ContextOverflowConfig {
+    /// Context windows by model
+    pub capabilities: CapabilityTable,
+    /// Share of the context window at which a request is flagged
+    pub warn_ratio: f64,
+}
+
+impl Default for ContextOverflowConfig {
+    fn default() -> Self {
+        Self {
+            capabilities: CapabilityTable::with_defaults(),
+            warn_ratio: 0.9,
+        }
+    }
+}
+
+impl ContextOverflowConfig {
+    /// Detector configuration for the given context limits
+    pub fn from_config(config: &ContextLimitConfig) -> Result<Self> {
+        Ok(Self {
+            capabilities: CapabilityTable::from_config(config)?,
+            warn_ratio: config.warn_ratio,
+        })
+    }
+}
+
+/// Context window use of one request
+#[derive(Debug, Clone, Copy)]
+struct ContextUse {
+    tokens: u64,
+    max_context_tokens: u32,
+}
+
+impl ContextUse {
+    fn utilization(&self) -> f64 {
+        self.tokens as f64 / self.max_context_tokens as f64
+    }
+
+    fn headroom_tokens(&self) -> i64 {
+        self.max_context_tokens as i64 - self.tokens as i64
+    }
+
+    fn headroom_pct(&self) -> f64 {
+        (1.0 - self.utilization()) * 100.0
+    }
+}
+
+/// Context window overflow detector
+///
+/// Emits [`AnomalyType::ContextOverflow`] with `metric: "context_tokens"`.
+/// Stateless: each request is judged on its own.
+#[derive(Debug)]
+pub struct ContextOverflowDetector {
+    config: ContextOverflowConfig,
+    stats: DetectorStats,
+}
+
+impl ContextOverflowDetector {
+    /// Create a new context overflow detector
+    pub fn new(config: ContextOverflowConfig) -> Self {
+        Self {
+            config,
+            stats: DetectorStats::empty(),
+        }
+    }
+
+    fn context_use(&self, event: &TelemetryEvent) -> Option<ContextUse> {
+        let max_context_tokens = self.config.capabilities.max_context_tokens(&event.model)?;
+        Some(ContextUse {
+            tokens: event.prompt.tokens as u64 + event.response.tokens as u64,
+            max_context_tokens,
+        })
+    }
+
+    fn build_anomaly(&self, event: &TelemetryEvent, context: ContextUse) -> AnomalyEvent {
+        let utilization = context.utilization();
+        let truncated = event.response.finish_reason == LENGTH_FINISH_REASON;
+        let severity = if utilization >= 1.0 || truncated {
+            Severity::High
+        } else {
+            Severity::Medium
+        };
+
+        let mut additional = HashMap::new();
+        additional.insert("utilization".to_string(), serde_json::json!(utilization));
+        additional.insert(
+            "headroom_pct".to_string(),
+            serde_json::json!(context.headroom_pct()),
+        );
+        additional.insert(
+            "headroom_tokens".to_string(),
+            serde_json::json!(context.headroom_tokens()),
+        );
+        additional.insert(
+            "max_context_tokens".to_string(),
+            serde_json::json!(context.max_context_tokens),
+        );
+        additional.insert(
+            "prompt_tokens".to_string(),
+            serde_json::json!(event.prompt.tokens),
+        );
+        additional.insert(
+            "response_tokens".to_string(),
+            serde_json::json!(event.response.tokens),
+        );
+        additional.insert(
+            "finish_reason".to_string(),
+            serde_json::json!(event.response.finish_reason),
+        );
+
+        let root_cause = if utilization >= 1.0 {
+            format!(
+                "Request used {} tokens, {} past the {}-token context window of {}",
+                context.tokens,
+                -context.headroom_tokens(),
+                context.max_context_tokens,
+                event.model
+            )
+        } else {
+            format!(
+                "Request used {:.0}% of the {}-token context window of {} ({:.1}% headroom)",
+                utilization * 100.0,
+                context.max_context_tokens,
+                event.model,
+                context.headroom_pct()
+            )
+        };
+        let root_cause = if truncated {
+            format!("{}; the response stopped at the length limit", root_cause)
+        } else {
+            root_cause
+        };
+
+        AnomalyEvent::new(
+            severity,
+            AnomalyType::ContextOverflow,
+            event.service_name.clone(),
+            event.model.clone(),
+            DetectionMethod::Custom("context_overflow".to_string()),
+            utilization.clamp(0.5, 0.99),
+            AnomalyDetails {
+                metric: "context_tokens".to_string(),
+                value: context.tokens as f64,
+                baseline: context.max_context_tokens as f64,
+                threshold: context.max_context_tokens as f64 * self.config.warn_ratio,
+                deviation_sigma: None,
+                additional,
+                observations: Vec::new(),
+            },
+            AnomalyContext {
+                trace_id: event.trace_id.clone(),
+                user_id: None,
+                region: None,
+                time_window: "request".to_string(),
+                sample_count: 1,
+                window: None,
+                additional: HashMap::new(),
+            },
+        )
+        .with_root_cause(root_cause)
+        .with_remediation("Trim conversation history or retrieved context before the request")
+        .with_remediation("Cap max_tokens so prompt and response fit the context window")
+        .with_remediation("Route long requests to a model with a larger context window")
+    }
+}
+
+#[async_trait]
+impl Detector for ContextOverflowDetector {
+    async fn detect(&self, event: &TelemetryEvent) -> Result<Option<AnomalyEvent>> {
+        let Some(context) = self.context_use(event) else {
+            return Ok(None);
+        };
+        if context.utilization() < self.config.warn_ratio {
+            return Ok(None);
+        }
+
+        debug!(
+            service = %event.service_name,
+            model = %event.model,
+            tokens = context.tokens,
+            max_context_tokens = context.max_context_tokens,
+            "Request near the model's context window"
+        );
+        Ok(Some(self.build_anomaly(event, context)))
+    }
+
+    fn name(&self) -> &str {
+        "context_overflow"
+    }
+
+    fn detector_type(&self) -> DetectorType {
+        DetectorType::Statistical
+    }
+
+    async fn update(&mut self, _event: &TelemetryEvent) -> Result<()> {
+        self.stats.events_processed += 1;
+        Ok(())
+    }
+
+    async fn reset(&mut self) -> Result<()> {
+        self.stats = DetectorStats::empty();
+        Ok(())
+    }
+
+    fn stats(&self) -> DetectorStats {
+        self.stats.clone()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use llm_sentinel_fixtures::event;
+
+    fn create_test_event(
+        model: &str,
+        prompt_tokens: u32,
+        response_tokens: u32,
+        finish_reason: &str,
+    ) -> TelemetryEvent {
+        event()
+            .service("support-bot")
+            .model(model)
+            .tokens(prompt_tokens, response_tokens)
+            .finish_reason(finish_reason)
+            .build()
+    }
+
+    #[tokio::test]
+    async fn test_approaching_and_exceeding_flagged() {
+        let detector = ContextOverflowDetector::new(ContextOverflowConfig::default());
+
+        // gpt-4 has an 8192-token window
+        let roomy = create_test_event("gpt-4-0613", 4000, 500, "stop");
+        assert!(detector.detect(&roomy).await.unwrap().is_none());
+
+        let close = create_test_event("gpt-4-0613", 7000, 500, "stop");
+        let anomaly = detector.detect(&close).await.unwrap().unwrap();
+        assert_eq!(anomaly.anomaly_type, AnomalyType::ContextOverflow);
+        assert_eq!(anomaly.severity, Severity::Medium);
+        assert_eq!(anomaly.details.metric, "context_tokens");
+        assert_eq!(anomaly.details.additional["headroom_tokens"], 692);
+        let headroom = anomaly.details.additional["headroom_pct"].as_f64().unwrap();
+        assert!((headroom - 8.447).abs() < 0.01);
+
+        let over = create_test_event("gpt-4-0613", 8000, 500, "stop");
+        let anomaly = detector.detect(&over).await.unwrap().unwrap();
+        assert_eq!(anomaly.severity, Severity::High);
+        assert!(anomaly.details.additional["headroom_pct"].as_f64().unwrap() < 0.0);
+
+        // Cut off at the length limit near the window: silent truncation
+        let truncated = create_test_event("gpt-4-0613", 7000, 500, "length");
+        let anomaly = detector.detect(&truncated).await.unwrap().unwrap();
+        assert_eq!(anomaly.severity, Severity::High);
+    }
+
+    #[tokio::test]
+    async fn test_unknown_model_ignored() {
+        let detector = ContextOverflowDetector::new(ContextOverflowConfig::default());
+        let event = create_test_event("in-house-llm", 1_000_000, 500, "length");
+        assert!(detector.detect(&event).await.unwrap().is_none());
+    }
+}
@@ -0,0 +1,389 @@
+//! Cost residual detector.
+//!
+//! Cost scales with tokens, so a raw cost spike usually repeats a token
+//! spike. This detector fits an outlier-resistant regression of cost on
+//! prompt and response tokens per model and flags requests that cost far
+//! more than the fit predicts for their token counts:
+//! - pricing errors (a wrong rate in the price table or at the provider)
+//! - wrong-model routing (a pricier model serving this model's traffic)
+//!
+//! Anomalous requests stay in the fit window; the Huber weights keep them
+//! from pulling the fit towards themselves.
+
+use crate::{
+    stats::{huber_fit, LinearFit},
+    Detector, DetectorStats, DetectorType,
+};
+use async_trait::async_trait;
+use dashmap::DashMap;
+use llm_sentinel_core::{
+    config::CostRegressionConfig,
+    events::{AnomalyContext, AnomalyDetails, AnomalyEvent, TelemetryEvent},
+    types::{AnomalyType, DetectionMethod, ModelId, Severity},
+    window::WindowSpec,
+    Result,
+};
+use std::{
+    collections::{HashMap, VecDeque},
+    sync::Arc,
+};
+use tracing::debug;
+
+/// Cost residual detector configuration
+#[derive(Debug, Clone)]
+pub struct CostResidualConfig {
+    /// Recent requests per model the fit is computed over
+    pub window_size: usize,
+    /// Requests needed before the first fit
+    pub min_samples: usize,
+    /// Requests between refits
+    pub refit_interval: usize,
+    /// Residual (in robust standard deviations) above which a request is
+    /// flagged
+    pub threshold_sigma: f64,
+    /// Share of the predicted cost a request must exceed it by
+    pub min_excess_ratio: f64,
+    /// Excess over the predicted cost reported as high severity
+    pub high_excess_ratio: f64,
+}
+
+impl Default for CostResidualConfig {
+    fn default() -> Self {
+        Self {
+            window_size: 500,
+            min_samples: 100,
+            refit_interval: 50,
+            threshold_sigma: 4.0,
+            min_excess_ratio: 0.25,
+            high_excess_ratio: 1.0,
+        }
+    }
+}
+
+impl CostResidualConfig {
+    /// Detector configuration for the given cost regression settings
+    pub fn from_config(config: &CostRegressionConfig) -> Self {
+        Self {
+            window_size: config.window_size,
+            min_samples: config.min_samples,
+            refit_interval: config.refit_interval,
+            threshold_sigma: config.threshold_sigma,
+            min_excess_ratio: config.min_excess_ratio,
+            high_excess_ratio: config.high_excess_ratio,
+        }
+    }
+}
+
+/// Token counts and cost of one request
+#[derive(Debug, Clone, Copy)]
+struct Observation {
+    tokens: [f64; 2],
+    cost_usd: f64,
+}
+
+impl Observation {
+    /// Requests without a reported cost are not observations
+    fn from_event(event: &TelemetryEvent) -> Option<Self> {
+        (event.cost_usd > 0.0).then_some(Self {
+            tokens: [event.prompt.tokens as f64, event.response.tokens as f64],
+            cost_usd: event.cost_usd,
+        })
+    }
+}
+
+/// Fit state for one model
+#[derive(Debug, Clone, Default)]
+struct RegressionState {
+    window: VecDeque<Observation>,
+    fit: Option<LinearFit>,
+    since_fit: usize,
+}
+
+impl RegressionState {
+    fn push(&mut self, observation: Observation, config: &CostResidualConfig) {
+        self.window.push_back(observation);
+        if self.window.len() > config.window_size {
+            self.window.pop_front();
+        }
+        self.since_fit += 1;
+
+        let min_samples = config.min_samples.min(config.window_size);
+        if self.window.len() >= min_samples
+            && (self.fit.is_none() || self.since_fit >= config.refit_interval)
+        {
+            self.refit();
+        }
+    }
+
+    fn refit(&mut self) {
+        let (tokens, costs): (Vec<[f64; 2]>, Vec<f64>) = self
+            .window
+            .iter()
+            .map(|observation| (observation.tokens, observation.cost_usd))
+            .unzip();
+        self.fit = huber_fit(&tokens, &costs);
+        self.since_fit = 0;
+    }
+}
+
+/// A request costing more than predicted for its tokens
+#[derive(Debug, Clone)]
+struct Excess {
+    observation: Observation,
+    predicted: f64,
+    fit: LinearFit,
+}
+
+impl Excess {
+    fn residual(&self) -> f64 {
+        self.observation.cost_usd - self.predicted
+    }
+
+    /// Excess as a share of the predicted cost
+    fn ratio(&self) -> f64 {
+        self.residual() / self.predicted
+    }
+
+    /// Residual in robust standard deviations, if the fit has any spread
+    fn sigma(&self) -> Option<f64> {
+        (self.fit.scale > 0.0).then_some(self.residual() / self.fit.scale)
+    }
+}
+
+/// Cost residual detector
+///
+/// Emits [`AnomalyType::CostAnomaly`] with `metric: "cost_residual"`.
+pub struct CostResidualDetector {
+    config: CostResidualConfig,
+    states: Arc<DashMap<ModelId, RegressionState>>,
+    stats: DetectorStats,
+}
+
+impl std::fmt::Debug for CostResidualDetector {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("CostResidualDetector")
+            .field("config", &self.config)
+            .field("models_count", &self.states.len())
+            .field("stats", &self.stats)
+            .finish()
+    }
+}
+
+impl CostResidualDetector {
+    /// Create a new cost residual detector
+    pub fn new(config: CostResidualConfig) -> Self {
+        Self {
+            config,
+            states: Arc::new(DashMap::new()),
+            stats: DetectorStats::empty(),
+        }
+    }
+
+    /// Current fit for a model, if enough requests have been seen
+    pub fn fit(&self, model: &ModelId) -> Option<LinearFit> {
+        self.states.get(model)?.fit.clone()
+    }
+
+    fn evaluate(&self, event: &TelemetryEvent) -> Option<Excess> {
+        let observation = Observation::from_event(event)?;
+        let fit = self.fit(&event.model)?;
+        let predicted = fit.predict(&observation.tokens);
+        if predicted <= 0.0 {
+            return None;
+        }
+
+        let excess = Excess {
+            observation,
+            predicted,
+            fit,
+        };
+        let residual = excess.residual();
+        (residual > self.config.threshold_sigma * excess.fit.scale
+            && excess.ratio() >= self.config.min_excess_ratio)
+            .then_some(excess)
+    }
+
+    fn build_anomaly(&self, event: &TelemetryEvent, excess: &Excess) -> AnomalyEvent {
+        let severity = if excess.ratio() >= self.config.high_excess_ratio {
+            Severity::High
+        } else {
+            Severity::Medium
+        };
+
+        let fit = &excess.fit;
+        let [prompt_tokens, response_tokens] = excess.observation.tokens;
+        let mut additional = HashMap::new();
+        for (name, value) in [
+            ("predicted_cost_usd", excess.predicted),
+            ("residual_usd", excess.residual()),
+            ("excess_ratio", excess.ratio()),
+            ("residual_scale_usd", fit.scale),
+            ("prompt_tokens", prompt_tokens),
+            ("response_tokens", response_tokens),
+            ("fit_intercept_usd", fit.intercept()),
+            ("fit_prompt_cost_per_1k", fit.coefficients[1] * 1000.0),
+            ("fit_response_cost_per_1k", fit.coefficients[2] * 1000.0),
+        ] {
+            additional.insert(name.to_string(), serde_json::json!(value));
+        }
+
+        AnomalyEvent::new(
+            severity,
+            AnomalyType::CostAnomaly,
+            event.service_name.clone(),
+            event.model.clone(),
+            DetectionMethod::Custom("cost_residual".to_string()),
+            (excess.ratio() / self.config.high_excess_ratio.max(f64::EPSILON)).clamp(0.5, 0.99),
+            AnomalyDetails {
+                metric: "cost_residual".to_string(),
+                value: excess.observation.cost_usd,
+                baseline: excess.predicted,
+                threshold: excess.predicted
+                    + (self.config.threshold_sigma * fit.scale)
+                        .max(self.config.min_excess_ratio * excess.predicted),
+                deviation_sigma: excess.sigma(),
+                additional,
+                observations: Vec::new(),
+            },
+            AnomalyContext {
+                trace_id: event.trace_id.clone(),
+                user_id: None,
+                region: None,
+                time_window: WindowSpec::samples(self.config.window_size).to_string(),
+                sample_count: fit.count,
+                window: None,
+                additional: HashMap::new(),
+            },
+        )
+        .with_root_cause(format!(
+            "Cost ${:.4} is {:.1}x the ${:.4} expected for {:.0} prompt and {:.0} response tokens on {}",
+            excess.observation.cost_usd,
+            excess.observation.cost_usd / excess.predicted,
+            excess.predicted,
+            prompt_tokens,
+            response_tokens,
+            event.model
+        ))
+        .with_remediation("Check the price table and provider billing for a changed or wrong rate")
+        .with_remediation("Verify the request was served by the intended model and not a pricier one")
+    }
+}
+
+#[async_trait]
+impl Detector for CostResidualDetector {
+    async fn detect(&self, event: &TelemetryEvent) -> Result<Option<AnomalyEvent>> {
+        Ok(self.evaluate(event).map(|excess| {
+            debug!(
+                service = %event.service_name,
+                model = %event.model,
+                cost_usd = event.cost_usd,
+                predicted_cost_usd = excess.predicted,
+                "Request cost above the cost-on-tokens fit"
+            );
+            self.build_anomaly(event, &excess)
+        }))
+    }
+
+    fn name(&self) -> &str {
+        "cost_residual"
+    }
+
+    fn detector_type(&self) -> DetectorType {
+        DetectorType::Statistical
+    }
+
+    async fn update(&mut self, event: &TelemetryEvent) -> Result<()> {
+        if let Some(observation) = Observation::from_event(event) {
+            self.states
+                .entry(event.model.clone())
+                .or_default()
+                .push(observation, &self.config);
+        }
+
+        self.stats.events_processed += 1;
+        Ok(())
+    }
+
+    async fn reset(&mut self) -> Result<()> {
+        self.states.clear();
+        self.stats = DetectorStats::empty();
+        Ok(())
+    }
+
+    fn stats(&self) -> DetectorStats {
+        self.stats.clone()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use llm_sentinel_fixtures::event;
+
+    /// gpt-4 list prices: $0.03 / 1k prompt and $0.06 / 1k response tokens
+    fn price(prompt_tokens: u32, response_tokens: u32) -> f64 {
+        (prompt_tokens as f64 * 0.03 + response_tokens as f64 * 0.06) / 1000.0
+    }
+
+    fn create_test_event(
+        prompt_tokens: u32,
+        response_tokens: u32,
+        cost_usd: f64,
+    ) -> TelemetryEvent {
+        event()
+            .service("support-bot")
+            .tokens(prompt_tokens, response_tokens)
+            .cost_usd(cost_usd)
+            .build()
+    }
+
+    async fn train(detector: &mut CostResidualDetector) {
+        for i in 0..200u32 {
+            let (prompt, response) = (200 + (i * 37) % 1800, 50 + (i * 13) % 700);
+            detector
+                .update(&create_test_event(
+                    prompt,
+                    response,
+                    price(prompt, response),
+                ))
+                .await
+                .unwrap();
+        }
+    }
+
+    #[tokio::test]
+    async fn test_overpriced_request_flagged() {
+        let mut detector = CostResidualDetector::new(CostResidualConfig::default());
+        let overpriced = create_test_event(1000, 200, price(1000, 200) * 3.0);
+        assert!(detector.detect(&overpriced).await.unwrap().is_none());
+
+        train(&mut detector).await;
+        let fit = detector.fit(&ModelId::new("gpt-4")).unwrap();
+        assert!((fit.coefficients[1] * 1000.0 - 0.03).abs() < 1e-6);
+
+        let anomaly = detector.detect(&overpriced).await.unwrap().unwrap();
+        assert_eq!(anomaly.anomaly_type, AnomalyType::CostAnomaly);
+        assert_eq!(anomaly.severity, Severity::High);
+        assert_eq!(anomaly.details.metric, "cost_residual");
+        assert!((anomaly.details.baseline - price(1000, 200)).abs() < 1e-6);
+        let ratio = anomaly.details.additional["excess_ratio"].as_f64().unwrap();
+        assert!((ratio - 2.0).abs() < 1e-3);
+
+        let pricier = create_test_event(1000, 200, price(1000, 200) * 1.5);
+        let anomaly = detector.detect(&pricier).await.unwrap().unwrap();
+        assert_eq!(anomaly.severity, Severity::Medium);
+    }
+
+    #[tokio::test]
+    async fn test_token_driven_cost_ignored() {
+        let mut detector = CostResidualDetector::new(CostResidualConfig::default());
+        train(&mut detector).await;
+
+        // Expensive because it is long, not because it is mispriced
+        let long = create_test_event(30_000, 4_000, price(30_000, 4_000));
+        assert!(detector.detect(&long).await.unwrap().is_none());
+
+        let cheap = create_test_event(1000, 200, price(1000, 200) * 0.5);
+        assert!(detector.detect(&cheap).await.unwrap().is_none());
+    }
+}
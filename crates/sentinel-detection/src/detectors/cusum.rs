@@ -12,8 +12,9 @@ use dashmap::DashMap;
 use llm_sentinel_core::{
     events::{AnomalyContext, AnomalyDetails, AnomalyEvent, TelemetryEvent},
     types::{AnomalyType, DetectionMethod, Severity},
-    Result,
+    Error, Result,
 };
+use llm_sentinel_stats::Cusum;
 use std::{collections::HashMap, sync::Arc};
 
 /// CUSUM detector configuration
@@ -37,39 +38,12 @@ impl Default for CusumConfig {
     }
 }
 
-/// CUSUM state for a specific metric
-#[derive(Debug, Clone)]
-struct CusumState {
-    /// Cumulative sum (positive deviations)
-    cusum_pos: f64,
-    /// Cumulative sum (negative deviations)
-    cusum_neg: f64,
-    /// Last update count
-    count: u64,
-}
-
-impl CusumState {
-    fn new() -> Self {
-        Self {
-            cusum_pos: 0.0,
-            cusum_neg: 0.0,
-            count: 0,
-        }
-    }
-
-    fn reset(&mut self) {
-        self.cusum_pos = 0.0;
-        self.cusum_neg = 0.0;
-        self.count = 0;
-    }
-}
-
 /// CUSUM anomaly detector
 ///
 /// Detects gradual changes in the process mean.
 /// Good for detecting sustained shifts rather than individual outliers.
 ///
-/// Formula:
+/// Formula (see [`Cusum`]):
 /// S_H = max(0, S_H + x - μ - k)  // Positive shifts
 /// S_L = min(0, S_L + x - μ + k)  // Negative shifts
 ///
@@ -77,7 +51,7 @@ impl CusumState {
 pub struct CusumDetector {
     config: CusumConfig,
     baseline_manager: Arc<BaselineManager>,
-    states: Arc<DashMap<BaselineKey, CusumState>>,
+    states: Arc<DashMap<BaselineKey, Cusum>>,
     stats: DetectorStats,
 }
 
@@ -113,24 +87,21 @@ impl CusumDetector {
         let cost = event.cost_usd;
 
         // Get or create CUSUM state
-        let mut state_ref = self.states.entry(key.clone()).or_insert_with(CusumState::new);
+        let mut state_ref = self.states.entry(key.clone()).or_default();
         let state = state_ref.value_mut();
 
         // Update CUSUM
-        let deviation = cost - baseline.mean;
-        state.cusum_pos = (state.cusum_pos + deviation - self.config.slack).max(0.0);
-        state.cusum_neg = (state.cusum_neg + deviation + self.config.slack).min(0.0);
-        state.count += 1;
+        state.update(cost, baseline.mean, self.config.slack);
 
         // Check if threshold exceeded
-        if state.cusum_pos > self.config.threshold || state.cusum_neg.abs() > self.config.threshold {
-            let severity = if state.cusum_pos > self.config.threshold * 2.0 {
+        if state.exceeds(self.config.threshold) {
+            let severity = if state.pos > self.config.threshold * 2.0 {
                 Severity::High
             } else {
                 Severity::Medium
             };
 
-            let confidence = (state.cusum_pos.max(state.cusum_neg.abs()) / self.config.threshold).min(0.95);
+            let confidence = (state.magnitude() / self.config.threshold).min(0.95);
 
             let anomaly = AnomalyEvent::new(
                 severity,
@@ -147,11 +118,12 @@ impl CusumDetector {
                     deviation_sigma: None,
                     additional: {
                         let mut map = HashMap::new();
-                        map.insert("cusum_pos".to_string(), serde_json::json!(state.cusum_pos));
-                        map.insert("cusum_neg".to_string(), serde_json::json!(state.cusum_neg));
+                        map.insert("cusum_pos".to_string(), serde_json::json!(state.pos));
+                        map.insert("cusum_neg".to_string(), serde_json::json!(state.neg));
                         map.insert("samples".to_string(), serde_json::json!(state.count));
                         map
                     },
+                    observations: Vec::new(),
                 },
                 AnomalyContext {
                     trace_id: event.trace_id.clone(),
@@ -159,12 +131,13 @@ impl CusumDetector {
                     region: event.metadata.get("region").cloned(),
                     time_window: format!("last_{}_samples", state.count),
                     sample_count: baseline.sample_count,
+                    window: baseline.window.clone(),
                     additional: HashMap::new(),
                 },
             )
             .with_root_cause(format!(
                 "Sustained cost increase detected (CUSUM: {:.2}, baseline: ${:.4})",
-                state.cusum_pos, baseline.mean
+                state.pos, baseline.mean
             ))
             .with_remediation("Review recent API usage patterns")
             .with_remediation("Check for model version changes or pricing updates");
@@ -202,7 +175,8 @@ impl Detector for CusumDetector {
         }
 
         let key = BaselineKey::cost(event.service_name.clone(), event.model.clone());
-        self.baseline_manager.update(key, event.cost_usd)?;
+        self.baseline_manager
+            .update_at(key, event.cost_usd, event.timestamp)?;
         Ok(())
     }
 
@@ -216,6 +190,27 @@ impl Detector for CusumDetector {
     fn stats(&self) -> DetectorStats {
         self.stats.clone()
     }
+
+    fn export_state(&self) -> Result<Option<serde_json::Value>> {
+        let states: Vec<(BaselineKey, Cusum)> = self
+            .states
+            .iter()
+            .map(|entry| (entry.key().clone(), *entry.value()))
+            .collect();
+        serde_json::to_value(states)
+            .map(Some)
+            .map_err(|e| Error::internal(format!("Failed to export CUSUM state: {}", e)))
+    }
+
+    fn import_state(&mut self, state: serde_json::Value) -> Result<()> {
+        let states: Vec<(BaselineKey, Cusum)> = serde_json::from_value(state)
+            .map_err(|e| Error::validation(format!("Invalid CUSUM state: {}", e)))?;
+        self.states.clear();
+        for (key, state) in states {
+            self.states.insert(key, state);
+        }
+        Ok(())
+    }
 }
 
 impl CusumDetector {
@@ -232,29 +227,11 @@ impl CusumDetector {
 #[cfg(test)]
 mod tests {
     use super::*;
-    use llm_sentinel_core::{
-        events::{PromptInfo, ResponseInfo},
-        types::{ModelId, ServiceId},
-    };
+    use llm_sentinel_core::types::{ModelId, ServiceId};
+    use llm_sentinel_fixtures::event;
 
     fn create_test_event(cost: f64) -> TelemetryEvent {
-        TelemetryEvent::new(
-            ServiceId::new("test"),
-            ModelId::new("gpt-4"),
-            PromptInfo {
-                text: "test".to_string(),
-                tokens: 10,
-                embedding: None,
-            },
-            ResponseInfo {
-                text: "response".to_string(),
-                tokens: 20,
-                finish_reason: "stop".to_string(),
-                embedding: None,
-            },
-            100.0,
-            cost,
-        )
+        event().cost_usd(cost).build()
     }
 
     #[tokio::test]
@@ -272,12 +249,32 @@ mod tests {
         // Gradual increase should trigger CUSUM
         for _ in 0..10 {
             let event = create_test_event(0.02);
-            let result = detector.detect(&event).await;
-            if result.is_ok() && result.as_ref().unwrap().is_some() {
-                let anomaly = result.unwrap().unwrap();
+            if let Ok(Some(anomaly)) = detector.detect(&event).await {
                 assert_eq!(anomaly.detection_method, DetectionMethod::Cusum);
                 break;
             }
         }
     }
+
+    #[tokio::test]
+    async fn test_cusum_state_export_import() {
+        let baseline_manager = Arc::new(BaselineManager::new(20));
+        let detector = CusumDetector::new(CusumConfig::default(), Arc::clone(&baseline_manager));
+        for _ in 0..20 {
+            baseline_manager
+                .update(BaselineKey::cost(ServiceId::new("test"), ModelId::new("gpt-4")), 0.01)
+                .unwrap();
+        }
+        detector.detect(&create_test_event(0.5)).await.unwrap();
+
+        let state = detector.export_state().unwrap().unwrap();
+        let mut restored = CusumDetector::new(CusumConfig::default(), baseline_manager);
+        restored.import_state(state).unwrap();
+        let key = BaselineKey::cost(ServiceId::new("test"), ModelId::new("gpt-4"));
+        assert_eq!(
+            *restored.states.get(&key).unwrap(),
+            *detector.states.get(&key).unwrap()
+        );
+        assert_eq!(restored.states.get(&key).unwrap().count, 1);
+    }
 }
@@ -112,13 +112,15 @@ impl IqrDetector {
                         map.insert("upper_bound".to_string(), serde_json::json!(upper_bound));
                         map
                     },
+                    observations: Vec::new(),
                 },
                 AnomalyContext {
                     trace_id: event.trace_id.clone(),
                     user_id: event.metadata.get("user_id").cloned(),
                     region: event.metadata.get("region").cloned(),
-                    time_window: "rolling_window".to_string(),
+                    time_window: self.baseline_manager.spec().to_string(),
                     sample_count: baseline.sample_count,
+                    window: baseline.window.clone(),
                     additional: HashMap::new(),
                 },
             )
@@ -187,7 +189,7 @@ impl Detector for IqrDetector {
 
         let latency_key = BaselineKey::latency(event.service_name.clone(), event.model.clone());
         self.baseline_manager
-            .update(latency_key, event.latency_ms)?;
+            .update_at(latency_key, event.latency_ms, event.timestamp)?;
 
         Ok(())
     }
@@ -206,29 +208,10 @@ impl Detector for IqrDetector {
 #[cfg(test)]
 mod tests {
     use super::*;
-    use llm_sentinel_core::{
-        events::{PromptInfo, ResponseInfo},
-        types::{ModelId, ServiceId},
-    };
+    use llm_sentinel_fixtures::event;
 
     fn create_test_event(latency: f64) -> TelemetryEvent {
-        TelemetryEvent::new(
-            ServiceId::new("test"),
-            ModelId::new("gpt-4"),
-            PromptInfo {
-                text: "test".to_string(),
-                tokens: 10,
-                embedding: None,
-            },
-            ResponseInfo {
-                text: "response".to_string(),
-                tokens: 20,
-                finish_reason: "stop".to_string(),
-                embedding: None,
-            },
-            latency,
-            0.01,
-        )
+        event().latency_ms(latency).build()
     }
 
     #[tokio::test]
@@ -0,0 +1,619 @@
+//! KL divergence output drift detector.
+//!
+//! Compares the distribution of recent model outputs against a reference
+//! window per model, using two signals:
+//! - response length (tokens), binned at the reference quantiles
+//! - finish reason (categorical: `stop`, `length`, `content_filter`, ...)
+//!
+//! D_KL(recent || reference) = Σ p(x) × ln(p(x) / q(x))
+//!
+//! Shifts in either typically follow silent provider-side model updates.
+//! The reference can stay fixed, be rebuilt periodically, or be rebuilt from
+//! post-drift traffic once a drift has been reported.
+
+use crate::{stats, Detector, DetectorStats, DetectorType};
+use async_trait::async_trait;
+use dashmap::DashMap;
+use llm_sentinel_core::{
+    config::{OutputDriftConfig, OutputDriftRefresh},
+    events::{AnomalyContext, AnomalyDetails, AnomalyEvent, TelemetryEvent},
+    types::{AnomalyType, DetectionMethod, ModelId, ServiceId, Severity},
+    window::WindowSpec,
+    Result,
+};
+use std::{
+    collections::{HashMap, VecDeque},
+    sync::Arc,
+};
+use tracing::{debug, info};
+
+/// Probability floor so unseen outcomes don't produce infinite divergence
+const MIN_PROBABILITY: f64 = 1e-4;
+
+/// How the reference distribution is refreshed
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ReferenceRefresh {
+    /// Keep the first reference forever
+    Fixed,
+    /// Rebuild the reference from recent history every `every` events
+    Periodic {
+        /// Events between refreshes
+        every: usize,
+    },
+    /// After a drift is reported, rebuild the reference once a full
+    /// reference window of post-drift events has been seen
+    OnDrift,
+}
+
+/// KL divergence detector configuration
+#[derive(Debug, Clone)]
+pub struct KlDivergenceConfig {
+    /// Events in the reference window
+    pub reference_size: usize,
+    /// Events in the recent window
+    pub window_size: usize,
+    /// Number of response length bins
+    pub length_bins: usize,
+    /// Divergence (nats) at which drift is reported
+    pub threshold: f64,
+    /// Divergence (nats) at which drift is reported as high severity
+    pub high_threshold: f64,
+    /// Reference refresh policy
+    pub refresh: ReferenceRefresh,
+}
+
+impl Default for KlDivergenceConfig {
+    fn default() -> Self {
+        Self {
+            reference_size: 1000,
+            window_size: 500,
+            length_bins: 10,
+            threshold: 0.1,
+            high_threshold: 0.5,
+            refresh: ReferenceRefresh::OnDrift,
+        }
+    }
+}
+
+impl KlDivergenceConfig {
+    /// Detector configuration for the given output drift settings
+    pub fn from_config(config: &OutputDriftConfig) -> Self {
+        Self {
+            reference_size: config.reference_size,
+            window_size: config.window_size,
+            length_bins: config.length_bins,
+            threshold: config.threshold,
+            high_threshold: config.high_threshold,
+            refresh: match config.refresh {
+                OutputDriftRefresh::Fixed => ReferenceRefresh::Fixed,
+                OutputDriftRefresh::Periodic { every } => ReferenceRefresh::Periodic { every },
+                OutputDriftRefresh::OnDrift => ReferenceRefresh::OnDrift,
+            },
+        }
+    }
+}
+
+/// Output signal being compared
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Signal {
+    ResponseLength,
+    FinishReason,
+}
+
+impl Signal {
+    const ALL: [Signal; 2] = [Signal::ResponseLength, Signal::FinishReason];
+
+    fn metric(&self) -> &'static str {
+        match self {
+            Signal::ResponseLength => "response_length",
+            Signal::FinishReason => "finish_reason",
+        }
+    }
+}
+
+/// One output observation
+#[derive(Debug, Clone)]
+struct Sample {
+    length: f64,
+    finish_reason: String,
+}
+
+impl Sample {
+    fn from_event(event: &TelemetryEvent) -> Self {
+        Self {
+            length: event.response.tokens as f64,
+            finish_reason: event.response.finish_reason.clone(),
+        }
+    }
+}
+
+/// Reference distributions
+#[derive(Debug, Clone)]
+struct Reference {
+    edges: Vec<f64>,
+    lengths: Vec<f64>,
+    finish_reasons: HashMap<String, f64>,
+}
+
+impl Reference {
+    fn build(samples: &VecDeque<Sample>, bins: usize) -> Self {
+        let values: Vec<f64> = samples.iter().map(|s| s.length).collect();
+        let edges = stats::quantile_edges(&values, bins);
+
+        let mut counts = vec![0u64; edges.len() + 1];
+        let mut reasons: HashMap<String, u64> = HashMap::new();
+        for sample in samples {
+            counts[stats::bin_index(&edges, sample.length)] += 1;
+            *reasons.entry(sample.finish_reason.clone()).or_default() += 1;
+        }
+
+        let total = samples.len().max(1) as f64;
+        Self {
+            edges,
+            lengths: counts.iter().map(|c| *c as f64 / total).collect(),
+            finish_reasons: reasons
+                .into_iter()
+                .map(|(reason, count)| (reason, count as f64 / total))
+                .collect(),
+        }
+    }
+}
+
+/// Recent window counts
+#[derive(Debug, Clone, Default)]
+struct WindowCounts {
+    total: u64,
+    lengths: Vec<u64>,
+    finish_reasons: HashMap<String, u64>,
+}
+
+impl WindowCounts {
+    fn add(&mut self, bin: usize, reason: &str) {
+        self.total += 1;
+        self.lengths[bin] += 1;
+        *self.finish_reasons.entry(reason.to_string()).or_default() += 1;
+    }
+
+    fn remove(&mut self, bin: usize, reason: &str) {
+        self.total -= 1;
+        self.lengths[bin] -= 1;
+        if let Some(count) = self.finish_reasons.get_mut(reason) {
+            *count -= 1;
+            if *count == 0 {
+                self.finish_reasons.remove(reason);
+            }
+        }
+    }
+
+    /// Divergence of the window from the reference for a signal
+    fn divergence(&self, reference: &Reference, signal: Signal) -> f64 {
+        let total = self.total.max(1) as f64;
+        match signal {
+            Signal::ResponseLength => self
+                .lengths
+                .iter()
+                .zip(&reference.lengths)
+                .map(|(count, q)| kl_term(*count as f64 / total, *q))
+                .sum(),
+            Signal::FinishReason => self
+                .finish_reasons
+                .iter()
+                .map(|(reason, count)| {
+                    let q = reference.finish_reasons.get(reason).copied().unwrap_or(0.0);
+                    kl_term(*count as f64 / total, q)
+                })
+                .sum(),
+        }
+    }
+
+    /// Window distribution for a signal, for reporting
+    fn distribution(&self, signal: Signal) -> serde_json::Value {
+        let total = self.total.max(1) as f64;
+        match signal {
+            Signal::ResponseLength => serde_json::json!(self
+                .lengths
+                .iter()
+                .map(|c| *c as f64 / total)
+                .collect::<Vec<_>>()),
+            Signal::FinishReason => serde_json::json!(self
+                .finish_reasons
+                .iter()
+                .map(|(reason, count)| (reason.clone(), *count as f64 / total))
+                .collect::<HashMap<_, _>>()),
+        }
+    }
+}
+
+/// Single term of the divergence sum
+fn kl_term(p: f64, q: f64) -> f64 {
+    if p <= 0.0 {
+        return 0.0;
+    }
+    p * (p / q.max(MIN_PROBABILITY)).ln()
+}
+
+/// Per-model detector state
+#[derive(Debug)]
+struct KlState {
+    /// Most recent samples (up to `reference_size`), used to (re)build references
+    history: VecDeque<Sample>,
+    reference: Option<Reference>,
+    /// Length bin and finish reason of each sample in the recent window
+    window: VecDeque<(usize, String)>,
+    counts: WindowCounts,
+    /// Samples since the reference was (re)built
+    since_refresh: usize,
+    /// Samples since the last reported drift (`OnDrift` refresh)
+    since_drift: Option<usize>,
+    /// Signals currently reported as drifting
+    drifting: [bool; 2],
+}
+
+impl KlState {
+    fn new() -> Self {
+        Self {
+            history: VecDeque::new(),
+            reference: None,
+            window: VecDeque::new(),
+            counts: WindowCounts::default(),
+            since_refresh: 0,
+            since_drift: None,
+            drifting: [false; 2],
+        }
+    }
+
+    /// (Re)build the reference from history and restart the window
+    fn rebuild(&mut self, config: &KlDivergenceConfig) {
+        let reference = Reference::build(&self.history, config.length_bins.max(2));
+        self.counts = WindowCounts {
+            lengths: vec![0; reference.edges.len() + 1],
+            ..Default::default()
+        };
+        self.window.clear();
+        self.reference = Some(reference);
+        self.since_refresh = 0;
+        self.since_drift = None;
+        self.drifting = [false; 2];
+    }
+
+    /// Window counts after adding a sample, without committing it
+    fn preview(&self, sample: &Sample, config: &KlDivergenceConfig) -> Option<WindowCounts> {
+        let reference = self.reference.as_ref()?;
+        let mut counts = self.counts.clone();
+        counts.add(
+            stats::bin_index(&reference.edges, sample.length),
+            &sample.finish_reason,
+        );
+        if self.window.len() >= config.window_size {
+            if let Some((bin, reason)) = self.window.front() {
+                counts.remove(*bin, reason);
+            }
+        }
+        (counts.total as usize >= config.window_size).then_some(counts)
+    }
+
+    /// Commit a sample and apply the refresh policy
+    fn push(&mut self, sample: Sample, config: &KlDivergenceConfig) {
+        self.history.push_back(sample.clone());
+        if self.history.len() > config.reference_size {
+            self.history.pop_front();
+        }
+
+        let Some(reference) = &self.reference else {
+            if self.history.len() >= config.reference_size {
+                self.rebuild(config);
+            }
+            return;
+        };
+
+        let bin = stats::bin_index(&reference.edges, sample.length);
+        self.counts.add(bin, &sample.finish_reason);
+        self.window.push_back((bin, sample.finish_reason));
+        if self.window.len() > config.window_size {
+            if let Some((bin, reason)) = self.window.pop_front() {
+                self.counts.remove(bin, &reason);
+            }
+        }
+
+        if self.counts.total as usize >= config.window_size {
+            for (i, signal) in Signal::ALL.iter().enumerate() {
+                let divergence = self.counts.divergence(reference, *signal);
+                let drifting = divergence >= config.threshold;
+                if drifting && !self.drifting[i] && self.since_drift.is_none() {
+                    self.since_drift = Some(0);
+                }
+                self.drifting[i] = drifting;
+            }
+        }
+
+        self.since_refresh += 1;
+        if let Some(since_drift) = self.since_drift.as_mut() {
+            *since_drift += 1;
+        }
+
+        let refresh = match config.refresh {
+            ReferenceRefresh::Fixed => false,
+            ReferenceRefresh::Periodic { every } => self.since_refresh >= every.max(1),
+            ReferenceRefresh::OnDrift => self
+                .since_drift
+                .is_some_and(|since| since >= config.reference_size),
+        };
+        if refresh {
+            self.rebuild(config);
+        }
+    }
+}
+
+/// KL divergence output drift detector
+///
+/// Tracks response length and finish reason distributions per model and
+/// emits [`AnomalyType::OutputDrift`].
+pub struct KlDivergenceDetector {
+    config: KlDivergenceConfig,
+    states: Arc<DashMap<ModelId, KlState>>,
+    stats: DetectorStats,
+}
+
+impl std::fmt::Debug for KlDivergenceDetector {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("KlDivergenceDetector")
+            .field("config", &self.config)
+            .field("models_count", &self.states.len())
+            .field("stats", &self.stats)
+            .finish()
+    }
+}
+
+impl KlDivergenceDetector {
+    /// Create a new KL divergence detector
+    pub fn new(config: KlDivergenceConfig) -> Self {
+        Self {
+            config,
+            states: Arc::new(DashMap::new()),
+            stats: DetectorStats::empty(),
+        }
+    }
+
+    fn build_anomaly(
+        &self,
+        event: &TelemetryEvent,
+        signal: Signal,
+        divergence: f64,
+        reference: &Reference,
+        counts: &WindowCounts,
+    ) -> AnomalyEvent {
+        let severity = if divergence >= self.config.high_threshold {
+            Severity::High
+        } else {
+            Severity::Medium
+        };
+
+        let (value, reference_distribution) = match signal {
+            Signal::ResponseLength => (
+                event.response.tokens as f64,
+                serde_json::json!(reference.lengths),
+            ),
+            Signal::FinishReason => {
+                let total = counts.total.max(1) as f64;
+                let share = counts
+                    .finish_reasons
+                    .get(&event.response.finish_reason)
+                    .map_or(0.0, |c| *c as f64 / total);
+                (share, serde_json::json!(reference.finish_reasons))
+            }
+        };
+
+        let mut context = HashMap::new();
+        context.insert("signal".to_string(), signal.metric().to_string());
+
+        AnomalyEvent::new(
+            severity,
+            AnomalyType::OutputDrift,
+            // Drift is tracked per model across services
+            ServiceId::new("all"),
+            event.model.clone(),
+            DetectionMethod::KlDivergence,
+            (divergence / self.config.high_threshold).clamp(0.5, 0.99),
+            AnomalyDetails {
+                metric: signal.metric().to_string(),
+                value,
+                baseline: 0.0,
+                threshold: self.config.threshold,
+                deviation_sigma: None,
+                additional: {
+                    let mut map = HashMap::new();
+                    map.insert("kl_divergence".to_string(), serde_json::json!(divergence));
+                    map.insert("reference".to_string(), reference_distribution);
+                    map.insert("recent".to_string(), counts.distribution(signal));
+                    if signal == Signal::ResponseLength {
+                        map.insert("bin_edges".to_string(), serde_json::json!(reference.edges));
+                    }
+                    map
+                },
+                observations: Vec::new(),
+            },
+            AnomalyContext {
+                trace_id: event.trace_id.clone(),
+                user_id: None,
+                region: None,
+                time_window: WindowSpec::samples(self.config.window_size).to_string(),
+                sample_count: counts.total as usize,
+                window: None,
+                additional: context,
+            },
+        )
+        .with_root_cause(format!(
+            "{} distribution of {} diverged from reference (KL: {:.3} nats)",
+            signal.metric(),
+            event.model,
+            divergence
+        ))
+        .with_remediation("Check for provider-side model version updates")
+        .with_remediation("Review sample responses for changed behaviour")
+    }
+}
+
+#[async_trait]
+impl Detector for KlDivergenceDetector {
+    async fn detect(&self, event: &TelemetryEvent) -> Result<Option<AnomalyEvent>> {
+        let Some(state) = self.states.get(&event.model) else {
+            return Ok(None);
+        };
+        let Some(reference) = state.reference.as_ref() else {
+            return Ok(None);
+        };
+        let Some(counts) = state.preview(&Sample::from_event(event), &self.config) else {
+            return Ok(None);
+        };
+
+        for (i, signal) in Signal::ALL.iter().enumerate() {
+            if state.drifting[i] {
+                continue;
+            }
+            let divergence = counts.divergence(reference, *signal);
+            if divergence >= self.config.threshold {
+                debug!(
+                    model = %event.model,
+                    signal = signal.metric(),
+                    divergence,
+                    "Output distribution drift detected"
+                );
+                return Ok(Some(
+                    self.build_anomaly(event, *signal, divergence, reference, &counts),
+                ));
+            }
+        }
+
+        Ok(None)
+    }
+
+    fn name(&self) -> &str {
+        "kl_divergence"
+    }
+
+    fn detector_type(&self) -> DetectorType {
+        DetectorType::Statistical
+    }
+
+    async fn update(&mut self, event: &TelemetryEvent) -> Result<()> {
+        let mut state = self
+            .states
+            .entry(event.model.clone())
+            .or_insert_with(KlState::new);
+
+        let had_reference = state.reference.is_some();
+        let refreshes = state.since_refresh;
+        state.push(Sample::from_event(event), &self.config);
+        if had_reference && state.since_refresh < refreshes {
+            info!(model = %event.model, "Rebuilt KL divergence reference");
+        }
+
+        self.stats.events_processed += 1;
+        Ok(())
+    }
+
+    async fn reset(&mut self) -> Result<()> {
+        self.states.clear();
+        self.stats = DetectorStats::empty();
+        Ok(())
+    }
+
+    fn stats(&self) -> DetectorStats {
+        self.stats.clone()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use llm_sentinel_fixtures::event;
+
+    fn create_test_event(tokens: u32, finish_reason: &str) -> TelemetryEvent {
+        event()
+            .response_tokens(tokens)
+            .finish_reason(finish_reason)
+            .build()
+    }
+
+    fn create_detector(refresh: ReferenceRefresh) -> KlDivergenceDetector {
+        KlDivergenceDetector::new(KlDivergenceConfig {
+            reference_size: 200,
+            window_size: 100,
+            refresh,
+            ..Default::default()
+        })
+    }
+
+    /// Feed events, returning the anomalies detected
+    async fn feed(
+        detector: &mut KlDivergenceDetector,
+        events: impl Iterator<Item = TelemetryEvent>,
+    ) -> Vec<AnomalyEvent> {
+        let mut detected = Vec::new();
+        for event in events {
+            if let Some(anomaly) = detector.detect(&event).await.unwrap() {
+                detected.push(anomaly);
+            }
+            detector.update(&event).await.unwrap();
+        }
+        detected
+    }
+
+    fn stable(n: u32) -> impl Iterator<Item = TelemetryEvent> {
+        (0..n)
+            .map(|i| create_test_event(100 + i % 100, if i % 10 == 0 { "length" } else { "stop" }))
+    }
+
+    #[tokio::test]
+    async fn test_stable_outputs() {
+        let mut detector = create_detector(ReferenceRefresh::Fixed);
+        assert!(feed(&mut detector, stable(600)).await.is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_finish_reason_drift() {
+        let mut detector = create_detector(ReferenceRefresh::Fixed);
+        feed(&mut detector, stable(400)).await;
+
+        // Same lengths, but a third of responses are now filtered
+        let shifted = (0..100u32).map(|i| {
+            let reason = if i % 3 == 0 { "content_filter" } else { "stop" };
+            create_test_event(100 + i % 100, reason)
+        });
+        let detected = feed(&mut detector, shifted).await;
+
+        assert_eq!(detected.len(), 1);
+        assert_eq!(detected[0].anomaly_type, AnomalyType::OutputDrift);
+        assert_eq!(detected[0].detection_method, DetectionMethod::KlDivergence);
+        assert_eq!(detected[0].details.metric, "finish_reason");
+        assert_eq!(detected[0].service_name, ServiceId::new("all"));
+    }
+
+    #[tokio::test]
+    async fn test_on_drift_refresh() {
+        let mut detector = create_detector(ReferenceRefresh::OnDrift);
+        feed(&mut detector, stable(400)).await;
+
+        // Responses get much shorter and stay that way
+        let shorter = || {
+            (0..300u32).map(|i| {
+                let reason = if i % 10 == 0 { "length" } else { "stop" };
+                create_test_event(20 + i % 20, reason)
+            })
+        };
+        let detected = feed(&mut detector, shorter()).await;
+        assert_eq!(detected.len(), 1);
+        assert_eq!(detected[0].details.metric, "response_length");
+
+        // The new behaviour becomes the reference
+        assert!(feed(&mut detector, shorter()).await.is_empty());
+        let state = detector.states.get(&ModelId::new("gpt-4")).unwrap();
+        assert!(state
+            .reference
+            .as_ref()
+            .unwrap()
+            .edges
+            .iter()
+            .all(|e| *e < 40.0));
+    }
+}
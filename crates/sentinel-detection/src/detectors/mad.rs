@@ -111,13 +111,15 @@ impl MadDetector {
                         map.insert("modified_zscore".to_string(), serde_json::json!(modified_zscore));
                         map
                     },
+                    observations: Vec::new(),
                 },
                 AnomalyContext {
                     trace_id: event.trace_id.clone(),
                     user_id: event.metadata.get("user_id").cloned(),
                     region: event.metadata.get("region").cloned(),
-                    time_window: "rolling_window".to_string(),
+                    time_window: self.baseline_manager.spec().to_string(),
                     sample_count: baseline.sample_count,
+                    window: baseline.window.clone(),
                     additional: HashMap::new(),
                 },
             )
@@ -153,7 +155,8 @@ impl Detector for MadDetector {
         }
 
         let key = BaselineKey::latency(event.service_name.clone(), event.model.clone());
-        self.baseline_manager.update(key, event.latency_ms)?;
+        self.baseline_manager
+            .update_at(key, event.latency_ms, event.timestamp)?;
         Ok(())
     }
 
@@ -1,8 +1,16 @@
 //! Anomaly detection implementations.
 
+pub mod bocpd;
+pub mod content_filter;
+pub mod context_overflow;
+pub mod cost_residual;
 pub mod cusum;
 pub mod iqr;
+pub mod kl_divergence;
 pub mod mad;
+pub mod multi_window;
+pub mod psi;
+pub mod token_efficiency;
 pub mod zscore;
 
 /// Common detection configuration
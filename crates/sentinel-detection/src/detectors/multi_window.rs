@@ -0,0 +1,550 @@
+//! Multi-window baselines and regime change detection.
+//!
+//! Keeps two baselines per service, model and metric: a short window (the
+//! last hour by default) and a long one (the last day). When the short
+//! window's median moves far from the long window's, or its spread grows or
+//! shrinks sharply, traffic has changed regime even if no single event is an
+//! outlier, and the detector reports [`AnomalyType::ConceptDrift`] with both
+//! baselines attached. The long window includes the short one, so a
+//! sustained shift shows fully in the short baseline and only gradually in
+//! the long one.
+//!
+//! A divergence is reported once; the detector re-arms when the windows
+//! agree well within the thresholds again. The long baseline moves slowly by design, so it is only
+//! recomputed every `long_refresh_samples` samples.
+
+use crate::{
+    baseline::{Baseline, BaselineKey},
+    detectors::DetectionConfig,
+    stats::SampleWindow,
+    Detector, DetectorStats, DetectorType,
+};
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+use dashmap::DashMap;
+use llm_sentinel_core::{
+    config::RegimeShiftConfig,
+    events::{AnomalyContext, AnomalyDetails, AnomalyEvent, TelemetryEvent},
+    types::{AnomalyType, DetectionMethod, Severity},
+    window::WindowSpec,
+    Error, Result,
+};
+use serde::{Deserialize, Serialize};
+use std::{collections::HashMap, sync::Arc};
+use tracing::debug;
+
+/// Divergence score below which a reported divergence is over
+const REARM_SCORE: f64 = 0.5;
+
+/// Multi-window detector configuration
+#[derive(Debug, Clone)]
+pub struct MultiWindowConfig {
+    /// Bounds of the short window
+    pub short_window: WindowSpec,
+    /// Bounds of the long window
+    pub long_window: WindowSpec,
+    /// Shift of the short-window median from the long-window median, in
+    /// long-window standard deviations, that signals a regime change
+    pub shift_threshold: f64,
+    /// Ratio between the short- and long-window IQR, either way, that
+    /// signals a regime change
+    pub spread_ratio_threshold: f64,
+    /// Shift considered severe
+    pub high_severity_shift: f64,
+    /// Samples the long window needs before the windows are compared
+    pub min_long_samples: usize,
+    /// Recompute the long baseline every this many samples
+    pub long_refresh_samples: usize,
+    /// Common detection config (`min_samples` applies to the short window)
+    pub detection: DetectionConfig,
+}
+
+impl Default for MultiWindowConfig {
+    fn default() -> Self {
+        Self {
+            short_window: WindowSpec::samples(500).with_max_age_secs(3600),
+            long_window: WindowSpec::samples(10_000).with_max_age_secs(86_400),
+            shift_threshold: 2.0,
+            spread_ratio_threshold: 3.0,
+            high_severity_shift: 4.0,
+            min_long_samples: 200,
+            long_refresh_samples: 50,
+            detection: DetectionConfig {
+                min_samples: 30,
+                update_baseline: true,
+            },
+        }
+    }
+}
+
+impl MultiWindowConfig {
+    /// Detector configuration for the given regime shift settings
+    pub fn from_config(config: &RegimeShiftConfig) -> Self {
+        Self {
+            short_window: config.short_window,
+            long_window: config.long_window,
+            shift_threshold: config.shift_threshold,
+            spread_ratio_threshold: config.spread_ratio_threshold,
+            high_severity_shift: config.high_severity_shift,
+            min_long_samples: config.min_long_samples,
+            long_refresh_samples: config.long_refresh_samples,
+            detection: DetectionConfig {
+                min_samples: config.min_samples,
+                update_baseline: true,
+            },
+        }
+    }
+}
+
+/// Short and long windows of one metric
+#[derive(Debug, Clone)]
+struct WindowPair {
+    short: SampleWindow,
+    long: SampleWindow,
+    short_baseline: Option<Baseline>,
+    long_baseline: Option<Baseline>,
+    /// Samples since the long baseline was recomputed
+    since_refresh: usize,
+    /// Whether a divergence is currently being reported
+    diverged: bool,
+}
+
+/// Samples of one window pair, for carrying it over to another process
+#[derive(Debug, Serialize, Deserialize)]
+struct WindowPairSnapshot {
+    key: BaselineKey,
+    short: Vec<(DateTime<Utc>, f64)>,
+    long: Vec<(DateTime<Utc>, f64)>,
+    diverged: bool,
+}
+
+/// How far the short window has moved from the long one
+#[derive(Debug, Clone, Copy)]
+struct Divergence {
+    /// Median shift in long-window standard deviations
+    shift: f64,
+    /// Short- to long-window IQR ratio, when both spreads are nonzero
+    spread_ratio: Option<f64>,
+}
+
+impl Divergence {
+    /// Strength relative to the thresholds; 1.0 or more is a regime change
+    fn score(&self, config: &MultiWindowConfig) -> f64 {
+        let shift = self.shift.abs() / config.shift_threshold;
+        let spread = self.spread_ratio.map_or(0.0, |ratio| {
+            ratio.max(1.0 / ratio) / config.spread_ratio_threshold
+        });
+        shift.max(spread)
+    }
+}
+
+/// Baseline of a window's samples
+fn baseline_of(window: &mut SampleWindow) -> Baseline {
+    let mut baseline = Baseline::from_data(window.values());
+    baseline.window = window.metadata();
+    baseline
+}
+
+impl WindowPair {
+    fn new(config: &MultiWindowConfig) -> Self {
+        Self {
+            short: SampleWindow::new(config.short_window),
+            long: SampleWindow::new(config.long_window),
+            short_baseline: None,
+            long_baseline: None,
+            since_refresh: 0,
+            diverged: false,
+        }
+    }
+
+    fn push(&mut self, value: f64, at: DateTime<Utc>, config: &MultiWindowConfig) {
+        self.short.push(value, at);
+        self.long.push(value, at);
+        self.short_baseline = Some(baseline_of(&mut self.short));
+        self.since_refresh += 1;
+        if self.long_baseline.is_none() || self.since_refresh >= config.long_refresh_samples {
+            self.refresh_long();
+        }
+    }
+
+    fn refresh_long(&mut self) {
+        self.long_baseline = Some(baseline_of(&mut self.long));
+        self.since_refresh = 0;
+    }
+
+    /// Divergence of the windows, once both hold enough samples
+    fn divergence(&self, config: &MultiWindowConfig) -> Option<Divergence> {
+        let short = self
+            .short_baseline
+            .as_ref()
+            .filter(|b| b.sample_count >= config.detection.min_samples)?;
+        let long = self
+            .long_baseline
+            .as_ref()
+            .filter(|b| b.sample_count >= config.min_long_samples)?;
+
+        // Constant long window: fall back to a small relative scale
+        let scale = if long.std_dev > f64::EPSILON {
+            long.std_dev
+        } else {
+            (long.mean.abs() * 0.01).max(1e-9)
+        };
+        Some(Divergence {
+            shift: (short.median - long.median) / scale,
+            spread_ratio: (short.iqr > f64::EPSILON && long.iqr > f64::EPSILON)
+                .then(|| short.iqr / long.iqr),
+        })
+    }
+
+    /// Latch on a divergence, and re-arm once the windows agree well within
+    /// the thresholds so a score hovering around 1.0 is reported only once
+    fn latch(&mut self, config: &MultiWindowConfig) {
+        match self.divergence(config).map(|d| d.score(config)) {
+            Some(score) if score >= 1.0 => self.diverged = true,
+            Some(score) if score < REARM_SCORE => self.diverged = false,
+            _ => {}
+        }
+    }
+}
+
+/// Multi-window regime change detector
+///
+/// Compares a short-window baseline of latency, token usage and cost per
+/// service/model with a long-window one, emitting
+/// [`AnomalyType::ConceptDrift`] when they disagree.
+pub struct MultiWindowDetector {
+    config: MultiWindowConfig,
+    states: Arc<DashMap<BaselineKey, WindowPair>>,
+    stats: DetectorStats,
+}
+
+impl std::fmt::Debug for MultiWindowDetector {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("MultiWindowDetector")
+            .field("config", &self.config)
+            .field("states_count", &self.states.len())
+            .field("stats", &self.stats)
+            .finish()
+    }
+}
+
+impl MultiWindowDetector {
+    /// Create a new multi-window detector
+    pub fn new(config: MultiWindowConfig) -> Self {
+        Self {
+            config,
+            states: Arc::new(DashMap::new()),
+            stats: DetectorStats::empty(),
+        }
+    }
+
+    /// Metric values tracked for an event
+    fn metrics(event: &TelemetryEvent) -> [(BaselineKey, f64); 3] {
+        let service = &event.service_name;
+        let model = &event.model;
+        [
+            (
+                BaselineKey::latency(service.clone(), model.clone()),
+                event.latency_ms,
+            ),
+            (
+                BaselineKey::tokens(service.clone(), model.clone()),
+                event.total_tokens() as f64,
+            ),
+            (
+                BaselineKey::cost(service.clone(), model.clone()),
+                event.cost_usd,
+            ),
+        ]
+    }
+
+    fn build_anomaly(
+        &self,
+        event: &TelemetryEvent,
+        key: &BaselineKey,
+        value: f64,
+        short: &Baseline,
+        long: &Baseline,
+        divergence: &Divergence,
+    ) -> AnomalyEvent {
+        let score = divergence.score(&self.config);
+        let severity = if divergence.shift.abs() >= self.config.high_severity_shift {
+            Severity::High
+        } else {
+            Severity::Medium
+        };
+        let direction = if divergence.shift >= 0.0 {
+            "increase"
+        } else {
+            "decrease"
+        };
+        let spread = divergence
+            .spread_ratio
+            .map_or_else(String::new, |ratio| format!(", spread x{:.2}", ratio));
+
+        AnomalyEvent::new(
+            severity,
+            AnomalyType::ConceptDrift,
+            event.service_name.clone(),
+            event.model.clone(),
+            DetectionMethod::MultiWindow,
+            (0.5 + 0.25 * (score - 1.0)).clamp(0.5, 0.95),
+            AnomalyDetails {
+                metric: key.metric.clone(),
+                value: short.median,
+                baseline: long.median,
+                threshold: self.config.shift_threshold,
+                deviation_sigma: Some(divergence.shift),
+                additional: {
+                    let mut map = HashMap::new();
+                    map.insert("event_value".to_string(), serde_json::json!(value));
+                    map.insert("short_baseline".to_string(), serde_json::json!(short));
+                    map.insert("long_baseline".to_string(), serde_json::json!(long));
+                    map.insert(
+                        "spread_ratio".to_string(),
+                        serde_json::json!(divergence.spread_ratio),
+                    );
+                    map
+                },
+                observations: Vec::new(),
+            },
+            AnomalyContext {
+                trace_id: event.trace_id.clone(),
+                user_id: event.metadata.get("user_id").cloned(),
+                region: event.metadata.get("region").cloned(),
+                time_window: self.config.short_window.to_string(),
+                sample_count: short.sample_count,
+                window: short.window.clone(),
+                additional: HashMap::new(),
+            },
+        )
+        .with_root_cause(format!(
+            "Regime change in {}: short-window median {} from {:.4} to {:.4} ({:+.1}σ{})",
+            key.metric, direction, long.median, short.median, divergence.shift, spread
+        ))
+        .with_remediation("Check for model version, prompt or traffic mix changes")
+        .with_remediation("Confirm whether the new level is expected before rebaselining")
+    }
+}
+
+#[async_trait]
+impl Detector for MultiWindowDetector {
+    /// Compare the windows as they were before the event
+    async fn detect(&self, event: &TelemetryEvent) -> Result<Option<AnomalyEvent>> {
+        for (key, value) in Self::metrics(event) {
+            let Some(pair) = self.states.get(&key) else {
+                continue;
+            };
+            if pair.diverged {
+                continue;
+            }
+            let Some(divergence) = pair.divergence(&self.config) else {
+                continue;
+            };
+            if divergence.score(&self.config) < 1.0 {
+                continue;
+            }
+            let (Some(short), Some(long)) = (&pair.short_baseline, &pair.long_baseline) else {
+                continue;
+            };
+
+            debug!(
+                service = %event.service_name,
+                model = %event.model,
+                metric = %key.metric,
+                shift = divergence.shift,
+                spread_ratio = ?divergence.spread_ratio,
+                "Short and long window baselines diverged"
+            );
+            return Ok(Some(self.build_anomaly(
+                event,
+                &key,
+                value,
+                short,
+                long,
+                &divergence,
+            )));
+        }
+
+        Ok(None)
+    }
+
+    fn name(&self) -> &str {
+        "multi_window"
+    }
+
+    fn detector_type(&self) -> DetectorType {
+        DetectorType::Statistical
+    }
+
+    async fn update(&mut self, event: &TelemetryEvent) -> Result<()> {
+        if !self.config.detection.update_baseline {
+            return Ok(());
+        }
+
+        for (key, value) in Self::metrics(event) {
+            let mut pair = self
+                .states
+                .entry(key)
+                .or_insert_with(|| WindowPair::new(&self.config));
+            // Judge the windows `detect` saw before adding the event
+            pair.latch(&self.config);
+            pair.push(value, event.timestamp, &self.config);
+        }
+
+        self.stats.events_processed += 1;
+        Ok(())
+    }
+
+    async fn reset(&mut self) -> Result<()> {
+        self.states.clear();
+        self.stats = DetectorStats::empty();
+        Ok(())
+    }
+
+    fn export_state(&self) -> Result<Option<serde_json::Value>> {
+        let pairs: Vec<WindowPairSnapshot> = self
+            .states
+            .iter()
+            .map(|entry| WindowPairSnapshot {
+                key: entry.key().clone(),
+                short: entry.value().short.samples().collect(),
+                long: entry.value().long.samples().collect(),
+                diverged: entry.value().diverged,
+            })
+            .collect();
+        serde_json::to_value(pairs)
+            .map(Some)
+            .map_err(|e| Error::internal(format!("Failed to export multi-window state: {}", e)))
+    }
+
+    fn import_state(&mut self, state: serde_json::Value) -> Result<()> {
+        let pairs: Vec<WindowPairSnapshot> = serde_json::from_value(state)
+            .map_err(|e| Error::validation(format!("Invalid multi-window state: {}", e)))?;
+        self.states.clear();
+        for snapshot in pairs {
+            let mut pair = WindowPair::new(&self.config);
+            for (at, value) in snapshot.short {
+                pair.short.push(value, at);
+            }
+            for (at, value) in snapshot.long {
+                pair.long.push(value, at);
+            }
+            if !pair.short.is_empty() {
+                pair.short_baseline = Some(baseline_of(&mut pair.short));
+            }
+            if !pair.long.is_empty() {
+                pair.refresh_long();
+            }
+            pair.diverged = snapshot.diverged;
+            self.states.insert(snapshot.key, pair);
+        }
+        Ok(())
+    }
+
+    fn stats(&self) -> DetectorStats {
+        self.stats.clone()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use llm_sentinel_fixtures::event;
+
+    fn create_test_event(latency: f64) -> TelemetryEvent {
+        event().latency_ms(latency).build()
+    }
+
+    /// Deterministic noise in [-1, 1)
+    fn noise(i: u64) -> f64 {
+        ((i.wrapping_mul(2654435761) % 1000) as f64 / 500.0) - 1.0
+    }
+
+    fn config() -> MultiWindowConfig {
+        MultiWindowConfig {
+            short_window: WindowSpec::samples(50),
+            long_window: WindowSpec::samples(1000),
+            ..Default::default()
+        }
+    }
+
+    async fn run(
+        detector: &mut MultiWindowDetector,
+        latencies: impl Iterator<Item = f64>,
+    ) -> Vec<AnomalyEvent> {
+        let mut detected = Vec::new();
+        for latency in latencies {
+            let event = create_test_event(latency);
+            if let Some(anomaly) = detector.detect(&event).await.unwrap() {
+                detected.push(anomaly);
+            }
+            detector.update(&event).await.unwrap();
+        }
+        detected
+    }
+
+    #[tokio::test]
+    async fn test_detects_level_shift_once() {
+        let mut detector = MultiWindowDetector::new(config());
+
+        // Stable around 100ms: the windows agree
+        let stable = (0..500).map(|i| 100.0 + 10.0 * noise(i));
+        assert!(run(&mut detector, stable).await.is_empty());
+
+        // Sustained move to 115ms: no single event is an outlier
+        let shifted = (500..700).map(|i| 115.0 + 10.0 * noise(i));
+        let detected = run(&mut detector, shifted).await;
+        assert_eq!(detected.len(), 1);
+
+        let anomaly = &detected[0];
+        assert_eq!(anomaly.anomaly_type, AnomalyType::ConceptDrift);
+        assert_eq!(anomaly.detection_method, DetectionMethod::MultiWindow);
+        assert_eq!(anomaly.details.metric, "latency_ms");
+        assert!(anomaly.details.deviation_sigma.unwrap() > 1.0);
+        let short: Baseline =
+            serde_json::from_value(anomaly.details.additional["short_baseline"].clone()).unwrap();
+        let long: Baseline =
+            serde_json::from_value(anomaly.details.additional["long_baseline"].clone()).unwrap();
+        assert_eq!(short.sample_count, 50);
+        assert!(long.sample_count >= 500);
+        assert!(short.median > long.median);
+    }
+
+    #[tokio::test]
+    async fn test_detects_spread_change_and_rearms() {
+        let mut detector = MultiWindowDetector::new(config());
+        let stable = (0..500).map(|i| 100.0 + 10.0 * noise(i));
+        assert!(run(&mut detector, stable).await.is_empty());
+
+        // Same level, eight times the spread
+        let noisy = (500..600).map(|i| 100.0 + 80.0 * noise(i));
+        let detected = run(&mut detector, noisy).await;
+        assert_eq!(detected.len(), 1);
+        assert!(
+            detected[0].details.additional["spread_ratio"]
+                .as_f64()
+                .unwrap()
+                >= 3.0
+        );
+
+        // Back to normal, then the spread grows again
+        let calm = (600..700).map(|i| 100.0 + 10.0 * noise(i));
+        assert!(run(&mut detector, calm).await.is_empty());
+        let noisy = (700..800).map(|i| 100.0 + 80.0 * noise(i));
+        assert_eq!(run(&mut detector, noisy).await.len(), 1);
+    }
+
+    #[tokio::test]
+    async fn test_state_carried_over() {
+        let mut detector = MultiWindowDetector::new(config());
+        run(&mut detector, (0..500).map(|i| 100.0 + 10.0 * noise(i))).await;
+
+        let mut restored = MultiWindowDetector::new(config());
+        restored
+            .import_state(detector.export_state().unwrap().unwrap())
+            .unwrap();
+        let shifted = (500..700).map(|i| 115.0 + 10.0 * noise(i));
+        assert_eq!(run(&mut restored, shifted).await.len(), 1);
+    }
+}
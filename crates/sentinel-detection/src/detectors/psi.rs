@@ -0,0 +1,438 @@
+//! Population Stability Index (PSI) drift detector.
+//!
+//! Builds a binned reference distribution per service/model/metric from the
+//! first `reference_size` observations, using reference quantiles as bin
+//! edges, then compares a sliding window of recent observations against it:
+//!
+//! PSI = Σ (actual% - expected%) × ln(actual% / expected%)
+//!
+//! Rule of thumb: < 0.1 stable, 0.1-0.25 moderate shift, > 0.25 significant.
+
+use crate::{
+    baseline::BaselineKey, detectors::DetectionConfig, stats, Detector, DetectorStats,
+    DetectorType,
+};
+use async_trait::async_trait;
+use dashmap::DashMap;
+use llm_sentinel_core::{
+    config::DistributionDriftConfig,
+    events::{AnomalyContext, AnomalyDetails, AnomalyEvent, TelemetryEvent},
+    types::{AnomalyType, DetectionMethod, Severity},
+    window::WindowSpec,
+    Result,
+};
+use std::{
+    collections::{HashMap, VecDeque},
+    sync::Arc,
+};
+use tracing::debug;
+
+/// Floor for bin proportions so empty bins don't produce infinite PSI
+const MIN_PROPORTION: f64 = 1e-4;
+
+/// PSI detector configuration
+#[derive(Debug, Clone)]
+pub struct PsiConfig {
+    /// Number of bins
+    pub bins: usize,
+    /// Observations used to build the reference distribution
+    pub reference_size: usize,
+    /// Size of the recent window compared against the reference
+    pub window_size: usize,
+    /// PSI at which drift is reported
+    pub threshold: f64,
+    /// PSI at which drift is reported as high severity
+    pub high_threshold: f64,
+    /// Common detection config
+    pub detection: DetectionConfig,
+}
+
+impl Default for PsiConfig {
+    fn default() -> Self {
+        Self {
+            bins: 10,
+            reference_size: 1000,
+            window_size: 500,
+            threshold: 0.25,
+            high_threshold: 0.5,
+            detection: DetectionConfig::default(),
+        }
+    }
+}
+
+impl PsiConfig {
+    /// Detector configuration for the given distribution drift settings
+    pub fn from_config(config: &DistributionDriftConfig) -> Self {
+        Self {
+            bins: config.bins,
+            reference_size: config.reference_size,
+            window_size: config.window_size,
+            threshold: config.threshold,
+            high_threshold: config.high_threshold,
+            detection: DetectionConfig::default(),
+        }
+    }
+}
+
+/// Binned distributions for one metric
+#[derive(Debug, Clone)]
+struct PsiState {
+    /// Values collected for the reference
+    pending: Vec<f64>,
+    /// Interior bin edges (empty until the reference is built)
+    edges: Vec<f64>,
+    /// Reference proportion per bin
+    reference: Vec<f64>,
+    /// Bin index of each value in the recent window
+    window: VecDeque<usize>,
+    /// Counts per bin in the recent window
+    counts: Vec<u64>,
+    /// Whether drift is currently being reported
+    in_drift: bool,
+}
+
+impl PsiState {
+    fn new() -> Self {
+        Self {
+            pending: Vec::new(),
+            edges: Vec::new(),
+            reference: Vec::new(),
+            window: VecDeque::new(),
+            counts: Vec::new(),
+            in_drift: false,
+        }
+    }
+
+    fn has_reference(&self) -> bool {
+        !self.reference.is_empty()
+    }
+
+    /// Build the reference from the pending values
+    fn build_reference(&mut self, bins: usize) {
+        let values = std::mem::take(&mut self.pending);
+        let edges = stats::quantile_edges(&values, bins);
+
+        let mut counts = vec![0u64; edges.len() + 1];
+        for value in &values {
+            counts[stats::bin_index(&edges, *value)] += 1;
+        }
+
+        self.reference = proportions(&counts, values.len());
+        self.counts = vec![0; edges.len() + 1];
+        self.edges = edges;
+    }
+
+    /// Add a value, returning the PSI once the window is full
+    fn push(&mut self, value: f64, config: &PsiConfig) -> Option<f64> {
+        if !self.has_reference() {
+            self.pending.push(value);
+            if self.pending.len() >= config.reference_size.max(config.bins) {
+                self.build_reference(config.bins.max(2));
+            }
+            return None;
+        }
+
+        let bin = stats::bin_index(&self.edges, value);
+        self.window.push_back(bin);
+        self.counts[bin] += 1;
+        if self.window.len() > config.window_size {
+            if let Some(evicted) = self.window.pop_front() {
+                self.counts[evicted] -= 1;
+            }
+        }
+
+        (self.window.len() >= config.window_size).then(|| self.psi())
+    }
+
+    /// PSI of the recent window against the reference
+    fn psi(&self) -> f64 {
+        self.contributions().iter().sum()
+    }
+
+    /// Per-bin PSI contributions
+    fn contributions(&self) -> Vec<f64> {
+        proportions(&self.counts, self.window.len())
+            .iter()
+            .zip(&self.reference)
+            .map(|(actual, expected)| (actual - expected) * (actual / expected).ln())
+            .collect()
+    }
+}
+
+/// Counts to floored proportions
+fn proportions(counts: &[u64], total: usize) -> Vec<f64> {
+    counts
+        .iter()
+        .map(|c| (*c as f64 / total.max(1) as f64).max(MIN_PROPORTION))
+        .collect()
+}
+
+/// PSI drift detector
+///
+/// Tracks prompt tokens (input drift), and response tokens and latency
+/// (output drift) per service/model.
+pub struct PsiDetector {
+    config: PsiConfig,
+    states: Arc<DashMap<BaselineKey, PsiState>>,
+    stats: DetectorStats,
+}
+
+impl std::fmt::Debug for PsiDetector {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("PsiDetector")
+            .field("config", &self.config)
+            .field("states_count", &self.states.len())
+            .field("stats", &self.stats)
+            .finish()
+    }
+}
+
+impl PsiDetector {
+    /// Create a new PSI detector
+    pub fn new(config: PsiConfig) -> Self {
+        Self {
+            config,
+            states: Arc::new(DashMap::new()),
+            stats: DetectorStats::empty(),
+        }
+    }
+
+    /// Tracked metrics with their values and drift type
+    fn metrics(event: &TelemetryEvent) -> [(BaselineKey, f64, AnomalyType); 3] {
+        let key = |metric: &str| {
+            BaselineKey::new(event.service_name.clone(), event.model.clone(), metric)
+        };
+        [
+            (
+                key("prompt_tokens"),
+                event.prompt.tokens as f64,
+                AnomalyType::InputDrift,
+            ),
+            (
+                key("response_tokens"),
+                event.response.tokens as f64,
+                AnomalyType::OutputDrift,
+            ),
+            (
+                BaselineKey::latency(event.service_name.clone(), event.model.clone()),
+                event.latency_ms,
+                AnomalyType::OutputDrift,
+            ),
+        ]
+    }
+
+    fn build_anomaly(
+        &self,
+        event: &TelemetryEvent,
+        key: &BaselineKey,
+        anomaly_type: AnomalyType,
+        value: f64,
+        state: &PsiState,
+        psi: f64,
+    ) -> AnomalyEvent {
+        let severity = if psi >= self.config.high_threshold {
+            Severity::High
+        } else {
+            Severity::Medium
+        };
+
+        let bins: Vec<serde_json::Value> = state
+            .contributions()
+            .iter()
+            .zip(proportions(&state.counts, state.window.len()))
+            .zip(&state.reference)
+            .enumerate()
+            .map(|(i, ((contribution, actual), expected))| {
+                serde_json::json!({
+                    "bin": i,
+                    "lower": i.checked_sub(1).map(|j| state.edges[j]),
+                    "upper": state.edges.get(i),
+                    "expected": expected,
+                    "actual": actual,
+                    "contribution": contribution,
+                })
+            })
+            .collect();
+
+        AnomalyEvent::new(
+            severity,
+            anomaly_type,
+            event.service_name.clone(),
+            event.model.clone(),
+            DetectionMethod::Psi,
+            (psi / self.config.high_threshold).clamp(0.5, 0.99),
+            AnomalyDetails {
+                metric: key.metric.clone(),
+                value,
+                baseline: 0.0,
+                threshold: self.config.threshold,
+                deviation_sigma: None,
+                additional: {
+                    let mut map = HashMap::new();
+                    map.insert("psi".to_string(), serde_json::json!(psi));
+                    map.insert("bins".to_string(), serde_json::json!(bins));
+                    map
+                },
+                observations: Vec::new(),
+            },
+            AnomalyContext {
+                trace_id: event.trace_id.clone(),
+                user_id: event.metadata.get("user_id").cloned(),
+                region: event.metadata.get("region").cloned(),
+                time_window: WindowSpec::samples(self.config.window_size).to_string(),
+                sample_count: state.window.len(),
+                window: None,
+                additional: HashMap::new(),
+            },
+        )
+        .with_root_cause(format!(
+            "{} distribution shifted from reference (PSI: {:.3}, threshold: {:.2})",
+            key.metric, psi, self.config.threshold
+        ))
+        .with_remediation("Compare recent traffic against the reference period")
+        .with_remediation("Check for prompt template, client or model changes")
+    }
+}
+
+#[async_trait]
+impl Detector for PsiDetector {
+    async fn detect(&self, event: &TelemetryEvent) -> Result<Option<AnomalyEvent>> {
+        // Evaluate on a copy; `update` commits the value
+        for (key, value, anomaly_type) in Self::metrics(event) {
+            let Some(state) = self.states.get(&key) else {
+                continue;
+            };
+            if state.in_drift || !state.has_reference() {
+                continue;
+            }
+
+            let mut next = state.clone();
+            drop(state);
+
+            if let Some(psi) = next.push(value, &self.config) {
+                if psi >= self.config.threshold {
+                    debug!(
+                        service = %event.service_name,
+                        model = %event.model,
+                        metric = %key.metric,
+                        psi,
+                        "Distribution drift detected"
+                    );
+                    return Ok(Some(self.build_anomaly(
+                        event,
+                        &key,
+                        anomaly_type,
+                        value,
+                        &next,
+                        psi,
+                    )));
+                }
+            }
+        }
+
+        Ok(None)
+    }
+
+    fn name(&self) -> &str {
+        "psi"
+    }
+
+    fn detector_type(&self) -> DetectorType {
+        DetectorType::Statistical
+    }
+
+    async fn update(&mut self, event: &TelemetryEvent) -> Result<()> {
+        if !self.config.detection.update_baseline {
+            return Ok(());
+        }
+
+        for (key, value, _) in Self::metrics(event) {
+            let mut state = self.states.entry(key).or_insert_with(PsiState::new);
+            if let Some(psi) = state.push(value, &self.config) {
+                state.in_drift = psi >= self.config.threshold;
+            }
+        }
+
+        self.stats.events_processed += 1;
+        Ok(())
+    }
+
+    async fn reset(&mut self) -> Result<()> {
+        self.states.clear();
+        self.stats = DetectorStats::empty();
+        Ok(())
+    }
+
+    fn stats(&self) -> DetectorStats {
+        self.stats.clone()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use llm_sentinel_fixtures::event;
+
+    fn create_test_event(prompt_tokens: u32, response_tokens: u32) -> TelemetryEvent {
+        event().tokens(prompt_tokens, response_tokens).build()
+    }
+
+    fn create_detector() -> PsiDetector {
+        PsiDetector::new(PsiConfig {
+            reference_size: 200,
+            window_size: 100,
+            ..Default::default()
+        })
+    }
+
+    #[test]
+    fn test_psi_identical_distributions() {
+        let config = PsiConfig {
+            reference_size: 100,
+            window_size: 100,
+            ..Default::default()
+        };
+        let mut state = PsiState::new();
+        for i in 0..100 {
+            assert!(state.push(i as f64, &config).is_none());
+        }
+        let mut psi = None;
+        for i in 0..100 {
+            psi = state.push(i as f64, &config);
+        }
+        assert!(psi.unwrap() < 1e-9);
+    }
+
+    #[tokio::test]
+    async fn test_detects_output_drift() {
+        let mut detector = create_detector();
+
+        // Response tokens cycle through 100..200
+        for i in 0..400u32 {
+            let event = create_test_event(50, 100 + i % 100);
+            assert!(detector.detect(&event).await.unwrap().is_none());
+            detector.update(&event).await.unwrap();
+        }
+
+        // Responses become much shorter
+        let mut detected = Vec::new();
+        for i in 0..100u32 {
+            let event = create_test_event(50, 20 + i % 20);
+            if let Some(anomaly) = detector.detect(&event).await.unwrap() {
+                detected.push(anomaly);
+            }
+            detector.update(&event).await.unwrap();
+        }
+
+        assert_eq!(detected.len(), 1);
+        let anomaly = &detected[0];
+        assert_eq!(anomaly.anomaly_type, AnomalyType::OutputDrift);
+        assert_eq!(anomaly.detection_method, DetectionMethod::Psi);
+        assert_eq!(anomaly.details.metric, "response_tokens");
+        assert!(anomaly.details.additional["psi"].as_f64().unwrap() >= 0.25);
+        assert_eq!(
+            anomaly.details.additional["bins"].as_array().unwrap().len(),
+            10
+        );
+    }
+}
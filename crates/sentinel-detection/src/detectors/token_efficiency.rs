@@ -0,0 +1,425 @@
+//! Token-efficiency regression detector.
+//!
+//! Compares recent token usage per service/model against a long-run
+//! reference and flags prompt bloat: prompt tokens grow while response
+//! tokens stay flat, so each request pays for more input without getting
+//! more output. Reported ratios:
+//! - efficiency: response tokens / prompt tokens
+//! - cost per useful token: cost / response tokens
+
+use crate::{
+    baseline::BaselineKey, detectors::DetectionConfig, Detector, DetectorStats, DetectorType,
+};
+use async_trait::async_trait;
+use dashmap::DashMap;
+use llm_sentinel_core::{
+    config::PromptBloatConfig,
+    events::{AnomalyContext, AnomalyDetails, AnomalyEvent, TelemetryEvent},
+    types::{AnomalyType, DetectionMethod, Severity},
+    window::WindowSpec,
+    Result,
+};
+use std::{
+    collections::{HashMap, VecDeque},
+    sync::Arc,
+};
+use tracing::debug;
+
+/// Token-efficiency detector configuration
+#[derive(Debug, Clone)]
+pub struct TokenEfficiencyConfig {
+    /// Recent window size (events)
+    pub window_size: usize,
+    /// Events over which the reference adapts
+    pub reference_size: usize,
+    /// Prompt tokens must grow by at least this factor
+    pub prompt_growth_threshold: f64,
+    /// Prompt growth reported as high severity
+    pub high_prompt_growth: f64,
+    /// Response tokens may grow by at most this factor ("flat")
+    pub max_response_growth: f64,
+    /// Common detection config (`min_samples` gates the reference)
+    pub detection: DetectionConfig,
+}
+
+impl Default for TokenEfficiencyConfig {
+    fn default() -> Self {
+        Self {
+            window_size: 100,
+            reference_size: 1000,
+            prompt_growth_threshold: 1.5,
+            high_prompt_growth: 2.5,
+            max_response_growth: 1.1,
+            detection: DetectionConfig {
+                min_samples: 100,
+                update_baseline: true,
+            },
+        }
+    }
+}
+
+impl TokenEfficiencyConfig {
+    /// Detector configuration for the given prompt bloat settings
+    pub fn from_config(config: &PromptBloatConfig) -> Self {
+        Self {
+            window_size: config.window_size,
+            reference_size: config.reference_size,
+            prompt_growth_threshold: config.prompt_growth_threshold,
+            high_prompt_growth: config.high_prompt_growth,
+            max_response_growth: config.max_response_growth,
+            detection: DetectionConfig {
+                min_samples: config.min_samples,
+                update_baseline: true,
+            },
+        }
+    }
+}
+
+/// Token usage and cost (per request, summed or averaged)
+#[derive(Debug, Clone, Copy, Default)]
+struct Usage {
+    prompt_tokens: f64,
+    response_tokens: f64,
+    cost_usd: f64,
+}
+
+impl Usage {
+    fn from_event(event: &TelemetryEvent) -> Self {
+        Self {
+            prompt_tokens: event.prompt.tokens as f64,
+            response_tokens: event.response.tokens as f64,
+            cost_usd: event.cost_usd,
+        }
+    }
+
+    /// Response tokens per prompt token
+    fn efficiency(&self) -> f64 {
+        ratio(self.response_tokens, self.prompt_tokens)
+    }
+
+    /// Cost per response token
+    fn cost_per_useful_token(&self) -> f64 {
+        ratio(self.cost_usd, self.response_tokens)
+    }
+}
+
+fn ratio(numerator: f64, denominator: f64) -> f64 {
+    if denominator > 0.0 {
+        numerator / denominator
+    } else {
+        0.0
+    }
+}
+
+/// Usage state for one service/model
+#[derive(Debug, Clone, Default)]
+struct EfficiencyState {
+    window: VecDeque<Usage>,
+    /// Sums over the window
+    sums: Usage,
+    /// Long-run means (EWMA over events leaving the window)
+    reference: Usage,
+    reference_samples: u64,
+    /// Whether a regression is currently being reported
+    in_regression: bool,
+}
+
+/// Evaluated regression
+#[derive(Debug, Clone, Copy)]
+struct Regression {
+    recent: Usage,
+    reference: Usage,
+    prompt_growth: f64,
+    response_growth: f64,
+}
+
+impl EfficiencyState {
+    fn push(&mut self, usage: Usage, config: &TokenEfficiencyConfig) {
+        self.window.push_back(usage);
+        self.sums.prompt_tokens += usage.prompt_tokens;
+        self.sums.response_tokens += usage.response_tokens;
+        self.sums.cost_usd += usage.cost_usd;
+
+        if self.window.len() > config.window_size {
+            if let Some(old) = self.window.pop_front() {
+                self.sums.prompt_tokens -= old.prompt_tokens;
+                self.sums.response_tokens -= old.response_tokens;
+                self.sums.cost_usd -= old.cost_usd;
+
+                self.reference_samples += 1;
+                // Plain average until the reference fills, then EWMA
+                let alpha = 1.0 / (self.reference_samples as f64).min(config.reference_size as f64);
+                self.reference.prompt_tokens +=
+                    alpha * (old.prompt_tokens - self.reference.prompt_tokens);
+                self.reference.response_tokens +=
+                    alpha * (old.response_tokens - self.reference.response_tokens);
+                self.reference.cost_usd += alpha * (old.cost_usd - self.reference.cost_usd);
+            }
+        }
+    }
+
+    /// Mean usage over the recent window
+    fn recent(&self) -> Usage {
+        let n = self.window.len().max(1) as f64;
+        Usage {
+            prompt_tokens: self.sums.prompt_tokens / n,
+            response_tokens: self.sums.response_tokens / n,
+            cost_usd: self.sums.cost_usd / n,
+        }
+    }
+
+    fn evaluate(&self, config: &TokenEfficiencyConfig) -> Option<Regression> {
+        if self.window.len() < config.window_size
+            || self.reference_samples < config.detection.min_samples as u64
+            || self.reference.prompt_tokens <= 0.0
+        {
+            return None;
+        }
+
+        let recent = self.recent();
+        let prompt_growth = recent.prompt_tokens / self.reference.prompt_tokens;
+        let response_growth = if self.reference.response_tokens > 0.0 {
+            recent.response_tokens / self.reference.response_tokens
+        } else {
+            1.0
+        };
+
+        (prompt_growth >= config.prompt_growth_threshold
+            && response_growth <= config.max_response_growth)
+            .then_some(Regression {
+                recent,
+                reference: self.reference,
+                prompt_growth,
+                response_growth,
+            })
+    }
+}
+
+/// Token-efficiency regression detector
+///
+/// Emits [`AnomalyType::TokenUsageSpike`] with `metric: "token_efficiency"`.
+pub struct TokenEfficiencyDetector {
+    config: TokenEfficiencyConfig,
+    states: Arc<DashMap<BaselineKey, EfficiencyState>>,
+    stats: DetectorStats,
+}
+
+impl std::fmt::Debug for TokenEfficiencyDetector {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("TokenEfficiencyDetector")
+            .field("config", &self.config)
+            .field("states_count", &self.states.len())
+            .field("stats", &self.stats)
+            .finish()
+    }
+}
+
+impl TokenEfficiencyDetector {
+    /// Create a new token-efficiency detector
+    pub fn new(config: TokenEfficiencyConfig) -> Self {
+        Self {
+            config,
+            states: Arc::new(DashMap::new()),
+            stats: DetectorStats::empty(),
+        }
+    }
+
+    fn key(event: &TelemetryEvent) -> BaselineKey {
+        BaselineKey::new(
+            event.service_name.clone(),
+            event.model.clone(),
+            "token_efficiency",
+        )
+    }
+
+    fn build_anomaly(&self, event: &TelemetryEvent, regression: &Regression) -> AnomalyEvent {
+        let severity = if regression.prompt_growth >= self.config.high_prompt_growth {
+            Severity::High
+        } else {
+            Severity::Medium
+        };
+
+        let Regression {
+            recent, reference, ..
+        } = regression;
+
+        let mut additional = HashMap::new();
+        for (name, value) in [
+            ("prompt_growth", regression.prompt_growth),
+            ("response_growth", regression.response_growth),
+            ("reference_prompt_tokens", reference.prompt_tokens),
+            ("recent_prompt_tokens", recent.prompt_tokens),
+            ("reference_response_tokens", reference.response_tokens),
+            ("recent_response_tokens", recent.response_tokens),
+            (
+                "reference_cost_per_useful_token",
+                reference.cost_per_useful_token(),
+            ),
+            (
+                "recent_cost_per_useful_token",
+                recent.cost_per_useful_token(),
+            ),
+        ] {
+            additional.insert(name.to_string(), serde_json::json!(value));
+        }
+
+        AnomalyEvent::new(
+            severity,
+            AnomalyType::TokenUsageSpike,
+            event.service_name.clone(),
+            event.model.clone(),
+            DetectionMethod::Custom("token_efficiency".to_string()),
+            (regression.prompt_growth / self.config.high_prompt_growth).clamp(0.5, 0.99),
+            AnomalyDetails {
+                metric: "token_efficiency".to_string(),
+                value: recent.efficiency(),
+                baseline: reference.efficiency(),
+                threshold: reference.efficiency() / self.config.prompt_growth_threshold,
+                deviation_sigma: None,
+                additional,
+                observations: Vec::new(),
+            },
+            AnomalyContext {
+                trace_id: event.trace_id.clone(),
+                user_id: None,
+                region: None,
+                time_window: WindowSpec::samples(self.config.window_size).to_string(),
+                sample_count: self.config.window_size,
+                window: None,
+                additional: HashMap::new(),
+            },
+        )
+        .with_root_cause(format!(
+            "Prompt tokens up {:.1}x ({:.0} -> {:.0}) with responses flat ({:.2}x); \
+             efficiency {:.3} -> {:.3} response tokens per prompt token",
+            regression.prompt_growth,
+            reference.prompt_tokens,
+            recent.prompt_tokens,
+            regression.response_growth,
+            reference.efficiency(),
+            recent.efficiency()
+        ))
+        .with_remediation("Review recent prompt template and context assembly changes")
+        .with_remediation("Check retrieval or conversation history truncation limits")
+    }
+}
+
+#[async_trait]
+impl Detector for TokenEfficiencyDetector {
+    async fn detect(&self, event: &TelemetryEvent) -> Result<Option<AnomalyEvent>> {
+        let Some(state) = self.states.get(&Self::key(event)) else {
+            return Ok(None);
+        };
+        if state.in_regression {
+            return Ok(None);
+        }
+
+        // Evaluate on a copy; `update` commits the event
+        let mut next = state.clone();
+        drop(state);
+        next.push(Usage::from_event(event), &self.config);
+
+        Ok(next.evaluate(&self.config).map(|regression| {
+            debug!(
+                service = %event.service_name,
+                model = %event.model,
+                prompt_growth = regression.prompt_growth,
+                "Token efficiency regression detected"
+            );
+            self.build_anomaly(event, &regression)
+        }))
+    }
+
+    fn name(&self) -> &str {
+        "token_efficiency"
+    }
+
+    fn detector_type(&self) -> DetectorType {
+        DetectorType::Statistical
+    }
+
+    async fn update(&mut self, event: &TelemetryEvent) -> Result<()> {
+        if !self.config.detection.update_baseline {
+            return Ok(());
+        }
+
+        let mut state = self.states.entry(Self::key(event)).or_default();
+        state.push(Usage::from_event(event), &self.config);
+        state.in_regression = state.evaluate(&self.config).is_some();
+
+        self.stats.events_processed += 1;
+        Ok(())
+    }
+
+    async fn reset(&mut self) -> Result<()> {
+        self.states.clear();
+        self.stats = DetectorStats::empty();
+        Ok(())
+    }
+
+    fn stats(&self) -> DetectorStats {
+        self.stats.clone()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use llm_sentinel_fixtures::event;
+
+    fn create_test_event(prompt_tokens: u32, response_tokens: u32) -> TelemetryEvent {
+        event()
+            .service("support-bot")
+            .tokens(prompt_tokens, response_tokens)
+            .cost_usd((prompt_tokens as f64 * 0.03 + response_tokens as f64 * 0.06) / 1000.0)
+            .build()
+    }
+
+    async fn feed(
+        detector: &mut TokenEfficiencyDetector,
+        events: impl Iterator<Item = TelemetryEvent>,
+    ) -> Vec<AnomalyEvent> {
+        let mut detected = Vec::new();
+        for event in events {
+            if let Some(anomaly) = detector.detect(&event).await.unwrap() {
+                detected.push(anomaly);
+            }
+            detector.update(&event).await.unwrap();
+        }
+        detected
+    }
+
+    #[tokio::test]
+    async fn test_prompt_bloat_detected() {
+        let mut detector = TokenEfficiencyDetector::new(TokenEfficiencyConfig::default());
+
+        let stable = (0..300u32).map(|i| create_test_event(500 + i % 50, 200 + i % 20));
+        assert!(feed(&mut detector, stable).await.is_empty());
+
+        // Prompt template triples in size, responses unchanged
+        let bloated = (0..100u32).map(|i| create_test_event(1500 + i % 50, 200 + i % 20));
+        let detected = feed(&mut detector, bloated).await;
+
+        assert_eq!(detected.len(), 1);
+        let anomaly = &detected[0];
+        assert_eq!(anomaly.anomaly_type, AnomalyType::TokenUsageSpike);
+        assert_eq!(anomaly.details.metric, "token_efficiency");
+        assert!(anomaly.details.value < anomaly.details.baseline);
+        let growth = anomaly.details.additional["prompt_growth"]
+            .as_f64()
+            .unwrap();
+        assert!(growth >= 1.5);
+    }
+
+    #[tokio::test]
+    async fn test_proportional_growth_ignored() {
+        let mut detector = TokenEfficiencyDetector::new(TokenEfficiencyConfig::default());
+
+        let stable = (0..300u32).map(|i| create_test_event(500 + i % 50, 200 + i % 20));
+        feed(&mut detector, stable).await;
+
+        // Longer prompts that also produce longer answers are not bloat
+        let longer = (0..200u32).map(|i| create_test_event(1500 + i % 50, 600 + i % 20));
+        assert!(feed(&mut detector, longer).await.is_empty());
+    }
+}
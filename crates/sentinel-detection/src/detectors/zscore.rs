@@ -110,13 +110,15 @@ impl ZScoreDetector {
                     threshold: baseline.mean + self.config.threshold * baseline.std_dev,
                     deviation_sigma: Some(z.abs()),
                     additional: HashMap::new(),
+                    observations: Vec::new(),
                 },
                 AnomalyContext {
                     trace_id: event.trace_id.clone(),
                     user_id: event.metadata.get("user_id").cloned(),
                     region: event.metadata.get("region").cloned(),
-                    time_window: "rolling_window".to_string(),
+                    time_window: self.baseline_manager.spec().to_string(),
                     sample_count: baseline.sample_count,
+                    window: baseline.window.clone(),
                     additional: HashMap::new(),
                 },
             )
@@ -172,13 +174,15 @@ impl ZScoreDetector {
                     threshold: baseline.mean + self.config.threshold * baseline.std_dev,
                     deviation_sigma: Some(z.abs()),
                     additional: HashMap::new(),
+                    observations: Vec::new(),
                 },
                 AnomalyContext {
                     trace_id: event.trace_id.clone(),
                     user_id: event.metadata.get("user_id").cloned(),
                     region: event.metadata.get("region").cloned(),
-                    time_window: "rolling_window".to_string(),
+                    time_window: self.baseline_manager.spec().to_string(),
                     sample_count: baseline.sample_count,
+                    window: baseline.window.clone(),
                     additional: HashMap::new(),
                 },
             )
@@ -226,13 +230,15 @@ impl ZScoreDetector {
                     threshold: baseline.mean + self.config.threshold * baseline.std_dev,
                     deviation_sigma: Some(z.abs()),
                     additional: HashMap::new(),
+                    observations: Vec::new(),
                 },
                 AnomalyContext {
                     trace_id: event.trace_id.clone(),
                     user_id: event.metadata.get("user_id").cloned(),
                     region: event.metadata.get("region").cloned(),
-                    time_window: "rolling_window".to_string(),
+                    time_window: self.baseline_manager.spec().to_string(),
                     sample_count: baseline.sample_count,
+                    window: baseline.window.clone(),
                     additional: HashMap::new(),
                 },
             )
@@ -308,14 +314,18 @@ impl Detector for ZScoreDetector {
         // Update baselines with event data
         let latency_key = BaselineKey::latency(event.service_name.clone(), event.model.clone());
         self.baseline_manager
-            .update(latency_key, event.latency_ms)?;
+            .update_at(latency_key, event.latency_ms, event.timestamp)?;
 
         let tokens_key = BaselineKey::tokens(event.service_name.clone(), event.model.clone());
-        self.baseline_manager
-            .update(tokens_key, event.total_tokens() as f64)?;
+        self.baseline_manager.update_at(
+            tokens_key,
+            event.total_tokens() as f64,
+            event.timestamp,
+        )?;
 
         let cost_key = BaselineKey::cost(event.service_name.clone(), event.model.clone());
-        self.baseline_manager.update(cost_key, event.cost_usd)?;
+        self.baseline_manager
+            .update_at(cost_key, event.cost_usd, event.timestamp)?;
 
         Ok(())
     }
@@ -334,29 +344,14 @@ impl Detector for ZScoreDetector {
 #[cfg(test)]
 mod tests {
     use super::*;
-    use llm_sentinel_core::{
-        events::{PromptInfo, ResponseInfo},
-        types::{ModelId, ServiceId},
-    };
+    use llm_sentinel_fixtures::event;
 
     fn create_test_event(latency: f64, tokens: u32, cost: f64) -> TelemetryEvent {
-        TelemetryEvent::new(
-            ServiceId::new("test"),
-            ModelId::new("gpt-4"),
-            PromptInfo {
-                text: "test".to_string(),
-                tokens: tokens / 2,
-                embedding: None,
-            },
-            ResponseInfo {
-                text: "response".to_string(),
-                tokens: tokens / 2,
-                finish_reason: "stop".to_string(),
-                embedding: None,
-            },
-            latency,
-            cost,
-        )
+        event()
+            .tokens(tokens / 2, tokens / 2)
+            .latency_ms(latency)
+            .cost_usd(cost)
+            .build()
     }
 
     #[tokio::test]
@@ -423,8 +418,9 @@ mod tests {
         let conf_4 = detector.calculate_confidence(4.0);
         let conf_6 = detector.calculate_confidence(6.0);
 
-        assert!(conf_3 >= 0.5 && conf_3 < 1.0);
-        assert!(conf_4 > conf_3);
+        assert!((0.5..1.0).contains(&conf_3));
+        // Up to one sigma past the threshold confidence sits at the 0.5 floor
+        assert!(conf_4 >= conf_3);
         assert!(conf_6 > conf_4);
     }
 }
@@ -3,20 +3,38 @@
 //! Coordinates multiple detectors and manages the detection pipeline.
 
 use crate::{
-    baseline::BaselineManager,
+    baseline::{BaselineKey, BaselineManager, BaselineSnapshot},
     detectors::{
+        bocpd::{BocpdConfig, BocpdDetector},
+        content_filter::{ContentFilterConfig, ContentFilterDetector},
+        context_overflow::{ContextOverflowConfig, ContextOverflowDetector},
+        cost_residual::{CostResidualConfig, CostResidualDetector},
         cusum::{CusumConfig, CusumDetector},
         iqr::{IqrConfig, IqrDetector},
+        kl_divergence::{KlDivergenceConfig, KlDivergenceDetector},
         mad::{MadConfig, MadDetector},
+        multi_window::{MultiWindowConfig, MultiWindowDetector},
+        psi::{PsiConfig, PsiDetector},
+        token_efficiency::{TokenEfficiencyConfig, TokenEfficiencyDetector},
         zscore::{ZScoreConfig, ZScoreDetector},
     },
-    Detector, DetectorStats,
+    latency::LatencyBreakdownTracker,
+    load::{DetectionLoad, LoadReport},
+    stats,
+    versions::ModelVersionTracker,
+    Detector, DetectorStats, DetectorType,
 };
 use llm_sentinel_core::{
+    clock::Clock,
+    config::DetectionTieringConfig,
     events::{AnomalyEvent, TelemetryEvent},
+    timing,
+    types::{ModelId, ServiceId},
+    window::WindowSpec,
     Error, Result,
 };
-use std::sync::Arc;
+use serde::{Deserialize, Serialize};
+use std::{borrow::Cow, collections::BTreeMap, sync::Arc};
 use tokio::sync::RwLock;
 use tracing::{debug, info, warn};
 
@@ -43,11 +61,64 @@ pub struct EngineConfig {
     /// CUSUM configuration
     pub cusum_config: CusumConfig,
 
-    /// Baseline window size
-    pub baseline_window_size: usize,
+    /// Enable BOCPD change point detector
+    pub enable_bocpd: bool,
+    /// BOCPD configuration
+    pub bocpd_config: BocpdConfig,
+
+    /// Enable multi-window regime change detector
+    pub enable_multi_window: bool,
+    /// Multi-window configuration
+    pub multi_window_config: MultiWindowConfig,
+
+    /// Enable PSI drift detector
+    pub enable_psi: bool,
+    /// PSI configuration
+    pub psi_config: PsiConfig,
+
+    /// Enable KL divergence output drift detector
+    pub enable_kl_divergence: bool,
+    /// KL divergence configuration
+    pub kl_divergence_config: KlDivergenceConfig,
+
+    /// Enable content-filter surge detector
+    pub enable_content_filter: bool,
+    /// Content-filter surge configuration
+    pub content_filter_config: ContentFilterConfig,
+
+    /// Enable token-efficiency regression detector
+    pub enable_token_efficiency: bool,
+    /// Token-efficiency configuration
+    pub token_efficiency_config: TokenEfficiencyConfig,
+
+    /// Enable context window overflow detector
+    pub enable_context_overflow: bool,
+    /// Context overflow configuration
+    pub context_overflow_config: ContextOverflowConfig,
+
+    /// Enable cost residual (cost vs tokens regression) detector
+    pub enable_cost_residual: bool,
+    /// Cost residual configuration
+    pub cost_residual_config: CostResidualConfig,
+
+    /// Bounds of the rolling baseline windows
+    pub baseline_window: WindowSpec,
 
     /// Update baselines continuously
     pub continuous_learning: bool,
+
+    /// Key detector baselines by model version (`gpt-4@0613`) rather than
+    /// model identifier
+    pub key_by_model_version: bool,
+    /// Lookback for attributing drift to a model version change (seconds)
+    pub version_change_lookback_secs: u64,
+
+    /// Run expensive detectors only on guard candidates (all detectors run
+    /// on every event when unset)
+    pub tiering: Option<DetectionTieringConfig>,
+
+    /// Clock baselines and anomaly timestamps are read from
+    pub clock: Clock,
 }
 
 impl Default for EngineConfig {
@@ -61,17 +132,63 @@ impl Default for EngineConfig {
             mad_config: MadConfig::default(),
             enable_cusum: true,
             cusum_config: CusumConfig::default(),
-            baseline_window_size: 1000,
+            enable_bocpd: false,
+            bocpd_config: BocpdConfig::default(),
+            enable_multi_window: false,
+            multi_window_config: MultiWindowConfig::default(),
+            enable_psi: false,
+            psi_config: PsiConfig::default(),
+            enable_kl_divergence: false,
+            kl_divergence_config: KlDivergenceConfig::default(),
+            enable_content_filter: false,
+            content_filter_config: ContentFilterConfig::default(),
+            enable_token_efficiency: false,
+            token_efficiency_config: TokenEfficiencyConfig::default(),
+            enable_context_overflow: false,
+            context_overflow_config: ContextOverflowConfig::default(),
+            enable_cost_residual: false,
+            cost_residual_config: CostResidualConfig::default(),
+            baseline_window: WindowSpec::default(),
             continuous_learning: true,
+            key_by_model_version: false,
+            version_change_lookback_secs: 3600,
+            tiering: None,
+            clock: Clock::system(),
         }
     }
 }
 
+impl EngineConfig {
+    /// Baselines over the most recent `size` samples
+    ///
+    /// Replaces the former `baseline_window_size` field.
+    #[deprecated(
+        since = "0.1.0",
+        note = "set `baseline_window: WindowSpec::samples(size)` instead"
+    )]
+    pub fn with_baseline_window_size(mut self, size: usize) -> Self {
+        self.baseline_window = WindowSpec::samples(size);
+        self
+    }
+}
+
+/// Learned state of a detection engine
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct EngineSnapshot {
+    /// Baseline windows shared by the statistical detectors
+    pub baselines: Vec<BaselineSnapshot>,
+    /// State of the detectors keeping their own, by detector name
+    pub detectors: BTreeMap<String, serde_json::Value>,
+}
+
 /// Detection engine that orchestrates multiple detectors
 pub struct DetectionEngine {
     config: EngineConfig,
     baseline_manager: Arc<BaselineManager>,
     detectors: Vec<Box<dyn Detector + Send + Sync>>,
+    versions: ModelVersionTracker,
+    latency: LatencyBreakdownTracker,
+    load: Arc<DetectionLoad>,
     stats: Arc<RwLock<EngineStats>>,
 }
 
@@ -95,6 +212,10 @@ pub struct EngineStats {
     pub detection_rate: f64,
     /// Detector-specific stats
     pub detector_stats: Vec<(String, DetectorStats)>,
+    /// Load since startup: skipped events, detector errors, queue waits
+    pub load: LoadReport,
+    /// Load over the last five minutes
+    pub recent: LoadReport,
 }
 
 impl EngineStats {
@@ -104,6 +225,8 @@ impl EngineStats {
             anomalies_detected: 0,
             detection_rate: 0.0,
             detector_stats: Vec::new(),
+            load: LoadReport::default(),
+            recent: LoadReport::default(),
         }
     }
 
@@ -121,7 +244,9 @@ impl DetectionEngine {
     pub fn new(config: EngineConfig) -> Result<Self> {
         info!("Creating detection engine");
 
-        let baseline_manager = Arc::new(BaselineManager::new(config.baseline_window_size));
+        let baseline_manager = Arc::new(
+            BaselineManager::with_spec(config.baseline_window).with_clock(config.clock.clone()),
+        );
         let mut detectors: Vec<Box<dyn Detector + Send + Sync>> = Vec::new();
 
         // Initialize enabled detectors
@@ -155,6 +280,54 @@ impl DetectionEngine {
             detectors.push(Box::new(detector));
         }
 
+        if config.enable_bocpd {
+            info!("Enabling BOCPD change point detector");
+            let detector = BocpdDetector::new(config.bocpd_config.clone());
+            detectors.push(Box::new(detector));
+        }
+
+        if config.enable_multi_window {
+            info!("Enabling multi-window regime change detector");
+            let detector = MultiWindowDetector::new(config.multi_window_config.clone());
+            detectors.push(Box::new(detector));
+        }
+
+        if config.enable_psi {
+            info!("Enabling PSI drift detector");
+            let detector = PsiDetector::new(config.psi_config.clone());
+            detectors.push(Box::new(detector));
+        }
+
+        if config.enable_kl_divergence {
+            info!("Enabling KL divergence drift detector");
+            let detector = KlDivergenceDetector::new(config.kl_divergence_config.clone());
+            detectors.push(Box::new(detector));
+        }
+
+        if config.enable_content_filter {
+            info!("Enabling content-filter surge detector");
+            let detector = ContentFilterDetector::new(config.content_filter_config.clone());
+            detectors.push(Box::new(detector));
+        }
+
+        if config.enable_token_efficiency {
+            info!("Enabling token-efficiency regression detector");
+            let detector = TokenEfficiencyDetector::new(config.token_efficiency_config.clone());
+            detectors.push(Box::new(detector));
+        }
+
+        if config.enable_context_overflow {
+            info!("Enabling context window overflow detector");
+            let detector = ContextOverflowDetector::new(config.context_overflow_config.clone());
+            detectors.push(Box::new(detector));
+        }
+
+        if config.enable_cost_residual {
+            info!("Enabling cost residual detector");
+            let detector = CostResidualDetector::new(config.cost_residual_config.clone());
+            detectors.push(Box::new(detector));
+        }
+
         if detectors.is_empty() {
             return Err(Error::config("No detectors enabled"));
         }
@@ -162,6 +335,9 @@ impl DetectionEngine {
         info!("Detection engine created with {} detectors", detectors.len());
 
         Ok(Self {
+            versions: ModelVersionTracker::new(config.version_change_lookback_secs),
+            latency: LatencyBreakdownTracker::new(),
+            load: Arc::new(DetectionLoad::new(config.clock.clone())),
             config,
             baseline_manager,
             detectors,
@@ -171,11 +347,10 @@ impl DetectionEngine {
 
     /// Detect anomalies in a telemetry event
     ///
-    /// Runs all enabled detectors and returns the first anomaly found.
-    /// In production, this could be extended to:
-    /// - Run detectors in parallel
-    /// - Aggregate multiple detections
-    /// - Apply ensemble voting
+    /// Runs the enabled detectors in order and returns the first anomaly
+    /// found. With tiering configured, expensive detectors only run when
+    /// the cheap ones found nothing and the guard flags the event as a
+    /// candidate.
     pub async fn detect(&self, event: &TelemetryEvent) -> Result<Option<AnomalyEvent>> {
         debug!(
             event_id = %event.event_id,
@@ -185,15 +360,79 @@ impl DetectionEngine {
         );
 
         let start = std::time::Instant::now();
+        let keyed = Self::detector_event(&self.config, event);
+
+        let found = match &self.config.tiering {
+            None => self.run_stage("all", None, event, &keyed, start).await,
+            Some(tiering) => {
+                let cheap = self
+                    .run_stage("cheap", Some(false), event, &keyed, start)
+                    .await;
+                if cheap.is_some() {
+                    cheap
+                } else if self.passes_guard(tiering, &keyed) {
+                    self.run_stage("expensive", Some(true), event, &keyed, start)
+                        .await
+                } else {
+                    None
+                }
+            }
+        };
+
+        let mut stats = self.stats.write().await;
+        stats.update(found.is_some());
+        self.load.record_event(found.is_some());
+        if found.is_none() {
+            metrics::histogram!("sentinel_detection_duration_seconds", "detector" => "all")
+                .record(start.elapsed().as_secs_f64());
+        }
+
+        Ok(found)
+    }
+
+    /// Run one stage of detectors: all of them, or only the cheap or only
+    /// the expensive ones
+    async fn run_stage(
+        &self,
+        stage: &'static str,
+        expensive: Option<bool>,
+        event: &TelemetryEvent,
+        keyed: &TelemetryEvent,
+        start: std::time::Instant,
+    ) -> Option<AnomalyEvent> {
+        let stage_start = std::time::Instant::now();
+        metrics::counter!("sentinel_detection_stage_events_total", "stage" => stage).increment(1);
+
+        let detectors = self.detectors.iter().filter(|detector| {
+            expensive.map_or(true, |e| self.is_expensive(detector.as_ref()) == e)
+        });
+        let mut found = None;
+        for detector in detectors {
+            let detector_start = std::time::Instant::now();
+            let result = detector.detect(keyed).await;
+            if timing::active() {
+                timing::record(
+                    format!("detect:{}", detector.name()),
+                    detector_start.elapsed(),
+                );
+            }
+            self.load.record_detector(detector.name(), result.is_err());
+            match result {
+                Ok(Some(mut anomaly)) => {
+                    anomaly.timestamp = self.config.clock.now();
+                    anomaly.service_name = event.service_name.clone();
+                    anomaly.model = event.model.clone();
+                    anomaly.refresh_signature_hash();
+                    anomaly.environment = event.environment.clone();
+                    anomaly.labels = event.labels.clone();
+                    self.versions.attribute(&mut anomaly, event);
+                    self.latency.attribute(&mut anomaly, keyed);
 
-        // Run detectors sequentially (can be parallelized for performance)
-        for detector in &self.detectors {
-            match detector.detect(event).await {
-                Ok(Some(anomaly)) => {
                     let elapsed = start.elapsed();
                     info!(
                         event_id = %event.event_id,
                         detector = detector.name(),
+                        stage,
                         anomaly_type = %anomaly.anomaly_type,
                         severity = %anomaly.severity,
                         confidence = anomaly.confidence,
@@ -201,10 +440,6 @@ impl DetectionEngine {
                         "Anomaly detected"
                     );
 
-                    // Update stats
-                    let mut stats = self.stats.write().await;
-                    stats.update(true);
-
                     // Record metrics - convert to owned strings for 'static lifetime
                     let detector_name = detector.name().to_string();
                     let anomaly_type_str = anomaly.anomaly_type.to_string();
@@ -224,7 +459,8 @@ impl DetectionEngine {
                     )
                     .record(elapsed.as_secs_f64());
 
-                    return Ok(Some(anomaly));
+                    found = Some(anomaly);
+                    break;
                 }
                 Ok(None) => {
                     // No anomaly detected by this detector
@@ -249,36 +485,134 @@ impl DetectionEngine {
             }
         }
 
-        // No anomalies detected
-        let mut stats = self.stats.write().await;
-        stats.update(false);
+        metrics::histogram!("sentinel_detection_stage_duration_seconds", "stage" => stage)
+            .record(stage_start.elapsed().as_secs_f64());
+        if found.is_some() {
+            metrics::counter!("sentinel_detection_stage_anomalies_total", "stage" => stage)
+                .increment(1);
+        }
+        found
+    }
+
+    /// Whether a detector only runs on guard candidates
+    fn is_expensive(&self, detector: &(dyn Detector + Send + Sync)) -> bool {
+        self.config.tiering.as_ref().is_some_and(|tiering| {
+            detector.detector_type() != DetectorType::Statistical
+                || tiering.expensive.iter().any(|name| name == detector.name())
+        })
+    }
+
+    /// Whether the guard passes an event on to the expensive detectors
+    fn passes_guard(&self, tiering: &DetectionTieringConfig, event: &TelemetryEvent) -> bool {
+        let candidate = self.is_candidate(tiering, event);
+        let outcome = if candidate { "candidate" } else { "filtered" };
+        metrics::counter!("sentinel_detection_guard_total", "outcome" => outcome).increment(1);
+        candidate
+    }
 
-        let elapsed = start.elapsed();
-        metrics::histogram!("sentinel_detection_duration_seconds", "detector" => "all")
-            .record(elapsed.as_secs_f64());
+    fn is_candidate(&self, tiering: &DetectionTieringConfig, event: &TelemetryEvent) -> bool {
+        if tiering.candidate_on_error && !event.errors.is_empty() {
+            return true;
+        }
+        if tiering
+            .max_latency_ms
+            .is_some_and(|max| event.latency_ms > max)
+            || tiering.max_cost_usd.is_some_and(|max| event.cost_usd > max)
+        {
+            return true;
+        }
 
-        Ok(None)
+        let (service, model) = (&event.service_name, &event.model);
+        let latency = BaselineKey::latency(service.clone(), model.clone());
+        let tokens = BaselineKey::tokens(service.clone(), model.clone());
+        let cost = BaselineKey::cost(service.clone(), model.clone());
+        [
+            (latency, event.latency_ms),
+            (tokens, event.total_tokens() as f64),
+            (cost, event.cost_usd),
+        ]
+        .into_iter()
+        .any(|(key, value)| match self.baseline_manager.get(&key) {
+            Some(baseline) if baseline.is_valid() => {
+                if baseline.std_dev == 0.0 {
+                    value != baseline.mean
+                } else {
+                    stats::zscore(value, baseline.mean, baseline.std_dev).abs()
+                        >= tiering.guard_sigma
+                }
+            }
+            // Nothing to judge the event by yet
+            _ => true,
+        })
     }
 
     /// Update detectors with new event (for learning)
     pub async fn update(&mut self, event: &TelemetryEvent) -> Result<()> {
+        self.versions.record(event);
+
         if !self.config.continuous_learning {
             return Ok(());
         }
 
+        let keyed = Self::detector_event(&self.config, event);
+        self.latency.record(&keyed);
         for detector in &mut self.detectors {
-            if let Err(e) = detector.update(event).await {
+            let detector_start = std::time::Instant::now();
+            if let Err(e) = detector.update(&keyed).await {
                 warn!(
                     detector = detector.name(),
                     error = %e,
                     "Failed to update detector"
                 );
             }
+            if timing::active() {
+                timing::record(
+                    format!("learn:{}", detector.name()),
+                    detector_start.elapsed(),
+                );
+            }
         }
 
         Ok(())
     }
 
+    /// Warm up detectors from historical telemetry
+    ///
+    /// Replays `events` oldest first through every detector's update path,
+    /// regardless of `continuous_learning`, so rolling windows and baselines
+    /// are populated before live traffic arrives. Returns the number of
+    /// events replayed.
+    pub async fn bootstrap(&mut self, events: &[TelemetryEvent]) -> usize {
+        let mut ordered: Vec<&TelemetryEvent> = events.iter().collect();
+        ordered.sort_by_key(|event| event.timestamp);
+
+        for event in &ordered {
+            self.versions.record(event);
+
+            let keyed = Self::detector_event(&self.config, event);
+            self.latency.record(&keyed);
+            for detector in &mut self.detectors {
+                if let Err(e) = detector.update(&keyed).await {
+                    warn!(
+                        detector = detector.name(),
+                        error = %e,
+                        "Failed to bootstrap detector"
+                    );
+                }
+            }
+        }
+
+        info!(
+            events = ordered.len(),
+            baselines = self.baseline_manager.stats().total_baselines,
+            "Bootstrapped detection baselines from history"
+        );
+        metrics::counter!("sentinel_baseline_bootstrap_events_total")
+            .increment(ordered.len() as u64);
+
+        ordered.len()
+    }
+
     /// Process a telemetry event (detect + update)
     pub async fn process(&mut self, event: &TelemetryEvent) -> Result<Option<AnomalyEvent>> {
         // First detect anomalies
@@ -301,10 +635,18 @@ impl DetectionEngine {
             .iter()
             .map(|d| (d.name().to_string(), d.stats()))
             .collect();
+        stats.load = self.load.lifetime();
+        stats.recent = self.load.recent();
 
         stats
     }
 
+    /// Ledger of detection load, shared with the pipeline feeding the
+    /// engine so it can count events kept out of detection
+    pub fn load(&self) -> Arc<DetectionLoad> {
+        Arc::clone(&self.load)
+    }
+
     /// Reset all detectors
     pub async fn reset(&mut self) -> Result<()> {
         info!("Resetting detection engine");
@@ -313,6 +655,10 @@ impl DetectionEngine {
             detector.reset().await?;
         }
 
+        self.versions.clear();
+        self.latency.clear();
+        self.load.clear();
+
         let mut stats = self.stats.write().await;
         *stats = EngineStats::empty();
 
@@ -320,11 +666,78 @@ impl DetectionEngine {
         Ok(())
     }
 
+    /// Event as presented to detectors
+    ///
+    /// With `key_by_model_version`, the model becomes `family@version` so
+    /// detectors keep separate baselines per model version. Events with an
+    /// environment use `environment/service` as the service, so e.g. staging
+    /// traffic never feeds production baselines.
+    fn detector_event<'a>(
+        config: &EngineConfig,
+        event: &'a TelemetryEvent,
+    ) -> Cow<'a, TelemetryEvent> {
+        let version = config
+            .key_by_model_version
+            .then(|| event.resolved_model_version())
+            .flatten();
+        if version.is_none() && event.environment.is_none() {
+            return Cow::Borrowed(event);
+        }
+
+        let mut keyed = event.clone();
+        if let Some(version) = version {
+            keyed.model = ModelId::new(version.to_string());
+        }
+        if let Some(environment) = &event.environment {
+            keyed.service_name = ServiceId::new(format!("{}/{}", environment, event.service_name));
+        }
+        Cow::Owned(keyed)
+    }
+
+    /// Add a detector after construction (e.g. a plugin)
+    ///
+    /// It runs after the built-in detectors.
+    pub fn add_detector(&mut self, detector: Box<dyn Detector + Send + Sync>) {
+        info!(detector = detector.name(), "Adding detector");
+        self.detectors.push(detector);
+    }
+
     /// Get baseline manager for external access
     pub fn baseline_manager(&self) -> &Arc<BaselineManager> {
         &self.baseline_manager
     }
 
+    /// Learned state, for restoring into another engine
+    pub fn snapshot(&self) -> Result<EngineSnapshot> {
+        let mut detectors = BTreeMap::new();
+        for detector in &self.detectors {
+            if let Some(state) = detector.export_state()? {
+                detectors.insert(detector.name().to_string(), state);
+            }
+        }
+        Ok(EngineSnapshot {
+            baselines: self.baseline_manager.snapshot(),
+            detectors,
+        })
+    }
+
+    /// Replace learned state with a snapshot
+    ///
+    /// State of detectors that are not enabled here is ignored.
+    pub fn restore(&mut self, snapshot: EngineSnapshot) -> Result<()> {
+        self.baseline_manager.restore(snapshot.baselines)?;
+        let mut states = snapshot.detectors;
+        for detector in &mut self.detectors {
+            if let Some(state) = states.remove(detector.name()) {
+                detector.import_state(state)?;
+            }
+        }
+        for name in states.keys() {
+            warn!(detector = %name, "Ignoring snapshot state of a detector that is not enabled");
+        }
+        Ok(())
+    }
+
     /// Get number of enabled detectors
     pub fn detector_count(&self) -> usize {
         self.detectors.len()
@@ -339,29 +752,31 @@ impl DetectionEngine {
 #[cfg(test)]
 mod tests {
     use super::*;
-    use llm_sentinel_core::{
-        events::{PromptInfo, ResponseInfo},
-        types::{ModelId, ServiceId},
-    };
+    use crate::baseline::BaselineKey;
+    use llm_sentinel_core::types::{ModelId, ModelVersion, ServiceId};
+    use llm_sentinel_fixtures::event;
+
+    /// Default config with a window the tests' 20 warm-up samples fill
+    fn test_config() -> EngineConfig {
+        EngineConfig {
+            baseline_window: WindowSpec::samples(20),
+            ..Default::default()
+        }
+    }
 
     fn create_test_event(latency: f64, tokens: u32, cost: f64) -> TelemetryEvent {
-        TelemetryEvent::new(
-            ServiceId::new("test"),
-            ModelId::new("gpt-4"),
-            PromptInfo {
-                text: "test".to_string(),
-                tokens: tokens / 2,
-                embedding: None,
-            },
-            ResponseInfo {
-                text: "response".to_string(),
-                tokens: tokens / 2,
-                finish_reason: "stop".to_string(),
-                embedding: None,
-            },
-            latency,
-            cost,
-        )
+        event()
+            .tokens(tokens / 2, tokens / 2)
+            .latency_ms(latency)
+            .cost_usd(cost)
+            .build()
+    }
+
+    #[test]
+    #[allow(deprecated)]
+    fn test_baseline_window_size_maps_to_window_spec() {
+        let config = EngineConfig::default().with_baseline_window_size(20);
+        assert_eq!(config.baseline_window, WindowSpec::samples(20));
     }
 
     #[tokio::test]
@@ -374,7 +789,7 @@ mod tests {
 
     #[tokio::test]
     async fn test_engine_detection() {
-        let config = EngineConfig::default();
+        let config = test_config();
         let mut engine = DetectionEngine::new(config).unwrap();
 
         // Build baselines
@@ -428,6 +843,94 @@ mod tests {
 
         let stats_after = engine.stats().await;
         assert_eq!(stats_after.events_processed, 0);
+        assert_eq!(stats_after.load.events_detected, 0);
+    }
+
+    #[tokio::test]
+    async fn test_engine_bootstrap() {
+        let config = EngineConfig {
+            continuous_learning: false,
+            ..test_config()
+        };
+        let mut engine = DetectionEngine::new(config).unwrap();
+
+        // History arrives most recent first, as storage returns it
+        let now = chrono::Utc::now();
+        let history: Vec<_> = (1..=20)
+            .map(|i| {
+                let mut event = create_test_event(100.0 + i as f64, 100, 0.01);
+                event.timestamp = now - chrono::Duration::minutes(i);
+                event
+            })
+            .collect();
+        assert_eq!(engine.bootstrap(&history).await, 20);
+
+        let key = BaselineKey::latency(ServiceId::new("test"), ModelId::new("gpt-4"));
+        assert!(engine.baseline_manager().has_valid_baseline(&key));
+
+        // Detection works immediately, without live warm-up
+        let anomaly = create_test_event(1000.0, 100, 0.01);
+        assert!(engine.detect(&anomaly).await.unwrap().is_some());
+    }
+
+    #[tokio::test]
+    async fn test_engine_manual_clock() {
+        let start = chrono::Utc::now() - chrono::Duration::days(7);
+        let clock = Clock::manual(start);
+        let mut engine = DetectionEngine::new(EngineConfig {
+            clock: clock.clone(),
+            ..test_config()
+        })
+        .unwrap();
+
+        for i in 1..=20 {
+            engine
+                .update(&create_test_event(100.0 + i as f64, 100, 0.01))
+                .await
+                .unwrap();
+            clock.advance(chrono::Duration::minutes(1));
+        }
+
+        let anomaly = engine
+            .detect(&create_test_event(1000.0, 100, 0.01))
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(anomaly.timestamp, start + chrono::Duration::minutes(20));
+    }
+
+    #[tokio::test]
+    async fn test_engine_snapshot_restore() {
+        let mut engine = DetectionEngine::new(test_config()).unwrap();
+        for i in 1..=20 {
+            engine
+                .update(&create_test_event(100.0 + i as f64, 100, 0.01 + i as f64 * 0.0001))
+                .await
+                .unwrap();
+        }
+        engine
+            .detect(&create_test_event(110.0, 100, 0.05))
+            .await
+            .unwrap();
+        let snapshot = engine.snapshot().unwrap();
+        assert!(snapshot.detectors.contains_key("cusum"));
+
+        // Snapshots survive serialization
+        let snapshot: EngineSnapshot =
+            serde_json::from_value(serde_json::to_value(&snapshot).unwrap()).unwrap();
+        let mut restored = DetectionEngine::new(test_config()).unwrap();
+        restored.restore(snapshot.clone()).unwrap();
+
+        let mut keys = restored.baseline_manager().keys();
+        keys.sort_by(|a, b| a.metric.cmp(&b.metric));
+        assert!(!keys.is_empty());
+        for key in keys {
+            assert_eq!(
+                restored.baseline_manager().get(&key).unwrap().mean,
+                engine.baseline_manager().get(&key).unwrap().mean
+            );
+        }
+        assert_eq!(restored.snapshot().unwrap().detectors, snapshot.detectors);
     }
 
     #[tokio::test]
@@ -458,4 +961,140 @@ mod tests {
         let result = DetectionEngine::new(config);
         assert!(result.is_err());
     }
+
+    #[tokio::test]
+    async fn test_engine_key_by_model_version() {
+        let config = EngineConfig {
+            enable_iqr: false,
+            enable_cusum: false,
+            key_by_model_version: true,
+            ..test_config()
+        };
+        let mut engine = DetectionEngine::new(config).unwrap();
+
+        for i in 0..20 {
+            let mut event = create_test_event(100.0 + i as f64, 50, 0.01);
+            event.model_version = Some(ModelVersion::new("gpt-4", "0613"));
+            engine.process(&event).await.unwrap();
+        }
+
+        let keys = engine.baseline_manager().keys();
+        assert!(!keys.is_empty());
+        assert!(keys.iter().all(|k| k.model.as_str() == "gpt-4@0613"));
+    }
+
+    #[tokio::test]
+    async fn test_engine_keys_by_environment() {
+        let mut engine = DetectionEngine::new(test_config()).unwrap();
+
+        // Prod builds a baseline; a noisy staging spike must not shift it
+        for i in 1..=20 {
+            let event = create_test_event(100.0 + i as f64, 100, 0.01).with_environment("prod");
+            engine.update(&event).await.unwrap();
+        }
+        for _ in 0..20 {
+            let event = create_test_event(5000.0, 100, 0.01).with_environment("staging");
+            engine.update(&event).await.unwrap();
+        }
+
+        let services: std::collections::HashSet<_> = engine
+            .baseline_manager()
+            .keys()
+            .into_iter()
+            .map(|k| k.service.to_string())
+            .collect();
+        assert!(services.contains("prod/test"));
+        assert!(services.contains("staging/test"));
+
+        let spike = create_test_event(1000.0, 100, 0.01).with_environment("prod");
+        let anomaly = engine.detect(&spike).await.unwrap().unwrap();
+        assert_eq!(anomaly.service_name.as_str(), "test");
+        assert_eq!(anomaly.environment.unwrap().as_str(), "prod");
+    }
+
+    /// Expensive detector counting the events it saw
+    struct CountingDetector(Arc<std::sync::atomic::AtomicUsize>);
+
+    #[async_trait::async_trait]
+    impl Detector for CountingDetector {
+        async fn detect(&self, _event: &TelemetryEvent) -> Result<Option<AnomalyEvent>> {
+            self.0.fetch_add(1, std::sync::atomic::Ordering::SeqCst);
+            Ok(None)
+        }
+
+        fn name(&self) -> &str {
+            "llm_check"
+        }
+
+        fn detector_type(&self) -> DetectorType {
+            DetectorType::LlmPowered
+        }
+
+        async fn reset(&mut self) -> Result<()> {
+            Ok(())
+        }
+
+        fn stats(&self) -> DetectorStats {
+            DetectorStats::empty()
+        }
+    }
+
+    #[tokio::test]
+    async fn test_engine_tiered_detection() {
+        let config = EngineConfig {
+            // The cheap stage never fires, so only the guard decides
+            zscore_config: ZScoreConfig {
+                threshold: 100.0,
+                ..Default::default()
+            },
+            enable_iqr: false,
+            enable_cusum: false,
+            tiering: Some(DetectionTieringConfig {
+                guard_sigma: 2.0,
+                max_latency_ms: None,
+                max_cost_usd: None,
+                candidate_on_error: true,
+                expensive: Vec::new(),
+            }),
+            ..test_config()
+        };
+        let mut engine = DetectionEngine::new(config).unwrap();
+        let calls = Arc::new(std::sync::atomic::AtomicUsize::new(0));
+        engine.add_detector(Box::new(CountingDetector(calls.clone())));
+        let calls = || calls.load(std::sync::atomic::Ordering::SeqCst);
+
+        // Without a baseline every event is a candidate
+        engine
+            .detect(&create_test_event(100.0, 100, 0.01))
+            .await
+            .unwrap();
+        assert_eq!(calls(), 1);
+
+        for i in 1..=20 {
+            let event = create_test_event(100.0 + i as f64, 100 + i, 0.01 + i as f64 * 0.0001);
+            engine.update(&event).await.unwrap();
+        }
+
+        // Typical events stop at the guard
+        engine
+            .detect(&create_test_event(110.0, 110, 0.011))
+            .await
+            .unwrap();
+        assert_eq!(calls(), 1);
+
+        // Outliers and failed requests reach the expensive stage
+        engine
+            .detect(&create_test_event(500.0, 110, 0.011))
+            .await
+            .unwrap();
+        assert_eq!(calls(), 2);
+        let mut failed = create_test_event(110.0, 110, 0.011);
+        failed.errors.push("timeout".to_string());
+        engine.detect(&failed).await.unwrap();
+        assert_eq!(calls(), 3);
+        let stats = engine.stats().await;
+        assert_eq!(stats.events_processed, 4);
+        assert_eq!(stats.recent.events_detected, 4);
+        assert_eq!(stats.recent.detectors["llm_check"].runs, 3);
+    }
 }
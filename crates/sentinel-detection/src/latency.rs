@@ -0,0 +1,271 @@
+//! Latency decomposition of latency anomalies.
+//!
+//! Learns each request component's usual share of latency per service and
+//! model from the latency breakdowns of telemetry events (retrieval, tool
+//! calls, the provider call). When a latency anomaly fires on an event with
+//! a breakdown, the component whose share grew most over its baseline is
+//! attached to the anomaly as root-cause evidence, so responders know
+//! whether to look at the vector store, a tool or the provider.
+
+use dashmap::DashMap;
+use llm_sentinel_core::{
+    events::{AnomalyEvent, TelemetryEvent},
+    types::{AnomalyType, ModelId, ServiceId},
+};
+use std::collections::BTreeMap;
+
+/// Weight of the newest event in the baseline shares
+const SHARE_ALPHA: f64 = 0.05;
+
+/// Events with a breakdown needed before shares are compared
+const MIN_SAMPLES: u64 = 20;
+
+/// Smallest share growth (of the request latency) attributed to a component
+const MIN_SHARE_GROWTH: f64 = 0.05;
+
+/// Baseline latency shares of one service/model
+#[derive(Debug, Clone, Default)]
+struct ComponentShares {
+    shares: BTreeMap<String, f64>,
+    samples: u64,
+}
+
+impl ComponentShares {
+    fn record(&mut self, shares: &BTreeMap<String, f64>) {
+        if self.samples == 0 {
+            self.shares = shares.clone();
+        } else {
+            for component in shares.keys() {
+                self.shares.entry(component.clone()).or_insert(0.0);
+            }
+            for (component, baseline) in self.shares.iter_mut() {
+                let share = shares.get(component).copied().unwrap_or(0.0);
+                *baseline += SHARE_ALPHA * (share - *baseline);
+            }
+        }
+        self.samples += 1;
+    }
+}
+
+/// A request component whose share of latency grew over its baseline
+#[derive(Debug, Clone, PartialEq)]
+pub struct ComponentShift {
+    /// Request component (e.g. `retrieval`)
+    pub component: String,
+    /// Latency of the component in the event (ms)
+    pub latency_ms: f64,
+    /// Share of the event's latency
+    pub share: f64,
+    /// Baseline share of latency
+    pub baseline_share: f64,
+}
+
+impl ComponentShift {
+    /// Growth of the component's share over its baseline
+    pub fn growth(&self) -> f64 {
+        self.share - self.baseline_share
+    }
+}
+
+/// Tracks per-component latency shares per service and model
+#[derive(Debug, Default)]
+pub struct LatencyBreakdownTracker {
+    states: DashMap<(ServiceId, ModelId), ComponentShares>,
+}
+
+impl LatencyBreakdownTracker {
+    /// Create an empty tracker
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Learn the latency shares of an event; events without a breakdown are
+    /// ignored
+    pub fn record(&self, event: &TelemetryEvent) {
+        let shares = event.latency_shares();
+        if shares.is_empty() {
+            return;
+        }
+        self.states
+            .entry((event.service_name.clone(), event.model.clone()))
+            .or_default()
+            .record(&shares);
+    }
+
+    /// Baseline latency shares of a service/model, once enough events with a
+    /// breakdown have been seen
+    pub fn baseline_shares(
+        &self,
+        service: &ServiceId,
+        model: &ModelId,
+    ) -> Option<BTreeMap<String, f64>> {
+        let state = self.states.get(&(service.clone(), model.clone()))?;
+        (state.samples >= MIN_SAMPLES).then(|| state.shares.clone())
+    }
+
+    /// Component of the event whose latency share grew most over its
+    /// baseline, if it grew noticeably
+    pub fn largest_shift(&self, event: &TelemetryEvent) -> Option<ComponentShift> {
+        let baseline = self.baseline_shares(&event.service_name, &event.model)?;
+        event
+            .latency_shares()
+            .into_iter()
+            .map(|(component, share)| ComponentShift {
+                latency_ms: event.latency_breakdown[&component],
+                baseline_share: baseline.get(&component).copied().unwrap_or(0.0),
+                component,
+                share,
+            })
+            .filter(|shift| shift.growth() >= MIN_SHARE_GROWTH)
+            .max_by(|a, b| a.growth().total_cmp(&b.growth()))
+    }
+
+    /// Attach the event's latency breakdown and the component whose share
+    /// grew to a latency anomaly
+    ///
+    /// Other anomalies and events without a breakdown are left untouched.
+    pub fn attribute(&self, anomaly: &mut AnomalyEvent, event: &TelemetryEvent) {
+        if anomaly.anomaly_type != AnomalyType::LatencySpike || event.latency_breakdown.is_empty() {
+            return;
+        }
+        anomaly.details.additional.insert(
+            "latency_breakdown".to_string(),
+            serde_json::json!(event.latency_breakdown),
+        );
+
+        let Some(shift) = self.largest_shift(event) else {
+            return;
+        };
+        let context = &mut anomaly.context.additional;
+        context.insert("latency_component".to_string(), shift.component.clone());
+        context.insert(
+            "latency_component_share".to_string(),
+            format!("{:.3}", shift.share),
+        );
+        context.insert(
+            "latency_component_baseline_share".to_string(),
+            format!("{:.3}", shift.baseline_share),
+        );
+
+        let evidence = format!(
+            "{} share of latency grew from {:.0}% to {:.0}% ({:.0} ms)",
+            shift.component,
+            shift.baseline_share * 100.0,
+            shift.share * 100.0,
+            shift.latency_ms
+        );
+        anomaly.root_cause = Some(match anomaly.root_cause.take() {
+            Some(root_cause) => format!("{}; {}", root_cause, evidence),
+            None => evidence,
+        });
+        anomaly
+            .remediation
+            .insert(0, component_remediation(&shift.component));
+    }
+
+    /// Clear all learned shares
+    pub fn clear(&self) {
+        self.states.clear();
+    }
+}
+
+fn component_remediation(component: &str) -> String {
+    match component {
+        "retrieval" => {
+            "Check the retrieval backend (vector store, reranker) for slow queries or larger result sets"
+                .to_string()
+        }
+        "tool" => "Check the slowed tool calls for timeouts or upstream degradation".to_string(),
+        "provider" => {
+            "Check the model provider's status; fail over to another region or model if it is degraded"
+                .to_string()
+        }
+        other => format!("Investigate the {} step of the request", other),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use llm_sentinel_fixtures::{anomaly, event};
+
+    fn create_test_event(retrieval_ms: f64, provider_ms: f64) -> TelemetryEvent {
+        event()
+            .service("rag-api")
+            .latency_ms(retrieval_ms + provider_ms)
+            .latency_component("retrieval", retrieval_ms)
+            .latency_component("provider", provider_ms)
+            .build()
+    }
+
+    fn create_anomaly(anomaly_type: AnomalyType) -> AnomalyEvent {
+        anomaly()
+            .anomaly_type(anomaly_type)
+            .service("rag-api")
+            .confidence(0.95)
+            .value(2000.0)
+            .baseline(500.0)
+            .deviation_sigma(Some(5.0))
+            .time_window("last_1000_samples")
+            .sample_count(1000)
+            .build()
+            .with_root_cause("Latency 2000.0ms is 5.0σ above baseline")
+    }
+
+    #[test]
+    fn test_component_share_growth_attributed() {
+        let tracker = LatencyBreakdownTracker::new();
+        let slow = create_test_event(1500.0, 500.0);
+        assert!(tracker.largest_shift(&slow).is_none());
+
+        for _ in 0..MIN_SAMPLES {
+            tracker.record(&create_test_event(100.0, 400.0));
+        }
+        let baseline = tracker
+            .baseline_shares(&slow.service_name, &slow.model)
+            .unwrap();
+        assert!((baseline["retrieval"] - 0.2).abs() < 1e-9);
+
+        let shift = tracker.largest_shift(&slow).unwrap();
+        assert_eq!(shift.component, "retrieval");
+        assert_eq!(shift.latency_ms, 1500.0);
+        assert_eq!(shift.share, 0.75);
+
+        let mut anomaly = create_anomaly(AnomalyType::LatencySpike);
+        tracker.attribute(&mut anomaly, &slow);
+        assert_eq!(anomaly.context.additional["latency_component"], "retrieval");
+        assert_eq!(
+            anomaly.context.additional["latency_component_baseline_share"],
+            "0.200"
+        );
+        assert_eq!(
+            anomaly.details.additional["latency_breakdown"]["retrieval"],
+            1500.0
+        );
+        assert!(anomaly
+            .root_cause
+            .unwrap()
+            .contains("retrieval share of latency grew from 20% to 75%"));
+        assert!(anomaly.remediation[0].contains("retrieval backend"));
+
+        // Everything slowed down evenly: no component stands out
+        let mut anomaly = create_anomaly(AnomalyType::LatencySpike);
+        tracker.attribute(&mut anomaly, &create_test_event(400.0, 1600.0));
+        assert!(anomaly.details.additional.contains_key("latency_breakdown"));
+        assert!(!anomaly.context.additional.contains_key("latency_component"));
+    }
+
+    #[test]
+    fn test_other_anomalies_untouched() {
+        let tracker = LatencyBreakdownTracker::new();
+        for _ in 0..MIN_SAMPLES {
+            tracker.record(&create_test_event(100.0, 400.0));
+        }
+
+        let mut anomaly = create_anomaly(AnomalyType::CostAnomaly);
+        tracker.attribute(&mut anomaly, &create_test_event(1500.0, 500.0));
+        assert!(anomaly.details.additional.is_empty());
+        assert!(anomaly.context.additional.is_empty());
+    }
+}
@@ -4,16 +4,46 @@
 //!
 //! This crate provides:
 //! - Statistical detection methods (Z-Score, IQR, CUSUM, MAD)
+//! - Bayesian online change point detection (BOCPD)
+//! - Regime change detection from short- vs long-window baselines
+//! - Distribution drift detection (PSI, KL divergence)
+//! - Content-filter surge and token-efficiency regression detection
+//! - Context window overflow detection against per-model limits
+//! - Cost residual detection against a robust cost-on-tokens fit
 //! - Baseline calculation and management
 //! - Detection engine orchestration
+//! - Model version tracking and drift attribution
+//! - Latency decomposition of latency anomalies by request component
 //! - Multi-detector support with confidence scoring
+//! - SLO compliance and error-budget burn-rate tracking
+//! - Provider and model availability tracking
+//! - Recovery monitoring of open anomalies
+//! - Telemetry silence detection for producers that stop reporting
+//! - Trace-level evaluation of multi-span LLM chains
+//! - Adaptive load shedding when detection falls behind
+//! - Rolling detection load: skipped events, detector errors, queue waits
+//! - Re-detection of stored telemetry with new detector settings
+//! - Per-detector quality reports from responder feedback
+//!
+//! The runtime-free statistics underneath live in `llm-sentinel-stats`.
 
 #![warn(missing_debug_implementations, rust_2018_idioms, unreachable_pub)]
 
+pub mod availability;
 pub mod baseline;
 pub mod detectors;
 pub mod engine;
+pub mod latency;
+pub mod load;
+pub mod quality;
+pub mod recovery;
+pub mod redetect;
+pub mod shedding;
+pub mod silence;
+pub mod slo;
 pub mod stats;
+pub mod traces;
+pub mod versions;
 
 use async_trait::async_trait;
 use llm_sentinel_core::{
@@ -45,6 +75,20 @@ pub trait Detector: Send + Sync {
     /// Reset detector state
     async fn reset(&mut self) -> Result<()>;
 
+    /// Learned state to carry over to another process
+    ///
+    /// Detectors without state of their own beyond the shared baselines
+    /// return `None`.
+    fn export_state(&self) -> Result<Option<serde_json::Value>> {
+        Ok(None)
+    }
+
+    /// Restore state returned by [`export_state`](Self::export_state)
+    fn import_state(&mut self, state: serde_json::Value) -> Result<()> {
+        let _ = state;
+        Ok(())
+    }
+
     /// Get detector statistics
     fn stats(&self) -> DetectorStats;
 }
@@ -58,6 +102,8 @@ pub enum DetectorType {
     MachineLearning,
     /// LLM-powered detection
     LlmPowered,
+    /// Externally supplied plugin (e.g. WASM)
+    Plugin,
 }
 
 /// Detector statistics
@@ -102,10 +148,34 @@ impl DetectorStats {
 
 /// Re-export commonly used types
 pub mod prelude {
+    pub use crate::availability::{AvailabilityTracker, AvailabilityTrackerConfig};
     pub use crate::baseline::{Baseline, BaselineManager};
     pub use crate::detectors::{
-        cusum::CusumDetector, iqr::IqrDetector, mad::MadDetector, zscore::ZScoreDetector,
+        bocpd::{BocpdConfig, BocpdDetector},
+        content_filter::{ContentFilterConfig, ContentFilterDetector},
+        context_overflow::{ContextOverflowConfig, ContextOverflowDetector},
+        cost_residual::{CostResidualConfig, CostResidualDetector},
+        cusum::CusumDetector,
+        iqr::IqrDetector,
+        kl_divergence::{KlDivergenceConfig, KlDivergenceDetector},
+        mad::MadDetector,
+        multi_window::{MultiWindowConfig, MultiWindowDetector},
+        psi::{PsiConfig, PsiDetector},
+        token_efficiency::{TokenEfficiencyConfig, TokenEfficiencyDetector},
+        zscore::ZScoreDetector,
+    };
+    pub use crate::engine::{DetectionEngine, EngineConfig, EngineSnapshot, EngineStats};
+    pub use crate::latency::{ComponentShift, LatencyBreakdownTracker};
+    pub use crate::load::{DetectionLoad, LoadReport};
+    pub use crate::quality::{quality_report, DetectorQuality};
+    pub use crate::recovery::{AnomalyRecovery, RecoveryMonitor, RecoveryMonitorConfig};
+    pub use crate::redetect::{
+        redetect, DetectorSettings, RedetectJob, RedetectRequest, RedetectStatus,
     };
-    pub use crate::engine::{DetectionEngine, EngineConfig};
+    pub use crate::shedding::{AdaptiveSampler, SheddingStatus};
+    pub use crate::silence::{SilenceMonitor, SilenceMonitorConfig};
+    pub use crate::slo::{BurnRatePolicy, SloDefinition, SloObjective, SloStatus, SloTracker};
+    pub use crate::traces::{TraceAggregator, TraceAggregatorConfig};
+    pub use crate::versions::ModelVersionTracker;
     pub use crate::{Detector, DetectorStats, DetectorType};
 }
@@ -0,0 +1,445 @@
+//! Detection load accounting.
+//!
+//! Lifetime counters say how much work detection has done since startup,
+//! but not whether it is struggling right now. The load ledger keeps its
+//! counts twice: since startup, and in 10-second buckets reported over the
+//! last five minutes. It counts events detected, events kept out of
+//! detection by load shedding (sampled out) or memory pressure (shed),
+//! events over their processing deadline, runs and errors per detector, and
+//! how long events waited in the detection queue. Queue waits are kept in a
+//! log-scale histogram, so percentiles take constant memory and are
+//! accurate to within one bucket (10%).
+
+use chrono::{DateTime, TimeZone, Utc};
+use llm_sentinel_core::clock::Clock;
+use serde::{Deserialize, Serialize};
+use std::{
+    collections::{BTreeMap, VecDeque},
+    sync::Mutex,
+    time::Duration,
+};
+
+/// Length of the rolling view (seconds)
+pub const RECENT_WINDOW_SECS: i64 = 300;
+
+/// Width of one rolling bucket (seconds)
+const BUCKET_SECS: i64 = 10;
+
+/// Upper bound of the first queue wait bucket (ms)
+const WAIT_BASE_MS: f64 = 0.1;
+
+/// Ratio between the upper bounds of consecutive queue wait buckets
+const WAIT_GROWTH: f64 = 1.1;
+
+/// Queue wait buckets; the last one (from about 6 minutes) also holds
+/// longer waits
+const WAIT_BUCKETS: usize = 160;
+
+/// Runs and errors of one detector
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct DetectorLoad {
+    /// Events the detector ran on
+    pub runs: u64,
+    /// Runs that returned an error
+    pub errors: u64,
+    /// Share of runs that returned an error
+    pub error_rate: f64,
+}
+
+/// Distribution of the time events waited in the detection queue
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct QueueWait {
+    /// Events that waited
+    pub count: u64,
+    /// Mean wait (ms)
+    pub mean_ms: f64,
+    /// Median wait (ms)
+    pub p50_ms: f64,
+    /// 95th percentile wait (ms)
+    pub p95_ms: f64,
+    /// 99th percentile wait (ms)
+    pub p99_ms: f64,
+    /// Longest wait (ms)
+    pub max_ms: f64,
+}
+
+/// Detection load over a period
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct LoadReport {
+    /// Seconds the report covers
+    pub window_secs: f64,
+    /// Events run through detection
+    pub events_detected: u64,
+    /// Events with an anomaly
+    pub anomalies_detected: u64,
+    /// Events detected per second
+    pub events_per_sec: f64,
+    /// Events skipped by load shedding
+    pub sampled_out: u64,
+    /// Events skipped under memory pressure
+    pub shed: u64,
+    /// Share of events skipped by load shedding or memory pressure
+    pub skipped_rate: f64,
+    /// Events over `detection.event_deadline_ms`
+    pub timed_out: u64,
+    /// Runs and errors by detector name
+    pub detectors: BTreeMap<String, DetectorLoad>,
+    /// Time events waited in the detection queue
+    pub queue_wait: QueueWait,
+}
+
+/// Log-scale histogram of queue waits
+#[derive(Debug, Clone)]
+struct WaitHistogram {
+    buckets: Vec<u64>,
+    count: u64,
+    total_ms: f64,
+    max_ms: f64,
+}
+
+impl Default for WaitHistogram {
+    fn default() -> Self {
+        Self {
+            buckets: vec![0; WAIT_BUCKETS],
+            count: 0,
+            total_ms: 0.0,
+            max_ms: 0.0,
+        }
+    }
+}
+
+impl WaitHistogram {
+    fn record(&mut self, ms: f64) {
+        let index = if ms <= WAIT_BASE_MS {
+            0
+        } else {
+            ((ms / WAIT_BASE_MS).ln() / WAIT_GROWTH.ln()).ceil() as usize
+        };
+        self.buckets[index.min(WAIT_BUCKETS - 1)] += 1;
+        self.count += 1;
+        self.total_ms += ms;
+        self.max_ms = self.max_ms.max(ms);
+    }
+
+    fn merge(&mut self, other: &Self) {
+        for (bucket, count) in self.buckets.iter_mut().zip(&other.buckets) {
+            *bucket += count;
+        }
+        self.count += other.count;
+        self.total_ms += other.total_ms;
+        self.max_ms = self.max_ms.max(other.max_ms);
+    }
+
+    /// Upper bound of the bucket holding quantile `q`, capped at the
+    /// longest wait
+    fn quantile(&self, q: f64) -> f64 {
+        let rank = ((q * self.count as f64).ceil() as u64).max(1);
+        let mut seen = 0;
+        for (index, count) in self.buckets.iter().enumerate() {
+            seen += count;
+            if seen >= rank {
+                return (WAIT_BASE_MS * WAIT_GROWTH.powi(index as i32)).min(self.max_ms);
+            }
+        }
+        self.max_ms
+    }
+
+    fn summary(&self) -> QueueWait {
+        if self.count == 0 {
+            return QueueWait::default();
+        }
+        QueueWait {
+            count: self.count,
+            mean_ms: self.total_ms / self.count as f64,
+            p50_ms: self.quantile(0.5),
+            p95_ms: self.quantile(0.95),
+            p99_ms: self.quantile(0.99),
+            max_ms: self.max_ms,
+        }
+    }
+}
+
+/// Counts over one period
+#[derive(Debug, Clone, Default)]
+struct Counters {
+    events: u64,
+    anomalies: u64,
+    sampled_out: u64,
+    shed: u64,
+    timed_out: u64,
+    detectors: BTreeMap<String, DetectorLoad>,
+    queue_wait: WaitHistogram,
+}
+
+impl Counters {
+    fn merge(&mut self, other: &Self) {
+        self.events += other.events;
+        self.anomalies += other.anomalies;
+        self.sampled_out += other.sampled_out;
+        self.shed += other.shed;
+        self.timed_out += other.timed_out;
+        for (name, load) in &other.detectors {
+            let total = self.detectors.entry(name.clone()).or_default();
+            total.runs += load.runs;
+            total.errors += load.errors;
+        }
+        self.queue_wait.merge(&other.queue_wait);
+    }
+
+    fn report(&self, window_secs: f64) -> LoadReport {
+        let skipped = self.sampled_out + self.shed;
+        let arrived = self.events + skipped;
+        LoadReport {
+            window_secs,
+            events_detected: self.events,
+            anomalies_detected: self.anomalies,
+            events_per_sec: if window_secs > 0.0 {
+                self.events as f64 / window_secs
+            } else {
+                0.0
+            },
+            sampled_out: self.sampled_out,
+            shed: self.shed,
+            skipped_rate: if arrived > 0 {
+                skipped as f64 / arrived as f64
+            } else {
+                0.0
+            },
+            timed_out: self.timed_out,
+            detectors: self
+                .detectors
+                .iter()
+                .map(|(name, load)| {
+                    let error_rate = if load.runs > 0 {
+                        load.errors as f64 / load.runs as f64
+                    } else {
+                        0.0
+                    };
+                    (
+                        name.clone(),
+                        DetectorLoad {
+                            error_rate,
+                            ..load.clone()
+                        },
+                    )
+                })
+                .collect(),
+            queue_wait: self.queue_wait.summary(),
+        }
+    }
+}
+
+#[derive(Debug)]
+struct LoadState {
+    since: DateTime<Utc>,
+    lifetime: Counters,
+    /// Rolling buckets by start time, oldest first
+    buckets: VecDeque<(DateTime<Utc>, Counters)>,
+}
+
+impl LoadState {
+    fn new(since: DateTime<Utc>) -> Self {
+        Self {
+            since,
+            lifetime: Counters::default(),
+            buckets: VecDeque::new(),
+        }
+    }
+
+    /// Start of the oldest bucket in the rolling view
+    fn window_start(now: DateTime<Utc>) -> DateTime<Utc> {
+        bucket_start(now) + chrono::Duration::seconds(BUCKET_SECS - RECENT_WINDOW_SECS)
+    }
+
+    fn expire(&mut self, now: DateTime<Utc>) {
+        let start = Self::window_start(now);
+        while self.buckets.front().is_some_and(|(at, _)| *at < start) {
+            self.buckets.pop_front();
+        }
+    }
+
+    fn current(&mut self, now: DateTime<Utc>) -> &mut Counters {
+        self.expire(now);
+        let start = bucket_start(now);
+        if self.buckets.back().map(|(at, _)| *at) != Some(start) {
+            self.buckets.push_back((start, Counters::default()));
+        }
+        &mut self.buckets.back_mut().expect("bucket pushed").1
+    }
+}
+
+fn bucket_start(at: DateTime<Utc>) -> DateTime<Utc> {
+    let secs = at.timestamp();
+    Utc.timestamp_opt(secs - secs.rem_euclid(BUCKET_SECS), 0)
+        .single()
+        .unwrap_or(at)
+}
+
+fn seconds_between(from: DateTime<Utc>, to: DateTime<Utc>) -> f64 {
+    ((to - from).num_milliseconds() as f64 / 1000.0).max(0.0)
+}
+
+/// Detection load since startup and over the last five minutes
+#[derive(Debug)]
+pub struct DetectionLoad {
+    clock: Clock,
+    state: Mutex<LoadState>,
+}
+
+impl Default for DetectionLoad {
+    fn default() -> Self {
+        Self::new(Clock::system())
+    }
+}
+
+impl DetectionLoad {
+    /// Create an empty ledger reading time from `clock`
+    pub fn new(clock: Clock) -> Self {
+        let since = clock.now();
+        Self {
+            clock,
+            state: Mutex::new(LoadState::new(since)),
+        }
+    }
+
+    fn record(&self, update: impl Fn(&mut Counters)) {
+        let now = self.clock.now();
+        let mut state = self.state.lock().unwrap();
+        update(&mut state.lifetime);
+        update(state.current(now));
+    }
+
+    /// Count an event run through detection
+    pub fn record_event(&self, anomaly: bool) {
+        self.record(|counters| {
+            counters.events += 1;
+            if anomaly {
+                counters.anomalies += 1;
+            }
+        });
+    }
+
+    /// Count a detector run, and whether it returned an error
+    pub fn record_detector(&self, name: &str, error: bool) {
+        self.record(|counters| {
+            let load = match counters.detectors.get_mut(name) {
+                Some(load) => load,
+                None => counters.detectors.entry(name.to_string()).or_default(),
+            };
+            load.runs += 1;
+            if error {
+                load.errors += 1;
+            }
+        });
+    }
+
+    /// Count an event skipped by load shedding
+    pub fn record_sampled_out(&self) {
+        self.record(|counters| counters.sampled_out += 1);
+    }
+
+    /// Count an event skipped under memory pressure
+    pub fn record_shed(&self) {
+        self.record(|counters| counters.shed += 1);
+    }
+
+    /// Count an event over its processing deadline
+    pub fn record_timed_out(&self) {
+        self.record(|counters| counters.timed_out += 1);
+    }
+
+    /// Record how long an event waited in the detection queue
+    pub fn record_queue_wait(&self, wait: Duration) {
+        let ms = wait.as_secs_f64() * 1000.0;
+        self.record(|counters| counters.queue_wait.record(ms));
+    }
+
+    /// Load since startup (or the last [`clear`](Self::clear))
+    pub fn lifetime(&self) -> LoadReport {
+        let now = self.clock.now();
+        let state = self.state.lock().unwrap();
+        state.lifetime.report(seconds_between(state.since, now))
+    }
+
+    /// Load over the last five minutes
+    pub fn recent(&self) -> LoadReport {
+        let now = self.clock.now();
+        let mut state = self.state.lock().unwrap();
+        state.expire(now);
+        let mut counters = Counters::default();
+        for (_, bucket) in &state.buckets {
+            counters.merge(bucket);
+        }
+        let start = LoadState::window_start(now).max(state.since);
+        counters.report(seconds_between(start, now))
+    }
+
+    /// Forget all counts
+    pub fn clear(&self) {
+        *self.state.lock().unwrap() = LoadState::new(self.clock.now());
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_recent_view_rolls_over() {
+        let clock = Clock::manual(Utc.with_ymd_and_hms(2024, 1, 1, 12, 0, 0).unwrap());
+        let load = DetectionLoad::new(clock.clone());
+
+        for i in 0..10 {
+            load.record_event(i == 0);
+            load.record_detector("zscore", i % 5 == 0);
+        }
+        load.record_sampled_out();
+        load.record_shed();
+        load.record_timed_out();
+
+        clock.advance(chrono::Duration::seconds(60));
+        let recent = load.recent();
+        assert_eq!(recent.window_secs, 60.0);
+        assert_eq!(recent.events_detected, 10);
+        assert_eq!(recent.anomalies_detected, 1);
+        assert_eq!(recent.skipped_rate, 2.0 / 12.0);
+        assert_eq!(recent.timed_out, 1);
+        assert_eq!(recent.detectors["zscore"].runs, 10);
+        assert_eq!(recent.detectors["zscore"].error_rate, 0.2);
+
+        // Five minutes later only new events are recent
+        clock.advance(chrono::Duration::seconds(RECENT_WINDOW_SECS));
+        load.record_event(false);
+        let recent = load.recent();
+        assert_eq!(recent.events_detected, 1);
+        assert!(recent.detectors.is_empty());
+        assert!(recent.window_secs <= RECENT_WINDOW_SECS as f64);
+
+        let lifetime = load.lifetime();
+        assert_eq!(lifetime.events_detected, 11);
+        assert_eq!(lifetime.shed, 1);
+        assert_eq!(lifetime.window_secs, 360.0);
+
+        load.clear();
+        assert_eq!(load.lifetime(), LoadReport::default());
+    }
+
+    #[test]
+    fn test_queue_wait_percentiles() {
+        let load = DetectionLoad::new(Clock::manual(Utc::now()));
+        assert_eq!(load.recent().queue_wait, QueueWait::default());
+
+        for ms in 1..=100 {
+            load.record_queue_wait(Duration::from_millis(ms));
+        }
+        let wait = load.recent().queue_wait;
+        assert_eq!(wait.count, 100);
+        assert!((wait.mean_ms - 50.5).abs() < 1e-9);
+        assert_eq!(wait.max_ms, 100.0);
+        // Percentiles are accurate to one bucket
+        assert!((50.0..=55.0).contains(&wait.p50_ms), "{}", wait.p50_ms);
+        assert!((95.0..=100.0).contains(&wait.p95_ms), "{}", wait.p95_ms);
+        assert!((99.0..=100.0).contains(&wait.p99_ms), "{}", wait.p99_ms);
+        assert_eq!(load.lifetime().queue_wait, wait);
+    }
+}
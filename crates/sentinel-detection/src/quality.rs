@@ -0,0 +1,183 @@
+//! Per-detector quality report.
+//!
+//! Responders label anomalies through their lifecycle state: acknowledged
+//! and resolved anomalies count as true positives, suppressed ones as false
+//! positives, and open or expired ones stay unlabeled. [`quality_report`]
+//! combines those labels with alert volume, deduplication and the running
+//! detectors' statistics into one row per detector, with precision proxied
+//! as `true / (true + false)` positives.
+
+use crate::DetectorStats;
+use llm_sentinel_core::{events::AnomalyEvent, lifecycle::AnomalyState, simulation};
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap};
+
+/// Quality of one detector's anomalies over a window
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct DetectorQuality {
+    /// Detector name, as detectors are enabled by
+    pub detector: String,
+    /// Anomalies stored in the window
+    pub anomalies: u64,
+    /// Anomalies acknowledged or resolved
+    pub true_positives: u64,
+    /// Anomalies suppressed
+    pub false_positives: u64,
+    /// Anomalies open or expired without a response
+    pub unlabeled: u64,
+    /// Share of labeled anomalies that were true positives (`None` without
+    /// labels)
+    pub precision: Option<f64>,
+    /// Mean confidence of all anomalies
+    pub mean_confidence: Option<f64>,
+    /// Mean confidence of true positives
+    pub mean_confidence_true_positives: Option<f64>,
+    /// Mean confidence of false positives
+    pub mean_confidence_false_positives: Option<f64>,
+    /// Alerts suppressed in open deduplication windows
+    pub deduplicated: u64,
+    /// Events the running detector processed since startup (`None` when it
+    /// is not enabled)
+    pub events_processed: Option<u64>,
+    /// Share of processed events the running detector flagged
+    pub detection_rate: Option<f64>,
+}
+
+#[derive(Default)]
+struct Confidence {
+    sum: f64,
+    count: u64,
+}
+
+impl Confidence {
+    fn add(&mut self, confidence: f64) {
+        self.sum += confidence;
+        self.count += 1;
+    }
+
+    fn mean(&self) -> Option<f64> {
+        (self.count > 0).then(|| self.sum / self.count as f64)
+    }
+}
+
+#[derive(Default)]
+struct Tally {
+    all: Confidence,
+    true_positives: Confidence,
+    false_positives: Confidence,
+}
+
+/// Build the per-detector quality report, ordered by detector name
+///
+/// `anomalies` are those stored in the reported window; test anomalies are
+/// left out. `detector_stats` come from the running engine and
+/// `deduplicated` counts suppressed alerts by detector. Detectors appear
+/// when any of the three mentions them.
+pub fn quality_report(
+    anomalies: &[AnomalyEvent],
+    detector_stats: &[(String, DetectorStats)],
+    deduplicated: &HashMap<String, u64>,
+) -> Vec<DetectorQuality> {
+    let mut tallies: BTreeMap<String, Tally> = BTreeMap::new();
+    for anomaly in anomalies.iter().filter(|a| !simulation::is_test(a)) {
+        let tally = tallies
+            .entry(anomaly.detection_method.detector())
+            .or_default();
+        tally.all.add(anomaly.confidence);
+        match anomaly.state {
+            AnomalyState::Acknowledged | AnomalyState::Resolved => {
+                tally.true_positives.add(anomaly.confidence)
+            }
+            AnomalyState::Suppressed => tally.false_positives.add(anomaly.confidence),
+            AnomalyState::Open | AnomalyState::Expired => {}
+        }
+    }
+    for (name, _) in detector_stats {
+        tallies.entry(name.clone()).or_default();
+    }
+    for name in deduplicated.keys() {
+        tallies.entry(name.clone()).or_default();
+    }
+
+    tallies
+        .into_iter()
+        .map(|(detector, tally)| {
+            let stats = detector_stats
+                .iter()
+                .find(|(name, _)| *name == detector)
+                .map(|(_, stats)| stats);
+            let (true_positives, false_positives) =
+                (tally.true_positives.count, tally.false_positives.count);
+            let labeled = true_positives + false_positives;
+            DetectorQuality {
+                anomalies: tally.all.count,
+                true_positives,
+                false_positives,
+                unlabeled: tally.all.count - labeled,
+                precision: (labeled > 0).then(|| true_positives as f64 / labeled as f64),
+                mean_confidence: tally.all.mean(),
+                mean_confidence_true_positives: tally.true_positives.mean(),
+                mean_confidence_false_positives: tally.false_positives.mean(),
+                deduplicated: deduplicated.get(&detector).copied().unwrap_or(0),
+                events_processed: stats.map(|s| s.events_processed),
+                detection_rate: stats.map(|s| s.detection_rate),
+                detector,
+            }
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use llm_sentinel_core::types::DetectionMethod;
+
+    fn anomaly(method: DetectionMethod, confidence: f64, state: AnomalyState) -> AnomalyEvent {
+        let mut anomaly = llm_sentinel_fixtures::anomaly()
+            .service("chat")
+            .method(method)
+            .confidence(confidence)
+            .build();
+        anomaly.state = state;
+        anomaly
+    }
+
+    #[test]
+    fn test_quality_report() {
+        let anomalies = vec![
+            anomaly(DetectionMethod::ZScore, 0.9, AnomalyState::Resolved),
+            anomaly(DetectionMethod::ZScore, 0.8, AnomalyState::Acknowledged),
+            anomaly(DetectionMethod::ZScore, 0.5, AnomalyState::Suppressed),
+            anomaly(DetectionMethod::ZScore, 0.6, AnomalyState::Open),
+            anomaly(DetectionMethod::Iqr, 0.7, AnomalyState::Open),
+        ];
+        let mut stats = DetectorStats::empty();
+        stats.update(true, Some(0.9));
+        stats.update(false, None);
+        let detector_stats = vec![
+            ("zscore".to_string(), stats),
+            ("cusum".to_string(), DetectorStats::empty()),
+        ];
+        let deduplicated = HashMap::from([("zscore".to_string(), 3)]);
+
+        let report = quality_report(&anomalies, &detector_stats, &deduplicated);
+        let names: Vec<&str> = report.iter().map(|q| q.detector.as_str()).collect();
+        assert_eq!(names, ["cusum", "iqr", "zscore"]);
+
+        let zscore = &report[2];
+        assert_eq!(zscore.anomalies, 4);
+        assert_eq!((zscore.true_positives, zscore.false_positives), (2, 1));
+        assert_eq!(zscore.unlabeled, 1);
+        assert!((zscore.precision.unwrap() - 2.0 / 3.0).abs() < 1e-9);
+        assert!((zscore.mean_confidence_true_positives.unwrap() - 0.85).abs() < 1e-9);
+        assert_eq!(zscore.mean_confidence_false_positives, Some(0.5));
+        assert_eq!(zscore.deduplicated, 3);
+        assert_eq!(zscore.events_processed, Some(2));
+        assert_eq!(zscore.detection_rate, Some(0.5));
+
+        // Unlabeled anomalies give no precision; idle detectors are listed
+        assert_eq!(report[1].precision, None);
+        assert_eq!(report[0].anomalies, 0);
+        assert_eq!(report[0].events_processed, Some(0));
+    }
+}
@@ -0,0 +1,357 @@
+//! Recovery monitoring for open anomalies.
+//!
+//! The monitor watches the metric behind every open anomaly it is given.
+//! Each telemetry event for the anomaly's service and model is checked
+//! against the band the detector considered normal: the baseline plus or
+//! minus the distance to the detection threshold. Once the metric has stayed
+//! inside that band for the recovery period, the anomaly is reported as
+//! recovered so the caller can resolve it. Any sample outside the band
+//! restarts the period.
+//!
+//! Only metrics carried by individual events (`latency_ms`, `total_tokens`,
+//! `prompt_tokens`, `response_tokens` and `cost_usd`) are watched; anomalies
+//! on aggregate metrics such as error rates are left to responders.
+
+use chrono::{DateTime, Duration, Utc};
+use llm_sentinel_core::{
+    events::{AnomalyEvent, TelemetryEvent},
+    types::{ModelId, ServiceId},
+    Error, Result,
+};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::sync::Mutex;
+use tracing::debug;
+use uuid::Uuid;
+
+/// Metrics that can be read from a single event
+pub const WATCHED_METRICS: &[&str] = &[
+    "latency_ms",
+    "total_tokens",
+    "prompt_tokens",
+    "response_tokens",
+    "cost_usd",
+];
+
+/// Recovery monitor configuration
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RecoveryMonitorConfig {
+    /// How long the metric must stay within baseline (seconds)
+    pub recovery_secs: u64,
+    /// Minimum in-baseline samples before an anomaly counts as recovered
+    pub min_samples: u64,
+}
+
+impl Default for RecoveryMonitorConfig {
+    fn default() -> Self {
+        Self {
+            recovery_secs: 300,
+            min_samples: 5,
+        }
+    }
+}
+
+impl RecoveryMonitorConfig {
+    /// Validate the configuration
+    pub fn validate(&self) -> Result<()> {
+        if self.recovery_secs == 0 {
+            return Err(Error::config("Recovery recovery_secs must be positive"));
+        }
+        if self.min_samples == 0 {
+            return Err(Error::config("Recovery min_samples must be positive"));
+        }
+        Ok(())
+    }
+}
+
+/// Value of a watchable metric in a single event
+pub fn event_metric(event: &TelemetryEvent, metric: &str) -> Option<f64> {
+    match metric {
+        "latency_ms" => Some(event.latency_ms),
+        "total_tokens" => Some(f64::from(event.total_tokens())),
+        "prompt_tokens" => Some(f64::from(event.prompt.tokens)),
+        "response_tokens" => Some(f64::from(event.response.tokens)),
+        "cost_usd" => Some(event.cost_usd),
+        _ => None,
+    }
+}
+
+/// An anomaly whose metric returned to baseline
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct AnomalyRecovery {
+    /// Recovered anomaly
+    pub alert_id: Uuid,
+    /// Service of the anomaly
+    pub service_name: ServiceId,
+    /// Model of the anomaly
+    pub model: ModelId,
+    /// Watched metric
+    pub metric: String,
+    /// Baseline value at detection
+    pub baseline: f64,
+    /// Most recent metric value
+    pub last_value: f64,
+    /// In-baseline samples seen during the recovery period
+    pub samples: u64,
+    /// When the metric returned to baseline
+    pub since: DateTime<Utc>,
+    /// When the recovery period completed
+    pub at: DateTime<Utc>,
+}
+
+impl AnomalyRecovery {
+    /// Human-readable reason for the resolution
+    pub fn reason(&self) -> String {
+        format!(
+            "{} within baseline {:.2} for {}s ({} samples, last {:.2})",
+            self.metric,
+            self.baseline,
+            (self.at - self.since).num_seconds(),
+            self.samples,
+            self.last_value
+        )
+    }
+}
+
+/// Open anomaly being watched
+#[derive(Debug, Clone)]
+struct Watch {
+    alert_id: Uuid,
+    metric: String,
+    baseline: f64,
+    /// Largest distance from the baseline still considered normal
+    tolerance: f64,
+    since: Option<DateTime<Utc>>,
+    samples: u64,
+}
+
+impl Watch {
+    fn within_baseline(&self, value: f64) -> bool {
+        (value - self.baseline).abs() <= self.tolerance
+    }
+}
+
+/// Watches open anomalies and reports those whose metric recovered
+#[derive(Debug)]
+pub struct RecoveryMonitor {
+    config: RecoveryMonitorConfig,
+    watches: Mutex<HashMap<(ServiceId, ModelId), Vec<Watch>>>,
+}
+
+impl RecoveryMonitor {
+    /// Create a new monitor
+    pub fn new(config: RecoveryMonitorConfig) -> Result<Self> {
+        config.validate()?;
+        Ok(Self {
+            config,
+            watches: Mutex::new(HashMap::new()),
+        })
+    }
+
+    /// Get the configuration
+    pub fn config(&self) -> &RecoveryMonitorConfig {
+        &self.config
+    }
+
+    /// Start watching an anomaly
+    ///
+    /// Returns `false` for anomalies that are no longer active or whose
+    /// metric cannot be read from individual events.
+    pub fn watch(&self, anomaly: &AnomalyEvent) -> bool {
+        let details = &anomaly.details;
+        let tolerance = (details.threshold - details.baseline).abs();
+        if !anomaly.state.is_active()
+            || !WATCHED_METRICS.contains(&details.metric.as_str())
+            || !tolerance.is_finite()
+        {
+            return false;
+        }
+
+        let key = (anomaly.service_name.clone(), anomaly.model.clone());
+        let mut watches = self.watches.lock().unwrap();
+        let entry = watches.entry(key).or_default();
+        if entry.iter().any(|w| w.alert_id == anomaly.alert_id) {
+            return true;
+        }
+        entry.push(Watch {
+            alert_id: anomaly.alert_id,
+            metric: details.metric.clone(),
+            baseline: details.baseline,
+            tolerance,
+            since: None,
+            samples: 0,
+        });
+        ::metrics::gauge!("sentinel_recovery_watched_anomalies").increment(1.0);
+        true
+    }
+
+    /// Stop watching an anomaly, e.g. after a responder resolved it
+    pub fn forget(&self, alert_id: Uuid) {
+        let mut watches = self.watches.lock().unwrap();
+        let mut removed = 0;
+        watches.retain(|_, entry| {
+            let before = entry.len();
+            entry.retain(|w| w.alert_id != alert_id);
+            removed += before - entry.len();
+            !entry.is_empty()
+        });
+        ::metrics::gauge!("sentinel_recovery_watched_anomalies").decrement(removed as f64);
+    }
+
+    /// Number of anomalies being watched
+    pub fn watched(&self) -> usize {
+        self.watches.lock().unwrap().values().map(Vec::len).sum()
+    }
+
+    /// Check an event against the anomalies of its service and model and
+    /// return those that recovered
+    ///
+    /// Recovered anomalies are no longer watched. The recovery period is
+    /// measured in event time.
+    pub fn record(&self, event: &TelemetryEvent) -> Vec<AnomalyRecovery> {
+        let key = (event.service_name.clone(), event.model.clone());
+        let mut watches = self.watches.lock().unwrap();
+        let Some(entry) = watches.get_mut(&key) else {
+            return Vec::new();
+        };
+
+        let period =
+            Duration::seconds(i64::try_from(self.config.recovery_secs).unwrap_or(i64::MAX));
+        let mut recovered = Vec::new();
+        entry.retain_mut(|watch| {
+            let Some(value) = event_metric(event, &watch.metric) else {
+                return true;
+            };
+            if !watch.within_baseline(value) {
+                watch.since = None;
+                watch.samples = 0;
+                return true;
+            }
+
+            let since = *watch.since.get_or_insert(event.timestamp);
+            watch.samples += 1;
+            if event.timestamp - since < period || watch.samples < self.config.min_samples {
+                return true;
+            }
+
+            debug!(alert_id = %watch.alert_id, metric = %watch.metric, "Anomaly recovered");
+            recovered.push(AnomalyRecovery {
+                alert_id: watch.alert_id,
+                service_name: event.service_name.clone(),
+                model: event.model.clone(),
+                metric: watch.metric.clone(),
+                baseline: watch.baseline,
+                last_value: value,
+                samples: watch.samples,
+                since,
+                at: event.timestamp,
+            });
+            false
+        });
+        if entry.is_empty() {
+            watches.remove(&key);
+        }
+
+        if !recovered.is_empty() {
+            ::metrics::gauge!("sentinel_recovery_watched_anomalies")
+                .decrement(recovered.len() as f64);
+        }
+        recovered
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+    use llm_sentinel_core::lifecycle::AnomalyState;
+
+    fn anomaly(metric: &str) -> AnomalyEvent {
+        llm_sentinel_fixtures::anomaly()
+            .service("checkout")
+            .metric(metric)
+            .threshold(400.0)
+            .build()
+    }
+
+    fn event(latency_ms: f64, secs: i64) -> TelemetryEvent {
+        llm_sentinel_fixtures::event()
+            .service("checkout")
+            .latency_ms(latency_ms)
+            .timestamp(Utc.with_ymd_and_hms(2024, 1, 1, 0, 0, 0).unwrap() + Duration::seconds(secs))
+            .build()
+    }
+
+    fn monitor() -> RecoveryMonitor {
+        RecoveryMonitor::new(RecoveryMonitorConfig {
+            recovery_secs: 60,
+            min_samples: 3,
+        })
+        .unwrap()
+    }
+
+    #[test]
+    fn test_recovers_after_period() {
+        let monitor = monitor();
+        let anomaly = anomaly("latency_ms");
+        assert!(monitor.watch(&anomaly));
+        assert_eq!(monitor.watched(), 1);
+
+        assert!(monitor.record(&event(120.0, 0)).is_empty());
+        assert!(monitor.record(&event(90.0, 30)).is_empty());
+        let recovered = monitor.record(&event(110.0, 60));
+        assert_eq!(recovered.len(), 1);
+        assert_eq!(recovered[0].alert_id, anomaly.alert_id);
+        assert_eq!(recovered[0].samples, 3);
+        assert_eq!(recovered[0].last_value, 110.0);
+        assert!(recovered[0].reason().contains("for 60s"));
+        assert_eq!(monitor.watched(), 0);
+    }
+
+    #[test]
+    fn test_out_of_baseline_sample_restarts_period() {
+        let monitor = monitor();
+        monitor.watch(&anomaly("latency_ms"));
+
+        monitor.record(&event(100.0, 0));
+        monitor.record(&event(100.0, 20));
+        monitor.record(&event(900.0, 40));
+        monitor.record(&event(100.0, 50));
+        monitor.record(&event(100.0, 80));
+        assert!(monitor.record(&event(100.0, 100)).is_empty());
+        assert_eq!(monitor.record(&event(100.0, 110)).len(), 1);
+    }
+
+    #[test]
+    fn test_min_samples() {
+        let monitor = monitor();
+        monitor.watch(&anomaly("latency_ms"));
+
+        monitor.record(&event(100.0, 0));
+        assert!(monitor.record(&event(100.0, 120)).is_empty());
+        assert_eq!(monitor.record(&event(100.0, 121)).len(), 1);
+    }
+
+    #[test]
+    fn test_watch_skips_unsupported_and_inactive() {
+        let monitor = monitor();
+        assert!(!monitor.watch(&anomaly("error_rate")));
+
+        let mut resolved = anomaly("latency_ms");
+        resolved.state = AnomalyState::Resolved;
+        assert!(!monitor.watch(&resolved));
+
+        let open = anomaly("cost_usd");
+        assert!(monitor.watch(&open));
+        assert!(monitor.watch(&open));
+        assert_eq!(monitor.watched(), 1);
+        monitor.forget(open.alert_id);
+        assert_eq!(monitor.watched(), 0);
+
+        assert!(RecoveryMonitor::new(RecoveryMonitorConfig {
+            recovery_secs: 0,
+            min_samples: 1,
+        })
+        .is_err());
+    }
+}
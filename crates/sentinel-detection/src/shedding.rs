@@ -0,0 +1,224 @@
+//! Adaptive load shedding for detection.
+//!
+//! When detection cannot keep up, its queue fills and every event waits
+//! longer, so anomalies are reported later and later without anyone being
+//! told. The sampler instead watches the queue: past the high watermark it
+//! enters degraded mode and sends only a share of events to detection, the
+//! share shrinking as the queue fills. Events carrying errors or slower
+//! than the configured latency are always detected, since they are the ones
+//! most likely to be anomalous. Degraded mode ends once the queue drains
+//! below the low watermark.
+//!
+//! The mode and sample rate are exported as the `sentinel_detection_degraded`
+//! and `sentinel_detection_sample_rate` gauges, and skipped events are
+//! counted in `sentinel_events_sampled_out_total`.
+
+use llm_sentinel_core::{config::LoadSheddingConfig, events::TelemetryEvent, Error, Result};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Mutex;
+use tracing::{info, warn};
+
+/// Current load shedding state
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct SheddingStatus {
+    /// Whether events are being sampled
+    pub degraded: bool,
+    /// Share of events sent to detection
+    pub sample_rate: f64,
+}
+
+/// Decides which events are detected while the detection queue is backed up
+#[derive(Debug)]
+pub struct AdaptiveSampler {
+    config: LoadSheddingConfig,
+    capacity: usize,
+    degraded: AtomicBool,
+    /// Sample rate and the fraction of an event owed to detection, spread
+    /// evenly instead of drawn at random
+    state: Mutex<(f64, f64)>,
+}
+
+impl AdaptiveSampler {
+    /// Create a sampler for a detection queue holding `capacity` events
+    pub fn from_config(config: &LoadSheddingConfig, capacity: usize) -> Result<Self> {
+        if capacity == 0 {
+            return Err(Error::config(
+                "Load shedding needs a bounded detection queue",
+            ));
+        }
+        if config.low_watermark >= config.high_watermark {
+            return Err(Error::config(
+                "Load shedding low_watermark must be below high_watermark",
+            ));
+        }
+
+        metrics::gauge!("sentinel_detection_degraded").set(0.0);
+        metrics::gauge!("sentinel_detection_sample_rate").set(1.0);
+        Ok(Self {
+            config: config.clone(),
+            capacity,
+            degraded: AtomicBool::new(false),
+            state: Mutex::new((1.0, 0.0)),
+        })
+    }
+
+    /// Whether `event` should be detected with `queue_depth` events waiting
+    ///
+    /// Events not detected should still be stored.
+    pub fn should_detect(&self, event: &TelemetryEvent, queue_depth: usize) -> bool {
+        let fill = (queue_depth as f64 / self.capacity as f64).min(1.0);
+        let degraded = self.update_mode(fill);
+
+        let mut state = self.state.lock().unwrap();
+        let rate = if degraded {
+            self.sample_rate(fill)
+        } else {
+            1.0
+        };
+        if rate != state.0 {
+            state.0 = rate;
+            metrics::gauge!("sentinel_detection_sample_rate").set(rate);
+        }
+        if !degraded {
+            state.1 = 0.0;
+            return true;
+        }
+        if !event.errors.is_empty() || event.latency_ms >= self.config.always_latency_ms {
+            return true;
+        }
+
+        state.1 += rate;
+        if state.1 >= 1.0 {
+            state.1 -= 1.0;
+            true
+        } else {
+            metrics::counter!("sentinel_events_sampled_out_total").increment(1);
+            false
+        }
+    }
+
+    /// Current mode and sample rate
+    pub fn status(&self) -> SheddingStatus {
+        SheddingStatus {
+            degraded: self.degraded.load(Ordering::Relaxed),
+            sample_rate: self.state.lock().unwrap().0,
+        }
+    }
+
+    /// Enter or leave degraded mode at the given queue fill, returning
+    /// whether it is degraded
+    fn update_mode(&self, fill: f64) -> bool {
+        let degraded = self.degraded.load(Ordering::Relaxed);
+        if !degraded && fill >= self.config.high_watermark {
+            if !self.degraded.swap(true, Ordering::Relaxed) {
+                warn!(
+                    queue_fill = fill,
+                    "Detection queue backed up, entering degraded mode"
+                );
+                metrics::gauge!("sentinel_detection_degraded").set(1.0);
+            }
+            true
+        } else if degraded && fill < self.config.low_watermark {
+            if self.degraded.swap(false, Ordering::Relaxed) {
+                info!(
+                    queue_fill = fill,
+                    "Detection queue drained, leaving degraded mode"
+                );
+                metrics::gauge!("sentinel_detection_degraded").set(0.0);
+            }
+            false
+        } else {
+            degraded
+        }
+    }
+
+    /// Share of events to detect at the given queue fill
+    ///
+    /// Between the low and high watermarks (while still degraded) every
+    /// event is detected so the queue can drain at full accuracy.
+    fn sample_rate(&self, fill: f64) -> f64 {
+        let high = self.config.high_watermark;
+        if fill < high || high >= 1.0 {
+            return 1.0;
+        }
+        let over = (fill - high) / (1.0 - high);
+        1.0 - over * (1.0 - self.config.min_sample_rate)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn config() -> LoadSheddingConfig {
+        LoadSheddingConfig {
+            high_watermark: 0.5,
+            low_watermark: 0.25,
+            min_sample_rate: 0.25,
+            always_latency_ms: 5000.0,
+        }
+    }
+
+    fn event(latency_ms: f64) -> TelemetryEvent {
+        llm_sentinel_fixtures::event()
+            .service("chat")
+            .latency_ms(latency_ms)
+            .build()
+    }
+
+    #[test]
+    fn test_degraded_mode_hysteresis() {
+        let sampler = AdaptiveSampler::from_config(&config(), 100).unwrap();
+        assert!(sampler.should_detect(&event(100.0), 49));
+        assert!(!sampler.status().degraded);
+
+        sampler.should_detect(&event(100.0), 50);
+        assert!(sampler.status().degraded);
+        // Stays degraded until the queue drains below the low watermark
+        sampler.should_detect(&event(100.0), 30);
+        assert!(sampler.status().degraded);
+        assert_eq!(sampler.status().sample_rate, 1.0);
+        sampler.should_detect(&event(100.0), 24);
+        assert_eq!(
+            sampler.status(),
+            SheddingStatus {
+                degraded: false,
+                sample_rate: 1.0
+            }
+        );
+    }
+
+    #[test]
+    fn test_samples_as_queue_fills() {
+        let sampler = AdaptiveSampler::from_config(&config(), 100).unwrap();
+
+        // Halfway from the high watermark to full: 62.5% of events
+        let detected = (0..100)
+            .filter(|_| sampler.should_detect(&event(100.0), 75))
+            .count();
+        assert_eq!(detected, 62);
+
+        // A full queue keeps the minimum rate
+        let detected = (0..100)
+            .filter(|_| sampler.should_detect(&event(100.0), 150))
+            .count();
+        assert_eq!(detected, 25);
+        assert_eq!(sampler.status().sample_rate, 0.25);
+
+        // Errors and slow events are always detected
+        let mut failed = event(100.0);
+        failed.errors.push("timeout".to_string());
+        assert!((0..10).all(|_| sampler.should_detect(&failed, 100)));
+        assert!((0..10).all(|_| sampler.should_detect(&event(6000.0), 100)));
+    }
+
+    #[test]
+    fn test_invalid_config() {
+        assert!(AdaptiveSampler::from_config(&config(), 0).is_err());
+        let inverted = LoadSheddingConfig {
+            low_watermark: 0.9,
+            ..config()
+        };
+        assert!(AdaptiveSampler::from_config(&inverted, 100).is_err());
+    }
+}
@@ -0,0 +1,298 @@
+//! Telemetry silence detection.
+//!
+//! A producer that stops sending telemetry altogether is an incident the
+//! detectors cannot see, since they only run on events that arrive. The
+//! [`SilenceMonitor`] records when each service and model was last heard
+//! from and, once a producer that sent at least `min_events` goes quiet for
+//! `silence_secs`, raises a [`SILENCE_ANOMALY`] anomaly. Each silence is
+//! reported once; the producer has to send again before a new silence can be
+//! reported. Producers quiet for `forget_secs` (e.g. retired models) are
+//! forgotten.
+//!
+//! Time is measured on the monitor's clock when events arrive, not in event
+//! time, so late or backfilled events do not mask a silence.
+
+use chrono::{DateTime, Duration, Utc};
+use llm_sentinel_core::{
+    clock::Clock,
+    events::{AnomalyContext, AnomalyDetails, AnomalyEvent, TelemetryEvent},
+    types::{AnomalyType, DetectionMethod, ModelId, ServiceId, Severity},
+    Error, Result,
+};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::sync::Mutex;
+use tracing::{info, warn};
+
+/// Anomaly type of silence anomalies
+pub const SILENCE_ANOMALY: &str = "telemetry_silence";
+
+/// Silence monitor configuration
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SilenceMonitorConfig {
+    /// Quiet time after which a producer counts as silent (seconds)
+    pub silence_secs: u64,
+    /// Events a producer must have sent to count as active
+    pub min_events: u64,
+    /// Quiet time after which a producer is forgotten (seconds)
+    pub forget_secs: u64,
+    /// Severity of silence anomalies
+    pub severity: Severity,
+    /// Clock quiet time is measured against
+    #[serde(skip)]
+    pub clock: Clock,
+}
+
+impl Default for SilenceMonitorConfig {
+    fn default() -> Self {
+        Self {
+            silence_secs: 300,
+            min_events: 10,
+            forget_secs: 86400,
+            severity: Severity::High,
+            clock: Clock::system(),
+        }
+    }
+}
+
+impl SilenceMonitorConfig {
+    /// Validate the configuration
+    pub fn validate(&self) -> Result<()> {
+        if self.silence_secs == 0 {
+            return Err(Error::config("Silence silence_secs must be positive"));
+        }
+        if self.min_events == 0 {
+            return Err(Error::config("Silence min_events must be positive"));
+        }
+        if self.forget_secs <= self.silence_secs {
+            return Err(Error::config(
+                "Silence forget_secs must be greater than silence_secs",
+            ));
+        }
+        Ok(())
+    }
+}
+
+/// Activity of one service and model
+#[derive(Debug, Clone)]
+struct Producer {
+    first_seen: DateTime<Utc>,
+    last_seen: DateTime<Utc>,
+    events: u64,
+    /// Whether the current silence was reported
+    reported: bool,
+}
+
+/// Tracks producers and reports those that went silent
+#[derive(Debug)]
+pub struct SilenceMonitor {
+    config: SilenceMonitorConfig,
+    producers: Mutex<HashMap<(ServiceId, ModelId), Producer>>,
+}
+
+impl SilenceMonitor {
+    /// Create a new monitor
+    pub fn new(config: SilenceMonitorConfig) -> Result<Self> {
+        config.validate()?;
+        Ok(Self {
+            config,
+            producers: Mutex::new(HashMap::new()),
+        })
+    }
+
+    /// Get the configuration
+    pub fn config(&self) -> &SilenceMonitorConfig {
+        &self.config
+    }
+
+    /// Number of producers being tracked
+    pub fn tracked(&self) -> usize {
+        self.producers.lock().unwrap().len()
+    }
+
+    /// Record that an event arrived from its service and model
+    pub fn record(&self, event: &TelemetryEvent) {
+        self.record_at(event, self.config.clock.now());
+    }
+
+    /// Record an event arriving at the given time
+    pub fn record_at(&self, event: &TelemetryEvent, now: DateTime<Utc>) {
+        let key = (event.service_name.clone(), event.model.clone());
+        let mut producers = self.producers.lock().unwrap();
+        let producer = producers.entry(key).or_insert_with(|| Producer {
+            first_seen: now,
+            last_seen: now,
+            events: 0,
+            reported: false,
+        });
+        if producer.reported {
+            info!(
+                service = %event.service_name,
+                model = %event.model,
+                silent_secs = (now - producer.last_seen).num_seconds(),
+                "Telemetry resumed after silence"
+            );
+            metrics::counter!("sentinel_telemetry_silence_resumed_total").increment(1);
+            producer.reported = false;
+        }
+        producer.last_seen = producer.last_seen.max(now);
+        producer.events += 1;
+    }
+
+    /// Report producers that went silent
+    pub fn check(&self) -> Vec<AnomalyEvent> {
+        self.check_at(self.config.clock.now())
+    }
+
+    /// Report producers silent at the given time, forgetting those quiet
+    /// for longer than `forget_secs`
+    pub fn check_at(&self, now: DateTime<Utc>) -> Vec<AnomalyEvent> {
+        let silence =
+            Duration::seconds(i64::try_from(self.config.silence_secs).unwrap_or(i64::MAX));
+        let forget = Duration::seconds(i64::try_from(self.config.forget_secs).unwrap_or(i64::MAX));
+
+        let mut producers = self.producers.lock().unwrap();
+        producers.retain(|_, producer| now - producer.last_seen < forget);
+
+        let mut anomalies = Vec::new();
+        for ((service, model), producer) in producers.iter_mut() {
+            let quiet = now - producer.last_seen;
+            if producer.reported || quiet < silence || producer.events < self.config.min_events {
+                continue;
+            }
+            producer.reported = true;
+            warn!(
+                service = %service,
+                model = %model,
+                silent_secs = quiet.num_seconds(),
+                "Telemetry silence detected"
+            );
+            metrics::counter!("sentinel_telemetry_silence_total").increment(1);
+            anomalies.push(self.anomaly(service, model, producer, quiet, now));
+        }
+        anomalies
+    }
+
+    fn anomaly(
+        &self,
+        service: &ServiceId,
+        model: &ModelId,
+        producer: &Producer,
+        quiet: Duration,
+        now: DateTime<Utc>,
+    ) -> AnomalyEvent {
+        // Typical gap between events while the producer was active
+        let active_secs =
+            (producer.last_seen - producer.first_seen).num_milliseconds() as f64 / 1000.0;
+        let interval = active_secs / (producer.events - 1).max(1) as f64;
+
+        let mut additional = HashMap::new();
+        additional.insert(
+            "last_seen".to_string(),
+            serde_json::json!(producer.last_seen.to_rfc3339()),
+        );
+        additional.insert("events".to_string(), serde_json::json!(producer.events));
+
+        let mut anomaly = AnomalyEvent::new(
+            self.config.severity,
+            AnomalyType::Custom(SILENCE_ANOMALY.to_string()),
+            service.clone(),
+            model.clone(),
+            DetectionMethod::Custom("silence".to_string()),
+            1.0,
+            AnomalyDetails {
+                metric: "seconds_since_last_event".to_string(),
+                value: quiet.num_milliseconds() as f64 / 1000.0,
+                baseline: interval,
+                threshold: self.config.silence_secs as f64,
+                deviation_sigma: None,
+                additional,
+                observations: Vec::new(),
+            },
+            AnomalyContext {
+                trace_id: None,
+                user_id: None,
+                region: None,
+                time_window: format!("{}s", self.config.silence_secs),
+                sample_count: producer.events as usize,
+                window: None,
+                additional: HashMap::new(),
+            },
+        );
+        anomaly.timestamp = now;
+        anomaly
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn event(service: &str, model: &str) -> TelemetryEvent {
+        llm_sentinel_fixtures::event()
+            .service(service)
+            .model(model)
+            .build()
+    }
+
+    fn monitor() -> SilenceMonitor {
+        SilenceMonitor::new(SilenceMonitorConfig {
+            silence_secs: 300,
+            min_events: 3,
+            forget_secs: 3600,
+            ..Default::default()
+        })
+        .unwrap()
+    }
+
+    #[test]
+    fn test_reports_silence_once() {
+        let monitor = monitor();
+        let start = Utc::now();
+        let at = |secs: i64| start + Duration::seconds(secs);
+        for i in 0..3 {
+            monitor.record_at(&event("chat", "gpt-4"), at(i * 10));
+        }
+        // Too few events to count as active
+        monitor.record_at(&event("batch", "gpt-4"), at(0));
+
+        assert!(monitor.check_at(at(200)).is_empty());
+
+        let anomalies = monitor.check_at(at(320));
+        assert_eq!(anomalies.len(), 1);
+        let anomaly = &anomalies[0];
+        assert_eq!(anomaly.service_name.as_str(), "chat");
+        assert_eq!(anomaly.anomaly_type.to_string(), SILENCE_ANOMALY);
+        assert_eq!(anomaly.details.value, 300.0);
+        assert_eq!(anomaly.details.baseline, 10.0);
+        assert_eq!(anomaly.severity, Severity::High);
+
+        // Reported once per silence
+        assert!(monitor.check_at(at(400)).is_empty());
+
+        // Resuming arms the next silence
+        monitor.record_at(&event("chat", "gpt-4"), at(500));
+        assert_eq!(monitor.check_at(at(900)).len(), 1);
+    }
+
+    #[test]
+    fn test_forgets_retired_producers() {
+        let monitor = monitor();
+        let start = Utc::now();
+        monitor.record_at(&event("chat", "gpt-3.5"), start);
+        assert_eq!(monitor.tracked(), 1);
+
+        monitor.check_at(start + Duration::seconds(3600));
+        assert_eq!(monitor.tracked(), 0);
+    }
+
+    #[test]
+    fn test_invalid_config() {
+        let config = SilenceMonitorConfig {
+            silence_secs: 600,
+            forget_secs: 300,
+            ..Default::default()
+        };
+        assert!(SilenceMonitor::new(config).is_err());
+    }
+}
@@ -0,0 +1,701 @@
+//! SLO evaluation and error-budget tracking.
+//!
+//! Each SLO classifies matching telemetry events as good or bad, keeps
+//! per-minute counts over the compliance window, and evaluates multi-window
+//! burn-rate policies. A policy fires when both its long and short window burn
+//! faster than the threshold, which catches fast budget consumption quickly
+//! without alerting on short blips.
+//!
+//! Burn rate is the observed error ratio divided by the error budget
+//! (`1 - target`): a burn rate of 1.0 spends exactly the budget over the
+//! window, 14.4 spends a 30-day budget in about two days.
+
+use chrono::{DateTime, Utc};
+use llm_sentinel_core::{
+    clock::Clock,
+    events::{AnomalyContext, AnomalyDetails, AnomalyEvent, TelemetryEvent},
+    types::{AnomalyType, DetectionMethod, ModelId, ServiceId, Severity},
+    Error, Result,
+};
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, VecDeque};
+use std::sync::Mutex;
+use tracing::{info, warn};
+
+/// Width of the counting buckets (seconds)
+const BUCKET_SECS: i64 = 60;
+
+/// What an SLO measures
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum SloObjective {
+    /// Requests complete within the latency threshold
+    Latency {
+        /// Latency threshold in milliseconds
+        threshold_ms: f64,
+    },
+    /// Requests complete without errors
+    ErrorRate,
+}
+
+impl SloObjective {
+    /// Whether an event counts as good for this objective
+    fn is_good(&self, event: &TelemetryEvent) -> bool {
+        match self {
+            Self::Latency { threshold_ms } => event.latency_ms <= *threshold_ms,
+            Self::ErrorRate => !event.has_errors(),
+        }
+    }
+
+    /// Anomaly type used for burn-rate alerts
+    fn anomaly_type(&self) -> AnomalyType {
+        match self {
+            Self::Latency { .. } => AnomalyType::LatencySpike,
+            Self::ErrorRate => AnomalyType::ErrorRateIncrease,
+        }
+    }
+}
+
+/// Multi-window burn-rate alerting policy
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BurnRatePolicy {
+    /// Long window (seconds)
+    pub long_window_secs: u64,
+    /// Short window (seconds)
+    pub short_window_secs: u64,
+    /// Burn rate both windows must reach
+    pub threshold: f64,
+    /// Severity of the resulting alert
+    pub severity: Severity,
+}
+
+impl BurnRatePolicy {
+    /// Default policies: fast burn (1h/5m at 14.4x) and slow burn (6h/30m at 6x)
+    pub fn defaults() -> Vec<Self> {
+        vec![
+            Self {
+                long_window_secs: 3600,
+                short_window_secs: 300,
+                threshold: 14.4,
+                severity: Severity::Critical,
+            },
+            Self {
+                long_window_secs: 21600,
+                short_window_secs: 1800,
+                threshold: 6.0,
+                severity: Severity::High,
+            },
+        ]
+    }
+}
+
+/// SLO definition
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SloDefinition {
+    /// Unique SLO name
+    pub name: String,
+    /// Restrict to a service (all services when absent)
+    pub service: Option<ServiceId>,
+    /// Restrict to a model (all models when absent)
+    pub model: Option<ModelId>,
+    /// What is measured
+    pub objective: SloObjective,
+    /// Target ratio of good events (e.g. 0.99)
+    pub target: f64,
+    /// Compliance window (seconds)
+    pub window_secs: u64,
+    /// Minimum events in a window before burn rates are evaluated
+    pub min_events: u64,
+    /// Burn-rate alerting policies
+    pub policies: Vec<BurnRatePolicy>,
+}
+
+impl SloDefinition {
+    /// Create an SLO with a 30-day window and the default burn-rate policies
+    pub fn new(name: impl Into<String>, objective: SloObjective, target: f64) -> Self {
+        Self {
+            name: name.into(),
+            service: None,
+            model: None,
+            objective,
+            target,
+            window_secs: 30 * 86400, // 30 days
+            min_events: 10,
+            policies: BurnRatePolicy::defaults(),
+        }
+    }
+
+    /// Restrict to a service
+    pub fn with_service(mut self, service: ServiceId) -> Self {
+        self.service = Some(service);
+        self
+    }
+
+    /// Restrict to a model
+    pub fn with_model(mut self, model: ModelId) -> Self {
+        self.model = Some(model);
+        self
+    }
+
+    /// Set the compliance window
+    pub fn with_window_secs(mut self, window_secs: u64) -> Self {
+        self.window_secs = window_secs;
+        self
+    }
+
+    /// Replace the burn-rate policies
+    pub fn with_policies(mut self, policies: Vec<BurnRatePolicy>) -> Self {
+        self.policies = policies;
+        self
+    }
+
+    /// Validate the definition
+    pub fn validate(&self) -> Result<()> {
+        if self.name.is_empty() {
+            return Err(Error::config("SLO name cannot be empty"));
+        }
+        if !(self.target > 0.0 && self.target < 1.0) {
+            return Err(Error::config(format!(
+                "SLO {} target must be between 0 and 1 (exclusive)",
+                self.name
+            )));
+        }
+        if self.window_secs < BUCKET_SECS as u64 {
+            return Err(Error::config(format!(
+                "SLO {} window must be at least {}s",
+                self.name, BUCKET_SECS
+            )));
+        }
+        for policy in &self.policies {
+            if policy.short_window_secs == 0
+                || policy.short_window_secs > policy.long_window_secs
+                || policy.long_window_secs > self.window_secs
+            {
+                return Err(Error::config(format!(
+                    "SLO {} has invalid burn-rate windows {}s/{}s",
+                    self.name, policy.long_window_secs, policy.short_window_secs
+                )));
+            }
+            if policy.threshold <= 0.0 {
+                return Err(Error::config(format!(
+                    "SLO {} burn-rate threshold must be positive",
+                    self.name
+                )));
+            }
+        }
+        if let SloObjective::Latency { threshold_ms } = self.objective {
+            if threshold_ms <= 0.0 {
+                return Err(Error::config(format!(
+                    "SLO {} latency threshold must be positive",
+                    self.name
+                )));
+            }
+        }
+        Ok(())
+    }
+
+    /// Whether an event falls under this SLO
+    fn matches(&self, event: &TelemetryEvent) -> bool {
+        self.service.as_ref().map_or(true, |s| *s == event.service_name)
+            && self.model.as_ref().map_or(true, |m| *m == event.model)
+    }
+
+    /// Error budget as a ratio of events
+    fn error_budget(&self) -> f64 {
+        1.0 - self.target
+    }
+}
+
+/// Burn rate over one window
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BurnRate {
+    /// Window length (seconds)
+    pub window_secs: u64,
+    /// Events in the window
+    pub total: u64,
+    /// Burn rate (error ratio / error budget)
+    pub rate: f64,
+}
+
+/// Current state of an SLO
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SloStatus {
+    /// SLO name
+    pub name: String,
+    /// Service scope
+    pub service: Option<ServiceId>,
+    /// Model scope
+    pub model: Option<ModelId>,
+    /// Objective
+    pub objective: SloObjective,
+    /// Target ratio of good events
+    pub target: f64,
+    /// Compliance window (seconds)
+    pub window_secs: u64,
+    /// Events in the window
+    pub total_events: u64,
+    /// Good events in the window
+    pub good_events: u64,
+    /// Observed ratio of good events (1.0 with no traffic)
+    pub compliance: f64,
+    /// Fraction of the error budget left (negative once exhausted)
+    pub error_budget_remaining: f64,
+    /// Whether the SLO is currently met
+    pub met: bool,
+    /// Burn rates for each distinct policy window
+    pub burn_rates: Vec<BurnRate>,
+    /// Severities of policies currently firing
+    pub firing: Vec<Severity>,
+}
+
+/// Good/total counts for one bucket
+#[derive(Debug, Clone, Copy)]
+struct Bucket {
+    start: i64,
+    good: u64,
+    total: u64,
+}
+
+/// Runtime state for one SLO
+#[derive(Debug)]
+struct SloState {
+    definition: SloDefinition,
+    buckets: VecDeque<Bucket>,
+    firing: Vec<bool>,
+}
+
+impl SloState {
+    fn new(definition: SloDefinition) -> Self {
+        let firing = vec![false; definition.policies.len()];
+        Self {
+            definition,
+            buckets: VecDeque::new(),
+            firing,
+        }
+    }
+
+    /// Add an event to its bucket (buckets are kept ordered by start)
+    fn record(&mut self, timestamp: DateTime<Utc>, good: bool) {
+        let start = timestamp.timestamp().div_euclid(BUCKET_SECS) * BUCKET_SECS;
+        let position = self.buckets.iter().rposition(|b| b.start <= start);
+
+        let bucket = match position {
+            Some(i) if self.buckets[i].start == start => &mut self.buckets[i],
+            Some(i) => {
+                self.buckets.insert(i + 1, Bucket { start, good: 0, total: 0 });
+                &mut self.buckets[i + 1]
+            }
+            None => {
+                self.buckets.push_front(Bucket { start, good: 0, total: 0 });
+                &mut self.buckets[0]
+            }
+        };
+
+        bucket.total += 1;
+        if good {
+            bucket.good += 1;
+        }
+    }
+
+    /// Drop buckets that have left the compliance window
+    fn prune(&mut self, now: DateTime<Utc>) {
+        let cutoff = now.timestamp() - self.definition.window_secs as i64;
+        while self.buckets.front().is_some_and(|b| b.start + BUCKET_SECS <= cutoff) {
+            self.buckets.pop_front();
+        }
+    }
+
+    /// Good and total counts over the trailing window
+    fn counts(&self, now: DateTime<Utc>, window_secs: u64) -> (u64, u64) {
+        let cutoff = now.timestamp() - window_secs as i64;
+        self.buckets
+            .iter()
+            .rev()
+            .take_while(|b| b.start + BUCKET_SECS > cutoff)
+            .fold((0, 0), |(good, total), b| (good + b.good, total + b.total))
+    }
+
+    /// Burn rate over the trailing window
+    fn burn_rate(&self, now: DateTime<Utc>, window_secs: u64) -> BurnRate {
+        let (good, total) = self.counts(now, window_secs);
+        let rate = if total == 0 {
+            0.0
+        } else {
+            let error_ratio = (total - good) as f64 / total as f64;
+            error_ratio / self.definition.error_budget()
+        };
+        BurnRate {
+            window_secs,
+            total,
+            rate,
+        }
+    }
+
+    /// Evaluate policies, returning alerts for those that started firing
+    fn evaluate(&mut self, now: DateTime<Utc>) -> Vec<AnomalyEvent> {
+        let mut alerts = Vec::new();
+
+        for (i, policy) in self.definition.policies.iter().enumerate() {
+            let long = self.burn_rate(now, policy.long_window_secs);
+            let short = self.burn_rate(now, policy.short_window_secs);
+
+            let firing = long.total >= self.definition.min_events
+                && long.rate >= policy.threshold
+                && short.rate >= policy.threshold;
+
+            if firing && !self.firing[i] {
+                warn!(
+                    slo = %self.definition.name,
+                    severity = %policy.severity,
+                    long_burn = long.rate,
+                    short_burn = short.rate,
+                    "SLO burn-rate alert"
+                );
+                metrics::counter!(
+                    "sentinel_slo_burn_alerts_total",
+                    "slo" => self.definition.name.clone(),
+                    "severity" => policy.severity.to_string()
+                )
+                .increment(1);
+                alerts.push(self.build_alert(policy, &long, &short, now));
+            } else if !firing && self.firing[i] {
+                info!(
+                    slo = %self.definition.name,
+                    severity = %policy.severity,
+                    "SLO burn rate back under threshold"
+                );
+            }
+
+            self.firing[i] = firing;
+        }
+
+        alerts
+    }
+
+    /// Build the anomaly event for a firing policy
+    fn build_alert(
+        &self,
+        policy: &BurnRatePolicy,
+        long: &BurnRate,
+        short: &BurnRate,
+        now: DateTime<Utc>,
+    ) -> AnomalyEvent {
+        let definition = &self.definition;
+        let (good, total) = self.counts(now, definition.window_secs);
+
+        let mut additional = HashMap::new();
+        additional.insert("slo".to_string(), serde_json::json!(definition.name));
+        additional.insert("target".to_string(), serde_json::json!(definition.target));
+        additional.insert("short_window_burn_rate".to_string(), serde_json::json!(short.rate));
+        additional.insert(
+            "error_budget_remaining".to_string(),
+            serde_json::json!(budget_remaining(definition, good, total)),
+        );
+
+        let mut context_additional = HashMap::new();
+        context_additional.insert("slo".to_string(), definition.name.clone());
+
+        let confidence = (short.rate.min(long.rate) / policy.threshold / 2.0).clamp(0.5, 1.0);
+
+        let mut alert = AnomalyEvent::new(
+            policy.severity,
+            definition.objective.anomaly_type(),
+            definition
+                .service
+                .clone()
+                .unwrap_or_else(|| ServiceId::new("all")),
+            definition.model.clone().unwrap_or_else(|| ModelId::new("all")),
+            DetectionMethod::Custom("slo_burn_rate".to_string()),
+            confidence,
+            AnomalyDetails {
+                metric: "slo_burn_rate".to_string(),
+                value: long.rate,
+                baseline: 1.0,
+                threshold: policy.threshold,
+                deviation_sigma: None,
+                additional,
+                observations: Vec::new(),
+            },
+            AnomalyContext {
+                trace_id: None,
+                user_id: None,
+                region: None,
+                time_window: format!(
+                    "{}s/{}s",
+                    policy.long_window_secs, policy.short_window_secs
+                ),
+                sample_count: long.total as usize,
+                window: None,
+                additional: context_additional,
+            },
+        );
+        alert.root_cause = Some(format!(
+            "SLO {} is burning its error budget at {:.1}x over {}s (threshold {:.1}x)",
+            definition.name, long.rate, policy.long_window_secs, policy.threshold
+        ));
+        alert
+    }
+
+    /// Snapshot the SLO status
+    fn status(&self, now: DateTime<Utc>) -> SloStatus {
+        let definition = &self.definition;
+        let (good, total) = self.counts(now, definition.window_secs);
+        let compliance = if total == 0 { 1.0 } else { good as f64 / total as f64 };
+
+        let mut windows: Vec<u64> = definition
+            .policies
+            .iter()
+            .flat_map(|p| [p.long_window_secs, p.short_window_secs])
+            .collect();
+        windows.sort_unstable();
+        windows.dedup();
+
+        SloStatus {
+            name: definition.name.clone(),
+            service: definition.service.clone(),
+            model: definition.model.clone(),
+            objective: definition.objective.clone(),
+            target: definition.target,
+            window_secs: definition.window_secs,
+            total_events: total,
+            good_events: good,
+            compliance,
+            error_budget_remaining: budget_remaining(definition, good, total),
+            met: compliance >= definition.target,
+            burn_rates: windows
+                .into_iter()
+                .map(|window| self.burn_rate(now, window))
+                .collect(),
+            firing: definition
+                .policies
+                .iter()
+                .zip(&self.firing)
+                .filter(|(_, firing)| **firing)
+                .map(|(policy, _)| policy.severity)
+                .collect(),
+        }
+    }
+}
+
+/// Fraction of the error budget left given window counts
+fn budget_remaining(definition: &SloDefinition, good: u64, total: u64) -> f64 {
+    if total == 0 {
+        return 1.0;
+    }
+    let allowed_bad = definition.error_budget() * total as f64;
+    1.0 - (total - good) as f64 / allowed_bad
+}
+
+/// Tracks SLO compliance and burn rates from telemetry
+#[derive(Debug)]
+pub struct SloTracker {
+    slos: Mutex<Vec<SloState>>,
+    clock: Clock,
+}
+
+impl SloTracker {
+    /// Create a tracker for the given SLOs
+    pub fn new(definitions: Vec<SloDefinition>) -> Result<Self> {
+        let mut names = std::collections::HashSet::new();
+        for definition in &definitions {
+            definition.validate()?;
+            if !names.insert(definition.name.clone()) {
+                return Err(Error::config(format!(
+                    "Duplicate SLO name: {}",
+                    definition.name
+                )));
+            }
+        }
+
+        info!("Tracking {} SLOs", definitions.len());
+
+        Ok(Self {
+            slos: Mutex::new(definitions.into_iter().map(SloState::new).collect()),
+            clock: Clock::system(),
+        })
+    }
+
+    /// Evaluate [`record`](Self::record) and [`status`](Self::status)
+    /// against the given clock
+    pub fn with_clock(mut self, clock: Clock) -> Self {
+        self.clock = clock;
+        self
+    }
+
+    /// Record an event and return burn-rate alerts that started firing
+    pub fn record(&self, event: &TelemetryEvent) -> Vec<AnomalyEvent> {
+        self.record_at(event, self.clock.now())
+    }
+
+    /// Record an event evaluated at the given time
+    pub fn record_at(&self, event: &TelemetryEvent, now: DateTime<Utc>) -> Vec<AnomalyEvent> {
+        let mut slos = self.slos.lock().unwrap();
+        let mut alerts = Vec::new();
+
+        for slo in slos.iter_mut().filter(|s| s.definition.matches(event)) {
+            slo.prune(now);
+            let window_start = now - chrono::Duration::seconds(slo.definition.window_secs as i64);
+            if event.timestamp <= window_start || event.timestamp > now {
+                continue;
+            }
+            slo.record(event.timestamp, slo.definition.objective.is_good(event));
+            alerts.extend(slo.evaluate(now));
+        }
+
+        alerts
+    }
+
+    /// Current status of all SLOs
+    pub fn status(&self) -> Vec<SloStatus> {
+        self.status_at(self.clock.now())
+    }
+
+    /// Status of all SLOs at the given time
+    pub fn status_at(&self, now: DateTime<Utc>) -> Vec<SloStatus> {
+        let slos = self.slos.lock().unwrap();
+        let statuses: Vec<SloStatus> = slos.iter().map(|s| s.status(now)).collect();
+
+        for status in &statuses {
+            metrics::gauge!(
+                "sentinel_slo_error_budget_remaining",
+                "slo" => status.name.clone()
+            )
+            .set(status.error_budget_remaining);
+        }
+
+        statuses
+    }
+
+    /// Number of tracked SLOs
+    pub fn len(&self) -> usize {
+        self.slos.lock().unwrap().len()
+    }
+
+    /// Whether no SLOs are tracked
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+    use llm_sentinel_fixtures::event;
+
+    fn create_test_event(latency: f64, failed: bool, at: DateTime<Utc>) -> TelemetryEvent {
+        let builder = event()
+            .service("checkout")
+            .latency_ms(latency)
+            .timestamp(at);
+        if failed {
+            builder.error("upstream timeout").build()
+        } else {
+            builder.build()
+        }
+    }
+
+    fn start() -> DateTime<Utc> {
+        Utc.with_ymd_and_hms(2024, 1, 1, 0, 0, 0).unwrap()
+    }
+
+    #[test]
+    fn test_definition_validation() {
+        assert!(SloDefinition::new("ok", SloObjective::ErrorRate, 0.99).validate().is_ok());
+        assert!(SloDefinition::new("bad", SloObjective::ErrorRate, 1.0).validate().is_err());
+        assert!(SloDefinition::new("", SloObjective::ErrorRate, 0.99).validate().is_err());
+        assert!(SloDefinition::new("short", SloObjective::ErrorRate, 0.99)
+            .with_window_secs(1800)
+            .validate()
+            .is_err());
+
+        let duplicate = vec![
+            SloDefinition::new("a", SloObjective::ErrorRate, 0.99),
+            SloDefinition::new("a", SloObjective::ErrorRate, 0.95),
+        ];
+        assert!(SloTracker::new(duplicate).is_err());
+    }
+
+    #[test]
+    fn test_compliance_and_budget() {
+        let tracker = SloTracker::new(vec![SloDefinition::new(
+            "latency",
+            SloObjective::Latency {
+                threshold_ms: 2000.0,
+            },
+            0.9,
+        )])
+        .unwrap();
+
+        let now = start() + chrono::Duration::minutes(10);
+        for i in 0..20 {
+            let latency = if i < 1 { 5000.0 } else { 500.0 };
+            let at = start() + chrono::Duration::seconds(i * 10);
+            assert!(tracker.record_at(&create_test_event(latency, false, at), now).is_empty());
+        }
+
+        let status = &tracker.status_at(now)[0];
+        assert_eq!(status.total_events, 20);
+        assert_eq!(status.good_events, 19);
+        assert!((status.compliance - 0.95).abs() < 1e-9);
+        // 1 bad out of 2 allowed
+        assert!((status.error_budget_remaining - 0.5).abs() < 1e-9);
+        assert!(status.met);
+        assert!(status.firing.is_empty());
+    }
+
+    #[test]
+    fn test_burn_rate_alert_fires_once() {
+        let tracker = SloTracker::new(vec![SloDefinition::new(
+            "errors",
+            SloObjective::ErrorRate,
+            0.99,
+        )
+        .with_service(ServiceId::new("checkout"))])
+        .unwrap();
+
+        let mut alerts = Vec::new();
+        for i in 0..30 {
+            let at = start() + chrono::Duration::seconds(i * 10);
+            let failed = i % 2 == 0; // 50% errors = 50x burn
+            alerts.extend(tracker.record_at(&create_test_event(100.0, failed, at), at));
+        }
+
+        // Both policies fire exactly once
+        assert_eq!(alerts.len(), 2);
+        assert_eq!(alerts[0].severity, Severity::Critical);
+        assert_eq!(alerts[0].anomaly_type, AnomalyType::ErrorRateIncrease);
+        assert_eq!(alerts[0].service_name, ServiceId::new("checkout"));
+        assert!(alerts[0].details.value >= 14.4);
+
+        let status = &tracker.status_at(start() + chrono::Duration::seconds(300))[0];
+        assert_eq!(status.firing.len(), 2);
+        assert!(!status.met);
+        assert!(status.error_budget_remaining < 0.0);
+    }
+
+    #[test]
+    fn test_unmatched_and_stale_events_ignored() {
+        let tracker = SloTracker::new(vec![SloDefinition::new(
+            "errors",
+            SloObjective::ErrorRate,
+            0.99,
+        )
+        .with_service(ServiceId::new("search"))
+        .with_window_secs(3600)
+        .with_policies(Vec::new())])
+        .unwrap();
+
+        let now = start() + chrono::Duration::hours(2);
+        tracker.record_at(&create_test_event(100.0, true, now), now);
+        tracker.record_at(&create_test_event(100.0, true, start()), now);
+
+        let mut event = create_test_event(100.0, true, now);
+        event.service_name = ServiceId::new("search");
+        tracker.record_at(&event, now);
+
+        let status = &tracker.status_at(now)[0];
+        assert_eq!(status.total_events, 1);
+        assert_eq!(status.good_events, 0);
+    }
+}
@@ -0,0 +1,485 @@
+//! Trace-level evaluation of multi-span LLM chains.
+//!
+//! An agent or retrieval chain emits one telemetry event per LLM call, all
+//! sharing a `trace_id`. Per-span detectors judge each call on its own: a
+//! chain that takes twice its usual steps goes unnoticed, while one slow
+//! chain can raise an anomaly per span. The [`TraceAggregator`] collects
+//! spans by trace and, once a trace has been quiet for `idle_secs` (or open
+//! for `max_duration_secs`), evaluates the chain as a whole. Its total
+//! latency, total cost and step count are each compared with a baseline of
+//! past chains entering at the same service and model, and a chain well
+//! above its baselines raises one anomaly carrying the trace ID.
+//!
+//! Time is measured on the aggregator's clock when spans arrive. Spans
+//! arriving after their trace was evaluated are dropped rather than starting
+//! a one-step chain. Events without a trace ID are ignored.
+
+use crate::baseline::{Baseline, BaselineKey, BaselineManager};
+use chrono::{DateTime, Duration, Utc};
+use llm_sentinel_core::{
+    clock::Clock,
+    events::{AnomalyContext, AnomalyDetails, AnomalyEvent, TelemetryEvent},
+    types::{AnomalyType, DetectionMethod, ModelId, ServiceId, Severity},
+    window::WindowSpec,
+    Error, Result,
+};
+use std::collections::HashMap;
+use std::sync::Mutex;
+use tracing::{debug, warn};
+
+/// Anomaly type of chains taking unusually many steps
+pub const CHAIN_STEPS_ANOMALY: &str = "chain_step_spike";
+
+/// Smallest spread assumed for a chain baseline, relative to its mean, so
+/// chains of a fixed shape are not flagged for any change at all
+const MIN_RELATIVE_SPREAD: f64 = 0.1;
+
+/// Trace aggregator configuration
+#[derive(Debug, Clone)]
+pub struct TraceAggregatorConfig {
+    /// Quiet time after which a trace counts as complete (seconds)
+    pub idle_secs: u64,
+    /// Time after its first span at which a trace is evaluated regardless
+    /// (seconds)
+    pub max_duration_secs: u64,
+    /// Traces collected at once
+    pub max_open_traces: usize,
+    /// Standard deviations above baseline at which a chain is anomalous
+    pub threshold_sigma: f64,
+    /// Completed chains required before chain baselines are used
+    pub min_samples: usize,
+    /// Bounds of the chain baselines
+    pub window: WindowSpec,
+    /// Clock quiet time is measured against
+    pub clock: Clock,
+}
+
+impl Default for TraceAggregatorConfig {
+    fn default() -> Self {
+        Self {
+            idle_secs: 30,
+            max_duration_secs: 600,
+            max_open_traces: 10_000,
+            threshold_sigma: 3.0,
+            min_samples: 30,
+            window: WindowSpec::samples(1000),
+            clock: Clock::system(),
+        }
+    }
+}
+
+impl TraceAggregatorConfig {
+    /// Validate the configuration
+    pub fn validate(&self) -> Result<()> {
+        if self.idle_secs == 0 {
+            return Err(Error::config("Trace idle_secs must be positive"));
+        }
+        if self.max_duration_secs < self.idle_secs {
+            return Err(Error::config(
+                "Trace max_duration_secs must be at least idle_secs",
+            ));
+        }
+        if self.max_open_traces == 0 {
+            return Err(Error::config("Trace max_open_traces must be positive"));
+        }
+        if self.threshold_sigma <= 0.0 {
+            return Err(Error::config("Trace threshold_sigma must be positive"));
+        }
+        Ok(())
+    }
+}
+
+/// Spans collected for one trace
+#[derive(Debug, Clone)]
+struct OpenTrace {
+    /// Service and model of the earliest span, which the chain is
+    /// attributed to
+    service: ServiceId,
+    model: ModelId,
+    /// Timestamp of the earliest span
+    entry_at: DateTime<Utc>,
+    first_seen: DateTime<Utc>,
+    last_seen: DateTime<Utc>,
+    steps: usize,
+    latency_ms: f64,
+    cost_usd: f64,
+    tokens: u64,
+    errors: usize,
+}
+
+impl OpenTrace {
+    fn new(event: &TelemetryEvent, now: DateTime<Utc>) -> Self {
+        Self {
+            service: event.service_name.clone(),
+            model: event.model.clone(),
+            entry_at: event.timestamp,
+            first_seen: now,
+            last_seen: now,
+            steps: 0,
+            latency_ms: 0.0,
+            cost_usd: 0.0,
+            tokens: 0,
+            errors: 0,
+        }
+    }
+
+    fn add(&mut self, event: &TelemetryEvent, now: DateTime<Utc>) {
+        if event.timestamp < self.entry_at {
+            self.service = event.service_name.clone();
+            self.model = event.model.clone();
+            self.entry_at = event.timestamp;
+        }
+        self.last_seen = self.last_seen.max(now);
+        self.steps += 1;
+        self.latency_ms += event.latency_ms;
+        self.cost_usd += event.cost_usd;
+        self.tokens += u64::from(event.total_tokens());
+        self.errors += usize::from(event.has_errors());
+    }
+
+    /// Chain metrics with the anomaly type each one raises
+    fn metrics(&self) -> [(&'static str, f64, AnomalyType); 3] {
+        [
+            (
+                "chain_latency_ms",
+                self.latency_ms,
+                AnomalyType::LatencySpike,
+            ),
+            ("chain_cost_usd", self.cost_usd, AnomalyType::CostAnomaly),
+            (
+                "chain_steps",
+                self.steps as f64,
+                AnomalyType::Custom(CHAIN_STEPS_ANOMALY.to_string()),
+            ),
+        ]
+    }
+}
+
+#[derive(Debug, Default)]
+struct Traces {
+    open: HashMap<String, OpenTrace>,
+    /// Recently evaluated traces, by when they were evaluated
+    closed: HashMap<String, DateTime<Utc>>,
+}
+
+/// Collects spans by trace and evaluates completed chains
+#[derive(Debug)]
+pub struct TraceAggregator {
+    config: TraceAggregatorConfig,
+    traces: Mutex<Traces>,
+    baselines: BaselineManager,
+}
+
+impl TraceAggregator {
+    /// Create a new aggregator
+    pub fn new(config: TraceAggregatorConfig) -> Result<Self> {
+        config.validate()?;
+        let baselines = BaselineManager::with_spec(config.window).with_clock(config.clock.clone());
+        Ok(Self {
+            config,
+            traces: Mutex::new(Traces::default()),
+            baselines,
+        })
+    }
+
+    /// Get the configuration
+    pub fn config(&self) -> &TraceAggregatorConfig {
+        &self.config
+    }
+
+    /// Number of traces collecting spans
+    pub fn open_traces(&self) -> usize {
+        self.traces.lock().unwrap().open.len()
+    }
+
+    /// Record a span
+    pub fn record(&self, event: &TelemetryEvent) {
+        self.record_at(event, self.config.clock.now());
+    }
+
+    /// Record a span arriving at the given time
+    pub fn record_at(&self, event: &TelemetryEvent, now: DateTime<Utc>) {
+        let Some(trace_id) = &event.trace_id else {
+            return;
+        };
+        let mut traces = self.traces.lock().unwrap();
+        if traces.closed.contains_key(trace_id) {
+            debug!(trace_id = %trace_id, "Dropping span of an evaluated trace");
+            metrics::counter!("sentinel_trace_spans_dropped_total", "reason" => "late")
+                .increment(1);
+            return;
+        }
+        if !traces.open.contains_key(trace_id) {
+            if traces.open.len() >= self.config.max_open_traces {
+                metrics::counter!("sentinel_trace_spans_dropped_total", "reason" => "capacity")
+                    .increment(1);
+                return;
+            }
+            traces
+                .open
+                .insert(trace_id.clone(), OpenTrace::new(event, now));
+            metrics::gauge!("sentinel_traces_open").set(traces.open.len() as f64);
+        }
+        if let Some(trace) = traces.open.get_mut(trace_id) {
+            trace.add(event, now);
+        }
+    }
+
+    /// Evaluate completed traces
+    pub fn check(&self) -> Vec<AnomalyEvent> {
+        self.check_at(self.config.clock.now())
+    }
+
+    /// Evaluate traces complete at the given time, returning anomalies for
+    /// chains well above their baselines
+    pub fn check_at(&self, now: DateTime<Utc>) -> Vec<AnomalyEvent> {
+        let idle = Duration::seconds(i64::try_from(self.config.idle_secs).unwrap_or(i64::MAX));
+        let max_duration =
+            Duration::seconds(i64::try_from(self.config.max_duration_secs).unwrap_or(i64::MAX));
+
+        let completed: Vec<(String, OpenTrace)> = {
+            let mut traces = self.traces.lock().unwrap();
+            traces.closed.retain(|_, at| now - *at < max_duration);
+            let ids: Vec<String> = traces
+                .open
+                .iter()
+                .filter(|(_, trace)| {
+                    now - trace.last_seen >= idle || now - trace.first_seen >= max_duration
+                })
+                .map(|(id, _)| id.clone())
+                .collect();
+            let completed = ids
+                .into_iter()
+                .filter_map(|id| traces.open.remove_entry(&id))
+                .collect::<Vec<_>>();
+            for (id, _) in &completed {
+                traces.closed.insert(id.clone(), now);
+            }
+            metrics::gauge!("sentinel_traces_open").set(traces.open.len() as f64);
+            completed
+        };
+
+        let mut anomalies = Vec::new();
+        for (trace_id, trace) in completed {
+            metrics::counter!("sentinel_traces_evaluated_total").increment(1);
+            if let Some(anomaly) = self.evaluate(&trace_id, &trace, now) {
+                anomalies.push(anomaly);
+            }
+            for (metric, value, _) in trace.metrics() {
+                let key = BaselineKey::new(trace.service.clone(), trace.model.clone(), metric);
+                if let Err(e) = self.baselines.update_at(key, value, now) {
+                    warn!(trace_id = %trace_id, "Failed to update chain baseline: {}", e);
+                }
+            }
+        }
+        anomalies
+    }
+
+    /// Anomaly for the chain metric furthest above its baseline, if any is
+    /// past the threshold
+    fn evaluate(
+        &self,
+        trace_id: &str,
+        trace: &OpenTrace,
+        now: DateTime<Utc>,
+    ) -> Option<AnomalyEvent> {
+        let mut worst: Option<(&str, f64, AnomalyType, Baseline, f64)> = None;
+        for (metric, value, anomaly_type) in trace.metrics() {
+            let key = BaselineKey::new(trace.service.clone(), trace.model.clone(), metric);
+            let Some(baseline) = self
+                .baselines
+                .get(&key)
+                .filter(|b| b.sample_count >= self.config.min_samples)
+            else {
+                continue;
+            };
+            let spread = baseline
+                .std_dev
+                .max(baseline.mean.abs() * MIN_RELATIVE_SPREAD);
+            if spread <= f64::EPSILON {
+                continue;
+            }
+            let sigma = (value - baseline.mean) / spread;
+            if sigma >= self.config.threshold_sigma
+                && worst.as_ref().map_or(true, |(.., worst)| sigma > *worst)
+            {
+                worst = Some((metric, value, anomaly_type, baseline, sigma));
+            }
+        }
+        let (metric, value, anomaly_type, baseline, sigma) = worst?;
+
+        warn!(
+            trace_id = %trace_id,
+            service = %trace.service,
+            model = %trace.model,
+            metric,
+            sigma,
+            "Anomalous chain detected"
+        );
+        let mut additional = HashMap::new();
+        additional.insert("steps".to_string(), serde_json::json!(trace.steps));
+        additional.insert(
+            "total_latency_ms".to_string(),
+            serde_json::json!(trace.latency_ms),
+        );
+        additional.insert(
+            "total_cost_usd".to_string(),
+            serde_json::json!(trace.cost_usd),
+        );
+        additional.insert("total_tokens".to_string(), serde_json::json!(trace.tokens));
+        additional.insert("errors".to_string(), serde_json::json!(trace.errors));
+
+        let mut anomaly = AnomalyEvent::new(
+            severity(sigma),
+            anomaly_type,
+            trace.service.clone(),
+            trace.model.clone(),
+            DetectionMethod::Custom("trace".to_string()),
+            (1.0 - 0.05 / 2.5_f64.powf(sigma - self.config.threshold_sigma)).clamp(0.5, 0.99),
+            AnomalyDetails {
+                metric: metric.to_string(),
+                value,
+                baseline: baseline.mean,
+                threshold: self.config.threshold_sigma,
+                deviation_sigma: Some(sigma),
+                additional,
+                observations: Vec::new(),
+            },
+            AnomalyContext {
+                trace_id: Some(trace_id.to_string()),
+                user_id: None,
+                region: None,
+                time_window: self.config.window.to_string(),
+                sample_count: baseline.sample_count,
+                window: baseline.window.clone(),
+                additional: HashMap::new(),
+            },
+        )
+        .with_root_cause(format!(
+            "Chain {} of {:.4} over {} steps is {:.1}σ above the usual {:.4}",
+            metric, value, trace.steps, sigma, baseline.mean
+        ))
+        .with_remediation("Inspect the trace for extra steps, retries or slow calls");
+        anomaly.timestamp = now;
+        Some(anomaly)
+    }
+}
+
+fn severity(sigma: f64) -> Severity {
+    if sigma >= 6.0 {
+        Severity::Critical
+    } else if sigma >= 4.0 {
+        Severity::High
+    } else {
+        Severity::Medium
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use llm_sentinel_fixtures::event;
+
+    fn span(trace_id: &str, latency: f64, cost: f64) -> TelemetryEvent {
+        event()
+            .service("agent")
+            .latency_ms(latency)
+            .cost_usd(cost)
+            .trace_id(trace_id)
+            .build()
+    }
+
+    fn aggregator() -> TraceAggregator {
+        TraceAggregator::new(TraceAggregatorConfig {
+            idle_secs: 30,
+            max_duration_secs: 600,
+            max_open_traces: 100,
+            min_samples: 20,
+            window: WindowSpec::samples(20),
+            ..Default::default()
+        })
+        .unwrap()
+    }
+
+    /// Record a chain at `at` and evaluate it once it is complete
+    fn chain(
+        aggregator: &TraceAggregator,
+        trace_id: &str,
+        spans: &[(f64, f64)],
+        at: DateTime<Utc>,
+    ) -> Vec<AnomalyEvent> {
+        for (latency, cost) in spans {
+            aggregator.record_at(&span(trace_id, *latency, *cost), at);
+        }
+        assert!(aggregator.check_at(at + Duration::seconds(10)).is_empty());
+        aggregator.check_at(at + Duration::seconds(30))
+    }
+
+    #[test]
+    fn test_evaluates_completed_chains() {
+        let aggregator = aggregator();
+        let start = Utc::now();
+        let at = |i: i64| start + Duration::seconds(i * 60);
+        for i in 0..20 {
+            let latency = 100.0 + (i % 5) as f64;
+            let spans = [(latency, 0.01); 3];
+            assert!(chain(&aggregator, &format!("t{}", i), &spans, at(i)).is_empty());
+        }
+
+        // Three slow steps
+        let anomalies = chain(&aggregator, "slow", &[(1000.0, 0.01); 3], at(20));
+        assert_eq!(anomalies.len(), 1);
+        let anomaly = &anomalies[0];
+        assert_eq!(anomaly.anomaly_type, AnomalyType::LatencySpike);
+        assert_eq!(anomaly.details.metric, "chain_latency_ms");
+        assert_eq!(anomaly.details.value, 3000.0);
+        assert_eq!(anomaly.context.trace_id.as_deref(), Some("slow"));
+        assert_eq!(anomaly.details.additional["steps"], 3);
+
+        // Usual latency and cost, spread over nine steps
+        let anomalies = chain(&aggregator, "long", &[(34.0, 0.01 / 3.0); 9], at(21));
+        assert_eq!(anomalies.len(), 1);
+        assert_eq!(anomalies[0].anomaly_type.to_string(), CHAIN_STEPS_ANOMALY);
+        assert_eq!(anomalies[0].details.value, 9.0);
+        assert_eq!(anomalies[0].severity, Severity::Critical);
+    }
+
+    #[test]
+    fn test_drops_late_spans_and_over_capacity() {
+        let aggregator = TraceAggregator::new(TraceAggregatorConfig {
+            max_open_traces: 1,
+            ..Default::default()
+        })
+        .unwrap();
+        let start = Utc::now();
+        aggregator.record_at(&span("a", 100.0, 0.01), start);
+        aggregator.record_at(&span("b", 100.0, 0.01), start);
+        aggregator.record_at(&span("a", 100.0, 0.01), start);
+        assert_eq!(aggregator.open_traces(), 1);
+
+        aggregator.check_at(start + Duration::seconds(30));
+        assert_eq!(aggregator.open_traces(), 0);
+
+        // A late span does not start a new chain
+        aggregator.record_at(&span("a", 100.0, 0.01), start + Duration::seconds(40));
+        assert_eq!(aggregator.open_traces(), 0);
+        aggregator.record_at(&span("b", 100.0, 0.01), start + Duration::seconds(40));
+        assert_eq!(aggregator.open_traces(), 1);
+
+        // Events outside a trace are ignored
+        let mut event = span("c", 100.0, 0.01);
+        event.trace_id = None;
+        aggregator.record_at(&event, start + Duration::seconds(40));
+        assert_eq!(aggregator.open_traces(), 1);
+    }
+
+    #[test]
+    fn test_invalid_config() {
+        let config = TraceAggregatorConfig {
+            idle_secs: 600,
+            max_duration_secs: 60,
+            ..Default::default()
+        };
+        assert!(TraceAggregator::new(config).is_err());
+    }
+}
@@ -0,0 +1,222 @@
+//! Model version tracking and drift attribution.
+//!
+//! Records the model version each service is served per model family and
+//! remembers the last version change. When a drift anomaly fires, a change
+//! inside the lookback window is attached to the anomaly as root-cause
+//! evidence, since a provider snapshot update is the most common cause of a
+//! sudden output shift.
+
+use chrono::{DateTime, Duration, Utc};
+use dashmap::DashMap;
+use llm_sentinel_core::{
+    events::{AnomalyEvent, TelemetryEvent},
+    types::ServiceId,
+};
+use serde::{Deserialize, Serialize};
+use tracing::info;
+
+/// A switch between two versions of a model family
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct VersionChange {
+    /// Model family (e.g. `gpt-4`)
+    pub family: String,
+    /// Previous version
+    pub from: String,
+    /// New version
+    pub to: String,
+    /// First event seen on the new version
+    pub changed_at: DateTime<Utc>,
+}
+
+/// Version state for one service/model family
+#[derive(Debug, Clone)]
+struct VersionState {
+    current: String,
+    last_change: Option<VersionChange>,
+}
+
+/// Tracks model version changes per service and model family
+#[derive(Debug)]
+pub struct ModelVersionTracker {
+    lookback: Duration,
+    states: DashMap<(ServiceId, String), VersionState>,
+}
+
+impl ModelVersionTracker {
+    /// Create a tracker attributing changes within `lookback_secs`
+    pub fn new(lookback_secs: u64) -> Self {
+        Self {
+            lookback: Duration::seconds(lookback_secs as i64),
+            states: DashMap::new(),
+        }
+    }
+
+    /// Record the version an event was served by
+    pub fn record(&self, event: &TelemetryEvent) {
+        let Some(version) = event.resolved_model_version() else {
+            return;
+        };
+
+        let key = (event.service_name.clone(), version.family.clone());
+        let mut state = self.states.entry(key).or_insert_with(|| VersionState {
+            current: version.version.clone(),
+            last_change: None,
+        });
+
+        if state.current != version.version {
+            info!(
+                service = %event.service_name,
+                family = %version.family,
+                from = %state.current,
+                to = %version.version,
+                "Model version changed"
+            );
+            metrics::counter!(
+                "sentinel_model_version_changes_total",
+                "family" => version.family.clone()
+            )
+            .increment(1);
+
+            let previous = std::mem::replace(&mut state.current, version.version.clone());
+            state.last_change = Some(VersionChange {
+                family: version.family,
+                from: previous,
+                to: version.version,
+                changed_at: event.timestamp,
+            });
+        }
+    }
+
+    /// Version change for the event's service/model within the lookback window
+    pub fn recent_change(&self, event: &TelemetryEvent) -> Option<VersionChange> {
+        let version = event.resolved_model_version()?;
+        let state = self
+            .states
+            .get(&(event.service_name.clone(), version.family))?;
+
+        state
+            .last_change
+            .clone()
+            .filter(|change| event.timestamp - change.changed_at <= self.lookback)
+    }
+
+    /// Attach version change evidence to a drift anomaly
+    ///
+    /// Non-drift anomalies and events without a parseable version are left
+    /// untouched.
+    pub fn attribute(&self, anomaly: &mut AnomalyEvent, event: &TelemetryEvent) {
+        if !anomaly.anomaly_type.is_drift() {
+            return;
+        }
+        let Some(version) = event.resolved_model_version() else {
+            return;
+        };
+
+        let context = &mut anomaly.context.additional;
+        context.insert("model_version".to_string(), version.version.clone());
+
+        match self.recent_change(event) {
+            Some(change) => {
+                context.insert("model_version_changed".to_string(), "true".to_string());
+                context.insert("model_version_previous".to_string(), change.from.clone());
+                context.insert(
+                    "model_version_changed_at".to_string(),
+                    change.changed_at.to_rfc3339(),
+                );
+
+                let evidence = format!(
+                    "{} version changed from {} to {} at {}",
+                    change.family,
+                    change.from,
+                    change.to,
+                    change.changed_at.to_rfc3339()
+                );
+                anomaly.root_cause = Some(match anomaly.root_cause.take() {
+                    Some(root_cause) => format!("{}; {}", root_cause, evidence),
+                    None => evidence,
+                });
+                anomaly.remediation.insert(
+                    0,
+                    format!(
+                        "Compare {} {} against {}; pin the previous version if the shift is a regression",
+                        change.family, change.to, change.from
+                    ),
+                );
+            }
+            None => {
+                context.insert("model_version_changed".to_string(), "false".to_string());
+            }
+        }
+    }
+
+    /// Clear all tracked versions
+    pub fn clear(&self) {
+        self.states.clear();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use llm_sentinel_core::types::{AnomalyType, DetectionMethod, Severity};
+    use llm_sentinel_fixtures::{anomaly, event};
+
+    fn create_test_event(model: &str, at: DateTime<Utc>) -> TelemetryEvent {
+        event().model(model).timestamp(at).build()
+    }
+
+    fn create_anomaly(anomaly_type: AnomalyType) -> AnomalyEvent {
+        anomaly()
+            .severity(Severity::Medium)
+            .anomaly_type(anomaly_type)
+            .model("gpt-4-1106")
+            .method(DetectionMethod::Psi)
+            .metric("response_tokens")
+            .value(0.0)
+            .baseline(0.0)
+            .threshold(0.25)
+            .deviation_sigma(None)
+            .build()
+            .with_root_cause("response_tokens distribution shifted")
+    }
+
+    #[test]
+    fn test_version_change_attributed() {
+        let tracker = ModelVersionTracker::new(3600);
+        let start = Utc::now();
+
+        tracker.record(&create_test_event("gpt-4-0613", start));
+        let changed_at = start + Duration::minutes(5);
+        let event = create_test_event("gpt-4-1106", changed_at);
+        tracker.record(&event);
+
+        let change = tracker.recent_change(&event).unwrap();
+        assert_eq!(change.from, "0613");
+        assert_eq!(change.to, "1106");
+        assert_eq!(change.changed_at, changed_at);
+
+        let mut anomaly = create_anomaly(AnomalyType::OutputDrift);
+        tracker.attribute(&mut anomaly, &event);
+        assert_eq!(anomaly.context.additional["model_version_changed"], "true");
+        assert_eq!(anomaly.context.additional["model_version_previous"], "0613");
+        assert!(anomaly.root_cause.unwrap().contains("from 0613 to 1106"));
+
+        // Outside the lookback window
+        let later = create_test_event("gpt-4-1106", changed_at + Duration::hours(2));
+        assert!(tracker.recent_change(&later).is_none());
+        let mut anomaly = create_anomaly(AnomalyType::OutputDrift);
+        tracker.attribute(&mut anomaly, &later);
+        assert_eq!(anomaly.context.additional["model_version_changed"], "false");
+    }
+
+    #[test]
+    fn test_non_drift_untouched() {
+        let tracker = ModelVersionTracker::new(3600);
+        let event = create_test_event("gpt-4-1106", Utc::now());
+        tracker.record(&event);
+
+        let mut anomaly = create_anomaly(AnomalyType::LatencySpike);
+        tracker.attribute(&mut anomaly, &event);
+        assert!(anomaly.context.additional.is_empty());
+    }
+}
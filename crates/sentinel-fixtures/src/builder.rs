@@ -0,0 +1,475 @@
+//! Builders for individual telemetry events and anomalies.
+
+use chrono::{DateTime, Utc};
+use llm_sentinel_core::{
+    events::{
+        AnomalyContext, AnomalyDetails, AnomalyEvent, PromptInfo, ResponseInfo, TelemetryEvent,
+    },
+    labels::Labels,
+    types::{AnomalyType, DetectionMethod, ModelId, ServiceId, Severity},
+};
+use std::collections::{BTreeMap, HashMap};
+
+/// Builds a [`TelemetryEvent`], defaulting every field a test does not
+/// care about
+///
+/// The defaults are a successful `gpt-4` request of the `test` service with
+/// 10 prompt and 20 response tokens, 100 ms latency and $0.01 cost.
+#[derive(Debug, Clone)]
+pub struct EventBuilder {
+    service: ServiceId,
+    model: ModelId,
+    prompt_text: String,
+    prompt_tokens: u32,
+    prompt_embedding: Option<Vec<f32>>,
+    response_text: String,
+    response_tokens: u32,
+    finish_reason: String,
+    latency_ms: f64,
+    cost_usd: f64,
+    latency_breakdown: BTreeMap<String, f64>,
+    environment: Option<String>,
+    timestamp: Option<DateTime<Utc>>,
+    trace_id: Option<String>,
+    metadata: HashMap<String, String>,
+    labels: Labels,
+    errors: Vec<String>,
+}
+
+impl Default for EventBuilder {
+    fn default() -> Self {
+        Self {
+            service: ServiceId::new("test"),
+            model: ModelId::new("gpt-4"),
+            prompt_text: "test".to_string(),
+            prompt_tokens: 10,
+            prompt_embedding: None,
+            response_text: "response".to_string(),
+            response_tokens: 20,
+            finish_reason: "stop".to_string(),
+            latency_ms: 100.0,
+            cost_usd: 0.01,
+            latency_breakdown: BTreeMap::new(),
+            environment: None,
+            timestamp: None,
+            trace_id: None,
+            metadata: HashMap::new(),
+            labels: Labels::new(),
+            errors: Vec::new(),
+        }
+    }
+}
+
+/// Start building an event from the defaults
+pub fn event() -> EventBuilder {
+    EventBuilder::default()
+}
+
+impl EventBuilder {
+    /// Start building an event from the defaults
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Set the service
+    pub fn service(mut self, service: impl Into<ServiceId>) -> Self {
+        self.service = service.into();
+        self
+    }
+
+    /// Set the model
+    pub fn model(mut self, model: impl Into<ModelId>) -> Self {
+        self.model = model.into();
+        self
+    }
+
+    /// Set the prompt text
+    pub fn prompt(mut self, text: impl Into<String>) -> Self {
+        self.prompt_text = text.into();
+        self
+    }
+
+    /// Set the prompt token count
+    pub fn prompt_tokens(mut self, tokens: u32) -> Self {
+        self.prompt_tokens = tokens;
+        self
+    }
+
+    /// Set the prompt embedding
+    pub fn prompt_embedding(mut self, embedding: Vec<f32>) -> Self {
+        self.prompt_embedding = Some(embedding);
+        self
+    }
+
+    /// Set the response text
+    pub fn response(mut self, text: impl Into<String>) -> Self {
+        self.response_text = text.into();
+        self
+    }
+
+    /// Set the response token count
+    pub fn response_tokens(mut self, tokens: u32) -> Self {
+        self.response_tokens = tokens;
+        self
+    }
+
+    /// Set both token counts
+    pub fn tokens(self, prompt: u32, response: u32) -> Self {
+        self.prompt_tokens(prompt).response_tokens(response)
+    }
+
+    /// Set the finish reason (e.g. `length`, `content_filter`)
+    pub fn finish_reason(mut self, reason: impl Into<String>) -> Self {
+        self.finish_reason = reason.into();
+        self
+    }
+
+    /// Set the request latency (ms)
+    pub fn latency_ms(mut self, latency_ms: f64) -> Self {
+        self.latency_ms = latency_ms;
+        self
+    }
+
+    /// Set the request cost (USD)
+    pub fn cost_usd(mut self, cost_usd: f64) -> Self {
+        self.cost_usd = cost_usd;
+        self
+    }
+
+    /// Add the latency of a request component (e.g. `retrieval`)
+    pub fn latency_component(mut self, component: impl Into<String>, latency_ms: f64) -> Self {
+        self.latency_breakdown.insert(component.into(), latency_ms);
+        self
+    }
+
+    /// Set the deployment environment
+    pub fn environment(mut self, environment: impl Into<String>) -> Self {
+        self.environment = Some(environment.into());
+        self
+    }
+
+    /// Set the event timestamp (now by default)
+    pub fn timestamp(mut self, timestamp: DateTime<Utc>) -> Self {
+        self.timestamp = Some(timestamp);
+        self
+    }
+
+    /// Set the trace ID
+    pub fn trace_id(mut self, trace_id: impl Into<String>) -> Self {
+        self.trace_id = Some(trace_id.into());
+        self
+    }
+
+    /// Add a metadata entry
+    pub fn metadata(mut self, key: impl Into<String>, value: impl Into<String>) -> Self {
+        self.metadata.insert(key.into(), value.into());
+        self
+    }
+
+    /// Add a label
+    pub fn label(mut self, key: impl Into<String>, value: impl Into<String>) -> Self {
+        self.labels.insert(key.into(), value.into());
+        self
+    }
+
+    /// Add an error, marking the request as failed
+    pub fn error(mut self, error: impl Into<String>) -> Self {
+        self.errors.push(error.into());
+        self
+    }
+
+    /// Build the event
+    pub fn build(self) -> TelemetryEvent {
+        let mut event = TelemetryEvent::new(
+            self.service,
+            self.model,
+            PromptInfo {
+                text: self.prompt_text,
+                tokens: self.prompt_tokens,
+                embedding: self.prompt_embedding,
+            },
+            ResponseInfo {
+                text: self.response_text,
+                tokens: self.response_tokens,
+                finish_reason: self.finish_reason,
+                embedding: None,
+            },
+            self.latency_ms,
+            self.cost_usd,
+        );
+        if let Some(environment) = self.environment {
+            event = event.with_environment(environment);
+        }
+        if let Some(timestamp) = self.timestamp {
+            event.timestamp = timestamp;
+        }
+        event.trace_id = self.trace_id;
+        event.latency_breakdown = self.latency_breakdown;
+        event.metadata = self.metadata;
+        event.labels = self.labels;
+        event.errors = self.errors;
+        event
+    }
+}
+
+/// Builds an [`AnomalyEvent`], defaulting every field a test does not
+/// care about
+///
+/// The defaults are a high severity z-score latency spike of `gpt-4` in the
+/// `test` service: 5000 ms against a 100 ms baseline (10 sigma over a
+/// threshold of 3) over the last 100 samples, with 0.9 confidence.
+#[derive(Debug, Clone)]
+pub struct AnomalyBuilder {
+    severity: Severity,
+    anomaly_type: AnomalyType,
+    service: ServiceId,
+    model: ModelId,
+    method: DetectionMethod,
+    confidence: f64,
+    metric: String,
+    value: f64,
+    baseline: f64,
+    threshold: f64,
+    deviation_sigma: Option<f64>,
+    trace_id: Option<String>,
+    time_window: String,
+    sample_count: usize,
+    timestamp: Option<DateTime<Utc>>,
+    labels: Labels,
+}
+
+impl Default for AnomalyBuilder {
+    fn default() -> Self {
+        Self {
+            severity: Severity::High,
+            anomaly_type: AnomalyType::LatencySpike,
+            service: ServiceId::new("test"),
+            model: ModelId::new("gpt-4"),
+            method: DetectionMethod::ZScore,
+            confidence: 0.9,
+            metric: "latency_ms".to_string(),
+            value: 5000.0,
+            baseline: 100.0,
+            threshold: 3.0,
+            deviation_sigma: Some(10.0),
+            trace_id: None,
+            time_window: "last_100_samples".to_string(),
+            sample_count: 100,
+            timestamp: None,
+            labels: Labels::new(),
+        }
+    }
+}
+
+/// Start building an anomaly from the defaults
+pub fn anomaly() -> AnomalyBuilder {
+    AnomalyBuilder::default()
+}
+
+impl AnomalyBuilder {
+    /// Start building an anomaly from the defaults
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Set the severity
+    pub fn severity(mut self, severity: Severity) -> Self {
+        self.severity = severity;
+        self
+    }
+
+    /// Set the anomaly type
+    pub fn anomaly_type(mut self, anomaly_type: AnomalyType) -> Self {
+        self.anomaly_type = anomaly_type;
+        self
+    }
+
+    /// Set the service
+    pub fn service(mut self, service: impl Into<ServiceId>) -> Self {
+        self.service = service.into();
+        self
+    }
+
+    /// Set the model
+    pub fn model(mut self, model: impl Into<ModelId>) -> Self {
+        self.model = model.into();
+        self
+    }
+
+    /// Set the detection method
+    pub fn method(mut self, method: DetectionMethod) -> Self {
+        self.method = method;
+        self
+    }
+
+    /// Set the confidence score
+    pub fn confidence(mut self, confidence: f64) -> Self {
+        self.confidence = confidence;
+        self
+    }
+
+    /// Set the anomalous metric (e.g. `cost_usd`)
+    pub fn metric(mut self, metric: impl Into<String>) -> Self {
+        self.metric = metric.into();
+        self
+    }
+
+    /// Set the observed value
+    pub fn value(mut self, value: f64) -> Self {
+        self.value = value;
+        self
+    }
+
+    /// Set the baseline value
+    pub fn baseline(mut self, baseline: f64) -> Self {
+        self.baseline = baseline;
+        self
+    }
+
+    /// Set the detection threshold
+    pub fn threshold(mut self, threshold: f64) -> Self {
+        self.threshold = threshold;
+        self
+    }
+
+    /// Set the deviation from the baseline in standard deviations
+    pub fn deviation_sigma(mut self, sigma: Option<f64>) -> Self {
+        self.deviation_sigma = sigma;
+        self
+    }
+
+    /// Set the trace ID
+    pub fn trace_id(mut self, trace_id: impl Into<String>) -> Self {
+        self.trace_id = Some(trace_id.into());
+        self
+    }
+
+    /// Set the description of the detection window (e.g. `rolling_window`)
+    pub fn time_window(mut self, time_window: impl Into<String>) -> Self {
+        self.time_window = time_window.into();
+        self
+    }
+
+    /// Set the number of samples in the detection window
+    pub fn sample_count(mut self, sample_count: usize) -> Self {
+        self.sample_count = sample_count;
+        self
+    }
+
+    /// Set the detection timestamp (now by default)
+    pub fn timestamp(mut self, timestamp: DateTime<Utc>) -> Self {
+        self.timestamp = Some(timestamp);
+        self
+    }
+
+    /// Add a label
+    pub fn label(mut self, key: impl Into<String>, value: impl Into<String>) -> Self {
+        self.labels.insert(key.into(), value.into());
+        self
+    }
+
+    /// Build the anomaly
+    pub fn build(self) -> AnomalyEvent {
+        let mut anomaly = AnomalyEvent::new(
+            self.severity,
+            self.anomaly_type,
+            self.service,
+            self.model,
+            self.method,
+            self.confidence,
+            AnomalyDetails {
+                metric: self.metric,
+                value: self.value,
+                baseline: self.baseline,
+                threshold: self.threshold,
+                deviation_sigma: self.deviation_sigma,
+                additional: HashMap::new(),
+                observations: Vec::new(),
+            },
+            AnomalyContext {
+                trace_id: self.trace_id,
+                user_id: None,
+                region: None,
+                time_window: self.time_window,
+                sample_count: self.sample_count,
+                window: None,
+                additional: HashMap::new(),
+            },
+        );
+        if let Some(timestamp) = self.timestamp {
+            anomaly.timestamp = timestamp;
+        }
+        anomaly.labels = self.labels;
+        anomaly
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_defaults_and_overrides() {
+        let default = event().build();
+        assert_eq!(default.service_name.as_str(), "test");
+        assert_eq!(default.model.as_str(), "gpt-4");
+        assert_eq!(default.prompt.tokens, 10);
+        assert_eq!(default.response.tokens, 20);
+        assert_eq!(default.latency_ms, 100.0);
+        assert!(default.errors.is_empty());
+
+        let event = event()
+            .service("search")
+            .tokens(500, 50)
+            .latency_ms(900.0)
+            .latency_component("retrieval", 600.0)
+            .environment("staging")
+            .label("team", "search")
+            .error("timeout")
+            .build();
+        assert_eq!(event.service_name.as_str(), "search");
+        assert_eq!(event.prompt.tokens, 500);
+        assert_eq!(event.latency_breakdown["retrieval"], 600.0);
+        assert_eq!(event.environment.unwrap().as_str(), "staging");
+        assert_eq!(event.labels["team"], "search");
+        assert_eq!(event.errors, vec!["timeout".to_string()]);
+    }
+
+    #[test]
+    fn test_anomaly_defaults_and_overrides() {
+        let default = anomaly().build();
+        assert_eq!(default.severity, Severity::High);
+        assert_eq!(default.anomaly_type, AnomalyType::LatencySpike);
+        assert_eq!(default.service_name.as_str(), "test");
+        assert_eq!(default.details.metric, "latency_ms");
+        assert_eq!(default.details.value, 5000.0);
+        assert!(default.context.trace_id.is_none());
+
+        let anomaly = anomaly()
+            .severity(Severity::Low)
+            .anomaly_type(AnomalyType::CostAnomaly)
+            .service("checkout")
+            .metric("cost_usd")
+            .value(5.0)
+            .deviation_sigma(None)
+            .trace_id("trace-1")
+            .label("team", "payments")
+            .build();
+        assert_eq!(anomaly.severity, Severity::Low);
+        assert_eq!(anomaly.service_name.as_str(), "checkout");
+        assert_eq!(anomaly.details.metric, "cost_usd");
+        assert!(anomaly.details.deviation_sigma.is_none());
+        assert_eq!(anomaly.context.trace_id.as_deref(), Some("trace-1"));
+        assert_eq!(anomaly.labels["team"], "payments");
+        // The signature covers the overridden service and metric
+        assert_eq!(
+            anomaly.signature_hash,
+            llm_sentinel_core::events::signature_hash(
+                &anomaly.service_name,
+                &anomaly.model,
+                &anomaly.anomaly_type,
+                &anomaly.details.metric
+            )
+        );
+    }
+}
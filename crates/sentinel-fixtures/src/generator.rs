@@ -0,0 +1,408 @@
+//! Seeded generator of realistic telemetry.
+//!
+//! Token counts are log-normal around a median, and latency and cost follow
+//! from the model: cost is the model's per-token prices times the tokens,
+//! latency a fixed overhead plus generation time per response token, with
+//! log-normal jitter. A share of events can be injected with an anomaly;
+//! injected events carry the [`INJECTED_LABEL`] label naming the injection,
+//! so tests can check which of them a detector caught. The same seed always
+//! generates the same events.
+
+use crate::builder::EventBuilder;
+use chrono::{DateTime, Duration, TimeZone, Utc};
+use llm_sentinel_core::events::TelemetryEvent;
+use rand::{rngs::StdRng, Rng, SeedableRng};
+
+/// Label naming the anomaly injected into an event
+pub const INJECTED_LABEL: &str = "fixture.injected";
+
+const WORDS: &[&str] = &[
+    "summarize",
+    "the",
+    "quarterly",
+    "report",
+    "and",
+    "list",
+    "open",
+    "risks",
+    "for",
+    "our",
+    "customer",
+    "support",
+    "team",
+    "with",
+    "examples",
+    "from",
+    "recent",
+    "tickets",
+];
+
+/// Prices, latency and context window of a model
+#[derive(Debug, Clone, PartialEq)]
+pub struct ModelProfile {
+    /// Model identifier
+    pub model: String,
+    /// Price of 1k prompt tokens (USD)
+    pub prompt_cost_per_1k: f64,
+    /// Price of 1k response tokens (USD)
+    pub response_cost_per_1k: f64,
+    /// Latency before the first token (ms)
+    pub base_latency_ms: f64,
+    /// Generation time per response token (ms)
+    pub latency_ms_per_token: f64,
+    /// Context window (tokens)
+    pub context_window: u32,
+}
+
+impl ModelProfile {
+    /// GPT-4 (8k context)
+    pub fn gpt_4() -> Self {
+        Self {
+            model: "gpt-4".to_string(),
+            prompt_cost_per_1k: 0.03,
+            response_cost_per_1k: 0.06,
+            base_latency_ms: 400.0,
+            latency_ms_per_token: 25.0,
+            context_window: 8_192,
+        }
+    }
+
+    /// GPT-3.5 Turbo
+    pub fn gpt_35_turbo() -> Self {
+        Self {
+            model: "gpt-3.5-turbo".to_string(),
+            prompt_cost_per_1k: 0.0005,
+            response_cost_per_1k: 0.0015,
+            base_latency_ms: 200.0,
+            latency_ms_per_token: 8.0,
+            context_window: 16_385,
+        }
+    }
+
+    /// Claude 3 Sonnet
+    pub fn claude_3_sonnet() -> Self {
+        Self {
+            model: "claude-3-sonnet".to_string(),
+            prompt_cost_per_1k: 0.003,
+            response_cost_per_1k: 0.015,
+            base_latency_ms: 300.0,
+            latency_ms_per_token: 15.0,
+            context_window: 200_000,
+        }
+    }
+
+    /// Cost of a request (USD)
+    pub fn cost_usd(&self, prompt_tokens: u32, response_tokens: u32) -> f64 {
+        (prompt_tokens as f64 * self.prompt_cost_per_1k
+            + response_tokens as f64 * self.response_cost_per_1k)
+            / 1000.0
+    }
+
+    /// Latency of a request without jitter (ms)
+    pub fn latency_ms(&self, response_tokens: u32) -> f64 {
+        self.base_latency_ms + response_tokens as f64 * self.latency_ms_per_token
+    }
+}
+
+/// Anomaly injected into generated events
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Injection {
+    /// Latency multiplied by `factor`
+    LatencySpike {
+        /// Latency multiplier
+        factor: f64,
+    },
+    /// Cost multiplied by `factor`, tokens unchanged (e.g. a pricing bug)
+    CostSpike {
+        /// Cost multiplier
+        factor: f64,
+    },
+    /// Response tokens multiplied by `factor`, with latency and cost
+    /// following
+    TokenSurge {
+        /// Response token multiplier
+        factor: f64,
+    },
+    /// Failed request
+    Error,
+}
+
+impl Injection {
+    /// Value of the [`INJECTED_LABEL`] label
+    pub fn name(&self) -> &'static str {
+        match self {
+            Injection::LatencySpike { .. } => "latency_spike",
+            Injection::CostSpike { .. } => "cost_spike",
+            Injection::TokenSurge { .. } => "token_surge",
+            Injection::Error => "error",
+        }
+    }
+}
+
+/// Anomaly injected into the event, if any
+pub fn injected(event: &TelemetryEvent) -> Option<&str> {
+    event.labels.get(INJECTED_LABEL).map(String::as_str)
+}
+
+/// Endless, reproducible stream of telemetry events
+///
+/// Events are spread over the configured services and models in turn and
+/// timestamped `interval` apart from `start`.
+#[derive(Debug, Clone)]
+pub struct TelemetryGenerator {
+    rng: StdRng,
+    services: Vec<String>,
+    models: Vec<ModelProfile>,
+    prompt_tokens: (f64, f64),
+    response_tokens: (f64, f64),
+    latency_jitter: f64,
+    error_rate: f64,
+    injections: Vec<(Injection, f64)>,
+    start: DateTime<Utc>,
+    interval: Duration,
+    generated: u64,
+}
+
+impl TelemetryGenerator {
+    /// Generator of `chat` traffic to GPT-4 and GPT-3.5 Turbo, starting at
+    /// 2024-01-01 with one event a second and no anomalies
+    pub fn new(seed: u64) -> Self {
+        Self {
+            rng: StdRng::seed_from_u64(seed),
+            services: vec!["chat".to_string()],
+            models: vec![ModelProfile::gpt_4(), ModelProfile::gpt_35_turbo()],
+            prompt_tokens: (400.0, 0.6),
+            response_tokens: (150.0, 0.5),
+            latency_jitter: 0.15,
+            error_rate: 0.0,
+            injections: Vec::new(),
+            start: Utc.with_ymd_and_hms(2024, 1, 1, 0, 0, 0).unwrap(),
+            interval: Duration::seconds(1),
+            generated: 0,
+        }
+    }
+
+    /// Services to generate events for
+    pub fn services<S: Into<String>>(mut self, services: impl IntoIterator<Item = S>) -> Self {
+        self.services = services.into_iter().map(Into::into).collect();
+        assert!(!self.services.is_empty(), "at least one service is needed");
+        self
+    }
+
+    /// Models to generate events for
+    pub fn models(mut self, models: impl IntoIterator<Item = ModelProfile>) -> Self {
+        self.models = models.into_iter().collect();
+        assert!(!self.models.is_empty(), "at least one model is needed");
+        self
+    }
+
+    /// Median prompt tokens, and the spread (log-normal sigma) around it
+    pub fn prompt_tokens(mut self, median: f64, spread: f64) -> Self {
+        self.prompt_tokens = (median, spread);
+        self
+    }
+
+    /// Median response tokens, and the spread (log-normal sigma) around it
+    pub fn response_tokens(mut self, median: f64, spread: f64) -> Self {
+        self.response_tokens = (median, spread);
+        self
+    }
+
+    /// Log-normal sigma of latency around the model's latency
+    pub fn latency_jitter(mut self, sigma: f64) -> Self {
+        self.latency_jitter = sigma;
+        self
+    }
+
+    /// Share of ordinary requests that fail
+    pub fn error_rate(mut self, rate: f64) -> Self {
+        self.error_rate = rate;
+        self
+    }
+
+    /// Inject `injection` into a share `rate` of events
+    ///
+    /// At most one injection is applied per event, the first one drawn in
+    /// the order they were added.
+    pub fn inject(mut self, injection: Injection, rate: f64) -> Self {
+        self.injections.push((injection, rate));
+        self
+    }
+
+    /// Timestamp of the first event
+    pub fn start(mut self, start: DateTime<Utc>) -> Self {
+        self.start = start;
+        self
+    }
+
+    /// Time between events
+    pub fn interval(mut self, interval: Duration) -> Self {
+        self.interval = interval;
+        self
+    }
+
+    /// Standard normal draw (Box-Muller)
+    fn normal(&mut self) -> f64 {
+        let u1: f64 = 1.0 - self.rng.gen::<f64>();
+        let u2: f64 = self.rng.gen();
+        (-2.0 * u1.ln()).sqrt() * (2.0 * std::f64::consts::PI * u2).cos()
+    }
+
+    fn log_normal(&mut self, median: f64, sigma: f64) -> f64 {
+        median * (sigma * self.normal()).exp()
+    }
+
+    fn tokens(&mut self, (median, spread): (f64, f64)) -> u32 {
+        self.log_normal(median, spread).round().max(1.0) as u32
+    }
+
+    fn text(&mut self, tokens: u32) -> String {
+        // About three words per four tokens, capped to keep events small
+        let words = (tokens as usize * 3 / 4).clamp(1, 200);
+        (0..words)
+            .map(|_| WORDS[self.rng.gen_range(0..WORDS.len())])
+            .collect::<Vec<_>>()
+            .join(" ")
+    }
+}
+
+impl Iterator for TelemetryGenerator {
+    type Item = TelemetryEvent;
+
+    fn next(&mut self) -> Option<TelemetryEvent> {
+        let index = self.generated;
+        self.generated += 1;
+        let service = self.services[index as usize % self.services.len()].clone();
+        let profile =
+            self.models[(index as usize / self.services.len()) % self.models.len()].clone();
+
+        let injection = self
+            .injections
+            .clone()
+            .into_iter()
+            .find(|(_, rate)| self.rng.gen::<f64>() < *rate)
+            .map(|(injection, _)| injection);
+
+        let prompt_tokens = self
+            .tokens(self.prompt_tokens)
+            .min(profile.context_window / 2);
+        let mut response_tokens = self
+            .tokens(self.response_tokens)
+            .min(profile.context_window - prompt_tokens);
+        if let Some(Injection::TokenSurge { factor }) = injection {
+            response_tokens = ((response_tokens as f64 * factor).round() as u32)
+                .min(profile.context_window - prompt_tokens);
+        }
+
+        let jitter = self.latency_jitter;
+        let mut latency_ms = self.log_normal(profile.latency_ms(response_tokens), jitter);
+        let mut cost_usd = profile.cost_usd(prompt_tokens, response_tokens);
+        let failed = injection == Some(Injection::Error)
+            || (injection.is_none() && self.rng.gen::<f64>() < self.error_rate);
+        match injection {
+            Some(Injection::LatencySpike { factor }) => latency_ms *= factor,
+            Some(Injection::CostSpike { factor }) => cost_usd *= factor,
+            _ => {}
+        }
+
+        let prompt = self.text(prompt_tokens);
+        let response = self.text(response_tokens);
+        let mut builder = EventBuilder::new()
+            .service(service)
+            .model(profile.model.as_str())
+            .prompt(prompt)
+            .response(response)
+            .tokens(prompt_tokens, response_tokens)
+            .latency_ms(latency_ms)
+            .cost_usd(cost_usd)
+            .timestamp(self.start + self.interval * index as i32);
+        if failed {
+            builder = builder
+                .error("upstream request failed")
+                .finish_reason("error");
+        }
+        if let Some(injection) = injection {
+            builder = builder.label(INJECTED_LABEL, injection.name());
+        }
+
+        let mut event = builder.build();
+        event.event_id = uuid::Builder::from_random_bytes(self.rng.gen()).into_uuid();
+        Some(event)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use uuid::Uuid;
+
+    #[test]
+    fn test_generation_is_reproducible_and_realistic() {
+        let first: Vec<_> = TelemetryGenerator::new(7).take(200).collect();
+        let second: Vec<_> = TelemetryGenerator::new(7).take(200).collect();
+        for (a, b) in first.iter().zip(&second) {
+            assert_eq!(a.event_id, b.event_id);
+            assert_eq!(a.latency_ms, b.latency_ms);
+            assert_eq!(a.prompt.text, b.prompt.text);
+        }
+        assert_ne!(first[0].event_id, Uuid::nil());
+        assert_eq!(
+            first[1].timestamp - first[0].timestamp,
+            Duration::seconds(1)
+        );
+
+        let gpt_4 = ModelProfile::gpt_4();
+        for event in first.iter().filter(|e| e.model.as_str() == "gpt-4") {
+            let cost = gpt_4.cost_usd(event.prompt.tokens, event.response.tokens);
+            assert!((event.cost_usd - cost).abs() < 1e-12);
+            assert!(event.prompt.tokens + event.response.tokens <= gpt_4.context_window);
+            assert!(injected(event).is_none());
+        }
+        // GPT-3.5 Turbo is cheaper and faster on average
+        let mean = |model: &str, f: fn(&TelemetryEvent) -> f64| {
+            let values: Vec<f64> = first
+                .iter()
+                .filter(|e| e.model.as_str() == model)
+                .map(f)
+                .collect();
+            values.iter().sum::<f64>() / values.len() as f64
+        };
+        assert!(mean("gpt-3.5-turbo", |e| e.latency_ms) < mean("gpt-4", |e| e.latency_ms));
+        assert!(mean("gpt-3.5-turbo", |e| e.cost_usd) < mean("gpt-4", |e| e.cost_usd));
+    }
+
+    #[test]
+    fn test_injected_anomalies_are_labelled() {
+        let events: Vec<_> = TelemetryGenerator::new(1)
+            .services(["chat", "search"])
+            .models([ModelProfile::claude_3_sonnet()])
+            .inject(Injection::LatencySpike { factor: 10.0 }, 0.05)
+            .inject(Injection::Error, 0.05)
+            .take(2000)
+            .collect();
+
+        assert_eq!(events[1].service_name.as_str(), "search");
+        let spikes: Vec<_> = events
+            .iter()
+            .filter(|e| injected(e) == Some("latency_spike"))
+            .collect();
+        let errors: Vec<_> = events
+            .iter()
+            .filter(|e| injected(e) == Some("error"))
+            .collect();
+        assert!((50..150).contains(&spikes.len()), "{}", spikes.len());
+        assert!((50..150).contains(&errors.len()), "{}", errors.len());
+        assert!(errors.iter().all(|e| !e.errors.is_empty()));
+
+        let profile = ModelProfile::claude_3_sonnet();
+        let slow = spikes
+            .iter()
+            .filter(|e| e.latency_ms > 3.0 * profile.latency_ms(e.response.tokens))
+            .count();
+        assert!(slow * 10 >= spikes.len() * 9);
+        assert!(events
+            .iter()
+            .filter(|e| injected(e).is_none())
+            .all(|e| e.errors.is_empty()));
+    }
+}
@@ -0,0 +1,22 @@
+//! # Sentinel Fixtures
+//!
+//! Telemetry test fixtures shared by the LLM-Sentinel crates.
+//!
+//! This crate provides:
+//! - [`EventBuilder`] for single events, defaulting every field a test does
+//!   not set
+//! - [`AnomalyBuilder`] for single anomalies, likewise
+//! - [`TelemetryGenerator`], a seeded stream of realistic events: log-normal
+//!   token counts, latency and cost following from per-model profiles, and
+//!   optional anomaly injection labelled on the injected events
+//!
+//! It is a development dependency only and is not published. It depends on
+//! `llm-sentinel-core`, so core's own tests cannot use it.
+
+#![warn(missing_debug_implementations, rust_2018_idioms, unreachable_pub)]
+
+pub mod builder;
+pub mod generator;
+
+pub use builder::{anomaly, event, AnomalyBuilder, EventBuilder};
+pub use generator::{injected, Injection, ModelProfile, TelemetryGenerator, INJECTED_LABEL};
@@ -0,0 +1,29 @@
+//! Fuzz OTLP span parsing and event validation.
+//!
+//! Any JSON document must either parse into an event that validates (or is
+//! rejected with a validation error), or fail with an ingestion error.
+
+#![no_main]
+
+use libfuzzer_sys::fuzz_target;
+use llm_sentinel_core::Error;
+use llm_sentinel_ingestion::{otlp::OtlpParser, validation::EventValidator};
+
+fuzz_target!(|data: &[u8]| {
+    let Ok(span) = serde_json::from_slice::<serde_json::Value>(data) else {
+        return;
+    };
+
+    match OtlpParser::new(256).parse_span(&span) {
+        Ok(mut event) => {
+            let validator = EventValidator::default();
+            match validator.validate(&event) {
+                Ok(()) | Err(Error::Validation(_)) => {}
+                Err(e) => panic!("unexpected validation error: {e:?}"),
+            }
+            validator.sanitize(&mut event).unwrap();
+        }
+        Err(Error::Ingestion(_)) => {}
+        Err(e) => panic!("unexpected parse error: {e:?}"),
+    }
+});
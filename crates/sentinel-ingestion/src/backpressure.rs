@@ -0,0 +1,140 @@
+//! Back-pressure from the detection queue to the ingester.
+//!
+//! Left alone, an ingester keeps fetching while detection or storage falls
+//! behind: consumed events pile up in memory waiting for queue space while
+//! consumer lag hides in the process. [`Backpressure`] watches the queue
+//! instead and tells the pipeline to pause the ingester once the queue fills
+//! past the high watermark and to resume it once the queue drains below the
+//! low watermark, leaving unconsumed telemetry in the broker.
+//!
+//! The state is exported as the `sentinel_ingestion_paused` gauge; pauses
+//! and resumes are counted in `sentinel_ingestion_paused_total` and
+//! `sentinel_ingestion_resumed_total`, and the time spent paused is recorded
+//! in the `sentinel_ingestion_paused_seconds` histogram.
+
+use llm_sentinel_core::{config::BackpressureConfig, Error, Result};
+use std::sync::Mutex;
+use std::time::Instant;
+use tracing::{info, warn};
+
+/// What the pipeline should do with its ingester
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BackpressureAction {
+    /// Stop fetching new events
+    Pause,
+    /// Fetch events again
+    Resume,
+}
+
+/// Decides when ingestion pauses for a backed-up detection queue
+#[derive(Debug)]
+pub struct Backpressure {
+    config: BackpressureConfig,
+    capacity: usize,
+    /// When ingestion was paused, if it is
+    paused_at: Mutex<Option<Instant>>,
+}
+
+impl Backpressure {
+    /// Create a controller for a detection queue holding `capacity` events
+    pub fn from_config(config: &BackpressureConfig, capacity: usize) -> Result<Self> {
+        if capacity == 0 {
+            return Err(Error::config(
+                "Ingestion back-pressure needs a bounded detection queue",
+            ));
+        }
+        if config.low_watermark >= config.high_watermark {
+            return Err(Error::config(
+                "Ingestion back-pressure low_watermark must be below high_watermark",
+            ));
+        }
+
+        metrics::gauge!("sentinel_ingestion_paused").set(0.0);
+        Ok(Self {
+            config: config.clone(),
+            capacity,
+            paused_at: Mutex::new(None),
+        })
+    }
+
+    /// Whether ingestion is paused
+    pub fn is_paused(&self) -> bool {
+        self.paused_at.lock().unwrap().is_some()
+    }
+
+    /// Check the queue, returning the action to take on the ingester when
+    /// ingestion should pause or resume
+    pub fn update(&self, queue_depth: usize) -> Option<BackpressureAction> {
+        let fill = (queue_depth as f64 / self.capacity as f64).min(1.0);
+        let mut paused_at = self.paused_at.lock().unwrap();
+        match *paused_at {
+            None if fill >= self.config.high_watermark => {
+                warn!(
+                    queue_depth,
+                    queue_fill = fill,
+                    "Detection queue backed up, pausing ingestion"
+                );
+                metrics::gauge!("sentinel_ingestion_paused").set(1.0);
+                metrics::counter!("sentinel_ingestion_paused_total").increment(1);
+                *paused_at = Some(Instant::now());
+                Some(BackpressureAction::Pause)
+            }
+            Some(since) if fill < self.config.low_watermark => {
+                let paused = since.elapsed();
+                info!(
+                    queue_depth,
+                    queue_fill = fill,
+                    paused_ms = paused.as_millis() as u64,
+                    "Detection queue drained, resuming ingestion"
+                );
+                metrics::gauge!("sentinel_ingestion_paused").set(0.0);
+                metrics::counter!("sentinel_ingestion_resumed_total").increment(1);
+                metrics::histogram!("sentinel_ingestion_paused_seconds")
+                    .record(paused.as_secs_f64());
+                *paused_at = None;
+                Some(BackpressureAction::Resume)
+            }
+            _ => None,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn config() -> BackpressureConfig {
+        BackpressureConfig {
+            high_watermark: 0.8,
+            low_watermark: 0.5,
+        }
+    }
+
+    #[test]
+    fn test_pause_hysteresis() {
+        let backpressure = Backpressure::from_config(&config(), 100).unwrap();
+        assert_eq!(backpressure.update(79), None);
+        assert!(!backpressure.is_paused());
+
+        assert_eq!(backpressure.update(80), Some(BackpressureAction::Pause));
+        assert!(backpressure.is_paused());
+        // Paused once, and until the queue drains below the low watermark
+        assert_eq!(backpressure.update(100), None);
+        assert_eq!(backpressure.update(50), None);
+        assert!(backpressure.is_paused());
+
+        assert_eq!(backpressure.update(49), Some(BackpressureAction::Resume));
+        assert!(!backpressure.is_paused());
+        assert_eq!(backpressure.update(0), None);
+    }
+
+    #[test]
+    fn test_invalid_config() {
+        assert!(Backpressure::from_config(&config(), 0).is_err());
+        let inverted = BackpressureConfig {
+            low_watermark: 0.9,
+            ..config()
+        };
+        assert!(Backpressure::from_config(&inverted, 100).is_err());
+    }
+}
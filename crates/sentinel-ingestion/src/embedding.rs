@@ -0,0 +1,363 @@
+//! Embedding computation for events sent without embeddings.
+//!
+//! Drift and similarity detection need prompt embeddings, but most
+//! producers do not compute them. The [`Embedder`] fills them in from an
+//! OpenAI-compatible embeddings service before detection: the distinct texts
+//! of a batch are sent in requests of up to `batch_size` texts, so a GPU
+//! backend sees full batches, with at most `max_concurrency` requests in
+//! flight across all batches. Embeddings of recently seen texts are served
+//! from a cache, which matters for templated and retried prompts.
+//!
+//! Tokens billed and their cost are counted in
+//! `sentinel_embedding_tokens_total` and `sentinel_embedding_cost_usd`. A
+//! failed request leaves its events without embeddings rather than holding
+//! up ingestion.
+
+use futures::future::join_all;
+use llm_sentinel_core::{config::EmbeddingConfig, events::TelemetryEvent, Error, Result};
+use moka::future::Cache;
+use reqwest::Client;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+use tokio::sync::Semaphore;
+use tracing::{debug, info, warn};
+
+/// Embeddings request body
+#[derive(Debug, Serialize)]
+struct EmbeddingRequest<'a> {
+    model: &'a str,
+    input: &'a [String],
+}
+
+/// Embeddings response body
+#[derive(Debug, Deserialize)]
+struct EmbeddingResponse {
+    data: Vec<EmbeddingData>,
+    #[serde(default)]
+    usage: Option<EmbeddingUsageResponse>,
+}
+
+#[derive(Debug, Deserialize)]
+struct EmbeddingData {
+    index: usize,
+    embedding: Vec<f32>,
+}
+
+#[derive(Debug, Deserialize)]
+struct EmbeddingUsageResponse {
+    #[serde(default)]
+    total_tokens: u64,
+}
+
+/// Work done by the embedding service so far
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize)]
+pub struct EmbeddingUsage {
+    /// Requests sent
+    pub requests: u64,
+    /// Texts embedded by the service
+    pub texts: u64,
+    /// Texts served from the cache
+    pub cache_hits: u64,
+    /// Tokens billed
+    pub tokens: u64,
+    /// Cost of the tokens billed in USD
+    pub cost_usd: f64,
+}
+
+/// Client computing embeddings through an embedding service
+pub struct Embedder {
+    client: Client,
+    config: EmbeddingConfig,
+    cache: Option<Cache<String, Arc<Vec<f32>>>>,
+    permits: Semaphore,
+    usage: Mutex<EmbeddingUsage>,
+}
+
+impl std::fmt::Debug for Embedder {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("Embedder")
+            .field("url", &self.config.url)
+            .field("model", &self.config.model)
+            .field("usage", &self.usage())
+            .finish_non_exhaustive()
+    }
+}
+
+impl Embedder {
+    /// Create a client from configuration
+    pub fn new(config: &EmbeddingConfig) -> Result<Self> {
+        if config.batch_size == 0 || config.max_concurrency == 0 {
+            return Err(Error::config(
+                "Embedding batch_size and max_concurrency must be positive",
+            ));
+        }
+
+        let client = Client::builder()
+            .timeout(Duration::from_secs(config.timeout_secs))
+            .build()
+            .map_err(|e| Error::config(format!("Failed to create HTTP client: {}", e)))?;
+        let cache = (config.cache_capacity > 0).then(|| Cache::new(config.cache_capacity));
+
+        info!(url = %config.url, model = %config.model, "Creating embedding client");
+        Ok(Self {
+            client,
+            config: config.clone(),
+            cache,
+            permits: Semaphore::new(config.max_concurrency),
+            usage: Mutex::new(EmbeddingUsage::default()),
+        })
+    }
+
+    /// Work done so far
+    pub fn usage(&self) -> EmbeddingUsage {
+        *self.usage.lock().unwrap()
+    }
+
+    /// Attach embeddings to events that have none
+    ///
+    /// Prompts (and responses, when configured) with an empty text are
+    /// left alone, as are texts whose request failed.
+    pub async fn embed(&self, events: &mut [TelemetryEvent]) {
+        let mut texts: Vec<String> = Vec::new();
+        for event in events.iter() {
+            if event.prompt.embedding.is_none() && !event.prompt.text.is_empty() {
+                texts.push(event.prompt.text.clone());
+            }
+            if self.config.embed_responses
+                && event.response.embedding.is_none()
+                && !event.response.text.is_empty()
+            {
+                texts.push(event.response.text.clone());
+            }
+        }
+        if texts.is_empty() {
+            return;
+        }
+        texts.sort_unstable();
+        texts.dedup();
+
+        // Serve what the cache holds, embedding the rest
+        let mut embeddings: HashMap<String, Arc<Vec<f32>>> = HashMap::new();
+        let mut missing = Vec::new();
+        for text in texts {
+            let cached = match &self.cache {
+                Some(cache) => cache.get(&text).await,
+                None => None,
+            };
+            match cached {
+                Some(embedding) => {
+                    embeddings.insert(text, embedding);
+                }
+                None => missing.push(text),
+            }
+        }
+        let hits = embeddings.len() as u64;
+        if hits > 0 {
+            self.usage.lock().unwrap().cache_hits += hits;
+            metrics::counter!("sentinel_embedding_cache_hits_total").increment(hits);
+        }
+
+        let requests = missing
+            .chunks(self.config.batch_size)
+            .map(|chunk| self.request(chunk));
+        for (chunk, result) in missing
+            .chunks(self.config.batch_size)
+            .zip(join_all(requests).await)
+        {
+            match result {
+                Ok(vectors) => {
+                    for (text, vector) in chunk.iter().zip(vectors) {
+                        let vector = Arc::new(vector);
+                        if let Some(cache) = &self.cache {
+                            cache.insert(text.clone(), Arc::clone(&vector)).await;
+                        }
+                        embeddings.insert(text.clone(), vector);
+                    }
+                }
+                Err(e) => {
+                    warn!(texts = chunk.len(), "Failed to compute embeddings: {}", e);
+                    metrics::counter!("sentinel_embedding_errors_total").increment(1);
+                }
+            }
+        }
+
+        for event in events.iter_mut() {
+            if event.prompt.embedding.is_none() {
+                if let Some(embedding) = embeddings.get(&event.prompt.text) {
+                    event.prompt_mut().embedding = Some(embedding.as_ref().clone());
+                }
+            }
+            if self.config.embed_responses && event.response.embedding.is_none() {
+                if let Some(embedding) = embeddings.get(&event.response.text) {
+                    event.response_mut().embedding = Some(embedding.as_ref().clone());
+                }
+            }
+        }
+    }
+
+    /// Embed one batch of texts, in order
+    async fn request(&self, texts: &[String]) -> Result<Vec<Vec<f32>>> {
+        let _permit = self
+            .permits
+            .acquire()
+            .await
+            .map_err(|e| Error::internal(format!("Embedding limiter closed: {}", e)))?;
+
+        let mut request = self.client.post(&self.config.url).json(&EmbeddingRequest {
+            model: &self.config.model,
+            input: texts,
+        });
+        if let Some(key) = &self.config.api_key {
+            request = request.bearer_auth(key);
+        }
+
+        let start = std::time::Instant::now();
+        let response: EmbeddingResponse = request
+            .send()
+            .await
+            .and_then(|r| r.error_for_status())
+            .map_err(|e| {
+                Error::connection(format!("Embedding request failed: {}", e.without_url()))
+            })?
+            .json()
+            .await
+            .map_err(|e| Error::connection(format!("Invalid embedding response: {}", e)))?;
+        metrics::histogram!("sentinel_embedding_request_duration_seconds")
+            .record(start.elapsed().as_secs_f64());
+
+        if response.data.len() != texts.len() {
+            return Err(Error::validation(format!(
+                "Embedding service returned {} embeddings for {} texts",
+                response.data.len(),
+                texts.len()
+            )));
+        }
+        let mut data = response.data;
+        data.sort_by_key(|d| d.index);
+
+        let tokens = response.usage.map_or(0, |usage| usage.total_tokens);
+        let cost = tokens as f64 / 1000.0 * self.config.cost_per_1k_tokens;
+        {
+            let mut usage = self.usage.lock().unwrap();
+            usage.requests += 1;
+            usage.texts += texts.len() as u64;
+            usage.tokens += tokens;
+            usage.cost_usd += cost;
+        }
+        metrics::counter!("sentinel_embedding_texts_total").increment(texts.len() as u64);
+        metrics::counter!("sentinel_embedding_tokens_total").increment(tokens);
+        metrics::gauge!("sentinel_embedding_cost_usd").increment(cost);
+        debug!(texts = texts.len(), tokens, "Computed embeddings");
+
+        Ok(data.into_iter().map(|d| d.embedding).collect())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use wiremock::{
+        matchers::{header, method, path},
+        Mock, MockServer, Request, ResponseTemplate,
+    };
+
+    fn config(url: &str) -> EmbeddingConfig {
+        EmbeddingConfig {
+            url: format!("{}/v1/embeddings", url),
+            api_key: Some("secret".to_string()),
+            model: "text-embedding-3-small".to_string(),
+            batch_size: 2,
+            max_concurrency: 2,
+            timeout_secs: 5,
+            cache_capacity: 100,
+            cost_per_1k_tokens: 0.02,
+            embed_responses: false,
+        }
+    }
+
+    fn event(prompt: &str) -> TelemetryEvent {
+        llm_sentinel_fixtures::event()
+            .service("chat")
+            .prompt(prompt)
+            .build()
+    }
+
+    /// Embeds each text as its length, billing 10 tokens per text
+    fn respond(request: &Request) -> ResponseTemplate {
+        let body: serde_json::Value = serde_json::from_slice(&request.body).unwrap();
+        let input = body["input"].as_array().unwrap();
+        let data: Vec<_> = input
+            .iter()
+            .enumerate()
+            .map(|(index, text)| {
+                let len = text.as_str().unwrap().len() as f32;
+                serde_json::json!({ "index": index, "embedding": [len, 1.0] })
+            })
+            .collect();
+        ResponseTemplate::new(200).set_body_json(serde_json::json!({
+            "data": data,
+            "usage": { "total_tokens": input.len() * 10 },
+        }))
+    }
+
+    #[tokio::test]
+    async fn test_batches_and_caches() {
+        let server = MockServer::start().await;
+        Mock::given(method("POST"))
+            .and(path("/v1/embeddings"))
+            .and(header("authorization", "Bearer secret"))
+            .respond_with(respond)
+            .expect(2)
+            .mount(&server)
+            .await;
+
+        let embedder = Embedder::new(&config(&server.uri())).unwrap();
+        let mut precomputed = event("kept");
+        precomputed.prompt_mut().embedding = Some(vec![0.0, 0.0]);
+        let mut events = vec![
+            event("a"),
+            event("bb"),
+            event("a"),
+            event("ccc"),
+            precomputed,
+        ];
+        embedder.embed(&mut events).await;
+
+        // Three distinct texts in batches of two
+        assert_eq!(events[0].prompt.embedding, Some(vec![1.0, 1.0]));
+        assert_eq!(events[1].prompt.embedding, Some(vec![2.0, 1.0]));
+        assert_eq!(events[2].prompt.embedding, Some(vec![1.0, 1.0]));
+        assert_eq!(events[3].prompt.embedding, Some(vec![3.0, 1.0]));
+        assert_eq!(events[4].prompt.embedding, Some(vec![0.0, 0.0]));
+        assert!(events[0].response.embedding.is_none());
+
+        // Served from the cache
+        let mut again = vec![event("bb")];
+        embedder.embed(&mut again).await;
+        assert_eq!(again[0].prompt.embedding, Some(vec![2.0, 1.0]));
+
+        let usage = embedder.usage();
+        assert_eq!(usage.requests, 2);
+        assert_eq!(usage.texts, 3);
+        assert_eq!(usage.cache_hits, 1);
+        assert_eq!(usage.tokens, 30);
+        assert!((usage.cost_usd - 0.0006).abs() < 1e-12);
+    }
+
+    #[tokio::test]
+    async fn test_failure_leaves_events_unembedded() {
+        let server = MockServer::start().await;
+        Mock::given(method("POST"))
+            .respond_with(ResponseTemplate::new(503))
+            .mount(&server)
+            .await;
+
+        let embedder = Embedder::new(&config(&server.uri())).unwrap();
+        let mut events = vec![event("a")];
+        embedder.embed(&mut events).await;
+        assert!(events[0].prompt.embedding.is_none());
+        assert_eq!(embedder.usage().requests, 0);
+    }
+}
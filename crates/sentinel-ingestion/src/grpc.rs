@@ -0,0 +1,240 @@
+//! gRPC server with health checking and reflection.
+//!
+//! Every gRPC surface is served from one [`GrpcServer`], which always
+//! carries the standard [health checking protocol] (for Kubernetes gRPC
+//! probes and load balancers) and [server reflection] (for grpcurl and
+//! similar tooling), so both work without per-surface setup. Surfaces added
+//! with [`GrpcServer::add_service`] are reported through the health service
+//! and described through reflection.
+//!
+//! [health checking protocol]: https://github.com/grpc/grpc/blob/master/doc/health-checking.md
+//! [server reflection]: https://github.com/grpc/grpc/blob/master/doc/server-reflection.md
+
+use llm_sentinel_core::{config::GrpcConfig, Error, Result};
+use std::{convert::Infallible, future::Future, net::SocketAddr};
+use tonic::{
+    body::BoxBody,
+    codegen::{
+        http::{Request, Response},
+        Service,
+    },
+    server::NamedService,
+    service::RoutesBuilder,
+    transport::{Identity, Server, ServerTlsConfig},
+};
+use tonic_health::{server::HealthReporter, ServingStatus};
+use tracing::{debug, info};
+
+/// Serving status reported over the gRPC health checking protocol
+///
+/// Cloning shares the status. The server as a whole (the empty service
+/// name Kubernetes probes ask for) and every added service report the same
+/// status.
+#[derive(Clone)]
+pub struct GrpcHealth {
+    reporter: HealthReporter,
+    services: Vec<&'static str>,
+}
+
+impl GrpcHealth {
+    /// Report the server as serving or not serving
+    pub async fn set_serving(&self, serving: bool) {
+        let status = if serving {
+            ServingStatus::Serving
+        } else {
+            ServingStatus::NotServing
+        };
+        let mut reporter = self.reporter.clone();
+        reporter.set_service_status("", status).await;
+        for service in &self.services {
+            reporter.set_service_status(*service, status).await;
+        }
+        debug!(serving, "gRPC health status updated");
+    }
+}
+
+impl std::fmt::Debug for GrpcHealth {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("GrpcHealth")
+            .field("services", &self.services)
+            .finish_non_exhaustive()
+    }
+}
+
+/// gRPC server carrying health checking and reflection
+pub struct GrpcServer {
+    config: GrpcConfig,
+    routes: RoutesBuilder,
+    health: GrpcHealth,
+    descriptor_sets: Vec<&'static [u8]>,
+}
+
+impl GrpcServer {
+    /// Create a server for the given configuration
+    ///
+    /// The server reports serving until told otherwise through
+    /// [`GrpcServer::health`].
+    pub fn new(config: GrpcConfig) -> Self {
+        let (reporter, health_service) = tonic_health::server::health_reporter();
+        let mut routes = RoutesBuilder::default();
+        routes.add_service(health_service);
+
+        Self {
+            config,
+            routes,
+            health: GrpcHealth {
+                reporter,
+                services: Vec::new(),
+            },
+            descriptor_sets: vec![tonic_health::pb::FILE_DESCRIPTOR_SET],
+        }
+    }
+
+    /// Serve a gRPC surface
+    ///
+    /// `file_descriptor_set` is the encoded descriptor set generated for the
+    /// service's protos, served through reflection.
+    pub fn add_service<S>(mut self, service: S, file_descriptor_set: &'static [u8]) -> Self
+    where
+        S: Service<Request<BoxBody>, Response = Response<BoxBody>, Error = Infallible>
+            + NamedService
+            + Clone
+            + Send
+            + 'static,
+        S::Future: Send + 'static,
+    {
+        self.routes.add_service(service);
+        self.health.services.push(S::NAME);
+        self.descriptor_sets.push(file_descriptor_set);
+        self
+    }
+
+    /// Handle to the reported serving status
+    pub fn health(&self) -> GrpcHealth {
+        self.health.clone()
+    }
+
+    /// Serve until `shutdown` completes
+    pub async fn serve_with_shutdown(self, shutdown: impl Future<Output = ()>) -> Result<()> {
+        let addr: SocketAddr = self.config.address.parse().map_err(|e| {
+            Error::config(format!(
+                "Invalid gRPC address {}: {}",
+                self.config.address, e
+            ))
+        })?;
+
+        let mut server = Server::builder();
+        if self.config.enable_tls {
+            server = server
+                .tls_config(tls_config(&self.config)?)
+                .map_err(|e| Error::config(format!("Invalid gRPC TLS configuration: {}", e)))?;
+        }
+
+        // Both reflection versions, as tooling still asks for v1alpha
+        let reflection_v1 = reflection(&self.descriptor_sets)
+            .build_v1()
+            .map_err(|e| Error::internal(format!("Failed to build gRPC reflection: {}", e)))?;
+        let reflection_v1alpha = reflection(&self.descriptor_sets)
+            .build_v1alpha()
+            .map_err(|e| Error::internal(format!("Failed to build gRPC reflection: {}", e)))?;
+
+        info!(
+            addr = %addr,
+            tls = self.config.enable_tls,
+            services = ?self.health.services,
+            "gRPC server listening"
+        );
+        server
+            .add_routes(self.routes.routes())
+            .add_service(reflection_v1)
+            .add_service(reflection_v1alpha)
+            .serve_with_shutdown(addr, shutdown)
+            .await
+            .map_err(|e| Error::connection(format!("gRPC server error: {}", e)))
+    }
+}
+
+impl std::fmt::Debug for GrpcServer {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("GrpcServer")
+            .field("config", &self.config)
+            .field("health", &self.health)
+            .finish_non_exhaustive()
+    }
+}
+
+fn reflection<'b>(descriptor_sets: &[&'b [u8]]) -> tonic_reflection::server::Builder<'b> {
+    descriptor_sets.iter().fold(
+        tonic_reflection::server::Builder::configure(),
+        |builder, set| builder.register_encoded_file_descriptor_set(set),
+    )
+}
+
+fn tls_config(config: &GrpcConfig) -> Result<ServerTlsConfig> {
+    let (Some(cert_path), Some(key_path)) = (&config.cert_path, &config.key_path) else {
+        return Err(Error::config("gRPC TLS requires cert_path and key_path"));
+    };
+    let read = |path: &str| {
+        std::fs::read(path).map_err(|e| Error::config(format!("Failed to read {}: {}", path, e)))
+    };
+    let identity = Identity::from_pem(read(cert_path)?, read(key_path)?);
+    Ok(ServerTlsConfig::new().identity(identity))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tonic::transport::Channel;
+    use tonic_health::pb::{
+        health_check_response::ServingStatus as Status, health_client::HealthClient,
+        HealthCheckRequest,
+    };
+
+    async fn status(client: &mut HealthClient<Channel>) -> i32 {
+        client
+            .check(HealthCheckRequest {
+                service: String::new(),
+            })
+            .await
+            .unwrap()
+            .into_inner()
+            .status
+    }
+
+    #[tokio::test]
+    async fn test_health_follows_reported_status() {
+        // Reserve a free port for the server
+        let addr = std::net::TcpListener::bind("127.0.0.1:0")
+            .unwrap()
+            .local_addr()
+            .unwrap();
+        let server = GrpcServer::new(GrpcConfig {
+            address: addr.to_string(),
+            enable_tls: false,
+            cert_path: None,
+            key_path: None,
+        });
+        let health = server.health();
+        let (stop, stopped) = tokio::sync::oneshot::channel::<()>();
+        let handle = tokio::spawn(server.serve_with_shutdown(async {
+            stopped.await.ok();
+        }));
+
+        let endpoint = Channel::from_shared(format!("http://{}", addr)).unwrap();
+        let mut client = loop {
+            match endpoint.connect().await {
+                Ok(channel) => break HealthClient::new(channel),
+                Err(_) => tokio::time::sleep(std::time::Duration::from_millis(10)).await,
+            }
+        };
+        assert_eq!(status(&mut client).await, Status::Serving as i32);
+
+        health.set_serving(false).await;
+        assert_eq!(status(&mut client).await, Status::NotServing as i32);
+        health.set_serving(true).await;
+        assert_eq!(status(&mut client).await, Status::Serving as i32);
+
+        stop.send(()).unwrap();
+        handle.await.unwrap().unwrap();
+    }
+}
@@ -1,20 +1,32 @@
 //! Kafka consumer for telemetry ingestion.
+//!
+//! Pausing stops fetching from every assigned partition while the consumer
+//! keeps polling, so it stays in its group and holds its assignment.
+//! Partitions assigned by a rebalance while paused are paused on the next
+//! poll.
 
 use crate::Ingester;
 use async_trait::async_trait;
 use rdkafka::{
-    consumer::{Consumer, StreamConsumer},
+    consumer::{BaseConsumer, Consumer, StreamConsumer},
+    error::{KafkaError, RDKafkaErrorCode},
     ClientConfig, Message,
 };
 use llm_sentinel_core::{
+    blocking::{self, BlockingPool},
     config::KafkaConfig,
     events::TelemetryEvent,
+    selfcheck::{CheckKind, CheckResult, SelfCheck},
     Error, Result,
 };
 use std::time::Duration;
+use tokio::time::Instant;
 use tracing::{debug, error, info};
 use validator::Validate;
 
+/// How long self-checks wait for broker metadata
+const METADATA_TIMEOUT: Duration = Duration::from_secs(5);
+
 /// Kafka-based telemetry ingester
 pub struct KafkaIngester {
     consumer: StreamConsumer,
@@ -22,6 +34,7 @@ pub struct KafkaIngester {
     batch_size: usize,
     batch_timeout: Duration,
     running: bool,
+    paused: bool,
 }
 
 impl std::fmt::Debug for KafkaIngester {
@@ -31,6 +44,7 @@ impl std::fmt::Debug for KafkaIngester {
             .field("batch_size", &self.batch_size)
             .field("batch_timeout", &self.batch_timeout)
             .field("running", &self.running)
+            .field("paused", &self.paused)
             .finish()
     }
 }
@@ -67,9 +81,24 @@ impl KafkaIngester {
             batch_size,
             batch_timeout: Duration::from_millis(batch_timeout_ms),
             running: false,
+            paused: false,
         })
     }
 
+    /// Pause or resume fetching from all assigned partitions
+    fn set_paused(&self, paused: bool) -> Result<()> {
+        let assignment = self.consumer.assignment().map_err(|e| {
+            Error::connection(format!("Failed to read partition assignment: {}", e))
+        })?;
+        let result = if paused {
+            self.consumer.pause(&assignment)
+        } else {
+            self.consumer.resume(&assignment)
+        };
+        result
+            .map_err(|e| Error::connection(format!("Failed to pause or resume partitions: {}", e)))
+    }
+
     /// Parse Kafka message to telemetry event
     fn parse_message(&self, message: &rdkafka::message::BorrowedMessage<'_>) -> Result<TelemetryEvent> {
         let payload = message
@@ -135,6 +164,11 @@ impl Ingester for KafkaIngester {
             return Err(Error::internal("Ingester is not running"));
         }
 
+        // Catch partitions assigned by a rebalance since the pause
+        if self.paused {
+            self.set_paused(true)?;
+        }
+
         let mut batch = Vec::with_capacity(self.batch_size);
         let deadline = tokio::time::Instant::now() + self.batch_timeout;
 
@@ -160,7 +194,8 @@ impl Ingester for KafkaIngester {
                         }
                         Err(e) => {
                             error!("Failed to parse message: {}", e);
-                            metrics::counter!("sentinel_events_dropped_total").increment(1);
+                            metrics::counter!("sentinel_events_dropped_total", "reason" => "parse_failed")
+                                .increment(1);
                             // Continue processing other messages
                             continue;
                         }
@@ -187,6 +222,20 @@ impl Ingester for KafkaIngester {
         Ok(batch)
     }
 
+    async fn pause(&mut self) -> Result<bool> {
+        self.set_paused(true)?;
+        self.paused = true;
+        info!(topic = %self.topic, "Kafka consumption paused");
+        Ok(true)
+    }
+
+    async fn resume(&mut self) -> Result<()> {
+        self.paused = false;
+        self.set_paused(false)?;
+        info!(topic = %self.topic, "Kafka consumption resumed");
+        Ok(())
+    }
+
     async fn health_check(&self) -> Result<()> {
         if !self.running {
             return Err(Error::internal("Ingester is not running"));
@@ -201,14 +250,142 @@ impl Ingester for KafkaIngester {
     }
 }
 
+/// Self-check of the Kafka cluster: brokers reachable, ACLs allowing the
+/// topic and consumer group, and the topic present
+#[derive(Debug, Clone)]
+pub struct KafkaCheck {
+    config: KafkaConfig,
+}
+
+impl KafkaCheck {
+    /// Check the brokers, topic and consumer group in `config`
+    pub fn new(config: KafkaConfig) -> Self {
+        Self { config }
+    }
+
+    fn run_blocking(&self) -> Vec<CheckResult> {
+        let name = "kafka";
+        let started = Instant::now();
+        let consumer: BaseConsumer = match ClientConfig::new()
+            .set("bootstrap.servers", self.config.brokers.join(","))
+            .set("group.id", &self.config.consumer_group)
+            .create()
+        {
+            Ok(consumer) => consumer,
+            Err(e) => {
+                return vec![
+                    CheckResult::fail(name, CheckKind::Connectivity, e.to_string()),
+                    CheckResult::skip(name, CheckKind::Permission),
+                    CheckResult::skip(name, CheckKind::Schema),
+                ]
+            }
+        };
+
+        let metadata = match consumer.fetch_metadata(Some(&self.config.topic), METADATA_TIMEOUT) {
+            Ok(metadata) => metadata,
+            Err(e) => {
+                return vec![
+                    CheckResult::fail(name, CheckKind::Connectivity, e.to_string()).timed(started),
+                    CheckResult::skip(name, CheckKind::Permission),
+                    CheckResult::skip(name, CheckKind::Schema),
+                ]
+            }
+        };
+        let mut results = vec![CheckResult::pass(
+            name,
+            CheckKind::Connectivity,
+            format!("{} broker(s) answered", metadata.brokers().len()),
+        )
+        .timed(started)];
+
+        let started = Instant::now();
+        let topic_error = metadata
+            .topics()
+            .iter()
+            .find(|t| t.name() == self.config.topic)
+            .and_then(|t| t.error())
+            .map(RDKafkaErrorCode::from);
+        if topic_error == Some(RDKafkaErrorCode::TopicAuthorizationFailed) {
+            results.push(
+                CheckResult::fail(
+                    name,
+                    CheckKind::Permission,
+                    format!("not authorized to describe topic {}", self.config.topic),
+                )
+                .timed(started),
+            );
+            results.push(CheckResult::skip(name, CheckKind::Schema));
+            return results;
+        }
+        let permission =
+            match consumer.fetch_group_list(Some(&self.config.consumer_group), METADATA_TIMEOUT) {
+                Ok(_) => CheckResult::pass(
+                    name,
+                    CheckKind::Permission,
+                    format!(
+                        "topic {} and group {} authorized",
+                        self.config.topic, self.config.consumer_group
+                    ),
+                ),
+                Err(KafkaError::GroupListFetch(RDKafkaErrorCode::GroupAuthorizationFailed)) => {
+                    CheckResult::fail(
+                        name,
+                        CheckKind::Permission,
+                        format!(
+                            "not authorized for consumer group {}",
+                            self.config.consumer_group
+                        ),
+                    )
+                }
+                Err(e) => CheckResult::fail(name, CheckKind::Permission, e.to_string()),
+            };
+        results.push(permission.timed(started));
+
+        results.push(match topic_error {
+            None => CheckResult::pass(
+                name,
+                CheckKind::Schema,
+                format!("topic {} exists", self.config.topic),
+            ),
+            Some(RDKafkaErrorCode::UnknownTopicOrPartition | RDKafkaErrorCode::UnknownTopic) => {
+                CheckResult::fail(
+                    name,
+                    CheckKind::Schema,
+                    format!("topic {} does not exist", self.config.topic),
+                )
+            }
+            Some(code) => CheckResult::fail(name, CheckKind::Schema, code.to_string()),
+        });
+        results
+    }
+}
+
+#[async_trait]
+impl SelfCheck for KafkaCheck {
+    fn dependency(&self) -> String {
+        "kafka".to_string()
+    }
+
+    async fn run(&self) -> Vec<CheckResult> {
+        // librdkafka's metadata calls block
+        let check = self.clone();
+        blocking::run(BlockingPool::Kafka, move || check.run_blocking())
+            .await
+            .unwrap_or_else(|e| {
+                vec![CheckResult::fail(
+                    "kafka",
+                    CheckKind::Connectivity,
+                    e.to_string(),
+                )]
+            })
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
-    use llm_sentinel_core::{
-        config::KafkaConfig,
-        events::{PromptInfo, ResponseInfo, TelemetryEvent},
-        types::{ModelId, ServiceId},
-    };
+    use llm_sentinel_core::{config::KafkaConfig, events::TelemetryEvent};
+    use llm_sentinel_fixtures::event;
 
     fn create_test_kafka_config() -> KafkaConfig {
         KafkaConfig {
@@ -232,23 +409,7 @@ mod tests {
 
     #[test]
     fn test_event_parsing() {
-        let event = TelemetryEvent::new(
-            ServiceId::new("test"),
-            ModelId::new("gpt-4"),
-            PromptInfo {
-                text: "test".to_string(),
-                tokens: 10,
-                embedding: None,
-            },
-            ResponseInfo {
-                text: "response".to_string(),
-                tokens: 20,
-                finish_reason: "stop".to_string(),
-                embedding: None,
-            },
-            100.0,
-            0.001,
-        );
+        let event = event().build();
 
         let json = serde_json::to_vec(&event).unwrap();
         let parsed: TelemetryEvent = serde_json::from_slice(&json).unwrap();
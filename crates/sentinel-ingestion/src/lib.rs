@@ -3,16 +3,39 @@
 //! Telemetry ingestion service for LLM-Sentinel.
 //!
 //! This crate provides:
-//! - Kafka consumer for high-throughput event streaming
+//! - Kafka consumer for high-throughput event streaming (`kafka` feature)
+//! - Google Cloud Pub/Sub subscriber
+//! - gRPC server with health checking and reflection (`grpc` feature)
 //! - OpenTelemetry Protocol (OTLP) parsing
 //! - Event validation and normalization
+//! - Cost enrichment from per-model pricing
+//! - Per-service ingestion quotas
+//! - Per-producer data quality scoring
+//! - Clock skew and late event policies
+//! - Pausing ingestion while the detection queue is backed up
+//! - Batched embedding of events sent without embeddings
+//! - Replay of recorded telemetry on a simulated clock
 //! - Buffering and batching for efficient processing
+//!
+//! Kafka (`kafka`) and gRPC (`grpc`) support are default features; disable
+//! default features to embed the parsers and pipeline without rdkafka or
+//! tonic.
 
 #![warn(missing_debug_implementations, rust_2018_idioms, unreachable_pub)]
 
+pub mod backpressure;
+pub mod embedding;
+#[cfg(feature = "grpc")]
+pub mod grpc;
+#[cfg(feature = "kafka")]
 pub mod kafka;
 pub mod otlp;
 pub mod pipeline;
+pub mod pubsub;
+pub mod quality;
+pub mod quota;
+pub mod replay;
+pub mod skew;
 pub mod validation;
 
 use async_trait::async_trait;
@@ -30,15 +53,40 @@ pub trait Ingester: Send + Sync {
     /// Get the next batch of telemetry events
     async fn next_batch(&mut self) -> Result<Vec<TelemetryEvent>>;
 
+    /// Stop fetching new events until [`Ingester::resume`]
+    ///
+    /// `next_batch` keeps being called while paused, so that it can keep
+    /// the connection alive, and returns no new events. Returns `false` if
+    /// the ingester cannot pause, in which case callers should stop calling
+    /// `next_batch` until they would resume it.
+    async fn pause(&mut self) -> Result<bool> {
+        Ok(false)
+    }
+
+    /// Fetch events again after [`Ingester::pause`]
+    async fn resume(&mut self) -> Result<()> {
+        Ok(())
+    }
+
     /// Check if ingester is healthy
     async fn health_check(&self) -> Result<()>;
 }
 
 /// Re-export commonly used types
 pub mod prelude {
+    pub use crate::backpressure::{Backpressure, BackpressureAction};
+    pub use crate::embedding::{Embedder, EmbeddingUsage};
+    #[cfg(feature = "grpc")]
+    pub use crate::grpc::{GrpcHealth, GrpcServer};
+    #[cfg(feature = "kafka")]
     pub use crate::kafka::KafkaIngester;
     pub use crate::otlp::OtlpParser;
     pub use crate::pipeline::{IngestionPipeline, PipelineConfig};
+    pub use crate::pubsub::PubSubIngester;
+    pub use crate::quality::{DataQualityMonitor, QualityIssue, QualityScore};
+    pub use crate::quota::{QuotaDecision, ServiceQuotas};
+    pub use crate::replay::ReplayIngester;
+    pub use crate::skew::{ClockSkewPolicy, SkewDecision};
     pub use crate::validation::EventValidator;
     pub use crate::Ingester;
 }
@@ -1,14 +1,38 @@
 //! OpenTelemetry Protocol (OTLP) parsing for telemetry events.
 
 use llm_sentinel_core::{
+    correlation,
     events::{PromptInfo, ResponseInfo, TelemetryEvent},
-    types::{ModelId, ServiceId},
+    types::{Environment, ModelId, ModelVersion, ServiceId},
     Error, Result,
 };
 use serde_json::Value;
-use std::collections::HashMap;
+use std::collections::{BTreeMap, HashMap};
 use tracing::{debug, warn};
 
+/// Span attribute prefix for event labels
+pub const LABEL_ATTRIBUTE_PREFIX: &str = "sentinel.label.";
+
+/// Resource (or span) attributes naming the deployment environment, in
+/// order of preference
+const ENVIRONMENT_ATTRIBUTES: &[&str] = &["deployment.environment.name", "deployment.environment"];
+
+/// Child span attribute naming the request component it belongs to
+/// (overrides classification by span name and attributes)
+pub const COMPONENT_ATTRIBUTE: &str = "sentinel.component";
+
+/// Request component of document retrieval (vector search, reranking)
+pub const RETRIEVAL_COMPONENT: &str = "retrieval";
+
+/// Request component of tool and function calls
+pub const TOOL_COMPONENT: &str = "tool";
+
+/// Request component of the model provider call
+pub const PROVIDER_COMPONENT: &str = "provider";
+
+/// Request component of child spans matching no other component
+pub const OTHER_COMPONENT: &str = "other";
+
 /// OTLP parser for telemetry events
 #[derive(Debug, Clone)]
 pub struct OtlpParser {
@@ -85,7 +109,7 @@ impl OtlpParser {
                 // Calculate from start/end time if available
                 let start = span_data.get("start_time_unix_nano")?.as_i64()?;
                 let end = span_data.get("end_time_unix_nano")?.as_i64()?;
-                Some(((end - start) as f64) / 1_000_000.0) // Convert ns to ms
+                Some((end.checked_sub(start)? as f64) / 1_000_000.0) // Convert ns to ms
             })
             .unwrap_or(0.0);
 
@@ -123,6 +147,12 @@ impl OtlpParser {
         if let Some(version) = self.extract_string(attributes, "service.version") {
             metadata.insert("version".to_string(), version);
         }
+        if let Some(id) = self
+            .extract_string(attributes, "correlation.id")
+            .filter(|id| correlation::is_valid(id))
+        {
+            metadata.insert(correlation::METADATA_KEY.to_string(), id);
+        }
 
         let mut event = TelemetryEvent::new(
             ServiceId::new(service_name),
@@ -142,6 +172,42 @@ impl OtlpParser {
             cost_usd,
         );
 
+        // The serving model may pin a version behind an alias (gpt-4 -> gpt-4-0613)
+        if let Some(version) = self
+            .extract_string(attributes, "llm.response.model")
+            .and_then(|served| ModelVersion::parse(&served))
+        {
+            event.model_version = Some(version);
+        }
+
+        // Environment comes from the resource, falling back to the span
+        let resource = span_data
+            .get("resource")
+            .and_then(|r| r.get("attributes"))
+            .and_then(|v| v.as_object());
+        event.environment = ENVIRONMENT_ATTRIBUTES
+            .iter()
+            .find_map(|key| {
+                resource
+                    .and_then(|r| self.extract_string(r, key))
+                    .or_else(|| self.extract_string(attributes, key))
+            })
+            .map(Environment::new);
+
+        // Labels arrive as `sentinel.label.<key>` attributes
+        for (key, value) in attributes {
+            if let (Some(key), Some(value)) =
+                (key.strip_prefix(LABEL_ATTRIBUTE_PREFIX), value.as_str())
+            {
+                event.labels.insert(key.to_string(), value.to_string());
+            }
+        }
+
+        // Child spans break the latency down by request component
+        if let Some(children) = span_data.get("children").and_then(|v| v.as_array()) {
+            event.latency_breakdown = self.latency_breakdown(children);
+        }
+
         event.trace_id = trace_id;
         event.span_id = span_id;
         event.metadata = metadata;
@@ -157,6 +223,63 @@ impl OtlpParser {
         Ok(event)
     }
 
+    /// Sum child span durations by request component
+    ///
+    /// Children without a start and end time, or ending before they start,
+    /// are skipped.
+    fn latency_breakdown(&self, children: &[Value]) -> BTreeMap<String, f64> {
+        let mut breakdown = BTreeMap::new();
+        for child in children {
+            let duration_ms = child
+                .get("start_time_unix_nano")
+                .and_then(|v| v.as_i64())
+                .zip(child.get("end_time_unix_nano").and_then(|v| v.as_i64()))
+                .and_then(|(start, end)| end.checked_sub(start))
+                .filter(|ns| *ns >= 0)
+                .map(|ns| ns as f64 / 1_000_000.0);
+            let Some(duration_ms) = duration_ms else {
+                continue;
+            };
+            *breakdown
+                .entry(self.classify_component(child))
+                .or_insert(0.0) += duration_ms;
+        }
+        breakdown
+    }
+
+    /// Request component of a child span: its `sentinel.component`
+    /// attribute, otherwise inferred from GenAI and database attributes and
+    /// the span name
+    fn classify_component(&self, child: &Value) -> String {
+        let attributes = child.get("attributes").and_then(|v| v.as_object());
+        if let Some(component) = attributes
+            .and_then(|a| self.extract_string(a, COMPONENT_ATTRIBUTE))
+            .map(|c| c.trim().to_lowercase())
+            .filter(|c| !c.is_empty())
+        {
+            return component;
+        }
+
+        let has = |key: &str| attributes.is_some_and(|a| a.contains_key(key));
+        let name = child
+            .get("name")
+            .and_then(|v| v.as_str())
+            .unwrap_or_default()
+            .to_lowercase();
+        let named = |words: &[&str]| words.iter().any(|word| name.contains(word));
+
+        let component = if has("gen_ai.tool.name") || named(&["tool", "function"]) {
+            TOOL_COMPONENT
+        } else if has("db.system") || named(&["retriev", "vector", "search", "rerank"]) {
+            RETRIEVAL_COMPONENT
+        } else if has("gen_ai.system") || named(&["llm", "chat", "completion", "provider"]) {
+            PROVIDER_COMPONENT
+        } else {
+            OTHER_COMPONENT
+        };
+        component.to_string()
+    }
+
     /// Extract string value from attributes
     fn extract_string(&self, obj: &serde_json::Map<String, Value>, key: &str) -> Option<String> {
         obj.get(key)?.as_str().map(|s| s.to_string())
@@ -218,8 +341,8 @@ mod tests {
         let span = json!({
             "trace_id": "abc123",
             "span_id": "def456",
-            "start_time_unix_nano": 1000000000000,
-            "end_time_unix_nano": 1000100000000,
+            "start_time_unix_nano": 1000000000000_u64,
+            "end_time_unix_nano": 1000100000000_u64,
             "attributes": {
                 "service.name": "test-service",
                 "llm.model": "gpt-4",
@@ -249,6 +372,114 @@ mod tests {
         assert_eq!(event.cost_usd, 0.001);
         assert!(!event.has_errors());
         assert_eq!(event.metadata.get("user_id").unwrap(), "user-123");
+        assert!(event.model_version.is_none());
+    }
+
+    #[test]
+    fn test_parse_span_model_version() {
+        let parser = OtlpParser::default();
+        let span = json!({
+            "attributes": {
+                "service.name": "test-service",
+                "llm.model": "gpt-4",
+                "llm.response.model": "gpt-4-0613",
+                "llm.prompt": "Test prompt",
+                "llm.response": "Test response"
+            }
+        });
+
+        let event = parser.parse_span(&span).unwrap();
+        assert_eq!(event.model.as_str(), "gpt-4");
+        assert_eq!(
+            event.model_version,
+            Some(ModelVersion::new("gpt-4", "0613"))
+        );
+    }
+
+    #[test]
+    fn test_parse_span_labels() {
+        let parser = OtlpParser::default();
+        let span = json!({
+            "attributes": {
+                "service.name": "test-service",
+                "llm.model": "gpt-4",
+                "llm.prompt": "Test prompt",
+                "llm.response": "Test response",
+                "sentinel.label.team": "search",
+                "sentinel.label.env": "prod"
+            }
+        });
+
+        let event = parser.parse_span(&span).unwrap();
+        assert_eq!(event.labels.len(), 2);
+        assert_eq!(event.labels["team"], "search");
+        assert_eq!(event.labels["env"], "prod");
+    }
+
+    #[test]
+    fn test_parse_span_environment() {
+        let parser = OtlpParser::default();
+        let mut span = json!({
+            "resource": {
+                "attributes": {
+                    "deployment.environment.name": "Staging"
+                }
+            },
+            "attributes": {
+                "service.name": "test-service",
+                "llm.model": "gpt-4",
+                "llm.prompt": "Test prompt",
+                "llm.response": "Test response",
+                "deployment.environment": "prod"
+            }
+        });
+
+        let event = parser.parse_span(&span).unwrap();
+        assert_eq!(event.environment.unwrap().as_str(), "staging");
+
+        span.as_object_mut().unwrap().remove("resource");
+        let event = parser.parse_span(&span).unwrap();
+        assert_eq!(event.environment.unwrap().as_str(), "prod");
+    }
+
+    #[test]
+    fn test_parse_span_latency_breakdown() {
+        let parser = OtlpParser::default();
+        let child = |name: &str, start_ms: i64, end_ms: i64, attributes: Value| {
+            json!({
+                "name": name,
+                "start_time_unix_nano": start_ms * 1_000_000,
+                "end_time_unix_nano": end_ms * 1_000_000,
+                "attributes": attributes
+            })
+        };
+        let span = json!({
+            "attributes": {
+                "service.name": "test-service",
+                "llm.model": "gpt-4",
+                "llm.prompt": "Test prompt",
+                "llm.response": "Test response",
+                "llm.latency_ms": 900.0
+            },
+            "children": [
+                child("vector_search", 0, 120, json!({})),
+                child("rerank", 120, 150, json!({"db.system": "qdrant"})),
+                child("execute_tool", 150, 350, json!({"gen_ai.tool.name": "weather"})),
+                child("chat gpt-4", 350, 850, json!({"gen_ai.system": "openai"})),
+                child("moderation", 850, 880, json!({"sentinel.component": "Guardrail"})),
+                child("serialize", 880, 890, json!({})),
+                json!({"name": "unfinished"})
+            ]
+        });
+
+        let event = parser.parse_span(&span).unwrap();
+        let breakdown = &event.latency_breakdown;
+        assert_eq!(breakdown.len(), 5);
+        assert_eq!(breakdown[RETRIEVAL_COMPONENT], 150.0);
+        assert_eq!(breakdown[TOOL_COMPONENT], 200.0);
+        assert_eq!(breakdown[PROVIDER_COMPONENT], 500.0);
+        assert_eq!(breakdown["guardrail"], 30.0);
+        assert_eq!(breakdown[OTHER_COMPONENT], 10.0);
     }
 
     #[test]
@@ -316,4 +547,154 @@ mod tests {
         assert!(embedding.is_some());
         assert_eq!(embedding.unwrap().len(), 5);
     }
+
+    mod properties {
+        use super::*;
+        use crate::validation::EventValidator;
+        use proptest::prelude::*;
+
+        const ATTRIBUTE_KEYS: &[&str] = &[
+            "service.name",
+            "service.version",
+            "llm.model",
+            "llm.prompt",
+            "llm.prompt.tokens",
+            "llm.prompt.embedding",
+            "llm.response",
+            "llm.response.tokens",
+            "llm.response.finish_reason",
+            "llm.response.embedding",
+            "llm.response.model",
+            "llm.latency_ms",
+            "llm.cost_usd",
+            "user.id",
+            "api.key",
+            "cloud.region",
+        ];
+
+        fn arb_text() -> impl Strategy<Value = String> {
+            prop_oneof!["\\PC{0,64}", "[a-z0-9@. -]{0,64}", "gpt-4(-[0-9]{4})?"]
+        }
+
+        fn arb_number() -> impl Strategy<Value = Value> {
+            prop_oneof![
+                any::<i64>().prop_map(Value::from),
+                any::<u64>().prop_map(Value::from),
+                any::<f64>().prop_map(Value::from),
+                (-1.0e6..1.0e6f64).prop_map(Value::from),
+            ]
+        }
+
+        fn arb_json() -> impl Strategy<Value = Value> {
+            let leaf = prop_oneof![
+                Just(Value::Null),
+                any::<bool>().prop_map(Value::from),
+                arb_number(),
+                arb_text().prop_map(Value::from),
+            ];
+            leaf.prop_recursive(4, 64, 8, |inner| {
+                prop_oneof![
+                    prop::collection::vec(inner.clone(), 0..8).prop_map(Value::from),
+                    prop::collection::hash_map(arb_text(), inner, 0..8)
+                        .prop_map(|map| Value::Object(map.into_iter().collect())),
+                ]
+            })
+        }
+
+        fn arb_attribute_value() -> impl Strategy<Value = Value> {
+            prop_oneof![
+                3 => arb_text().prop_map(Value::from),
+                2 => arb_number(),
+                1 => prop::collection::vec(arb_number(), 0..16).prop_map(Value::from),
+                1 => arb_json(),
+            ]
+        }
+
+        /// Span-shaped objects: known attribute keys with values of any type
+        fn arb_span() -> impl Strategy<Value = Value> {
+            (
+                prop::collection::vec(
+                    (prop::sample::select(ATTRIBUTE_KEYS), arb_attribute_value()),
+                    0..20,
+                ),
+                prop::option::of(arb_number()),
+                prop::option::of(arb_number()),
+                prop::option::of((arb_number(), prop::option::of(arb_json()))),
+                prop::option::of(arb_json()),
+            )
+                .prop_map(|(attributes, start, end, status, trace_id)| {
+                    let attributes: serde_json::Map<String, Value> = attributes
+                        .into_iter()
+                        .map(|(key, value)| (key.to_string(), value))
+                        .collect();
+                    let mut span = json!({ "attributes": attributes });
+                    if let Some(start) = start {
+                        span["start_time_unix_nano"] = start;
+                    }
+                    if let Some(end) = end {
+                        span["end_time_unix_nano"] = end;
+                    }
+                    if let Some((code, message)) = status {
+                        span["status"] = json!({ "code": code, "message": message });
+                    }
+                    if let Some(trace_id) = trace_id {
+                        span["trace_id"] = trace_id;
+                    }
+                    span
+                })
+        }
+
+        proptest! {
+            #[test]
+            fn parse_span_never_panics(span in arb_json()) {
+                let result = OtlpParser::default().parse_span(&span);
+                prop_assert!(matches!(result, Ok(_) | Err(Error::Ingestion(_))));
+            }
+
+            #[test]
+            fn parsed_spans_validate_or_reject(span in arb_span(), max_text_length in 0..128usize) {
+                let validator = EventValidator::default();
+                match OtlpParser::new(max_text_length).parse_span(&span) {
+                    Ok(mut event) => {
+                        prop_assert!(event.prompt.text.chars().count() <= max_text_length + 14);
+                        match validator.validate(&event) {
+                            Ok(()) => {
+                                prop_assert!(event.latency_ms.is_finite() && event.latency_ms >= 0.0);
+                                prop_assert!(event.cost_usd.is_finite() && event.cost_usd >= 0.0);
+                            }
+                            Err(Error::Validation(_)) => {}
+                            Err(e) => prop_assert!(false, "unexpected error type: {:?}", e),
+                        }
+                        prop_assert!(validator.sanitize(&mut event).is_ok());
+                        prop_assert!(!event.metadata.contains_key("api_key"));
+                    }
+                    Err(Error::Ingestion(_)) => {}
+                    Err(e) => prop_assert!(false, "unexpected error type: {:?}", e),
+                }
+            }
+
+            #[test]
+            fn well_formed_spans_parse(
+                model in arb_text(),
+                prompt in arb_text(),
+                response in arb_text(),
+                start in any::<i64>(),
+                end in any::<i64>(),
+            ) {
+                let span = json!({
+                    "start_time_unix_nano": start,
+                    "end_time_unix_nano": end,
+                    "attributes": {
+                        "llm.model": model,
+                        "llm.prompt": prompt,
+                        "llm.response": response,
+                    },
+                });
+
+                let event = OtlpParser::default().parse_span(&span).unwrap();
+                prop_assert_eq!(event.model.as_str(), model.as_str());
+                prop_assert!(event.latency_ms.is_finite());
+            }
+        }
+    }
 }
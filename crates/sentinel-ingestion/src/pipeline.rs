@@ -3,6 +3,7 @@
 use crate::{otlp::OtlpParser, validation::EventValidator};
 use llm_sentinel_core::{
     events::TelemetryEvent,
+    pricing::PriceTable,
     Result, Error,
 };
 use std::sync::Arc;
@@ -41,6 +42,7 @@ pub struct IngestionPipeline {
     validator: Arc<EventValidator>,
     #[allow(dead_code)]
     parser: Arc<OtlpParser>,
+    pricing: Option<Arc<PriceTable>>,
     tx: Option<UnboundedSender<TelemetryEvent>>,
     rx: Option<UnboundedReceiver<TelemetryEvent>>,
     worker_handles: Vec<JoinHandle<()>>,
@@ -52,6 +54,7 @@ impl std::fmt::Debug for IngestionPipeline {
             .field("config", &self.config)
             .field("validator", &self.validator)
             .field("parser", &self.parser)
+            .field("pricing", &self.pricing.is_some())
             .field("tx", &self.tx.is_some())
             .field("rx", &self.rx.is_some())
             .field("worker_handles", &self.worker_handles.len())
@@ -68,17 +71,23 @@ impl IngestionPipeline {
             config,
             validator: Arc::new(EventValidator::default()),
             parser: Arc::new(OtlpParser::default()),
+            pricing: None,
             tx: Some(tx),
             rx: Some(rx),
             worker_handles: Vec::new(),
         }
     }
 
+    /// Compute missing event costs from the given price table
+    pub fn with_pricing(mut self, pricing: Arc<PriceTable>) -> Self {
+        self.pricing = Some(pricing);
+        self
+    }
+
     /// Get a sender for pushing events into the pipeline
     pub fn sender(&self) -> Result<UnboundedSender<TelemetryEvent>> {
         self.tx
-            .as_ref()
-            .map(|tx| tx.clone())
+            .clone()
             .ok_or_else(|| Error::internal("Pipeline sender not available"))
     }
 
@@ -100,6 +109,7 @@ impl IngestionPipeline {
         for worker_id in 0..self.config.workers {
             let rx_clone = Arc::clone(&rx_shared);
             let validator = Arc::clone(&self.validator);
+            let pricing = self.pricing.clone();
             let enable_validation = self.config.enable_validation;
             let enable_sanitization = self.config.enable_sanitization;
 
@@ -108,6 +118,7 @@ impl IngestionPipeline {
                     worker_id,
                     rx_clone,
                     validator,
+                    pricing,
                     enable_validation,
                     enable_sanitization,
                 )
@@ -126,6 +137,7 @@ impl IngestionPipeline {
         worker_id: usize,
         rx: Arc<Mutex<UnboundedReceiver<TelemetryEvent>>>,
         validator: Arc<EventValidator>,
+        pricing: Option<Arc<PriceTable>>,
         enable_validation: bool,
         enable_sanitization: bool,
     ) {
@@ -168,6 +180,11 @@ impl IngestionPipeline {
                         }
                     }
 
+                    // Enrich with computed cost
+                    if let Some(pricing) = &pricing {
+                        pricing.apply(&mut event);
+                    }
+
                     debug!(
                         worker_id,
                         event_id = %event.event_id,
@@ -228,29 +245,10 @@ pub struct PipelineStats {
 #[cfg(test)]
 mod tests {
     use super::*;
-    use llm_sentinel_core::{
-        events::{PromptInfo, ResponseInfo},
-        types::{ModelId, ServiceId},
-    };
+    use llm_sentinel_fixtures::event;
 
     fn create_test_event() -> TelemetryEvent {
-        TelemetryEvent::new(
-            ServiceId::new("test"),
-            ModelId::new("gpt-4"),
-            PromptInfo {
-                text: "Test".to_string(),
-                tokens: 10,
-                embedding: None,
-            },
-            ResponseInfo {
-                text: "Response".to_string(),
-                tokens: 20,
-                finish_reason: "stop".to_string(),
-                embedding: None,
-            },
-            100.0,
-            0.001,
-        )
+        event().cost_usd(0.001).build()
     }
 
     #[tokio::test]
@@ -265,6 +263,7 @@ mod tests {
         let pipeline = IngestionPipeline::new(PipelineConfig::default());
         let sender = pipeline.sender();
         assert!(sender.is_ok());
+        assert!(sender.unwrap().send(create_test_event()).is_ok());
     }
 
     #[tokio::test]
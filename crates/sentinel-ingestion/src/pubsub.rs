@@ -0,0 +1,362 @@
+//! Google Cloud Pub/Sub subscriber for telemetry ingestion.
+//!
+//! Uses the Pub/Sub REST API (pull + acknowledge). Authentication uses a
+//! configured access token, the GCE/GKE metadata server, or no auth at all
+//! when talking to the emulator (`PUBSUB_EMULATOR_HOST`).
+
+use crate::Ingester;
+use async_trait::async_trait;
+use base64::{engine::general_purpose::STANDARD as BASE64, Engine as _};
+use llm_sentinel_core::{
+    config::PubSubSubscriptionConfig,
+    events::TelemetryEvent,
+    gcp::{endpoint_auth, TokenSource},
+    Error, Result,
+};
+use reqwest::Client;
+use serde::Deserialize;
+use std::time::Duration;
+use tracing::{debug, error, info};
+use validator::Validate;
+
+const DEFAULT_ENDPOINT: &str = "https://pubsub.googleapis.com";
+
+/// Pull response from the Pub/Sub API
+#[derive(Debug, Default, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct PullResponse {
+    #[serde(default)]
+    received_messages: Vec<ReceivedMessage>,
+}
+
+/// A message received from a subscription
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct ReceivedMessage {
+    ack_id: String,
+    message: PubsubMessage,
+}
+
+/// Pub/Sub message payload
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct PubsubMessage {
+    #[serde(default)]
+    data: String,
+    #[serde(default)]
+    message_id: String,
+}
+
+/// Pub/Sub-based telemetry ingester
+pub struct PubSubIngester {
+    client: Client,
+    token: TokenSource,
+    subscription_url: String,
+    batch_size: u32,
+    batch_timeout: Duration,
+    running: bool,
+}
+
+impl std::fmt::Debug for PubSubIngester {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("PubSubIngester")
+            .field("subscription_url", &self.subscription_url)
+            .field("batch_size", &self.batch_size)
+            .field("batch_timeout", &self.batch_timeout)
+            .field("running", &self.running)
+            .finish()
+    }
+}
+
+impl PubSubIngester {
+    /// Create a new Pub/Sub ingester
+    pub fn new(
+        config: &PubSubSubscriptionConfig,
+        batch_size: usize,
+        batch_timeout_ms: u64,
+    ) -> Result<Self> {
+        config
+            .validate()
+            .map_err(|e| Error::config(format!("Invalid Pub/Sub configuration: {}", e)))?;
+
+        let emulator_host = std::env::var("PUBSUB_EMULATOR_HOST").ok();
+        let (endpoint, token) = endpoint_auth(
+            DEFAULT_ENDPOINT,
+            config.endpoint.as_deref(),
+            config.access_token.as_deref(),
+            emulator_host.as_deref(),
+        );
+
+        let subscription_url = format!(
+            "{}/v1/projects/{}/subscriptions/{}",
+            endpoint.trim_end_matches('/'),
+            config.project_id,
+            config.subscription
+        );
+
+        info!("Creating Pub/Sub ingester for {}", subscription_url);
+
+        let client = Client::builder()
+            .timeout(Duration::from_secs(config.timeout_secs))
+            .build()
+            .map_err(|e| Error::config(format!("Failed to create HTTP client: {}", e)))?;
+
+        Ok(Self {
+            client,
+            token,
+            subscription_url,
+            batch_size: (batch_size as u32).clamp(1, config.max_messages),
+            batch_timeout: Duration::from_millis(batch_timeout_ms),
+            running: false,
+        })
+    }
+
+    /// Build an authenticated request
+    async fn request(&self, method: reqwest::Method, url: &str) -> Result<reqwest::RequestBuilder> {
+        let mut request = self.client.request(method, url);
+        if let Some(token) = self.token.token(&self.client).await? {
+            request = request.bearer_auth(token);
+        }
+        Ok(request)
+    }
+
+    /// Decode a Pub/Sub message into a telemetry event
+    fn parse_message(message: &PubsubMessage) -> Result<TelemetryEvent> {
+        let payload = BASE64
+            .decode(message.data.as_bytes())
+            .map_err(|e| Error::ingestion(format!("Invalid base64 message data: {}", e)))?;
+
+        let event: TelemetryEvent = serde_json::from_slice(&payload)
+            .map_err(|e| Error::ingestion(format!("Failed to parse telemetry event: {}", e)))?;
+
+        event
+            .validate()
+            .map_err(|e| Error::validation(format!("Invalid telemetry event: {}", e)))?;
+
+        Ok(event)
+    }
+
+    /// Acknowledge received messages
+    async fn acknowledge(&self, ack_ids: Vec<String>) -> Result<()> {
+        if ack_ids.is_empty() {
+            return Ok(());
+        }
+
+        let url = format!("{}:acknowledge", self.subscription_url);
+        self.request(reqwest::Method::POST, &url)
+            .await?
+            .json(&serde_json::json!({ "ackIds": ack_ids }))
+            .send()
+            .await
+            .and_then(|r| r.error_for_status())
+            .map_err(|e| Error::connection(format!("Failed to acknowledge Pub/Sub messages: {}", e)))?;
+
+        Ok(())
+    }
+}
+
+#[async_trait]
+impl Ingester for PubSubIngester {
+    async fn start(&mut self) -> Result<()> {
+        if self.running {
+            return Err(Error::already_exists("Ingester is already running"));
+        }
+
+        info!("Starting Pub/Sub ingester for {}", self.subscription_url);
+        self.running = true;
+        if let Err(e) = self.health_check().await {
+            self.running = false;
+            return Err(e);
+        }
+        info!("Pub/Sub ingester started successfully");
+
+        Ok(())
+    }
+
+    async fn stop(&mut self) -> Result<()> {
+        if self.running {
+            info!("Stopping Pub/Sub ingester");
+            self.running = false;
+        }
+        Ok(())
+    }
+
+    async fn next_batch(&mut self) -> Result<Vec<TelemetryEvent>> {
+        if !self.running {
+            return Err(Error::internal("Ingester is not running"));
+        }
+
+        let url = format!("{}:pull", self.subscription_url);
+        let request = self
+            .request(reqwest::Method::POST, &url)
+            .await?
+            .json(&serde_json::json!({ "maxMessages": self.batch_size }));
+
+        // Pull blocks server-side until messages arrive; treat our batch timeout as "no messages"
+        let response = match tokio::time::timeout(self.batch_timeout, request.send()).await {
+            Ok(result) => result
+                .and_then(|r| r.error_for_status())
+                .map_err(|e| {
+                    error!("Pub/Sub pull error: {}", e);
+                    metrics::counter!("sentinel_errors_total", "error_type" => "pubsub").increment(1);
+                    Error::connection(format!("Pub/Sub pull failed: {}", e))
+                })?,
+            Err(_) => {
+                debug!("No events received in batch");
+                return Ok(Vec::new());
+            }
+        };
+
+        let pulled: PullResponse = response
+            .json()
+            .await
+            .map_err(|e| Error::ingestion(format!("Invalid Pub/Sub pull response: {}", e)))?;
+
+        let mut batch = Vec::with_capacity(pulled.received_messages.len());
+        let mut ack_ids = Vec::with_capacity(pulled.received_messages.len());
+
+        for received in pulled.received_messages {
+            // Unparseable messages are acknowledged too, otherwise they would be redelivered forever
+            ack_ids.push(received.ack_id);
+
+            match Self::parse_message(&received.message) {
+                Ok(event) => {
+                    batch.push(event);
+                    metrics::counter!("sentinel_events_ingested_total").increment(1);
+                }
+                Err(e) => {
+                    error!(message_id = %received.message.message_id, "Failed to parse message: {}", e);
+                    metrics::counter!("sentinel_events_dropped_total", "reason" => "parse_failed")
+                        .increment(1);
+                }
+            }
+        }
+
+        self.acknowledge(ack_ids).await?;
+
+        if !batch.is_empty() {
+            debug!("Received batch of {} events", batch.len());
+        }
+
+        Ok(batch)
+    }
+
+    async fn health_check(&self) -> Result<()> {
+        if !self.running {
+            return Err(Error::internal("Ingester is not running"));
+        }
+
+        self.request(reqwest::Method::GET, &self.subscription_url)
+            .await?
+            .send()
+            .await
+            .and_then(|r| r.error_for_status())
+            .map_err(|e| Error::connection(format!("Pub/Sub health check failed: {}", e)))?;
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use llm_sentinel_fixtures::event;
+    use wiremock::{
+        matchers::{method, path},
+        Mock, MockServer, ResponseTemplate,
+    };
+
+    fn create_test_config(endpoint: &str) -> PubSubSubscriptionConfig {
+        PubSubSubscriptionConfig {
+            project_id: "test-project".to_string(),
+            subscription: "telemetry-sub".to_string(),
+            endpoint: Some(endpoint.to_string()),
+            access_token: None,
+            max_messages: 100,
+            timeout_secs: 5,
+        }
+    }
+
+    fn create_test_event() -> TelemetryEvent {
+        event().cost_usd(0.001).build()
+    }
+
+    #[test]
+    fn test_invalid_config() {
+        let mut config = create_test_config("http://localhost:8085");
+        config.subscription = String::new();
+        assert!(PubSubIngester::new(&config, 100, 1000).is_err());
+    }
+
+    #[test]
+    fn test_parse_message() {
+        let event = create_test_event();
+        let message = PubsubMessage {
+            data: BASE64.encode(serde_json::to_vec(&event).unwrap()),
+            message_id: "1".to_string(),
+        };
+
+        let parsed = PubSubIngester::parse_message(&message).unwrap();
+        assert_eq!(parsed.event_id, event.event_id);
+
+        let invalid = PubsubMessage {
+            data: BASE64.encode(b"not json"),
+            message_id: "2".to_string(),
+        };
+        assert!(PubSubIngester::parse_message(&invalid).is_err());
+    }
+
+    #[tokio::test]
+    async fn test_next_batch_pulls_and_acks() {
+        let mock_server = MockServer::start().await;
+        let event = create_test_event();
+
+        Mock::given(method("GET"))
+            .and(path("/v1/projects/test-project/subscriptions/telemetry-sub"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({})))
+            .mount(&mock_server)
+            .await;
+
+        Mock::given(method("POST"))
+            .and(path("/v1/projects/test-project/subscriptions/telemetry-sub:pull"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({
+                "receivedMessages": [
+                    {
+                        "ackId": "ack-1",
+                        "message": {
+                            "data": BASE64.encode(serde_json::to_vec(&event).unwrap()),
+                            "messageId": "1"
+                        }
+                    },
+                    {
+                        "ackId": "ack-2",
+                        "message": { "data": BASE64.encode(b"garbage"), "messageId": "2" }
+                    }
+                ]
+            })))
+            .mount(&mock_server)
+            .await;
+
+        Mock::given(method("POST"))
+            .and(path("/v1/projects/test-project/subscriptions/telemetry-sub:acknowledge"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({})))
+            .expect(1)
+            .mount(&mock_server)
+            .await;
+
+        let mut ingester =
+            PubSubIngester::new(&create_test_config(&mock_server.uri()), 100, 5000).unwrap();
+        ingester.start().await.unwrap();
+
+        let batch = ingester.next_batch().await.unwrap();
+        assert_eq!(batch.len(), 1);
+        assert_eq!(batch[0].event_id, event.event_id);
+    }
+
+    #[tokio::test]
+    async fn test_next_batch_requires_start() {
+        let mut ingester =
+            PubSubIngester::new(&create_test_config("http://localhost:8085"), 100, 1000).unwrap();
+        assert!(ingester.next_batch().await.is_err());
+    }
+}
@@ -0,0 +1,342 @@
+//! Data quality scoring of ingested telemetry.
+//!
+//! Events with missing token counts, zero latency, no cost or a timestamp
+//! far from when they were received still parse and validate, but feeding
+//! them to detection silently drags baselines towards zero. Each event is
+//! scored by the share of checks it passes, and each producer (service)
+//! gets a rolling score over its recent events. Scores are exported as
+//! metrics, and a producer whose score falls below the configured minimum
+//! raises one alert until it recovers.
+
+use chrono::{DateTime, Utc};
+use llm_sentinel_core::{
+    clock::Clock,
+    config::DataQualityConfig,
+    events::{AnomalyContext, AnomalyDetails, AnomalyEvent, TelemetryEvent},
+    types::{AnomalyType, DetectionMethod, ModelId, ServiceId},
+    Error, Result,
+};
+use std::collections::{HashMap, VecDeque};
+use std::sync::Mutex;
+use tracing::warn;
+
+/// Number of checks an event is scored on
+const CHECKS: usize = 4;
+
+/// A data quality problem found on an event
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum QualityIssue {
+    /// No prompt token count, or no response token count on a successful
+    /// request
+    MissingTokens,
+    /// Latency of zero
+    ZeroLatency,
+    /// No cost, even after pricing
+    MissingCost,
+    /// Timestamp too far from the receive time
+    ClockSkew,
+}
+
+impl QualityIssue {
+    /// Metric label of the issue
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::MissingTokens => "missing_tokens",
+            Self::ZeroLatency => "zero_latency",
+            Self::MissingCost => "missing_cost",
+            Self::ClockSkew => "clock_skew",
+        }
+    }
+}
+
+/// Quality assessment of one event
+#[derive(Debug, Clone, PartialEq)]
+pub struct QualityScore {
+    /// Share of checks passed, from 0 to 1
+    pub score: f64,
+    /// Problems found
+    pub issues: Vec<QualityIssue>,
+}
+
+/// Recent event scores of one producer
+#[derive(Debug, Default)]
+struct ProducerQuality {
+    scores: VecDeque<f64>,
+    sum: f64,
+    seen: u64,
+    alerting: bool,
+}
+
+impl ProducerQuality {
+    fn push(&mut self, score: f64, window: usize) -> f64 {
+        self.scores.push_back(score);
+        self.sum += score;
+        if self.scores.len() > window {
+            self.sum -= self.scores.pop_front().unwrap_or_default();
+        }
+        self.seen += 1;
+        self.sum / self.scores.len() as f64
+    }
+}
+
+/// Scores events and tracks per-producer data quality
+#[derive(Debug)]
+pub struct DataQualityMonitor {
+    config: DataQualityConfig,
+    clock: Clock,
+    producers: Mutex<HashMap<ServiceId, ProducerQuality>>,
+}
+
+impl DataQualityMonitor {
+    /// Create a monitor from configuration, reading receive times from
+    /// `clock`
+    pub fn from_config(config: &DataQualityConfig, clock: Clock) -> Result<Self> {
+        if config.window_events == 0 {
+            return Err(Error::config("Data quality window_events must be positive"));
+        }
+        if !(0.0..=1.0).contains(&config.min_score) {
+            return Err(Error::config("Data quality min_score must be in [0, 1]"));
+        }
+        if config
+            .exclude_below
+            .is_some_and(|score| !(0.0..=1.0).contains(&score))
+        {
+            return Err(Error::config(
+                "Data quality exclude_below must be in [0, 1]",
+            ));
+        }
+
+        Ok(Self {
+            config: config.clone(),
+            clock,
+            producers: Mutex::new(HashMap::new()),
+        })
+    }
+
+    /// Score one event received at `received`
+    pub fn score(&self, event: &TelemetryEvent, received: DateTime<Utc>) -> QualityScore {
+        let mut issues = Vec::new();
+        if event.prompt.tokens == 0 || (event.response.tokens == 0 && event.errors.is_empty()) {
+            issues.push(QualityIssue::MissingTokens);
+        }
+        if event.latency_ms <= 0.0 {
+            issues.push(QualityIssue::ZeroLatency);
+        }
+        if event.cost_usd <= 0.0 {
+            issues.push(QualityIssue::MissingCost);
+        }
+        let skew = (received - event.timestamp).num_seconds().unsigned_abs();
+        if skew > self.config.max_clock_skew_secs {
+            issues.push(QualityIssue::ClockSkew);
+        }
+
+        QualityScore {
+            score: (CHECKS - issues.len()) as f64 / CHECKS as f64,
+            issues,
+        }
+    }
+
+    /// Whether an event with this score is kept out of detection
+    pub fn is_excluded(&self, score: &QualityScore) -> bool {
+        self.config
+            .exclude_below
+            .is_some_and(|threshold| score.score < threshold)
+    }
+
+    /// Score an event received now and update its producer's score
+    ///
+    /// Returns the event's score, and an alert when the producer's score
+    /// has just fallen below the minimum. A producer alerts again only
+    /// after its score has recovered.
+    pub fn record(&self, event: &TelemetryEvent) -> (QualityScore, Option<AnomalyEvent>) {
+        let now = self.clock.now();
+        let score = self.score(event, now);
+        let service = event.service_name.to_string();
+        for issue in &score.issues {
+            metrics::counter!(
+                "sentinel_data_quality_issues_total",
+                "service" => service.clone(),
+                "issue" => issue.as_str()
+            )
+            .increment(1);
+        }
+
+        let mut producers = self.producers.lock().unwrap();
+        let producer = producers.entry(event.service_name.clone()).or_default();
+        let producer_score = producer.push(score.score, self.config.window_events);
+        metrics::gauge!("sentinel_data_quality_score", "service" => service).set(producer_score);
+
+        let below = producer_score < self.config.min_score;
+        let warmed_up = producer.seen >= self.config.min_events as u64;
+        let alert = if below && warmed_up && !producer.alerting {
+            producer.alerting = true;
+            warn!(
+                service = %event.service_name,
+                score = producer_score,
+                "Producer data quality dropped"
+            );
+            Some(self.alert(event, producer_score, producer.scores.len(), now))
+        } else {
+            if !below {
+                producer.alerting = false;
+            }
+            None
+        };
+
+        (score, alert)
+    }
+
+    /// Current rolling score of every producer seen
+    pub fn scores(&self) -> HashMap<ServiceId, f64> {
+        self.producers
+            .lock()
+            .unwrap()
+            .iter()
+            .map(|(service, producer)| {
+                (
+                    service.clone(),
+                    producer.sum / producer.scores.len().max(1) as f64,
+                )
+            })
+            .collect()
+    }
+
+    fn alert(
+        &self,
+        event: &TelemetryEvent,
+        producer_score: f64,
+        sample_count: usize,
+        now: DateTime<Utc>,
+    ) -> AnomalyEvent {
+        let mut alert = AnomalyEvent::new(
+            self.config.severity,
+            AnomalyType::Custom("data_quality".to_string()),
+            event.service_name.clone(),
+            ModelId::new("all"),
+            DetectionMethod::Custom("data_quality".to_string()),
+            (1.0 - producer_score).clamp(0.5, 1.0),
+            AnomalyDetails {
+                metric: "data_quality_score".to_string(),
+                value: producer_score,
+                baseline: 1.0,
+                threshold: self.config.min_score,
+                deviation_sigma: None,
+                additional: HashMap::new(),
+                observations: Vec::new(),
+            },
+            AnomalyContext {
+                trace_id: None,
+                user_id: None,
+                region: None,
+                time_window: format!("last {} events", sample_count),
+                sample_count,
+                window: None,
+                additional: HashMap::new(),
+            },
+        );
+        alert.timestamp = now;
+        alert.root_cause = Some(format!(
+            "Telemetry from {} scores {:.2} on data quality (minimum {:.2}); check its \
+             token counts, latency, cost and clock",
+            event.service_name, producer_score, self.config.min_score
+        ));
+        alert
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::Duration;
+
+    fn config() -> DataQualityConfig {
+        DataQualityConfig {
+            max_clock_skew_secs: 300,
+            window_events: 10,
+            min_events: 5,
+            min_score: 0.9,
+            exclude_below: Some(0.5),
+            severity: llm_sentinel_core::types::Severity::Medium,
+        }
+    }
+
+    fn event(at: DateTime<Utc>) -> TelemetryEvent {
+        llm_sentinel_fixtures::event()
+            .service("chat")
+            .timestamp(at)
+            .build()
+    }
+
+    #[test]
+    fn test_event_score() {
+        let now = Utc::now();
+        let monitor = DataQualityMonitor::from_config(&config(), Clock::manual(now)).unwrap();
+        let clean = monitor.score(&event(now), now);
+        assert_eq!(clean.score, 1.0);
+        assert!(clean.issues.is_empty());
+
+        let mut bad = event(now - Duration::hours(1));
+        bad.prompt_mut().tokens = 0;
+        bad.latency_ms = 0.0;
+        bad.cost_usd = 0.0;
+        let scored = monitor.score(&bad, now);
+        assert_eq!(scored.score, 0.0);
+        assert_eq!(
+            scored.issues,
+            vec![
+                QualityIssue::MissingTokens,
+                QualityIssue::ZeroLatency,
+                QualityIssue::MissingCost,
+                QualityIssue::ClockSkew,
+            ]
+        );
+        assert!(monitor.is_excluded(&scored));
+        assert!(!monitor.is_excluded(&clean));
+
+        // Failed requests may legitimately have no response tokens
+        let mut failed = event(now);
+        failed.response_mut().tokens = 0;
+        failed.errors.push("timeout".to_string());
+        assert_eq!(monitor.score(&failed, now).score, 1.0);
+    }
+
+    #[test]
+    fn test_alerts_once_per_drop() {
+        let now = Utc::now();
+        let monitor = DataQualityMonitor::from_config(&config(), Clock::manual(now)).unwrap();
+        let mut zero_latency = event(now);
+        zero_latency.latency_ms = 0.0;
+
+        for _ in 0..5 {
+            assert!(monitor.record(&event(now)).1.is_none());
+        }
+        // Drops below 0.9 on the fourth bad event, alerting once
+        let alerts: Vec<_> = (0..5)
+            .filter_map(|_| monitor.record(&zero_latency).1)
+            .collect();
+        assert_eq!(alerts.len(), 1);
+        assert_eq!(alerts[0].service_name, ServiceId::new("chat"));
+        assert_eq!(
+            alerts[0].detection_method,
+            DetectionMethod::Custom("data_quality".to_string())
+        );
+        assert!(alerts[0].details.value < 0.9);
+
+        // Recovers, then alerts again on the next drop
+        for _ in 0..10 {
+            assert!(monitor.record(&event(now)).1.is_none());
+        }
+        assert_eq!(monitor.scores()[&ServiceId::new("chat")], 1.0);
+        let alerts = (0..5)
+            .filter_map(|_| monitor.record(&zero_latency).1)
+            .count();
+        assert_eq!(alerts, 1);
+    }
+
+    #[test]
+    fn test_invalid_config() {
+        let mut invalid = config();
+        invalid.window_events = 0;
+        assert!(DataQualityMonitor::from_config(&invalid, Clock::system()).is_err());
+    }
+}
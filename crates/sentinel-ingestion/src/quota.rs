@@ -0,0 +1,212 @@
+//! Per-service ingestion quotas.
+//!
+//! Each service gets a token bucket refilled at its events-per-second quota
+//! and holding up to `burst_secs` worth of events. Events beyond the quota
+//! are rejected before enrichment and detection, so a single misbehaving
+//! producer cannot starve every other service of detection capacity. A
+//! configurable fraction of over-quota events is still processed, keeping
+//! the flooding service visible.
+
+use llm_sentinel_core::{config::IngestionQuotaConfig, types::ServiceId, Error, Result};
+use std::collections::HashMap;
+use std::sync::Mutex;
+use std::time::Instant;
+
+/// Outcome of a quota check
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum QuotaDecision {
+    /// Within quota
+    Admitted,
+    /// Over quota, but sampled for processing
+    Sampled,
+    /// Over quota and dropped
+    Rejected,
+}
+
+impl QuotaDecision {
+    /// Whether the event should be processed
+    pub fn is_processed(self) -> bool {
+        !matches!(self, Self::Rejected)
+    }
+}
+
+/// Token bucket of one service
+#[derive(Debug)]
+struct Bucket {
+    rate: f64,
+    capacity: f64,
+    tokens: f64,
+    updated: Instant,
+    /// Accumulated sample rate; an over-quota event is sampled each time it
+    /// reaches one
+    sample_credit: f64,
+}
+
+/// Enforces per-service events-per-second quotas
+#[derive(Debug)]
+pub struct ServiceQuotas {
+    default_rate: Option<f64>,
+    overrides: HashMap<ServiceId, f64>,
+    burst_secs: f64,
+    sample_rate: f64,
+    buckets: Mutex<HashMap<ServiceId, Bucket>>,
+}
+
+impl ServiceQuotas {
+    /// Create quotas from configuration
+    pub fn from_config(config: &IngestionQuotaConfig) -> Result<Self> {
+        let valid_rate = |rate: f64| rate.is_finite() && rate > 0.0;
+        if config
+            .default_events_per_sec
+            .is_some_and(|rate| !valid_rate(rate))
+        {
+            return Err(Error::config(
+                "Quota default_events_per_sec must be positive",
+            ));
+        }
+        if let Some((service, _)) = config.services.iter().find(|(_, rate)| !valid_rate(**rate)) {
+            return Err(Error::config(format!(
+                "Quota for service {} must be positive",
+                service
+            )));
+        }
+        if !(config.burst_secs.is_finite() && config.burst_secs > 0.0) {
+            return Err(Error::config("Quota burst_secs must be positive"));
+        }
+        if !(0.0..=1.0).contains(&config.over_quota_sample_rate) {
+            return Err(Error::config(
+                "Quota over_quota_sample_rate must be in [0, 1]",
+            ));
+        }
+
+        Ok(Self {
+            default_rate: config.default_events_per_sec,
+            overrides: config
+                .services
+                .iter()
+                .map(|(service, rate)| (ServiceId::new(service), *rate))
+                .collect(),
+            burst_secs: config.burst_secs,
+            sample_rate: config.over_quota_sample_rate,
+            buckets: Mutex::new(HashMap::new()),
+        })
+    }
+
+    /// Quota of a service in events per second (`None` when unlimited)
+    pub fn rate_for(&self, service: &ServiceId) -> Option<f64> {
+        self.overrides.get(service).copied().or(self.default_rate)
+    }
+
+    /// Check one event of a service against its quota
+    pub fn check(&self, service: &ServiceId) -> QuotaDecision {
+        self.check_at(service, Instant::now())
+    }
+
+    /// Check one event of a service against its quota at the given time
+    pub fn check_at(&self, service: &ServiceId, now: Instant) -> QuotaDecision {
+        let Some(rate) = self.rate_for(service) else {
+            return QuotaDecision::Admitted;
+        };
+
+        let mut buckets = self.buckets.lock().unwrap();
+        let capacity = (rate * self.burst_secs).max(1.0);
+        let bucket = buckets.entry(service.clone()).or_insert_with(|| Bucket {
+            rate,
+            capacity,
+            tokens: capacity,
+            updated: now,
+            sample_credit: 0.0,
+        });
+
+        let elapsed = now.saturating_duration_since(bucket.updated).as_secs_f64();
+        bucket.tokens = (bucket.tokens + elapsed * bucket.rate).min(bucket.capacity);
+        bucket.updated = bucket.updated.max(now);
+
+        if bucket.tokens >= 1.0 {
+            bucket.tokens -= 1.0;
+            return QuotaDecision::Admitted;
+        }
+
+        bucket.sample_credit += self.sample_rate;
+        if bucket.sample_credit >= 1.0 {
+            bucket.sample_credit -= 1.0;
+            QuotaDecision::Sampled
+        } else {
+            QuotaDecision::Rejected
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::Duration;
+
+    fn quotas(sample_rate: f64) -> ServiceQuotas {
+        ServiceQuotas::from_config(&IngestionQuotaConfig {
+            default_events_per_sec: Some(10.0),
+            services: HashMap::from([("batch".to_string(), 2.0)]),
+            burst_secs: 1.0,
+            over_quota_sample_rate: sample_rate,
+        })
+        .unwrap()
+    }
+
+    #[test]
+    fn test_quota_enforced_per_service() {
+        let quotas = quotas(0.0);
+        let start = Instant::now();
+        let batch = ServiceId::new("batch");
+        let chat = ServiceId::new("chat");
+
+        let admitted = (0..20)
+            .filter(|_| quotas.check_at(&batch, start).is_processed())
+            .count();
+        assert_eq!(admitted, 2);
+        // Another service keeps its own quota
+        let admitted = (0..20)
+            .filter(|_| quotas.check_at(&chat, start).is_processed())
+            .count();
+        assert_eq!(admitted, 10);
+
+        // Refilled at the quota rate
+        let later = start + Duration::from_millis(500);
+        assert_eq!(quotas.check_at(&batch, later), QuotaDecision::Admitted);
+        assert_eq!(quotas.check_at(&batch, later), QuotaDecision::Rejected);
+    }
+
+    #[test]
+    fn test_over_quota_sampling() {
+        let quotas = quotas(0.25);
+        let start = Instant::now();
+        let batch = ServiceId::new("batch");
+
+        let decisions: Vec<_> = (0..10).map(|_| quotas.check_at(&batch, start)).collect();
+        let count = |decision| decisions.iter().filter(|d| **d == decision).count();
+        assert_eq!(count(QuotaDecision::Admitted), 2);
+        assert_eq!(count(QuotaDecision::Sampled), 2);
+        assert_eq!(count(QuotaDecision::Rejected), 6);
+    }
+
+    #[test]
+    fn test_unlimited_without_default() {
+        let quotas = ServiceQuotas::from_config(&IngestionQuotaConfig {
+            default_events_per_sec: None,
+            services: HashMap::from([("batch".to_string(), 1.0)]),
+            burst_secs: 1.0,
+            over_quota_sample_rate: 0.0,
+        })
+        .unwrap();
+        let chat = ServiceId::new("chat");
+        assert_eq!(quotas.rate_for(&chat), None);
+        assert!((0..1000).all(|_| quotas.check(&chat).is_processed()));
+
+        let invalid = IngestionQuotaConfig {
+            default_events_per_sec: Some(0.0),
+            services: HashMap::new(),
+            burst_secs: 1.0,
+            over_quota_sample_rate: 0.0,
+        };
+        assert!(ServiceQuotas::from_config(&invalid).is_err());
+    }
+}
@@ -0,0 +1,204 @@
+//! Clock skew and late event handling.
+//!
+//! Producers with drifting clocks, replays from queues that fell behind and
+//! retried batches all deliver events timestamped far from when they are
+//! received. Windowed detection and baselines assume roughly live events,
+//! so events outside the tolerated skew are rejected, clamped to the
+//! receive time, flagged, or (when late) routed to a backfill path that
+//! stores them without feeding live baselines.
+
+use chrono::{DateTime, Utc};
+use llm_sentinel_core::{
+    clock::Clock,
+    config::{ClockSkewConfig, SkewPolicy},
+    events::TelemetryEvent,
+    Error, Result,
+};
+
+/// Metadata key marking an event outside the tolerated skew (`late` or
+/// `future`)
+pub const CLOCK_SKEW_KEY: &str = "clock_skew";
+
+/// Metadata key holding the skew of a flagged event in seconds (positive
+/// when late)
+pub const CLOCK_SKEW_SECS_KEY: &str = "clock_skew_secs";
+
+/// Metadata key holding the timestamp a clamped event was sent with
+pub const ORIGINAL_TIMESTAMP_KEY: &str = "original_timestamp";
+
+/// Outcome of the skew check of one event
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SkewDecision {
+    /// Within the tolerated skew
+    OnTime,
+    /// Outside the tolerated skew, processed as is
+    Flagged,
+    /// Timestamp moved to the receive time
+    Clamped,
+    /// Late, to be stored without running detection
+    Backfill,
+    /// Dropped
+    Rejected,
+}
+
+/// Applies the configured skew policies
+#[derive(Debug)]
+pub struct ClockSkewPolicy {
+    config: ClockSkewConfig,
+    clock: Clock,
+}
+
+impl ClockSkewPolicy {
+    /// Create the policy from configuration, reading receive times from
+    /// `clock`
+    pub fn from_config(config: &ClockSkewConfig, clock: Clock) -> Result<Self> {
+        if config.future_policy == SkewPolicy::Backfill {
+            return Err(Error::config("Clock skew future_policy cannot be backfill"));
+        }
+        Ok(Self {
+            config: config.clone(),
+            clock,
+        })
+    }
+
+    /// Check an event received now, updating its timestamp or metadata as
+    /// the policy requires
+    pub fn apply(&self, event: &mut TelemetryEvent) -> SkewDecision {
+        self.apply_at(event, self.clock.now())
+    }
+
+    /// Check an event received at `received`
+    pub fn apply_at(&self, event: &mut TelemetryEvent, received: DateTime<Utc>) -> SkewDecision {
+        let skew_ms = (received - event.timestamp).num_milliseconds();
+        let (direction, limit, policy) = if skew_ms >= 0 {
+            ("late", self.config.max_late_secs, self.config.late_policy)
+        } else {
+            (
+                "future",
+                self.config.max_future_secs,
+                self.config.future_policy,
+            )
+        };
+        let skew_secs = skew_ms.unsigned_abs() as f64 / 1000.0;
+        metrics::histogram!("sentinel_event_clock_skew_seconds", "direction" => direction)
+            .record(skew_secs);
+
+        if skew_secs <= limit as f64 {
+            return SkewDecision::OnTime;
+        }
+
+        let decision = match policy {
+            SkewPolicy::Reject => SkewDecision::Rejected,
+            SkewPolicy::Clamp => {
+                event.metadata.insert(
+                    ORIGINAL_TIMESTAMP_KEY.to_string(),
+                    event.timestamp.to_rfc3339(),
+                );
+                event.timestamp = received;
+                SkewDecision::Clamped
+            }
+            SkewPolicy::Flag | SkewPolicy::Backfill => {
+                event
+                    .metadata
+                    .insert(CLOCK_SKEW_KEY.to_string(), direction.to_string());
+                event.metadata.insert(
+                    CLOCK_SKEW_SECS_KEY.to_string(),
+                    format!("{:.3}", skew_ms as f64 / 1000.0),
+                );
+                if policy == SkewPolicy::Backfill {
+                    SkewDecision::Backfill
+                } else {
+                    SkewDecision::Flagged
+                }
+            }
+        };
+
+        let action = match decision {
+            SkewDecision::Rejected => "rejected",
+            SkewDecision::Clamped => "clamped",
+            SkewDecision::Backfill => "backfill",
+            _ => "flagged",
+        };
+        metrics::counter!(
+            "sentinel_events_skewed_total",
+            "direction" => direction,
+            "action" => action
+        )
+        .increment(1);
+        decision
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::Duration;
+
+    fn policy(future_policy: SkewPolicy, late_policy: SkewPolicy) -> ClockSkewPolicy {
+        ClockSkewPolicy::from_config(
+            &ClockSkewConfig {
+                max_future_secs: 60,
+                max_late_secs: 300,
+                future_policy,
+                late_policy,
+            },
+            Clock::system(),
+        )
+        .unwrap()
+    }
+
+    fn event(at: DateTime<Utc>) -> TelemetryEvent {
+        llm_sentinel_fixtures::event()
+            .service("chat")
+            .timestamp(at)
+            .build()
+    }
+
+    #[test]
+    fn test_within_tolerance() {
+        let now = Utc::now();
+        let policy = policy(SkewPolicy::Reject, SkewPolicy::Reject);
+        let mut recent = event(now - Duration::seconds(200));
+        assert_eq!(policy.apply_at(&mut recent, now), SkewDecision::OnTime);
+        let mut ahead = event(now + Duration::seconds(30));
+        assert_eq!(policy.apply_at(&mut ahead, now), SkewDecision::OnTime);
+        assert!(ahead.metadata.is_empty());
+    }
+
+    #[test]
+    fn test_policies() {
+        let now = Utc::now();
+        let clamping = policy(SkewPolicy::Clamp, SkewPolicy::Backfill);
+
+        let sent = now + Duration::hours(1);
+        let mut future = event(sent);
+        assert_eq!(clamping.apply_at(&mut future, now), SkewDecision::Clamped);
+        assert_eq!(future.timestamp, now);
+        assert_eq!(future.metadata[ORIGINAL_TIMESTAMP_KEY], sent.to_rfc3339());
+
+        let mut late = event(now - Duration::hours(2));
+        assert_eq!(clamping.apply_at(&mut late, now), SkewDecision::Backfill);
+        assert_eq!(late.timestamp, now - Duration::hours(2));
+        assert_eq!(late.metadata[CLOCK_SKEW_KEY], "late");
+        assert_eq!(late.metadata[CLOCK_SKEW_SECS_KEY], "7200.000");
+
+        let flagging = policy(SkewPolicy::Flag, SkewPolicy::Reject);
+        let mut future = event(now + Duration::hours(1));
+        assert_eq!(flagging.apply_at(&mut future, now), SkewDecision::Flagged);
+        assert_eq!(future.metadata[CLOCK_SKEW_KEY], "future");
+        assert_eq!(future.metadata[CLOCK_SKEW_SECS_KEY], "-3600.000");
+        let mut late = event(now - Duration::hours(2));
+        assert_eq!(flagging.apply_at(&mut late, now), SkewDecision::Rejected);
+    }
+
+    #[test]
+    fn test_future_backfill_rejected() {
+        let config = ClockSkewConfig {
+            max_future_secs: 60,
+            max_late_secs: 300,
+            future_policy: SkewPolicy::Backfill,
+            late_policy: SkewPolicy::Backfill,
+        };
+        assert!(ClockSkewPolicy::from_config(&config, Clock::system()).is_err());
+    }
+}
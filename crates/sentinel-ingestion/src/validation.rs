@@ -1,6 +1,6 @@
 //! Event validation and sanitization.
 
-use llm_sentinel_core::{events::TelemetryEvent, Error, Result};
+use llm_sentinel_core::{events::TelemetryEvent, labels, Error, Result};
 use tracing::{debug, warn};
 use validator::Validate;
 
@@ -51,6 +51,13 @@ impl EventValidator {
             .validate()
             .map_err(|e| Error::validation(format!("Event validation failed: {}", e)))?;
 
+        // NaN slips through every range comparison below
+        if !event.latency_ms.is_finite() || !event.cost_usd.is_finite() {
+            return Err(Error::validation(
+                "Latency and cost must be finite numbers".to_string(),
+            ));
+        }
+
         // Validate latency range
         if event.latency_ms < self.min_latency_ms {
             warn!(
@@ -108,6 +115,19 @@ impl EventValidator {
             return Err(Error::validation("Cost cannot be negative".to_string()));
         }
 
+        labels::validate_labels(&event.labels)?;
+
+        if let Some((component, ms)) = event
+            .latency_breakdown
+            .iter()
+            .find(|(_, ms)| !ms.is_finite() || **ms < 0.0)
+        {
+            return Err(Error::validation(format!(
+                "Latency of component {} must be a non-negative number, got {}",
+                component, ms
+            )));
+        }
+
         debug!(
             event_id = %event.event_id,
             service = %event.service_name,
@@ -125,7 +145,7 @@ impl EventValidator {
                 event_id = %event.event_id,
                 "Potential PII detected in prompt, masking"
             );
-            event.prompt.text = self.mask_pii(&event.prompt.text);
+            event.prompt_mut().text = self.mask_pii(&event.prompt.text);
         }
 
         if self.contains_pii(&event.response.text) {
@@ -133,7 +153,7 @@ impl EventValidator {
                 event_id = %event.event_id,
                 "Potential PII detected in response, masking"
             );
-            event.response.text = self.mask_pii(&event.response.text);
+            event.response_mut().text = self.mask_pii(&event.response.text);
         }
 
         // Remove sensitive metadata
@@ -206,25 +226,14 @@ mod tests {
         events::{PromptInfo, ResponseInfo},
         types::{ModelId, ServiceId},
     };
+    use llm_sentinel_fixtures::event;
 
     fn create_test_event() -> TelemetryEvent {
-        TelemetryEvent::new(
-            ServiceId::new("test"),
-            ModelId::new("gpt-4"),
-            PromptInfo {
-                text: "Test prompt".to_string(),
-                tokens: 10,
-                embedding: None,
-            },
-            ResponseInfo {
-                text: "Test response".to_string(),
-                tokens: 20,
-                finish_reason: "stop".to_string(),
-                embedding: None,
-            },
-            100.0,
-            0.001,
-        )
+        event()
+            .prompt("Test prompt")
+            .response("Test response")
+            .cost_usd(0.001)
+            .build()
     }
 
     #[test]
@@ -256,14 +265,15 @@ mod tests {
         event.latency_ms = -1.0;
         // Should fail validator validation
         assert!(event.validate().is_err());
+        assert!(validator.validate(&event).is_err());
     }
 
     #[test]
     fn test_tokens_too_high() {
         let validator = EventValidator::default();
         let mut event = create_test_event();
-        event.prompt.tokens = 100_000;
-        event.response.tokens = 50_000; // Total 150k > 128k
+        event.prompt_mut().tokens = 100_000;
+        event.response_mut().tokens = 50_000; // Total 150k > 128k
         assert!(validator.validate(&event).is_err());
     }
 
@@ -275,6 +285,29 @@ mod tests {
         assert!(validator.validate(&event).is_err());
     }
 
+    #[test]
+    fn test_invalid_labels() {
+        let validator = EventValidator::default();
+        let event = create_test_event().with_label("team", "search");
+        assert!(validator.validate(&event).is_ok());
+
+        let event = create_test_event().with_label("Team Name", "search");
+        assert!(validator.validate(&event).is_err());
+    }
+
+    #[test]
+    fn test_invalid_latency_breakdown() {
+        let validator = EventValidator::default();
+        let mut event = create_test_event();
+        event
+            .latency_breakdown
+            .insert("retrieval".to_string(), 40.0);
+        assert!(validator.validate(&event).is_ok());
+
+        event.latency_breakdown.insert("tool".to_string(), -5.0);
+        assert!(validator.validate(&event).is_err());
+    }
+
     #[test]
     fn test_pii_detection() {
         let validator = EventValidator::default();
@@ -315,4 +348,103 @@ mod tests {
         // User ID should remain
         assert!(event.metadata.contains_key("user_id"));
     }
+
+    mod properties {
+        use super::*;
+        use proptest::prelude::*;
+        use std::collections::HashMap;
+
+        fn arb_text() -> impl Strategy<Value = String> {
+            prop_oneof!["\\PC{0,128}", "[a-z0-9@. -]{0,128}", "SSN [0-9-]{0,16}"]
+        }
+
+        fn arb_metadata() -> impl Strategy<Value = HashMap<String, String>> {
+            prop::collection::hash_map(
+                prop_oneof![
+                    Just("api_key".to_string()),
+                    Just("password".to_string()),
+                    Just("user_id".to_string()),
+                    "[a-z_]{1,12}",
+                ],
+                arb_text(),
+                0..6,
+            )
+        }
+
+        /// Arbitrary telemetry payloads, including non-finite and extreme values
+        fn arb_event() -> impl Strategy<Value = TelemetryEvent> {
+            (
+                (arb_text(), any::<u32>(), arb_text(), any::<u32>()),
+                (any::<f64>(), -1.0e3..1.0e6f64),
+                (any::<f64>(), -1.0..10.0f64),
+                arb_metadata(),
+            )
+                .prop_map(
+                    |(
+                        (prompt, prompt_tokens, response, response_tokens),
+                        (wild_latency, latency_ms),
+                        (wild_cost, cost_usd),
+                        metadata,
+                    )| {
+                        // Alternate between arbitrary and plausible magnitudes
+                        let latency_ms = if prompt_tokens % 2 == 0 {
+                            wild_latency
+                        } else {
+                            latency_ms
+                        };
+                        let cost_usd = if response_tokens % 2 == 0 {
+                            wild_cost
+                        } else {
+                            cost_usd
+                        };
+                        let mut event = TelemetryEvent::new(
+                            ServiceId::new("test"),
+                            ModelId::new("gpt-4"),
+                            PromptInfo {
+                                text: prompt,
+                                tokens: prompt_tokens % 100_000,
+                                embedding: None,
+                            },
+                            ResponseInfo {
+                                text: response,
+                                tokens: response_tokens,
+                                finish_reason: "stop".to_string(),
+                                embedding: None,
+                            },
+                            latency_ms,
+                            cost_usd,
+                        );
+                        event.metadata = metadata;
+                        event
+                    },
+                )
+        }
+
+        proptest! {
+            #[test]
+            fn validate_accepts_only_sane_events(event in arb_event()) {
+                let validator = EventValidator::default();
+                match validator.validate(&event) {
+                    Ok(()) => {
+                        prop_assert!(event.latency_ms.is_finite());
+                        prop_assert!(event.latency_ms >= validator.min_latency_ms);
+                        prop_assert!(event.latency_ms <= validator.max_latency_ms);
+                        prop_assert!(event.cost_usd >= 0.0 && event.cost_usd <= validator.max_cost_usd);
+                        prop_assert!(event.total_tokens() <= validator.max_tokens);
+                    }
+                    Err(Error::Validation(_)) => {}
+                    Err(e) => prop_assert!(false, "unexpected error type: {:?}", e),
+                }
+            }
+
+            #[test]
+            fn sanitize_strips_sensitive_metadata(mut event in arb_event()) {
+                let validator = EventValidator::default();
+                prop_assert!(validator.sanitize(&mut event).is_ok());
+                prop_assert!(!event.metadata.contains_key("api_key"));
+                prop_assert!(!event.metadata.contains_key("password"));
+                prop_assert!(!event.metadata.contains_key("secret"));
+            }
+        }
+    }
 }
@@ -0,0 +1,44 @@
+//! # Sentinel Plugins
+//!
+//! Sandboxed extension points for LLM-Sentinel.
+//!
+//! This crate provides:
+//! - WASM detector plugins reporting anomalies for telemetry events
+//! - WASM enricher plugins rewriting telemetry events before detection
+//! - Per-plugin resource limits (memory, fuel, output size)
+//! - Hot-reloaded Rhai scripts hooking enrichment, detection
+//!   post-processing and alert routing
+//!
+//! Plugins and scripts are loaded from configuration, so teams can ship
+//! custom logic without rebuilding sentinel.
+
+#![warn(missing_debug_implementations, rust_2018_idioms, unreachable_pub)]
+
+pub mod script;
+pub mod wasm;
+
+use async_trait::async_trait;
+use llm_sentinel_core::{events::TelemetryEvent, Result};
+
+/// Trait for telemetry enrichers
+///
+/// Enrichers run after ingestion and before storage and detection, and may
+/// add metadata or normalize fields.
+#[async_trait]
+pub trait Enricher: Send + Sync {
+    /// Enrich a telemetry event in place
+    async fn enrich(&self, event: &mut TelemetryEvent) -> Result<()>;
+
+    /// Get the enricher name
+    fn name(&self) -> &str;
+}
+
+/// Re-export commonly used types
+pub mod prelude {
+    pub use crate::script::ScriptHooks;
+    pub use crate::wasm::{
+        load_plugins, LoadedPlugins, PluginFinding, PluginLimits, WasmDetector, WasmEnricher,
+        WasmPlugin,
+    };
+    pub use crate::Enricher;
+}
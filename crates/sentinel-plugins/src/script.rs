@@ -0,0 +1,546 @@
+//! Rhai scripting hooks.
+//!
+//! A lighter-weight extension point than WASM plugins: `*.rhai` scripts in a
+//! directory, reloaded when they change. A script defines any of these
+//! functions:
+//!
+//! - `fn enrich(event)`: return the modified event, or `()` to leave it
+//!   unchanged
+//! - `fn post_detect(anomaly, event)`: return the modified anomaly, `false`
+//!   to suppress it, or `()` to leave it unchanged
+//! - `fn route(anomaly, alerters)`: return the names of the alerters to
+//!   deliver to, or `()` for all of them
+//!
+//! Events and anomalies are passed as object maps copied from the structs,
+//! so scripts cannot reach into sentinel itself, and results are validated
+//! by deserializing them back. Scripts run in file name order, each seeing
+//! the previous script's output. A failing hook is logged and skipped.
+
+use crate::Enricher;
+use async_trait::async_trait;
+use llm_sentinel_core::{
+    config::ScriptingConfig,
+    events::{AnomalyEvent, TelemetryEvent},
+    Error, Result,
+};
+use rhai::{CallFnOptions, Dynamic, Engine, FuncArgs, Scope, AST};
+use serde::{de::DeserializeOwned, Serialize};
+use std::{
+    path::{Path, PathBuf},
+    sync::{Arc, RwLock},
+    time::{Duration, SystemTime},
+};
+use tracing::{debug, error, info, warn};
+
+/// Hooks a script can define, with their arity
+const HOOKS: &[(&str, usize)] = &[("enrich", 1), ("post_detect", 2), ("route", 2)];
+
+/// A compiled script
+struct Script {
+    name: String,
+    modified: SystemTime,
+    ast: AST,
+    hooks: Vec<&'static str>,
+}
+
+impl Script {
+    fn has(&self, hook: &str) -> bool {
+        self.hooks.contains(&hook)
+    }
+}
+
+/// Scripting hooks loaded from a directory
+pub struct ScriptHooks {
+    dir: PathBuf,
+    engine: Engine,
+    scripts: RwLock<Vec<Arc<Script>>>,
+}
+
+impl ScriptHooks {
+    /// Load all scripts in `dir`
+    ///
+    /// Fails if the directory cannot be read or any script does not compile.
+    pub fn new(dir: impl Into<PathBuf>, max_operations: u64) -> Result<Self> {
+        let mut engine = Engine::new();
+        engine.set_max_operations(max_operations);
+        engine.set_max_call_levels(32);
+        engine.set_max_expr_depths(64, 32);
+        engine.set_max_string_size(1024 * 1024);
+        engine.set_max_array_size(10_000);
+        engine.set_max_map_size(10_000);
+        engine.disable_symbol("eval");
+        engine.on_print(|text| info!(target: "sentinel::script", "{}", text));
+        engine.on_debug(|text, source, pos| {
+            debug!(target: "sentinel::script", source = source.unwrap_or(""), %pos, "{}", text)
+        });
+
+        let hooks = Self {
+            dir: dir.into(),
+            engine,
+            scripts: RwLock::new(Vec::new()),
+        };
+        hooks.load(true)?;
+        Ok(hooks)
+    }
+
+    /// Load the scripts configured under `scripting`
+    pub fn from_config(config: &ScriptingConfig) -> Result<Self> {
+        Self::new(&config.dir, config.max_operations)
+    }
+
+    /// Script directory
+    pub fn dir(&self) -> &Path {
+        &self.dir
+    }
+
+    /// Names of the loaded scripts, in run order
+    pub fn script_names(&self) -> Vec<String> {
+        self.snapshot().iter().map(|s| s.name.clone()).collect()
+    }
+
+    /// Pick up added, changed and removed scripts
+    ///
+    /// A script that no longer compiles keeps its previous version. Returns
+    /// whether the set of loaded scripts changed.
+    pub fn reload(&self) -> Result<bool> {
+        self.load(false)
+    }
+
+    /// Reload scripts every `interval` in the background
+    pub fn start_reload_task(self: Arc<Self>, interval: Duration) -> tokio::task::JoinHandle<()> {
+        tokio::spawn(async move {
+            let mut ticker = tokio::time::interval(interval);
+            ticker.tick().await;
+            loop {
+                ticker.tick().await;
+                if let Err(e) = self.reload() {
+                    error!(dir = %self.dir.display(), "Failed to reload scripts: {}", e);
+                }
+            }
+        })
+    }
+
+    /// Run `post_detect` hooks on an anomaly
+    ///
+    /// Returns `None` when a script suppresses the anomaly.
+    pub fn post_detect(
+        &self,
+        mut anomaly: AnomalyEvent,
+        event: &TelemetryEvent,
+    ) -> Option<AnomalyEvent> {
+        let scripts = self.snapshot();
+        if !scripts.iter().any(|s| s.has("post_detect")) {
+            return Some(anomaly);
+        }
+        let event = match to_dynamic(event) {
+            Ok(event) => event,
+            Err(e) => {
+                warn!("Failed to expose event to scripts: {}", e);
+                return Some(anomaly);
+            }
+        };
+
+        for script in scripts.iter().filter(|s| s.has("post_detect")) {
+            let Ok(current) = to_dynamic(&anomaly) else {
+                continue;
+            };
+            let Some(result) = self.call(script, "post_detect", (current, event.clone())) else {
+                continue;
+            };
+
+            if result.is_unit() {
+                continue;
+            }
+            if let Ok(keep) = result.as_bool() {
+                if !keep {
+                    info!(
+                        script = %script.name,
+                        alert_id = %anomaly.alert_id,
+                        "Anomaly suppressed by script"
+                    );
+                    return None;
+                }
+                continue;
+            }
+            match from_dynamic::<AnomalyEvent>(&result) {
+                Ok(updated) if updated.alert_id == anomaly.alert_id => anomaly = updated,
+                Ok(_) => self.hook_error(script, "post_detect", "alert_id may not change"),
+                Err(e) => self.hook_error(script, "post_detect", e),
+            }
+        }
+
+        Some(anomaly)
+    }
+
+    /// Run `route` hooks to pick the alerters for an anomaly
+    ///
+    /// Scripts can only narrow the set; unknown names are ignored.
+    pub fn route(&self, anomaly: &AnomalyEvent, mut alerters: Vec<String>) -> Vec<String> {
+        let scripts = self.snapshot();
+        if !scripts.iter().any(|s| s.has("route")) {
+            return alerters;
+        }
+        let Ok(anomaly) = to_dynamic(anomaly) else {
+            return alerters;
+        };
+
+        for script in scripts.iter().filter(|s| s.has("route")) {
+            let names: rhai::Array = alerters.iter().cloned().map(Dynamic::from).collect();
+            let Some(result) = self.call(script, "route", (anomaly.clone(), names)) else {
+                continue;
+            };
+            if result.is_unit() {
+                continue;
+            }
+            match from_dynamic::<Vec<String>>(&result) {
+                Ok(selected) => alerters.retain(|name| selected.contains(name)),
+                Err(e) => self.hook_error(script, "route", e),
+            }
+        }
+
+        alerters
+    }
+
+    /// Scan the directory and compile new or changed scripts
+    fn load(&self, strict: bool) -> Result<bool> {
+        let entries = std::fs::read_dir(&self.dir).map_err(|e| {
+            Error::config(format!(
+                "Failed to read script directory {}: {}",
+                self.dir.display(),
+                e
+            ))
+        })?;
+
+        let mut files: Vec<(String, PathBuf, SystemTime)> = Vec::new();
+        for entry in entries.flatten() {
+            let path = entry.path();
+            if path.extension().and_then(|e| e.to_str()) != Some("rhai") {
+                continue;
+            }
+            let Ok(modified) = entry.metadata().and_then(|m| m.modified()) else {
+                continue;
+            };
+            let name = entry.file_name().to_string_lossy().into_owned();
+            files.push((name, path, modified));
+        }
+        files.sort_by(|a, b| a.0.cmp(&b.0));
+
+        let previous = self.snapshot();
+        let mut changed = previous.len() != files.len();
+        let mut scripts = Vec::with_capacity(files.len());
+
+        for (name, path, modified) in files {
+            let existing = previous.iter().find(|s| s.name == name);
+            if let Some(script) = existing.filter(|s| s.modified == modified) {
+                scripts.push(script.clone());
+                continue;
+            }
+
+            match self.compile(&name, &path, modified) {
+                Ok(script) => {
+                    info!(script = %name, hooks = ?script.hooks, "Loaded script");
+                    scripts.push(Arc::new(script));
+                    changed = true;
+                }
+                Err(e) if strict => return Err(e),
+                Err(e) => {
+                    error!(script = %name, "Failed to compile script: {}", e);
+                    metrics::counter!("sentinel_script_errors_total", "script" => name, "hook" => "compile")
+                        .increment(1);
+                    if let Some(script) = existing {
+                        scripts.push(script.clone());
+                    } else {
+                        changed = true;
+                    }
+                }
+            }
+        }
+
+        *self.scripts.write().unwrap() = scripts;
+        Ok(changed)
+    }
+
+    fn compile(&self, name: &str, path: &Path, modified: SystemTime) -> Result<Script> {
+        let source = std::fs::read_to_string(path)
+            .map_err(|e| Error::config(format!("Failed to read script {}: {}", name, e)))?;
+        let ast = self
+            .engine
+            .compile(&source)
+            .map_err(|e| Error::config(format!("Invalid script {}: {}", name, e)))?;
+
+        let hooks = HOOKS
+            .iter()
+            .filter(|(hook, arity)| {
+                ast.iter_functions()
+                    .any(|f| f.name == *hook && f.params.len() == *arity)
+            })
+            .map(|(hook, _)| *hook)
+            .collect();
+
+        Ok(Script {
+            name: name.to_string(),
+            modified,
+            ast,
+            hooks,
+        })
+    }
+
+    fn snapshot(&self) -> Vec<Arc<Script>> {
+        self.scripts.read().unwrap().clone()
+    }
+
+    /// Call a hook, logging failures
+    fn call(&self, script: &Script, hook: &str, args: impl FuncArgs) -> Option<Dynamic> {
+        let options = CallFnOptions::new().eval_ast(false);
+        match self.engine.call_fn_with_options::<Dynamic>(
+            options,
+            &mut Scope::new(),
+            &script.ast,
+            hook,
+            args,
+        ) {
+            Ok(result) => Some(result),
+            Err(e) => {
+                self.hook_error(script, hook, e);
+                None
+            }
+        }
+    }
+
+    fn hook_error(&self, script: &Script, hook: &str, error: impl std::fmt::Display) {
+        warn!(script = %script.name, hook, "Script hook failed: {}", error);
+        metrics::counter!(
+            "sentinel_script_errors_total",
+            "script" => script.name.clone(),
+            "hook" => hook.to_string()
+        )
+        .increment(1);
+    }
+}
+
+#[async_trait]
+impl Enricher for ScriptHooks {
+    async fn enrich(&self, event: &mut TelemetryEvent) -> Result<()> {
+        for script in self.snapshot().iter().filter(|s| s.has("enrich")) {
+            let Some(result) = self.call(script, "enrich", (to_dynamic(&*event)?,)) else {
+                continue;
+            };
+            if result.is_unit() {
+                continue;
+            }
+            match from_dynamic::<TelemetryEvent>(&result) {
+                Ok(enriched) if enriched.event_id == event.event_id => *event = enriched,
+                Ok(_) => self.hook_error(script, "enrich", "event_id may not change"),
+                Err(e) => self.hook_error(script, "enrich", e),
+            }
+        }
+        Ok(())
+    }
+
+    fn name(&self) -> &str {
+        "scripts"
+    }
+}
+
+impl std::fmt::Debug for ScriptHooks {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("ScriptHooks")
+            .field("dir", &self.dir)
+            .field("scripts", &self.script_names())
+            .finish()
+    }
+}
+
+/// Copy a value into a script object map
+fn to_dynamic<T: Serialize>(value: &T) -> Result<Dynamic> {
+    rhai::serde::to_dynamic(value).map_err(|e| Error::internal(e.to_string()))
+}
+
+/// Read a script value back, going through JSON so integers are accepted
+/// where floats are expected
+fn from_dynamic<T: DeserializeOwned>(value: &Dynamic) -> std::result::Result<T, String> {
+    let json: serde_json::Value = rhai::serde::from_dynamic(value).map_err(|e| e.to_string())?;
+    serde_json::from_value(json).map_err(|e| e.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use llm_sentinel_core::types::Severity;
+    use llm_sentinel_fixtures::{anomaly, event};
+
+    struct ScriptDir(PathBuf, std::cell::Cell<u64>);
+
+    impl ScriptDir {
+        fn new(test: &str) -> Self {
+            let dir = std::env::temp_dir().join(format!(
+                "sentinel-scripts-{}-{}",
+                std::process::id(),
+                test
+            ));
+            std::fs::create_dir_all(&dir).unwrap();
+            Self(dir, std::cell::Cell::new(0))
+        }
+
+        fn write(&self, name: &str, source: &str) {
+            let path = self.0.join(name);
+            std::fs::write(&path, source).unwrap();
+            // Distinct mtimes regardless of filesystem timestamp resolution
+            self.1.set(self.1.get() + 1);
+            std::fs::File::options()
+                .write(true)
+                .open(&path)
+                .unwrap()
+                .set_modified(SystemTime::UNIX_EPOCH + Duration::from_secs(self.1.get()))
+                .unwrap();
+        }
+    }
+
+    impl Drop for ScriptDir {
+        fn drop(&mut self) {
+            let _ = std::fs::remove_dir_all(&self.0);
+        }
+    }
+
+    fn create_test_event(service: &str) -> TelemetryEvent {
+        event().service(service).build()
+    }
+
+    fn create_anomaly(severity: Severity) -> AnomalyEvent {
+        anomaly().severity(severity).build()
+    }
+
+    #[tokio::test]
+    async fn test_enrich_hook() {
+        let dir = ScriptDir::new("enrich");
+        dir.write(
+            "10-team.rhai",
+            r#"
+                fn enrich(event) {
+                    event.metadata.team = "search";
+                    event.latency_ms = 250;
+                    event
+                }
+            "#,
+        );
+        dir.write(
+            "20-noop.rhai",
+            "fn enrich(event) { print(`seen ${event.service_name}`); }",
+        );
+
+        let hooks = ScriptHooks::new(&dir.0, 10_000).unwrap();
+        assert_eq!(hooks.script_names(), vec!["10-team.rhai", "20-noop.rhai"]);
+
+        let mut event = create_test_event("test");
+        hooks.enrich(&mut event).await.unwrap();
+        assert_eq!(event.metadata["team"], "search");
+        assert_eq!(event.latency_ms, 250.0);
+    }
+
+    #[tokio::test]
+    async fn test_operation_limit() {
+        let dir = ScriptDir::new("limit");
+        dir.write("spin.rhai", "fn enrich(event) { loop {} }");
+
+        let hooks = ScriptHooks::new(&dir.0, 1_000).unwrap();
+        let mut event = create_test_event("test");
+        let original = event.clone();
+        hooks.enrich(&mut event).await.unwrap();
+        assert_eq!(event.metadata, original.metadata);
+    }
+
+    #[test]
+    fn test_post_detect_hook() {
+        let dir = ScriptDir::new("post-detect");
+        dir.write(
+            "triage.rhai",
+            r#"
+                fn post_detect(anomaly, event) {
+                    if event.service_name == "batch" {
+                        return false;
+                    }
+                    anomaly.severity = "critical";
+                    anomaly.remediation.push("Page the on-call");
+                    anomaly
+                }
+            "#,
+        );
+        let hooks = ScriptHooks::new(&dir.0, 10_000).unwrap();
+
+        let anomaly = hooks
+            .post_detect(create_anomaly(Severity::Medium), &create_test_event("chat"))
+            .unwrap();
+        assert_eq!(anomaly.severity, Severity::Critical);
+        assert_eq!(anomaly.remediation, vec!["Page the on-call"]);
+
+        assert!(hooks
+            .post_detect(
+                create_anomaly(Severity::Medium),
+                &create_test_event("batch")
+            )
+            .is_none());
+    }
+
+    #[test]
+    fn test_route_hook() {
+        let dir = ScriptDir::new("route");
+        dir.write(
+            "route.rhai",
+            r#"
+                fn route(anomaly, alerters) {
+                    if anomaly.severity == "critical" {
+                        return;
+                    }
+                    alerters.filter(|name| name != "pager")
+                }
+            "#,
+        );
+        let hooks = ScriptHooks::new(&dir.0, 10_000).unwrap();
+        let alerters = vec!["pager".to_string(), "slack".to_string()];
+
+        assert_eq!(
+            hooks.route(&create_anomaly(Severity::Critical), alerters.clone()),
+            alerters
+        );
+        assert_eq!(
+            hooks.route(&create_anomaly(Severity::Low), alerters),
+            vec!["slack"]
+        );
+    }
+
+    #[test]
+    fn test_hot_reload() {
+        let dir = ScriptDir::new("reload");
+        dir.write("route.rhai", r#"fn route(anomaly, alerters) { ["slack"] }"#);
+        let hooks = ScriptHooks::new(&dir.0, 10_000).unwrap();
+        let alerters = vec!["pager".to_string(), "slack".to_string()];
+        let anomaly = create_anomaly(Severity::High);
+        assert_eq!(hooks.route(&anomaly, alerters.clone()), vec!["slack"]);
+        assert!(!hooks.reload().unwrap());
+
+        // Changed script is picked up
+        dir.write("route.rhai", r#"fn route(anomaly, alerters) { ["pager"] }"#);
+        assert!(hooks.reload().unwrap());
+        assert_eq!(hooks.route(&anomaly, alerters.clone()), vec!["pager"]);
+
+        // A broken edit keeps the previous version
+        dir.write("route.rhai", "fn route(anomaly, alerters) {");
+        assert!(!hooks.reload().unwrap());
+        assert_eq!(hooks.route(&anomaly, alerters.clone()), vec!["pager"]);
+
+        // Removed scripts are dropped
+        std::fs::remove_file(dir.0.join("route.rhai")).unwrap();
+        assert!(hooks.reload().unwrap());
+        assert!(hooks.script_names().is_empty());
+        assert_eq!(hooks.route(&anomaly, alerters.clone()), alerters);
+    }
+
+    #[test]
+    fn test_invalid_scripts() {
+        let dir = ScriptDir::new("invalid");
+        dir.write("broken.rhai", "fn enrich(event) {");
+        assert!(ScriptHooks::new(&dir.0, 10_000).is_err());
+
+        assert!(ScriptHooks::new(dir.0.join("missing"), 10_000).is_err());
+    }
+}
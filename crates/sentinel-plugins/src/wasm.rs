@@ -0,0 +1,683 @@
+//! WASM plugin runtime.
+//!
+//! Plugins are WebAssembly modules run with wasmtime. Each call gets a fresh
+//! instance, so plugins cannot keep state between events or observe each
+//! other, and every call is bounded by the plugin's memory, fuel and output
+//! limits. Plugins have no imports: no filesystem, network or clock.
+//!
+//! # ABI
+//!
+//! A plugin module exports:
+//! - `memory`: its linear memory
+//! - `sentinel_alloc(len: i32) -> i32`: reserve `len` bytes for the input
+//! - `sentinel_detect(ptr: i32, len: i32) -> i64` (detectors) or
+//!   `sentinel_enrich(ptr: i32, len: i32) -> i64` (enrichers)
+//!
+//! The input is the JSON-encoded [`TelemetryEvent`]. The result packs the
+//! output location as `(ptr << 32) | len`; a zero length means "no anomaly"
+//! for detectors and "unchanged" for enrichers. Detectors return a JSON
+//! [`PluginFinding`], enrichers the JSON-encoded enriched event.
+
+use crate::Enricher;
+use async_trait::async_trait;
+use llm_sentinel_core::{
+    config::{PluginConfig, PluginKind},
+    events::{AnomalyContext, AnomalyDetails, AnomalyEvent, TelemetryEvent},
+    types::{AnomalyType, DetectionMethod, Severity},
+    Error, Result,
+};
+use llm_sentinel_detection::{Detector, DetectorStats, DetectorType};
+use serde::{Deserialize, Serialize};
+use std::{collections::HashMap, sync::Mutex, time::Instant};
+use tracing::{debug, info};
+use wasmtime::{Engine, InstancePre, Linker, Module, Store, StoreLimits, StoreLimitsBuilder};
+
+/// Export reserving input memory
+pub const ALLOC_EXPORT: &str = "sentinel_alloc";
+/// Detector entry point
+pub const DETECT_EXPORT: &str = "sentinel_detect";
+/// Enricher entry point
+pub const ENRICH_EXPORT: &str = "sentinel_enrich";
+
+/// Resource limits applied to every plugin call
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct PluginLimits {
+    /// Maximum linear memory in bytes
+    pub max_memory_bytes: usize,
+    /// Fuel available per call
+    pub fuel: u64,
+    /// Maximum output size in bytes
+    pub max_output_bytes: usize,
+}
+
+impl Default for PluginLimits {
+    fn default() -> Self {
+        Self {
+            max_memory_bytes: 16 * 1024 * 1024,
+            fuel: 10_000_000,
+            max_output_bytes: 1024 * 1024,
+        }
+    }
+}
+
+impl From<&PluginConfig> for PluginLimits {
+    fn from(config: &PluginConfig) -> Self {
+        Self {
+            max_memory_bytes: config.max_memory_bytes,
+            fuel: config.fuel,
+            max_output_bytes: config.max_output_bytes,
+        }
+    }
+}
+
+/// Per-call store state
+struct PluginState {
+    limits: StoreLimits,
+}
+
+/// A compiled WASM plugin
+pub struct WasmPlugin {
+    name: String,
+    kind: PluginKind,
+    limits: PluginLimits,
+    engine: Engine,
+    pre: InstancePre<PluginState>,
+}
+
+impl WasmPlugin {
+    /// Load the plugin described by a config entry
+    pub fn load(config: &PluginConfig) -> Result<Self> {
+        let bytes = std::fs::read(&config.path).map_err(|e| {
+            Error::config(format!(
+                "Failed to read plugin {} from {}: {}",
+                config.name, config.path, e
+            ))
+        })?;
+        Self::from_bytes(&config.name, config.kind, bytes, config.into())
+    }
+
+    /// Compile a plugin from WASM binary or text
+    pub fn from_bytes(
+        name: impl Into<String>,
+        kind: PluginKind,
+        bytes: impl AsRef<[u8]>,
+        limits: PluginLimits,
+    ) -> Result<Self> {
+        let name = name.into();
+        let invalid =
+            |e: wasmtime::Error| Error::config(format!("Invalid plugin {}: {:#}", name, e));
+
+        let mut config = wasmtime::Config::new();
+        config.consume_fuel(true);
+        let engine = Engine::new(&config).map_err(invalid)?;
+
+        let module = Module::new(&engine, bytes).map_err(invalid)?;
+        for export in ["memory", ALLOC_EXPORT, entry_point(kind)] {
+            if module.get_export(export).is_none() {
+                return Err(Error::config(format!(
+                    "Invalid plugin {}: missing export `{}`",
+                    name, export
+                )));
+            }
+        }
+
+        let pre = Linker::new(&engine)
+            .instantiate_pre(&module)
+            .map_err(invalid)?;
+
+        info!(plugin = %name, kind = ?kind, "Loaded WASM plugin");
+
+        Ok(Self {
+            name,
+            kind,
+            limits,
+            engine,
+            pre,
+        })
+    }
+
+    /// Plugin name
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// Plugin role
+    pub fn kind(&self) -> PluginKind {
+        self.kind
+    }
+
+    /// Resource limits
+    pub fn limits(&self) -> PluginLimits {
+        self.limits
+    }
+
+    /// Run the plugin's entry point on `input`
+    ///
+    /// Returns `None` when the plugin reports no output.
+    pub fn call(&self, input: &[u8]) -> Result<Option<Vec<u8>>> {
+        let start = Instant::now();
+        let failed = |e: wasmtime::Error| self.error(format!("{:#}", e));
+
+        let limits = StoreLimitsBuilder::new()
+            .memory_size(self.limits.max_memory_bytes)
+            .instances(1)
+            .build();
+        let mut store = Store::new(&self.engine, PluginState { limits });
+        store.limiter(|state| &mut state.limits);
+        store.set_fuel(self.limits.fuel).map_err(failed)?;
+
+        let instance = self.pre.instantiate(&mut store).map_err(failed)?;
+        let memory = instance
+            .get_memory(&mut store, "memory")
+            .ok_or_else(|| self.error("`memory` is not a memory export"))?;
+        let alloc = instance
+            .get_typed_func::<i32, i32>(&mut store, ALLOC_EXPORT)
+            .map_err(failed)?;
+        let entry = instance
+            .get_typed_func::<(i32, i32), i64>(&mut store, entry_point(self.kind))
+            .map_err(failed)?;
+
+        let len = i32::try_from(input.len()).map_err(|_| self.error("input too large"))?;
+        let ptr = alloc.call(&mut store, len).map_err(failed)?;
+        memory
+            .write(&mut store, ptr as u32 as usize, input)
+            .map_err(|e| self.error(format!("input out of bounds: {}", e)))?;
+
+        let packed = entry.call(&mut store, (ptr, len)).map_err(failed)? as u64;
+        let (out_ptr, out_len) = ((packed >> 32) as usize, (packed & 0xffff_ffff) as usize);
+
+        let output = if out_len == 0 {
+            None
+        } else if out_len > self.limits.max_output_bytes {
+            return Err(self.error(format!(
+                "output of {} bytes exceeds limit of {} bytes",
+                out_len, self.limits.max_output_bytes
+            )));
+        } else {
+            let mut output = vec![0; out_len];
+            memory
+                .read(&store, out_ptr, &mut output)
+                .map_err(|e| self.error(format!("output out of bounds: {}", e)))?;
+            Some(output)
+        };
+
+        let fuel_used = self.limits.fuel - store.get_fuel().unwrap_or(0);
+        debug!(
+            plugin = %self.name,
+            fuel_used,
+            elapsed_us = start.elapsed().as_micros() as u64,
+            "WASM plugin call complete"
+        );
+        metrics::histogram!("sentinel_plugin_call_duration_seconds", "plugin" => self.name.clone())
+            .record(start.elapsed().as_secs_f64());
+
+        Ok(output)
+    }
+
+    /// Plugin error in the domain of the plugin's role
+    fn error(&self, message: impl std::fmt::Display) -> Error {
+        let message = format!("Plugin {} failed: {}", self.name, message);
+        match self.kind {
+            PluginKind::Detector => Error::detection(message),
+            PluginKind::Enricher => Error::ingestion(message),
+        }
+    }
+}
+
+impl std::fmt::Debug for WasmPlugin {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("WasmPlugin")
+            .field("name", &self.name)
+            .field("kind", &self.kind)
+            .field("limits", &self.limits)
+            .finish()
+    }
+}
+
+fn entry_point(kind: PluginKind) -> &'static str {
+    match kind {
+        PluginKind::Detector => DETECT_EXPORT,
+        PluginKind::Enricher => ENRICH_EXPORT,
+    }
+}
+
+/// Anomaly reported by a detector plugin
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct PluginFinding {
+    /// Alert severity
+    pub severity: Severity,
+    /// Anomaly type; names other than the built-in ones become custom types
+    pub anomaly_type: String,
+    /// Confidence score (0.0 - 1.0)
+    pub confidence: f64,
+    /// Metric name
+    pub metric: String,
+    /// Observed value
+    pub value: f64,
+    /// Baseline/expected value
+    #[serde(default)]
+    pub baseline: f64,
+    /// Threshold that was exceeded
+    #[serde(default)]
+    pub threshold: f64,
+    /// Root cause analysis
+    #[serde(default)]
+    pub root_cause: Option<String>,
+    /// Remediation suggestions
+    #[serde(default)]
+    pub remediation: Vec<String>,
+    /// Additional details
+    #[serde(default)]
+    pub additional: HashMap<String, serde_json::Value>,
+}
+
+impl PluginFinding {
+    /// Build the anomaly event for `event`
+    pub fn into_anomaly(self, plugin: &str, event: &TelemetryEvent) -> AnomalyEvent {
+        let anomaly_type =
+            serde_json::from_value(serde_json::Value::String(self.anomaly_type.clone()))
+                .unwrap_or(AnomalyType::Custom(self.anomaly_type));
+
+        let mut anomaly = AnomalyEvent::new(
+            self.severity,
+            anomaly_type,
+            event.service_name.clone(),
+            event.model.clone(),
+            DetectionMethod::Custom(format!("wasm:{}", plugin)),
+            self.confidence.clamp(0.0, 1.0),
+            AnomalyDetails {
+                metric: self.metric,
+                value: self.value,
+                baseline: self.baseline,
+                threshold: self.threshold,
+                deviation_sigma: None,
+                additional: self.additional,
+                observations: Vec::new(),
+            },
+            AnomalyContext {
+                trace_id: event.trace_id.clone(),
+                user_id: event.metadata.get("user_id").cloned(),
+                region: event.metadata.get("region").cloned(),
+                time_window: "event".to_string(),
+                sample_count: 1,
+                window: None,
+                additional: HashMap::new(),
+            },
+        );
+        anomaly.root_cause = self.root_cause;
+        anomaly.remediation = self.remediation;
+        anomaly
+    }
+}
+
+/// Detector backed by a WASM plugin
+pub struct WasmDetector {
+    plugin: WasmPlugin,
+    stats: Mutex<DetectorStats>,
+}
+
+impl WasmDetector {
+    /// Wrap a detector plugin
+    pub fn new(plugin: WasmPlugin) -> Result<Self> {
+        if plugin.kind() != PluginKind::Detector {
+            return Err(Error::config(format!(
+                "Plugin {} is not a detector",
+                plugin.name()
+            )));
+        }
+        Ok(Self {
+            plugin,
+            stats: Mutex::new(DetectorStats::empty()),
+        })
+    }
+}
+
+#[async_trait]
+impl Detector for WasmDetector {
+    async fn detect(&self, event: &TelemetryEvent) -> Result<Option<AnomalyEvent>> {
+        let input = serde_json::to_vec(event)?;
+        let anomaly = match self.plugin.call(&input)? {
+            Some(output) => {
+                let finding: PluginFinding = serde_json::from_slice(&output)
+                    .map_err(|e| self.plugin.error(format!("invalid finding: {}", e)))?;
+                if !finding.confidence.is_finite() || !finding.value.is_finite() {
+                    return Err(self.plugin.error("finding contains non-finite numbers"));
+                }
+                Some(finding.into_anomaly(self.plugin.name(), event))
+            }
+            None => None,
+        };
+
+        self.stats
+            .lock()
+            .unwrap()
+            .update(anomaly.is_some(), anomaly.as_ref().map(|a| a.confidence));
+        Ok(anomaly)
+    }
+
+    fn name(&self) -> &str {
+        self.plugin.name()
+    }
+
+    fn detector_type(&self) -> DetectorType {
+        DetectorType::Plugin
+    }
+
+    async fn reset(&mut self) -> Result<()> {
+        *self.stats.lock().unwrap() = DetectorStats::empty();
+        Ok(())
+    }
+
+    fn stats(&self) -> DetectorStats {
+        self.stats.lock().unwrap().clone()
+    }
+}
+
+impl std::fmt::Debug for WasmDetector {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("WasmDetector")
+            .field("plugin", &self.plugin)
+            .finish()
+    }
+}
+
+/// Enricher backed by a WASM plugin
+#[derive(Debug)]
+pub struct WasmEnricher {
+    plugin: WasmPlugin,
+}
+
+impl WasmEnricher {
+    /// Wrap an enricher plugin
+    pub fn new(plugin: WasmPlugin) -> Result<Self> {
+        if plugin.kind() != PluginKind::Enricher {
+            return Err(Error::config(format!(
+                "Plugin {} is not an enricher",
+                plugin.name()
+            )));
+        }
+        Ok(Self { plugin })
+    }
+}
+
+#[async_trait]
+impl Enricher for WasmEnricher {
+    async fn enrich(&self, event: &mut TelemetryEvent) -> Result<()> {
+        let input = serde_json::to_vec(&*event)?;
+        let Some(output) = self.plugin.call(&input)? else {
+            return Ok(());
+        };
+
+        let enriched: TelemetryEvent = serde_json::from_slice(&output)
+            .map_err(|e| self.plugin.error(format!("invalid event: {}", e)))?;
+        if enriched.event_id != event.event_id {
+            return Err(self.plugin.error("enrichers may not change event_id"));
+        }
+
+        *event = enriched;
+        Ok(())
+    }
+
+    fn name(&self) -> &str {
+        self.plugin.name()
+    }
+}
+
+/// Plugins loaded from configuration, split by role
+#[derive(Debug, Default)]
+pub struct LoadedPlugins {
+    /// Detector plugins
+    pub detectors: Vec<WasmDetector>,
+    /// Enricher plugins, in configuration order
+    pub enrichers: Vec<WasmEnricher>,
+}
+
+/// Load and compile all configured plugins
+pub fn load_plugins(configs: &[PluginConfig]) -> Result<LoadedPlugins> {
+    let mut plugins = LoadedPlugins::default();
+    for config in configs {
+        let plugin = WasmPlugin::load(config)?;
+        match config.kind {
+            PluginKind::Detector => plugins.detectors.push(WasmDetector::new(plugin)?),
+            PluginKind::Enricher => plugins.enrichers.push(WasmEnricher::new(plugin)?),
+        }
+    }
+    Ok(plugins)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use llm_sentinel_fixtures::event;
+
+    fn create_test_event(prompt: &str) -> TelemetryEvent {
+        event().prompt(prompt).build()
+    }
+
+    /// Module returning `output` from `entry` when the input is longer than
+    /// `min_input_len` bytes
+    fn constant_plugin(entry: &str, output: &str, min_input_len: usize) -> String {
+        let escaped = output.replace('\\', "\\\\").replace('"', "\\\"");
+        format!(
+            r#"(module
+                (memory (export "memory") 2)
+                (data (i32.const 0) "{escaped}")
+                (func (export "sentinel_alloc") (param i32) (result i32)
+                    i32.const 4096)
+                (func (export "{entry}") (param i32 i32) (result i64)
+                    (if (result i64) (i32.gt_u (local.get 1) (i32.const {min_input_len}))
+                        (then (i64.const {len}))
+                        (else (i64.const 0)))))"#,
+            len = output.len()
+        )
+    }
+
+    fn detector(wat: &str, limits: PluginLimits) -> WasmDetector {
+        WasmDetector::new(
+            WasmPlugin::from_bytes("test-plugin", PluginKind::Detector, wat, limits).unwrap(),
+        )
+        .unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_detector_plugin() {
+        let finding = r#"{"severity":"high","anomaly_type":"prompt_stuffing","confidence":0.8,"metric":"prompt_bytes","value":600,"threshold":512}"#;
+        let event = create_test_event(&"x".repeat(600));
+        let min_len = serde_json::to_vec(&create_test_event("")).unwrap().len() + 512;
+        let detector = detector(
+            &constant_plugin(DETECT_EXPORT, finding, min_len),
+            PluginLimits::default(),
+        );
+
+        let anomaly = detector.detect(&event).await.unwrap().unwrap();
+        assert_eq!(anomaly.severity, Severity::High);
+        assert_eq!(
+            anomaly.anomaly_type,
+            AnomalyType::Custom("prompt_stuffing".to_string())
+        );
+        assert_eq!(
+            anomaly.detection_method,
+            DetectionMethod::Custom("wasm:test-plugin".to_string())
+        );
+        assert_eq!(anomaly.details.threshold, 512.0);
+
+        assert!(detector
+            .detect(&create_test_event("short"))
+            .await
+            .unwrap()
+            .is_none());
+        assert_eq!(detector.stats().events_processed, 2);
+        assert_eq!(detector.stats().anomalies_detected, 1);
+    }
+
+    #[tokio::test]
+    async fn test_builtin_anomaly_type() {
+        let finding = r#"{"severity":"low","anomaly_type":"latency_spike","confidence":2.0,"metric":"latency_ms","value":100}"#;
+        let detector = detector(
+            &constant_plugin(DETECT_EXPORT, finding, 0),
+            PluginLimits::default(),
+        );
+
+        let anomaly = detector
+            .detect(&create_test_event("test"))
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(anomaly.anomaly_type, AnomalyType::LatencySpike);
+        assert_eq!(anomaly.confidence, 1.0);
+    }
+
+    #[tokio::test]
+    async fn test_fuel_limit() {
+        let wat = r#"(module
+            (memory (export "memory") 1)
+            (func (export "sentinel_alloc") (param i32) (result i32) i32.const 0)
+            (func (export "sentinel_detect") (param i32 i32) (result i64)
+                (loop $spin (br $spin))
+                i64.const 0))"#;
+        let detector = detector(
+            wat,
+            PluginLimits {
+                fuel: 10_000,
+                ..PluginLimits::default()
+            },
+        );
+
+        let err = detector
+            .detect(&create_test_event("test"))
+            .await
+            .unwrap_err();
+        assert!(matches!(err, Error::Detection(_)));
+        assert!(err.to_string().contains("fuel"), "{}", err);
+    }
+
+    #[tokio::test]
+    async fn test_memory_limit() {
+        let wat = r#"(module
+            (memory (export "memory") 1)
+            (func (export "sentinel_alloc") (param i32) (result i32) i32.const 0)
+            (func (export "sentinel_detect") (param i32 i32) (result i64)
+                (if (i32.lt_s (memory.grow (i32.const 64)) (i32.const 0))
+                    (then unreachable))
+                i64.const 0))"#;
+
+        let limited = detector(
+            wat,
+            PluginLimits {
+                max_memory_bytes: 2 * 65536,
+                ..PluginLimits::default()
+            },
+        );
+        assert!(limited.detect(&create_test_event("test")).await.is_err());
+
+        let unlimited = detector(wat, PluginLimits::default());
+        assert!(unlimited
+            .detect(&create_test_event("test"))
+            .await
+            .unwrap()
+            .is_none());
+    }
+
+    #[tokio::test]
+    async fn test_output_limit() {
+        let finding = r#"{"severity":"low","anomaly_type":"latency_spike","confidence":0.5,"metric":"latency_ms","value":100}"#;
+        let detector = detector(
+            &constant_plugin(DETECT_EXPORT, finding, 0),
+            PluginLimits {
+                max_output_bytes: 16,
+                ..PluginLimits::default()
+            },
+        );
+        let err = detector
+            .detect(&create_test_event("test"))
+            .await
+            .unwrap_err();
+        assert!(err.to_string().contains("exceeds limit"));
+    }
+
+    #[tokio::test]
+    async fn test_enricher_plugin() {
+        let mut event = create_test_event("test");
+        let mut expected = event.clone();
+        expected
+            .metadata
+            .insert("team".to_string(), "search".to_string());
+        let output = serde_json::to_string(&expected).unwrap();
+
+        let enricher = WasmEnricher::new(
+            WasmPlugin::from_bytes(
+                "team-tagger",
+                PluginKind::Enricher,
+                constant_plugin(ENRICH_EXPORT, &output, 0),
+                PluginLimits::default(),
+            )
+            .unwrap(),
+        )
+        .unwrap();
+
+        enricher.enrich(&mut event).await.unwrap();
+        assert_eq!(event.metadata["team"], "search");
+
+        // A different event may not be swapped in
+        let mut other = create_test_event("other");
+        let err = enricher.enrich(&mut other).await.unwrap_err();
+        assert!(matches!(err, Error::Ingestion(_)));
+        assert!(!other.metadata.contains_key("team"));
+    }
+
+    #[test]
+    fn test_invalid_plugins() {
+        let limits = PluginLimits::default();
+
+        // Missing entry point for the configured role
+        let wat = constant_plugin(DETECT_EXPORT, "{}", 0);
+        let err = WasmPlugin::from_bytes("p", PluginKind::Enricher, &wat, limits).unwrap_err();
+        assert!(err.to_string().contains("sentinel_enrich"));
+
+        // Host imports are not provided
+        let wat = r#"(module
+            (import "env" "now" (func))
+            (memory (export "memory") 1)
+            (func (export "sentinel_alloc") (param i32) (result i32) i32.const 0)
+            (func (export "sentinel_detect") (param i32 i32) (result i64) i64.const 0))"#;
+        assert!(WasmPlugin::from_bytes("p", PluginKind::Detector, wat, limits).is_err());
+
+        assert!(WasmPlugin::from_bytes("p", PluginKind::Detector, "not wasm", limits).is_err());
+
+        let plugin = WasmPlugin::from_bytes(
+            "p",
+            PluginKind::Detector,
+            constant_plugin(DETECT_EXPORT, "{}", 0),
+            limits,
+        )
+        .unwrap();
+        assert!(WasmEnricher::new(plugin).is_err());
+    }
+
+    #[test]
+    fn test_load_plugins() {
+        let dir = std::env::temp_dir().join(format!("sentinel-plugins-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("detector.wat");
+        std::fs::write(&path, constant_plugin(DETECT_EXPORT, "{}", 0)).unwrap();
+
+        let configs = vec![PluginConfig {
+            name: "custom".to_string(),
+            path: path.to_string_lossy().into_owned(),
+            kind: PluginKind::Detector,
+            max_memory_bytes: 1 << 20,
+            fuel: 1_000,
+            max_output_bytes: 1024,
+        }];
+        let plugins = load_plugins(&configs).unwrap();
+        assert_eq!(plugins.detectors.len(), 1);
+        assert!(plugins.enrichers.is_empty());
+        assert_eq!(plugins.detectors[0].plugin.limits().fuel, 1_000);
+
+        let mut missing = configs[0].clone();
+        missing.path = dir.join("missing.wasm").to_string_lossy().into_owned();
+        assert!(load_plugins(&[missing]).is_err());
+
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+}
@@ -0,0 +1,84 @@
+//! Two-sided CUSUM (cumulative sum) accumulator.
+//!
+//! ```text
+//! S_H = max(0, S_H + x - μ - k)  // Positive shifts
+//! S_L = min(0, S_L + x - μ + k)  // Negative shifts
+//! ```
+//!
+//! where `k` is the slack parameter.
+
+/// Running CUSUM sums for one metric
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Cusum {
+    /// Cumulative sum of positive deviations
+    pub pos: f64,
+    /// Cumulative sum of negative deviations
+    pub neg: f64,
+    /// Observations since the last reset
+    pub count: u64,
+}
+
+impl Cusum {
+    /// Create a zeroed accumulator
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Accumulate the deviation of `value` from `mean`
+    pub fn update(&mut self, value: f64, mean: f64, slack: f64) {
+        let deviation = value - mean;
+        self.pos = (self.pos + deviation - slack).max(0.0);
+        self.neg = (self.neg + deviation + slack).min(0.0);
+        self.count += 1;
+    }
+
+    /// Largest absolute sum in either direction
+    pub fn magnitude(&self) -> f64 {
+        self.pos.max(self.neg.abs())
+    }
+
+    /// Check whether either sum exceeds `threshold`
+    pub fn exceeds(&self, threshold: f64) -> bool {
+        self.pos > threshold || self.neg.abs() > threshold
+    }
+
+    /// Zero the sums
+    pub fn reset(&mut self) {
+        *self = Self::default();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_cusum_accumulates_sustained_shift() {
+        let mut cusum = Cusum::new();
+        for _ in 0..10 {
+            cusum.update(1.0, 1.0, 0.5);
+        }
+        assert_eq!(cusum.magnitude(), 0.0);
+
+        for _ in 0..4 {
+            cusum.update(2.0, 1.0, 0.5);
+        }
+        assert_eq!(cusum.pos, 2.0);
+        assert_eq!(cusum.count, 14);
+        assert!(cusum.exceeds(1.5));
+        assert!(!cusum.exceeds(2.0));
+
+        cusum.reset();
+        assert_eq!(cusum, Cusum::new());
+    }
+
+    #[test]
+    fn test_cusum_negative_shift() {
+        let mut cusum = Cusum::new();
+        cusum.update(0.0, 3.0, 0.5);
+        assert_eq!(cusum.pos, 0.0);
+        assert_eq!(cusum.neg, -2.5);
+        assert_eq!(cusum.magnitude(), 2.5);
+    }
+}
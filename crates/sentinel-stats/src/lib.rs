@@ -0,0 +1,235 @@
+//! # Sentinel Stats
+//!
+//! Pure statistical primitives behind LLM-Sentinel's anomaly detectors.
+//!
+//! This crate provides:
+//! - Descriptive statistics (mean, standard deviation, median, MAD, IQR,
+//!   percentiles) and quantile binning
+//! - Z-score, IQR and MAD outlier tests
+//! - Fixed-capacity rolling windows
+//! - Baseline summaries of a sample
+//! - CUSUM accumulators for mean-shift detection
+//! - Outlier-resistant (Huber) linear regression
+//!
+//! It has no async runtime, storage or metrics dependencies, so the exact
+//! production algorithms can be reused by other projects, including WASM
+//! dashboards. `llm-sentinel-detection` builds its detectors on top of it.
+
+#![warn(missing_debug_implementations, rust_2018_idioms, unreachable_pub)]
+
+pub mod cusum;
+pub mod regression;
+pub mod summary;
+pub mod window;
+
+pub use cusum::Cusum;
+pub use regression::{huber_fit, LinearFit};
+pub use summary::Summary;
+pub use window::RollingWindow;
+
+use statrs::statistics::{Data, Distribution, OrderStatistics};
+
+/// Calculate mean of a slice
+pub fn mean(data: &[f64]) -> f64 {
+    if data.is_empty() {
+        return 0.0;
+    }
+    data.iter().sum::<f64>() / data.len() as f64
+}
+
+/// Calculate standard deviation of a slice
+pub fn std_dev(data: &[f64]) -> f64 {
+    if data.len() < 2 {
+        return 0.0;
+    }
+
+    let data_obj = Data::new(data.to_vec());
+    data_obj.std_dev().unwrap_or(0.0)
+}
+
+/// Calculate median of a slice
+pub fn median(data: &[f64]) -> f64 {
+    if data.is_empty() {
+        return 0.0;
+    }
+
+    let mut sorted = data.to_vec();
+    sorted.sort_by(|a, b| a.partial_cmp(b).unwrap_or(std::cmp::Ordering::Equal));
+
+    let mid = sorted.len() / 2;
+    if sorted.len() % 2 == 0 {
+        (sorted[mid - 1] + sorted[mid]) / 2.0
+    } else {
+        sorted[mid]
+    }
+}
+
+/// Calculate median absolute deviation (MAD)
+pub fn mad(data: &[f64]) -> f64 {
+    if data.is_empty() {
+        return 0.0;
+    }
+
+    let med = median(data);
+    let deviations: Vec<f64> = data.iter().map(|x| (x - med).abs()).collect();
+    median(&deviations)
+}
+
+/// Calculate interquartile range (IQR)
+pub fn iqr(data: &[f64]) -> (f64, f64, f64) {
+    if data.is_empty() {
+        return (0.0, 0.0, 0.0);
+    }
+
+    let mut sorted = data.to_vec();
+    sorted.sort_by(|a, b| a.partial_cmp(b).unwrap_or(std::cmp::Ordering::Equal));
+
+    let mut data_obj = Data::new(sorted);
+    let q1 = data_obj.lower_quartile();
+    let q3 = data_obj.upper_quartile();
+    let iqr_value = q3 - q1;
+
+    (q1, q3, iqr_value)
+}
+
+/// Calculate percentile
+pub fn percentile(data: &[f64], p: f64) -> f64 {
+    if data.is_empty() {
+        return 0.0;
+    }
+
+    let mut sorted = data.to_vec();
+    sorted.sort_by(|a, b| a.partial_cmp(b).unwrap_or(std::cmp::Ordering::Equal));
+
+    let mut data_obj = Data::new(sorted);
+    data_obj.percentile(p as usize)
+}
+
+/// Interior bin edges at the quantiles of `data`, with duplicates removed
+pub fn quantile_edges(data: &[f64], bins: usize) -> Vec<f64> {
+    if data.is_empty() || bins < 2 {
+        return Vec::new();
+    }
+
+    let mut sorted = data.to_vec();
+    sorted.sort_by(|a, b| a.total_cmp(b));
+
+    let mut edges: Vec<f64> = (1..bins)
+        .map(|i| sorted[(i * sorted.len() / bins).min(sorted.len() - 1)])
+        .collect();
+    edges.dedup();
+    edges
+}
+
+/// Bin index of a value given interior edges (bins are upper-inclusive)
+pub fn bin_index(edges: &[f64], value: f64) -> usize {
+    edges.partition_point(|edge| *edge < value)
+}
+
+/// Z-score calculation
+pub fn zscore(value: f64, mean: f64, std_dev: f64) -> f64 {
+    if std_dev == 0.0 {
+        return 0.0;
+    }
+    (value - mean) / std_dev
+}
+
+/// Check if value is outlier using Z-score
+pub fn is_zscore_outlier(value: f64, mean: f64, std_dev: f64, threshold: f64) -> bool {
+    zscore(value, mean, std_dev).abs() > threshold
+}
+
+/// Check if value is outlier using IQR method
+pub fn is_iqr_outlier(value: f64, q1: f64, q3: f64, iqr: f64, multiplier: f64) -> bool {
+    let lower_bound = q1 - multiplier * iqr;
+    let upper_bound = q3 + multiplier * iqr;
+    value < lower_bound || value > upper_bound
+}
+
+/// Check if value is outlier using MAD method
+pub fn is_mad_outlier(value: f64, median: f64, mad: f64, threshold: f64) -> bool {
+    if mad == 0.0 {
+        return false;
+    }
+    // Modified Z-score using MAD
+    let modified_zscore = 0.6745 * (value - median).abs() / mad;
+    modified_zscore > threshold
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use approx::assert_relative_eq;
+
+    #[test]
+    fn test_quantile_edges() {
+        let data: Vec<f64> = (0..100).map(f64::from).collect();
+        let edges = quantile_edges(&data, 4);
+        assert_eq!(edges, vec![25.0, 50.0, 75.0]);
+        assert_eq!(bin_index(&edges, 10.0), 0);
+        assert_eq!(bin_index(&edges, 25.0), 0);
+        assert_eq!(bin_index(&edges, 26.0), 1);
+        assert_eq!(bin_index(&edges, 99.0), 3);
+
+        // Ties collapse into fewer bins
+        assert_eq!(quantile_edges(&[5.0; 20], 10), vec![5.0]);
+    }
+
+    #[test]
+    fn test_mean() {
+        assert_eq!(mean(&[1.0, 2.0, 3.0, 4.0, 5.0]), 3.0);
+        assert_eq!(mean(&[]), 0.0);
+        assert_eq!(mean(&[5.0]), 5.0);
+    }
+
+    #[test]
+    fn test_std_dev() {
+        let data = vec![2.0, 4.0, 4.0, 4.0, 5.0, 5.0, 7.0, 9.0];
+        let sd = std_dev(&data);
+        // Sample standard deviation: sqrt(32 / 7)
+        assert_relative_eq!(sd, 2.138, epsilon = 0.001);
+    }
+
+    #[test]
+    fn test_median() {
+        assert_eq!(median(&[1.0, 2.0, 3.0, 4.0, 5.0]), 3.0);
+        assert_eq!(median(&[1.0, 2.0, 3.0, 4.0]), 2.5);
+        assert_eq!(median(&[5.0]), 5.0);
+        assert_eq!(median(&[]), 0.0);
+    }
+
+    #[test]
+    fn test_mad() {
+        let data = vec![1.0, 2.0, 3.0, 4.0, 5.0];
+        let mad_value = mad(&data);
+        assert_eq!(mad_value, 1.0); // Median is 3, deviations are [2,1,0,1,2], median is 1
+    }
+
+    #[test]
+    fn test_iqr() {
+        let data = vec![1.0, 2.0, 3.0, 4.0, 5.0, 6.0, 7.0, 8.0, 9.0];
+        let (q1, q3, iqr_value) = iqr(&data);
+        assert!(q1 > 0.0);
+        assert!(q3 > q1);
+        assert_eq!(iqr_value, q3 - q1);
+    }
+
+    #[test]
+    fn test_zscore() {
+        assert_eq!(zscore(5.0, 3.0, 2.0), 1.0);
+        assert_eq!(zscore(1.0, 3.0, 2.0), -1.0);
+        assert_eq!(zscore(3.0, 3.0, 2.0), 0.0);
+    }
+
+    #[test]
+    fn test_is_zscore_outlier() {
+        assert!(is_zscore_outlier(10.0, 3.0, 2.0, 3.0));
+        assert!(!is_zscore_outlier(5.0, 3.0, 2.0, 3.0));
+    }
+
+    #[test]
+    fn test_is_iqr_outlier() {
+        assert!(is_iqr_outlier(100.0, 2.0, 8.0, 6.0, 1.5));
+        assert!(!is_iqr_outlier(5.0, 2.0, 8.0, 6.0, 1.5));
+    }
+}
@@ -0,0 +1,237 @@
+//! Outlier-resistant linear regression.
+//!
+//! Fits `y = b0 + b1 x1 + ... + bk xk` by iteratively reweighted least
+//! squares with Huber weights: observations more than [`HUBER_K`] robust
+//! standard deviations from the fit are down-weighted, so a few wild
+//! observations do not drag the fit towards them. The residual scale is the
+//! normalized median absolute residual.
+
+use crate::median;
+
+/// Huber tuning constant (95% efficiency on normal residuals)
+pub const HUBER_K: f64 = 1.345;
+
+/// Makes the median absolute residual estimate the standard deviation of
+/// normal residuals
+const MAD_NORMAL_SCALE: f64 = 1.4826;
+
+const MAX_ITERATIONS: usize = 50;
+const TOLERANCE: f64 = 1e-9;
+
+/// Pivots this small relative to the largest diagonal entry are treated as
+/// zero (collinear regressors)
+const SINGULAR_TOLERANCE: f64 = 1e-10;
+
+/// Fitted linear model
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct LinearFit {
+    /// Intercept followed by one coefficient per regressor
+    pub coefficients: Vec<f64>,
+    /// Robust standard deviation of the residuals
+    pub scale: f64,
+    /// Observations fitted
+    pub count: usize,
+}
+
+impl LinearFit {
+    /// Intercept of the fit
+    pub fn intercept(&self) -> f64 {
+        self.coefficients[0]
+    }
+
+    /// Predicted value for regressors `x`
+    pub fn predict(&self, x: &[f64]) -> f64 {
+        predict(&self.coefficients, x)
+    }
+
+    /// Observed minus predicted value
+    pub fn residual(&self, x: &[f64], y: f64) -> f64 {
+        y - self.predict(x)
+    }
+}
+
+/// Fit `y` on the regressors `x` (one row per observation) with Huber
+/// weights
+///
+/// Coefficients of regressors that are collinear with earlier ones (e.g. a
+/// regressor that never changes, collinear with the intercept) are zero.
+/// Returns `None` for mismatched or empty input.
+pub fn huber_fit<X: AsRef<[f64]>>(x: &[X], y: &[f64]) -> Option<LinearFit> {
+    if x.is_empty() || x.len() != y.len() {
+        return None;
+    }
+    let width = x[0].as_ref().len();
+    if x.iter().any(|row| row.as_ref().len() != width) {
+        return None;
+    }
+
+    let mut weights = vec![1.0; y.len()];
+    let mut coefficients = weighted_least_squares(x, y, &weights);
+    let mut scale = residual_scale(x, y, &coefficients);
+    // Residuals this small are rounding: the majority fits exactly
+    let negligible = TOLERANCE * (1.0 + y.iter().map(|y| y.abs()).sum::<f64>() / y.len() as f64);
+
+    for _ in 0..MAX_ITERATIONS {
+        if scale <= negligible {
+            break;
+        }
+        for ((row, y), weight) in x.iter().zip(y).zip(weights.iter_mut()) {
+            let u = (y - predict(&coefficients, row.as_ref())).abs() / scale;
+            *weight = if u <= HUBER_K { 1.0 } else { HUBER_K / u };
+        }
+
+        let next = weighted_least_squares(x, y, &weights);
+        let change = next
+            .iter()
+            .zip(&coefficients)
+            .map(|(a, b)| (a - b).abs())
+            .fold(0.0, f64::max);
+        let size = coefficients.iter().map(|b| b.abs()).fold(0.0, f64::max);
+        coefficients = next;
+        scale = residual_scale(x, y, &coefficients);
+        if change <= TOLERANCE * (1.0 + size) {
+            break;
+        }
+    }
+
+    Some(LinearFit {
+        coefficients,
+        scale,
+        count: y.len(),
+    })
+}
+
+fn predict(coefficients: &[f64], x: &[f64]) -> f64 {
+    coefficients[0]
+        + coefficients[1..]
+            .iter()
+            .zip(x)
+            .map(|(b, x)| b * x)
+            .sum::<f64>()
+}
+
+/// Normalized median absolute residual
+fn residual_scale<X: AsRef<[f64]>>(x: &[X], y: &[f64], coefficients: &[f64]) -> f64 {
+    let residuals: Vec<f64> = x
+        .iter()
+        .zip(y)
+        .map(|(row, y)| (y - predict(coefficients, row.as_ref())).abs())
+        .collect();
+    MAD_NORMAL_SCALE * median(&residuals)
+}
+
+/// Solve the weighted normal equations `(XᵀWX) b = XᵀWy`, with an
+/// intercept column prepended to `x`
+fn weighted_least_squares<X: AsRef<[f64]>>(x: &[X], y: &[f64], weights: &[f64]) -> Vec<f64> {
+    let n = x[0].as_ref().len() + 1;
+    let mut a = vec![vec![0.0; n]; n];
+    let mut b = vec![0.0; n];
+    let mut row = vec![0.0; n];
+    for ((x, y), w) in x.iter().zip(y).zip(weights) {
+        row[0] = 1.0;
+        row[1..].copy_from_slice(x.as_ref());
+        for i in 0..n {
+            b[i] += w * row[i] * y;
+            for j in 0..n {
+                a[i][j] += w * row[i] * row[j];
+            }
+        }
+    }
+    solve(a, b)
+}
+
+/// Gauss-Jordan elimination with partial pivoting; unknowns without a
+/// usable pivot are set to zero
+fn solve(mut a: Vec<Vec<f64>>, mut b: Vec<f64>) -> Vec<f64> {
+    let n = b.len();
+    let tolerance = SINGULAR_TOLERANCE * (0..n).map(|i| a[i][i].abs()).fold(0.0, f64::max);
+    let mut pivots = Vec::with_capacity(n);
+
+    for col in 0..n {
+        let rank = pivots.len();
+        let Some(pivot) = (rank..n).max_by(|&i, &j| a[i][col].abs().total_cmp(&a[j][col].abs()))
+        else {
+            break;
+        };
+        if a[pivot][col].abs() <= tolerance {
+            continue;
+        }
+        a.swap(rank, pivot);
+        b.swap(rank, pivot);
+
+        let divisor = a[rank][col];
+        for value in &mut a[rank] {
+            *value /= divisor;
+        }
+        b[rank] /= divisor;
+        let pivot_row = a[rank].clone();
+        for i in (0..n).filter(|&i| i != rank) {
+            let factor = a[i][col];
+            if factor != 0.0 {
+                for (value, pivot_value) in a[i].iter_mut().zip(&pivot_row) {
+                    *value -= factor * pivot_value;
+                }
+                b[i] -= factor * b[rank];
+            }
+        }
+        pivots.push(col);
+    }
+
+    let mut solution = vec![0.0; n];
+    for (rank, col) in pivots.into_iter().enumerate() {
+        solution[col] = b[rank];
+    }
+    solution
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use approx::assert_relative_eq;
+
+    /// Deterministic noise in [-0.5, 0.5)
+    fn noise(i: usize) -> f64 {
+        ((i * 7919 + 13) % 1000) as f64 / 1000.0 - 0.5
+    }
+
+    #[test]
+    fn test_fit_resists_outliers() {
+        let x: Vec<[f64; 2]> = (0..200)
+            .map(|i| [(i % 37) as f64 * 10.0, (i % 11) as f64 * 5.0])
+            .collect();
+        let mut y: Vec<f64> = x
+            .iter()
+            .enumerate()
+            .map(|(i, [a, b])| 2.0 + 0.5 * a + 3.0 * b + noise(i))
+            .collect();
+        // One in ten observations is wildly high
+        for value in y.iter_mut().step_by(10) {
+            *value += 500.0;
+        }
+
+        let fit = huber_fit(&x, &y).unwrap();
+        assert_eq!(fit.count, 200);
+        assert_relative_eq!(fit.coefficients[1], 0.5, epsilon = 0.02);
+        assert_relative_eq!(fit.coefficients[2], 3.0, epsilon = 0.05);
+        assert!(fit.scale < 1.0);
+        assert!(fit.residual(&x[0], y[0]) > 400.0);
+        assert!(fit.residual(&x[1], y[1]).abs() < 2.0);
+    }
+
+    #[test]
+    fn test_exact_and_collinear_fits() {
+        // A regressor that never changes is absorbed by the intercept
+        let x: Vec<[f64; 2]> = (0..50).map(|i| [i as f64, 7.0]).collect();
+        let y: Vec<f64> = x.iter().map(|[a, b]| 0.25 * a + b).collect();
+
+        let fit = huber_fit(&x, &y).unwrap();
+        assert_relative_eq!(fit.predict(&[100.0, 7.0]), 32.0, epsilon = 1e-9);
+        assert_relative_eq!(fit.coefficients[1], 0.25, epsilon = 1e-9);
+        assert_eq!(fit.coefficients[2], 0.0);
+        assert!(fit.scale < 1e-9);
+
+        assert!(huber_fit::<[f64; 2]>(&[], &[]).is_none());
+        assert!(huber_fit(&x, &y[1..]).is_none());
+    }
+}
@@ -0,0 +1,139 @@
+//! Summary statistics of a sample.
+//!
+//! Baselines are resummarized on every update, so this is on the detection
+//! hot path. The order statistics are all taken from one sorted copy of the
+//! sample, held in a per-thread scratch buffer that is reused across calls,
+//! so summarizing allocates nothing once the buffer has grown to the window
+//! size.
+
+use crate::{mean, std_dev};
+use statrs::statistics::{Data, OrderStatistics};
+use std::cell::RefCell;
+
+thread_local! {
+    /// Scratch buffer the sample is sorted in
+    static SCRATCH: RefCell<Vec<f64>> = const { RefCell::new(Vec::new()) };
+}
+
+/// Median of sorted values
+fn sorted_median(sorted: &[f64]) -> f64 {
+    let mid = sorted.len() / 2;
+    if sorted.len() % 2 == 0 {
+        (sorted[mid - 1] + sorted[mid]) / 2.0
+    } else {
+        sorted[mid]
+    }
+}
+
+/// Descriptive statistics detectors compare new values against
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Summary {
+    /// Mean value
+    pub mean: f64,
+    /// Standard deviation
+    pub std_dev: f64,
+    /// Median value
+    pub median: f64,
+    /// Median absolute deviation
+    pub mad: f64,
+    /// 25th percentile (Q1)
+    pub q1: f64,
+    /// 75th percentile (Q3)
+    pub q3: f64,
+    /// Interquartile range
+    pub iqr: f64,
+    /// 95th percentile
+    pub p95: f64,
+    /// 99th percentile
+    pub p99: f64,
+    /// Minimum value
+    pub min: f64,
+    /// Maximum value
+    pub max: f64,
+    /// Number of samples
+    pub sample_count: usize,
+}
+
+impl Summary {
+    /// Summarize a sample (all zeros when empty)
+    pub fn from_data(data: &[f64]) -> Self {
+        if data.is_empty() {
+            return Self::default();
+        }
+
+        SCRATCH.with(|scratch| {
+            let mut scratch = scratch.borrow_mut();
+            scratch.clear();
+            scratch.extend_from_slice(data);
+            scratch.sort_by(|a, b| a.partial_cmp(b).unwrap_or(std::cmp::Ordering::Equal));
+
+            let (min, max) = (scratch[0], scratch[scratch.len() - 1]);
+            let median = sorted_median(&scratch);
+
+            // Quantiles select in place, leaving the buffer unsorted
+            let mut quantiles = Data::new(scratch.as_mut_slice());
+            let q1 = quantiles.lower_quartile();
+            let q3 = quantiles.upper_quartile();
+            let p95 = quantiles.percentile(95);
+            let p99 = quantiles.percentile(99);
+
+            for (deviation, value) in scratch.iter_mut().zip(data) {
+                *deviation = (value - median).abs();
+            }
+            scratch.sort_by(|a, b| a.partial_cmp(b).unwrap_or(std::cmp::Ordering::Equal));
+            let mad = sorted_median(&scratch);
+
+            Self {
+                mean: mean(data),
+                std_dev: std_dev(data),
+                median,
+                mad,
+                q1,
+                q3,
+                iqr: q3 - q1,
+                p95,
+                p99,
+                min,
+                max,
+                sample_count: data.len(),
+            }
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_summary_from_data() {
+        let data: Vec<f64> = (1..=9).map(f64::from).collect();
+        let summary = Summary::from_data(&data);
+        assert_eq!(summary.mean, 5.0);
+        assert_eq!(summary.median, 5.0);
+        assert_eq!(summary.min, 1.0);
+        assert_eq!(summary.max, 9.0);
+        assert_eq!(summary.sample_count, 9);
+        assert_eq!(summary.iqr, summary.q3 - summary.q1);
+
+        assert_eq!(Summary::from_data(&[]), Summary::default());
+    }
+
+    #[test]
+    fn test_summary_matches_primitives() {
+        let data: Vec<f64> = (0..101).map(|i| ((i * 37) % 101) as f64 * 1.5).collect();
+        let summary = Summary::from_data(&data);
+        let (q1, q3, iqr) = crate::iqr(&data);
+        assert_eq!(summary.median, crate::median(&data));
+        assert_eq!(summary.mad, crate::mad(&data));
+        assert_eq!((summary.q1, summary.q3, summary.iqr), (q1, q3, iqr));
+        assert_eq!(summary.p95, crate::percentile(&data, 95.0));
+        assert_eq!(summary.p99, crate::percentile(&data, 99.0));
+
+        // The scratch buffer is reused for smaller samples
+        let small = Summary::from_data(&data[..10]);
+        assert_eq!(small.median, crate::median(&data[..10]));
+        assert_eq!(small.sample_count, 10);
+    }
+}
@@ -0,0 +1,109 @@
+//! Fixed-capacity rolling windows.
+
+use crate::{mad, mean, median, std_dev};
+
+/// Rolling window statistics
+#[derive(Debug, Clone)]
+pub struct RollingWindow {
+    data: Vec<f64>,
+    capacity: usize,
+}
+
+impl RollingWindow {
+    /// Create a new rolling window
+    pub fn new(capacity: usize) -> Self {
+        Self {
+            data: Vec::with_capacity(capacity),
+            capacity,
+        }
+    }
+
+    /// Add a value to the window
+    pub fn push(&mut self, value: f64) {
+        if self.data.len() >= self.capacity {
+            self.data.remove(0);
+        }
+        self.data.push(value);
+    }
+
+    /// Get the current data
+    pub fn data(&self) -> &[f64] {
+        &self.data
+    }
+
+    /// Check if window is full
+    pub fn is_full(&self) -> bool {
+        self.data.len() >= self.capacity
+    }
+
+    /// Get window size
+    pub fn len(&self) -> usize {
+        self.data.len()
+    }
+
+    /// Check if window is empty
+    pub fn is_empty(&self) -> bool {
+        self.data.is_empty()
+    }
+
+    /// Calculate mean of window
+    pub fn mean(&self) -> f64 {
+        mean(&self.data)
+    }
+
+    /// Calculate standard deviation of window
+    pub fn std_dev(&self) -> f64 {
+        std_dev(&self.data)
+    }
+
+    /// Calculate median of window
+    pub fn median(&self) -> f64 {
+        median(&self.data)
+    }
+
+    /// Calculate MAD of window
+    pub fn mad(&self) -> f64 {
+        mad(&self.data)
+    }
+
+    /// Clear the window
+    pub fn clear(&mut self) {
+        self.data.clear();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_rolling_window() {
+        let mut window = RollingWindow::new(3);
+        assert!(window.is_empty());
+        assert!(!window.is_full());
+
+        window.push(1.0);
+        window.push(2.0);
+        window.push(3.0);
+        assert!(window.is_full());
+        assert_eq!(window.len(), 3);
+        assert_eq!(window.mean(), 2.0);
+
+        window.push(4.0); // Should remove 1.0
+        assert_eq!(window.data(), &[2.0, 3.0, 4.0]);
+        assert_eq!(window.mean(), 3.0);
+    }
+
+    #[test]
+    fn test_rolling_window_clear() {
+        let mut window = RollingWindow::new(5);
+        window.push(1.0);
+        window.push(2.0);
+        window.push(3.0);
+        assert_eq!(window.len(), 3);
+
+        window.clear();
+        assert!(window.is_empty());
+        assert_eq!(window.len(), 0);
+    }
+}
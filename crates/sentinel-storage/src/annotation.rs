@@ -0,0 +1,153 @@
+//! Anomaly annotations against storage.
+//!
+//! [`annotate_anomaly`] records a responder's note, link or resolution
+//! summary on a stored anomaly, and [`attach_annotations`] fills in an
+//! anomaly's annotations when it is read back.
+
+use crate::{query::TimeRange, Storage};
+use chrono::Utc;
+use llm_sentinel_core::{
+    annotation::{AnnotationKind, AnomalyAnnotation},
+    events::AnomalyEvent,
+    Error, Result,
+};
+use serde::{Deserialize, Serialize};
+use tracing::info;
+use uuid::Uuid;
+
+/// Query for anomaly annotations
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AnnotationQuery {
+    /// Time range (by creation time)
+    pub time_range: TimeRange,
+    /// Filter by anomaly
+    #[serde(default)]
+    pub alert_id: Option<Uuid>,
+}
+
+impl AnnotationQuery {
+    /// Create a new annotation query
+    pub fn new(time_range: TimeRange) -> Self {
+        Self {
+            time_range,
+            alert_id: None,
+        }
+    }
+
+    /// Filter by anomaly
+    pub fn with_alert_id(mut self, alert_id: Uuid) -> Self {
+        self.alert_id = Some(alert_id);
+        self
+    }
+}
+
+/// Annotate a stored anomaly
+///
+/// Fails with [`Error::NotFound`] for unknown anomalies and
+/// [`Error::Validation`] for blank text or links without a URL.
+pub async fn annotate_anomaly(
+    storage: &dyn Storage,
+    alert_id: Uuid,
+    kind: AnnotationKind,
+    text: String,
+    url: Option<String>,
+    author: Option<String>,
+) -> Result<AnomalyAnnotation> {
+    if storage.get_anomaly(alert_id).await?.is_none() {
+        return Err(Error::not_found(format!("Anomaly {}", alert_id)));
+    }
+
+    let annotation = AnomalyAnnotation::new(alert_id, kind, text, url, author)?;
+    storage
+        .write_anomaly_annotations(std::slice::from_ref(&annotation))
+        .await?;
+
+    info!(
+        alert_id = %alert_id,
+        kind = %annotation.kind,
+        author = annotation.author.as_deref(),
+        "Anomaly annotated"
+    );
+    metrics::counter!(
+        "sentinel_anomaly_annotations_total",
+        "kind" => annotation.kind.to_string()
+    )
+    .increment(1);
+
+    Ok(annotation)
+}
+
+/// Attach the annotations written on an anomaly since its detection
+pub async fn attach_annotations(storage: &dyn Storage, anomaly: &mut AnomalyEvent) -> Result<()> {
+    let query = AnnotationQuery::new(TimeRange::new(anomaly.timestamp, Utc::now()))
+        .with_alert_id(anomaly.alert_id);
+    anomaly.annotations = storage.query_anomaly_annotations(query).await?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::memory::InMemoryStorage;
+
+    use llm_sentinel_fixtures::anomaly;
+
+    fn create_anomaly() -> AnomalyEvent {
+        anomaly().service("checkout").build()
+    }
+
+    #[tokio::test]
+    async fn test_annotate_anomaly() {
+        let storage = InMemoryStorage::new();
+        let mut anomaly = create_anomaly();
+        storage.write_anomaly(&anomaly).await.unwrap();
+
+        annotate_anomaly(
+            &storage,
+            anomaly.alert_id,
+            AnnotationKind::Note,
+            "Deploy 1234 went out at the same time".to_string(),
+            None,
+            Some("alice".to_string()),
+        )
+        .await
+        .unwrap();
+        let resolution = annotate_anomaly(
+            &storage,
+            anomaly.alert_id,
+            AnnotationKind::Resolution,
+            "Rolled back deploy 1234".to_string(),
+            Some("https://tickets.example.com/OPS-42".to_string()),
+            None,
+        )
+        .await
+        .unwrap();
+
+        attach_annotations(&storage, &mut anomaly).await.unwrap();
+        assert_eq!(anomaly.annotations.len(), 2);
+        assert_eq!(anomaly.annotations[0].kind, AnnotationKind::Note);
+        assert_eq!(anomaly.annotations[1], resolution);
+
+        let missing = annotate_anomaly(
+            &storage,
+            Uuid::new_v4(),
+            AnnotationKind::Note,
+            "note".to_string(),
+            None,
+            None,
+        )
+        .await;
+        assert!(matches!(missing, Err(Error::NotFound(_))));
+
+        let blank = annotate_anomaly(
+            &storage,
+            anomaly.alert_id,
+            AnnotationKind::Note,
+            String::new(),
+            None,
+            None,
+        )
+        .await;
+        assert!(matches!(blank, Err(Error::Validation(_))));
+    }
+}
@@ -0,0 +1,412 @@
+//! Cost budget burn-rate forecasting.
+//!
+//! A service's month-end spend is projected as its month-to-date cost plus
+//! forecast daily spend for the rest of the month. Daily spend is fitted to
+//! the last complete days of cost rollups, as a least-squares linear trend
+//! or with Holt's double exponential smoothing, so a rising burn rate shows
+//! in the projection well before the budget is spent.
+
+use crate::{
+    cost::{CostDimension, CostReportQuery},
+    query::TimeRange,
+    Storage,
+};
+use chrono::{DateTime, Datelike, Duration, TimeZone, Utc};
+use llm_sentinel_core::{
+    config::{CostBudgetConfig, ForecastMethod},
+    events::{AnomalyContext, AnomalyDetails, AnomalyEvent},
+    types::{AnomalyType, DetectionMethod, ModelId, ServiceId},
+    Error, Result,
+};
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap};
+
+const DAY_SECS: i64 = 86_400;
+
+/// Projected month-end spend of one budgeted service
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct BudgetProjection {
+    /// Service the budget applies to
+    pub service: ServiceId,
+    /// Monthly budget (USD)
+    pub budget_usd: f64,
+    /// Spend since the start of the month (USD)
+    pub month_to_date_usd: f64,
+    /// Projected spend at the end of the month (USD)
+    pub projected_usd: f64,
+    /// Projected spend as a fraction of the budget
+    pub projected_ratio: f64,
+    /// Forecast spend for the current day (USD)
+    pub forecast_daily_usd: f64,
+    /// Forecast method used
+    pub method: ForecastMethod,
+    /// Complete days of spend the forecast was fitted to
+    pub days_observed: usize,
+    /// End of the projected month
+    pub month_end: DateTime<Utc>,
+    /// Whether the projection exceeds the budget by more than the margin
+    pub over_budget: bool,
+}
+
+/// Projects month-end spend against configured budgets
+#[derive(Debug)]
+pub struct BudgetForecaster {
+    config: CostBudgetConfig,
+}
+
+impl BudgetForecaster {
+    /// Create a forecaster for the configured budgets
+    pub fn new(config: CostBudgetConfig) -> Result<Self> {
+        if let Some((service, _)) = config
+            .monthly_usd
+            .iter()
+            .find(|(_, budget)| budget.is_nan() || **budget <= 0.0)
+        {
+            return Err(Error::config(format!(
+                "Monthly budget for service {} must be positive",
+                service
+            )));
+        }
+
+        Ok(Self { config })
+    }
+
+    /// Budget configuration
+    pub fn config(&self) -> &CostBudgetConfig {
+        &self.config
+    }
+
+    /// Project every budgeted service's month-end spend as of `now`,
+    /// ordered by service
+    pub async fn project(
+        &self,
+        storage: &dyn Storage,
+        now: DateTime<Utc>,
+    ) -> Result<Vec<BudgetProjection>> {
+        let month_start = Utc
+            .with_ymd_and_hms(now.year(), now.month(), 1, 0, 0, 0)
+            .unwrap();
+        let month_end = next_month(month_start);
+        let today = Utc
+            .timestamp_opt(now.timestamp().div_euclid(DAY_SECS) * DAY_SECS, 0)
+            .unwrap();
+        let lookback_start = today - Duration::days(self.config.lookback_days as i64);
+
+        let month_to_date = storage
+            .query_cost_report(
+                CostReportQuery::new(TimeRange::new(month_start, now))
+                    .group_by(CostDimension::Service),
+            )
+            .await?;
+        let daily = storage
+            .query_cost_report(
+                CostReportQuery::new(TimeRange::new(lookback_start, today))
+                    .group_by(CostDimension::Service)
+                    .with_interval(DAY_SECS as u64),
+            )
+            .await?;
+
+        let mut spent: HashMap<&str, f64> = HashMap::new();
+        for row in &month_to_date {
+            if let Some(service) = row.group.get("service") {
+                *spent.entry(service.as_str()).or_default() += row.cost_usd;
+            }
+        }
+        let mut days: HashMap<&str, BTreeMap<i64, f64>> = HashMap::new();
+        for row in &daily {
+            if let (Some(service), Some(bucket)) = (row.group.get("service"), row.bucket_start) {
+                let index = (bucket - lookback_start).num_days();
+                *days
+                    .entry(service.as_str())
+                    .or_default()
+                    .entry(index)
+                    .or_default() += row.cost_usd;
+            }
+        }
+
+        // Days left in the month including today, with today partly spent
+        let remaining_days = (month_end - today).num_days().max(1) as usize;
+        let today_left = (today + Duration::days(1) - now).num_seconds() as f64 / DAY_SECS as f64;
+
+        let mut budgets: Vec<(&String, &f64)> = self.config.monthly_usd.iter().collect();
+        budgets.sort_by(|a, b| a.0.cmp(b.0));
+
+        Ok(budgets
+            .into_iter()
+            .map(|(service, &budget_usd)| {
+                let series = days
+                    .get(service.as_str())
+                    .map(|days| daily_series(days, self.config.lookback_days as i64))
+                    .unwrap_or_default();
+                let forecast = forecast_daily(
+                    self.config.method,
+                    self.config.alpha,
+                    self.config.beta,
+                    &series,
+                    remaining_days,
+                );
+
+                let month_to_date_usd = spent.get(service.as_str()).copied().unwrap_or(0.0);
+                let projected_usd = month_to_date_usd
+                    + forecast.first().copied().unwrap_or(0.0) * today_left
+                    + forecast.iter().skip(1).sum::<f64>();
+                BudgetProjection {
+                    service: ServiceId::new(service.clone()),
+                    budget_usd,
+                    month_to_date_usd,
+                    projected_usd,
+                    projected_ratio: projected_usd / budget_usd,
+                    forecast_daily_usd: forecast.first().copied().unwrap_or(0.0),
+                    method: self.config.method,
+                    days_observed: series.len(),
+                    month_end,
+                    over_budget: projected_usd > budget_usd * (1.0 + self.config.margin),
+                }
+            })
+            .collect())
+    }
+
+    /// Alert for a projection over budget
+    pub fn alert(&self, projection: &BudgetProjection) -> AnomalyEvent {
+        let mut additional = HashMap::new();
+        additional.insert(
+            "month_to_date_usd".to_string(),
+            serde_json::json!(projection.month_to_date_usd),
+        );
+        additional.insert(
+            "forecast_daily_usd".to_string(),
+            serde_json::json!(projection.forecast_daily_usd),
+        );
+        additional.insert(
+            "month_end".to_string(),
+            serde_json::json!(projection.month_end.to_rfc3339()),
+        );
+
+        // Projections from a short history are less certain
+        let confidence =
+            (projection.days_observed as f64 / self.config.lookback_days as f64).clamp(0.5, 1.0);
+
+        let mut alert = AnomalyEvent::new(
+            self.config.severity,
+            AnomalyType::CostAnomaly,
+            projection.service.clone(),
+            ModelId::new("all"),
+            DetectionMethod::Custom("budget_forecast".to_string()),
+            confidence,
+            AnomalyDetails {
+                metric: "projected_monthly_cost_usd".to_string(),
+                value: projection.projected_usd,
+                baseline: projection.budget_usd,
+                threshold: projection.budget_usd * (1.0 + self.config.margin),
+                deviation_sigma: None,
+                additional,
+                observations: Vec::new(),
+            },
+            AnomalyContext {
+                trace_id: None,
+                user_id: None,
+                region: None,
+                time_window: format!("{}d", self.config.lookback_days),
+                sample_count: projection.days_observed,
+                window: None,
+                additional: HashMap::new(),
+            },
+        );
+        alert.root_cause = Some(format!(
+            "Service {} is projected to spend ${:.2} this month against a ${:.2} budget ({:.0}%, {} forecast)",
+            projection.service,
+            projection.projected_usd,
+            projection.budget_usd,
+            projection.projected_ratio * 100.0,
+            match projection.method {
+                ForecastMethod::Linear => "linear",
+                ForecastMethod::Holt => "Holt",
+            }
+        ));
+        alert
+    }
+}
+
+/// First instant of the month after the one starting at `month_start`
+fn next_month(month_start: DateTime<Utc>) -> DateTime<Utc> {
+    let (year, month) = match month_start.month() {
+        12 => (month_start.year() + 1, 1),
+        month => (month_start.year(), month + 1),
+    };
+    Utc.with_ymd_and_hms(year, month, 1, 0, 0, 0).unwrap()
+}
+
+/// Daily spend from the first day with spend through the last lookback day,
+/// with days without rollups as zero
+fn daily_series(days: &BTreeMap<i64, f64>, lookback_days: i64) -> Vec<f64> {
+    let Some(&first) = days.keys().find(|&&day| day >= 0) else {
+        return Vec::new();
+    };
+    (first..lookback_days)
+        .map(|day| days.get(&day).copied().unwrap_or(0.0))
+        .collect()
+}
+
+/// Forecast the `horizon` days following `series`, never below zero
+///
+/// An empty series forecasts no spend; a single day forecasts that day's
+/// spend flat.
+pub fn forecast_daily(
+    method: ForecastMethod,
+    alpha: f64,
+    beta: f64,
+    series: &[f64],
+    horizon: usize,
+) -> Vec<f64> {
+    let Some(&first) = series.first() else {
+        return vec![0.0; horizon];
+    };
+
+    let (level, trend) = match method {
+        ForecastMethod::Linear => {
+            let n = series.len() as f64;
+            let mean_x = (n - 1.0) / 2.0;
+            let mean_y = series.iter().sum::<f64>() / n;
+            let (mut covariance, mut variance) = (0.0, 0.0);
+            for (x, y) in series.iter().enumerate() {
+                covariance += (x as f64 - mean_x) * (y - mean_y);
+                variance += (x as f64 - mean_x).powi(2);
+            }
+            let slope = if variance > 0.0 {
+                covariance / variance
+            } else {
+                0.0
+            };
+            // Fitted value on the last observed day
+            (mean_y + slope * (n - 1.0 - mean_x), slope)
+        }
+        ForecastMethod::Holt => {
+            let mut level = first;
+            let mut trend = series.get(1).map_or(0.0, |second| second - first);
+            for &value in &series[1..] {
+                let previous = level;
+                level = alpha * value + (1.0 - alpha) * (level + trend);
+                trend = beta * (level - previous) + (1.0 - beta) * trend;
+            }
+            (level, trend)
+        }
+    };
+
+    (1..=horizon)
+        .map(|step| (level + trend * step as f64).max(0.0))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{cost::CostRollup, memory::InMemoryStorage};
+
+    fn config(method: ForecastMethod) -> CostBudgetConfig {
+        CostBudgetConfig {
+            monthly_usd: HashMap::from([
+                ("chat".to_string(), 300.0),
+                ("search".to_string(), 1000.0),
+            ]),
+            method,
+            lookback_days: 7,
+            margin: 0.1,
+            alpha: 0.5,
+            beta: 0.3,
+            check_interval_secs: 3600,
+            severity: llm_sentinel_core::types::Severity::High,
+        }
+    }
+
+    fn rollup(service: &str, at: DateTime<Utc>, cost_usd: f64) -> CostRollup {
+        CostRollup {
+            bucket_start: at,
+            last_event: at,
+            service: ServiceId::new(service),
+            model: ModelId::new("gpt-4"),
+            user: None,
+            tags: BTreeMap::new(),
+            cost_usd,
+            requests: 1,
+            prompt_tokens: 0,
+            completion_tokens: 0,
+        }
+    }
+
+    #[test]
+    fn test_forecast_daily() {
+        let rising = [1.0, 2.0, 3.0, 4.0];
+        let linear = forecast_daily(ForecastMethod::Linear, 0.5, 0.3, &rising, 2);
+        assert!((linear[0] - 5.0).abs() < 1e-9);
+        assert!((linear[1] - 6.0).abs() < 1e-9);
+
+        // Holt follows an exact trend too
+        let holt = forecast_daily(ForecastMethod::Holt, 0.5, 0.3, &rising, 2);
+        assert!((holt[0] - 5.0).abs() < 1e-9);
+
+        // Falling spend bottoms out at zero; one day forecasts flat
+        let falling = forecast_daily(ForecastMethod::Linear, 0.5, 0.3, &[4.0, 2.0], 3);
+        assert_eq!(falling, vec![0.0, 0.0, 0.0]);
+        assert_eq!(
+            forecast_daily(ForecastMethod::Holt, 0.5, 0.3, &[2.5], 2),
+            vec![2.5, 2.5]
+        );
+        assert_eq!(
+            forecast_daily(ForecastMethod::Linear, 0.5, 0.3, &[], 2),
+            vec![0.0, 0.0]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_projection_over_budget() {
+        let storage = InMemoryStorage::new();
+        let now = Utc.with_ymd_and_hms(2024, 4, 10, 12, 0, 0).unwrap();
+        // chat spends 10, 12, ..., 20 USD a day over the last six days
+        let rollups: Vec<CostRollup> = (1..=6)
+            .map(|days_ago| {
+                rollup(
+                    "chat",
+                    now - Duration::days(days_ago),
+                    10.0 + 2.0 * (6 - days_ago) as f64,
+                )
+            })
+            .chain([rollup("chat", now - Duration::hours(1), 5.0)])
+            .collect();
+        storage.write_cost_rollups(&rollups).await.unwrap();
+
+        let forecaster = BudgetForecaster::new(config(ForecastMethod::Linear)).unwrap();
+        let projections = forecaster.project(&storage, now).await.unwrap();
+        assert_eq!(projections.len(), 2);
+
+        let chat = &projections[0];
+        assert_eq!(chat.service.as_str(), "chat");
+        assert_eq!(chat.days_observed, 6);
+        assert!((chat.month_to_date_usd - 95.0).abs() < 1e-9);
+        assert!((chat.forecast_daily_usd - 22.0).abs() < 1e-9);
+        // 95 so far, half of today at 22 and 20 more days rising by 2 a day
+        let expected = 95.0 + 11.0 + (24..=62).step_by(2).sum::<i32>() as f64;
+        assert!((chat.projected_usd - expected).abs() < 1e-6);
+        assert!(chat.over_budget);
+        assert_eq!(
+            chat.month_end,
+            Utc.with_ymd_and_hms(2024, 5, 1, 0, 0, 0).unwrap()
+        );
+
+        let alert = forecaster.alert(chat);
+        assert_eq!(alert.anomaly_type, AnomalyType::CostAnomaly);
+        assert_eq!(alert.details.baseline, 300.0);
+        assert!((alert.details.threshold - 330.0).abs() < 1e-9);
+
+        // A service without spend projects nothing
+        let search = &projections[1];
+        assert_eq!((search.projected_usd, search.days_observed), (0.0, 0));
+        assert!(!search.over_budget);
+    }
+
+    #[test]
+    fn test_invalid_budget() {
+        let mut config = config(ForecastMethod::Holt);
+        config.monthly_usd.insert("chat".to_string(), 0.0);
+        assert!(BudgetForecaster::new(config).is_err());
+    }
+}
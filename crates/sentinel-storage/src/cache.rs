@@ -1,11 +1,18 @@
 //! Caching layer for baselines and hot data.
 
 use moka::future::Cache;
-use llm_sentinel_core::{Error, Result};
-use serde::{Deserialize, Serialize};
 use std::time::Duration;
 use tracing::{debug, info};
 
+#[cfg(feature = "redis")]
+use llm_sentinel_core::selfcheck::{CheckKind, CheckResult, SelfCheck};
+#[cfg(feature = "redis")]
+use llm_sentinel_core::{Error, Result};
+#[cfg(feature = "redis")]
+use serde::{Deserialize, Serialize};
+#[cfg(feature = "redis")]
+use tokio::time::Instant;
+
 /// Cache configuration
 #[derive(Debug, Clone)]
 pub struct CacheConfig {
@@ -83,10 +90,10 @@ where
         let value = self.cache.get(key).await;
 
         if value.is_some() {
-            metrics::counter!("sentinel_cache_hits_total").increment(1);
+            metrics::counter!("sentinel_cache_hits_total", "cache" => "memory").increment(1);
             debug!("Cache hit");
         } else {
-            metrics::counter!("sentinel_cache_misses_total").increment(1);
+            metrics::counter!("sentinel_cache_misses_total", "cache" => "memory").increment(1);
             debug!("Cache miss");
         }
 
@@ -96,13 +103,13 @@ where
     /// Insert a value into cache
     pub async fn insert(&self, key: K, value: V) {
         self.cache.insert(key, value).await;
-        metrics::counter!("sentinel_cache_inserts_total").increment(1);
+        metrics::counter!("sentinel_cache_inserts_total", "cache" => "memory").increment(1);
     }
 
     /// Remove a value from cache
     pub async fn remove(&self, key: &K) {
         self.cache.invalidate(key).await;
-        metrics::counter!("sentinel_cache_removals_total").increment(1);
+        metrics::counter!("sentinel_cache_removals_total", "cache" => "memory").increment(1);
     }
 
     /// Clear all entries
@@ -146,11 +153,13 @@ pub struct CacheStats {
 }
 
 /// Redis-backed distributed cache
+#[cfg(feature = "redis")]
 pub struct RedisCache {
     client: redis::Client,
     config: RedisCacheConfig,
 }
 
+#[cfg(feature = "redis")]
 impl std::fmt::Debug for RedisCache {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         f.debug_struct("RedisCache")
@@ -180,6 +189,7 @@ impl Default for RedisCacheConfig {
     }
 }
 
+#[cfg(feature = "redis")]
 impl RedisCache {
     /// Create a new Redis cache
     pub async fn new(config: RedisCacheConfig) -> Result<Self> {
@@ -310,6 +320,107 @@ impl RedisCache {
     }
 }
 
+/// Self-check of a Redis server: reachable, credentials accepted and
+/// allowed to write the short-lived keys leases and cache entries use
+#[cfg(feature = "redis")]
+#[derive(Debug, Clone)]
+pub struct RedisCheck {
+    dependency: String,
+    url: String,
+}
+
+#[cfg(feature = "redis")]
+impl RedisCheck {
+    /// Check the Redis server at `url`, reported as `dependency`
+    pub fn new(dependency: impl Into<String>, url: impl Into<String>) -> Self {
+        Self {
+            dependency: dependency.into(),
+            url: url.into(),
+        }
+    }
+}
+
+/// Whether Redis refused the credentials, or required some
+#[cfg(feature = "redis")]
+fn auth_failed(e: &redis::RedisError) -> bool {
+    e.kind() == redis::ErrorKind::AuthenticationFailed
+        || matches!(e.code(), Some("WRONGPASS" | "NOAUTH"))
+}
+
+#[cfg(feature = "redis")]
+#[async_trait::async_trait]
+impl SelfCheck for RedisCheck {
+    fn dependency(&self) -> String {
+        self.dependency.clone()
+    }
+
+    async fn run(&self) -> Vec<CheckResult> {
+        let name = self.dependency.as_str();
+        let started = Instant::now();
+        let client = match redis::Client::open(self.url.as_str()) {
+            Ok(client) => client,
+            Err(e) => {
+                return vec![
+                    CheckResult::fail(name, CheckKind::Connectivity, format!("invalid URL: {}", e)),
+                    CheckResult::skip(name, CheckKind::Permission),
+                ]
+            }
+        };
+        let connected = match client.get_multiplexed_async_connection().await {
+            Ok(mut conn) => redis::cmd("PING")
+                .query_async::<String>(&mut conn)
+                .await
+                .map(|_| conn),
+            Err(e) => Err(e),
+        };
+        let mut conn = match connected {
+            Ok(conn) => conn,
+            Err(e) if auth_failed(&e) => {
+                return vec![
+                    CheckResult::pass(name, CheckKind::Connectivity, "server answered")
+                        .timed(started),
+                    CheckResult::fail(
+                        name,
+                        CheckKind::Permission,
+                        format!("authentication failed: {}", e),
+                    ),
+                ]
+            }
+            Err(e) => {
+                return vec![
+                    CheckResult::fail(name, CheckKind::Connectivity, e.to_string()).timed(started),
+                    CheckResult::skip(name, CheckKind::Permission),
+                ]
+            }
+        };
+        let mut results =
+            vec![CheckResult::pass(name, CheckKind::Connectivity, "PING answered").timed(started)];
+
+        let started = Instant::now();
+        let key = format!("sentinel:selfcheck:{}", uuid::Uuid::new_v4());
+        let written = redis::cmd("SET")
+            .arg(&key)
+            .arg("1")
+            .arg("PX")
+            .arg(10_000)
+            .query_async::<()>(&mut conn)
+            .await;
+        let permission = match written {
+            Ok(()) => {
+                // The key expires on its own if this fails
+                let _ = redis::cmd("DEL")
+                    .arg(&key)
+                    .query_async::<()>(&mut conn)
+                    .await;
+                CheckResult::pass(name, CheckKind::Permission, "SET and DEL allowed")
+            }
+            Err(e) => CheckResult::fail(name, CheckKind::Permission, e.to_string()),
+        };
+        results.push(permission.timed(started));
+        results
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -341,7 +452,8 @@ mod tests {
         let value = cache.get(&"key1".to_string()).await;
         assert_eq!(value, Some(42));
 
-        // Stats
+        // Stats (entry counts are eventually consistent)
+        cache.cache.run_pending_tasks().await;
         let stats = cache.stats().await;
         assert_eq!(stats.entry_count, 2);
 
@@ -352,6 +464,7 @@ mod tests {
 
         // Clear
         cache.clear().await;
+        cache.cache.run_pending_tasks().await;
         let stats = cache.stats().await;
         assert_eq!(stats.entry_count, 0);
     }
@@ -363,6 +476,7 @@ mod tests {
         assert_eq!(config.ttl_secs, 300);
     }
 
+    #[cfg(feature = "redis")]
     #[tokio::test]
     async fn test_redis_cache_key_building() {
         let config = RedisCacheConfig::default();
@@ -0,0 +1,163 @@
+//! Tag cardinality estimation.
+//!
+//! Time-series databases create one series per distinct tag combination, so a
+//! single high-cardinality tag can exhaust memory on the database side long
+//! before anything fails here. A [`CardinalityEstimator`] keeps a HyperLogLog
+//! sketch per tag key to estimate how many distinct values have been written,
+//! exports the estimates as metrics and warns once a tag crosses a threshold.
+
+use std::{
+    collections::{hash_map::DefaultHasher, BTreeMap, HashMap},
+    hash::{Hash, Hasher},
+    sync::Mutex,
+};
+use tracing::warn;
+
+/// Register index bits; 2^10 registers give roughly 3% standard error
+const PRECISION: u32 = 10;
+
+/// Number of registers per sketch
+const REGISTERS: usize = 1 << PRECISION;
+
+/// HyperLogLog sketch of one tag's values
+#[derive(Debug, Clone)]
+struct Sketch {
+    registers: Vec<u8>,
+    warned: bool,
+}
+
+impl Sketch {
+    fn new() -> Self {
+        Self {
+            registers: vec![0; REGISTERS],
+            warned: false,
+        }
+    }
+
+    /// Record a value; returns whether the sketch changed
+    fn insert(&mut self, value: &str) -> bool {
+        let mut hasher = DefaultHasher::new();
+        value.hash(&mut hasher);
+        let hash = hasher.finish();
+
+        let index = (hash >> (64 - PRECISION)) as usize;
+        let rank = ((hash << PRECISION) | (1 << (PRECISION - 1))).leading_zeros() as u8 + 1;
+        if rank > self.registers[index] {
+            self.registers[index] = rank;
+            true
+        } else {
+            false
+        }
+    }
+
+    fn estimate(&self) -> u64 {
+        let m = REGISTERS as f64;
+        let alpha = 0.7213 / (1.0 + 1.079 / m);
+        let sum: f64 = self.registers.iter().map(|&r| 2f64.powi(-(r as i32))).sum();
+        let raw = alpha * m * m / sum;
+
+        let zeros = self.registers.iter().filter(|&&r| r == 0).count();
+        let estimate = if raw <= 2.5 * m && zeros > 0 {
+            // Linear counting is more accurate for small cardinalities
+            m * (m / zeros as f64).ln()
+        } else {
+            raw
+        };
+        estimate.round() as u64
+    }
+}
+
+/// Estimates distinct values per tag key
+#[derive(Debug)]
+pub struct CardinalityEstimator {
+    warn_threshold: u64,
+    sketches: Mutex<HashMap<String, Sketch>>,
+}
+
+impl CardinalityEstimator {
+    /// Create an estimator warning when a tag exceeds `warn_threshold` values
+    pub fn new(warn_threshold: u64) -> Self {
+        Self {
+            warn_threshold,
+            sketches: Mutex::new(HashMap::new()),
+        }
+    }
+
+    /// Record a written tag value
+    pub fn observe(&self, key: &str, value: &str) {
+        let mut sketches = self.sketches.lock().unwrap();
+        let sketch = match sketches.get_mut(key) {
+            Some(sketch) => sketch,
+            None => sketches.entry(key.to_string()).or_insert_with(Sketch::new),
+        };
+        if !sketch.insert(value) {
+            return;
+        }
+
+        let estimate = sketch.estimate();
+        metrics::gauge!("sentinel_storage_tag_cardinality", "tag" => key.to_string())
+            .set(estimate as f64);
+
+        if !sketch.warned && estimate > self.warn_threshold {
+            sketch.warned = true;
+            warn!(
+                tag = key,
+                estimate,
+                threshold = self.warn_threshold,
+                "Tag has high cardinality; consider storing it as a field"
+            );
+        }
+    }
+
+    /// Estimated distinct values of a tag
+    pub fn estimate(&self, key: &str) -> u64 {
+        self.sketches
+            .lock()
+            .unwrap()
+            .get(key)
+            .map_or(0, Sketch::estimate)
+    }
+
+    /// Estimated distinct values of every observed tag
+    pub fn estimates(&self) -> BTreeMap<String, u64> {
+        self.sketches
+            .lock()
+            .unwrap()
+            .iter()
+            .map(|(key, sketch)| (key.clone(), sketch.estimate()))
+            .collect()
+    }
+
+    /// Tags whose estimate exceeds the warning threshold
+    pub fn over_threshold(&self) -> Vec<String> {
+        self.estimates()
+            .into_iter()
+            .filter(|(_, estimate)| *estimate > self.warn_threshold)
+            .map(|(key, _)| key)
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_estimate_accuracy() {
+        let estimator = CardinalityEstimator::new(1_000);
+        for i in 0..20_000 {
+            estimator.observe("user_id", &format!("user-{}", i));
+            estimator.observe("service", ["checkout", "search"][i % 2]);
+        }
+
+        assert_eq!(estimator.estimate("service"), 2);
+        let users = estimator.estimate("user_id") as f64;
+        assert!(
+            (users - 20_000.0).abs() / 20_000.0 < 0.1,
+            "estimate {}",
+            users
+        );
+        assert_eq!(estimator.estimate("missing"), 0);
+        assert_eq!(estimator.over_threshold(), vec!["user_id".to_string()]);
+    }
+}
@@ -0,0 +1,174 @@
+//! Recent telemetry context for anomalies.
+//!
+//! Responders want to see what the affected service/model was doing when an
+//! alert fired. [`attach_recent_context`] queries the most recent telemetry
+//! events and records summary statistics in `AnomalyContext.additional`.
+
+use crate::{
+    delivery::percentile,
+    query::{TelemetryQuery, TimeRange},
+    Storage,
+};
+use chrono::{Duration, Utc};
+use llm_sentinel_core::{
+    config::RecentContextConfig,
+    events::{AnomalyContext, AnomalyEvent, TelemetryEvent},
+    Result,
+};
+use serde::{Deserialize, Serialize};
+
+/// Summary of recent telemetry events
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct RecentTelemetrySummary {
+    /// Number of events summarized
+    pub sample_count: usize,
+    /// Median latency (ms)
+    pub p50_latency_ms: f64,
+    /// 95th percentile latency (ms)
+    pub p95_latency_ms: f64,
+    /// Events reporting errors
+    pub error_count: usize,
+    /// Example trace IDs, most recent first
+    pub trace_ids: Vec<String>,
+}
+
+impl RecentTelemetrySummary {
+    /// Summarize events, most recent first; `None` when there are none
+    pub fn from_events(events: &[TelemetryEvent], max_trace_ids: usize) -> Option<Self> {
+        let mut latencies: Vec<f64> = events.iter().map(|e| e.latency_ms).collect();
+        latencies.sort_by(|a, b| a.total_cmp(b));
+
+        Some(Self {
+            sample_count: events.len(),
+            p50_latency_ms: percentile(&latencies, 0.50)?,
+            p95_latency_ms: percentile(&latencies, 0.95)?,
+            error_count: events.iter().filter(|e| !e.errors.is_empty()).count(),
+            trace_ids: events
+                .iter()
+                .filter_map(|e| e.trace_id.clone())
+                .take(max_trace_ids)
+                .collect(),
+        })
+    }
+
+    /// Record the summary under `recent_*` keys
+    pub fn apply(&self, context: &mut AnomalyContext) {
+        let additional = &mut context.additional;
+        additional.insert(
+            "recent_sample_count".to_string(),
+            self.sample_count.to_string(),
+        );
+        additional.insert(
+            "recent_p50_latency_ms".to_string(),
+            format!("{:.1}", self.p50_latency_ms),
+        );
+        additional.insert(
+            "recent_p95_latency_ms".to_string(),
+            format!("{:.1}", self.p95_latency_ms),
+        );
+        additional.insert(
+            "recent_error_count".to_string(),
+            self.error_count.to_string(),
+        );
+        if !self.trace_ids.is_empty() {
+            additional.insert("recent_trace_ids".to_string(), self.trace_ids.join(","));
+        }
+    }
+}
+
+/// Attach a summary of the anomaly's recent service/model telemetry
+///
+/// Returns the summary, or `None` when no recent events were found.
+pub async fn attach_recent_context(
+    storage: &dyn Storage,
+    anomaly: &mut AnomalyEvent,
+    config: &RecentContextConfig,
+) -> Result<Option<RecentTelemetrySummary>> {
+    let end = Utc::now();
+    let start = end - Duration::seconds(config.lookback_secs as i64);
+    let query = TelemetryQuery::new(TimeRange::new(start, end))
+        .with_service(anomaly.service_name.clone())
+        .with_model(anomaly.model.clone())
+        .with_limit(config.sample_size);
+
+    let events = storage.query_telemetry(query).await?;
+    let summary = RecentTelemetrySummary::from_events(&events, config.max_trace_ids);
+    if let Some(summary) = &summary {
+        summary.apply(&mut anomaly.context);
+    }
+
+    Ok(summary)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::memory::InMemoryStorage;
+
+    use llm_sentinel_fixtures::{anomaly, event};
+
+    fn create_event(service: &str, latency_ms: f64, minutes_ago: i64) -> TelemetryEvent {
+        event()
+            .service(service)
+            .latency_ms(latency_ms)
+            .timestamp(Utc::now() - Duration::minutes(minutes_ago))
+            .trace_id(format!("trace-{}", minutes_ago))
+            .build()
+    }
+
+    fn create_anomaly() -> AnomalyEvent {
+        anomaly().service("checkout").build()
+    }
+
+    #[test]
+    fn test_summary() {
+        let mut events: Vec<_> = (1..=20)
+            .map(|i| create_event("checkout", i as f64 * 10.0, i))
+            .collect();
+        events[0].errors.push("timeout".to_string());
+        events[1].trace_id = None;
+
+        let summary = RecentTelemetrySummary::from_events(&events, 2).unwrap();
+        assert_eq!(summary.sample_count, 20);
+        assert_eq!(summary.p50_latency_ms, 100.0);
+        assert_eq!(summary.p95_latency_ms, 190.0);
+        assert_eq!(summary.error_count, 1);
+        assert_eq!(summary.trace_ids, vec!["trace-1", "trace-3"]);
+
+        assert!(RecentTelemetrySummary::from_events(&[], 2).is_none());
+    }
+
+    #[tokio::test]
+    async fn test_attach_recent_context() {
+        let storage = InMemoryStorage::new();
+        let events = vec![
+            create_event("checkout", 100.0, 1),
+            create_event("checkout", 300.0, 2),
+            create_event("checkout", 200.0, 3),
+            // Outside the lookback window
+            create_event("checkout", 9000.0, 120),
+            // Other service
+            create_event("search", 9000.0, 1),
+        ];
+        storage.write_telemetry_batch(&events).await.unwrap();
+
+        let config = RecentContextConfig {
+            sample_size: 2,
+            lookback_secs: 3600,
+            max_trace_ids: 5,
+        };
+        let mut anomaly = create_anomaly();
+        let summary = attach_recent_context(&storage, &mut anomaly, &config)
+            .await
+            .unwrap()
+            .unwrap();
+
+        // Only the two most recent checkout events
+        assert_eq!(summary.sample_count, 2);
+        let additional = &anomaly.context.additional;
+        assert_eq!(additional["recent_sample_count"], "2");
+        assert_eq!(additional["recent_p95_latency_ms"], "300.0");
+        assert_eq!(additional["recent_error_count"], "0");
+        assert_eq!(additional["recent_trace_ids"], "trace-1,trace-2");
+    }
+}
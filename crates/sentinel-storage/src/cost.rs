@@ -0,0 +1,463 @@
+//! Cost attribution and reporting.
+//!
+//! Telemetry `cost_usd` is rolled up in memory per time bucket and
+//! service/model/user/tag combination, then periodically flushed to storage
+//! as a dedicated cost measurement. Reports are aggregate queries over those
+//! rollups, so they stay cheap over long ranges.
+
+use crate::{query::TimeRange, Storage};
+use chrono::{DateTime, TimeZone, Utc};
+use llm_sentinel_core::{
+    events::TelemetryEvent,
+    types::{ModelId, ServiceId},
+    Error, Result,
+};
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap};
+use std::fmt;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+use tokio::task::JoinHandle;
+use tracing::{debug, error, info};
+
+/// Cost aggregator configuration
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CostAggregatorConfig {
+    /// Rollup bucket width (seconds)
+    pub bucket_secs: u64,
+    /// How often pending rollups are flushed to storage (seconds)
+    pub flush_interval_secs: u64,
+    /// Telemetry metadata key holding the user identifier
+    pub user_key: String,
+    /// Telemetry metadata keys to attribute cost by
+    pub tag_keys: Vec<String>,
+}
+
+impl Default for CostAggregatorConfig {
+    fn default() -> Self {
+        Self {
+            bucket_secs: 3600,        // 1 hour
+            flush_interval_secs: 60,  // 1 minute
+            user_key: "user_id".to_string(),
+            tag_keys: Vec::new(),
+        }
+    }
+}
+
+/// Dimension a cost report can be grouped by
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum CostDimension {
+    /// Service name
+    Service,
+    /// Model identifier
+    Model,
+    /// User identifier
+    User,
+    /// Metadata tag
+    Tag(String),
+}
+
+impl CostDimension {
+    /// Storage column the dimension is written under
+    pub fn column(&self) -> String {
+        match self {
+            Self::Service => "service".to_string(),
+            Self::Model => "model".to_string(),
+            Self::User => "user".to_string(),
+            Self::Tag(key) => format!("tag_{}", key),
+        }
+    }
+}
+
+impl fmt::Display for CostDimension {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Service => write!(f, "service"),
+            Self::Model => write!(f, "model"),
+            Self::User => write!(f, "user"),
+            Self::Tag(key) => write!(f, "tag:{}", key),
+        }
+    }
+}
+
+impl std::str::FromStr for CostDimension {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        match s.trim() {
+            "service" => Ok(Self::Service),
+            "model" => Ok(Self::Model),
+            "user" => Ok(Self::User),
+            other => match other.strip_prefix("tag:") {
+                Some(key) if is_valid_tag_key(key) => Ok(Self::Tag(key.to_string())),
+                _ => Err(Error::validation(format!(
+                    "Invalid cost dimension: {} (expected service, model, user or tag:<key>)",
+                    s
+                ))),
+            },
+        }
+    }
+}
+
+/// Tag keys end up in column names, so keep them to a safe character set
+fn is_valid_tag_key(key: &str) -> bool {
+    !key.is_empty()
+        && key
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || c == '_' || c == '-')
+}
+
+/// Cost totals for one bucket and attribution key
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct CostRollup {
+    /// Bucket start
+    pub bucket_start: DateTime<Utc>,
+    /// Timestamp of the most recent event included
+    pub last_event: DateTime<Utc>,
+    /// Service name
+    pub service: ServiceId,
+    /// Model identifier
+    pub model: ModelId,
+    /// User identifier, if present in metadata
+    pub user: Option<String>,
+    /// Configured metadata tags present on the events
+    pub tags: BTreeMap<String, String>,
+    /// Total cost in USD
+    pub cost_usd: f64,
+    /// Number of requests
+    pub requests: u64,
+    /// Total prompt tokens
+    pub prompt_tokens: u64,
+    /// Total completion tokens
+    pub completion_tokens: u64,
+}
+
+/// Key identifying a rollup
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+struct RollupKey {
+    bucket_start: i64,
+    service: String,
+    model: String,
+    user: Option<String>,
+    tags: BTreeMap<String, String>,
+}
+
+/// In-memory cost aggregator
+pub struct CostAggregator {
+    config: CostAggregatorConfig,
+    pending: Mutex<HashMap<RollupKey, CostRollup>>,
+}
+
+impl fmt::Debug for CostAggregator {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("CostAggregator")
+            .field("config", &self.config)
+            .field("pending", &self.pending_count())
+            .finish()
+    }
+}
+
+impl CostAggregator {
+    /// Create a new cost aggregator
+    pub fn new(config: CostAggregatorConfig) -> Result<Self> {
+        if config.bucket_secs == 0 {
+            return Err(Error::config("Cost bucket width must be positive"));
+        }
+        if let Some(key) = config.tag_keys.iter().find(|k| !is_valid_tag_key(k)) {
+            return Err(Error::config(format!("Invalid cost tag key: {}", key)));
+        }
+
+        Ok(Self {
+            config,
+            pending: Mutex::new(HashMap::new()),
+        })
+    }
+
+    /// Add an event's cost to the pending rollups
+    pub fn record(&self, event: &TelemetryEvent) {
+        let bucket_secs = self.config.bucket_secs as i64;
+        let bucket_start = event.timestamp.timestamp().div_euclid(bucket_secs) * bucket_secs;
+
+        let user = event.metadata.get(&self.config.user_key).cloned();
+        let tags: BTreeMap<String, String> = self
+            .config
+            .tag_keys
+            .iter()
+            .filter_map(|key| event.metadata.get(key).map(|v| (key.clone(), v.clone())))
+            .collect();
+
+        let key = RollupKey {
+            bucket_start,
+            service: event.service_name.as_str().to_string(),
+            model: event.model.as_str().to_string(),
+            user: user.clone(),
+            tags: tags.clone(),
+        };
+
+        let mut pending = self.pending.lock().unwrap();
+        let rollup = pending.entry(key).or_insert_with(|| CostRollup {
+            bucket_start: Utc.timestamp_opt(bucket_start, 0).unwrap(),
+            last_event: event.timestamp,
+            service: event.service_name.clone(),
+            model: event.model.clone(),
+            user,
+            tags,
+            cost_usd: 0.0,
+            requests: 0,
+            prompt_tokens: 0,
+            completion_tokens: 0,
+        });
+
+        rollup.last_event = rollup.last_event.max(event.timestamp);
+        rollup.cost_usd += event.cost_usd;
+        rollup.requests += 1;
+        rollup.prompt_tokens += event.prompt.tokens as u64;
+        rollup.completion_tokens += event.response.tokens as u64;
+    }
+
+    /// Number of pending rollups
+    pub fn pending_count(&self) -> usize {
+        self.pending.lock().unwrap().len()
+    }
+
+    /// Take all pending rollups
+    pub fn drain(&self) -> Vec<CostRollup> {
+        let mut pending = self.pending.lock().unwrap();
+        pending.drain().map(|(_, rollup)| rollup).collect()
+    }
+
+    /// Flush pending rollups to storage.
+    ///
+    /// Rollups are put back if the write fails so the next flush retries them.
+    pub async fn flush(&self, storage: &dyn Storage) -> Result<usize> {
+        let rollups = self.drain();
+        if rollups.is_empty() {
+            return Ok(0);
+        }
+
+        match storage.write_cost_rollups(&rollups).await {
+            Ok(()) => {
+                debug!("Flushed {} cost rollups", rollups.len());
+                metrics::counter!("sentinel_cost_rollups_flushed_total")
+                    .increment(rollups.len() as u64);
+                Ok(rollups.len())
+            }
+            Err(e) => {
+                self.restore(rollups);
+                Err(e)
+            }
+        }
+    }
+
+    /// Merge rollups back into the pending set
+    fn restore(&self, rollups: Vec<CostRollup>) {
+        let mut pending = self.pending.lock().unwrap();
+        for rollup in rollups {
+            let key = RollupKey {
+                bucket_start: rollup.bucket_start.timestamp(),
+                service: rollup.service.as_str().to_string(),
+                model: rollup.model.as_str().to_string(),
+                user: rollup.user.clone(),
+                tags: rollup.tags.clone(),
+            };
+            match pending.get_mut(&key) {
+                Some(existing) => {
+                    existing.last_event = existing.last_event.max(rollup.last_event);
+                    existing.cost_usd += rollup.cost_usd;
+                    existing.requests += rollup.requests;
+                    existing.prompt_tokens += rollup.prompt_tokens;
+                    existing.completion_tokens += rollup.completion_tokens;
+                }
+                None => {
+                    pending.insert(key, rollup);
+                }
+            }
+        }
+    }
+
+    /// Start the periodic flush task
+    pub fn start_flush_task(self: Arc<Self>, storage: Arc<dyn Storage>) -> JoinHandle<()> {
+        let interval = Duration::from_secs(self.config.flush_interval_secs.max(1));
+        info!(
+            "Starting cost rollup flush task (every {}s)",
+            interval.as_secs()
+        );
+
+        tokio::spawn(async move {
+            let mut ticker = tokio::time::interval(interval);
+
+            loop {
+                ticker.tick().await;
+                if let Err(e) = self.flush(storage.as_ref()).await {
+                    error!("Failed to flush cost rollups: {}", e);
+                    metrics::counter!("sentinel_storage_errors_total", "type" => "cost")
+                        .increment(1);
+                }
+            }
+        })
+    }
+}
+
+/// Query for a cost report
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CostReportQuery {
+    /// Time range
+    pub time_range: TimeRange,
+
+    /// Dimensions to group by (empty = grand total)
+    pub group_by: Vec<CostDimension>,
+
+    /// Optional bucket width (seconds) for a time series breakdown
+    pub interval_secs: Option<u64>,
+
+    /// Filter by service
+    pub service: Option<ServiceId>,
+
+    /// Filter by model
+    pub model: Option<ModelId>,
+}
+
+impl CostReportQuery {
+    /// Create a new cost report query
+    pub fn new(time_range: TimeRange) -> Self {
+        Self {
+            time_range,
+            group_by: Vec::new(),
+            interval_secs: None,
+            service: None,
+            model: None,
+        }
+    }
+
+    /// Group by a dimension
+    pub fn group_by(mut self, dimension: CostDimension) -> Self {
+        if !self.group_by.contains(&dimension) {
+            self.group_by.push(dimension);
+        }
+        self
+    }
+
+    /// Break the report down into time buckets
+    pub fn with_interval(mut self, interval_secs: u64) -> Self {
+        self.interval_secs = Some(interval_secs);
+        self
+    }
+
+    /// Filter by service
+    pub fn with_service(mut self, service: ServiceId) -> Self {
+        self.service = Some(service);
+        self
+    }
+
+    /// Filter by model
+    pub fn with_model(mut self, model: ModelId) -> Self {
+        self.model = Some(model);
+        self
+    }
+}
+
+/// One row of a cost report
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct CostReportRow {
+    /// Bucket start (only for interval reports)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub bucket_start: Option<DateTime<Utc>>,
+    /// Group values keyed by dimension name
+    pub group: BTreeMap<String, String>,
+    /// Total cost in USD
+    pub cost_usd: f64,
+    /// Number of requests
+    pub requests: u64,
+    /// Total prompt tokens
+    pub prompt_tokens: u64,
+    /// Total completion tokens
+    pub completion_tokens: u64,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use llm_sentinel_fixtures::event;
+
+    fn create_test_event(service: &str, user: Option<&str>, cost: f64) -> TelemetryEvent {
+        let mut builder = event()
+            .service(service)
+            .cost_usd(cost)
+            .timestamp(Utc.with_ymd_and_hms(2024, 1, 1, 12, 30, 0).unwrap())
+            .metadata("team", "search");
+        if let Some(user) = user {
+            builder = builder.metadata("user_id", user);
+        }
+        builder.build()
+    }
+
+    #[test]
+    fn test_dimension_parsing() {
+        assert_eq!("service".parse::<CostDimension>().unwrap(), CostDimension::Service);
+        assert_eq!(
+            "tag:team".parse::<CostDimension>().unwrap(),
+            CostDimension::Tag("team".to_string())
+        );
+        assert_eq!(CostDimension::Tag("team".to_string()).column(), "tag_team");
+        assert!("tag:".parse::<CostDimension>().is_err());
+        assert!("tag:a\"b".parse::<CostDimension>().is_err());
+        assert!("region".parse::<CostDimension>().is_err());
+    }
+
+    #[test]
+    fn test_record_and_drain() {
+        let aggregator = CostAggregator::new(CostAggregatorConfig {
+            tag_keys: vec!["team".to_string()],
+            ..Default::default()
+        })
+        .unwrap();
+
+        aggregator.record(&create_test_event("checkout", Some("alice"), 0.01));
+        aggregator.record(&create_test_event("checkout", Some("alice"), 0.02));
+        aggregator.record(&create_test_event("checkout", Some("bob"), 0.05));
+        assert_eq!(aggregator.pending_count(), 2);
+
+        let mut rollups = aggregator.drain();
+        rollups.sort_by(|a, b| a.user.cmp(&b.user));
+        assert_eq!(aggregator.pending_count(), 0);
+
+        let alice = &rollups[0];
+        assert_eq!(alice.user.as_deref(), Some("alice"));
+        assert_eq!(alice.requests, 2);
+        assert!((alice.cost_usd - 0.03).abs() < 1e-9);
+        assert_eq!(alice.prompt_tokens, 20);
+        assert_eq!(alice.completion_tokens, 40);
+        assert_eq!(alice.tags.get("team").map(String::as_str), Some("search"));
+        assert_eq!(alice.bucket_start.timestamp() % 3600, 0);
+    }
+
+    #[test]
+    fn test_restore_merges() {
+        let aggregator = CostAggregator::new(CostAggregatorConfig::default()).unwrap();
+        aggregator.record(&create_test_event("checkout", None, 0.01));
+        let drained = aggregator.drain();
+
+        aggregator.record(&create_test_event("checkout", None, 0.02));
+        aggregator.restore(drained);
+
+        let rollups = aggregator.drain();
+        assert_eq!(rollups.len(), 1);
+        assert_eq!(rollups[0].requests, 2);
+    }
+
+    #[test]
+    fn test_invalid_config() {
+        let config = CostAggregatorConfig {
+            bucket_secs: 0,
+            ..Default::default()
+        };
+        assert!(CostAggregator::new(config).is_err());
+
+        let config = CostAggregatorConfig {
+            tag_keys: vec!["bad key".to_string()],
+            ..Default::default()
+        };
+        assert!(CostAggregator::new(config).is_err());
+    }
+}
@@ -0,0 +1,268 @@
+//! Alert delivery records and SLA reporting.
+//!
+//! Each alert sent through an alerter produces an [`AlertMetadata`] record
+//! with its outcome and end-to-end latency (anomaly detection to delivery
+//! confirmation). Reports group records by alerter and severity and compare
+//! them against per-severity delivery objectives, e.g. 99% of critical alerts
+//! delivered within 30 seconds.
+
+use crate::query::TimeRange;
+use llm_sentinel_core::{
+    events::{AlertMetadata, AlertStatus},
+    types::Severity,
+};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+
+/// Delivery objective for one severity
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct DeliverySlaPolicy {
+    /// Severity the objective applies to
+    pub severity: Severity,
+    /// Maximum end-to-end delivery latency (seconds)
+    pub target_secs: u64,
+    /// Fraction of alerts that must be delivered within the target
+    pub objective: f64,
+}
+
+impl DeliverySlaPolicy {
+    /// Default objectives: critical/high within 30s/60s at 99%,
+    /// medium/low within 5m/15m at 95%
+    pub fn defaults() -> Vec<Self> {
+        vec![
+            Self {
+                severity: Severity::Critical,
+                target_secs: 30,
+                objective: 0.99,
+            },
+            Self {
+                severity: Severity::High,
+                target_secs: 60,
+                objective: 0.99,
+            },
+            Self {
+                severity: Severity::Medium,
+                target_secs: 300,
+                objective: 0.95,
+            },
+            Self {
+                severity: Severity::Low,
+                target_secs: 900,
+                objective: 0.95,
+            },
+        ]
+    }
+}
+
+/// Query for alert delivery records
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DeliveryQuery {
+    /// Time range (by delivery attempt)
+    pub time_range: TimeRange,
+    /// Filter by alerter
+    pub alerter: Option<String>,
+    /// Filter by severity
+    pub severity: Option<Severity>,
+    /// Filter by alert ID
+    #[serde(default)]
+    pub alert_id: Option<String>,
+}
+
+impl DeliveryQuery {
+    /// Create a new delivery query
+    pub fn new(time_range: TimeRange) -> Self {
+        Self {
+            time_range,
+            alerter: None,
+            severity: None,
+            alert_id: None,
+        }
+    }
+
+    /// Filter by alerter
+    pub fn with_alerter(mut self, alerter: impl Into<String>) -> Self {
+        self.alerter = Some(alerter.into());
+        self
+    }
+
+    /// Filter by severity
+    pub fn with_severity(mut self, severity: Severity) -> Self {
+        self.severity = Some(severity);
+        self
+    }
+
+    /// Filter by alert ID
+    pub fn with_alert_id(mut self, alert_id: impl Into<String>) -> Self {
+        self.alert_id = Some(alert_id.into());
+        self
+    }
+}
+
+/// SLA compliance for one alerter and severity
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct DeliverySlaRow {
+    /// Alerter name
+    pub alerter: String,
+    /// Alert severity
+    pub severity: Severity,
+    /// Latency target (seconds)
+    pub target_secs: u64,
+    /// Required compliance
+    pub objective: f64,
+    /// Alerts attempted
+    pub total: u64,
+    /// Alerts delivered
+    pub delivered: u64,
+    /// Alerts that failed delivery
+    pub failed: u64,
+    /// Alerts delivered within the target
+    pub within_target: u64,
+    /// Fraction of alerts delivered within the target
+    pub compliance: f64,
+    /// Whether the objective is met
+    pub met: bool,
+    /// Median delivery latency (ms)
+    pub p50_latency_ms: Option<f64>,
+    /// 95th percentile delivery latency (ms)
+    pub p95_latency_ms: Option<f64>,
+    /// 99th percentile delivery latency (ms)
+    pub p99_latency_ms: Option<f64>,
+}
+
+/// Build an SLA report from delivery records
+///
+/// Failed deliveries count against compliance. Severities without a policy
+/// are left out.
+pub fn sla_report(
+    records: &[AlertMetadata],
+    policies: &[DeliverySlaPolicy],
+) -> Vec<DeliverySlaRow> {
+    let mut groups: BTreeMap<(String, Severity), Vec<&AlertMetadata>> = BTreeMap::new();
+    for record in records {
+        if matches!(record.status, AlertStatus::Delivered | AlertStatus::Failed) {
+            groups
+                .entry((record.alerter.clone(), record.severity))
+                .or_default()
+                .push(record);
+        }
+    }
+
+    let mut rows: Vec<DeliverySlaRow> = groups
+        .into_iter()
+        .filter_map(|((alerter, severity), records)| {
+            let policy = policies.iter().find(|p| p.severity == severity)?;
+            let target_ms = policy.target_secs as f64 * 1000.0;
+
+            let mut latencies: Vec<f64> = records
+                .iter()
+                .filter(|r| r.status == AlertStatus::Delivered)
+                .filter_map(|r| r.latency_ms)
+                .collect();
+            latencies.sort_by(|a, b| a.total_cmp(b));
+
+            let total = records.len() as u64;
+            let delivered = latencies.len() as u64;
+            let within_target = latencies.iter().filter(|l| **l <= target_ms).count() as u64;
+            let compliance = within_target as f64 / total as f64;
+
+            Some(DeliverySlaRow {
+                alerter,
+                severity,
+                target_secs: policy.target_secs,
+                objective: policy.objective,
+                total,
+                delivered,
+                failed: total - delivered,
+                within_target,
+                compliance,
+                met: compliance >= policy.objective,
+                p50_latency_ms: percentile(&latencies, 0.50),
+                p95_latency_ms: percentile(&latencies, 0.95),
+                p99_latency_ms: percentile(&latencies, 0.99),
+            })
+        })
+        .collect();
+
+    // Most severe first, then by alerter
+    rows.sort_by(|a, b| {
+        b.severity
+            .cmp(&a.severity)
+            .then_with(|| a.alerter.cmp(&b.alerter))
+    });
+    rows
+}
+
+/// Nearest-rank percentile of sorted values
+pub(crate) fn percentile(sorted: &[f64], p: f64) -> Option<f64> {
+    if sorted.is_empty() {
+        return None;
+    }
+    let rank = (p * sorted.len() as f64).ceil() as usize;
+    Some(sorted[rank.clamp(1, sorted.len()) - 1])
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::{Duration, Utc};
+
+    fn record(alerter: &str, severity: Severity, latency_ms: Option<f64>) -> AlertMetadata {
+        let now = Utc::now();
+        AlertMetadata {
+            alert_id: "alert".to_string(),
+            alerter: alerter.to_string(),
+            severity,
+            anomaly_timestamp: now - Duration::seconds(1),
+            attempts: 1,
+            last_attempt: now,
+            status: if latency_ms.is_some() {
+                AlertStatus::Delivered
+            } else {
+                AlertStatus::Failed
+            },
+            latency_ms,
+            error: latency_ms.is_none().then(|| "timeout".to_string()),
+            request: None,
+            response: None,
+        }
+    }
+
+    #[test]
+    fn test_sla_report() {
+        let mut records: Vec<AlertMetadata> = (0..98)
+            .map(|i| record("webhook", Severity::Critical, Some(1000.0 + i as f64)))
+            .collect();
+        records.push(record("webhook", Severity::Critical, Some(45_000.0)));
+        records.push(record("webhook", Severity::Critical, None));
+        records.push(record("rabbitmq", Severity::Medium, Some(200.0)));
+
+        let rows = sla_report(&records, &DeliverySlaPolicy::defaults());
+        assert_eq!(rows.len(), 2);
+
+        let critical = &rows[0];
+        assert_eq!(critical.alerter, "webhook");
+        assert_eq!(critical.total, 100);
+        assert_eq!(critical.delivered, 99);
+        assert_eq!(critical.failed, 1);
+        assert_eq!(critical.within_target, 98);
+        assert!((critical.compliance - 0.98).abs() < 1e-9);
+        assert!(!critical.met);
+        assert_eq!(critical.p99_latency_ms, Some(45_000.0));
+
+        let medium = &rows[1];
+        assert_eq!(medium.alerter, "rabbitmq");
+        assert!(medium.met);
+        assert_eq!(medium.p50_latency_ms, Some(200.0));
+    }
+
+    #[test]
+    fn test_sla_report_without_policy() {
+        let records = vec![record("webhook", Severity::Low, Some(100.0))];
+        let policies = vec![DeliverySlaPolicy {
+            severity: Severity::Critical,
+            target_secs: 30,
+            objective: 0.99,
+        }];
+        assert!(sla_report(&records, &policies).is_empty());
+    }
+}
@@ -0,0 +1,436 @@
+//! Digest reports summarizing a period.
+//!
+//! A [`DigestReport`] condenses one period of stored anomalies, cost rollups
+//! and alert deliveries, compared against the period of the same length
+//! before it, into what a daily or weekly digest shows, along with the
+//! annotations responders wrote during the period. Reports render
+//! through `{placeholder}` templates.
+
+use crate::{
+    annotation::AnnotationQuery,
+    cost::{CostDimension, CostReportQuery},
+    delivery::{percentile, DeliveryQuery},
+    query::{AnomalyQuery, TimeRange},
+    Storage,
+};
+use llm_sentinel_core::{
+    annotation::AnomalyAnnotation,
+    events::{AlertStatus, AnomalyEvent},
+    types::Severity,
+    Result,
+};
+use serde::{Deserialize, Serialize};
+use std::{collections::BTreeMap, fmt::Write};
+
+/// Maximum number of anomalies summarized per period
+const MAX_DIGEST_ANOMALIES: usize = 100_000;
+
+/// Body of a digest when no template is configured
+pub const DEFAULT_DIGEST_TEMPLATE: &str = "\
+Anomalies from {start} to {end}: {anomalies} ({anomaly_change} on the previous period)
+
+By severity:
+{by_severity}
+
+By service:
+{by_service}
+
+Top regressions:
+{top_regressions}
+
+Cost: ${cost_usd} ({cost_change} on the previous period)
+{top_costs}
+
+Alert delivery: {deliveries}
+
+Annotations:
+{annotations}
+";
+
+/// Anomaly increase of one service and model
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct DigestRegression {
+    /// Service name
+    pub service: String,
+    /// Model identifier
+    pub model: String,
+    /// Anomalies in the period
+    pub anomalies: u64,
+    /// Anomalies in the previous period
+    pub previous_anomalies: u64,
+}
+
+/// Alert delivery within a period
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct DigestDeliveries {
+    /// Alerts attempted
+    pub total: u64,
+    /// Alerts delivered
+    pub delivered: u64,
+    /// Alerts that failed delivery
+    pub failed: u64,
+    /// 95th percentile delivery latency (ms)
+    pub p95_latency_ms: Option<f64>,
+}
+
+/// Annotation written during a period
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct DigestAnnotation {
+    /// Service of the annotated anomaly, when it was detected in the period
+    /// or the one before
+    pub service: Option<String>,
+    /// Type of the annotated anomaly, when it was detected in the period or
+    /// the one before
+    pub anomaly_type: Option<String>,
+    /// The annotation
+    #[serde(flatten)]
+    pub annotation: AnomalyAnnotation,
+}
+
+/// Summary of one period
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct DigestReport {
+    /// Period summarized
+    pub period: TimeRange,
+    /// Anomalies in the period
+    pub anomalies: u64,
+    /// Anomalies in the previous period
+    pub previous_anomalies: u64,
+    /// Anomalies by severity
+    pub by_severity: BTreeMap<Severity, u64>,
+    /// Anomalies by service
+    pub by_service: BTreeMap<String, u64>,
+    /// Service/model pairs with the largest anomaly increase
+    pub top_regressions: Vec<DigestRegression>,
+    /// Cost in the period (USD)
+    pub cost_usd: f64,
+    /// Cost in the previous period (USD)
+    pub previous_cost_usd: f64,
+    /// Most expensive services (USD)
+    pub top_costs: Vec<(String, f64)>,
+    /// Alert delivery in the period
+    pub deliveries: DigestDeliveries,
+    /// Annotations written in the period, oldest first
+    pub annotations: Vec<DigestAnnotation>,
+}
+
+impl DigestReport {
+    /// Summarize `period` from storage, listing `top` entries per ranking
+    pub async fn build(storage: &dyn Storage, period: TimeRange, top: usize) -> Result<Self> {
+        let previous = TimeRange::new(period.start - (period.end - period.start), period.start);
+
+        let anomalies = query_anomalies(storage, &period).await?;
+        let previous_anomalies = query_anomalies(storage, &previous).await?;
+
+        let mut by_severity = BTreeMap::new();
+        let mut by_service = BTreeMap::new();
+        for anomaly in &anomalies {
+            *by_severity.entry(anomaly.severity).or_insert(0) += 1;
+            *by_service
+                .entry(anomaly.service_name.to_string())
+                .or_insert(0) += 1;
+        }
+
+        let cost_usd = total_cost(storage, &period).await?;
+        let previous_cost_usd = total_cost(storage, &previous).await?;
+        let mut top_costs: Vec<(String, f64)> = storage
+            .query_cost_report(CostReportQuery::new(period.clone()).group_by(CostDimension::Service))
+            .await?
+            .into_iter()
+            .map(|row| {
+                let service = row.group.get("service").cloned().unwrap_or_default();
+                (service, row.cost_usd)
+            })
+            .collect();
+        top_costs.sort_by(|a, b| b.1.total_cmp(&a.1).then_with(|| a.0.cmp(&b.0)));
+        top_costs.truncate(top);
+
+        let records = storage
+            .query_alert_deliveries(DeliveryQuery::new(period.clone()))
+            .await?;
+        let mut latencies: Vec<f64> = records
+            .iter()
+            .filter(|r| r.status == AlertStatus::Delivered)
+            .filter_map(|r| r.latency_ms)
+            .collect();
+        latencies.sort_by(|a, b| a.total_cmp(b));
+        let failed = records
+            .iter()
+            .filter(|r| r.status == AlertStatus::Failed)
+            .count() as u64;
+
+        let annotations = storage
+            .query_anomaly_annotations(AnnotationQuery::new(period.clone()))
+            .await?
+            .into_iter()
+            .map(|annotation| {
+                let anomaly = anomalies
+                    .iter()
+                    .chain(&previous_anomalies)
+                    .find(|a| a.alert_id == annotation.alert_id);
+                DigestAnnotation {
+                    service: anomaly.map(|a| a.service_name.to_string()),
+                    anomaly_type: anomaly.map(|a| a.anomaly_type.to_string()),
+                    annotation,
+                }
+            })
+            .collect();
+
+        Ok(Self {
+            anomalies: anomalies.len() as u64,
+            previous_anomalies: previous_anomalies.len() as u64,
+            by_severity,
+            by_service,
+            top_regressions: regressions(&anomalies, &previous_anomalies, top),
+            cost_usd,
+            previous_cost_usd,
+            top_costs,
+            deliveries: DigestDeliveries {
+                total: records.len() as u64,
+                delivered: latencies.len() as u64,
+                failed,
+                p95_latency_ms: percentile(&latencies, 0.95),
+            },
+            annotations,
+            period,
+        })
+    }
+
+    /// Render a template
+    ///
+    /// Replaces the report placeholders (`{start}`, `{end}`, `{anomalies}`,
+    /// `{anomaly_change}`, `{by_severity}`, `{by_service}`,
+    /// `{top_regressions}`, `{cost_usd}`, `{cost_change}`, `{top_costs}`,
+    /// `{deliveries}`, `{annotations}`) and the given extra ones.
+    pub fn render(&self, template: &str, extra: &[(&str, &str)]) -> String {
+        let list = |lines: Vec<String>| {
+            if lines.is_empty() {
+                "  (none)".to_string()
+            } else {
+                lines.join("\n")
+            }
+        };
+
+        let by_severity = list(
+            self.by_severity
+                .iter()
+                .rev()
+                .map(|(severity, count)| format!("  {}: {}", severity, count))
+                .collect(),
+        );
+        let mut services: Vec<_> = self.by_service.iter().collect();
+        services.sort_by(|a, b| b.1.cmp(a.1).then_with(|| a.0.cmp(b.0)));
+        let by_service = list(
+            services
+                .into_iter()
+                .map(|(service, count)| format!("  {}: {}", service, count))
+                .collect(),
+        );
+        let top_regressions = list(
+            self.top_regressions
+                .iter()
+                .map(|r| {
+                    format!(
+                        "  {}/{}: {} (was {})",
+                        r.service, r.model, r.anomalies, r.previous_anomalies
+                    )
+                })
+                .collect(),
+        );
+        let top_costs = list(
+            self.top_costs
+                .iter()
+                .map(|(service, cost)| format!("  {}: ${:.2}", service, cost))
+                .collect(),
+        );
+        let mut deliveries = format!(
+            "{} sent, {} delivered, {} failed",
+            self.deliveries.total, self.deliveries.delivered, self.deliveries.failed
+        );
+        if let Some(p95) = self.deliveries.p95_latency_ms {
+            let _ = write!(deliveries, ", p95 {:.0} ms", p95);
+        }
+        let annotations = list(
+            self.annotations
+                .iter()
+                .map(|a| {
+                    let anomaly = match (&a.service, &a.anomaly_type) {
+                        (Some(service), Some(anomaly_type)) => {
+                            format!("{} {}", service, anomaly_type)
+                        }
+                        _ => a.annotation.alert_id.to_string(),
+                    };
+                    let mut line = format!(
+                        "  {} ({}): {}",
+                        anomaly, a.annotation.kind, a.annotation.text
+                    );
+                    if let Some(url) = &a.annotation.url {
+                        let _ = write!(line, " <{}>", url);
+                    }
+                    if let Some(author) = &a.annotation.author {
+                        let _ = write!(line, " by {}", author);
+                    }
+                    line
+                })
+                .collect(),
+        );
+
+        let placeholders = [
+            ("start", self.period.start.format("%Y-%m-%d %H:%M UTC").to_string()),
+            ("end", self.period.end.format("%Y-%m-%d %H:%M UTC").to_string()),
+            ("anomalies", self.anomalies.to_string()),
+            (
+                "anomaly_change",
+                change(self.anomalies as f64, self.previous_anomalies as f64),
+            ),
+            ("by_severity", by_severity),
+            ("by_service", by_service),
+            ("top_regressions", top_regressions),
+            ("cost_usd", format!("{:.2}", self.cost_usd)),
+            ("cost_change", change(self.cost_usd, self.previous_cost_usd)),
+            ("top_costs", top_costs),
+            ("deliveries", deliveries),
+            ("annotations", annotations),
+        ];
+
+        let mut rendered = template.to_string();
+        for (name, value) in extra {
+            rendered = rendered.replace(&format!("{{{}}}", name), value);
+        }
+        for (name, value) in &placeholders {
+            rendered = rendered.replace(&format!("{{{}}}", name), value);
+        }
+        rendered
+    }
+}
+
+async fn query_anomalies(storage: &dyn Storage, range: &TimeRange) -> Result<Vec<AnomalyEvent>> {
+    // Expired anomalies were still detected in the period
+    let mut query = AnomalyQuery::new(range.clone()).with_expired();
+    query.limit = Some(MAX_DIGEST_ANOMALIES);
+    storage.query_anomalies(query).await
+}
+
+async fn total_cost(storage: &dyn Storage, range: &TimeRange) -> Result<f64> {
+    Ok(storage
+        .query_cost_report(CostReportQuery::new(range.clone()))
+        .await?
+        .iter()
+        .map(|row| row.cost_usd)
+        .sum())
+}
+
+/// Service/model pairs whose anomaly count grew the most
+fn regressions(
+    current: &[AnomalyEvent],
+    previous: &[AnomalyEvent],
+    top: usize,
+) -> Vec<DigestRegression> {
+    let mut counts: BTreeMap<(String, String), (u64, u64)> = BTreeMap::new();
+    for anomaly in current {
+        let key = (anomaly.service_name.to_string(), anomaly.model.to_string());
+        counts.entry(key).or_default().0 += 1;
+    }
+    for anomaly in previous {
+        let key = (anomaly.service_name.to_string(), anomaly.model.to_string());
+        counts.entry(key).or_default().1 += 1;
+    }
+
+    let mut regressions: Vec<DigestRegression> = counts
+        .into_iter()
+        .filter(|(_, (current, previous))| current > previous)
+        .map(|((service, model), (anomalies, previous_anomalies))| DigestRegression {
+            service,
+            model,
+            anomalies,
+            previous_anomalies,
+        })
+        .collect();
+    regressions.sort_by(|a, b| {
+        (b.anomalies - b.previous_anomalies).cmp(&(a.anomalies - a.previous_anomalies))
+    });
+    regressions.truncate(top);
+    regressions
+}
+
+/// Relative change, e.g. `+25%`
+fn change(current: f64, previous: f64) -> String {
+    if previous == 0.0 {
+        if current == 0.0 {
+            "no change".to_string()
+        } else {
+            "new".to_string()
+        }
+    } else {
+        format!("{:+.0}%", (current - previous) / previous * 100.0)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::memory::InMemoryStorage;
+    use chrono::{Duration, Utc};
+    use llm_sentinel_core::annotation::AnnotationKind;
+
+    fn anomaly(service: &str, severity: Severity, hours_ago: i64) -> AnomalyEvent {
+        let mut anomaly = llm_sentinel_fixtures::anomaly()
+            .severity(severity)
+            .service(service)
+            .build();
+        anomaly.timestamp = Utc::now() - Duration::hours(hours_ago);
+        anomaly
+    }
+
+    #[tokio::test]
+    async fn test_digest_report() {
+        let storage = InMemoryStorage::new();
+        let anomalies = vec![
+            anomaly("checkout", Severity::High, 1),
+            anomaly("checkout", Severity::Critical, 2),
+            anomaly("checkout", Severity::Low, 3),
+            anomaly("search", Severity::Low, 4),
+            // Previous day
+            anomaly("search", Severity::Low, 30),
+            anomaly("search", Severity::Low, 31),
+        ];
+        storage.write_anomaly_batch(&anomalies).await.unwrap();
+        let resolution = AnomalyAnnotation::new(
+            anomalies[1].alert_id,
+            AnnotationKind::Resolution,
+            "Rolled back deploy 1234",
+            None,
+            Some("alice".to_string()),
+        )
+        .unwrap();
+        storage
+            .write_anomaly_annotations(std::slice::from_ref(&resolution))
+            .await
+            .unwrap();
+
+        let report = DigestReport::build(&storage, TimeRange::last_hours(24), 5)
+            .await
+            .unwrap();
+        assert_eq!(report.anomalies, 4);
+        assert_eq!(report.previous_anomalies, 2);
+        assert_eq!(report.by_severity[&Severity::Low], 2);
+        assert_eq!(report.by_service["checkout"], 3);
+        // Search dropped from two to one, so only checkout regressed
+        assert_eq!(report.top_regressions.len(), 1);
+        assert_eq!(report.top_regressions[0].service, "checkout");
+        assert_eq!(report.top_regressions[0].anomalies, 3);
+
+        assert_eq!(report.annotations.len(), 1);
+        assert_eq!(report.annotations[0].service.as_deref(), Some("checkout"));
+
+        let rendered = report.render(
+            "{name}: {anomalies} ({anomaly_change})\n{top_regressions}\n{deliveries}\n{annotations}",
+            &[("name", "ops")],
+        );
+        assert_eq!(
+            rendered,
+            "ops: 4 (+100%)\n  checkout/gpt-4: 3 (was 0)\n0 sent, 0 delivered, 0 failed\n  \
+             checkout latency_spike (resolution): Rolled back deploy 1234 by alice"
+        );
+    }
+}
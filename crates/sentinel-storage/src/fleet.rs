@@ -0,0 +1,462 @@
+//! Fleet comparison for anomalies.
+//!
+//! A latency spike means something different when every service calling a
+//! model sees it (a provider-wide issue) than when only one does (a
+//! service-specific one). [`compare_to_fleet`] puts the anomaly's metric
+//! next to the same metric across all services using the model over the
+//! window leading up to the anomaly, e.g. "4x the fleet P95 latency of
+//! gpt-4", and classifies the anomaly's [`FleetScope`] by comparing the
+//! affected service against the rest of the fleet.
+//!
+//! Value metrics (latency, tokens, cost) are compared at the 95th
+//! percentile; rate metrics (`error_rate`) by their mean.
+
+use crate::{
+    delivery::percentile,
+    query::{TelemetryQuery, TimeRange},
+    Storage,
+};
+use chrono::{DateTime, Duration, Utc};
+use llm_sentinel_core::{
+    events::{AnomalyEvent, TelemetryEvent},
+    types::{ModelId, ServiceId},
+    Error, Result,
+};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use uuid::Uuid;
+
+/// Affected service at least this many times the rest of the fleet is
+/// service-specific
+const SERVICE_SPECIFIC_RATIO: f64 = 2.0;
+
+/// Affected service at most this many times the rest of the fleet is
+/// fleet-wide
+const FLEET_WIDE_RATIO: f64 = 1.25;
+
+/// Services listed in a comparison
+const MAX_SERVICES: usize = 20;
+
+/// Anomaly metrics recorded per event
+const COMPARABLE_METRICS: &[&str] = &[
+    "latency_ms",
+    "total_tokens",
+    "prompt_tokens",
+    "response_tokens",
+    "cost_usd",
+    "error_rate",
+];
+
+/// Value of a comparable metric in a single event
+fn event_value(event: &TelemetryEvent, metric: &str) -> Option<f64> {
+    match metric {
+        "latency_ms" => Some(event.latency_ms),
+        "total_tokens" => Some(f64::from(event.total_tokens())),
+        "prompt_tokens" => Some(f64::from(event.prompt.tokens)),
+        "response_tokens" => Some(f64::from(event.response.tokens)),
+        "cost_usd" => Some(event.cost_usd),
+        "error_rate" => Some(event.error_rate()),
+        _ => None,
+    }
+}
+
+/// Statistic a metric is compared by
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum FleetStatistic {
+    /// 95th percentile, for value metrics
+    P95,
+    /// Mean, for rate metrics
+    Mean,
+}
+
+impl FleetStatistic {
+    fn for_metric(metric: &str) -> Self {
+        if metric.ends_with("_rate") {
+            Self::Mean
+        } else {
+            Self::P95
+        }
+    }
+
+    fn of(&self, stats: &MetricStats) -> f64 {
+        match self {
+            Self::P95 => stats.p95,
+            Self::Mean => stats.mean,
+        }
+    }
+}
+
+/// Distribution of a metric over a set of events
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct MetricStats {
+    /// Events sampled
+    pub sample_count: usize,
+    /// Services the events came from
+    pub services: usize,
+    /// Mean value
+    pub mean: f64,
+    /// Median value
+    pub p50: f64,
+    /// 95th percentile value
+    pub p95: f64,
+    /// 99th percentile value
+    pub p99: f64,
+}
+
+impl MetricStats {
+    /// Summarize `(service, value)` samples; `None` when there are none
+    fn from_samples<'a>(samples: impl Iterator<Item = (&'a ServiceId, f64)>) -> Option<Self> {
+        let mut services = Vec::new();
+        let mut values = Vec::new();
+        for (service, value) in samples {
+            services.push(service);
+            values.push(value);
+        }
+        services.sort();
+        services.dedup();
+        values.sort_by(|a, b| a.total_cmp(b));
+
+        Some(Self {
+            sample_count: values.len(),
+            services: services.len(),
+            mean: values.iter().sum::<f64>() / values.len().max(1) as f64,
+            p50: percentile(&values, 0.50)?,
+            p95: percentile(&values, 0.95)?,
+            p99: percentile(&values, 0.99)?,
+        })
+    }
+}
+
+/// Metric distribution of one service
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ServiceMetricStats {
+    /// Service ID
+    pub service: ServiceId,
+    /// Distribution of the service's events
+    #[serde(flatten)]
+    pub stats: MetricStats,
+}
+
+/// Whether an anomaly looks specific to its service or shared by the fleet
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum FleetScope {
+    /// The affected service is well above the rest of the fleet
+    ServiceSpecific,
+    /// The rest of the fleet sees similar values, e.g. a provider issue
+    FleetWide,
+    /// The affected service is somewhat above the rest of the fleet
+    Mixed,
+    /// No other service used the model in the window
+    Unknown,
+}
+
+/// An anomaly's metric compared against all services using its model
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct FleetComparison {
+    /// Compared anomaly
+    pub alert_id: Uuid,
+    /// Affected service
+    pub service: ServiceId,
+    /// Model whose fleet the service is compared against
+    pub model: ModelId,
+    /// Compared metric
+    pub metric: String,
+    /// Statistic the metric is compared by
+    pub statistic: FleetStatistic,
+    /// Window start
+    pub start: DateTime<Utc>,
+    /// Window end (the anomaly's detection time)
+    pub end: DateTime<Utc>,
+    /// Anomalous value
+    pub value: f64,
+    /// Distribution over the affected service's events
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub affected: Option<MetricStats>,
+    /// Distribution over all services' events, the affected one included
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub fleet: Option<MetricStats>,
+    /// Distribution over the other services' events
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub others: Option<MetricStats>,
+    /// Anomalous value as a multiple of the fleet statistic
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub value_to_fleet: Option<f64>,
+    /// Share of fleet events below the anomalous value, in percent
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub fleet_percentile: Option<f64>,
+    /// Affected service's statistic as a multiple of the other services'
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub affected_to_others: Option<f64>,
+    /// Whether the anomaly looks service-specific or fleet-wide
+    pub scope: FleetScope,
+    /// Services using the model, highest statistic first
+    pub services: Vec<ServiceMetricStats>,
+}
+
+/// Ratio of two statistics, when the denominator is positive
+fn ratio(numerator: f64, denominator: f64) -> Option<f64> {
+    (denominator > 0.0).then(|| numerator / denominator)
+}
+
+/// Compare an anomaly's metric against every service using its model over
+/// the `window` before the anomaly
+///
+/// At most `max_events` of the fleet's most recent events are sampled; the
+/// affected service is sampled separately so a busy fleet cannot crowd it
+/// out. Fails with a validation error for metrics that are not recorded per
+/// event, such as SLO burn rates.
+pub async fn compare_to_fleet(
+    storage: &dyn Storage,
+    anomaly: &AnomalyEvent,
+    window: Duration,
+    max_events: usize,
+) -> Result<FleetComparison> {
+    let metric = anomaly.details.metric.as_str();
+    if !COMPARABLE_METRICS.contains(&metric) {
+        return Err(Error::validation(format!(
+            "Metric {} cannot be compared across the fleet",
+            metric
+        )));
+    }
+    let statistic = FleetStatistic::for_metric(metric);
+
+    let end = anomaly.timestamp;
+    let start = end - window;
+    let mut query = TelemetryQuery::new(TimeRange::new(start, end))
+        .with_model(anomaly.model.clone())
+        .with_limit(max_events);
+    if let Some(environment) = &anomaly.environment {
+        query = query.with_environment(environment.clone());
+    }
+    let affected_query = query.clone().with_service(anomaly.service_name.clone());
+
+    let fleet_events = storage.query_telemetry(query).await?;
+    let affected_events = storage.query_telemetry(affected_query).await?;
+
+    let samples = |events: &[TelemetryEvent]| -> Vec<(ServiceId, f64)> {
+        events
+            .iter()
+            .filter_map(|e| Some((e.service_name.clone(), event_value(e, metric)?)))
+            .collect()
+    };
+    let fleet_samples = samples(&fleet_events);
+    let affected_samples = samples(&affected_events);
+    let others_samples: Vec<&(ServiceId, f64)> = fleet_samples
+        .iter()
+        .filter(|(service, _)| *service != anomaly.service_name)
+        .collect();
+
+    let affected = MetricStats::from_samples(affected_samples.iter().map(|(s, v)| (s, *v)));
+    let others = MetricStats::from_samples(others_samples.iter().map(|(s, v)| (s, *v)));
+    // The fleet is the other services plus every sampled event of the
+    // affected one
+    let fleet = MetricStats::from_samples(
+        others_samples
+            .iter()
+            .map(|(s, v)| (s, *v))
+            .chain(affected_samples.iter().map(|(s, v)| (s, *v))),
+    );
+
+    let value = anomaly.details.value;
+    let value_to_fleet = fleet
+        .as_ref()
+        .and_then(|fleet| ratio(value, statistic.of(fleet)));
+    let fleet_percentile = fleet.as_ref().map(|fleet| {
+        let below = others_samples
+            .iter()
+            .map(|(_, v)| *v)
+            .chain(affected_samples.iter().map(|(_, v)| *v))
+            .filter(|v| *v < value)
+            .count();
+        below as f64 / fleet.sample_count as f64 * 100.0
+    });
+    let affected_to_others = match (&affected, &others) {
+        (Some(affected), Some(others)) => ratio(statistic.of(affected), statistic.of(others)),
+        _ => None,
+    };
+    let scope = match (&others, affected_to_others) {
+        (None, _) => FleetScope::Unknown,
+        (Some(_), Some(r)) if r >= SERVICE_SPECIFIC_RATIO => FleetScope::ServiceSpecific,
+        (Some(_), Some(r)) if r > FLEET_WIDE_RATIO => FleetScope::Mixed,
+        // The rest of the fleet is at zero (e.g. no errors) while the
+        // affected service is not
+        (Some(_), None) if affected.as_ref().is_some_and(|a| statistic.of(a) > 0.0) => {
+            FleetScope::ServiceSpecific
+        }
+        (Some(_), _) => FleetScope::FleetWide,
+    };
+
+    let mut by_service: BTreeMap<&ServiceId, Vec<f64>> = BTreeMap::new();
+    for (service, value) in others_samples {
+        by_service.entry(service).or_default().push(*value);
+    }
+    if !affected_samples.is_empty() {
+        by_service.insert(
+            &anomaly.service_name,
+            affected_samples.iter().map(|(_, v)| *v).collect(),
+        );
+    }
+    let mut services: Vec<ServiceMetricStats> = by_service
+        .into_iter()
+        .filter_map(|(service, values)| {
+            let stats = MetricStats::from_samples(values.into_iter().map(|v| (service, v)))?;
+            Some(ServiceMetricStats {
+                service: service.clone(),
+                stats,
+            })
+        })
+        .collect();
+    services.sort_by(|a, b| statistic.of(&b.stats).total_cmp(&statistic.of(&a.stats)));
+    services.truncate(MAX_SERVICES);
+
+    Ok(FleetComparison {
+        alert_id: anomaly.alert_id,
+        service: anomaly.service_name.clone(),
+        model: anomaly.model.clone(),
+        metric: metric.to_string(),
+        statistic,
+        start,
+        end,
+        value,
+        affected,
+        fleet,
+        others,
+        value_to_fleet,
+        fleet_percentile,
+        affected_to_others,
+        scope,
+        services,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::memory::InMemoryStorage;
+
+    use llm_sentinel_fixtures::{anomaly, event};
+
+    fn create_event(
+        service: &str,
+        model: &str,
+        latency_ms: f64,
+        minutes_ago: i64,
+    ) -> TelemetryEvent {
+        event()
+            .service(service)
+            .model(model)
+            .latency_ms(latency_ms)
+            .timestamp(Utc::now() - Duration::minutes(minutes_ago))
+            .build()
+    }
+
+    fn create_anomaly(metric: &str, value: f64) -> AnomalyEvent {
+        anomaly()
+            .service("checkout")
+            .metric(metric)
+            .value(value)
+            .build()
+    }
+
+    async fn storage_with(checkout_latency_ms: f64) -> InMemoryStorage {
+        let storage = InMemoryStorage::new();
+        let mut events = Vec::new();
+        for i in 1..=20 {
+            events.push(create_event("checkout", "gpt-4", checkout_latency_ms, i));
+            events.push(create_event("search", "gpt-4", 100.0 + i as f64, i));
+            events.push(create_event("billing", "gpt-4", 90.0, i));
+            // Other models and older events are not part of the fleet
+            events.push(create_event("search", "gpt-3.5", 9000.0, i));
+            events.push(create_event("search", "gpt-4", 9000.0, 120 + i));
+        }
+        storage.write_telemetry_batch(&events).await.unwrap();
+        storage
+    }
+
+    #[tokio::test]
+    async fn test_service_specific() {
+        let storage = storage_with(400.0).await;
+        let comparison = compare_to_fleet(
+            &storage,
+            &create_anomaly("latency_ms", 480.0),
+            Duration::hours(1),
+            1000,
+        )
+        .await
+        .unwrap();
+
+        assert_eq!(comparison.statistic, FleetStatistic::P95);
+        assert_eq!(comparison.affected.as_ref().unwrap().p95, 400.0);
+        let others = comparison.others.as_ref().unwrap();
+        assert_eq!((others.sample_count, others.services), (40, 2));
+        assert_eq!(others.p95, 118.0);
+        let fleet = comparison.fleet.as_ref().unwrap();
+        assert_eq!((fleet.sample_count, fleet.services), (60, 3));
+        assert_eq!(comparison.value_to_fleet, Some(480.0 / 400.0));
+        assert_eq!(comparison.fleet_percentile, Some(100.0));
+        assert_eq!(comparison.scope, FleetScope::ServiceSpecific);
+        let services: Vec<&str> = comparison
+            .services
+            .iter()
+            .map(|s| s.service.as_str())
+            .collect();
+        assert_eq!(services, vec!["checkout", "search", "billing"]);
+    }
+
+    #[tokio::test]
+    async fn test_fleet_wide() {
+        let storage = storage_with(110.0).await;
+        let comparison = compare_to_fleet(
+            &storage,
+            &create_anomaly("latency_ms", 480.0),
+            Duration::hours(1),
+            1000,
+        )
+        .await
+        .unwrap();
+        assert_eq!(comparison.scope, FleetScope::FleetWide);
+
+        // Only the affected service used the model
+        let storage = InMemoryStorage::new();
+        storage
+            .write_telemetry(&create_event("checkout", "gpt-4", 400.0, 1))
+            .await
+            .unwrap();
+        let comparison = compare_to_fleet(
+            &storage,
+            &create_anomaly("latency_ms", 480.0),
+            Duration::hours(1),
+            1000,
+        )
+        .await
+        .unwrap();
+        assert_eq!(comparison.scope, FleetScope::Unknown);
+        assert!(comparison.others.is_none());
+    }
+
+    #[tokio::test]
+    async fn test_rate_metrics_and_unsupported() {
+        let storage = storage_with(100.0).await;
+        let comparison = compare_to_fleet(
+            &storage,
+            &create_anomaly("error_rate", 0.5),
+            Duration::hours(1),
+            1000,
+        )
+        .await
+        .unwrap();
+        assert_eq!(comparison.statistic, FleetStatistic::Mean);
+        assert_eq!(comparison.fleet.unwrap().mean, 0.0);
+
+        let result = compare_to_fleet(
+            &storage,
+            &create_anomaly("slo_burn_rate", 14.0),
+            Duration::hours(1),
+            1000,
+        )
+        .await;
+        assert!(matches!(result, Err(Error::Validation(_))));
+    }
+}
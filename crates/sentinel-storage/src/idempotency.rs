@@ -0,0 +1,403 @@
+//! Idempotent writes for backends without native upserts.
+//!
+//! Kafka redelivers everything after the last committed offset when a
+//! consumer crashes, so telemetry and anomalies can be written twice with the
+//! same `event_id` / `alert_id`. InfluxDB overwrites the identical point, but
+//! backends that aggregate on write (Prometheus remote write) would count the
+//! event again. [`IdempotentStorage`] remembers recently written IDs and
+//! skips writes of IDs it has already stored.
+
+use crate::{
+    annotation::AnnotationQuery,
+    cost::{CostReportQuery, CostReportRow, CostRollup},
+    delivery::DeliveryQuery,
+    incident::IncidentQuery,
+    query::{AnomalyQuery, HeatmapBucket, HeatmapQuery, TelemetryQuery},
+    stream::StreamOffset,
+    Storage,
+};
+use async_trait::async_trait;
+use llm_sentinel_core::{
+    annotation::AnomalyAnnotation,
+    config::WriteDedupConfig,
+    events::{AlertMetadata, AnomalyEvent, TelemetryEvent},
+    incident::Incident,
+    lifecycle::AnomalyStateChange,
+    Result,
+};
+use moka::future::Cache;
+use std::collections::HashSet;
+use std::sync::Arc;
+use std::time::Duration;
+use tracing::debug;
+use uuid::Uuid;
+
+/// Storage wrapper skipping writes of recently written IDs
+///
+/// IDs are remembered only after the inner write succeeds, so failed writes
+/// can be retried.
+pub struct IdempotentStorage {
+    inner: Arc<dyn Storage>,
+    written: Cache<Uuid, ()>,
+}
+
+impl std::fmt::Debug for IdempotentStorage {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("IdempotentStorage")
+            .field("remembered_ids", &self.written.entry_count())
+            .finish_non_exhaustive()
+    }
+}
+
+impl IdempotentStorage {
+    /// Wrap a backend, remembering up to `config.capacity` IDs for
+    /// `config.ttl_secs`
+    pub fn new(inner: Arc<dyn Storage>, config: &WriteDedupConfig) -> Self {
+        Self {
+            inner,
+            written: Cache::builder()
+                .max_capacity(config.capacity)
+                .time_to_live(Duration::from_secs(config.ttl_secs))
+                .build(),
+        }
+    }
+
+    /// Wrap a backend unless it already upserts by ID
+    pub fn wrap(inner: Arc<dyn Storage>, config: &WriteDedupConfig) -> Arc<dyn Storage> {
+        if inner.upserts_by_id() {
+            inner
+        } else {
+            Arc::new(Self::new(inner, config))
+        }
+    }
+
+    /// Items whose IDs were not written before, first occurrence only
+    fn unwritten<T: Clone>(
+        &self,
+        items: &[T],
+        kind: &'static str,
+        id: impl Fn(&T) -> Uuid,
+    ) -> Vec<T> {
+        let mut seen = HashSet::with_capacity(items.len());
+        let fresh: Vec<T> = items
+            .iter()
+            .filter(|item| {
+                let id = id(item);
+                !self.written.contains_key(&id) && seen.insert(id)
+            })
+            .cloned()
+            .collect();
+
+        let skipped = items.len() - fresh.len();
+        if skipped > 0 {
+            debug!(kind, skipped, "Skipping writes of already stored IDs");
+            ::metrics::counter!("sentinel_storage_duplicate_writes_total", "kind" => kind)
+                .increment(skipped as u64);
+        }
+        fresh
+    }
+
+    async fn remember(&self, ids: Vec<Uuid>) {
+        for id in ids {
+            self.written.insert(id, ()).await;
+        }
+    }
+}
+
+#[async_trait]
+impl Storage for IdempotentStorage {
+    async fn write_telemetry(&self, event: &TelemetryEvent) -> Result<()> {
+        self.write_telemetry_batch(std::slice::from_ref(event))
+            .await
+    }
+
+    async fn write_anomaly(&self, anomaly: &AnomalyEvent) -> Result<()> {
+        self.write_anomaly_batch(std::slice::from_ref(anomaly))
+            .await
+    }
+
+    async fn write_telemetry_batch(&self, events: &[TelemetryEvent]) -> Result<()> {
+        let events = self.unwritten(events, "telemetry", |e| e.event_id);
+        if events.is_empty() {
+            return Ok(());
+        }
+        self.inner.write_telemetry_batch(&events).await?;
+        self.remember(events.iter().map(|e| e.event_id).collect())
+            .await;
+        Ok(())
+    }
+
+    async fn write_anomaly_batch(&self, anomalies: &[AnomalyEvent]) -> Result<()> {
+        let anomalies = self.unwritten(anomalies, "anomaly", |a| a.alert_id);
+        if anomalies.is_empty() {
+            return Ok(());
+        }
+        self.inner.write_anomaly_batch(&anomalies).await?;
+        self.remember(anomalies.iter().map(|a| a.alert_id).collect())
+            .await;
+        Ok(())
+    }
+
+    async fn query_telemetry(&self, query: TelemetryQuery) -> Result<Vec<TelemetryEvent>> {
+        self.inner.query_telemetry(query).await
+    }
+
+    async fn query_anomalies(&self, query: AnomalyQuery) -> Result<Vec<AnomalyEvent>> {
+        self.inner.query_anomalies(query).await
+    }
+
+    async fn get_anomaly(&self, alert_id: Uuid) -> Result<Option<AnomalyEvent>> {
+        self.inner.get_anomaly(alert_id).await
+    }
+
+    async fn write_anomaly_state_changes(&self, changes: &[AnomalyStateChange]) -> Result<()> {
+        self.inner.write_anomaly_state_changes(changes).await
+    }
+
+    async fn query_anomaly_state_changes(&self, alert_id: Uuid) -> Result<Vec<AnomalyStateChange>> {
+        self.inner.query_anomaly_state_changes(alert_id).await
+    }
+
+    async fn write_anomaly_annotations(&self, annotations: &[AnomalyAnnotation]) -> Result<()> {
+        self.inner.write_anomaly_annotations(annotations).await
+    }
+
+    async fn query_anomaly_annotations(
+        &self,
+        query: AnnotationQuery,
+    ) -> Result<Vec<AnomalyAnnotation>> {
+        self.inner.query_anomaly_annotations(query).await
+    }
+
+    async fn write_stream_offset(&self, offset: &StreamOffset) -> Result<()> {
+        self.inner.write_stream_offset(offset).await
+    }
+
+    async fn get_stream_offset(&self, consumer_id: &str) -> Result<Option<StreamOffset>> {
+        self.inner.get_stream_offset(consumer_id).await
+    }
+
+    async fn anomaly_heatmap(&self, query: HeatmapQuery) -> Result<Vec<HeatmapBucket>> {
+        self.inner.anomaly_heatmap(query).await
+    }
+
+    async fn write_cost_rollups(&self, rollups: &[CostRollup]) -> Result<()> {
+        self.inner.write_cost_rollups(rollups).await
+    }
+
+    async fn query_cost_report(&self, query: CostReportQuery) -> Result<Vec<CostReportRow>> {
+        self.inner.query_cost_report(query).await
+    }
+
+    async fn write_alert_deliveries(&self, deliveries: &[AlertMetadata]) -> Result<()> {
+        self.inner.write_alert_deliveries(deliveries).await
+    }
+
+    async fn query_alert_deliveries(&self, query: DeliveryQuery) -> Result<Vec<AlertMetadata>> {
+        self.inner.query_alert_deliveries(query).await
+    }
+
+    async fn write_incident(&self, incident: &Incident) -> Result<()> {
+        self.inner.write_incident(incident).await
+    }
+
+    async fn query_incidents(&self, query: IncidentQuery) -> Result<Vec<Incident>> {
+        self.inner.query_incidents(query).await
+    }
+
+    async fn write_redetected_anomalies(
+        &self,
+        job_id: Uuid,
+        anomalies: &[AnomalyEvent],
+    ) -> Result<()> {
+        self.inner
+            .write_redetected_anomalies(job_id, anomalies)
+            .await
+    }
+
+    async fn health_check(&self) -> Result<()> {
+        self.inner.health_check().await
+    }
+
+    fn upserts_by_id(&self) -> bool {
+        true
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use llm_sentinel_fixtures::event;
+    use std::sync::Mutex;
+
+    // Backend appending every write, like an aggregating backend would count it
+    #[derive(Default)]
+    struct AppendOnlyStorage {
+        telemetry: Mutex<Vec<Uuid>>,
+        fail: std::sync::atomic::AtomicBool,
+    }
+
+    #[async_trait]
+    impl Storage for AppendOnlyStorage {
+        async fn write_telemetry(&self, event: &TelemetryEvent) -> Result<()> {
+            self.write_telemetry_batch(std::slice::from_ref(event))
+                .await
+        }
+
+        async fn write_anomaly(&self, _anomaly: &AnomalyEvent) -> Result<()> {
+            Ok(())
+        }
+
+        async fn write_telemetry_batch(&self, events: &[TelemetryEvent]) -> Result<()> {
+            if self.fail.load(std::sync::atomic::Ordering::SeqCst) {
+                return Err(llm_sentinel_core::Error::storage("unavailable"));
+            }
+            let mut telemetry = self.telemetry.lock().unwrap();
+            telemetry.extend(events.iter().map(|e| e.event_id));
+            Ok(())
+        }
+
+        async fn write_anomaly_batch(&self, _anomalies: &[AnomalyEvent]) -> Result<()> {
+            Ok(())
+        }
+
+        async fn query_telemetry(&self, _query: TelemetryQuery) -> Result<Vec<TelemetryEvent>> {
+            Ok(Vec::new())
+        }
+
+        async fn query_anomalies(&self, _query: AnomalyQuery) -> Result<Vec<AnomalyEvent>> {
+            Ok(Vec::new())
+        }
+
+        async fn get_anomaly(&self, _alert_id: Uuid) -> Result<Option<AnomalyEvent>> {
+            Ok(None)
+        }
+
+        async fn write_anomaly_state_changes(&self, _changes: &[AnomalyStateChange]) -> Result<()> {
+            Ok(())
+        }
+
+        async fn query_anomaly_state_changes(
+            &self,
+            _alert_id: Uuid,
+        ) -> Result<Vec<AnomalyStateChange>> {
+            Ok(Vec::new())
+        }
+
+        async fn write_anomaly_annotations(
+            &self,
+            _annotations: &[AnomalyAnnotation],
+        ) -> Result<()> {
+            Ok(())
+        }
+
+        async fn query_anomaly_annotations(
+            &self,
+            _query: AnnotationQuery,
+        ) -> Result<Vec<AnomalyAnnotation>> {
+            Ok(Vec::new())
+        }
+
+        async fn write_stream_offset(&self, _offset: &StreamOffset) -> Result<()> {
+            Ok(())
+        }
+
+        async fn get_stream_offset(&self, _consumer_id: &str) -> Result<Option<StreamOffset>> {
+            Ok(None)
+        }
+
+        async fn anomaly_heatmap(&self, _query: HeatmapQuery) -> Result<Vec<HeatmapBucket>> {
+            Ok(Vec::new())
+        }
+
+        async fn write_cost_rollups(&self, _rollups: &[CostRollup]) -> Result<()> {
+            Ok(())
+        }
+
+        async fn query_cost_report(&self, _query: CostReportQuery) -> Result<Vec<CostReportRow>> {
+            Ok(Vec::new())
+        }
+
+        async fn write_alert_deliveries(&self, _deliveries: &[AlertMetadata]) -> Result<()> {
+            Ok(())
+        }
+
+        async fn query_alert_deliveries(
+            &self,
+            _query: DeliveryQuery,
+        ) -> Result<Vec<AlertMetadata>> {
+            Ok(Vec::new())
+        }
+
+        async fn write_incident(&self, _incident: &Incident) -> Result<()> {
+            Ok(())
+        }
+
+        async fn query_incidents(&self, _query: IncidentQuery) -> Result<Vec<Incident>> {
+            Ok(Vec::new())
+        }
+
+        async fn write_redetected_anomalies(
+            &self,
+            _job_id: Uuid,
+            _anomalies: &[AnomalyEvent],
+        ) -> Result<()> {
+            Ok(())
+        }
+
+        async fn health_check(&self) -> Result<()> {
+            Ok(())
+        }
+
+        fn upserts_by_id(&self) -> bool {
+            false
+        }
+    }
+
+    fn create_event() -> TelemetryEvent {
+        event().build()
+    }
+
+    #[tokio::test]
+    async fn test_redelivered_writes_skipped() {
+        let inner = Arc::new(AppendOnlyStorage::default());
+        let storage = IdempotentStorage::wrap(inner.clone(), &WriteDedupConfig::default());
+        assert!(storage.upserts_by_id());
+
+        let first = create_event();
+        let second = create_event();
+        storage
+            .write_telemetry_batch(&[first.clone(), first.clone(), second.clone()])
+            .await
+            .unwrap();
+        // Redelivery after a crash
+        storage.write_telemetry(&first).await.unwrap();
+        storage.write_telemetry(&second).await.unwrap();
+
+        assert_eq!(
+            *inner.telemetry.lock().unwrap(),
+            vec![first.event_id, second.event_id]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_failed_write_retried() {
+        let inner = Arc::new(AppendOnlyStorage::default());
+        let storage = IdempotentStorage::new(inner.clone(), &WriteDedupConfig::default());
+
+        let event = create_event();
+        inner.fail.store(true, std::sync::atomic::Ordering::SeqCst);
+        assert!(storage.write_telemetry(&event).await.is_err());
+        inner.fail.store(false, std::sync::atomic::Ordering::SeqCst);
+        storage.write_telemetry(&event).await.unwrap();
+
+        assert_eq!(*inner.telemetry.lock().unwrap(), vec![event.event_id]);
+    }
+
+    #[tokio::test]
+    async fn test_upserting_backend_not_wrapped() {
+        let inner: Arc<dyn Storage> = Arc::new(crate::memory::InMemoryStorage::new());
+        let storage = IdempotentStorage::wrap(inner.clone(), &WriteDedupConfig::default());
+        assert!(Arc::ptr_eq(&storage, &inner));
+    }
+}
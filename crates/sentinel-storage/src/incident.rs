@@ -0,0 +1,496 @@
+//! Incidents against storage.
+//!
+//! An [`IncidentTracker`] attaches each anomaly to the active incident of its
+//! group, opening one when the group has none, and writes the updated
+//! incident. Every write stores the whole incident; backends return the
+//! latest version of each. [`transition_incident`] and [`link_incident`]
+//! apply responders' changes the same way, so the tracker always continues
+//! from what responders last did; while a tracker is running, its
+//! [`IncidentTracker::transition`] and [`IncidentTracker::link`] apply them
+//! under the same lock as attaches, so neither overwrites the other.
+
+use crate::{query::TimeRange, Storage};
+use chrono::{DateTime, TimeZone, Utc};
+use llm_sentinel_core::{
+    clock::Clock,
+    config::{IncidentConfig, IncidentGroupField},
+    events::AnomalyEvent,
+    incident::{group_key, Incident, IncidentState, IncidentUpdate},
+    types::ServiceId,
+    Error, Result,
+};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use tokio::sync::Mutex;
+use tracing::info;
+use uuid::Uuid;
+
+/// Query for incidents
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct IncidentQuery {
+    /// Time range (by last update)
+    pub time_range: TimeRange,
+    /// Filter by state
+    #[serde(default)]
+    pub state: Option<IncidentState>,
+    /// Filter by service
+    #[serde(default)]
+    pub service: Option<ServiceId>,
+    /// Filter by incident
+    #[serde(default)]
+    pub incident_id: Option<Uuid>,
+    /// Maximum number of incidents
+    #[serde(default)]
+    pub limit: Option<usize>,
+}
+
+impl IncidentQuery {
+    /// Create a new incident query
+    pub fn new(time_range: TimeRange) -> Self {
+        Self {
+            time_range,
+            state: None,
+            service: None,
+            incident_id: None,
+            limit: None,
+        }
+    }
+
+    /// Filter by state
+    pub fn with_state(mut self, state: IncidentState) -> Self {
+        self.state = Some(state);
+        self
+    }
+
+    /// Filter by service
+    pub fn with_service(mut self, service: ServiceId) -> Self {
+        self.service = Some(service);
+        self
+    }
+
+    /// Filter by incident
+    pub fn with_incident_id(mut self, incident_id: Uuid) -> Self {
+        self.incident_id = Some(incident_id);
+        self
+    }
+
+    /// Limit the number of incidents
+    pub fn with_limit(mut self, limit: usize) -> Self {
+        self.limit = Some(limit);
+        self
+    }
+
+    /// Whether the latest version of an incident matches
+    pub fn matches(&self, incident: &Incident) -> bool {
+        incident.updated_at >= self.time_range.start
+            && incident.updated_at < self.time_range.end
+            && self.state.map_or(true, |state| incident.state == state)
+            && self
+                .service
+                .as_ref()
+                .map_or(true, |service| incident.services.contains(service))
+            && self
+                .incident_id
+                .map_or(true, |id| incident.incident_id == id)
+    }
+
+    /// Keep the matching incidents, most recently updated first, up to the
+    /// limit
+    pub fn select(&self, incidents: impl IntoIterator<Item = Incident>) -> Vec<Incident> {
+        let mut incidents: Vec<Incident> = incidents
+            .into_iter()
+            .filter(|incident| self.matches(incident))
+            .collect();
+        incidents.sort_by_key(|incident| std::cmp::Reverse(incident.updated_at));
+        incidents.truncate(self.limit.unwrap_or(usize::MAX));
+        incidents
+    }
+}
+
+/// Time range covering every incident stored up to `now`
+fn all_time(now: DateTime<Utc>) -> TimeRange {
+    TimeRange::new(
+        Utc.timestamp_opt(0, 0).unwrap(),
+        now + chrono::Duration::days(1),
+    )
+}
+
+/// Look up the latest version of an incident updated in a time range
+async fn find_incident(
+    storage: &dyn Storage,
+    incident_id: Uuid,
+    time_range: TimeRange,
+) -> Result<Option<Incident>> {
+    let query = IncidentQuery::new(time_range).with_incident_id(incident_id);
+    Ok(storage.query_incidents(query).await?.into_iter().next())
+}
+
+/// Look up the latest version of an incident
+pub async fn get_incident(storage: &dyn Storage, incident_id: Uuid) -> Result<Option<Incident>> {
+    find_incident(storage, incident_id, all_time(Utc::now())).await
+}
+
+/// Look up a stored incident, failing with [`Error::NotFound`] when unknown
+async fn load_incident(
+    storage: &dyn Storage,
+    incident_id: Uuid,
+    now: DateTime<Utc>,
+) -> Result<Incident> {
+    find_incident(storage, incident_id, all_time(now))
+        .await?
+        .ok_or_else(|| Error::not_found(format!("Incident {}", incident_id)))
+}
+
+/// Apply a state change at `now` and write the incident
+async fn apply_transition(
+    storage: &dyn Storage,
+    incident_id: Uuid,
+    to: IncidentState,
+    actor: Option<String>,
+    reason: Option<String>,
+    now: DateTime<Utc>,
+) -> Result<Incident> {
+    let mut incident = load_incident(storage, incident_id, now).await?;
+    let from = incident.state;
+    incident.transition(to, actor, reason, now)?;
+    storage.write_incident(&incident).await?;
+
+    info!(incident_id = %incident_id, from = %from, to = %to, "Incident state changed");
+    metrics::counter!(
+        "sentinel_incident_state_changes_total",
+        "to" => to.to_string()
+    )
+    .increment(1);
+
+    Ok(incident)
+}
+
+/// Attach a link at `now` and write the incident
+async fn apply_link(
+    storage: &dyn Storage,
+    incident_id: Uuid,
+    title: String,
+    url: String,
+    added_by: Option<String>,
+    now: DateTime<Utc>,
+) -> Result<Incident> {
+    let mut incident = load_incident(storage, incident_id, now).await?;
+    incident.link(title, url, added_by, now)?;
+    storage.write_incident(&incident).await?;
+    Ok(incident)
+}
+
+/// Move a stored incident to a new state
+///
+/// Fails with [`Error::NotFound`] for unknown incidents and
+/// [`Error::Validation`] for transitions the current state does not allow.
+pub async fn transition_incident(
+    storage: &dyn Storage,
+    incident_id: Uuid,
+    to: IncidentState,
+    actor: Option<String>,
+    reason: Option<String>,
+) -> Result<Incident> {
+    apply_transition(storage, incident_id, to, actor, reason, Utc::now()).await
+}
+
+/// Attach a link to a stored incident
+///
+/// Fails with [`Error::NotFound`] for unknown incidents and
+/// [`Error::Validation`] for a blank title or URL.
+pub async fn link_incident(
+    storage: &dyn Storage,
+    incident_id: Uuid,
+    title: String,
+    url: String,
+    added_by: Option<String>,
+) -> Result<Incident> {
+    apply_link(storage, incident_id, title, url, added_by, Utc::now()).await
+}
+
+/// Attaches anomalies to the incidents of their groups
+///
+/// Keeps the incident each group last attached to, but prefers the stored
+/// version before every attach, so responders' changes are kept; backends
+/// keeping no incidents still group anomalies. Attaches are serialized with
+/// each other, which keeps concurrent anomalies of one group from opening
+/// two incidents, and with the tracker's state changes and links, which
+/// keeps an attach from overwriting a responder's change.
+#[derive(Debug)]
+pub struct IncidentTracker {
+    group_by: Vec<IncidentGroupField>,
+    window: chrono::Duration,
+    clock: Clock,
+    active: Mutex<HashMap<String, Incident>>,
+}
+
+impl IncidentTracker {
+    /// Create a tracker grouping anomalies as configured
+    pub fn new(config: &IncidentConfig) -> Self {
+        Self {
+            group_by: config.group_by.clone(),
+            window: chrono::Duration::seconds(config.group_window_secs as i64),
+            clock: Clock::system(),
+            active: Mutex::new(HashMap::new()),
+        }
+    }
+
+    /// Measure group windows against `clock`
+    pub fn with_clock(mut self, clock: Clock) -> Self {
+        self.clock = clock;
+        self
+    }
+
+    /// Incidents updated within the group window before `now`
+    fn window_range(&self, now: DateTime<Utc>) -> TimeRange {
+        TimeRange::new(now - self.window, now + chrono::Duration::seconds(1))
+    }
+
+    /// Pick up the active incidents updated within the group window, so
+    /// anomalies after a restart attach to them; returns how many
+    pub async fn restore(&self, storage: &dyn Storage) -> Result<usize> {
+        let now = self.clock.now();
+        let query = IncidentQuery::new(self.window_range(now));
+        let incidents = storage.query_incidents(query).await?;
+
+        let mut active = self.active.lock().await;
+        for incident in incidents {
+            let newer = active
+                .get(&incident.group_key)
+                .map_or(true, |a| a.last_anomaly_at < incident.last_anomaly_at);
+            if newer && incident.accepts(now, self.window) {
+                active.insert(incident.group_key.clone(), incident);
+            }
+        }
+        Ok(active.len())
+    }
+
+    /// Attach an anomaly to its group's incident, opening one if the group
+    /// has no incident accepting it, and write the incident
+    pub async fn record(
+        &self,
+        storage: &dyn Storage,
+        anomaly: &AnomalyEvent,
+    ) -> Result<(Incident, IncidentUpdate)> {
+        let key = group_key(anomaly, &self.group_by);
+        let now = self.clock.now();
+        let mut active = self.active.lock().await;
+
+        let current = match active.get(&key).cloned() {
+            Some(cached) if cached.accepts(now, self.window) => {
+                find_incident(storage, cached.incident_id, self.window_range(now))
+                    .await?
+                    .or(Some(cached))
+            }
+            _ => None,
+        };
+        let (incident, update) = match current.filter(|i| i.accepts(now, self.window)) {
+            Some(mut incident) => {
+                let update = incident.attach(anomaly, now);
+                (incident, update)
+            }
+            None => (
+                Incident::open(key.clone(), anomaly, now),
+                IncidentUpdate::Opened,
+            ),
+        };
+        storage.write_incident(&incident).await?;
+        active.insert(key, incident.clone());
+        active.retain(|_, incident| incident.accepts(now, self.window));
+        drop(active);
+
+        if update == IncidentUpdate::Opened {
+            info!(
+                incident_id = %incident.incident_id,
+                group = %incident.group_key,
+                alert_id = %anomaly.alert_id,
+                "Incident opened"
+            );
+            metrics::counter!("sentinel_incidents_opened_total").increment(1);
+        }
+        metrics::counter!(
+            "sentinel_incident_anomalies_total",
+            "update" => update.to_string()
+        )
+        .increment(1);
+
+        Ok((incident, update))
+    }
+
+    /// Move a stored incident to a new state, like [`transition_incident`]
+    /// but serialized with attaches and timed by the tracker's clock
+    pub async fn transition(
+        &self,
+        storage: &dyn Storage,
+        incident_id: Uuid,
+        to: IncidentState,
+        actor: Option<String>,
+        reason: Option<String>,
+    ) -> Result<Incident> {
+        let mut active = self.active.lock().await;
+        let incident =
+            apply_transition(storage, incident_id, to, actor, reason, self.clock.now()).await?;
+        Self::update_active(&mut active, &incident);
+        Ok(incident)
+    }
+
+    /// Attach a link to a stored incident, like [`link_incident`] but
+    /// serialized with attaches and timed by the tracker's clock
+    pub async fn link(
+        &self,
+        storage: &dyn Storage,
+        incident_id: Uuid,
+        title: String,
+        url: String,
+        added_by: Option<String>,
+    ) -> Result<Incident> {
+        let mut active = self.active.lock().await;
+        let incident =
+            apply_link(storage, incident_id, title, url, added_by, self.clock.now()).await?;
+        Self::update_active(&mut active, &incident);
+        Ok(incident)
+    }
+
+    /// Replace the cached copy of a changed incident, if it is its group's
+    fn update_active(active: &mut HashMap<String, Incident>, incident: &Incident) {
+        if let Some(cached) = active
+            .get_mut(&incident.group_key)
+            .filter(|cached| cached.incident_id == incident.incident_id)
+        {
+            *cached = incident.clone();
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::memory::InMemoryStorage;
+    use llm_sentinel_core::types::Severity;
+    use llm_sentinel_fixtures::anomaly;
+
+    fn create_anomaly(service: &str, severity: Severity) -> AnomalyEvent {
+        anomaly().severity(severity).service(service).build()
+    }
+
+    fn config() -> IncidentConfig {
+        IncidentConfig {
+            group_by: vec![IncidentGroupField::Service],
+            group_window_secs: 600,
+            notify_alerters: Vec::new(),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_tracker_groups_anomalies() {
+        let storage = InMemoryStorage::new();
+        let clock = Clock::manual(Utc::now());
+        let tracker = IncidentTracker::new(&config()).with_clock(clock.clone());
+
+        let (first, update) = tracker
+            .record(&storage, &create_anomaly("checkout", Severity::Medium))
+            .await
+            .unwrap();
+        assert_eq!(update, IncidentUpdate::Opened);
+
+        clock.advance(chrono::Duration::seconds(120));
+        let (incident, update) = tracker
+            .record(&storage, &create_anomaly("checkout", Severity::High))
+            .await
+            .unwrap();
+        assert_eq!(update, IncidentUpdate::Escalated);
+        assert_eq!(incident.incident_id, first.incident_id);
+        assert_eq!(incident.anomaly_count, 2);
+
+        // Another group opens its own incident
+        let (other, _) = tracker
+            .record(&storage, &create_anomaly("search", Severity::Low))
+            .await
+            .unwrap();
+        assert_ne!(other.incident_id, first.incident_id);
+
+        // Responders' links survive later attaches
+        tracker
+            .link(
+                &storage,
+                other.incident_id,
+                "Ticket".to_string(),
+                "https://tickets.example.com/42".to_string(),
+                None,
+            )
+            .await
+            .unwrap();
+        let (linked, _) = tracker
+            .record(&storage, &create_anomaly("search", Severity::Low))
+            .await
+            .unwrap();
+        assert_eq!(linked.links.len(), 1);
+
+        // A resolved incident is closed to new anomalies
+        let resolved = tracker
+            .transition(
+                &storage,
+                first.incident_id,
+                IncidentState::Resolved,
+                Some("alice".to_string()),
+                None,
+            )
+            .await
+            .unwrap();
+        assert_eq!(resolved.resolved_at, Some(clock.now()));
+        let (reopened, update) = tracker
+            .record(&storage, &create_anomaly("checkout", Severity::Low))
+            .await
+            .unwrap();
+        assert_eq!(update, IncidentUpdate::Opened);
+        assert_ne!(reopened.incident_id, first.incident_id);
+
+        // So is one whose group went quiet for longer than the window
+        clock.advance(chrono::Duration::seconds(601));
+        let (later, _) = tracker
+            .record(&storage, &create_anomaly("search", Severity::Low))
+            .await
+            .unwrap();
+        assert_ne!(later.incident_id, other.incident_id);
+
+        let resolved = storage
+            .query_incidents(
+                IncidentQuery::new(all_time(clock.now())).with_state(IncidentState::Resolved),
+            )
+            .await
+            .unwrap();
+        assert_eq!(resolved.len(), 1);
+        assert_eq!(resolved[0].anomaly_count, 2);
+        assert!(matches!(
+            transition_incident(
+                &storage,
+                Uuid::new_v4(),
+                IncidentState::Resolved,
+                None,
+                None
+            )
+            .await,
+            Err(Error::NotFound(_))
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_tracker_restores_active_incidents() {
+        let storage = InMemoryStorage::new();
+        let clock = Clock::manual(Utc::now());
+        let tracker = IncidentTracker::new(&config()).with_clock(clock.clone());
+        let (opened, _) = tracker
+            .record(&storage, &create_anomaly("checkout", Severity::Medium))
+            .await
+            .unwrap();
+
+        let restarted = IncidentTracker::new(&config()).with_clock(clock.clone());
+        assert_eq!(restarted.restore(&storage).await.unwrap(), 1);
+        let (incident, update) = restarted
+            .record(&storage, &create_anomaly("checkout", Severity::Medium))
+            .await
+            .unwrap();
+        assert_eq!(update, IncidentUpdate::AnomalyAttached);
+        assert_eq!(incident.incident_id, opened.incident_id);
+    }
+}
@@ -1,14 +1,45 @@
 //! InfluxDB storage backend for time-series data.
 
-use crate::{query::{AnomalyQuery, TelemetryQuery}, Storage};
+use crate::{
+    annotation::AnnotationQuery,
+    cardinality::CardinalityEstimator,
+    cost::{CostReportQuery, CostReportRow, CostRollup},
+    delivery::DeliveryQuery,
+    incident::IncidentQuery,
+    query::{
+        AnomalyQuery, AnomalySort, Cursor, HeatmapBucket, HeatmapQuery, TelemetryQuery,
+        TelemetrySort,
+    },
+    stream::StreamOffset,
+    Storage,
+};
 use async_trait::async_trait;
-use influxdb2::models::DataPoint;
-use influxdb2::Client;
+use chrono::TimeZone;
+use influxdb2::api::buckets::ListBucketsRequest;
+use influxdb2::models::{data_point::DataPointBuilder, DataPoint, Query};
+use influxdb2::{Client, RequestError};
 use llm_sentinel_core::{
-    events::{AnomalyEvent, TelemetryEvent},
+    annotation::AnomalyAnnotation,
+    config::{Config, InfluxTagConfig, UnlistedTagPolicy},
+    deadline,
+    events::{AlertMetadata, AnomalyEvent, TelemetryEvent},
+    incident::Incident,
+    labels::Labels,
+    lifecycle::AnomalyStateChange,
+    retry::{retry, RetryError, RetryPolicy},
+    secrets::CredentialRotation,
+    selfcheck::{CheckKind, CheckResult, SelfCheck},
     Error, Result,
 };
+use std::{
+    collections::BTreeMap,
+    future::Future,
+    sync::{Arc, RwLock},
+    time::Duration,
+};
+use tokio::time::Instant;
 use tracing::{debug, error, info, warn};
+use uuid::Uuid;
 
 /// InfluxDB configuration
 #[derive(Debug, Clone)]
@@ -25,8 +56,12 @@ pub struct InfluxDbConfig {
     pub token: String,
     /// Batch size for writes
     pub batch_size: usize,
-    /// Connection timeout in seconds
+    /// Timeout for each write attempt in seconds
     pub timeout_secs: u64,
+    /// Retry policy for failed writes
+    pub retry: RetryPolicy,
+    /// Which metadata is written as tags
+    pub tags: InfluxTagConfig,
 }
 
 impl Default for InfluxDbConfig {
@@ -39,14 +74,119 @@ impl Default for InfluxDbConfig {
             token: String::new(),
             batch_size: 100,
             timeout_secs: 10,
+            retry: RetryPolicy::default(),
+            tags: InfluxTagConfig::default(),
+        }
+    }
+}
+
+/// Outcome of writing a batch of points
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct BatchWriteReport {
+    /// Points written
+    pub written: usize,
+    /// Points InfluxDB rejected
+    pub rejected: Vec<RejectedPoint>,
+}
+
+/// Point InfluxDB refused to write
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RejectedPoint {
+    /// Event, alert or rollup the point was built from
+    pub id: String,
+    /// Rejection reason
+    pub error: String,
+}
+
+/// Why a write attempt failed
+#[derive(Debug)]
+enum WriteFailure {
+    /// InfluxDB rejected the points; retrying the same points cannot succeed
+    Rejected(String),
+    /// Transport error or timeout that outlasted the retry policy
+    Failed(Error),
+}
+
+/// Write `points`, bisecting batches InfluxDB rejects so that only the
+/// offending points are dropped
+async fn write_isolating<T, F, Fut>(
+    points: Vec<(String, T)>,
+    mut write: F,
+) -> Result<BatchWriteReport>
+where
+    T: Clone,
+    F: FnMut(Vec<T>) -> Fut,
+    Fut: Future<Output = std::result::Result<(), WriteFailure>>,
+{
+    let mut report = BatchWriteReport::default();
+    let mut pending = vec![points];
+
+    while let Some(mut chunk) = pending.pop() {
+        if chunk.is_empty() {
+            continue;
+        }
+        let batch = chunk.iter().map(|(_, point)| point.clone()).collect();
+        match write(batch).await {
+            Ok(()) => report.written += chunk.len(),
+            Err(WriteFailure::Rejected(error)) if chunk.len() == 1 => {
+                let (id, _) = chunk.remove(0);
+                report.rejected.push(RejectedPoint { id, error });
+            }
+            Err(WriteFailure::Rejected(_)) => {
+                let back = chunk.split_off(chunk.len() / 2);
+                pending.push(back);
+                pending.push(chunk);
+            }
+            Err(WriteFailure::Failed(e)) => {
+                if report.written > 0 {
+                    warn!(
+                        written = report.written,
+                        "InfluxDB batch write failed part way"
+                    );
+                }
+                return Err(e);
+            }
+        }
+    }
+
+    Ok(report)
+}
+
+#[async_trait]
+impl CredentialRotation for InfluxDbStorage {
+    fn component(&self) -> &str {
+        "influxdb"
+    }
+
+    async fn rotate(&self, config: &Config) -> Result<bool> {
+        match &config.storage.influxdb {
+            Some(influxdb) => self.rotate_token(&influxdb.token).await,
+            None => Ok(false),
         }
     }
 }
 
+/// Create a client with the given token and check that InfluxDB answers
+async fn connect(config: &InfluxDbConfig, token: &str) -> Result<Client> {
+    let client = Client::new(&config.url, &config.org, token);
+    if let Err(e) = client.health().await {
+        error!("Failed to connect to InfluxDB: {}", e);
+        return Err(Error::connection(format!(
+            "InfluxDB connection failed: {}",
+            e
+        )));
+    }
+    Ok(client)
+}
+
 /// InfluxDB storage backend
 pub struct InfluxDbStorage {
-    client: Client,
+    /// Replaced when the token is rotated; requests in flight keep the
+    /// client they started with
+    client: RwLock<Arc<Client>>,
+    token: RwLock<String>,
     config: InfluxDbConfig,
+    cardinality: CardinalityEstimator,
 }
 
 impl std::fmt::Debug for InfluxDbStorage {
@@ -65,38 +205,194 @@ impl InfluxDbStorage {
             config.url, config.org
         );
 
-        let client = Client::new(&config.url, &config.org, &config.token);
+        let client = connect(&config, &config.token).await?;
+        info!("Connected to InfluxDB successfully");
 
-        // Test connection
-        if let Err(e) = client.health().await {
-            error!("Failed to connect to InfluxDB: {}", e);
-            return Err(Error::connection(format!(
-                "InfluxDB connection failed: {}",
-                e
+        Ok(Self::with_client(client, config))
+    }
+
+    fn with_client(client: Client, config: InfluxDbConfig) -> Self {
+        let cardinality = CardinalityEstimator::new(config.tags.cardinality_warning);
+        Self {
+            client: RwLock::new(Arc::new(client)),
+            token: RwLock::new(config.token.clone()),
+            config,
+            cardinality,
+        }
+    }
+
+    /// Switch to a new auth token
+    ///
+    /// The new client is checked before it replaces the old one, so a bad
+    /// token leaves the current client in place. Returns whether the token
+    /// changed.
+    pub async fn rotate_token(&self, token: &str) -> Result<bool> {
+        if *self.token.read().unwrap() == token {
+            return Ok(false);
+        }
+
+        let client = connect(&self.config, token).await?;
+        *self.client.write().unwrap() = Arc::new(client);
+        *self.token.write().unwrap() = token.to_string();
+        info!("Rotated InfluxDB token");
+        Ok(true)
+    }
+
+    fn client(&self) -> Arc<Client> {
+        self.client.read().unwrap().clone()
+    }
+
+    /// Estimated distinct values of every written tag
+    pub fn tag_cardinality(&self) -> BTreeMap<String, u64> {
+        self.cardinality.estimates()
+    }
+
+    /// Write points to a bucket with retries, per-attempt timeouts and
+    /// isolation of rejected points
+    ///
+    /// Fails if no point could be written.
+    async fn write_points(
+        &self,
+        bucket: &str,
+        kind: &'static str,
+        points: Vec<(String, DataPoint)>,
+    ) -> Result<BatchWriteReport> {
+        let total = points.len();
+        let report = write_isolating(points, |batch| self.write_with_retry(bucket, batch)).await?;
+
+        metrics::counter!("sentinel_storage_writes_total", "type" => kind)
+            .increment(report.written as u64);
+        if !report.rejected.is_empty() {
+            metrics::counter!("sentinel_storage_rejected_points_total", "type" => kind)
+                .increment(report.rejected.len() as u64);
+            for rejected in &report.rejected {
+                warn!(
+                    kind,
+                    id = %rejected.id,
+                    error = %rejected.error,
+                    "InfluxDB rejected point"
+                );
+            }
+        }
+
+        if report.written == 0 && total > 0 {
+            return Err(Error::storage(format!(
+                "InfluxDB rejected all {} {} points: {}",
+                total, kind, report.rejected[0].error
             )));
         }
+        Ok(report)
+    }
 
-        info!("Connected to InfluxDB successfully");
+    /// Write one batch, retrying transport errors and timeouts
+    async fn write_with_retry(
+        &self,
+        bucket: &str,
+        points: Vec<DataPoint>,
+    ) -> std::result::Result<(), WriteFailure> {
+        let timeout = Duration::from_secs(self.config.timeout_secs);
+        retry(&self.config.retry, |attempt| {
+            let points = points.clone();
+            async move {
+                let client = self.client();
+                let write = client.write(bucket, futures::stream::iter(points));
+                let error = match tokio::time::timeout(timeout, write).await {
+                    Ok(Ok(())) => return Ok(()),
+                    Ok(Err(RequestError::Http { status, text }))
+                        if status.is_client_error() && !matches!(status.as_u16(), 408 | 429) =>
+                    {
+                        return Err(RetryError::Permanent(WriteFailure::Rejected(format!(
+                            "{}: {}",
+                            status, text
+                        ))));
+                    }
+                    Ok(Err(e)) => Error::storage(format!("InfluxDB write failed: {}", e)),
+                    Err(_) => {
+                        Error::timeout(format!("InfluxDB write exceeded {}s", timeout.as_secs()))
+                    }
+                };
+                warn!(attempt, error = %error, "InfluxDB write attempt failed");
+                metrics::counter!("sentinel_storage_write_retries_total").increment(1);
+                Err(RetryError::Transient(WriteFailure::Failed(error)))
+            }
+        })
+        .await
+        .map_err(|failure| failure.error)
+    }
 
-        Ok(Self { client, config })
+    /// Write telemetry events, reporting points InfluxDB rejected
+    pub async fn write_telemetry_report(
+        &self,
+        events: &[TelemetryEvent],
+    ) -> Result<BatchWriteReport> {
+        let points = events
+            .iter()
+            .map(|e| (e.event_id.to_string(), self.telemetry_to_point(e)))
+            .collect();
+        self.write_points(&self.config.telemetry_bucket, "telemetry", points)
+            .await
+    }
+
+    /// Write anomalies, reporting points InfluxDB rejected
+    pub async fn write_anomaly_report(
+        &self,
+        anomalies: &[AnomalyEvent],
+    ) -> Result<BatchWriteReport> {
+        let points = anomalies
+            .iter()
+            .map(|a| (a.alert_id.to_string(), self.anomaly_to_point(a)))
+            .collect();
+        self.write_points(&self.config.anomaly_bucket, "anomaly", points)
+            .await
+    }
+
+    /// Add a tag, recording its value for cardinality estimation
+    fn tag(
+        &self,
+        point: DataPointBuilder,
+        key: impl Into<String>,
+        value: impl Into<String>,
+    ) -> DataPointBuilder {
+        let (key, value) = (key.into(), value.into());
+        self.cardinality.observe(&key, &value);
+        point.tag(key, value)
     }
 
     /// Convert telemetry event to InfluxDB data point
     fn telemetry_to_point(&self, event: &TelemetryEvent) -> DataPoint {
         let mut point = DataPoint::builder("telemetry")
-            .tag("service", event.service_name.as_str())
-            .tag("model", event.model.as_str())
             .field("latency_ms", event.latency_ms)
             .field("prompt_tokens", event.prompt.tokens as i64)
             .field("response_tokens", event.response.tokens as i64)
             .field("total_tokens", event.total_tokens() as i64)
             .field("cost_usd", event.cost_usd)
             .field("has_errors", event.has_errors() as i64)
+            .field("event_id", event.event_id.to_string())
             .timestamp(event.timestamp.timestamp_nanos_opt().unwrap_or(0));
 
-        // Add metadata as tags
+        point = self.tag(point, "service", event.service_name.as_str());
+        point = self.tag(point, "model", event.model.as_str());
+
+        // Only allowlisted metadata becomes tags
+        let tags = &self.config.tags;
         for (key, value) in &event.metadata {
-            point = point.tag(key, value);
+            if tags.allow.contains(key) {
+                point = self.tag(point, key, value);
+            } else if tags.unlisted == UnlistedTagPolicy::Field {
+                point = point.field(format!("meta_{}", key), value.as_str());
+            }
+        }
+
+        if let Some(environment) = &event.environment {
+            point = self.tag(point, "environment", environment.as_str());
+        }
+
+        for (key, value) in &event.labels {
+            point = self.tag(point, label_tag(key), value);
+        }
+
+        for (component, ms) in &event.latency_breakdown {
+            point = point.field(latency_component_field(component), *ms);
         }
 
         point.build().unwrap()
@@ -104,49 +400,505 @@ impl InfluxDbStorage {
 
     /// Convert anomaly event to InfluxDB data point
     fn anomaly_to_point(&self, anomaly: &AnomalyEvent) -> DataPoint {
-        DataPoint::builder("anomaly")
-            .tag("service", anomaly.service_name.as_str())
-            .tag("model", anomaly.model.as_str())
-            .tag("severity", &anomaly.severity.to_string())
-            .tag("type", &anomaly.anomaly_type.to_string())
-            .tag("method", &anomaly.detection_method.to_string())
+        self.anomaly_point("anomaly", anomaly).build().unwrap()
+    }
+
+    /// Convert an anomaly found by a re-detection job to a data point in
+    /// the `redetected_anomaly` measurement, tagged with the job ID
+    fn redetected_to_point(&self, job_id: Uuid, anomaly: &AnomalyEvent) -> DataPoint {
+        self.anomaly_point("redetected_anomaly", anomaly)
+            .tag("job_id", job_id.to_string())
+            .build()
+            .unwrap()
+    }
+
+    fn anomaly_point(&self, measurement: &str, anomaly: &AnomalyEvent) -> DataPointBuilder {
+        let mut point = DataPoint::builder(measurement)
+            .tag("severity", anomaly.severity.to_string())
+            .tag("type", anomaly.anomaly_type.to_string())
+            .tag("method", anomaly.detection_method.to_string())
             .field("confidence", anomaly.confidence)
             .field("metric", anomaly.details.metric.as_str())
             .field("value", anomaly.details.value)
             .field("baseline", anomaly.details.baseline)
             .field("threshold", anomaly.details.threshold)
-            .timestamp(anomaly.timestamp.timestamp_nanos_opt().unwrap_or(0))
+            .field("alert_id", anomaly.alert_id.to_string())
+            .field("signature_hash", anomaly.signature_hash.as_str())
+            .field("severity_level", AnomalySort::Severity.value(anomaly))
+            .field("state", anomaly.state.to_string())
+            .timestamp(anomaly.timestamp.timestamp_nanos_opt().unwrap_or(0));
+
+        point = self.tag(point, "service", anomaly.service_name.as_str());
+        point = self.tag(point, "model", anomaly.model.as_str());
+
+        if let Some(environment) = &anomaly.environment {
+            point = self.tag(point, "environment", environment.as_str());
+        }
+
+        // A field, not a tag: every anomaly has its own
+        if let Some(correlation_id) = &anomaly.correlation_id {
+            point = point.field("correlation_id", correlation_id.as_str());
+        }
+
+        for (key, value) in &anomaly.labels {
+            point = self.tag(point, label_tag(key), value);
+        }
+
+        point
+    }
+
+    /// Convert a cost rollup to an InfluxDB data point.
+    ///
+    /// Points are timestamped with the rollup's latest event so successive
+    /// partial flushes of the same bucket land on distinct points and sum up.
+    fn cost_to_point(&self, rollup: &CostRollup) -> DataPoint {
+        let mut point = DataPoint::builder("cost")
+            .tag("service", rollup.service.as_str())
+            .tag("model", rollup.model.as_str());
+
+        if let Some(ref user) = rollup.user {
+            point = self.tag(point, "user", user);
+        }
+
+        for (key, value) in &rollup.tags {
+            point = self.tag(point, format!("tag_{}", key), value);
+        }
+
+        point
+            .field("cost_usd", rollup.cost_usd)
+            .field("requests", rollup.requests as i64)
+            .field("prompt_tokens", rollup.prompt_tokens as i64)
+            .field("completion_tokens", rollup.completion_tokens as i64)
+            .timestamp(rollup.last_event.timestamp_nanos_opt().unwrap_or(0))
+            .build()
+            .unwrap()
+    }
+
+    /// Convert an alert delivery record to an InfluxDB data point
+    fn delivery_to_point(&self, delivery: &AlertMetadata) -> DataPoint {
+        let mut point = DataPoint::builder("alert_delivery")
+            .tag("alerter", delivery.alerter.as_str())
+            .tag("severity", delivery.severity.to_string())
+            .tag("status", delivery.status.to_string())
+            .field("alert_id", delivery.alert_id.as_str())
+            .field("attempts", delivery.attempts as i64)
+            .field(
+                "anomaly_ns",
+                delivery.anomaly_timestamp.timestamp_nanos_opt().unwrap_or(0),
+            );
+
+        if let Some(latency_ms) = delivery.latency_ms {
+            point = point.field("latency_ms", latency_ms);
+        }
+        if let Some(ref error) = delivery.error {
+            point = point.field("error", error.as_str());
+        }
+        if let Some(ref request) = delivery.request {
+            point = point.field("request", request.as_str());
+        }
+        if let Some(ref response) = delivery.response {
+            point = point.field("response", response.as_str());
+        }
+
+        point
+            .timestamp(delivery.last_attempt.timestamp_nanos_opt().unwrap_or(0))
             .build()
             .unwrap()
     }
+
+    /// Convert a version of an incident to an InfluxDB data point
+    ///
+    /// The whole incident is kept as JSON; points are timestamped with its
+    /// last update, so the latest point per incident is its current version.
+    fn incident_to_point(&self, incident: &Incident) -> Result<DataPoint> {
+        let body = serde_json::to_string(incident)?;
+        Ok(DataPoint::builder("incident")
+            .tag("incident_id", incident.incident_id.to_string())
+            .tag("state", incident.state.to_string())
+            .field("severity", incident.severity.to_string())
+            .field("anomaly_count", incident.anomaly_count as i64)
+            .field("body", body)
+            .timestamp(incident.updated_at.timestamp_nanos_opt().unwrap_or(0))
+            .build()
+            .unwrap())
+    }
+
+    /// Build the Flux query for the latest version of each incident
+    fn incident_flux(&self, query: &IncidentQuery) -> String {
+        let mut flux = format!(
+            r#"from(bucket: "{}")
+              |> range(start: {}, stop: {})
+              |> filter(fn: (r) => r._measurement == "incident" and r._field == "body")"#,
+            self.config.anomaly_bucket,
+            query.time_range.start.to_rfc3339(),
+            query.time_range.end.to_rfc3339()
+        );
+
+        if let Some(incident_id) = query.incident_id {
+            flux.push_str(&format!(
+                r#" |> filter(fn: (r) => r.incident_id == "{}")"#,
+                incident_id
+            ));
+        }
+
+        // State and service are filtered on the latest version only
+        flux.push_str(
+            r#" |> group(columns: ["incident_id"])
+              |> last()
+              |> group()"#,
+        );
+        flux
+    }
+
+    /// Convert an anomaly state change to an InfluxDB data point
+    fn state_change_to_point(&self, change: &AnomalyStateChange) -> DataPoint {
+        let mut point = DataPoint::builder("anomaly_state")
+            .tag("state", change.to.to_string())
+            .field("alert_id", change.alert_id.to_string())
+            .field("from", change.from.to_string());
+
+        if let Some(ref actor) = change.actor {
+            point = point.field("actor", actor.as_str());
+        }
+        if let Some(ref reason) = change.reason {
+            point = point.field("reason", reason.as_str());
+        }
+
+        point
+            .timestamp(change.at.timestamp_nanos_opt().unwrap_or(0))
+            .build()
+            .unwrap()
+    }
+
+    /// Build the Flux query for the state changes of an anomaly
+    fn state_change_flux(&self, alert_id: Uuid) -> String {
+        format!(
+            r#"from(bucket: "{}")
+              |> range(start: 0)
+              |> filter(fn: (r) => r._measurement == "anomaly_state")
+              |> pivot(rowKey: ["_time"], columnKey: ["_field"], valueColumn: "_value")
+              |> filter(fn: (r) => r.alert_id == "{}")
+              |> group()
+              |> sort(columns: ["_time"])
+              |> map(fn: (r) => ({{r with at_ns: int(v: r._time)}}))"#,
+            self.config.anomaly_bucket, alert_id
+        )
+    }
+
+    /// Convert an anomaly annotation to an InfluxDB data point
+    fn annotation_to_point(&self, annotation: &AnomalyAnnotation) -> DataPoint {
+        let mut point = DataPoint::builder("anomaly_annotation")
+            .tag("kind", annotation.kind.to_string())
+            .field("annotation_id", annotation.annotation_id.to_string())
+            .field("alert_id", annotation.alert_id.to_string())
+            .field("text", annotation.text.as_str());
+
+        if let Some(ref url) = annotation.url {
+            point = point.field("url", url.as_str());
+        }
+        if let Some(ref author) = annotation.author {
+            point = point.field("author", author.as_str());
+        }
+
+        point
+            .timestamp(annotation.created_at.timestamp_nanos_opt().unwrap_or(0))
+            .build()
+            .unwrap()
+    }
+
+    /// Convert a stream consumer offset to an InfluxDB data point
+    fn stream_offset_to_point(&self, offset: &StreamOffset) -> DataPoint {
+        DataPoint::builder("stream_offset")
+            .tag("consumer", offset.consumer_id.as_str())
+            .field("cursor_value", offset.cursor.value)
+            .field("cursor_id", offset.cursor.id.to_string())
+            .timestamp(offset.acked_at.timestamp_nanos_opt().unwrap_or(0))
+            .build()
+            .unwrap()
+    }
+
+    /// Build the Flux query for a consumer's latest stream offset
+    fn stream_offset_flux(&self, consumer_id: &str) -> String {
+        format!(
+            r#"from(bucket: "{}")
+              |> range(start: 0)
+              |> filter(fn: (r) => r._measurement == "stream_offset")
+              |> filter(fn: (r) => r.consumer == "{}")
+              |> pivot(rowKey: ["_time"], columnKey: ["_field"], valueColumn: "_value")
+              |> group()
+              |> sort(columns: ["_time"], desc: true)
+              |> limit(n: 1)
+              |> map(fn: (r) => ({{r with acked_ns: int(v: r._time)}}))"#,
+            self.config.anomaly_bucket,
+            escape_flux(consumer_id)
+        )
+    }
+
+    /// Build the Flux query for anomaly annotations
+    fn annotation_flux(&self, query: &AnnotationQuery) -> String {
+        let mut flux = format!(
+            r#"from(bucket: "{}")
+              |> range(start: {}, stop: {})
+              |> filter(fn: (r) => r._measurement == "anomaly_annotation")
+              |> pivot(rowKey: ["_time"], columnKey: ["_field"], valueColumn: "_value")"#,
+            self.config.anomaly_bucket,
+            query.time_range.start.to_rfc3339(),
+            query.time_range.end.to_rfc3339()
+        );
+
+        // The alert ID is a field, so it can only be filtered after pivoting
+        if let Some(alert_id) = query.alert_id {
+            flux.push_str(&format!(
+                r#" |> filter(fn: (r) => r.alert_id == "{}")"#,
+                alert_id
+            ));
+        }
+
+        flux.push_str(
+            r#" |> group()
+              |> sort(columns: ["_time"])
+              |> map(fn: (r) => ({r with created_ns: int(v: r._time)}))"#,
+        );
+        flux
+    }
+
+    /// Build the Flux query for alert delivery records
+    fn delivery_flux(&self, query: &DeliveryQuery) -> String {
+        let mut flux = format!(
+            r#"from(bucket: "{}")
+              |> range(start: {}, stop: {})
+              |> filter(fn: (r) => r._measurement == "alert_delivery")"#,
+            self.config.anomaly_bucket,
+            query.time_range.start.to_rfc3339(),
+            query.time_range.end.to_rfc3339()
+        );
+
+        if let Some(ref alerter) = query.alerter {
+            flux.push_str(&format!(
+                r#" |> filter(fn: (r) => r.alerter == "{}")"#,
+                alerter
+            ));
+        }
+
+        if let Some(ref severity) = query.severity {
+            flux.push_str(&format!(
+                r#" |> filter(fn: (r) => r.severity == "{}")"#,
+                severity
+            ));
+        }
+
+        flux.push_str(
+            r#" |> pivot(rowKey: ["_time"], columnKey: ["_field"], valueColumn: "_value")
+              |> group()
+              |> map(fn: (r) => ({r with attempt_ns: int(v: r._time)}))"#,
+        );
+
+        // The alert ID is a field, so it can only be filtered after pivoting
+        if let Some(ref alert_id) = query.alert_id {
+            flux.push_str(&format!(
+                r#" |> filter(fn: (r) => r.alert_id == "{}")"#,
+                escape_flux(alert_id)
+            ));
+        }
+
+        flux
+    }
+
+    /// Build the Flux query for a cost report
+    fn cost_report_flux(&self, query: &CostReportQuery) -> String {
+        let columns: Vec<String> = query.group_by.iter().map(|d| d.column()).collect();
+        let quoted: Vec<String> = columns.iter().map(|c| format!(r#""{}""#, c)).collect();
+
+        let mut flux = format!(
+            r#"from(bucket: "{}")
+              |> range(start: {}, stop: {})
+              |> filter(fn: (r) => r._measurement == "cost")"#,
+            self.config.telemetry_bucket,
+            query.time_range.start.to_rfc3339(),
+            query.time_range.end.to_rfc3339()
+        );
+
+        if let Some(ref service) = query.service {
+            flux.push_str(&format!(
+                r#" |> filter(fn: (r) => r.service == "{}")"#,
+                service.as_str()
+            ));
+        }
+
+        if let Some(ref model) = query.model {
+            flux.push_str(&format!(
+                r#" |> filter(fn: (r) => r.model == "{}")"#,
+                model.as_str()
+            ));
+        }
+
+        let mut field_group = quoted.clone();
+        field_group.push(r#""_field""#.to_string());
+        flux.push_str(&format!(
+            r#" |> group(columns: [{}])
+              |> map(fn: (r) => ({{r with _value: float(v: r._value)}}))"#,
+            field_group.join(", ")
+        ));
+
+        let row_key = match query.interval_secs {
+            Some(interval) => {
+                flux.push_str(&format!(
+                    r#" |> aggregateWindow(every: {}s, fn: sum, createEmpty: false, timeSrc: "_start")"#,
+                    interval.max(1)
+                ));
+                "_time"
+            }
+            None => {
+                flux.push_str(" |> sum()");
+                "_start"
+            }
+        };
+
+        flux.push_str(&format!(
+            r#" |> group(columns: [{}])
+              |> pivot(rowKey: ["{}"], columnKey: ["_field"], valueColumn: "_value")"#,
+            quoted.join(", "),
+            row_key
+        ));
+
+        if query.interval_secs.is_some() {
+            flux.push_str(r#" |> map(fn: (r) => ({r with bucket_ns: int(v: r._time)}))"#);
+        }
+
+        flux
+    }
+
+    /// Build the Flux query for an anomaly heatmap.
+    ///
+    /// Counts one field per anomaly point so each anomaly is counted once, and
+    /// projects the window start as epoch nanoseconds for easy decoding.
+    fn heatmap_flux(&self, query: &HeatmapQuery) -> String {
+        let tag = query.group_by.tag();
+
+        let mut flux = format!(
+            r#"from(bucket: "{}")
+              |> range(start: {}, stop: {})
+              |> filter(fn: (r) => r._measurement == "anomaly" and r._field == "confidence")"#,
+            self.config.anomaly_bucket,
+            query.time_range.start.to_rfc3339(),
+            query.time_range.end.to_rfc3339()
+        );
+
+        if let Some(ref service) = query.service {
+            flux.push_str(&format!(
+                r#" |> filter(fn: (r) => r.service == "{}")"#,
+                service.as_str()
+            ));
+        }
+
+        if let Some(ref severity) = query.severity {
+            flux.push_str(&format!(
+                r#" |> filter(fn: (r) => r.severity == "{}")"#,
+                severity
+            ));
+        }
+
+        flux.push_str(&format!(
+            r#" |> group(columns: ["{tag}"])
+              |> aggregateWindow(every: {}s, fn: count, createEmpty: false, timeSrc: "_start")
+              |> map(fn: (r) => ({{bucket_ns: int(v: r._time), group: r.{tag}, count: r._value}}))"#,
+            query.interval_secs.max(1)
+        ));
+
+        flux
+    }
+}
+
+/// Tag holding the value of a label
+fn label_tag(key: &str) -> String {
+    format!("label_{}", key)
+}
+
+/// Field holding the latency of a request component
+fn latency_component_field(component: &str) -> String {
+    format!("latency_{}_ms", component)
+}
+
+/// Escape a value for a Flux string literal
+fn escape_flux(value: &str) -> String {
+    value.replace('\\', "\\\\").replace('"', "\\\"")
+}
+
+/// Flux filters requiring every label to match
+fn label_filters(labels: &Labels) -> String {
+    labels
+        .iter()
+        .map(|(key, value)| {
+            format!(
+                r#" |> filter(fn: (r) => r["{}"] == "{}")"#,
+                label_tag(key),
+                escape_flux(value)
+            )
+        })
+        .collect()
+}
+
+/// Flux pivot, ordering and keyset pagination
+///
+/// Pivots fields into columns, sorts by `column` with `id_column` breaking
+/// ties and, with a cursor, keeps only rows strictly after it. `_time` is
+/// compared in microseconds, matching the cursor's timestamp encoding.
+fn page_flux(
+    column: &str,
+    id_column: &str,
+    ascending: bool,
+    cursor: Option<&Cursor>,
+    offset: Option<usize>,
+    limit: Option<usize>,
+) -> String {
+    let mut flux =
+        r#" |> pivot(rowKey: ["_time"], columnKey: ["_field"], valueColumn: "_value")"#.to_string();
+
+    if let Some(cursor) = cursor {
+        let key = match column {
+            "_time" => "float(v: int(v: r._time) / 1000)".to_string(),
+            _ => format!(r#"float(v: r["{}"])"#, column),
+        };
+        let op = if ascending { ">" } else { "<" };
+        flux.push_str(&format!(
+            r#" |> filter(fn: (r) => {key} {op} {value} or ({key} == {value} and r["{id_column}"] {op} "{id}"))"#,
+            value = format!(r#"float(v: "{}")"#, cursor.value),
+            id = cursor.id,
+        ));
+    }
+
+    flux.push_str(&format!(
+        r#" |> group() |> sort(columns: ["{}", "{}"], desc: {})"#,
+        column, id_column, !ascending
+    ));
+
+    if let Some(limit) = limit {
+        flux.push_str(&format!(
+            " |> limit(n: {}, offset: {})",
+            limit,
+            offset.unwrap_or(0)
+        ));
+    }
+
+    flux
 }
 
 #[async_trait]
 impl Storage for InfluxDbStorage {
     async fn write_telemetry(&self, event: &TelemetryEvent) -> Result<()> {
-        let point = self.telemetry_to_point(event);
-
-        self.client
-            .write(&self.config.telemetry_bucket, futures::stream::iter(vec![point]))
-            .await
-            .map_err(|e| Error::storage(format!("Failed to write telemetry: {}", e)))?;
+        self.write_telemetry_report(std::slice::from_ref(event))
+            .await?;
 
         debug!(event_id = %event.event_id, "Wrote telemetry to InfluxDB");
-        metrics::counter!("sentinel_storage_writes_total", "type" => "telemetry").increment(1);
 
         Ok(())
     }
 
     async fn write_anomaly(&self, anomaly: &AnomalyEvent) -> Result<()> {
-        let point = self.anomaly_to_point(anomaly);
-
-        self.client
-            .write(&self.config.anomaly_bucket, futures::stream::iter(vec![point]))
-            .await
-            .map_err(|e| Error::storage(format!("Failed to write anomaly: {}", e)))?;
+        self.write_anomaly_report(std::slice::from_ref(anomaly))
+            .await?;
 
         debug!(alert_id = %anomaly.alert_id, "Wrote anomaly to InfluxDB");
-        metrics::counter!("sentinel_storage_writes_total", "type" => "anomaly").increment(1);
 
         Ok(())
     }
@@ -156,16 +908,9 @@ impl Storage for InfluxDbStorage {
             return Ok(());
         }
 
-        let points: Vec<_> = events.iter().map(|e| self.telemetry_to_point(e)).collect();
+        let report = self.write_telemetry_report(events).await?;
 
-        self.client
-            .write(&self.config.telemetry_bucket, futures::stream::iter(points))
-            .await
-            .map_err(|e| Error::storage(format!("Failed to write telemetry batch: {}", e)))?;
-
-        info!("Wrote {} telemetry events to InfluxDB", events.len());
-        metrics::counter!("sentinel_storage_writes_total", "type" => "telemetry")
-            .increment(events.len() as u64);
+        info!("Wrote {} telemetry events to InfluxDB", report.written);
 
         Ok(())
     }
@@ -175,16 +920,9 @@ impl Storage for InfluxDbStorage {
             return Ok(());
         }
 
-        let points: Vec<_> = anomalies.iter().map(|a| self.anomaly_to_point(a)).collect();
-
-        self.client
-            .write(&self.config.anomaly_bucket, futures::stream::iter(points))
-            .await
-            .map_err(|e| Error::storage(format!("Failed to write anomaly batch: {}", e)))?;
+        let report = self.write_anomaly_report(anomalies).await?;
 
-        info!("Wrote {} anomalies to InfluxDB", anomalies.len());
-        metrics::counter!("sentinel_storage_writes_total", "type" => "anomaly")
-            .increment(anomalies.len() as u64);
+        info!("Wrote {} anomalies to InfluxDB", report.written);
 
         Ok(())
     }
@@ -214,10 +952,30 @@ impl Storage for InfluxDbStorage {
             ));
         }
 
-        if let Some(limit) = query.limit {
-            flux.push_str(&format!(" |> limit(n: {})", limit));
+        if let Some(ref environment) = query.environment {
+            flux.push_str(&format!(
+                r#" |> filter(fn: (r) => r.environment == "{}")"#,
+                escape_flux(environment.as_str())
+            ));
         }
 
+        flux.push_str(&label_filters(&query.labels));
+
+        let column = match query.sort_by {
+            TelemetrySort::Timestamp => "_time",
+            TelemetrySort::Latency => "latency_ms",
+            TelemetrySort::Cost => "cost_usd",
+            TelemetrySort::Tokens => "total_tokens",
+        };
+        flux.push_str(&page_flux(
+            column,
+            "event_id",
+            query.ascending,
+            query.cursor.as_ref(),
+            query.offset,
+            query.limit,
+        ));
+
         debug!("Executing InfluxDB query: {}", flux);
 
         // Execute query
@@ -249,14 +1007,33 @@ impl Storage for InfluxDbStorage {
         if let Some(ref severity) = query.severity {
             flux.push_str(&format!(
                 r#" |> filter(fn: (r) => r.severity == "{}")"#,
-                severity.to_string()
+                severity
             ));
         }
 
-        if let Some(limit) = query.limit {
-            flux.push_str(&format!(" |> limit(n: {})", limit));
+        if let Some(ref environment) = query.environment {
+            flux.push_str(&format!(
+                r#" |> filter(fn: (r) => r.environment == "{}")"#,
+                escape_flux(environment.as_str())
+            ));
         }
 
+        flux.push_str(&label_filters(&query.labels));
+
+        let column = match query.sort_by {
+            AnomalySort::Timestamp => "_time",
+            AnomalySort::Confidence => "confidence",
+            AnomalySort::Severity => "severity_level",
+        };
+        flux.push_str(&page_flux(
+            column,
+            "alert_id",
+            query.ascending,
+            query.cursor.as_ref(),
+            query.offset,
+            query.limit,
+        ));
+
         debug!("Executing InfluxDB query: {}", flux);
 
         warn!("Query anomalies not fully implemented yet - returning empty results");
@@ -264,23 +1041,528 @@ impl Storage for InfluxDbStorage {
         Ok(Vec::new())
     }
 
-    async fn health_check(&self) -> Result<()> {
-        self.client
-            .health()
+    async fn get_anomaly(&self, alert_id: Uuid) -> Result<Option<AnomalyEvent>> {
+        debug!(alert_id = %alert_id, "Looking up anomaly in InfluxDB");
+
+        warn!("Get anomaly not fully implemented yet - returning no result");
+
+        Ok(None)
+    }
+
+    async fn write_anomaly_state_changes(&self, changes: &[AnomalyStateChange]) -> Result<()> {
+        if changes.is_empty() {
+            return Ok(());
+        }
+
+        let points = changes
+            .iter()
+            .map(|c| (c.alert_id.to_string(), self.state_change_to_point(c)))
+            .collect();
+        let report = self
+            .write_points(&self.config.anomaly_bucket, "anomaly_state", points)
+            .await?;
+
+        debug!("Wrote {} anomaly state changes to InfluxDB", report.written);
+
+        Ok(())
+    }
+
+    async fn query_anomaly_state_changes(
+        &self,
+        alert_id: Uuid,
+    ) -> Result<Vec<AnomalyStateChange>> {
+        let flux = self.state_change_flux(alert_id);
+
+        debug!("Executing InfluxDB query: {}", flux);
+
+        let records = self
+            .client()
+            .query_raw(Some(Query::new(flux)))
+            .await
+            .map_err(|e| {
+                Error::storage(format!("Failed to query anomaly state changes: {}", e))
+            })?;
+
+        let changes = records
+            .iter()
+            .filter_map(|record| {
+                let string = |key: &str| record.values.get(key).and_then(|v| v.string());
+
+                Some(AnomalyStateChange {
+                    alert_id,
+                    from: string("from")?.parse().ok()?,
+                    to: string("state")?.parse().ok()?,
+                    at: chrono::Utc.timestamp_nanos(record.values.get("at_ns")?.i64()?),
+                    actor: string("actor"),
+                    reason: string("reason"),
+                })
+            })
+            .collect();
+
+        metrics::counter!("sentinel_storage_queries_total", "type" => "anomaly_state")
+            .increment(1);
+
+        Ok(changes)
+    }
+
+    async fn write_anomaly_annotations(&self, annotations: &[AnomalyAnnotation]) -> Result<()> {
+        if annotations.is_empty() {
+            return Ok(());
+        }
+
+        let points = annotations
+            .iter()
+            .map(|a| (a.annotation_id.to_string(), self.annotation_to_point(a)))
+            .collect();
+        let report = self
+            .write_points(&self.config.anomaly_bucket, "anomaly_annotation", points)
+            .await?;
+
+        debug!("Wrote {} anomaly annotations to InfluxDB", report.written);
+
+        Ok(())
+    }
+
+    async fn query_anomaly_annotations(
+        &self,
+        query: AnnotationQuery,
+    ) -> Result<Vec<AnomalyAnnotation>> {
+        let flux = self.annotation_flux(&query);
+
+        debug!("Executing InfluxDB query: {}", flux);
+
+        let records = self
+            .client()
+            .query_raw(Some(Query::new(flux)))
+            .await
+            .map_err(|e| Error::storage(format!("Failed to query anomaly annotations: {}", e)))?;
+
+        let annotations = records
+            .iter()
+            .filter_map(|record| {
+                let string = |key: &str| record.values.get(key).and_then(|v| v.string());
+
+                Some(AnomalyAnnotation {
+                    annotation_id: string("annotation_id")?.parse().ok()?,
+                    alert_id: string("alert_id")?.parse().ok()?,
+                    kind: string("kind")?.parse().ok()?,
+                    text: string("text")?,
+                    url: string("url"),
+                    author: string("author"),
+                    created_at: chrono::Utc
+                        .timestamp_nanos(record.values.get("created_ns")?.i64()?),
+                })
+            })
+            .collect();
+
+        metrics::counter!("sentinel_storage_queries_total", "type" => "anomaly_annotation")
+            .increment(1);
+
+        Ok(annotations)
+    }
+
+    async fn write_stream_offset(&self, offset: &StreamOffset) -> Result<()> {
+        let points = vec![(
+            offset.consumer_id.clone(),
+            self.stream_offset_to_point(offset),
+        )];
+        self.write_points(&self.config.anomaly_bucket, "stream_offset", points)
+            .await?;
+
+        debug!(consumer_id = %offset.consumer_id, "Wrote stream offset to InfluxDB");
+
+        Ok(())
+    }
+
+    async fn get_stream_offset(&self, consumer_id: &str) -> Result<Option<StreamOffset>> {
+        let flux = self.stream_offset_flux(consumer_id);
+
+        debug!("Executing InfluxDB query: {}", flux);
+
+        let records = self
+            .client()
+            .query_raw(Some(Query::new(flux)))
+            .await
+            .map_err(|e| Error::storage(format!("Failed to query stream offset: {}", e)))?;
+
+        let offset = records.first().and_then(|record| {
+            let value = |key: &str| record.values.get(key);
+            Some(StreamOffset {
+                consumer_id: consumer_id.to_string(),
+                cursor: Cursor::new(
+                    value("cursor_value")?.f64()?,
+                    value("cursor_id")?.string()?.parse().ok()?,
+                ),
+                acked_at: chrono::Utc.timestamp_nanos(value("acked_ns")?.i64()?),
+            })
+        });
+
+        metrics::counter!("sentinel_storage_queries_total", "type" => "stream_offset").increment(1);
+
+        Ok(offset)
+    }
+
+    async fn anomaly_heatmap(&self, query: HeatmapQuery) -> Result<Vec<HeatmapBucket>> {
+        let flux = self.heatmap_flux(&query);
+
+        debug!("Executing InfluxDB query: {}", flux);
+
+        let records = self
+            .client()
+            .query_raw(Some(Query::new(flux)))
+            .await
+            .map_err(|e| Error::storage(format!("Failed to query anomaly heatmap: {}", e)))?;
+
+        let mut buckets: Vec<HeatmapBucket> = records
+            .iter()
+            .filter_map(|record| {
+                let bucket_ns = record.values.get("bucket_ns")?.i64()?;
+                Some(HeatmapBucket {
+                    start: chrono::Utc.timestamp_nanos(bucket_ns),
+                    group: record.values.get("group")?.string()?,
+                    count: record.values.get("count")?.i64()?.max(0) as u64,
+                })
+            })
+            .collect();
+
+        buckets.sort_by(|a, b| a.start.cmp(&b.start).then_with(|| a.group.cmp(&b.group)));
+
+        metrics::counter!("sentinel_storage_queries_total", "type" => "heatmap").increment(1);
+
+        Ok(buckets)
+    }
+
+    async fn write_cost_rollups(&self, rollups: &[CostRollup]) -> Result<()> {
+        if rollups.is_empty() {
+            return Ok(());
+        }
+
+        let points = rollups
+            .iter()
+            .map(|r| {
+                let id = format!("{}/{}@{}", r.service, r.model, r.bucket_start);
+                (id, self.cost_to_point(r))
+            })
+            .collect();
+        let report = self
+            .write_points(&self.config.telemetry_bucket, "cost", points)
+            .await?;
+
+        debug!("Wrote {} cost rollups to InfluxDB", report.written);
+
+        Ok(())
+    }
+
+    async fn query_cost_report(&self, query: CostReportQuery) -> Result<Vec<CostReportRow>> {
+        let flux = self.cost_report_flux(&query);
+
+        debug!("Executing InfluxDB query: {}", flux);
+
+        let records = self
+            .client()
+            .query_raw(Some(Query::new(flux)))
+            .await
+            .map_err(|e| Error::storage(format!("Failed to query cost report: {}", e)))?;
+
+        let mut rows: Vec<CostReportRow> = records
+            .iter()
+            .map(|record| {
+                let number =
+                    |key: &str| record.values.get(key).and_then(|v| v.f64()).unwrap_or(0.0);
+                let group: BTreeMap<String, String> = query
+                    .group_by
+                    .iter()
+                    .map(|dimension| {
+                        let value = record
+                            .values
+                            .get(&dimension.column())
+                            .and_then(|v| v.string())
+                            .unwrap_or_default();
+                        (dimension.to_string(), value)
+                    })
+                    .collect();
+
+                CostReportRow {
+                    bucket_start: record
+                        .values
+                        .get("bucket_ns")
+                        .and_then(|v| v.i64())
+                        .map(|ns| chrono::Utc.timestamp_nanos(ns)),
+                    group,
+                    cost_usd: number("cost_usd"),
+                    requests: number("requests") as u64,
+                    prompt_tokens: number("prompt_tokens") as u64,
+                    completion_tokens: number("completion_tokens") as u64,
+                }
+            })
+            .collect();
+
+        rows.sort_by(|a, b| {
+            a.bucket_start
+                .cmp(&b.bucket_start)
+                .then_with(|| b.cost_usd.total_cmp(&a.cost_usd))
+        });
+
+        metrics::counter!("sentinel_storage_queries_total", "type" => "cost").increment(1);
+
+        Ok(rows)
+    }
+
+    async fn write_alert_deliveries(&self, deliveries: &[AlertMetadata]) -> Result<()> {
+        if deliveries.is_empty() {
+            return Ok(());
+        }
+
+        let points = deliveries
+            .iter()
+            .map(|d| (d.alert_id.clone(), self.delivery_to_point(d)))
+            .collect();
+        let report = self
+            .write_points(&self.config.anomaly_bucket, "alert_delivery", points)
+            .await?;
+
+        debug!("Wrote {} alert deliveries to InfluxDB", report.written);
+
+        Ok(())
+    }
+
+    async fn query_alert_deliveries(&self, query: DeliveryQuery) -> Result<Vec<AlertMetadata>> {
+        let flux = self.delivery_flux(&query);
+
+        debug!("Executing InfluxDB query: {}", flux);
+
+        let records = self
+            .client()
+            .query_raw(Some(Query::new(flux)))
+            .await
+            .map_err(|e| Error::storage(format!("Failed to query alert deliveries: {}", e)))?;
+
+        let mut deliveries: Vec<AlertMetadata> = records
+            .iter()
+            .filter_map(|record| {
+                let string = |key: &str| record.values.get(key).and_then(|v| v.string());
+                let int = |key: &str| record.values.get(key).and_then(|v| v.i64());
+
+                Some(AlertMetadata {
+                    alert_id: string("alert_id")?,
+                    alerter: string("alerter")?,
+                    severity: string("severity")?.parse().ok()?,
+                    anomaly_timestamp: chrono::Utc.timestamp_nanos(int("anomaly_ns")?),
+                    attempts: int("attempts").unwrap_or(1).max(0) as u32,
+                    last_attempt: chrono::Utc.timestamp_nanos(int("attempt_ns")?),
+                    status: string("status")?.parse().ok()?,
+                    latency_ms: record.values.get("latency_ms").and_then(|v| v.f64()),
+                    error: string("error"),
+                    request: string("request"),
+                    response: string("response"),
+                })
+            })
+            .collect();
+
+        deliveries.sort_by_key(|d| d.last_attempt);
+
+        metrics::counter!("sentinel_storage_queries_total", "type" => "alert_delivery")
+            .increment(1);
+
+        Ok(deliveries)
+    }
+
+    async fn write_incident(&self, incident: &Incident) -> Result<()> {
+        let points = vec![(
+            incident.incident_id.to_string(),
+            self.incident_to_point(incident)?,
+        )];
+        self.write_points(&self.config.anomaly_bucket, "incident", points)
+            .await?;
+
+        debug!(incident_id = %incident.incident_id, "Wrote incident to InfluxDB");
+
+        Ok(())
+    }
+
+    async fn query_incidents(&self, query: IncidentQuery) -> Result<Vec<Incident>> {
+        let flux = self.incident_flux(&query);
+
+        debug!("Executing InfluxDB query: {}", flux);
+
+        let records = self
+            .client()
+            .query_raw(Some(Query::new(flux)))
             .await
-            .map_err(|e| Error::connection(format!("InfluxDB health check failed: {}", e)))?;
+            .map_err(|e| Error::storage(format!("Failed to query incidents: {}", e)))?;
+
+        let incidents: Vec<Incident> = records
+            .iter()
+            .filter_map(|record| {
+                let body = record.values.get("_value")?.string()?;
+                serde_json::from_str(&body).ok()
+            })
+            .collect();
+
+        metrics::counter!("sentinel_storage_queries_total", "type" => "incident").increment(1);
+
+        Ok(query.select(incidents))
+    }
+
+    async fn write_redetected_anomalies(
+        &self,
+        job_id: Uuid,
+        anomalies: &[AnomalyEvent],
+    ) -> Result<()> {
+        if anomalies.is_empty() {
+            return Ok(());
+        }
+
+        let points = anomalies
+            .iter()
+            .map(|a| (a.alert_id.to_string(), self.redetected_to_point(job_id, a)))
+            .collect();
+        let report = self
+            .write_points(&self.config.anomaly_bucket, "redetected_anomaly", points)
+            .await?;
+
+        debug!(job_id = %job_id, "Wrote {} redetected anomalies to InfluxDB", report.written);
+
+        Ok(())
+    }
+
+    async fn health_check(&self) -> Result<()> {
+        // Readiness probes must not outlive their request
+        deadline::enforce("InfluxDB health check", async {
+            self.client()
+                .health()
+                .await
+                .map_err(|e| Error::connection(format!("InfluxDB health check failed: {}", e)))
+        })
+        .await?;
 
         Ok(())
     }
+
+    fn upserts_by_id(&self) -> bool {
+        // A redelivered event or anomaly produces a point with the same
+        // measurement, tags and timestamp, which InfluxDB overwrites
+        true
+    }
+}
+
+/// Self-check of InfluxDB: reachable, token accepted for the organization,
+/// and both buckets present
+#[derive(Debug, Clone)]
+pub struct InfluxDbCheck {
+    config: InfluxDbConfig,
+}
+
+impl InfluxDbCheck {
+    /// Check the InfluxDB instance and buckets in `config`
+    pub fn new(config: InfluxDbConfig) -> Self {
+        Self { config }
+    }
+
+    async fn find_bucket(
+        &self,
+        client: &Client,
+        bucket: &str,
+    ) -> std::result::Result<bool, RequestError> {
+        let request = ListBucketsRequest {
+            name: Some(bucket.to_string()),
+            org: Some(self.config.org.clone()),
+            ..Default::default()
+        };
+        let found = client.list_buckets(Some(request)).await?;
+        Ok(found.buckets.iter().any(|b| b.name == bucket))
+    }
+}
+
+#[async_trait]
+impl SelfCheck for InfluxDbCheck {
+    fn dependency(&self) -> String {
+        "influxdb".to_string()
+    }
+
+    async fn run(&self) -> Vec<CheckResult> {
+        let name = "influxdb";
+        let started = Instant::now();
+        let client = Client::new(&self.config.url, &self.config.org, &self.config.token);
+        if let Err(e) = client.health().await {
+            return vec![
+                CheckResult::fail(name, CheckKind::Connectivity, e.to_string()).timed(started),
+                CheckResult::skip(name, CheckKind::Permission),
+                CheckResult::skip(name, CheckKind::Schema),
+            ];
+        }
+        let mut results =
+            vec![
+                CheckResult::pass(name, CheckKind::Connectivity, "health endpoint answered")
+                    .timed(started),
+            ];
+
+        // Listing buckets needs a token valid for the organization; a
+        // bucket-scoped token only lists the buckets it may read
+        let started = Instant::now();
+        let buckets = [&self.config.telemetry_bucket, &self.config.anomaly_bucket];
+        let mut missing = Vec::new();
+        for bucket in buckets {
+            match self.find_bucket(&client, bucket).await {
+                Ok(true) => {}
+                Ok(false) => missing.push(bucket.as_str()),
+                Err(RequestError::Http { status, text })
+                    if matches!(status.as_u16(), 401 | 403) =>
+                {
+                    results.push(
+                        CheckResult::fail(
+                            name,
+                            CheckKind::Permission,
+                            format!("token rejected for org {}: {}", self.config.org, text),
+                        )
+                        .timed(started),
+                    );
+                    results.push(CheckResult::skip(name, CheckKind::Schema));
+                    return results;
+                }
+                Err(e) => {
+                    results.push(
+                        CheckResult::fail(name, CheckKind::Permission, e.to_string())
+                            .timed(started),
+                    );
+                    results.push(CheckResult::skip(name, CheckKind::Schema));
+                    return results;
+                }
+            }
+        }
+        results.push(
+            CheckResult::pass(
+                name,
+                CheckKind::Permission,
+                format!("token accepted for org {}", self.config.org),
+            )
+            .timed(started),
+        );
+        results.push(if missing.is_empty() {
+            CheckResult::pass(
+                name,
+                CheckKind::Schema,
+                format!("buckets {} exist", buckets.map(|b| b.as_str()).join(", ")),
+            )
+        } else {
+            CheckResult::fail(
+                name,
+                CheckKind::Schema,
+                format!(
+                    "buckets {} missing or not readable with this token",
+                    missing.join(", ")
+                ),
+            )
+        });
+        results
+    }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
-    use llm_sentinel_core::{
-        events::{PromptInfo, ResponseInfo},
-        types::{ModelId, ServiceId},
-    };
+    use llm_sentinel_core::types::ModelId;
+    use llm_sentinel_fixtures::event;
 
     fn create_test_config() -> InfluxDbConfig {
         InfluxDbConfig {
@@ -291,27 +1573,13 @@ mod tests {
             token: "test-token".to_string(),
             batch_size: 100,
             timeout_secs: 10,
+            retry: RetryPolicy::default(),
+            tags: InfluxTagConfig::default(),
         }
     }
 
     fn create_test_event() -> TelemetryEvent {
-        TelemetryEvent::new(
-            ServiceId::new("test"),
-            ModelId::new("gpt-4"),
-            PromptInfo {
-                text: "test".to_string(),
-                tokens: 10,
-                embedding: None,
-            },
-            ResponseInfo {
-                text: "response".to_string(),
-                tokens: 20,
-                finish_reason: "stop".to_string(),
-                embedding: None,
-            },
-            100.0,
-            0.01,
-        )
+        event().build()
     }
 
     #[test]
@@ -323,16 +1591,310 @@ mod tests {
 
     #[test]
     fn test_telemetry_to_point() {
+        use influxdb2::models::WriteDataPoint;
+
         let config = create_test_config();
-        let storage = InfluxDbStorage {
-            client: Client::new(&config.url, &config.org, &config.token),
+        let storage = InfluxDbStorage::with_client(
+            Client::new(&config.url, &config.org, &config.token),
             config,
-        };
+        );
 
         let event = create_test_event();
         let point = storage.telemetry_to_point(&event);
 
         // Point is created successfully (actual write would require running InfluxDB)
-        assert!(point.name == "telemetry");
+        let mut line = Vec::new();
+        point.write_data_point_to(&mut line).unwrap();
+        assert!(line.starts_with(b"telemetry,"));
+    }
+
+    #[test]
+    fn test_metadata_tag_allowlist() {
+        use influxdb2::models::WriteDataPoint;
+
+        let write = |tags: InfluxTagConfig| {
+            let storage = InfluxDbStorage::with_client(
+                Client::new("http://localhost:8086", "test", "token"),
+                InfluxDbConfig {
+                    tags,
+                    ..create_test_config()
+                },
+            );
+            let mut event = create_test_event();
+            event
+                .metadata
+                .insert("region".to_string(), "eu".to_string());
+            event
+                .metadata
+                .insert("user_id".to_string(), "u-1".to_string());
+            let mut line = Vec::new();
+            storage
+                .telemetry_to_point(&event)
+                .write_data_point_to(&mut line)
+                .unwrap();
+            (String::from_utf8(line).unwrap(), storage.tag_cardinality())
+        };
+
+        let allow = vec!["region".to_string()];
+        let (line, cardinality) = write(InfluxTagConfig {
+            allow: allow.clone(),
+            ..InfluxTagConfig::default()
+        });
+        assert!(line.contains(",region=eu"));
+        assert!(!line.contains(",user_id="));
+        assert!(line.contains(r#"meta_user_id="u-1""#));
+        assert_eq!(cardinality.get("region"), Some(&1));
+        assert!(!cardinality.contains_key("user_id"));
+
+        let (line, _) = write(InfluxTagConfig {
+            allow,
+            unlisted: UnlistedTagPolicy::Drop,
+            ..InfluxTagConfig::default()
+        });
+        assert!(line.contains(",region=eu"));
+        assert!(!line.contains("user_id"));
+    }
+
+    #[tokio::test]
+    async fn test_write_isolates_rejected_points() {
+        let points: Vec<_> = (0..8).map(|i| (format!("event-{}", i), i)).collect();
+        let mut batches = 0;
+        let report = write_isolating(points, |batch: Vec<i32>| {
+            batches += 1;
+            async move {
+                match batch.iter().find(|&&p| p == 2 || p == 5) {
+                    Some(bad) => Err(WriteFailure::Rejected(format!("bad point {}", bad))),
+                    None => Ok(()),
+                }
+            }
+        })
+        .await
+        .unwrap();
+
+        assert_eq!(report.written, 6);
+        assert_eq!(
+            report.rejected,
+            vec![
+                RejectedPoint {
+                    id: "event-2".to_string(),
+                    error: "bad point 2".to_string(),
+                },
+                RejectedPoint {
+                    id: "event-5".to_string(),
+                    error: "bad point 5".to_string(),
+                },
+            ]
+        );
+        assert!(batches < 16);
+
+        let result = write_isolating(vec![("event-0".to_string(), 0)], |_| async {
+            Err(WriteFailure::Failed(Error::timeout("write")))
+        })
+        .await;
+        assert!(matches!(result, Err(Error::Timeout(_))));
+    }
+
+    #[tokio::test]
+    async fn test_rotate_token() {
+        use wiremock::{
+            matchers::{method, path},
+            Mock, MockServer, ResponseTemplate,
+        };
+
+        let server = MockServer::start().await;
+        Mock::given(method("GET"))
+            .and(path("/health"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({
+                "name": "influxdb",
+                "message": "ready for queries and writes",
+                "status": "pass",
+                "checks": [],
+                "version": "v2.7.1",
+                "commit": "407fa622e9",
+            })))
+            .mount(&server)
+            .await;
+
+        let config = InfluxDbConfig {
+            url: server.uri(),
+            ..create_test_config()
+        };
+        let storage = InfluxDbStorage::new(config).await.unwrap();
+        assert!(!storage.rotate_token("test-token").await.unwrap());
+        assert!(storage.rotate_token("rotated-token").await.unwrap());
+        assert_eq!(*storage.token.read().unwrap(), "rotated-token");
+
+        // A client failing its health check does not replace the current one
+        server.reset().await;
+        assert!(storage.rotate_token("third-token").await.is_err());
+        assert_eq!(*storage.token.read().unwrap(), "rotated-token");
+    }
+
+    #[test]
+    fn test_label_filters() {
+        let labels = Labels::from([
+            ("env".to_string(), "prod".to_string()),
+            ("team".to_string(), r#"say "hi""#.to_string()),
+        ]);
+        assert_eq!(
+            label_filters(&labels),
+            r#" |> filter(fn: (r) => r["label_env"] == "prod") |> filter(fn: (r) => r["label_team"] == "say \"hi\"")"#
+        );
+        assert_eq!(label_filters(&Labels::new()), "");
+    }
+
+    #[test]
+    fn test_page_flux() {
+        let flux = page_flux("latency_ms", "event_id", false, None, None, Some(50));
+        assert!(flux.contains("pivot("));
+        assert!(flux.contains(r#"sort(columns: ["latency_ms", "event_id"], desc: true)"#));
+        assert!(flux.ends_with("limit(n: 50, offset: 0)"));
+        assert!(!flux.contains("filter"));
+
+        let id = uuid::Uuid::new_v4();
+        let flux = page_flux(
+            "_time",
+            "alert_id",
+            true,
+            Some(&Cursor::new(1.5, id)),
+            None,
+            None,
+        );
+        assert!(flux.contains(&format!(
+            r#"float(v: int(v: r._time) / 1000) > float(v: "1.5") or (float(v: int(v: r._time) / 1000) == float(v: "1.5") and r["alert_id"] > "{}")"#,
+            id
+        )));
+        assert!(flux.contains("desc: false"));
+        assert!(!flux.contains("limit"));
+    }
+
+    #[test]
+    fn test_heatmap_flux() {
+        use crate::query::{HeatmapGroupBy, TimeRange};
+        use llm_sentinel_core::types::Severity;
+
+        let config = create_test_config();
+        let storage = InfluxDbStorage::with_client(
+            Client::new(&config.url, &config.org, &config.token),
+            config,
+        );
+
+        let query = HeatmapQuery::new(TimeRange::last_days(7), 86400, HeatmapGroupBy::Service)
+            .with_severity(Severity::High);
+        let flux = storage.heatmap_flux(&query);
+
+        assert!(flux.starts_with(r#"from(bucket: "test-anomalies")"#));
+        assert!(flux.contains(r#"r.severity == "high""#));
+        assert!(flux.contains(r#"group(columns: ["service"])"#));
+        assert!(flux.contains("aggregateWindow(every: 86400s, fn: count"));
+        assert!(flux.contains("group: r.service"));
+    }
+
+    #[test]
+    fn test_cost_report_flux() {
+        use crate::cost::CostDimension;
+        use crate::query::TimeRange;
+
+        let config = create_test_config();
+        let storage = InfluxDbStorage::with_client(
+            Client::new(&config.url, &config.org, &config.token),
+            config,
+        );
+
+        let query = CostReportQuery::new(TimeRange::last_days(30))
+            .group_by(CostDimension::Service)
+            .group_by(CostDimension::Tag("team".to_string()))
+            .with_model(ModelId::new("gpt-4"));
+        let flux = storage.cost_report_flux(&query);
+
+        assert!(flux.contains(r#"r._measurement == "cost""#));
+        assert!(flux.contains(r#"r.model == "gpt-4""#));
+        assert!(flux.contains(r#"group(columns: ["service", "tag_team", "_field"])"#));
+        assert!(flux.contains("|> sum()"));
+        assert!(flux.contains(r#"rowKey: ["_start"]"#));
+
+        let flux = storage.cost_report_flux(&query.with_interval(86400));
+        assert!(flux.contains("aggregateWindow(every: 86400s, fn: sum"));
+        assert!(flux.contains("bucket_ns"));
+    }
+
+    #[test]
+    fn test_delivery_flux() {
+        use crate::query::TimeRange;
+        use llm_sentinel_core::types::Severity;
+
+        let config = create_test_config();
+        let storage = InfluxDbStorage::with_client(
+            Client::new(&config.url, &config.org, &config.token),
+            config,
+        );
+
+        let query = DeliveryQuery::new(TimeRange::last_hours(24))
+            .with_alerter("webhook")
+            .with_severity(Severity::Critical);
+        let flux = storage.delivery_flux(&query);
+
+        assert!(flux.contains(r#"from(bucket: "test-anomalies")"#));
+        assert!(flux.contains(r#"r._measurement == "alert_delivery""#));
+        assert!(flux.contains(r#"r.alerter == "webhook""#));
+        assert!(flux.contains(r#"r.severity == "critical""#));
+        assert!(flux.contains("attempt_ns"));
+
+        let flux = storage
+            .delivery_flux(&DeliveryQuery::new(TimeRange::last_hours(24)).with_alert_id("a1"));
+        assert!(flux.ends_with(r#"|> filter(fn: (r) => r.alert_id == "a1")"#));
+    }
+
+    #[test]
+    fn test_state_change_flux() {
+        let config = create_test_config();
+        let storage = InfluxDbStorage::with_client(
+            Client::new(&config.url, &config.org, &config.token),
+            config,
+        );
+
+        let alert_id = Uuid::new_v4();
+        let flux = storage.state_change_flux(alert_id);
+
+        assert!(flux.contains(r#"from(bucket: "test-anomalies")"#));
+        assert!(flux.contains(r#"r._measurement == "anomaly_state""#));
+        assert!(flux.contains(&format!(r#"r.alert_id == "{}""#, alert_id)));
+        assert!(flux.contains("at_ns"));
+    }
+
+    #[test]
+    fn test_annotation_flux() {
+        use crate::query::TimeRange;
+
+        let config = create_test_config();
+        let storage = InfluxDbStorage::with_client(
+            Client::new(&config.url, &config.org, &config.token),
+            config,
+        );
+
+        let query = AnnotationQuery::new(TimeRange::last_hours(24));
+        let flux = storage.annotation_flux(&query);
+        assert!(flux.contains(r#"r._measurement == "anomaly_annotation""#));
+        assert!(!flux.contains("r.alert_id"));
+        assert!(flux.contains("created_ns"));
+
+        let alert_id = Uuid::new_v4();
+        let flux = storage.annotation_flux(&query.with_alert_id(alert_id));
+        assert!(flux.contains(&format!(r#"r.alert_id == "{}""#, alert_id)));
+    }
+
+    #[test]
+    fn test_stream_offset_flux() {
+        let config = create_test_config();
+        let storage = InfluxDbStorage::with_client(
+            Client::new(&config.url, &config.org, &config.token),
+            config,
+        );
+
+        let flux = storage.stream_offset_flux(r#"billing"sink"#);
+        assert!(flux.contains(r#"from(bucket: "test-anomalies")"#));
+        assert!(flux.contains(r#"r._measurement == "stream_offset""#));
+        assert!(flux.contains(r#"r.consumer == "billing\"sink""#));
+        assert!(flux.contains("limit(n: 1)"));
     }
 }
@@ -0,0 +1,1220 @@
+//! InfluxDB 1.x compatible storage backend.
+//!
+//! [`InfluxDbV1Storage`] writes line protocol to the v1 `/write` API and
+//! reads back with InfluxQL through `/query`, for deployments on InfluxDB
+//! 1.8 or servers implementing its API. Measurements, tags and fields are
+//! the same as with the InfluxDB 2.x backend, in a single database.
+//! As with that backend, telemetry and anomaly event queries are not
+//! implemented yet and return nothing.
+
+use crate::{
+    annotation::AnnotationQuery,
+    cardinality::CardinalityEstimator,
+    cost::{CostReportQuery, CostReportRow, CostRollup},
+    delivery::DeliveryQuery,
+    incident::IncidentQuery,
+    query::{
+        AnomalyQuery, AnomalySort, Cursor, HeatmapBucket, HeatmapQuery, TelemetryQuery, TimeRange,
+    },
+    stream::StreamOffset,
+    Storage,
+};
+use async_trait::async_trait;
+use chrono::{DateTime, TimeZone, Utc};
+use llm_sentinel_core::{
+    annotation::AnomalyAnnotation,
+    config::{InfluxDbV1Config, UnlistedTagPolicy},
+    deadline,
+    events::{AlertMetadata, AnomalyEvent, TelemetryEvent},
+    incident::Incident,
+    lifecycle::AnomalyStateChange,
+    retry::{retry, RetryError, RetryPolicy},
+    Error, Result,
+};
+use reqwest::{Client, RequestBuilder, StatusCode};
+use serde::Deserialize;
+use std::{collections::BTreeMap, time::Duration};
+use tracing::{debug, info, warn};
+use uuid::Uuid;
+
+/// Field value in line protocol
+#[derive(Debug, Clone, PartialEq)]
+enum FieldValue {
+    Float(f64),
+    Integer(i64),
+    String(String),
+}
+
+impl From<f64> for FieldValue {
+    fn from(value: f64) -> Self {
+        Self::Float(value)
+    }
+}
+
+impl From<i64> for FieldValue {
+    fn from(value: i64) -> Self {
+        Self::Integer(value)
+    }
+}
+
+impl From<&str> for FieldValue {
+    fn from(value: &str) -> Self {
+        Self::String(value.to_string())
+    }
+}
+
+impl From<String> for FieldValue {
+    fn from(value: String) -> Self {
+        Self::String(value)
+    }
+}
+
+/// One line protocol point
+#[derive(Debug, Clone, PartialEq)]
+struct Point {
+    measurement: &'static str,
+    tags: BTreeMap<String, String>,
+    fields: Vec<(String, FieldValue)>,
+    timestamp: i64,
+}
+
+impl Point {
+    fn new(measurement: &'static str, timestamp: DateTime<Utc>) -> Self {
+        Self {
+            measurement,
+            tags: BTreeMap::new(),
+            fields: Vec::new(),
+            timestamp: timestamp.timestamp_nanos_opt().unwrap_or(0),
+        }
+    }
+
+    /// Add a tag; empty values are left out, as line protocol cannot
+    /// express them
+    fn tag(mut self, key: impl Into<String>, value: impl Into<String>) -> Self {
+        let value = value.into();
+        if !value.is_empty() {
+            self.tags.insert(key.into(), value);
+        }
+        self
+    }
+
+    /// Add a field; non-finite floats are left out, as line protocol cannot
+    /// express them
+    fn field(mut self, key: impl Into<String>, value: impl Into<FieldValue>) -> Self {
+        let value = value.into();
+        if !matches!(value, FieldValue::Float(f) if !f.is_finite()) {
+            self.fields.push((key.into(), value));
+        }
+        self
+    }
+
+    /// Render as a line of line protocol
+    fn line(&self) -> String {
+        let mut line = escape_lp(self.measurement, &[',', ' ']);
+        for (key, value) in &self.tags {
+            line.push_str(&format!(
+                ",{}={}",
+                escape_lp(key, &[',', '=', ' ']),
+                escape_lp(value, &[',', '=', ' '])
+            ));
+        }
+        let fields: Vec<String> = self
+            .fields
+            .iter()
+            .map(|(key, value)| {
+                let value = match value {
+                    FieldValue::Float(f) => f.to_string(),
+                    FieldValue::Integer(i) => format!("{}i", i),
+                    FieldValue::String(s) => {
+                        format!("\"{}\"", s.replace('\\', "\\\\").replace('"', "\\\""))
+                    }
+                };
+                format!("{}={}", escape_lp(key, &[',', '=', ' ']), value)
+            })
+            .collect();
+        line.push(' ');
+        line.push_str(&fields.join(","));
+        line.push(' ');
+        line.push_str(&self.timestamp.to_string());
+        line
+    }
+}
+
+/// Escape line protocol special characters; newlines, which cannot be
+/// escaped, become spaces
+fn escape_lp(value: &str, special: &[char]) -> String {
+    let mut out = String::with_capacity(value.len());
+    for c in value.chars() {
+        let c = if c == '\n' || c == '\r' { ' ' } else { c };
+        if special.contains(&c) {
+            out.push('\\');
+        }
+        out.push(c);
+    }
+    out
+}
+
+/// Quote an InfluxQL identifier
+fn quote_ident(value: &str) -> String {
+    format!("\"{}\"", value.replace('\\', "\\\\").replace('"', "\\\""))
+}
+
+/// Quote an InfluxQL string literal
+fn quote_str(value: &str) -> String {
+    format!("'{}'", value.replace('\\', "\\\\").replace('\'', "\\'"))
+}
+
+/// InfluxQL condition selecting a time range (end exclusive)
+fn time_condition(range: &TimeRange) -> String {
+    format!(
+        "time >= {} AND time < {}",
+        range.start.timestamp_nanos_opt().unwrap_or(0),
+        range.end.timestamp_nanos_opt().unwrap_or(i64::MAX)
+    )
+}
+
+/// Tag holding the value of a label
+fn label_tag(key: &str) -> String {
+    format!("label_{}", key)
+}
+
+/// Field holding the latency of a request component
+fn latency_component_field(component: &str) -> String {
+    format!("latency_{}_ms", component)
+}
+
+/// `/query` response
+#[derive(Debug, Deserialize)]
+struct QueryResponse {
+    #[serde(default)]
+    results: Vec<StatementResult>,
+    #[serde(default)]
+    error: Option<String>,
+}
+
+/// Result of one InfluxQL statement
+#[derive(Debug, Deserialize)]
+struct StatementResult {
+    #[serde(default)]
+    series: Vec<Series>,
+    #[serde(default)]
+    error: Option<String>,
+}
+
+/// Series of a statement result, one per `GROUP BY` tag set
+#[derive(Debug, Deserialize)]
+struct Series {
+    #[serde(default)]
+    tags: BTreeMap<String, String>,
+    #[serde(default)]
+    columns: Vec<String>,
+    #[serde(default)]
+    values: Vec<Vec<serde_json::Value>>,
+}
+
+impl Series {
+    /// Rows as column name to value, including the series tags
+    fn rows(self) -> impl Iterator<Item = Row> {
+        let Series {
+            tags,
+            columns,
+            values,
+        } = self;
+        values.into_iter().map(move |values| {
+            let mut row: BTreeMap<String, serde_json::Value> = tags
+                .iter()
+                .map(|(key, value)| (key.clone(), serde_json::Value::from(value.as_str())))
+                .collect();
+            row.extend(columns.iter().cloned().zip(values));
+            Row(row)
+        })
+    }
+}
+
+/// One result row
+#[derive(Debug)]
+struct Row(BTreeMap<String, serde_json::Value>);
+
+impl Row {
+    fn string(&self, key: &str) -> Option<String> {
+        self.0.get(key)?.as_str().map(str::to_string)
+    }
+
+    fn f64(&self, key: &str) -> Option<f64> {
+        self.0.get(key)?.as_f64()
+    }
+
+    fn i64(&self, key: &str) -> Option<i64> {
+        let value = self.0.get(key)?;
+        value.as_i64().or_else(|| value.as_f64().map(|f| f as i64))
+    }
+
+    /// Row time, queried as epoch nanoseconds
+    fn time(&self) -> Option<DateTime<Utc>> {
+        Some(Utc.timestamp_nanos(self.i64("time")?))
+    }
+}
+
+/// Storage backend for InfluxDB 1.x compatible servers
+pub struct InfluxDbV1Storage {
+    client: Client,
+    config: InfluxDbV1Config,
+    retry: RetryPolicy,
+    cardinality: CardinalityEstimator,
+}
+
+impl std::fmt::Debug for InfluxDbV1Storage {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("InfluxDbV1Storage")
+            .field("url", &self.config.url)
+            .field("database", &self.config.database)
+            .field("retention_policy", &self.config.retention_policy)
+            .finish()
+    }
+}
+
+impl InfluxDbV1Storage {
+    /// Create a backend and check that the server answers
+    pub async fn new(config: InfluxDbV1Config) -> Result<Self> {
+        info!(
+            "Connecting to InfluxDB 1.x at {} (database: {})",
+            config.url, config.database
+        );
+
+        let client = Client::builder()
+            .timeout(Duration::from_secs(config.timeout_secs))
+            .build()
+            .map_err(|e| Error::config(format!("Failed to build HTTP client: {}", e)))?;
+
+        let storage = Self {
+            client,
+            retry: RetryPolicy::new(config.write_attempts)
+                .with_initial_delay(Duration::from_millis(config.retry_delay_ms)),
+            cardinality: CardinalityEstimator::new(config.tags.cardinality_warning),
+            config,
+        };
+
+        storage
+            .health_check()
+            .await
+            .map_err(|e| Error::connection(format!("InfluxDB 1.x unreachable: {}", e)))?;
+        info!("Connected to InfluxDB 1.x successfully");
+
+        Ok(storage)
+    }
+
+    /// Estimated distinct values of every written tag
+    pub fn tag_cardinality(&self) -> BTreeMap<String, u64> {
+        self.cardinality.estimates()
+    }
+
+    fn url(&self, path: &str) -> String {
+        format!("{}/{}", self.config.url.trim_end_matches('/'), path)
+    }
+
+    /// Add basic authentication when a username is configured
+    fn authenticated(&self, request: RequestBuilder) -> RequestBuilder {
+        match &self.config.username {
+            Some(username) => request.basic_auth(username, self.config.password.as_ref()),
+            None => request,
+        }
+    }
+
+    /// Add a tag, recording its value for cardinality estimation
+    fn tag(&self, point: Point, key: impl Into<String>, value: impl Into<String>) -> Point {
+        let (key, value) = (key.into(), value.into());
+        self.cardinality.observe(&key, &value);
+        point.tag(key, value)
+    }
+
+    fn telemetry_to_point(&self, event: &TelemetryEvent) -> Point {
+        let mut point = Point::new("telemetry", event.timestamp)
+            .field("latency_ms", event.latency_ms)
+            .field("prompt_tokens", event.prompt.tokens as i64)
+            .field("response_tokens", event.response.tokens as i64)
+            .field("total_tokens", event.total_tokens() as i64)
+            .field("cost_usd", event.cost_usd)
+            .field("has_errors", event.has_errors() as i64)
+            .field("event_id", event.event_id.to_string());
+
+        point = self.tag(point, "service", event.service_name.as_str());
+        point = self.tag(point, "model", event.model.as_str());
+
+        // Only allowlisted metadata becomes tags
+        let tags = &self.config.tags;
+        for (key, value) in &event.metadata {
+            if tags.allow.contains(key) {
+                point = self.tag(point, key, value);
+            } else if tags.unlisted == UnlistedTagPolicy::Field {
+                point = point.field(format!("meta_{}", key), value.as_str());
+            }
+        }
+
+        if let Some(environment) = &event.environment {
+            point = self.tag(point, "environment", environment.as_str());
+        }
+
+        for (key, value) in &event.labels {
+            point = self.tag(point, label_tag(key), value);
+        }
+
+        for (component, ms) in &event.latency_breakdown {
+            point = point.field(latency_component_field(component), *ms);
+        }
+
+        point
+    }
+
+    fn anomaly_to_point(&self, measurement: &'static str, anomaly: &AnomalyEvent) -> Point {
+        let mut point = Point::new(measurement, anomaly.timestamp)
+            .tag("severity", anomaly.severity.to_string())
+            .tag("type", anomaly.anomaly_type.to_string())
+            .tag("method", anomaly.detection_method.to_string())
+            .field("confidence", anomaly.confidence)
+            .field("metric", anomaly.details.metric.as_str())
+            .field("value", anomaly.details.value)
+            .field("baseline", anomaly.details.baseline)
+            .field("threshold", anomaly.details.threshold)
+            .field("alert_id", anomaly.alert_id.to_string())
+            .field("signature_hash", anomaly.signature_hash.as_str())
+            .field("severity_level", AnomalySort::Severity.value(anomaly))
+            .field("state", anomaly.state.to_string());
+
+        point = self.tag(point, "service", anomaly.service_name.as_str());
+        point = self.tag(point, "model", anomaly.model.as_str());
+
+        if let Some(environment) = &anomaly.environment {
+            point = self.tag(point, "environment", environment.as_str());
+        }
+
+        // A field, not a tag: every anomaly has its own
+        if let Some(correlation_id) = &anomaly.correlation_id {
+            point = point.field("correlation_id", correlation_id.as_str());
+        }
+
+        for (key, value) in &anomaly.labels {
+            point = self.tag(point, label_tag(key), value);
+        }
+
+        point
+    }
+
+    /// Cost rollups are timestamped with their latest event so successive
+    /// partial flushes of the same bucket land on distinct points and sum up
+    fn cost_to_point(&self, rollup: &CostRollup) -> Point {
+        let mut point = Point::new("cost", rollup.last_event)
+            .tag("service", rollup.service.as_str())
+            .tag("model", rollup.model.as_str());
+
+        if let Some(ref user) = rollup.user {
+            point = self.tag(point, "user", user);
+        }
+
+        for (key, value) in &rollup.tags {
+            point = self.tag(point, format!("tag_{}", key), value);
+        }
+
+        point
+            .field("cost_usd", rollup.cost_usd)
+            .field("requests", rollup.requests as i64)
+            .field("prompt_tokens", rollup.prompt_tokens as i64)
+            .field("completion_tokens", rollup.completion_tokens as i64)
+    }
+
+    fn incident_to_point(&self, incident: &Incident) -> Result<Point> {
+        let body = serde_json::to_string(incident)?;
+        Ok(Point::new("incident", incident.updated_at)
+            .tag("incident_id", incident.incident_id.to_string())
+            .tag("state", incident.state.to_string())
+            .field("severity", incident.severity.to_string())
+            .field("anomaly_count", incident.anomaly_count as i64)
+            .field("body", body))
+    }
+
+    fn delivery_to_point(&self, delivery: &AlertMetadata) -> Point {
+        let mut point = Point::new("alert_delivery", delivery.last_attempt)
+            .tag("alerter", delivery.alerter.as_str())
+            .tag("severity", delivery.severity.to_string())
+            .tag("status", delivery.status.to_string())
+            .field("alert_id", delivery.alert_id.as_str())
+            .field("attempts", delivery.attempts as i64)
+            .field(
+                "anomaly_ns",
+                delivery
+                    .anomaly_timestamp
+                    .timestamp_nanos_opt()
+                    .unwrap_or(0),
+            );
+
+        if let Some(latency_ms) = delivery.latency_ms {
+            point = point.field("latency_ms", latency_ms);
+        }
+        if let Some(ref error) = delivery.error {
+            point = point.field("error", error.as_str());
+        }
+        if let Some(ref request) = delivery.request {
+            point = point.field("request", request.as_str());
+        }
+        if let Some(ref response) = delivery.response {
+            point = point.field("response", response.as_str());
+        }
+
+        point
+    }
+
+    fn state_change_to_point(&self, change: &AnomalyStateChange) -> Point {
+        let mut point = Point::new("anomaly_state", change.at)
+            .tag("state", change.to.to_string())
+            .field("alert_id", change.alert_id.to_string())
+            .field("from", change.from.to_string());
+
+        if let Some(ref actor) = change.actor {
+            point = point.field("actor", actor.as_str());
+        }
+        if let Some(ref reason) = change.reason {
+            point = point.field("reason", reason.as_str());
+        }
+
+        point
+    }
+
+    fn annotation_to_point(&self, annotation: &AnomalyAnnotation) -> Point {
+        let mut point = Point::new("anomaly_annotation", annotation.created_at)
+            .tag("kind", annotation.kind.to_string())
+            .field("annotation_id", annotation.annotation_id.to_string())
+            .field("alert_id", annotation.alert_id.to_string())
+            .field("text", annotation.text.as_str());
+
+        if let Some(ref url) = annotation.url {
+            point = point.field("url", url.as_str());
+        }
+        if let Some(ref author) = annotation.author {
+            point = point.field("author", author.as_str());
+        }
+
+        point
+    }
+
+    fn stream_offset_to_point(&self, offset: &StreamOffset) -> Point {
+        Point::new("stream_offset", offset.acked_at)
+            .tag("consumer", offset.consumer_id.as_str())
+            .field("cursor_value", offset.cursor.value)
+            .field("cursor_id", offset.cursor.id.to_string())
+    }
+
+    /// Write points, retrying transport errors and server errors
+    ///
+    /// InfluxDB writes the valid points of a batch and reports the rest as a
+    /// partial write, so rejected points are counted and dropped without
+    /// failing the batch.
+    async fn write_points(&self, kind: &'static str, points: Vec<Point>) -> Result<()> {
+        if points.is_empty() {
+            return Ok(());
+        }
+        let total = points.len();
+        let body = points
+            .iter()
+            .map(Point::line)
+            .collect::<Vec<_>>()
+            .join("\n");
+
+        let mut params = vec![
+            ("db", self.config.database.clone()),
+            ("precision", "ns".to_string()),
+        ];
+        if let Some(ref rp) = self.config.retention_policy {
+            params.push(("rp", rp.clone()));
+        }
+
+        retry(&self.retry, |attempt| {
+            let request = self
+                .authenticated(self.client.post(self.url("write")))
+                .query(&params)
+                .body(body.clone());
+
+            async move {
+                let response = match request.send().await {
+                    Ok(response) => response,
+                    Err(e) => {
+                        warn!(attempt, error = %e, "InfluxDB 1.x write request failed");
+                        return Err(RetryError::Transient(e.to_string()));
+                    }
+                };
+
+                let status = response.status();
+                if status.is_success() {
+                    return Ok(());
+                }
+
+                let body = response.text().await.unwrap_or_default();
+                if status == StatusCode::BAD_REQUEST && body.contains("partial write") {
+                    warn!(kind, error = %body, "InfluxDB 1.x rejected points");
+                    metrics::counter!("sentinel_storage_rejected_points_total", "type" => kind)
+                        .increment(1);
+                    return Ok(());
+                }
+                let message = format!("status {}: {}", status, body);
+                if status.is_client_error() && !matches!(status.as_u16(), 408 | 429) {
+                    return Err(RetryError::Permanent(message));
+                }
+                warn!(attempt, status = %status, "InfluxDB 1.x write failed with retryable status");
+                Err(RetryError::Transient(message))
+            }
+        })
+        .await
+        .map_err(|failure| {
+            Error::storage(format!(
+                "InfluxDB 1.x write failed after {} attempts: {}",
+                failure.attempts, failure.error
+            ))
+        })?;
+
+        metrics::counter!("sentinel_storage_writes_total", "type" => kind).increment(total as u64);
+        Ok(())
+    }
+
+    /// Run an InfluxQL query, returning the rows of its first statement
+    async fn influxql(&self, kind: &'static str, query: &str) -> Result<Vec<Row>> {
+        debug!("Executing InfluxQL query: {}", query);
+
+        let mut request = self
+            .authenticated(self.client.get(self.url("query")))
+            .query(&[
+                ("db", self.config.database.as_str()),
+                ("q", query),
+                ("epoch", "ns"),
+            ]);
+        if let Some(remaining) = deadline::remaining() {
+            request = request.timeout(remaining);
+        }
+        let deadline_error = || Error::timeout("InfluxQL query exceeded the request deadline");
+        let response = request.send().await.map_err(|e| match e.is_timeout() {
+            true => deadline_error(),
+            false => Error::storage(format!("InfluxQL request failed: {}", e)),
+        })?;
+
+        let status = response.status();
+        let body: QueryResponse = response.json().await.map_err(|e| match e.is_timeout() {
+            true => deadline_error(),
+            false => Error::storage(format!("Invalid InfluxQL response ({}): {}", status, e)),
+        })?;
+
+        let failed =
+            |error: String| Error::storage(format!("InfluxQL {} query failed: {}", kind, error));
+        if let Some(error) = body.error {
+            return Err(failed(error));
+        }
+        let Some(result) = body.results.into_iter().next() else {
+            return Ok(Vec::new());
+        };
+        if let Some(error) = result.error {
+            return Err(failed(error));
+        }
+
+        metrics::counter!("sentinel_storage_queries_total", "type" => kind).increment(1);
+        Ok(result.series.into_iter().flat_map(Series::rows).collect())
+    }
+
+    fn state_change_influxql(&self, alert_id: Uuid) -> String {
+        format!(
+            r#"SELECT * FROM "anomaly_state" WHERE "alert_id" = {} ORDER BY time ASC"#,
+            quote_str(&alert_id.to_string())
+        )
+    }
+
+    fn annotation_influxql(&self, query: &AnnotationQuery) -> String {
+        let mut influxql = format!(
+            r#"SELECT * FROM "anomaly_annotation" WHERE {}"#,
+            time_condition(&query.time_range)
+        );
+        if let Some(alert_id) = query.alert_id {
+            influxql.push_str(&format!(
+                r#" AND "alert_id" = {}"#,
+                quote_str(&alert_id.to_string())
+            ));
+        }
+        influxql.push_str(" ORDER BY time ASC");
+        influxql
+    }
+
+    fn delivery_influxql(&self, query: &DeliveryQuery) -> String {
+        let mut influxql = format!(
+            r#"SELECT * FROM "alert_delivery" WHERE {}"#,
+            time_condition(&query.time_range)
+        );
+        if let Some(ref alerter) = query.alerter {
+            influxql.push_str(&format!(r#" AND "alerter" = {}"#, quote_str(alerter)));
+        }
+        if let Some(ref severity) = query.severity {
+            influxql.push_str(&format!(
+                r#" AND "severity" = {}"#,
+                quote_str(&severity.to_string())
+            ));
+        }
+        if let Some(ref alert_id) = query.alert_id {
+            influxql.push_str(&format!(r#" AND "alert_id" = {}"#, quote_str(alert_id)));
+        }
+        influxql
+    }
+
+    fn incident_influxql(&self, query: &IncidentQuery) -> String {
+        // State and service are filtered on the latest version only
+        let mut influxql = format!(
+            r#"SELECT last("body") AS "body" FROM "incident" WHERE {}"#,
+            time_condition(&query.time_range)
+        );
+        if let Some(incident_id) = query.incident_id {
+            influxql.push_str(&format!(
+                r#" AND "incident_id" = {}"#,
+                quote_str(&incident_id.to_string())
+            ));
+        }
+        influxql.push_str(r#" GROUP BY "incident_id""#);
+        influxql
+    }
+
+    fn cost_report_influxql(&self, query: &CostReportQuery) -> String {
+        let mut influxql = format!(
+            r#"SELECT sum("cost_usd") AS "cost_usd", sum("requests") AS "requests", sum("prompt_tokens") AS "prompt_tokens", sum("completion_tokens") AS "completion_tokens" FROM "cost" WHERE {}"#,
+            time_condition(&query.time_range)
+        );
+        if let Some(ref service) = query.service {
+            influxql.push_str(&format!(
+                r#" AND "service" = {}"#,
+                quote_str(service.as_str())
+            ));
+        }
+        if let Some(ref model) = query.model {
+            influxql.push_str(&format!(r#" AND "model" = {}"#, quote_str(model.as_str())));
+        }
+
+        let mut group_by: Vec<String> = query
+            .group_by
+            .iter()
+            .map(|dimension| quote_ident(&dimension.column()))
+            .collect();
+        if let Some(interval) = query.interval_secs {
+            group_by.push(format!("time({}s)", interval.max(1)));
+        }
+        if !group_by.is_empty() {
+            influxql.push_str(&format!(" GROUP BY {} fill(none)", group_by.join(", ")));
+        }
+        influxql
+    }
+
+    fn heatmap_influxql(&self, query: &HeatmapQuery) -> String {
+        let mut influxql = format!(
+            r#"SELECT count("confidence") AS "count" FROM "anomaly" WHERE {}"#,
+            time_condition(&query.time_range)
+        );
+        if let Some(ref service) = query.service {
+            influxql.push_str(&format!(
+                r#" AND "service" = {}"#,
+                quote_str(service.as_str())
+            ));
+        }
+        if let Some(ref severity) = query.severity {
+            influxql.push_str(&format!(
+                r#" AND "severity" = {}"#,
+                quote_str(&severity.to_string())
+            ));
+        }
+        influxql.push_str(&format!(
+            " GROUP BY time({}s), {} fill(none)",
+            query.interval_secs.max(1),
+            quote_ident(query.group_by.tag())
+        ));
+        influxql
+    }
+
+    fn stream_offset_influxql(&self, consumer_id: &str) -> String {
+        format!(
+            r#"SELECT * FROM "stream_offset" WHERE "consumer" = {} ORDER BY time DESC LIMIT 1"#,
+            quote_str(consumer_id)
+        )
+    }
+}
+
+#[async_trait]
+impl Storage for InfluxDbV1Storage {
+    async fn write_telemetry(&self, event: &TelemetryEvent) -> Result<()> {
+        self.write_telemetry_batch(std::slice::from_ref(event))
+            .await
+    }
+
+    async fn write_anomaly(&self, anomaly: &AnomalyEvent) -> Result<()> {
+        self.write_anomaly_batch(std::slice::from_ref(anomaly))
+            .await
+    }
+
+    async fn write_telemetry_batch(&self, events: &[TelemetryEvent]) -> Result<()> {
+        let points = events.iter().map(|e| self.telemetry_to_point(e)).collect();
+        self.write_points("telemetry", points).await?;
+
+        debug!("Wrote {} telemetry events to InfluxDB 1.x", events.len());
+
+        Ok(())
+    }
+
+    async fn write_anomaly_batch(&self, anomalies: &[AnomalyEvent]) -> Result<()> {
+        let points = anomalies
+            .iter()
+            .map(|a| self.anomaly_to_point("anomaly", a))
+            .collect();
+        self.write_points("anomaly", points).await?;
+
+        debug!("Wrote {} anomalies to InfluxDB 1.x", anomalies.len());
+
+        Ok(())
+    }
+
+    async fn query_telemetry(&self, _query: TelemetryQuery) -> Result<Vec<TelemetryEvent>> {
+        warn!("Query telemetry not implemented for InfluxDB 1.x - returning empty results");
+
+        Ok(Vec::new())
+    }
+
+    async fn query_anomalies(&self, _query: AnomalyQuery) -> Result<Vec<AnomalyEvent>> {
+        warn!("Query anomalies not implemented for InfluxDB 1.x - returning empty results");
+
+        Ok(Vec::new())
+    }
+
+    async fn get_anomaly(&self, alert_id: Uuid) -> Result<Option<AnomalyEvent>> {
+        debug!(alert_id = %alert_id, "Looking up anomaly in InfluxDB 1.x");
+
+        warn!("Get anomaly not implemented for InfluxDB 1.x - returning no result");
+
+        Ok(None)
+    }
+
+    async fn write_anomaly_state_changes(&self, changes: &[AnomalyStateChange]) -> Result<()> {
+        let points = changes
+            .iter()
+            .map(|c| self.state_change_to_point(c))
+            .collect();
+        self.write_points("anomaly_state", points).await
+    }
+
+    async fn query_anomaly_state_changes(&self, alert_id: Uuid) -> Result<Vec<AnomalyStateChange>> {
+        let rows = self
+            .influxql("anomaly_state", &self.state_change_influxql(alert_id))
+            .await?;
+
+        Ok(rows
+            .iter()
+            .filter_map(|row| {
+                Some(AnomalyStateChange {
+                    alert_id,
+                    from: row.string("from")?.parse().ok()?,
+                    to: row.string("state")?.parse().ok()?,
+                    at: row.time()?,
+                    actor: row.string("actor"),
+                    reason: row.string("reason"),
+                })
+            })
+            .collect())
+    }
+
+    async fn write_anomaly_annotations(&self, annotations: &[AnomalyAnnotation]) -> Result<()> {
+        let points = annotations
+            .iter()
+            .map(|a| self.annotation_to_point(a))
+            .collect();
+        self.write_points("anomaly_annotation", points).await
+    }
+
+    async fn query_anomaly_annotations(
+        &self,
+        query: AnnotationQuery,
+    ) -> Result<Vec<AnomalyAnnotation>> {
+        let rows = self
+            .influxql("anomaly_annotation", &self.annotation_influxql(&query))
+            .await?;
+
+        Ok(rows
+            .iter()
+            .filter_map(|row| {
+                Some(AnomalyAnnotation {
+                    annotation_id: row.string("annotation_id")?.parse().ok()?,
+                    alert_id: row.string("alert_id")?.parse().ok()?,
+                    kind: row.string("kind")?.parse().ok()?,
+                    text: row.string("text")?,
+                    url: row.string("url"),
+                    author: row.string("author"),
+                    created_at: row.time()?,
+                })
+            })
+            .collect())
+    }
+
+    async fn write_stream_offset(&self, offset: &StreamOffset) -> Result<()> {
+        self.write_points("stream_offset", vec![self.stream_offset_to_point(offset)])
+            .await
+    }
+
+    async fn get_stream_offset(&self, consumer_id: &str) -> Result<Option<StreamOffset>> {
+        let rows = self
+            .influxql("stream_offset", &self.stream_offset_influxql(consumer_id))
+            .await?;
+
+        Ok(rows.first().and_then(|row| {
+            Some(StreamOffset {
+                consumer_id: consumer_id.to_string(),
+                cursor: Cursor::new(
+                    row.f64("cursor_value")?,
+                    row.string("cursor_id")?.parse().ok()?,
+                ),
+                acked_at: row.time()?,
+            })
+        }))
+    }
+
+    async fn anomaly_heatmap(&self, query: HeatmapQuery) -> Result<Vec<HeatmapBucket>> {
+        let rows = self
+            .influxql("heatmap", &self.heatmap_influxql(&query))
+            .await?;
+
+        let tag = query.group_by.tag();
+        let mut buckets: Vec<HeatmapBucket> = rows
+            .iter()
+            .filter_map(|row| {
+                Some(HeatmapBucket {
+                    start: row.time()?,
+                    group: row.string(tag)?,
+                    count: row.i64("count")?.max(0) as u64,
+                })
+            })
+            .collect();
+
+        buckets.sort_by(|a, b| a.start.cmp(&b.start).then_with(|| a.group.cmp(&b.group)));
+        Ok(buckets)
+    }
+
+    async fn write_cost_rollups(&self, rollups: &[CostRollup]) -> Result<()> {
+        let points = rollups.iter().map(|r| self.cost_to_point(r)).collect();
+        self.write_points("cost", points).await
+    }
+
+    async fn query_cost_report(&self, query: CostReportQuery) -> Result<Vec<CostReportRow>> {
+        let rows = self
+            .influxql("cost", &self.cost_report_influxql(&query))
+            .await?;
+
+        let mut report: Vec<CostReportRow> = rows
+            .iter()
+            .map(|row| {
+                let number = |key: &str| row.f64(key).unwrap_or(0.0);
+                let group = query
+                    .group_by
+                    .iter()
+                    .map(|dimension| {
+                        let value = row.string(&dimension.column()).unwrap_or_default();
+                        (dimension.to_string(), value)
+                    })
+                    .collect();
+
+                CostReportRow {
+                    bucket_start: query.interval_secs.and_then(|_| row.time()),
+                    group,
+                    cost_usd: number("cost_usd"),
+                    requests: number("requests") as u64,
+                    prompt_tokens: number("prompt_tokens") as u64,
+                    completion_tokens: number("completion_tokens") as u64,
+                }
+            })
+            .collect();
+
+        report.sort_by(|a, b| {
+            a.bucket_start
+                .cmp(&b.bucket_start)
+                .then_with(|| b.cost_usd.total_cmp(&a.cost_usd))
+        });
+        Ok(report)
+    }
+
+    async fn write_alert_deliveries(&self, deliveries: &[AlertMetadata]) -> Result<()> {
+        let points = deliveries
+            .iter()
+            .map(|d| self.delivery_to_point(d))
+            .collect();
+        self.write_points("alert_delivery", points).await
+    }
+
+    async fn query_alert_deliveries(&self, query: DeliveryQuery) -> Result<Vec<AlertMetadata>> {
+        let rows = self
+            .influxql("alert_delivery", &self.delivery_influxql(&query))
+            .await?;
+
+        let mut deliveries: Vec<AlertMetadata> = rows
+            .iter()
+            .filter_map(|row| {
+                Some(AlertMetadata {
+                    alert_id: row.string("alert_id")?,
+                    alerter: row.string("alerter")?,
+                    severity: row.string("severity")?.parse().ok()?,
+                    anomaly_timestamp: Utc.timestamp_nanos(row.i64("anomaly_ns")?),
+                    attempts: row.i64("attempts").unwrap_or(1).max(0) as u32,
+                    last_attempt: row.time()?,
+                    status: row.string("status")?.parse().ok()?,
+                    latency_ms: row.f64("latency_ms"),
+                    error: row.string("error"),
+                    request: row.string("request"),
+                    response: row.string("response"),
+                })
+            })
+            .collect();
+
+        deliveries.sort_by_key(|d| d.last_attempt);
+        Ok(deliveries)
+    }
+
+    async fn write_incident(&self, incident: &Incident) -> Result<()> {
+        self.write_points("incident", vec![self.incident_to_point(incident)?])
+            .await
+    }
+
+    async fn query_incidents(&self, query: IncidentQuery) -> Result<Vec<Incident>> {
+        let rows = self
+            .influxql("incident", &self.incident_influxql(&query))
+            .await?;
+
+        let incidents: Vec<Incident> = rows
+            .iter()
+            .filter_map(|row| serde_json::from_str(&row.string("body")?).ok())
+            .collect();
+        Ok(query.select(incidents))
+    }
+
+    async fn write_redetected_anomalies(
+        &self,
+        job_id: Uuid,
+        anomalies: &[AnomalyEvent],
+    ) -> Result<()> {
+        let points = anomalies
+            .iter()
+            .map(|a| {
+                self.anomaly_to_point("redetected_anomaly", a)
+                    .tag("job_id", job_id.to_string())
+            })
+            .collect();
+        self.write_points("redetected_anomaly", points).await?;
+
+        debug!(job_id = %job_id, "Wrote {} redetected anomalies to InfluxDB 1.x", anomalies.len());
+
+        Ok(())
+    }
+
+    async fn health_check(&self) -> Result<()> {
+        // Readiness probes must not outlive their request
+        deadline::enforce("InfluxDB 1.x health check", async {
+            let response = self
+                .authenticated(self.client.get(self.url("ping")))
+                .send()
+                .await
+                .map_err(|e| Error::connection(format!("InfluxDB 1.x ping failed: {}", e)))?;
+            if response.status().is_success() {
+                Ok(())
+            } else {
+                Err(Error::connection(format!(
+                    "InfluxDB 1.x ping failed with status {}",
+                    response.status()
+                )))
+            }
+        })
+        .await
+    }
+
+    fn upserts_by_id(&self) -> bool {
+        // InfluxDB overwrites a point with the same series and timestamp,
+        // but compatible servers such as QuestDB append it again unless the
+        // table deduplicates
+        false
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::query::HeatmapGroupBy;
+    use llm_sentinel_core::{
+        config::InfluxTagConfig,
+        types::{ModelId, ServiceId},
+    };
+    use llm_sentinel_fixtures::event;
+    use wiremock::{matchers::*, Mock, MockServer, ResponseTemplate};
+
+    async fn create_storage(server: &MockServer) -> InfluxDbV1Storage {
+        Mock::given(method("GET"))
+            .and(path("/ping"))
+            .respond_with(ResponseTemplate::new(204))
+            .mount(server)
+            .await;
+
+        InfluxDbV1Storage::new(InfluxDbV1Config {
+            url: server.uri(),
+            database: "sentinel".to_string(),
+            retention_policy: Some("autogen".to_string()),
+            username: Some("sentinel".to_string()),
+            password: Some("secret".to_string()),
+            timeout_secs: 5,
+            write_attempts: 1,
+            retry_delay_ms: 10,
+            tags: InfluxTagConfig::default(),
+        })
+        .await
+        .unwrap()
+    }
+
+    #[test]
+    fn test_line_protocol() {
+        let at = Utc.timestamp_nanos(1_700_000_000_000_000_000);
+        let point = Point::new("telemetry", at)
+            .tag("service", "chat api")
+            .tag("label_team", "a,b=c")
+            .tag("empty", "")
+            .field("latency_ms", 12.5)
+            .field("total_tokens", 42i64)
+            .field("note", r#"say "hi"\n"#)
+            .field("nan", f64::NAN);
+
+        assert_eq!(
+            point.line(),
+            r#"telemetry,label_team=a\,b\=c,service=chat\ api latency_ms=12.5,total_tokens=42i,note="say \"hi\"\\n" 1700000000000000000"#
+        );
+    }
+
+    #[test]
+    fn test_influxql_quoting() {
+        assert_eq!(quote_str("o'brien"), r"'o\'brien'");
+        assert_eq!(quote_ident(r#"tag_"x""#), r#""tag_\"x\"""#);
+    }
+
+    #[tokio::test]
+    async fn test_write_telemetry() {
+        let server = MockServer::start().await;
+        let storage = create_storage(&server).await;
+
+        Mock::given(method("POST"))
+            .and(path("/write"))
+            .and(query_param("db", "sentinel"))
+            .and(query_param("rp", "autogen"))
+            .and(query_param("precision", "ns"))
+            .and(header_exists("authorization"))
+            .respond_with(ResponseTemplate::new(204))
+            .expect(1)
+            .mount(&server)
+            .await;
+
+        let event = event()
+            .service("chat-api")
+            .latency_ms(150.0)
+            .latency_component("retrieval", 40.0)
+            .build();
+        storage.write_telemetry(&event).await.unwrap();
+
+        let requests = server.received_requests().await.unwrap();
+        let write = requests.iter().find(|r| r.url.path() == "/write").unwrap();
+        let body = String::from_utf8(write.body.clone()).unwrap();
+        assert!(body.starts_with("telemetry,model=gpt-4,service=chat-api "));
+        assert!(body.contains("total_tokens=30i"));
+        assert!(body.contains("latency_retrieval_ms=40"));
+        assert!(body.contains(&format!(r#"event_id="{}""#, event.event_id)));
+    }
+
+    #[tokio::test]
+    async fn test_partial_write_is_not_an_error() {
+        let server = MockServer::start().await;
+        let storage = create_storage(&server).await;
+
+        Mock::given(method("POST"))
+            .and(path("/write"))
+            .respond_with(
+                ResponseTemplate::new(400)
+                    .set_body_string(r#"{"error":"partial write: field type conflict dropped=1"}"#),
+            )
+            .mount(&server)
+            .await;
+
+        let rollup = CostRollup {
+            bucket_start: Utc::now(),
+            last_event: Utc::now(),
+            service: ServiceId::new("chat-api"),
+            model: ModelId::new("gpt-4"),
+            user: None,
+            tags: BTreeMap::new(),
+            cost_usd: 1.5,
+            requests: 3,
+            prompt_tokens: 30,
+            completion_tokens: 60,
+        };
+        storage.write_cost_rollups(&[rollup]).await.unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_heatmap_query() {
+        let server = MockServer::start().await;
+        let storage = create_storage(&server).await;
+
+        Mock::given(method("GET"))
+            .and(path("/query"))
+            .and(query_param("db", "sentinel"))
+            .and(query_param("epoch", "ns"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({
+                "results": [{
+                    "statement_id": 0,
+                    "series": [
+                        {
+                            "name": "anomaly",
+                            "tags": {"service": "search"},
+                            "columns": ["time", "count"],
+                            "values": [[1_700_000_000_000_000_000i64, 2]]
+                        },
+                        {
+                            "name": "anomaly",
+                            "tags": {"service": "chat-api"},
+                            "columns": ["time", "count"],
+                            "values": [
+                                [1_700_000_000_000_000_000i64, 5],
+                                [1_700_000_300_000_000_000i64, 1]
+                            ]
+                        }
+                    ]
+                }]
+            })))
+            .mount(&server)
+            .await;
+
+        let query = HeatmapQuery::new(TimeRange::last_hours(1), 300, HeatmapGroupBy::Service);
+        let influxql = storage.heatmap_influxql(&query);
+        assert!(influxql.contains(r#"count("confidence")"#));
+        assert!(influxql.contains(r#"GROUP BY time(300s), "service" fill(none)"#));
+
+        let buckets = storage.anomaly_heatmap(query).await.unwrap();
+        let summary: Vec<_> = buckets
+            .iter()
+            .map(|b| (b.group.as_str(), b.count))
+            .collect();
+        assert_eq!(
+            summary,
+            vec![("chat-api", 5), ("search", 2), ("chat-api", 1)]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_query_error() {
+        let server = MockServer::start().await;
+        let storage = create_storage(&server).await;
+
+        Mock::given(method("GET"))
+            .and(path("/query"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({
+                "results": [{"statement_id": 0, "error": "database not found: sentinel"}]
+            })))
+            .mount(&server)
+            .await;
+
+        let err = storage.get_stream_offset("billing").await.unwrap_err();
+        assert!(err.to_string().contains("database not found"));
+    }
+}
@@ -0,0 +1,171 @@
+//! Redis-backed leader lease.
+//!
+//! The lease is a Redis key holding the leader's identity with a TTL. A
+//! replica acquires it when the key is absent and extends it while it holds
+//! it; both happen atomically in a Lua script so two replicas can never
+//! hold the lease at once.
+
+use async_trait::async_trait;
+use llm_sentinel_core::{
+    config::{Config, LeaderElectionConfig},
+    leader::LeaderElector,
+    secrets::CredentialRotation,
+    Error, Result,
+};
+use std::sync::RwLock;
+use std::time::Duration;
+use tracing::info;
+
+/// Take the lease when free, extend it when held by `ARGV[1]`
+const ACQUIRE_SCRIPT: &str = r#"
+local holder = redis.call('GET', KEYS[1])
+if holder == false then
+  redis.call('SET', KEYS[1], ARGV[1], 'PX', ARGV[2])
+  return 1
+elseif holder == ARGV[1] then
+  redis.call('PEXPIRE', KEYS[1], ARGV[2])
+  return 1
+end
+return 0
+"#;
+
+/// Delete the lease only when held by `ARGV[1]`
+const RELEASE_SCRIPT: &str = r#"
+if redis.call('GET', KEYS[1]) == ARGV[1] then
+  return redis.call('DEL', KEYS[1])
+end
+return 0
+"#;
+
+/// Leader lease held in Redis
+pub struct RedisLeaderElector {
+    /// Replaced when the URL (and its password) is rotated
+    client: RwLock<redis::Client>,
+    url: RwLock<String>,
+    key: String,
+    identity: String,
+    lease: Duration,
+}
+
+impl std::fmt::Debug for RedisLeaderElector {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("RedisLeaderElector")
+            .field("key", &self.key)
+            .field("identity", &self.identity)
+            .field("lease", &self.lease)
+            .finish()
+    }
+}
+
+impl RedisLeaderElector {
+    /// Connect to the Redis instance holding the lease
+    pub async fn new(config: &LeaderElectionConfig) -> Result<Self> {
+        let client = connect(&config.redis_url).await?;
+
+        let identity = config.identity.clone().unwrap_or_else(default_identity);
+        info!(key = %config.key, identity = %identity, "Campaigning for leader lease");
+
+        Ok(Self {
+            client: RwLock::new(client),
+            url: RwLock::new(config.redis_url.clone()),
+            key: config.key.clone(),
+            identity,
+            lease: Duration::from_secs(config.lease_secs),
+        })
+    }
+
+    /// How often to renew the lease
+    pub fn renew_interval(&self) -> Duration {
+        self.lease / 3
+    }
+
+    /// Switch to a new Redis URL, such as one with a rotated password
+    ///
+    /// The new client must answer a ping before it replaces the old one.
+    /// Returns whether the URL changed.
+    pub async fn rotate_url(&self, url: &str) -> Result<bool> {
+        if *self.url.read().unwrap() == url {
+            return Ok(false);
+        }
+
+        let client = connect(url).await?;
+        *self.client.write().unwrap() = client;
+        *self.url.write().unwrap() = url.to_string();
+        info!(key = %self.key, "Rotated leader lease Redis credentials");
+        Ok(true)
+    }
+
+    async fn run_script(&self, script: &str, with_lease: bool) -> Result<i64> {
+        let client = self.client.read().unwrap().clone();
+        let mut conn = client
+            .get_multiplexed_async_connection()
+            .await
+            .map_err(|e| Error::connection(format!("Failed to get Redis connection: {}", e)))?;
+
+        let script = redis::Script::new(script);
+        let mut invocation = script.key(&self.key);
+        invocation.arg(&self.identity);
+        if with_lease {
+            invocation.arg(self.lease.as_millis() as u64);
+        }
+        invocation
+            .invoke_async(&mut conn)
+            .await
+            .map_err(|e| Error::connection(format!("Redis leader lease script failed: {}", e)))
+    }
+}
+
+#[async_trait]
+impl LeaderElector for RedisLeaderElector {
+    async fn try_acquire(&self) -> Result<bool> {
+        Ok(self.run_script(ACQUIRE_SCRIPT, true).await? == 1)
+    }
+
+    async fn release(&self) -> Result<()> {
+        self.run_script(RELEASE_SCRIPT, false).await?;
+        info!(identity = %self.identity, "Released leader lease");
+        Ok(())
+    }
+
+    fn identity(&self) -> &str {
+        &self.identity
+    }
+}
+
+#[async_trait]
+impl CredentialRotation for RedisLeaderElector {
+    fn component(&self) -> &str {
+        "leader_election"
+    }
+
+    async fn rotate(&self, config: &Config) -> Result<bool> {
+        match &config.leader_election {
+            Some(leader_election) => self.rotate_url(&leader_election.redis_url).await,
+            None => Ok(false),
+        }
+    }
+}
+
+/// Open a client and check that Redis answers
+pub(crate) async fn connect(url: &str) -> Result<redis::Client> {
+    let client = redis::Client::open(url)
+        .map_err(|e| Error::connection(format!("Failed to create Redis client: {}", e)))?;
+
+    let mut conn = client
+        .get_multiplexed_async_connection()
+        .await
+        .map_err(|e| Error::connection(format!("Failed to connect to Redis: {}", e)))?;
+    redis::cmd("PING")
+        .query_async::<String>(&mut conn)
+        .await
+        .map_err(|e| Error::connection(format!("Redis ping failed: {}", e)))?;
+    Ok(client)
+}
+
+/// Host name (the pod name on Kubernetes), or a random identity
+fn default_identity() -> String {
+    std::env::var("HOSTNAME")
+        .ok()
+        .filter(|name| !name.is_empty())
+        .unwrap_or_else(|| format!("sentinel-{}", uuid::Uuid::new_v4()))
+}
@@ -3,22 +3,74 @@
 //! Data persistence layer for LLM-Sentinel.
 //!
 //! This crate provides:
-//! - Time-series storage (InfluxDB)
+//! - Time-series storage (InfluxDB, `influxdb` feature) with tag cardinality
+//!   protection
+//! - Prometheus remote-write backend (VictoriaMetrics, Mimir)
+//! - InfluxDB 1.x compatible backend (line protocol and InfluxQL)
 //! - In-memory caching (Moka)
-//! - Distributed caching (Redis)
+//! - Distributed caching (Redis, `redis` feature)
+//! - Leader lease for HA deployments (Redis, `redis` feature)
 //! - Query interfaces for metrics and anomalies
+//! - Cost attribution rollups and reports
+//! - Cost budget burn-rate forecasting
+//! - Alert delivery records and SLA reports
+//! - Digest reports for scheduled summaries
+//! - Idempotent writes for backends without native upserts
+//! - Batched anomaly writes and an outbox coupling them to alert dispatch
+//! - Anomaly lifecycle state changes
+//! - Responder annotations on anomalies
+//! - Incidents grouping related anomalies
+//! - Anomaly stream consumer offsets
+//! - Recent telemetry context for anomalies
+//! - Fleet comparison of an anomaly's metric across services on its model
+//! - Alert noise scoring per anomaly signature
+//! - Full-text search over prompt/response text (tantivy, `search` feature)
+//! - Embedding similarity search
+//! - Runtime state snapshots for blue/green deploys
+//! - State stream from a primary to its warm standbys (Redis, `redis`
+//!   feature)
+//! - In-memory backend for tests (`test-util` feature)
 
 #![warn(missing_debug_implementations, rust_2018_idioms, unreachable_pub)]
 
+pub mod annotation;
+pub mod budget;
 pub mod cache;
+pub mod cardinality;
+pub mod context;
+pub mod cost;
+pub mod delivery;
+pub mod digest;
+pub mod fleet;
+pub mod idempotency;
+pub mod incident;
+#[cfg(feature = "influxdb")]
 pub mod influxdb;
+pub mod influxdb_v1;
+#[cfg(feature = "redis")]
+pub mod leader;
+pub mod lifecycle;
+#[cfg(any(test, feature = "test-util"))]
+pub mod memory;
+pub mod noise;
+pub mod outbox;
+pub mod prometheus;
 pub mod query;
+pub mod search;
+pub mod similarity;
+pub mod snapshot;
+pub mod standby;
+pub mod stream;
 
 use async_trait::async_trait;
 use llm_sentinel_core::{
-    events::{AnomalyEvent, TelemetryEvent},
+    annotation::AnomalyAnnotation,
+    events::{AlertMetadata, AnomalyEvent, TelemetryEvent},
+    incident::Incident,
+    lifecycle::AnomalyStateChange,
     Result,
 };
+use uuid::Uuid;
 
 /// Trait for storage backends
 #[async_trait]
@@ -41,14 +93,119 @@ pub trait Storage: Send + Sync {
     /// Query anomaly events
     async fn query_anomalies(&self, query: query::AnomalyQuery) -> Result<Vec<AnomalyEvent>>;
 
+    /// Get an anomaly by alert ID, in its current lifecycle state
+    async fn get_anomaly(&self, alert_id: Uuid) -> Result<Option<AnomalyEvent>>;
+
+    /// Record anomaly lifecycle state changes
+    async fn write_anomaly_state_changes(&self, changes: &[AnomalyStateChange]) -> Result<()>;
+
+    /// Query the lifecycle state changes of an anomaly, oldest first
+    async fn query_anomaly_state_changes(&self, alert_id: Uuid)
+        -> Result<Vec<AnomalyStateChange>>;
+
+    /// Record annotations on anomalies
+    async fn write_anomaly_annotations(&self, annotations: &[AnomalyAnnotation]) -> Result<()>;
+
+    /// Query anomaly annotations, oldest first
+    async fn query_anomaly_annotations(
+        &self,
+        query: annotation::AnnotationQuery,
+    ) -> Result<Vec<AnomalyAnnotation>>;
+
+    /// Store a stream consumer's acknowledged offset
+    async fn write_stream_offset(&self, offset: &stream::StreamOffset) -> Result<()>;
+
+    /// Latest acknowledged offset of a stream consumer
+    async fn get_stream_offset(&self, consumer_id: &str) -> Result<Option<stream::StreamOffset>>;
+
+    /// Count anomalies per time bucket and group
+    async fn anomaly_heatmap(&self, query: query::HeatmapQuery)
+        -> Result<Vec<query::HeatmapBucket>>;
+
+    /// Write cost rollups to the cost measurement
+    async fn write_cost_rollups(&self, rollups: &[cost::CostRollup]) -> Result<()>;
+
+    /// Query an aggregated cost report
+    async fn query_cost_report(&self, query: cost::CostReportQuery)
+        -> Result<Vec<cost::CostReportRow>>;
+
+    /// Write alert delivery records
+    async fn write_alert_deliveries(&self, deliveries: &[AlertMetadata]) -> Result<()>;
+
+    /// Query alert delivery records
+    async fn query_alert_deliveries(&self, query: delivery::DeliveryQuery)
+        -> Result<Vec<AlertMetadata>>;
+
+    /// Write the current version of an incident
+    async fn write_incident(&self, incident: &Incident) -> Result<()>;
+
+    /// Query the latest version of each incident
+    async fn query_incidents(&self, query: incident::IncidentQuery) -> Result<Vec<Incident>>;
+
+    /// Write anomalies found by a re-detection job, apart from live
+    /// anomalies and tagged with the job ID
+    async fn write_redetected_anomalies(
+        &self,
+        job_id: Uuid,
+        anomalies: &[AnomalyEvent],
+    ) -> Result<()>;
+
     /// Health check
     async fn health_check(&self) -> Result<()>;
+
+    /// Whether writing a telemetry event or anomaly again with the same ID
+    /// replaces the stored record instead of adding another
+    ///
+    /// Backends returning `false` are wrapped in
+    /// [`idempotency::IdempotentStorage`] so redelivered events are not
+    /// double-counted.
+    fn upserts_by_id(&self) -> bool;
 }
 
 /// Re-export commonly used types
 pub mod prelude {
+    pub use crate::annotation::{annotate_anomaly, attach_annotations, AnnotationQuery};
+    pub use crate::budget::{BudgetForecaster, BudgetProjection};
     pub use crate::cache::{BaselineCache, CacheConfig};
+    pub use crate::cardinality::CardinalityEstimator;
+    pub use crate::context::{attach_recent_context, RecentTelemetrySummary};
+    pub use crate::cost::{
+        CostAggregator, CostAggregatorConfig, CostDimension, CostReportQuery, CostReportRow,
+        CostRollup,
+    };
+    pub use crate::delivery::{DeliveryQuery, DeliverySlaPolicy, DeliverySlaRow};
+    pub use crate::digest::{DigestReport, DEFAULT_DIGEST_TEMPLATE};
+    pub use crate::fleet::{compare_to_fleet, FleetComparison, FleetScope};
+    pub use crate::idempotency::IdempotentStorage;
+    pub use crate::incident::{
+        get_incident, link_incident, transition_incident, IncidentQuery, IncidentTracker,
+    };
+    #[cfg(feature = "influxdb")]
     pub use crate::influxdb::{InfluxDbStorage, InfluxDbConfig};
-    pub use crate::query::{AnomalyQuery, TelemetryQuery, TimeRange};
+    pub use crate::influxdb_v1::InfluxDbV1Storage;
+    #[cfg(feature = "redis")]
+    pub use crate::leader::RedisLeaderElector;
+    pub use crate::lifecycle::transition_anomaly;
+    #[cfg(any(test, feature = "test-util"))]
+    pub use crate::memory::InMemoryStorage;
+    pub use crate::noise::{noise_report, NoiseReport, SignatureNoise};
+    pub use crate::outbox::{AnomalyWriter, Outbox, OutboxEntry, OutboxStatus};
+    pub use crate::prometheus::PrometheusStorage;
+    pub use crate::query::{
+        AnomalyQuery, HeatmapBucket, HeatmapGroupBy, HeatmapQuery, TelemetryQuery, TimeRange,
+    };
+    #[cfg(feature = "search")]
+    pub use crate::search::TantivySearchIndex;
+    pub use crate::search::{start_commit_task, SearchHit, SearchQuery, TextSearch};
+    pub use crate::similarity::{
+        EmbeddingSource, FlatVectorIndex, SimilarHit, SimilarityQuery, VectorIndex,
+    };
+    pub use crate::snapshot::{FileSnapshotStore, SnapshotStore};
+    #[cfg(any(test, feature = "test-util"))]
+    pub use crate::standby::MemoryStateStream;
+    #[cfg(feature = "redis")]
+    pub use crate::standby::RedisStateStream;
+    pub use crate::standby::StateStream;
+    pub use crate::stream::{acknowledge, replay_page, stream_cursor, StreamOffset};
     pub use crate::Storage;
 }
@@ -0,0 +1,152 @@
+//! Anomaly lifecycle transitions against storage.
+//!
+//! [`transition_anomaly`] loads an anomaly, validates the requested state
+//! change with [`AnomalyEvent::transition`] and records it, so API handlers
+//! and background jobs change states the same way.
+
+use crate::Storage;
+use llm_sentinel_core::{
+    events::AnomalyEvent,
+    lifecycle::{AnomalyState, AnomalyStateChange},
+    Error, Result,
+};
+use tracing::info;
+use uuid::Uuid;
+
+/// Move a stored anomaly to a new lifecycle state
+///
+/// Returns the updated anomaly and the recorded change. Fails with
+/// [`Error::NotFound`] for unknown anomalies and [`Error::Validation`] for
+/// transitions the current state does not allow.
+pub async fn transition_anomaly(
+    storage: &dyn Storage,
+    alert_id: Uuid,
+    to: AnomalyState,
+    actor: Option<String>,
+    reason: Option<String>,
+) -> Result<(AnomalyEvent, AnomalyStateChange)> {
+    let mut anomaly = storage
+        .get_anomaly(alert_id)
+        .await?
+        .ok_or_else(|| Error::not_found(format!("Anomaly {}", alert_id)))?;
+
+    let change = anomaly.transition(to, actor, reason)?;
+    storage
+        .write_anomaly_state_changes(std::slice::from_ref(&change))
+        .await?;
+
+    info!(
+        alert_id = %alert_id,
+        from = %change.from,
+        to = %change.to,
+        actor = change.actor.as_deref(),
+        "Anomaly state changed"
+    );
+    metrics::counter!(
+        "sentinel_anomaly_state_changes_total",
+        "from" => change.from.to_string(),
+        "to" => change.to.to_string()
+    )
+    .increment(1);
+
+    Ok((anomaly, change))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::memory::InMemoryStorage;
+
+    use llm_sentinel_fixtures::anomaly;
+
+    fn create_anomaly() -> AnomalyEvent {
+        anomaly().service("checkout").build()
+    }
+
+    #[tokio::test]
+    async fn test_transition_anomaly() {
+        let storage = InMemoryStorage::new();
+        let anomaly = create_anomaly();
+        storage.write_anomaly(&anomaly).await.unwrap();
+
+        let (updated, change) = transition_anomaly(
+            &storage,
+            anomaly.alert_id,
+            AnomalyState::Acknowledged,
+            Some("alice".to_string()),
+            Some("investigating".to_string()),
+        )
+        .await
+        .unwrap();
+        assert_eq!(updated.state, AnomalyState::Acknowledged);
+        assert_eq!(change.from, AnomalyState::Open);
+
+        transition_anomaly(
+            &storage,
+            anomaly.alert_id,
+            AnomalyState::Resolved,
+            None,
+            None,
+        )
+        .await
+        .unwrap();
+
+        let stored = storage
+            .get_anomaly(anomaly.alert_id)
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(stored.state, AnomalyState::Resolved);
+        let history = storage
+            .query_anomaly_state_changes(anomaly.alert_id)
+            .await
+            .unwrap();
+        let states: Vec<_> = history.iter().map(|c| (c.from, c.to)).collect();
+        assert_eq!(
+            states,
+            vec![
+                (AnomalyState::Open, AnomalyState::Acknowledged),
+                (AnomalyState::Acknowledged, AnomalyState::Resolved),
+            ]
+        );
+        assert_eq!(history[0].actor.as_deref(), Some("alice"));
+    }
+
+    #[tokio::test]
+    async fn test_transition_anomaly_rejected() {
+        let storage = InMemoryStorage::new();
+        let anomaly = create_anomaly();
+        storage.write_anomaly(&anomaly).await.unwrap();
+
+        let result =
+            transition_anomaly(&storage, Uuid::new_v4(), AnomalyState::Resolved, None, None).await;
+        assert!(matches!(result, Err(Error::NotFound(_))));
+
+        transition_anomaly(
+            &storage,
+            anomaly.alert_id,
+            AnomalyState::Resolved,
+            None,
+            None,
+        )
+        .await
+        .unwrap();
+        let result = transition_anomaly(
+            &storage,
+            anomaly.alert_id,
+            AnomalyState::Acknowledged,
+            None,
+            None,
+        )
+        .await;
+        assert!(matches!(result, Err(Error::Validation(_))));
+        assert_eq!(
+            storage
+                .query_anomaly_state_changes(anomaly.alert_id)
+                .await
+                .unwrap()
+                .len(),
+            1
+        );
+    }
+}
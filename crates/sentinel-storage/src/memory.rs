@@ -0,0 +1,833 @@
+//! In-memory storage backend for tests.
+//!
+//! [`InMemoryStorage`] keeps everything in process and applies the same
+//! filters, ordering and aggregation as the InfluxDB backend, so end-to-end
+//! tests can run the full pipeline without Docker. Available with the
+//! `test-util` feature.
+
+use crate::{
+    annotation::AnnotationQuery,
+    cost::{CostDimension, CostReportQuery, CostReportRow, CostRollup},
+    delivery::DeliveryQuery,
+    incident::IncidentQuery,
+    query::{
+        AnomalyQuery, Cursor, HeatmapBucket, HeatmapGroupBy, HeatmapQuery, TelemetryQuery,
+        TimeRange,
+    },
+    stream::StreamOffset,
+    Storage,
+};
+use async_trait::async_trait;
+use chrono::{DateTime, TimeZone, Utc};
+use llm_sentinel_core::{
+    annotation::AnomalyAnnotation,
+    events::{AlertMetadata, AnomalyEvent, TelemetryEvent},
+    incident::Incident,
+    labels,
+    lifecycle::{AnomalyState, AnomalyStateChange},
+    Error, Result,
+};
+use std::collections::{BTreeMap, HashMap};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::RwLock;
+use uuid::Uuid;
+
+/// Storage backend holding all data in memory
+#[derive(Debug)]
+pub struct InMemoryStorage {
+    telemetry: RwLock<Vec<TelemetryEvent>>,
+    anomalies: RwLock<Vec<AnomalyEvent>>,
+    cost_rollups: RwLock<Vec<CostRollup>>,
+    deliveries: RwLock<Vec<AlertMetadata>>,
+    state_changes: RwLock<Vec<AnomalyStateChange>>,
+    annotations: RwLock<Vec<AnomalyAnnotation>>,
+    stream_offsets: RwLock<HashMap<String, StreamOffset>>,
+    redetected: RwLock<Vec<(Uuid, AnomalyEvent)>>,
+    incidents: RwLock<HashMap<Uuid, Incident>>,
+    healthy: AtomicBool,
+    writable: AtomicBool,
+}
+
+impl Default for InMemoryStorage {
+    fn default() -> Self {
+        Self {
+            telemetry: RwLock::default(),
+            anomalies: RwLock::default(),
+            cost_rollups: RwLock::default(),
+            deliveries: RwLock::default(),
+            state_changes: RwLock::default(),
+            annotations: RwLock::default(),
+            stream_offsets: RwLock::default(),
+            redetected: RwLock::default(),
+            incidents: RwLock::default(),
+            healthy: AtomicBool::new(true),
+            writable: AtomicBool::new(true),
+        }
+    }
+}
+
+impl InMemoryStorage {
+    /// Create an empty in-memory storage
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// All stored telemetry events, in write order
+    pub fn telemetry(&self) -> Vec<TelemetryEvent> {
+        self.telemetry.read().unwrap().clone()
+    }
+
+    /// All stored anomalies, in write order
+    pub fn anomalies(&self) -> Vec<AnomalyEvent> {
+        self.anomalies.read().unwrap().clone()
+    }
+
+    /// All stored cost rollups, in write order
+    pub fn cost_rollups(&self) -> Vec<CostRollup> {
+        self.cost_rollups.read().unwrap().clone()
+    }
+
+    /// All stored alert delivery records, in write order
+    pub fn deliveries(&self) -> Vec<AlertMetadata> {
+        self.deliveries.read().unwrap().clone()
+    }
+
+    /// All recorded anomaly state changes, in write order
+    pub fn state_changes(&self) -> Vec<AnomalyStateChange> {
+        self.state_changes.read().unwrap().clone()
+    }
+
+    /// All recorded anomaly annotations, in write order
+    pub fn annotations(&self) -> Vec<AnomalyAnnotation> {
+        self.annotations.read().unwrap().clone()
+    }
+
+    /// Anomalies written by the re-detection job `job_id`, in write order
+    pub fn redetected(&self, job_id: Uuid) -> Vec<AnomalyEvent> {
+        self.redetected
+            .read()
+            .unwrap()
+            .iter()
+            .filter(|(job, _)| *job == job_id)
+            .map(|(_, anomaly)| anomaly.clone())
+            .collect()
+    }
+
+    /// Make health checks fail (or succeed again)
+    pub fn set_healthy(&self, healthy: bool) {
+        self.healthy.store(healthy, Ordering::SeqCst);
+    }
+
+    /// Make telemetry and anomaly writes fail (or succeed again)
+    pub fn set_writable(&self, writable: bool) {
+        self.writable.store(writable, Ordering::SeqCst);
+    }
+
+    fn check_writable(&self) -> Result<()> {
+        if self.writable.load(Ordering::SeqCst) {
+            Ok(())
+        } else {
+            Err(Error::storage("In-memory storage is read-only"))
+        }
+    }
+
+    /// Remove all stored data
+    pub fn clear(&self) {
+        self.telemetry.write().unwrap().clear();
+        self.anomalies.write().unwrap().clear();
+        self.cost_rollups.write().unwrap().clear();
+        self.deliveries.write().unwrap().clear();
+        self.state_changes.write().unwrap().clear();
+        self.annotations.write().unwrap().clear();
+        self.stream_offsets.write().unwrap().clear();
+        self.redetected.write().unwrap().clear();
+    }
+}
+
+/// Whether a timestamp falls inside the range (end exclusive)
+/// Replace items with the same ID in place and append the rest
+fn upsert<T: Clone>(stored: &mut Vec<T>, items: &[T], id: impl Fn(&T) -> Uuid) {
+    for item in items {
+        match stored.iter_mut().find(|s| id(s) == id(item)) {
+            Some(existing) => *existing = item.clone(),
+            None => stored.push(item.clone()),
+        }
+    }
+}
+
+fn in_range(range: &TimeRange, at: DateTime<Utc>) -> bool {
+    at >= range.start && at < range.end
+}
+
+/// Start of the epoch-aligned bucket containing `at`
+fn bucket_start(at: DateTime<Utc>, interval_secs: u64) -> DateTime<Utc> {
+    let interval = interval_secs.max(1) as i64;
+    let start = at.timestamp().div_euclid(interval) * interval;
+    Utc.timestamp_opt(start, 0).unwrap()
+}
+
+/// Sort by `(key, id)` and apply cursor, offset and limit pagination
+fn paginate<T>(
+    mut items: Vec<T>,
+    key: impl Fn(&T) -> (f64, Uuid),
+    ascending: bool,
+    cursor: Option<&Cursor>,
+    offset: Option<usize>,
+    limit: Option<usize>,
+) -> Vec<T> {
+    items.sort_by(|a, b| {
+        let ((a_value, a_id), (b_value, b_id)) = (key(a), key(b));
+        a_value.total_cmp(&b_value).then_with(|| a_id.cmp(&b_id))
+    });
+    if !ascending {
+        items.reverse();
+    }
+    items
+        .into_iter()
+        .filter(|item| {
+            let (value, id) = key(item);
+            cursor.map_or(true, |c| c.is_after(value, id, ascending))
+        })
+        .skip(offset.unwrap_or(0))
+        .take(limit.unwrap_or(usize::MAX))
+        .collect()
+}
+
+#[async_trait]
+impl Storage for InMemoryStorage {
+    async fn write_telemetry(&self, event: &TelemetryEvent) -> Result<()> {
+        self.write_telemetry_batch(std::slice::from_ref(event))
+            .await
+    }
+
+    async fn write_anomaly(&self, anomaly: &AnomalyEvent) -> Result<()> {
+        self.write_anomaly_batch(std::slice::from_ref(anomaly))
+            .await
+    }
+
+    async fn write_telemetry_batch(&self, events: &[TelemetryEvent]) -> Result<()> {
+        self.check_writable()?;
+        upsert(&mut self.telemetry.write().unwrap(), events, |e| e.event_id);
+        Ok(())
+    }
+
+    async fn write_anomaly_batch(&self, anomalies: &[AnomalyEvent]) -> Result<()> {
+        self.check_writable()?;
+        upsert(&mut self.anomalies.write().unwrap(), anomalies, |a| {
+            a.alert_id
+        });
+        Ok(())
+    }
+
+    async fn query_telemetry(&self, query: TelemetryQuery) -> Result<Vec<TelemetryEvent>> {
+        let events: Vec<TelemetryEvent> = self
+            .telemetry
+            .read()
+            .unwrap()
+            .iter()
+            .filter(|e| in_range(&query.time_range, e.timestamp))
+            .filter(|e| {
+                query
+                    .service
+                    .as_ref()
+                    .map_or(true, |s| &e.service_name == s)
+            })
+            .filter(|e| query.model.as_ref().map_or(true, |m| &e.model == m))
+            .filter(|e| {
+                query
+                    .environment
+                    .as_ref()
+                    .map_or(true, |env| e.environment.as_ref() == Some(env))
+            })
+            .filter(|e| labels::matches(&e.labels, &query.labels))
+            .cloned()
+            .collect();
+
+        Ok(paginate(
+            events,
+            |e| (query.sort_by.value(e), e.event_id),
+            query.ascending,
+            query.cursor.as_ref(),
+            query.offset,
+            query.limit,
+        ))
+    }
+
+    async fn query_anomalies(&self, query: AnomalyQuery) -> Result<Vec<AnomalyEvent>> {
+        let anomalies: Vec<AnomalyEvent> = self
+            .anomalies
+            .read()
+            .unwrap()
+            .iter()
+            .filter(|a| in_range(&query.time_range, a.timestamp))
+            .filter(|a| query.include_expired || a.state != AnomalyState::Expired)
+            .filter(|a| {
+                query
+                    .service
+                    .as_ref()
+                    .map_or(true, |s| &a.service_name == s)
+            })
+            .filter(|a| query.model.as_ref().map_or(true, |m| &a.model == m))
+            .filter(|a| {
+                query
+                    .environment
+                    .as_ref()
+                    .map_or(true, |env| a.environment.as_ref() == Some(env))
+            })
+            .filter(|a| query.severity.map_or(true, |s| a.severity == s))
+            .filter(|a| {
+                query
+                    .anomaly_type
+                    .as_ref()
+                    .map_or(true, |t| &a.anomaly_type == t)
+            })
+            .filter(|a| query.min_confidence.map_or(true, |c| a.confidence >= c))
+            .filter(|a| labels::matches(&a.labels, &query.labels))
+            .cloned()
+            .collect();
+
+        Ok(paginate(
+            anomalies,
+            |a| (query.sort_by.value(a), a.alert_id),
+            query.ascending,
+            query.cursor.as_ref(),
+            query.offset,
+            query.limit,
+        ))
+    }
+
+    async fn get_anomaly(&self, alert_id: Uuid) -> Result<Option<AnomalyEvent>> {
+        Ok(self
+            .anomalies
+            .read()
+            .unwrap()
+            .iter()
+            .find(|a| a.alert_id == alert_id)
+            .cloned())
+    }
+
+    async fn write_anomaly_state_changes(&self, changes: &[AnomalyStateChange]) -> Result<()> {
+        let mut anomalies = self.anomalies.write().unwrap();
+        for change in changes {
+            if let Some(anomaly) = anomalies.iter_mut().find(|a| a.alert_id == change.alert_id) {
+                anomaly.apply_state_change(change);
+            }
+        }
+        self.state_changes
+            .write()
+            .unwrap()
+            .extend_from_slice(changes);
+        Ok(())
+    }
+
+    async fn query_anomaly_state_changes(&self, alert_id: Uuid) -> Result<Vec<AnomalyStateChange>> {
+        let mut changes: Vec<AnomalyStateChange> = self
+            .state_changes
+            .read()
+            .unwrap()
+            .iter()
+            .filter(|c| c.alert_id == alert_id)
+            .cloned()
+            .collect();
+
+        changes.sort_by_key(|c| c.at);
+        Ok(changes)
+    }
+
+    async fn write_anomaly_annotations(&self, annotations: &[AnomalyAnnotation]) -> Result<()> {
+        self.annotations
+            .write()
+            .unwrap()
+            .extend_from_slice(annotations);
+        Ok(())
+    }
+
+    async fn query_anomaly_annotations(
+        &self,
+        query: AnnotationQuery,
+    ) -> Result<Vec<AnomalyAnnotation>> {
+        let mut annotations: Vec<AnomalyAnnotation> = self
+            .annotations
+            .read()
+            .unwrap()
+            .iter()
+            .filter(|a| in_range(&query.time_range, a.created_at))
+            .filter(|a| query.alert_id.map_or(true, |id| a.alert_id == id))
+            .cloned()
+            .collect();
+
+        annotations.sort_by_key(|a| a.created_at);
+        Ok(annotations)
+    }
+
+    async fn write_stream_offset(&self, offset: &StreamOffset) -> Result<()> {
+        self.stream_offsets
+            .write()
+            .unwrap()
+            .insert(offset.consumer_id.clone(), offset.clone());
+        Ok(())
+    }
+
+    async fn get_stream_offset(&self, consumer_id: &str) -> Result<Option<StreamOffset>> {
+        Ok(self
+            .stream_offsets
+            .read()
+            .unwrap()
+            .get(consumer_id)
+            .cloned())
+    }
+
+    async fn anomaly_heatmap(&self, query: HeatmapQuery) -> Result<Vec<HeatmapBucket>> {
+        let mut counts: BTreeMap<(DateTime<Utc>, String), u64> = BTreeMap::new();
+
+        for anomaly in self.anomalies.read().unwrap().iter() {
+            if !in_range(&query.time_range, anomaly.timestamp)
+                || query
+                    .service
+                    .as_ref()
+                    .is_some_and(|s| &anomaly.service_name != s)
+                || query.severity.is_some_and(|s| anomaly.severity != s)
+            {
+                continue;
+            }
+
+            let group = match query.group_by {
+                HeatmapGroupBy::Service => anomaly.service_name.as_str().to_string(),
+                HeatmapGroupBy::Severity => anomaly.severity.to_string(),
+            };
+            *counts
+                .entry((bucket_start(anomaly.timestamp, query.interval_secs), group))
+                .or_default() += 1;
+        }
+
+        Ok(counts
+            .into_iter()
+            .map(|((start, group), count)| HeatmapBucket {
+                start,
+                group,
+                count,
+            })
+            .collect())
+    }
+
+    async fn write_cost_rollups(&self, rollups: &[CostRollup]) -> Result<()> {
+        self.cost_rollups
+            .write()
+            .unwrap()
+            .extend_from_slice(rollups);
+        Ok(())
+    }
+
+    async fn query_cost_report(&self, query: CostReportQuery) -> Result<Vec<CostReportRow>> {
+        let mut rows: BTreeMap<(Option<DateTime<Utc>>, BTreeMap<String, String>), CostReportRow> =
+            BTreeMap::new();
+
+        for rollup in self.cost_rollups.read().unwrap().iter() {
+            if !in_range(&query.time_range, rollup.last_event)
+                || query.service.as_ref().is_some_and(|s| &rollup.service != s)
+                || query.model.as_ref().is_some_and(|m| &rollup.model != m)
+            {
+                continue;
+            }
+
+            let bucket = query
+                .interval_secs
+                .map(|interval| bucket_start(rollup.bucket_start, interval));
+            let group: BTreeMap<String, String> = query
+                .group_by
+                .iter()
+                .map(|dimension| {
+                    let value = match dimension {
+                        CostDimension::Service => Some(rollup.service.as_str().to_string()),
+                        CostDimension::Model => Some(rollup.model.as_str().to_string()),
+                        CostDimension::User => rollup.user.clone(),
+                        CostDimension::Tag(key) => rollup.tags.get(key).cloned(),
+                    };
+                    (dimension.to_string(), value.unwrap_or_default())
+                })
+                .collect();
+
+            let row = rows
+                .entry((bucket, group.clone()))
+                .or_insert_with(|| CostReportRow {
+                    bucket_start: bucket,
+                    group,
+                    cost_usd: 0.0,
+                    requests: 0,
+                    prompt_tokens: 0,
+                    completion_tokens: 0,
+                });
+            row.cost_usd += rollup.cost_usd;
+            row.requests += rollup.requests;
+            row.prompt_tokens += rollup.prompt_tokens;
+            row.completion_tokens += rollup.completion_tokens;
+        }
+
+        let mut rows: Vec<CostReportRow> = rows.into_values().collect();
+        rows.sort_by(|a, b| {
+            a.bucket_start
+                .cmp(&b.bucket_start)
+                .then_with(|| b.cost_usd.total_cmp(&a.cost_usd))
+        });
+        Ok(rows)
+    }
+
+    async fn write_alert_deliveries(&self, deliveries: &[AlertMetadata]) -> Result<()> {
+        self.deliveries
+            .write()
+            .unwrap()
+            .extend_from_slice(deliveries);
+        Ok(())
+    }
+
+    async fn query_alert_deliveries(&self, query: DeliveryQuery) -> Result<Vec<AlertMetadata>> {
+        let mut deliveries: Vec<AlertMetadata> = self
+            .deliveries
+            .read()
+            .unwrap()
+            .iter()
+            .filter(|d| in_range(&query.time_range, d.last_attempt))
+            .filter(|d| query.alerter.as_ref().map_or(true, |a| &d.alerter == a))
+            .filter(|d| query.severity.map_or(true, |s| d.severity == s))
+            .filter(|d| query.alert_id.as_ref().map_or(true, |id| &d.alert_id == id))
+            .cloned()
+            .collect();
+
+        deliveries.sort_by_key(|d| d.last_attempt);
+        Ok(deliveries)
+    }
+
+    async fn write_incident(&self, incident: &Incident) -> Result<()> {
+        self.incidents
+            .write()
+            .unwrap()
+            .insert(incident.incident_id, incident.clone());
+        Ok(())
+    }
+
+    async fn query_incidents(&self, query: IncidentQuery) -> Result<Vec<Incident>> {
+        let incidents = self.incidents.read().unwrap();
+        Ok(query.select(incidents.values().cloned()))
+    }
+
+    async fn write_redetected_anomalies(
+        &self,
+        job_id: Uuid,
+        anomalies: &[AnomalyEvent],
+    ) -> Result<()> {
+        self.redetected
+            .write()
+            .unwrap()
+            .extend(anomalies.iter().map(|anomaly| (job_id, anomaly.clone())));
+        Ok(())
+    }
+
+    async fn health_check(&self) -> Result<()> {
+        if self.healthy.load(Ordering::SeqCst) {
+            Ok(())
+        } else {
+            Err(Error::storage("In-memory storage marked unhealthy"))
+        }
+    }
+
+    fn upserts_by_id(&self) -> bool {
+        true
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::query::{AnomalySort, TelemetrySort};
+    use chrono::Duration;
+    use llm_sentinel_core::types::{Environment, ServiceId, Severity};
+    use llm_sentinel_fixtures::{anomaly, event};
+
+    fn create_test_event(service: &str, at: DateTime<Utc>) -> TelemetryEvent {
+        event()
+            .service(service)
+            .prompt("Hello")
+            .response("Hi")
+            .response_tokens(5)
+            .timestamp(at)
+            .build()
+    }
+
+    fn create_test_anomaly(service: &str, severity: Severity, at: DateTime<Utc>) -> AnomalyEvent {
+        let mut anomaly = anomaly().severity(severity).service(service).build();
+        anomaly.timestamp = at;
+        anomaly
+    }
+
+    #[tokio::test]
+    async fn test_writes_upsert_by_id() {
+        let storage = InMemoryStorage::new();
+        let mut event = create_test_event("chat", Utc::now());
+        let other = create_test_event("chat", Utc::now());
+        storage
+            .write_telemetry_batch(&[event.clone(), other.clone()])
+            .await
+            .unwrap();
+        event.latency_ms = 250.0;
+        storage.write_telemetry(&event).await.unwrap();
+
+        let telemetry = storage.telemetry();
+        assert_eq!(telemetry.len(), 2);
+        assert_eq!(telemetry[0].event_id, event.event_id);
+        assert_eq!(telemetry[0].latency_ms, 250.0);
+
+        let anomaly = create_test_anomaly("chat", Severity::High, Utc::now());
+        storage.write_anomaly(&anomaly).await.unwrap();
+        storage.write_anomaly_batch(&[anomaly]).await.unwrap();
+        assert_eq!(storage.anomalies().len(), 1);
+    }
+
+    #[tokio::test]
+    async fn test_query_filtering() {
+        let storage = InMemoryStorage::new();
+        let now = Utc::now();
+
+        for i in 0..5 {
+            let at = now - Duration::minutes(i);
+            storage
+                .write_telemetry(&create_test_event("checkout", at))
+                .await
+                .unwrap();
+        }
+        storage
+            .write_telemetry(&create_test_event("search", now))
+            .await
+            .unwrap();
+        storage
+            .write_telemetry(&create_test_event("checkout", now - Duration::days(2)))
+            .await
+            .unwrap();
+
+        let query = TelemetryQuery::new(TimeRange::new(
+            now - Duration::hours(1),
+            now + Duration::seconds(1),
+        ))
+        .with_service(ServiceId::new("checkout"))
+        .with_limit(3);
+        let events = storage.query_telemetry(query).await.unwrap();
+        assert_eq!(events.len(), 3);
+        assert_eq!(events[0].timestamp, now);
+        assert!(events.iter().all(|e| e.service_name.as_str() == "checkout"));
+
+        storage
+            .write_anomaly(&create_test_anomaly("checkout", Severity::High, now))
+            .await
+            .unwrap();
+        storage
+            .write_anomaly(&create_test_anomaly("search", Severity::Low, now))
+            .await
+            .unwrap();
+
+        let query = AnomalyQuery::new(TimeRange::new(
+            now - Duration::hours(1),
+            now + Duration::seconds(1),
+        ))
+        .with_severity(Severity::High);
+        let anomalies = storage.query_anomalies(query).await.unwrap();
+        assert_eq!(anomalies.len(), 1);
+        assert_eq!(anomalies[0].service_name.as_str(), "checkout");
+    }
+
+    #[tokio::test]
+    async fn test_label_filtering() {
+        let storage = InMemoryStorage::new();
+        let now = Utc::now();
+        let range = TimeRange::new(now - Duration::hours(1), now + Duration::seconds(1));
+
+        let events = vec![
+            create_test_event("checkout", now)
+                .with_label("team", "payments")
+                .with_label("env", "prod"),
+            create_test_event("checkout", now).with_label("team", "payments"),
+            create_test_event("search", now).with_label("team", "search"),
+        ];
+        storage.write_telemetry_batch(&events).await.unwrap();
+
+        let query = TelemetryQuery::new(range.clone()).with_label("team", "payments");
+        assert_eq!(storage.query_telemetry(query).await.unwrap().len(), 2);
+        let query = TelemetryQuery::new(range.clone())
+            .with_label("team", "payments")
+            .with_label("env", "prod");
+        assert_eq!(storage.query_telemetry(query).await.unwrap().len(), 1);
+
+        let mut anomaly = create_test_anomaly("search", Severity::High, now);
+        anomaly.labels = events[2].labels.clone();
+        storage.write_anomaly(&anomaly).await.unwrap();
+        storage
+            .write_anomaly(&create_test_anomaly("checkout", Severity::High, now))
+            .await
+            .unwrap();
+
+        let query = AnomalyQuery::new(range).with_label("team", "search");
+        let anomalies = storage.query_anomalies(query).await.unwrap();
+        assert_eq!(anomalies.len(), 1);
+        assert_eq!(anomalies[0].service_name.as_str(), "search");
+    }
+
+    #[tokio::test]
+    async fn test_environment_filtering() {
+        let storage = InMemoryStorage::new();
+        let now = Utc::now();
+        let range = TimeRange::new(now - Duration::hours(1), now + Duration::seconds(1));
+
+        let events = vec![
+            create_test_event("checkout", now).with_environment("prod"),
+            create_test_event("checkout", now).with_environment("staging"),
+            create_test_event("checkout", now),
+        ];
+        storage.write_telemetry_batch(&events).await.unwrap();
+
+        let query = TelemetryQuery::new(range.clone()).with_environment(Environment::new("prod"));
+        let events = storage.query_telemetry(query).await.unwrap();
+        assert_eq!(events.len(), 1);
+        assert_eq!(events[0].environment, Some(Environment::new("prod")));
+
+        let mut anomaly = create_test_anomaly("checkout", Severity::High, now);
+        anomaly.environment = Some(Environment::new("staging"));
+        storage.write_anomaly(&anomaly).await.unwrap();
+
+        let query = AnomalyQuery::new(range.clone()).with_environment(Environment::new("staging"));
+        assert_eq!(storage.query_anomalies(query).await.unwrap().len(), 1);
+        let query = AnomalyQuery::new(range).with_environment(Environment::new("prod"));
+        assert!(storage.query_anomalies(query).await.unwrap().is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_expired_anomalies_excluded_by_default() {
+        let storage = InMemoryStorage::new();
+        let now = Utc::now();
+        let range = TimeRange::new(now - Duration::hours(1), now + Duration::seconds(1));
+
+        let mut expired = create_test_anomaly("checkout", Severity::Low, now);
+        let open = create_test_anomaly("checkout", Severity::High, now);
+        storage
+            .write_anomaly_batch(&[expired.clone(), open.clone()])
+            .await
+            .unwrap();
+        let change = expired
+            .transition(AnomalyState::Expired, Some("expirer".to_string()), None)
+            .unwrap();
+        storage
+            .write_anomaly_state_changes(&[change])
+            .await
+            .unwrap();
+
+        let anomalies = storage
+            .query_anomalies(AnomalyQuery::new(range.clone()))
+            .await
+            .unwrap();
+        assert_eq!(anomalies.len(), 1);
+        assert_eq!(anomalies[0].alert_id, open.alert_id);
+
+        let query = AnomalyQuery::new(range).with_expired();
+        assert_eq!(storage.query_anomalies(query).await.unwrap().len(), 2);
+    }
+
+    #[tokio::test]
+    async fn test_sort_and_cursor_pagination() {
+        let storage = InMemoryStorage::new();
+        let now = Utc::now();
+        let range = TimeRange::new(now - Duration::hours(1), now + Duration::seconds(1));
+
+        // Two events share a latency so the cursor must break the tie by ID
+        let events: Vec<_> = [300.0, 100.0, 200.0, 200.0, 50.0]
+            .into_iter()
+            .map(|latency| {
+                let mut event = create_test_event("checkout", now);
+                event.latency_ms = latency;
+                event
+            })
+            .collect();
+        storage.write_telemetry_batch(&events).await.unwrap();
+
+        let query = TelemetryQuery::new(range.clone())
+            .sort_by(TelemetrySort::Latency)
+            .with_limit(2);
+        let mut latencies = Vec::new();
+        let mut cursor = None;
+        loop {
+            let mut page_query = query.clone();
+            if let Some(cursor) = cursor {
+                page_query = page_query.with_cursor(cursor);
+            }
+            let page = storage.query_telemetry(page_query).await.unwrap();
+            latencies.extend(page.iter().map(|e| e.latency_ms));
+            match page.last() {
+                Some(last) if page.len() == 2 => cursor = Some(TelemetrySort::Latency.cursor(last)),
+                _ => break,
+            }
+        }
+        assert_eq!(latencies, vec![300.0, 200.0, 200.0, 100.0, 50.0]);
+
+        let query = TelemetryQuery::new(range.clone())
+            .sort_by(TelemetrySort::Latency)
+            .ascending()
+            .with_limit(1);
+        let events = storage.query_telemetry(query).await.unwrap();
+        assert_eq!(events[0].latency_ms, 50.0);
+
+        for severity in [Severity::Low, Severity::Critical, Severity::Medium] {
+            storage
+                .write_anomaly(&create_test_anomaly("checkout", severity, now))
+                .await
+                .unwrap();
+        }
+        let query = AnomalyQuery::new(range).sort_by(AnomalySort::Severity);
+        let anomalies = storage.query_anomalies(query.clone()).await.unwrap();
+        let severities: Vec<_> = anomalies.iter().map(|a| a.severity).collect();
+        assert_eq!(
+            severities,
+            vec![Severity::Critical, Severity::Medium, Severity::Low]
+        );
+
+        let cursor = AnomalySort::Severity.cursor(&anomalies[0]);
+        let rest = storage
+            .query_anomalies(query.with_cursor(cursor))
+            .await
+            .unwrap();
+        assert_eq!(rest.len(), 2);
+        assert_eq!(rest[0].severity, Severity::Medium);
+    }
+
+    #[tokio::test]
+    async fn test_heatmap_and_health() {
+        let storage = InMemoryStorage::new();
+        let day = Utc.timestamp_opt(86400 * 100, 0).unwrap();
+
+        for (offset, severity) in [
+            (0, Severity::High),
+            (60, Severity::High),
+            (86400, Severity::Low),
+        ] {
+            storage
+                .write_anomaly(&create_test_anomaly(
+                    "checkout",
+                    severity,
+                    day + Duration::seconds(offset),
+                ))
+                .await
+                .unwrap();
+        }
+
+        let query = HeatmapQuery::new(
+            TimeRange::new(day, day + Duration::days(2)),
+            86400,
+            HeatmapGroupBy::Severity,
+        );
+        let buckets = storage.anomaly_heatmap(query).await.unwrap();
+        assert_eq!(buckets.len(), 2);
+        assert_eq!(buckets[0].start, day);
+        assert_eq!(buckets[0].group, "high");
+        assert_eq!(buckets[0].count, 2);
+        assert_eq!(buckets[1].group, "low");
+
+        assert!(storage.health_check().await.is_ok());
+        storage.set_healthy(false);
+        assert!(storage.health_check().await.is_err());
+    }
+}
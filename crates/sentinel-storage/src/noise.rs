@@ -0,0 +1,355 @@
+//! Alert noise scoring.
+//!
+//! Pager fatigue comes from signatures that fire often and that nobody acts
+//! on. [`noise_report`] groups the anomalies of a time range by
+//! `signature_hash` and works out from their lifecycle state changes how
+//! often each signature fires, how many of its anomalies a responder
+//! acknowledged or resolved, and how long that took. Signatures are ranked by
+//! noise score: anomalies per day that no responder acted on. Changes made by
+//! Sentinel itself (auto-resolutions and expiries, see
+//! [`AnomalyStateChange::is_automatic`]) are not responses, and suppressing an
+//! anomaly is not acting on it.
+//!
+//! Test anomalies are left out.
+
+use crate::{delivery::percentile, query::AnomalyQuery, Storage};
+use chrono::{DateTime, Utc};
+use futures::{StreamExt, TryStreamExt};
+use llm_sentinel_core::{
+    events::AnomalyEvent,
+    lifecycle::{AnomalyState, AnomalyStateChange},
+    simulation,
+    types::{AnomalyType, ModelId, ServiceId, Severity},
+    Error, Result,
+};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use uuid::Uuid;
+
+/// State histories fetched concurrently
+const HISTORY_CONCURRENCY: usize = 16;
+
+/// Noise statistics of one anomaly signature
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SignatureNoise {
+    /// Signature shared by the anomalies
+    pub signature_hash: String,
+    /// Service
+    pub service: ServiceId,
+    /// Model
+    pub model: ModelId,
+    /// Anomaly type
+    pub anomaly_type: AnomalyType,
+    /// Metric
+    pub metric: String,
+    /// Highest severity seen
+    pub max_severity: Severity,
+    /// Anomalies in the range
+    pub anomalies: usize,
+    /// Anomalies per day
+    pub per_day: f64,
+    /// Anomalies a responder acknowledged
+    pub acknowledged: usize,
+    /// Anomalies a responder resolved
+    pub resolved: usize,
+    /// Anomalies resolved by the recovery monitor
+    pub auto_resolved: usize,
+    /// Anomalies suppressed
+    pub suppressed: usize,
+    /// Anomalies that expired
+    pub expired: usize,
+    /// Share of anomalies a responder acknowledged
+    pub ack_rate: f64,
+    /// Share of anomalies a responder acknowledged or resolved
+    pub action_rate: f64,
+    /// Median time from detection to acknowledgement (seconds)
+    pub median_ack_secs: Option<f64>,
+    /// Median time from detection to resolution, by anyone (seconds)
+    pub median_resolve_secs: Option<f64>,
+    /// First anomaly
+    pub first_seen: DateTime<Utc>,
+    /// Last anomaly
+    pub last_seen: DateTime<Utc>,
+    /// Anomalies per day no responder acted on
+    pub noise_score: f64,
+}
+
+/// Noisiest signatures of a time range
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct NoiseReport {
+    /// Start of the range
+    pub start: DateTime<Utc>,
+    /// End of the range
+    pub end: DateTime<Utc>,
+    /// Anomalies analyzed
+    pub anomalies: usize,
+    /// Distinct signatures
+    pub signatures: usize,
+    /// Whether more anomalies matched than were analyzed
+    pub truncated: bool,
+    /// Signatures by descending noise score
+    pub noisiest: Vec<SignatureNoise>,
+}
+
+/// Responses to one anomaly
+#[derive(Debug, Default)]
+struct Responses {
+    ack_secs: Option<f64>,
+    resolve_secs: Option<f64>,
+    resolved: bool,
+    auto_resolved: bool,
+    suppressed: bool,
+}
+
+impl Responses {
+    fn from_changes(anomaly: &AnomalyEvent, changes: &[AnomalyStateChange]) -> Self {
+        let since = |change: &AnomalyStateChange| {
+            (change.at - anomaly.timestamp).num_milliseconds().max(0) as f64 / 1000.0
+        };
+
+        let mut responses = Self::default();
+        for change in changes {
+            match change.to {
+                AnomalyState::Acknowledged if !change.is_automatic() => {
+                    responses.ack_secs.get_or_insert_with(|| since(change));
+                }
+                AnomalyState::Resolved => {
+                    responses.resolve_secs.get_or_insert_with(|| since(change));
+                    if change.is_automatic() {
+                        responses.auto_resolved = true;
+                    } else {
+                        responses.resolved = true;
+                    }
+                }
+                AnomalyState::Suppressed => responses.suppressed = true,
+                _ => {}
+            }
+        }
+        responses
+    }
+
+    fn acted_on(&self) -> bool {
+        self.ack_secs.is_some() || self.resolved
+    }
+}
+
+/// Report the noisiest anomaly signatures matching `query`
+///
+/// Analyzes at most `max_anomalies` anomalies, expired ones included, and
+/// returns the `limit` signatures with the highest noise score.
+pub async fn noise_report(
+    storage: &dyn Storage,
+    query: AnomalyQuery,
+    max_anomalies: usize,
+    limit: usize,
+) -> Result<NoiseReport> {
+    let range = query.time_range.clone();
+    let mut anomalies = storage
+        .query_anomalies(query.with_expired().with_limit(max_anomalies + 1))
+        .await?;
+    let truncated = anomalies.len() > max_anomalies;
+    anomalies.truncate(max_anomalies);
+    anomalies.retain(|anomaly| !simulation::is_test(anomaly));
+
+    // Anomalies never changed state have no history to fetch
+    let changed: Vec<Uuid> = anomalies
+        .iter()
+        .filter(|anomaly| anomaly.state_changed_at.is_some())
+        .map(|anomaly| anomaly.alert_id)
+        .collect();
+    let histories: HashMap<_, _> = futures::stream::iter(changed)
+        .map(|alert_id| async move {
+            let changes = storage.query_anomaly_state_changes(alert_id).await?;
+            Ok::<_, Error>((alert_id, changes))
+        })
+        .buffer_unordered(HISTORY_CONCURRENCY)
+        .try_collect()
+        .await?;
+
+    let mut groups: HashMap<&str, Vec<&AnomalyEvent>> = HashMap::new();
+    for anomaly in &anomalies {
+        groups
+            .entry(anomaly.signature_hash.as_str())
+            .or_default()
+            .push(anomaly);
+    }
+
+    let days = ((range.end - range.start).num_seconds() as f64 / 86_400.0).max(1.0 / 24.0);
+    let signatures = groups.len();
+    let mut noisiest: Vec<SignatureNoise> = groups
+        .into_values()
+        .map(|group| {
+            let responses: Vec<Responses> = group
+                .iter()
+                .map(|anomaly| {
+                    let changes = histories
+                        .get(&anomaly.alert_id)
+                        .map(Vec::as_slice)
+                        .unwrap_or_default();
+                    Responses::from_changes(anomaly, changes)
+                })
+                .collect();
+            signature_noise(&group, &responses, days)
+        })
+        .collect();
+    noisiest.sort_by(|a, b| {
+        b.noise_score
+            .total_cmp(&a.noise_score)
+            .then(b.anomalies.cmp(&a.anomalies))
+            .then_with(|| a.signature_hash.cmp(&b.signature_hash))
+    });
+    noisiest.truncate(limit);
+
+    Ok(NoiseReport {
+        start: range.start,
+        end: range.end,
+        anomalies: anomalies.len(),
+        signatures,
+        truncated,
+        noisiest,
+    })
+}
+
+fn signature_noise(group: &[&AnomalyEvent], responses: &[Responses], days: f64) -> SignatureNoise {
+    let first = group[0];
+    let count = group.len();
+    let share = |n: usize| n as f64 / count as f64;
+    let median = |mut secs: Vec<f64>| {
+        secs.sort_by(f64::total_cmp);
+        percentile(&secs, 0.5)
+    };
+
+    let acknowledged = responses.iter().filter(|r| r.ack_secs.is_some()).count();
+    let acted_on = responses.iter().filter(|r| r.acted_on()).count();
+    let per_day = count as f64 / days;
+    SignatureNoise {
+        signature_hash: first.signature_hash.clone(),
+        service: first.service_name.clone(),
+        model: first.model.clone(),
+        anomaly_type: first.anomaly_type.clone(),
+        metric: first.details.metric.clone(),
+        max_severity: group
+            .iter()
+            .map(|a| a.severity)
+            .max()
+            .unwrap_or(first.severity),
+        anomalies: count,
+        per_day,
+        acknowledged,
+        resolved: responses.iter().filter(|r| r.resolved).count(),
+        auto_resolved: responses.iter().filter(|r| r.auto_resolved).count(),
+        suppressed: responses.iter().filter(|r| r.suppressed).count(),
+        expired: group
+            .iter()
+            .filter(|a| a.state == AnomalyState::Expired)
+            .count(),
+        ack_rate: share(acknowledged),
+        action_rate: share(acted_on),
+        median_ack_secs: median(responses.iter().filter_map(|r| r.ack_secs).collect()),
+        median_resolve_secs: median(responses.iter().filter_map(|r| r.resolve_secs).collect()),
+        first_seen: group
+            .iter()
+            .map(|a| a.timestamp)
+            .min()
+            .unwrap_or(first.timestamp),
+        last_seen: group
+            .iter()
+            .map(|a| a.timestamp)
+            .max()
+            .unwrap_or(first.timestamp),
+        noise_score: per_day * (1.0 - share(acted_on)),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{lifecycle::transition_anomaly, memory::InMemoryStorage, query::TimeRange};
+    use chrono::Duration;
+    use llm_sentinel_core::lifecycle::{AUTO_RESOLVER, EXPIRER};
+    use llm_sentinel_fixtures::anomaly;
+
+    fn create_anomaly(service: &str, minutes_ago: i64) -> AnomalyEvent {
+        let mut anomaly = anomaly()
+            .severity(Severity::Medium)
+            .service(service)
+            .value(500.0)
+            .deviation_sigma(Some(4.0))
+            .build();
+        anomaly.timestamp = Utc::now() - Duration::minutes(minutes_ago);
+        anomaly
+    }
+
+    async fn transition(
+        storage: &InMemoryStorage,
+        anomaly: &AnomalyEvent,
+        to: AnomalyState,
+        actor: &str,
+    ) {
+        transition_anomaly(storage, anomaly.alert_id, to, Some(actor.to_string()), None)
+            .await
+            .unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_noise_report() {
+        let storage = InMemoryStorage::new();
+
+        // Flapping: fires often, auto-resolves or expires untouched
+        let flapping: Vec<_> = (1..=6).map(|i| create_anomaly("search", i * 10)).collect();
+        // Actionable: acknowledged and resolved by a responder
+        let actionable: Vec<_> = (1..=2)
+            .map(|i| create_anomaly("checkout", i * 10))
+            .collect();
+        let mut test = create_anomaly("checkout", 5);
+        test.labels
+            .insert(simulation::TEST_LABEL.to_string(), "true".to_string());
+        storage
+            .write_anomaly_batch(&[flapping.clone(), actionable.clone(), vec![test]].concat())
+            .await
+            .unwrap();
+
+        transition(
+            &storage,
+            &flapping[0],
+            AnomalyState::Resolved,
+            AUTO_RESOLVER,
+        )
+        .await;
+        transition(&storage, &flapping[1], AnomalyState::Expired, EXPIRER).await;
+        transition(&storage, &flapping[2], AnomalyState::Acknowledged, "alice").await;
+        for anomaly in &actionable {
+            transition(&storage, anomaly, AnomalyState::Acknowledged, "alice").await;
+            transition(&storage, anomaly, AnomalyState::Resolved, "alice").await;
+        }
+
+        let query = AnomalyQuery::new(TimeRange::last_hours(24));
+        let report = noise_report(&storage, query, 100, 10).await.unwrap();
+        assert_eq!(report.anomalies, 8);
+        assert_eq!(report.signatures, 2);
+        assert!(!report.truncated);
+
+        let noisiest = &report.noisiest[0];
+        assert_eq!(noisiest.service.as_str(), "search");
+        assert_eq!(noisiest.anomalies, 6);
+        assert_eq!(noisiest.acknowledged, 1);
+        assert_eq!(noisiest.auto_resolved, 1);
+        assert_eq!(noisiest.expired, 1);
+        assert_eq!(noisiest.resolved, 0);
+        assert!((noisiest.per_day - 6.0).abs() < 1e-9);
+        assert!((noisiest.noise_score - 5.0).abs() < 1e-9);
+
+        let quietest = &report.noisiest[1];
+        assert_eq!(quietest.service.as_str(), "checkout");
+        assert_eq!(quietest.ack_rate, 1.0);
+        assert_eq!(quietest.resolved, 2);
+        assert_eq!(quietest.noise_score, 0.0);
+        assert!(quietest.median_ack_secs.unwrap() >= 600.0);
+        assert!(quietest.median_resolve_secs.unwrap() >= quietest.median_ack_secs.unwrap());
+
+        let query = AnomalyQuery::new(TimeRange::last_hours(24));
+        let report = noise_report(&storage, query, 4, 1).await.unwrap();
+        assert!(report.truncated);
+        assert_eq!(report.noisiest.len(), 1);
+    }
+}
@@ -0,0 +1,337 @@
+//! Anomaly writes coupled to alert dispatch.
+//!
+//! An alert must never reference an anomaly the query API cannot find, so
+//! anomalies are written before their alerts are dispatched. The
+//! [`AnomalyWriter`] batches anomalies written concurrently into a single
+//! storage write and reports each caller's outcome. The [`Outbox`] records
+//! every anomaly between its write and the end of its dispatch; anomalies
+//! whose write failed stay in it, unwritten, until a retry succeeds, so
+//! their alerts are sent late instead of referencing a missing anomaly.
+
+use crate::Storage;
+use chrono::{DateTime, Utc};
+use llm_sentinel_core::{config::AnomalyWriterConfig, events::AnomalyEvent, Error, Result};
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+use tokio::sync::{mpsc, oneshot};
+use tracing::{debug, warn};
+use uuid::Uuid;
+
+/// Anomaly waiting for its batch to be written
+struct WriteRequest {
+    anomaly: AnomalyEvent,
+    written: oneshot::Sender<Result<()>>,
+}
+
+/// Batching anomaly writer
+///
+/// Writes are queued to a background task, which writes the anomalies
+/// queued within `linger_ms` of each other, up to `batch_size`, with one
+/// batch write. When a batch write fails, its anomalies are written one by
+/// one so a single rejected point does not fail the others.
+#[derive(Debug, Clone)]
+pub struct AnomalyWriter {
+    requests: mpsc::Sender<WriteRequest>,
+}
+
+impl AnomalyWriter {
+    /// Start the writer task
+    pub fn spawn(storage: Arc<dyn Storage>, config: &AnomalyWriterConfig) -> Self {
+        let batch_size = config.batch_size.max(1);
+        let (requests, queue) = mpsc::channel(batch_size * 8);
+        tokio::spawn(run_writer(
+            storage,
+            queue,
+            batch_size,
+            Duration::from_millis(config.linger_ms),
+        ));
+        Self { requests }
+    }
+
+    /// Write an anomaly, returning once its batch was written
+    pub async fn write(&self, anomaly: &AnomalyEvent) -> Result<()> {
+        let (written, outcome) = oneshot::channel();
+        let request = WriteRequest {
+            anomaly: anomaly.clone(),
+            written,
+        };
+        if self.requests.send(request).await.is_err() {
+            return Err(Error::storage("Anomaly writer stopped"));
+        }
+        outcome
+            .await
+            .unwrap_or_else(|_| Err(Error::storage("Anomaly writer stopped")))
+    }
+}
+
+async fn run_writer(
+    storage: Arc<dyn Storage>,
+    mut queue: mpsc::Receiver<WriteRequest>,
+    batch_size: usize,
+    linger: Duration,
+) {
+    while let Some(first) = queue.recv().await {
+        let deadline = tokio::time::Instant::now() + linger;
+        let mut batch = vec![first];
+        while batch.len() < batch_size {
+            match tokio::time::timeout_at(deadline, queue.recv()).await {
+                Ok(Some(request)) => batch.push(request),
+                _ => break,
+            }
+        }
+        write_batch(storage.as_ref(), batch).await;
+    }
+}
+
+async fn write_batch(storage: &dyn Storage, batch: Vec<WriteRequest>) {
+    metrics::histogram!("sentinel_anomaly_write_batch_size").record(batch.len() as f64);
+    let anomalies: Vec<AnomalyEvent> = batch.iter().map(|r| r.anomaly.clone()).collect();
+    match storage.write_anomaly_batch(&anomalies).await {
+        Ok(()) => {
+            for request in batch {
+                let _ = request.written.send(Ok(()));
+            }
+        }
+        Err(e) if batch.len() == 1 => {
+            let _ = batch.into_iter().next().unwrap().written.send(Err(e));
+        }
+        Err(e) => {
+            debug!(
+                anomalies = batch.len(),
+                "Anomaly batch write failed, writing one by one: {}", e
+            );
+            for request in batch {
+                let written = storage.write_anomaly(&request.anomaly).await;
+                let _ = request.written.send(written);
+            }
+        }
+    }
+}
+
+/// Whether an outbox entry was written to storage
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum OutboxStatus {
+    /// Write failed; retried before the anomaly is dispatched
+    Unwritten,
+    /// Written; being dispatched
+    Written,
+}
+
+impl OutboxStatus {
+    fn as_str(self) -> &'static str {
+        match self {
+            Self::Unwritten => "unwritten",
+            Self::Written => "written",
+        }
+    }
+}
+
+/// Anomaly in the outbox
+#[derive(Debug, Clone)]
+pub struct OutboxEntry {
+    /// Anomaly to dispatch
+    pub anomaly: AnomalyEvent,
+    /// Write status
+    pub status: OutboxStatus,
+    /// Failed write attempts
+    pub failed_writes: u32,
+    /// When the entry was recorded
+    pub recorded_at: DateTime<Utc>,
+    /// Insertion order
+    sequence: u64,
+}
+
+/// Anomalies between their write and the end of their dispatch
+///
+/// Holds at most `capacity` unwritten anomalies; the oldest one is dropped
+/// when another write fails with the outbox full.
+#[derive(Debug)]
+pub struct Outbox {
+    entries: Mutex<HashMap<Uuid, OutboxEntry>>,
+    capacity: usize,
+    next_sequence: AtomicU64,
+}
+
+impl Outbox {
+    /// Create an outbox holding up to `capacity` unwritten anomalies
+    pub fn new(capacity: usize) -> Self {
+        Self {
+            entries: Mutex::new(HashMap::new()),
+            capacity,
+            next_sequence: AtomicU64::new(0),
+        }
+    }
+
+    /// Record a written anomaly about to be dispatched
+    pub fn record(&self, anomaly: &AnomalyEvent) {
+        let mut entries = self.entries.lock().unwrap();
+        entries.insert(
+            anomaly.alert_id,
+            OutboxEntry {
+                anomaly: anomaly.clone(),
+                status: OutboxStatus::Written,
+                failed_writes: 0,
+                recorded_at: Utc::now(),
+                sequence: self.next_sequence.fetch_add(1, Ordering::Relaxed),
+            },
+        );
+        update_gauges(&entries);
+    }
+
+    /// Keep an anomaly whose write failed until a retry succeeds, counting
+    /// the failure
+    pub fn defer(&self, anomaly: &AnomalyEvent) {
+        let mut entries = self.entries.lock().unwrap();
+        let unwritten = entries
+            .values()
+            .filter(|entry| entry.status == OutboxStatus::Unwritten)
+            .count();
+        if unwritten >= self.capacity && !entries.contains_key(&anomaly.alert_id) {
+            let oldest = entries
+                .values()
+                .filter(|entry| entry.status == OutboxStatus::Unwritten)
+                .min_by_key(|entry| entry.sequence)
+                .map(|entry| entry.anomaly.alert_id);
+            if let Some(alert_id) = oldest {
+                entries.remove(&alert_id);
+                warn!(%alert_id, "Outbox full, dropping unwritten anomaly");
+                metrics::counter!("sentinel_outbox_dropped_total").increment(1);
+            }
+        }
+        let entry = entries.entry(anomaly.alert_id).or_insert_with(|| {
+            metrics::counter!("sentinel_outbox_deferred_total").increment(1);
+            OutboxEntry {
+                anomaly: anomaly.clone(),
+                status: OutboxStatus::Unwritten,
+                failed_writes: 0,
+                recorded_at: Utc::now(),
+                sequence: self.next_sequence.fetch_add(1, Ordering::Relaxed),
+            }
+        });
+        entry.failed_writes += 1;
+        update_gauges(&entries);
+    }
+
+    /// Unwritten anomalies, oldest first
+    pub fn unwritten(&self) -> Vec<AnomalyEvent> {
+        let entries = self.entries.lock().unwrap();
+        let mut unwritten: Vec<&OutboxEntry> = entries
+            .values()
+            .filter(|entry| entry.status == OutboxStatus::Unwritten)
+            .collect();
+        unwritten.sort_by_key(|entry| entry.sequence);
+        unwritten.into_iter().map(|e| e.anomaly.clone()).collect()
+    }
+
+    /// Mark an unwritten anomaly as written, returning whether it was in
+    /// the outbox
+    pub fn mark_written(&self, alert_id: Uuid) -> bool {
+        let mut entries = self.entries.lock().unwrap();
+        let Some(entry) = entries.get_mut(&alert_id) else {
+            return false;
+        };
+        entry.status = OutboxStatus::Written;
+        update_gauges(&entries);
+        true
+    }
+
+    /// Remove a dispatched anomaly
+    pub fn complete(&self, alert_id: Uuid) {
+        let mut entries = self.entries.lock().unwrap();
+        entries.remove(&alert_id);
+        update_gauges(&entries);
+    }
+
+    /// Entry of an anomaly, if it is in the outbox
+    pub fn get(&self, alert_id: Uuid) -> Option<OutboxEntry> {
+        self.entries.lock().unwrap().get(&alert_id).cloned()
+    }
+
+    /// Number of anomalies in the outbox
+    pub fn len(&self) -> usize {
+        self.entries.lock().unwrap().len()
+    }
+
+    /// Whether the outbox is empty
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+}
+
+fn update_gauges(entries: &HashMap<Uuid, OutboxEntry>) {
+    for status in [OutboxStatus::Unwritten, OutboxStatus::Written] {
+        let count = entries.values().filter(|e| e.status == status).count();
+        metrics::gauge!("sentinel_outbox_entries", "status" => status.as_str()).set(count as f64);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{memory::InMemoryStorage, query::AnomalyQuery};
+
+    fn anomaly() -> AnomalyEvent {
+        llm_sentinel_fixtures::anomaly()
+            .service("chat")
+            .value(900.0)
+            .deviation_sigma(Some(8.0))
+            .build()
+    }
+
+    #[tokio::test]
+    async fn test_writer_batches_concurrent_writes() {
+        let storage = Arc::new(InMemoryStorage::new());
+        let config = AnomalyWriterConfig {
+            linger_ms: 50,
+            ..Default::default()
+        };
+        let writer = AnomalyWriter::spawn(storage.clone(), &config);
+
+        let anomalies: Vec<AnomalyEvent> = (0..10).map(|_| anomaly()).collect();
+        let writes = anomalies.iter().map(|a| writer.write(a));
+        for written in futures::future::join_all(writes).await {
+            written.unwrap();
+        }
+
+        let mut query = AnomalyQuery::new(crate::query::TimeRange::last_hours(1));
+        query.limit = None;
+        assert_eq!(storage.query_anomalies(query).await.unwrap().len(), 10);
+        for anomaly in &anomalies {
+            assert!(storage
+                .get_anomaly(anomaly.alert_id)
+                .await
+                .unwrap()
+                .is_some());
+        }
+    }
+
+    #[test]
+    fn test_outbox() {
+        let outbox = Outbox::new(2);
+        let (first, second, third) = (anomaly(), anomaly(), anomaly());
+
+        outbox.record(&first);
+        assert_eq!(
+            outbox.get(first.alert_id).unwrap().status,
+            OutboxStatus::Written
+        );
+        outbox.complete(first.alert_id);
+        assert!(outbox.is_empty());
+
+        outbox.defer(&first);
+        outbox.defer(&second);
+        outbox.defer(&second);
+        assert_eq!(outbox.get(second.alert_id).unwrap().failed_writes, 2);
+        // Full: the oldest unwritten anomaly is dropped
+        outbox.defer(&third);
+        assert!(outbox.get(first.alert_id).is_none());
+        let unwritten: Vec<Uuid> = outbox.unwritten().iter().map(|a| a.alert_id).collect();
+        assert_eq!(unwritten, [second.alert_id, third.alert_id]);
+
+        assert!(outbox.mark_written(second.alert_id));
+        assert_eq!(outbox.unwritten().len(), 1);
+        assert!(!outbox.mark_written(first.alert_id));
+    }
+}
@@ -0,0 +1,1055 @@
+//! Prometheus remote-write storage backend.
+//!
+//! [`PrometheusStorage`] keeps running counters of telemetry, anomalies,
+//! anomaly state changes, annotations and alert deliveries and pushes them
+//! to a Prometheus remote-write receiver such as VictoriaMetrics or Mimir,
+//! so LLM metrics sit next to infrastructure metrics. Aggregate queries
+//! (anomaly heatmaps and cost reports) are answered with PromQL through the
+//! receiver's query API.
+//! Individual events are not stored, so event queries return nothing.
+
+use crate::{
+    annotation::AnnotationQuery,
+    cost::{CostDimension, CostReportQuery, CostReportRow, CostRollup},
+    delivery::DeliveryQuery,
+    incident::IncidentQuery,
+    query::{AnomalyQuery, HeatmapBucket, HeatmapQuery, TelemetryQuery},
+    stream::StreamOffset,
+    Storage,
+};
+use async_trait::async_trait;
+use chrono::{DateTime, TimeZone, Utc};
+use llm_sentinel_core::{
+    annotation::AnomalyAnnotation,
+    config::PrometheusStorageConfig,
+    deadline,
+    events::{AlertMetadata, AnomalyEvent, TelemetryEvent},
+    incident::Incident,
+    lifecycle::AnomalyStateChange,
+    retry::{retry, RetryError, RetryPolicy},
+    Error, Result,
+};
+use prost::Message;
+use reqwest::{
+    header::{HeaderMap, HeaderName, HeaderValue, AUTHORIZATION, CONTENT_ENCODING, CONTENT_TYPE},
+    Client,
+};
+use serde::Deserialize;
+use std::{
+    collections::{BTreeMap, BTreeSet},
+    sync::Mutex,
+    time::Duration,
+};
+use tracing::{debug, info, warn};
+use uuid::Uuid;
+
+/// Labels of one series, including `__name__`, ordered by name
+type SeriesKey = Vec<(String, String)>;
+
+/// Remote-write request (`prometheus.WriteRequest`)
+#[derive(Clone, PartialEq, prost::Message)]
+struct WriteRequest {
+    #[prost(message, repeated, tag = "1")]
+    timeseries: Vec<TimeSeries>,
+}
+
+/// Series with its samples (`prometheus.TimeSeries`)
+#[derive(Clone, PartialEq, prost::Message)]
+struct TimeSeries {
+    #[prost(message, repeated, tag = "1")]
+    labels: Vec<Label>,
+    #[prost(message, repeated, tag = "2")]
+    samples: Vec<Sample>,
+}
+
+/// Series label (`prometheus.Label`)
+#[derive(Clone, PartialEq, prost::Message)]
+struct Label {
+    #[prost(string, tag = "1")]
+    name: String,
+    #[prost(string, tag = "2")]
+    value: String,
+}
+
+/// Sample value at a millisecond timestamp (`prometheus.Sample`)
+#[derive(Clone, PartialEq, prost::Message)]
+struct Sample {
+    #[prost(double, tag = "1")]
+    value: f64,
+    #[prost(int64, tag = "2")]
+    timestamp: i64,
+}
+
+/// Encode `data` as a snappy block made of literals only
+///
+/// Remote write requires snappy-encoded bodies. Literal-only blocks are
+/// valid snappy that every decoder accepts; pushes are small, so skipping
+/// the compression itself costs little.
+fn snappy_literal(data: &[u8]) -> Vec<u8> {
+    let mut out = Vec::with_capacity(data.len() + data.len() / 65536 * 3 + 8);
+
+    // Uncompressed length as a varint
+    let mut len = data.len() as u64;
+    while len >= 0x80 {
+        out.push((len as u8 & 0x7f) | 0x80);
+        len >>= 7;
+    }
+    out.push(len as u8);
+
+    for chunk in data.chunks(1 << 16) {
+        let n = chunk.len() - 1;
+        if n < 60 {
+            out.push((n as u8) << 2);
+        } else if n < 256 {
+            out.push(60 << 2);
+            out.push(n as u8);
+        } else {
+            out.push(61 << 2);
+            out.extend_from_slice(&(n as u16).to_le_bytes());
+        }
+        out.extend_from_slice(chunk);
+    }
+    out
+}
+
+/// Prometheus label name for a telemetry label
+fn label_name(key: &str) -> String {
+    let key: String = key
+        .chars()
+        .map(|c| if c.is_ascii_alphanumeric() { c } else { '_' })
+        .collect();
+    format!("label_{}", key)
+}
+
+/// Escape a PromQL string literal
+fn escape_promql(value: &str) -> String {
+    value.replace('\\', r"\\").replace('"', r#"\""#)
+}
+
+/// PromQL label matchers, e.g. `{service="checkout"}`
+fn selector(matchers: &[(&str, String)]) -> String {
+    let matchers: Vec<_> = matchers
+        .iter()
+        .map(|(name, value)| format!(r#"{}="{}""#, name, escape_promql(value)))
+        .collect();
+    format!("{{{}}}", matchers.join(","))
+}
+
+/// Query API response
+#[derive(Debug, Deserialize)]
+struct PromResponse {
+    status: String,
+    #[serde(default)]
+    data: Option<PromData>,
+    #[serde(default)]
+    error: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+struct PromData {
+    result: Vec<PromSeries>,
+}
+
+/// Series of an instant (`value`) or range (`values`) query result
+#[derive(Debug, Deserialize)]
+struct PromSeries {
+    #[serde(default)]
+    metric: BTreeMap<String, String>,
+    #[serde(default)]
+    value: Option<(f64, String)>,
+    #[serde(default)]
+    values: Vec<(f64, String)>,
+}
+
+impl PromSeries {
+    /// All samples as (timestamp, value)
+    fn samples(self) -> impl Iterator<Item = (DateTime<Utc>, f64)> {
+        self.value
+            .into_iter()
+            .chain(self.values)
+            .filter_map(|(ts, value)| {
+                let ts = Utc.timestamp_millis_opt((ts * 1000.0) as i64).single()?;
+                Some((ts, value.parse().ok()?))
+            })
+    }
+}
+
+/// Storage backend pushing aggregates via Prometheus remote write
+#[derive(Debug)]
+pub struct PrometheusStorage {
+    client: Client,
+    config: PrometheusStorageConfig,
+    retry: RetryPolicy,
+    counters: Mutex<BTreeMap<SeriesKey, f64>>,
+}
+
+impl PrometheusStorage {
+    /// Create a backend and check that the query API is reachable
+    pub async fn new(config: PrometheusStorageConfig) -> Result<Self> {
+        info!(
+            "Connecting to Prometheus remote write at {}",
+            config.remote_write_url
+        );
+
+        let mut headers = HeaderMap::new();
+        if let Some(token) = &config.bearer_token {
+            let value = HeaderValue::from_str(&format!("Bearer {}", token))
+                .map_err(|_| Error::config("Invalid Prometheus bearer token"))?;
+            headers.insert(AUTHORIZATION, value);
+        }
+        for (name, value) in &config.headers {
+            let name = HeaderName::from_bytes(name.as_bytes())
+                .map_err(|_| Error::config(format!("Invalid header name '{}'", name)))?;
+            let value = HeaderValue::from_str(value)
+                .map_err(|_| Error::config(format!("Invalid value for header '{}'", name)))?;
+            headers.insert(name, value);
+        }
+
+        let client = Client::builder()
+            .timeout(Duration::from_secs(config.timeout_secs))
+            .default_headers(headers)
+            .build()
+            .map_err(|e| Error::config(format!("Failed to build HTTP client: {}", e)))?;
+
+        let storage = Self {
+            client,
+            retry: RetryPolicy::new(config.write_attempts)
+                .with_initial_delay(Duration::from_millis(200)),
+            config,
+            counters: Mutex::new(BTreeMap::new()),
+        };
+
+        storage
+            .health_check()
+            .await
+            .map_err(|e| Error::connection(format!("Prometheus query API unreachable: {}", e)))?;
+        info!("Connected to Prometheus successfully");
+
+        Ok(storage)
+    }
+
+    /// Full metric name
+    fn metric(&self, name: &str) -> String {
+        format!("{}_{}", self.config.metric_prefix, name)
+    }
+
+    /// Series key for a metric and its labels
+    fn series(&self, name: &str, labels: &[(String, String)]) -> SeriesKey {
+        let mut key = Vec::with_capacity(labels.len() + 1);
+        key.push(("__name__".to_string(), self.metric(name)));
+        key.extend(labels.iter().cloned());
+        key.sort();
+        key
+    }
+
+    /// Add to counters and build a request with the new totals of every
+    /// touched series
+    ///
+    /// Totals are cumulative, so a failed push is made up by the next one.
+    fn record(&self, updates: Vec<(SeriesKey, f64)>) -> WriteRequest {
+        let timestamp = Utc::now().timestamp_millis();
+        let mut counters = self.counters.lock().unwrap();
+        let mut touched = BTreeSet::new();
+        for (key, value) in updates {
+            *counters.entry(key.clone()).or_insert(0.0) += value;
+            touched.insert(key);
+        }
+
+        let timeseries = touched
+            .into_iter()
+            .map(|key| {
+                let value = counters[&key];
+                TimeSeries {
+                    labels: key
+                        .into_iter()
+                        .map(|(name, value)| Label { name, value })
+                        .collect(),
+                    samples: vec![Sample { value, timestamp }],
+                }
+            })
+            .collect();
+        WriteRequest { timeseries }
+    }
+
+    /// Push a write request, retrying transport errors and server errors
+    async fn push(&self, request: WriteRequest) -> Result<()> {
+        let body = snappy_literal(&request.encode_to_vec());
+
+        retry(&self.retry, |attempt| {
+            let request = self
+                .client
+                .post(&self.config.remote_write_url)
+                .header(CONTENT_TYPE, "application/x-protobuf")
+                .header(CONTENT_ENCODING, "snappy")
+                .header("X-Prometheus-Remote-Write-Version", "0.1.0")
+                .body(body.clone());
+
+            async move {
+                let response = match request.send().await {
+                    Ok(response) => response,
+                    Err(e) => {
+                        warn!(attempt, error = %e, "Remote write request failed");
+                        return Err(RetryError::Transient(e.to_string()));
+                    }
+                };
+
+                let status = response.status();
+                if status.is_success() {
+                    return Ok(());
+                }
+
+                let body = response.text().await.unwrap_or_default();
+                let message = format!("status {}: {}", status, body);
+                if status.is_client_error() && !matches!(status.as_u16(), 408 | 429) {
+                    return Err(RetryError::Permanent(message));
+                }
+                warn!(attempt, status = %status, "Remote write failed with retryable status");
+                Err(RetryError::Transient(message))
+            }
+        })
+        .await
+        .map_err(|failure| {
+            Error::storage(format!(
+                "Remote write failed after {} attempts: {}",
+                failure.attempts, failure.error
+            ))
+        })
+    }
+
+    /// Run a PromQL query against `/api/v1/{endpoint}`
+    async fn promql(&self, endpoint: &str, params: &[(&str, String)]) -> Result<Vec<PromSeries>> {
+        let url = format!(
+            "{}/api/v1/{}",
+            self.config.query_url.trim_end_matches('/'),
+            endpoint
+        );
+        let mut request = self.client.get(&url).query(params);
+        if let Some(remaining) = deadline::remaining() {
+            // Let Prometheus abandon the query as well
+            let timeout_ms = remaining.as_millis().max(1);
+            request = request
+                .query(&[("timeout", format!("{}ms", timeout_ms))])
+                .timeout(remaining);
+        }
+        let deadline_error = || Error::timeout("PromQL query exceeded the request deadline");
+        let response = request.send().await.map_err(|e| match e.is_timeout() {
+            true => deadline_error(),
+            false => Error::storage(format!("PromQL request failed: {}", e)),
+        })?;
+
+        let status = response.status();
+        let body: PromResponse = response.json().await.map_err(|e| match e.is_timeout() {
+            true => deadline_error(),
+            false => Error::storage(format!("Invalid PromQL response ({}): {}", status, e)),
+        })?;
+
+        match body {
+            PromResponse {
+                data: Some(data), ..
+            } if body.status == "success" => Ok(data.result),
+            PromResponse { error, .. } => Err(Error::storage(format!(
+                "PromQL query failed: {}",
+                error.unwrap_or_else(|| status.to_string())
+            ))),
+        }
+    }
+}
+
+/// Series labels shared by telemetry and anomaly counters
+fn event_labels(
+    service: &str,
+    model: &str,
+    environment: Option<&str>,
+    labels: &BTreeMap<String, String>,
+) -> Vec<(String, String)> {
+    let mut out = vec![
+        ("service".to_string(), service.to_string()),
+        ("model".to_string(), model.to_string()),
+    ];
+    if let Some(environment) = environment {
+        out.push(("environment".to_string(), environment.to_string()));
+    }
+    out.extend(
+        labels
+            .iter()
+            .map(|(key, value)| (label_name(key), value.clone())),
+    );
+    out
+}
+
+#[async_trait]
+impl Storage for PrometheusStorage {
+    async fn write_telemetry(&self, event: &TelemetryEvent) -> Result<()> {
+        self.write_telemetry_batch(std::slice::from_ref(event))
+            .await
+    }
+
+    async fn write_anomaly(&self, anomaly: &AnomalyEvent) -> Result<()> {
+        self.write_anomaly_batch(std::slice::from_ref(anomaly))
+            .await
+    }
+
+    async fn write_telemetry_batch(&self, events: &[TelemetryEvent]) -> Result<()> {
+        if events.is_empty() {
+            return Ok(());
+        }
+
+        let mut updates = Vec::with_capacity(events.len() * 7);
+        for event in events {
+            let labels = event_labels(
+                event.service_name.as_str(),
+                event.model.as_str(),
+                event.environment.as_ref().map(|e| e.as_str()),
+                &event.labels,
+            );
+            let values = [
+                ("requests_total", 1.0),
+                ("errors_total", if event.has_errors() { 1.0 } else { 0.0 }),
+                ("prompt_tokens_total", event.prompt.tokens as f64),
+                ("completion_tokens_total", event.response.tokens as f64),
+                ("cost_usd_total", event.cost_usd),
+                ("latency_ms_sum", event.latency_ms),
+                ("latency_ms_count", 1.0),
+            ];
+            for (name, value) in values {
+                updates.push((self.series(name, &labels), value));
+            }
+        }
+
+        self.push(self.record(updates)).await?;
+
+        debug!("Pushed {} telemetry events via remote write", events.len());
+        metrics::counter!("sentinel_storage_writes_total", "type" => "telemetry")
+            .increment(events.len() as u64);
+
+        Ok(())
+    }
+
+    async fn write_anomaly_batch(&self, anomalies: &[AnomalyEvent]) -> Result<()> {
+        if anomalies.is_empty() {
+            return Ok(());
+        }
+
+        let updates = anomalies
+            .iter()
+            .map(|anomaly| {
+                let mut labels = event_labels(
+                    anomaly.service_name.as_str(),
+                    anomaly.model.as_str(),
+                    anomaly.environment.as_ref().map(|e| e.as_str()),
+                    &anomaly.labels,
+                );
+                labels.push(("severity".to_string(), anomaly.severity.to_string()));
+                labels.push(("type".to_string(), anomaly.anomaly_type.to_string()));
+                (self.series("anomalies_total", &labels), 1.0)
+            })
+            .collect();
+
+        self.push(self.record(updates)).await?;
+
+        debug!("Pushed {} anomalies via remote write", anomalies.len());
+        metrics::counter!("sentinel_storage_writes_total", "type" => "anomaly")
+            .increment(anomalies.len() as u64);
+
+        Ok(())
+    }
+
+    async fn query_telemetry(&self, _query: TelemetryQuery) -> Result<Vec<TelemetryEvent>> {
+        debug!("Prometheus storage keeps aggregates only; no telemetry events to return");
+        Ok(Vec::new())
+    }
+
+    async fn query_anomalies(&self, _query: AnomalyQuery) -> Result<Vec<AnomalyEvent>> {
+        debug!("Prometheus storage keeps aggregates only; no anomaly events to return");
+        Ok(Vec::new())
+    }
+
+    async fn get_anomaly(&self, _alert_id: Uuid) -> Result<Option<AnomalyEvent>> {
+        debug!("Prometheus storage keeps aggregates only; no anomaly events to return");
+        Ok(None)
+    }
+
+    async fn write_anomaly_state_changes(&self, changes: &[AnomalyStateChange]) -> Result<()> {
+        if changes.is_empty() {
+            return Ok(());
+        }
+
+        let updates = changes
+            .iter()
+            .map(|change| {
+                let labels = vec![
+                    ("from".to_string(), change.from.to_string()),
+                    ("to".to_string(), change.to.to_string()),
+                ];
+                (self.series("anomaly_state_changes_total", &labels), 1.0)
+            })
+            .collect();
+
+        self.push(self.record(updates)).await?;
+
+        metrics::counter!("sentinel_storage_writes_total", "type" => "anomaly_state")
+            .increment(changes.len() as u64);
+
+        Ok(())
+    }
+
+    async fn query_anomaly_state_changes(
+        &self,
+        _alert_id: Uuid,
+    ) -> Result<Vec<AnomalyStateChange>> {
+        debug!("Prometheus storage keeps aggregates only; no state changes to return");
+        Ok(Vec::new())
+    }
+
+    async fn write_anomaly_annotations(&self, annotations: &[AnomalyAnnotation]) -> Result<()> {
+        if annotations.is_empty() {
+            return Ok(());
+        }
+
+        let updates = annotations
+            .iter()
+            .map(|annotation| {
+                let labels = vec![("kind".to_string(), annotation.kind.to_string())];
+                (self.series("anomaly_annotations_total", &labels), 1.0)
+            })
+            .collect();
+
+        self.push(self.record(updates)).await?;
+
+        metrics::counter!("sentinel_storage_writes_total", "type" => "anomaly_annotation")
+            .increment(annotations.len() as u64);
+
+        Ok(())
+    }
+
+    async fn query_anomaly_annotations(
+        &self,
+        _query: AnnotationQuery,
+    ) -> Result<Vec<AnomalyAnnotation>> {
+        debug!("Prometheus storage keeps aggregates only; no annotations to return");
+        Ok(Vec::new())
+    }
+
+    async fn write_stream_offset(&self, offset: &StreamOffset) -> Result<()> {
+        debug!(
+            consumer_id = %offset.consumer_id,
+            "Prometheus storage keeps aggregates only; stream offset not stored"
+        );
+        Ok(())
+    }
+
+    async fn get_stream_offset(&self, _consumer_id: &str) -> Result<Option<StreamOffset>> {
+        Ok(None)
+    }
+
+    async fn anomaly_heatmap(&self, query: HeatmapQuery) -> Result<Vec<HeatmapBucket>> {
+        let step = query.interval_secs.max(1);
+        let group = query.group_by.tag();
+
+        let mut matchers = Vec::new();
+        if let Some(service) = &query.service {
+            matchers.push(("service", service.as_str().to_string()));
+        }
+        if let Some(severity) = query.severity {
+            matchers.push(("severity", severity.to_string()));
+        }
+        let promql = format!(
+            "sum by ({}) (increase({}{}[{}s]))",
+            group,
+            self.metric("anomalies_total"),
+            selector(&matchers),
+            step
+        );
+
+        // Each sample counts the window ending at its timestamp
+        let start = query.time_range.start + chrono::Duration::seconds(step as i64);
+        let series = self
+            .promql(
+                "query_range",
+                &[
+                    ("query", promql),
+                    ("start", start.timestamp().to_string()),
+                    ("end", query.time_range.end.timestamp().to_string()),
+                    ("step", format!("{}s", step)),
+                ],
+            )
+            .await?;
+
+        let mut buckets: Vec<HeatmapBucket> = series
+            .into_iter()
+            .flat_map(|series| {
+                let group = series.metric.get(group).cloned().unwrap_or_default();
+                series
+                    .samples()
+                    .map(move |(end, count)| HeatmapBucket {
+                        start: end - chrono::Duration::seconds(step as i64),
+                        group: group.clone(),
+                        count: count.round() as u64,
+                    })
+                    .collect::<Vec<_>>()
+            })
+            .filter(|bucket| bucket.count > 0)
+            .collect();
+        buckets.sort_by(|a, b| a.start.cmp(&b.start).then_with(|| a.group.cmp(&b.group)));
+
+        metrics::counter!("sentinel_storage_queries_total", "type" => "heatmap").increment(1);
+
+        Ok(buckets)
+    }
+
+    /// Cost is derived from the telemetry counters, so rollups are not pushed
+    async fn write_cost_rollups(&self, rollups: &[CostRollup]) -> Result<()> {
+        debug!(
+            "Skipping {} cost rollups; Prometheus storage reports cost from telemetry counters",
+            rollups.len()
+        );
+        Ok(())
+    }
+
+    async fn query_cost_report(&self, query: CostReportQuery) -> Result<Vec<CostReportRow>> {
+        if let Some(dimension) = query
+            .group_by
+            .iter()
+            .find(|d| !matches!(d, CostDimension::Service | CostDimension::Model))
+        {
+            return Err(Error::validation(format!(
+                "Prometheus storage cannot report cost by {}; group by service or model",
+                dimension
+            )));
+        }
+
+        let group_by: Vec<String> = query.group_by.iter().map(|d| d.column()).collect();
+        let mut matchers = Vec::new();
+        if let Some(service) = &query.service {
+            matchers.push(("service", service.as_str().to_string()));
+        }
+        if let Some(model) = &query.model {
+            matchers.push(("model", model.as_str().to_string()));
+        }
+
+        let window = match query.interval_secs {
+            Some(interval) => interval.max(1),
+            None => (query.time_range.end - query.time_range.start)
+                .num_seconds()
+                .max(1) as u64,
+        };
+        let range_params = |promql: String| match query.interval_secs {
+            Some(_) => (
+                "query_range",
+                vec![
+                    ("query", promql),
+                    (
+                        "start",
+                        (query.time_range.start + chrono::Duration::seconds(window as i64))
+                            .timestamp()
+                            .to_string(),
+                    ),
+                    ("end", query.time_range.end.timestamp().to_string()),
+                    ("step", format!("{}s", window)),
+                ],
+            ),
+            None => (
+                "query",
+                vec![
+                    ("query", promql),
+                    ("time", query.time_range.end.timestamp().to_string()),
+                ],
+            ),
+        };
+
+        let metrics = [
+            "cost_usd_total",
+            "requests_total",
+            "prompt_tokens_total",
+            "completion_tokens_total",
+        ];
+        let results = futures::future::try_join_all(metrics.iter().map(|name| {
+            let promql = format!(
+                "sum by ({}) (increase({}{}[{}s]))",
+                group_by.join(","),
+                self.metric(name),
+                selector(&matchers),
+                window
+            );
+            let (endpoint, params) = range_params(promql);
+            async move { self.promql(endpoint, &params).await }
+        }))
+        .await?;
+
+        let mut rows: BTreeMap<(Option<DateTime<Utc>>, BTreeMap<String, String>), CostReportRow> =
+            BTreeMap::new();
+        for (name, series) in metrics.iter().zip(results) {
+            for series in series {
+                let group: BTreeMap<String, String> = query
+                    .group_by
+                    .iter()
+                    .map(|d| {
+                        let value = series.metric.get(&d.column()).cloned().unwrap_or_default();
+                        (d.to_string(), value)
+                    })
+                    .collect();
+                let interval = query.interval_secs.is_some();
+                for (ts, value) in series.samples() {
+                    let bucket_start =
+                        interval.then(|| ts - chrono::Duration::seconds(window as i64));
+                    let row = rows
+                        .entry((bucket_start, group.clone()))
+                        .or_insert_with(|| CostReportRow {
+                            bucket_start,
+                            group: group.clone(),
+                            cost_usd: 0.0,
+                            requests: 0,
+                            prompt_tokens: 0,
+                            completion_tokens: 0,
+                        });
+                    match *name {
+                        "cost_usd_total" => row.cost_usd = value,
+                        "requests_total" => row.requests = value.round() as u64,
+                        "prompt_tokens_total" => row.prompt_tokens = value.round() as u64,
+                        _ => row.completion_tokens = value.round() as u64,
+                    }
+                }
+            }
+        }
+
+        metrics::counter!("sentinel_storage_queries_total", "type" => "cost").increment(1);
+
+        Ok(rows.into_values().collect())
+    }
+
+    async fn write_alert_deliveries(&self, deliveries: &[AlertMetadata]) -> Result<()> {
+        if deliveries.is_empty() {
+            return Ok(());
+        }
+
+        let updates = deliveries
+            .iter()
+            .map(|delivery| {
+                let labels = vec![
+                    ("alerter".to_string(), delivery.alerter.clone()),
+                    ("severity".to_string(), delivery.severity.to_string()),
+                    ("status".to_string(), delivery.status.to_string()),
+                ];
+                (self.series("alert_deliveries_total", &labels), 1.0)
+            })
+            .collect();
+
+        self.push(self.record(updates)).await?;
+
+        metrics::counter!("sentinel_storage_writes_total", "type" => "alert_delivery")
+            .increment(deliveries.len() as u64);
+
+        Ok(())
+    }
+
+    async fn query_alert_deliveries(&self, _query: DeliveryQuery) -> Result<Vec<AlertMetadata>> {
+        debug!("Prometheus storage keeps aggregates only; no alert deliveries to return");
+        Ok(Vec::new())
+    }
+
+    async fn write_incident(&self, incident: &Incident) -> Result<()> {
+        debug!(
+            incident_id = %incident.incident_id,
+            "Prometheus storage keeps aggregates only; incident not stored"
+        );
+        Ok(())
+    }
+
+    async fn query_incidents(&self, _query: IncidentQuery) -> Result<Vec<Incident>> {
+        debug!("Prometheus storage keeps aggregates only; no incidents to return");
+        Ok(Vec::new())
+    }
+
+    async fn write_redetected_anomalies(
+        &self,
+        job_id: Uuid,
+        anomalies: &[AnomalyEvent],
+    ) -> Result<()> {
+        if anomalies.is_empty() {
+            return Ok(());
+        }
+
+        let updates = anomalies
+            .iter()
+            .map(|anomaly| {
+                let labels = vec![
+                    ("job_id".to_string(), job_id.to_string()),
+                    ("service".to_string(), anomaly.service_name.as_str().to_string()),
+                    ("model".to_string(), anomaly.model.as_str().to_string()),
+                    ("severity".to_string(), anomaly.severity.to_string()),
+                    ("method".to_string(), anomaly.detection_method.to_string()),
+                ];
+                (self.series("redetected_anomalies_total", &labels), 1.0)
+            })
+            .collect();
+
+        self.push(self.record(updates)).await?;
+
+        metrics::counter!("sentinel_storage_writes_total", "type" => "redetected_anomaly")
+            .increment(anomalies.len() as u64);
+
+        Ok(())
+    }
+
+    async fn health_check(&self) -> Result<()> {
+        self.promql("query", &[("query", "vector(1)".to_string())])
+            .await
+            .map(|_| ())
+    }
+
+    fn upserts_by_id(&self) -> bool {
+        // Telemetry is pushed as counters, so every write adds to them
+        false
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::query::{HeatmapGroupBy, TimeRange};
+    use llm_sentinel_fixtures::event;
+    use wiremock::{matchers::*, Mock, MockServer, ResponseTemplate};
+
+    /// Decode a literal-only snappy block
+    fn unsnappy(data: &[u8]) -> Vec<u8> {
+        let mut pos = 0;
+        while data[pos] & 0x80 != 0 {
+            pos += 1;
+        }
+        pos += 1;
+
+        let mut out = Vec::new();
+        while pos < data.len() {
+            let tag = data[pos] >> 2;
+            pos += 1;
+            let len = match tag {
+                60 => {
+                    pos += 1;
+                    data[pos - 1] as usize + 1
+                }
+                61 => {
+                    pos += 2;
+                    u16::from_le_bytes([data[pos - 2], data[pos - 1]]) as usize + 1
+                }
+                n => n as usize + 1,
+            };
+            out.extend_from_slice(&data[pos..pos + len]);
+            pos += len;
+        }
+        out
+    }
+
+    async fn create_storage(server: &MockServer) -> PrometheusStorage {
+        Mock::given(method("GET"))
+            .and(path("/api/v1/query"))
+            .and(query_param("query", "vector(1)"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({
+                "status": "success",
+                "data": {"resultType": "vector", "result": []}
+            })))
+            .mount(server)
+            .await;
+
+        PrometheusStorage::new(PrometheusStorageConfig {
+            remote_write_url: format!("{}/api/v1/write", server.uri()),
+            query_url: server.uri(),
+            bearer_token: Some("secret".to_string()),
+            headers: BTreeMap::from([("X-Scope-OrgID".to_string(), "team-a".to_string())]),
+            metric_prefix: "llm_sentinel".to_string(),
+            timeout_secs: 5,
+            write_attempts: 1,
+        })
+        .await
+        .unwrap()
+    }
+
+    fn create_event(cost: f64) -> TelemetryEvent {
+        event().service("checkout").cost_usd(cost).build()
+    }
+
+    #[test]
+    fn test_snappy_literal() {
+        assert_eq!(snappy_literal(b"hello"), b"\x05\x10hello");
+
+        let data = vec![7u8; 300];
+        let encoded = snappy_literal(&data);
+        assert_eq!(&encoded[..5], &[0xAC, 0x02, 61 << 2, 0x2B, 0x01]);
+        assert_eq!(unsnappy(&encoded), data);
+
+        let data = vec![1u8; 70_000];
+        assert_eq!(unsnappy(&snappy_literal(&data)), data);
+    }
+
+    #[test]
+    fn test_selector() {
+        assert_eq!(selector(&[]), "{}");
+        assert_eq!(
+            selector(&[
+                ("service", r#"a"b"#.to_string()),
+                ("severity", "high".to_string())
+            ]),
+            r#"{service="a\"b",severity="high"}"#
+        );
+        assert_eq!(label_name("team.name-x"), "label_team_name_x");
+    }
+
+    #[tokio::test]
+    async fn test_remote_write() {
+        let server = MockServer::start().await;
+        Mock::given(method("POST"))
+            .and(path("/api/v1/write"))
+            .and(header("Content-Encoding", "snappy"))
+            .and(header("Authorization", "Bearer secret"))
+            .and(header("X-Scope-OrgID", "team-a"))
+            .respond_with(ResponseTemplate::new(204))
+            .expect(2)
+            .mount(&server)
+            .await;
+        let storage = create_storage(&server).await;
+
+        storage
+            .write_telemetry_batch(&[create_event(0.01), create_event(0.02)])
+            .await
+            .unwrap();
+        storage.write_telemetry(&create_event(0.03)).await.unwrap();
+
+        let requests = server.received_requests().await.unwrap();
+        let last = requests
+            .iter()
+            .rfind(|r| r.method.as_str() == "POST")
+            .unwrap();
+        let request = WriteRequest::decode(unsnappy(&last.body).as_slice()).unwrap();
+
+        let value = |name: &str| {
+            request
+                .timeseries
+                .iter()
+                .find(|ts| {
+                    ts.labels
+                        .iter()
+                        .any(|l| l.name == "__name__" && l.value == name)
+                })
+                .map(|ts| ts.samples[0].value)
+                .unwrap()
+        };
+        assert_eq!(value("llm_sentinel_requests_total"), 3.0);
+        assert_eq!(value("llm_sentinel_prompt_tokens_total"), 30.0);
+        assert!((value("llm_sentinel_cost_usd_total") - 0.06).abs() < 1e-9);
+        assert_eq!(value("llm_sentinel_errors_total"), 0.0);
+
+        let series = &request.timeseries[0];
+        assert!(series
+            .labels
+            .iter()
+            .any(|l| l.name == "service" && l.value == "checkout"));
+    }
+
+    #[tokio::test]
+    async fn test_rejected_write_is_not_retried() {
+        let server = MockServer::start().await;
+        Mock::given(method("POST"))
+            .and(path("/api/v1/write"))
+            .respond_with(ResponseTemplate::new(400).set_body_string("out of order sample"))
+            .expect(1)
+            .mount(&server)
+            .await;
+        let mut storage = create_storage(&server).await;
+        storage.retry = RetryPolicy::new(3).with_initial_delay(Duration::from_millis(1));
+
+        let error = storage
+            .write_telemetry(&create_event(0.01))
+            .await
+            .unwrap_err();
+        assert!(error.to_string().contains("out of order sample"));
+    }
+
+    #[tokio::test]
+    async fn test_anomaly_heatmap() {
+        let server = MockServer::start().await;
+        let storage = create_storage(&server).await;
+
+        let start = Utc.timestamp_opt(1_700_000_000, 0).unwrap();
+        Mock::given(method("GET"))
+            .and(path("/api/v1/query_range"))
+            .and(query_param(
+                "query",
+                r#"sum by (service) (increase(llm_sentinel_anomalies_total{severity="high"}[3600s]))"#,
+            ))
+            .and(query_param("step", "3600s"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({
+                "status": "success",
+                "data": {
+                    "resultType": "matrix",
+                    "result": [{
+                        "metric": {"service": "checkout"},
+                        "values": [[1_700_003_600.0, "2.4"], [1_700_007_200.0, "0"]]
+                    }]
+                }
+            })))
+            .mount(&server)
+            .await;
+
+        let query = HeatmapQuery::new(
+            TimeRange::new(start, start + chrono::Duration::hours(2)),
+            3600,
+            HeatmapGroupBy::Service,
+        )
+        .with_severity(llm_sentinel_core::types::Severity::High);
+        let buckets = storage.anomaly_heatmap(query).await.unwrap();
+
+        assert_eq!(
+            buckets,
+            vec![HeatmapBucket {
+                start,
+                group: "checkout".to_string(),
+                count: 2,
+            }]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_query_bounded_by_request_deadline() {
+        let server = MockServer::start().await;
+        let storage = create_storage(&server).await;
+        Mock::given(method("GET"))
+            .and(path("/api/v1/query_range"))
+            .respond_with(
+                ResponseTemplate::new(200)
+                    .set_body_json(serde_json::json!({"status": "success", "data": {"result": []}}))
+                    .set_delay(Duration::from_secs(5)),
+            )
+            .mount(&server)
+            .await;
+
+        let query = HeatmapQuery::new(TimeRange::last_hours(1), 60, HeatmapGroupBy::Service);
+        let deadline = tokio::time::Instant::now() + Duration::from_millis(200);
+        let result = deadline::scope(deadline, storage.anomaly_heatmap(query)).await;
+        assert!(matches!(result, Err(Error::Timeout(_))));
+
+        let requests = server.received_requests().await.unwrap();
+        let request = requests
+            .iter()
+            .find(|r| r.url.path() == "/api/v1/query_range")
+            .unwrap();
+        let timeout = request
+            .url
+            .query_pairs()
+            .find(|(name, _)| name == "timeout")
+            .map(|(_, value)| value.into_owned())
+            .unwrap();
+        assert!(timeout.ends_with("ms"));
+    }
+
+    #[tokio::test]
+    async fn test_cost_report_rejects_user_dimension() {
+        let server = MockServer::start().await;
+        let storage = create_storage(&server).await;
+
+        let mut query = CostReportQuery::new(TimeRange::last_hours(1));
+        query.group_by = vec![CostDimension::User];
+        assert!(matches!(
+            storage.query_cost_report(query).await,
+            Err(Error::Validation(_))
+        ));
+    }
+}
@@ -1,11 +1,19 @@
 //! Query definitions for storage backends.
 
+use base64::{engine::general_purpose::URL_SAFE_NO_PAD, Engine};
 use chrono::{DateTime, Utc};
-use llm_sentinel_core::types::{AnomalyType, ModelId, ServiceId, Severity};
+use llm_sentinel_core::{
+    events::{AnomalyEvent, TelemetryEvent},
+    labels::Labels,
+    types::{AnomalyType, Environment, ModelId, ServiceId, Severity},
+    Error, Result,
+};
 use serde::{Deserialize, Serialize};
+use std::cmp::Ordering;
+use uuid::Uuid;
 
 /// Time range for queries
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct TimeRange {
     /// Start time (inclusive)
     pub start: DateTime<Utc>,
@@ -58,6 +66,14 @@ pub struct TelemetryQuery {
     /// Filter by model
     pub model: Option<ModelId>,
 
+    /// Filter by environment
+    #[serde(default)]
+    pub environment: Option<Environment>,
+
+    /// Filter by labels (all must match)
+    #[serde(default)]
+    pub labels: Labels,
+
     /// Limit number of results
     pub limit: Option<usize>,
 
@@ -66,6 +82,14 @@ pub struct TelemetryQuery {
 
     /// Sort order (true = ascending, false = descending)
     pub ascending: bool,
+
+    /// Sort field
+    #[serde(default)]
+    pub sort_by: TelemetrySort,
+
+    /// Continue after the last item of a previous page
+    #[serde(default)]
+    pub cursor: Option<Cursor>,
 }
 
 impl TelemetryQuery {
@@ -75,9 +99,13 @@ impl TelemetryQuery {
             time_range,
             service: None,
             model: None,
+            environment: None,
+            labels: Labels::new(),
             limit: Some(1000), // Default limit
             offset: None,
             ascending: false, // Default: newest first
+            sort_by: TelemetrySort::default(),
+            cursor: None,
         }
     }
 
@@ -93,6 +121,24 @@ impl TelemetryQuery {
         self
     }
 
+    /// Filter by environment
+    pub fn with_environment(mut self, environment: Environment) -> Self {
+        self.environment = Some(environment);
+        self
+    }
+
+    /// Filter by label
+    pub fn with_label(mut self, key: impl Into<String>, value: impl Into<String>) -> Self {
+        self.labels.insert(key.into(), value.into());
+        self
+    }
+
+    /// Filter by labels
+    pub fn with_labels(mut self, labels: Labels) -> Self {
+        self.labels.extend(labels);
+        self
+    }
+
     /// Set limit
     pub fn with_limit(mut self, limit: usize) -> Self {
         self.limit = Some(limit);
@@ -116,6 +162,18 @@ impl TelemetryQuery {
         self.ascending = false;
         self
     }
+
+    /// Set sort field
+    pub fn sort_by(mut self, sort: TelemetrySort) -> Self {
+        self.sort_by = sort;
+        self
+    }
+
+    /// Continue after a cursor
+    pub fn with_cursor(mut self, cursor: Cursor) -> Self {
+        self.cursor = Some(cursor);
+        self
+    }
 }
 
 /// Query for anomaly events
@@ -130,6 +188,10 @@ pub struct AnomalyQuery {
     /// Filter by model
     pub model: Option<ModelId>,
 
+    /// Filter by environment
+    #[serde(default)]
+    pub environment: Option<Environment>,
+
     /// Filter by severity
     pub severity: Option<Severity>,
 
@@ -139,6 +201,10 @@ pub struct AnomalyQuery {
     /// Minimum confidence threshold
     pub min_confidence: Option<f64>,
 
+    /// Filter by labels (all must match)
+    #[serde(default)]
+    pub labels: Labels,
+
     /// Limit number of results
     pub limit: Option<usize>,
 
@@ -147,6 +213,18 @@ pub struct AnomalyQuery {
 
     /// Sort order
     pub ascending: bool,
+
+    /// Sort field
+    #[serde(default)]
+    pub sort_by: AnomalySort,
+
+    /// Continue after the last item of a previous page
+    #[serde(default)]
+    pub cursor: Option<Cursor>,
+
+    /// Include expired anomalies
+    #[serde(default)]
+    pub include_expired: bool,
 }
 
 impl AnomalyQuery {
@@ -156,12 +234,17 @@ impl AnomalyQuery {
             time_range,
             service: None,
             model: None,
+            environment: None,
             severity: None,
             anomaly_type: None,
             min_confidence: None,
+            labels: Labels::new(),
             limit: Some(1000),
             offset: None,
             ascending: false,
+            sort_by: AnomalySort::default(),
+            cursor: None,
+            include_expired: false,
         }
     }
 
@@ -177,6 +260,18 @@ impl AnomalyQuery {
         self
     }
 
+    /// Filter by environment
+    pub fn with_environment(mut self, environment: Environment) -> Self {
+        self.environment = Some(environment);
+        self
+    }
+
+    /// Include expired anomalies, which are left out by default
+    pub fn with_expired(mut self) -> Self {
+        self.include_expired = true;
+        self
+    }
+
     /// Filter by severity
     pub fn with_severity(mut self, severity: Severity) -> Self {
         self.severity = Some(severity);
@@ -195,11 +290,267 @@ impl AnomalyQuery {
         self
     }
 
+    /// Filter by label
+    pub fn with_label(mut self, key: impl Into<String>, value: impl Into<String>) -> Self {
+        self.labels.insert(key.into(), value.into());
+        self
+    }
+
+    /// Filter by labels
+    pub fn with_labels(mut self, labels: Labels) -> Self {
+        self.labels.extend(labels);
+        self
+    }
+
     /// Set limit
     pub fn with_limit(mut self, limit: usize) -> Self {
         self.limit = Some(limit);
         self
     }
+
+    /// Set offset
+    pub fn with_offset(mut self, offset: usize) -> Self {
+        self.offset = Some(offset);
+        self
+    }
+
+    /// Set sort order
+    pub fn ascending(mut self) -> Self {
+        self.ascending = true;
+        self
+    }
+
+    /// Set sort field
+    pub fn sort_by(mut self, sort: AnomalySort) -> Self {
+        self.sort_by = sort;
+        self
+    }
+
+    /// Continue after a cursor
+    pub fn with_cursor(mut self, cursor: Cursor) -> Self {
+        self.cursor = Some(cursor);
+        self
+    }
+}
+
+/// Sort field for telemetry queries
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum TelemetrySort {
+    /// Event time
+    #[default]
+    Timestamp,
+    /// Request latency
+    Latency,
+    /// Request cost
+    Cost,
+    /// Prompt plus completion tokens
+    Tokens,
+}
+
+impl TelemetrySort {
+    /// Parse a sort field name, accepting the event field names as aliases
+    pub fn parse(name: &str) -> Option<Self> {
+        match name {
+            "timestamp" => Some(Self::Timestamp),
+            "latency" | "latency_ms" => Some(Self::Latency),
+            "cost" | "cost_usd" => Some(Self::Cost),
+            "tokens" | "total_tokens" => Some(Self::Tokens),
+            _ => None,
+        }
+    }
+
+    /// Sort key of an event
+    pub fn value(&self, event: &TelemetryEvent) -> f64 {
+        match self {
+            Self::Timestamp => event.timestamp.timestamp_micros() as f64,
+            Self::Latency => event.latency_ms,
+            Self::Cost => event.cost_usd,
+            Self::Tokens => event.total_tokens() as f64,
+        }
+    }
+
+    /// Cursor pointing at an event
+    pub fn cursor(&self, event: &TelemetryEvent) -> Cursor {
+        Cursor::new(self.value(event), event.event_id)
+    }
+}
+
+/// Sort field for anomaly queries
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum AnomalySort {
+    /// Detection time
+    #[default]
+    Timestamp,
+    /// Detection confidence
+    Confidence,
+    /// Severity level
+    Severity,
+}
+
+impl AnomalySort {
+    /// Parse a sort field name
+    pub fn parse(name: &str) -> Option<Self> {
+        match name {
+            "timestamp" => Some(Self::Timestamp),
+            "confidence" => Some(Self::Confidence),
+            "severity" => Some(Self::Severity),
+            _ => None,
+        }
+    }
+
+    /// Sort key of an anomaly
+    pub fn value(&self, anomaly: &AnomalyEvent) -> f64 {
+        match self {
+            Self::Timestamp => anomaly.timestamp.timestamp_micros() as f64,
+            Self::Confidence => anomaly.confidence,
+            Self::Severity => match anomaly.severity {
+                Severity::Low => 0.0,
+                Severity::Medium => 1.0,
+                Severity::High => 2.0,
+                Severity::Critical => 3.0,
+            },
+        }
+    }
+
+    /// Cursor pointing at an anomaly
+    pub fn cursor(&self, anomaly: &AnomalyEvent) -> Cursor {
+        Cursor::new(self.value(anomaly), anomaly.alert_id)
+    }
+}
+
+/// Continuation token for keyset pagination
+///
+/// A cursor records the sort key and ID of the last item on a page; the next
+/// page starts strictly after it in query order. Unlike offsets, cursors stay
+/// stable while new events arrive and do not require the backend to skip
+/// over earlier pages. Clients treat the encoded form as opaque.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct Cursor {
+    /// Sort key of the last item
+    #[serde(rename = "v")]
+    pub value: f64,
+    /// ID of the last item, breaking ties between equal sort keys
+    #[serde(rename = "id")]
+    pub id: Uuid,
+}
+
+impl Cursor {
+    /// Create a cursor
+    pub fn new(value: f64, id: Uuid) -> Self {
+        Self { value, id }
+    }
+
+    /// Encode as an opaque URL-safe token
+    pub fn encode(&self) -> String {
+        // Serializing two plain fields cannot fail
+        URL_SAFE_NO_PAD.encode(serde_json::to_vec(self).unwrap_or_default())
+    }
+
+    /// Decode a token produced by [`Cursor::encode`]
+    pub fn decode(token: &str) -> Result<Self> {
+        URL_SAFE_NO_PAD
+            .decode(token)
+            .ok()
+            .and_then(|bytes| serde_json::from_slice(&bytes).ok())
+            .ok_or_else(|| Error::validation("Invalid cursor"))
+    }
+
+    /// Compare a sort key and ID against the cursor position
+    pub fn compare(&self, value: f64, id: Uuid) -> Ordering {
+        value.total_cmp(&self.value).then_with(|| id.cmp(&self.id))
+    }
+
+    /// Whether an item comes after the cursor in the given direction
+    pub fn is_after(&self, value: f64, id: Uuid, ascending: bool) -> bool {
+        match self.compare(value, id) {
+            Ordering::Greater => ascending,
+            Ordering::Less => !ascending,
+            Ordering::Equal => false,
+        }
+    }
+}
+
+/// Dimension used to group heatmap buckets
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum HeatmapGroupBy {
+    /// Group by service
+    Service,
+    /// Group by severity
+    Severity,
+}
+
+impl HeatmapGroupBy {
+    /// Tag name the dimension is stored under
+    pub fn tag(&self) -> &'static str {
+        match self {
+            Self::Service => "service",
+            Self::Severity => "severity",
+        }
+    }
+}
+
+/// Aggregate query counting anomalies per time bucket and group
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct HeatmapQuery {
+    /// Time range
+    pub time_range: TimeRange,
+
+    /// Bucket width in seconds
+    pub interval_secs: u64,
+
+    /// Grouping dimension
+    pub group_by: HeatmapGroupBy,
+
+    /// Filter by service
+    pub service: Option<ServiceId>,
+
+    /// Filter by severity
+    pub severity: Option<Severity>,
+}
+
+impl HeatmapQuery {
+    /// Create a new heatmap query
+    pub fn new(time_range: TimeRange, interval_secs: u64, group_by: HeatmapGroupBy) -> Self {
+        Self {
+            time_range,
+            interval_secs,
+            group_by,
+            service: None,
+            severity: None,
+        }
+    }
+
+    /// Filter by service
+    pub fn with_service(mut self, service: ServiceId) -> Self {
+        self.service = Some(service);
+        self
+    }
+
+    /// Filter by severity
+    pub fn with_severity(mut self, severity: Severity) -> Self {
+        self.severity = Some(severity);
+        self
+    }
+
+    /// Number of buckets the time range spans
+    pub fn bucket_count(&self) -> u64 {
+        let duration = self.time_range.duration_secs().max(0) as u64;
+        duration.div_ceil(self.interval_secs.max(1))
+    }
+}
+
+/// Anomaly count for one time bucket and group
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct HeatmapBucket {
+    /// Bucket start (inclusive)
+    pub start: DateTime<Utc>,
+    /// Group value (service name or severity)
+    pub group: String,
+    /// Number of anomalies in the bucket
+    pub count: u64,
 }
 
 #[cfg(test)]
@@ -244,4 +595,44 @@ mod tests {
         assert_eq!(query.min_confidence, Some(0.9));
         assert_eq!(query.limit, Some(50));
     }
+
+    #[test]
+    fn test_cursor_roundtrip() {
+        let cursor = Cursor::new(123.5, Uuid::new_v4());
+        let token = cursor.encode();
+        assert!(!token.contains(['+', '/', '=']));
+        assert_eq!(Cursor::decode(&token).unwrap(), cursor);
+
+        assert!(Cursor::decode("not a cursor").is_err());
+        assert!(Cursor::decode(&URL_SAFE_NO_PAD.encode("{}")).is_err());
+
+        assert!(cursor.is_after(100.0, cursor.id, false));
+        assert!(!cursor.is_after(100.0, cursor.id, true));
+        assert!(!cursor.is_after(cursor.value, cursor.id, false));
+    }
+
+    #[test]
+    fn test_sort_parse() {
+        assert_eq!(
+            TelemetrySort::parse("latency_ms"),
+            Some(TelemetrySort::Latency)
+        );
+        assert_eq!(TelemetrySort::parse("cost"), Some(TelemetrySort::Cost));
+        assert_eq!(TelemetrySort::parse("prompt"), None);
+        assert_eq!(AnomalySort::parse("severity"), Some(AnomalySort::Severity));
+        assert_eq!(AnomalySort::parse("latency"), None);
+    }
+
+    #[test]
+    fn test_heatmap_query_builder() {
+        let end = Utc::now();
+        let start = end - chrono::Duration::days(7);
+        let query = HeatmapQuery::new(TimeRange::new(start, end), 86400, HeatmapGroupBy::Severity)
+            .with_service(ServiceId::new("checkout"));
+
+        assert_eq!(query.bucket_count(), 7);
+        assert_eq!(query.group_by.tag(), "severity");
+        assert!(query.severity.is_none());
+        assert_eq!(query.service, Some(ServiceId::new("checkout")));
+    }
 }
@@ -0,0 +1,451 @@
+//! Full-text search over prompt and response text.
+//!
+//! During incident triage responders need to find which requests contained a
+//! phrase. A [`TextSearch`] index stores each event's prompt and response text
+//! next to its service, model, environment and timestamp, so keyword queries
+//! can be narrowed the same way telemetry queries are. With the `search`
+//! feature, [`TantivySearchIndex`] provides an in-memory or on-disk index.
+
+use crate::query::TimeRange;
+use chrono::{DateTime, Utc};
+use llm_sentinel_core::{
+    events::TelemetryEvent,
+    types::{Environment, ModelId, ServiceId},
+    Result,
+};
+use serde::{Deserialize, Serialize};
+use std::{sync::Arc, time::Duration};
+use tokio::task::JoinHandle;
+use tracing::{error, info};
+use uuid::Uuid;
+
+#[cfg(feature = "search")]
+pub use self::tantivy_index::TantivySearchIndex;
+
+/// Keyword query over indexed prompt and response text
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SearchQuery {
+    /// Query text; supports phrases (`"rate limit"`), `AND`/`OR` and `-term`
+    pub text: String,
+
+    /// Time range
+    pub time_range: TimeRange,
+
+    /// Filter by service
+    pub service: Option<ServiceId>,
+
+    /// Filter by model
+    pub model: Option<ModelId>,
+
+    /// Filter by environment
+    pub environment: Option<Environment>,
+
+    /// Maximum number of hits
+    pub limit: usize,
+}
+
+impl SearchQuery {
+    /// Create a new search query
+    pub fn new(text: impl Into<String>, time_range: TimeRange) -> Self {
+        Self {
+            text: text.into(),
+            time_range,
+            service: None,
+            model: None,
+            environment: None,
+            limit: 100,
+        }
+    }
+
+    /// Filter by service
+    pub fn with_service(mut self, service: ServiceId) -> Self {
+        self.service = Some(service);
+        self
+    }
+
+    /// Filter by model
+    pub fn with_model(mut self, model: ModelId) -> Self {
+        self.model = Some(model);
+        self
+    }
+
+    /// Filter by environment
+    pub fn with_environment(mut self, environment: Environment) -> Self {
+        self.environment = Some(environment);
+        self
+    }
+
+    /// Set limit
+    pub fn with_limit(mut self, limit: usize) -> Self {
+        self.limit = limit;
+        self
+    }
+}
+
+/// Event matching a search query
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SearchHit {
+    /// Matching event
+    pub event_id: Uuid,
+    /// Event time
+    pub timestamp: DateTime<Utc>,
+    /// Service
+    pub service_name: ServiceId,
+    /// Model
+    pub model: ModelId,
+    /// Environment
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub environment: Option<Environment>,
+    /// Relevance score, higher is better
+    pub score: f32,
+    /// Prompt text
+    pub prompt: String,
+    /// Response text
+    pub response: String,
+}
+
+/// Full-text index over telemetry text
+///
+/// Indexing and searching may block and should run off the async runtime for
+/// large indexes.
+pub trait TextSearch: Send + Sync {
+    /// Add events to the index
+    ///
+    /// Events become searchable after the next [`TextSearch::commit`].
+    fn index(&self, events: &[TelemetryEvent]) -> Result<()>;
+
+    /// Make indexed events searchable
+    fn commit(&self) -> Result<()>;
+
+    /// Find events matching the query, best matches first
+    fn search(&self, query: &SearchQuery) -> Result<Vec<SearchHit>>;
+}
+
+/// Commit the index every `interval` so new events become searchable
+pub fn start_commit_task(search: Arc<dyn TextSearch>, interval: Duration) -> JoinHandle<()> {
+    let interval = interval.max(Duration::from_secs(1));
+    info!(
+        "Starting search index commit task (every {}s)",
+        interval.as_secs()
+    );
+
+    tokio::spawn(async move {
+        let mut ticker = tokio::time::interval(interval);
+
+        loop {
+            ticker.tick().await;
+            let search = search.clone();
+            let result = tokio::task::spawn_blocking(move || search.commit()).await;
+            if let Ok(Err(e)) = result {
+                error!("Failed to commit search index: {}", e);
+                metrics::counter!("sentinel_storage_errors_total", "type" => "search").increment(1);
+            }
+        }
+    })
+}
+
+#[cfg(feature = "search")]
+mod tantivy_index {
+    use super::{SearchHit, SearchQuery, TextSearch};
+    use chrono::DateTime;
+    use llm_sentinel_core::{
+        config::SearchConfig,
+        events::TelemetryEvent,
+        types::{Environment, ModelId, ServiceId},
+        Error, Result,
+    };
+    use std::{path::Path, sync::Mutex};
+    use tantivy::{
+        collector::TopDocs,
+        directory::MmapDirectory,
+        query::{BooleanQuery, Occur, Query, QueryParser, RangeQuery, TermQuery},
+        schema::{Field, IndexRecordOption, Schema, Value, FAST, INDEXED, STORED, STRING, TEXT},
+        Index, IndexReader, IndexWriter, ReloadPolicy, TantivyDocument, Term,
+    };
+    use uuid::Uuid;
+
+    /// Schema fields
+    #[derive(Debug, Clone, Copy)]
+    struct Fields {
+        event_id: Field,
+        timestamp: Field,
+        service: Field,
+        model: Field,
+        environment: Field,
+        prompt: Field,
+        response: Field,
+    }
+
+    impl Fields {
+        fn schema() -> (Schema, Self) {
+            let mut builder = Schema::builder();
+            let fields = Self {
+                event_id: builder.add_text_field("event_id", STRING | STORED),
+                // Microseconds since the epoch
+                timestamp: builder.add_i64_field("timestamp", INDEXED | STORED | FAST),
+                service: builder.add_text_field("service", STRING | STORED),
+                model: builder.add_text_field("model", STRING | STORED),
+                environment: builder.add_text_field("environment", STRING | STORED),
+                prompt: builder.add_text_field("prompt", TEXT | STORED),
+                response: builder.add_text_field("response", TEXT | STORED),
+            };
+            (builder.build(), fields)
+        }
+    }
+
+    fn search_error(e: impl std::fmt::Display) -> Error {
+        Error::storage(format!("Search index error: {}", e))
+    }
+
+    /// Tantivy-backed search index
+    pub struct TantivySearchIndex {
+        index: Index,
+        reader: IndexReader,
+        writer: Mutex<IndexWriter>,
+        fields: Fields,
+    }
+
+    impl std::fmt::Debug for TantivySearchIndex {
+        fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+            f.debug_struct("TantivySearchIndex")
+                .field("fields", &self.fields)
+                .finish_non_exhaustive()
+        }
+    }
+
+    impl TantivySearchIndex {
+        /// Create an index held in memory
+        pub fn in_memory(writer_memory_mb: usize) -> Result<Self> {
+            let (schema, fields) = Fields::schema();
+            Self::with_index(Index::create_in_ram(schema), fields, writer_memory_mb)
+        }
+
+        /// Open the index in `path`, creating it if needed
+        pub fn open(path: impl AsRef<Path>, writer_memory_mb: usize) -> Result<Self> {
+            let path = path.as_ref();
+            std::fs::create_dir_all(path)?;
+            let directory = MmapDirectory::open(path).map_err(search_error)?;
+            let (schema, fields) = Fields::schema();
+            let index = Index::open_or_create(directory, schema).map_err(search_error)?;
+            Self::with_index(index, fields, writer_memory_mb)
+        }
+
+        /// Create an index from configuration
+        pub fn from_config(config: &SearchConfig) -> Result<Self> {
+            match &config.path {
+                Some(path) => Self::open(path, config.writer_memory_mb),
+                None => Self::in_memory(config.writer_memory_mb),
+            }
+        }
+
+        fn with_index(index: Index, fields: Fields, writer_memory_mb: usize) -> Result<Self> {
+            let writer = index
+                .writer_with_num_threads(1, writer_memory_mb * 1024 * 1024)
+                .map_err(search_error)?;
+            let reader = index
+                .reader_builder()
+                .reload_policy(ReloadPolicy::Manual)
+                .try_into()
+                .map_err(search_error)?;
+            Ok(Self {
+                index,
+                reader,
+                writer: Mutex::new(writer),
+                fields,
+            })
+        }
+
+        fn document(&self, event: &TelemetryEvent) -> TantivyDocument {
+            let f = &self.fields;
+            let mut doc = TantivyDocument::default();
+            doc.add_text(f.event_id, event.event_id.to_string());
+            doc.add_i64(f.timestamp, event.timestamp.timestamp_micros());
+            doc.add_text(f.service, event.service_name.as_str());
+            doc.add_text(f.model, event.model.as_str());
+            if let Some(environment) = &event.environment {
+                doc.add_text(f.environment, environment.as_str());
+            }
+            doc.add_text(f.prompt, &event.prompt.text);
+            doc.add_text(f.response, &event.response.text);
+            doc
+        }
+
+        fn hit(&self, doc: &TantivyDocument, score: f32) -> Option<SearchHit> {
+            let f = &self.fields;
+            let text = |field| doc.get_first(field).and_then(|v| v.as_str());
+            Some(SearchHit {
+                event_id: Uuid::parse_str(text(f.event_id)?).ok()?,
+                timestamp: DateTime::from_timestamp_micros(doc.get_first(f.timestamp)?.as_i64()?)?,
+                service_name: ServiceId::new(text(f.service)?),
+                model: ModelId::new(text(f.model)?),
+                environment: text(f.environment).map(Environment::new),
+                score,
+                prompt: text(f.prompt).unwrap_or_default().to_string(),
+                response: text(f.response).unwrap_or_default().to_string(),
+            })
+        }
+    }
+
+    impl TextSearch for TantivySearchIndex {
+        fn index(&self, events: &[TelemetryEvent]) -> Result<()> {
+            let writer = self.writer.lock().unwrap();
+            for event in events {
+                writer
+                    .add_document(self.document(event))
+                    .map_err(search_error)?;
+            }
+            Ok(())
+        }
+
+        fn commit(&self) -> Result<()> {
+            self.writer.lock().unwrap().commit().map_err(search_error)?;
+            self.reader.reload().map_err(search_error)
+        }
+
+        fn search(&self, query: &SearchQuery) -> Result<Vec<SearchHit>> {
+            let f = &self.fields;
+            if query.text.trim().is_empty() {
+                return Err(Error::validation("Search query must not be empty"));
+            }
+
+            let parser = QueryParser::for_index(&self.index, vec![f.prompt, f.response]);
+            let text_query = parser
+                .parse_query(&query.text)
+                .map_err(|e| Error::validation(format!("Invalid search query: {}", e)))?;
+
+            let range =
+                query.time_range.start.timestamp_micros()..query.time_range.end.timestamp_micros();
+            let mut clauses: Vec<(Occur, Box<dyn Query>)> = vec![
+                (Occur::Must, text_query),
+                (
+                    Occur::Must,
+                    Box::new(RangeQuery::new_i64("timestamp".to_string(), range)),
+                ),
+            ];
+            let filters = [
+                (f.service, query.service.as_ref().map(ServiceId::as_str)),
+                (f.model, query.model.as_ref().map(ModelId::as_str)),
+                (
+                    f.environment,
+                    query.environment.as_ref().map(Environment::as_str),
+                ),
+            ];
+            for (field, value) in filters {
+                if let Some(value) = value {
+                    clauses.push((
+                        Occur::Must,
+                        Box::new(TermQuery::new(
+                            Term::from_field_text(field, value),
+                            IndexRecordOption::Basic,
+                        )),
+                    ));
+                }
+            }
+
+            let searcher = self.reader.searcher();
+            let top_docs = searcher
+                .search(
+                    &BooleanQuery::new(clauses),
+                    &TopDocs::with_limit(query.limit.max(1)),
+                )
+                .map_err(search_error)?;
+
+            top_docs
+                .into_iter()
+                .map(|(score, address)| {
+                    let doc: TantivyDocument = searcher.doc(address).map_err(search_error)?;
+                    Ok(self.hit(&doc, score))
+                })
+                .filter_map(Result::transpose)
+                .collect()
+        }
+    }
+
+    #[cfg(test)]
+    mod tests {
+        use super::*;
+        use crate::query::TimeRange;
+        use chrono::{Duration, Utc};
+        use llm_sentinel_fixtures::event;
+
+        fn create_event(service: &str, prompt: &str, response: &str) -> TelemetryEvent {
+            event()
+                .service(service)
+                .prompt(prompt)
+                .response(response)
+                .build()
+        }
+
+        #[test]
+        fn test_search() {
+            let index = TantivySearchIndex::in_memory(15).unwrap();
+            let mut old = create_event("checkout", "refund my order", "Refund issued");
+            old.timestamp = Utc::now() - Duration::days(2);
+            let events = vec![
+                create_event(
+                    "checkout",
+                    "where is my refund",
+                    "Your refund is on its way",
+                ),
+                create_event("search", "refund policy", "See the refund policy")
+                    .with_environment("staging"),
+                create_event("checkout", "hello", "Hi there"),
+                old,
+            ];
+            index.index(&events).unwrap();
+
+            let range = TimeRange::last_hours(1);
+            let query = SearchQuery::new("refund", range.clone());
+            // Nothing is searchable before a commit
+            assert!(index.search(&query).unwrap().is_empty());
+
+            index.commit().unwrap();
+            let hits = index.search(&query).unwrap();
+            assert_eq!(hits.len(), 2);
+
+            let hits = index
+                .search(&query.clone().with_service(ServiceId::new("checkout")))
+                .unwrap();
+            assert_eq!(hits.len(), 1);
+            assert_eq!(hits[0].event_id, events[0].event_id);
+            assert_eq!(hits[0].prompt, "where is my refund");
+
+            let hits = index
+                .search(&query.clone().with_environment(Environment::new("staging")))
+                .unwrap();
+            assert_eq!(hits.len(), 1);
+            assert_eq!(hits[0].service_name.as_str(), "search");
+            assert_eq!(hits[0].environment, Some(Environment::new("staging")));
+
+            let phrase = SearchQuery::new(r#""refund policy""#, range.clone());
+            assert_eq!(index.search(&phrase).unwrap().len(), 1);
+
+            let all_time = SearchQuery::new("refund", TimeRange::last_days(7));
+            assert_eq!(index.search(&all_time).unwrap().len(), 3);
+
+            assert!(index
+                .search(&SearchQuery::new("  ", range.clone()))
+                .is_err());
+            assert!(index.search(&SearchQuery::new("prompt:(", range)).is_err());
+        }
+
+        #[test]
+        fn test_open_on_disk() {
+            let dir = std::env::temp_dir().join(format!("sentinel-search-{}", Uuid::new_v4()));
+            {
+                let index = TantivySearchIndex::open(&dir, 15).unwrap();
+                index
+                    .index(&[create_event("checkout", "timeout calling tool", "Sorry")])
+                    .unwrap();
+                index.commit().unwrap();
+            }
+
+            let index = TantivySearchIndex::open(&dir, 15).unwrap();
+            let hits = index
+                .search(&SearchQuery::new("timeout", TimeRange::last_hours(1)))
+                .unwrap();
+            assert_eq!(hits.len(), 1);
+            std::fs::remove_dir_all(&dir).unwrap();
+        }
+    }
+}
@@ -0,0 +1,385 @@
+//! Embedding similarity search.
+//!
+//! When telemetry carries prompt or response embeddings, a [`VectorIndex`]
+//! keeps them so responders can find events most similar to a suspicious
+//! one, e.g. to see how widespread a cluster of bad outputs is.
+//! [`FlatVectorIndex`] is an exact in-process index over the most recent
+//! events; the trait leaves room for external vector databases.
+
+use crate::query::TimeRange;
+use chrono::{DateTime, Utc};
+use llm_sentinel_core::{
+    events::TelemetryEvent,
+    types::{Environment, ModelId, ServiceId},
+    Error, Result,
+};
+use serde::{Deserialize, Serialize};
+use std::{collections::VecDeque, sync::RwLock};
+use uuid::Uuid;
+
+/// Characters of matching text kept for previews
+const PREVIEW_CHARS: usize = 200;
+
+/// Which embedding of an event to compare
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum EmbeddingSource {
+    /// Prompt embedding
+    #[default]
+    Prompt,
+    /// Response embedding
+    Response,
+}
+
+/// Nearest-neighbour query over stored embeddings
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SimilarityQuery {
+    /// Embedding to compare against
+    pub embedding: Vec<f32>,
+
+    /// Which stored embeddings to compare
+    pub source: EmbeddingSource,
+
+    /// Restrict to events in this time range
+    pub time_range: Option<TimeRange>,
+
+    /// Filter by service
+    pub service: Option<ServiceId>,
+
+    /// Filter by model
+    pub model: Option<ModelId>,
+
+    /// Filter by environment
+    pub environment: Option<Environment>,
+
+    /// Minimum cosine similarity
+    pub min_score: Option<f32>,
+
+    /// Event to leave out, usually the one the embedding came from
+    pub exclude: Option<Uuid>,
+
+    /// Maximum number of hits
+    pub limit: usize,
+}
+
+impl SimilarityQuery {
+    /// Create a new similarity query
+    pub fn new(embedding: Vec<f32>, source: EmbeddingSource) -> Self {
+        Self {
+            embedding,
+            source,
+            time_range: None,
+            service: None,
+            model: None,
+            environment: None,
+            min_score: None,
+            exclude: None,
+            limit: 20,
+        }
+    }
+
+    /// Restrict to a time range
+    pub fn with_time_range(mut self, time_range: TimeRange) -> Self {
+        self.time_range = Some(time_range);
+        self
+    }
+
+    /// Filter by service
+    pub fn with_service(mut self, service: ServiceId) -> Self {
+        self.service = Some(service);
+        self
+    }
+
+    /// Filter by model
+    pub fn with_model(mut self, model: ModelId) -> Self {
+        self.model = Some(model);
+        self
+    }
+
+    /// Filter by environment
+    pub fn with_environment(mut self, environment: Environment) -> Self {
+        self.environment = Some(environment);
+        self
+    }
+
+    /// Set minimum cosine similarity
+    pub fn with_min_score(mut self, min_score: f32) -> Self {
+        self.min_score = Some(min_score);
+        self
+    }
+
+    /// Leave out an event
+    pub fn excluding(mut self, event_id: Uuid) -> Self {
+        self.exclude = Some(event_id);
+        self
+    }
+
+    /// Set limit
+    pub fn with_limit(mut self, limit: usize) -> Self {
+        self.limit = limit;
+        self
+    }
+}
+
+/// Event similar to a query embedding
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SimilarHit {
+    /// Matching event
+    pub event_id: Uuid,
+    /// Event time
+    pub timestamp: DateTime<Utc>,
+    /// Service
+    pub service_name: ServiceId,
+    /// Model
+    pub model: ModelId,
+    /// Environment
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub environment: Option<Environment>,
+    /// Cosine similarity, 1.0 for identical direction
+    pub score: f32,
+    /// Start of the compared prompt or response text
+    pub preview: String,
+}
+
+/// Index of telemetry embeddings
+pub trait VectorIndex: Send + Sync {
+    /// Add an event's embeddings; events without embeddings are ignored
+    fn insert(&self, event: &TelemetryEvent);
+
+    /// Stored embedding of an event, if it is still indexed
+    fn embedding(&self, event_id: Uuid, source: EmbeddingSource) -> Option<Vec<f32>>;
+
+    /// Find the most similar events, best matches first
+    fn search(&self, query: &SimilarityQuery) -> Result<Vec<SimilarHit>>;
+}
+
+/// Indexed event
+#[derive(Debug, Clone)]
+struct Entry {
+    event_id: Uuid,
+    timestamp: DateTime<Utc>,
+    service_name: ServiceId,
+    model: ModelId,
+    environment: Option<Environment>,
+    prompt: Option<(Vec<f32>, String)>,
+    response: Option<(Vec<f32>, String)>,
+}
+
+impl Entry {
+    fn get(&self, source: EmbeddingSource) -> Option<&(Vec<f32>, String)> {
+        match source {
+            EmbeddingSource::Prompt => self.prompt.as_ref(),
+            EmbeddingSource::Response => self.response.as_ref(),
+        }
+    }
+}
+
+/// Scale a vector to unit length; `None` for empty or zero vectors
+fn normalize(vector: &[f32]) -> Option<Vec<f32>> {
+    let norm = vector.iter().map(|x| x * x).sum::<f32>().sqrt();
+    (norm > 0.0 && norm.is_finite()).then(|| vector.iter().map(|x| x / norm).collect())
+}
+
+fn preview(text: &str) -> String {
+    text.chars().take(PREVIEW_CHARS).collect()
+}
+
+/// Exact cosine-similarity index over the most recent embedded events
+///
+/// Scans every entry per query, which stays fast for the tens of thousands of
+/// events it is meant to hold. The oldest events are evicted at capacity.
+#[derive(Debug)]
+pub struct FlatVectorIndex {
+    capacity: usize,
+    entries: RwLock<VecDeque<Entry>>,
+}
+
+impl FlatVectorIndex {
+    /// Create an index holding at most `capacity` events
+    pub fn new(capacity: usize) -> Self {
+        Self {
+            capacity: capacity.max(1),
+            entries: RwLock::new(VecDeque::new()),
+        }
+    }
+
+    /// Number of indexed events
+    pub fn len(&self) -> usize {
+        self.entries.read().unwrap().len()
+    }
+
+    /// Whether the index is empty
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+}
+
+impl VectorIndex for FlatVectorIndex {
+    fn insert(&self, event: &TelemetryEvent) {
+        let embedded = |embedding: &Option<Vec<f32>>, text: &str| {
+            embedding
+                .as_deref()
+                .and_then(normalize)
+                .map(|vector| (vector, preview(text)))
+        };
+        let prompt = embedded(&event.prompt.embedding, &event.prompt.text);
+        let response = embedded(&event.response.embedding, &event.response.text);
+        if prompt.is_none() && response.is_none() {
+            return;
+        }
+
+        let mut entries = self.entries.write().unwrap();
+        if entries.len() >= self.capacity {
+            entries.pop_front();
+        }
+        entries.push_back(Entry {
+            event_id: event.event_id,
+            timestamp: event.timestamp,
+            service_name: event.service_name.clone(),
+            model: event.model.clone(),
+            environment: event.environment.clone(),
+            prompt,
+            response,
+        });
+    }
+
+    fn embedding(&self, event_id: Uuid, source: EmbeddingSource) -> Option<Vec<f32>> {
+        self.entries
+            .read()
+            .unwrap()
+            .iter()
+            .find(|entry| entry.event_id == event_id)
+            .and_then(|entry| entry.get(source))
+            .map(|(vector, _)| vector.clone())
+    }
+
+    fn search(&self, query: &SimilarityQuery) -> Result<Vec<SimilarHit>> {
+        let target = normalize(&query.embedding)
+            .ok_or_else(|| Error::validation("Embedding must be a non-zero vector"))?;
+
+        let entries = self.entries.read().unwrap();
+        let mut hits: Vec<SimilarHit> = entries
+            .iter()
+            .filter(|e| query.exclude != Some(e.event_id))
+            .filter(|e| {
+                query
+                    .time_range
+                    .as_ref()
+                    .map_or(true, |r| e.timestamp >= r.start && e.timestamp < r.end)
+            })
+            .filter(|e| {
+                query
+                    .service
+                    .as_ref()
+                    .map_or(true, |s| &e.service_name == s)
+            })
+            .filter(|e| query.model.as_ref().map_or(true, |m| &e.model == m))
+            .filter(|e| {
+                query
+                    .environment
+                    .as_ref()
+                    .map_or(true, |env| e.environment.as_ref() == Some(env))
+            })
+            .filter_map(|e| {
+                let (vector, preview) = e.get(query.source)?;
+                // Embeddings from different models are not comparable
+                if vector.len() != target.len() {
+                    return None;
+                }
+                let score: f32 = vector.iter().zip(&target).map(|(a, b)| a * b).sum();
+                if query.min_score.is_some_and(|min| score < min) {
+                    return None;
+                }
+                Some(SimilarHit {
+                    event_id: e.event_id,
+                    timestamp: e.timestamp,
+                    service_name: e.service_name.clone(),
+                    model: e.model.clone(),
+                    environment: e.environment.clone(),
+                    score,
+                    preview: preview.clone(),
+                })
+            })
+            .collect();
+
+        hits.sort_by(|a, b| b.score.total_cmp(&a.score));
+        hits.truncate(query.limit);
+        Ok(hits)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use llm_sentinel_fixtures::event;
+
+    fn create_event(service: &str, prompt: Option<Vec<f32>>, text: &str) -> TelemetryEvent {
+        let builder = event().service(service).prompt(text);
+        match prompt {
+            Some(embedding) => builder.prompt_embedding(embedding).build(),
+            None => builder.build(),
+        }
+    }
+
+    #[test]
+    fn test_similarity_search() {
+        let index = FlatVectorIndex::new(10);
+        let events = vec![
+            create_event("checkout", Some(vec![1.0, 0.0, 0.0]), "refund please"),
+            create_event("checkout", Some(vec![0.9, 0.1, 0.0]), "refund my order"),
+            create_event("search", Some(vec![0.8, 0.2, 0.0]), "refund policy"),
+            create_event("checkout", Some(vec![0.0, 0.0, 1.0]), "hello"),
+            // Different embedding dimension
+            create_event("checkout", Some(vec![1.0, 0.0]), "other model"),
+            create_event("checkout", None, "no embedding"),
+        ];
+        for event in &events {
+            index.insert(event);
+        }
+        assert_eq!(index.len(), 5);
+
+        let embedding = index
+            .embedding(events[0].event_id, EmbeddingSource::Prompt)
+            .unwrap();
+        let query = SimilarityQuery::new(embedding, EmbeddingSource::Prompt)
+            .excluding(events[0].event_id)
+            .with_min_score(0.5);
+        let hits = index.search(&query).unwrap();
+        assert_eq!(hits.len(), 2);
+        assert_eq!(hits[0].event_id, events[1].event_id);
+        assert_eq!(hits[0].preview, "refund my order");
+        assert!(hits[0].score > hits[1].score);
+
+        let hits = index
+            .search(&query.clone().with_service(ServiceId::new("search")))
+            .unwrap();
+        assert_eq!(hits.len(), 1);
+
+        let response = SimilarityQuery::new(vec![1.0, 0.0, 0.0], EmbeddingSource::Response);
+        assert!(index.search(&response).unwrap().is_empty());
+        assert!(index
+            .embedding(events[0].event_id, EmbeddingSource::Response)
+            .is_none());
+
+        let zero = SimilarityQuery::new(vec![0.0, 0.0, 0.0], EmbeddingSource::Prompt);
+        assert!(index.search(&zero).is_err());
+    }
+
+    #[test]
+    fn test_capacity_evicts_oldest() {
+        let index = FlatVectorIndex::new(2);
+        let events: Vec<_> = (0..3)
+            .map(|i| create_event("checkout", Some(vec![1.0, i as f32]), "text"))
+            .collect();
+        for event in &events {
+            index.insert(event);
+        }
+        assert_eq!(index.len(), 2);
+        assert!(index
+            .embedding(events[0].event_id, EmbeddingSource::Prompt)
+            .is_none());
+        assert!(index
+            .embedding(events[2].event_id, EmbeddingSource::Prompt)
+            .is_some());
+    }
+}
@@ -0,0 +1,181 @@
+//! Storage of runtime state snapshots.
+//!
+//! [`FileSnapshotStore`] keeps each snapshot as a JSON file in a directory
+//! shared between deployments (a volume, or a mounted bucket). Files are
+//! written to a temporary name and renamed, so a restore never reads a
+//! partially written snapshot.
+
+use async_trait::async_trait;
+use llm_sentinel_core::{
+    snapshot::{RuntimeSnapshot, SnapshotInfo},
+    Error, Result,
+};
+use std::path::{Path, PathBuf};
+use tracing::{info, warn};
+
+/// Destination for runtime state snapshots
+#[async_trait]
+pub trait SnapshotStore: Send + Sync + std::fmt::Debug {
+    /// Store a snapshot
+    async fn save(&self, snapshot: &RuntimeSnapshot) -> Result<()>;
+
+    /// Load a snapshot by ID
+    async fn load(&self, id: &str) -> Result<Option<RuntimeSnapshot>>;
+
+    /// Stored snapshots, newest first
+    async fn list(&self) -> Result<Vec<SnapshotInfo>>;
+
+    /// Load the newest snapshot
+    async fn latest(&self) -> Result<Option<RuntimeSnapshot>> {
+        match self.list().await?.first() {
+            Some(info) => self.load(&info.id).await,
+            None => Ok(None),
+        }
+    }
+}
+
+/// Snapshots as JSON files in a directory
+#[derive(Debug)]
+pub struct FileSnapshotStore {
+    dir: PathBuf,
+    keep: usize,
+}
+
+impl FileSnapshotStore {
+    /// Store snapshots in `dir`, keeping the newest `keep`
+    pub fn new(dir: impl Into<PathBuf>, keep: usize) -> Result<Self> {
+        let dir = dir.into();
+        std::fs::create_dir_all(&dir).map_err(|e| {
+            Error::storage(format!(
+                "Failed to create snapshot directory {}: {}",
+                dir.display(),
+                e
+            ))
+        })?;
+        Ok(Self {
+            dir,
+            keep: keep.max(1),
+        })
+    }
+
+    fn path(&self, id: &str) -> Result<PathBuf> {
+        // IDs come from the API; keep them inside the directory
+        if id.is_empty() || !id.chars().all(|c| c.is_ascii_alphanumeric() || ".-_".contains(c)) {
+            return Err(Error::validation(format!("Invalid snapshot ID: {}", id)));
+        }
+        Ok(self.dir.join(format!("{}.json", id)))
+    }
+
+    /// IDs of the stored snapshots, newest first
+    async fn ids(&self) -> Result<Vec<String>> {
+        let mut entries = tokio::fs::read_dir(&self.dir)
+            .await
+            .map_err(|e| Error::storage(format!("Failed to list snapshots: {}", e)))?;
+        let mut ids = Vec::new();
+        while let Some(entry) = entries
+            .next_entry()
+            .await
+            .map_err(|e| Error::storage(format!("Failed to list snapshots: {}", e)))?
+        {
+            let path = entry.path();
+            if path.extension().is_some_and(|ext| ext == "json") {
+                if let Some(id) = path.file_stem().and_then(|stem| stem.to_str()) {
+                    ids.push(id.to_string());
+                }
+            }
+        }
+        // IDs are timestamps, so they sort by creation time
+        ids.sort_unstable_by(|a, b| b.cmp(a));
+        Ok(ids)
+    }
+
+    async fn read(path: &Path) -> Result<Option<RuntimeSnapshot>> {
+        let bytes = match tokio::fs::read(path).await {
+            Ok(bytes) => bytes,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(None),
+            Err(e) => {
+                return Err(Error::storage(format!(
+                    "Failed to read snapshot {}: {}",
+                    path.display(),
+                    e
+                )))
+            }
+        };
+        serde_json::from_slice(&bytes).map(Some).map_err(|e| {
+            Error::storage(format!("Invalid snapshot {}: {}", path.display(), e))
+        })
+    }
+}
+
+#[async_trait]
+impl SnapshotStore for FileSnapshotStore {
+    async fn save(&self, snapshot: &RuntimeSnapshot) -> Result<()> {
+        let path = self.path(&snapshot.id)?;
+        let bytes = serde_json::to_vec(snapshot)
+            .map_err(|e| Error::storage(format!("Failed to serialize snapshot: {}", e)))?;
+
+        let tmp = path.with_extension("json.tmp");
+        tokio::fs::write(&tmp, &bytes)
+            .await
+            .map_err(|e| Error::storage(format!("Failed to write snapshot: {}", e)))?;
+        tokio::fs::rename(&tmp, &path)
+            .await
+            .map_err(|e| Error::storage(format!("Failed to write snapshot: {}", e)))?;
+        info!(id = %snapshot.id, bytes = bytes.len(), "Saved runtime snapshot");
+
+        for id in self.ids().await?.into_iter().skip(self.keep) {
+            if let Err(e) = tokio::fs::remove_file(self.path(&id)?).await {
+                warn!(id = %id, "Failed to delete old snapshot: {}", e);
+            }
+        }
+        Ok(())
+    }
+
+    async fn load(&self, id: &str) -> Result<Option<RuntimeSnapshot>> {
+        Self::read(&self.path(id)?).await
+    }
+
+    async fn list(&self) -> Result<Vec<SnapshotInfo>> {
+        let mut infos = Vec::new();
+        for id in self.ids().await? {
+            if let Some(snapshot) = Self::read(&self.path(&id)?).await? {
+                infos.push(snapshot.info());
+            }
+        }
+        Ok(infos)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::{Duration, Utc};
+    use uuid::Uuid;
+
+    #[tokio::test]
+    async fn test_file_snapshot_store() {
+        let dir = std::env::temp_dir().join(format!("sentinel-snapshots-{}", Uuid::new_v4()));
+        let store = FileSnapshotStore::new(&dir, 2).unwrap();
+        assert!(store.latest().await.unwrap().is_none());
+
+        let start = Utc::now();
+        let mut ids = Vec::new();
+        for i in 0..3 {
+            let mut snapshot = RuntimeSnapshot::new("0.1.0", start + Duration::seconds(i));
+            snapshot.insert("index", &i).unwrap();
+            store.save(&snapshot).await.unwrap();
+            ids.push(snapshot.id);
+        }
+
+        // Only the newest two are kept, newest first
+        let listed: Vec<String> = store.list().await.unwrap().into_iter().map(|i| i.id).collect();
+        assert_eq!(listed, vec![ids[2].clone(), ids[1].clone()]);
+        assert!(store.load(&ids[0]).await.unwrap().is_none());
+
+        let latest = store.latest().await.unwrap().unwrap();
+        assert_eq!(latest.get::<i64>("index").unwrap(), Some(2));
+
+        assert!(store.load("../etc/passwd").await.is_err());
+        std::fs::remove_dir_all(dir).unwrap();
+    }
+}
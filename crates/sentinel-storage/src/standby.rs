@@ -0,0 +1,158 @@
+//! State stream between a primary and its warm standbys.
+//!
+//! The primary of an HA pair publishes its learned runtime state (the same
+//! [`RuntimeSnapshot`] taken for blue/green deploys) every few seconds;
+//! standbys read the newest one and restore it without consuming telemetry.
+//! Only the newest state matters, so [`RedisStateStream`] trims the Redis
+//! stream to a few entries on every write.
+
+use async_trait::async_trait;
+use llm_sentinel_core::{snapshot::RuntimeSnapshot, Result};
+
+#[cfg(feature = "redis")]
+use llm_sentinel_core::{config::StandbyConfig, Error};
+#[cfg(feature = "redis")]
+use std::collections::HashMap;
+
+/// Stream field holding the serialized snapshot
+#[cfg(feature = "redis")]
+const STATE_FIELD: &str = "state";
+
+/// Runtime state published by a primary to its standbys
+#[async_trait]
+pub trait StateStream: Send + Sync + std::fmt::Debug {
+    /// Publish the primary's state
+    async fn publish(&self, snapshot: &RuntimeSnapshot) -> Result<()>;
+
+    /// The newest published state
+    async fn latest(&self) -> Result<Option<RuntimeSnapshot>>;
+}
+
+/// State stream held in memory, for tests and single-process pairs
+#[cfg(any(test, feature = "test-util"))]
+#[derive(Debug, Default)]
+pub struct MemoryStateStream {
+    latest: std::sync::Mutex<Option<RuntimeSnapshot>>,
+}
+
+#[cfg(any(test, feature = "test-util"))]
+impl MemoryStateStream {
+    /// Create an empty stream
+    pub fn new() -> Self {
+        Self::default()
+    }
+}
+
+#[cfg(any(test, feature = "test-util"))]
+#[async_trait]
+impl StateStream for MemoryStateStream {
+    async fn publish(&self, snapshot: &RuntimeSnapshot) -> Result<()> {
+        *self.latest.lock().unwrap() = Some(snapshot.clone());
+        Ok(())
+    }
+
+    async fn latest(&self) -> Result<Option<RuntimeSnapshot>> {
+        Ok(self.latest.lock().unwrap().clone())
+    }
+}
+
+/// State stream held in a Redis stream
+#[cfg(feature = "redis")]
+pub struct RedisStateStream {
+    client: redis::Client,
+    stream: String,
+    max_len: usize,
+}
+
+#[cfg(feature = "redis")]
+impl std::fmt::Debug for RedisStateStream {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("RedisStateStream")
+            .field("stream", &self.stream)
+            .field("max_len", &self.max_len)
+            .finish()
+    }
+}
+
+#[cfg(feature = "redis")]
+impl RedisStateStream {
+    /// Connect to the Redis instance holding the stream
+    pub async fn new(config: &StandbyConfig) -> Result<Self> {
+        let client = crate::leader::connect(&config.redis_url).await?;
+        Ok(Self {
+            client,
+            stream: config.stream.clone(),
+            max_len: config.max_len,
+        })
+    }
+
+    async fn connection(&self) -> Result<redis::aio::MultiplexedConnection> {
+        self.client
+            .get_multiplexed_async_connection()
+            .await
+            .map_err(|e| Error::connection(format!("Failed to get Redis connection: {}", e)))
+    }
+}
+
+#[cfg(feature = "redis")]
+#[async_trait]
+impl StateStream for RedisStateStream {
+    async fn publish(&self, snapshot: &RuntimeSnapshot) -> Result<()> {
+        let state = serde_json::to_vec(snapshot)
+            .map_err(|e| Error::internal(format!("Failed to serialize state: {}", e)))?;
+        let mut conn = self.connection().await?;
+        redis::cmd("XADD")
+            .arg(&self.stream)
+            .arg("MAXLEN")
+            .arg("~")
+            .arg(self.max_len)
+            .arg("*")
+            .arg(STATE_FIELD)
+            .arg(state)
+            .query_async::<String>(&mut conn)
+            .await
+            .map_err(|e| Error::connection(format!("Failed to publish state: {}", e)))?;
+        Ok(())
+    }
+
+    async fn latest(&self) -> Result<Option<RuntimeSnapshot>> {
+        let mut conn = self.connection().await?;
+        let entries: Vec<(String, HashMap<String, Vec<u8>>)> = redis::cmd("XREVRANGE")
+            .arg(&self.stream)
+            .arg("+")
+            .arg("-")
+            .arg("COUNT")
+            .arg(1)
+            .query_async(&mut conn)
+            .await
+            .map_err(|e| Error::connection(format!("Failed to read state: {}", e)))?;
+
+        let Some((id, fields)) = entries.into_iter().next() else {
+            return Ok(None);
+        };
+        let state = fields
+            .get(STATE_FIELD)
+            .ok_or_else(|| Error::storage(format!("State entry {} has no state", id)))?;
+        serde_json::from_slice(state)
+            .map(Some)
+            .map_err(|e| Error::storage(format!("Invalid state entry {}: {}", id, e)))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::Utc;
+
+    #[tokio::test]
+    async fn test_memory_stream_keeps_latest() {
+        let stream = MemoryStateStream::new();
+        assert!(stream.latest().await.unwrap().is_none());
+
+        let first = RuntimeSnapshot::new("1.0.0", Utc::now() - chrono::Duration::seconds(5));
+        let second = RuntimeSnapshot::new("1.0.0", Utc::now());
+        stream.publish(&first).await.unwrap();
+        stream.publish(&second).await.unwrap();
+        assert_eq!(stream.latest().await.unwrap(), Some(second));
+    }
+}
@@ -0,0 +1,128 @@
+//! Anomaly stream consumer offsets.
+//!
+//! A downstream processor reading the anomaly stream under a consumer ID
+//! acknowledges each anomaly once it has processed it. The acknowledged
+//! position is stored as a [`StreamOffset`], so when the consumer reconnects
+//! the anomalies stored after it are replayed before live ones, and none are
+//! missed or handed out again.
+//!
+//! Stream positions are the anomaly's detection time with its ID breaking
+//! ties: the same keyset [`Cursor`] as timestamp-sorted anomaly queries.
+//! Offsets only move forward, so acknowledging an anomaly that arrived out
+//! of order does not rewind the consumer.
+
+use crate::{
+    query::{AnomalyQuery, AnomalySort, Cursor, TimeRange},
+    Storage,
+};
+use chrono::{DateTime, Utc};
+use llm_sentinel_core::{events::AnomalyEvent, Error, Result};
+use serde::{Deserialize, Serialize};
+use tracing::debug;
+
+/// Acknowledged position of a stream consumer
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct StreamOffset {
+    /// Consumer ID
+    pub consumer_id: String,
+    /// Position of the last acknowledged anomaly
+    pub cursor: Cursor,
+    /// When it was acknowledged
+    pub acked_at: DateTime<Utc>,
+}
+
+impl StreamOffset {
+    /// Offset acknowledged now
+    pub fn new(consumer_id: impl Into<String>, cursor: Cursor) -> Self {
+        Self {
+            consumer_id: consumer_id.into(),
+            cursor,
+            acked_at: Utc::now(),
+        }
+    }
+}
+
+/// Position of an anomaly in the stream
+pub fn stream_cursor(anomaly: &AnomalyEvent) -> Cursor {
+    AnomalySort::Timestamp.cursor(anomaly)
+}
+
+/// Record that a consumer has processed everything up to `cursor`
+///
+/// Returns whether the stored offset moved; acknowledgements at or before
+/// it are ignored.
+pub async fn acknowledge(storage: &dyn Storage, consumer_id: &str, cursor: Cursor) -> Result<bool> {
+    if let Some(offset) = storage.get_stream_offset(consumer_id).await? {
+        if !offset.cursor.is_after(cursor.value, cursor.id, true) {
+            return Ok(false);
+        }
+    }
+
+    storage
+        .write_stream_offset(&StreamOffset::new(consumer_id, cursor))
+        .await?;
+    debug!(consumer_id, cursor = %cursor.encode(), "Stream offset acknowledged");
+    metrics::counter!("sentinel_stream_acks_total").increment(1);
+    Ok(true)
+}
+
+/// The next page of stored anomalies after `cursor`, in stream order
+pub async fn replay_page(
+    storage: &dyn Storage,
+    cursor: Cursor,
+    limit: usize,
+) -> Result<Vec<AnomalyEvent>> {
+    let start = DateTime::from_timestamp_micros(cursor.value as i64)
+        .ok_or_else(|| Error::validation("Invalid stream offset"))?;
+    let query = AnomalyQuery::new(TimeRange::new(start, Utc::now()))
+        .with_expired()
+        .sort_by(AnomalySort::Timestamp)
+        .ascending()
+        .with_cursor(cursor)
+        .with_limit(limit);
+    storage.query_anomalies(query).await
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::memory::InMemoryStorage;
+
+    use llm_sentinel_fixtures::anomaly;
+
+    fn create_anomaly(seconds_ago: i64) -> AnomalyEvent {
+        let mut anomaly = anomaly().service("checkout").build();
+        anomaly.timestamp = Utc::now() - chrono::Duration::seconds(seconds_ago);
+        anomaly
+    }
+
+    #[tokio::test]
+    async fn test_acknowledge_and_replay() {
+        let storage = InMemoryStorage::new();
+        let anomalies: Vec<_> = (0..5).rev().map(|i| create_anomaly(10 + i)).collect();
+        storage.write_anomaly_batch(&anomalies).await.unwrap();
+
+        assert!(storage
+            .get_stream_offset("billing")
+            .await
+            .unwrap()
+            .is_none());
+        let second = stream_cursor(&anomalies[1]);
+        assert!(acknowledge(&storage, "billing", second).await.unwrap());
+
+        // Offsets do not move backwards
+        let first = stream_cursor(&anomalies[0]);
+        assert!(!acknowledge(&storage, "billing", first).await.unwrap());
+        let offset = storage.get_stream_offset("billing").await.unwrap().unwrap();
+        assert_eq!(offset.cursor, second);
+
+        let page = replay_page(&storage, offset.cursor, 2).await.unwrap();
+        let ids: Vec<_> = page.iter().map(|a| a.alert_id).collect();
+        assert_eq!(ids, vec![anomalies[2].alert_id, anomalies[3].alert_id]);
+
+        let last = stream_cursor(page.last().unwrap());
+        let page = replay_page(&storage, last, 2).await.unwrap();
+        assert_eq!(page.len(), 1);
+        assert_eq!(page[0].alert_id, anomalies[4].alert_id);
+    }
+}
@@ -0,0 +1,423 @@
+//! Fault injection into dependencies.
+//!
+//! With `chaos` configured, storage, alerter and ingester calls fail or are
+//! delayed at random so retries, circuit breakers and failed-delivery
+//! handling can be exercised in staging. Injected failures are connection
+//! errors, the way an unreachable backend surfaces. Health checks are never
+//! faulted, and injection stops `duration_secs` after the wrappers are
+//! created. Faults are counted as `sentinel_chaos_faults_total{target,fault}`.
+
+use async_trait::async_trait;
+use llm_sentinel_alerting::Alerter;
+use llm_sentinel_core::{
+    annotation::AnomalyAnnotation,
+    config::{ChaosConfig, FaultConfig},
+    events::{AlertMetadata, AnomalyEvent, TelemetryEvent},
+    incident::Incident,
+    lifecycle::AnomalyStateChange,
+    Error, Result,
+};
+use llm_sentinel_ingestion::Ingester;
+use llm_sentinel_storage::{
+    annotation::AnnotationQuery,
+    cost::{CostReportQuery, CostReportRow, CostRollup},
+    delivery::DeliveryQuery,
+    incident::IncidentQuery,
+    query::{AnomalyQuery, HeatmapBucket, HeatmapQuery, TelemetryQuery},
+    stream::StreamOffset,
+    Storage,
+};
+use rand::Rng;
+use std::{sync::Arc, time::Duration};
+use tokio::time::Instant;
+use tracing::{debug, warn};
+use uuid::Uuid;
+
+/// Injects the faults configured for one kind of dependency
+#[derive(Debug, Clone)]
+pub struct FaultInjector {
+    target: &'static str,
+    config: FaultConfig,
+    until: Option<Instant>,
+}
+
+impl FaultInjector {
+    /// Inject faults into `target` calls, for `duration_secs` from now
+    /// when given
+    pub fn new(target: &'static str, config: &FaultConfig, duration_secs: Option<u64>) -> Self {
+        warn!(
+            dependency = target,
+            failure_probability = config.failure_probability,
+            latency_probability = config.latency_probability,
+            latency_ms = config.latency_ms,
+            "Fault injection enabled"
+        );
+        Self {
+            target,
+            config: config.clone(),
+            until: duration_secs.map(|secs| Instant::now() + Duration::from_secs(secs)),
+        }
+    }
+
+    /// Delay and/or fail a call to `operation`
+    pub async fn inject(&self, operation: &str) -> Result<()> {
+        if self.until.is_some_and(|until| Instant::now() >= until) {
+            return Ok(());
+        }
+        let (delay, fail) = {
+            let mut rng = rand::thread_rng();
+            (
+                rng.gen_bool(self.config.latency_probability),
+                rng.gen_bool(self.config.failure_probability),
+            )
+        };
+
+        if delay {
+            debug!(dependency = self.target, operation, "Injecting latency");
+            metrics::counter!(
+                "sentinel_chaos_faults_total",
+                "target" => self.target,
+                "fault" => "latency"
+            )
+            .increment(1);
+            tokio::time::sleep(Duration::from_millis(self.config.latency_ms)).await;
+        }
+        if fail {
+            debug!(dependency = self.target, operation, "Injecting failure");
+            metrics::counter!(
+                "sentinel_chaos_faults_total",
+                "target" => self.target,
+                "fault" => "failure"
+            )
+            .increment(1);
+            return Err(Error::connection(format!(
+                "Injected {} failure in {}",
+                self.target, operation
+            )));
+        }
+        Ok(())
+    }
+}
+
+/// Wrap storage in the faults configured under `chaos.storage`
+pub fn wrap_storage(storage: Arc<dyn Storage>, config: Option<&ChaosConfig>) -> Arc<dyn Storage> {
+    match config.and_then(|chaos| Some((chaos.storage.as_ref()?, chaos.duration_secs))) {
+        Some((faults, duration_secs)) => Arc::new(ChaosStorage {
+            inner: storage,
+            faults: FaultInjector::new("storage", faults, duration_secs),
+        }),
+        None => storage,
+    }
+}
+
+/// Wrap alerters in the faults configured under `chaos.alerting`
+pub fn wrap_alerters(
+    alerters: Vec<Arc<dyn Alerter>>,
+    config: Option<&ChaosConfig>,
+) -> Vec<Arc<dyn Alerter>> {
+    match config.and_then(|chaos| Some((chaos.alerting.as_ref()?, chaos.duration_secs))) {
+        Some((faults, duration_secs)) => {
+            let faults = FaultInjector::new("alerting", faults, duration_secs);
+            alerters
+                .into_iter()
+                .map(|inner| {
+                    Arc::new(ChaosAlerter {
+                        inner,
+                        faults: faults.clone(),
+                    }) as Arc<dyn Alerter>
+                })
+                .collect()
+        }
+        None => alerters,
+    }
+}
+
+/// Wrap an ingester in the faults configured under `chaos.ingestion`
+pub fn wrap_ingester(
+    ingester: Box<dyn Ingester>,
+    config: Option<&ChaosConfig>,
+) -> Box<dyn Ingester> {
+    match config.and_then(|chaos| Some((chaos.ingestion.as_ref()?, chaos.duration_secs))) {
+        Some((faults, duration_secs)) => Box::new(ChaosIngester {
+            inner: ingester,
+            faults: FaultInjector::new("ingestion", faults, duration_secs),
+        }),
+        None => ingester,
+    }
+}
+
+/// Storage with injected faults
+struct ChaosStorage {
+    inner: Arc<dyn Storage>,
+    faults: FaultInjector,
+}
+
+#[async_trait]
+impl Storage for ChaosStorage {
+    async fn write_telemetry(&self, event: &TelemetryEvent) -> Result<()> {
+        self.faults.inject("write_telemetry").await?;
+        self.inner.write_telemetry(event).await
+    }
+
+    async fn write_anomaly(&self, anomaly: &AnomalyEvent) -> Result<()> {
+        self.faults.inject("write_anomaly").await?;
+        self.inner.write_anomaly(anomaly).await
+    }
+
+    async fn write_telemetry_batch(&self, events: &[TelemetryEvent]) -> Result<()> {
+        self.faults.inject("write_telemetry_batch").await?;
+        self.inner.write_telemetry_batch(events).await
+    }
+
+    async fn write_anomaly_batch(&self, anomalies: &[AnomalyEvent]) -> Result<()> {
+        self.faults.inject("write_anomaly_batch").await?;
+        self.inner.write_anomaly_batch(anomalies).await
+    }
+
+    async fn query_telemetry(&self, query: TelemetryQuery) -> Result<Vec<TelemetryEvent>> {
+        self.faults.inject("query_telemetry").await?;
+        self.inner.query_telemetry(query).await
+    }
+
+    async fn query_anomalies(&self, query: AnomalyQuery) -> Result<Vec<AnomalyEvent>> {
+        self.faults.inject("query_anomalies").await?;
+        self.inner.query_anomalies(query).await
+    }
+
+    async fn get_anomaly(&self, alert_id: Uuid) -> Result<Option<AnomalyEvent>> {
+        self.faults.inject("get_anomaly").await?;
+        self.inner.get_anomaly(alert_id).await
+    }
+
+    async fn write_anomaly_state_changes(&self, changes: &[AnomalyStateChange]) -> Result<()> {
+        self.faults.inject("write_anomaly_state_changes").await?;
+        self.inner.write_anomaly_state_changes(changes).await
+    }
+
+    async fn query_anomaly_state_changes(&self, alert_id: Uuid) -> Result<Vec<AnomalyStateChange>> {
+        self.faults.inject("query_anomaly_state_changes").await?;
+        self.inner.query_anomaly_state_changes(alert_id).await
+    }
+
+    async fn write_anomaly_annotations(&self, annotations: &[AnomalyAnnotation]) -> Result<()> {
+        self.faults.inject("write_anomaly_annotations").await?;
+        self.inner.write_anomaly_annotations(annotations).await
+    }
+
+    async fn query_anomaly_annotations(
+        &self,
+        query: AnnotationQuery,
+    ) -> Result<Vec<AnomalyAnnotation>> {
+        self.faults.inject("query_anomaly_annotations").await?;
+        self.inner.query_anomaly_annotations(query).await
+    }
+
+    async fn write_stream_offset(&self, offset: &StreamOffset) -> Result<()> {
+        self.faults.inject("write_stream_offset").await?;
+        self.inner.write_stream_offset(offset).await
+    }
+
+    async fn get_stream_offset(&self, consumer_id: &str) -> Result<Option<StreamOffset>> {
+        self.faults.inject("get_stream_offset").await?;
+        self.inner.get_stream_offset(consumer_id).await
+    }
+
+    async fn anomaly_heatmap(&self, query: HeatmapQuery) -> Result<Vec<HeatmapBucket>> {
+        self.faults.inject("anomaly_heatmap").await?;
+        self.inner.anomaly_heatmap(query).await
+    }
+
+    async fn write_cost_rollups(&self, rollups: &[CostRollup]) -> Result<()> {
+        self.faults.inject("write_cost_rollups").await?;
+        self.inner.write_cost_rollups(rollups).await
+    }
+
+    async fn query_cost_report(&self, query: CostReportQuery) -> Result<Vec<CostReportRow>> {
+        self.faults.inject("query_cost_report").await?;
+        self.inner.query_cost_report(query).await
+    }
+
+    async fn write_alert_deliveries(&self, deliveries: &[AlertMetadata]) -> Result<()> {
+        self.faults.inject("write_alert_deliveries").await?;
+        self.inner.write_alert_deliveries(deliveries).await
+    }
+
+    async fn query_alert_deliveries(&self, query: DeliveryQuery) -> Result<Vec<AlertMetadata>> {
+        self.faults.inject("query_alert_deliveries").await?;
+        self.inner.query_alert_deliveries(query).await
+    }
+
+    async fn write_incident(&self, incident: &Incident) -> Result<()> {
+        self.faults.inject("write_incident").await?;
+        self.inner.write_incident(incident).await
+    }
+
+    async fn query_incidents(&self, query: IncidentQuery) -> Result<Vec<Incident>> {
+        self.faults.inject("query_incidents").await?;
+        self.inner.query_incidents(query).await
+    }
+
+    async fn write_redetected_anomalies(
+        &self,
+        job_id: Uuid,
+        anomalies: &[AnomalyEvent],
+    ) -> Result<()> {
+        self.faults.inject("write_redetected_anomalies").await?;
+        self.inner
+            .write_redetected_anomalies(job_id, anomalies)
+            .await
+    }
+
+    async fn health_check(&self) -> Result<()> {
+        self.inner.health_check().await
+    }
+
+    fn upserts_by_id(&self) -> bool {
+        self.inner.upserts_by_id()
+    }
+}
+
+/// Alerter with injected faults, keeping the wrapped alerter's name
+struct ChaosAlerter {
+    inner: Arc<dyn Alerter>,
+    faults: FaultInjector,
+}
+
+#[async_trait]
+impl Alerter for ChaosAlerter {
+    async fn send(&self, alert: &AnomalyEvent) -> Result<()> {
+        self.faults.inject(self.inner.name()).await?;
+        self.inner.send(alert).await
+    }
+
+    async fn send_batch(&self, alerts: &[AnomalyEvent]) -> Result<()> {
+        self.faults.inject(self.inner.name()).await?;
+        self.inner.send_batch(alerts).await
+    }
+
+    async fn send_with_receipt(&self, alert: &AnomalyEvent) -> Result<Option<String>> {
+        self.faults.inject(self.inner.name()).await?;
+        self.inner.send_with_receipt(alert).await
+    }
+
+    async fn send_batch_with_receipts(
+        &self,
+        alerts: &[AnomalyEvent],
+    ) -> Result<Vec<Option<String>>> {
+        self.faults.inject(self.inner.name()).await?;
+        self.inner.send_batch_with_receipts(alerts).await
+    }
+
+    async fn health_check(&self) -> Result<()> {
+        self.inner.health_check().await
+    }
+
+    async fn rotate_credentials(&self, settings: &serde_json::Value) -> Result<bool> {
+        self.inner.rotate_credentials(settings).await
+    }
+
+    fn name(&self) -> &str {
+        self.inner.name()
+    }
+}
+
+/// Ingester with injected faults in batch reads
+struct ChaosIngester {
+    inner: Box<dyn Ingester>,
+    faults: FaultInjector,
+}
+
+#[async_trait]
+impl Ingester for ChaosIngester {
+    async fn start(&mut self) -> Result<()> {
+        self.inner.start().await
+    }
+
+    async fn stop(&mut self) -> Result<()> {
+        self.inner.stop().await
+    }
+
+    async fn next_batch(&mut self) -> Result<Vec<TelemetryEvent>> {
+        self.faults.inject("next_batch").await?;
+        self.inner.next_batch().await
+    }
+
+    async fn pause(&mut self) -> Result<bool> {
+        self.inner.pause().await
+    }
+
+    async fn resume(&mut self) -> Result<()> {
+        self.inner.resume().await
+    }
+
+    async fn health_check(&self) -> Result<()> {
+        self.inner.health_check().await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use llm_sentinel_storage::prelude::InMemoryStorage;
+
+    fn chaos(faults: FaultConfig, duration_secs: Option<u64>) -> ChaosConfig {
+        ChaosConfig {
+            duration_secs,
+            storage: Some(faults),
+            alerting: None,
+            ingestion: None,
+        }
+    }
+
+    fn failing() -> FaultConfig {
+        FaultConfig {
+            failure_probability: 1.0,
+            latency_probability: 0.0,
+            latency_ms: 0,
+        }
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn test_faults_injected_until_duration_elapses() {
+        let storage = wrap_storage(
+            Arc::new(InMemoryStorage::new()),
+            Some(&chaos(failing(), Some(60))),
+        );
+        let err = storage.get_anomaly(Uuid::new_v4()).await.unwrap_err();
+        assert!(matches!(err, Error::Connection(_)));
+        // Health checks are left alone
+        storage.health_check().await.unwrap();
+
+        tokio::time::advance(Duration::from_secs(61)).await;
+        assert!(storage.get_anomaly(Uuid::new_v4()).await.unwrap().is_none());
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn test_latency_injected() {
+        let faults = FaultConfig {
+            failure_probability: 0.0,
+            latency_probability: 1.0,
+            latency_ms: 500,
+        };
+        let storage = wrap_storage(Arc::new(InMemoryStorage::new()), Some(&chaos(faults, None)));
+
+        let start = Instant::now();
+        storage.get_anomaly(Uuid::new_v4()).await.unwrap();
+        assert!(start.elapsed() >= Duration::from_millis(500));
+    }
+
+    #[test]
+    fn test_unconfigured_targets_unwrapped() {
+        let storage: Arc<dyn Storage> = Arc::new(InMemoryStorage::new());
+        let config = ChaosConfig {
+            duration_secs: None,
+            storage: None,
+            alerting: Some(failing()),
+            ingestion: None,
+        };
+        assert!(Arc::ptr_eq(
+            &wrap_storage(storage.clone(), Some(&config)),
+            &storage
+        ));
+    }
+}
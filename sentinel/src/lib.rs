@@ -0,0 +1,6229 @@
+//! # LLM-Sentinel
+//!
+//! Orchestrates all components of the sentinel system:
+//! - Ingestion: Kafka or Pub/Sub consumer for telemetry with per-service
+//!   quotas, data quality scoring, clock skew policies and embedding of
+//!   events sent without embeddings
+//! - Plugins: WASM enrichers and detectors, and Rhai scripting hooks
+//! - Detection: Multi-detector anomaly detection engine, SLO burn-rate and
+//!   provider availability tracking, and auto-resolution of recovered
+//!   anomalies
+//! - Storage: InfluxDB time-series storage and cost rollups
+//! - Alerting: RabbitMQ, Pub/Sub and registry-constructed alerters,
+//!   remediation actions, and scheduled digests
+//! - API: REST API server, WebSocket anomaly stream, and gRPC health
+//!   checking and reflection
+//!
+//! Processed telemetry, stored anomalies, dispatched alerts, anomaly state
+//! changes and incident updates are published on an [`EventBus`]; the API
+//! stream, the audit log, alert re-sending and incident notifications
+//! subscribe to it, and embedders can add their own subscribers through
+//! [`Sentinel::event_bus`].
+//!
+//! With `storage.snapshots` configured, learned runtime state (baselines,
+//! detector state and open deduplication windows) is snapshotted through
+//! [`SnapshotControl`] and can be restored on startup, so a new deployment
+//! does not relearn it.
+//!
+//! With `federation` configured, an edge instance forwards the anomalies it
+//! detects to a central instance through [`FederationForwarder`], and a
+//! central instance accepts them as a [`FederationSink`], deduplicating
+//! across regions and serving them through its API.
+//!
+//! With `leader_election` configured, replicas compete for a Redis lease and
+//! [`TaskScope::LeaderOnly`] background tasks run only on the replica
+//! holding it. With `standby` configured as well, only the leader consumes
+//! telemetry: it publishes its learned state to a [`StateStream`], and the
+//! other replicas stand by restoring it, ready to take over with warm
+//! baselines.
+//!
+//! With `observability.log_shipping` configured and a [`LogShipper`] passed
+//! to the builder, the sentinel's own warnings and errors are shipped to
+//! Kafka or OpenSearch with their correlation IDs.
+//!
+//! Binaries built with the `chaos` feature inject the failures and latency
+//! configured under `chaos` into storage, alerter and ingester calls, to
+//! exercise retries and circuit breakers in staging.
+//!
+//! [`SentinelBuilder`] constructs every component from configuration, and
+//! lets callers inject their own [`Storage`], [`Ingester`], [`Alerter`],
+//! [`Enricher`] and [`DetectionEngine`] instead, for embedding sentinel in other binaries or
+//! for integration tests.
+//!
+//! Embedders depend on this crate alone: [`config`], [`events`],
+//! [`ingestion`], [`detection`], [`storage`], [`alerting`], [`plugins`],
+//! [`services`] and [`error`] re-export the types the builder and its
+//! extension points take, and
+//! [`prelude`] the ones most embedders use. These paths are the stable API
+//! and follow semver; items reached through the component crates
+//! (`llm_sentinel_core`, `llm_sentinel_detection`, ...) directly may change
+//! in any release.
+//!
+//! ```no_run
+//! use llm_sentinel::prelude::*;
+//!
+//! # async fn embed(storage: std::sync::Arc<dyn Storage>) -> anyhow::Result<()> {
+//! let config = Config::from_file("sentinel.yaml")?;
+//! let sentinel = Sentinel::builder(config)
+//!     .with_storage(storage)
+//!     .with_api(false)
+//!     .build()
+//!     .await?;
+//! sentinel.run().await
+//! # }
+//! ```
+
+#![warn(missing_debug_implementations, rust_2018_idioms, unreachable_pub)]
+
+#[cfg(feature = "chaos")]
+pub mod chaos;
+pub mod log_shipping;
+pub mod runtime;
+
+/// Errors returned by component traits and configuration loading
+pub mod error {
+    pub use llm_sentinel_core::{Error, Result};
+}
+
+/// Configuration, as loaded from `sentinel.yaml`, and detection documents
+pub mod config {
+    pub use llm_sentinel_core::config::*;
+    pub use llm_sentinel_core::tuning::{
+        render_changes, DetectionDocument, DetectionPlan, SectionChange,
+    };
+}
+
+/// Telemetry and anomaly events, and the bus subsystems publish them on
+pub mod events {
+    pub use llm_sentinel_core::bus::{AlertDispatched, EventBus, IncidentEvent, LifecycleEvent};
+    pub use llm_sentinel_core::events::{
+        AlertMetadata, AlertStatus, AnomalyContext, AnomalyDetails, AnomalyEvent, PromptInfo,
+        ResponseInfo, TelemetryEvent,
+    };
+    pub use llm_sentinel_core::incident::{Incident, IncidentState, IncidentUpdate};
+    pub use llm_sentinel_core::labels::Labels;
+    pub use llm_sentinel_core::lifecycle::AnomalyState;
+    pub use llm_sentinel_core::types::{
+        AnomalyType, DetectionMethod, Environment, ModelId, ServiceId, Severity,
+    };
+}
+
+/// Telemetry sources
+pub mod ingestion {
+    pub use llm_sentinel_ingestion::prelude::{OtlpParser, ReplayIngester};
+    pub use llm_sentinel_ingestion::Ingester;
+}
+
+/// Anomaly detection
+pub mod detection {
+    pub use llm_sentinel_detection::prelude::{DetectionEngine, EngineConfig, EngineSnapshot};
+    pub use llm_sentinel_detection::{Detector, DetectorStats, DetectorType};
+}
+
+/// Storage backends and queries
+pub mod storage {
+    pub use llm_sentinel_storage::prelude::{
+        AnomalyQuery, SnapshotStore, StateStream, TelemetryQuery, TimeRange,
+    };
+    #[cfg(any(test, feature = "test-util"))]
+    pub use llm_sentinel_storage::prelude::{InMemoryStorage, MemoryStateStream};
+    pub use llm_sentinel_storage::Storage;
+}
+
+/// Alert delivery
+pub mod alerting {
+    #[cfg(any(test, feature = "test-util"))]
+    pub use llm_sentinel_alerting::prelude::RecordingAlerter;
+    pub use llm_sentinel_alerting::prelude::{AlerterFactory, AlerterRegistry};
+    pub use llm_sentinel_alerting::Alerter;
+}
+
+/// Enrichment and scripting hooks
+pub mod plugins {
+    pub use llm_sentinel_plugins::prelude::ScriptHooks;
+    pub use llm_sentinel_plugins::Enricher;
+}
+
+/// Runtime services the builder takes: clock, leadership, audit log and
+/// snapshots
+pub mod services {
+    pub use llm_sentinel_core::audit::{AuditEntry, AuditLog, AuditOutcome};
+    pub use llm_sentinel_core::clock::Clock;
+    pub use llm_sentinel_core::leader::{LeaderElector, Leadership, TaskScope};
+    pub use llm_sentinel_core::snapshot::{RuntimeSnapshot, SnapshotInfo};
+}
+
+/// The types most embedders use
+pub mod prelude {
+    pub use crate::alerting::Alerter;
+    pub use crate::config::Config;
+    pub use crate::detection::{DetectionEngine, Detector};
+    pub use crate::error::Error;
+    pub use crate::events::{
+        AnomalyEvent, AnomalyType, ModelId, ServiceId, Severity, TelemetryEvent,
+    };
+    pub use crate::ingestion::Ingester;
+    pub use crate::plugins::Enricher;
+    pub use crate::storage::Storage;
+    pub use crate::{Sentinel, SentinelBuilder};
+}
+
+use crate::log_shipping::LogShipper;
+use anyhow::{Context, Result};
+use llm_sentinel_alerting::{deduplication, prelude::*, routing::DELIVERY_SCHEDULE_LABEL};
+use llm_sentinel_api::prelude::*;
+use llm_sentinel_core::{
+    audit::{AuditEntry, AuditLog, AuditOutcome, TracingAuditLog},
+    bus::{AlertDispatched, EventBus, IncidentEvent, LifecycleEvent},
+    clock::Clock,
+    config::{BaselineBootstrapConfig, Config, DetectionOrdering, SearchConfig, SloConfig},
+    correlation,
+    events::{AlertMetadata, AlertStatus, AnomalyEvent, TelemetryEvent},
+    federation::stamp_region,
+    identifiers::IdentifierNormalizer,
+    incident::IncidentUpdate,
+    leader::{Leadership, TaskScope},
+    lifecycle::{AnomalyState, AUTO_RESOLVER, EXPIRER},
+    memory::{MemoryConsumer, MemoryWatchdog, ShedCost},
+    pricing::PriceTable,
+    privacy::{self, AggregationOnly},
+    retry::{retry, RetryError, RetryPolicy},
+    secrets::{CredentialRotation, SecretResolver, AWS_SECRETS_MANAGER_SCHEME, VAULT_SCHEME},
+    selfcheck::{self, SelfCheck, SelfCheckReport},
+    simulation::{self, SimulationReport},
+    snapshot::{RuntimeSnapshot, SnapshotInfo},
+    suppression::{self, SuppressionSchedules},
+    timing::{self, Breakdown, StageTiming},
+    tuning::{DetectionDocument, DetectionPlan, SectionChange},
+    types::{AnomalyType, ModelId, ServiceId},
+    window::WindowSpec,
+    workers::{WorkerPool, WorkerPoolConfig},
+};
+use llm_sentinel_detection::prelude::*;
+use llm_sentinel_ingestion::prelude::*;
+use llm_sentinel_plugins::prelude::*;
+use llm_sentinel_storage::prelude::*;
+use std::{
+    borrow::Cow,
+    collections::{BTreeMap, HashMap},
+    future::Future,
+    sync::{
+        atomic::{AtomicBool, AtomicUsize, Ordering},
+        Arc, RwLock,
+    },
+    time::{Duration, Instant},
+};
+use tokio::{signal, sync::Mutex};
+use tracing::{debug, error, info, warn, Instrument};
+use uuid::Uuid;
+
+/// How often expired deduplication windows are summarized
+const DEDUP_SUMMARY_INTERVAL: Duration = Duration::from_secs(30);
+
+/// How often scheduled digests are checked for being due
+const DIGEST_CHECK_INTERVAL: Duration = Duration::from_secs(60);
+
+/// How long shutdown waits for queued alerts to be sent
+const ALERT_FLUSH_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// Attempts at persisting a priority (High or Critical) anomaly or the
+/// event that triggered it
+const PRIORITY_WRITE_ATTEMPTS: u32 = 5;
+
+/// Backoff before the first retry of a priority write
+const PRIORITY_WRITE_DELAY: Duration = Duration::from_millis(100);
+
+/// How long self-checks wait for one dependency
+const SELF_CHECK_TIMEOUT: Duration = Duration::from_secs(15);
+
+/// How often the gRPC health status is refreshed from storage health
+#[cfg(feature = "grpc")]
+const GRPC_HEALTH_INTERVAL: Duration = Duration::from_secs(10);
+
+/// How often a standby checks whether it took the leader lease
+const STANDBY_POLL_INTERVAL: Duration = Duration::from_millis(250);
+
+/// Snapshot section holding baselines and detector state
+const DETECTION_SECTION: &str = "detection";
+
+/// Snapshot section holding open deduplication windows
+const DEDUPLICATION_SECTION: &str = "deduplication";
+
+/// Telemetry waiting in the detection queue, tracked against the memory
+/// budget
+///
+/// Queued events cannot be evicted; while the budget is exceeded new events
+/// are stored without detection instead.
+#[derive(Debug, Default)]
+struct DetectionQueueMemory {
+    bytes: AtomicUsize,
+}
+
+impl DetectionQueueMemory {
+    fn enqueued(&self, event: &TelemetryEvent) {
+        self.bytes
+            .fetch_add(event.approximate_size(), Ordering::Relaxed);
+    }
+
+    fn dequeued(&self, event: &TelemetryEvent) {
+        self.bytes
+            .fetch_sub(event.approximate_size(), Ordering::Relaxed);
+    }
+}
+
+impl MemoryConsumer for DetectionQueueMemory {
+    fn name(&self) -> &str {
+        "detection_queue"
+    }
+
+    fn memory_usage(&self) -> usize {
+        self.bytes.load(Ordering::Relaxed)
+    }
+
+    fn shed_cost(&self) -> ShedCost {
+        ShedCost::Expensive
+    }
+
+    fn shed(&self, _bytes: usize) -> usize {
+        0
+    }
+}
+
+/// Event queued for a detection worker
+#[derive(Debug)]
+struct DetectionJob {
+    event: TelemetryEvent,
+    /// When preparation of the event's batch started
+    received: Instant,
+    /// Time spent preparing the event's batch
+    prepared: Duration,
+    /// When the event was queued
+    queued: Instant,
+}
+
+/// Main Sentinel orchestrator
+pub struct Sentinel {
+    config: Config,
+    storage: Arc<dyn Storage>,
+    anomaly_writer: AnomalyWriter,
+    outbox: Outbox,
+    ingester: Mutex<Option<Box<dyn Ingester>>>,
+    enrichers: Vec<Arc<dyn Enricher>>,
+    scripts: Option<Arc<ScriptHooks>>,
+    cost_aggregator: Option<Arc<CostAggregator>>,
+    budgets: Option<Arc<BudgetForecaster>>,
+    pricing: Option<Arc<PriceTable>>,
+    identifiers: Option<Arc<IdentifierNormalizer>>,
+    quotas: Option<Arc<ServiceQuotas>>,
+    quality: Option<Arc<DataQualityMonitor>>,
+    clock_skew: Option<Arc<ClockSkewPolicy>>,
+    embedder: Option<Arc<Embedder>>,
+    search: Option<Arc<dyn TextSearch>>,
+    similarity: Option<Arc<dyn VectorIndex>>,
+    slo_tracker: Option<Arc<SloTracker>>,
+    availability_tracker: Option<Arc<AvailabilityTracker>>,
+    recovery_monitor: Option<Arc<RecoveryMonitor>>,
+    silence_monitor: Option<Arc<SilenceMonitor>>,
+    trace_aggregator: Option<Arc<TraceAggregator>>,
+    load_shedding: Option<Arc<AdaptiveSampler>>,
+    backpressure: Option<Backpressure>,
+    aggregation_only: Option<AggregationOnly>,
+    memory: Option<Arc<MemoryWatchdog>>,
+    detection_queue: Arc<DetectionQueueMemory>,
+    detection_engine: Arc<Mutex<DetectionEngine>>,
+    /// Detection load ledger, shared with the engine
+    detection_load: Arc<DetectionLoad>,
+    alerters: Vec<Arc<dyn Alerter>>,
+    router: AlertRouter,
+    suppression: SuppressionSchedules,
+    remediation: Option<Arc<RemediationEngine>>,
+    deduplicator: Arc<AlertDeduplicator>,
+    dispatcher: Option<AlertDispatcher>,
+    holding_queue: Option<Arc<HoldingQueue>>,
+    digests: Option<Arc<DigestScheduler>>,
+    incidents: Option<Arc<IncidentTracker>>,
+    audit_log: Arc<dyn AuditLog>,
+    snapshot_store: Option<Arc<dyn SnapshotStore>>,
+    state_stream: Option<Arc<dyn StateStream>>,
+    /// Set until a standby is promoted
+    standby: AtomicBool,
+    /// ID of the last state restored from the primary
+    synced_state: RwLock<Option<String>>,
+    bus: EventBus,
+    leadership: Leadership,
+    secrets: Arc<SecretResolver>,
+    /// Configuration before secret references were resolved
+    unresolved_config: Config,
+    credentials: Vec<Arc<dyn CredentialRotation>>,
+    /// Alerters built from `alerting.alerters`, in order
+    spec_alerters: Vec<Arc<dyn Alerter>>,
+    /// Re-detection jobs submitted since startup
+    redetect_jobs: Arc<RwLock<HashMap<Uuid, RedetectJob>>>,
+    /// Clock deduplication summaries and digests are timed by
+    clock: Clock,
+    enable_api: bool,
+}
+
+impl Sentinel {
+    /// Start building a Sentinel from configuration
+    pub fn builder(config: Config) -> SentinelBuilder {
+        SentinelBuilder::new(config)
+    }
+
+    /// Create a Sentinel with every component constructed from configuration
+    pub async fn new(config: Config) -> Result<Self> {
+        Self::builder(config).build().await
+    }
+
+    /// Configuration the Sentinel was built from
+    pub fn config(&self) -> &Config {
+        &self.config
+    }
+
+    /// Names of the configured alerters
+    pub fn alerter_names(&self) -> Vec<&str> {
+        self.alerters.iter().map(|a| a.name()).collect()
+    }
+
+    /// Bus the processing pipeline publishes to
+    pub fn event_bus(&self) -> &EventBus {
+        &self.bus
+    }
+
+    /// Whether this replica leads
+    pub fn leadership(&self) -> &Leadership {
+        &self.leadership
+    }
+
+    /// Whether this replica is a warm standby, restoring the primary's
+    /// state instead of consuming telemetry
+    pub fn is_standby(&self) -> bool {
+        self.standby.load(Ordering::SeqCst)
+    }
+
+    /// Run `task` every `interval`; [`TaskScope::LeaderOnly`] tasks skip
+    /// their runs while this replica does not lead
+    pub fn spawn_background_task<F, Fut>(
+        self: &Arc<Self>,
+        name: &'static str,
+        interval: Duration,
+        scope: TaskScope,
+        task: F,
+    ) -> tokio::task::JoinHandle<()>
+    where
+        F: Fn(Arc<Sentinel>) -> Fut + Send + 'static,
+        Fut: Future<Output = ()> + Send,
+    {
+        let sentinel = Arc::clone(self);
+        tokio::spawn(async move {
+            let mut ticker = tokio::time::interval(interval);
+            loop {
+                ticker.tick().await;
+                if !sentinel.leadership.should_run(scope) {
+                    debug!(task = name, "Skipping leader-only task on follower");
+                    continue;
+                }
+                task(Arc::clone(&sentinel)).await;
+            }
+        })
+    }
+
+    /// Run the sentinel system until SIGTERM or CTRL+C
+    pub async fn run(self) -> Result<()> {
+        self.run_until(wait_for_shutdown()).await
+    }
+
+    /// Run the sentinel system until `shutdown` completes
+    pub async fn run_until(
+        self,
+        shutdown: impl Future<Output = ()> + Send + 'static,
+    ) -> Result<()> {
+        info!("Starting Sentinel services...");
+
+        let sentinel = Arc::new(self);
+
+        // Subscribe before anything publishes
+        sentinel.spawn_bus_subscribers();
+
+        // Start API server in background
+        let api_server = sentinel.enable_api.then(|| {
+            let sentinel = sentinel.clone();
+            tokio::spawn(async move { sentinel.start_api_server().await })
+        });
+        let api_server = async move {
+            match api_server {
+                Some(handle) => handle.await,
+                None => std::future::pending().await,
+            }
+        };
+
+        // Start gRPC health checking and reflection in background
+        let grpc_server = sentinel.config.ingestion.grpc.clone().map(|grpc_config| {
+            let sentinel = sentinel.clone();
+            tokio::spawn(async move { grpc_server(sentinel, grpc_config).await })
+        });
+        let grpc_server = async move {
+            match grpc_server {
+                Some(handle) => handle.await,
+                None => std::future::pending().await,
+            }
+        };
+
+        // Write anomalies whose first write failed again and dispatch them.
+        // Every replica holds the anomalies it detected.
+        sentinel.spawn_background_task(
+            "outbox_relay",
+            Duration::from_secs(sentinel.config.storage.anomaly_writer.retry_interval_secs),
+            TaskScope::EveryReplica,
+            |sentinel| async move { sentinel.relay_outbox().await },
+        );
+
+        // Publish learned state from the primary, and restore it on
+        // standbys. Every replica syncs, in the direction of its role.
+        if let Some(standby) = &sentinel.config.standby {
+            sentinel.spawn_background_task(
+                "state_sync",
+                Duration::from_secs(standby.sync_interval_secs),
+                TaskScope::EveryReplica,
+                |sentinel| async move { sentinel.sync_state().await },
+            );
+        }
+
+        // Send held alerts once their delivery window opens. The holding
+        // queue file belongs to this instance, so every replica releases
+        // its own.
+        if let Some(schedules) = &sentinel.config.alerting.delivery_schedules {
+            sentinel.spawn_background_task(
+                "held_alert_release",
+                Duration::from_secs(schedules.release_interval_secs),
+                TaskScope::EveryReplica,
+                |sentinel| async move { sentinel.release_held_alerts().await },
+            );
+        }
+
+        // Send deduplication summaries as windows expire. Deduplication
+        // windows are held in memory, so every replica summarizes its own.
+        if sentinel.config.alerting.dedup_summary {
+            sentinel.spawn_background_task(
+                "dedup_summaries",
+                DEDUP_SUMMARY_INTERVAL,
+                TaskScope::EveryReplica,
+                |sentinel| async move { sentinel.flush_dedup_summaries().await },
+            );
+        }
+
+        // Send scheduled digests. Digests report on shared storage, so only
+        // the leader sends them.
+        if sentinel.digests.is_some() {
+            sentinel.spawn_background_task(
+                "digests",
+                DIGEST_CHECK_INTERVAL,
+                TaskScope::LeaderOnly,
+                |sentinel| async move { sentinel.send_due_digests(sentinel.clock.now()).await },
+            );
+        }
+
+        // Expire anomalies nobody acted on. Anomaly states are shared, so
+        // only the leader sweeps.
+        if let Some(expiry) = &sentinel.config.alerting.expiry {
+            sentinel.spawn_background_task(
+                "anomaly_expiry",
+                Duration::from_secs(expiry.check_interval_secs),
+                TaskScope::LeaderOnly,
+                |sentinel| async move { sentinel.expire_stale_anomalies(sentinel.clock.now()).await },
+            );
+        }
+
+        // Project spend against budgets. Cost rollups are shared, so only
+        // the leader alerts.
+        if let Some(budgets) = &sentinel.budgets {
+            sentinel.spawn_background_task(
+                "budget_forecast",
+                Duration::from_secs(budgets.config().check_interval_secs),
+                TaskScope::LeaderOnly,
+                |sentinel| async move { sentinel.check_budgets(sentinel.clock.now()).await },
+            );
+        }
+
+        // Report producers that went quiet. Each replica only hears from the
+        // producers whose events it consumes.
+        if let Some(silence) = &sentinel.config.detection.silence {
+            sentinel.spawn_background_task(
+                "telemetry_silence",
+                Duration::from_secs(silence.check_interval_secs),
+                TaskScope::EveryReplica,
+                |sentinel| async move { sentinel.check_silence().await },
+            );
+        }
+
+        // Evaluate chains whose spans stopped arriving. Each replica only
+        // sees the spans it consumes.
+        if let Some(traces) = &sentinel.config.detection.traces {
+            sentinel.spawn_background_task(
+                "trace_evaluation",
+                Duration::from_secs(traces.check_interval_secs),
+                TaskScope::EveryReplica,
+                |sentinel| async move { sentinel.check_traces().await },
+            );
+        }
+
+        // Evict from in-memory structures nearing the memory budget. Every
+        // replica holds its own.
+        if let Some(memory) = &sentinel.config.memory {
+            sentinel.spawn_background_task(
+                "memory_watchdog",
+                Duration::from_secs(memory.check_interval_secs),
+                TaskScope::EveryReplica,
+                |sentinel| async move {
+                    if let Some(watchdog) = &sentinel.memory {
+                        watchdog.check();
+                    }
+                },
+            );
+        }
+
+        // Fetch secret references again and switch clients to rotated
+        // values. Every replica holds its own clients.
+        if let Some(refresh_secs) = sentinel
+            .config
+            .secrets
+            .as_ref()
+            .and_then(|secrets| secrets.refresh_secs)
+        {
+            sentinel.spawn_background_task(
+                "secret_refresh",
+                Duration::from_secs(refresh_secs),
+                TaskScope::EveryReplica,
+                |sentinel| async move {
+                    if let Err(e) = CredentialControl::rotate(sentinel.as_ref()).await {
+                        warn!("Failed to rotate credentials: {}", e);
+                    }
+                },
+            );
+        }
+
+        // Rotate credentials on SIGHUP
+        #[cfg(unix)]
+        {
+            let sentinel = sentinel.clone();
+            tokio::spawn(async move { rotate_on_hangup(sentinel).await });
+        }
+
+        // Start ingestion pipeline
+        let ingestion_pipeline = {
+            let sentinel = sentinel.clone();
+            tokio::spawn(async move { sentinel.start_ingestion_pipeline().await })
+        };
+
+        // Wait for shutdown signal
+        let shutdown = tokio::spawn(async move {
+            shutdown.await;
+            info!("Shutdown signal received");
+        });
+
+        // Run all tasks concurrently
+        tokio::select! {
+            result = api_server => {
+                error!("API server exited: {:?}", result);
+            }
+            result = grpc_server => {
+                error!("gRPC server exited: {:?}", result);
+            }
+            result = ingestion_pipeline => {
+                error!("Ingestion pipeline exited: {:?}", result);
+            }
+            _ = shutdown => {
+                info!("Initiating graceful shutdown...");
+            }
+        }
+
+        if !sentinel.flush_alerts(ALERT_FLUSH_TIMEOUT).await {
+            warn!("Queued alerts were still being sent at shutdown");
+        }
+
+        if let Some(aggregator) = &sentinel.cost_aggregator {
+            if let Err(e) = aggregator.flush(sentinel.storage.as_ref()).await {
+                error!("Failed to flush cost rollups on shutdown: {}", e);
+            }
+        }
+
+        if let Some(search) = &sentinel.search {
+            if let Err(e) = search.commit() {
+                error!("Failed to commit search index on shutdown: {}", e);
+            }
+        }
+
+        if sentinel
+            .config
+            .storage
+            .snapshots
+            .as_ref()
+            .is_some_and(|snapshots| snapshots.snapshot_on_shutdown)
+        {
+            if let Err(e) = SnapshotControl::snapshot(sentinel.as_ref()).await {
+                error!("Failed to snapshot runtime state on shutdown: {}", e);
+            }
+        }
+
+        if let Err(e) = sentinel.leadership.release().await {
+            error!("Failed to release leader lease on shutdown: {}", e);
+        }
+
+        info!("Sentinel stopped");
+
+        Ok(())
+    }
+
+    /// Start API server
+    async fn start_api_server(self: Arc<Self>) -> Result<()> {
+        let api_config = ApiConfig {
+            bind_addr: format!("{}:{}", self.config.server.host, self.config.server.port)
+                .parse()
+                .context("Invalid server bind address")?,
+            tls: self.config.server.tls.clone(),
+            listeners: self.config.server.listeners.clone(),
+            enable_cors: true,
+            cors_origins: vec!["*".to_string()],
+            timeout_secs: self.config.server.request_timeout_secs,
+            max_body_size: self.config.server.max_body_size,
+            compression: self.config.server.compression,
+            access_log: self.config.server.access_log.clone(),
+            route_timeouts: self.config.server.route_timeouts.clone(),
+            response_cache: self.config.server.response_cache.clone(),
+            idempotency: self.config.server.idempotency.clone(),
+            dashboard: self.config.server.dashboard,
+            enable_logging: true,
+            metrics_path: "/metrics".to_string(),
+        };
+
+        let mut server = ApiServer::new(
+            api_config,
+            self.storage.clone(),
+            env!("CARGO_PKG_VERSION").to_string(),
+        );
+        if let Some(tracker) = &self.slo_tracker {
+            server = server.with_slo_tracker(tracker.clone());
+        }
+        if let Some(tracker) = &self.availability_tracker {
+            server = server.with_availability_tracker(tracker.clone());
+        }
+        if let Some(pricing) = &self.pricing {
+            server = server.with_pricing(pricing.clone());
+        }
+        if let Some(budgets) = &self.budgets {
+            server = server.with_budgets(budgets.clone());
+        }
+        if let Some(incidents) = &self.incidents {
+            server = server.with_incident_tracker(incidents.clone());
+        }
+        if let Some(memory) = &self.memory {
+            server = server.with_memory_watchdog(memory.clone());
+        }
+        if let Some(search) = &self.search {
+            server = server.with_search(search.clone());
+        }
+        if let Some(similarity) = &self.similarity {
+            server = server.with_similarity(similarity.clone());
+        }
+        let deduplicator = self.deduplicator.clone();
+        server = server.with_dedup_stats(Arc::new(move || {
+            let stats = deduplicator.get_stats();
+            DedupStatsReport {
+                active_signatures: stats.total_signatures,
+                total_deduplicated: stats.total_deduplicated,
+                deduplication_rate: stats.deduplication_rate(),
+                by_severity: stats.by_severity.into_iter().collect(),
+            }
+        }));
+        server = server.with_event_bus(self.bus.clone());
+        if self.snapshot_store.is_some() {
+            server = server.with_snapshots(self.clone());
+        }
+        server = server.with_credentials(self.clone());
+        server = server.with_simulator(self.clone());
+        server = server.with_redetector(self.clone());
+        server = server.with_redelivery(self.clone());
+        server = server.with_self_checker(self.clone());
+        server = server.with_detection_stats(self.clone());
+        server = server.with_tuning(self.clone());
+        if let Some(sampler) = &self.load_shedding {
+            let sampler = sampler.clone();
+            server = server.with_health_check(Arc::new(move || {
+                let status = sampler.status();
+                if status.degraded {
+                    ComponentHealth::degraded(
+                        "detection",
+                        format!(
+                            "Detection queue backed up, detecting {:.0}% of events",
+                            status.sample_rate * 100.0
+                        ),
+                    )
+                } else {
+                    ComponentHealth::healthy("detection")
+                }
+            }));
+        }
+        if let Some(federation) = &self.config.federation {
+            if !federation.edges.is_empty() {
+                server = server
+                    .with_federation(FederationReceiver::new(&federation.edges, self.clone()));
+            }
+        }
+
+        server
+            .serve()
+            .await
+            .map_err(|e| anyhow::anyhow!("API server error: {}", e))?;
+
+        Ok(())
+    }
+
+    /// Start the built-in bus subscribers
+    ///
+    /// State changes are recorded in the audit log, and anomalies whose
+    /// state changed are re-sent so downstream systems follow. Anomalies
+    /// responders closed are no longer watched for recovery. Incident
+    /// updates are sent to the alerters notified of incidents, and
+    /// responders' incident state changes are audited.
+    fn spawn_bus_subscribers(self: &Arc<Self>) {
+        let mut lifecycle = self.bus.lifecycle().subscribe();
+        let sentinel = Arc::clone(self);
+        tokio::spawn(async move {
+            while let Some(event) = lifecycle.recv().await {
+                let LifecycleEvent { anomaly, change } = event.as_ref();
+                if let Some(monitor) = &sentinel.recovery_monitor {
+                    if !anomaly.state.is_active() {
+                        monitor.forget(anomaly.alert_id);
+                    }
+                }
+                // Auto-resolutions are sent by resolve_recovered when
+                // configured; expiries are not sent
+                if !change.is_automatic() {
+                    sentinel.dispatch(anomaly).await;
+                }
+            }
+        });
+
+        let mut lifecycle = self.bus.lifecycle().subscribe();
+        let audit_log = self.audit_log.clone();
+        tokio::spawn(async move {
+            while let Some(event) = lifecycle.recv().await {
+                let change = &event.change;
+                let mut entry = AuditEntry::new(
+                    change.actor.as_deref().unwrap_or("api"),
+                    "anomaly.state",
+                    change.alert_id.to_string(),
+                    AuditOutcome::Success,
+                )
+                .with_detail("from", change.from.to_string())
+                .with_detail("to", change.to.to_string());
+                if let Some(reason) = &change.reason {
+                    entry = entry.with_detail("reason", reason.clone());
+                }
+                entry.timestamp = change.at;
+                audit_log.record(entry);
+            }
+        });
+
+        let mut alerts = self.bus.alerts().subscribe();
+        let audit_log = self.audit_log.clone();
+        tokio::spawn(async move {
+            while let Some(dispatched) = alerts.recv().await {
+                for delivery in &dispatched.deliveries {
+                    let outcome = match delivery.status {
+                        AlertStatus::Delivered => AuditOutcome::Success,
+                        AlertStatus::Failed => AuditOutcome::Failure,
+                        AlertStatus::Pending | AlertStatus::Deduplicated => AuditOutcome::Skipped,
+                    };
+                    let mut entry = AuditEntry::new(
+                        &delivery.alerter,
+                        "alert.delivery",
+                        delivery.alert_id.clone(),
+                        outcome,
+                    )
+                    .with_detail("attempts", delivery.attempts.to_string());
+                    if let Some(error) = &delivery.error {
+                        entry = entry.with_detail("error", error.clone());
+                    }
+                    if let Some(request) = &delivery.request {
+                        entry = entry.with_detail("request", request.clone());
+                    }
+                    if let Some(response) = &delivery.response {
+                        entry = entry.with_detail("response", response.clone());
+                    }
+                    entry.timestamp = delivery.last_attempt;
+                    audit_log.record(entry);
+                }
+            }
+        });
+
+        let mut incidents = self.bus.incidents().subscribe();
+        let sentinel = Arc::clone(self);
+        tokio::spawn(async move {
+            while let Some(event) = incidents.recv().await {
+                sentinel.notify_incident(&event).await;
+            }
+        });
+
+        let mut incidents = self.bus.incidents().subscribe();
+        let audit_log = self.audit_log.clone();
+        tokio::spawn(async move {
+            while let Some(event) = incidents.recv().await {
+                if event.update != IncidentUpdate::StateChanged {
+                    continue;
+                }
+                let incident = &event.incident;
+                let Some(change) = incident.timeline.last() else {
+                    continue;
+                };
+                let mut entry = AuditEntry::new(
+                    change.actor.as_deref().unwrap_or("api"),
+                    "incident.state",
+                    incident.incident_id.to_string(),
+                    AuditOutcome::Success,
+                )
+                .with_detail("to", incident.state.to_string());
+                if let Some(reason) = &change.text {
+                    entry = entry.with_detail("reason", reason.clone());
+                }
+                entry.timestamp = change.at;
+                audit_log.record(entry);
+            }
+        });
+    }
+
+    /// Create the ingester configured under `ingestion`
+    fn ingester_from_config(&self) -> Result<Box<dyn Ingester>> {
+        let ingester: Box<dyn Ingester> = match &self.config.ingestion.pubsub {
+            Some(pubsub_config) => {
+                info!("Starting Pub/Sub ingestion pipeline...");
+                Box::new(
+                    PubSubIngester::new(
+                        pubsub_config,
+                        self.config.ingestion.batch_size,
+                        self.config.ingestion.batch_timeout_ms,
+                    )
+                    .context("Failed to create Pub/Sub ingester")?,
+                )
+            }
+            None => kafka_ingester(&self.config)?,
+        };
+        Ok(ingester)
+    }
+
+    /// Start ingestion and detection pipeline
+    ///
+    /// Batches are enriched and priced as they arrive, then queued onto a
+    /// bounded pool of `detection.workers` tasks. With keyed ordering, events
+    /// are sharded by service (and model) so each key's events are handled
+    /// by one worker in arrival order while other keys run in parallel. With
+    /// load shedding, events sampled out while the queue is backed up are
+    /// stored without detection. With back-pressure, the ingester is paused
+    /// while the queue is backed up. A standby starts consuming once it is
+    /// promoted.
+    async fn start_ingestion_pipeline(self: &Arc<Self>) -> Result<()> {
+        let injected = self.ingester.lock().await.take();
+        let mut ingester = match injected {
+            Some(ingester) => ingester,
+            None => self.ingester_from_config()?,
+        };
+        #[cfg(feature = "chaos")]
+        {
+            ingester = chaos::wrap_ingester(ingester, self.config.chaos.as_ref());
+        }
+
+        if self.is_standby() {
+            self.promote().await;
+        }
+        ingester.start().await.context("Failed to start ingester")?;
+
+        let detection = &self.config.detection;
+        let pool_config = WorkerPoolConfig {
+            workers: detection.workers,
+            queue_capacity: detection.queue_capacity,
+        };
+        let handler = {
+            let sentinel = Arc::clone(self);
+            move |job: DetectionJob| {
+                let sentinel = Arc::clone(&sentinel);
+                async move {
+                    sentinel.detection_queue.dequeued(&job.event);
+                    let waited = job.queued.elapsed();
+                    sentinel.detection_load.record_queue_wait(waited);
+                    let upstream = [("prepare", job.prepared), ("queue", waited)];
+                    sentinel
+                        .process_timed(&job.event, job.received, &upstream)
+                        .await
+                }
+            }
+        };
+        let pool = match detection.ordering {
+            DetectionOrdering::None => WorkerPool::spawn("detection", &pool_config, handler),
+            DetectionOrdering::PerService => WorkerPool::spawn_ordered(
+                "detection",
+                &pool_config,
+                |job: &DetectionJob| job.event.service_name.clone(),
+                handler,
+            ),
+            DetectionOrdering::PerServiceModel => WorkerPool::spawn_ordered(
+                "detection",
+                &pool_config,
+                |job: &DetectionJob| (job.event.service_name.clone(), job.event.model.clone()),
+                handler,
+            ),
+        };
+
+        info!(
+            workers = pool.workers(),
+            ordering = ?detection.ordering,
+            "Ingestion pipeline ready, consuming telemetry..."
+        );
+
+        // Whether the ingester keeps its connection alive while paused
+        let mut pausable = true;
+        loop {
+            if let Some(backpressure) = &self.backpressure {
+                match backpressure.update(pool.queue_depth()) {
+                    Some(BackpressureAction::Pause) => {
+                        pausable = ingester.pause().await.unwrap_or_else(|e| {
+                            error!("Failed to pause ingester: {}", e);
+                            false
+                        });
+                    }
+                    Some(BackpressureAction::Resume) if pausable => {
+                        if let Err(e) = ingester.resume().await {
+                            error!("Failed to resume ingester: {}", e);
+                            ::metrics::counter!("sentinel_ingestion_errors_total").increment(1);
+                        }
+                    }
+                    _ => {}
+                }
+                // Ingesters that cannot pause are simply not read from
+                if backpressure.is_paused() && !pausable {
+                    tokio::time::sleep(Duration::from_millis(
+                        self.config.ingestion.batch_timeout_ms,
+                    ))
+                    .await;
+                    continue;
+                }
+            }
+
+            match ingester.next_batch().await {
+                Ok(mut events) => {
+                    if events.is_empty() {
+                        continue;
+                    }
+                    let received = Instant::now();
+                    let excluded = self.prepare_batch(&mut events).await;
+                    let prepared = received.elapsed();
+                    for event in &excluded {
+                        self.store_telemetry(event).await;
+                    }
+                    for event in events {
+                        if self.memory.as_ref().is_some_and(|m| m.is_shedding()) {
+                            ::metrics::counter!("sentinel_events_excluded_total",
+                                "reason" => "memory"
+                            )
+                            .increment(1);
+                            self.detection_load.record_shed();
+                            self.store_telemetry(&event).await;
+                            continue;
+                        }
+                        if let Some(sampler) = &self.load_shedding {
+                            if !sampler.should_detect(&event, pool.queue_depth()) {
+                                self.detection_load.record_sampled_out();
+                                self.store_telemetry(&event).await;
+                                continue;
+                            }
+                        }
+                        self.detection_queue.enqueued(&event);
+                        pool.submit(DetectionJob {
+                            event,
+                            received,
+                            prepared,
+                            queued: Instant::now(),
+                        })
+                        .await?;
+                    }
+                }
+                Err(e) => {
+                    error!("Ingestion error: {}", e);
+                    ::metrics::counter!("sentinel_ingestion_errors_total").increment(1);
+
+                    // Backoff on errors
+                    tokio::time::sleep(tokio::time::Duration::from_secs(5)).await;
+                }
+            }
+        }
+    }
+
+    /// Price, store and run detection on a batch of telemetry events
+    pub async fn process_batch(&self, mut events: Vec<TelemetryEvent>) {
+        let received = Instant::now();
+        let excluded = self.prepare_batch(&mut events).await;
+        let prepared = received.elapsed();
+        for event in &excluded {
+            self.store_telemetry(event).await;
+        }
+        for event in &events {
+            // Events wait for the ones before them in the batch
+            let upstream = [
+                ("prepare", prepared),
+                ("queue", received.elapsed() - prepared),
+            ];
+            self.process_timed(event, received, &upstream).await;
+        }
+    }
+
+    /// Process an event, checking it against `detection.event_deadline_ms`
+    ///
+    /// `received` is when preparation of the event's batch started and
+    /// `upstream` the time spent in stages before this call. Events over the
+    /// deadline are logged with the time spent in every stage and counted in
+    /// `sentinel_events_over_deadline_total`, labelled by their slowest stage.
+    async fn process_timed(
+        &self,
+        event: &TelemetryEvent,
+        received: Instant,
+        upstream: &[(&'static str, Duration)],
+    ) {
+        let Some(deadline_ms) = self.config.detection.event_deadline_ms else {
+            return self.process_event(event).await;
+        };
+        let ((), breakdown) = timing::collect(self.process_event(event)).await;
+        let elapsed = received.elapsed();
+        ::metrics::histogram!("sentinel_event_processing_seconds").record(elapsed.as_secs_f64());
+        if elapsed <= Duration::from_millis(deadline_ms) {
+            return;
+        }
+        self.detection_load.record_timed_out();
+
+        let mut stages: Vec<StageTiming> = upstream
+            .iter()
+            .map(|&(stage, elapsed)| StageTiming {
+                stage: stage.into(),
+                elapsed,
+            })
+            .collect();
+        stages.extend(breakdown.0);
+        let breakdown = Breakdown(stages);
+        let slowest = breakdown
+            .slowest()
+            .map(|timing| timing.stage.to_string())
+            .unwrap_or_default();
+        warn!(
+            event_id = %event.event_id,
+            correlation_id = correlation::of(event).unwrap_or_default(),
+            service = %event.service_name,
+            model = %event.model,
+            elapsed_ms = elapsed.as_millis() as u64,
+            deadline_ms,
+            slowest = %slowest,
+            stages = %breakdown,
+            "Event exceeded processing deadline"
+        );
+        ::metrics::counter!("sentinel_events_over_deadline_total", "stage" => slowest).increment(1);
+    }
+
+    /// Normalize, enrich, price, score and embed a batch before anything
+    /// consumes it
+    ///
+    /// Returns the events kept out of detection, late events routed to
+    /// backfill and events of poor data quality; they are still stored.
+    async fn prepare_batch(&self, events: &mut Vec<TelemetryEvent>) -> Vec<TelemetryEvent> {
+        // Tie everything done for an event back to it, keeping IDs set by
+        // producers
+        for event in events.iter_mut() {
+            correlation::assign(event);
+        }
+
+        // Normalize IDs so enrichers and baselines see canonical names
+        if let Some(identifiers) = &self.identifiers {
+            events.retain_mut(|event| match identifiers.apply(event) {
+                Ok(()) => true,
+                Err(e) => {
+                    warn!(event_id = %event.event_id, "Dropping event: {}", e);
+                    ::metrics::counter!("sentinel_events_dropped_total",
+                        "reason" => "invalid_identifier"
+                    )
+                    .increment(1);
+                    false
+                }
+            });
+        }
+
+        // Any event shows its producer is alive, even one dropped below
+        if let Some(monitor) = &self.silence_monitor {
+            for event in events.iter() {
+                monitor.record(event);
+            }
+        }
+
+        // Drop events over their service's quota before spending work on them
+        if let Some(quotas) = &self.quotas {
+            events.retain(|event| match quotas.check(&event.service_name) {
+                QuotaDecision::Admitted => true,
+                decision => {
+                    ::metrics::counter!("sentinel_events_over_quota_total",
+                        "service" => event.service_name.to_string(),
+                        "sampled" => decision.is_processed().to_string()
+                    )
+                    .increment(1);
+                    if !decision.is_processed() {
+                        ::metrics::counter!("sentinel_events_dropped_total",
+                            "reason" => "over_quota"
+                        )
+                        .increment(1);
+                    }
+                    decision.is_processed()
+                }
+            });
+        }
+
+        // Apply the clock skew policy, setting late events aside for backfill
+        let mut excluded = Vec::new();
+        if let Some(skew) = &self.clock_skew {
+            let mut live = Vec::with_capacity(events.len());
+            for mut event in events.drain(..) {
+                match skew.apply(&mut event) {
+                    SkewDecision::Rejected => {
+                        debug!(
+                            event_id = %event.event_id,
+                            timestamp = %event.timestamp,
+                            "Dropping event outside the tolerated clock skew"
+                        );
+                        ::metrics::counter!("sentinel_events_dropped_total",
+                            "reason" => "clock_skew"
+                        )
+                        .increment(1);
+                    }
+                    SkewDecision::Backfill => {
+                        ::metrics::counter!("sentinel_events_excluded_total",
+                            "reason" => "backfill"
+                        )
+                        .increment(1);
+                        excluded.push(event);
+                    }
+                    _ => live.push(event),
+                }
+            }
+            *events = live;
+        }
+
+        // Enrich first so pricing and detection see the final event
+        for event in events.iter_mut().chain(excluded.iter_mut()) {
+            for enricher in &self.enrichers {
+                if let Err(e) = enricher.enrich(event).await {
+                    error!(enricher = enricher.name(), "Failed to enrich event: {}", e);
+                    ::metrics::counter!("sentinel_enrichment_errors_total").increment(1);
+                }
+            }
+        }
+
+        // Compute missing costs before anything consumes them
+        if let Some(pricing) = &self.pricing {
+            for event in events.iter_mut().chain(excluded.iter_mut()) {
+                pricing.apply(event);
+            }
+        }
+
+        // Score data quality on the final event, so pricing can fill costs
+        if let Some(quality) = &self.quality {
+            let mut kept = Vec::with_capacity(events.len());
+            for event in events.drain(..) {
+                let (score, alert) = quality.record(&event);
+                if let Some(alert) = alert {
+                    self.handle_anomaly(&alert).await;
+                }
+                if quality.is_excluded(&score) {
+                    debug!(
+                        event_id = %event.event_id,
+                        issues = ?score.issues,
+                        "Keeping low-quality event out of detection"
+                    );
+                    ::metrics::counter!("sentinel_events_excluded_total",
+                        "reason" => "data_quality"
+                    )
+                    .increment(1);
+                    excluded.push(event);
+                } else {
+                    kept.push(event);
+                }
+            }
+            *events = kept;
+        }
+
+        // Embed what producers did not, before drift detection and indexing
+        if let Some(embedder) = &self.embedder {
+            embedder.embed(events).await;
+        }
+
+        info!(
+            "Received batch of {} telemetry events",
+            events.len() + excluded.len()
+        );
+        excluded
+    }
+
+    /// Record, store and run detection on a single prepared event
+    ///
+    /// Runs in the event's correlation scope, so anomalies detected from it
+    /// and log lines written on the way carry its correlation ID.
+    pub async fn process_event(&self, event: &TelemetryEvent) {
+        let id = correlation::of(event).map_or_else(correlation::generate, str::to_string);
+        let span = tracing::info_span!("event", correlation_id = %id);
+        correlation::scope(id, self.detect_event(event))
+            .instrument(span)
+            .await
+    }
+
+    async fn detect_event(&self, event: &TelemetryEvent) {
+        if let Some(aggregator) = &self.cost_aggregator {
+            aggregator.record(event);
+        }
+
+        // Evaluate SLO burn rates
+        if let Some(tracker) = &self.slo_tracker {
+            for alert in tracker.record(event) {
+                self.handle_anomaly(&alert).await;
+            }
+        }
+
+        // Track provider/model availability
+        if let Some(tracker) = &self.availability_tracker {
+            for alert in tracker.record(event) {
+                self.handle_anomaly(&alert).await;
+            }
+        }
+
+        // Resolve anomalies whose metric returned to baseline
+        if let Some(monitor) = &self.recovery_monitor {
+            for recovery in monitor.record(event) {
+                self.resolve_recovered(&recovery).await;
+            }
+        }
+
+        // Collect spans of multi-span chains
+        if let Some(aggregator) = &self.trace_aggregator {
+            aggregator.record(event);
+        }
+
+        // Events covered by aggregation-only mode only feed the detectors
+        let keep_raw = !self.withholds_raw(event);
+        let stored = self.store_telemetry(event).await;
+
+        // Index embeddings for similarity search
+        if let Some(similarity) = self.similarity.as_ref().filter(|_| keep_raw) {
+            let start = Instant::now();
+            similarity.insert(event);
+            timing::record("similarity", start.elapsed());
+        }
+
+        if keep_raw && self.bus.telemetry().has_subscribers() {
+            self.bus.telemetry().publish(event.clone());
+        }
+
+        // Run detection
+        let lock_start = Instant::now();
+        let mut engine = self.detection_engine.lock().await;
+        timing::record("detection_lock", lock_start.elapsed());
+        let detected = engine.process(event).await;
+        drop(engine);
+        let detected = match (detected, &self.scripts) {
+            (Ok(Some(anomaly)), Some(scripts)) => {
+                let anomaly = scripts.post_detect(anomaly, event);
+                if anomaly.is_none() {
+                    ::metrics::counter!("sentinel_anomalies_suppressed_total").increment(1);
+                }
+                Ok(anomaly)
+            }
+            (detected, _) => detected,
+        };
+        match detected {
+            Ok(Some(anomaly)) if self.is_left_to_chain(event, &anomaly) => {
+                debug!(
+                    alert_id = %anomaly.alert_id,
+                    trace_id = ?event.trace_id,
+                    "Leaving span anomaly to chain evaluation"
+                );
+                ::metrics::counter!("sentinel_span_anomalies_suppressed_total").increment(1);
+            }
+            Ok(Some(anomaly)) => {
+                info!(
+                    alert_id = %anomaly.alert_id,
+                    severity = ?anomaly.severity,
+                    anomaly_type = ?anomaly.anomaly_type,
+                    "Anomaly detected"
+                );
+                // Keep the evidence behind a priority anomaly
+                if keep_raw && !stored && anomaly.severity.is_priority() {
+                    self.persist_trigger(event).await;
+                }
+                self.handle_anomaly(&anomaly).await;
+            }
+            Ok(None) => {
+                // No anomaly detected
+                ::metrics::counter!("sentinel_events_normal_total").increment(1);
+            }
+            Err(e) => {
+                error!("Detection failed: {}", e);
+                ::metrics::counter!("sentinel_detection_errors_total", "detector" => "engine")
+                    .increment(1);
+            }
+        }
+
+        ::metrics::counter!("sentinel_events_processed_total").increment(1);
+    }
+
+    /// Whether aggregation-only mode keeps the raw event out of storage,
+    /// search, similarity and the bus
+    fn withholds_raw(&self, event: &TelemetryEvent) -> bool {
+        self.aggregation_only
+            .as_ref()
+            .is_some_and(|policy| policy.covers_event(event))
+    }
+
+    /// Store an event and index its text for search, returning whether the
+    /// event was stored
+    ///
+    /// Events of aggregation-only services are withheld on every path,
+    /// including events kept out of detection.
+    async fn store_telemetry(&self, event: &TelemetryEvent) -> bool {
+        if self.withholds_raw(event) {
+            ::metrics::counter!("sentinel_raw_events_withheld_total").increment(1);
+            return false;
+        }
+        let stored = timing::time("store", self.storage.write_telemetry(event)).await;
+        if let Err(e) = &stored {
+            error!("Failed to write telemetry: {}", e);
+            ::metrics::counter!("sentinel_storage_errors_total", "type" => "telemetry")
+                .increment(1);
+        }
+
+        // Index prompt/response text for search
+        if let Some(search) = &self.search {
+            let start = Instant::now();
+            let indexed = search.index(std::slice::from_ref(event));
+            timing::record("search_index", start.elapsed());
+            if let Err(e) = indexed {
+                error!("Failed to index telemetry: {}", e);
+                ::metrics::counter!("sentinel_storage_errors_total", "type" => "search")
+                    .increment(1);
+            }
+        }
+        stored.is_ok()
+    }
+
+    /// Retry storing an event that triggered a priority anomaly after its
+    /// first write failed
+    async fn persist_trigger(&self, event: &TelemetryEvent) {
+        let stored = timing::time(
+            "store_retry",
+            write_priority(|| self.storage.write_telemetry(event)),
+        )
+        .await;
+        if let Err(e) = stored {
+            error!(
+                event_id = %event.event_id,
+                "Failed to persist telemetry behind a priority anomaly: {}", e
+            );
+            ::metrics::counter!("sentinel_priority_write_failures_total", "type" => "telemetry")
+                .increment(1);
+        }
+    }
+
+    /// Attach runbook, trace links and recent telemetry context, store an
+    /// anomaly, run matching remediation rules, dispatch it to all alerters
+    /// unless deduplicated or expected by a suppression schedule and record
+    /// each delivery outcome
+    pub async fn handle_anomaly(&self, anomaly: &AnomalyEvent) {
+        self.process_anomaly(anomaly).await;
+    }
+
+    /// Handle an anomaly, returning the alerters it was routed to, or
+    /// `None` when it was deduplicated, expected or could not be written
+    ///
+    /// Test anomalies are not watched for recovery and do not trigger
+    /// remediation. Anomalies inside a suppression window are labelled as
+    /// expected, stored and published, but neither remediated nor alerted
+    /// on. Anomalies are only published and dispatched once written, so no
+    /// alert references an anomaly the query API cannot find; those whose
+    /// write failed wait in the outbox for [`Sentinel::relay_outbox`].
+    async fn process_anomaly(&self, anomaly: &AnomalyEvent) -> Option<Vec<String>> {
+        // Attach the runbook, trace links and recent telemetry so responders
+        // see what led up to the alert
+        let mut anomaly = anomaly.clone();
+        if anomaly.correlation_id.is_none() {
+            anomaly.correlation_id =
+                Some(correlation::current().unwrap_or_else(correlation::generate));
+        }
+        // Anomalies of aggregation-only services keep aggregated evidence only
+        let aggregated = self
+            .aggregation_only
+            .as_ref()
+            .is_some_and(|policy| policy.covers_anomaly(&anomaly));
+        if aggregated {
+            privacy::scrub(&mut anomaly);
+        }
+        let expected = self.suppression.annotate(&mut anomaly);
+        anomaly.apply_runbook(&self.config.alerting.runbooks);
+        anomaly.attach_trace_links(&self.config.alerting.trace_links);
+        let recent_context = self
+            .config
+            .alerting
+            .recent_context
+            .as_ref()
+            .filter(|_| !aggregated);
+        if let Some(context_config) = recent_context {
+            let attached = timing::time(
+                "recent_context",
+                attach_recent_context(self.storage.as_ref(), &mut anomaly, context_config),
+            )
+            .await;
+            if let Err(e) = attached {
+                error!("Failed to attach recent telemetry context: {}", e);
+            }
+        }
+        let anomaly = &anomaly;
+
+        let priority = anomaly.severity.is_priority();
+        // Priority anomalies are retried rather than lost to a storage blip
+        let write = async {
+            if priority {
+                write_priority(|| self.anomaly_writer.write(anomaly)).await
+            } else {
+                self.anomaly_writer.write(anomaly).await
+            }
+        };
+        if let Err(e) = timing::time("store_anomaly", write).await {
+            error!(
+                alert_id = %anomaly.alert_id,
+                "Failed to write anomaly, deferring its alerts: {}", e
+            );
+            if priority {
+                ::metrics::counter!("sentinel_priority_write_failures_total", "type" => "anomaly")
+                    .increment(1);
+            }
+            self.outbox.defer(anomaly);
+            return None;
+        }
+
+        self.outbox.record(anomaly);
+        let routed = self.deliver(anomaly, expected).await;
+        self.outbox.complete(anomaly.alert_id);
+        routed
+    }
+
+    /// Write the anomalies in the outbox whose write failed, oldest first,
+    /// and deliver each one written
+    ///
+    /// Stops at the first failure, leaving the rest for the next run.
+    pub async fn relay_outbox(&self) {
+        for anomaly in self.outbox.unwritten() {
+            if let Err(e) = self.anomaly_writer.write(&anomaly).await {
+                debug!(alert_id = %anomaly.alert_id, "Outbox write retry failed: {}", e);
+                self.outbox.defer(&anomaly);
+                return;
+            }
+            self.outbox.mark_written(anomaly.alert_id);
+            info!(alert_id = %anomaly.alert_id, "Deferred anomaly written");
+            ::metrics::counter!("sentinel_outbox_relayed_total").increment(1);
+            let expected = suppression::expected_by(&anomaly).map(str::to_string);
+            self.deliver(&anomaly, expected).await;
+            self.outbox.complete(anomaly.alert_id);
+        }
+    }
+
+    /// Publish a written anomaly, then remediate and dispatch it unless it
+    /// is expected or deduplicated
+    ///
+    /// Deduplication summaries report on alerts already handled, so they
+    /// are published and dispatched only.
+    async fn deliver(
+        &self,
+        anomaly: &AnomalyEvent,
+        expected: Option<String>,
+    ) -> Option<Vec<String>> {
+        let test = simulation::is_test(anomaly);
+        let summary = deduplication::is_summary(anomaly);
+        if let Some(monitor) = self.recovery_monitor.as_ref().filter(|_| !test && !summary) {
+            monitor.watch(anomaly);
+        }
+        self.bus.anomalies().publish(anomaly.clone());
+        if summary {
+            return Some(timing::time("dispatch", self.dispatch(anomaly)).await);
+        }
+
+        if let Some(schedule) = expected {
+            info!(
+                alert_id = %anomaly.alert_id,
+                schedule = %schedule,
+                "Anomaly expected by suppression schedule"
+            );
+            ::metrics::counter!("sentinel_anomalies_expected_total", "schedule" => schedule)
+                .increment(1);
+            return None;
+        }
+
+        // Remediation has its own cooldowns, so it runs before deduplication
+        if let Some(remediation) = self.remediation.as_ref().filter(|_| !test) {
+            timing::time("remediation", remediation.handle(anomaly)).await;
+        }
+
+        // Incidents collect deduplicated anomalies too
+        if let Some(incidents) = self.incidents.as_ref().filter(|_| !test) {
+            self.track_incident(incidents, anomaly).await;
+        }
+
+        if !self.deduplicator.should_send(anomaly) {
+            info!(alert_id = %anomaly.alert_id, "Alert deduplicated");
+            return None;
+        }
+
+        Some(timing::time("dispatch", self.dispatch(anomaly)).await)
+    }
+
+    /// Attach an anomaly to its incident and publish the update
+    async fn track_incident(&self, incidents: &IncidentTracker, anomaly: &AnomalyEvent) {
+        match incidents.record(self.storage.as_ref(), anomaly).await {
+            Ok((incident, update)) => self.bus.incidents().publish(IncidentEvent {
+                incident,
+                update,
+                anomaly: Some(anomaly.clone()),
+            }),
+            Err(e) => error!(
+                alert_id = %anomaly.alert_id,
+                "Failed to attach anomaly to its incident: {}", e
+            ),
+        }
+    }
+
+    /// Send an incident update to the alerters notified of incidents
+    ///
+    /// Only openings, escalations and state changes are sent. The alert is
+    /// built from the anomaly behind the update, or from the incident's
+    /// latest anomaly for responders' state changes.
+    async fn notify_incident(&self, event: &IncidentEvent) {
+        let Some(config) = &self.config.alerting.incidents else {
+            return;
+        };
+        if config.notify_alerters.is_empty() || !event.update.is_notified() {
+            return;
+        }
+        let incident = &event.incident;
+        let anomaly = match &event.anomaly {
+            Some(anomaly) => anomaly.clone(),
+            None => {
+                let latest = incident
+                    .timeline
+                    .iter()
+                    .rev()
+                    .find_map(|entry| entry.alert_id);
+                let found = match latest {
+                    Some(alert_id) => self.storage.get_anomaly(alert_id).await,
+                    None => Ok(None),
+                };
+                match found {
+                    Ok(Some(anomaly)) => anomaly,
+                    Ok(None) => {
+                        warn!(
+                            incident_id = %incident.incident_id,
+                            "No stored anomaly to notify the incident update with"
+                        );
+                        return;
+                    }
+                    Err(e) => {
+                        error!(
+                            incident_id = %incident.incident_id,
+                            "Failed to load the incident's latest anomaly: {}", e
+                        );
+                        return;
+                    }
+                }
+            }
+        };
+
+        let alert = incident.notification(&anomaly, event.update);
+        let alert = self.alert_payload(&alert);
+        let routed = self
+            .router
+            .route(alert.as_ref(), config.notify_alerters.clone());
+        info!(
+            incident_id = %incident.incident_id,
+            update = %event.update,
+            alerters = routed.len(),
+            "Notifying incident update"
+        );
+        ::metrics::counter!("sentinel_incident_notifications_total",
+            "update" => event.update.to_string()
+        )
+        .increment(1);
+        self.send(alert.as_ref(), &routed).await;
+    }
+
+    /// Whether an alerter is notified of incidents instead of anomalies
+    fn notifies_incidents(&self, alerter: &str) -> bool {
+        self.config
+            .alerting
+            .incidents
+            .as_ref()
+            .is_some_and(|incidents| incidents.notify_alerters.iter().any(|n| n == alerter))
+    }
+
+    /// Send the digests due at `now`, each reporting on the period before
+    /// its run
+    pub async fn send_due_digests(&self, now: chrono::DateTime<chrono::Utc>) {
+        let Some(digests) = &self.digests else {
+            return;
+        };
+        for (sender, run) in digests.take_due(now) {
+            let config = sender.config();
+            let (start, end) = sender.period(run);
+            let period = TimeRange::new(start, end);
+            let report = match DigestReport::build(self.storage.as_ref(), period, config.top).await
+            {
+                Ok(report) => report,
+                Err(e) => {
+                    error!(digest = %config.name, "Failed to build digest: {}", e);
+                    continue;
+                }
+            };
+
+            let schedule = config.schedule.to_string();
+            let extra = [
+                ("name", config.name.as_str()),
+                ("schedule", schedule.as_str()),
+            ];
+            let subject = report.render(&config.subject, &extra);
+            let template = config
+                .template
+                .as_deref()
+                .unwrap_or(DEFAULT_DIGEST_TEMPLATE);
+            let body = report.render(template, &extra);
+            // Failures are logged and counted by the sender
+            let _ = sender.send(&subject, &body).await;
+        }
+    }
+
+    /// Expire unresolved anomalies whose last state change (or detection)
+    /// is older than their severity's TTL at `now`
+    ///
+    /// Expiries are recorded as state changes by [`EXPIRER`] and published
+    /// on the bus, so they are audited, but are not sent to alerters.
+    pub async fn expire_stale_anomalies(&self, now: chrono::DateTime<chrono::Utc>) {
+        let Some(expiry) = &self.config.alerting.expiry else {
+            return;
+        };
+        let lookback = chrono::Duration::hours(expiry.lookback_hours as i64);
+        let mut query = AnomalyQuery::new(TimeRange::new(now - lookback, now));
+        query.limit = None;
+        let anomalies = match self.storage.query_anomalies(query).await {
+            Ok(anomalies) => anomalies,
+            Err(e) => {
+                error!("Failed to query anomalies for expiry: {}", e);
+                return;
+            }
+        };
+
+        for anomaly in anomalies {
+            let Some(&ttl_secs) = expiry.ttl_secs.get(&anomaly.severity) else {
+                continue;
+            };
+            let last_activity = anomaly.state_changed_at.unwrap_or(anomaly.timestamp);
+            if !anomaly.state.can_transition_to(AnomalyState::Expired)
+                || now - last_activity < chrono::Duration::seconds(ttl_secs as i64)
+            {
+                continue;
+            }
+
+            let expired = transition_anomaly(
+                self.storage.as_ref(),
+                anomaly.alert_id,
+                AnomalyState::Expired,
+                Some(EXPIRER.to_string()),
+                Some(format!("No activity for {}s", ttl_secs)),
+            )
+            .await;
+            match expired {
+                Ok((anomaly, change)) => {
+                    ::metrics::counter!(
+                        "sentinel_anomalies_expired_total",
+                        "severity" => anomaly.severity.to_string()
+                    )
+                    .increment(1);
+                    self.bus
+                        .lifecycle()
+                        .publish(LifecycleEvent { anomaly, change });
+                }
+                // Changed by a responder meanwhile, or not readable from storage
+                Err(
+                    e @ (llm_sentinel_core::Error::Validation(_)
+                    | llm_sentinel_core::Error::NotFound(_)),
+                ) => {
+                    debug!(alert_id = %anomaly.alert_id, "Skipping expiry: {}", e);
+                }
+                Err(e) => {
+                    error!(alert_id = %anomaly.alert_id, "Failed to expire anomaly: {}", e);
+                }
+            }
+        }
+    }
+
+    /// Raise anomalies for services and models that stopped sending
+    /// telemetry
+    pub async fn check_silence(&self) {
+        let Some(monitor) = &self.silence_monitor else {
+            return;
+        };
+        for anomaly in monitor.check() {
+            self.handle_anomaly(&anomaly).await;
+        }
+    }
+
+    /// Evaluate traces whose spans stopped arriving as whole chains
+    pub async fn check_traces(&self) {
+        let Some(aggregator) = &self.trace_aggregator else {
+            return;
+        };
+        for anomaly in aggregator.check() {
+            self.handle_anomaly(&anomaly).await;
+        }
+    }
+
+    /// Whether a span anomaly is left to the evaluation of its chain
+    fn is_left_to_chain(&self, event: &TelemetryEvent, anomaly: &AnomalyEvent) -> bool {
+        event.trace_id.is_some()
+            && self
+                .config
+                .detection
+                .traces
+                .as_ref()
+                .is_some_and(|traces| traces.suppress_span_alerts)
+            && matches!(
+                anomaly.anomaly_type,
+                AnomalyType::LatencySpike | AnomalyType::TokenUsageSpike | AnomalyType::CostAnomaly
+            )
+    }
+
+    /// Project month-end spend against budgets at `now`, alerting on
+    /// services projected over budget by more than the margin
+    pub async fn check_budgets(&self, now: chrono::DateTime<chrono::Utc>) {
+        let Some(budgets) = &self.budgets else {
+            return;
+        };
+        let projections = match budgets.project(self.storage.as_ref(), now).await {
+            Ok(projections) => projections,
+            Err(e) => {
+                error!("Failed to project costs against budgets: {}", e);
+                return;
+            }
+        };
+
+        for projection in projections {
+            ::metrics::gauge!(
+                "sentinel_cost_projected_usd",
+                "service" => projection.service.as_str().to_string()
+            )
+            .set(projection.projected_usd);
+            if projection.over_budget {
+                warn!(
+                    service = %projection.service,
+                    projected_usd = projection.projected_usd,
+                    budget_usd = projection.budget_usd,
+                    "Projected spend exceeds budget"
+                );
+                self.handle_anomaly(&budgets.alert(&projection)).await;
+            }
+        }
+    }
+
+    /// Send deduplication summaries for windows that have expired
+    pub async fn flush_dedup_summaries(&self) {
+        self.deduplicator.cleanup_expired();
+        for summary in self.deduplicator.take_summaries() {
+            let alert = summary.to_alert(self.clock.now());
+            info!(
+                alert_id = %alert.alert_id,
+                occurrences = summary.occurrences,
+                "Sending deduplication summary"
+            );
+            // Like any anomaly, a summary is only sent once it can be found
+            if let Err(e) = self.anomaly_writer.write(&alert).await {
+                error!(
+                    alert_id = %alert.alert_id,
+                    "Failed to write deduplication summary, deferring it: {}", e
+                );
+                self.outbox.defer(&alert);
+                continue;
+            }
+            self.outbox.record(&alert);
+            self.deliver(&alert, None).await;
+            self.outbox.complete(alert.alert_id);
+        }
+    }
+
+    /// Resolve an anomaly whose metric returned to baseline and, when
+    /// configured, send the resolved anomaly to alerters
+    async fn resolve_recovered(&self, recovery: &AnomalyRecovery) {
+        let resolved = transition_anomaly(
+            self.storage.as_ref(),
+            recovery.alert_id,
+            AnomalyState::Resolved,
+            Some(AUTO_RESOLVER.to_string()),
+            Some(recovery.reason()),
+        )
+        .await;
+        let anomaly = match resolved {
+            Ok((anomaly, change)) => {
+                self.bus.lifecycle().publish(LifecycleEvent {
+                    anomaly: anomaly.clone(),
+                    change,
+                });
+                anomaly
+            }
+            // Closed by a responder meanwhile, or not readable from storage
+            Err(
+                e @ (llm_sentinel_core::Error::Validation(_)
+                | llm_sentinel_core::Error::NotFound(_)),
+            ) => {
+                debug!(alert_id = %recovery.alert_id, "Skipping auto-resolution: {}", e);
+                return;
+            }
+            Err(e) => {
+                error!(alert_id = %recovery.alert_id, "Failed to auto-resolve anomaly: {}", e);
+                return;
+            }
+        };
+
+        info!(
+            alert_id = %anomaly.alert_id,
+            metric = %recovery.metric,
+            "Anomaly auto-resolved"
+        );
+        ::metrics::counter!("sentinel_anomalies_auto_resolved_total").increment(1);
+
+        if self
+            .config
+            .detection
+            .recovery
+            .as_ref()
+            .is_some_and(|recovery| recovery.notify)
+        {
+            self.dispatch(&anomaly).await;
+        }
+    }
+
+    /// Send an alert to the routed alerters and record each delivery
+    ///
+    /// Severity rules adjust the sent alert's severity before routing.
+    /// Alerters whose delivery window is closed get the alert once it opens.
+    /// With queued dispatch the alert is only queued here; deliveries are
+    /// recorded once the alerters' tasks have sent it. Returns the routed
+    /// alerters, held ones included. Alerters notified of incidents are
+    /// left out.
+    async fn dispatch(&self, anomaly: &AnomalyEvent) -> Vec<String> {
+        let anomaly = self.alert_payload(anomaly);
+        let anomaly = anomaly.as_ref();
+        let mut routed: Vec<String> = self
+            .alerter_names()
+            .into_iter()
+            .filter(|name| !self.notifies_incidents(name))
+            .map(String::from)
+            .collect();
+        routed = self.router.route(anomaly, routed);
+        if let Some(scripts) = &self.scripts {
+            routed = scripts.route(anomaly, routed);
+        }
+
+        let held = match &self.holding_queue {
+            Some(queue) => self.hold(queue, anomaly, &routed).await,
+            None => Vec::new(),
+        };
+        let sending: Vec<String> = routed
+            .iter()
+            .filter(|name| !held.contains(name))
+            .cloned()
+            .collect();
+        self.send(anomaly, &sending).await;
+        routed
+    }
+
+    /// An anomaly as sent to alerters: fitted to the payload limit, with
+    /// its severity adjusted by the routing rules
+    fn alert_payload<'a>(&self, anomaly: &'a AnomalyEvent) -> Cow<'a, AnomalyEvent> {
+        let mut anomaly = match &self.config.alerting.payload_limit {
+            Some(limit) => fit_payload(anomaly, limit),
+            None => Cow::Borrowed(anomaly),
+        };
+        if let Some(adjustment) = self.router.severity_adjustment(&anomaly) {
+            info!(
+                alert_id = %anomaly.alert_id,
+                rule = %adjustment.rule,
+                from = %adjustment.from,
+                to = %adjustment.to,
+                "Alert severity adjusted"
+            );
+            ::metrics::counter!("sentinel_alert_severity_adjusted_total",
+                "rule" => adjustment.rule.clone()
+            )
+            .increment(1);
+            adjustment.apply(anomaly.to_mut());
+        }
+        anomaly
+    }
+
+    /// Hold an alert for the routed alerters whose delivery window is
+    /// closed, returning those it was held for
+    ///
+    /// An alert that cannot be written to the holding queue is sent now
+    /// rather than lost.
+    async fn hold(
+        &self,
+        queue: &HoldingQueue,
+        anomaly: &AnomalyEvent,
+        routed: &[String],
+    ) -> Vec<String> {
+        let now = self.clock.now();
+        let mut held = Vec::new();
+        for alerter in routed {
+            let Some(hold) = self.router.delivery_hold(anomaly, alerter, now) else {
+                continue;
+            };
+            let mut alert = anomaly.clone();
+            alert
+                .labels
+                .insert(DELIVERY_SCHEDULE_LABEL.to_string(), hold.schedule.clone());
+            let entry = HeldAlert {
+                alert,
+                alerter: alerter.clone(),
+                schedule: hold.schedule.clone(),
+                held_at: now,
+                release_at: hold.until,
+            };
+            if let Err(e) = queue.hold(entry).await {
+                error!(
+                    alert_id = %anomaly.alert_id,
+                    alerter = %alerter,
+                    "Failed to hold alert, sending it now: {}", e
+                );
+                continue;
+            }
+            info!(
+                alert_id = %anomaly.alert_id,
+                alerter = %alerter,
+                schedule = %hold.schedule,
+                until = %hold.until,
+                "Alert held for delivery window"
+            );
+            ::metrics::counter!("sentinel_alerts_held_total",
+                "alerter" => alerter.clone(),
+                "schedule" => hold.schedule
+            )
+            .increment(1);
+            held.push(alerter.clone());
+        }
+        held
+    }
+
+    /// Send an alert to the named alerters and record each delivery
+    async fn send(&self, anomaly: &AnomalyEvent, alerters: &[String]) {
+        if let Some(dispatcher) = &self.dispatcher {
+            dispatcher.dispatch(anomaly.clone(), alerters).await;
+            return;
+        }
+
+        let mut deliveries = Vec::with_capacity(alerters.len());
+        for alerter in self
+            .alerters
+            .iter()
+            .filter(|a| alerters.iter().any(|name| name == a.name()))
+        {
+            deliveries.push(deliver(alerter.as_ref(), anomaly).await);
+        }
+
+        let dispatched = AlertDispatched {
+            alert: anomaly.clone(),
+            deliveries,
+        };
+        record_dispatched(self.storage.as_ref(), &self.bus, dispatched).await;
+    }
+
+    /// Send the held alerts whose delivery window has opened, each
+    /// alerter's together, and remove them from the holding queue
+    pub async fn release_held_alerts(&self) {
+        let Some(queue) = &self.holding_queue else {
+            return;
+        };
+        let due = queue.due(self.clock.now()).await;
+        if due.is_empty() {
+            return;
+        }
+
+        let mut by_alerter: BTreeMap<&str, Vec<AnomalyEvent>> = BTreeMap::new();
+        for held in &due {
+            by_alerter
+                .entry(held.alerter.as_str())
+                .or_default()
+                .push(held.alert.clone());
+        }
+        for (name, alerts) in by_alerter {
+            let Some(alerter) = self.alerters.iter().find(|a| a.name() == name) else {
+                warn!(
+                    alerter = name,
+                    count = alerts.len(),
+                    "Dropping held alerts of an alerter no longer configured"
+                );
+                continue;
+            };
+            info!(
+                alerter = name,
+                count = alerts.len(),
+                "Releasing held alerts"
+            );
+            ::metrics::counter!("sentinel_alerts_released_total", "alerter" => name.to_string())
+                .increment(alerts.len() as u64);
+
+            if let Some(dispatcher) = &self.dispatcher {
+                for alert in alerts {
+                    dispatcher.dispatch(alert, &[name.to_string()]).await;
+                }
+                continue;
+            }
+            let deliveries = deliver_batch(alerter.as_ref(), &alerts).await;
+            for (alert, delivery) in alerts.into_iter().zip(deliveries) {
+                let dispatched = AlertDispatched {
+                    alert,
+                    deliveries: vec![delivery],
+                };
+                record_dispatched(self.storage.as_ref(), &self.bus, dispatched).await;
+            }
+        }
+
+        if let Err(e) = queue.release(&due).await {
+            error!(
+                "Failed to remove released alerts from the holding queue: {}",
+                e
+            );
+        }
+    }
+
+    /// Sync learned state between the replicas of an HA pair
+    ///
+    /// The primary publishes its baselines and deduplication windows; a
+    /// standby restores the newest published state unless it already has
+    /// it. A former primary that lost the lease neither publishes nor
+    /// restores, so its live state is never overwritten.
+    pub async fn sync_state(&self) {
+        let Some(stream) = &self.state_stream else {
+            return;
+        };
+        let standby = self.is_standby();
+        ::metrics::gauge!("sentinel_standby").set(if standby { 1.0 } else { 0.0 });
+
+        let (operation, synced) = if standby {
+            let restored = self.restore_published_state(stream.as_ref()).await;
+            ("restore", restored.map(|_| ()))
+        } else if self.leadership.is_leader() {
+            ("publish", self.publish_state(stream.as_ref()).await)
+        } else {
+            return;
+        };
+        if let Err(e) = synced {
+            warn!(operation, "State sync failed: {}", e);
+            ::metrics::counter!("sentinel_state_sync_failures_total", "operation" => operation)
+                .increment(1);
+        }
+    }
+
+    async fn publish_state(&self, stream: &dyn StateStream) -> llm_sentinel_core::Result<()> {
+        let snapshot = self.runtime_snapshot().await?;
+        stream.publish(&snapshot).await?;
+        ::metrics::counter!("sentinel_state_syncs_total", "operation" => "publish").increment(1);
+        Ok(())
+    }
+
+    /// Restore the newest published state, returning whether it was new
+    async fn restore_published_state(
+        &self,
+        stream: &dyn StateStream,
+    ) -> llm_sentinel_core::Result<bool> {
+        let Some(snapshot) = stream.latest().await? else {
+            return Ok(false);
+        };
+        let age = self.clock.now() - snapshot.created_at;
+        ::metrics::gauge!("sentinel_standby_state_age_seconds")
+            .set(age.num_milliseconds() as f64 / 1000.0);
+        if self.synced_state.read().unwrap().as_deref() == Some(snapshot.id.as_str()) {
+            return Ok(false);
+        }
+
+        self.restore_runtime(&snapshot).await?;
+        debug!(id = %snapshot.id, "Restored state published by the primary");
+        *self.synced_state.write().unwrap() = Some(snapshot.id);
+        ::metrics::counter!("sentinel_state_syncs_total", "operation" => "restore").increment(1);
+        Ok(true)
+    }
+
+    /// Wait until this standby takes the leader lease, then restore the
+    /// primary's last published state and stop standing by
+    async fn promote(&self) {
+        info!("Standing by for the leader lease");
+        while !self.leadership.is_leader() {
+            tokio::time::sleep(STANDBY_POLL_INTERVAL).await;
+        }
+        if let Some(stream) = &self.state_stream {
+            if let Err(e) = self.restore_published_state(stream.as_ref()).await {
+                warn!("Failed to restore the primary's state on promotion: {}", e);
+            }
+        }
+        self.standby.store(false, Ordering::SeqCst);
+        ::metrics::gauge!("sentinel_standby").set(0.0);
+        ::metrics::counter!("sentinel_standby_promotions_total").increment(1);
+        info!("Promoted from standby");
+    }
+
+    /// Learned runtime state: baselines, detector state and open
+    /// deduplication windows
+    async fn runtime_snapshot(&self) -> llm_sentinel_core::Result<RuntimeSnapshot> {
+        let mut snapshot = RuntimeSnapshot::new(env!("CARGO_PKG_VERSION"), self.clock.now());
+        let detection = self.detection_engine.lock().await.snapshot()?;
+        snapshot.insert(DETECTION_SECTION, &detection)?;
+        snapshot.insert(DEDUPLICATION_SECTION, &self.deduplicator.snapshot())?;
+        Ok(snapshot)
+    }
+
+    /// Replace learned runtime state with a snapshot's
+    async fn restore_runtime(&self, snapshot: &RuntimeSnapshot) -> llm_sentinel_core::Result<()> {
+        // Decode every section before touching live state
+        let detection = snapshot.get::<EngineSnapshot>(DETECTION_SECTION)?;
+        let deduplication = snapshot.get::<DeduplicationSnapshot>(DEDUPLICATION_SECTION)?;
+        if let Some(detection) = detection {
+            self.detection_engine.lock().await.restore(detection)?;
+        }
+        if let Some(deduplication) = deduplication {
+            self.deduplicator.restore(deduplication);
+        }
+        Ok(())
+    }
+
+    /// Wait for queued alerts to be sent, for at most `timeout`
+    ///
+    /// Returns whether every queued alert was handled in time; always true
+    /// when alerts are sent inline.
+    pub async fn flush_alerts(&self, timeout: Duration) -> bool {
+        match &self.dispatcher {
+            Some(dispatcher) => dispatcher.flush(timeout).await,
+            None => true,
+        }
+    }
+}
+
+/// Log failed deliveries, store every delivery and publish the dispatch
+async fn record_dispatched(storage: &dyn Storage, bus: &EventBus, dispatched: AlertDispatched) {
+    for delivery in &dispatched.deliveries {
+        if let Some(e) = &delivery.error {
+            error!(alerter = %delivery.alerter, "Failed to send alert: {}", e);
+            ::metrics::counter!("sentinel_alert_failures_total").increment(1);
+        }
+    }
+
+    if let Err(e) = storage.write_alert_deliveries(&dispatched.deliveries).await {
+        error!("Failed to write alert deliveries: {}", e);
+    }
+
+    bus.alerts().publish(dispatched);
+}
+
+#[async_trait::async_trait]
+impl SnapshotControl for Sentinel {
+    async fn snapshot(&self) -> llm_sentinel_core::Result<SnapshotInfo> {
+        let store = snapshot_store(&self.snapshot_store)?;
+        let snapshot = self.runtime_snapshot().await?;
+        store.save(&snapshot).await?;
+        ::metrics::counter!("sentinel_snapshots_total", "operation" => "snapshot").increment(1);
+        Ok(snapshot.info())
+    }
+
+    async fn restore(&self, id: &str) -> llm_sentinel_core::Result<SnapshotInfo> {
+        let store = snapshot_store(&self.snapshot_store)?;
+        let snapshot = match id {
+            "latest" => store.latest().await?,
+            id => store.load(id).await?,
+        }
+        .ok_or_else(|| llm_sentinel_core::Error::not_found(format!("Snapshot {}", id)))?;
+        self.restore_runtime(&snapshot).await?;
+
+        info!(id = %snapshot.id, version = %snapshot.version, "Restored runtime snapshot");
+        ::metrics::counter!("sentinel_snapshots_total", "operation" => "restore").increment(1);
+        Ok(snapshot.info())
+    }
+
+    async fn list(&self) -> llm_sentinel_core::Result<Vec<SnapshotInfo>> {
+        snapshot_store(&self.snapshot_store)?.list().await
+    }
+}
+
+#[async_trait::async_trait]
+impl FederationSink for Sentinel {
+    /// Handle anomalies forwarded by an edge like locally detected ones
+    ///
+    /// Each is labelled with the authenticated region. The deduplication
+    /// key does not include the region, so the same anomaly reported by
+    /// several regions is sent once. Standbys refuse them, so the edge
+    /// retries until it reaches the primary.
+    async fn receive(
+        &self,
+        region: &str,
+        anomalies: Vec<AnomalyEvent>,
+    ) -> llm_sentinel_core::Result<usize> {
+        if self.is_standby() {
+            return Err(llm_sentinel_core::Error::connection(
+                "Standby replica does not accept federated anomalies",
+            ));
+        }
+        let accepted = anomalies.len();
+        for mut anomaly in anomalies {
+            stamp_region(&mut anomaly, region);
+            self.handle_anomaly(&anomaly).await;
+        }
+        debug!(region, accepted, "Federated anomalies received");
+        ::metrics::counter!("sentinel_federated_anomalies_total", "region" => region.to_string())
+            .increment(accepted as u64);
+        Ok(accepted)
+    }
+}
+
+#[async_trait::async_trait]
+impl AnomalySimulator for Sentinel {
+    /// Handle a test anomaly like a detected one
+    ///
+    /// It is stored and goes through suppression schedules, deduplication,
+    /// routing and alerting; remediation and recovery tracking skip it.
+    async fn simulate(&self, anomaly: AnomalyEvent) -> llm_sentinel_core::Result<SimulationReport> {
+        let alert_id = anomaly.alert_id;
+        let expected = self
+            .suppression
+            .matching(&anomaly)
+            .map(|schedule| schedule.name.clone());
+        let routed = self.process_anomaly(&anomaly).await;
+        ::metrics::counter!("sentinel_test_anomalies_total").increment(1);
+        Ok(SimulationReport {
+            alert_id,
+            deduplicated: routed.is_none() && expected.is_none(),
+            expected,
+            routed: routed.unwrap_or_default(),
+        })
+    }
+}
+
+#[async_trait::async_trait]
+impl AlertRedelivery for Sentinel {
+    /// Send a stored anomaly to one alerter, bypassing deduplication,
+    /// routing, delivery windows and the dispatch queue
+    ///
+    /// It is sent as it was originally: fitted to the payload limit and with
+    /// its severity adjusted. The delivery is recorded and published like
+    /// any other.
+    async fn redeliver(
+        &self,
+        alert_id: Uuid,
+        alerter: &str,
+    ) -> llm_sentinel_core::Result<AlertMetadata> {
+        let anomaly =
+            self.storage.get_anomaly(alert_id).await?.ok_or_else(|| {
+                llm_sentinel_core::Error::not_found(format!("Anomaly {}", alert_id))
+            })?;
+        let target = self
+            .alerters
+            .iter()
+            .find(|a| a.name() == alerter)
+            .ok_or_else(|| llm_sentinel_core::Error::not_found(format!("Alerter {}", alerter)))?;
+
+        let alert = self.alert_payload(&anomaly).into_owned();
+        let delivery = deliver(target.as_ref(), &alert).await;
+        ::metrics::counter!("sentinel_alert_redeliveries_total",
+            "alerter" => alerter.to_string(),
+            "status" => delivery.status.to_string()
+        )
+        .increment(1);
+        let dispatched = AlertDispatched {
+            alert,
+            deliveries: vec![delivery.clone()],
+        };
+        record_dispatched(self.storage.as_ref(), &self.bus, dispatched).await;
+        Ok(delivery)
+    }
+}
+
+#[async_trait::async_trait]
+impl Redetector for Sentinel {
+    /// Replay the requested range in the background
+    ///
+    /// Anomalies found are written apart from live ones, tagged with the
+    /// job ID, and are neither deduplicated nor sent.
+    async fn submit(&self, request: RedetectRequest) -> llm_sentinel_core::Result<RedetectJob> {
+        request.validate()?;
+        let job = RedetectJob::new(&request);
+        let job_id = job.job_id;
+        self.redetect_jobs
+            .write()
+            .unwrap()
+            .insert(job_id, job.clone());
+        info!(job_id = %job_id, "Starting re-detection job");
+
+        let storage = self.storage.clone();
+        let jobs = self.redetect_jobs.clone();
+        tokio::spawn(async move {
+            let outcome = run_redetection(storage.as_ref(), job_id, &request).await;
+            match &outcome {
+                Ok((events, anomalies)) => {
+                    info!(job_id = %job_id, events, anomalies, "Re-detection job completed");
+                }
+                Err(e) => error!(job_id = %job_id, "Re-detection job failed: {}", e),
+            }
+            if let Some(job) = jobs.write().unwrap().get_mut(&job_id) {
+                job.finish(outcome);
+            }
+        });
+        Ok(job)
+    }
+
+    async fn job(&self, job_id: Uuid) -> llm_sentinel_core::Result<RedetectJob> {
+        self.redetect_jobs
+            .read()
+            .unwrap()
+            .get(&job_id)
+            .cloned()
+            .ok_or_else(|| {
+                llm_sentinel_core::Error::not_found(format!("re-detection job {}", job_id))
+            })
+    }
+}
+
+#[async_trait::async_trait]
+impl DetectionStatsSource for Sentinel {
+    async fn detector_stats(&self) -> Vec<(String, DetectorStats)> {
+        self.detection_engine
+            .lock()
+            .await
+            .stats()
+            .await
+            .detector_stats
+    }
+
+    fn deduplicated_by_detector(&self) -> HashMap<String, u64> {
+        self.deduplicator.get_stats().by_detector
+    }
+
+    async fn engine_stats(&self) -> EngineStats {
+        self.detection_engine.lock().await.stats().await
+    }
+}
+
+#[async_trait::async_trait]
+impl CredentialControl for Sentinel {
+    /// Fetch secrets again and re-establish clients whose credentials
+    /// changed
+    ///
+    /// Every component is tried. One that fails keeps its current
+    /// credentials, and the first failure is returned once the others have
+    /// rotated.
+    async fn rotate(&self) -> llm_sentinel_core::Result<Vec<String>> {
+        self.secrets.refresh().await?;
+        let config = self.secrets.apply(&self.unresolved_config).await?;
+
+        let mut rotated = Vec::new();
+        let mut failure = None;
+        for component in &self.credentials {
+            let result = component.rotate(&config).await;
+            record_rotation(component.component(), result, &mut rotated, &mut failure);
+        }
+        for (index, (alerter, spec)) in self
+            .spec_alerters
+            .iter()
+            .zip(&config.alerting.alerters)
+            .enumerate()
+        {
+            let settings = serde_json::Value::Object(spec.settings.clone());
+            let result = alerter.rotate_credentials(&settings).await;
+            let component = format!("alerting.alerters[{}]", index);
+            record_rotation(&component, result, &mut rotated, &mut failure);
+        }
+
+        match failure {
+            Some(e) => Err(e),
+            None => Ok(rotated),
+        }
+    }
+}
+
+#[async_trait::async_trait]
+impl SelfChecker for Sentinel {
+    /// Check the configured dependencies with the current credentials,
+    /// rotated ones included
+    async fn self_check(&self) -> llm_sentinel_core::Result<SelfCheckReport> {
+        let config = self.secrets.apply(&self.unresolved_config).await?;
+        Ok(self_check(&config).await)
+    }
+}
+
+#[async_trait::async_trait]
+impl DetectionConfigControl for Sentinel {
+    fn export(&self) -> llm_sentinel_core::Result<DetectionDocument> {
+        Ok(DetectionDocument::from_config(&self.unresolved_config))
+    }
+
+    /// Validate `document` against the running configuration and write it
+    /// to `detection_file`
+    ///
+    /// Detectors, routes, severity rules and suppression schedules are built
+    /// once at startup, so the running configuration (and [`export`]) is
+    /// unchanged; the staged document takes effect on the next start.
+    ///
+    /// [`export`]: DetectionConfigControl::export
+    async fn stage(
+        &self,
+        document: DetectionDocument,
+        dry_run: bool,
+    ) -> llm_sentinel_core::Result<Vec<SectionChange>> {
+        let plan = plan_detection(&self.unresolved_config, &document)?;
+        if dry_run {
+            return Ok(plan.changes);
+        }
+        let Some(path) = &self.unresolved_config.detection_file else {
+            return Err(llm_sentinel_core::Error::config(
+                "No detection_file is configured to write to",
+            ));
+        };
+        document.write(path)?;
+        info!(
+            path = %path,
+            changes = plan.changes.len(),
+            "Detection document staged; it takes effect on restart"
+        );
+        Ok(plan.changes)
+    }
+}
+
+/// Run a storage write for a priority anomaly, retrying failures with
+/// backoff up to [`PRIORITY_WRITE_ATTEMPTS`] times
+async fn write_priority<F, Fut>(mut write: F) -> llm_sentinel_core::Result<()>
+where
+    F: FnMut() -> Fut,
+    Fut: Future<Output = llm_sentinel_core::Result<()>>,
+{
+    let policy = RetryPolicy::new(PRIORITY_WRITE_ATTEMPTS).with_initial_delay(PRIORITY_WRITE_DELAY);
+    retry(&policy, |attempt| {
+        let written = write();
+        async move {
+            written.await.map_err(|e| {
+                warn!(attempt, "Priority storage write failed: {}", e);
+                RetryError::Transient(e)
+            })
+        }
+    })
+    .await
+    .map_err(|failure| failure.error)
+}
+
+fn snapshot_store(
+    store: &Option<Arc<dyn SnapshotStore>>,
+) -> llm_sentinel_core::Result<&Arc<dyn SnapshotStore>> {
+    store.as_ref().ok_or_else(|| {
+        llm_sentinel_core::Error::config("Runtime state snapshots are not configured")
+    })
+}
+
+impl std::fmt::Debug for Sentinel {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("Sentinel")
+            .field("alerters", &self.alerter_names())
+            .field("outbox", &self.outbox.len())
+            .field(
+                "enrichers",
+                &self.enrichers.iter().map(|e| e.name()).collect::<Vec<_>>(),
+            )
+            .field("scripts", &self.scripts)
+            .field("cost_aggregator", &self.cost_aggregator.is_some())
+            .field("budgets", &self.budgets.is_some())
+            .field("pricing", &self.pricing.is_some())
+            .field("identifiers", &self.identifiers)
+            .field("quotas", &self.quotas.is_some())
+            .field("quality", &self.quality.is_some())
+            .field("clock_skew", &self.clock_skew.is_some())
+            .field("embedder", &self.embedder)
+            .field("slo_tracker", &self.slo_tracker.is_some())
+            .field("availability_tracker", &self.availability_tracker.is_some())
+            .field("recovery_monitor", &self.recovery_monitor.is_some())
+            .field("silence_monitor", &self.silence_monitor.is_some())
+            .field("trace_aggregator", &self.trace_aggregator.is_some())
+            .field("load_shedding", &self.load_shedding.is_some())
+            .field("backpressure", &self.backpressure)
+            .field("aggregation_only", &self.aggregation_only)
+            .field("memory", &self.memory)
+            .field("router", &self.router)
+            .field("suppression", &self.suppression)
+            .field("remediation", &self.remediation)
+            .field("dispatcher", &self.dispatcher)
+            .field("holding_queue", &self.holding_queue)
+            .field("digests", &self.digests)
+            .field("incidents", &self.incidents.is_some())
+            .field("audit_log", &self.audit_log)
+            .field("snapshot_store", &self.snapshot_store)
+            .field("state_stream", &self.state_stream)
+            .field("standby", &self.is_standby())
+            .field("leadership", &self.leadership)
+            .field("secrets", &self.secrets)
+            .field(
+                "credentials",
+                &self
+                    .credentials
+                    .iter()
+                    .map(|component| component.component())
+                    .collect::<Vec<_>>(),
+            )
+            .field("enable_api", &self.enable_api)
+            .finish_non_exhaustive()
+    }
+}
+
+/// Builder for [`Sentinel`]
+///
+/// Components that are not injected are constructed from configuration when
+/// [`build`](SentinelBuilder::build) is called.
+pub struct SentinelBuilder {
+    config: Config,
+    storage: Option<Arc<dyn Storage>>,
+    ingester: Option<Box<dyn Ingester>>,
+    detection_engine: Option<DetectionEngine>,
+    alerters: Option<Vec<Arc<dyn Alerter>>>,
+    alerter_registry: Option<AlerterRegistry>,
+    enrichers: Vec<Arc<dyn Enricher>>,
+    scripts: Option<Arc<ScriptHooks>>,
+    audit_log: Option<Arc<dyn AuditLog>>,
+    snapshot_store: Option<Arc<dyn SnapshotStore>>,
+    state_stream: Option<Arc<dyn StateStream>>,
+    clock: Clock,
+    bus: Option<EventBus>,
+    leadership: Option<Leadership>,
+    log_shipper: Option<LogShipper>,
+    enable_api: bool,
+}
+
+impl SentinelBuilder {
+    /// Create a builder from configuration
+    pub fn new(config: Config) -> Self {
+        Self {
+            config,
+            storage: None,
+            ingester: None,
+            detection_engine: None,
+            alerters: None,
+            alerter_registry: None,
+            enrichers: Vec::new(),
+            scripts: None,
+            audit_log: None,
+            snapshot_store: None,
+            state_stream: None,
+            bus: None,
+            leadership: None,
+            log_shipper: None,
+            clock: Clock::system(),
+            enable_api: true,
+        }
+    }
+
+    /// Use the given storage instead of connecting to InfluxDB
+    pub fn with_storage(mut self, storage: Arc<dyn Storage>) -> Self {
+        self.storage = Some(storage);
+        self
+    }
+
+    /// Consume telemetry from the given ingester instead of Kafka/Pub/Sub
+    pub fn with_ingester(mut self, ingester: Box<dyn Ingester>) -> Self {
+        self.ingester = Some(ingester);
+        self
+    }
+
+    /// Use the given detection engine instead of the default one
+    pub fn with_detection_engine(mut self, engine: DetectionEngine) -> Self {
+        self.detection_engine = Some(engine);
+        self
+    }
+
+    /// Add an alerter
+    ///
+    /// Injected alerters replace the ones configured under `alerting`.
+    pub fn with_alerter(mut self, alerter: Arc<dyn Alerter>) -> Self {
+        self.alerters.get_or_insert_with(Vec::new).push(alerter);
+        self
+    }
+
+    /// Construct configured `alerting.alerters` entries from the given
+    /// registry instead of the built-in one
+    pub fn with_alerter_registry(mut self, registry: AlerterRegistry) -> Self {
+        self.alerter_registry = Some(registry);
+        self
+    }
+
+    /// Add an enricher
+    ///
+    /// Injected enrichers run after the WASM enrichers configured under
+    /// `plugins`.
+    pub fn with_enricher(mut self, enricher: Arc<dyn Enricher>) -> Self {
+        self.enrichers.push(enricher);
+        self
+    }
+
+    /// Use the given scripting hooks instead of the ones configured under
+    /// `scripting`
+    pub fn with_scripts(mut self, scripts: Arc<ScriptHooks>) -> Self {
+        self.scripts = Some(scripts);
+        self
+    }
+
+    /// Record remediation actions in the given audit log instead of the
+    /// `audit` tracing target
+    pub fn with_audit_log(mut self, audit_log: Arc<dyn AuditLog>) -> Self {
+        self.audit_log = Some(audit_log);
+        self
+    }
+
+    /// Keep runtime state snapshots in the given store instead of the
+    /// directory configured under `storage.snapshots`
+    pub fn with_snapshot_store(mut self, store: Arc<dyn SnapshotStore>) -> Self {
+        self.snapshot_store = Some(store);
+        self
+    }
+
+    /// Sync standby state through the given stream instead of the Redis
+    /// stream configured under `standby`
+    pub fn with_state_stream(mut self, stream: Arc<dyn StateStream>) -> Self {
+        self.state_stream = Some(stream);
+        self
+    }
+
+    /// Read time from the given clock instead of the system clock
+    ///
+    /// The clock drives deduplication windows, SLO and availability windows
+    /// and, unless an engine is injected, detection baselines. Pair a
+    /// [`Clock::manual`] clock with a `ReplayIngester` to simulate time.
+    pub fn with_clock(mut self, clock: Clock) -> Self {
+        self.clock = clock;
+        self
+    }
+
+    /// Publish on the given bus instead of a new one, to subscribe before
+    /// the sentinel is built
+    pub fn with_event_bus(mut self, bus: EventBus) -> Self {
+        self.bus = Some(bus);
+        self
+    }
+
+    /// Use the given leadership instead of the one configured under
+    /// `leader_election`
+    pub fn with_leadership(mut self, leadership: Leadership) -> Self {
+        self.leadership = Some(leadership);
+        self
+    }
+
+    /// Start `shipper` with `observability.log_shipping` once secrets are
+    /// resolved; without a shipper that section is ignored
+    pub fn with_log_shipper(mut self, shipper: LogShipper) -> Self {
+        self.log_shipper = Some(shipper);
+        self
+    }
+
+    /// Enable or disable the REST API server (enabled by default)
+    pub fn with_api(mut self, enabled: bool) -> Self {
+        self.enable_api = enabled;
+        self
+    }
+
+    /// Build the Sentinel, constructing components that were not injected
+    pub async fn build(self) -> Result<Sentinel> {
+        info!("Initializing Sentinel components...");
+
+        // Resolve secret references before any component reads the config
+        let secrets = Arc::new(secret_resolver(&self.config)?);
+        let config = secrets
+            .resolve_config(&self.config)
+            .await
+            .context("Failed to resolve secrets")?;
+
+        // Install the metrics recorder before any component records metrics
+        llm_sentinel_api::handlers::metrics::install_recorder(&config.observability.metrics)
+            .context("Failed to install metrics recorder")?;
+
+        if let (Some(shipper), Some(shipping)) =
+            (&self.log_shipper, &config.observability.log_shipping)
+        {
+            shipper
+                .start(shipping)
+                .context("Failed to start log shipping")?;
+        }
+
+        // Clients whose credentials can be rotated at runtime
+        let mut credentials: Vec<Arc<dyn CredentialRotation>> = Vec::new();
+
+        // The leader lease tells a warm standby's primary from its standbys
+        let standalone = config.leader_election.is_none() && self.leadership.is_none();
+        if config.standby.is_some() && standalone {
+            anyhow::bail!("standby requires leader_election");
+        }
+
+        // Campaign for leadership before any background task starts
+        let leadership = match self.leadership {
+            Some(leadership) => leadership,
+            None => match &config.leader_election {
+                Some(leader_config) => redis_leadership(leader_config, &mut credentials).await?,
+                None => Leadership::single(),
+            },
+        };
+
+        // Initialize storage
+        let storage: Arc<dyn Storage> = match self.storage {
+            Some(storage) => storage,
+            None => match (
+                config.storage.prometheus.clone(),
+                config.storage.influxdb_v1.clone(),
+            ) {
+                (Some(prometheus_config), _) => {
+                    info!("Connecting to Prometheus remote write...");
+                    Arc::new(
+                        PrometheusStorage::new(prometheus_config)
+                            .await
+                            .context("Failed to initialize Prometheus storage")?,
+                    )
+                }
+                (None, Some(influxdb_v1_config)) => {
+                    info!("Connecting to InfluxDB 1.x...");
+                    Arc::new(
+                        InfluxDbV1Storage::new(influxdb_v1_config)
+                            .await
+                            .context("Failed to initialize InfluxDB 1.x storage")?,
+                    )
+                }
+                (None, None) => influxdb_storage(&config, &mut credentials).await?,
+            },
+        };
+        // Keep redelivered telemetry from being counted twice
+        let storage = IdempotentStorage::wrap(storage, &config.storage.write_dedup);
+
+        // Inject configured faults into dependencies (staging only)
+        #[cfg(not(feature = "chaos"))]
+        if config.chaos.is_some() {
+            anyhow::bail!("chaos requires building with the `chaos` feature");
+        }
+        #[cfg(feature = "chaos")]
+        let storage = chaos::wrap_storage(storage, config.chaos.as_ref());
+
+        // Anomalies are written ahead of their alerts
+        let anomaly_writer = AnomalyWriter::spawn(storage.clone(), &config.storage.anomaly_writer);
+        let outbox = Outbox::new(config.storage.anomaly_writer.outbox_capacity);
+
+        // Initialize cost attribution
+        let cost_aggregator = match config.storage.cost_reporting.clone() {
+            Some(core_cost_config) => {
+                let aggregator = CostAggregator::new(CostAggregatorConfig {
+                    bucket_secs: core_cost_config.bucket_secs,
+                    flush_interval_secs: core_cost_config.flush_interval_secs,
+                    user_key: core_cost_config.user_key,
+                    tag_keys: core_cost_config.tag_keys,
+                })
+                .context("Failed to initialize cost aggregator")?;
+                let aggregator = Arc::new(aggregator);
+                aggregator.clone().start_flush_task(storage.clone());
+                info!("Cost attribution enabled");
+                Some(aggregator)
+            }
+            None => None,
+        };
+
+        // Initialize cost budget forecasting
+        let budgets = match config
+            .storage
+            .cost_reporting
+            .as_ref()
+            .and_then(|cost| cost.budgets.clone())
+        {
+            Some(budget_config) => {
+                let forecaster = BudgetForecaster::new(budget_config)
+                    .context("Failed to initialize cost budgets")?;
+                info!(
+                    "Cost budgets enabled for {} services",
+                    forecaster.config().monthly_usd.len()
+                );
+                Some(Arc::new(forecaster))
+            }
+            None => None,
+        };
+
+        // Initialize model pricing
+        let pricing = match &config.ingestion.pricing {
+            Some(pricing_config) => {
+                let table = PriceTable::from_config(pricing_config)
+                    .context("Failed to initialize model pricing")?;
+                info!("Model pricing enabled for {} models", table.prices().len());
+                Some(Arc::new(table))
+            }
+            None => None,
+        };
+
+        // Initialize identifier normalization
+        let identifiers = match &config.ingestion.identifiers {
+            Some(identifiers_config) => {
+                let normalizer = IdentifierNormalizer::from_config(identifiers_config)
+                    .context("Failed to initialize identifier normalization")?;
+                info!("Identifier normalization enabled");
+                Some(Arc::new(normalizer))
+            }
+            None => None,
+        };
+
+        // Initialize per-service ingestion quotas
+        let quotas = match &config.ingestion.quotas {
+            Some(quota_config) => {
+                let quotas = ServiceQuotas::from_config(quota_config)
+                    .context("Failed to initialize ingestion quotas")?;
+                info!("Ingestion quotas enabled");
+                Some(Arc::new(quotas))
+            }
+            None => None,
+        };
+
+        // Initialize data quality scoring
+        let quality = match &config.ingestion.quality {
+            Some(quality_config) => {
+                let quality = DataQualityMonitor::from_config(quality_config, self.clock.clone())
+                    .context("Failed to initialize data quality scoring")?;
+                info!("Data quality scoring enabled");
+                Some(Arc::new(quality))
+            }
+            None => None,
+        };
+
+        // Initialize the clock skew policy
+        let clock_skew = match &config.ingestion.clock_skew {
+            Some(skew_config) => {
+                let skew = ClockSkewPolicy::from_config(skew_config, self.clock.clone())
+                    .context("Failed to initialize clock skew policy")?;
+                info!("Clock skew policy enabled");
+                Some(Arc::new(skew))
+            }
+            None => None,
+        };
+
+        // Initialize the embedding client
+        let embedder = match &config.ingestion.embeddings {
+            Some(embedding_config) => {
+                let embedder = Embedder::new(embedding_config)
+                    .context("Failed to initialize embedding client")?;
+                info!("Embedding of events without embeddings enabled");
+                Some(Arc::new(embedder))
+            }
+            None => None,
+        };
+
+        // Initialize full-text search
+        let search = match &config.storage.search {
+            Some(search_config) => Some(search_index(search_config)?),
+            None => None,
+        };
+
+        // Initialize embedding similarity search
+        let similarity = config.storage.similarity.as_ref().map(|similarity_config| {
+            info!(
+                capacity = similarity_config.capacity,
+                "Embedding similarity search enabled"
+            );
+            Arc::new(FlatVectorIndex::new(similarity_config.capacity)) as Arc<dyn VectorIndex>
+        });
+
+        // Load WASM plugins
+        let plugins = load_plugins(&config.plugins).context("Failed to load plugins")?;
+        if !config.plugins.is_empty() {
+            info!(
+                detectors = plugins.detectors.len(),
+                enrichers = plugins.enrichers.len(),
+                "WASM plugins loaded"
+            );
+        }
+        let mut enrichers: Vec<Arc<dyn Enricher>> = plugins
+            .enrichers
+            .into_iter()
+            .map(|enricher| Arc::new(enricher) as Arc<dyn Enricher>)
+            .collect();
+
+        // Load scripting hooks
+        let scripts = match (self.scripts, &config.scripting) {
+            (Some(scripts), _) => Some(scripts),
+            (None, Some(scripting)) => {
+                let scripts = Arc::new(
+                    ScriptHooks::from_config(scripting).context("Failed to load scripts")?,
+                );
+                scripts
+                    .clone()
+                    .start_reload_task(Duration::from_secs(scripting.reload_interval_secs));
+                info!(scripts = ?scripts.script_names(), "Scripting hooks enabled");
+                Some(scripts)
+            }
+            (None, None) => None,
+        };
+        if let Some(scripts) = &scripts {
+            enrichers.push(scripts.clone());
+        }
+        enrichers.extend(self.enrichers);
+
+        // Initialize detection engine
+        let mut detection_engine = match self.detection_engine {
+            Some(engine) => engine,
+            None => {
+                info!("Initializing detection engine...");
+
+                // Convert DetectionConfig to EngineConfig
+                let mut engine_config = EngineConfig {
+                    baseline_window: config.detection.baseline_window.spec(),
+                    key_by_model_version: config.detection.key_by_model_version,
+                    version_change_lookback_secs: config.detection.version_change_lookback_secs,
+                    tiering: config.detection.tiering.clone(),
+                    clock: self.clock.clone(),
+                    ..Default::default()
+                };
+                if let Some(limits) = &config.detection.context_limits {
+                    engine_config.enable_context_overflow = true;
+                    engine_config.context_overflow_config =
+                        ContextOverflowConfig::from_config(limits)
+                            .context("Failed to load model context windows")?;
+                }
+                if let Some(cost_regression) = &config.detection.cost_regression {
+                    engine_config.enable_cost_residual = true;
+                    engine_config.cost_residual_config =
+                        CostResidualConfig::from_config(cost_regression);
+                }
+                if let Some(regime_shift) = &config.detection.regime_shift {
+                    engine_config.enable_multi_window = true;
+                    engine_config.multi_window_config =
+                        MultiWindowConfig::from_config(regime_shift);
+                }
+                if let Some(prompt_bloat) = &config.detection.prompt_bloat {
+                    engine_config.enable_token_efficiency = true;
+                    engine_config.token_efficiency_config =
+                        TokenEfficiencyConfig::from_config(prompt_bloat);
+                }
+                if let Some(content_filter_surge) = &config.detection.content_filter_surge {
+                    engine_config.enable_content_filter = true;
+                    engine_config.content_filter_config =
+                        ContentFilterConfig::from_config(content_filter_surge);
+                }
+                if let Some(output_drift) = &config.detection.output_drift {
+                    engine_config.enable_kl_divergence = true;
+                    engine_config.kl_divergence_config =
+                        KlDivergenceConfig::from_config(output_drift);
+                }
+                if let Some(distribution_drift) = &config.detection.distribution_drift {
+                    engine_config.enable_psi = true;
+                    engine_config.psi_config = PsiConfig::from_config(distribution_drift);
+                }
+                if let Some(change_points) = &config.detection.change_points {
+                    engine_config.enable_bocpd = true;
+                    engine_config.bocpd_config = BocpdConfig::from_config(change_points);
+                }
+
+                DetectionEngine::new(engine_config).context("Failed to create detection engine")?
+            }
+        };
+        for detector in plugins.detectors {
+            detection_engine.add_detector(Box::new(detector));
+        }
+        if let Some(bootstrap) = &config.detection.bootstrap {
+            bootstrap_baselines(
+                storage.as_ref(),
+                &mut detection_engine,
+                bootstrap,
+                &self.clock,
+            )
+            .await;
+        }
+        let detection_load = detection_engine.load();
+        let detection_engine = Arc::new(Mutex::new(detection_engine));
+        info!("Detection engine initialized");
+
+        // Initialize SLO tracking
+        let slo_tracker = if config.detection.slos.is_empty() {
+            None
+        } else {
+            let definitions = config
+                .detection
+                .slos
+                .iter()
+                .map(slo_definition)
+                .collect::<Result<Vec<_>>>()?;
+            Some(Arc::new(
+                SloTracker::new(definitions)
+                    .context("Failed to initialize SLO tracker")?
+                    .with_clock(self.clock.clone()),
+            ))
+        };
+
+        // Initialize availability tracking
+        let availability_tracker = match &config.detection.availability {
+            Some(availability) => {
+                let tracker = AvailabilityTracker::new(AvailabilityTrackerConfig {
+                    bucket_secs: availability.bucket_secs,
+                    retention_secs: availability.retention_secs,
+                    window_secs: availability.window_secs,
+                    error_rate_threshold: availability.error_rate_threshold,
+                    min_requests: availability.min_requests,
+                    provider_outage_min_services: availability.provider_outage_min_services,
+                    provider_key: availability.provider_key.clone(),
+                    clock: self.clock.clone(),
+                })
+                .context("Failed to initialize availability tracker")?;
+                info!("Availability tracking enabled");
+                Some(Arc::new(tracker))
+            }
+            None => None,
+        };
+
+        // Initialize anomaly auto-resolution
+        let recovery_monitor = match &config.detection.recovery {
+            Some(recovery) => {
+                let monitor = RecoveryMonitor::new(RecoveryMonitorConfig {
+                    recovery_secs: recovery.recovery_secs,
+                    min_samples: recovery.min_samples,
+                })
+                .context("Failed to initialize recovery monitor")?;
+                info!("Anomaly auto-resolution enabled");
+                Some(Arc::new(monitor))
+            }
+            None => None,
+        };
+
+        // Initialize telemetry silence detection
+        let silence_monitor = match &config.detection.silence {
+            Some(silence) => {
+                let monitor = SilenceMonitor::new(SilenceMonitorConfig {
+                    silence_secs: silence.silence_secs,
+                    min_events: silence.min_events,
+                    forget_secs: silence.forget_secs,
+                    severity: silence.severity,
+                    clock: self.clock.clone(),
+                })
+                .context("Failed to initialize silence monitor")?;
+                info!("Telemetry silence detection enabled");
+                Some(Arc::new(monitor))
+            }
+            None => None,
+        };
+
+        // Initialize trace-level evaluation of multi-span chains
+        let trace_aggregator = match &config.detection.traces {
+            Some(traces) => {
+                let aggregator = TraceAggregator::new(TraceAggregatorConfig {
+                    idle_secs: traces.idle_secs,
+                    max_duration_secs: traces.max_duration_secs,
+                    max_open_traces: traces.max_open_traces,
+                    threshold_sigma: traces.threshold_sigma,
+                    min_samples: traces.min_samples,
+                    window: WindowSpec::samples(traces.window_size),
+                    clock: self.clock.clone(),
+                })
+                .context("Failed to initialize trace aggregator")?;
+                info!("Trace-level chain evaluation enabled");
+                Some(Arc::new(aggregator))
+            }
+            None => None,
+        };
+
+        // Initialize detection load shedding
+        let load_shedding = match &config.detection.load_shedding {
+            Some(shedding) => {
+                let sampler =
+                    AdaptiveSampler::from_config(shedding, config.detection.queue_capacity)
+                        .context("Failed to initialize detection load shedding")?;
+                info!("Detection load shedding enabled");
+                Some(Arc::new(sampler))
+            }
+            None => None,
+        };
+
+        // Initialize ingestion back-pressure
+        let backpressure = match &config.ingestion.backpressure {
+            Some(backpressure) => {
+                let backpressure =
+                    Backpressure::from_config(backpressure, config.detection.queue_capacity)
+                        .context("Failed to initialize ingestion back-pressure")?;
+                info!("Ingestion back-pressure enabled");
+                Some(backpressure)
+            }
+            None => None,
+        };
+
+        // Initialize aggregation-only data handling
+        let aggregation_only = match &config.storage.aggregation_only {
+            Some(aggregation_only) => {
+                let policy = AggregationOnly::from_config(aggregation_only)
+                    .context("Failed to initialize aggregation-only mode")?;
+                info!("Aggregation-only mode enabled");
+                Some(policy)
+            }
+            None => None,
+        };
+
+        // Initialize alerting
+        let (alerters, spec_alerters) = match self.alerters {
+            Some(alerters) => (alerters, Vec::new()),
+            None => {
+                let registry = self
+                    .alerter_registry
+                    .unwrap_or_else(AlerterRegistry::with_builtin);
+                let alerters = configured_alerters(&config, &registry).await?;
+                // Alerters built from `alerting.alerters` come last
+                let first_spec = alerters.len() - config.alerting.alerters.len();
+                let spec_alerters = alerters[first_spec..].to_vec();
+                (alerters, spec_alerters)
+            }
+        };
+        #[cfg(feature = "chaos")]
+        let alerters = chaos::wrap_alerters(alerters, config.chaos.as_ref());
+
+        // Initialize remediation
+        let audit_log = self.audit_log.unwrap_or_else(|| Arc::new(TracingAuditLog));
+        let remediation = match config.alerting.remediation.clone() {
+            Some(remediation_config) => Some(Arc::new(
+                RemediationEngine::new(remediation_config, audit_log.clone())
+                    .context("Failed to initialize remediation")?,
+            )),
+            None => None,
+        };
+
+        // Initialize deduplicator
+        let dedup_config = DeduplicationConfig {
+            window_secs: config.alerting.dedup_window_secs,
+            enabled: true,
+            cleanup_interval_secs: 60,
+            key_fields: config.alerting.dedup_key.clone(),
+            summarize_on_expiry: config.alerting.dedup_summary,
+            clock: self.clock.clone(),
+        };
+        let deduplicator = Arc::new(AlertDeduplicator::new(dedup_config));
+
+        // Start cleanup task
+        deduplicator.clone().start_cleanup_task();
+
+        // Track large in-memory structures against the memory budget
+        let detection_queue = Arc::new(DetectionQueueMemory::default());
+        let memory = match &config.memory {
+            Some(budget) => {
+                let watchdog = MemoryWatchdog::new(budget.clone());
+                watchdog.register(detection_engine.lock().await.baseline_manager().clone());
+                watchdog.register(deduplicator.clone());
+                watchdog.register(detection_queue.clone());
+                Some(Arc::new(watchdog))
+            }
+            None => None,
+        };
+
+        let mut router = AlertRouter::new(config.alerting.routes.clone())
+            .with_severity_rules(&config.alerting.severity_rules)
+            .context("Failed to initialize severity rules")?;
+        let holding_queue = match &config.alerting.delivery_schedules {
+            Some(schedules) => {
+                router = router
+                    .with_delivery_schedules(&schedules.schedules)
+                    .context("Failed to initialize delivery schedules")?;
+                let queue = HoldingQueue::open(&schedules.queue_path)
+                    .await
+                    .context("Failed to open the held alert queue")?;
+                info!(
+                    schedules = schedules.schedules.len(),
+                    held = queue.len().await,
+                    "Delivery schedules enabled"
+                );
+                Some(Arc::new(queue))
+            }
+            None => None,
+        };
+        let suppression = SuppressionSchedules::new(&config.alerting.suppression_schedules)
+            .context("Failed to initialize suppression schedules")?;
+
+        // Queue alerts per alerter so slow alerters do not stall detection
+        let bus = self.bus.unwrap_or_default();
+        let dispatcher = config.alerting.dispatch.as_ref().map(|dispatch_config| {
+            let storage = storage.clone();
+            let bus = bus.clone();
+            info!(
+                queue_capacity = dispatch_config.queue_capacity,
+                "Queued alert dispatch enabled"
+            );
+            AlertDispatcher::spawn(
+                &alerters,
+                dispatch_config,
+                config.alerting.batch_size,
+                Duration::from_millis(config.alerting.batch_timeout_ms),
+                move |dispatched| {
+                    let storage = storage.clone();
+                    let bus = bus.clone();
+                    async move { record_dispatched(storage.as_ref(), &bus, dispatched).await }
+                },
+            )
+        });
+
+        // Initialize telemetry fan-out
+        if let Some(fanout_config) = &config.ingestion.fanout {
+            telemetry_fanout(fanout_config, &bus).await?;
+        }
+
+        // Initialize write-only exports
+        if let Some(export_config) = &config.storage.export {
+            analytics_export(export_config, &bus).await?;
+        }
+
+        let digests = if config.alerting.digests.is_empty() {
+            None
+        } else {
+            Some(Arc::new(
+                DigestScheduler::new(config.alerting.digests.clone(), self.clock.now())
+                    .context("Failed to initialize digests")?,
+            ))
+        };
+
+        // Initialize incident grouping
+        let incidents = match &config.alerting.incidents {
+            Some(incident_config) => {
+                let tracker = IncidentTracker::new(incident_config).with_clock(self.clock.clone());
+                // A failed restore opens new incidents for the active groups
+                match tracker.restore(storage.as_ref()).await {
+                    Ok(active) => info!(active, "Incident grouping enabled"),
+                    Err(e) => warn!("Failed to restore active incidents: {}", e),
+                }
+                for name in &incident_config.notify_alerters {
+                    if !alerters.iter().any(|alerter| alerter.name() == name) {
+                        warn!(alerter = %name, "Incident alerter is not configured");
+                    }
+                }
+                Some(Arc::new(tracker))
+            }
+            None => None,
+        };
+
+        let snapshot_store: Option<Arc<dyn SnapshotStore>> = match self.snapshot_store {
+            Some(store) => Some(store),
+            None => match &config.storage.snapshots {
+                Some(snapshot_config) => Some(Arc::new(
+                    FileSnapshotStore::new(&snapshot_config.dir, snapshot_config.keep)
+                        .context("Failed to initialize snapshot store")?,
+                )),
+                None => None,
+            },
+        };
+        let state_stream: Option<Arc<dyn StateStream>> = match &config.standby {
+            Some(standby_config) => match self.state_stream {
+                Some(stream) => Some(stream),
+                None => Some(redis_state_stream(standby_config).await?),
+            },
+            None => None,
+        };
+        let restore_on_start = config
+            .storage
+            .snapshots
+            .as_ref()
+            .is_some_and(|snapshots| snapshots.restore_on_start);
+
+        info!("All components initialized successfully");
+
+        let sentinel = Sentinel {
+            config,
+            storage,
+            anomaly_writer,
+            outbox,
+            ingester: Mutex::new(self.ingester),
+            enrichers,
+            scripts,
+            cost_aggregator,
+            budgets,
+            pricing,
+            identifiers,
+            quotas,
+            quality,
+            clock_skew,
+            embedder,
+            search,
+            similarity,
+            slo_tracker,
+            availability_tracker,
+            recovery_monitor,
+            silence_monitor,
+            trace_aggregator,
+            load_shedding,
+            backpressure,
+            aggregation_only,
+            memory,
+            detection_queue,
+            detection_engine,
+            detection_load,
+            alerters,
+            router,
+            suppression,
+            remediation,
+            deduplicator,
+            dispatcher,
+            holding_queue,
+            digests,
+            incidents,
+            audit_log,
+            snapshot_store,
+            standby: AtomicBool::new(state_stream.is_some()),
+            state_stream,
+            synced_state: RwLock::new(None),
+            bus,
+            leadership,
+            secrets,
+            unresolved_config: self.config,
+            credentials,
+            spec_alerters,
+            redetect_jobs: Arc::default(),
+            clock: self.clock.clone(),
+            enable_api: self.enable_api,
+        };
+
+        // A failed restore leaves the sentinel to learn from live traffic
+        if restore_on_start {
+            match SnapshotControl::restore(&sentinel, "latest").await {
+                Ok(snapshot) => info!(id = %snapshot.id, "Resumed from runtime snapshot"),
+                Err(llm_sentinel_core::Error::NotFound(_)) => {
+                    info!("No runtime snapshot to resume from")
+                }
+                Err(e) => warn!("Failed to restore runtime snapshot: {}", e),
+            }
+        }
+
+        Ok(sentinel)
+    }
+}
+
+impl std::fmt::Debug for SentinelBuilder {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("SentinelBuilder")
+            .field("storage", &self.storage.is_some())
+            .field("ingester", &self.ingester.is_some())
+            .field("detection_engine", &self.detection_engine.is_some())
+            .field("alerters", &self.alerters.as_ref().map(Vec::len))
+            .field("alerter_registry", &self.alerter_registry)
+            .field("enrichers", &self.enrichers.len())
+            .field("scripts", &self.scripts)
+            .field("audit_log", &self.audit_log)
+            .field("snapshot_store", &self.snapshot_store)
+            .field("state_stream", &self.state_stream)
+            .field("clock", &self.clock)
+            .field("bus", &self.bus.is_some())
+            .field("leadership", &self.leadership)
+            .field("log_shipper", &self.log_shipper.is_some())
+            .field("enable_api", &self.enable_api)
+            .finish_non_exhaustive()
+    }
+}
+
+/// Replay recent stored telemetry into the detection engine
+///
+/// The lookback ends at `clock`'s now, so replays and simulations warm up
+/// from the history before their own start. Failures are logged; detection
+/// then warms up from live traffic.
+async fn bootstrap_baselines(
+    storage: &dyn Storage,
+    engine: &mut DetectionEngine,
+    config: &BaselineBootstrapConfig,
+    clock: &Clock,
+) {
+    let end = clock.now();
+    let start = end - chrono::Duration::hours(config.lookback_hours as i64);
+    let query = TelemetryQuery::new(TimeRange::new(start, end)).with_limit(config.max_events);
+
+    match storage.query_telemetry(query).await {
+        Ok(events) => {
+            engine.bootstrap(&events).await;
+        }
+        Err(e) => {
+            warn!("Failed to load telemetry for baseline bootstrap: {}", e);
+        }
+    }
+}
+
+/// Replay stored telemetry in the requested range through a fresh
+/// detection engine and write the anomalies found under `job_id`
+///
+/// Returns the number of events replayed and anomalies found.
+async fn run_redetection(
+    storage: &dyn Storage,
+    job_id: Uuid,
+    request: &RedetectRequest,
+) -> llm_sentinel_core::Result<(usize, usize)> {
+    let query = TelemetryQuery::new(TimeRange::new(request.start, request.end))
+        .with_limit(request.max_events);
+    let events = storage.query_telemetry(query).await?;
+    let replayed = events.len();
+
+    let anomalies = redetect(&request.settings, events).await?;
+    storage
+        .write_redetected_anomalies(job_id, &anomalies)
+        .await?;
+    ::metrics::counter!("sentinel_redetected_anomalies_total").increment(anomalies.len() as u64);
+
+    Ok((replayed, anomalies.len()))
+}
+
+/// Open the search index configured under `storage.search` and start
+/// committing it periodically
+#[cfg(feature = "search")]
+fn search_index(config: &SearchConfig) -> Result<Arc<dyn TextSearch>> {
+    let index: Arc<dyn TextSearch> = Arc::new(
+        TantivySearchIndex::from_config(config).context("Failed to initialize search index")?,
+    );
+    start_commit_task(
+        index.clone(),
+        Duration::from_secs(config.commit_interval_secs),
+    );
+    info!("Full-text search enabled");
+    Ok(index)
+}
+
+#[cfg(not(feature = "search"))]
+fn search_index(_config: &SearchConfig) -> Result<Arc<dyn TextSearch>> {
+    anyhow::bail!("storage.search requires building with the `search` feature")
+}
+
+/// Record the outcome of rotating one component's credentials
+fn record_rotation(
+    component: &str,
+    result: llm_sentinel_core::Result<bool>,
+    rotated: &mut Vec<String>,
+    failure: &mut Option<llm_sentinel_core::Error>,
+) {
+    match result {
+        Ok(true) => {
+            info!(component, "Credentials rotated");
+            ::metrics::counter!(
+                "sentinel_credential_rotations_total",
+                "component" => component.to_string()
+            )
+            .increment(1);
+            rotated.push(component.to_string());
+        }
+        Ok(false) => {}
+        Err(e) => {
+            warn!(component, "Failed to rotate credentials: {}", e);
+            ::metrics::counter!(
+                "sentinel_credential_rotation_failures_total",
+                "component" => component.to_string()
+            )
+            .increment(1);
+            if failure.is_none() {
+                *failure = Some(e);
+            }
+        }
+    }
+}
+
+/// Rotate credentials each time the process receives SIGHUP
+#[cfg(unix)]
+async fn rotate_on_hangup(sentinel: Arc<Sentinel>) {
+    let mut hangup = match signal::unix::signal(signal::unix::SignalKind::hangup()) {
+        Ok(hangup) => hangup,
+        Err(e) => {
+            warn!("Failed to install SIGHUP handler: {}", e);
+            return;
+        }
+    };
+    while hangup.recv().await.is_some() {
+        info!("Received SIGHUP, rotating credentials");
+        if let Err(e) = CredentialControl::rotate(sentinel.as_ref()).await {
+            warn!("Failed to rotate credentials: {}", e);
+        }
+    }
+}
+
+/// Build the secret resolver, with the Vault and AWS Secrets Manager
+/// backends configured under `secrets`
+pub fn secret_resolver(config: &Config) -> Result<SecretResolver> {
+    let mut resolver = SecretResolver::new();
+    let Some(secrets) = &config.secrets else {
+        return Ok(resolver);
+    };
+    if let Some(vault_config) = &secrets.vault {
+        let vault = VaultSecrets::new(vault_config).context("Failed to create Vault backend")?;
+        resolver = resolver.with_backend(VAULT_SCHEME, Arc::new(vault));
+    }
+    if let Some(aws_config) = &secrets.aws {
+        let secrets_manager = SecretsManagerSecrets::new(aws_config)
+            .context("Failed to create AWS Secrets Manager backend")?;
+        resolver = resolver.with_backend(AWS_SECRETS_MANAGER_SCHEME, Arc::new(secrets_manager));
+    }
+    Ok(resolver)
+}
+
+/// Self-checks of the dependencies configured in `config`
+///
+/// A section configured in a binary built without the feature it needs is
+/// checked as a failure.
+pub fn self_checks(config: &Config) -> Vec<Arc<dyn SelfCheck>> {
+    let mut checks: Vec<Arc<dyn SelfCheck>> = Vec::new();
+
+    #[cfg(feature = "kafka")]
+    if let Some(kafka) = &config.ingestion.kafka {
+        checks.push(Arc::new(llm_sentinel_ingestion::kafka::KafkaCheck::new(
+            kafka.clone(),
+        )));
+    }
+    #[cfg(not(feature = "kafka"))]
+    if config.ingestion.kafka.is_some() {
+        checks.push(Arc::new(FeatureMissing("kafka", "kafka")));
+    }
+
+    #[cfg(feature = "influxdb")]
+    if let Some(influxdb) = &config.storage.influxdb {
+        checks.push(Arc::new(
+            llm_sentinel_storage::influxdb::InfluxDbCheck::new(influxdb_config(influxdb.clone())),
+        ));
+    }
+    #[cfg(not(feature = "influxdb"))]
+    if config.storage.influxdb.is_some() {
+        checks.push(Arc::new(FeatureMissing("influxdb", "influxdb")));
+    }
+
+    #[cfg(feature = "redis")]
+    {
+        use llm_sentinel_storage::cache::RedisCheck;
+
+        if let Some(redis) = &config.storage.redis {
+            checks.push(Arc::new(RedisCheck::new("redis", &redis.url)));
+        }
+        if let Some(leader_election) = &config.leader_election {
+            checks.push(Arc::new(RedisCheck::new(
+                "redis-leader",
+                &leader_election.redis_url,
+            )));
+        }
+        if let Some(standby) = &config.standby {
+            checks.push(Arc::new(RedisCheck::new(
+                "redis-standby",
+                &standby.redis_url,
+            )));
+        }
+    }
+    #[cfg(not(feature = "redis"))]
+    {
+        if config.storage.redis.is_some() {
+            checks.push(Arc::new(FeatureMissing("redis", "redis")));
+        }
+        if config.leader_election.is_some() {
+            checks.push(Arc::new(FeatureMissing("redis-leader", "redis")));
+        }
+        if config.standby.is_some() {
+            checks.push(Arc::new(FeatureMissing("redis-standby", "redis")));
+        }
+    }
+
+    #[cfg(feature = "rabbitmq")]
+    {
+        use llm_sentinel_alerting::rabbitmq::RabbitMqCheck;
+
+        if let Some(rabbitmq) = &config.alerting.rabbitmq {
+            checks.push(Arc::new(RabbitMqCheck::new(
+                "rabbitmq",
+                &rabbitmq.url,
+                &rabbitmq.exchange,
+                &rabbitmq.exchange_type,
+            )));
+        }
+        if let Some(fanout) = &config.ingestion.fanout {
+            for destination in &fanout.destinations {
+                checks.push(Arc::new(RabbitMqCheck::new(
+                    format!("rabbitmq-fanout:{}", destination.exchange),
+                    &fanout.url,
+                    &destination.exchange,
+                    &destination.exchange_type,
+                )));
+            }
+        }
+    }
+    #[cfg(not(feature = "rabbitmq"))]
+    {
+        if config.alerting.rabbitmq.is_some() {
+            checks.push(Arc::new(FeatureMissing("rabbitmq", "rabbitmq")));
+        }
+        if config.ingestion.fanout.is_some() {
+            checks.push(Arc::new(FeatureMissing("rabbitmq-fanout", "rabbitmq")));
+        }
+    }
+
+    checks
+}
+
+/// Check the dependencies configured in `config`
+pub async fn self_check(config: &Config) -> SelfCheckReport {
+    selfcheck::run_checks(self_checks(config), SELF_CHECK_TIMEOUT).await
+}
+
+/// Validate `document` against `config`, as the sentinel would build it,
+/// and list the sections it changes
+///
+/// Besides the configuration's own validation, severity rules and
+/// suppression schedules must parse.
+pub fn plan_detection(
+    config: &Config,
+    document: &DetectionDocument,
+) -> llm_sentinel_core::Result<DetectionPlan> {
+    let plan = document.plan(config)?;
+    let invalid = |e: llm_sentinel_core::Error| llm_sentinel_core::Error::validation(e.to_string());
+    AlertRouter::new(plan.config.alerting.routes.clone())
+        .with_severity_rules(&plan.config.alerting.severity_rules)
+        .map_err(invalid)?;
+    SuppressionSchedules::new(&plan.config.alerting.suppression_schedules).map_err(invalid)?;
+    Ok(plan)
+}
+
+/// Dependency configured in a binary built without the feature it needs
+#[cfg(not(all(
+    feature = "kafka",
+    feature = "influxdb",
+    feature = "redis",
+    feature = "rabbitmq"
+)))]
+struct FeatureMissing(&'static str, &'static str);
+
+#[cfg(not(all(
+    feature = "kafka",
+    feature = "influxdb",
+    feature = "redis",
+    feature = "rabbitmq"
+)))]
+#[async_trait::async_trait]
+impl SelfCheck for FeatureMissing {
+    fn dependency(&self) -> String {
+        self.0.to_string()
+    }
+
+    async fn run(&self) -> Vec<selfcheck::CheckResult> {
+        vec![selfcheck::CheckResult::fail(
+            self.0,
+            selfcheck::CheckKind::Connectivity,
+            format!("requires building with the `{}` feature", self.1),
+        )]
+    }
+}
+
+/// Create the Kafka ingester configured under `ingestion.kafka`
+#[cfg(feature = "kafka")]
+fn kafka_ingester(config: &Config) -> Result<Box<dyn Ingester>> {
+    info!("Starting Kafka ingestion pipeline...");
+    let kafka_config = config
+        .ingestion
+        .kafka
+        .as_ref()
+        .context("Kafka configuration is required")?;
+    Ok(Box::new(
+        KafkaIngester::new(
+            kafka_config,
+            config.ingestion.batch_size,
+            config.ingestion.batch_timeout_ms,
+        )
+        .context("Failed to create Kafka ingester")?,
+    ))
+}
+
+#[cfg(not(feature = "kafka"))]
+fn kafka_ingester(_config: &Config) -> Result<Box<dyn Ingester>> {
+    anyhow::bail!("Kafka ingestion requires building with the `kafka` feature")
+}
+
+/// Serve gRPC health checking and reflection on `ingestion.grpc`
+///
+/// The server reports serving while storage is healthy, the condition
+/// `/health/ready` checks.
+#[cfg(feature = "grpc")]
+async fn grpc_server(
+    sentinel: Arc<Sentinel>,
+    config: llm_sentinel_core::config::GrpcConfig,
+) -> Result<()> {
+    let server = GrpcServer::new(config);
+    let health = server.health();
+    health.set_serving(false).await;
+
+    let storage = sentinel.storage.clone();
+    let reporter = tokio::spawn(async move {
+        let mut ticker = tokio::time::interval(GRPC_HEALTH_INTERVAL);
+        loop {
+            ticker.tick().await;
+            health
+                .set_serving(storage.health_check().await.is_ok())
+                .await;
+        }
+    });
+
+    let result = server.serve_with_shutdown(std::future::pending()).await;
+    reporter.abort();
+    result.context("gRPC server failed")
+}
+
+#[cfg(not(feature = "grpc"))]
+async fn grpc_server(
+    _sentinel: Arc<Sentinel>,
+    _config: llm_sentinel_core::config::GrpcConfig,
+) -> Result<()> {
+    anyhow::bail!("The gRPC server requires building with the `grpc` feature")
+}
+
+/// Convert core InfluxDbConfig to storage InfluxDbConfig
+#[cfg(feature = "influxdb")]
+fn influxdb_config(
+    core_influxdb_config: llm_sentinel_core::config::InfluxDbConfig,
+) -> llm_sentinel_storage::influxdb::InfluxDbConfig {
+    llm_sentinel_storage::influxdb::InfluxDbConfig {
+        url: core_influxdb_config.url,
+        org: core_influxdb_config.org,
+        telemetry_bucket: core_influxdb_config.bucket.clone(),
+        anomaly_bucket: format!("{}-anomalies", core_influxdb_config.bucket),
+        token: core_influxdb_config.token,
+        batch_size: 100,
+        timeout_secs: core_influxdb_config.timeout_secs,
+        retry: RetryPolicy::new(core_influxdb_config.write_attempts)
+            .with_initial_delay(Duration::from_millis(core_influxdb_config.retry_delay_ms)),
+        tags: core_influxdb_config.tags,
+    }
+}
+
+/// Connect to the InfluxDB instance configured under `storage`
+#[cfg(feature = "influxdb")]
+async fn influxdb_storage(
+    config: &Config,
+    credentials: &mut Vec<Arc<dyn CredentialRotation>>,
+) -> Result<Arc<dyn Storage>> {
+    info!("Connecting to InfluxDB...");
+    let core_influxdb_config = config
+        .storage
+        .influxdb
+        .clone()
+        .context("InfluxDB configuration is required")?;
+    let influxdb_config = influxdb_config(core_influxdb_config);
+
+    let storage = InfluxDbStorage::new(influxdb_config)
+        .await
+        .context("Failed to initialize storage")?;
+    info!("InfluxDB connected");
+
+    let storage = Arc::new(storage);
+    credentials.push(storage.clone());
+    Ok(storage)
+}
+
+#[cfg(not(feature = "influxdb"))]
+async fn influxdb_storage(
+    _config: &Config,
+    _credentials: &mut Vec<Arc<dyn CredentialRotation>>,
+) -> Result<Arc<dyn Storage>> {
+    anyhow::bail!("InfluxDB storage requires building with the `influxdb` feature")
+}
+
+/// Campaign for the Redis lease configured under `leader_election`
+#[cfg(feature = "redis")]
+async fn redis_leadership(
+    config: &llm_sentinel_core::config::LeaderElectionConfig,
+    credentials: &mut Vec<Arc<dyn CredentialRotation>>,
+) -> Result<Leadership> {
+    let elector = RedisLeaderElector::new(config)
+        .await
+        .context("Failed to initialize leader election")?;
+    let renew_every = elector.renew_interval();
+    let elector = Arc::new(elector);
+    credentials.push(elector.clone());
+    Ok(Leadership::campaign(elector, renew_every))
+}
+
+#[cfg(not(feature = "redis"))]
+async fn redis_leadership(
+    _config: &llm_sentinel_core::config::LeaderElectionConfig,
+    _credentials: &mut Vec<Arc<dyn CredentialRotation>>,
+) -> Result<Leadership> {
+    anyhow::bail!("Leader election requires building with the `redis` feature")
+}
+
+/// Connect to the state stream configured under `standby`
+#[cfg(feature = "redis")]
+async fn redis_state_stream(
+    config: &llm_sentinel_core::config::StandbyConfig,
+) -> Result<Arc<dyn StateStream>> {
+    let stream = RedisStateStream::new(config)
+        .await
+        .context("Failed to initialize standby state stream")?;
+    Ok(Arc::new(stream))
+}
+
+#[cfg(not(feature = "redis"))]
+async fn redis_state_stream(
+    _config: &llm_sentinel_core::config::StandbyConfig,
+) -> Result<Arc<dyn StateStream>> {
+    anyhow::bail!("Warm standby requires building with the `redis` feature")
+}
+
+/// Connect to the RabbitMQ exchange configured under `alerting.rabbitmq`
+#[cfg(feature = "rabbitmq")]
+async fn rabbitmq_alerter(
+    core_rabbitmq_config: llm_sentinel_core::config::RabbitMqConfig,
+) -> Result<Arc<dyn Alerter>> {
+    use llm_sentinel_alerting::rabbitmq::RetryConfig;
+
+    info!("Connecting to RabbitMQ...");
+
+    // Convert core RabbitMqConfig to alerting RabbitMqConfig
+    let rabbitmq_config = llm_sentinel_alerting::rabbitmq::RabbitMqConfig {
+        url: core_rabbitmq_config.url,
+        exchange: core_rabbitmq_config.exchange,
+        exchange_type: core_rabbitmq_config.exchange_type,
+        routing_key_prefix: "alert".to_string(),
+        persistent: core_rabbitmq_config.durable,
+        timeout_secs: 10,
+        retry_config: RetryConfig {
+            max_attempts: core_rabbitmq_config.retry_attempts,
+            initial_delay_ms: core_rabbitmq_config.retry_delay_ms,
+            backoff_multiplier: 2.0,
+            max_delay_ms: 30000,
+        },
+        cloudevents: None,
+        encoding: Default::default(),
+    };
+
+    let alerter = RabbitMqAlerter::new(rabbitmq_config)
+        .await
+        .context("Failed to initialize RabbitMQ alerter")?;
+    info!("RabbitMQ connected");
+
+    Ok(Arc::new(alerter))
+}
+
+#[cfg(not(feature = "rabbitmq"))]
+async fn rabbitmq_alerter(
+    _config: llm_sentinel_core::config::RabbitMqConfig,
+) -> Result<Arc<dyn Alerter>> {
+    anyhow::bail!("alerting.rabbitmq requires building with the `rabbitmq` feature")
+}
+
+/// Publish telemetry to the exchanges configured under `ingestion.fanout`
+#[cfg(feature = "rabbitmq")]
+async fn telemetry_fanout(
+    config: &llm_sentinel_core::config::TelemetryFanoutConfig,
+    bus: &EventBus,
+) -> Result<()> {
+    let fanout = TelemetryFanout::connect(config)
+        .await
+        .context("Failed to initialize telemetry fan-out")?;
+    // Subscribed before anything publishes
+    fanout.spawn(bus.telemetry().subscribe());
+    info!(
+        destinations = config.destinations.len(),
+        "Telemetry fan-out enabled"
+    );
+    Ok(())
+}
+
+#[cfg(not(feature = "rabbitmq"))]
+async fn telemetry_fanout(
+    _config: &llm_sentinel_core::config::TelemetryFanoutConfig,
+    _bus: &EventBus,
+) -> Result<()> {
+    anyhow::bail!("ingestion.fanout requires building with the `rabbitmq` feature")
+}
+
+/// Export telemetry and anomalies to the stores configured under
+/// `storage.export`
+async fn analytics_export(
+    config: &llm_sentinel_core::config::ExportConfig,
+    bus: &EventBus,
+) -> Result<()> {
+    let mut sinks: Vec<Arc<dyn ExportSink>> = Vec::new();
+    if let Some(timestream) = &config.timestream {
+        let sink = TimestreamSink::connect(timestream.clone())
+            .await
+            .context("Failed to initialize Timestream export")?;
+        sinks.push(Arc::new(sink));
+    }
+    if let Some(bigquery) = &config.bigquery {
+        let sink =
+            BigQuerySink::new(bigquery.clone()).context("Failed to initialize BigQuery export")?;
+        sinks.push(Arc::new(sink));
+    }
+    if sinks.is_empty() {
+        warn!("storage.export has no sinks configured");
+        return Ok(());
+    }
+
+    let names: Vec<String> = sinks.iter().map(|sink| sink.name().to_string()).collect();
+    // Subscribed before anything publishes
+    Exporter::new(sinks, config).spawn(bus.telemetry().subscribe(), bus.anomalies().subscribe());
+    info!(sinks = ?names, "Analytics export enabled");
+    Ok(())
+}
+
+/// Construct the alerters configured under `alerting`
+async fn configured_alerters(
+    config: &Config,
+    registry: &AlerterRegistry,
+) -> Result<Vec<Arc<dyn Alerter>>> {
+    let mut alerters: Vec<Arc<dyn Alerter>> = Vec::new();
+
+    if let Some(core_rabbitmq_config) = config.alerting.rabbitmq.clone() {
+        alerters.push(rabbitmq_alerter(core_rabbitmq_config).await?);
+    }
+
+    if let Some(core_pubsub_config) = config.alerting.pubsub.clone() {
+        // Convert core PubSubTopicConfig to alerting PubSubConfig
+        let pubsub_config = PubSubConfig {
+            project_id: core_pubsub_config.project_id,
+            topic: core_pubsub_config.topic,
+            endpoint: core_pubsub_config.endpoint,
+            access_token: core_pubsub_config.access_token,
+            ordered_by_service: core_pubsub_config.ordered_by_service,
+            timeout_secs: core_pubsub_config.timeout_secs,
+            cloudevents: None,
+            encoding: Default::default(),
+            http: Default::default(),
+        };
+
+        let alerter =
+            PubSubAlerter::new(pubsub_config).context("Failed to initialize Pub/Sub alerter")?;
+        alerters.push(Arc::new(alerter));
+        info!("Pub/Sub alerter initialized");
+    }
+
+    if let Some(federation) = &config.federation {
+        if let Some(upstream) = &federation.upstream {
+            let forwarder = FederationForwarder::new(federation.region.clone(), upstream)
+                .context("Failed to initialize federation forwarder")?;
+            alerters.push(Arc::new(forwarder));
+            info!(region = %federation.region, "Federation forwarder initialized");
+        }
+    }
+
+    for alerter in registry
+        .build_all(&config.alerting.alerters)
+        .await
+        .context("Failed to initialize alerters")?
+    {
+        info!(alerter = alerter.name(), "Alerter initialized");
+        alerters.push(alerter);
+    }
+
+    if alerters.is_empty() {
+        anyhow::bail!(
+            "At least one alerting backend (rabbitmq, pubsub, alerters or a federation \
+             upstream) must be configured"
+        );
+    }
+
+    Ok(alerters)
+}
+
+/// Build a detection SLO definition from configuration
+fn slo_definition(config: &SloConfig) -> Result<SloDefinition> {
+    let objective = match config.objective.as_str() {
+        "latency" => SloObjective::Latency {
+            threshold_ms: config
+                .latency_threshold_ms
+                .with_context(|| format!("SLO {} requires latency_threshold_ms", config.name))?,
+        },
+        "error_rate" => SloObjective::ErrorRate,
+        other => anyhow::bail!("SLO {} has unknown objective: {}", config.name, other),
+    };
+
+    let mut definition = SloDefinition::new(config.name.clone(), objective, config.target)
+        .with_window_secs(config.window_secs);
+
+    if let Some(service) = &config.service {
+        definition = definition.with_service(ServiceId::new(service));
+    }
+    if let Some(model) = &config.model {
+        definition = definition.with_model(ModelId::new(model));
+    }
+    if !config.burn_rate_policies.is_empty() {
+        definition = definition.with_policies(
+            config
+                .burn_rate_policies
+                .iter()
+                .map(|p| BurnRatePolicy {
+                    long_window_secs: p.long_window_secs,
+                    short_window_secs: p.short_window_secs,
+                    threshold: p.threshold,
+                    severity: p.severity,
+                })
+                .collect(),
+        );
+    }
+
+    Ok(definition)
+}
+
+/// Wait for shutdown signal (SIGTERM or CTRL+C)
+async fn wait_for_shutdown() {
+    let ctrl_c = async {
+        signal::ctrl_c()
+            .await
+            .expect("Failed to install CTRL+C handler");
+    };
+
+    #[cfg(unix)]
+    let terminate = async {
+        signal::unix::signal(signal::unix::SignalKind::terminate())
+            .expect("Failed to install SIGTERM handler")
+            .recv()
+            .await;
+    };
+
+    #[cfg(not(unix))]
+    let terminate = std::future::pending::<()>();
+
+    tokio::select! {
+        _ = ctrl_c => {
+            info!("Received CTRL+C");
+        },
+        _ = terminate => {
+            info!("Received SIGTERM");
+        },
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use async_trait::async_trait;
+    use llm_sentinel_core::{
+        audit::MemoryAuditLog,
+        config::{
+            AggregationOnlyConfig, AlertDispatchConfig, AlertRouteConfig, AnomalyExpiryConfig,
+            BaselineWindowConfig, ClockSkewConfig, CostBudgetConfig, CostReportingConfig,
+            DataQualityConfig, ForecastMethod, IdentifierConfig, IngestionQuotaConfig,
+            RecentContextConfig, RecoveryConfig, RemediationActionConfig, RemediationConfig,
+            RemediationRuleConfig, SeverityRuleConfig, SimilarityConfig, SkewPolicy,
+            SuppressionScheduleConfig, TraceLinkConfig,
+        },
+        leader::LeaderElector,
+        types::{AnomalyType, DetectionMethod, Severity},
+    };
+    use llm_sentinel_fixtures::{anomaly, event};
+    use std::{
+        collections::HashMap,
+        sync::atomic::{AtomicUsize, Ordering},
+    };
+
+    // Ingester yielding one batch, then nothing
+    struct OneBatchIngester(Option<Vec<TelemetryEvent>>);
+
+    #[async_trait]
+    impl Ingester for OneBatchIngester {
+        async fn start(&mut self) -> llm_sentinel_core::Result<()> {
+            Ok(())
+        }
+
+        async fn stop(&mut self) -> llm_sentinel_core::Result<()> {
+            Ok(())
+        }
+
+        async fn next_batch(&mut self) -> llm_sentinel_core::Result<Vec<TelemetryEvent>> {
+            match self.0.take() {
+                Some(events) => Ok(events),
+                None => {
+                    tokio::time::sleep(std::time::Duration::from_millis(10)).await;
+                    Ok(Vec::new())
+                }
+            }
+        }
+
+        async fn health_check(&self) -> llm_sentinel_core::Result<()> {
+            Ok(())
+        }
+    }
+
+    // Enricher tagging every event with a team
+    #[derive(Debug)]
+    struct TeamEnricher;
+
+    #[async_trait]
+    impl Enricher for TeamEnricher {
+        async fn enrich(&self, event: &mut TelemetryEvent) -> llm_sentinel_core::Result<()> {
+            event
+                .metadata
+                .insert("team".to_string(), "search".to_string());
+            Ok(())
+        }
+
+        fn name(&self) -> &str {
+            "team"
+        }
+    }
+
+    /// Default detection engine whose baselines form after `samples` events
+    fn warm_engine(samples: usize) -> DetectionEngine {
+        DetectionEngine::new(EngineConfig {
+            baseline_window: WindowSpec::samples(samples),
+            ..Default::default()
+        })
+        .unwrap()
+    }
+
+    fn create_test_event() -> TelemetryEvent {
+        event()
+            .prompt("Hello")
+            .response("Hi")
+            .response_tokens(5)
+            .build()
+    }
+
+    fn create_test_anomaly() -> AnomalyEvent {
+        anomaly().build()
+    }
+
+    #[tokio::test]
+    async fn test_builder_with_injected_components() {
+        let storage = Arc::new(InMemoryStorage::new());
+        let alerter = Arc::new(RecordingAlerter::new("recording"));
+
+        let sentinel = Sentinel::builder(Config::default_test())
+            .with_storage(storage.clone())
+            .with_alerter(alerter.clone())
+            .with_api(false)
+            .build()
+            .await
+            .unwrap();
+        assert_eq!(sentinel.alerter_names(), vec!["recording"]);
+
+        let anomaly = create_test_anomaly();
+        sentinel.handle_anomaly(&anomaly).await;
+        // Duplicate within the dedup window is stored but not sent
+        let mut duplicate = create_test_anomaly();
+        duplicate.timestamp = anomaly.timestamp;
+        sentinel.handle_anomaly(&duplicate).await;
+
+        assert_eq!(alerter.count(), 1);
+        assert_eq!(storage.anomalies().len(), 2);
+        let deliveries = storage.deliveries();
+        assert_eq!(deliveries.len(), 1);
+        assert_eq!(deliveries[0].alerter, "recording");
+    }
+
+    #[tokio::test]
+    async fn test_rotate_webhook_secret() {
+        const SECRET_VAR: &str = "SENTINEL_TEST_ROTATED_WEBHOOK_SECRET";
+        std::env::set_var(SECRET_VAR, "s0");
+        let mut config = Config::default_test();
+        config.alerting.rabbitmq = None;
+        config.alerting.alerters = vec![serde_json::from_value(serde_json::json!({
+            "type": "webhook",
+            "url": "https://example.com/hook",
+            "secret": format!("env:{}", SECRET_VAR),
+        }))
+        .unwrap()];
+
+        let sentinel = Sentinel::builder(config)
+            .with_storage(Arc::new(InMemoryStorage::new()))
+            .with_api(false)
+            .build()
+            .await
+            .unwrap();
+        assert!(CredentialControl::rotate(&sentinel)
+            .await
+            .unwrap()
+            .is_empty());
+
+        std::env::set_var(SECRET_VAR, "s1");
+        assert_eq!(
+            CredentialControl::rotate(&sentinel).await.unwrap(),
+            vec!["alerting.alerters[0]"]
+        );
+        assert!(CredentialControl::rotate(&sentinel)
+            .await
+            .unwrap()
+            .is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_queued_alert_dispatch() {
+        let storage = Arc::new(InMemoryStorage::new());
+        let alerter = Arc::new(RecordingAlerter::new("recording"));
+        let bus = EventBus::default();
+        let mut alerts = bus.alerts().subscribe();
+        let mut config = Config::default_test();
+        config.alerting.batch_timeout_ms = 100;
+        config.alerting.dispatch = Some(AlertDispatchConfig {
+            queue_capacity: 10,
+            priority_queue_capacity: 10,
+        });
+
+        let sentinel = Sentinel::builder(config)
+            .with_storage(storage.clone())
+            .with_alerter(alerter.clone())
+            .with_event_bus(bus)
+            .with_api(false)
+            .build()
+            .await
+            .unwrap();
+
+        let anomaly = create_test_anomaly();
+        sentinel.handle_anomaly(&anomaly).await;
+        // Stored at once, sent by the alerter's task
+        assert_eq!(storage.anomalies().len(), 1);
+        assert!(sentinel.flush_alerts(Duration::from_secs(5)).await);
+
+        assert_eq!(alerter.count(), 1);
+        let deliveries = storage.deliveries();
+        assert_eq!(deliveries.len(), 1);
+        assert_eq!(deliveries[0].alerter, "recording");
+        let dispatched = alerts.recv().await.unwrap();
+        assert_eq!(dispatched.alert.alert_id, anomaly.alert_id);
+        assert_eq!(dispatched.deliveries.len(), 1);
+    }
+
+    #[tokio::test]
+    async fn test_dedup_summary_sent() {
+        let storage = Arc::new(InMemoryStorage::new());
+        let alerter = Arc::new(RecordingAlerter::new("recording"));
+
+        let mut config = Config::default_test();
+        config.alerting.dedup_window_secs = 1;
+        config.alerting.dedup_summary = true;
+        let clock = Clock::manual(chrono::Utc::now());
+        let sentinel = Sentinel::builder(config)
+            .with_storage(storage.clone())
+            .with_alerter(alerter.clone())
+            .with_clock(clock.clone())
+            .with_api(false)
+            .build()
+            .await
+            .unwrap();
+
+        let first = create_test_anomaly();
+        sentinel.handle_anomaly(&first).await;
+        for _ in 0..2 {
+            let mut duplicate = create_test_anomaly();
+            duplicate.timestamp = first.timestamp;
+            sentinel.handle_anomaly(&duplicate).await;
+        }
+        assert_eq!(alerter.count(), 1);
+
+        clock.advance(chrono::Duration::seconds(2));
+        sentinel.flush_dedup_summaries().await;
+
+        let sent = alerter.sent();
+        assert_eq!(sent.len(), 2);
+        assert_eq!(sent[1].context.additional["dedup_occurrences"], "3");
+        assert_eq!(sent[1].related_alerts.len(), 2);
+        assert_eq!(sent[1].timestamp, clock.now());
+        assert_eq!(storage.anomalies().len(), 4);
+
+        // A summary that cannot be written waits in the outbox
+        let next = create_test_anomaly();
+        sentinel.handle_anomaly(&next).await;
+        let mut duplicate = create_test_anomaly();
+        duplicate.timestamp = next.timestamp;
+        sentinel.handle_anomaly(&duplicate).await;
+        assert_eq!(alerter.count(), 3);
+        clock.advance(chrono::Duration::seconds(2));
+        storage.set_writable(false);
+        sentinel.flush_dedup_summaries().await;
+        assert_eq!(alerter.count(), 3);
+        assert_eq!(sentinel.outbox.len(), 1);
+
+        storage.set_writable(true);
+        sentinel.relay_outbox().await;
+        let sent = alerter.sent();
+        assert_eq!(sent.len(), 4);
+        assert_eq!(sent[3].context.additional["dedup_occurrences"], "2");
+        assert!(sentinel.outbox.is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_federated_anomalies_deduplicated_across_regions() {
+        use llm_sentinel_core::federation::REGION_LABEL;
+
+        let storage = Arc::new(InMemoryStorage::new());
+        let alerter = Arc::new(RecordingAlerter::new("recording"));
+        let sentinel = Sentinel::builder(Config::default_test())
+            .with_storage(storage.clone())
+            .with_alerter(alerter.clone())
+            .with_api(false)
+            .build()
+            .await
+            .unwrap();
+
+        let eu = create_test_anomaly();
+        let mut us = create_test_anomaly();
+        us.timestamp = eu.timestamp;
+        // An edge cannot claim another region
+        us.labels
+            .insert(REGION_LABEL.to_string(), "eu-west-1".to_string());
+        assert_eq!(
+            FederationSink::receive(&sentinel, "eu-west-1", vec![eu])
+                .await
+                .unwrap(),
+            1
+        );
+        FederationSink::receive(&sentinel, "us-east-1", vec![us])
+            .await
+            .unwrap();
+
+        // Both are stored for the global view, but alerted once
+        let mut regions: Vec<_> = storage
+            .anomalies()
+            .iter()
+            .map(|a| a.labels[REGION_LABEL].clone())
+            .collect();
+        regions.sort();
+        assert_eq!(regions, vec!["eu-west-1", "us-east-1"]);
+        assert_eq!(alerter.count(), 1);
+    }
+
+    #[tokio::test]
+    async fn test_run_until_with_injected_ingester() {
+        let storage = Arc::new(InMemoryStorage::new());
+        let events = vec![create_test_event(), create_test_event()];
+
+        let sentinel = Sentinel::builder(Config::default_test())
+            .with_storage(storage.clone())
+            .with_ingester(Box::new(OneBatchIngester(Some(events))))
+            .with_alerter(Arc::new(RecordingAlerter::new("recording")))
+            .with_api(false)
+            .build()
+            .await
+            .unwrap();
+
+        sentinel
+            .run_until(tokio::time::sleep(std::time::Duration::from_millis(100)))
+            .await
+            .unwrap();
+
+        assert_eq!(storage.telemetry().len(), 2);
+    }
+
+    #[tokio::test]
+    async fn test_run_until_with_unordered_workers() {
+        let storage = Arc::new(InMemoryStorage::new());
+        let events: Vec<_> = (0..20).map(|_| create_test_event()).collect();
+
+        let mut config = Config::default_test();
+        config.detection.workers = 3;
+        config.detection.queue_capacity = 4;
+        config.detection.ordering = DetectionOrdering::None;
+
+        let sentinel = Sentinel::builder(config)
+            .with_storage(storage.clone())
+            .with_ingester(Box::new(OneBatchIngester(Some(events))))
+            .with_alerter(Arc::new(RecordingAlerter::new("recording")))
+            .with_api(false)
+            .build()
+            .await
+            .unwrap();
+
+        sentinel
+            .run_until(tokio::time::sleep(std::time::Duration::from_millis(200)))
+            .await
+            .unwrap();
+
+        assert_eq!(storage.telemetry().len(), 20);
+    }
+
+    #[tokio::test]
+    async fn test_baselines_bootstrapped_from_storage() {
+        let storage = Arc::new(InMemoryStorage::new());
+        let history: Vec<_> = (1..=20)
+            .map(|i| {
+                let mut event = create_test_event();
+                event.latency_ms = 100.0 + i as f64;
+                event.timestamp = chrono::Utc::now() - chrono::Duration::minutes(i);
+                event
+            })
+            .collect();
+        storage.write_telemetry_batch(&history).await.unwrap();
+
+        let mut config = Config::default_test();
+        config.detection.bootstrap = Some(BaselineBootstrapConfig {
+            lookback_hours: 1,
+            max_events: 1000,
+        });
+
+        let sentinel = Sentinel::builder(config)
+            .with_storage(storage)
+            .with_alerter(Arc::new(RecordingAlerter::new("recording")))
+            .with_detection_engine(warm_engine(20))
+            .with_api(false)
+            .build()
+            .await
+            .unwrap();
+
+        let engine = sentinel.detection_engine.lock().await;
+        let stats = engine.baseline_manager().stats();
+        assert!(stats.valid_baselines > 0);
+    }
+
+    #[tokio::test]
+    async fn test_baselines_bootstrapped_up_to_clock() {
+        // Replaying last week: history is relative to the injected clock
+        let clock = Clock::manual(chrono::Utc::now() - chrono::Duration::days(7));
+        let storage = Arc::new(InMemoryStorage::new());
+        let history: Vec<_> = (1..=20)
+            .map(|i| {
+                event()
+                    .latency_ms(100.0 + i as f64)
+                    .timestamp(clock.now() - chrono::Duration::minutes(i))
+                    .build()
+            })
+            .collect();
+        storage.write_telemetry_batch(&history).await.unwrap();
+
+        let mut config = Config::default_test();
+        config.detection.bootstrap = Some(BaselineBootstrapConfig {
+            lookback_hours: 1,
+            max_events: 1000,
+        });
+
+        let sentinel = Sentinel::builder(config)
+            .with_storage(storage)
+            .with_alerter(Arc::new(RecordingAlerter::new("recording")))
+            .with_detection_engine(warm_engine(20))
+            .with_clock(clock)
+            .with_api(false)
+            .build()
+            .await
+            .unwrap();
+
+        let engine = sentinel.detection_engine.lock().await;
+        let stats = engine.baseline_manager().stats();
+        assert!(stats.valid_baselines > 0);
+    }
+
+    #[tokio::test]
+    async fn test_baselines_keyed_by_model_version() {
+        let mut config = Config::default_test();
+        config.detection.key_by_model_version = true;
+        config.detection.baseline_window = BaselineWindowConfig {
+            samples: 20,
+            max_age_secs: Some(3600),
+        };
+
+        let sentinel = Sentinel::builder(config)
+            .with_storage(Arc::new(InMemoryStorage::new()))
+            .with_alerter(Arc::new(RecordingAlerter::new("recording")))
+            .with_api(false)
+            .build()
+            .await
+            .unwrap();
+
+        // Baselines form once the configured window is full
+        for _ in 0..20 {
+            sentinel
+                .process_event(&event().model("gpt-4-0613").build())
+                .await;
+        }
+
+        let engine = sentinel.detection_engine.lock().await;
+        let keys = engine.baseline_manager().keys();
+        assert!(!keys.is_empty());
+        assert!(keys.iter().all(|k| k.model.as_str() == "gpt-4@0613"));
+    }
+
+    #[tokio::test]
+    async fn test_workers_preserve_order_per_key() {
+        let storage = Arc::new(InMemoryStorage::new());
+        let events: Vec<_> = (0..30)
+            .map(|i| {
+                let mut event = create_test_event();
+                event.service_name = ServiceId::new(format!("service-{}", i % 2));
+                event.model = ModelId::new(format!("model-{}", i % 3));
+                event
+            })
+            .collect();
+        let expected = events.clone();
+
+        let mut config = Config::default_test();
+        config.detection.workers = 4;
+
+        let sentinel = Sentinel::builder(config)
+            .with_storage(storage.clone())
+            .with_ingester(Box::new(OneBatchIngester(Some(events))))
+            .with_alerter(Arc::new(RecordingAlerter::new("recording")))
+            .with_api(false)
+            .build()
+            .await
+            .unwrap();
+        assert_eq!(
+            sentinel.config().detection.ordering,
+            DetectionOrdering::PerServiceModel
+        );
+
+        sentinel
+            .run_until(tokio::time::sleep(std::time::Duration::from_millis(200)))
+            .await
+            .unwrap();
+
+        let stored = storage.telemetry();
+        assert_eq!(stored.len(), 30);
+        let ids_for = |events: &[TelemetryEvent], service: &str, model: &str| {
+            events
+                .iter()
+                .filter(|e| e.service_name.as_str() == service && e.model.as_str() == model)
+                .map(|e| e.event_id)
+                .collect::<Vec<_>>()
+        };
+        for service in ["service-0", "service-1"] {
+            for model in ["model-0", "model-1", "model-2"] {
+                assert_eq!(
+                    ids_for(&stored, service, model),
+                    ids_for(&expected, service, model)
+                );
+            }
+        }
+    }
+
+    #[tokio::test]
+    async fn test_enrichers_run_before_storage() {
+        let storage = Arc::new(InMemoryStorage::new());
+
+        let sentinel = Sentinel::builder(Config::default_test())
+            .with_storage(storage.clone())
+            .with_enricher(Arc::new(TeamEnricher))
+            .with_alerter(Arc::new(RecordingAlerter::new("recording")))
+            .with_api(false)
+            .build()
+            .await
+            .unwrap();
+
+        sentinel.process_batch(vec![create_test_event()]).await;
+
+        let telemetry = storage.telemetry();
+        assert_eq!(telemetry.len(), 1);
+        assert_eq!(telemetry[0].metadata["team"], "search");
+    }
+
+    #[tokio::test]
+    async fn test_identifiers_normalized_at_ingestion() {
+        let storage = Arc::new(InMemoryStorage::new());
+        let mut config = Config::default_test();
+        config.ingestion.identifiers = Some(IdentifierConfig {
+            service_aliases: HashMap::new(),
+            model_aliases: HashMap::from([(
+                "gpt-4-turbo-preview".to_string(),
+                "gpt-4-turbo".to_string(),
+            )]),
+        });
+
+        let sentinel = Sentinel::builder(config)
+            .with_storage(storage.clone())
+            .with_alerter(Arc::new(RecordingAlerter::new("recording")))
+            .with_api(false)
+            .build()
+            .await
+            .unwrap();
+
+        let mut aliased = create_test_event();
+        aliased.service_name = ServiceId::new(" Checkout ");
+        aliased.model = ModelId::new("GPT-4-Turbo-Preview");
+        let mut invalid = create_test_event();
+        invalid.service_name = ServiceId::new("check out");
+        sentinel.process_batch(vec![aliased, invalid]).await;
+
+        let telemetry = storage.telemetry();
+        assert_eq!(telemetry.len(), 1);
+        assert_eq!(telemetry[0].service_name.as_str(), "checkout");
+        assert_eq!(telemetry[0].model.as_str(), "gpt-4-turbo");
+    }
+
+    #[tokio::test]
+    async fn test_ingestion_quota_drops_flood() {
+        let storage = Arc::new(InMemoryStorage::new());
+        let mut config = Config::default_test();
+        config.ingestion.quotas = Some(IngestionQuotaConfig {
+            default_events_per_sec: None,
+            services: HashMap::from([("noisy".to_string(), 3.0)]),
+            burst_secs: 1.0,
+            over_quota_sample_rate: 0.0,
+        });
+
+        let sentinel = Sentinel::builder(config)
+            .with_storage(storage.clone())
+            .with_alerter(Arc::new(RecordingAlerter::new("recording")))
+            .with_api(false)
+            .build()
+            .await
+            .unwrap();
+
+        let mut events = Vec::new();
+        for _ in 0..10 {
+            let mut noisy = create_test_event();
+            noisy.service_name = ServiceId::new("noisy");
+            events.push(noisy);
+            events.push(create_test_event());
+        }
+        sentinel.process_batch(events).await;
+
+        let telemetry = storage.telemetry();
+        let count = |service: &str| {
+            telemetry
+                .iter()
+                .filter(|e| e.service_name.as_str() == service)
+                .count()
+        };
+        assert_eq!(count("noisy"), 3);
+        assert_eq!(count("test"), 10);
+    }
+
+    #[tokio::test]
+    async fn test_data_quality_alerts_and_excludes() {
+        let storage = Arc::new(InMemoryStorage::new());
+        let alerter = Arc::new(RecordingAlerter::new("recording"));
+        let mut config = Config::default_test();
+        config.ingestion.quality = Some(DataQualityConfig {
+            max_clock_skew_secs: 300,
+            window_events: 10,
+            min_events: 5,
+            min_score: 0.9,
+            exclude_below: Some(0.5),
+            severity: Severity::Medium,
+        });
+
+        let sentinel = Sentinel::builder(config)
+            .with_storage(storage.clone())
+            .with_alerter(alerter.clone())
+            .with_api(false)
+            .build()
+            .await
+            .unwrap();
+
+        let mut events: Vec<_> = (0..5).map(|_| create_test_event()).collect();
+        for _ in 0..5 {
+            let mut garbage = create_test_event();
+            garbage.prompt_mut().tokens = 0;
+            garbage.latency_ms = 0.0;
+            garbage.cost_usd = 0.0;
+            events.push(garbage);
+        }
+        sentinel.process_batch(events).await;
+
+        // Low-quality events are still stored
+        assert_eq!(storage.telemetry().len(), 10);
+        let sent = alerter.sent();
+        assert_eq!(sent.len(), 1);
+        assert_eq!(
+            sent[0].detection_method,
+            DetectionMethod::Custom("data_quality".to_string())
+        );
+        assert_eq!(sent[0].service_name, ServiceId::new("test"));
+    }
+
+    #[tokio::test]
+    async fn test_late_events_backfilled() {
+        let storage = Arc::new(InMemoryStorage::new());
+        let bus = EventBus::default();
+        let mut telemetry = bus.telemetry().subscribe();
+        let mut config = Config::default_test();
+        config.ingestion.clock_skew = Some(ClockSkewConfig {
+            max_future_secs: 60,
+            max_late_secs: 300,
+            future_policy: SkewPolicy::Clamp,
+            late_policy: SkewPolicy::Backfill,
+        });
+        let clock = Clock::manual(chrono::Utc::now());
+
+        let sentinel = Sentinel::builder(config)
+            .with_storage(storage.clone())
+            .with_alerter(Arc::new(RecordingAlerter::new("recording")))
+            .with_event_bus(bus)
+            .with_clock(clock.clone())
+            .with_api(false)
+            .build()
+            .await
+            .unwrap();
+
+        let mut future = create_test_event();
+        future.timestamp = clock.now() + chrono::Duration::hours(1);
+        let mut late = create_test_event();
+        late.timestamp = clock.now() - chrono::Duration::hours(2);
+        let on_time = create_test_event();
+        sentinel
+            .process_batch(vec![future.clone(), late.clone(), on_time.clone()])
+            .await;
+
+        // Every event is stored, the late one with its original timestamp
+        let stored = storage.telemetry();
+        assert_eq!(stored.len(), 3);
+        let stored_late = stored.iter().find(|e| e.event_id == late.event_id).unwrap();
+        assert_eq!(stored_late.timestamp, late.timestamp);
+        assert_eq!(stored_late.metadata["clock_skew"], "late");
+
+        // Only live events reach detection, the future one clamped
+        let first = telemetry.recv().await.unwrap();
+        assert_eq!(first.event_id, future.event_id);
+        assert_eq!(first.timestamp, clock.now());
+        assert_eq!(telemetry.recv().await.unwrap().event_id, on_time.event_id);
+    }
+
+    #[tokio::test]
+    async fn test_correlation_id_propagated() {
+        use llm_sentinel_core::correlation;
+
+        let storage = Arc::new(InMemoryStorage::new());
+        let alerter = Arc::new(RecordingAlerter::new("recording"));
+        let sentinel = Sentinel::builder(Config::default_test())
+            .with_storage(storage.clone())
+            .with_alerter(alerter.clone())
+            .with_api(false)
+            .build()
+            .await
+            .unwrap();
+
+        let mut provided = create_test_event();
+        provided
+            .metadata
+            .insert(correlation::METADATA_KEY.to_string(), "req-42".to_string());
+        sentinel
+            .process_batch(vec![provided, create_test_event()])
+            .await;
+        let telemetry = storage.telemetry();
+        assert_eq!(correlation::of(&telemetry[0]), Some("req-42"));
+        assert!(correlation::of(&telemetry[1]).is_some_and(correlation::is_valid));
+
+        // Anomalies take the ID of the event or request being processed
+        correlation::scope(
+            "req-42".to_string(),
+            sentinel.handle_anomaly(&create_test_anomaly()),
+        )
+        .await;
+        let mut background = create_test_anomaly();
+        background.service_name = ServiceId::new("batch-summarizer");
+        background.refresh_signature_hash();
+        sentinel.handle_anomaly(&background).await;
+        let sent = alerter.sent();
+        assert_eq!(sent[0].correlation_id.as_deref(), Some("req-42"));
+        assert_eq!(
+            storage.anomalies()[0].correlation_id.as_deref(),
+            Some("req-42")
+        );
+        assert!(sent[1].correlation_id.is_some());
+    }
+
+    #[tokio::test]
+    async fn test_telemetry_silence_alerted() {
+        use llm_sentinel_core::config::SilenceConfig;
+
+        let mut config = Config::default_test();
+        config.detection.silence = Some(SilenceConfig {
+            silence_secs: 300,
+            min_events: 3,
+            forget_secs: 86400,
+            check_interval_secs: 30,
+            severity: Severity::Critical,
+        });
+        let clock = Clock::manual(chrono::Utc::now());
+        let alerter = Arc::new(RecordingAlerter::new("recording"));
+        let sentinel = Sentinel::builder(config)
+            .with_storage(Arc::new(InMemoryStorage::new()))
+            .with_alerter(alerter.clone())
+            .with_clock(clock.clone())
+            .with_api(false)
+            .build()
+            .await
+            .unwrap();
+
+        sentinel
+            .process_batch((0..3).map(|_| create_test_event()).collect())
+            .await;
+        clock.advance(chrono::Duration::seconds(60));
+        sentinel.check_silence().await;
+        assert_eq!(alerter.count(), 0);
+
+        clock.advance(chrono::Duration::seconds(300));
+        sentinel.check_silence().await;
+        let sent = alerter.sent();
+        assert_eq!(sent.len(), 1);
+        assert_eq!(sent[0].anomaly_type.to_string(), "telemetry_silence");
+        assert_eq!(sent[0].severity, Severity::Critical);
+    }
+
+    #[tokio::test]
+    async fn test_trace_evaluated_as_chain() {
+        use llm_sentinel_core::config::TraceConfig;
+
+        let mut config = Config::default_test();
+        config.detection.traces = Some(TraceConfig {
+            idle_secs: 30,
+            max_duration_secs: 600,
+            max_open_traces: 100,
+            threshold_sigma: 3.0,
+            min_samples: 10,
+            window_size: 10,
+            check_interval_secs: 5,
+            suppress_span_alerts: true,
+        });
+        let clock = Clock::manual(chrono::Utc::now());
+        let alerter = Arc::new(RecordingAlerter::new("recording"));
+        let sentinel = Sentinel::builder(config)
+            .with_storage(Arc::new(InMemoryStorage::new()))
+            .with_alerter(alerter.clone())
+            .with_clock(clock.clone())
+            .with_api(false)
+            .build()
+            .await
+            .unwrap();
+
+        let chain = |trace_id: String, steps: usize| {
+            (0..steps)
+                .map(|_| {
+                    let mut event = create_test_event();
+                    event.trace_id = Some(trace_id.clone());
+                    event
+                })
+                .collect::<Vec<_>>()
+        };
+        for i in 0..10 {
+            sentinel.process_batch(chain(format!("t{}", i), 3)).await;
+            clock.advance(chrono::Duration::seconds(60));
+            sentinel.check_traces().await;
+        }
+        assert_eq!(alerter.count(), 0);
+
+        sentinel.process_batch(chain("loop".to_string(), 8)).await;
+        sentinel.check_traces().await;
+        assert_eq!(alerter.count(), 0);
+        clock.advance(chrono::Duration::seconds(60));
+        sentinel.check_traces().await;
+        let sent = alerter.sent();
+        assert_eq!(sent.len(), 1);
+        assert_eq!(sent[0].context.trace_id.as_deref(), Some("loop"));
+    }
+
+    #[tokio::test]
+    async fn test_script_routing() {
+        let dir = std::env::temp_dir().join(format!("sentinel-routing-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        std::fs::write(
+            dir.join("route.rhai"),
+            r#"fn route(anomaly, alerters) { alerters.filter(|name| name != "pager") }"#,
+        )
+        .unwrap();
+
+        let pager = Arc::new(RecordingAlerter::new("pager"));
+        let chat = Arc::new(RecordingAlerter::new("chat"));
+        let sentinel = Sentinel::builder(Config::default_test())
+            .with_storage(Arc::new(InMemoryStorage::new()))
+            .with_alerter(pager.clone())
+            .with_alerter(chat.clone())
+            .with_scripts(Arc::new(ScriptHooks::new(&dir, 10_000).unwrap()))
+            .with_api(false)
+            .build()
+            .await
+            .unwrap();
+
+        sentinel.handle_anomaly(&create_test_anomaly()).await;
+        assert_eq!(pager.count(), 0);
+        assert_eq!(chat.count(), 1);
+
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_label_routing() {
+        let mut config = Config::default_test();
+        config.alerting.routes = vec![AlertRouteConfig {
+            environment: None,
+            labels: [("team".to_string(), "search".to_string())].into(),
+            alerters: vec!["chat".to_string()],
+        }];
+
+        let pager = Arc::new(RecordingAlerter::new("pager"));
+        let chat = Arc::new(RecordingAlerter::new("chat"));
+        let sentinel = Sentinel::builder(config)
+            .with_storage(Arc::new(InMemoryStorage::new()))
+            .with_alerter(pager.clone())
+            .with_alerter(chat.clone())
+            .with_api(false)
+            .build()
+            .await
+            .unwrap();
+
+        let mut anomaly = create_test_anomaly();
+        anomaly
+            .labels
+            .insert("team".to_string(), "search".to_string());
+        sentinel.handle_anomaly(&anomaly).await;
+        assert_eq!(pager.count(), 0);
+        assert_eq!(chat.count(), 1);
+    }
+
+    #[tokio::test]
+    async fn test_severity_rule_adjusts_sent_alert() {
+        use llm_sentinel_alerting::routing::SEVERITY_RULE_LABEL;
+
+        let mut config = Config::default_test();
+        config.alerting.severity_rules = vec![SeverityRuleConfig {
+            name: "launch-week".to_string(),
+            services: vec!["test".to_string()],
+            anomaly_types: Vec::new(),
+            severities: Vec::new(),
+            business_hours: None,
+            outside_business_hours: false,
+            start: None,
+            end: None,
+            adjust: 1,
+        }];
+
+        let storage = Arc::new(InMemoryStorage::new());
+        let alerter = Arc::new(RecordingAlerter::new("recording"));
+        let sentinel = Sentinel::builder(config)
+            .with_storage(storage.clone())
+            .with_alerter(alerter.clone())
+            .with_api(false)
+            .build()
+            .await
+            .unwrap();
+
+        sentinel.handle_anomaly(&create_test_anomaly()).await;
+        let sent = alerter.sent();
+        assert_eq!(sent[0].severity, Severity::Critical);
+        assert_eq!(sent[0].labels[SEVERITY_RULE_LABEL], "launch-week");
+        // Stored as detected
+        assert_eq!(storage.anomalies()[0].severity, Severity::High);
+    }
+
+    #[tokio::test]
+    async fn test_alert_held_until_delivery_window() {
+        use llm_sentinel_core::config::{
+            BusinessHoursConfig, DeliveryScheduleConfig, DeliverySchedulesConfig,
+        };
+
+        let dir = std::env::temp_dir().join(format!("sentinel-held-{}", Uuid::new_v4()));
+        let queue_path = dir.join("held.json");
+        // A window opening in an hour, so now is outside it
+        let clock = Clock::manual(chrono::Utc::now());
+        let now = clock.now();
+        let mut config = Config::default_test();
+        config.alerting.delivery_schedules = Some(DeliverySchedulesConfig {
+            queue_path: queue_path.to_string_lossy().into_owned(),
+            release_interval_secs: 60,
+            schedules: vec![DeliveryScheduleConfig {
+                name: "daytime".to_string(),
+                alerters: vec!["chat".to_string()],
+                severities: Vec::new(),
+                window: BusinessHoursConfig {
+                    days: (0..7).collect(),
+                    start: (now + chrono::Duration::hours(1))
+                        .format("%H:%M")
+                        .to_string(),
+                    end: (now + chrono::Duration::hours(2))
+                        .format("%H:%M")
+                        .to_string(),
+                    utc_offset_minutes: 0,
+                },
+            }],
+        });
+        let build = |config: Config, chat: Arc<RecordingAlerter>, pager: Arc<RecordingAlerter>| {
+            Sentinel::builder(config)
+                .with_storage(Arc::new(InMemoryStorage::new()))
+                .with_alerter(chat)
+                .with_alerter(pager)
+                .with_clock(clock.clone())
+                .with_api(false)
+                .build()
+        };
+
+        let chat = Arc::new(RecordingAlerter::new("chat"));
+        let pager = Arc::new(RecordingAlerter::new("pager"));
+        let sentinel = build(config.clone(), chat.clone(), pager.clone())
+            .await
+            .unwrap();
+        sentinel.handle_anomaly(&create_test_anomaly()).await;
+        assert_eq!(pager.count(), 1);
+        assert_eq!(chat.count(), 0);
+        sentinel.release_held_alerts().await;
+        assert_eq!(chat.count(), 0);
+        drop(sentinel);
+
+        // The window opens while the instance is down
+        clock.advance(chrono::Duration::hours(1));
+
+        let chat = Arc::new(RecordingAlerter::new("chat"));
+        let sentinel = build(config, chat.clone(), pager).await.unwrap();
+        sentinel.release_held_alerts().await;
+        let sent = chat.sent();
+        assert_eq!(sent.len(), 1);
+        assert_eq!(sent[0].labels[DELIVERY_SCHEDULE_LABEL], "daytime");
+        sentinel.release_held_alerts().await;
+        assert_eq!(chat.count(), 1);
+
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_expected_anomaly_not_alerted() {
+        use llm_sentinel_core::suppression::expected_by;
+
+        let mut config = Config::default_test();
+        config.alerting.suppression_schedules = vec![SuppressionScheduleConfig {
+            name: "nightly-batch".to_string(),
+            cron: "* * * * *".to_string(),
+            duration_mins: 1,
+            services: vec!["batch-summarizer".to_string()],
+            anomaly_types: Vec::new(),
+        }];
+
+        let storage = Arc::new(InMemoryStorage::new());
+        let alerter = Arc::new(RecordingAlerter::new("recording"));
+        let sentinel = Sentinel::builder(config)
+            .with_storage(storage.clone())
+            .with_alerter(alerter.clone())
+            .with_api(false)
+            .build()
+            .await
+            .unwrap();
+        let mut anomalies = sentinel.event_bus().anomalies().subscribe();
+
+        let mut batch = create_test_anomaly();
+        batch.service_name = ServiceId::new("batch-summarizer");
+        sentinel.handle_anomaly(&batch).await;
+        assert_eq!(alerter.count(), 0);
+        let stored = storage.anomalies();
+        assert_eq!(stored.len(), 1);
+        assert_eq!(expected_by(&stored[0]), Some("nightly-batch"));
+        assert_eq!(
+            expected_by(&anomalies.recv().await.unwrap()),
+            Some("nightly-batch")
+        );
+
+        // Other services still alert
+        sentinel.handle_anomaly(&create_test_anomaly()).await;
+        assert_eq!(alerter.count(), 1);
+        assert!(expected_by(&storage.anomalies()[1]).is_none());
+    }
+
+    #[tokio::test]
+    async fn test_alert_deferred_until_anomaly_written() {
+        let storage = Arc::new(InMemoryStorage::new());
+        let alerter = Arc::new(RecordingAlerter::new("recording"));
+        let sentinel = Sentinel::builder(Config::default_test())
+            .with_storage(storage.clone())
+            .with_alerter(alerter.clone())
+            .with_api(false)
+            .build()
+            .await
+            .unwrap();
+
+        let mut anomaly = create_test_anomaly();
+        anomaly.severity = Severity::Medium;
+        storage.set_writable(false);
+        sentinel.handle_anomaly(&anomaly).await;
+        // Not alerted while the anomaly cannot be found
+        assert_eq!(alerter.count(), 0);
+        assert_eq!(sentinel.outbox.len(), 1);
+
+        // Still unwritten: kept for the next retry
+        sentinel.relay_outbox().await;
+        assert_eq!(alerter.count(), 0);
+        assert_eq!(sentinel.outbox.len(), 1);
+
+        storage.set_writable(true);
+        sentinel.relay_outbox().await;
+        assert_eq!(alerter.count(), 1);
+        assert!(sentinel.outbox.is_empty());
+        assert_eq!(storage.anomalies()[0].alert_id, anomaly.alert_id);
+    }
+
+    #[tokio::test]
+    async fn test_simulated_anomaly_routed_and_deduplicated() {
+        use llm_sentinel_core::simulation::{is_test, TestAnomalyRequest};
+
+        let mut config = Config::default_test();
+        config.alerting.routes = vec![AlertRouteConfig {
+            environment: None,
+            labels: [("team".to_string(), "search".to_string())].into(),
+            alerters: vec!["chat".to_string()],
+        }];
+
+        let storage = Arc::new(InMemoryStorage::new());
+        let pager = Arc::new(RecordingAlerter::new("pager"));
+        let chat = Arc::new(RecordingAlerter::new("chat"));
+        let sentinel = Sentinel::builder(config)
+            .with_storage(storage.clone())
+            .with_alerter(pager.clone())
+            .with_alerter(chat.clone())
+            .with_api(false)
+            .build()
+            .await
+            .unwrap();
+
+        let request = TestAnomalyRequest {
+            severity: Severity::High,
+            anomaly_type: AnomalyType::LatencySpike,
+            service: ServiceId::new("search-api"),
+            model: None,
+            environment: None,
+            labels: [("team".to_string(), "search".to_string())].into(),
+        };
+        let report = AnomalySimulator::simulate(&sentinel, request.synthesize())
+            .await
+            .unwrap();
+        assert!(!report.deduplicated);
+        assert_eq!(report.routed, vec!["chat"]);
+        assert_eq!(pager.count(), 0);
+        assert_eq!(chat.count(), 1);
+
+        let stored = storage.anomalies();
+        assert_eq!(stored.len(), 1);
+        assert_eq!(stored[0].alert_id, report.alert_id);
+        assert!(is_test(&stored[0]));
+
+        // A second test of the same anomaly falls in the open window
+        let report = AnomalySimulator::simulate(&sentinel, request.synthesize())
+            .await
+            .unwrap();
+        assert!(report.deduplicated);
+        assert!(report.routed.is_empty());
+        assert_eq!(chat.count(), 1);
+    }
+
+    #[tokio::test]
+    async fn test_alert_redelivered_to_one_alerter() {
+        use llm_sentinel_core::simulation::TestAnomalyRequest;
+        use llm_sentinel_storage::delivery::DeliveryQuery;
+
+        let storage = Arc::new(InMemoryStorage::new());
+        let pager = Arc::new(RecordingAlerter::new("pager"));
+        let chat = Arc::new(RecordingAlerter::new("chat"));
+        let sentinel = Sentinel::builder(Config::default_test())
+            .with_storage(storage.clone())
+            .with_alerter(pager.clone())
+            .with_alerter(chat.clone())
+            .with_api(false)
+            .build()
+            .await
+            .unwrap();
+
+        let request = TestAnomalyRequest {
+            severity: Severity::High,
+            anomaly_type: AnomalyType::LatencySpike,
+            service: ServiceId::new("search-api"),
+            model: None,
+            environment: None,
+            labels: Default::default(),
+        };
+        let report = AnomalySimulator::simulate(&sentinel, request.synthesize())
+            .await
+            .unwrap();
+
+        // Sent again despite the open deduplication window
+        let delivery = AlertRedelivery::redeliver(&sentinel, report.alert_id, "pager")
+            .await
+            .unwrap();
+        assert_eq!(delivery.status, AlertStatus::Delivered);
+        assert_eq!((pager.count(), chat.count()), (2, 1));
+
+        let err = AlertRedelivery::redeliver(&sentinel, report.alert_id, "sms")
+            .await
+            .unwrap_err();
+        assert!(matches!(err, llm_sentinel_core::Error::NotFound(_)));
+        let err = AlertRedelivery::redeliver(&sentinel, Uuid::new_v4(), "pager")
+            .await
+            .unwrap_err();
+        assert!(matches!(err, llm_sentinel_core::Error::NotFound(_)));
+
+        let query =
+            DeliveryQuery::new(TimeRange::last_hours(1)).with_alert_id(report.alert_id.to_string());
+        let deliveries = storage.query_alert_deliveries(query).await.unwrap();
+        assert_eq!(deliveries.len(), 3);
+        let to_pager = deliveries.iter().filter(|d| d.alerter == "pager").count();
+        assert_eq!(to_pager, 2);
+    }
+
+    #[tokio::test]
+    async fn test_telemetry_indexed_for_search() {
+        let mut config = Config::default_test();
+        config.storage.search = Some(SearchConfig {
+            path: None,
+            writer_memory_mb: 15,
+            commit_interval_secs: 3600,
+        });
+        let sentinel = Sentinel::builder(config)
+            .with_storage(Arc::new(InMemoryStorage::new()))
+            .with_alerter(Arc::new(RecordingAlerter::new("recording")))
+            .with_api(false)
+            .build()
+            .await
+            .unwrap();
+
+        let mut event = create_test_event();
+        event.prompt_mut().text = "Why was my refund declined?".to_string();
+        sentinel
+            .process_batch(vec![event.clone(), create_test_event()])
+            .await;
+
+        let search = sentinel.search.as_ref().unwrap();
+        search.commit().unwrap();
+        let hits = search
+            .search(&SearchQuery::new("refund", TimeRange::last_hours(1)))
+            .unwrap();
+        assert_eq!(hits.len(), 1);
+        assert_eq!(hits[0].event_id, event.event_id);
+    }
+
+    #[tokio::test]
+    async fn test_embeddings_indexed_for_similarity() {
+        let mut config = Config::default_test();
+        config.storage.similarity = Some(SimilarityConfig { capacity: 100 });
+        let sentinel = Sentinel::builder(config)
+            .with_storage(Arc::new(InMemoryStorage::new()))
+            .with_alerter(Arc::new(RecordingAlerter::new("recording")))
+            .with_api(false)
+            .build()
+            .await
+            .unwrap();
+
+        let mut embedded = create_test_event();
+        embedded.prompt_mut().embedding = Some(vec![0.6, 0.8]);
+        sentinel
+            .process_batch(vec![embedded.clone(), create_test_event()])
+            .await;
+
+        let index = sentinel.similarity.as_ref().unwrap();
+        let hits = index
+            .search(&SimilarityQuery::new(
+                vec![3.0, 4.0],
+                EmbeddingSource::Prompt,
+            ))
+            .unwrap();
+        assert_eq!(hits.len(), 1);
+        assert_eq!(hits[0].event_id, embedded.event_id);
+        assert!((hits[0].score - 1.0).abs() < 1e-6);
+    }
+
+    #[tokio::test]
+    async fn test_recent_context_attached() {
+        let mut config = Config::default_test();
+        config.alerting.recent_context = Some(RecentContextConfig {
+            sample_size: 10,
+            lookback_secs: 3600,
+            max_trace_ids: 3,
+        });
+        let storage = Arc::new(InMemoryStorage::new());
+        let alerter = Arc::new(RecordingAlerter::new("recording"));
+        let mut event = create_test_event();
+        event.trace_id = Some("abc123".to_string());
+        storage.write_telemetry(&event).await.unwrap();
+
+        let sentinel = Sentinel::builder(config)
+            .with_storage(storage.clone())
+            .with_alerter(alerter.clone())
+            .with_api(false)
+            .build()
+            .await
+            .unwrap();
+        sentinel.handle_anomaly(&create_test_anomaly()).await;
+
+        let sent = alerter.sent();
+        assert_eq!(sent[0].context.additional["recent_trace_ids"], "abc123");
+        assert_eq!(
+            storage.anomalies()[0].context.additional["recent_sample_count"],
+            "1"
+        );
+    }
+
+    #[tokio::test]
+    async fn test_recovered_anomaly_auto_resolved() {
+        let mut config = Config::default_test();
+        config.detection.recovery = Some(RecoveryConfig {
+            recovery_secs: 60,
+            min_samples: 2,
+            notify: true,
+        });
+        let storage = Arc::new(InMemoryStorage::new());
+        let alerter = Arc::new(RecordingAlerter::new("recording"));
+        let sentinel = Sentinel::builder(config)
+            .with_storage(storage.clone())
+            .with_alerter(alerter.clone())
+            .with_api(false)
+            .build()
+            .await
+            .unwrap();
+
+        let anomaly = create_test_anomaly();
+        sentinel.handle_anomaly(&anomaly).await;
+
+        let mut event = create_test_event();
+        sentinel.process_event(&event).await;
+        assert_eq!(storage.anomalies()[0].state, AnomalyState::Open);
+        event.timestamp += chrono::Duration::seconds(60);
+        sentinel.process_event(&event).await;
+
+        assert_eq!(storage.anomalies()[0].state, AnomalyState::Resolved);
+        let changes = storage.state_changes();
+        assert_eq!(changes.len(), 1);
+        assert_eq!(changes[0].actor.as_deref(), Some(AUTO_RESOLVER));
+        let sent = alerter.sent();
+        assert_eq!(sent.len(), 2);
+        assert_eq!(sent[1].alert_id, anomaly.alert_id);
+        assert_eq!(sent[1].state, AnomalyState::Resolved);
+    }
+
+    #[tokio::test]
+    async fn test_stale_anomalies_expired() {
+        let mut config = Config::default_test();
+        config.alerting.expiry = Some(AnomalyExpiryConfig {
+            ttl_secs: [(Severity::High, 3600)].into(),
+            check_interval_secs: 300,
+            lookback_hours: 24,
+        });
+        let storage = Arc::new(InMemoryStorage::new());
+        let alerter = Arc::new(RecordingAlerter::new("recording"));
+        let sentinel = Sentinel::builder(config)
+            .with_storage(storage.clone())
+            .with_alerter(alerter.clone())
+            .with_api(false)
+            .build()
+            .await
+            .unwrap();
+
+        let now = chrono::Utc::now();
+        let mut stale = create_test_anomaly();
+        stale.timestamp = now - chrono::Duration::hours(2);
+        let mut recent = create_test_anomaly();
+        recent.timestamp = now - chrono::Duration::minutes(10);
+        // No TTL configured for this severity
+        let mut untracked = create_test_anomaly();
+        untracked.severity = Severity::Low;
+        untracked.timestamp = stale.timestamp;
+        for anomaly in [&stale, &recent, &untracked] {
+            storage.write_anomaly(anomaly).await.unwrap();
+        }
+
+        sentinel.expire_stale_anomalies(now).await;
+
+        let changes = storage.state_changes();
+        assert_eq!(changes.len(), 1);
+        assert_eq!(changes[0].alert_id, stale.alert_id);
+        assert_eq!(changes[0].to, AnomalyState::Expired);
+        assert_eq!(changes[0].actor.as_deref(), Some(EXPIRER));
+        assert!(alerter.sent().is_empty());
+
+        // Expired anomalies are left out of default queries
+        let range = TimeRange::new(now - chrono::Duration::hours(24), now);
+        let listed = storage
+            .query_anomalies(AnomalyQuery::new(range.clone()))
+            .await
+            .unwrap();
+        assert_eq!(listed.len(), 2);
+        assert!(listed.iter().all(|a| a.alert_id != stale.alert_id));
+        let all = storage
+            .query_anomalies(AnomalyQuery::new(range).with_expired())
+            .await
+            .unwrap();
+        assert_eq!(all.len(), 3);
+    }
+
+    #[tokio::test]
+    async fn test_projected_budget_overrun_alerts() {
+        let mut config = Config::default_test();
+        config.storage.cost_reporting = Some(CostReportingConfig {
+            bucket_secs: 3600,
+            flush_interval_secs: 60,
+            user_key: "user_id".to_string(),
+            tag_keys: Vec::new(),
+            budgets: Some(CostBudgetConfig {
+                monthly_usd: [
+                    ("test-service".to_string(), 100.0),
+                    ("other".to_string(), 10_000.0),
+                ]
+                .into(),
+                method: ForecastMethod::Holt,
+                lookback_days: 7,
+                margin: 0.1,
+                alpha: 0.5,
+                beta: 0.3,
+                check_interval_secs: 3600,
+                severity: Severity::High,
+            }),
+        });
+        let storage = Arc::new(InMemoryStorage::new());
+        let alerter = Arc::new(RecordingAlerter::new("recording"));
+        let sentinel = Sentinel::builder(config)
+            .with_storage(storage.clone())
+            .with_alerter(alerter.clone())
+            .with_api(false)
+            .build()
+            .await
+            .unwrap();
+
+        // 10 USD a day projects both services at about 255
+        let now = chrono::TimeZone::with_ymd_and_hms(&chrono::Utc, 2024, 4, 10, 12, 0, 0).unwrap();
+        let rollups: Vec<CostRollup> = ["test-service", "other"]
+            .into_iter()
+            .flat_map(|service| {
+                (1..=5).map(move |days_ago| CostRollup {
+                    bucket_start: now - chrono::Duration::days(days_ago),
+                    last_event: now - chrono::Duration::days(days_ago),
+                    service: ServiceId::new(service),
+                    model: ModelId::new("gpt-4"),
+                    user: None,
+                    tags: Default::default(),
+                    cost_usd: 10.0,
+                    requests: 100,
+                    prompt_tokens: 0,
+                    completion_tokens: 0,
+                })
+            })
+            .collect();
+        storage.write_cost_rollups(&rollups).await.unwrap();
+
+        sentinel.check_budgets(now).await;
+
+        let sent = alerter.sent();
+        assert_eq!(sent.len(), 1);
+        assert_eq!(sent[0].service_name.as_str(), "test-service");
+        assert_eq!(sent[0].anomaly_type, AnomalyType::CostAnomaly);
+        assert!(sent[0].details.value > 110.0);
+    }
+
+    #[tokio::test]
+    async fn test_redetection_job_writes_results_apart() {
+        let storage = Arc::new(InMemoryStorage::new());
+        let alerter = Arc::new(RecordingAlerter::new("recording"));
+        let sentinel = Sentinel::builder(Config::default_test())
+            .with_storage(storage.clone())
+            .with_alerter(alerter.clone())
+            .with_api(false)
+            .build()
+            .await
+            .unwrap();
+
+        let start = chrono::Utc::now() - chrono::Duration::hours(2);
+        // Enough history to fill the default 1000-sample baseline window
+        let mut history: Vec<TelemetryEvent> = (0..1000)
+            .map(|i| {
+                let mut event = create_test_event();
+                event.latency_ms = 100.0 + (i % 5) as f64;
+                event.timestamp = start + chrono::Duration::seconds(i);
+                event
+            })
+            .collect();
+        let mut spike = create_test_event();
+        spike.latency_ms = 5000.0;
+        spike.timestamp = start + chrono::Duration::seconds(1000);
+        history.push(spike);
+        storage.write_telemetry_batch(&history).await.unwrap();
+
+        let request = RedetectRequest {
+            start,
+            end: chrono::Utc::now(),
+            max_events: 2000,
+            settings: DetectorSettings {
+                detectors: vec!["zscore".to_string()],
+                ..Default::default()
+            },
+        };
+        let job = sentinel.submit(request).await.unwrap();
+
+        let mut finished = sentinel.job(job.job_id).await.unwrap();
+        for _ in 0..100 {
+            if finished.status != RedetectStatus::Running {
+                break;
+            }
+            tokio::time::sleep(Duration::from_millis(10)).await;
+            finished = sentinel.job(job.job_id).await.unwrap();
+        }
+        assert_eq!(finished.status, RedetectStatus::Completed);
+        assert_eq!(finished.events_processed, 1001);
+        assert_eq!(finished.anomalies_detected, 1);
+
+        let redetected = storage.redetected(job.job_id);
+        assert_eq!(redetected.len(), 1);
+        assert_eq!(redetected[0].timestamp, history[1000].timestamp);
+        // Live anomalies and alerting are untouched
+        assert!(storage.anomalies().is_empty());
+        assert!(alerter.sent().is_empty());
+
+        assert!(matches!(
+            sentinel.job(Uuid::new_v4()).await,
+            Err(llm_sentinel_core::Error::NotFound(_))
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_pipeline_published_on_bus() {
+        let bus = EventBus::default();
+        let mut telemetry = bus.telemetry().subscribe();
+        let mut anomalies = bus.anomalies().subscribe();
+        let mut alerts = bus.alerts().subscribe();
+        let sentinel = Sentinel::builder(Config::default_test())
+            .with_storage(Arc::new(InMemoryStorage::new()))
+            .with_alerter(Arc::new(RecordingAlerter::new("recording")))
+            .with_event_bus(bus)
+            .with_api(false)
+            .build()
+            .await
+            .unwrap();
+
+        let event = create_test_event();
+        sentinel.process_event(&event).await;
+        assert_eq!(telemetry.recv().await.unwrap().event_id, event.event_id);
+
+        let anomaly = create_test_anomaly();
+        sentinel.handle_anomaly(&anomaly).await;
+        assert_eq!(anomalies.recv().await.unwrap().alert_id, anomaly.alert_id);
+        let dispatched = alerts.recv().await.unwrap();
+        assert_eq!(dispatched.alert.alert_id, anomaly.alert_id);
+        assert_eq!(dispatched.deliveries.len(), 1);
+    }
+
+    #[tokio::test]
+    async fn test_aggregation_only_keeps_no_raw_events() {
+        let mut config = Config::default_test();
+        config.alerting.recent_context = Some(RecentContextConfig {
+            sample_size: 10,
+            lookback_secs: 3600,
+            max_trace_ids: 3,
+        });
+        config.alerting.trace_links = vec![TraceLinkConfig {
+            name: "jaeger".to_string(),
+            url_template: "https://jaeger/trace/{trace_id}".to_string(),
+        }];
+        config.storage.aggregation_only = Some(AggregationOnlyConfig {
+            services: vec!["test".to_string()],
+            selectors: Vec::new(),
+        });
+        let storage = Arc::new(InMemoryStorage::new());
+        let alerter = Arc::new(RecordingAlerter::new("recording"));
+        let bus = EventBus::default();
+        let mut telemetry = bus.telemetry().subscribe();
+        let sentinel = Sentinel::builder(config)
+            .with_storage(storage.clone())
+            .with_alerter(alerter.clone())
+            .with_event_bus(bus)
+            .with_detection_engine(warm_engine(30))
+            .with_api(false)
+            .build()
+            .await
+            .unwrap();
+
+        for i in 0..30 {
+            let mut event = create_test_event();
+            event.latency_ms = 100.0 + (i % 5) as f64;
+            sentinel.process_event(&event).await;
+        }
+        let mut spike = create_test_event();
+        spike.latency_ms = 5000.0;
+        spike.trace_id = Some("abc123".to_string());
+        spike
+            .metadata
+            .insert("user_id".to_string(), "user-42".to_string());
+        sentinel.process_event(&spike).await;
+
+        // Detectors learned from the events, but none was kept
+        assert!(storage.telemetry().is_empty());
+        let sent = alerter.sent();
+        assert!(!sent.is_empty());
+        for anomaly in sent.iter().chain(storage.anomalies().iter()) {
+            assert!(anomaly.context.trace_id.is_none());
+            assert!(anomaly.context.user_id.is_none());
+            assert!(anomaly.trace_links.is_empty());
+            assert!(!anomaly.context.additional.contains_key("recent_trace_ids"));
+        }
+
+        // Other services are unaffected
+        let mut other = create_test_event();
+        other.service_name = ServiceId::new("search");
+        sentinel.process_event(&other).await;
+        assert_eq!(storage.telemetry().len(), 1);
+        // The first event published on the bus
+        assert_eq!(telemetry.recv().await.unwrap().event_id, other.event_id);
+    }
+
+    #[tokio::test]
+    async fn test_aggregation_only_withholds_excluded_events() {
+        let mut config = Config::default_test();
+        config.storage.aggregation_only = Some(AggregationOnlyConfig {
+            services: vec!["test".to_string()],
+            selectors: Vec::new(),
+        });
+        config.ingestion.clock_skew = Some(ClockSkewConfig {
+            max_future_secs: 60,
+            max_late_secs: 300,
+            future_policy: SkewPolicy::Clamp,
+            late_policy: SkewPolicy::Backfill,
+        });
+        config.ingestion.quality = Some(DataQualityConfig {
+            max_clock_skew_secs: 300,
+            window_events: 10,
+            min_events: 5,
+            min_score: 0.9,
+            exclude_below: Some(0.5),
+            severity: Severity::Medium,
+        });
+        let storage = Arc::new(InMemoryStorage::new());
+        let clock = Clock::manual(chrono::Utc::now());
+        let sentinel = Sentinel::builder(config)
+            .with_storage(storage.clone())
+            .with_alerter(Arc::new(RecordingAlerter::new("recording")))
+            .with_clock(clock.clone())
+            .with_api(false)
+            .build()
+            .await
+            .unwrap();
+
+        let mut late = create_test_event();
+        late.timestamp = clock.now() - chrono::Duration::hours(2);
+        let mut garbage = create_test_event();
+        garbage.prompt_mut().tokens = 0;
+        garbage.latency_ms = 0.0;
+        garbage.cost_usd = 0.0;
+        sentinel.process_batch(vec![late, garbage]).await;
+
+        // Backfilled and low-quality events of a covered service are not kept
+        assert!(storage.telemetry().is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_state_changes_resent_and_audited() {
+        let storage = Arc::new(InMemoryStorage::new());
+        let alerter = Arc::new(RecordingAlerter::new("recording"));
+        let audit = Arc::new(MemoryAuditLog::new(10));
+        let sentinel = Arc::new(
+            Sentinel::builder(Config::default_test())
+                .with_storage(storage.clone())
+                .with_alerter(alerter.clone())
+                .with_audit_log(audit.clone())
+                .with_api(false)
+                .build()
+                .await
+                .unwrap(),
+        );
+        sentinel.spawn_bus_subscribers();
+
+        let anomaly = create_test_anomaly();
+        storage.write_anomaly(&anomaly).await.unwrap();
+        let (anomaly, change) = transition_anomaly(
+            storage.as_ref(),
+            anomaly.alert_id,
+            AnomalyState::Acknowledged,
+            Some("alice".to_string()),
+            None,
+        )
+        .await
+        .unwrap();
+        let alert_id = anomaly.alert_id;
+        sentinel
+            .event_bus()
+            .lifecycle()
+            .publish(LifecycleEvent { anomaly, change });
+
+        for _ in 0..100 {
+            if !alerter.sent().is_empty() && audit.entries().len() == 2 {
+                break;
+            }
+            tokio::time::sleep(Duration::from_millis(10)).await;
+        }
+        let sent = alerter.sent();
+        assert_eq!(sent.len(), 1);
+        assert_eq!(sent[0].state, AnomalyState::Acknowledged);
+        let entries = audit.entries();
+        assert_eq!(entries.len(), 2);
+        let change = entries
+            .iter()
+            .find(|entry| entry.action == "anomaly.state")
+            .unwrap();
+        assert_eq!(change.actor, "alice");
+        assert_eq!(change.details["to"], "acknowledged");
+        // The resent alert's delivery is audited too
+        let delivery = entries
+            .iter()
+            .find(|entry| entry.action == "alert.delivery")
+            .unwrap();
+        assert_eq!(delivery.actor, "recording");
+        assert_eq!(delivery.target, alert_id.to_string());
+        assert_eq!(delivery.outcome, AuditOutcome::Success);
+    }
+
+    #[tokio::test]
+    async fn test_incident_alerters_notified_of_incidents() {
+        use llm_sentinel_core::{
+            config::{IncidentConfig, IncidentGroupField},
+            incident::IncidentState,
+        };
+
+        let storage = Arc::new(InMemoryStorage::new());
+        let per_anomaly = Arc::new(RecordingAlerter::new("recording"));
+        let on_call = Arc::new(RecordingAlerter::new("on-call"));
+        let audit = Arc::new(MemoryAuditLog::new(10));
+        let mut config = Config::default_test();
+        config.alerting.incidents = Some(IncidentConfig {
+            group_by: vec![IncidentGroupField::Service],
+            group_window_secs: 1800,
+            notify_alerters: vec!["on-call".to_string()],
+        });
+        let sentinel = Arc::new(
+            Sentinel::builder(config)
+                .with_storage(storage.clone())
+                .with_alerter(per_anomaly.clone())
+                .with_alerter(on_call.clone())
+                .with_audit_log(audit.clone())
+                .with_api(false)
+                .build()
+                .await
+                .unwrap(),
+        );
+        sentinel.spawn_bus_subscribers();
+
+        let mut first = create_test_anomaly();
+        first.severity = Severity::Medium;
+        sentinel.handle_anomaly(&first).await;
+        let mut second = create_test_anomaly();
+        second.anomaly_type = AnomalyType::ErrorRateIncrease;
+        sentinel.handle_anomaly(&second).await;
+
+        let wait_for = |count: usize| {
+            let on_call = on_call.clone();
+            async move {
+                for _ in 0..100 {
+                    if on_call.sent().len() >= count {
+                        break;
+                    }
+                    tokio::time::sleep(Duration::from_millis(10)).await;
+                }
+            }
+        };
+        wait_for(2).await;
+
+        // Both anomalies went to the per-anomaly alerter, while the
+        // incident alerter heard of the opening and the escalation
+        assert_eq!(per_anomaly.sent().len(), 2);
+        let sent = on_call.sent();
+        assert_eq!(sent.len(), 2);
+        let incident_id = sent[0].context.additional["incident_id"].clone();
+        assert_eq!(sent[0].context.additional["incident_update"], "opened");
+        assert_eq!(sent[1].context.additional["incident_update"], "escalated");
+        assert_eq!(sent[1].context.additional["incident_id"], incident_id);
+        assert_eq!(sent[1].severity, Severity::High);
+        assert_eq!(
+            sent[1].related_alerts,
+            vec![first.alert_id, second.alert_id]
+        );
+
+        // Responders' state changes are sent with the latest anomaly
+        let incident = transition_incident(
+            storage.as_ref(),
+            incident_id.parse().unwrap(),
+            IncidentState::Acknowledged,
+            Some("alice".to_string()),
+            None,
+        )
+        .await
+        .unwrap();
+        sentinel.event_bus().incidents().publish(IncidentEvent {
+            incident,
+            update: IncidentUpdate::StateChanged,
+            anomaly: None,
+        });
+        wait_for(3).await;
+
+        let sent = on_call.sent();
+        assert_eq!(sent.len(), 3);
+        assert_eq!(sent[2].state, AnomalyState::Acknowledged);
+        assert_eq!(sent[2].anomaly_type, AnomalyType::ErrorRateIncrease);
+        let change = audit
+            .entries()
+            .into_iter()
+            .find(|entry| entry.action == "incident.state")
+            .unwrap();
+        assert_eq!(change.actor, "alice");
+        assert_eq!(change.details["to"], "acknowledged");
+    }
+
+    // Elector that never wins the lease
+    #[derive(Debug)]
+    struct FollowerElector;
+
+    #[async_trait]
+    impl LeaderElector for FollowerElector {
+        async fn try_acquire(&self) -> llm_sentinel_core::Result<bool> {
+            Ok(false)
+        }
+
+        async fn release(&self) -> llm_sentinel_core::Result<()> {
+            Ok(())
+        }
+
+        fn identity(&self) -> &str {
+            "follower"
+        }
+    }
+
+    #[tokio::test]
+    async fn test_leader_only_tasks_skipped_on_follower() {
+        let leadership = Leadership::campaign(Arc::new(FollowerElector), Duration::from_millis(5));
+        let sentinel = Arc::new(
+            Sentinel::builder(Config::default_test())
+                .with_storage(Arc::new(InMemoryStorage::new()))
+                .with_alerter(Arc::new(RecordingAlerter::new("recording")))
+                .with_leadership(leadership)
+                .with_api(false)
+                .build()
+                .await
+                .unwrap(),
+        );
+
+        let runs = |scope| {
+            let count = Arc::new(AtomicUsize::new(0));
+            let counter = count.clone();
+            sentinel.spawn_background_task("test", Duration::from_millis(5), scope, move |_| {
+                counter.fetch_add(1, Ordering::SeqCst);
+                async {}
+            });
+            count
+        };
+        let every_replica = runs(TaskScope::EveryReplica);
+        let leader_only = runs(TaskScope::LeaderOnly);
+        tokio::time::sleep(Duration::from_millis(50)).await;
+
+        assert!(!sentinel.leadership().is_leader());
+        assert!(every_replica.load(Ordering::SeqCst) > 0);
+        assert_eq!(leader_only.load(Ordering::SeqCst), 0);
+    }
+
+    /// Elector whose lease the test hands over
+    #[derive(Debug, Default)]
+    struct HandoverElector {
+        leading: AtomicBool,
+    }
+
+    #[async_trait]
+    impl LeaderElector for HandoverElector {
+        async fn try_acquire(&self) -> llm_sentinel_core::Result<bool> {
+            Ok(self.leading.load(Ordering::SeqCst))
+        }
+
+        async fn release(&self) -> llm_sentinel_core::Result<()> {
+            Ok(())
+        }
+
+        fn identity(&self) -> &str {
+            "standby"
+        }
+    }
+
+    #[tokio::test]
+    async fn test_standby_warmed_and_promoted() {
+        let mut config = Config::default_test();
+        config.standby = Some(llm_sentinel_core::config::StandbyConfig {
+            redis_url: "redis://localhost:6379".to_string(),
+            stream: "sentinel:state".to_string(),
+            sync_interval_secs: 1,
+            max_len: 10,
+        });
+        let stream = Arc::new(MemoryStateStream::new());
+
+        // The primary learns baselines, opens a deduplication window and
+        // publishes both
+        let primary_alerter = Arc::new(RecordingAlerter::new("recording"));
+        let primary = Sentinel::builder(config.clone())
+            .with_storage(Arc::new(InMemoryStorage::new()))
+            .with_alerter(primary_alerter.clone())
+            .with_leadership(Leadership::single())
+            .with_state_stream(stream.clone())
+            .with_api(false)
+            .build()
+            .await
+            .unwrap();
+        primary.promote().await;
+        for _ in 0..20 {
+            primary.process_event(&create_test_event()).await;
+        }
+        let anomaly = create_test_anomaly();
+        primary.handle_anomaly(&anomaly).await;
+        assert_eq!(primary_alerter.count(), 1);
+        primary.sync_state().await;
+        let published = stream.latest().await.unwrap().unwrap();
+
+        // The standby restores it, and neither publishes nor accepts
+        // anomalies
+        let elector = Arc::new(HandoverElector::default());
+        let standby_alerter = Arc::new(RecordingAlerter::new("recording"));
+        let standby = Sentinel::builder(config)
+            .with_storage(Arc::new(InMemoryStorage::new()))
+            .with_alerter(standby_alerter.clone())
+            .with_leadership(Leadership::campaign(
+                elector.clone(),
+                Duration::from_millis(5),
+            ))
+            .with_state_stream(stream.clone())
+            .with_api(false)
+            .build()
+            .await
+            .unwrap();
+        assert!(standby.is_standby());
+        standby.sync_state().await;
+        standby.sync_state().await;
+        assert_eq!(stream.latest().await.unwrap().unwrap().id, published.id);
+        let learned = primary.detection_engine.lock().await.snapshot().unwrap();
+        let warmed = standby.detection_engine.lock().await.snapshot().unwrap();
+        assert!(!learned.baselines.is_empty());
+        assert_eq!(warmed.baselines.len(), learned.baselines.len());
+        assert!(
+            FederationSink::receive(&standby, "eu", vec![create_test_anomaly()])
+                .await
+                .is_err()
+        );
+        assert_eq!(standby_alerter.count(), 0);
+
+        // Promoted, it carries on from the primary's deduplication windows
+        elector.leading.store(true, Ordering::SeqCst);
+        tokio::time::timeout(Duration::from_secs(5), standby.promote())
+            .await
+            .unwrap();
+        assert!(!standby.is_standby());
+        let mut duplicate = create_test_anomaly();
+        duplicate.timestamp = anomaly.timestamp;
+        standby.handle_anomaly(&duplicate).await;
+        assert_eq!(standby_alerter.count(), 0);
+    }
+
+    #[tokio::test]
+    async fn test_standby_state_age_by_clock() {
+        let mut config = Config::default_test();
+        config.standby = Some(llm_sentinel_core::config::StandbyConfig {
+            redis_url: "redis://localhost:6379".to_string(),
+            stream: "sentinel:state".to_string(),
+            sync_interval_secs: 1,
+            max_len: 10,
+        });
+        let stream = Arc::new(MemoryStateStream::new());
+        let clock = Clock::manual(chrono::Utc::now());
+
+        let primary = Sentinel::builder(config.clone())
+            .with_storage(Arc::new(InMemoryStorage::new()))
+            .with_alerter(Arc::new(RecordingAlerter::new("recording")))
+            .with_leadership(Leadership::single())
+            .with_state_stream(stream.clone())
+            .with_clock(clock.clone())
+            .with_api(false)
+            .build()
+            .await
+            .unwrap();
+        primary.promote().await;
+        primary.sync_state().await;
+        let published = stream.latest().await.unwrap().unwrap();
+        assert_eq!(published.created_at, clock.now());
+
+        clock.advance(chrono::Duration::seconds(30));
+        let standby = Sentinel::builder(config)
+            .with_storage(Arc::new(InMemoryStorage::new()))
+            .with_alerter(Arc::new(RecordingAlerter::new("recording")))
+            .with_leadership(Leadership::campaign(
+                Arc::new(HandoverElector::default()),
+                Duration::from_millis(5),
+            ))
+            .with_state_stream(stream)
+            .with_clock(clock)
+            .with_api(false)
+            .build()
+            .await
+            .unwrap();
+        let recorder = metrics_exporter_prometheus::PrometheusBuilder::new().build_recorder();
+        let handle = recorder.handle();
+        let _guard = ::metrics::set_default_local_recorder(&recorder);
+        standby.sync_state().await;
+
+        assert!(handle
+            .render()
+            .lines()
+            .any(|line| line == "sentinel_standby_state_age_seconds 30"));
+    }
+
+    #[tokio::test]
+    async fn test_facade_builds_sentinel() {
+        use crate::prelude::*;
+
+        let alerter = Arc::new(crate::alerting::RecordingAlerter::new("recording"));
+        let sentinel: Sentinel = SentinelBuilder::new(Config::default_test())
+            .with_storage(Arc::new(crate::storage::InMemoryStorage::new()))
+            .with_alerter(alerter.clone())
+            .with_leadership(crate::services::Leadership::single())
+            .with_api(false)
+            .build()
+            .await
+            .unwrap();
+        let anomaly: crate::events::AnomalyEvent = create_test_anomaly();
+        sentinel.handle_anomaly(&anomaly).await;
+        assert_eq!(alerter.count(), 1);
+    }
+
+    #[tokio::test]
+    async fn test_snapshot_restored_on_start() {
+        let dir = std::env::temp_dir().join(format!(
+            "sentinel-snapshot-test-{}-{}",
+            std::process::id(),
+            chrono::Utc::now().timestamp_nanos_opt().unwrap_or_default()
+        ));
+        let mut config = Config::default_test();
+        config.storage.snapshots = Some(llm_sentinel_core::config::SnapshotConfig {
+            dir: dir.display().to_string(),
+            keep: 2,
+            restore_on_start: true,
+            snapshot_on_shutdown: false,
+        });
+
+        // Blue learns baselines and opens a deduplication window
+        let blue_alerter = Arc::new(RecordingAlerter::new("recording"));
+        let blue = Sentinel::builder(config.clone())
+            .with_storage(Arc::new(InMemoryStorage::new()))
+            .with_alerter(blue_alerter.clone())
+            .with_api(false)
+            .build()
+            .await
+            .unwrap();
+        for _ in 0..20 {
+            blue.process_event(&create_test_event()).await;
+        }
+        let anomaly = create_test_anomaly();
+        blue.handle_anomaly(&anomaly).await;
+        assert_eq!(blue_alerter.count(), 1);
+        let snapshot = SnapshotControl::snapshot(&blue).await.unwrap();
+        assert_eq!(snapshot.sections, vec!["deduplication", "detection"]);
+
+        // Green resumes from blue's snapshot
+        let green_alerter = Arc::new(RecordingAlerter::new("recording"));
+        let green = Sentinel::builder(config)
+            .with_storage(Arc::new(InMemoryStorage::new()))
+            .with_alerter(green_alerter.clone())
+            .with_api(false)
+            .build()
+            .await
+            .unwrap();
+        let learned = blue.detection_engine.lock().await.snapshot().unwrap();
+        let resumed = green.detection_engine.lock().await.snapshot().unwrap();
+        assert!(!learned.baselines.is_empty());
+        assert_eq!(resumed.baselines.len(), learned.baselines.len());
+        assert!(resumed.detectors.keys().eq(learned.detectors.keys()));
+        let mut duplicate = create_test_anomaly();
+        duplicate.timestamp = anomaly.timestamp;
+        green.handle_anomaly(&duplicate).await;
+        assert_eq!(green_alerter.count(), 0);
+
+        assert_eq!(SnapshotControl::list(&green).await.unwrap(), vec![snapshot]);
+        assert!(SnapshotControl::restore(&green, "missing").await.is_err());
+        std::fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_remediation_dry_run_audited() {
+        let mut config = Config::default_test();
+        config.alerting.remediation = Some(RemediationConfig {
+            dry_run: true,
+            max_actions_per_hour: 10,
+            rules: vec![RemediationRuleConfig {
+                name: "restart".to_string(),
+                anomaly_types: vec!["latency_spike".to_string()],
+                min_severity: None,
+                services: Vec::new(),
+                models: Vec::new(),
+                action: RemediationActionConfig::Webhook {
+                    url: "http://localhost:9/restart".to_string(),
+                    headers: Default::default(),
+                },
+                cooldown_secs: 60,
+                max_actions_per_hour: 1,
+            }],
+        });
+        let audit = Arc::new(MemoryAuditLog::new(10));
+
+        let sentinel = Sentinel::builder(config)
+            .with_storage(Arc::new(InMemoryStorage::new()))
+            .with_alerter(Arc::new(RecordingAlerter::new("recording")))
+            .with_audit_log(audit.clone())
+            .with_api(false)
+            .build()
+            .await
+            .unwrap();
+
+        sentinel.handle_anomaly(&create_test_anomaly()).await;
+        let entries = audit.entries();
+        assert_eq!(entries.len(), 1);
+        assert_eq!(entries[0].action, "remediation.webhook");
+        assert_eq!(entries[0].details["status"], "dry_run");
+    }
+
+    #[test]
+    fn test_self_checks_cover_configured_dependencies() {
+        let mut config = Config::default_test();
+        config.leader_election =
+            Some(serde_yaml::from_str("redis_url: redis://redis:6379").unwrap());
+        config.ingestion.fanout = Some(
+            serde_yaml::from_str(
+                "url: amqp://localhost:5672\ndestinations:\n  - exchange: telemetry.raw\n",
+            )
+            .unwrap(),
+        );
+
+        // Sections needing a feature the binary lacks are still checked, as
+        // failures
+        let dependencies: Vec<String> = self_checks(&config)
+            .iter()
+            .map(|check| check.dependency())
+            .collect();
+        assert_eq!(
+            dependencies,
+            [
+                "kafka",
+                "influxdb",
+                "redis-leader",
+                "rabbitmq",
+                "rabbitmq-fanout:telemetry.raw"
+            ]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_detection_document_staged() {
+        let dir = std::env::temp_dir().join(format!("sentinel-tuning-{}", Uuid::new_v4()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let detection_file = dir.join("detection.yaml");
+        let mut config = Config::default_test();
+        config.detection_file = Some(detection_file.display().to_string());
+        let sentinel = Sentinel::builder(config)
+            .with_storage(Arc::new(InMemoryStorage::new()))
+            .with_alerter(Arc::new(RecordingAlerter::new("recording")))
+            .with_leadership(Leadership::single())
+            .with_api(false)
+            .build()
+            .await
+            .unwrap();
+
+        let mut document = sentinel.export().unwrap();
+        document.suppression_schedules = vec![SuppressionScheduleConfig {
+            name: "nightly-batch".to_string(),
+            cron: "0 25 * * *".to_string(),
+            duration_mins: 60,
+            services: Vec::new(),
+            anomaly_types: Vec::new(),
+        }];
+        let result = sentinel.stage(document.clone(), true).await;
+        assert!(matches!(
+            result,
+            Err(llm_sentinel_core::Error::Validation(_))
+        ));
+
+        document.suppression_schedules[0].cron = "0 2 * * *".to_string();
+        let changes = sentinel.stage(document.clone(), true).await.unwrap();
+        assert_eq!(changes.len(), 1);
+        assert_eq!(changes[0].section, "alerting.suppression_schedules");
+        assert!(!detection_file.exists());
+
+        let changes = sentinel.stage(document, false).await.unwrap();
+        assert_eq!(changes.len(), 1);
+        let loaded = DetectionDocument::from_file(&detection_file).unwrap();
+        assert_eq!(loaded.suppression_schedules[0].name, "nightly-batch");
+        // Staged for the next start; the running configuration is unchanged
+        assert!(sentinel.export().unwrap().suppression_schedules.is_empty());
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+}
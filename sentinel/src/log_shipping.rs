@@ -0,0 +1,448 @@
+//! Shipping of the sentinel's own logs.
+//!
+//! [`LogShipper::layer`] is installed in the tracing subscriber when logging
+//! is initialized, before configuration is loaded; records are shipped once
+//! [`LogShipper::start`] connects it to the sink configured under
+//! `observability.log_shipping`. Each record is serialized as JSON with its
+//! level, target, message, fields, enclosing spans and the correlation ID of
+//! the task that logged it (or of the nearest span carrying a
+//! `correlation_id` field), then queued for a background task that ships
+//! batches to Kafka or the OpenSearch `_bulk` API.
+//!
+//! Logging never blocks on the sink: records that do not fit the queue are
+//! dropped and counted as `sentinel_log_records_dropped_total{reason}`.
+//! Records logged by the shipper itself and by the Kafka and HTTP clients
+//! are not shipped, so a failing sink does not feed on its own errors.
+
+use anyhow::{Context as _, Result};
+use llm_sentinel_core::{
+    config::{LogShippingConfig, LogSinkConfig},
+    correlation,
+};
+use serde_json::{Map, Value};
+use std::{
+    fmt,
+    sync::{Arc, OnceLock},
+    time::Duration,
+};
+use tokio::sync::mpsc;
+use tracing::{
+    field::{Field, Visit},
+    span, warn, Event, Level, Subscriber,
+};
+use tracing_subscriber::{layer::Context, registry::LookupSpan, Layer};
+
+/// Targets whose records are never shipped
+const EXCLUDED_TARGETS: &[&str] = &[module_path!(), "rdkafka", "reqwest", "hyper", "h2"];
+
+/// Time to wait for space in the Kafka producer queue
+#[cfg(feature = "kafka")]
+const KAFKA_QUEUE_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// Queue the layer hands records to
+#[derive(Debug)]
+struct Route {
+    tx: mpsc::Sender<Value>,
+    min_level: Level,
+}
+
+/// Forwards log records to the configured sink once started
+#[derive(Debug, Clone, Default)]
+pub struct LogShipper {
+    route: Arc<OnceLock<Route>>,
+}
+
+impl LogShipper {
+    /// Create a shipper that drops records until started
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Tracing layer queueing records for this shipper
+    pub fn layer(&self) -> LogShippingLayer {
+        LogShippingLayer {
+            route: Arc::clone(&self.route),
+        }
+    }
+
+    /// Connect to the sink and ship queued records from a background task
+    ///
+    /// Must be called from within a Tokio runtime; fails when the sink cannot
+    /// be created or the shipper was already started.
+    pub fn start(&self, config: &LogShippingConfig) -> Result<tokio::task::JoinHandle<()>> {
+        let min_level = config
+            .min_level
+            .parse::<Level>()
+            .with_context(|| format!("Invalid log shipping level: {}", config.min_level))?;
+        let sink = Sink::new(&config.sink)?;
+        let rx = self.connect(min_level, config.queue_capacity)?;
+
+        tracing::info!(
+            sink = config.sink.name(),
+            min_level = %min_level,
+            "Shipping logs"
+        );
+        Ok(tokio::spawn(ship(
+            rx,
+            sink,
+            config.batch_size,
+            Duration::from_millis(config.flush_interval_ms),
+        )))
+    }
+
+    /// Route records at `min_level` or more severe to a new queue
+    fn connect(&self, min_level: Level, capacity: usize) -> Result<mpsc::Receiver<Value>> {
+        let (tx, rx) = mpsc::channel(capacity);
+        self.route
+            .set(Route { tx, min_level })
+            .map_err(|_| anyhow::anyhow!("Log shipping already started"))?;
+        Ok(rx)
+    }
+}
+
+/// Tracing layer serializing records for a [`LogShipper`]
+#[derive(Debug)]
+pub struct LogShippingLayer {
+    route: Arc<OnceLock<Route>>,
+}
+
+/// Fields recorded on a span, kept in its extensions
+struct SpanFields(Map<String, Value>);
+
+impl<S> Layer<S> for LogShippingLayer
+where
+    S: Subscriber + for<'a> LookupSpan<'a>,
+{
+    fn on_new_span(&self, attrs: &span::Attributes<'_>, id: &span::Id, ctx: Context<'_, S>) {
+        if self.route.get().is_none() {
+            return;
+        }
+        let Some(span) = ctx.span(id) else {
+            return;
+        };
+        let mut fields = JsonVisitor::default();
+        attrs.record(&mut fields);
+        span.extensions_mut().insert(SpanFields(fields.0));
+    }
+
+    fn on_record(&self, id: &span::Id, values: &span::Record<'_>, ctx: Context<'_, S>) {
+        let Some(span) = ctx.span(id) else {
+            return;
+        };
+        let mut extensions = span.extensions_mut();
+        if let Some(SpanFields(fields)) = extensions.get_mut::<SpanFields>() {
+            let mut visitor = JsonVisitor(std::mem::take(fields));
+            values.record(&mut visitor);
+            *fields = visitor.0;
+        }
+    }
+
+    fn on_event(&self, event: &Event<'_>, ctx: Context<'_, S>) {
+        let Some(route) = self.route.get() else {
+            return;
+        };
+        let metadata = event.metadata();
+        if *metadata.level() > route.min_level
+            || EXCLUDED_TARGETS
+                .iter()
+                .any(|target| metadata.target().starts_with(target))
+        {
+            return;
+        }
+
+        let mut visitor = JsonVisitor::default();
+        event.record(&mut visitor);
+        let mut fields = visitor.0;
+        let message = fields.remove("message").unwrap_or(Value::Null);
+
+        // Innermost span first, so the nearest correlation ID wins
+        let mut spans = Vec::new();
+        let mut span_correlation_id = None;
+        if let Some(scope) = ctx.event_scope(event) {
+            for span in scope {
+                spans.push(Value::String(span.name().to_string()));
+                if span_correlation_id.is_none() {
+                    if let Some(SpanFields(span_fields)) = span.extensions().get::<SpanFields>() {
+                        span_correlation_id = span_fields
+                            .get(correlation::METADATA_KEY)
+                            .and_then(Value::as_str)
+                            .map(str::to_string);
+                    }
+                }
+            }
+        }
+        spans.reverse();
+
+        let correlation_id = correlation::current().or(span_correlation_id);
+        let record = serde_json::json!({
+            "timestamp": chrono::Utc::now().to_rfc3339(),
+            "level": metadata.level().to_string(),
+            "target": metadata.target(),
+            "message": message,
+            "fields": fields,
+            "spans": spans,
+            "correlation_id": correlation_id,
+        });
+
+        if route.tx.try_send(record).is_err() {
+            ::metrics::counter!("sentinel_log_records_dropped_total", "reason" => "queue_full")
+                .increment(1);
+        }
+    }
+}
+
+/// Collects event and span fields as JSON values
+#[derive(Default)]
+struct JsonVisitor(Map<String, Value>);
+
+impl Visit for JsonVisitor {
+    fn record_f64(&mut self, field: &Field, value: f64) {
+        self.0.insert(field.name().to_string(), value.into());
+    }
+
+    fn record_i64(&mut self, field: &Field, value: i64) {
+        self.0.insert(field.name().to_string(), value.into());
+    }
+
+    fn record_u64(&mut self, field: &Field, value: u64) {
+        self.0.insert(field.name().to_string(), value.into());
+    }
+
+    fn record_bool(&mut self, field: &Field, value: bool) {
+        self.0.insert(field.name().to_string(), value.into());
+    }
+
+    fn record_str(&mut self, field: &Field, value: &str) {
+        self.0.insert(field.name().to_string(), value.into());
+    }
+
+    fn record_error(&mut self, field: &Field, value: &(dyn std::error::Error + 'static)) {
+        self.0
+            .insert(field.name().to_string(), value.to_string().into());
+    }
+
+    fn record_debug(&mut self, field: &Field, value: &dyn fmt::Debug) {
+        self.0
+            .insert(field.name().to_string(), format!("{:?}", value).into());
+    }
+}
+
+/// Destination of shipped batches
+enum Sink {
+    #[cfg(feature = "kafka")]
+    Kafka {
+        producer: rdkafka::producer::FutureProducer,
+        topic: String,
+    },
+    Opensearch {
+        client: reqwest::Client,
+        bulk_url: String,
+        index: String,
+        credentials: Option<(String, Option<String>)>,
+    },
+}
+
+impl Sink {
+    fn new(config: &LogSinkConfig) -> Result<Self> {
+        match config {
+            #[cfg(feature = "kafka")]
+            LogSinkConfig::Kafka {
+                brokers,
+                topic,
+                properties,
+            } => {
+                let mut client_config = rdkafka::ClientConfig::new();
+                client_config
+                    .set("bootstrap.servers", brokers.join(","))
+                    .set("client.id", "sentinel-log-shipper");
+                for (key, value) in properties {
+                    client_config.set(key, value);
+                }
+                let producer = client_config
+                    .create()
+                    .context("Failed to create Kafka log producer")?;
+                Ok(Self::Kafka {
+                    producer,
+                    topic: topic.clone(),
+                })
+            }
+            #[cfg(not(feature = "kafka"))]
+            LogSinkConfig::Kafka { .. } => {
+                anyhow::bail!("Kafka log shipping requires the kafka feature")
+            }
+            LogSinkConfig::Opensearch {
+                url,
+                index,
+                username,
+                password,
+            } => Ok(Self::Opensearch {
+                client: reqwest::Client::builder()
+                    .timeout(Duration::from_secs(30))
+                    .build()
+                    .context("Failed to create OpenSearch client")?,
+                bulk_url: format!("{}/_bulk", url.trim_end_matches('/')),
+                index: index.clone(),
+                credentials: username
+                    .clone()
+                    .map(|username| (username, password.clone())),
+            }),
+        }
+    }
+
+    fn name(&self) -> &'static str {
+        match self {
+            #[cfg(feature = "kafka")]
+            Self::Kafka { .. } => "kafka",
+            Self::Opensearch { .. } => "opensearch",
+        }
+    }
+
+    /// Ship `records`, returning how many were accepted
+    async fn send(&self, records: &[Value]) -> Result<usize> {
+        match self {
+            #[cfg(feature = "kafka")]
+            Self::Kafka { producer, topic } => {
+                use rdkafka::producer::FutureRecord;
+
+                let payloads: Vec<String> = records.iter().map(Value::to_string).collect();
+                // Keyed by correlation ID so the records of a request stay in order
+                let sends = records.iter().zip(&payloads).map(|(record, payload)| {
+                    let mut kafka_record = FutureRecord::to(topic).payload(payload);
+                    if let Some(key) = record["correlation_id"].as_str() {
+                        kafka_record = kafka_record.key(key);
+                    }
+                    producer.send(kafka_record, KAFKA_QUEUE_TIMEOUT)
+                });
+                let results = futures::future::join_all(sends).await;
+                Ok(results.iter().filter(|result| result.is_ok()).count())
+            }
+            Self::Opensearch {
+                client,
+                bulk_url,
+                index,
+                credentials,
+            } => {
+                let body = bulk_body(index, records);
+                let mut request = client
+                    .post(bulk_url)
+                    .header(reqwest::header::CONTENT_TYPE, "application/x-ndjson")
+                    .body(body);
+                if let Some((username, password)) = credentials {
+                    request = request.basic_auth(username, password.as_ref());
+                }
+                let response = request
+                    .send()
+                    .await
+                    .context("OpenSearch bulk request failed")?
+                    .error_for_status()
+                    .context("OpenSearch rejected the bulk request")?;
+                let result: Value = response
+                    .json()
+                    .await
+                    .context("Invalid OpenSearch bulk response")?;
+                let failed = result["items"].as_array().map_or(0, |items| {
+                    items
+                        .iter()
+                        .filter(|item| item["index"]["error"].is_object())
+                        .count()
+                });
+                Ok(records.len().saturating_sub(failed))
+            }
+        }
+    }
+}
+
+/// NDJSON `_bulk` body indexing `records` into `index`
+fn bulk_body(index: &str, records: &[Value]) -> String {
+    let action = serde_json::json!({ "index": { "_index": index } }).to_string();
+    let mut body = String::new();
+    for record in records {
+        body.push_str(&action);
+        body.push('\n');
+        body.push_str(&record.to_string());
+        body.push('\n');
+    }
+    body
+}
+
+/// Ship queued records in batches of up to `batch_size`, flushing a partial
+/// batch `flush_interval` after its first record
+async fn ship(
+    mut rx: mpsc::Receiver<Value>,
+    sink: Sink,
+    batch_size: usize,
+    flush_interval: Duration,
+) {
+    let mut batch = Vec::with_capacity(batch_size);
+    while let Some(record) = rx.recv().await {
+        batch.push(record);
+        let flush = tokio::time::sleep(flush_interval);
+        tokio::pin!(flush);
+        while batch.len() < batch_size {
+            tokio::select! {
+                record = rx.recv() => match record {
+                    Some(record) => batch.push(record),
+                    None => break,
+                },
+                _ = &mut flush => break,
+            }
+        }
+
+        let shipped = match sink.send(&batch).await {
+            Ok(shipped) => shipped,
+            Err(e) => {
+                warn!("Failed to ship {} log records: {:#}", batch.len(), e);
+                0
+            }
+        };
+        ::metrics::counter!("sentinel_log_records_shipped_total", "sink" => sink.name())
+            .increment(shipped as u64);
+        let failed = batch.len() - shipped;
+        if failed > 0 {
+            ::metrics::counter!("sentinel_log_records_dropped_total", "reason" => "sink_error")
+                .increment(failed as u64);
+        }
+        batch.clear();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tracing_subscriber::layer::SubscriberExt;
+
+    #[test]
+    fn test_layer_ships_records_with_correlation_id() {
+        let shipper = LogShipper::new();
+        let subscriber = tracing_subscriber::registry().with(shipper.layer());
+        let mut rx = shipper.connect(Level::WARN, 16).unwrap();
+        assert!(shipper.connect(Level::WARN, 16).is_err());
+
+        // This module's own records are excluded, so log as the pipeline
+        tracing::subscriber::with_default(subscriber, || {
+            tracing::info!(target: "llm_sentinel", "Below the shipping level");
+            let span = tracing::info_span!("process_event", correlation_id = "req-42");
+            let _entered = span.enter();
+            tracing::warn!(
+                target: "llm_sentinel",
+                service = "chat",
+                attempts = 3,
+                "Storage write retried"
+            );
+        });
+
+        let record = rx.try_recv().unwrap();
+        assert!(rx.try_recv().is_err());
+        assert_eq!(record["level"], "WARN");
+        assert_eq!(record["message"], "Storage write retried");
+        assert_eq!(record["fields"]["service"], "chat");
+        assert_eq!(record["fields"]["attempts"], 3);
+        assert_eq!(record["spans"][0], "process_event");
+        assert_eq!(record["correlation_id"], "req-42");
+
+        let body = bulk_body("sentinel-logs", &[record]);
+        let lines: Vec<&str> = body.lines().collect();
+        assert_eq!(lines.len(), 2);
+        assert_eq!(lines[0], r#"{"index":{"_index":"sentinel-logs"}}"#);
+    }
+}
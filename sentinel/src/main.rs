@@ -1,23 +1,19 @@
 //! LLM-Sentinel Main Binary
 //!
-//! Orchestrates all components of the sentinel system:
-//! - Ingestion: Kafka consumer for telemetry
-//! - Detection: Multi-detector anomaly detection engine
-//! - Storage: InfluxDB time-series storage
-//! - Alerting: RabbitMQ alert publisher
-//! - API: REST API server
+//! Loads configuration and runs the [`Sentinel`] orchestrator.
 
 use anyhow::{Context, Result};
-use clap::Parser;
-use llm_sentinel_alerting::{prelude::*, rabbitmq::RetryConfig};
-use llm_sentinel_api::prelude::*;
-use llm_sentinel_core::config::Config;
-use llm_sentinel_detection::prelude::*;
-use llm_sentinel_ingestion::prelude::*;
-use llm_sentinel_storage::prelude::*;
-use std::{path::PathBuf, sync::Arc};
-use tokio::{signal, sync::Mutex};
-use tracing::{error, info};
+use clap::{Parser, Subcommand};
+use llm_sentinel::{
+    config::{render_changes, Config, DetectionDocument},
+    log_shipping::LogShipper,
+    Sentinel,
+};
+use std::{
+    path::{Path, PathBuf},
+    time::Duration,
+};
+use tracing::info;
 use tracing_subscriber::{layer::SubscriberExt, util::SubscriberInitExt};
 
 /// LLM-Sentinel CLI arguments
@@ -25,9 +21,14 @@ use tracing_subscriber::{layer::SubscriberExt, util::SubscriberInitExt};
 #[clap(name = "sentinel", version, about = "LLM observability and anomaly detection")]
 struct Cli {
     /// Configuration file path
-    #[clap(short, long, default_value = "config/sentinel.yaml")]
+    #[clap(short, long, global = true, default_value = "config/sentinel.yaml")]
     config: PathBuf,
 
+    /// Configuration profile; merges the overlay next to the config file
+    /// (e.g. `sentinel.prod.yaml` for `prod`) over it
+    #[clap(short, long, global = true, env = "SENTINEL_PROFILE")]
+    profile: Option<String>,
+
     /// Log level (trace, debug, info, warn, error)
     #[clap(long, env = "SENTINEL_LOG_LEVEL", default_value = "info")]
     log_level: String,
@@ -39,20 +40,90 @@ struct Cli {
     /// Dry run mode (don't start services)
     #[clap(long)]
     dry_run: bool,
+
+    #[clap(subcommand)]
+    command: Option<Command>,
+}
+
+/// Subcommands; the sentinel runs when none is given
+#[derive(Debug, Subcommand)]
+enum Command {
+    /// Inspect configuration
+    Config {
+        #[clap(subcommand)]
+        command: ConfigCommand,
+    },
+    /// Check connectivity, permissions and schema of every configured
+    /// dependency; exits non-zero when a check fails
+    Doctor {
+        /// Print the report as JSON
+        #[clap(long)]
+        json: bool,
+    },
 }
 
-#[tokio::main]
-async fn main() -> Result<()> {
+/// Configuration subcommands
+#[derive(Debug, Subcommand)]
+enum ConfigCommand {
+    /// Print the merged configuration with secrets masked
+    Show {
+        /// Resolve secret references, as at startup
+        #[clap(long)]
+        resolved: bool,
+    },
+    /// Print the detection configuration (engines, thresholds, SLOs,
+    /// routes, severity rules and suppression schedules) as a versioned
+    /// YAML document
+    ExportDetection {
+        /// Write the document to this file instead
+        #[clap(long)]
+        output: Option<PathBuf>,
+    },
+    /// Validate a detection document, print the sections it changes and
+    /// write it to `detection_file`
+    ImportDetection {
+        /// Detection document to import
+        file: PathBuf,
+
+        /// Validate and print the changes without writing the document
+        #[clap(long)]
+        dry_run: bool,
+    },
+}
+
+fn main() -> Result<()> {
     let cli = Cli::parse();
 
-    // Initialize logging
-    init_logging(&cli)?;
+    // Print configuration and reports without logging over them
+    match &cli.command {
+        Some(Command::Config {
+            command: ConfigCommand::Show { resolved },
+        }) => return command_runtime()?.block_on(show_config(&cli, *resolved)),
+        Some(Command::Config {
+            command: ConfigCommand::ExportDetection { output },
+        }) => return export_detection(&cli, output.as_deref()),
+        Some(Command::Config {
+            command: ConfigCommand::ImportDetection { file, dry_run },
+        }) => return import_detection(&cli, file, *dry_run),
+        Some(Command::Doctor { json }) => return command_runtime()?.block_on(doctor(&cli, *json)),
+        None => {}
+    }
+
+    // Initialize logging; records are shipped once the configuration is loaded
+    let log_shipper = LogShipper::new();
+    init_logging(&cli, &log_shipper)?;
 
     info!("Starting LLM-Sentinel v{}", env!("CARGO_PKG_VERSION"));
-    info!("Loading configuration from: {:?}", cli.config);
+    match &cli.profile {
+        Some(profile) => info!(
+            "Loading configuration from: {:?} (profile {})",
+            cli.config, profile
+        ),
+        None => info!("Loading configuration from: {:?}", cli.config),
+    }
 
     // Load configuration
-    let config = Config::from_file(&cli.config)
+    let config = Config::from_file_with_profile(&cli.config, cli.profile.as_deref())
         .context("Failed to load configuration")?;
 
     info!("Configuration loaded successfully");
@@ -62,8 +133,30 @@ async fn main() -> Result<()> {
         return Ok(());
     }
 
+    // Size the runtime from the server config before anything is spawned
+    let runtime = llm_sentinel::runtime::build(&config.server)?;
+    runtime.block_on(run(config, log_shipper))
+}
+
+/// Runtime for one-shot subcommands
+fn command_runtime() -> Result<tokio::runtime::Runtime> {
+    tokio::runtime::Builder::new_current_thread()
+        .enable_all()
+        .build()
+        .context("Failed to build Tokio runtime")
+}
+
+/// Start all components and run until shutdown
+async fn run(config: Config, log_shipper: LogShipper) -> Result<()> {
+    llm_sentinel::runtime::spawn_metrics(Duration::from_secs(
+        config.server.runtime.metrics_interval_secs.max(1),
+    ));
+
     // Initialize components
-    let sentinel = Sentinel::new(config).await?;
+    let sentinel = Sentinel::builder(config)
+        .with_log_shipper(log_shipper)
+        .build()
+        .await?;
 
     // Run the sentinel
     sentinel.run().await?;
@@ -71,8 +164,89 @@ async fn main() -> Result<()> {
     Ok(())
 }
 
-/// Initialize logging based on CLI arguments
-fn init_logging(cli: &Cli) -> Result<()> {
+/// Print the effective configuration, masking secret values
+async fn show_config(cli: &Cli, resolved: bool) -> Result<()> {
+    let config = Config::from_file_with_profile(&cli.config, cli.profile.as_deref())
+        .context("Failed to load configuration")?;
+    let secrets = llm_sentinel::secret_resolver(&config)?;
+    let config = if resolved {
+        secrets
+            .resolve_config(&config)
+            .await
+            .context("Failed to resolve secrets")?
+    } else {
+        config
+    };
+
+    let masked = secrets.masked(&config)?;
+    print!("{}", serde_yaml::to_string(&masked)?);
+    Ok(())
+}
+
+/// Print or write the detection document of the effective configuration
+fn export_detection(cli: &Cli, output: Option<&Path>) -> Result<()> {
+    let config = Config::from_file_with_profile(&cli.config, cli.profile.as_deref())
+        .context("Failed to load configuration")?;
+    let document = DetectionDocument::from_config(&config);
+    match output {
+        Some(path) => document
+            .write(path)
+            .with_context(|| format!("Failed to write {}", path.display()))?,
+        None => print!("{}", document.to_yaml()?),
+    }
+    Ok(())
+}
+
+/// Validate a detection document against the effective configuration,
+/// print its changes and, unless `dry_run`, write it to `detection_file`
+fn import_detection(cli: &Cli, file: &Path, dry_run: bool) -> Result<()> {
+    let config = Config::from_file_with_profile(&cli.config, cli.profile.as_deref())
+        .context("Failed to load configuration")?;
+    let document = DetectionDocument::from_file(file)?;
+    let plan =
+        llm_sentinel::plan_detection(&config, &document).context("Detection document rejected")?;
+    print!("{}", render_changes(&plan.changes));
+    if dry_run {
+        return Ok(());
+    }
+
+    let path = config
+        .detection_file
+        .as_deref()
+        .context("Set detection_file in the configuration to import detection documents")?;
+    document
+        .write(path)
+        .with_context(|| format!("Failed to write {}", path))?;
+    println!("Wrote {}; restart the sentinel to apply it", path);
+    Ok(())
+}
+
+/// Check the configured dependencies and print the report
+async fn doctor(cli: &Cli, json: bool) -> Result<()> {
+    let config = Config::from_file_with_profile(&cli.config, cli.profile.as_deref())
+        .context("Failed to load configuration")?;
+    let config = llm_sentinel::secret_resolver(&config)?
+        .resolve_config(&config)
+        .await
+        .context("Failed to resolve secrets")?;
+
+    let report = llm_sentinel::self_check(&config).await;
+    if json {
+        println!("{}", serde_json::to_string_pretty(&report)?);
+    } else {
+        print!("{}", report.render());
+    }
+
+    let failed = report.failures().count();
+    if failed > 0 {
+        anyhow::bail!("{} self-check(s) failed", failed);
+    }
+    Ok(())
+}
+
+/// Initialize logging based on CLI arguments, forwarding records to
+/// `log_shipper`
+fn init_logging(cli: &Cli, log_shipper: &LogShipper) -> Result<()> {
     let log_level = cli
         .log_level
         .parse::<tracing::Level>()
@@ -81,6 +255,7 @@ fn init_logging(cli: &Cli) -> Result<()> {
     if cli.log_json {
         // JSON structured logging
         tracing_subscriber::registry()
+            .with(log_shipper.layer())
             .with(
                 tracing_subscriber::fmt::layer()
                     .json()
@@ -96,6 +271,7 @@ fn init_logging(cli: &Cli) -> Result<()> {
     } else {
         // Human-readable logging
         tracing_subscriber::registry()
+            .with(log_shipper.layer())
             .with(
                 tracing_subscriber::fmt::layer()
                     .with_target(true)
@@ -113,294 +289,3 @@ fn init_logging(cli: &Cli) -> Result<()> {
 
     Ok(())
 }
-
-/// Main Sentinel orchestrator
-struct Sentinel {
-    config: Config,
-    storage: Arc<InfluxDbStorage>,
-    detection_engine: Arc<Mutex<DetectionEngine>>,
-    alerter: Arc<RabbitMqAlerter>,
-    deduplicator: Arc<AlertDeduplicator>,
-}
-
-impl Sentinel {
-    /// Create a new Sentinel instance
-    async fn new(config: Config) -> Result<Self> {
-        info!("Initializing Sentinel components...");
-
-        // Initialize storage
-        info!("Connecting to InfluxDB...");
-        let core_influxdb_config = config.storage.influxdb.clone()
-            .context("InfluxDB configuration is required")?;
-
-        // Convert core InfluxDbConfig to storage InfluxDbConfig
-        let influxdb_config = llm_sentinel_storage::influxdb::InfluxDbConfig {
-            url: core_influxdb_config.url,
-            org: core_influxdb_config.org,
-            telemetry_bucket: core_influxdb_config.bucket.clone(),
-            anomaly_bucket: format!("{}-anomalies", core_influxdb_config.bucket),
-            token: core_influxdb_config.token,
-            batch_size: 100,
-            timeout_secs: core_influxdb_config.timeout_secs,
-        };
-
-        let storage = InfluxDbStorage::new(influxdb_config)
-            .await
-            .context("Failed to initialize storage")?;
-        let storage = Arc::new(storage);
-        info!("InfluxDB connected");
-
-        // Initialize detection engine
-        info!("Initializing detection engine...");
-
-        // Convert DetectionConfig to EngineConfig
-        // For now, use default EngineConfig - in production this should be configured
-        let engine_config = EngineConfig::default();
-
-        let detection_engine = Arc::new(Mutex::new(
-            DetectionEngine::new(engine_config)
-                .context("Failed to create detection engine")?,
-        ));
-        info!("Detection engine initialized");
-
-        // Initialize alerting
-        info!("Connecting to RabbitMQ...");
-        let core_rabbitmq_config = config.alerting.rabbitmq.clone()
-            .context("RabbitMQ configuration is required")?;
-
-        // Convert core RabbitMqConfig to alerting RabbitMqConfig
-        let rabbitmq_config = llm_sentinel_alerting::rabbitmq::RabbitMqConfig {
-            url: core_rabbitmq_config.url,
-            exchange: core_rabbitmq_config.exchange,
-            exchange_type: core_rabbitmq_config.exchange_type,
-            routing_key_prefix: "alert".to_string(),
-            persistent: core_rabbitmq_config.durable,
-            timeout_secs: 10,
-            retry_config: RetryConfig {
-                max_attempts: core_rabbitmq_config.retry_attempts,
-                initial_delay_ms: core_rabbitmq_config.retry_delay_ms,
-                backoff_multiplier: 2.0,
-                max_delay_ms: 30000,
-            },
-        };
-
-        let alerter = RabbitMqAlerter::new(rabbitmq_config)
-            .await
-            .context("Failed to initialize RabbitMQ alerter")?;
-        let alerter = Arc::new(alerter);
-        info!("RabbitMQ connected");
-
-        // Initialize deduplicator
-        let dedup_config = DeduplicationConfig {
-            window_secs: config.alerting.dedup_window_secs,
-            enabled: true,
-            cleanup_interval_secs: 60,
-        };
-        let deduplicator = Arc::new(AlertDeduplicator::new(dedup_config));
-
-        // Start cleanup task
-        deduplicator.clone().start_cleanup_task();
-
-        info!("All components initialized successfully");
-
-        Ok(Self {
-            config,
-            storage,
-            detection_engine,
-            alerter,
-            deduplicator,
-        })
-    }
-
-    /// Run the sentinel system
-    async fn run(self) -> Result<()> {
-        info!("Starting Sentinel services...");
-
-        let sentinel = Arc::new(self);
-
-        // Start API server in background
-        let api_server = {
-            let sentinel = sentinel.clone();
-            tokio::spawn(async move {
-                sentinel.start_api_server().await
-            })
-        };
-
-        // Start ingestion pipeline
-        let ingestion_pipeline = {
-            let sentinel = sentinel.clone();
-            tokio::spawn(async move {
-                sentinel.start_ingestion_pipeline().await
-            })
-        };
-
-        // Wait for shutdown signal
-        let shutdown = tokio::spawn(async {
-            wait_for_shutdown().await;
-            info!("Shutdown signal received");
-        });
-
-        // Run all tasks concurrently
-        tokio::select! {
-            result = api_server => {
-                error!("API server exited: {:?}", result);
-            }
-            result = ingestion_pipeline => {
-                error!("Ingestion pipeline exited: {:?}", result);
-            }
-            _ = shutdown => {
-                info!("Initiating graceful shutdown...");
-            }
-        }
-
-        info!("Sentinel stopped");
-
-        Ok(())
-    }
-
-    /// Start API server
-    async fn start_api_server(self: &Self) -> Result<()> {
-        let api_config = ApiConfig {
-            bind_addr: format!("{}:{}", self.config.server.host, self.config.server.port)
-                .parse()
-                .context("Invalid server bind address")?,
-            enable_cors: true,
-            cors_origins: vec!["*".to_string()],
-            timeout_secs: self.config.server.request_timeout_secs,
-            max_body_size: 10 * 1024 * 1024, // 10MB
-            enable_logging: true,
-            metrics_path: "/metrics".to_string(),
-        };
-        let storage: Arc<dyn Storage> = self.storage.clone();
-
-        let server = ApiServer::new(
-            api_config,
-            storage,
-            env!("CARGO_PKG_VERSION").to_string(),
-        );
-
-        server.serve().await
-            .map_err(|e| anyhow::anyhow!("API server error: {}", e))?;
-
-        Ok(())
-    }
-
-    /// Start ingestion and detection pipeline
-    async fn start_ingestion_pipeline(self: &Self) -> Result<()> {
-        info!("Starting Kafka ingestion pipeline...");
-
-        let kafka_config = self.config.ingestion.kafka.as_ref()
-            .context("Kafka configuration is required")?;
-        let mut ingester = KafkaIngester::new(
-            kafka_config,
-            self.config.ingestion.batch_size,
-            self.config.ingestion.batch_timeout_ms,
-        ).context("Failed to create Kafka ingester")?;
-
-        info!("Ingestion pipeline ready, consuming from Kafka...");
-
-        loop {
-            match ingester.next_batch().await {
-                Ok(events) => {
-                    if events.is_empty() {
-                        continue;
-                    }
-
-                    let event_count = events.len();
-                    info!("Received batch of {} telemetry events", event_count);
-
-                    // Process each event
-                    for event in &events {
-                        // Store telemetry
-                        if let Err(e) = self.storage.write_telemetry(&event).await {
-                            error!("Failed to write telemetry: {}", e);
-                            ::metrics::counter!("sentinel_storage_errors_total")
-                                .increment(1);
-                        }
-
-                        // Run detection
-                        match self.detection_engine.lock().await.process(&event).await {
-                            Ok(Some(anomaly)) => {
-                                info!(
-                                    alert_id = %anomaly.alert_id,
-                                    severity = ?anomaly.severity,
-                                    anomaly_type = ?anomaly.anomaly_type,
-                                    "Anomaly detected"
-                                );
-
-                                // Store anomaly
-                                if let Err(e) = self.storage.write_anomaly(&anomaly).await {
-                                    error!("Failed to write anomaly: {}", e);
-                                }
-
-                                // Check deduplication
-                                if self.deduplicator.should_send(&anomaly) {
-                                    // Send alert
-                                    if let Err(e) = self.alerter.send(&anomaly).await {
-                                        error!("Failed to send alert: {}", e);
-                                        ::metrics::counter!("sentinel_alert_failures_total")
-                                            .increment(1);
-                                    }
-                                } else {
-                                    info!(
-                                        alert_id = %anomaly.alert_id,
-                                        "Alert deduplicated"
-                                    );
-                                }
-                            }
-                            Ok(None) => {
-                                // No anomaly detected
-                                ::metrics::counter!("sentinel_events_normal_total")
-                                    .increment(1);
-                            }
-                            Err(e) => {
-                                error!("Detection failed: {}", e);
-                                ::metrics::counter!("sentinel_detection_errors_total")
-                                    .increment(1);
-                            }
-                        }
-                    }
-
-                    ::metrics::counter!("sentinel_events_processed_total")
-                        .increment(event_count as u64);
-                }
-                Err(e) => {
-                    error!("Ingestion error: {}", e);
-                    ::metrics::counter!("sentinel_ingestion_errors_total").increment(1);
-
-                    // Backoff on errors
-                    tokio::time::sleep(tokio::time::Duration::from_secs(5)).await;
-                }
-            }
-        }
-    }
-}
-
-/// Wait for shutdown signal (SIGTERM or CTRL+C)
-async fn wait_for_shutdown() {
-    let ctrl_c = async {
-        signal::ctrl_c()
-            .await
-            .expect("Failed to install CTRL+C handler");
-    };
-
-    #[cfg(unix)]
-    let terminate = async {
-        signal::unix::signal(signal::unix::SignalKind::terminate())
-            .expect("Failed to install SIGTERM handler")
-            .recv()
-            .await;
-    };
-
-    #[cfg(not(unix))]
-    let terminate = std::future::pending::<()>();
-
-    tokio::select! {
-        _ = ctrl_c => {
-            info!("Received CTRL+C");
-        },
-        _ = terminate => {
-            info!("Received SIGTERM");
-        },
-    }
-}
@@ -0,0 +1,115 @@
+//! Tokio runtime setup for the sentinel binary.
+//!
+//! The runtime is built from `server.worker_threads` (0 for one worker per
+//! CPU core) and `server.runtime`, which caps the shared blocking pool,
+//! sizes the dedicated Kafka and compression pools and can pin runtime
+//! threads to a set of CPU cores. [`spawn_metrics`] samples the runtime into
+//! `sentinel_runtime_*` gauges: worker count, alive tasks, global queue
+//! depth, worker busy ratio and the delay before a newly spawned task is
+//! first polled.
+
+use anyhow::{Context, Result};
+use llm_sentinel_core::{blocking, config::ServerConfig};
+use std::{
+    sync::atomic::{AtomicUsize, Ordering},
+    time::{Duration, Instant},
+};
+use tokio::{
+    runtime::{Builder, Handle, Runtime},
+    task::JoinHandle,
+};
+use tracing::{info, warn};
+
+/// CPU cores to pin to, checked against the cores of this machine
+fn pinned_cores(cpus: &[usize]) -> Result<Vec<core_affinity::CoreId>> {
+    let available = core_affinity::get_core_ids().context("Failed to list CPU cores")?;
+    cpus.iter()
+        .map(|&cpu| {
+            available
+                .iter()
+                .copied()
+                .find(|core| core.id == cpu)
+                .with_context(|| format!("CPU core {} is not available for pinning", cpu))
+        })
+        .collect()
+}
+
+/// Build the runtime and the dedicated blocking pools
+pub fn build(config: &ServerConfig) -> Result<Runtime> {
+    let tuning = &config.runtime;
+    let mut builder = Builder::new_multi_thread();
+    builder
+        .enable_all()
+        .thread_name("sentinel-worker")
+        .max_blocking_threads(tuning.max_blocking_threads.max(1));
+    if config.worker_threads > 0 {
+        builder.worker_threads(config.worker_threads);
+    }
+
+    if !tuning.cpu_affinity.is_empty() {
+        let cores = pinned_cores(&tuning.cpu_affinity)?;
+        let next = AtomicUsize::new(0);
+        // Applies to blocking threads too, keeping all runtime threads on
+        // the configured cores
+        builder.on_thread_start(move || {
+            let core = cores[next.fetch_add(1, Ordering::Relaxed) % cores.len()];
+            if !core_affinity::set_for_current(core) {
+                warn!(core = core.id, "Failed to pin runtime thread");
+            }
+        });
+    }
+
+    let runtime = builder.build().context("Failed to build Tokio runtime")?;
+    blocking::init(tuning).context("Failed to initialize blocking pools")?;
+
+    info!(
+        workers = runtime.metrics().num_workers(),
+        max_blocking_threads = tuning.max_blocking_threads,
+        cpu_affinity = ?tuning.cpu_affinity,
+        "Tokio runtime built"
+    );
+    Ok(runtime)
+}
+
+/// Sample runtime metrics every `interval` on the current runtime
+pub fn spawn_metrics(interval: Duration) -> JoinHandle<()> {
+    let handle = Handle::current();
+    tokio::spawn(async move {
+        let runtime = handle.metrics();
+        let workers = runtime.num_workers();
+        let busy_total = || -> Duration {
+            (0..workers)
+                .map(|worker| runtime.worker_total_busy_duration(worker))
+                .sum()
+        };
+
+        let mut ticker = tokio::time::interval(interval);
+        let mut last_busy = busy_total();
+        let mut last_sample = Instant::now();
+        loop {
+            ticker.tick().await;
+
+            metrics::gauge!("sentinel_runtime_workers").set(workers as f64);
+            metrics::gauge!("sentinel_runtime_alive_tasks").set(runtime.num_alive_tasks() as f64);
+            metrics::gauge!("sentinel_runtime_global_queue_depth")
+                .set(runtime.global_queue_depth() as f64);
+
+            let busy = busy_total();
+            let elapsed = last_sample.elapsed().as_secs_f64() * workers.max(1) as f64;
+            if elapsed > 0.0 {
+                let ratio = (busy - last_busy).as_secs_f64() / elapsed;
+                metrics::gauge!("sentinel_runtime_busy_ratio").set(ratio.min(1.0));
+            }
+            last_busy = busy;
+            last_sample = Instant::now();
+
+            // Time until a new task is first polled grows when workers are
+            // saturated or tasks poll for too long
+            let spawned = Instant::now();
+            if let Ok(delay) = tokio::spawn(async move { spawned.elapsed() }).await {
+                metrics::histogram!("sentinel_runtime_schedule_delay_seconds")
+                    .record(delay.as_secs_f64());
+            }
+        }
+    })
+}